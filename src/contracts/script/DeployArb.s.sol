@@ -6,15 +6,43 @@ import {ArbExecutor} from "../src/ArbExecutor.sol";
 
 /// @title Deploy ArbExecutor to Polygon
 /// @notice Run: forge script script/DeployArb.s.sol --rpc-url polygon --broadcast --private-key $PK
+/// @dev Allows the Polygon routers, Aave V3 pool and V2 factories the bot trades
+///      on; any other venue reverts until the owner allows it (setRouter,
+///      setAavePool, setV2Factory).
 /// @author AI-Generated
 /// @custom:created 2026-01-30
+/// @custom:modified 2026-10-17 (venue allowlist set up after deployment)
 
 contract DeployArb is Script {
+    // Swap routers (Polygon)
+    address constant UNI_V3_ROUTER = 0xE592427A0AEce92De3Edee1F18E0157C05861564;
+    address constant SUSHI_V3_ROUTER = 0x0aF89E1620b96170e2a9D0b68fEebb767eD044c3;
+    address constant QUICKSWAP_V3_ROUTER = 0xf5b509bB0909a69B1c207E495f687a596C168E12; // Algebra
+    address constant QUICKSWAP_V2_ROUTER = 0xa5E0829CaCEd8fFDD4De3c43696c57F7D7A678ff;
+    address constant SUSHI_V2_ROUTER = 0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506;
+
+    // Aave V3 Pool (Polygon)
+    address constant AAVE_V3_POOL = 0x794a61358D6845594F94dc1DB02A252b5b4814aD;
+
+    // V2 factories (Polygon)
+    address constant QUICKSWAP_V2_FACTORY = 0x5757371414417b8C6CAad45bAeF941aBc7d3Ab32;
+    address constant SUSHI_V2_FACTORY = 0xc35DADB65012eC5796536bD9864eD8773aBc74C4;
+
     function run() external {
         vm.startBroadcast();
         ArbExecutor arb = new ArbExecutor();
         console.log("ArbExecutor deployed at:", address(arb));
         console.log("Owner:", arb.owner());
+
+        arb.setRouter(UNI_V3_ROUTER, true);
+        arb.setRouter(SUSHI_V3_ROUTER, true);
+        arb.setRouter(QUICKSWAP_V3_ROUTER, true);
+        arb.setRouter(QUICKSWAP_V2_ROUTER, true);
+        arb.setRouter(SUSHI_V2_ROUTER, true);
+        arb.setAavePool(AAVE_V3_POOL, true);
+        arb.setV2Factory(QUICKSWAP_V2_FACTORY, true);
+        arb.setV2Factory(SUSHI_V2_FACTORY, true);
+        console.log("Venue allowlist: 5 routers, 1 Aave pool, 2 V2 factories");
         vm.stopBroadcast();
     }
 }
@@ -20,10 +20,54 @@ pragma solidity ^0.8.20;
 ///
 ///   If any step fails, the entire transaction reverts — zero risk.
 ///
+/// @dev Flashloan-funded variant (executeArbFlash): the input is borrowed from an
+///      Aave V3 pool via flashLoanSimple instead of pulled from the caller. Both
+///      legs run in executeOperation; the pool pulls back amount + premium (0.05%)
+///      and the tx reverts unless amountOut >= amountIn + premium + minProfit.
+///      Only the profit is transferred to the caller.
+///
+/// @dev Split-sell variant (executeArbSplit): one buy leg, then the token1
+///      received is sold across two pools (e.g. the 0.05% and 0.30% tiers of
+///      the same pair) in the proportion sellShareBps, so a thin sell pool only
+///      takes the share its depth can absorb. Same profit check as executeArb.
+///
+/// @dev Operators: the owner may allow further wallets (setOperator) to call the
+///      execute functions, so submissions spread over several nonce sequences.
+///      Each caller trades its own balance — tokens are pulled from and paid
+///      back to msg.sender. Rescues stay owner-only.
+///
+/// @dev Venue allowlist: every router, Aave pool and V2 pair (by its factory)
+///      an execute function touches must be allowed by the owner (setRouter,
+///      setAavePool, setV2Factory), and each swap's output is measured as this
+///      contract's balance change, never taken from the router's return value.
+///      An operator can't route a leg or a flash callback through a contract of
+///      its own to report inflated output or collect approvals and transfers.
+///
+/// @dev Permit2 variant (executeArbWithPermit): step 1 pulls amountIn through
+///      Permit2 permitTransferFrom with a signature the caller made for this
+///      trade (spender = this contract, unordered nonce, short deadline)
+///      instead of an ERC20 allowance to this contract. Legs, profit check and
+///      payout are those of executeArb.
+///
+/// @dev V2 flash-swap variant (executeArbFlashV2): when the buy leg is a V2
+///      pair, the pair itself funds the trade. swap() sends baseOut of token1
+///      first and calls uniswapV2Call; the callback sells it on routerSell and
+///      pays the pair getAmountIn(baseOut) of token0 (its 0.3% fee included,
+///      capped at amountIn). Reverts unless amountOut >= owed + minProfit;
+///      only the profit is transferred to the caller.
+///
 /// @author AI-Generated
 /// @custom:created 2026-01-30
 /// @custom:modified 2026-01-30 (V2: Algebra SwapRouter support via fee=0 sentinel)
 /// @custom:modified 2026-01-30 (V3: V2 router support via fee=type(uint24).max sentinel)
+/// @custom:modified 2026-10-16 (executeArbFlash: Aave V3 flashLoanSimple-funded two-leg arb)
+/// @custom:modified 2026-10-16 (executeArbSplit: buy once, sell across two pools by a bps share)
+/// @custom:modified 2026-10-16 (operator allowlist: setOperator, execute functions onlyOperator)
+/// @custom:modified 2026-10-16 (executeArbWithPermit: input pulled by a Permit2 signature transfer)
+/// @custom:modified 2026-10-16 (executeArbFlashV2: buy leg as a V2 pair flash swap, repaid in uniswapV2Call)
+/// @custom:modified 2026-10-17 (owner allowlist of routers, Aave pools and V2 factories; swap output measured by balance)
+/// @custom:modified 2026-10-17 (FlashArb struct renamed TwoLegArb — it is also executeArbWithPermit's argument)
+/// @custom:modified 2026-10-17 (executeOperation: an output short of amount + premium reverts InsufficientProfit)
 
 import {IERC20} from "forge-std/interfaces/IERC20.sol";
 
@@ -79,10 +123,75 @@ interface IUniswapV2Router02 {
     ) external returns (uint256[] memory amounts);
 }
 
+/// @notice Minimal Aave V3 Pool interface (flashLoanSimple)
+///         The pool calls executeOperation on the receiver, then pulls amount + premium.
+interface IAavePool {
+    function flashLoanSimple(
+        address receiverAddress,
+        address asset,
+        uint256 amount,
+        bytes calldata params,
+        uint16 referralCode
+    ) external;
+}
+
+/// @notice Minimal Uniswap V2 pair interface (flash swap)
+///         swap() with non-empty data sends the output first, then calls
+///         uniswapV2Call on the recipient before checking the k invariant.
+interface IUniswapV2Pair {
+    function factory() external view returns (address);
+    function token0() external view returns (address);
+    function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
+    function swap(uint256 amount0Out, uint256 amount1Out, address to, bytes calldata data) external;
+}
+
+/// @notice Minimal Uniswap V2 factory interface (pair lookup)
+interface IUniswapV2Factory {
+    function getPair(address tokenA, address tokenB) external view returns (address pair);
+}
+
+/// @notice Minimal Permit2 SignatureTransfer interface (permitTransferFrom)
+interface IPermit2 {
+    struct TokenPermissions {
+        address token;
+        uint256 amount;
+    }
+
+    struct PermitTransferFrom {
+        TokenPermissions permitted;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    struct SignatureTransferDetails {
+        address to;
+        uint256 requestedAmount;
+    }
+
+    function permitTransferFrom(
+        PermitTransferFrom calldata permit,
+        SignatureTransferDetails calldata transferDetails,
+        address owner,
+        bytes calldata signature
+    ) external;
+}
+
 contract ArbExecutor {
-    /// @notice Contract owner (only address that can execute arbs and rescue tokens)
+    /// @notice Contract owner (executes arbs, manages operators, rescues tokens)
     address public immutable owner;
 
+    /// @notice Wallets besides the owner allowed to execute arbs
+    mapping(address => bool) public operators;
+
+    /// @notice Swap routers the legs may use
+    mapping(address => bool) public allowedRouters;
+
+    /// @notice Aave V3 pools executeArbFlash may borrow from
+    mapping(address => bool) public allowedAavePools;
+
+    /// @notice V2 factories whose pairs executeArbFlashV2 may flash swap on
+    mapping(address => bool) public allowedV2Factories;
+
     /// @notice Emitted on successful arbitrage execution
     event ArbExecuted(
         address indexed token0,
@@ -94,6 +203,47 @@ contract ArbExecutor {
         address routerSell
     );
 
+    /// @notice Emitted on successful flashloan-funded arbitrage execution
+    ///         profit = amountOut - amountIn - premium
+    event ArbFlashExecuted(
+        address indexed token0,
+        address indexed token1,
+        uint256 amountIn,
+        uint256 amountOut,
+        uint256 premium,
+        uint256 profit
+    );
+
+    /// @notice Emitted on successful V2 flash-swap arbitrage execution
+    ///         owed = token0 repaid to the pair, profit = amountOut - owed
+    event ArbFlashV2Executed(
+        address indexed token0,
+        address indexed token1,
+        address pair,
+        uint256 owed,
+        uint256 amountOut,
+        uint256 profit
+    );
+
+    /// @notice Emitted on successful split-sell arbitrage execution
+    ///         sellShareBps = share of token1 sold on the second sell leg
+    event ArbSplitExecuted(
+        address indexed token0,
+        address indexed token1,
+        uint256 amountIn,
+        uint256 amountOut,
+        uint256 profit,
+        uint16 sellShareBps
+    );
+
+    /// @notice Emitted when the owner allows or revokes an operator
+    event OperatorSet(address indexed operator, bool allowed);
+
+    /// @notice Emitted when the owner allows or revokes a router, Aave pool or V2 factory
+    event RouterSet(address indexed router, bool allowed);
+    event AavePoolSet(address indexed pool, bool allowed);
+    event V2FactorySet(address indexed factory, bool allowed);
+
     /// @notice Emitted when tokens are rescued by the owner
     event TokensRescued(address indexed token, uint256 amount);
 
@@ -101,19 +251,90 @@ contract ArbExecutor {
     ///         fee=0 → Algebra, fee=1..65535 → standard V3, fee=16777215 → V2
     uint24 public constant FEE_V2_SENTINEL = type(uint24).max; // 16777215
 
+    /// @notice One two-leg arb (executeArbFlash, executeArbFlashV2, executeArbWithPermit);
+    ///         passed through the flashloan and flash-swap callbacks
+    struct TwoLegArb {
+        address token0;
+        address token1;
+        address routerBuy;
+        address routerSell;
+        uint24 feeBuy;
+        uint24 feeSell;
+        uint256 amountIn;
+        uint256 minProfit;
+    }
+
+    /// @notice One buy leg and two sell legs; the second sell leg gets
+    ///         sellShareBps / 10000 of the token1 bought, the first the rest
+    struct SplitArb {
+        address token0;
+        address token1;
+        address routerBuy;
+        uint24 feeBuy;
+        address routerSellA;
+        uint24 feeSellA;
+        address routerSellB;
+        uint24 feeSellB;
+        uint16 sellShareBps;
+        uint256 amountIn;
+        uint256 minProfit;
+    }
+
+    /// @notice Aave pool of the flashloan in progress (zero outside executeArbFlash)
+    address private flashPool;
+
+    /// @notice V2 pair of the flash swap in progress (zero outside executeArbFlashV2)
+    address private flashPair;
+
     error OnlyOwner();
+    error OnlyOperator();
     error InsufficientProfit(uint256 got, uint256 required);
     error ZeroAmount();
+    error UnauthorizedFlashCallback();
+    error InvalidSplit(uint16 sellShareBps);
+    error FlashV2OwedExceedsCap(uint256 owed, uint256 cap);
+    error RouterNotAllowed(address router);
+    error AavePoolNotAllowed(address pool);
+    error PairNotAllowed(address pair);
 
     modifier onlyOwner() {
         if (msg.sender != owner) revert OnlyOwner();
         _;
     }
 
+    modifier onlyOperator() {
+        if (msg.sender != owner && !operators[msg.sender]) revert OnlyOperator();
+        _;
+    }
+
     constructor() {
         owner = msg.sender;
     }
 
+    /// @notice Allow or revoke a wallet as an arb executor (owner only)
+    function setOperator(address operator, bool allowed) external onlyOwner {
+        operators[operator] = allowed;
+        emit OperatorSet(operator, allowed);
+    }
+
+    /// @notice Allow or revoke a swap router for the legs (owner only)
+    function setRouter(address router, bool allowed) external onlyOwner {
+        allowedRouters[router] = allowed;
+        emit RouterSet(router, allowed);
+    }
+
+    /// @notice Allow or revoke an Aave V3 pool for executeArbFlash (owner only)
+    function setAavePool(address pool, bool allowed) external onlyOwner {
+        allowedAavePools[pool] = allowed;
+        emit AavePoolSet(pool, allowed);
+    }
+
+    /// @notice Allow or revoke a V2 factory's pairs for executeArbFlashV2 (owner only)
+    function setV2Factory(address factory, bool allowed) external onlyOwner {
+        allowedV2Factories[factory] = allowed;
+        emit V2FactorySet(factory, allowed);
+    }
+
     /// @notice Execute an atomic two-leg arbitrage
     /// @param token0      The base token (e.g., USDC) — start and end with this
     /// @param token1      The intermediate token (e.g., WETH) — held only within this tx
@@ -133,7 +354,7 @@ contract ArbExecutor {
         uint24 feeSell,
         uint256 amountIn,
         uint256 minProfit
-    ) external onlyOwner returns (uint256 profit) {
+    ) external onlyOperator returns (uint256 profit) {
         if (amountIn == 0) revert ZeroAmount();
 
         // 1. Pull token0 from caller
@@ -174,6 +395,7 @@ contract ArbExecutor {
     ///      fee = FEE_V2_SENTINEL (type(uint24).max) → V2 swapExactTokensForTokens
     ///      fee = 0 → Algebra SwapRouter (QuickSwap V3, no fee in params)
     ///      fee = 1..65535 → Standard V3 SwapRouter (Uniswap V3 / SushiSwap V3)
+    ///      Only allowed routers; amountOut is the tokenOut balance gained.
     function _swapSingle(
         address router,
         uint24 fee,
@@ -182,22 +404,23 @@ contract ArbExecutor {
         uint256 amountIn,
         uint256 amountOutMin
     ) internal returns (uint256 amountOut) {
+        if (!allowedRouters[router]) revert RouterNotAllowed(router);
+        uint256 balanceBefore = IERC20(tokenOut).balanceOf(address(this));
         if (fee == FEE_V2_SENTINEL) {
             // V2 Router (QuickSwap V2 / SushiSwap V2) — swapExactTokensForTokens
             address[] memory path = new address[](2);
             path[0] = tokenIn;
             path[1] = tokenOut;
-            uint256[] memory amounts = IUniswapV2Router02(router).swapExactTokensForTokens(
+            IUniswapV2Router02(router).swapExactTokensForTokens(
                 amountIn,
                 amountOutMin,
                 path,
                 address(this),
                 block.timestamp + 120
             );
-            amountOut = amounts[amounts.length - 1];
         } else if (fee == 0) {
             // Algebra SwapRouter (QuickSwap V3) — no fee parameter
-            amountOut = IAlgebraSwapRouter(router).exactInputSingle(
+            IAlgebraSwapRouter(router).exactInputSingle(
                 IAlgebraSwapRouter.ExactInputSingleParams({
                     tokenIn: tokenIn,
                     tokenOut: tokenOut,
@@ -210,7 +433,7 @@ contract ArbExecutor {
             );
         } else {
             // Standard V3 SwapRouter (Uniswap V3 / SushiSwap V3) — fee in params
-            amountOut = ISwapRouter(router).exactInputSingle(
+            ISwapRouter(router).exactInputSingle(
                 ISwapRouter.ExactInputSingleParams({
                     tokenIn: tokenIn,
                     tokenOut: tokenOut,
@@ -223,6 +446,202 @@ contract ArbExecutor {
                 })
             );
         }
+        amountOut = IERC20(tokenOut).balanceOf(address(this)) - balanceBefore;
+        require(amountOut >= amountOutMin, "insufficient output");
+    }
+
+    /// @notice Execute an atomic two-leg arbitrage funded by an Aave V3 flashloan
+    /// @param aavePool  Aave V3 Pool to borrow arb.amountIn of arb.token0 from
+    /// @param arb       Same fields as executeArb; minProfit is net of the premium
+    /// @return profit   Net profit in token0 units (after the premium), sent to the caller
+    function executeArbFlash(address aavePool, TwoLegArb calldata arb)
+        external
+        onlyOperator
+        returns (uint256 profit)
+    {
+        if (arb.amountIn == 0) revert ZeroAmount();
+        if (!allowedAavePools[aavePool]) revert AavePoolNotAllowed(aavePool);
+
+        uint256 balanceBefore = IERC20(arb.token0).balanceOf(address(this));
+        flashPool = aavePool;
+        IAavePool(aavePool).flashLoanSimple(address(this), arb.token0, arb.amountIn, abi.encode(arb), 0);
+        flashPool = address(0);
+
+        // Repayment already pulled by the pool — what is left over is the profit
+        profit = IERC20(arb.token0).balanceOf(address(this)) - balanceBefore;
+        require(IERC20(arb.token0).transfer(msg.sender, profit), "transfer out failed");
+    }
+
+    /// @notice Aave V3 flashloan callback: run both legs, approve the repayment
+    /// @dev Only the pool of the flashloan this contract started may call it.
+    function executeOperation(
+        address asset,
+        uint256 amount,
+        uint256 premium,
+        address initiator,
+        bytes calldata params
+    ) external returns (bool) {
+        if (msg.sender != flashPool || initiator != address(this)) revert UnauthorizedFlashCallback();
+        TwoLegArb memory arb = abi.decode(params, (TwoLegArb));
+
+        // Buy leg: token0 → token1
+        IERC20(asset).approve(arb.routerBuy, amount);
+        uint256 token1Received = _swapSingle(arb.routerBuy, arb.feeBuy, asset, arb.token1, amount, 0);
+
+        // Sell leg: token1 → token0, must repay the loan and the premium
+        IERC20(arb.token1).approve(arb.routerSell, token1Received);
+        uint256 owed = amount + premium;
+        uint256 token0Received = _swapSingle(arb.routerSell, arb.feeSell, arb.token1, asset, token1Received, 0);
+
+        if (token0Received < owed + arb.minProfit) {
+            revert InsufficientProfit(token0Received > owed ? token0Received - owed : 0, arb.minProfit);
+        }
+
+        // The pool pulls amount + premium after this returns
+        IERC20(asset).approve(msg.sender, owed);
+
+        emit ArbFlashExecuted(asset, arb.token1, amount, token0Received, premium, token0Received - owed);
+        return true;
+    }
+
+    /// @notice Execute an atomic two-leg arbitrage with the buy leg as a V2 pair flash swap
+    /// @param pair     V2 pair of token0/token1 — the buy pool; no router buy leg
+    /// @param baseOut  token1 to take from the pair (the buy leg's output)
+    /// @param arb      Sell leg fields of executeArb (routerBuy/feeBuy unused);
+    ///                 amountIn caps the token0 owed, minProfit is net of it
+    /// @return profit  Net profit in token0 units (after repaying the pair), sent to the caller
+    function executeArbFlashV2(address pair, uint256 baseOut, TwoLegArb calldata arb)
+        external
+        onlyOperator
+        returns (uint256 profit)
+    {
+        if (baseOut == 0 || arb.amountIn == 0) revert ZeroAmount();
+        // Only a pair its (allowed) factory lists for this token pair
+        address factory = IUniswapV2Pair(pair).factory();
+        if (!allowedV2Factories[factory] || IUniswapV2Factory(factory).getPair(arb.token0, arb.token1) != pair) {
+            revert PairNotAllowed(pair);
+        }
+
+        bool token0IsPair0 = IUniswapV2Pair(pair).token0() == arb.token0;
+        (uint112 reserve0, uint112 reserve1, ) = IUniswapV2Pair(pair).getReserves();
+        (uint256 reserveIn, uint256 reserveOut) = token0IsPair0
+            ? (uint256(reserve0), uint256(reserve1))
+            : (uint256(reserve1), uint256(reserve0));
+        // UniswapV2Library.getAmountIn: token0 the pair needs for baseOut, fee included
+        uint256 owed = reserveIn * baseOut * 1000 / ((reserveOut - baseOut) * 997) + 1;
+        if (owed > arb.amountIn) revert FlashV2OwedExceedsCap(owed, arb.amountIn);
+
+        uint256 balanceBefore = IERC20(arb.token0).balanceOf(address(this));
+        flashPair = pair;
+        (uint256 amount0Out, uint256 amount1Out) = token0IsPair0 ? (uint256(0), baseOut) : (baseOut, uint256(0));
+        IUniswapV2Pair(pair).swap(amount0Out, amount1Out, address(this), abi.encode(arb, owed));
+        flashPair = address(0);
+
+        // The pair was repaid in the callback — what is left over is the profit
+        profit = IERC20(arb.token0).balanceOf(address(this)) - balanceBefore;
+        require(IERC20(arb.token0).transfer(msg.sender, profit), "transfer out failed");
+    }
+
+    /// @notice V2 flash-swap callback: sell the token1 received, repay the pair in token0
+    /// @dev Only the pair of the flash swap this contract started may call it.
+    function uniswapV2Call(address sender, uint256 amount0, uint256 amount1, bytes calldata data) external {
+        if (msg.sender != flashPair || sender != address(this)) revert UnauthorizedFlashCallback();
+        (TwoLegArb memory arb, uint256 owed) = abi.decode(data, (TwoLegArb, uint256));
+        uint256 token1Received = amount0 + amount1;
+
+        // Sell leg: token1 → token0, must at least repay the pair
+        IERC20(arb.token1).approve(arb.routerSell, token1Received);
+        uint256 token0Received = _swapSingle(arb.routerSell, arb.feeSell, arb.token1, arb.token0, token1Received, owed);
+
+        if (token0Received < owed + arb.minProfit) {
+            revert InsufficientProfit(token0Received - owed, arb.minProfit);
+        }
+
+        // The pair checks k once this returns
+        require(IERC20(arb.token0).transfer(msg.sender, owed), "repay failed");
+
+        emit ArbFlashV2Executed(arb.token0, arb.token1, msg.sender, owed, token0Received, token0Received - owed);
+    }
+
+    /// @notice Execute an atomic two-leg arbitrage, input pulled via a Permit2 signature transfer
+    /// @param permit2   Permit2 contract the caller approved token0 to
+    /// @param arb       Same fields as executeArb
+    /// @param nonce     Permit2 unordered nonce the signature was made with
+    /// @param deadline  Signature expiry (unix seconds)
+    /// @param signature Caller's EIP-712 PermitTransferFrom signature (spender = this contract)
+    /// @return profit   Net profit in token0 units
+    function executeArbWithPermit(
+        address permit2,
+        TwoLegArb calldata arb,
+        uint256 nonce,
+        uint256 deadline,
+        bytes calldata signature
+    ) external onlyOperator returns (uint256 profit) {
+        if (arb.amountIn == 0) revert ZeroAmount();
+
+        // 1. Pull token0 from caller — Permit2 checks signature, nonce and deadline
+        IPermit2(permit2).permitTransferFrom(
+            IPermit2.PermitTransferFrom({
+                permitted: IPermit2.TokenPermissions({token: arb.token0, amount: arb.amountIn}),
+                nonce: nonce,
+                deadline: deadline
+            }),
+            IPermit2.SignatureTransferDetails({to: address(this), requestedAmount: arb.amountIn}),
+            msg.sender,
+            signature
+        );
+
+        // 2-3. Buy leg: token0 → token1
+        IERC20(arb.token0).approve(arb.routerBuy, arb.amountIn);
+        uint256 token1Received = _swapSingle(arb.routerBuy, arb.feeBuy, arb.token0, arb.token1, arb.amountIn, 0);
+
+        // 4-5. Sell leg: token1 → token0
+        IERC20(arb.token1).approve(arb.routerSell, token1Received);
+        uint256 token0Received = _swapSingle(arb.routerSell, arb.feeSell, arb.token1, arb.token0, token1Received, arb.amountIn);
+
+        // 6. Profit check — revert if below threshold
+        if (token0Received < arb.amountIn + arb.minProfit) {
+            revert InsufficientProfit(token0Received - arb.amountIn, arb.minProfit);
+        }
+        profit = token0Received - arb.amountIn;
+
+        // 7. Return all token0 to caller
+        require(IERC20(arb.token0).transfer(msg.sender, token0Received), "transfer out failed");
+
+        emit ArbExecuted(
+            arb.token0, arb.token1, arb.amountIn, token0Received, profit, arb.routerBuy, arb.routerSell
+        );
+    }
+
+    /// @notice Execute an atomic arbitrage with the sell side split across two pools
+    /// @param arb     Buy leg, both sell legs and the split (sellShareBps in 1..9999)
+    /// @return profit Net profit in token0 units
+    function executeArbSplit(SplitArb calldata arb) external onlyOperator returns (uint256 profit) {
+        if (arb.amountIn == 0) revert ZeroAmount();
+        if (arb.sellShareBps == 0 || arb.sellShareBps >= 10000) revert InvalidSplit(arb.sellShareBps);
+
+        require(IERC20(arb.token0).transferFrom(msg.sender, address(this), arb.amountIn), "transferFrom failed");
+
+        // Buy leg: token0 → token1, full amountIn
+        IERC20(arb.token0).approve(arb.routerBuy, arb.amountIn);
+        uint256 token1Received = _swapSingle(arb.routerBuy, arb.feeBuy, arb.token0, arb.token1, arb.amountIn, 0);
+
+        // Sell legs: token1 → token0, split by sellShareBps (B gets the share, A the rest)
+        uint256 sellB = token1Received * arb.sellShareBps / 10000;
+        uint256 sellA = token1Received - sellB;
+        IERC20(arb.token1).approve(arb.routerSellA, sellA);
+        uint256 token0Received = _swapSingle(arb.routerSellA, arb.feeSellA, arb.token1, arb.token0, sellA, 0);
+        IERC20(arb.token1).approve(arb.routerSellB, sellB);
+        token0Received += _swapSingle(arb.routerSellB, arb.feeSellB, arb.token1, arb.token0, sellB, 0);
+
+        if (token0Received < arb.amountIn + arb.minProfit) {
+            revert InsufficientProfit(token0Received > arb.amountIn ? token0Received - arb.amountIn : 0, arb.minProfit);
+        }
+        profit = token0Received - arb.amountIn;
+
+        require(IERC20(arb.token0).transfer(msg.sender, token0Received), "transfer out failed");
+
+        emit ArbSplitExecuted(arb.token0, arb.token1, arb.amountIn, token0Received, profit, arb.sellShareBps);
     }
 
     /// @notice Rescue any tokens stuck in the contract (owner only)
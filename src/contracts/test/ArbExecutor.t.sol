@@ -13,17 +13,35 @@ import {IERC20} from "forge-std/interfaces/IERC20.sol";
 ///
 /// Tests:
 ///   1. Deployment and ownership
-///   2. OnlyOwner enforcement
+///   2. OnlyOwner / OnlyOperator enforcement, operator allowlist
 ///   3. executeArb V3↔V3 (real pools, Polygon fork)
 ///   4. executeArb V2→V3 cross-protocol (QuickSwap V2 buy, Uni V3 sell)
 ///   5. executeArb V3→V2 cross-protocol (Uni V3 buy, QuickSwap V2 sell)
 ///   6. executeArb V2→V2 (QuickSwap V2 buy, SushiSwap V2 sell)
 ///   7. rescueTokens works
 ///   8. FEE_V2_SENTINEL constant check
+///   9. executeArbFlash: OnlyOperator, ZeroAmount, callback only from the active flashloan;
+///      fork: real Aave V3 pool repaid amount + premium, profit to the caller, premium shortfall reverts
+///  10. executeArbSplit: OnlyOperator, ZeroAmount, InvalidSplit; fork: Uni 0.05% buy, 0.05% + 0.30% sells
+///  11. executeArbWithPermit: OnlyOperator, ZeroAmount before any Permit2 call (the bot's support probe);
+///      fork: a vm.sign'd PermitTransferFrom through canonical Permit2 funds both legs, a reused nonce reverts
+///  12. executeArbFlashV2: OnlyOperator, ZeroAmount, callback only from the active flash swap;
+///      fork: QuickSwap V2 pair in both orientations repaid getAmountIn(baseOut), FlashV2OwedExceedsCap
+///  13. Venue allowlist: routers, Aave pools and V2 factories owner-managed; a lying
+///      router's reported output is ignored (balance-measured)
 ///
 /// @author AI-Generated
 /// @custom:created 2026-01-30
 /// @custom:modified 2026-01-30 (V2 cross-protocol fork tests)
+/// @custom:modified 2026-10-16 (executeArbFlash guard tests)
+/// @custom:modified 2026-10-16 (executeArbSplit guard + fork tests)
+/// @custom:modified 2026-10-16 (operator allowlist tests; execute guards revert OnlyOperator)
+/// @custom:modified 2026-10-16 (executeArbWithPermit guard tests)
+/// @custom:modified 2026-10-16 (executeArbFlashV2 guard tests)
+/// @custom:modified 2026-10-17 (venue allowlist tests; setUp allows the Polygon venues)
+/// @custom:modified 2026-10-17 (executeArbWithPermit fork tests with a real Permit2 signature; FixedRateRouter)
+/// @custom:modified 2026-10-17 (executeArbFlash fork tests against the Aave V3 pool)
+/// @custom:modified 2026-10-17 (executeArbFlashV2 fork tests on the QuickSwap V2 pair, both orientations)
 
 contract ArbExecutorTest is Test {
     ArbExecutor public arb;
@@ -41,22 +59,34 @@ contract ArbExecutorTest is Test {
     address constant QUICKSWAP_V2_ROUTER = 0xa5E0829CaCEd8fFDD4De3c43696c57F7D7A678ff;
     address constant SUSHI_V2_ROUTER = 0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506;
 
+    // Aave V3 Pool (Polygon)
+    address constant AAVE_V3_POOL = 0x794a61358D6845594F94dc1DB02A252b5b4814aD;
+
+    // QuickSwap V2 factory (Polygon)
+    address constant QUICKSWAP_V2_FACTORY = 0x5757371414417b8C6CAad45bAeF941aBc7d3Ab32;
+
     // Test wallet (will be funded via deal)
     address deployer;
 
     function setUp() public {
         deployer = address(this);
         arb = new ArbExecutor();
+        arb.setRouter(UNI_V3_ROUTER, true);
+        arb.setRouter(SUSHI_V3_ROUTER, true);
+        arb.setRouter(QUICKSWAP_V2_ROUTER, true);
+        arb.setRouter(SUSHI_V2_ROUTER, true);
+        arb.setAavePool(AAVE_V3_POOL, true);
+        arb.setV2Factory(QUICKSWAP_V2_FACTORY, true);
     }
 
     function test_ownerIsDeployer() public view {
         assertEq(arb.owner(), deployer);
     }
 
-    function test_onlyOwnerCanExecute() public {
+    function test_onlyOperatorCanExecute() public {
         address notOwner = address(0xBEEF);
         vm.prank(notOwner);
-        vm.expectRevert(ArbExecutor.OnlyOwner.selector);
+        vm.expectRevert(ArbExecutor.OnlyOperator.selector);
         arb.executeArb(
             USDC, WETH, UNI_V3_ROUTER, SUSHI_V3_ROUTER,
             500, 3000, 100e6, 0
@@ -78,6 +108,41 @@ contract ArbExecutorTest is Test {
         );
     }
 
+    function test_onlyOwnerCanSetOperator() public {
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(ArbExecutor.OnlyOwner.selector);
+        arb.setOperator(address(0xBEEF), true);
+        assertFalse(arb.operators(address(0xBEEF)));
+    }
+
+    /// @notice An allowed operator gets past the guard (and reverts on the
+    ///         zero amount instead); a revoked one is stopped again
+    function test_operatorCanExecute() public {
+        address operator = address(0xBEEF);
+        arb.setOperator(operator, true);
+        assertTrue(arb.operators(operator));
+
+        vm.prank(operator);
+        vm.expectRevert(ArbExecutor.ZeroAmount.selector);
+        arb.executeArb(
+            USDC, WETH, UNI_V3_ROUTER, SUSHI_V3_ROUTER,
+            500, 3000, 0, 0
+        );
+
+        // Operators can't rescue
+        vm.prank(operator);
+        vm.expectRevert(ArbExecutor.OnlyOwner.selector);
+        arb.rescueTokens(USDC);
+
+        arb.setOperator(operator, false);
+        vm.prank(operator);
+        vm.expectRevert(ArbExecutor.OnlyOperator.selector);
+        arb.executeArb(
+            USDC, WETH, UNI_V3_ROUTER, SUSHI_V3_ROUTER,
+            500, 3000, 0, 0
+        );
+    }
+
     /// @notice Test executeArb on Polygon fork — both legs should execute.
     ///         We set minProfit=0 so the tx won't revert due to normal slippage/fees.
     ///         This verifies the contract can call both SwapRouters atomically.
@@ -209,4 +274,513 @@ contract ArbExecutorTest is Test {
         assertEq(IERC20(USDC).balanceOf(address(arb)), 0);
         assertEq(IERC20(USDC).balanceOf(deployer), beforeBal + amount);
     }
+
+    function _twoLegArb(uint256 amountIn) internal pure returns (ArbExecutor.TwoLegArb memory) {
+        return ArbExecutor.TwoLegArb({
+            token0: USDC,
+            token1: WETH,
+            routerBuy: UNI_V3_ROUTER,
+            routerSell: SUSHI_V3_ROUTER,
+            feeBuy: 500,
+            feeSell: 3000,
+            amountIn: amountIn,
+            minProfit: 0
+        });
+    }
+
+    function test_onlyOperatorCanExecuteFlash() public {
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(ArbExecutor.OnlyOperator.selector);
+        arb.executeArbFlash(AAVE_V3_POOL, _twoLegArb(100e6));
+    }
+
+    function test_flashZeroAmountReverts() public {
+        vm.expectRevert(ArbExecutor.ZeroAmount.selector);
+        arb.executeArbFlash(AAVE_V3_POOL, _twoLegArb(0));
+    }
+
+    /// @notice executeOperation outside an active flashloan (or from another
+    ///         caller / initiator) must revert — nobody can drive the swaps
+    function test_flashCallbackRejectsStrangers() public {
+        bytes memory params = abi.encode(_twoLegArb(100e6));
+
+        vm.prank(AAVE_V3_POOL);
+        vm.expectRevert(ArbExecutor.UnauthorizedFlashCallback.selector);
+        arb.executeOperation(USDC, 100e6, 50000, address(arb), params);
+
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(ArbExecutor.UnauthorizedFlashCallback.selector);
+        arb.executeOperation(USDC, 100e6, 50000, address(0xBEEF), params);
+    }
+
+    event ArbFlashExecuted(
+        address indexed token0,
+        address indexed token1,
+        uint256 amountIn,
+        uint256 amountOut,
+        uint256 premium,
+        uint256 profit
+    );
+
+    /// @notice Aave's premium on a flashloan: amount × FLASHLOAN_PREMIUM_TOTAL
+    ///         bps, rounded half up (PercentageMath.percentMul)
+    function _aavePremium(uint256 amount) internal view returns (uint256) {
+        uint256 premiumBps = IAavePoolPremium(AAVE_V3_POOL).FLASHLOAN_PREMIUM_TOTAL();
+        return (amount * premiumBps + 5000) / 10000;
+    }
+
+    /// @notice Fork: executeArbFlash borrows from the real Aave V3 pool, both
+    ///         legs run in executeOperation, the pool pulls back amount +
+    ///         premium and only the rest reaches the caller
+    function test_executeArbFlash_fork_repaysAndPaysProfit() public {
+        FixedRateRouter router = _fixedRateRouter(); // 100 USDC → 101 USDC
+        uint256 amountIn = 100e6;
+        uint256 premium = _aavePremium(amountIn);
+        uint256 poolBefore = IERC20(USDC).balanceOf(_aUsdc());
+        uint256 callerBefore = IERC20(USDC).balanceOf(deployer);
+
+        vm.expectEmit(true, true, false, true, address(arb));
+        emit ArbFlashExecuted(USDC, WETH, amountIn, 101e6, premium, 101e6 - amountIn - premium);
+        uint256 profit = arb.executeArbFlash(AAVE_V3_POOL, _fixedRateArb(address(router), amountIn));
+
+        assertEq(profit, 101e6 - amountIn - premium);
+        assertEq(IERC20(USDC).balanceOf(deployer), callerBefore + profit);
+        // The aToken reserve got the loan back with the premium
+        assertEq(IERC20(USDC).balanceOf(_aUsdc()), poolBefore + premium);
+        assertEq(IERC20(USDC).balanceOf(address(arb)), 0);
+        assertEq(IERC20(WETH).balanceOf(address(arb)), 0);
+        assertEq(IERC20(USDC).allowance(address(arb), AAVE_V3_POOL), 0);
+    }
+
+    /// @notice Fork: an output that repays the loan but not the premium
+    ///         reverts InsufficientProfit — the premium is never paid from
+    ///         the contract's own balance
+    function test_executeArbFlash_fork_premiumShortfallReverts() public {
+        FixedRateRouter router = _fixedRateRouter();
+        uint256 amountIn = 100e6;
+        uint256 premium = _aavePremium(amountIn);
+        assertGt(premium, 1);
+        // Sell leg returns amountIn + premium - 1
+        router.setRate(WETH, USDC, (amountIn + premium - 1) * 1e18 / 5e16);
+        deal(USDC, address(arb), 1_000e6); // stranded, must stay untouched
+
+        vm.expectRevert(abi.encodeWithSelector(ArbExecutor.InsufficientProfit.selector, uint256(0), uint256(0)));
+        arb.executeArbFlash(AAVE_V3_POOL, _fixedRateArb(address(router), amountIn));
+        assertEq(IERC20(USDC).balanceOf(address(arb)), 1_000e6);
+    }
+
+    /// @notice Aave V3 aToken holding the pool's USDC reserve
+    function _aUsdc() internal view returns (address) {
+        return IAavePoolPremium(AAVE_V3_POOL).getReserveData(USDC).aTokenAddress;
+    }
+
+    /// @notice Canonical Permit2 deployment (same address on every chain)
+    address constant PERMIT2 = 0x000000000022D473030F116dDEE9F6B43aC78BA3;
+
+    function test_onlyOperatorCanExecuteWithPermit() public {
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(ArbExecutor.OnlyOperator.selector);
+        arb.executeArbWithPermit(PERMIT2, _twoLegArb(100e6), 0, block.timestamp + 120, "");
+    }
+
+    /// @notice A zero amount reverts ZeroAmount before Permit2 is touched — the
+    ///         bot's startup eth_call relies on this selector to detect support
+    function test_permitZeroAmountReverts() public {
+        vm.expectRevert(ArbExecutor.ZeroAmount.selector);
+        arb.executeArbWithPermit(address(0), _twoLegArb(0), 0, 0, "");
+    }
+
+    // Permit2 SignatureTransfer EIP-712 types
+    bytes32 constant TOKEN_PERMISSIONS_TYPEHASH = keccak256("TokenPermissions(address token,uint256 amount)");
+    bytes32 constant PERMIT_TRANSFER_FROM_TYPEHASH = keccak256(
+        "PermitTransferFrom(TokenPermissions permitted,address spender,uint256 nonce,uint256 deadline)TokenPermissions(address token,uint256 amount)"
+    );
+
+    /// @notice Sign a PermitTransferFrom of `amount` USDC for the arb contract
+    function _signPermit(uint256 key, uint256 amount, uint256 nonce, uint256 deadline)
+        internal
+        view
+        returns (bytes memory)
+    {
+        bytes32 structHash = keccak256(
+            abi.encode(
+                PERMIT_TRANSFER_FROM_TYPEHASH,
+                keccak256(abi.encode(TOKEN_PERMISSIONS_TYPEHASH, USDC, amount)),
+                address(arb),
+                nonce,
+                deadline
+            )
+        );
+        bytes32 digest = keccak256(abi.encodePacked("\x19\x01", IPermit2Domain(PERMIT2).DOMAIN_SEPARATOR(), structHash));
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(key, digest);
+        return abi.encodePacked(r, s, v);
+    }
+
+    /// @notice Both legs on one FixedRateRouter: 100 USDC → 0.05 WETH → 101 USDC
+    function _fixedRateRouter() internal returns (FixedRateRouter router) {
+        router = new FixedRateRouter();
+        arb.setRouter(address(router), true);
+        router.setRate(USDC, WETH, 5e26); // 1e6 USDC → 5e14 WETH
+        router.setRate(WETH, USDC, 2020e6); // 1e18 WETH → 2020e6 USDC
+        deal(USDC, address(router), 10_000e6);
+        deal(WETH, address(router), 10e18);
+    }
+
+    function _fixedRateArb(address router, uint256 amountIn) internal pure returns (ArbExecutor.TwoLegArb memory a) {
+        a = _twoLegArb(amountIn);
+        a.routerBuy = router;
+        a.routerSell = router;
+    }
+
+    /// @notice Fork: an operator's real Permit2 signature (canonical Permit2,
+    ///         no allowance to the arb contract) funds both legs; the output
+    ///         and the profit go back to the signer
+    function test_executeArbWithPermit_fork_signedTransfer() public {
+        uint256 key = 0xA11CE;
+        address signer = vm.addr(key);
+        arb.setOperator(signer, true);
+        FixedRateRouter router = _fixedRateRouter();
+        deal(USDC, signer, 100e6);
+        vm.prank(signer);
+        IERC20(USDC).approve(PERMIT2, type(uint256).max);
+
+        uint256 deadline = block.timestamp + 120;
+        bytes memory sig = _signPermit(key, 100e6, 7, deadline);
+        vm.prank(signer);
+        uint256 profit = arb.executeArbWithPermit(PERMIT2, _fixedRateArb(address(router), 100e6), 7, deadline, sig);
+
+        assertEq(profit, 1e6);
+        assertEq(IERC20(USDC).balanceOf(signer), 101e6);
+        assertEq(IERC20(USDC).allowance(signer, address(arb)), 0);
+        assertEq(IERC20(USDC).balanceOf(address(arb)), 0);
+        assertEq(IERC20(WETH).balanceOf(address(arb)), 0);
+    }
+
+    /// @notice Permit2 nonces are single-use: a second signature over a spent
+    ///         nonce reverts InvalidNonce and nothing is pulled
+    function test_executeArbWithPermit_fork_reusedNonceReverts() public {
+        uint256 key = 0xA11CE;
+        address signer = vm.addr(key);
+        arb.setOperator(signer, true);
+        FixedRateRouter router = _fixedRateRouter();
+        deal(USDC, signer, 200e6);
+        vm.prank(signer);
+        IERC20(USDC).approve(PERMIT2, type(uint256).max);
+
+        uint256 deadline = block.timestamp + 120;
+        ArbExecutor.TwoLegArb memory a = _fixedRateArb(address(router), 100e6);
+        vm.prank(signer);
+        arb.executeArbWithPermit(PERMIT2, a, 7, deadline, _signPermit(key, 100e6, 7, deadline));
+        assertEq(IERC20(USDC).balanceOf(signer), 201e6);
+
+        bytes memory again = _signPermit(key, 100e6, 7, deadline);
+        vm.prank(signer);
+        vm.expectRevert(bytes4(keccak256("InvalidNonce()")));
+        arb.executeArbWithPermit(PERMIT2, a, 7, deadline, again);
+        assertEq(IERC20(USDC).balanceOf(signer), 201e6);
+    }
+
+    // QuickSwap V2 USDC.e/WETH pair (Polygon)
+    address constant QUICKSWAP_V2_USDC_WETH = 0x853Ee4b2A13f8a742d64C8F088bE7bA2131f670d;
+
+    function test_onlyOperatorCanExecuteFlashV2() public {
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(ArbExecutor.OnlyOperator.selector);
+        arb.executeArbFlashV2(QUICKSWAP_V2_USDC_WETH, 1e16, _twoLegArb(100e6));
+    }
+
+    function test_flashV2ZeroAmountReverts() public {
+        vm.expectRevert(ArbExecutor.ZeroAmount.selector);
+        arb.executeArbFlashV2(QUICKSWAP_V2_USDC_WETH, 0, _twoLegArb(100e6));
+        vm.expectRevert(ArbExecutor.ZeroAmount.selector);
+        arb.executeArbFlashV2(QUICKSWAP_V2_USDC_WETH, 1e16, _twoLegArb(0));
+    }
+
+    /// @notice uniswapV2Call outside an active flash swap (or naming another
+    ///         sender) must revert — nobody can make the contract sell or repay
+    function test_flashV2CallbackRejectsStrangers() public {
+        bytes memory data = abi.encode(_twoLegArb(100e6), uint256(100e6));
+
+        vm.prank(QUICKSWAP_V2_USDC_WETH);
+        vm.expectRevert(ArbExecutor.UnauthorizedFlashCallback.selector);
+        arb.uniswapV2Call(address(arb), 0, 1e16, data);
+
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(ArbExecutor.UnauthorizedFlashCallback.selector);
+        arb.uniswapV2Call(address(0xBEEF), 0, 1e16, data);
+    }
+
+    // USDC.e (bridged, 6 dec) — token0 of the QuickSwap V2 USDC.e/WETH pair
+    address constant USDC_E = 0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174;
+
+    event ArbFlashV2Executed(
+        address indexed token0,
+        address indexed token1,
+        address pair,
+        uint256 owed,
+        uint256 amountOut,
+        uint256 profit
+    );
+
+    /// @notice UniswapV2Library.getAmountIn on the pair's live reserves: the
+    ///         `tokenIn` the pair needs for `baseOut` of the other token
+    function _v2AmountIn(address tokenIn, uint256 baseOut) internal view returns (uint256) {
+        IQuickSwapV2Pair pair = IQuickSwapV2Pair(QUICKSWAP_V2_USDC_WETH);
+        (uint112 reserve0, uint112 reserve1, ) = pair.getReserves();
+        (uint256 reserveIn, uint256 reserveOut) = pair.token0() == tokenIn
+            ? (uint256(reserve0), uint256(reserve1))
+            : (uint256(reserve1), uint256(reserve0));
+        return reserveIn * baseOut * 1000 / ((reserveOut - baseOut) * 997) + 1;
+    }
+
+    /// @notice Flash-V2 arb on the QuickSwap pair: borrow `baseOut` of token1,
+    ///         sell it on a FixedRateRouter for owed + `profit` of token0
+    function _flashV2Fork(address token0, address token1, uint256 baseOut, uint256 profit) internal {
+        uint256 owed = _v2AmountIn(token0, baseOut);
+        FixedRateRouter router = new FixedRateRouter();
+        arb.setRouter(address(router), true);
+        router.setRate(token1, token0, (owed + profit) * 1e18 / baseOut);
+        deal(token0, address(router), (owed + profit) * 2);
+
+        ArbExecutor.TwoLegArb memory a = _fixedRateArb(address(router), owed);
+        a.token0 = token0;
+        a.token1 = token1;
+        uint256 pairBefore = IERC20(token0).balanceOf(QUICKSWAP_V2_USDC_WETH);
+        uint256 pairToken1Before = IERC20(token1).balanceOf(QUICKSWAP_V2_USDC_WETH);
+        uint256 callerBefore = IERC20(token0).balanceOf(deployer);
+
+        vm.expectEmit(true, true, false, true, address(arb));
+        emit ArbFlashV2Executed(token0, token1, QUICKSWAP_V2_USDC_WETH, owed, owed + profit, profit);
+        uint256 got = arb.executeArbFlashV2(QUICKSWAP_V2_USDC_WETH, baseOut, a);
+
+        // Repaid exactly getAmountIn(baseOut), in token0, for baseOut of token1
+        assertEq(IERC20(token0).balanceOf(QUICKSWAP_V2_USDC_WETH), pairBefore + owed);
+        assertEq(IERC20(token1).balanceOf(QUICKSWAP_V2_USDC_WETH), pairToken1Before - baseOut);
+        assertEq(got, profit);
+        assertEq(IERC20(token0).balanceOf(deployer), callerBefore + profit);
+        assertEq(IERC20(token0).balanceOf(address(arb)), 0);
+        assertEq(IERC20(token1).balanceOf(address(arb)), 0);
+
+        // A cap one unit below what the pair is owed (on the moved reserves) is refused up front
+        owed = _v2AmountIn(token0, baseOut);
+        a.amountIn = owed - 1;
+        vm.expectRevert(abi.encodeWithSelector(ArbExecutor.FlashV2OwedExceedsCap.selector, owed, owed - 1));
+        arb.executeArbFlashV2(QUICKSWAP_V2_USDC_WETH, baseOut, a);
+    }
+
+    /// @notice Fork: arb token0 is the pair's token0 (USDC.e) — flash 0.01 WETH
+    ///         out through uniswapV2Call, repay USDC.e
+    function test_executeArbFlashV2_fork_token0IsPairToken0() public {
+        _flashV2Fork(USDC_E, WETH, 1e16, 1e6);
+    }
+
+    /// @notice Fork: arb token0 is the pair's token1 (WETH) — flash 20 USDC.e
+    ///         out through uniswapV2Call, repay WETH
+    function test_executeArbFlashV2_fork_token0IsPairToken1() public {
+        _flashV2Fork(WETH, USDC_E, 20e6, 1e14);
+    }
+
+    function _splitArb(uint256 amountIn, uint16 sellShareBps) internal pure returns (ArbExecutor.SplitArb memory) {
+        return ArbExecutor.SplitArb({
+            token0: USDC,
+            token1: WETH,
+            routerBuy: UNI_V3_ROUTER,
+            feeBuy: 500,
+            routerSellA: UNI_V3_ROUTER,
+            feeSellA: 500,
+            routerSellB: UNI_V3_ROUTER,
+            feeSellB: 3000,
+            sellShareBps: sellShareBps,
+            amountIn: amountIn,
+            minProfit: 0
+        });
+    }
+
+    function test_onlyOperatorCanExecuteSplit() public {
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(ArbExecutor.OnlyOperator.selector);
+        arb.executeArbSplit(_splitArb(100e6, 3000));
+    }
+
+    function test_splitZeroAmountAndShareBoundsRevert() public {
+        vm.expectRevert(ArbExecutor.ZeroAmount.selector);
+        arb.executeArbSplit(_splitArb(0, 3000));
+
+        vm.expectRevert(abi.encodeWithSelector(ArbExecutor.InvalidSplit.selector, uint16(0)));
+        arb.executeArbSplit(_splitArb(100e6, 0));
+
+        vm.expectRevert(abi.encodeWithSelector(ArbExecutor.InvalidSplit.selector, uint16(10000)));
+        arb.executeArbSplit(_splitArb(100e6, 10000));
+    }
+
+    /// @notice Fork: all three swaps run in one tx; the round trip loses the
+    ///         fees, so with minProfit=0 it either returns >= amountIn or
+    ///         reverts with nothing lost
+    function test_executeArbSplit_fork_allLegsExecute() public {
+        uint256 amountIn = 100e6;
+        deal(USDC, deployer, amountIn);
+        IERC20(USDC).approve(address(arb), amountIn);
+
+        try arb.executeArbSplit(_splitArb(amountIn, 4000)) returns (uint256 profit) {
+            console.log("Split arb executed! Profit:", profit);
+            assertGe(IERC20(USDC).balanceOf(deployer), amountIn);
+        } catch {
+            console.log("Split arb reverted (expected: fees exceed spread)");
+            assertEq(IERC20(USDC).balanceOf(deployer), amountIn);
+        }
+        assertEq(IERC20(USDC).balanceOf(address(arb)), 0);
+        assertEq(IERC20(WETH).balanceOf(address(arb)), 0);
+    }
+
+    function test_onlyOwnerManagesVenues() public {
+        vm.startPrank(address(0xBEEF));
+        vm.expectRevert(ArbExecutor.OnlyOwner.selector);
+        arb.setRouter(address(0xBAD), true);
+        vm.expectRevert(ArbExecutor.OnlyOwner.selector);
+        arb.setAavePool(address(0xBAD), true);
+        vm.expectRevert(ArbExecutor.OnlyOwner.selector);
+        arb.setV2Factory(address(0xBAD), true);
+        vm.stopPrank();
+        assertTrue(arb.allowedRouters(UNI_V3_ROUTER));
+        assertFalse(arb.allowedRouters(address(0xBAD)));
+    }
+
+    /// @notice An operator can't route a leg through a router the owner didn't allow
+    function test_unlistedRouterReverts() public {
+        address operator = address(0xBEEF);
+        arb.setOperator(operator, true);
+        LyingRouter fake = new LyingRouter();
+        deal(USDC, operator, 100e6);
+        vm.startPrank(operator);
+        IERC20(USDC).approve(address(arb), 100e6);
+        vm.expectRevert(abi.encodeWithSelector(ArbExecutor.RouterNotAllowed.selector, address(fake)));
+        arb.executeArb(USDC, WETH, address(fake), UNI_V3_ROUTER, 500, 500, 100e6, 0);
+        vm.stopPrank();
+    }
+
+    /// @notice A router's reported amountOut is never trusted: one that keeps
+    ///         the input and reports a huge output can't pay out the contract's
+    ///         stranded balance
+    function test_reportedOutputIgnored() public {
+        LyingRouter liar = new LyingRouter();
+        arb.setRouter(address(liar), true);
+        deal(USDC, address(arb), 1_000e6); // stranded
+        deal(USDC, deployer, 100e6);
+        IERC20(USDC).approve(address(arb), 100e6);
+
+        vm.expectRevert(bytes("insufficient output"));
+        arb.executeArb(USDC, WETH, address(liar), address(liar), 500, 500, 100e6, 0);
+        assertEq(IERC20(USDC).balanceOf(address(arb)), 1_000e6);
+        assertEq(IERC20(USDC).balanceOf(deployer), 100e6);
+    }
+
+    /// @notice executeArbFlash only borrows from allowed Aave pools — a fake
+    ///         pool can't get the callback (and the repayment approval)
+    function test_unlistedAavePoolReverts() public {
+        vm.expectRevert(abi.encodeWithSelector(ArbExecutor.AavePoolNotAllowed.selector, address(0xBAD)));
+        arb.executeArbFlash(address(0xBAD), _twoLegArb(100e6));
+    }
+
+    /// @notice executeArbFlashV2 only flash swaps on a pair an allowed factory
+    ///         lists for the token pair — not one claiming that factory
+    function test_unlistedV2PairReverts() public {
+        FakeV2Pair fake = new FakeV2Pair(QUICKSWAP_V2_FACTORY, USDC);
+        vm.expectRevert(abi.encodeWithSelector(ArbExecutor.PairNotAllowed.selector, address(fake)));
+        arb.executeArbFlashV2(address(fake), 1e16, _twoLegArb(100e6));
+
+        FakeV2Pair unlisted = new FakeV2Pair(address(0xFAC), USDC);
+        vm.expectRevert(abi.encodeWithSelector(ArbExecutor.PairNotAllowed.selector, address(unlisted)));
+        arb.executeArbFlashV2(address(unlisted), 1e16, _twoLegArb(100e6));
+    }
+}
+
+/// @notice Router that keeps the input and reports an inflated output
+contract LyingRouter {
+    function exactInputSingle(ISwapRouterParams calldata params) external returns (uint256) {
+        IERC20(params.tokenIn).transferFrom(msg.sender, address(this), params.amountIn);
+        return params.amountIn * 1000;
+    }
+}
+
+/// @notice V3-shaped router paying fixed rates out of its own balance, so the
+///         legs (and the profit) of a fork test are known exactly
+contract FixedRateRouter {
+    /// @dev tokenIn => tokenOut => output per 1e18 units of input
+    mapping(address => mapping(address => uint256)) public rate;
+
+    function setRate(address tokenIn, address tokenOut, uint256 rateE18) external {
+        rate[tokenIn][tokenOut] = rateE18;
+    }
+
+    function exactInputSingle(ISwapRouterParams calldata params) external returns (uint256 amountOut) {
+        IERC20(params.tokenIn).transferFrom(msg.sender, address(this), params.amountIn);
+        amountOut = params.amountIn * rate[params.tokenIn][params.tokenOut] / 1e18;
+        IERC20(params.tokenOut).transfer(params.recipient, amountOut);
+    }
+}
+
+/// @notice Aave V3 Pool getters the flashloan fork tests read
+interface IAavePoolPremium {
+    struct ReserveData {
+        uint256 configuration;
+        uint128 liquidityIndex;
+        uint128 currentLiquidityRate;
+        uint128 variableBorrowIndex;
+        uint128 currentVariableBorrowRate;
+        uint128 currentStableBorrowRate;
+        uint40 lastUpdateTimestamp;
+        uint16 id;
+        address aTokenAddress;
+        address stableDebtTokenAddress;
+        address variableDebtTokenAddress;
+        address interestRateStrategyAddress;
+        uint128 accruedToTreasury;
+        uint128 unbacked;
+        uint128 isolationModeTotalDebt;
+    }
+
+    function FLASHLOAN_PREMIUM_TOTAL() external view returns (uint128);
+
+    function getReserveData(address asset) external view returns (ReserveData memory);
+}
+
+/// @notice V2 pair getters the flash-swap fork tests read
+interface IQuickSwapV2Pair {
+    function token0() external view returns (address);
+
+    function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
+}
+
+/// @notice Permit2's EIP-712 domain separator getter
+interface IPermit2Domain {
+    function DOMAIN_SEPARATOR() external view returns (bytes32);
+}
+
+/// @notice ISwapRouter.ExactInputSingleParams layout (Uniswap V3 SwapRouter)
+struct ISwapRouterParams {
+    address tokenIn;
+    address tokenOut;
+    uint24 fee;
+    address recipient;
+    uint256 deadline;
+    uint256 amountIn;
+    uint256 amountOutMinimum;
+    uint160 sqrtPriceLimitX96;
+}
+
+/// @notice Contract posing as a V2 pair of `factory`
+contract FakeV2Pair {
+    address public immutable factory;
+    address public immutable token0;
+
+    constructor(address factory_, address token0_) {
+        factory = factory_;
+        token0 = token0_;
+    }
+
+    function getReserves() external pure returns (uint112, uint112, uint32) {
+        return (1e12, 1e21, 0);
+    }
+
+    function swap(uint256, uint256, address, bytes calldata) external {}
 }
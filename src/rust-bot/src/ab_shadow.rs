@@ -0,0 +1,345 @@
+//! Strategy A/B Shadow — an experimental detector config beside the live one
+//!
+//! Purpose:
+//!     Trial new thresholds (lower MIN_PROFIT_USD, another slippage model, a
+//!     different sizing) against live data without risking capital or running
+//!     a separate paper-trading process. SHADOW_CONFIG names a TOML of
+//!     overrides (chain_config.rs format) layered over the live config; a
+//!     second OpportunityDetector built from it scans the same
+//!     PoolStateManager every block and never executes.
+//!
+//! Per block:
+//!   1. observe() — right after the live scan, before the live gates (stale
+//!      legs, spread confirmation, dedup): the shadow scans and both raw scans
+//!      are diffed with shadow::compare(), live as primary, shadow as follower:
+//!      extra = the shadow would trade it, live didn't see it; missed = live
+//!      saw it, the shadow didn't; threshold = one-sided, estimate between the
+//!      two MIN_PROFIT_USD; estimate / ordering as in shadow.rs.
+//!      CPU only: the detector scan plus a hash diff (well under 1ms/block).
+//!   2. finish() — after the block's trades: when the shadow found between 1
+//!      and SHADOW_VERIFY_MAX routes, the main loop Multicall-quotes them (one
+//!      batch, off the hot path) and passes the results in.
+//!
+//! Output (data/{chain}/shadow):
+//!   decisions_YYYYMMDD.csv    one row per route the shadow would have traded:
+//!                             pools, size, prices, estimate, quote (when run)
+//!                             — enough to compute its hypothetical PnL later
+//!   ab_divergence_YYYYMMDD.csv  divergence rows (primary = live, follower = shadow)
+//!
+//! Only the live config's pools are synced: shadow pairs outside the live
+//! whitelist find no state.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::arbitrage::multicall_quoter::VerifiedOpportunity;
+use crate::arbitrage::slippage::route_key;
+use crate::arbitrage::OpportunityDetector;
+use crate::pool::PoolStateManager;
+use crate::shadow::{append_divergences, compare, BlockDecisions, Divergence, DivergenceKind};
+use crate::types::{ArbitrageOpportunity, BotConfig};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+
+const DECISIONS_HEADER: &str = "timestamp,block,route,pair,buy_dex,sell_dex,buy_pool,sell_pool,trade_size_raw,\
+trade_size_usd,buy_price,sell_price,spread_percent,estimated_profit_usd,min_profit_usd,live_detected,\
+verified,quoted_profit_raw,quoted_profit_usd,verify_error";
+
+/// A scanned block waiting for its (optional) quotes
+struct PendingBlock {
+    block: u64,
+    opportunities: Vec<ArbitrageOpportunity>,
+    live_routes: HashSet<String>,
+    divergences: Vec<Divergence>,
+}
+
+/// Running counts for the status line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AbStats {
+    pub blocks: u64,
+    pub shadow_routes: u64,
+    pub live_routes: u64,
+    pub extra: u64,
+    pub missed: u64,
+    pub threshold: u64,
+    pub verified: u64,
+    /// Quoted with both legs valid and a positive quoted profit
+    pub verified_profitable: u64,
+    pub scan_micros_total: u64,
+    pub scan_micros_max: u64,
+}
+
+/// The experimental detector and its decision log
+pub struct AbShadow {
+    detector: OpportunityDetector,
+    min_profit_usd: f64,
+    verify_max: usize,
+    out_dir: PathBuf,
+    pending: Option<PendingBlock>,
+    stats: AbStats,
+}
+
+impl AbShadow {
+    /// `config` = the experimental config (load_shadow_config); `verify_max`
+    /// from the live config's SHADOW_VERIFY_MAX
+    pub fn new(config: BotConfig, state_manager: PoolStateManager, out_dir: &str, verify_max: usize) -> Self {
+        Self {
+            min_profit_usd: config.min_profit_usd,
+            detector: OpportunityDetector::new(config, state_manager),
+            verify_max,
+            out_dir: PathBuf::from(out_dir),
+            pending: None,
+            stats: AbStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> &AbStats {
+        &self.stats
+    }
+
+    /// Scan with the shadow config and diff against live's raw scan of the
+    /// same state (detection-only routes excluded on both sides). Returns the
+    /// divergences; the block is held until finish().
+    pub fn observe(&mut self, block: u64, live: &[ArbitrageOpportunity], live_min_profit_usd: f64) -> &[Divergence] {
+        let started = Instant::now();
+        let opportunities: Vec<ArbitrageOpportunity> = self
+            .detector
+            .scan_opportunities()
+            .into_iter()
+            .filter(|o| !o.buy_dex.is_detection_only() && !o.sell_dex.is_detection_only())
+            .collect();
+        let divergences = compare(
+            &BlockDecisions::from_detected(block, live, live_min_profit_usd),
+            &BlockDecisions::from_detected(block, &opportunities, self.min_profit_usd),
+        );
+        let micros = started.elapsed().as_micros() as u64;
+
+        self.stats.blocks += 1;
+        self.stats.shadow_routes += opportunities.len() as u64;
+        self.stats.live_routes += live.len() as u64;
+        self.stats.scan_micros_total += micros;
+        self.stats.scan_micros_max = self.stats.scan_micros_max.max(micros);
+        for d in &divergences {
+            match d.kind {
+                DivergenceKind::Extra => self.stats.extra += 1,
+                DivergenceKind::Missed => self.stats.missed += 1,
+                DivergenceKind::Threshold => self.stats.threshold += 1,
+                DivergenceKind::Estimate | DivergenceKind::Ordering => {}
+            }
+        }
+
+        let live_routes = live.iter().map(route_key).collect();
+        let pending = self.pending.insert(PendingBlock { block, opportunities, live_routes, divergences });
+        &pending.divergences
+    }
+
+    /// The observed block's routes worth one Multicall batch: all of them when
+    /// there are 1..=SHADOW_VERIFY_MAX, else none
+    pub fn to_verify(&self) -> Vec<ArbitrageOpportunity> {
+        match &self.pending {
+            Some(p) if !p.opportunities.is_empty() && p.opportunities.len() <= self.verify_max => p.opportunities.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Write the observed block's decisions and divergences. `verified` =
+    /// batch_verify() over to_verify() (empty = not quoted).
+    pub fn finish(&mut self, verified: &[VerifiedOpportunity]) -> Result<()> {
+        let Some(pending) = self.pending.take() else {
+            return Ok(());
+        };
+        for v in verified {
+            self.stats.verified += 1;
+            if v.both_legs_valid && v.quoted_profit_raw > 0 {
+                self.stats.verified_profitable += 1;
+            }
+        }
+        let day = Utc::now().format("%Y%m%d");
+        std::fs::create_dir_all(&self.out_dir).with_context(|| format!("create {}", self.out_dir.display()))?;
+        append_divergences(&self.out_dir.join(format!("ab_divergence_{}.csv", day)), &pending.divergences)?;
+        if pending.opportunities.is_empty() {
+            return Ok(());
+        }
+
+        let path = self.out_dir.join(format!("decisions_{}.csv", day));
+        let is_new = !path.exists();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("open {}", path.display()))?;
+        if is_new {
+            writeln!(file, "{}", DECISIONS_HEADER)?;
+        }
+        let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+        let pool = |a: Option<ethers::types::Address>| a.map(|a| format!("{:?}", a)).unwrap_or_default();
+        for (i, opp) in pending.opportunities.iter().enumerate() {
+            let route = route_key(opp);
+            let quote = verified.iter().find(|v| v.original_index == i);
+            let (is_verified, raw, usd, error) = match quote {
+                Some(v) => {
+                    let valid = v.both_legs_valid && v.error.is_none();
+                    let size = opp.trade_size.raw().low_u128();
+                    let usd = if size == 0 { 0.0 } else { v.quoted_profit_raw as f64 * opp.trade_size_usd / size as f64 };
+                    (
+                        valid.to_string(),
+                        v.quoted_profit_raw.to_string(),
+                        format!("{:.6}", usd),
+                        v.error.clone().unwrap_or_default().replace('"', "'"),
+                    )
+                }
+                None => Default::default(),
+            };
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{},{},{},{:.4},{:.10e},{:.10e},{:.6},{:.6},{:.4},{},{},{},{},\"{}\"",
+                now,
+                pending.block,
+                route,
+                opp.pair.symbol,
+                opp.buy_dex,
+                opp.sell_dex,
+                pool(opp.buy_pool_address),
+                pool(opp.sell_pool_address),
+                opp.trade_size.raw(),
+                opp.trade_size_usd,
+                opp.buy_price,
+                opp.sell_price,
+                opp.spread_percent,
+                opp.estimated_profit,
+                opp.min_profit_usd.unwrap_or(self.min_profit_usd),
+                pending.live_routes.contains(&route),
+                is_verified,
+                raw,
+                usd,
+                error,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Totals since start: routes per side, one-sided counts, quotes, scan time
+    pub fn status_line(&self) -> String {
+        let s = &self.stats;
+        let avg = s.scan_micros_total.checked_div(s.blocks).unwrap_or(0);
+        format!(
+            "{} blocks | shadow {} routes vs live {} | {} shadow-only, {} live-only, {} threshold | \
+             {} quoted ({} profitable) | scan avg {}µs max {}µs",
+            s.blocks, s.shadow_routes, s.live_routes, s.extra, s.missed, s.threshold,
+            s.verified, s.verified_profitable, avg, s.scan_micros_max
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::golden_state;
+    use ethers::types::U256;
+
+    fn live_scan(config: BotConfig, state: PoolStateManager) -> Vec<ArbitrageOpportunity> {
+        OpportunityDetector::new(config, state)
+            .scan_opportunities()
+            .into_iter()
+            .filter(|o| !o.buy_dex.is_detection_only() && !o.sell_dex.is_detection_only())
+            .collect()
+    }
+
+    #[test]
+    fn test_two_configs_over_one_state_diverge() {
+        let (config, state) = golden_state();
+        // Live trades WETH/USDC only at $1; the experiment adds WMATIC/USDC at $4
+        let mut live_config = config.clone();
+        live_config.pairs.retain(|p| p.symbol == "WETH/USDC");
+        let mut shadow_config = config.clone();
+        shadow_config.pairs.retain(|p| p.symbol == "WETH/USDC" || p.symbol == "WMATIC/USDC");
+        shadow_config.min_profit_usd = 4.0;
+
+        let live = live_scan(live_config, state.clone());
+        let dir = std::env::temp_dir().join(format!("dexarb_ab_shadow_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut ab = AbShadow::new(shadow_config, state, dir.to_str().unwrap(), 5);
+
+        let divergences: Vec<(DivergenceKind, String)> =
+            ab.observe(100, &live, 1.0).iter().map(|d| (d.kind, d.route.clone())).collect();
+        let shadow_only: Vec<&str> = divergences
+            .iter()
+            .filter(|(kind, _)| *kind == DivergenceKind::Extra)
+            .map(|(_, route)| route.as_str())
+            .collect();
+        assert_eq!(shadow_only, vec!["WMATIC/USDC QuickSwapV2→UniswapV3_030", "WMATIC/USDC UniswapV3_005→UniswapV3_030"]);
+        // Live's $1..$4 WETH/USDC routes sit between the two thresholds: not misses
+        let threshold: Vec<&str> = divergences
+            .iter()
+            .filter(|(kind, _)| *kind == DivergenceKind::Threshold)
+            .map(|(_, route)| route.as_str())
+            .collect();
+        assert!(!threshold.is_empty());
+        assert!(threshold.iter().all(|r| r.starts_with("WETH/USDC")));
+        assert!(divergences.iter().all(|(kind, _)| *kind != DivergenceKind::Missed));
+        assert_eq!((ab.stats().extra, ab.stats().threshold), (2, threshold.len() as u64));
+
+        // More routes than SHADOW_VERIFY_MAX: nothing quoted, decisions still written
+        let shadow_routes = ab.stats().shadow_routes as usize;
+        assert!(shadow_routes > 5);
+        assert!(ab.to_verify().is_empty());
+        ab.finish(&[]).unwrap();
+
+        let day = Utc::now().format("%Y%m%d");
+        let decisions = std::fs::read_to_string(dir.join(format!("decisions_{}.csv", day))).unwrap();
+        let rows: Vec<&str> = decisions.lines().skip(1).collect();
+        assert_eq!(rows.len(), shadow_routes);
+        let wmatic: Vec<&&str> = rows.iter().filter(|r| r.contains(",WMATIC/USDC,")).collect();
+        assert_eq!(wmatic.len(), 2);
+        assert!(wmatic.iter().all(|r| r.contains(",false,,,,\"\"")), "{:?}", wmatic);
+        let divergence_rows = std::fs::read_to_string(dir.join(format!("ab_divergence_{}.csv", day))).unwrap();
+        assert_eq!(divergence_rows.lines().count(), divergences.len() + 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_quotes_recorded_when_cheap() {
+        let (mut config, state) = golden_state();
+        config.pairs.retain(|p| p.symbol == "WMATIC/USDC");
+        let dir = std::env::temp_dir().join(format!("dexarb_ab_shadow_quotes_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut ab = AbShadow::new(config, state, dir.to_str().unwrap(), 5);
+
+        // Live saw nothing: every shadow route is shadow-only
+        assert_eq!(ab.observe(100, &[], 1.0).len(), 2);
+        let to_verify = ab.to_verify();
+        assert_eq!(to_verify.len(), 2);
+        let size = to_verify[0].trade_size.raw().low_u128() as i128;
+        let verified = vec![
+            VerifiedOpportunity {
+                original_index: 0,
+                buy_quoted_out: U256::from(1u64),
+                sell_quoted_out: U256::from(1u64),
+                quoted_profit_raw: size / 100,
+                both_legs_valid: true,
+                error: None,
+            },
+            VerifiedOpportunity { error: Some("Quoter reverted".to_string()), both_legs_valid: false, ..VerifiedOpportunity::passthrough(1) },
+        ];
+        ab.finish(&verified).unwrap();
+        assert_eq!((ab.stats().verified, ab.stats().verified_profitable), (2, 1));
+
+        let day = Utc::now().format("%Y%m%d");
+        let decisions = std::fs::read_to_string(dir.join(format!("decisions_{}.csv", day))).unwrap();
+        let rows: Vec<&str> = decisions.lines().collect();
+        assert_eq!(rows[0], DECISIONS_HEADER);
+        // 1% of the trade size quoted as profit
+        let usd = format!("{:.6}", to_verify[0].trade_size_usd / 100.0);
+        assert!(rows[1].ends_with(&format!(",false,true,{},{},\"\"", size / 100, usd)), "{}", rows[1]);
+        assert!(rows[2].ends_with(",false,false,0,0.000000,\"Quoter reverted\""), "{}", rows[2]);
+        // Nothing pending: finish() is a no-op
+        ab.finish(&[]).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join(format!("decisions_{}.csv", day))).unwrap(), decisions);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,88 @@
+//! Alert Dispatcher — severity-tagged operator alerts
+//!
+//! One place to send an operator alert: logs at the level matching the
+//! severity and, when ALERT_WEBHOOK_URL is set, posts to the webhook
+//! (Discord-compatible `{"content": ...}`). Sends are spawned with a timeout
+//! so a slow webhook never delays the block loop.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Webhook request timeout
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How urgently a human needs to look
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    fn emoji(self) -> &'static str {
+        match self {
+            AlertSeverity::Info => "ℹ️",
+            AlertSeverity::Warning => "⚠️",
+            AlertSeverity::Critical => "🚨",
+        }
+    }
+}
+
+impl fmt::Display for AlertSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            AlertSeverity::Info => "INFO",
+            AlertSeverity::Warning => "WARNING",
+            AlertSeverity::Critical => "CRITICAL",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Log + optional webhook, fire-and-forget
+#[derive(Clone)]
+pub struct AlertDispatcher {
+    webhook_url: Option<String>,
+    client: reqwest::Client,
+}
+
+impl AlertDispatcher {
+    pub fn new(webhook_url: Option<String>) -> Self {
+        Self { webhook_url, client: reqwest::Client::new() }
+    }
+
+    /// Text as logged and posted: "<emoji> [SEVERITY] message"
+    pub fn format(severity: AlertSeverity, message: &str) -> String {
+        format!("{} [{}] {}", severity.emoji(), severity, message)
+    }
+
+    /// Log at the severity's level and post to the webhook in the background.
+    /// Must be called from within a tokio runtime when a webhook is configured.
+    pub fn dispatch(&self, severity: AlertSeverity, message: &str) {
+        let text = Self::format(severity, message);
+        match severity {
+            AlertSeverity::Info => info!("{}", text),
+            AlertSeverity::Warning => warn!("{}", text),
+            AlertSeverity::Critical => error!("{}", text),
+        }
+
+        let url = match self.webhook_url {
+            Some(ref u) => u.clone(),
+            None => return,
+        };
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let body = serde_json::json!({ "content": text });
+            if let Err(e) = client.post(&url).json(&body).timeout(WEBHOOK_TIMEOUT).send().await {
+                warn!("Alert webhook failed: {}", e);
+            }
+        });
+    }
+}
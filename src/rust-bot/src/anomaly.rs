@@ -0,0 +1,554 @@
+//! Anomaly Monitor — rolling baselines over the bot's own behavior
+//!
+//! Nobody watches the logs all day. This module notices when the bot's own
+//! behavior shifts: detection collapsing (sync problem), reverts spiking
+//! (competitor or config drift), execution getting slower, profit trending
+//! down.
+//!
+//! Series, one sample per ANOMALY_WINDOW_SECS window:
+//!   opportunities_per_hour  — detector output, before cooldown/canary filters
+//!   prescreen_pass_rate     — verified / pre-screened (multicall path only)
+//!   revert_rate             — reverted / submitted trades
+//!   median_exec_ms          — median TradeResult.execution_time_ms of submitted trades
+//!   net_profit_per_hour     — sum of submitted trades' net_profit_usd
+//! Windows without submitted trades yield no revert/latency sample; windows
+//! without a multicall pre-screen yield no pass-rate sample.
+//!
+//! Each series keeps an EWMA mean and variance. Once warmed up (WARMUP_SAMPLES),
+//! a sample beyond ANOMALY_SIGMA standard deviations in the metric's bad
+//! direction opens a breach; a breach sustained for ANOMALY_MIN_DURATION_SECS
+//! is flagged once (severity per metric) and a recovery is flagged when the
+//! series comes back. The baseline is frozen during a breach so the anomaly
+//! doesn't absorb itself; a flagged breach still open after REBASELINE_SECS is
+//! accepted as the new normal (baseline reset, warmup again).
+//!
+//! Baselines persist to data/{chain}/anomaly_baselines.json after every window
+//! so a restart doesn't cold-start into false alarms.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::alerts::AlertSeverity;
+use crate::types::{BotConfig, TradeResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// EWMA weight of the newest sample (~20-window memory)
+pub const EWMA_ALPHA: f64 = 0.05;
+
+/// Samples a baseline needs before it can flag
+pub const WARMUP_SAMPLES: u64 = 8;
+
+/// A flagged breach this old is a regime change, not an anomaly
+pub const REBASELINE_SECS: u64 = 24 * 3600;
+
+/// Std-dev floor as a fraction of |mean| (a near-constant series would
+/// otherwise flag on noise)
+const MIN_REL_STD: f64 = 0.05;
+
+/// One monitored series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    OpportunitiesPerHour,
+    PrescreenPassRate,
+    RevertRate,
+    MedianExecMs,
+    NetProfitPerHour,
+}
+
+/// Which deviation is bad
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Low,
+    High,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 5] = [
+        Metric::OpportunitiesPerHour,
+        Metric::PrescreenPassRate,
+        Metric::RevertRate,
+        Metric::MedianExecMs,
+        Metric::NetProfitPerHour,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Metric::OpportunitiesPerHour => "opportunities_per_hour",
+            Metric::PrescreenPassRate => "prescreen_pass_rate",
+            Metric::RevertRate => "revert_rate",
+            Metric::MedianExecMs => "median_exec_ms",
+            Metric::NetProfitPerHour => "net_profit_per_hour",
+        }
+    }
+
+    pub fn bad_direction(self) -> Direction {
+        match self {
+            Metric::RevertRate | Metric::MedianExecMs => Direction::High,
+            Metric::OpportunitiesPerHour | Metric::PrescreenPassRate | Metric::NetProfitPerHour => Direction::Low,
+        }
+    }
+
+    /// Detection collapse and revert spikes can mean lost money now
+    pub fn severity(self) -> AlertSeverity {
+        match self {
+            Metric::OpportunitiesPerHour | Metric::RevertRate => AlertSeverity::Critical,
+            Metric::PrescreenPassRate | Metric::MedianExecMs | Metric::NetProfitPerHour => AlertSeverity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Thresholds and window length
+#[derive(Debug, Clone)]
+pub struct AnomalyConfig {
+    pub window_secs: u64,
+    /// Deviation threshold in standard deviations
+    pub sigma: f64,
+    /// A breach must last this long before it is flagged
+    pub min_duration_secs: u64,
+}
+
+impl AnomalyConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            window_secs: config.anomaly_window_secs.max(1),
+            sigma: config.anomaly_sigma,
+            min_duration_secs: config.anomaly_min_duration_secs,
+        }
+    }
+}
+
+/// EWMA mean + variance of one series
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub mean: f64,
+    pub var: f64,
+    pub samples: u64,
+}
+
+impl Baseline {
+    pub fn update(&mut self, x: f64) {
+        if self.samples == 0 {
+            self.mean = x;
+            self.var = 0.0;
+        } else {
+            let diff = x - self.mean;
+            let incr = EWMA_ALPHA * diff;
+            self.mean += incr;
+            self.var = (1.0 - EWMA_ALPHA) * (self.var + diff * incr);
+        }
+        self.samples += 1;
+    }
+
+    pub fn std(&self) -> f64 {
+        self.var.sqrt().max(MIN_REL_STD * self.mean.abs()).max(1e-9)
+    }
+
+    /// Standard score of `x` (None until warmed up)
+    pub fn z(&self, x: f64) -> Option<f64> {
+        (self.samples >= WARMUP_SAMPLES).then(|| (x - self.mean) / self.std())
+    }
+}
+
+/// A flagged deviation (or its recovery)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyFlag {
+    pub metric: Metric,
+    pub severity: AlertSeverity,
+    pub value: f64,
+    pub mean: f64,
+    pub std: f64,
+    pub z: f64,
+    /// Breach length when flagged
+    pub sustained_secs: u64,
+    pub recovered: bool,
+}
+
+impl AnomalyFlag {
+    pub fn message(&self) -> String {
+        if self.recovered {
+            format!(
+                "Anomaly cleared: {} back to {:.3} (baseline {:.3})",
+                self.metric, self.value, self.mean
+            )
+        } else {
+            format!(
+                "Anomaly: {} = {:.3} vs baseline {:.3} ± {:.3} (z = {:+.1}) for {} min",
+                self.metric, self.value, self.mean, self.std, self.z, self.sustained_secs / 60
+            )
+        }
+    }
+}
+
+/// Raw counts for the current window
+#[derive(Debug, Default)]
+struct WindowCounts {
+    opportunities: u64,
+    prescreened: u64,
+    prescreen_passed: u64,
+    trades: u64,
+    reverts: u64,
+    exec_ms: Vec<u64>,
+    net_profit_usd: f64,
+}
+
+impl WindowCounts {
+    /// Samples for this window, `elapsed` seconds long
+    fn samples(&mut self, elapsed: u64) -> Vec<(Metric, f64)> {
+        let per_hour = 3600.0 / elapsed.max(1) as f64;
+        let mut out = vec![
+            (Metric::OpportunitiesPerHour, self.opportunities as f64 * per_hour),
+            (Metric::NetProfitPerHour, self.net_profit_usd * per_hour),
+        ];
+        if self.prescreened > 0 {
+            out.push((Metric::PrescreenPassRate, self.prescreen_passed as f64 / self.prescreened as f64));
+        }
+        if self.trades > 0 {
+            out.push((Metric::RevertRate, self.reverts as f64 / self.trades as f64));
+            self.exec_ms.sort_unstable();
+            out.push((Metric::MedianExecMs, self.exec_ms[self.exec_ms.len() / 2] as f64));
+        }
+        out
+    }
+}
+
+/// Persisted baselines
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineFile {
+    saved_at: String,
+    baselines: BTreeMap<Metric, Baseline>,
+}
+
+/// Windowed series → EWMA baselines → sustained-deviation flags
+pub struct AnomalyMonitor {
+    config: AnomalyConfig,
+    baselines: BTreeMap<Metric, Baseline>,
+    /// Breach start (window start time) and whether it has been flagged
+    breaches: BTreeMap<Metric, (u64, bool)>,
+    window_start: Option<u64>,
+    counts: WindowCounts,
+    path: Option<PathBuf>,
+}
+
+impl AnomalyMonitor {
+    pub fn new(config: AnomalyConfig) -> Self {
+        info!(
+            "Anomaly monitor: {}s windows | {:.1}σ sustained {}s",
+            config.window_secs, config.sigma, config.min_duration_secs
+        );
+        Self {
+            config,
+            baselines: BTreeMap::new(),
+            breaches: BTreeMap::new(),
+            window_start: None,
+            counts: WindowCounts::default(),
+            path: None,
+        }
+    }
+
+    /// Load and save baselines in `{data_dir}/anomaly_baselines.json`
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Anomaly: failed to create {}: {}", data_dir, e);
+        }
+        let path = dir.join("anomaly_baselines.json");
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<BaselineFile>(&content) {
+                Ok(file) => {
+                    info!("Anomaly: {} baselines restored (saved {})", file.baselines.len(), file.saved_at);
+                    self.baselines = file.baselines;
+                }
+                Err(e) => warn!("Anomaly: ignoring unreadable {}: {}", path.display(), e),
+            }
+        }
+        self.path = Some(path);
+        self
+    }
+
+    pub fn baseline(&self, metric: Metric) -> Option<&Baseline> {
+        self.baselines.get(&metric)
+    }
+
+    /// Opportunities found by one detector scan
+    pub fn record_opportunities(&mut self, count: usize) {
+        self.counts.opportunities += count as u64;
+    }
+
+    /// One multicall pre-screen: `passed` of `total` verified
+    pub fn record_prescreen(&mut self, passed: usize, total: usize) {
+        self.counts.prescreened += total as u64;
+        self.counts.prescreen_passed += passed as u64;
+    }
+
+    /// One execution result; only submitted transactions count as trades
+    pub fn record_trade(&mut self, result: &TradeResult) {
+        if result.tx_hash.is_none() {
+            return;
+        }
+        self.counts.trades += 1;
+        if !result.success && result.error.as_deref().is_some_and(|e| e.contains("reverted")) {
+            self.counts.reverts += 1;
+        }
+        self.counts.exec_ms.push(result.execution_time_ms);
+        self.counts.net_profit_usd += result.net_profit_usd;
+    }
+
+    /// Close the window if `now` (unix secs) is past its end; returns new flags
+    pub fn tick(&mut self, now: u64) -> Vec<AnomalyFlag> {
+        let start = match self.window_start {
+            Some(s) => s,
+            None => {
+                self.window_start = Some(now);
+                return Vec::new();
+            }
+        };
+        let elapsed = now.saturating_sub(start);
+        if elapsed < self.config.window_secs {
+            return Vec::new();
+        }
+
+        let samples = std::mem::take(&mut self.counts).samples(elapsed);
+        let flags = samples.into_iter()
+            .filter_map(|(metric, value)| self.observe(metric, value, start, now))
+            .collect();
+        self.window_start = Some(now);
+        self.save();
+        flags
+    }
+
+    /// Evaluate one sample of `metric` for the window [start, end)
+    fn observe(&mut self, metric: Metric, value: f64, start: u64, end: u64) -> Option<AnomalyFlag> {
+        let baseline = self.baselines.entry(metric).or_default();
+        let z = baseline.z(value);
+        let breaching = z.is_some_and(|z| match metric.bad_direction() {
+            Direction::Low => z < -self.config.sigma,
+            Direction::High => z > self.config.sigma,
+        });
+        let flag = |recovered: bool, sustained_secs: u64| AnomalyFlag {
+            metric,
+            severity: if recovered { AlertSeverity::Info } else { metric.severity() },
+            value,
+            mean: baseline.mean,
+            std: baseline.std(),
+            z: z.unwrap_or(0.0),
+            sustained_secs,
+            recovered,
+        };
+
+        if !breaching {
+            let recovered = self.breaches.remove(&metric)
+                .filter(|(_, flagged)| *flagged)
+                .map(|(since, _)| flag(true, end - since));
+            baseline.update(value);
+            return recovered;
+        }
+
+        let (since, flagged) = self.breaches.entry(metric).or_insert((start, false));
+        let sustained = end - *since;
+        if !*flagged && sustained >= self.config.min_duration_secs {
+            *flagged = true;
+            return Some(flag(false, sustained));
+        }
+        if *flagged && sustained >= REBASELINE_SECS {
+            info!("Anomaly: {} shifted for {}h — accepting as new baseline", metric, sustained / 3600);
+            self.breaches.remove(&metric);
+            *baseline = Baseline::default();
+            baseline.update(value);
+        }
+        None
+    }
+
+    fn save(&self) {
+        let path = match self.path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let file = BaselineFile {
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            baselines: self.baselines.clone(),
+        };
+        match serde_json::to_string_pretty(&file) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    warn!("Anomaly: failed to write {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => warn!("Anomaly: failed to serialize baselines: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paper_trading::determinism::SimRng;
+
+    const WINDOW: u64 = 900;
+
+    fn monitor() -> AnomalyMonitor {
+        AnomalyMonitor::new(AnomalyConfig { window_secs: WINDOW, sigma: 3.0, min_duration_secs: 1800 })
+    }
+
+    fn trade(reverted: bool, exec_ms: u64, net: f64) -> TradeResult {
+        TradeResult {
+            opportunity: "WETH/USDC".to_string(),
+            opportunity_id: 0,
+            tx_hash: Some("0xabc".to_string()),
+            block_number: None,
+            success: !reverted,
+            profit_usd: 0.0,
+            gas_cost_usd: 0.0,
+            gas_used_native: 0.0,
+            net_profit_usd: net,
+            execution_time_ms: exec_ms,
+            error: reverted.then(|| "Atomic tx reverted on-chain".to_string()),
+            amount_in: None,
+            amount_out: None,
+        }
+    }
+
+    /// Drive `windows` windows starting at window index `from`; `opps(i)` =
+    /// opportunities in window i. Returns all flags with their window index.
+    fn drive(
+        m: &mut AnomalyMonitor,
+        from: u64,
+        windows: u64,
+        opps: impl Fn(u64) -> usize,
+    ) -> Vec<(u64, AnomalyFlag)> {
+        let mut out = Vec::new();
+        if from == 0 {
+            m.tick(0);
+        }
+        for i in from..from + windows {
+            m.record_opportunities(opps(i));
+            out.extend(m.tick((i + 1) * WINDOW).into_iter().map(|f| (i, f)));
+        }
+        out
+    }
+
+    /// ~100 opportunities per window ± 10
+    fn noisy(rng: &SimRng) -> usize {
+        90 + rng.range_u64(0, 21) as usize
+    }
+
+    #[test]
+    fn test_ewma_baseline() {
+        let mut b = Baseline::default();
+        b.update(10.0);
+        assert_eq!((b.mean, b.var, b.samples), (10.0, 0.0, 1));
+        b.update(20.0);
+        assert!((b.mean - 10.5).abs() < 1e-12);
+        assert!((b.var - 0.95 * 10.0 * 0.5).abs() < 1e-12);
+        // Not warmed up yet
+        assert_eq!(b.z(100.0), None);
+        // Constant series: std floored at 5% of the mean
+        let mut c = Baseline::default();
+        for _ in 0..WARMUP_SAMPLES {
+            c.update(400.0);
+        }
+        assert_eq!(c.std(), 20.0);
+        assert_eq!(c.z(340.0), Some(-3.0));
+    }
+
+    #[test]
+    fn test_stable_series_no_false_alarms() {
+        let rng = SimRng::from_seed(7);
+        let mut m = monitor();
+        assert!(drive(&mut m, 0, 200, |_| noisy(&rng)).is_empty());
+        let b = m.baseline(Metric::OpportunitiesPerHour).unwrap();
+        assert!((b.mean - 400.0).abs() < 20.0, "{:?}", b);
+    }
+
+    #[test]
+    fn test_sustained_collapse_flags_once_then_recovers() {
+        let rng = SimRng::from_seed(11);
+        let mut m = monitor();
+        drive(&mut m, 0, 40, |_| noisy(&rng));
+
+        // Detection collapses at window 40: flagged after 1800s (2 windows), once
+        let flags = drive(&mut m, 40, 6, |_| 5);
+        assert_eq!(flags.len(), 1, "{:?}", flags);
+        let (window, flag) = &flags[0];
+        assert_eq!(*window, 41);
+        assert_eq!(flag.metric, Metric::OpportunitiesPerHour);
+        assert_eq!(flag.severity, AlertSeverity::Critical);
+        assert_eq!(flag.sustained_secs, 1800);
+        assert!(flag.z < -3.0 && !flag.recovered);
+
+        // Back to normal → one recovery flag
+        let flags = drive(&mut m, 46, 3, |_| 100);
+        assert_eq!(flags.len(), 1);
+        assert!(flags[0].1.recovered);
+        assert_eq!(flags[0].1.severity, AlertSeverity::Info);
+    }
+
+    #[test]
+    fn test_short_spike_and_good_direction_ignored() {
+        let rng = SimRng::from_seed(3);
+        let mut m = monitor();
+        drive(&mut m, 0, 40, |_| noisy(&rng));
+        // One bad window (< min duration) — not flagged
+        assert!(drive(&mut m, 40, 1, |_| 5).is_empty());
+        assert!(drive(&mut m, 41, 5, |_| noisy(&rng)).is_empty());
+        // Detection surging is not an anomaly
+        assert!(drive(&mut m, 46, 6, |_| 1000).is_empty());
+    }
+
+    #[test]
+    fn test_revert_spike_and_latency_creep() {
+        let rng = SimRng::from_seed(5);
+        let mut m = monitor();
+        m.tick(0);
+        let mut flags = Vec::new();
+        for i in 0..60u64 {
+            for k in 0..10u64 {
+                // Baseline: 1 revert in 10, ~200ms. From window 40: 6 in 10 reverted, 200ms.
+                // From window 50: 0 reverts, latency 800ms.
+                let reverted = if i >= 50 { false } else if i >= 40 { k < 6 } else { k == 0 };
+                let exec_ms = if i >= 50 { 800 } else { 180 + rng.range_u64(0, 41) };
+                m.record_trade(&trade(reverted, exec_ms, 1.0));
+            }
+            m.record_opportunities(100);
+            flags.extend(m.tick((i + 1) * WINDOW));
+        }
+        let fired: Vec<_> = flags.iter().filter(|f| !f.recovered).map(|f| (f.metric, f.severity)).collect();
+        assert_eq!(
+            fired,
+            vec![(Metric::RevertRate, AlertSeverity::Critical), (Metric::MedianExecMs, AlertSeverity::Warning)]
+        );
+        // Pre-trade rejections (no tx) are not trades
+        let mut rejected = trade(false, 5, 0.0);
+        rejected.tx_hash = None;
+        m.record_trade(&rejected);
+        assert_eq!(m.counts.trades, 0);
+    }
+
+    #[test]
+    fn test_baselines_persist_across_restart() {
+        let dir = std::env::temp_dir().join(format!("dexarb_anomaly_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let rng = SimRng::from_seed(9);
+        let mut m = monitor().with_persistence(dir.to_str().unwrap());
+        drive(&mut m, 0, 30, |_| noisy(&rng));
+        let saved = m.baseline(Metric::OpportunitiesPerHour).unwrap().clone();
+
+        // Restarted process: warm baseline restored, collapse flagged without a warmup period
+        let mut m = monitor().with_persistence(dir.to_str().unwrap());
+        assert_eq!(m.baseline(Metric::OpportunitiesPerHour), Some(&saved));
+        let flags = drive(&mut m, 0, 3, |_| 0);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].1.metric, Metric::OpportunitiesPerHour);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
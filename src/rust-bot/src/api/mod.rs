@@ -0,0 +1,428 @@
+//! Live State API — remote read-only view of the running bot
+//!
+//! The block loop owns an `ApiPublisher`. Once per block it builds an
+//! `ApiSnapshot` (pool prices exactly as the detector reads them, the last N
+//! detected opportunities, cooldowns, executor counters) and swaps it into
+//! `ApiShared` behind an `Arc` — request handlers clone that `Arc` and never
+//! touch bot state, so a slow client cannot hold up the hot path. The same
+//! block's prices are broadcast to WebSocket subscribers; a subscriber that
+//! falls behind skips blocks instead of queueing them.
+//!
+//! - `GET /pools`          V3 + V2 pool prices (V3: tick price, V2: decimal-adjusted reserves)
+//! - `GET /opportunities`  last API_RECENT_OPPORTUNITIES detections, newest first
+//! - `GET /status`         block, counters, cooled routes, executor stats
+//! - `GET /ws`             WebSocket: one `{"type":"block",…}` message per block
+//!
+//! The server (`server.rs`) is built with `--features api`; without it
+//! API_PORT is ignored with a warning. Binds API_BIND (default loopback).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+#[cfg(feature = "api")]
+pub mod server;
+
+use crate::arbitrage::cooldown::{CooledRoute, RouteCooldown};
+use crate::pool::PoolStateManager;
+use crate::telemetry::StatusCounters;
+use crate::types::{ArbitrageOpportunity, DexType, TradeResult};
+use ethers::types::Address;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+
+/// Per-block messages buffered per WebSocket subscriber before it skips ahead
+const BLOCK_CHANNEL_CAPACITY: usize = 16;
+
+/// One pool as the detector prices it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PoolView {
+    pub address: Address,
+    pub dex: DexType,
+    pub pair: String,
+    /// "v3" | "v2"
+    pub protocol: &'static str,
+    /// token1 per token0, decimal-adjusted (detector price)
+    pub price: f64,
+    /// Fee in hundredths of a bip (3000 for V2)
+    pub fee: u32,
+    /// V3 in-range liquidity (decimal string)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liquidity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick: Option<i32>,
+    /// V2 reserves (decimal strings)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserve0: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserve1: Option<String>,
+    /// Block the pool state was last synced at
+    pub block: u64,
+}
+
+/// One detected opportunity
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpportunityView {
+    pub block: u64,
+    pub timestamp: u64,
+    pub pair: String,
+    pub buy_dex: DexType,
+    pub sell_dex: DexType,
+    pub buy_pool: Option<Address>,
+    pub sell_pool: Option<Address>,
+    pub buy_price: f64,
+    pub sell_price: f64,
+    pub spread_percent: f64,
+    pub estimated_profit_usd: f64,
+    pub trade_size_usd: f64,
+    /// trade_size in quote token units (e.g. "500 USDC")
+    pub trade_size: String,
+}
+
+impl OpportunityView {
+    pub fn from_opportunity(opp: &ArbitrageOpportunity, block: u64) -> Self {
+        Self {
+            block,
+            timestamp: opp.timestamp,
+            pair: opp.pair.symbol.clone(),
+            buy_dex: opp.buy_dex,
+            sell_dex: opp.sell_dex,
+            buy_pool: opp.buy_pool_address,
+            sell_pool: opp.sell_pool_address,
+            buy_price: opp.buy_price,
+            sell_price: opp.sell_price,
+            spread_percent: opp.spread_percent,
+            estimated_profit_usd: opp.estimated_profit,
+            trade_size_usd: opp.trade_size_usd,
+            trade_size: opp.size_display(),
+        }
+    }
+}
+
+/// Executor results since startup (submitted transactions only count as trades)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExecutorStats {
+    pub attempts: u64,
+    pub trades: u64,
+    pub trades_succeeded: u64,
+    pub trades_failed: u64,
+    pub execution_errors: u64,
+    pub net_profit_usd: f64,
+    pub gas_cost_usd: f64,
+}
+
+/// `/status` body
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StatusView {
+    pub mode: String,
+    pub chain: String,
+    pub block: u64,
+    /// Unix seconds the snapshot was published
+    pub updated_at: u64,
+    pub uptime_secs: u64,
+    pub v3_pools: usize,
+    pub v2_pools: usize,
+    pub scans: u64,
+    pub opportunities: u64,
+    pub routes_cooled: usize,
+    pub cooled_routes: Vec<CooledRoute>,
+    pub executor: ExecutorStats,
+}
+
+/// Everything the API serves, rebuilt once per block
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ApiSnapshot {
+    pub status: StatusView,
+    pub pools: Vec<PoolView>,
+    pub opportunities: Vec<OpportunityView>,
+}
+
+/// WebSocket per-block message
+#[derive(Debug, Serialize)]
+struct BlockUpdate<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    block: u64,
+    timestamp: u64,
+    pools: &'a [PoolView],
+}
+
+/// Block message for a snapshot (also sent to a subscriber on connect)
+pub fn block_message(snapshot: &ApiSnapshot) -> String {
+    serde_json::to_string(&BlockUpdate {
+        kind: "block",
+        block: snapshot.status.block,
+        timestamp: snapshot.status.updated_at,
+        pools: &snapshot.pools,
+    })
+    .unwrap_or_default()
+}
+
+/// State shared between the block loop and the server tasks
+pub struct ApiShared {
+    snapshot: RwLock<Arc<ApiSnapshot>>,
+    blocks: broadcast::Sender<Arc<str>>,
+}
+
+impl ApiShared {
+    pub fn new() -> Self {
+        let (blocks, _) = broadcast::channel(BLOCK_CHANNEL_CAPACITY);
+        Self {
+            snapshot: RwLock::new(Arc::new(ApiSnapshot::default())),
+            blocks,
+        }
+    }
+
+    /// Latest published snapshot
+    pub fn snapshot(&self) -> Arc<ApiSnapshot> {
+        self.snapshot.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Per-block price messages from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<str>> {
+        self.blocks.subscribe()
+    }
+
+    fn publish(&self, snapshot: ApiSnapshot) {
+        let snapshot = Arc::new(snapshot);
+        if self.blocks.receiver_count() > 0 {
+            // Err only when every subscriber dropped in between
+            let _ = self.blocks.send(Arc::from(block_message(&snapshot)));
+        }
+        *self.snapshot.write().unwrap_or_else(|e| e.into_inner()) = snapshot;
+    }
+}
+
+impl Default for ApiShared {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Block-loop side: accumulates detections + executor results, publishes per block
+pub struct ApiPublisher {
+    shared: Arc<ApiShared>,
+    mode: String,
+    chain: String,
+    started_at: u64,
+    max_recent: usize,
+    recent: VecDeque<OpportunityView>,
+    executor: ExecutorStats,
+}
+
+impl ApiPublisher {
+    pub fn new(mode: &str, chain: &str, max_recent: usize, now: u64) -> Self {
+        Self {
+            shared: Arc::new(ApiShared::new()),
+            mode: mode.to_string(),
+            chain: chain.to_string(),
+            started_at: now,
+            max_recent,
+            recent: VecDeque::with_capacity(max_recent),
+            executor: ExecutorStats::default(),
+        }
+    }
+
+    /// Handle for the server tasks
+    pub fn shared(&self) -> Arc<ApiShared> {
+        self.shared.clone()
+    }
+
+    /// This block's detections (before cooldown / triage filtering)
+    pub fn record_opportunities(&mut self, block: u64, opportunities: &[ArbitrageOpportunity]) {
+        for opp in opportunities {
+            if self.recent.len() == self.max_recent {
+                self.recent.pop_back();
+            }
+            self.recent.push_front(OpportunityView::from_opportunity(opp, block));
+        }
+    }
+
+    /// One execution result
+    pub fn record_trade(&mut self, result: &TradeResult) {
+        self.executor.attempts += 1;
+        if result.tx_hash.is_none() {
+            return;
+        }
+        self.executor.trades += 1;
+        if result.success {
+            self.executor.trades_succeeded += 1;
+        } else {
+            self.executor.trades_failed += 1;
+        }
+        self.executor.net_profit_usd += result.net_profit_usd;
+        self.executor.gas_cost_usd += result.gas_cost_usd;
+    }
+
+    /// The executor returned Err
+    pub fn record_execution_error(&mut self) {
+        self.executor.attempts += 1;
+        self.executor.execution_errors += 1;
+    }
+
+    /// Build this block's snapshot, swap it in and push prices to subscribers
+    pub fn publish_block(
+        &mut self,
+        state_manager: &PoolStateManager,
+        cooldown: &RouteCooldown,
+        status: &StatusCounters,
+        now: u64,
+    ) {
+        let pools = pool_views(state_manager);
+        let cooled_routes = cooldown.active_routes(status.block_height);
+        let snapshot = ApiSnapshot {
+            status: StatusView {
+                mode: self.mode.clone(),
+                chain: self.chain.clone(),
+                block: status.block_height,
+                updated_at: now,
+                uptime_secs: now.saturating_sub(self.started_at),
+                v3_pools: pools.iter().filter(|p| p.protocol == "v3").count(),
+                v2_pools: pools.iter().filter(|p| p.protocol == "v2").count(),
+                scans: status.scans,
+                opportunities: status.opportunities,
+                routes_cooled: cooled_routes.len(),
+                cooled_routes,
+                executor: self.executor.clone(),
+            },
+            pools,
+            opportunities: self.recent.iter().cloned().collect(),
+        };
+        self.shared.publish(snapshot);
+    }
+}
+
+/// Every pool in the state manager, priced the way the detector prices it
+pub fn pool_views(state_manager: &PoolStateManager) -> Vec<PoolView> {
+    let mut pools: Vec<PoolView> = state_manager
+        .get_all_v3_pools()
+        .into_iter()
+        .map(|p| PoolView {
+            address: p.address,
+            dex: p.dex,
+            pair: p.pair.symbol.clone(),
+            protocol: "v3",
+            price: p.price(),
+            fee: p.fee,
+            liquidity: Some(p.liquidity.to_string()),
+            tick: Some(p.tick),
+            reserve0: None,
+            reserve1: None,
+            block: p.last_updated,
+        })
+        .chain(state_manager.get_all_pools().into_iter().map(|p| PoolView {
+            address: p.address,
+            dex: p.dex,
+            pair: p.pair.symbol.clone(),
+            protocol: "v2",
+            price: p.price_adjusted(),
+            fee: 3000,
+            liquidity: None,
+            tick: None,
+            reserve0: Some(p.reserve0.to_string()),
+            reserve1: Some(p.reserve1.to_string()),
+            block: p.last_updated,
+        }))
+        .collect();
+    pools.sort_by(|a, b| a.pair.cmp(&b.pair).then_with(|| a.protocol.cmp(b.protocol)).then_with(|| a.fee.cmp(&b.fee)));
+    pools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{PoolState, TradingPair, V3PoolState};
+    use ethers::types::U256;
+
+    fn pair() -> TradingPair {
+        TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string())
+    }
+
+    fn state_manager() -> PoolStateManager {
+        let sm = PoolStateManager::new();
+        sm.update_v3_pool(V3PoolState {
+            address: Address::from_low_u64_be(10),
+            dex: DexType::UniswapV3_005,
+            pair: pair(),
+            sqrt_price_x96: U256::zero(),
+            tick: -197_000,
+            fee: 500,
+            liquidity: 10u128.pow(18),
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 100,
+        });
+        sm.update_pool(PoolState {
+            address: Address::from_low_u64_be(11),
+            dex: DexType::QuickSwapV2,
+            pair: pair(),
+            reserve0: U256::from(1_000_000_000_000u64),
+            reserve1: U256::from(400u64) * U256::exp10(18),
+            last_updated: 99,
+            token0_decimals: 6,
+            token1_decimals: 18,
+        });
+        sm
+    }
+
+    fn opportunity(spread: f64) -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::new(
+            pair(),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.0004,
+            0.000_401,
+            U256::from(500_000_000u64),
+        );
+        opp.set_quote_layout(6, 18, true);
+        opp.spread_percent = spread;
+        opp
+    }
+
+    #[test]
+    fn test_pool_views_use_detector_prices() {
+        let sm = state_manager();
+        let pools = pool_views(&sm);
+        assert_eq!(pools.len(), 2);
+
+        let v2 = pools.iter().find(|p| p.protocol == "v2").unwrap();
+        let v3 = pools.iter().find(|p| p.protocol == "v3").unwrap();
+        assert_eq!(v3.price, sm.get_all_v3_pools()[0].price());
+        assert_eq!(v2.price, sm.get_all_pools()[0].price_adjusted());
+        assert!((v2.price - 0.0004).abs() < 1e-12, "400 WETH per 1M USDC");
+        assert_eq!(v3.liquidity.as_deref(), Some("1000000000000000000"));
+        assert_eq!(v2.reserve0.as_deref(), Some("1000000000000"));
+    }
+
+    #[test]
+    fn test_publish_keeps_last_n_opportunities_and_pushes_block() {
+        let sm = state_manager();
+        let mut cooldown = RouteCooldown::new(10);
+        cooldown.record_failure("WETH/USDC", DexType::UniswapV3_005, DexType::UniswapV3_030, 500.0, 118);
+        let mut api = ApiPublisher::new("dry_run", "polygon", 3, 1_000);
+        let shared = api.shared();
+        let mut rx = shared.subscribe();
+
+        api.record_opportunities(119, &[opportunity(0.1), opportunity(0.2)]);
+        api.record_opportunities(120, &[opportunity(0.3), opportunity(0.4)]);
+        api.record_execution_error();
+        let status = StatusCounters { block_height: 120, scans: 20, opportunities: 4, routes_cooled: 1 };
+        api.publish_block(&sm, &cooldown, &status, 1_060);
+
+        let snapshot = shared.snapshot();
+        let spreads: Vec<f64> = snapshot.opportunities.iter().map(|o| o.spread_percent).collect();
+        assert_eq!(spreads, vec![0.4, 0.3, 0.2], "newest first, capped at 3");
+        assert_eq!(snapshot.opportunities[0].block, 120);
+        assert_eq!(snapshot.status.block, 120);
+        assert_eq!(snapshot.status.uptime_secs, 60);
+        assert_eq!((snapshot.status.v3_pools, snapshot.status.v2_pools), (1, 1));
+        assert_eq!(snapshot.status.cooled_routes[0].remaining_blocks, 8);
+        assert_eq!(snapshot.status.executor.execution_errors, 1);
+
+        let msg: serde_json::Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(msg["type"], "block");
+        assert_eq!(msg["block"], 120);
+        assert_eq!(msg["pools"].as_array().unwrap().len(), 2);
+    }
+}
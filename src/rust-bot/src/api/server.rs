@@ -0,0 +1,186 @@
+//! Live State API server (`--features api`)
+//!
+//! Minimal HTTP/1.1 over tokio (one request per connection, like the
+//! telemetry collector) serving the latest `ApiSnapshot`. `GET /ws` with an
+//! `Upgrade: websocket` header completes the handshake here and hands the
+//! socket to tokio-tungstenite: the current block's prices are sent at once,
+//! then one message per published block.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use super::{block_message, ApiShared};
+use anyhow::{anyhow, Result};
+use futures::{SinkExt, StreamExt};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tracing::{debug, info};
+
+/// Accept connections forever; each is handled on its own task
+pub async fn serve(listener: TcpListener, shared: Arc<ApiShared>) -> Result<()> {
+    info!("Live state API listening on {}", listener.local_addr()?);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let shared = shared.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &shared).await {
+                debug!("Live state API: {} — {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, shared: &ApiShared) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("").split('?').next().unwrap_or("").to_string();
+
+    let mut websocket_key = None;
+    let mut upgrade = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("sec-websocket-key") {
+                websocket_key = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("upgrade") {
+                upgrade = value.trim().eq_ignore_ascii_case("websocket");
+            }
+        }
+    }
+
+    if method == "GET" && path == "/ws" && upgrade {
+        let key = websocket_key.ok_or_else(|| anyhow!("WebSocket upgrade without Sec-WebSocket-Key"))?;
+        return stream_blocks(reader.into_inner(), &key, shared).await;
+    }
+
+    let (status, body) = match (method.as_str(), path.as_str()) {
+        ("GET", "/pools") => ("200 OK", serde_json::to_string_pretty(&shared.snapshot().pools)?),
+        ("GET", "/opportunities") => ("200 OK", serde_json::to_string_pretty(&shared.snapshot().opportunities)?),
+        ("GET", "/status") => ("200 OK", serde_json::to_string_pretty(&shared.snapshot().status)?),
+        ("GET", _) => ("404 Not Found", String::new()),
+        _ => ("405 Method Not Allowed", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let mut stream = reader.into_inner();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Finish the upgrade, then push every published block until the client leaves
+async fn stream_blocks(mut stream: TcpStream, key: &str, shared: &ApiShared) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    );
+    stream.write_all(response.as_bytes()).await?;
+
+    // Subscribe before sending the current block so none is missed in between
+    let mut blocks = shared.subscribe();
+    let (mut sink, mut incoming) = WebSocketStream::from_raw_socket(stream, Role::Server, None).await.split();
+    sink.send(Message::Text(block_message(&shared.snapshot()))).await?;
+
+    loop {
+        tokio::select! {
+            block = blocks.recv() => match block {
+                Ok(msg) => sink.send(Message::Text(msg.to_string())).await?,
+                // Slow subscriber: skip to the newest blocks
+                Err(RecvError::Lagged(skipped)) => debug!("Live state API: subscriber skipped {} block(s)", skipped),
+                Err(RecvError::Closed) => break,
+            },
+            msg = incoming.next() => match msg {
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(Message::Ping(payload))) => sink.send(Message::Pong(payload)).await?,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+    let _ = sink.close().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiPublisher;
+    use crate::arbitrage::cooldown::RouteCooldown;
+    use crate::pool::PoolStateManager;
+    use crate::telemetry::StatusCounters;
+    use crate::types::{DexType, PoolState, TradingPair};
+    use ethers::types::{Address, U256};
+
+    fn v2_pool(reserve0: u64) -> PoolState {
+        PoolState {
+            address: Address::from_low_u64_be(11),
+            dex: DexType::QuickSwapV2,
+            pair: TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            reserve0: U256::from(reserve0),
+            reserve1: U256::from(400u64) * U256::exp10(18),
+            last_updated: 1,
+            token0_decimals: 6,
+            token1_decimals: 18,
+        }
+    }
+
+    /// JSON parsing without float_roundtrip may be off in the last ulp
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-12 * b.abs()
+    }
+
+    #[tokio::test]
+    async fn test_http_endpoints_and_websocket_block_push() {
+        let sm = PoolStateManager::new();
+        sm.update_pool(v2_pool(1_000_000_000_000));
+        let cooldown = RouteCooldown::new(10);
+        let mut api = ApiPublisher::new("dry_run", "polygon", 10, 0);
+        let status = |block| StatusCounters { block_height: block, ..Default::default() };
+        api.publish_block(&sm, &cooldown, &status(100), 1);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, api.shared()));
+
+        let pools: serde_json::Value = reqwest::get(format!("http://{}/pools", addr)).await.unwrap().json().await.unwrap();
+        assert!(close(pools[0]["price"].as_f64().unwrap(), sm.get_all_pools()[0].price_adjusted()));
+        let status_body: serde_json::Value = reqwest::get(format!("http://{}/status", addr)).await.unwrap().json().await.unwrap();
+        assert_eq!(status_body["block"], 100);
+        assert_eq!(reqwest::get(format!("http://{}/nope", addr)).await.unwrap().status(), 404);
+
+        let tcp = TcpStream::connect(addr).await.unwrap();
+        let (mut ws, _) = tokio_tungstenite::client_async(format!("ws://{}/ws", addr), tcp).await.unwrap();
+        let first: serde_json::Value = match ws.next().await.unwrap().unwrap() {
+            Message::Text(t) => serde_json::from_str(&t).unwrap(),
+            other => panic!("expected text, got {:?}", other),
+        };
+        assert_eq!(first["block"], 100);
+
+        // Reserves move: the next block carries the new price
+        sm.update_pool(v2_pool(2_000_000_000_000));
+        api.publish_block(&sm, &cooldown, &status(101), 2);
+        let next: serde_json::Value = match ws.next().await.unwrap().unwrap() {
+            Message::Text(t) => serde_json::from_str(&t).unwrap(),
+            other => panic!("expected text, got {:?}", other),
+        };
+        assert_eq!(next["block"], 101);
+        assert!(close(next["pools"][0]["price"].as_f64().unwrap(), sm.get_all_pools()[0].price_adjusted()));
+    }
+}
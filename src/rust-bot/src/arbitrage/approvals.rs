@@ -0,0 +1,232 @@
+//! Token Approval Pre-flight — allowances granted before trading starts
+//!
+//! The legacy two-tx path approves a router lazily (ensure_approval) the first
+//! time a token is spent through it: one extra transaction in the middle of a
+//! trade, at the worst possible moment. At boot in live mode the bot instead
+//! checks every (token, spender) pair it can use and sends the missing max
+//! approvals in one batch before entering the block loop. `--approvals`
+//! runs the same check (status), grant, or a full revoke outside a session.
+//!
+//! Spenders:
+//!   - the router of every DEX a whitelisted pool trades on, for both tokens
+//!     of the pool's pair (buy leg spends quote, sell leg spends base)
+//!   - the ArbExecutor contract for the quote tokens, when atomic mode is configured
+//!   - Permit2 for the quote tokens, when PERMIT2_ADDRESS is set (wallet-funded
+//!     atomic trades pull through a signed permit instead of the allowance above)
+//!
+//! Tokens like USDT revert on approve(x) while the allowance is non-zero, so a
+//! non-zero allowance below max is reset to 0 first (nonces keep the order).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - ApprovalSummary::merge (one summary over every signing wallet)
+//! Modified: 2026-10-16 - Permit2 spender for the quote tokens (PERMIT2_ADDRESS)
+
+use crate::types::{BotConfig, DexType, TradingPair};
+use ethers::types::{Address, U256};
+use std::fmt;
+use std::str::FromStr;
+
+/// What `--approvals` does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalAction {
+    /// Read and log allowances only
+    Status,
+    /// Max-approve every missing (token, spender)
+    Grant,
+    /// Set every non-zero allowance to 0
+    RevokeAll,
+}
+
+impl FromStr for ApprovalAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "status" => Ok(ApprovalAction::Status),
+            "grant" => Ok(ApprovalAction::Grant),
+            "revoke-all" => Ok(ApprovalAction::RevokeAll),
+            other => anyhow::bail!("Unknown approvals action '{}' (status, grant, revoke-all)", other),
+        }
+    }
+}
+
+/// One allowance the bot relies on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalTarget {
+    pub token: Address,
+    /// Pair symbol the token was found in, for logs
+    pub token_label: String,
+    pub spender: Address,
+    /// "UniswapV3_0.05% router", "ArbExecutor", ...
+    pub spender_label: String,
+}
+
+impl fmt::Display for ApprovalTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} ({}) → {} {:?}", self.token, self.token_label, self.spender_label, self.spender)
+    }
+}
+
+/// Every (token, spender) the bot can use, deduplicated. `pools` = the synced
+/// whitelist pools; `router` = the router address a DEX's swaps go through.
+pub fn approval_targets(
+    config: &BotConfig,
+    pools: &[(TradingPair, DexType)],
+    router: impl Fn(DexType) -> Address,
+) -> Vec<ApprovalTarget> {
+    let mut targets: Vec<ApprovalTarget> = Vec::new();
+    let mut push = |target: ApprovalTarget| {
+        if !targets.iter().any(|t| t.token == target.token && t.spender == target.spender) {
+            targets.push(target);
+        }
+    };
+
+    // Curve and Kyber Elastic pools are detection-only: nothing is ever sent through them
+    for (pair, dex) in pools.iter().filter(|(_, dex)| !dex.is_detection_only()) {
+        let spender = router(*dex);
+        for token in [pair.token0, pair.token1] {
+            push(ApprovalTarget {
+                token,
+                token_label: pair.symbol.clone(),
+                spender,
+                spender_label: format!("{} router", dex),
+            });
+        }
+    }
+
+    if let Some(arb_executor) = config.arb_executor_address {
+        let quote_tokens = std::iter::once(config.quote_token_address).chain(config.quote_token_address_native);
+        for token in quote_tokens {
+            push(ApprovalTarget {
+                token,
+                token_label: "quote".to_string(),
+                spender: arb_executor,
+                spender_label: "ArbExecutor".to_string(),
+            });
+        }
+        // One max approval to Permit2 per quote token; each trade then signs its own transfer
+        if let Some(permit2) = config.permit2_address {
+            let quote_tokens = std::iter::once(config.quote_token_address).chain(config.quote_token_address_native);
+            for token in quote_tokens {
+                push(ApprovalTarget {
+                    token,
+                    token_label: "quote".to_string(),
+                    spender: permit2,
+                    spender_label: "Permit2".to_string(),
+                });
+            }
+        }
+    }
+    targets
+}
+
+/// Allowances at or above this count as a max approval (some tokens decrement
+/// even an infinite allowance)
+pub fn is_max_approval(allowance: U256) -> bool {
+    allowance >= U256::MAX >> 1
+}
+
+/// approve() amounts to send, in order, to take `allowance` where `action` wants it
+pub fn approval_plan(action: ApprovalAction, allowance: U256) -> Vec<U256> {
+    match action {
+        ApprovalAction::Status => Vec::new(),
+        ApprovalAction::Grant if is_max_approval(allowance) => Vec::new(),
+        ApprovalAction::Grant if allowance.is_zero() => vec![U256::MAX],
+        // USDT-style tokens: approve(0) before the new value
+        ApprovalAction::Grant => vec![U256::zero(), U256::MAX],
+        ApprovalAction::RevokeAll if allowance.is_zero() => Vec::new(),
+        ApprovalAction::RevokeAll => vec![U256::zero()],
+    }
+}
+
+/// Outcome of one pre-flight / `--approvals` run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApprovalSummary {
+    pub checked: usize,
+    /// Already where the action wants them
+    pub unchanged: usize,
+    pub granted: usize,
+    pub revoked: usize,
+    /// Allowance read, send or receipt failed
+    pub failed: usize,
+    /// Targets below max (status) / still missing (failed grant)
+    pub missing: usize,
+}
+
+impl ApprovalSummary {
+    /// Add another wallet's counts
+    pub fn merge(&mut self, other: &ApprovalSummary) {
+        self.checked += other.checked;
+        self.unchanged += other.unchanged;
+        self.granted += other.granted;
+        self.revoked += other.revoked;
+        self.failed += other.failed;
+        self.missing += other.missing;
+    }
+}
+
+impl fmt::Display for ApprovalSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} checked | {} unchanged | {} granted | {} revoked | {} missing | {} failed",
+            self.checked, self.unchanged, self.granted, self.revoked, self.missing, self.failed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+
+    #[test]
+    fn test_targets_cover_pair_tokens_per_router_and_the_executor() {
+        let mut config = create_test_config();
+        let (usdc, weth, wmatic) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2), Address::from_low_u64_be(3));
+        config.quote_token_address = usdc;
+        config.quote_token_address_native = None;
+        let weth_usdc = TradingPair::new(usdc, weth, "WETH/USDC".to_string());
+        let wmatic_usdc = TradingPair::new(wmatic, usdc, "WMATIC/USDC".to_string());
+        let pools = vec![
+            (weth_usdc.clone(), DexType::UniswapV3_005),
+            (weth_usdc.clone(), DexType::UniswapV3_030), // same router
+            (weth_usdc, DexType::SushiV3_005),
+            (wmatic_usdc, DexType::UniswapV3_005),
+        ];
+        let router = |dex: DexType| Address::from_low_u64_be(if dex.to_string().starts_with("Sushi") { 0x51 } else { 0x11 });
+
+        let targets = approval_targets(&config, &pools, router);
+        // usdc, weth → uni + sushi; wmatic → uni
+        assert_eq!(targets.len(), 5);
+        assert!(targets.iter().all(|t| t.spender != Address::from_low_u64_be(0xab)));
+
+        config.arb_executor_address = Some(Address::from_low_u64_be(0xab));
+        let targets = approval_targets(&config, &pools, router);
+        assert_eq!(targets.len(), 6);
+        let executor: Vec<_> = targets.iter().filter(|t| t.spender_label == "ArbExecutor").collect();
+        assert_eq!(executor.len(), 1);
+        assert_eq!(executor[0].token, usdc);
+
+        config.permit2_address = Some(Address::from_low_u64_be(0x22));
+        let targets = approval_targets(&config, &pools, router);
+        assert_eq!(targets.len(), 7);
+        let permit2: Vec<_> = targets.iter().filter(|t| t.spender_label == "Permit2").collect();
+        assert_eq!((permit2.len(), permit2[0].token), (1, usdc));
+    }
+
+    #[test]
+    fn test_plan_resets_partial_allowances_before_granting() {
+        use ApprovalAction::*;
+        assert_eq!(approval_plan(Grant, U256::zero()), vec![U256::MAX]);
+        assert_eq!(approval_plan(Grant, U256::from(1_000u64)), vec![U256::zero(), U256::MAX]);
+        assert!(approval_plan(Grant, U256::MAX).is_empty());
+        assert!(approval_plan(Grant, U256::MAX - U256::from(5_000_000u64)).is_empty(), "spent-down max approval");
+        assert_eq!(approval_plan(RevokeAll, U256::MAX), vec![U256::zero()]);
+        assert!(approval_plan(RevokeAll, U256::zero()).is_empty());
+        assert!(approval_plan(Status, U256::zero()).is_empty());
+        assert_eq!("revoke-all".parse::<ApprovalAction>().unwrap(), RevokeAll);
+        assert!("revoke".parse::<ApprovalAction>().is_err());
+    }
+}
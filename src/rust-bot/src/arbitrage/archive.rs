@@ -0,0 +1,568 @@
+//! Opportunity Archive — every detected opportunity with an outcome label
+//!
+//! Execution logs only cover the opportunities we traded. With
+//! OPPORTUNITY_ARCHIVE_ENABLED the bot also appends every opportunity that
+//! passes the route cooldown to data/{chain}/research/opportunities_YYYYMMDD.jsonl,
+//! one JSON object per line, tagged by `record`:
+//!
+//!   detected   route, prices, spread, estimate, trade size, pool liquidity
+//!   verified   multicall pre-screen verdict (absent when not pre-screened)
+//!   outcome    label: landed / reverted / rejected (executor result, with
+//!              TradeOutcome, tx hash, net profit), dry_run (simulated, never
+//!              sent) or expired (not executed within
+//!              OPPORTUNITY_ARCHIVE_EXPIRY_BLOCKS)
+//!
+//! All three carry `id` = ArbitrageOpportunity::id, which the executor copies
+//! to TradeResult::opportunity_id; `load_labeled` joins a day file back into
+//! one row per opportunity for analysis.
+//!
+//! Lines are serialized on the caller's thread and written by the
+//! opportunity_archive writer thread (io_writer.rs, lossy): a stalled disk
+//! drops the oldest queued lines, it never holds up the block loop. The
+//! writer deletes day files older than OPPORTUNITY_ARCHIVE_RETAIN_DAYS when
+//! it opens a new day (0 = keep all).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::arbitrage::executor::DRY_RUN_TX_HASH;
+use crate::arbitrage::multicall_quoter::VerifiedOpportunity;
+use crate::arbitrage::outcome::TradeOutcome;
+use crate::io_writer::{IsolatedWriter, StreamContract};
+use crate::types::{ArbitrageOpportunity, DexType, TradeResult};
+use anyhow::{Context, Result};
+use chrono::{Duration as ChronoDuration, NaiveDate, SecondsFormat, Utc};
+use ethers::types::{Address, TxHash};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// What became of an archived opportunity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeLabel {
+    /// Tx landed and the trade succeeded
+    Landed,
+    /// Tx sent but the trade failed (revert, gas-negative, receipt timeout)
+    Reverted,
+    /// Attempted, nothing sent (pre-trade rejection or executor error)
+    Rejected,
+    /// Dry run: the executor simulated it, nothing was sent
+    DryRun,
+    /// Not executed within the expiry window
+    Expired,
+}
+
+/// Opportunity as the detector (or mempool dispatch) produced it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedRecord {
+    pub id: u64,
+    pub timestamp: String,
+    pub chain: String,
+    pub block: u64,
+    pub pair: String,
+    pub buy_dex: DexType,
+    pub sell_dex: DexType,
+    pub buy_pool: Option<Address>,
+    pub sell_pool: Option<Address>,
+    pub buy_price: f64,
+    pub sell_price: f64,
+    pub spread_percent: f64,
+    pub estimated_profit_usd: f64,
+    pub trade_size_usd: f64,
+    pub buy_liquidity: Option<u128>,
+    pub sell_liquidity: Option<u128>,
+    /// Mid-block re-scan trigger tx (None = synced state)
+    pub anticipatory: Option<TxHash>,
+}
+
+/// Multicall pre-screen verdict
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerifiedRecord {
+    pub id: u64,
+    pub block: u64,
+    pub both_legs_valid: bool,
+    pub quoted_profit_raw: i128,
+    pub error: Option<String>,
+}
+
+/// Outcome label
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutcomeRecord {
+    pub id: u64,
+    pub block: u64,
+    pub label: OutcomeLabel,
+    /// TradeOutcome name (None = expired)
+    pub outcome: Option<String>,
+    pub tx_hash: Option<String>,
+    pub net_profit_usd: Option<f64>,
+    pub gas_cost_usd: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// One archive line. Read back with `parse_record`: serde's internally tagged
+/// enums buffer the line and cannot hold the u128/i128 fields.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum ArchiveRecord {
+    Detected(DetectedRecord),
+    Verified(VerifiedRecord),
+    Outcome(OutcomeRecord),
+}
+
+/// The `record` tag of a line
+#[derive(Deserialize)]
+struct RecordTag {
+    record: String,
+}
+
+/// Parse one archive line (None = unknown tag or malformed)
+pub fn parse_record(line: &str) -> Option<ArchiveRecord> {
+    let tag: RecordTag = serde_json::from_str(line).ok()?;
+    match tag.record.as_str() {
+        "detected" => serde_json::from_str(line).ok().map(ArchiveRecord::Detected),
+        "verified" => serde_json::from_str(line).ok().map(ArchiveRecord::Verified),
+        "outcome" => serde_json::from_str(line).ok().map(ArchiveRecord::Outcome),
+        _ => None,
+    }
+}
+
+/// One opportunity with its verdict and label joined by id
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledOpportunity {
+    pub detected: DetectedRecord,
+    pub verified: Option<VerifiedRecord>,
+    /// None = still pending when the file was read
+    pub outcome: Option<OutcomeRecord>,
+}
+
+/// A serialized record, routed to its day's file by the writer thread
+pub struct ArchiveLine {
+    date: NaiveDate,
+    json: String,
+}
+
+/// Day file name
+pub fn file_name(date: NaiveDate) -> String {
+    format!("opportunities_{}.jsonl", date.format("%Y%m%d"))
+}
+
+/// Date of a day file name
+fn file_date(name: &str) -> Option<NaiveDate> {
+    let day = name.strip_prefix("opportunities_")?.strip_suffix(".jsonl")?;
+    NaiveDate::parse_from_str(day, "%Y%m%d").ok()
+}
+
+/// Delete day files more than `retain_days` before `today` (0 = keep all)
+pub fn prune(dir: &Path, today: NaiveDate, retain_days: u32) -> Result<usize> {
+    if retain_days == 0 {
+        return Ok(0);
+    }
+    let cutoff = today - ChronoDuration::days(retain_days as i64);
+    let mut removed = 0;
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        let expired = path.file_name().and_then(|n| n.to_str()).and_then(file_date).is_some_and(|d| d < cutoff);
+        if expired {
+            fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Join archive lines into one row per detected opportunity, in detection
+/// order. Verdicts and labels without a detection line are ignored.
+pub fn join_labels(records: impl IntoIterator<Item = ArchiveRecord>) -> Vec<LabeledOpportunity> {
+    let mut rows: Vec<LabeledOpportunity> = Vec::new();
+    let mut index: HashMap<u64, usize> = HashMap::new();
+    for record in records {
+        match record {
+            ArchiveRecord::Detected(detected) => {
+                index.insert(detected.id, rows.len());
+                rows.push(LabeledOpportunity { detected, verified: None, outcome: None });
+            }
+            ArchiveRecord::Verified(v) => {
+                if let Some(&i) = index.get(&v.id) {
+                    rows[i].verified = Some(v);
+                }
+            }
+            ArchiveRecord::Outcome(o) => {
+                if let Some(&i) = index.get(&o.id) {
+                    rows[i].outcome = Some(o);
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// Read and join one day file (unparseable lines skipped)
+pub fn load_labeled(path: &Path) -> Result<Vec<LabeledOpportunity>> {
+    let content = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    Ok(join_labels(content.lines().filter_map(parse_record)))
+}
+
+/// Archive handle: queues lines for the writer thread and tracks which
+/// archived opportunities still await a label
+pub struct OpportunityArchive {
+    chain: String,
+    writer: IsolatedWriter<ArchiveLine>,
+    /// Unlabeled opportunity id → block archived
+    pending: HashMap<u64, u64>,
+    expiry_blocks: u64,
+}
+
+impl OpportunityArchive {
+    /// Archive under `dir` (opportunities_YYYYMMDD.jsonl); expired day files are
+    /// pruned by the writer at its first line and at each new day
+    pub fn new(dir: &str, chain: &str, expiry_blocks: u64, retain_days: u32, contract: StreamContract) -> Self {
+        if let Err(e) = fs::create_dir_all(dir) {
+            warn!("Opportunity archive: failed to create {}: {}", dir, e);
+        }
+        let dir = PathBuf::from(dir);
+        let mut open_day: Option<NaiveDate> = None;
+        Self::spawn(chain, expiry_blocks, contract, move |line: &mut ArchiveLine| {
+            if open_day != Some(line.date) {
+                open_day = Some(line.date);
+                match prune(&dir, line.date, retain_days) {
+                    Ok(0) => {}
+                    Ok(n) => info!("Opportunity archive: {} day file(s) past {} days deleted", n, retain_days),
+                    Err(e) => warn!("Opportunity archive: retention failed: {}", e),
+                }
+            }
+            let path = dir.join(file_name(line.date));
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("open {}", path.display()))?;
+            writeln!(file, "{}", line.json)?;
+            Ok(())
+        })
+    }
+
+    fn spawn<W>(chain: &str, expiry_blocks: u64, contract: StreamContract, write: W) -> Self
+    where
+        W: FnMut(&mut ArchiveLine) -> Result<()> + Send + 'static,
+    {
+        Self {
+            chain: chain.to_string(),
+            writer: IsolatedWriter::spawn(contract, write),
+            pending: HashMap::new(),
+            expiry_blocks: expiry_blocks.max(1),
+        }
+    }
+
+    /// Queue one record; never waits (a full queue drops its oldest line)
+    fn emit(&self, record: ArchiveRecord) {
+        match serde_json::to_string(&record) {
+            Ok(json) => {
+                if let Err(e) = self.writer.send(ArchiveLine { date: Utc::now().date_naive(), json }) {
+                    warn!("Opportunity archive: {} — record dropped", e);
+                }
+            }
+            Err(e) => warn!("Opportunity archive: failed to serialize record: {}", e),
+        }
+    }
+
+    /// Archive a detected opportunity; it awaits a label from here on
+    pub fn record_detected(&mut self, block: u64, opp: &ArbitrageOpportunity) {
+        self.pending.insert(opp.id, block);
+        self.emit(ArchiveRecord::Detected(DetectedRecord {
+            id: opp.id,
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            chain: self.chain.clone(),
+            block,
+            pair: opp.pair.symbol.clone(),
+            buy_dex: opp.buy_dex,
+            sell_dex: opp.sell_dex,
+            buy_pool: opp.buy_pool_address,
+            sell_pool: opp.sell_pool_address,
+            buy_price: opp.buy_price,
+            sell_price: opp.sell_price,
+            spread_percent: opp.spread_percent,
+            estimated_profit_usd: opp.estimated_profit,
+            trade_size_usd: opp.trade_size_usd,
+            buy_liquidity: opp.buy_pool_liquidity,
+            sell_liquidity: opp.sell_pool_liquidity,
+            anticipatory: opp.anticipatory,
+        }));
+    }
+
+    /// Archive the pre-screen verdict of an archived opportunity
+    pub fn record_verified(&self, block: u64, opp: &ArbitrageOpportunity, verified: &VerifiedOpportunity) {
+        if !self.pending.contains_key(&opp.id) {
+            return;
+        }
+        self.emit(ArchiveRecord::Verified(VerifiedRecord {
+            id: opp.id,
+            block,
+            both_legs_valid: verified.both_legs_valid,
+            quoted_profit_raw: verified.quoted_profit_raw,
+            error: verified.error.clone(),
+        }));
+    }
+
+    /// Label an executed opportunity from the executor's result. The id comes
+    /// from the TradeResult (the opportunity's when the executor returned Err).
+    pub fn record_execution(&mut self, block: u64, opp: &ArbitrageOpportunity, exec: &Result<TradeResult>, outcome: TradeOutcome) {
+        let id = exec.as_ref().map_or(opp.id, |r| r.opportunity_id);
+        if self.pending.remove(&id).is_none() {
+            return;
+        }
+        let record = match exec {
+            Ok(r) => OutcomeRecord {
+                id,
+                block,
+                label: match (r.success, r.tx_hash.is_some()) {
+                    _ if r.tx_hash.as_deref() == Some(DRY_RUN_TX_HASH) => OutcomeLabel::DryRun,
+                    (true, _) => OutcomeLabel::Landed,
+                    (false, true) => OutcomeLabel::Reverted,
+                    (false, false) => OutcomeLabel::Rejected,
+                },
+                outcome: Some(format!("{:?}", outcome)),
+                tx_hash: r.tx_hash.clone(),
+                net_profit_usd: Some(r.net_profit_usd),
+                gas_cost_usd: Some(r.gas_cost_usd),
+                error: r.error.clone(),
+            },
+            Err(e) => OutcomeRecord {
+                id,
+                block,
+                label: OutcomeLabel::Rejected,
+                outcome: Some(format!("{:?}", outcome)),
+                tx_hash: None,
+                net_profit_usd: None,
+                gas_cost_usd: None,
+                error: Some(e.to_string()),
+            },
+        };
+        self.emit(ArchiveRecord::Outcome(record));
+    }
+
+    /// Label every opportunity archived `expiry_blocks` or more before
+    /// `current_block` and still unexecuted as expired. Returns how many.
+    pub fn expire(&mut self, current_block: u64) -> usize {
+        let expired: Vec<u64> = self.pending.iter()
+            .filter(|(_, &block)| current_block >= block + self.expiry_blocks)
+            .map(|(&id, _)| id)
+            .collect();
+        for &id in &expired {
+            let block = self.pending.remove(&id).unwrap_or(current_block);
+            self.emit(ArchiveRecord::Outcome(OutcomeRecord {
+                id,
+                block,
+                label: OutcomeLabel::Expired,
+                outcome: None,
+                tx_hash: None,
+                net_profit_usd: None,
+                gas_cost_usd: None,
+                error: None,
+            }));
+        }
+        if !expired.is_empty() {
+            debug!("Opportunity archive: {} opportunity(ies) expired unexecuted at block {}", expired.len(), current_block);
+        }
+        expired.len()
+    }
+
+    /// Opportunities archived and not yet labeled
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Backpressure summary for the periodic status (None while quiet)
+    pub fn status_line(&self) -> Option<String> {
+        self.writer.status_line()
+    }
+
+    /// Drain queued lines (shutdown)
+    pub fn flush(&self, timeout: Duration) -> bool {
+        self.writer.flush(timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::executor::tests::tax_test_executor;
+    use crate::types::TradingPair;
+    use anyhow::anyhow;
+    use ethers::types::U256;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Instant;
+
+    fn opp(pair: &str) -> ArbitrageOpportunity {
+        let pair = TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), pair.to_string());
+        let mut o = ArbitrageOpportunity::new(pair, DexType::UniswapV3_005, DexType::QuickswapV3, 2500.0, 2512.0, U256::exp10(21));
+        o.estimated_profit = 1.5;
+        o.buy_pool_liquidity = Some(1_000_000);
+        o
+    }
+
+    fn result(opp: &ArbitrageOpportunity, success: bool, tx_hash: Option<&str>) -> TradeResult {
+        TradeResult {
+            opportunity: opp.pair.symbol.clone(),
+            opportunity_id: opp.id,
+            tx_hash: tx_hash.map(str::to_string),
+            block_number: Some(101),
+            success,
+            profit_usd: 1.2,
+            gas_cost_usd: 0.02,
+            gas_used_native: 0.01,
+            net_profit_usd: if success { 1.18 } else { -0.02 },
+            execution_time_ms: 900,
+            error: (!success).then(|| "execution reverted".to_string()),
+            amount_in: None,
+            amount_out: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_opportunity_id_threads_through_executor_to_result() {
+        let a = opp("WETH/USDC");
+        let b = a.clone();
+        let c = opp("WETH/USDC");
+        assert_eq!(a.id, b.id, "clones keep the detection's id");
+        assert!(c.id > a.id, "fresh opportunities get new ids");
+
+        // Dry run and an early mempool rejection both tag their result
+        let mut executor = tax_test_executor();
+        assert_eq!(executor.execute(&a).await.unwrap().opportunity_id, a.id);
+        let mempool = executor.execute_from_mempool(&c, U256::one(), None, None, 0.1).await.unwrap();
+        assert_eq!(mempool.opportunity_id, c.id);
+
+        // ...and the id survives a serde round trip of the opportunity
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(serde_json::from_str::<ArbitrageOpportunity>(&json).unwrap().id, a.id);
+    }
+
+    #[test]
+    fn test_labels_join_detections_by_id() {
+        let dir = std::env::temp_dir().join(format!("dexarb_archive_join_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let contract = StreamContract::lossy("opportunity_archive", 64, Duration::from_secs(1));
+        let mut archive = OpportunityArchive::new(dir.to_str().unwrap(), "polygon", 2, 0, contract);
+
+        let (landed, reverted, errored, stale) = (opp("WETH/USDC"), opp("WBTC/USDC"), opp("WMATIC/USDC"), opp("LINK/USDC"));
+        for o in [&landed, &reverted, &errored, &stale] {
+            archive.record_detected(100, o);
+        }
+        let verdict = VerifiedOpportunity { quoted_profit_raw: 1_400_000, ..VerifiedOpportunity::passthrough(0) };
+        archive.record_verified(100, &landed, &verdict);
+        archive.record_execution(100, &landed, &Ok(result(&landed, true, Some("0xaa"))), TradeOutcome::Success);
+        archive.record_execution(100, &reverted, &Ok(result(&reverted, false, Some("0xbb"))), TradeOutcome::AtomicRevert);
+        archive.record_execution(100, &errored, &Err(anyhow!("rpc timeout")), TradeOutcome::ExecutionError);
+        // A second result for the same opportunity is not a second label
+        archive.record_execution(100, &landed, &Ok(result(&landed, true, Some("0xaa"))), TradeOutcome::Success);
+        assert_eq!(archive.expire(101), 0);
+        assert_eq!(archive.expire(102), 1);
+        assert_eq!(archive.pending_count(), 0);
+        assert!(archive.flush(Duration::from_secs(5)));
+
+        let rows = load_labeled(&dir.join(file_name(Utc::now().date_naive()))).unwrap();
+        let labels: Vec<(&str, Option<OutcomeLabel>)> =
+            rows.iter().map(|r| (r.detected.pair.as_str(), r.outcome.as_ref().map(|o| o.label))).collect();
+        assert_eq!(labels, vec![
+            ("WETH/USDC", Some(OutcomeLabel::Landed)),
+            ("WBTC/USDC", Some(OutcomeLabel::Reverted)),
+            ("WMATIC/USDC", Some(OutcomeLabel::Rejected)),
+            ("LINK/USDC", Some(OutcomeLabel::Expired)),
+        ]);
+        assert_eq!(rows[0].detected.id, landed.id);
+        assert_eq!(rows[0].detected.buy_liquidity, Some(1_000_000));
+        assert_eq!(rows[0].verified.as_ref().map(|v| v.quoted_profit_raw), Some(1_400_000));
+        assert_eq!(rows[0].outcome.as_ref().unwrap().net_profit_usd, Some(1.18));
+        assert_eq!(rows[1].outcome.as_ref().unwrap().outcome.as_deref(), Some("AtomicRevert"));
+        assert_eq!(rows[2].outcome.as_ref().unwrap().error.as_deref(), Some("rpc timeout"));
+        assert!(rows[3].verified.is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_result_is_not_labelled_landed() {
+        let dir = std::env::temp_dir().join(format!("dexarb_archive_dry_run_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let contract = StreamContract::lossy("opportunity_archive", 64, Duration::from_secs(1));
+        let mut archive = OpportunityArchive::new(dir.to_str().unwrap(), "polygon", 2, 0, contract);
+
+        let o = opp("WETH/USDC");
+        archive.record_detected(100, &o);
+        let exec = tax_test_executor().execute(&o).await;
+        assert!(exec.as_ref().is_ok_and(|r| r.success), "dry run reports success");
+        archive.record_execution(100, &o, &exec, TradeOutcome::Success);
+        assert!(archive.flush(Duration::from_secs(5)));
+
+        let rows = load_labeled(&dir.join(file_name(Utc::now().date_naive()))).unwrap();
+        let outcome = rows[0].outcome.as_ref().unwrap();
+        assert_eq!(outcome.label, OutcomeLabel::DryRun);
+        assert_eq!(outcome.tx_hash.as_deref(), Some(DRY_RUN_TX_HASH));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stalled_disk_drops_oldest_lines_without_blocking() {
+        let stall = Arc::new(AtomicBool::new(true));
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (s, w) = (stall.clone(), written.clone());
+        let contract = StreamContract::lossy("opportunity_archive", 8, Duration::from_millis(20));
+        let mut archive = OpportunityArchive::spawn("polygon", 2, contract, move |line: &mut ArchiveLine| {
+            while s.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            w.lock().unwrap().push(line.json.clone());
+            Ok(())
+        });
+
+        let opps: Vec<ArbitrageOpportunity> = (0..100).map(|_| opp("WETH/USDC")).collect();
+        archive.record_detected(100, &opps[0]);
+        thread::sleep(Duration::from_millis(20)); // writer picks up the first line and hangs
+        // A burst of detections against a hung disk: each call returns at once
+        let started = Instant::now();
+        for o in &opps[1..] {
+            archive.record_detected(100, o);
+        }
+        assert!(started.elapsed() < Duration::from_millis(200));
+        thread::sleep(Duration::from_millis(40));
+        assert!(archive.status_line().unwrap().contains("STALLED"));
+        // Labels are still tracked for everything archived
+        assert_eq!(archive.pending_count(), 100);
+
+        // Disk back: the first line (stuck in the write) and the newest 8 land
+        stall.store(false, Ordering::SeqCst);
+        assert!(archive.flush(Duration::from_secs(5)));
+        let ids: Vec<u64> = written.lock().unwrap().iter()
+            .map(|json| match parse_record(json) {
+                Some(ArchiveRecord::Detected(d)) => d.id,
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        let expected: Vec<u64> = std::iter::once(opps[0].id).chain(opps[92..].iter().map(|o| o.id)).collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_retention_deletes_only_expired_day_files() {
+        let dir = std::env::temp_dir().join(format!("dexarb_archive_retention_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let day = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        for name in ["opportunities_20261001.jsonl", "opportunities_20261009.jsonl", "opportunities_20261016.jsonl", "events_20261001.jsonl"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert_eq!(prune(&dir, day("2026-10-16"), 0).unwrap(), 0);
+        assert_eq!(prune(&dir, day("2026-10-16"), 7).unwrap(), 1);
+        let mut left: Vec<String> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name().into_string().unwrap()).collect();
+        left.sort();
+        assert_eq!(left, vec!["events_20261001.jsonl", "opportunities_20261009.jsonl", "opportunities_20261016.jsonl"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,289 @@
+//! Balance Clamping — trade size held to the wallet's quote token balance
+//!
+//! The detector sizes every opportunity at MAX_TRADE_SIZE_USD whatever the
+//! wallet holds. A trade larger than the balance is a guaranteed revert
+//! (transferFrom inside ArbExecutor) that still burns gas and cools down a
+//! good route. `BalanceTracker` caches the wallet balance of each quote token
+//! (read at startup for every pool quote token, re-read after each
+//! submission) and, before exposure pacing, every live dispatch is:
+//!   - rejected when the balance is worth less than MIN_QUOTE_BALANCE_USD
+//!   - clamped to BALANCE_USABLE_FRACTION (0.98) of the balance otherwise,
+//!     profit and trade_size_usd prorated (as exposure pacing does) and
+//!     rejected if the clamped profit no longer clears the minimum
+//!
+//! Either case logs a low-balance warning, at most once per hour per token.
+//! Rejections carry BALANCE_CLAMP_PREFIX and classify as ExposureLimited
+//! (wallet state: no route cooldown).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::arbitrage::stranded::token_universe;
+use crate::pool::PoolStateManager;
+use crate::quote_amount::{QuoteAmount, QuoteToken};
+use crate::token_registry::TokenRegistry;
+use crate::types::{ArbitrageOpportunity, BotConfig};
+use ethers::types::{Address, U256};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Prefix of every balance clamp rejection message (see TradeOutcome::classify)
+pub const BALANCE_CLAMP_PREFIX: &str = "Balance clamp:";
+
+/// Repeat interval of the low-balance warning per token
+pub const LOW_BALANCE_WARN_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone)]
+pub struct BalanceConfig {
+    pub enabled: bool,
+    /// Share of the balance a single trade may use (headroom for rounding / fee-on-transfer)
+    pub usable_fraction: f64,
+    pub min_balance_usd: f64,
+}
+
+impl BalanceConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            enabled: config.balance_clamp_enabled,
+            usable_fraction: config.balance_usable_fraction,
+            min_balance_usd: config.min_quote_balance_usd,
+        }
+    }
+}
+
+/// Why a trade was not dispatched at the wallet's balance
+#[derive(Debug, Clone, PartialEq)]
+pub enum BalanceRejection {
+    /// Wallet balance of the quote token is below the configured minimum
+    BelowMinimum { balance_usd: f64, min_usd: f64 },
+    /// Clamped trade no longer clears the minimum profit
+    ClampedUnprofitable { estimated_profit: f64 },
+}
+
+impl fmt::Display for BalanceRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BalanceRejection::BelowMinimum { balance_usd, min_usd } => write!(
+                f, "{} wallet balance ${:.2} below minimum ${:.2}",
+                BALANCE_CLAMP_PREFIX, balance_usd, min_usd
+            ),
+            BalanceRejection::ClampedUnprofitable { estimated_profit } => write!(
+                f, "{} clamped trade est. profit ${:.4} below minimum",
+                BALANCE_CLAMP_PREFIX, estimated_profit
+            ),
+        }
+    }
+}
+
+/// Quote tokens of the synced pools: the configured quote tokens plus the
+/// second token of every configured pair ("WMATIC/WETH" → WETH)
+pub fn quote_tokens(state: &PoolStateManager, config: &BotConfig, registry: &TokenRegistry) -> Vec<QuoteToken> {
+    let quote_symbols: HashSet<&str> = config.pairs.iter().filter_map(|p| p.symbol.split('/').nth(1)).collect();
+    token_universe(state, config, registry)
+        .into_iter()
+        .filter(|t| config.is_quote_token(&t.address) || quote_symbols.contains(t.symbol.as_str()))
+        .map(|t| QuoteToken::new(t.address, t.decimals, &t.symbol))
+        .collect()
+}
+
+/// Per-quote-token wallet balance cache + clamp policy
+pub struct BalanceTracker {
+    config: BalanceConfig,
+    tokens: Vec<QuoteToken>,
+    balances: HashMap<Address, QuoteAmount>,
+    /// Cached balances moved by a submission since they were read
+    stale: HashSet<Address>,
+    last_low_warning: HashMap<Address, Instant>,
+}
+
+impl BalanceTracker {
+    pub fn new(config: BalanceConfig) -> Self {
+        Self {
+            config,
+            tokens: Vec::new(),
+            balances: HashMap::new(),
+            stale: HashSet::new(),
+            last_low_warning: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Quote tokens read by a full refresh
+    pub fn track(&mut self, tokens: Vec<QuoteToken>) {
+        self.tokens = tokens;
+    }
+
+    pub fn tokens(&self) -> &[QuoteToken] {
+        &self.tokens
+    }
+
+    /// Last read balance of `quote_token` (raw units), None until read
+    pub fn available(&self, quote_token: Address) -> Option<U256> {
+        self.balances.get(&quote_token).map(|b| b.raw())
+    }
+
+    /// Cached balance still valid for `quote` (same token + decimals, not moved since)
+    pub fn fresh_balance(&self, quote: QuoteToken) -> Option<QuoteAmount> {
+        if self.stale.contains(&quote.address) {
+            return None;
+        }
+        self.balances.get(&quote.address).copied().filter(|b| b.token() == quote)
+    }
+
+    pub fn observe(&mut self, balance: QuoteAmount) {
+        let address = balance.token().address;
+        self.stale.remove(&address);
+        self.balances.insert(address, balance);
+    }
+
+    /// A trade was submitted: re-read balances before the next dispatch
+    pub fn mark_stale(&mut self) {
+        self.stale.extend(self.balances.keys().copied());
+    }
+
+    /// True once per LOW_BALANCE_WARN_INTERVAL per token
+    pub fn should_warn_low(&mut self, quote_token: Address, now: Instant) -> bool {
+        match self.last_low_warning.get(&quote_token) {
+            Some(last) if now.duration_since(*last) < LOW_BALANCE_WARN_INTERVAL => false,
+            _ => {
+                self.last_low_warning.insert(quote_token, now);
+                true
+            }
+        }
+    }
+
+    /// Hold `opp` to `balance`, clamping trade_size in place. Ok(Some(requested))
+    /// = clamped from `requested`, Ok(None) = fits. `min_profit_usd` is the
+    /// minProfit the dispatch path will send.
+    pub fn clamp(
+        &self,
+        opp: &mut ArbitrageOpportunity,
+        balance: QuoteAmount,
+        min_profit_usd: f64,
+    ) -> Result<Option<QuoteAmount>, BalanceRejection> {
+        let requested = opp.trade_size;
+        // USD per raw unit as the detector priced trade_size (covers non-USD quotes like WETH)
+        let usd_of = |amount: QuoteAmount| -> f64 {
+            if requested.is_zero() {
+                0.0
+            } else {
+                opp.trade_size_usd * amount.to_human() / requested.to_human()
+            }
+        };
+
+        let balance_usd = usd_of(balance);
+        if balance_usd < self.config.min_balance_usd {
+            return Err(BalanceRejection::BelowMinimum { balance_usd, min_usd: self.config.min_balance_usd });
+        }
+
+        let usable = balance.fraction(self.config.usable_fraction);
+        if requested <= usable {
+            return Ok(None);
+        }
+        let ratio = usable.to_human() / requested.to_human();
+        let estimated_profit = opp.estimated_profit * ratio;
+        if estimated_profit < min_profit_usd {
+            return Err(BalanceRejection::ClampedUnprofitable { estimated_profit });
+        }
+        opp.trade_size = usable;
+        opp.trade_size_usd = usd_of(usable);
+        opp.estimated_profit = estimated_profit;
+        // Pre-screen leg outputs were quoted for the full size
+        opp.quoted_legs = None;
+        Ok(Some(requested))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+
+    fn tracker() -> BalanceTracker {
+        BalanceTracker::new(BalanceConfig { enabled: true, usable_fraction: 0.98, min_balance_usd: 10.0 })
+    }
+
+    fn units(whole: u64, decimals: u8) -> U256 {
+        U256::from(whole) * U256::exp10(decimals as usize)
+    }
+
+    /// BASE/{quote} opportunity sized `size` whole quote tokens worth `size_usd`, est. profit $2
+    fn opp(quote: &str, decimals: u8, size: u64, size_usd: f64) -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(0x0a), Address::from_low_u64_be(0xe7), format!("WMATIC/{}", quote)),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            1.0,
+            0.99,
+            units(size, decimals),
+        );
+        opp.set_quote_layout(decimals, 18, true);
+        opp.trade_size_usd = size_usd;
+        opp.estimated_profit = 2.0;
+        opp
+    }
+
+    #[test]
+    fn test_clamp_to_usable_balance_across_quote_decimals() {
+        let tracker = tracker();
+
+        // USDC (6 dec): $500 trade, 300 USDC in the wallet → 294 USDC
+        let mut usdc = opp("USDC", 6, 500, 500.0);
+        let balance = usdc.quote_amount(units(300, 6));
+        let requested = tracker.clamp(&mut usdc, balance, 0.5).unwrap();
+        assert_eq!(requested.map(|r| r.raw()), Some(units(500, 6)));
+        assert_eq!(usdc.trade_size.raw(), U256::from(294_000_000u64));
+        assert!((usdc.trade_size_usd - 294.0).abs() < 1e-6);
+        assert!((usdc.estimated_profit - 2.0 * 294.0 / 500.0).abs() < 1e-9);
+
+        // WETH (18 dec, $2500): 0.2 WETH trade ($500), 0.1 WETH held → 0.098 WETH = $245
+        let mut weth = opp("WETH", 18, 0, 500.0);
+        weth.trade_size = weth.quote_amount(U256::exp10(17) * 2);
+        let balance = weth.quote_amount(U256::exp10(17));
+        tracker.clamp(&mut weth, balance, 0.5).unwrap().expect("clamped");
+        assert_eq!(weth.trade_size.raw(), U256::exp10(15) * 98);
+        assert!((weth.trade_size_usd - 245.0).abs() < 1e-6);
+
+        // WBTC (8 dec): fits → untouched
+        let mut wbtc = opp("WBTC", 8, 1, 60_000.0);
+        let balance = wbtc.quote_amount(units(2, 8));
+        assert_eq!(tracker.clamp(&mut wbtc, balance, 0.5).unwrap(), None);
+        assert_eq!(wbtc.trade_size.raw(), units(1, 8));
+    }
+
+    #[test]
+    fn test_rejections_and_hourly_low_balance_warning() {
+        let mut tracker = tracker();
+
+        // 5 USDC in the wallet: below the $10 minimum
+        let mut low = opp("USDC", 6, 500, 500.0);
+        let balance = low.quote_amount(units(5, 6));
+        let err = tracker.clamp(&mut low, balance, 0.5).unwrap_err();
+        assert!(matches!(err, BalanceRejection::BelowMinimum { balance_usd, .. } if (balance_usd - 5.0).abs() < 1e-9));
+        assert!(err.to_string().starts_with(BALANCE_CLAMP_PREFIX));
+        assert_eq!(low.trade_size.raw(), units(500, 6), "rejection leaves the opportunity as is");
+
+        // 50 USDC: clamped to 49 → est. profit $0.196 < $0.5
+        let balance = low.quote_amount(units(50, 6));
+        assert!(matches!(tracker.clamp(&mut low, balance, 0.5), Err(BalanceRejection::ClampedUnprofitable { .. })));
+
+        let usdc = low.quote().address;
+        let t0 = Instant::now();
+        assert!(tracker.should_warn_low(usdc, t0));
+        assert!(!tracker.should_warn_low(usdc, t0 + Duration::from_secs(1800)));
+        assert!(tracker.should_warn_low(Address::from_low_u64_be(0xe7), t0), "per token");
+        assert!(tracker.should_warn_low(usdc, t0 + Duration::from_secs(3601)));
+
+        // Cache: stale after a submission until re-read
+        tracker.observe(balance);
+        assert_eq!(tracker.available(usdc), Some(units(50, 6)));
+        assert_eq!(tracker.fresh_balance(low.quote()), Some(balance));
+        tracker.mark_stale();
+        assert_eq!(tracker.fresh_balance(low.quote()), None);
+        assert_eq!(tracker.available(usdc), Some(units(50, 6)));
+    }
+}
@@ -0,0 +1,257 @@
+//! Burst Triage — bounded pre-screen and execution during sharp market moves
+//!
+//! A large move can make the detector emit 30+ opportunities in one block. The
+//! pre-screen batch then grows large and slow, and by the time serial execution
+//! reaches the tail of the list those quotes are stale. When a block's detected
+//! count exceeds BURST_THRESHOLD the block loop switches to triage:
+//!
+//!   1. Spread: opportunities below BURST_MIN_SPREAD_PERCENT (executable spread)
+//!      are dropped — the marginal ones won't survive the move anyway.
+//!   2. Top-K: per silo (quote token) only the BURST_TOP_K best by estimated
+//!      profit reach the pre-screen.
+//!   3. Logging: one summary line per block instead of one per opportunity.
+//!
+//! Triage ends after BURST_EXIT_BLOCKS consecutive blocks at or below the
+//! threshold. Triaged-away opportunities are counted separately from pre-screen
+//! and executor rejections (they were never quoted) and reported with the
+//! periodic status line. BURST_THRESHOLD=0 disables triage.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::types::{ArbitrageOpportunity, BotConfig};
+use ethers::types::Address;
+use std::collections::HashMap;
+
+/// Triage thresholds (BotConfig burst_*)
+#[derive(Debug, Clone)]
+pub struct BurstConfig {
+    /// Detected opportunities per block above which triage starts (0 = disabled)
+    pub threshold: usize,
+    /// Opportunities per silo kept for the pre-screen during triage
+    pub top_k: usize,
+    /// Executable spread floor (percent) during triage
+    pub min_spread_percent: f64,
+    /// Consecutive calm blocks before triage ends
+    pub exit_blocks: u32,
+}
+
+impl BurstConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            threshold: config.burst_threshold,
+            top_k: config.burst_top_k.max(1),
+            min_spread_percent: config.burst_min_spread_percent.max(0.0),
+            exit_blocks: config.burst_exit_blocks.max(1),
+        }
+    }
+}
+
+/// Mode change reported by `observe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BurstTransition {
+    /// Detected count exceeded the threshold
+    Entered { count: usize },
+    /// Calm for exit_blocks blocks; triage lasted `blocks` blocks
+    Exited { blocks: u64 },
+}
+
+/// Triage counters, logged with the periodic status line
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BurstStats {
+    /// Times triage was entered
+    pub bursts: u64,
+    /// Blocks processed in triage mode
+    pub triage_blocks: u64,
+    /// Dropped below the tightened spread threshold
+    pub triaged_spread: u64,
+    /// Dropped beyond the per-silo top-K
+    pub triaged_top_k: u64,
+}
+
+/// One block's triage result
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TriageSummary {
+    pub kept: usize,
+    pub below_spread: usize,
+    pub beyond_top_k: usize,
+    pub silos: usize,
+}
+
+/// Burst detection (with exit hysteresis) and per-block triage
+pub struct BurstTriage {
+    config: BurstConfig,
+    active: bool,
+    calm_blocks: u32,
+    active_since: u64,
+    stats: BurstStats,
+}
+
+impl BurstTriage {
+    pub fn new(config: BurstConfig) -> Self {
+        Self { config, active: false, calm_blocks: 0, active_since: 0, stats: BurstStats::default() }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.threshold > 0
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn stats(&self) -> &BurstStats {
+        &self.stats
+    }
+
+    /// Feed one block's detected opportunity count
+    pub fn observe(&mut self, count: usize, block: u64) -> Option<BurstTransition> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let burst = count > self.config.threshold;
+        if !self.active {
+            if !burst {
+                return None;
+            }
+            self.active = true;
+            self.calm_blocks = 0;
+            self.active_since = block;
+            self.stats.bursts += 1;
+            self.stats.triage_blocks += 1;
+            return Some(BurstTransition::Entered { count });
+        }
+        if burst {
+            self.calm_blocks = 0;
+        } else {
+            self.calm_blocks += 1;
+            if self.calm_blocks >= self.config.exit_blocks {
+                self.active = false;
+                return Some(BurstTransition::Exited { blocks: block.saturating_sub(self.active_since) });
+            }
+        }
+        self.stats.triage_blocks += 1;
+        None
+    }
+
+    /// Drop marginal spreads, then keep the top-K per silo by estimated profit
+    /// (best first). No-op outside triage mode.
+    pub fn triage(&mut self, opportunities: Vec<ArbitrageOpportunity>) -> (Vec<ArbitrageOpportunity>, TriageSummary) {
+        if !self.active {
+            let kept = opportunities.len();
+            return (opportunities, TriageSummary { kept, ..Default::default() });
+        }
+        let total = opportunities.len();
+        let mut candidates: Vec<ArbitrageOpportunity> = opportunities
+            .into_iter()
+            .filter(|opp| opp.spread_percent >= self.config.min_spread_percent)
+            .collect();
+        let below_spread = total - candidates.len();
+
+        candidates.sort_by(|a, b| {
+            b.estimated_profit.partial_cmp(&a.estimated_profit).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut per_silo: HashMap<Address, usize> = HashMap::new();
+        candidates.retain(|opp| {
+            let taken = per_silo.entry(opp.quote_token()).or_insert(0);
+            *taken += 1;
+            *taken <= self.config.top_k
+        });
+        let beyond_top_k = total - below_spread - candidates.len();
+
+        self.stats.triaged_spread += below_spread as u64;
+        self.stats.triaged_top_k += beyond_top_k as u64;
+        let summary = TriageSummary { kept: candidates.len(), below_spread, beyond_top_k, silos: per_silo.len() };
+        (candidates, summary)
+    }
+
+    /// Mode + counters for the status line (None until the first burst)
+    pub fn status_line(&self) -> Option<String> {
+        if self.stats.bursts == 0 {
+            return None;
+        }
+        Some(format!(
+            "{} | {} burst(s), {} triage blocks | triaged away: {} below {:.2}% spread, {} beyond top-{} per silo",
+            if self.active { "TRIAGE" } else { "normal" },
+            self.stats.bursts,
+            self.stats.triage_blocks,
+            self.stats.triaged_spread,
+            self.config.min_spread_percent,
+            self.stats.triaged_top_k,
+            self.config.top_k
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+    use ethers::types::U256;
+
+    fn triage() -> BurstTriage {
+        BurstTriage::new(BurstConfig { threshold: 10, top_k: 2, min_spread_percent: 0.5, exit_blocks: 3 })
+    }
+
+    fn opp(quote: u64, spread: f64, profit: f64) -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(quote), Address::from_low_u64_be(0xff), "X/Q".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            1.0,
+            1.01,
+            U256::from(1_000_000u64),
+        );
+        opp.spread_percent = spread;
+        opp.estimated_profit = profit;
+        opp
+    }
+
+    #[test]
+    fn test_entry_exit_hysteresis() {
+        let mut b = triage();
+        assert_eq!(b.observe(10, 100), None); // at threshold: not a burst
+        assert_eq!(b.observe(31, 101), Some(BurstTransition::Entered { count: 31 }));
+        assert!(b.is_active());
+        // Calm blocks interrupted by another spike restart the exit count
+        assert_eq!(b.observe(4, 102), None);
+        assert_eq!(b.observe(4, 103), None);
+        assert_eq!(b.observe(12, 104), None);
+        assert_eq!(b.observe(4, 105), None);
+        assert_eq!(b.observe(4, 106), None);
+        assert!(b.is_active());
+        assert_eq!(b.observe(4, 107), Some(BurstTransition::Exited { blocks: 6 }));
+        assert!(!b.is_active());
+        assert_eq!(b.stats().bursts, 1);
+        assert_eq!(b.stats().triage_blocks, 6);
+
+        let mut off = BurstTriage::new(BurstConfig { threshold: 0, ..triage().config });
+        assert_eq!(off.observe(500, 1), None);
+        assert!(!off.is_active());
+    }
+
+    #[test]
+    fn test_top_k_per_silo() {
+        let mut b = triage();
+        let opps = vec![
+            opp(1, 0.8, 3.0),
+            opp(1, 0.9, 5.0),
+            opp(1, 0.7, 4.0),
+            opp(1, 0.2, 9.0), // marginal spread: dropped before ranking
+            opp(2, 0.6, 1.0),
+            opp(2, 0.6, 2.0),
+            opp(2, 0.6, 0.5),
+        ];
+        // Outside triage everything passes untouched
+        let (kept, summary) = b.triage(opps.clone());
+        assert_eq!((kept.len(), summary.below_spread, summary.beyond_top_k), (7, 0, 0));
+
+        b.observe(40, 1);
+        let (kept, summary) = b.triage(opps);
+        let profits: Vec<f64> = kept.iter().map(|o| o.estimated_profit).collect();
+        assert_eq!(profits, vec![5.0, 4.0, 2.0, 1.0]);
+        assert_eq!(summary, TriageSummary { kept: 4, below_spread: 1, beyond_top_k: 2, silos: 2 });
+        assert_eq!((b.stats().triaged_spread, b.stats().triaged_top_k), (1, 2));
+        assert!(b.status_line().unwrap().starts_with("TRIAGE | 1 burst(s)"));
+    }
+}
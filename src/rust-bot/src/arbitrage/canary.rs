@@ -0,0 +1,685 @@
+//! Canary Mode — Time-boxed, size-capped trading for newly promoted pools
+//!
+//! Purpose:
+//!     When a pool is promoted from observation to active, its first live trades
+//!     run in "canary" state instead of at full size. The window is declared on
+//!     the whitelist entry itself (`canary_until` and/or `canary_trades`), so it
+//!     cannot be forgotten.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - trade_size_usd in canary_trades.csv; submitted volume (USD) in stats
+//! Modified: 2026-10-16 - Canary cap computed on QuoteAmount trade sizes
+//! Modified: 2026-10-17 - State and report writes queued to an isolated writer (off the execution path)
+//!
+//! Design:
+//!     - State machine per pool: Canary → Promoted | Demoted
+//!     - While Canary: any route touching the pool gets trade_size capped to
+//!       CANARY_MAX_TRADE_SIZE_USD and is forced through the Multicall pre-screen
+//!       even when SKIP_MULTICALL_PRESCREEN=true
+//!     - Every executor result on a canary route is appended to canary_trades.csv
+//!     - Window ends at canary_until or after canary_trades submitted trades,
+//!       whichever comes first. Then:
+//!         promote if submitted >= CANARY_MIN_TRADES, win rate >= CANARY_MIN_WIN_RATE
+//!           and net P&L >= CANARY_MIN_NET_PROFIT_USD → canary fields removed
+//!         otherwise demote → whitelist status set to "observation", route blocked
+//!     - Decisions are written back to the whitelist JSON and logged loudly
+//!     - Stats persist to canary_state.json so a restart does not reset the window
+//!     - record_result only queues the state snapshot and report row; a writer
+//!       thread (io_writer, "canary" stream) does the file I/O
+
+use crate::io_writer::{IsolatedWriter, StreamContract};
+use anyhow::{anyhow, Context, Result};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::filters::whitelist::PoolWhitelist;
+use crate::types::{ArbitrageOpportunity, BotConfig, TradeResult};
+
+/// Thresholds for canary sizing and the promote/demote decision
+#[derive(Debug, Clone)]
+pub struct CanaryConfig {
+    /// Trade size cap in USD for routes touching a canary pool
+    pub max_trade_size_usd: f64,
+    /// Minimum wins / submitted trades to promote
+    pub min_win_rate: f64,
+    /// Minimum cumulative net P&L (USD) to promote
+    pub min_net_profit_usd: f64,
+    /// Minimum submitted trades — fewer at window end counts as insufficient evidence
+    pub min_trades: u32,
+}
+
+impl CanaryConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            max_trade_size_usd: config.canary_max_trade_size_usd,
+            min_win_rate: config.canary_min_win_rate,
+            min_net_profit_usd: config.canary_min_net_profit_usd,
+            min_trades: config.canary_min_trades,
+        }
+    }
+}
+
+/// Outcome counters for one canary pool (persisted across restarts)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CanaryStats {
+    /// Every executor result, including zero-cost pre-trade rejections
+    pub attempts: u32,
+    /// Results with an on-chain tx
+    pub submitted: u32,
+    /// Submitted trades with positive net profit
+    pub wins: u32,
+    /// Sum of net_profit_usd over submitted trades
+    pub net_profit_usd: f64,
+    /// Sum of trade_size_usd over submitted trades
+    #[serde(default)]
+    pub volume_usd: f64,
+}
+
+impl CanaryStats {
+    pub fn win_rate(&self) -> f64 {
+        if self.submitted == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.submitted as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanaryStatus {
+    Canary,
+    Promoted,
+    Demoted,
+}
+
+/// One pool under canary supervision
+#[derive(Debug, Clone)]
+struct CanaryPool {
+    pair: String,
+    /// Unix seconds the window closes (None = trade-count only)
+    until: Option<i64>,
+    /// Submitted-trade limit (None = time only)
+    max_trades: Option<u32>,
+    stats: CanaryStats,
+    status: CanaryStatus,
+}
+
+/// Result of a closed canary window
+#[derive(Debug, Clone, PartialEq)]
+pub enum CanaryDecision {
+    Promote { address: Address, pair: String, stats: CanaryStats },
+    Demote { address: Address, pair: String, stats: CanaryStats, reason: String },
+}
+
+/// One queued file write
+enum CanaryWrite {
+    /// Full canary_state.json contents
+    State(String),
+    /// One canary_trades.csv row
+    ReportRow(String),
+}
+
+const REPORT_HEADER: &str = "timestamp,pool,pair,buy_dex,sell_dex,trade_size,tx_hash,success,net_profit_usd,error,trade_size_usd";
+
+/// Canary state machine for all whitelisted pools carrying canary fields
+pub struct CanaryTracker {
+    pools: HashMap<Address, CanaryPool>,
+    config: CanaryConfig,
+    /// Writes canary_state.json (stats persistence) and canary_trades.csv
+    /// (per-result report) off the caller's thread
+    writer: Option<IsolatedWriter<CanaryWrite>>,
+    /// Whitelist JSON to rewrite on promote/demote
+    whitelist_path: Option<String>,
+}
+
+impl CanaryTracker {
+    /// Build from a parsed whitelist. Only active/v2_ready entries with
+    /// canary_until or canary_trades are tracked.
+    pub fn from_whitelist(whitelist: &PoolWhitelist, config: CanaryConfig) -> Self {
+        let mut pools = HashMap::new();
+        for p in &whitelist.whitelist.pools {
+            if p.status != "active" && p.status != "v2_ready" {
+                continue;
+            }
+            if p.canary_until.is_none() && p.canary_trades.is_none() {
+                continue;
+            }
+            let address: Address = match p.address.parse() {
+                Ok(a) => a,
+                Err(_) => {
+                    warn!("Canary: invalid pool address {} — skipped", p.address);
+                    continue;
+                }
+            };
+            let until = match p.canary_until.as_deref().map(parse_canary_until) {
+                Some(Some(ts)) => Some(ts),
+                Some(None) => {
+                    warn!("Canary: unparseable canary_until for {} — treating as trade-count only", p.address);
+                    None
+                }
+                None => None,
+            };
+            if until.is_none() && p.canary_trades.is_none() {
+                continue;
+            }
+            pools.insert(address, CanaryPool {
+                pair: p.pair.clone(),
+                until,
+                max_trades: p.canary_trades,
+                stats: CanaryStats::default(),
+                status: CanaryStatus::Canary,
+            });
+        }
+
+        if !pools.is_empty() {
+            info!(
+                "🐤 Canary mode: {} pool(s) capped at ${:.0} until review",
+                pools.len(), config.max_trade_size_usd
+            );
+        }
+
+        Self {
+            pools,
+            config,
+            writer: None,
+            whitelist_path: None,
+        }
+    }
+
+    /// Enable persistence: stats in `{data_dir}/canary_state.json`, results in
+    /// `{data_dir}/canary_trades.csv` (both written through `contract`'s
+    /// writer thread), decisions written back to `whitelist_path`.
+    pub fn with_persistence(mut self, data_dir: &str, whitelist_path: &str, contract: StreamContract) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Canary: failed to create {}: {}", data_dir, e);
+        }
+        let state_path = dir.join("canary_state.json");
+        if let Ok(content) = std::fs::read_to_string(&state_path) {
+            match serde_json::from_str::<HashMap<String, CanaryStats>>(&content) {
+                Ok(saved) => {
+                    for (addr, stats) in saved {
+                        if let Ok(a) = addr.parse::<Address>() {
+                            if let Some(pool) = self.pools.get_mut(&a) {
+                                pool.stats = stats;
+                            }
+                        }
+                    }
+                }
+                Err(e) => warn!("Canary: ignoring unreadable {}: {}", state_path.display(), e),
+            }
+        }
+        let report_path = dir.join("canary_trades.csv");
+        self.writer = Some(IsolatedWriter::spawn(contract, move |write: &mut CanaryWrite| {
+            match write {
+                CanaryWrite::State(json) => std::fs::write(&state_path, json.as_bytes())
+                    .with_context(|| format!("write {}", state_path.display())),
+                CanaryWrite::ReportRow(row) => {
+                    let is_new = !report_path.exists();
+                    let mut file = OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&report_path)
+                        .with_context(|| format!("open {}", report_path.display()))?;
+                    if is_new {
+                        writeln!(file, "{}", REPORT_HEADER)?;
+                    }
+                    writeln!(file, "{}", row)?;
+                    Ok(())
+                }
+            }
+        }));
+        self.whitelist_path = Some(whitelist_path.to_string());
+        self
+    }
+
+    /// Wait up to `timeout` for queued state and report writes (shutdown).
+    /// True when nothing is left queued (or persistence is off).
+    pub fn flush(&self, timeout: Duration) -> bool {
+        self.writer.as_ref().is_none_or(|w| w.flush(timeout))
+    }
+
+    fn queue(&self, write: CanaryWrite) {
+        if let Some(ref writer) = self.writer {
+            if let Err(e) = writer.send(write) {
+                warn!("Canary: {} — write dropped", e);
+            }
+        }
+    }
+
+    /// Number of pools still in canary state
+    pub fn active_count(&self) -> usize {
+        self.pools.values().filter(|p| p.status == CanaryStatus::Canary).count()
+    }
+
+    fn route_pools(opp: &ArbitrageOpportunity) -> impl Iterator<Item = Address> {
+        opp.buy_pool_address.into_iter().chain(opp.sell_pool_address)
+    }
+
+    fn status_of(&self, address: &Address) -> Option<CanaryStatus> {
+        self.pools.get(address).map(|p| p.status)
+    }
+
+    /// True if either leg's pool is in canary state
+    pub fn is_canary_route(&self, opp: &ArbitrageOpportunity) -> bool {
+        Self::route_pools(opp).any(|a| self.status_of(&a) == Some(CanaryStatus::Canary))
+    }
+
+    /// True if either leg's pool failed its canary (blocked until re-promoted by hand)
+    pub fn is_demoted_route(&self, opp: &ArbitrageOpportunity) -> bool {
+        Self::route_pools(opp).any(|a| self.status_of(&a) == Some(CanaryStatus::Demoted))
+    }
+
+    /// Cap trade_size (quote token raw units) to the canary notional.
+    /// Estimated profit and trade_size_usd are scaled down proportionally.
+    /// Returns true if capped.
+    pub fn cap_trade_size(&self, opp: &mut ArbitrageOpportunity) -> bool {
+        if !self.is_canary_route(opp) {
+            return false;
+        }
+        let cap = opp.usd_to_quote(self.config.max_trade_size_usd);
+        if opp.trade_size <= cap || opp.trade_size.is_zero() {
+            return false;
+        }
+        let ratio = cap.raw().low_u128() as f64 / opp.trade_size.raw().low_u128() as f64;
+        opp.estimated_profit *= ratio;
+        opp.trade_size_usd *= ratio;
+        opp.trade_size = cap;
+        true
+    }
+
+    /// Record an executor result for every canary pool on the route.
+    pub fn record_result(&mut self, opp: &ArbitrageOpportunity, result: &TradeResult) {
+        let mut touched = false;
+        for address in Self::route_pools(opp) {
+            let pool = match self.pools.get_mut(&address) {
+                Some(p) if p.status == CanaryStatus::Canary => p,
+                _ => continue,
+            };
+            pool.stats.attempts += 1;
+            if result.tx_hash.is_some() {
+                pool.stats.submitted += 1;
+                pool.stats.net_profit_usd += result.net_profit_usd;
+                pool.stats.volume_usd += opp.trade_size_usd;
+                if result.success {
+                    pool.stats.wins += 1;
+                }
+            }
+            touched = true;
+            self.append_report_row(address, opp, result);
+        }
+        if touched {
+            self.save_state();
+        }
+    }
+
+    /// Close every canary window that has ended (time or trade count) and
+    /// return the decisions. Pure state transition — see `apply_decision`.
+    pub fn due_decisions(&mut self, now: i64) -> Vec<CanaryDecision> {
+        let mut decisions = Vec::new();
+        for (address, pool) in self.pools.iter_mut() {
+            if pool.status != CanaryStatus::Canary {
+                continue;
+            }
+            let time_up = pool.until.is_some_and(|t| now >= t);
+            let trades_up = pool.max_trades.is_some_and(|n| pool.stats.submitted >= n);
+            if !time_up && !trades_up {
+                continue;
+            }
+
+            let stats = pool.stats.clone();
+            let reason = if stats.submitted < self.config.min_trades {
+                Some(format!("{} submitted trades < minimum {}", stats.submitted, self.config.min_trades))
+            } else if stats.win_rate() < self.config.min_win_rate {
+                Some(format!("win rate {:.0}% < {:.0}%", stats.win_rate() * 100.0, self.config.min_win_rate * 100.0))
+            } else if stats.net_profit_usd < self.config.min_net_profit_usd {
+                Some(format!("net P&L ${:.4} < ${:.4}", stats.net_profit_usd, self.config.min_net_profit_usd))
+            } else {
+                None
+            };
+
+            match reason {
+                None => {
+                    pool.status = CanaryStatus::Promoted;
+                    decisions.push(CanaryDecision::Promote { address: *address, pair: pool.pair.clone(), stats });
+                }
+                Some(reason) => {
+                    pool.status = CanaryStatus::Demoted;
+                    decisions.push(CanaryDecision::Demote { address: *address, pair: pool.pair.clone(), stats, reason });
+                }
+            }
+        }
+        decisions
+    }
+
+    /// Log/alert the decision and write it back to the whitelist JSON.
+    pub fn apply_decision(&self, decision: &CanaryDecision) {
+        let (address, promote) = match decision {
+            CanaryDecision::Promote { address, pair, stats } => {
+                info!(
+                    "🐤✅ CANARY PROMOTED: {} {:?} | {} submitted, win rate {:.0}%, net ${:.4}",
+                    pair, address, stats.submitted, stats.win_rate() * 100.0, stats.net_profit_usd
+                );
+                (*address, true)
+            }
+            CanaryDecision::Demote { address, pair, stats, reason } => {
+                error!(
+                    "🐤❌ CANARY DEMOTED → observation: {} {:?} | {} | {} submitted, net ${:.4} — review needed",
+                    pair, address, reason, stats.submitted, stats.net_profit_usd
+                );
+                (*address, false)
+            }
+        };
+        if let Some(ref path) = self.whitelist_path {
+            if let Err(e) = update_whitelist_file(path, address, promote) {
+                error!("Canary: failed to update whitelist {}: {} — update by hand", path, e);
+            }
+        }
+    }
+
+    /// Human-readable summary of all canary pools
+    pub fn report(&self) -> String {
+        let mut lines: Vec<String> = self.pools.iter().map(|(addr, p)| {
+            format!(
+                "{} {:?} [{:?}] attempts={} submitted={} wins={} win_rate={:.0}% net=${:.4}",
+                p.pair, addr, p.status, p.stats.attempts, p.stats.submitted, p.stats.wins,
+                p.stats.win_rate() * 100.0, p.stats.net_profit_usd
+            )
+        }).collect();
+        lines.sort();
+        lines.join("\n")
+    }
+
+    fn save_state(&self) {
+        if self.writer.is_none() {
+            return;
+        }
+        let snapshot: HashMap<String, &CanaryStats> = self.pools.iter()
+            .map(|(a, p)| (format!("{:?}", a), &p.stats))
+            .collect();
+        match serde_json::to_string_pretty(&snapshot) {
+            Ok(json) => self.queue(CanaryWrite::State(json)),
+            Err(e) => warn!("Canary: failed to serialize state: {}", e),
+        }
+    }
+
+    fn append_report_row(&self, pool: Address, opp: &ArbitrageOpportunity, result: &TradeResult) {
+        if self.writer.is_none() {
+            return;
+        }
+        let row = format!(
+            "{},{:?},{},{:?},{:?},{},{},{},{:.6},{},{:.2}",
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+            pool,
+            opp.pair.symbol,
+            opp.buy_dex,
+            opp.sell_dex,
+            opp.trade_size.raw(),
+            result.tx_hash.as_deref().unwrap_or(""),
+            result.success,
+            result.net_profit_usd,
+            result.error.as_deref().unwrap_or("").replace(',', ";"),
+            opp.trade_size_usd,
+        );
+        self.queue(CanaryWrite::ReportRow(row));
+    }
+}
+
+/// Parse canary_until as RFC3339 or a bare date (midnight UTC). Returns unix seconds.
+fn parse_canary_until(s: &str) -> Option<i64> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Some(dt.timestamp());
+    }
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp())
+}
+
+/// Rewrite a single whitelist entry in place. Edits the raw JSON so fields
+/// the Rust structs do not model (changelog, candidate sections) survive.
+///   promote: drop canary_until / canary_trades
+///   demote:  drop canary fields and set status = "observation"
+fn update_whitelist_file(path: &str, address: Address, promote: bool) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read whitelist file: {}", path))?;
+    let mut json: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse whitelist JSON: {}", path))?;
+
+    let target = format!("{:?}", address).to_lowercase();
+    let pools = json
+        .pointer_mut("/whitelist/pools")
+        .and_then(|v| v.as_array_mut())
+        .ok_or_else(|| anyhow!("whitelist.pools missing"))?;
+
+    let entry = pools
+        .iter_mut()
+        .find(|p| p.get("address").and_then(|a| a.as_str()).map(|a| a.to_lowercase()) == Some(target.clone()))
+        .and_then(|p| p.as_object_mut())
+        .ok_or_else(|| anyhow!("pool {} not found in whitelist", target))?;
+
+    entry.remove("canary_until");
+    entry.remove("canary_trades");
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    entry.insert("last_verified".to_string(), serde_json::Value::String(today.clone()));
+    if !promote {
+        entry.insert("status".to_string(), serde_json::Value::String("observation".to_string()));
+        entry.insert(
+            "notes".to_string(),
+            serde_json::Value::String(format!("Canary demoted {} — review before re-activating", today)),
+        );
+    }
+
+    std::fs::write(path, serde_json::to_string_pretty(&json)?)
+        .with_context(|| format!("Failed to write whitelist file: {}", path))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+    use ethers::types::U256;
+
+    const CANARY_POOL: &str = "0x45dda9cb7c25131df268515131f647d726f50608";
+    const OTHER_POOL: &str = "0x0e44ceb592acfc5d3f09d996302eb4c499ff8c10";
+
+    fn test_config() -> CanaryConfig {
+        CanaryConfig {
+            max_trade_size_usd: 25.0,
+            min_win_rate: 0.5,
+            min_net_profit_usd: 0.0,
+            min_trades: 2,
+        }
+    }
+
+    fn test_whitelist(canary_until: &str, canary_trades: Option<u32>) -> PoolWhitelist {
+        let trades = canary_trades.map(|n| format!(r#","canary_trades": {}"#, n)).unwrap_or_default();
+        let json = format!(r#"{{
+            "version": "1.0",
+            "last_updated": "2026-10-16T00:00:00Z",
+            "config": {{ "default_min_liquidity": 1000, "whitelist_enforcement": "strict" }},
+            "whitelist": {{ "pools": [
+                {{ "address": "{}", "pair": "WETH/USDC", "dex": "UniswapV3", "fee_tier": 500,
+                   "status": "active", "canary_until": "{}"{} }},
+                {{ "address": "{}", "pair": "WETH/USDC", "dex": "UniswapV3", "fee_tier": 3000,
+                   "status": "active" }}
+            ] }},
+            "blacklist": {{ "pools": [], "fee_tiers": [] }}
+        }}"#, CANARY_POOL, canary_until, trades, OTHER_POOL);
+        serde_json::from_str(&json).unwrap()
+    }
+
+    /// 500 USDC (6 dec, token0) route from the canary pool to the other pool
+    fn test_opp() -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::with_pool_addresses(
+            TradingPair::new(Address::zero(), Address::zero(), "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.0004,
+            0.00041,
+            U256::from(500_000_000u64),
+            CANARY_POOL.parse().unwrap(),
+            OTHER_POOL.parse().unwrap(),
+        );
+        opp.set_quote_layout(6, 18, true);
+        opp.estimated_profit = 2.0;
+        opp.normalize_trade_size(1.0);
+        opp
+    }
+
+    fn result(submitted: bool, net: f64) -> TradeResult {
+        TradeResult {
+            opportunity: "WETH/USDC".to_string(),
+            opportunity_id: 0,
+            tx_hash: if submitted { Some("0xabc".to_string()) } else { None },
+            block_number: None,
+            success: submitted && net > 0.0,
+            profit_usd: net,
+            gas_cost_usd: 0.0,
+            gas_used_native: 0.0,
+            net_profit_usd: net,
+            execution_time_ms: 0,
+            error: None,
+            amount_in: None,
+            amount_out: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_canary_until() {
+        assert_eq!(parse_canary_until("2026-10-17"), Some(1_792_195_200));
+        assert_eq!(parse_canary_until("2026-10-17T00:00:00Z"), Some(1_792_195_200));
+        assert_eq!(parse_canary_until("tomorrow"), None);
+    }
+
+    #[test]
+    fn test_only_canary_pools_tracked() {
+        let tracker = CanaryTracker::from_whitelist(&test_whitelist("2026-10-17", None), test_config());
+        assert_eq!(tracker.active_count(), 1);
+        assert!(tracker.is_canary_route(&test_opp()));
+
+        let mut other_only = test_opp();
+        other_only.buy_pool_address = other_only.sell_pool_address;
+        assert!(!tracker.is_canary_route(&other_only));
+    }
+
+    #[test]
+    fn test_cap_trade_size() {
+        let tracker = CanaryTracker::from_whitelist(&test_whitelist("2026-10-17", None), test_config());
+        let mut opp = test_opp();
+        assert!(tracker.cap_trade_size(&mut opp));
+        assert_eq!(opp.trade_size.raw(), U256::from(25_000_000u64)); // $25 at 6 decimals
+        assert!((opp.estimated_profit - 0.1).abs() < 1e-9); // 2.0 × 25/500
+        assert!((opp.trade_size_usd - 25.0).abs() < 1e-9);
+
+        // Already below the cap → unchanged
+        assert!(!tracker.cap_trade_size(&mut opp));
+    }
+
+    #[test]
+    fn test_window_open_no_decision() {
+        let mut tracker = CanaryTracker::from_whitelist(&test_whitelist("2026-10-17", None), test_config());
+        tracker.record_result(&test_opp(), &result(true, 0.5));
+        assert!(tracker.due_decisions(1_792_195_199).is_empty());
+        assert_eq!(tracker.active_count(), 1);
+    }
+
+    #[test]
+    fn test_promote_after_time_window() {
+        let mut tracker = CanaryTracker::from_whitelist(&test_whitelist("2026-10-17", None), test_config());
+        let opp = test_opp();
+        tracker.record_result(&opp, &result(false, 0.0)); // pre-trade reject: attempt only
+        tracker.record_result(&opp, &result(true, 0.5));
+        tracker.record_result(&opp, &result(true, -0.1));
+
+        let decisions = tracker.due_decisions(1_792_195_200);
+        assert_eq!(decisions.len(), 1);
+        match &decisions[0] {
+            CanaryDecision::Promote { stats, .. } => {
+                assert_eq!(stats.attempts, 3);
+                assert_eq!(stats.submitted, 2);
+                assert_eq!(stats.wins, 1);
+            }
+            d => panic!("expected promote, got {:?}", d),
+        }
+        assert!(!tracker.is_canary_route(&opp));
+        assert!(!tracker.is_demoted_route(&opp));
+        // Decisions are one-shot
+        assert!(tracker.due_decisions(1_792_195_300).is_empty());
+    }
+
+    #[test]
+    fn test_demote_on_losses_after_trade_count() {
+        let mut tracker = CanaryTracker::from_whitelist(&test_whitelist("2099-01-01", Some(2)), test_config());
+        let opp = test_opp();
+        tracker.record_result(&opp, &result(true, -0.2));
+        assert!(tracker.due_decisions(0).is_empty());
+        tracker.record_result(&opp, &result(true, -0.3));
+
+        let decisions = tracker.due_decisions(0);
+        assert!(matches!(&decisions[0], CanaryDecision::Demote { reason, .. } if reason.contains("win rate")));
+        assert!(tracker.is_demoted_route(&opp));
+        // Demoted pools no longer capped (they are blocked instead)
+        let mut capped = opp.clone();
+        assert!(!tracker.cap_trade_size(&mut capped));
+    }
+
+    #[test]
+    fn test_demote_on_insufficient_trades() {
+        let mut tracker = CanaryTracker::from_whitelist(&test_whitelist("2026-10-17", None), test_config());
+        tracker.record_result(&test_opp(), &result(true, 1.0));
+        let decisions = tracker.due_decisions(1_792_195_200);
+        assert!(matches!(&decisions[0], CanaryDecision::Demote { reason, .. } if reason.contains("minimum")));
+    }
+
+    #[test]
+    fn test_demote_rewrites_whitelist_and_persists_stats() {
+        let dir = std::env::temp_dir().join(format!("canary_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let wl_path = dir.join("pools_whitelist.json");
+        let raw = test_whitelist("2026-10-17", None);
+        let mut json = serde_json::to_value(&raw).unwrap();
+        json["changelog"] = serde_json::Value::String("keep me".to_string());
+        std::fs::write(&wl_path, serde_json::to_string_pretty(&json).unwrap()).unwrap();
+
+        let data_dir = dir.to_str().unwrap();
+        let wl = wl_path.to_str().unwrap();
+        let contract = || StreamContract::lossy("canary", 16, Duration::from_secs(1));
+        let mut tracker = CanaryTracker::from_whitelist(&raw, test_config()).with_persistence(data_dir, wl, contract());
+        tracker.record_result(&test_opp(), &result(true, -1.0));
+        assert!(tracker.flush(Duration::from_secs(5)));
+
+        // Restart: stats survive
+        let mut tracker = CanaryTracker::from_whitelist(&raw, test_config()).with_persistence(data_dir, wl, contract());
+        let decisions = tracker.due_decisions(1_792_195_200);
+        match &decisions[0] {
+            CanaryDecision::Demote { stats, .. } => {
+                assert_eq!(stats.submitted, 1);
+                assert!((stats.volume_usd - 500.0).abs() < 1e-9);
+            }
+            d => panic!("expected demote, got {:?}", d),
+        }
+        tracker.apply_decision(&decisions[0]);
+
+        let updated: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&wl_path).unwrap()).unwrap();
+        let pool = &updated["whitelist"]["pools"][0];
+        assert_eq!(pool["status"], "observation");
+        assert!(pool.get("canary_until").is_none());
+        assert_eq!(updated["whitelist"]["pools"][1]["status"], "active");
+        assert_eq!(updated["changelog"], "keep me");
+        let report = std::fs::read_to_string(dir.join("canary_trades.csv")).unwrap();
+        assert!(report.lines().next().unwrap().ends_with(",trade_size_usd"));
+        assert!(report.lines().nth(1).unwrap().ends_with(",500.00"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
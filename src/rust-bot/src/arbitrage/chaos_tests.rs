@@ -0,0 +1,296 @@
+//! Chaos (failure injection) pipeline tests
+//!
+//! Drives a live-mode atomic TradeExecutor over a MockProvider with one
+//! injected fault per test, then runs the result through TradeOutcome and
+//! RouteCooldown exactly as the block loop does. Each test asserts the
+//! documented intent for its failure class (outcome.rs):
+//!   classification → next step (try next / stop block / HALT) →
+//!   route cooldown effect → operator alert.
+//!
+//! MockProvider answers the last-pushed response first, so each test pushes
+//! the RPC responses it expects in reverse call order:
+//!   eth_getTransactionCount → eth_estimateGas → eth_sendRawTransaction → receipts
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use super::cooldown::RouteCooldown;
+use super::detector::tests::create_test_config;
+use super::executor::TradeExecutor;
+use super::outcome::{CooldownEffect, NextStep, TradeOutcome};
+use super::preflight::PreflightBatcher;
+use crate::chaos::{ChaosConfig, ChaosInjector, Fault, FaultPoint, FaultRule, CHAOS_TAG};
+use crate::types::{ArbitrageOpportunity, DexType, TradingPair};
+use ethers::prelude::*;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+const WETH: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+/// Well-known test key (anvil account 0)
+const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const BLOCK: u64 = 1_000;
+const PENDING_NONCE: u64 = 7;
+
+type MockExecutor = TradeExecutor<Provider<MockProvider>>;
+
+fn rule(point: FaultPoint, fault: Fault, on_calls: Vec<u64>) -> FaultRule {
+    FaultRule { point, fault, on_calls, every: None, probability: None, max_fires: None }
+}
+
+/// Live atomic executor with `rules` injected; base fee pre-cached so the
+/// first RPC of a submission is the nonce fetch.
+fn executor(rules: Vec<FaultRule>) -> (MockExecutor, MockProvider, Arc<ChaosInjector>) {
+    let mut config = create_test_config();
+    config.arb_executor_address = Some(Address::from_low_u64_be(0xa4b));
+    let (provider, mock) = Provider::mocked();
+    let wallet = LocalWallet::from_str(TEST_KEY).unwrap();
+    let mut executor = TradeExecutor::new(Arc::new(provider), wallet, config);
+    executor.set_dry_run(false);
+    executor.set_base_fee(U256::from(30_000_000_000u64));
+    executor.set_current_block(BLOCK);
+    let injector = Arc::new(ChaosInjector::new(ChaosConfig { seed: 1, rules }));
+    executor.set_fault_injector(injector.clone());
+    (executor, mock, injector)
+}
+
+fn opportunity() -> ArbitrageOpportunity {
+    let usdc = Address::from_str(USDC).unwrap();
+    let weth = Address::from_str(WETH).unwrap();
+    // USDC.e (0x2791) < WETH (0x7ceB) → USDC is token0
+    let mut opp = ArbitrageOpportunity::new(
+        TradingPair::new(usdc, weth, "WETH/USDC".to_string()),
+        DexType::UniswapV3_005,
+        DexType::UniswapV3_030,
+        0.000_400,
+        0.000_396,
+        U256::from(500_000_000u64),
+    );
+    opp.set_quote_layout(6, 18, true);
+    opp.normalize_trade_size(1.0);
+    opp
+}
+
+/// Queue a successful public-path send (reverse call order, receipts pushed first)
+fn push_send(mock: &MockProvider, with_nonce: bool) {
+    mock.push(H256::from_low_u64_be(0xbeef)).unwrap(); // eth_sendRawTransaction
+    mock.push(U256::from(350_000u64)).unwrap(); // eth_estimateGas
+    if with_nonce {
+        mock.push(U256::from(PENDING_NONCE)).unwrap(); // eth_getTransactionCount
+    }
+}
+
+/// execute() + classification + cooldown, as the block loop runs them
+async fn attempt(
+    executor: &mut MockExecutor,
+    cooldown: &mut RouteCooldown,
+    opp: &ArbitrageOpportunity,
+) -> (anyhow::Result<crate::types::TradeResult>, TradeOutcome) {
+    let exec = executor.execute(opp).await;
+    let outcome = TradeOutcome::classify(&exec, true);
+    outcome.apply_cooldown(cooldown, opp, BLOCK);
+    (exec, outcome)
+}
+
+fn cooled(cooldown: &RouteCooldown, opp: &ArbitrageOpportunity) -> bool {
+    cooldown.is_cooled_down(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.trade_size_usd, BLOCK + 1)
+}
+
+/// before_send: node rejects the tx as "nonce too low" after a nonce desync.
+/// Nothing on-chain → pre-trade failure, stop the block, cool the route, no
+/// alert; the tracked nonce is dropped and re-read on the next submission.
+#[tokio::test(start_paused = true)]
+async fn test_before_send_nonce_error_resyncs_nonce() {
+    let (mut executor, mock, injector) = executor(vec![
+        rule(FaultPoint::NonceFetch, Fault::NonceGap { offset: -2 }, vec![1]),
+        rule(FaultPoint::BeforeSend, Fault::RpcError { message: "nonce too low".into() }, vec![1]),
+        rule(FaultPoint::AfterSend, Fault::Timeout, vec![1]),
+    ]);
+    let mut cooldown = RouteCooldown::new(10);
+    let opp = opportunity();
+
+    mock.push(U256::from(PENDING_NONCE)).unwrap();
+    let (exec, outcome) = attempt(&mut executor, &mut cooldown, &opp).await;
+    let result = exec.unwrap();
+    assert!(result.tx_hash.is_none());
+    let err = result.error.unwrap();
+    assert!(err.contains("nonce too low") && err.contains(CHAOS_TAG), "{}", err);
+    assert_eq!(outcome, TradeOutcome::PreTradeFailure);
+    assert_eq!(outcome.next_step(), NextStep::StopBlock);
+    assert!(!outcome.alerts_operator());
+    assert!(cooled(&cooldown, &opp));
+    assert_eq!(executor.tracked_nonce(), None);
+
+    // Next submission re-reads the pending nonce (7, not the desynced 5)
+    push_send(&mock, true);
+    let (_, outcome) = attempt(&mut executor, &mut cooldown, &opp).await;
+    assert_eq!(outcome, TradeOutcome::ReceiptTimeout);
+    assert_eq!(executor.tracked_nonce(), Some(PENDING_NONCE + 1));
+    assert_eq!(injector.fired(FaultPoint::NonceFetch), 1);
+    assert_eq!(injector.calls(FaultPoint::NonceFetch), 2);
+}
+
+/// before_send revert (estimateGas / send rejects with InsufficientProfit):
+/// same class as any pre-send failure — no tx, stop the block, cool the route.
+#[tokio::test(start_paused = true)]
+async fn test_before_send_revert_is_pre_trade() {
+    let (mut executor, mock, _) = executor(vec![rule(FaultPoint::BeforeSend, Fault::Revert, vec![])]);
+    let mut cooldown = RouteCooldown::new(10);
+    let opp = opportunity();
+
+    mock.push(U256::from(PENDING_NONCE)).unwrap();
+    let (exec, outcome) = attempt(&mut executor, &mut cooldown, &opp).await;
+    let result = exec.unwrap();
+    assert!(result.tx_hash.is_none());
+    assert!(result.error.unwrap().contains("execution reverted"));
+    assert_eq!(outcome, TradeOutcome::PreTradeFailure);
+    assert!(cooled(&cooldown, &opp));
+    // Not a nonce error — the tracked nonce is kept and was not consumed
+    assert_eq!(executor.tracked_nonce(), Some(PENDING_NONCE));
+}
+
+/// after_send timeout: tx submitted, no receipt. Unknown state → HALT with an
+/// operator alert; the route is NOT cooled (nothing wrong with the route).
+#[tokio::test(start_paused = true)]
+async fn test_after_send_timeout_halts_and_alerts() {
+    let (mut executor, mock, _) = executor(vec![rule(FaultPoint::AfterSend, Fault::Timeout, vec![])]);
+    let mut cooldown = RouteCooldown::new(10);
+    let opp = opportunity();
+
+    push_send(&mock, true);
+    let (exec, outcome) = attempt(&mut executor, &mut cooldown, &opp).await;
+    let result = exec.unwrap();
+    assert!(result.tx_hash.is_some());
+    assert!(result.error.unwrap().contains("Receipt timeout"));
+    assert_eq!(outcome, TradeOutcome::ReceiptTimeout);
+    assert_eq!(outcome.next_step(), NextStep::Halt);
+    assert!(outcome.alerts_operator());
+    assert_eq!(outcome.cooldown(), CooldownEffect::Unchanged);
+    assert!(!cooled(&cooldown, &opp));
+    // Sent tx consumed the nonce
+    assert_eq!(executor.tracked_nonce(), Some(PENDING_NONCE + 1));
+}
+
+/// receipt_poll timeout on every poll: the executor gives up on the normal
+/// 30s schedule (122 polls) and the result is the same HALT class.
+#[tokio::test(start_paused = true)]
+async fn test_receipt_poll_misses_time_out_after_30s() {
+    let (mut executor, mock, injector) = executor(vec![rule(FaultPoint::ReceiptPoll, Fault::Timeout, vec![])]);
+    let mut cooldown = RouteCooldown::new(10);
+    let opp = opportunity();
+
+    push_send(&mock, true);
+    let start = tokio::time::Instant::now();
+    let (_, outcome) = attempt(&mut executor, &mut cooldown, &opp).await;
+    assert_eq!(outcome, TradeOutcome::ReceiptTimeout);
+    assert!(start.elapsed() >= std::time::Duration::from_secs(30));
+    assert_eq!(injector.fired(FaultPoint::ReceiptPoll), 122);
+}
+
+/// after_send revert: receipt status 0. Atomic revert costs gas only →
+/// cool the route and try the next ranked opportunity.
+#[tokio::test(start_paused = true)]
+async fn test_after_send_revert_tries_next_route() {
+    let (mut executor, mock, _) = executor(vec![rule(FaultPoint::AfterSend, Fault::Revert, vec![])]);
+    let mut cooldown = RouteCooldown::new(10);
+    let opp = opportunity();
+
+    push_send(&mock, true);
+    let (exec, outcome) = attempt(&mut executor, &mut cooldown, &opp).await;
+    assert!(exec.unwrap().error.unwrap().contains("reverted on-chain"));
+    assert_eq!(outcome, TradeOutcome::AtomicRevert);
+    assert_eq!(outcome.next_step(), NextStep::TryNext);
+    assert!(!outcome.alerts_operator());
+    assert!(cooled(&cooldown, &opp));
+}
+
+/// receipt_poll RPC error on the 3rd poll (first two are real misses): the
+/// executor returns Err → execution error, stop the block, cool the route.
+/// The wallet is not left busy with the unsettled tx.
+#[tokio::test(start_paused = true)]
+async fn test_receipt_poll_rpc_error_stops_block() {
+    let (mut executor, mock, injector) = executor(vec![rule(
+        FaultPoint::ReceiptPoll,
+        Fault::RpcError { message: "upstream connect error".into() },
+        vec![3],
+    )]);
+    let mut cooldown = RouteCooldown::new(10);
+    let opp = opportunity();
+
+    mock.push(serde_json::Value::Null).unwrap();
+    mock.push(serde_json::Value::Null).unwrap();
+    push_send(&mock, true);
+    let (exec, outcome) = attempt(&mut executor, &mut cooldown, &opp).await;
+    let err = exec.unwrap_err().to_string();
+    assert!(err.starts_with("Failed to fetch receipt") && err.contains("upstream connect error"), "{}", err);
+    assert_eq!(outcome, TradeOutcome::ExecutionError);
+    assert_eq!(outcome.next_step(), NextStep::StopBlock);
+    assert!(cooled(&cooldown, &opp));
+    assert_eq!(injector.calls(FaultPoint::ReceiptPoll), 3);
+    assert_eq!(executor.wallet_in_flight(), 0);
+}
+
+/// quoter revert in the preflight batch: route rejection before any RPC →
+/// cool the route and try the next opportunity.
+#[tokio::test(start_paused = true)]
+async fn test_quoter_revert_rejects_route() {
+    let (mut executor, mock, _) = executor(vec![rule(FaultPoint::Quoter, Fault::Revert, vec![])]);
+    let mut config = create_test_config();
+    config.uniswap_v3_quoter = Some(Address::from_low_u64_be(0xb27));
+    let provider = Arc::new(Provider::new(mock.clone()));
+    executor.set_preflight(PreflightBatcher::new(provider, &config).unwrap());
+    let mut cooldown = RouteCooldown::new(10);
+    let opp = opportunity();
+
+    let (exec, outcome) = attempt(&mut executor, &mut cooldown, &opp).await;
+    let result = exec.unwrap();
+    assert!(result.error.unwrap().contains("buy leg Quoter failed"));
+    assert_eq!(outcome, TradeOutcome::RouteRejected);
+    assert_eq!(outcome.next_step(), NextStep::TryNext);
+    assert!(cooled(&cooldown, &opp));
+    // Rejected before nonce initialization — nothing was sent
+    assert_eq!(executor.tracked_nonce(), None);
+}
+
+/// nonce_fetch timeout: executor returns Err before building the tx →
+/// execution error, stop the block; the next attempt fetches the nonce again.
+#[tokio::test(start_paused = true)]
+async fn test_nonce_fetch_timeout_is_execution_error() {
+    let (mut executor, mock, _) = executor(vec![rule(FaultPoint::NonceFetch, Fault::Timeout, vec![1])]);
+    let mut cooldown = RouteCooldown::new(10);
+    let opp = opportunity();
+
+    let (exec, outcome) = attempt(&mut executor, &mut cooldown, &opp).await;
+    assert!(exec.unwrap_err().to_string().contains("eth_getTransactionCount timed out"));
+    assert_eq!(outcome, TradeOutcome::ExecutionError);
+    assert!(cooled(&cooldown, &opp));
+    assert_eq!(executor.tracked_nonce(), None);
+
+    push_send(&mock, true);
+    executor.set_fault_injector(Arc::new(ChaosInjector::new(ChaosConfig {
+        seed: 0,
+        rules: vec![rule(FaultPoint::AfterSend, Fault::Timeout, vec![])],
+    })));
+    attempt(&mut executor, &mut cooldown, &opp).await.0.unwrap();
+    assert_eq!(executor.tracked_nonce(), Some(PENDING_NONCE + 1));
+}
+
+/// The mempool path shares the before_send seam
+#[tokio::test(start_paused = true)]
+async fn test_mempool_before_send_fault() {
+    let (mut executor, mock, injector) = executor(vec![rule(
+        FaultPoint::BeforeSend,
+        Fault::RpcError { message: "already known".into() },
+        vec![],
+    )]);
+    let opp = opportunity();
+
+    mock.push(U256::from(PENDING_NONCE)).unwrap();
+    let result = executor
+        .execute_from_mempool(&opp, U256::from(50_000_000_000u64), None, None, 0.05)
+        .await
+        .unwrap();
+    assert!(result.tx_hash.is_none());
+    assert!(result.error.unwrap().starts_with("Mempool tx send failed: eth_sendRawTransaction failed: already known"));
+    assert_eq!(injector.fired(FaultPoint::BeforeSend), 1);
+}
@@ -0,0 +1,443 @@
+//! Competition Model — win probability against competing bots' priority fees
+//!
+//! The atomic path bids the gas oracle's priority fee. When another bot takes
+//! the same spread earlier in the block our tx reverts and we pay gas for
+//! nothing, so a route's gross profit overstates what it is worth when it is
+//! contested. This module keeps, per route, a rolling window of the priority
+//! fees that won that route's races:
+//!
+//!   - our landed trades (our bid won)
+//!   - on an atomic revert: txs from the same block that touched either pool
+//!     before ours (optionally only from COMPETITOR_ADDRESSES); the highest
+//!     effective priority fee among them is the bid we lost to
+//!
+//! win_probability(route, bid) is the share of recorded winning fees our bid
+//! beats (ties count half), Laplace-smoothed. Routes with fewer than
+//! COMPETITION_MIN_SAMPLES samples use the pooled distribution of all routes;
+//! with no data at all it is 1.0, i.e. no adjustment.
+//!
+//! With COMPETITION_MODEL_ENABLED the execution order is re-ranked by
+//!   EV = p × profit − (1 − p) × gas × (1 − COMPETITION_GAS_REFUND_FRACTION)
+//! — a lost race costs the (partially refunded) gas of the reverted tx. Each
+//! submission logs its estimate and every resolved race is appended to
+//! data/{chain}/win_probability.csv so the estimates can be calibrated.
+//! Distributions persist to data/{chain}/competition_bids.json.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::arbitrage::slippage::route_key;
+use crate::types::{ArbitrageOpportunity, BotConfig};
+use crate::watchdog::rpc_timeout;
+use anyhow::{anyhow, Result};
+use ethers::providers::Middleware;
+use ethers::types::{Address, Filter, Transaction, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::{debug, info, warn};
+
+/// Winning priority fees kept per route
+pub const COMPETITION_WINDOW: usize = 200;
+
+/// Model settings (BotConfig competition_*)
+#[derive(Debug, Clone)]
+pub struct CompetitionConfig {
+    pub enabled: bool,
+    /// Samples a route needs before its own distribution is used
+    pub min_samples: usize,
+    /// Share of a reverted tx's gas cost refunded (0 = full cost)
+    pub gas_refund_fraction: f64,
+    /// Known competitor senders (empty = every earlier tx on the pools counts)
+    pub competitors: Vec<Address>,
+}
+
+impl CompetitionConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            enabled: config.competition_model_enabled,
+            min_samples: config.competition_min_samples.max(1),
+            gas_refund_fraction: config.competition_gas_refund_fraction.clamp(0.0, 1.0),
+            competitors: config.competitor_addresses.clone(),
+        }
+    }
+}
+
+/// Winning priority fees of one route (gwei, newest last) + our race record
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteBids {
+    pub winning_gwei: VecDeque<f64>,
+    pub races_won: u64,
+    pub races_lost: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CompetitionFile {
+    saved_at: String,
+    routes: BTreeMap<String, RouteBids>,
+}
+
+/// Candidate for EV ranking
+#[derive(Debug, Clone)]
+pub struct EvCandidate {
+    pub route: String,
+    pub profit_usd: f64,
+}
+
+/// One ranked candidate (`index` into the candidate slice)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvRank {
+    pub index: usize,
+    pub win_probability: f64,
+    pub expected_value: f64,
+}
+
+/// Atomic revert whose block is searched for the winning bid
+#[derive(Debug, Clone)]
+pub struct LostRace {
+    pub route: String,
+    pub tx_hash: H256,
+    /// Buy and sell pool of the route
+    pub pools: Vec<Address>,
+}
+
+/// Priority fee a mined tx actually paid above `base_fee`
+pub fn effective_priority_fee(tx: &Transaction, base_fee: U256) -> U256 {
+    match (tx.max_priority_fee_per_gas, tx.max_fee_per_gas) {
+        (Some(tip), Some(max_fee)) => tip.min(max_fee.saturating_sub(base_fee)),
+        _ => tx.gas_price.unwrap_or_default().saturating_sub(base_fee),
+    }
+}
+
+fn wei_to_gwei(wei: U256) -> f64 {
+    wei.as_u128() as f64 / 1e9
+}
+
+/// Effective priority fees (gwei) of the txs that touched `race.pools` before
+/// our reverted tx in its block, restricted to `competitors` when non-empty
+pub async fn discover_winning_bids<M: Middleware>(
+    provider: &M,
+    race: &LostRace,
+    competitors: &[Address],
+) -> Result<Vec<f64>> {
+    let receipt = rpc_timeout("eth_getTransactionReceipt", provider.get_transaction_receipt(race.tx_hash))
+        .await?
+        .ok_or_else(|| anyhow!("no receipt for {:?}", race.tx_hash))?;
+    let block = receipt.block_number.ok_or_else(|| anyhow!("receipt without block number"))?;
+    let our_index = receipt.transaction_index;
+    let base_fee = rpc_timeout("eth_getBlockByNumber", provider.get_block(block))
+        .await?
+        .and_then(|b| b.base_fee_per_gas)
+        .unwrap_or_default();
+
+    let filter = Filter::new().address(race.pools.clone()).from_block(block).to_block(block);
+    let logs = rpc_timeout("eth_getLogs", provider.get_logs(&filter)).await?;
+    let earlier: BTreeSet<H256> = logs
+        .iter()
+        .filter(|log| log.transaction_index.is_some_and(|i| i < our_index))
+        .filter_map(|log| log.transaction_hash)
+        .collect();
+
+    let mut bids = Vec::new();
+    for hash in earlier {
+        let tx = match rpc_timeout("eth_getTransactionByHash", provider.get_transaction(hash)).await? {
+            Some(tx) => tx,
+            None => continue,
+        };
+        if !competitors.is_empty() && !competitors.contains(&tx.from) {
+            continue;
+        }
+        bids.push(wei_to_gwei(effective_priority_fee(&tx, base_fee)));
+    }
+    Ok(bids)
+}
+
+/// Per-route winning bid distributions and the EV ranking adjustment
+pub struct CompetitionModel {
+    config: CompetitionConfig,
+    routes: BTreeMap<String, RouteBids>,
+    path: Option<PathBuf>,
+    calibration_path: Option<PathBuf>,
+}
+
+impl CompetitionModel {
+    pub fn new(config: CompetitionConfig) -> Self {
+        Self { config, routes: BTreeMap::new(), path: None, calibration_path: None }
+    }
+
+    /// Load and save distributions in `{data_dir}/competition_bids.json`,
+    /// append calibration rows to `{data_dir}/win_probability.csv`
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Competition: failed to create {}: {}", data_dir, e);
+        }
+        let path = dir.join("competition_bids.json");
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<CompetitionFile>(&content) {
+                Ok(file) => {
+                    info!("Competition: {} route(s) restored (saved {})", file.routes.len(), file.saved_at);
+                    self.routes = file.routes;
+                }
+                Err(e) => warn!("Competition: ignoring unreadable {}: {}", path.display(), e),
+            }
+        }
+        self.path = Some(path);
+        self.calibration_path = Some(dir.join("win_probability.csv"));
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn competitors(&self) -> &[Address] {
+        &self.config.competitors
+    }
+
+    pub fn route(&self, opp: &ArbitrageOpportunity) -> Option<&RouteBids> {
+        self.routes.get(&route_key(opp))
+    }
+
+    /// Record one winning priority fee for a route
+    pub fn record_winning_bid(&mut self, route: &str, priority_gwei: f64) {
+        let samples = &mut self.routes.entry(route.to_string()).or_default().winning_gwei;
+        samples.push_back(priority_gwei);
+        while samples.len() > COMPETITION_WINDOW {
+            samples.pop_front();
+        }
+        debug!("Competition {}: winning bid {:.1} gwei", route, priority_gwei);
+        self.save();
+    }
+
+    /// Probability that `our_priority_gwei` wins a race on `route`
+    pub fn win_probability(&self, route: &str, our_priority_gwei: f64) -> f64 {
+        let own = self.routes.get(route).map(|r| &r.winning_gwei).filter(|s| s.len() >= self.config.min_samples);
+        let (beaten, total) = match own {
+            Some(samples) => Self::beaten(samples.iter(), our_priority_gwei),
+            None => {
+                let pooled = self.routes.values().flat_map(|r| r.winning_gwei.iter());
+                Self::beaten(pooled, our_priority_gwei)
+            }
+        };
+        if total < self.config.min_samples {
+            return 1.0;
+        }
+        (beaten + 1.0) / (total as f64 + 2.0)
+    }
+
+    /// (samples our bid beats, ties counting half; sample count)
+    fn beaten<'a>(samples: impl Iterator<Item = &'a f64>, ours: f64) -> (f64, usize) {
+        samples.fold((0.0, 0), |(beaten, n), &s| {
+            let score = if ours > s {
+                1.0
+            } else if ours == s {
+                0.5
+            } else {
+                0.0
+            };
+            (beaten + score, n + 1)
+        })
+    }
+
+    /// EV of attempting: win the profit with p, pay the unrefunded gas otherwise
+    pub fn expected_value(&self, profit_usd: f64, gas_cost_usd: f64, win_probability: f64) -> f64 {
+        let lost_gas = gas_cost_usd * (1.0 - self.config.gas_refund_fraction);
+        win_probability * profit_usd - (1.0 - win_probability) * lost_gas
+    }
+
+    /// Candidates ordered by EV, best first (stable for equal EV)
+    pub fn rank(&self, candidates: &[EvCandidate], our_priority_gwei: f64, gas_cost_usd: f64) -> Vec<EvRank> {
+        let mut ranked: Vec<EvRank> = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, c)| {
+                let win_probability = self.win_probability(&c.route, our_priority_gwei);
+                let expected_value = self.expected_value(c.profit_usd, gas_cost_usd, win_probability);
+                EvRank { index, win_probability, expected_value }
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.expected_value.partial_cmp(&a.expected_value).unwrap_or(std::cmp::Ordering::Equal));
+        ranked
+    }
+
+    /// Record a resolved race: counts it, appends a calibration row and, when
+    /// we won, adds our bid to the route's winning distribution
+    pub fn record_race(&mut self, route: &str, our_priority_gwei: f64, win_probability: f64, won: bool) {
+        let bids = self.routes.entry(route.to_string()).or_default();
+        if won {
+            bids.races_won += 1;
+        } else {
+            bids.races_lost += 1;
+        }
+        self.append_calibration(route, our_priority_gwei, win_probability, won);
+        if won {
+            self.record_winning_bid(route, our_priority_gwei);
+        } else {
+            self.save();
+        }
+    }
+
+    fn append_calibration(&self, route: &str, our_priority_gwei: f64, win_probability: f64, won: bool) {
+        let path = match self.calibration_path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let new_file = !path.exists();
+        let result = std::fs::OpenOptions::new().create(true).append(true).open(path).and_then(|mut f| {
+            if new_file {
+                writeln!(f, "timestamp,route,priority_gwei,win_probability,won")?;
+            }
+            writeln!(
+                f,
+                "{},\"{}\",{:.3},{:.4},{}",
+                chrono::Utc::now().to_rfc3339(),
+                route,
+                our_priority_gwei,
+                win_probability,
+                won
+            )
+        });
+        if let Err(e) = result {
+            warn!("Competition: failed to append {}: {}", path.display(), e);
+        }
+    }
+
+    /// One line per route: samples, median winning bid, our race record
+    pub fn summary_lines(&self) -> Vec<String> {
+        self.routes
+            .iter()
+            .map(|(key, bids)| {
+                let mut sorted: Vec<f64> = bids.winning_gwei.iter().copied().collect();
+                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                let median = sorted.get(sorted.len() / 2).copied().unwrap_or(0.0);
+                format!(
+                    "{} | n={} median winning bid {:.1} gwei | races won {} lost {}",
+                    key,
+                    sorted.len(),
+                    median,
+                    bids.races_won,
+                    bids.races_lost
+                )
+            })
+            .collect()
+    }
+
+    fn save(&self) {
+        let path = match self.path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let file = CompetitionFile {
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            routes: self.routes.clone(),
+        };
+        match serde_json::to_string_pretty(&file) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    warn!("Competition: failed to write {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => warn!("Competition: failed to serialize bids: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> CompetitionModel {
+        CompetitionModel::new(CompetitionConfig {
+            enabled: true,
+            min_samples: 4,
+            gas_refund_fraction: 0.0,
+            competitors: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_distribution_updates() {
+        let mut m = model();
+        assert_eq!(m.win_probability("A", 100.0), 1.0); // no data: no adjustment
+        for _ in 0..(COMPETITION_WINDOW + 10) {
+            m.record_winning_bid("A", 50.0);
+        }
+        assert_eq!(m.routes["A"].winning_gwei.len(), COMPETITION_WINDOW);
+
+        m.record_race("B", 30.0, 0.9, true);
+        m.record_race("B", 30.0, 0.9, false);
+        let b = &m.routes["B"];
+        assert_eq!((b.races_won, b.races_lost), (1, 1));
+        assert_eq!(b.winning_gwei, VecDeque::from(vec![30.0])); // only the won race adds a bid
+    }
+
+    #[test]
+    fn test_win_probability_by_bid() {
+        let mut m = model();
+        for gwei in [10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0] {
+            m.record_winning_bid("A", gwei);
+        }
+        // Laplace-smoothed share of winning bids beaten: (beaten + 1) / (n + 2)
+        assert!((m.win_probability("A", 5.0) - 0.1).abs() < 1e-9);
+        assert!((m.win_probability("A", 45.0) - 0.5).abs() < 1e-9);
+        assert!((m.win_probability("A", 40.0) - 0.45).abs() < 1e-9); // tie counts half
+        assert!((m.win_probability("A", 100.0) - 0.9).abs() < 1e-9);
+        let mut last = 0.0;
+        for bid in [0.0, 15.0, 35.0, 55.0, 75.0, 95.0] {
+            let p = m.win_probability("A", bid);
+            assert!(p > last, "monotonic in bid");
+            last = p;
+        }
+
+        // Sparse route falls back to the pooled distribution
+        m.record_winning_bid("B", 1000.0);
+        let pooled = m.win_probability("B", 45.0);
+        assert!((pooled - (4.0 + 1.0) / (9.0 + 2.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ev_ranking() {
+        let mut m = model();
+        for _ in 0..8 {
+            m.record_winning_bid("contested", 9000.0);
+            m.record_winning_bid("quiet", 100.0);
+        }
+        let candidates = vec![
+            EvCandidate { route: "contested".to_string(), profit_usd: 5.0 },
+            EvCandidate { route: "quiet".to_string(), profit_usd: 2.0 },
+            EvCandidate { route: "unseen".to_string(), profit_usd: 1.0 },
+        ];
+        // At 5000 gwei we lose every contested race: EV = 0.1 × 5 − 0.9 × 0.5
+        let ranked = m.rank(&candidates, 5000.0, 0.5);
+        let order: Vec<usize> = ranked.iter().map(|r| r.index).collect();
+        assert_eq!(order, vec![1, 2, 0]);
+        assert!((ranked[2].expected_value - 0.05).abs() < 1e-9);
+        assert!((ranked[0].win_probability - 0.9).abs() < 1e-9);
+
+        // Outbidding the competitor restores the profit ranking
+        let ranked = m.rank(&candidates, 10_000.0, 0.5);
+        assert_eq!(ranked[0].index, 0);
+
+        // A full gas refund leaves only the scaled profit
+        let refunded = CompetitionModel::new(CompetitionConfig { gas_refund_fraction: 1.0, ..m.config.clone() });
+        assert!((refunded.expected_value(5.0, 0.5, 0.1) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_effective_priority_fee() {
+        let gwei = U256::exp10(9);
+        let base = gwei * 100;
+        let mut tx = Transaction {
+            max_priority_fee_per_gas: Some(gwei * 30),
+            max_fee_per_gas: Some(gwei * 120),
+            ..Default::default()
+        };
+        assert_eq!(effective_priority_fee(&tx, base), gwei * 20); // capped by max_fee − base
+        tx.max_fee_per_gas = Some(gwei * 200);
+        assert_eq!(effective_priority_fee(&tx, base), gwei * 30);
+        let legacy = Transaction { gas_price: Some(gwei * 150), ..Default::default() };
+        assert_eq!(effective_priority_fee(&legacy, base), gwei * 50);
+    }
+}
@@ -0,0 +1,205 @@
+//! Signing-Path Connection Manager
+//!
+//! Owns the connections the executor signs and submits through, so none of
+//! them are built on the submission path:
+//!   - the wallet-wrapped signer over the main (WS) provider — built once,
+//!     shared by atomic, mempool, legacy swap, approval and rescue calls
+//!   - the private-RPC HTTP provider (Fastlane) when configured — built once
+//!     with a keep-alive connection pool, recreated after a send error
+//!
+//! keep_warm() issues a cheap eth_chainId on the private provider when it has
+//! been idle for WARM_INTERVAL, so the TLS session is still open when the next
+//! trade arrives. A failed ping recreates the provider.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Idle time after which keep_warm() pings the private RPC
+pub const WARM_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Upper bound on a keep-warm ping (it runs inside the block loop)
+const WARM_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Wallet-wrapped client used for every signed call
+pub type SigningClient<M> = SignerMiddleware<Arc<M>, LocalWallet>;
+
+/// Private-RPC HTTP provider plus the state needed to rebuild and warm it
+struct PrivateRpc {
+    url: String,
+    provider: Arc<Provider<Http>>,
+    last_activity: Instant,
+}
+
+/// Long-lived signing connections owned by TradeExecutor
+pub struct SigningConnections<M: Middleware> {
+    signer: Arc<SigningClient<M>>,
+    private: Option<PrivateRpc>,
+    /// HTTP provider constructions (initial build + every recreation)
+    http_builds: u64,
+}
+
+impl<M: Middleware> SigningConnections<M> {
+    /// Wrap `provider` with `wallet` once. The wallet's chain id is set here.
+    pub fn new(provider: Arc<M>, wallet: LocalWallet, chain_id: u64) -> Self {
+        Self {
+            signer: Arc::new(SignerMiddleware::new(provider, wallet.with_chain_id(chain_id))),
+            private: None,
+            http_builds: 0,
+        }
+    }
+
+    /// Shared signing client (cheap Arc clone)
+    pub fn signer(&self) -> Arc<SigningClient<M>> {
+        self.signer.clone()
+    }
+
+    /// Build the private-RPC provider. Called once at startup.
+    pub fn set_private_rpc(&mut self, url: &str) -> Result<()> {
+        let provider = self.build_http(url)?;
+        self.private = Some(PrivateRpc {
+            url: url.to_string(),
+            provider,
+            last_activity: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Private-RPC provider for raw tx submission, if configured
+    pub fn private_client(&mut self) -> Option<Arc<Provider<Http>>> {
+        self.private.as_mut().map(|p| {
+            p.last_activity = Instant::now();
+            p.provider.clone()
+        })
+    }
+
+    /// A send through the private RPC failed — rebuild the provider so the
+    /// next trade does not reuse a broken connection.
+    pub fn on_private_error(&mut self) {
+        let url = match self.private {
+            Some(ref p) => p.url.clone(),
+            None => return,
+        };
+        match self.build_http(&url) {
+            Ok(provider) => {
+                warn!("Private RPC provider recreated after error ({})", url);
+                if let Some(ref mut p) = self.private {
+                    p.provider = provider;
+                    p.last_activity = Instant::now();
+                }
+            }
+            Err(e) => warn!("Private RPC provider recreate failed: {} — keeping old one", e),
+        }
+    }
+
+    /// Ping the private RPC if it has been idle for WARM_INTERVAL.
+    /// Recreates the provider if the ping fails.
+    pub async fn keep_warm(&mut self) {
+        let provider = match self.private {
+            Some(ref mut p) if p.last_activity.elapsed() >= WARM_INTERVAL => {
+                p.last_activity = Instant::now();
+                p.provider.clone()
+            }
+            _ => return,
+        };
+        let ping = tokio::time::timeout(WARM_TIMEOUT, provider.get_chainid()).await;
+        if !matches!(ping, Ok(Ok(_))) {
+            warn!("Private RPC keep-warm ping failed");
+            self.on_private_error();
+        }
+    }
+
+    /// Number of HTTP providers built so far
+    pub fn http_builds(&self) -> u64 {
+        self.http_builds
+    }
+
+    /// HTTP provider with a long-lived keep-alive pool, so the TLS session
+    /// survives between trades.
+    fn build_http(&mut self, url: &str) -> Result<Arc<Provider<Http>>> {
+        let start = Instant::now();
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| anyhow!("Invalid private RPC URL {}: {}", url, e))?;
+        let client = reqwest::Client::builder()
+            .pool_idle_timeout(None)
+            .tcp_keepalive(Some(Duration::from_secs(30)))
+            .build()
+            .map_err(|e| anyhow!("Failed to build HTTP client for {}: {}", url, e))?;
+        let provider = Provider::new(Http::new_with_client(parsed, client));
+        self.http_builds += 1;
+        info!(
+            "Private RPC provider built in {}µs (build #{}, reused until error)",
+            start.elapsed().as_micros(), self.http_builds
+        );
+        Ok(Arc::new(provider))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nothing listens on port 1: connections are refused immediately
+    const DEAD_URL: &str = "http://127.0.0.1:1";
+
+    fn connections() -> SigningConnections<Provider<Http>> {
+        let provider = Arc::new(Provider::<Http>::try_from(DEAD_URL).unwrap());
+        let wallet: LocalWallet = "0x0123456789012345678901234567890123456789012345678901234567890123"
+            .parse()
+            .unwrap();
+        SigningConnections::new(provider, wallet, 137)
+    }
+
+    #[test]
+    fn test_providers_built_once_and_reused() {
+        let mut conns = connections();
+        assert!(Arc::ptr_eq(&conns.signer(), &conns.signer()));
+        assert_eq!(conns.signer().signer().chain_id(), 137);
+        assert!(conns.private_client().is_none());
+
+        conns.set_private_rpc("https://rpc.example.invalid").unwrap();
+        let first = conns.private_client().unwrap();
+        for _ in 0..5 {
+            assert!(Arc::ptr_eq(&first, &conns.private_client().unwrap()));
+        }
+        assert_eq!(conns.http_builds(), 1);
+    }
+
+    #[test]
+    fn test_recreate_on_error() {
+        let mut conns = connections();
+        conns.on_private_error(); // no private RPC: no-op
+        assert_eq!(conns.http_builds(), 0);
+
+        conns.set_private_rpc("https://rpc.example.invalid").unwrap();
+        let before = conns.private_client().unwrap();
+        conns.on_private_error();
+        let after = conns.private_client().unwrap();
+        assert_eq!(conns.http_builds(), 2);
+        assert!(!Arc::ptr_eq(&before, &after));
+
+        assert!(conns.set_private_rpc("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_keep_warm_recreates_on_failed_ping() {
+        let mut conns = connections();
+        conns.set_private_rpc(DEAD_URL).unwrap();
+
+        // Recently used: no ping, no rebuild
+        conns.keep_warm().await;
+        assert_eq!(conns.http_builds(), 1);
+
+        // Idle past WARM_INTERVAL: ping fails (connection refused) → rebuilt
+        if let Some(ref mut p) = conns.private {
+            p.last_activity = Instant::now() - WARM_INTERVAL;
+        }
+        conns.keep_warm().await;
+        assert_eq!(conns.http_builds(), 2);
+    }
+}
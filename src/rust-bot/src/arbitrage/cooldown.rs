@@ -8,20 +8,40 @@
 //! Author: AI-Generated
 //! Created: 2026-01-31
 //! Modified: 2026-01-31
+//! Modified: 2026-10-16 - revoke_success (reorged-away trade counts as a failure)
+//! Modified: 2026-10-16 - with_escalation (escalation factor / cap per instance, policy simulator)
+//! Modified: 2026-10-16 - active_routes (cooled routes with blocks remaining, for the status API)
+//! Modified: 2026-10-16 - with_persistence / persist (cooldowns survive a graceful restart)
+//! Modified: 2026-10-16 - with_size_buckets (failures cool their trade-size bucket and larger ones)
+//! Modified: 2026-10-16 - is_cooled_down_for_source (mempool signals bypass young cooldowns, bypass stats)
+//! Modified: 2026-10-16 - note_bypass_attempt (bypasses counted when executed, not when checked)
 //!
 //! Design:
-//!     - Route key: (pair_symbol, buy_dex, sell_dex)
+//!     - Route key: (pair_symbol, buy_dex, sell_dex, size_bucket)
+//!     - Size buckets (ROUTE_COOLDOWN_SIZE_BUCKETS_USD): a failure at $2000 says
+//!       nothing about $300 on the same route, so it cools its own bucket and
+//!       every larger one; smaller sizes stay tradeable. Each bucket escalates
+//!       on its own failures. No thresholds = one bucket per route.
 //!     - Escalating backoff: initial → 5× → 5× → cap (default: 10 → 50 → 250 → 1250 → 1800 blocks)
 //!     - On success: entry removed (instant reset)
+//!     - Mempool bypass (ROUTE_COOLDOWN_MEMPOOL_BYPASS_FAILURES): a mempool-triggered
+//!       attempt reflects flow that just moved the pool, so the earlier failure
+//!       says little about it. Cooldowns with fewer failures than the threshold
+//!       are ignored for CooldownSource::Mempool; routes escalated to the cap
+//!       (max strike) stay blocked for every source. The check is read-only;
+//!       the caller notes a bypass (note_bypass_attempt) only once the attempt
+//!       survives its other gates and is executed.
 //!     - Periodic cleanup removes expired entries to bound memory
 
-use std::collections::HashMap;
-use tracing::{info, debug};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use tracing::{info, debug, warn};
 
 use crate::types::DexType;
 
-/// Unique identifier for a route: (pair_symbol, buy_dex, sell_dex)
-type RouteKey = (String, DexType, DexType);
+/// Unique identifier for a route size bucket: (pair_symbol, buy_dex, sell_dex, size_bucket)
+type RouteKey = (String, DexType, DexType, u8);
 
 /// Tracks cooldown state for a single route
 struct CooldownEntry {
@@ -30,18 +50,85 @@ struct CooldownEntry {
     failure_count: u32,
 }
 
+/// Cooldown entry as persisted in route_cooldown.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedRoute {
+    pair: String,
+    buy_dex: DexType,
+    sell_dex: DexType,
+    /// Absent in files saved before size buckets (= bucket 0)
+    #[serde(default)]
+    size_bucket: u8,
+    last_failed_block: u64,
+    cooldown_blocks: u64,
+    failure_count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CooldownFile {
+    saved_at: String,
+    routes: Vec<SavedRoute>,
+}
+
+/// One suppressed route as reported by `active_routes`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CooledRoute {
+    pub pair: String,
+    pub buy_dex: DexType,
+    pub sell_dex: DexType,
+    /// Smallest size bucket suppressed (0 = all sizes)
+    pub size_bucket: u8,
+    pub failure_count: u32,
+    pub remaining_blocks: u64,
+}
+
+/// Where an opportunity came from, for the cooldown check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CooldownSource {
+    /// Block-loop scan of synced pool state
+    BlockScan,
+    /// Mempool signal or mid-block re-scan behind a pending swap
+    Mempool,
+}
+
+/// Mempool cooldown bypasses and what came of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BypassStats {
+    pub bypasses: u64,
+    pub successes: u64,
+    pub failures: u64,
+}
+
+impl BypassStats {
+    /// Successes over resolved bypasses (None before any resolved)
+    pub fn success_rate(&self) -> Option<f64> {
+        let resolved = self.successes + self.failures;
+        (resolved > 0).then(|| self.successes as f64 / resolved as f64)
+    }
+}
+
 /// Route-level cooldown tracker with escalating backoff
 pub struct RouteCooldown {
     entries: HashMap<RouteKey, CooldownEntry>,
     initial_cooldown: u64,
+    escalation_factor: u64,
     max_cooldown: u64,
+    /// Ascending USD bucket boundaries (empty = one bucket per route)
+    size_thresholds_usd: Vec<f64>,
+    /// route_cooldown.json (None = not persisted)
+    path: Option<PathBuf>,
+    /// Mempool attempts ignore cooldowns with fewer failures than this (0 = never)
+    mempool_bypass_failures: u32,
+    bypass_stats: BypassStats,
+    /// Bypassed route buckets whose outcome has not been recorded yet
+    pending_bypasses: HashSet<RouteKey>,
 }
 
 /// Escalation multiplier per failure (5× each step)
-const ESCALATION_FACTOR: u64 = 5;
+pub const DEFAULT_ESCALATION_FACTOR: u64 = 5;
 
 /// Maximum cooldown cap in blocks (~1 hour on Polygon with ~2s blocks)
-const DEFAULT_MAX_COOLDOWN: u64 = 1800;
+pub const DEFAULT_MAX_COOLDOWN: u64 = 1800;
 
 impl RouteCooldown {
     /// Create a new cooldown tracker.
@@ -50,46 +137,225 @@ impl RouteCooldown {
         Self {
             entries: HashMap::new(),
             initial_cooldown,
+            escalation_factor: DEFAULT_ESCALATION_FACTOR,
             max_cooldown: DEFAULT_MAX_COOLDOWN,
+            size_thresholds_usd: Vec::new(),
+            path: None,
+            mempool_bypass_failures: 0,
+            bypass_stats: BypassStats::default(),
+            pending_bypasses: HashSet::new(),
         }
     }
 
-    /// Returns true if this route is currently suppressed (in cooldown).
+    /// Restore cooldowns saved by `persist` in `data_dir`; later `persist`
+    /// calls write there. Entries keep their block numbers, so any that
+    /// expired while the bot was down are dropped at the next cleanup.
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Route cooldown: failed to create {}: {}", data_dir, e);
+        }
+        let path = dir.join("route_cooldown.json");
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<CooldownFile>(&content) {
+                Ok(file) => {
+                    info!("Route cooldown: {} route(s) restored (saved {})", file.routes.len(), file.saved_at);
+                    for r in file.routes {
+                        self.entries.insert((r.pair, r.buy_dex, r.sell_dex, r.size_bucket), CooldownEntry {
+                            last_failed_block: r.last_failed_block,
+                            cooldown_blocks: r.cooldown_blocks,
+                            failure_count: r.failure_count,
+                        });
+                    }
+                }
+                Err(e) => warn!("Route cooldown: ignoring unreadable {}: {}", path.display(), e),
+            }
+        }
+        self.path = Some(path);
+        self
+    }
+
+    /// Write the current cooldowns (shutdown). No-op without persistence.
+    pub fn persist(&self) {
+        let path = match self.path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let file = CooldownFile {
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            routes: self.entries.iter()
+                .map(|((pair, buy_dex, sell_dex, size_bucket), entry)| SavedRoute {
+                    pair: pair.clone(),
+                    buy_dex: *buy_dex,
+                    sell_dex: *sell_dex,
+                    size_bucket: *size_bucket,
+                    last_failed_block: entry.last_failed_block,
+                    cooldown_blocks: entry.cooldown_blocks,
+                    failure_count: entry.failure_count,
+                })
+                .collect(),
+        };
+        match serde_json::to_string_pretty(&file) {
+            Ok(json) => match std::fs::write(path, json) {
+                Ok(()) => info!("Route cooldown: {} route(s) saved to {}", file.routes.len(), path.display()),
+                Err(e) => warn!("Route cooldown: failed to write {}: {}", path.display(), e),
+            },
+            Err(e) => warn!("Route cooldown: failed to serialize: {}", e),
+        }
+    }
+
+    /// Override the per-failure multiplier and the cap (blocks)
+    pub fn with_escalation(mut self, factor: u64, max_cooldown: u64) -> Self {
+        self.escalation_factor = factor;
+        self.max_cooldown = max_cooldown;
+        self
+    }
+
+    /// Split each route by trade size at these USD boundaries: [500, 1500] →
+    /// buckets < $500, $500–1500, ≥ $1500. Empty keeps one bucket per route.
+    pub fn with_size_buckets(mut self, thresholds_usd: &[f64]) -> Self {
+        self.size_thresholds_usd = thresholds_usd.to_vec();
+        self.size_thresholds_usd.sort_by(|a, b| a.total_cmp(b));
+        self
+    }
+
+    /// Let mempool-sourced attempts through cooldowns with fewer than
+    /// `failures` failures (0 = mempool attempts are cooled like block scans)
+    pub fn with_mempool_bypass(mut self, failures: u32) -> Self {
+        self.mempool_bypass_failures = failures;
+        self
+    }
+
+    /// Size bucket of a trade (0 = smallest; always 0 without thresholds)
+    fn size_bucket(&self, size_usd: f64) -> u8 {
+        self.size_thresholds_usd.iter().filter(|t| size_usd >= **t).count() as u8
+    }
+
+    /// Returns true if this route is currently suppressed (in cooldown) at this
+    /// trade size: a failure in its own bucket or any smaller one.
     /// Returns false if no entry exists or cooldown has expired.
     pub fn is_cooled_down(
         &self,
         pair: &str,
         buy_dex: DexType,
         sell_dex: DexType,
+        size_usd: f64,
         current_block: u64,
     ) -> bool {
         if self.initial_cooldown == 0 {
             return false; // Cooldown disabled
         }
 
-        let key = (pair.to_string(), buy_dex, sell_dex);
-        if let Some(entry) = self.entries.get(&key) {
-            let expires_at = entry.last_failed_block + entry.cooldown_blocks;
-            current_block < expires_at
-        } else {
-            false
+        (0..=self.size_bucket(size_usd)).any(|bucket| {
+            let key = (pair.to_string(), buy_dex, sell_dex, bucket);
+            self.entries.get(&key).is_some_and(|entry| {
+                let expires_at = entry.last_failed_block + entry.cooldown_blocks;
+                current_block < expires_at
+            })
+        })
+    }
+
+    /// `is_cooled_down` for an attempt from `source`. A mempool attempt passes
+    /// when every active cooldown at this size is younger than the bypass
+    /// threshold and below the cap. Read-only: see `note_bypass_attempt`.
+    pub fn is_cooled_down_for_source(
+        &self,
+        pair: &str,
+        buy_dex: DexType,
+        sell_dex: DexType,
+        size_usd: f64,
+        current_block: u64,
+        source: CooldownSource,
+    ) -> bool {
+        if !self.is_cooled_down(pair, buy_dex, sell_dex, size_usd, current_block) {
+            return false;
+        }
+        source == CooldownSource::BlockScan
+            || self.bypassed_cooldowns(pair, buy_dex, sell_dex, size_usd, current_block).is_none()
+    }
+
+    /// Active cooldowns at this size when a mempool attempt may bypass all of
+    /// them (None = not bypassable, or nothing to bypass)
+    fn bypassed_cooldowns(
+        &self,
+        pair: &str,
+        buy_dex: DexType,
+        sell_dex: DexType,
+        size_usd: f64,
+        current_block: u64,
+    ) -> Option<Vec<RouteKey>> {
+        if self.mempool_bypass_failures == 0 {
+            return None;
+        }
+        let active: Vec<RouteKey> = (0..=self.size_bucket(size_usd))
+            .map(|bucket| (pair.to_string(), buy_dex, sell_dex, bucket))
+            .filter(|key| self.entries.get(key).is_some_and(|e| current_block < e.last_failed_block + e.cooldown_blocks))
+            .collect();
+        let bypassable = !active.is_empty() && active.iter().all(|key| {
+            let entry = &self.entries[key];
+            entry.failure_count < self.mempool_bypass_failures && entry.cooldown_blocks < self.max_cooldown
+        });
+        bypassable.then_some(active)
+    }
+
+    /// A mempool attempt that passed `is_cooled_down_for_source` is being
+    /// executed: if it passed only by bypassing a cooldown, count the bypass;
+    /// the next success/failure recorded for the route resolves it.
+    pub fn note_bypass_attempt(
+        &mut self,
+        pair: &str,
+        buy_dex: DexType,
+        sell_dex: DexType,
+        size_usd: f64,
+        current_block: u64,
+    ) {
+        if self.initial_cooldown == 0 {
+            return;
+        }
+        let Some(active) = self.bypassed_cooldowns(pair, buy_dex, sell_dex, size_usd, current_block) else {
+            return;
+        };
+        self.bypass_stats.bypasses += 1;
+        debug!("Route cooldown: mempool bypass for {} {:?}→{:?} (${:.0})", pair, buy_dex, sell_dex, size_usd);
+        self.pending_bypasses.extend(active);
+    }
+
+    /// Mempool bypasses so far and how they resolved
+    pub fn bypass_stats(&self) -> BypassStats {
+        self.bypass_stats
+    }
+
+    /// Count the outcome of a pending bypass on any of these route buckets
+    fn resolve_bypass(&mut self, pair: &str, buy_dex: DexType, sell_dex: DexType, success: bool) {
+        let before = self.pending_bypasses.len();
+        self.pending_bypasses.retain(|(p, b, s, _)| !(p == pair && *b == buy_dex && *s == sell_dex));
+        if self.pending_bypasses.len() < before {
+            if success {
+                self.bypass_stats.successes += 1;
+            } else {
+                self.bypass_stats.failures += 1;
+            }
         }
     }
 
-    /// Record a failure for this route. Creates or escalates the cooldown.
-    /// Escalation: initial → initial×5 → initial×25 → ... → max_cooldown
+    /// Record a failure for this route at this trade size. Creates or escalates
+    /// the cooldown of the size's bucket (larger sizes are cooled with it).
+    /// Escalation: initial → initial×factor → initial×factor² → ... → max_cooldown
     pub fn record_failure(
         &mut self,
         pair: &str,
         buy_dex: DexType,
         sell_dex: DexType,
+        size_usd: f64,
         block: u64,
     ) {
         if self.initial_cooldown == 0 {
             return; // Cooldown disabled
         }
+        self.resolve_bypass(pair, buy_dex, sell_dex, false);
 
-        let key = (pair.to_string(), buy_dex, sell_dex);
+        let bucket = self.size_bucket(size_usd);
+        let key = (pair.to_string(), buy_dex, sell_dex, bucket);
         let entry = self.entries.entry(key).or_insert_with(|| CooldownEntry {
             last_failed_block: block,
             cooldown_blocks: 0, // Will be set below
@@ -99,30 +365,49 @@ impl RouteCooldown {
         entry.failure_count += 1;
         entry.last_failed_block = block;
 
-        // Escalate: initial × 5^(failures-1), capped at max
+        // Escalate: initial × factor^(failures-1), capped at max
         let escalated = self.initial_cooldown
-            .saturating_mul(ESCALATION_FACTOR.saturating_pow(entry.failure_count.saturating_sub(1)));
+            .saturating_mul(self.escalation_factor.saturating_pow(entry.failure_count.saturating_sub(1)));
         entry.cooldown_blocks = escalated.min(self.max_cooldown);
 
         debug!(
-            "Route cooldown: {} {:?}→{:?} | fail #{} | suppressed for {} blocks",
-            pair, buy_dex, sell_dex, entry.failure_count, entry.cooldown_blocks
+            "Route cooldown: {} {:?}→{:?} bucket {} | fail #{} | suppressed for {} blocks",
+            pair, buy_dex, sell_dex, bucket, entry.failure_count, entry.cooldown_blocks
         );
     }
 
-    /// Record a success — removes the cooldown entry entirely (instant reset).
+    /// Record a success — removes the cooldown entries of this size's bucket
+    /// and every smaller one (instant reset). Larger buckets keep theirs.
     pub fn record_success(
         &mut self,
         pair: &str,
         buy_dex: DexType,
         sell_dex: DexType,
+        size_usd: f64,
     ) {
-        let key = (pair.to_string(), buy_dex, sell_dex);
-        if self.entries.remove(&key).is_some() {
-            info!("Route cooldown reset: {} {:?}→{:?} (trade succeeded)", pair, buy_dex, sell_dex);
+        self.resolve_bypass(pair, buy_dex, sell_dex, true);
+        let bucket = self.size_bucket(size_usd);
+        let before = self.entries.len();
+        self.entries.retain(|(p, b, s, k), _| !(p == pair && *b == buy_dex && *s == sell_dex && *k <= bucket));
+        if self.entries.len() < before {
+            info!("Route cooldown reset: {} {:?}→{:?} bucket ≤ {} (trade succeeded)", pair, buy_dex, sell_dex, bucket);
         }
     }
 
+    /// A trade booked as a success was reorged away before confirmation depth:
+    /// undo the instant reset by recording the failure it turned out to be.
+    pub fn revoke_success(
+        &mut self,
+        pair: &str,
+        buy_dex: DexType,
+        sell_dex: DexType,
+        size_usd: f64,
+        current_block: u64,
+    ) {
+        info!("Route cooldown: success revoked for {} {:?}→{:?} (trade reversed)", pair, buy_dex, sell_dex);
+        self.record_failure(pair, buy_dex, sell_dex, size_usd, current_block);
+    }
+
     /// Remove expired entries to bound memory usage.
     /// Call periodically (e.g., every ~100 blocks).
     pub fn cleanup(&mut self, current_block: u64) {
@@ -137,32 +422,58 @@ impl RouteCooldown {
         }
     }
 
-    /// Number of currently active (non-expired) cooldown entries.
+    /// Number of currently active (non-expired) cooldown entries (one per route size bucket).
     pub fn active_count(&self) -> usize {
         self.entries.len()
     }
+
+    /// Routes still suppressed at `current_block`, longest remaining first
+    pub fn active_routes(&self, current_block: u64) -> Vec<CooledRoute> {
+        let mut routes: Vec<CooledRoute> = self.entries.iter()
+            .filter_map(|((pair, buy_dex, sell_dex, size_bucket), entry)| {
+                let expires_at = entry.last_failed_block + entry.cooldown_blocks;
+                (current_block < expires_at).then(|| CooledRoute {
+                    pair: pair.clone(),
+                    buy_dex: *buy_dex,
+                    sell_dex: *sell_dex,
+                    size_bucket: *size_bucket,
+                    failure_count: entry.failure_count,
+                    remaining_blocks: expires_at - current_block,
+                })
+            })
+            .collect();
+        routes.sort_by(|a, b| {
+            b.remaining_blocks.cmp(&a.remaining_blocks)
+                .then_with(|| a.pair.cmp(&b.pair))
+                .then_with(|| a.size_bucket.cmp(&b.size_bucket))
+        });
+        routes
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Trade size for single-bucket tests (no thresholds: any size is bucket 0)
+    const SIZE: f64 = 500.0;
+
     #[test]
     fn test_no_cooldown_initially() {
         let cd = RouteCooldown::new(10);
-        assert!(!cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 100));
+        assert!(!cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 100));
     }
 
     #[test]
     fn test_cooldown_after_failure() {
         let mut cd = RouteCooldown::new(10);
-        cd.record_failure("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 100);
+        cd.record_failure("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 100);
 
         // Should be cooled down for blocks 100..109
-        assert!(cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 100));
-        assert!(cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 109));
+        assert!(cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 100));
+        assert!(cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 109));
         // Block 110 = expired
-        assert!(!cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 110));
+        assert!(!cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 110));
     }
 
     #[test]
@@ -173,34 +484,50 @@ mod tests {
         let sell = DexType::UniswapV3_030;
 
         // Failure 1: 10 blocks (~20s)
-        cd.record_failure(pair, buy, sell, 100);
-        assert!(cd.is_cooled_down(pair, buy, sell, 109));
-        assert!(!cd.is_cooled_down(pair, buy, sell, 110));
+        cd.record_failure(pair, buy, sell, SIZE, 100);
+        assert!(cd.is_cooled_down(pair, buy, sell, SIZE, 109));
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 110));
 
         // Failure 2: 50 blocks (~1.7 min)
-        cd.record_failure(pair, buy, sell, 200);
-        assert!(cd.is_cooled_down(pair, buy, sell, 249));
-        assert!(!cd.is_cooled_down(pair, buy, sell, 250));
+        cd.record_failure(pair, buy, sell, SIZE, 200);
+        assert!(cd.is_cooled_down(pair, buy, sell, SIZE, 249));
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 250));
 
         // Failure 3: 250 blocks (~8.3 min)
-        cd.record_failure(pair, buy, sell, 300);
-        assert!(cd.is_cooled_down(pair, buy, sell, 549));
-        assert!(!cd.is_cooled_down(pair, buy, sell, 550));
+        cd.record_failure(pair, buy, sell, SIZE, 300);
+        assert!(cd.is_cooled_down(pair, buy, sell, SIZE, 549));
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 550));
 
         // Failure 4: 1250 blocks (~42 min)
-        cd.record_failure(pair, buy, sell, 600);
-        assert!(cd.is_cooled_down(pair, buy, sell, 1849));
-        assert!(!cd.is_cooled_down(pair, buy, sell, 1850));
+        cd.record_failure(pair, buy, sell, SIZE, 600);
+        assert!(cd.is_cooled_down(pair, buy, sell, SIZE, 1849));
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 1850));
 
         // Failure 5: 1800 blocks (capped at max, ~1 hour)
-        cd.record_failure(pair, buy, sell, 2000);
-        assert!(cd.is_cooled_down(pair, buy, sell, 3799));
-        assert!(!cd.is_cooled_down(pair, buy, sell, 3800));
+        cd.record_failure(pair, buy, sell, SIZE, 2000);
+        assert!(cd.is_cooled_down(pair, buy, sell, SIZE, 3799));
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 3800));
 
         // Failure 6: still 1800 blocks (cap holds)
-        cd.record_failure(pair, buy, sell, 4000);
-        assert!(cd.is_cooled_down(pair, buy, sell, 5799));
-        assert!(!cd.is_cooled_down(pair, buy, sell, 5800));
+        cd.record_failure(pair, buy, sell, SIZE, 4000);
+        assert!(cd.is_cooled_down(pair, buy, sell, SIZE, 5799));
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 5800));
+    }
+
+    #[test]
+    fn test_custom_escalation() {
+        let mut cd = RouteCooldown::new(4).with_escalation(2, 10);
+        let (pair, buy, sell) = ("WETH/USDC", DexType::UniswapV3_005, DexType::UniswapV3_030);
+
+        // 4 → 8 → 10 (capped)
+        cd.record_failure(pair, buy, sell, SIZE, 100);
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 104));
+        cd.record_failure(pair, buy, sell, SIZE, 104);
+        assert!(cd.is_cooled_down(pair, buy, sell, SIZE, 111));
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 112));
+        cd.record_failure(pair, buy, sell, SIZE, 112);
+        assert!(cd.is_cooled_down(pair, buy, sell, SIZE, 121));
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 122));
     }
 
     #[test]
@@ -210,37 +537,37 @@ mod tests {
         let buy = DexType::UniswapV3_005;
         let sell = DexType::SushiV3_005;
 
-        cd.record_failure(pair, buy, sell, 100);
-        assert!(cd.is_cooled_down(pair, buy, sell, 101));
+        cd.record_failure(pair, buy, sell, SIZE, 100);
+        assert!(cd.is_cooled_down(pair, buy, sell, SIZE, 101));
 
-        cd.record_success(pair, buy, sell);
-        assert!(!cd.is_cooled_down(pair, buy, sell, 101));
+        cd.record_success(pair, buy, sell, SIZE);
+        assert!(!cd.is_cooled_down(pair, buy, sell, SIZE, 101));
         assert_eq!(cd.active_count(), 0);
     }
 
     #[test]
     fn test_different_routes_independent() {
         let mut cd = RouteCooldown::new(10);
-        cd.record_failure("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 100);
+        cd.record_failure("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 100);
 
         // Different pair — not cooled down
-        assert!(!cd.is_cooled_down("WBTC/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 101));
+        assert!(!cd.is_cooled_down("WBTC/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 101));
         // Same pair, different dexes — not cooled down
-        assert!(!cd.is_cooled_down("WETH/USDC", DexType::SushiV3_005, DexType::UniswapV3_030, 101));
+        assert!(!cd.is_cooled_down("WETH/USDC", DexType::SushiV3_005, DexType::UniswapV3_030, SIZE, 101));
     }
 
     #[test]
     fn test_disabled_when_zero() {
         let mut cd = RouteCooldown::new(0);
-        cd.record_failure("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 100);
-        assert!(!cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 100));
+        cd.record_failure("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 100);
+        assert!(!cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 100));
     }
 
     #[test]
     fn test_cleanup_removes_expired() {
         let mut cd = RouteCooldown::new(10);
-        cd.record_failure("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, 100);
-        cd.record_failure("WBTC/USDC", DexType::UniswapV3_005, DexType::SushiV3_005, 200);
+        cd.record_failure("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 100);
+        cd.record_failure("WBTC/USDC", DexType::UniswapV3_005, DexType::SushiV3_005, SIZE, 200);
 
         assert_eq!(cd.active_count(), 2);
 
@@ -252,4 +579,155 @@ mod tests {
         cd.cleanup(211);
         assert_eq!(cd.active_count(), 0);
     }
+
+    #[test]
+    fn test_active_routes_reports_remaining_blocks() {
+        let mut cd = RouteCooldown::new(10);
+        cd.record_failure("WETH/USDC", DexType::UniswapV3_001, DexType::UniswapV3_030, SIZE, 100);
+        cd.record_failure("WBTC/USDC", DexType::UniswapV3_005, DexType::SushiV3_005, SIZE, 200);
+        cd.record_failure("WBTC/USDC", DexType::UniswapV3_005, DexType::SushiV3_005, SIZE, 201);
+
+        let routes = cd.active_routes(205);
+        assert_eq!(routes.len(), 1, "WETH/USDC expired at 110");
+        assert_eq!(routes[0].pair, "WBTC/USDC");
+        assert_eq!(routes[0].failure_count, 2);
+        assert_eq!(routes[0].remaining_blocks, 201 + 50 - 205);
+    }
+
+    #[test]
+    fn test_cooldowns_survive_persist_and_restore() {
+        let dir = std::env::temp_dir().join(format!("dexarb_cooldown_{}", std::process::id()));
+        let data_dir = dir.to_str().unwrap();
+        let mut cd = RouteCooldown::new(10).with_persistence(data_dir);
+        cd.record_failure("WBTC/USDC", DexType::UniswapV3_005, DexType::SushiV3_005, SIZE, 200);
+        cd.record_failure("WBTC/USDC", DexType::UniswapV3_005, DexType::SushiV3_005, SIZE, 201);
+        cd.persist();
+
+        let restored = RouteCooldown::new(10).with_persistence(data_dir);
+        assert_eq!(restored.active_routes(205), cd.active_routes(205));
+        assert!(restored.is_cooled_down("WBTC/USDC", DexType::UniswapV3_005, DexType::SushiV3_005, SIZE, 250));
+        assert!(!restored.is_cooled_down("WBTC/USDC", DexType::UniswapV3_005, DexType::SushiV3_005, SIZE, 251));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_large_failure_leaves_smaller_buckets_tradeable() {
+        let mut cd = RouteCooldown::new(10).with_size_buckets(&[1500.0, 500.0]);
+        let (pair, buy, sell) = ("WETH/USDC", DexType::UniswapV3_005, DexType::QuickSwapV2);
+
+        // $2000 reverts: large bucket cooled, $300 and $800 still hot
+        cd.record_failure(pair, buy, sell, 2000.0, 100);
+        assert!(cd.is_cooled_down(pair, buy, sell, 1500.0, 105));
+        assert!(!cd.is_cooled_down(pair, buy, sell, 800.0, 105));
+        assert!(!cd.is_cooled_down(pair, buy, sell, 300.0, 105));
+
+        // $800 fails too: medium and larger cooled, small still tradeable
+        cd.record_failure(pair, buy, sell, 800.0, 105);
+        assert!(cd.is_cooled_down(pair, buy, sell, 500.0, 106));
+        assert!(cd.is_cooled_down(pair, buy, sell, 5000.0, 106));
+        assert!(!cd.is_cooled_down(pair, buy, sell, 499.0, 106));
+        assert_eq!(cd.active_routes(106).iter().map(|r| r.size_bucket).collect::<Vec<_>>(), vec![1, 2]);
+
+        // A $300 success only clears the small bucket; a $900 success clears medium
+        cd.record_success(pair, buy, sell, 300.0);
+        assert!(cd.is_cooled_down(pair, buy, sell, 800.0, 106));
+        cd.record_success(pair, buy, sell, 900.0);
+        assert!(!cd.is_cooled_down(pair, buy, sell, 800.0, 106));
+        assert!(cd.is_cooled_down(pair, buy, sell, 2000.0, 106));
+    }
+
+    #[test]
+    fn test_escalation_independent_per_bucket() {
+        let mut cd = RouteCooldown::new(10).with_size_buckets(&[500.0, 1500.0]);
+        let (pair, buy, sell) = ("WETH/USDC", DexType::UniswapV3_005, DexType::QuickSwapV2);
+
+        // Large bucket: 10 → 50
+        cd.record_failure(pair, buy, sell, 2000.0, 100);
+        cd.record_failure(pair, buy, sell, 2000.0, 110);
+        assert!(cd.is_cooled_down(pair, buy, sell, 2000.0, 159));
+        assert!(!cd.is_cooled_down(pair, buy, sell, 2000.0, 160));
+
+        // Medium bucket's first failure starts at the initial 10, not 250
+        cd.record_failure(pair, buy, sell, 800.0, 120);
+        assert!(cd.is_cooled_down(pair, buy, sell, 800.0, 129));
+        assert!(!cd.is_cooled_down(pair, buy, sell, 800.0, 130));
+        // ...and leaves the large bucket's escalation where it was
+        assert!(cd.is_cooled_down(pair, buy, sell, 2000.0, 159));
+        let large = cd.active_routes(121).into_iter().find(|r| r.size_bucket == 2).unwrap();
+        assert_eq!(large.failure_count, 2);
+    }
+
+    #[test]
+    fn test_mempool_bypasses_young_cooldown_but_block_scan_stays_cooled() {
+        let mut cd = RouteCooldown::new(10).with_mempool_bypass(2);
+        let (pair, buy, sell) = ("WETH/USDC", DexType::UniswapV3_005, DexType::QuickswapV3);
+        cd.record_failure(pair, buy, sell, SIZE, 100);
+
+        assert!(cd.is_cooled_down_for_source(pair, buy, sell, SIZE, 101, CooldownSource::BlockScan));
+        assert!(!cd.is_cooled_down_for_source(pair, buy, sell, SIZE, 101, CooldownSource::Mempool));
+        cd.note_bypass_attempt(pair, buy, sell, SIZE, 101);
+        assert_eq!(cd.bypass_stats().bypasses, 1);
+
+        // The bypassed attempt fails: second failure reaches the threshold
+        cd.record_failure(pair, buy, sell, SIZE, 101);
+        assert!(cd.is_cooled_down_for_source(pair, buy, sell, SIZE, 102, CooldownSource::Mempool));
+        assert_eq!(cd.bypass_stats(), BypassStats { bypasses: 1, successes: 0, failures: 1 });
+
+        // A success on another bypass resolves it as one
+        let (other, osell) = ("WBTC/USDC", DexType::SushiV3_005);
+        cd.record_failure(other, buy, osell, SIZE, 102);
+        assert!(!cd.is_cooled_down_for_source(other, buy, osell, SIZE, 103, CooldownSource::Mempool));
+        cd.note_bypass_attempt(other, buy, osell, SIZE, 103);
+        cd.record_success(other, buy, osell, SIZE);
+        assert_eq!(cd.bypass_stats(), BypassStats { bypasses: 2, successes: 1, failures: 1 });
+        assert_eq!(cd.bypass_stats().success_rate(), Some(0.5));
+    }
+
+    #[test]
+    fn test_bypassed_but_filtered_route_leaves_stats_alone() {
+        let mut cd = RouteCooldown::new(10).with_mempool_bypass(2);
+        let (pair, buy, sell) = ("WETH/USDC", DexType::UniswapV3_005, DexType::QuickswapV3);
+        cd.record_failure(pair, buy, sell, SIZE, 100);
+
+        // Checked (passes) but dropped by a later gate: no bypass counted
+        assert!(!cd.is_cooled_down_for_source(pair, buy, sell, SIZE, 101, CooldownSource::Mempool));
+        assert!(!cd.is_cooled_down_for_source(pair, buy, sell, SIZE, 102, CooldownSource::Mempool));
+        assert_eq!(cd.bypass_stats(), BypassStats::default());
+
+        // ...so the route's next outcome resolves nothing either
+        cd.record_success(pair, buy, sell, SIZE);
+        assert_eq!(cd.bypass_stats(), BypassStats::default());
+
+        // Not cooled at all: executing it is no bypass
+        cd.note_bypass_attempt(pair, buy, sell, SIZE, 103);
+        assert_eq!(cd.bypass_stats().bypasses, 0);
+    }
+
+    #[test]
+    fn test_max_strike_route_blocks_every_source() {
+        let mut cd = RouteCooldown::new(10).with_escalation(5, 50).with_mempool_bypass(10);
+        let (pair, buy, sell) = ("WETH/USDC", DexType::UniswapV3_005, DexType::QuickswapV3);
+
+        // 10 → 50 (cap): blacklisted until it expires, whatever the source
+        cd.record_failure(pair, buy, sell, SIZE, 100);
+        cd.record_failure(pair, buy, sell, SIZE, 101);
+        assert!(cd.is_cooled_down_for_source(pair, buy, sell, SIZE, 102, CooldownSource::Mempool));
+        assert!(cd.is_cooled_down_for_source(pair, buy, sell, SIZE, 102, CooldownSource::BlockScan));
+        cd.note_bypass_attempt(pair, buy, sell, SIZE, 102);
+        assert_eq!(cd.bypass_stats().bypasses, 0);
+
+        // No bypass threshold: mempool attempts are cooled like block scans
+        let mut strict = RouteCooldown::new(10);
+        strict.record_failure(pair, buy, sell, SIZE, 100);
+        assert!(strict.is_cooled_down_for_source(pair, buy, sell, SIZE, 101, CooldownSource::Mempool));
+    }
+
+    #[test]
+    fn test_no_thresholds_is_one_bucket_per_route() {
+        let mut cd = RouteCooldown::new(10);
+        cd.record_failure("WETH/USDC", DexType::UniswapV3_005, DexType::QuickSwapV2, 2000.0, 100);
+        assert!(cd.is_cooled_down("WETH/USDC", DexType::UniswapV3_005, DexType::QuickSwapV2, 10.0, 105));
+        cd.record_success("WETH/USDC", DexType::UniswapV3_005, DexType::QuickSwapV2, 10.0);
+        assert_eq!(cd.active_count(), 0);
+    }
 }
@@ -0,0 +1,469 @@
+//! Cooldown Policy Simulator — replay recorded route attempts under candidate policies
+//!
+//! Purpose:
+//!     The route cooldown trades gas burned on dead spreads against profit
+//!     forgone on spreads that recover. This replays what actually happened
+//!     (route_attempts_YYYYMMDD.jsonl + cooled pre-screen records) through
+//!     the real RouteCooldown configured per candidate policy, and reports
+//!     how each one would have done relative to no cooldown at all.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Replay model:
+//!     - Recorded attempt: executed with its recorded gas, net profit and cooldown effect
+//!     - Cooled record quoted profitable: the live policy suppressed it; a policy
+//!       that would not have is credited the quoted profit minus the gas estimate
+//!       as a success (cooled records the pre-screen rejected are dropped — the
+//!       bot would not have sent them either)
+//!     - Reversal: counts only if the policy executed the reversed tx — its
+//!       success is revoked and its booked profit taken back
+//!     - Per block: reversals first, then every detection is checked against the
+//!       cooldown before any of the block's outcomes apply (as the block loop does)
+//!     - Expired entries are cleaned up every CLEANUP_INTERVAL_BLOCKS, matching
+//!       the block loop's periodic cleanup (which resets the escalation)
+
+use crate::arbitrage::cooldown::{RouteCooldown, DEFAULT_ESCALATION_FACTOR, DEFAULT_MAX_COOLDOWN};
+use crate::arbitrage::outcome::CooldownEffect;
+use crate::arbitrage::prescreen_export::{load_attempts, load_records, AttemptRecord, PrescreenRecord};
+use crate::types::DexType;
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Block loop cleanup cadence (main.rs: every 100 iterations)
+pub const CLEANUP_INTERVAL_BLOCKS: u64 = 100;
+
+/// Trade size every event replays at: candidate policies have no size
+/// buckets, so one size stands for the whole route
+const REPLAY_SIZE_USD: f64 = 0.0;
+
+/// One candidate RouteCooldown configuration ("initial:factor:max" in blocks)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CooldownPolicy {
+    pub initial: u64,
+    pub factor: u64,
+    pub max: u64,
+}
+
+impl CooldownPolicy {
+    /// The live configuration: ROUTE_COOLDOWN_BLOCKS with the default escalation
+    pub fn live(initial: u64) -> Self {
+        Self { initial, factor: DEFAULT_ESCALATION_FACTOR, max: DEFAULT_MAX_COOLDOWN }
+    }
+
+    fn cooldown(&self) -> RouteCooldown {
+        RouteCooldown::new(self.initial).with_escalation(self.factor, self.max)
+    }
+}
+
+impl fmt::Display for CooldownPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.initial, self.factor, self.max)
+    }
+}
+
+impl FromStr for CooldownPolicy {
+    type Err = anyhow::Error;
+
+    /// "initial[:factor[:max]]" — missing parts take the live defaults
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.trim().split(':').collect();
+        if parts.is_empty() || parts.len() > 3 {
+            bail!("cooldown policy '{}': expected initial[:factor[:max]]", s);
+        }
+        let num = |i: usize, default: u64| -> Result<u64> {
+            match parts.get(i) {
+                Some(p) => p.trim().parse().with_context(|| format!("cooldown policy '{}': bad number '{}'", s, p)),
+                None => Ok(default),
+            }
+        };
+        let policy = Self { initial: num(0, 0)?, factor: num(1, DEFAULT_ESCALATION_FACTOR)?, max: num(2, DEFAULT_MAX_COOLDOWN)? };
+        if policy.factor == 0 {
+            bail!("cooldown policy '{}': escalation factor must be >= 1", s);
+        }
+        Ok(policy)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum EventKind {
+    /// Recorded attempt or cooled counterfactual
+    Attempt { effect: CooldownEffect, tx_hash: Option<String>, counterfactual: bool },
+    Reversal { tx_hash: String },
+}
+
+/// One replayable event on a route
+#[derive(Debug, Clone, PartialEq)]
+struct RouteEvent {
+    block: u64,
+    pair: String,
+    buy_dex: DexType,
+    sell_dex: DexType,
+    gas_cost_usd: f64,
+    net_profit_usd: f64,
+    kind: EventKind,
+}
+
+impl RouteEvent {
+    fn from_attempt(a: &AttemptRecord) -> Self {
+        let kind = if a.is_reversal() {
+            EventKind::Reversal { tx_hash: a.tx_hash.clone().unwrap_or_default() }
+        } else {
+            EventKind::Attempt { effect: a.cooldown, tx_hash: a.tx_hash.clone(), counterfactual: false }
+        };
+        Self {
+            block: a.block,
+            pair: a.pair.clone(),
+            buy_dex: a.buy_dex,
+            sell_dex: a.sell_dex,
+            gas_cost_usd: a.gas_cost_usd,
+            net_profit_usd: a.net_profit_usd,
+            kind,
+        }
+    }
+
+    /// Cooled record quoted profitable → assumed success at the quoted profit
+    fn from_cooled(r: &PrescreenRecord, gas_cost_usd: f64) -> Option<Self> {
+        if !r.cooled || !r.both_legs_valid || r.quoted_profit_raw <= 0 {
+            return None;
+        }
+        Some(Self {
+            block: r.block,
+            pair: r.pair.clone(),
+            buy_dex: r.buy_dex,
+            sell_dex: r.sell_dex,
+            gas_cost_usd,
+            net_profit_usd: r.quoted_profit_usd() - gas_cost_usd,
+            kind: EventKind::Attempt { effect: CooldownEffect::Reset, tx_hash: None, counterfactual: true },
+        })
+    }
+}
+
+/// Recorded history to replay, ordered by block (reversals first within a block)
+#[derive(Debug, Clone, Default)]
+pub struct AttemptHistory {
+    events: Vec<RouteEvent>,
+}
+
+impl AttemptHistory {
+    /// `counterfactual_gas_usd` prices the cooled counterfactuals (ESTIMATED_GAS_COST_USD)
+    pub fn new(attempts: &[AttemptRecord], prescreen: &[PrescreenRecord], counterfactual_gas_usd: f64) -> Self {
+        let mut events: Vec<RouteEvent> = attempts.iter().map(RouteEvent::from_attempt)
+            .chain(prescreen.iter().filter_map(|r| RouteEvent::from_cooled(r, counterfactual_gas_usd)))
+            .collect();
+        events.sort_by_key(|e| (e.block, !matches!(e.kind, EventKind::Reversal { .. })));
+        Self { events }
+    }
+
+    /// Load `route_attempts_*` and `prescreen_*` files for every day in `from..=to`
+    pub fn load(research_dir: &Path, from: NaiveDate, to: NaiveDate, counterfactual_gas_usd: f64) -> Result<Self> {
+        let mut attempts = Vec::new();
+        let mut prescreen = Vec::new();
+        for day in from.iter_days().take_while(|d| *d <= to) {
+            let stamp = day.format("%Y%m%d");
+            let path = research_dir.join(format!("route_attempts_{}.jsonl", stamp));
+            if path.exists() {
+                attempts.extend(load_attempts(&path)?);
+            }
+            let path = research_dir.join(format!("prescreen_{}.jsonl", stamp));
+            if path.exists() {
+                prescreen.extend(load_records(&path)?.into_iter().filter(|r| r.cooled));
+            }
+        }
+        Ok(Self::new(&attempts, &prescreen, counterfactual_gas_usd))
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Replay under one policy
+    pub fn replay(&self, policy: CooldownPolicy) -> PolicyReport {
+        let mut cooldown = policy.cooldown();
+        let mut report = PolicyReport { policy: policy.to_string(), ..Default::default() };
+        let mut executed_txs: HashSet<&str> = HashSet::new();
+        let mut last_cleanup = 0u64;
+
+        let mut i = 0;
+        while i < self.events.len() {
+            let block = self.events[i].block;
+            let end = i + self.events[i..].iter().take_while(|e| e.block == block).count();
+            if block >= last_cleanup + CLEANUP_INTERVAL_BLOCKS {
+                cooldown.cleanup(block);
+                last_cleanup = block;
+            }
+
+            let block_events = &self.events[i..end];
+            for e in block_events {
+                if let EventKind::Reversal { tx_hash } = &e.kind {
+                    if executed_txs.contains(tx_hash.as_str()) {
+                        cooldown.revoke_success(&e.pair, e.buy_dex, e.sell_dex, REPLAY_SIZE_USD, block);
+                        report.net_profit_usd += e.net_profit_usd;
+                    } else {
+                        // Reversed profit was never booked under this policy
+                        report.profit_forgone_usd += e.net_profit_usd;
+                    }
+                }
+            }
+
+            let suppressed: Vec<bool> = block_events.iter()
+                .map(|e| cooldown.is_cooled_down(&e.pair, e.buy_dex, e.sell_dex, REPLAY_SIZE_USD, block))
+                .collect();
+            for (e, suppressed) in block_events.iter().zip(suppressed) {
+                let EventKind::Attempt { effect, tx_hash, counterfactual } = &e.kind else { continue };
+                if suppressed {
+                    report.suppressed += 1;
+                    report.gas_saved_usd += e.gas_cost_usd;
+                    report.profit_forgone_usd += e.net_profit_usd + e.gas_cost_usd;
+                    if *effect == CooldownEffect::Reset {
+                        report.suppressed_successes += 1;
+                    }
+                    continue;
+                }
+                report.attempts += 1;
+                if *counterfactual {
+                    report.counterfactual_attempts += 1;
+                }
+                report.net_profit_usd += e.net_profit_usd;
+                if let Some(tx) = tx_hash {
+                    executed_txs.insert(tx);
+                }
+                effect.apply(&mut cooldown, &e.pair, e.buy_dex, e.sell_dex, REPLAY_SIZE_USD, block);
+            }
+            i = end;
+        }
+
+        report.net_delta_usd = report.gas_saved_usd - report.profit_forgone_usd;
+        report
+    }
+}
+
+/// Per-policy totals; deltas are relative to running with no cooldown
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyReport {
+    pub policy: String,
+    pub attempts: u64,
+    pub suppressed: u64,
+    /// Suppressed attempts that were (or were quoted as) successes
+    pub suppressed_successes: u64,
+    /// Cooled counterfactuals this policy would have sent
+    pub counterfactual_attempts: u64,
+    pub gas_saved_usd: f64,
+    pub profit_forgone_usd: f64,
+    /// gas_saved_usd − profit_forgone_usd
+    pub net_delta_usd: f64,
+    pub net_profit_usd: f64,
+}
+
+/// Simulator output: one report per policy, best net delta first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub generated_at: String,
+    pub from: String,
+    pub to: String,
+    pub events: usize,
+    pub policies: Vec<PolicyReport>,
+}
+
+/// Replay every policy; reports sorted by net delta, best first
+pub fn simulate(history: &AttemptHistory, policies: &[CooldownPolicy], from: NaiveDate, to: NaiveDate) -> SimulationReport {
+    let mut reports: Vec<PolicyReport> = policies.iter().map(|p| history.replay(*p)).collect();
+    reports.sort_by(|a, b| b.net_delta_usd.partial_cmp(&a.net_delta_usd).unwrap_or(std::cmp::Ordering::Equal));
+    SimulationReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        from: from.format("%Y%m%d").to_string(),
+        to: to.format("%Y%m%d").to_string(),
+        events: history.len(),
+        policies: reports,
+    }
+}
+
+/// Fixed-width table for the log
+pub fn format_table(report: &SimulationReport) -> String {
+    let mut out = format!(
+        "{:<16} {:>8} {:>10} {:>8} {:>8} {:>11} {:>11} {:>11} {:>11}\n",
+        "policy", "attempts", "suppressed", "supp_ok", "cf_sent", "gas_saved", "forgone", "net_delta", "net"
+    );
+    for p in &report.policies {
+        out.push_str(&format!(
+            "{:<16} {:>8} {:>10} {:>8} {:>8} {:>11.4} {:>11.4} {:>11.4} {:>11.4}\n",
+            p.policy, p.attempts, p.suppressed, p.suppressed_successes, p.counterfactual_attempts,
+            p.gas_saved_usd, p.profit_forgone_usd, p.net_delta_usd, p.net_profit_usd
+        ));
+    }
+    out
+}
+
+/// Write `{research_dir}/cooldown_sim_YYYYMMDD_HHMMSS.json`; returns the path
+pub fn write_report(research_dir: &str, report: &SimulationReport) -> Result<PathBuf> {
+    std::fs::create_dir_all(research_dir).with_context(|| format!("creating {}", research_dir))?;
+    let path = PathBuf::from(research_dir)
+        .join(format!("cooldown_sim_{}.json", chrono::Utc::now().format("%Y%m%d_%H%M%S")));
+    std::fs::write(&path, serde_json::to_string_pretty(report)?)
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(block: u64, pair: &str, effect: CooldownEffect, gas: f64, net: f64, tx: Option<&str>) -> AttemptRecord {
+        AttemptRecord {
+            timestamp: String::new(),
+            block,
+            pair: pair.to_string(),
+            buy_dex: DexType::UniswapV3_005,
+            sell_dex: DexType::UniswapV3_030,
+            outcome: if effect == CooldownEffect::Reset { "Success" } else { "AtomicRevert" }.to_string(),
+            cooldown: effect,
+            tx_hash: tx.map(str::to_string),
+            gas_cost_usd: gas,
+            net_profit_usd: net,
+        }
+    }
+
+    fn day() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 15).unwrap()
+    }
+
+    fn policy(s: &str) -> CooldownPolicy {
+        s.parse().unwrap()
+    }
+
+    /// Route A is dead (reverts every block), route B fails once then recovers.
+    /// A short cooldown kills A's gas burn and lets B back in time; no cooldown
+    /// burns gas on A; a long one also locks B out of its run of successes.
+    #[test]
+    fn test_known_optimum() {
+        let mut attempts: Vec<AttemptRecord> = (1..=40)
+            .map(|b| attempt(b, "A/USDC", CooldownEffect::Escalate, 0.10, -0.10, None))
+            .collect();
+        attempts.push(attempt(10, "B/USDC", CooldownEffect::Escalate, 0.10, -0.10, None));
+        attempts.extend((12..=20).map(|b| attempt(b, "B/USDC", CooldownEffect::Reset, 0.10, 1.0, None)));
+        let history = AttemptHistory::new(&attempts, &[], 0.05);
+
+        let policies = [policy("0:5:1800"), policy("2:5:1800"), policy("100:5:1800")];
+        let report = simulate(&history, &policies, day(), day());
+        let best = &report.policies[0];
+        assert_eq!(best.policy, "2:5:1800");
+        // A: fails at 1 (→3), 3 (→13), 13 (→63) — 37 suppressed; B untouched
+        assert_eq!(best.attempts, 3 + 10);
+        assert_eq!(best.suppressed, 37);
+        assert_eq!(best.suppressed_successes, 0);
+        assert!((best.gas_saved_usd - 3.7).abs() < 1e-9);
+        assert!((best.net_delta_usd - 3.7).abs() < 1e-9);
+        assert!((best.net_profit_usd - 8.6).abs() < 1e-9);
+
+        let none = report.policies.iter().find(|p| p.policy == "0:5:1800").unwrap();
+        assert_eq!(none.suppressed, 0);
+        assert!(none.net_delta_usd.abs() < 1e-9);
+        assert!((none.net_profit_usd - 4.9).abs() < 1e-9);
+
+        let long = &report.policies[2];
+        assert_eq!(long.policy, "100:5:1800");
+        assert_eq!(long.suppressed, 39 + 9);
+        assert_eq!(long.suppressed_successes, 9);
+        assert!((long.profit_forgone_usd - 9.9).abs() < 1e-9);
+        assert!((long.net_delta_usd + 5.1).abs() < 1e-9);
+        assert!((long.net_profit_usd + 0.2).abs() < 1e-9);
+        // Every policy accounts for the same no-cooldown baseline
+        for p in &report.policies {
+            assert!((p.net_profit_usd - p.net_delta_usd - 4.9).abs() < 1e-9, "{:?}", p);
+        }
+
+        let table = format_table(&report);
+        assert_eq!(table.lines().count(), 4);
+        assert!(table.lines().nth(1).unwrap().starts_with("2:5:1800"));
+    }
+
+    /// Cooled counterfactuals are sent by looser policies; a reversal only
+    /// counts when the policy executed the reversed tx
+    #[test]
+    fn test_counterfactual_and_reversal() {
+        let attempts = vec![
+            attempt(1, "A/USDC", CooldownEffect::Escalate, 0.10, -0.10, None),
+            attempt(5, "B/USDC", CooldownEffect::Reset, 0.10, 2.0, Some("0xb")),
+            AttemptRecord::reversal("B/USDC", DexType::UniswapV3_005, DexType::UniswapV3_030, "0xb".into(), 2.0, 15),
+        ];
+        let cooled = PrescreenRecord {
+            timestamp: String::new(),
+            block: 2,
+            pair: "A/USDC".to_string(),
+            buy_dex: DexType::UniswapV3_005,
+            sell_dex: DexType::UniswapV3_030,
+            buy_pool: None,
+            sell_pool: None,
+            trade_size: ethers::types::U256::from(100_000_000u64),
+            trade_size_usd: 100.0,
+            estimated_profit_usd: 0.5,
+            spread_percent: 0.5,
+            both_legs_valid: true,
+            buy_quoted_out: ethers::types::U256::zero(),
+            sell_quoted_out: ethers::types::U256::zero(),
+            quoted_profit_raw: 300_000, // $0.30
+            error: None,
+            pool_snapshots: None,
+            cooled: true,
+        };
+        let unprofitable = PrescreenRecord { block: 3, quoted_profit_raw: -5, ..cooled.clone() };
+        let history = AttemptHistory::new(&attempts, &[cooled, unprofitable], 0.05);
+        assert_eq!(history.len(), 4);
+
+        let none = history.replay(policy("0"));
+        assert_eq!(none.counterfactual_attempts, 1);
+        assert_eq!(none.attempts, 3);
+        // -0.10 + (0.30 - 0.05) + 2.0 - 2.0
+        assert!((none.net_profit_usd - 0.15).abs() < 1e-9);
+
+        let live = history.replay(policy("10"));
+        assert_eq!(live.suppressed, 1);
+        assert_eq!(live.counterfactual_attempts, 0);
+        assert!((live.profit_forgone_usd - 0.30).abs() < 1e-9);
+        assert!((live.net_delta_usd + 0.25).abs() < 1e-9);
+
+        // The reversed tx re-cools B: a B attempt right after is suppressed
+        let mut attempts = attempts;
+        attempts.push(attempt(16, "B/USDC", CooldownEffect::Reset, 0.10, 1.0, Some("0xc")));
+        let replay = AttemptHistory::new(&attempts, &[], 0.05).replay(policy("10"));
+        assert_eq!(replay.suppressed, 1);
+        assert_eq!(replay.suppressed_successes, 1);
+    }
+
+    #[test]
+    fn test_policy_parse() {
+        assert_eq!(policy("10"), CooldownPolicy::live(10));
+        assert_eq!(policy("2:3:600"), CooldownPolicy { initial: 2, factor: 3, max: 600 });
+        assert_eq!(policy("2:3:600").to_string(), "2:3:600");
+        assert!("2:0".parse::<CooldownPolicy>().is_err());
+        assert!("a:b".parse::<CooldownPolicy>().is_err());
+        assert!("1:2:3:4".parse::<CooldownPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_load_date_range() {
+        let dir = std::env::temp_dir().join(format!("dexarb_cooldown_sim_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let line = |b: u64| serde_json::to_string(&attempt(b, "A/USDC", CooldownEffect::Escalate, 0.1, -0.1, None)).unwrap();
+        std::fs::write(dir.join("route_attempts_20261014.jsonl"), format!("{}\n", line(1))).unwrap();
+        std::fs::write(dir.join("route_attempts_20261015.jsonl"), format!("{}\n{}\n", line(2), line(3))).unwrap();
+        std::fs::write(dir.join("route_attempts_20261016.jsonl"), format!("{}\n", line(4))).unwrap();
+
+        let history = AttemptHistory::load(&dir, NaiveDate::from_ymd_opt(2026, 10, 14).unwrap(), day(), 0.05).unwrap();
+        assert_eq!(history.len(), 3);
+        let report = simulate(&history, &[policy("0")], day(), day());
+        let path = write_report(dir.to_str().unwrap(), &report).unwrap();
+        let back: SimulationReport = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(back.policies, report.policies);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
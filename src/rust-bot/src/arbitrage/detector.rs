@@ -13,29 +13,70 @@
 //! Modified: 2026-01-29 - V3-only: drop V2 from scan, exclude 1% fee tier
 //! Modified: 2026-01-29 - Phase 1.1: whitelist/blacklist filtering
 //! Modified: 2026-01-31 - Multi-chain: quote token + gas cost from config instead of constants
-
+//! Modified: 2026-10-16 - Trade size at quote token decimals; WBTC (8 dec) in legacy V2 sizing
+//! Modified: 2026-10-16 - Opportunities carry both legs' raw pool state (post-mortem snapshots)
+//! Modified: 2026-10-16 - trade_size_usd on every opportunity; sizes logged with explicit units
+//! Modified: 2026-10-16 - Both legs' pool liquidity recorded (execution-time liquidity guard)
+//! Modified: 2026-10-16 - trade_size bound to its quote token (QuoteAmount)
+//! Modified: 2026-10-16 - Triangular detection pass (quote → A → B → quote over three pools)
+//! Modified: 2026-10-16 - Opportunities flagged flash_eligible when FLASHLOAN_ENABLED
+//! Modified: 2026-10-16 - Pools marked stale after a reorg are skipped until resynced
+//! Modified: 2026-10-16 - Curve stable pools (CURVE_ENABLED) compared within their own stablecoin pair
+//! Modified: 2026-10-16 - Pools quarantined by pool health are skipped until reinstated
+//! Modified: 2026-10-16 - Size-adjusted spread/profit from both legs' pool depth (flat 1% slippage only without depth)
+//! Modified: 2026-10-16 - Unified and triangular scans iterate PoolAdapters (no per-protocol branches)
+//! Modified: 2026-10-16 - drop_stale_opportunities(): legs older than MAX_POOL_AGE_BLOCKS dropped before execution
+//! Modified: 2026-10-16 - SPLIT_ROUTING: sell side split across the two best sell pools when one can't absorb the size
+//! Modified: 2026-10-16 - MIN_PROFIT_ADAPTIVE: per-route minimum profit from the shared ThresholdModel
+//! Modified: 2026-10-16 - Triangular route labels from the TokenRegistry when attached
+//! Modified: 2026-10-16 - Test: Algebra's live pool fee (not a fixed tier) decides the round-trip fee
+//! Modified: 2026-10-16 - Whitelist pair/pool trade limits: most restrictive of override and global per route
+//! Modified: 2026-10-16 - Quote-priced USD conversions: legacy path prices profit at token0 decimals and the quote price, triangle minProfit at the quote price
+//! Modified: 2026-10-16 - Test: golden pool state shared with the paper live-parity test
+//! Modified: 2026-10-16 - Two-leg and split spreads in fixed point (Q64.96 prices, WAD spreads, rounded against the route)
+//! Modified: 2026-10-16 - confirm_spreads(): SPREAD_CONFIRM_BLOCKS multi-block confirmation over a per-route SpreadHistory
+//! Modified: 2026-10-16 - DODO pools: pair legs only (no split or triangle leg — no ArbExecutor leg yet)
+//! Modified: 2026-10-16 - scan_opportunities_on(): scan a shadow pool state (MIDBLOCK_SCAN)
+//! Modified: 2026-10-16 - Opportunities flagged flash_v2_eligible when FLASH_V2_ENABLED
+//! Modified: 2026-10-17 - Pair, split and triangle routes sized at the quote price (MAX_TRADE_SIZE_USD / WETH price, not 1 WETH per $)
+
+use super::flashloan;
+use super::split;
+use super::threshold::ThresholdModel;
+use crate::filters::whitelist::EffectiveLimits;
 use crate::filters::WhitelistFilter;
-use crate::pool::{PoolStateManager, PriceCalculator};
-use crate::types::{ArbitrageOpportunity, BotConfig, DexType, PoolState, TradingPair};
+use crate::pool::fixed_point::{executable_spread_wad, return_wad, wad_to_f64, PriceX96};
+use crate::pool::{PoolAdapter, PoolStateManager, PriceCalculator};
+use crate::quote_amount::{QuoteAmount, QuoteToken};
+use crate::token_registry::TokenRegistry;
+use crate::types::{
+    format_usd, ArbitrageOpportunity, BotConfig, DexType,
+    LegSnapshots, next_opportunity_id, PoolState, PoolStateSnapshot, SplitSell, TradingPair, TriangularLeg, TriangularOpportunity,
+};
 use ethers::types::{Address, U256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
 /// Minimum spread percentage to consider (covers fees)
 const MIN_SPREAD_PERCENT: f64 = 0.3;
 
-/// V2 DEX fee percentage (Quickswap, Sushiswap, Apeswap)
-const V2_FEE_PERCENT: f64 = 0.30;
+/// Triangular routes pay for three swaps instead of two
+const TRIANGULAR_GAS_FACTOR: f64 = 1.5;
 
-/// Unified pool representation for comparing V2 and V3
-#[derive(Debug, Clone)]
+/// Split routes too: one buy, two sells
+const SPLIT_GAS_FACTOR: f64 = 1.5;
+
+/// A pool in the unified cross-protocol comparison: the adapter plus the
+/// values the scan reads from it once
 struct UnifiedPool {
-    dex: DexType,
+    adapter: Box<dyn PoolAdapter>,
+    /// Decimal-adjusted (logging and the opportunity's buy/sell prices)
     price: f64,
+    /// Raw, exact: what the pools are compared on
+    price_x96: PriceX96,
     fee_percent: f64,  // Single swap fee
-    address: Address,
-    pair: TradingPair,
-    token0_decimals: u8,
-    token1_decimals: u8,
+    fee_wad: U256,
     liquidity: u128,
     /// The actual quote token address this pool uses (USDC.e or native USDC).
     /// Pools with different quote tokens must NOT be compared for arb —
@@ -43,11 +84,123 @@ struct UnifiedPool {
     quote_token: Address,
 }
 
+/// Pool as an undirected edge of the token graph (triangular scan)
+#[derive(Debug, Clone)]
+struct TrianglePool {
+    dex: DexType,
+    address: Address,
+    pair: TradingPair,
+    /// token1 per token0, decimal-adjusted
+    price: f64,
+    fee_percent: f64,
+    token0_decimals: u8,
+    token1_decimals: u8,
+    liquidity: u128,
+    snapshot: PoolStateSnapshot,
+}
+
+impl TrianglePool {
+    /// The pool's other token, if `token` is one of its two
+    fn other(&self, token: &Address) -> Option<Address> {
+        if *token == self.pair.token0 {
+            Some(self.pair.token1)
+        } else if *token == self.pair.token1 {
+            Some(self.pair.token0)
+        } else {
+            None
+        }
+    }
+
+    fn decimals_of(&self, token: &Address) -> u8 {
+        if *token == self.pair.token0 { self.token0_decimals } else { self.token1_decimals }
+    }
+
+    /// Swap `token_in` for the other token
+    fn leg(&self, token_in: Address) -> TriangularLeg {
+        let (token_out, rate) = if token_in == self.pair.token0 {
+            (self.pair.token1, self.price)
+        } else {
+            (self.pair.token0, 1.0 / self.price)
+        };
+        TriangularLeg {
+            dex: self.dex,
+            pool_address: self.address,
+            token_in,
+            token_out,
+            rate,
+            fee_percent: self.fee_percent,
+            liquidity: self.liquidity,
+            snapshot: self.snapshot.clone(),
+        }
+    }
+}
+
+/// Route a spread is tracked under: (pair, buy dex, sell dex)
+type SpreadRoute = (String, DexType, DexType);
+
+/// Per-route spread history over the last `window` blocks
+/// (SPREAD_CONFIRM_BLOCKS / SPREAD_HISTORY_BLOCKS). A route's spread is
+/// confirmed once it has been seen in `confirm_blocks` consecutive blocks;
+/// routes not seen for `window` blocks are evicted.
+#[derive(Debug)]
+struct SpreadHistory {
+    confirm_blocks: u64,
+    window: u64,
+    /// Newest last: (block, spread %), at most `window` entries
+    routes: HashMap<SpreadRoute, VecDeque<(u64, f64)>>,
+}
+
+impl SpreadHistory {
+    fn new(confirm_blocks: u64, window: u64) -> Self {
+        Self {
+            confirm_blocks: confirm_blocks.max(1),
+            window: window.max(confirm_blocks).max(1),
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Record a route's spread at `block` (a rescan of the same block
+    /// replaces the entry); returns the spread's age in blocks — the number
+    /// of consecutive blocks up to `block` it has been seen in
+    fn record(&mut self, route: SpreadRoute, block: u64, spread_percent: f64) -> u64 {
+        let history = self.routes.entry(route).or_default();
+        match history.back_mut() {
+            Some(last) if last.0 == block => last.1 = spread_percent,
+            _ => history.push_back((block, spread_percent)),
+        }
+        while history.len() as u64 > self.window {
+            history.pop_front();
+        }
+        let mut age = 0;
+        for (expected, (seen, _)) in (0..=block).rev().zip(history.iter().rev()) {
+            if *seen != expected {
+                break;
+            }
+            age += 1;
+        }
+        age
+    }
+
+    /// Drop routes whose latest sighting is `window` or more blocks old
+    fn evict(&mut self, current_block: u64) {
+        let window = self.window;
+        self.routes.retain(|_, history| {
+            history.back().is_some_and(|(seen, _)| current_block.saturating_sub(*seen) < window)
+        });
+    }
+}
+
 /// Opportunity detector for cross-DEX arbitrage
 pub struct OpportunityDetector {
     config: BotConfig,
     state_manager: PoolStateManager,
     whitelist: WhitelistFilter,
+    /// Per-route minimum profit (MIN_PROFIT_ADAPTIVE); None = MIN_PROFIT_USD everywhere
+    threshold_model: Option<ThresholdModel>,
+    /// Multi-block spread confirmation (SPREAD_CONFIRM_BLOCKS)
+    spread_history: SpreadHistory,
+    /// On-chain token symbols for route labels; None = from pair symbols
+    tokens: Option<Arc<TokenRegistry>>,
 }
 
 impl OpportunityDetector {
@@ -68,10 +221,65 @@ impl OpportunityDetector {
             }
         };
 
+        let spread_history = SpreadHistory::new(config.spread_confirm_blocks, config.spread_history_blocks);
         Self {
             config,
             state_manager,
             whitelist,
+            threshold_model: None,
+            spread_history,
+            tokens: None,
+        }
+    }
+
+    /// Hold each route to its adaptive minimum profit (main.rs keeps the model updated)
+    pub fn with_threshold_model(mut self, model: ThresholdModel) -> Self {
+        self.threshold_model = Some(model);
+        self
+    }
+
+    /// Label tokens with the symbols their contracts report
+    pub fn with_token_registry(mut self, tokens: Arc<TokenRegistry>) -> Self {
+        self.tokens = Some(tokens);
+        self
+    }
+
+    /// MAX_TRADE_SIZE_USD / MIN_PROFIT_USD / MAX_SLIPPAGE_PERCENT
+    fn global_limits(&self) -> EffectiveLimits {
+        EffectiveLimits::global(&self.config)
+    }
+
+    /// Limits of a route: the global ones tightened by the whitelist
+    /// overrides of its pairs and pools
+    fn route_limits(&self, pairs: &[&str], pools: &[Address]) -> EffectiveLimits {
+        self.whitelist.limits_for(pairs, pools).effective(self.global_limits())
+    }
+
+    /// Carry a route's overrides to the executor (minProfit, min_out allowance)
+    fn stamp_limits(&self, opp: &mut ArbitrageOpportunity, limits: &EffectiveLimits) {
+        let global = self.global_limits();
+        if limits.min_profit_usd > global.min_profit_usd {
+            opp.min_profit_usd = Some(limits.min_profit_usd);
+        }
+        if limits.max_slippage_percent < global.max_slippage_percent {
+            opp.max_slippage_percent = Some(limits.max_slippage_percent);
+        }
+    }
+
+    /// Startup: each configured pair's effective limits (pool overrides can
+    /// tighten individual routes further)
+    pub fn log_pair_limits(&self) {
+        let pool_overrides = self.whitelist.pool_limit_count();
+        for pair_config in &self.config.pairs {
+            let limits = self.route_limits(&[pair_config.symbol.as_str()], &[]);
+            let marker = if limits == self.global_limits() { "global" } else { "override" };
+            info!(
+                "Pair limits {}: max size ${:.0}, min profit ${:.2}, max slippage {:.2}% ({})",
+                pair_config.symbol, limits.max_trade_size_usd, limits.min_profit_usd, limits.max_slippage_percent, marker
+            );
+        }
+        if pool_overrides > 0 {
+            info!("Pair limits: {} whitelisted pool(s) carry their own overrides", pool_overrides);
         }
     }
 
@@ -88,6 +296,20 @@ impl OpportunityDetector {
             opportunities.extend(v3_opps);
         }
 
+        // Adaptive minimum profit: routes that revert often must clear more
+        if let Some(model) = &self.threshold_model {
+            opportunities = model.apply(opportunities);
+        }
+
+        // Flashloan funding: flag routes still above the floor after the premium
+        if self.config.flashloan_enabled {
+            flashloan::mark_flash_eligible(&mut opportunities, self.config.min_profit_usd);
+        }
+        // V2 flash swaps: flag V2-bought routes still above the floor once the pair is repaid
+        if self.config.flash_v2_enabled {
+            flashloan::mark_flash_v2_eligible(&mut opportunities, self.config.min_profit_usd);
+        }
+
         // Sort by estimated profit descending
         opportunities.sort_by(|a, b| {
             b.estimated_profit
@@ -98,6 +320,86 @@ impl OpportunityDetector {
         opportunities
     }
 
+    /// scan_opportunities() against `state` instead of the live pool state
+    /// (mid-block re-scan over a simulated shadow copy); the live state is
+    /// back in place when this returns
+    pub fn scan_opportunities_on(&mut self, state: PoolStateManager) -> Vec<ArbitrageOpportunity> {
+        let live = std::mem::replace(&mut self.state_manager, state);
+        let opportunities = self.scan_opportunities();
+        self.state_manager = live;
+        opportunities
+    }
+
+    /// Drop opportunities with a leg whose pool state is more than
+    /// MAX_POOL_AGE_BLOCKS behind `current_block` (a laggy sync would have us
+    /// trade on prices the chain has moved past). 0 = no age check.
+    pub fn drop_stale_opportunities(
+        &self,
+        opportunities: Vec<ArbitrageOpportunity>,
+        current_block: u64,
+    ) -> Vec<ArbitrageOpportunity> {
+        let max_age = self.config.max_pool_age_blocks;
+        if max_age == 0 {
+            return opportunities;
+        }
+        opportunities
+            .into_iter()
+            .filter(|opp| {
+                let legs = opp
+                    .buy_pool_address
+                    .iter()
+                    .chain(opp.sell_pool_address.iter())
+                    .copied()
+                    .chain(opp.route_legs.iter().flatten().map(|leg| leg.pool_address))
+                    .chain(opp.split_sell.iter().map(|split| split.pool_address));
+                for pool in legs {
+                    let age = self.state_manager.pool_age_blocks(&pool, current_block).unwrap_or(0);
+                    if age > max_age {
+                        info!(
+                            "STALE: {} {}→{} dropped — pool {:?} state {} blocks old (max {})",
+                            opp.pair.symbol, opp.buy_dex, opp.sell_dex, pool, age, max_age
+                        );
+                        return false;
+                    }
+                }
+                true
+            })
+            .collect()
+    }
+
+    /// Keep opportunities whose route has shown a spread for at least
+    /// SPREAD_CONFIRM_BLOCKS consecutive blocks up to `current_block` (1 =
+    /// every opportunity passes). Every route is recorded, so the spread's
+    /// age is logged either way.
+    pub fn confirm_spreads(
+        &mut self,
+        opportunities: Vec<ArbitrageOpportunity>,
+        current_block: u64,
+    ) -> Vec<ArbitrageOpportunity> {
+        let confirm_blocks = self.spread_history.confirm_blocks;
+        let confirmed = opportunities
+            .into_iter()
+            .filter(|opp| {
+                let route = (opp.pair.symbol.clone(), opp.buy_dex, opp.sell_dex);
+                let age = self.spread_history.record(route, current_block, opp.spread_percent);
+                if age < confirm_blocks {
+                    info!(
+                        "UNCONFIRMED: {} {}→{} spread {:.3}% age {} block(s) (need {})",
+                        opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.spread_percent, age, confirm_blocks
+                    );
+                    return false;
+                }
+                debug!(
+                    "Spread {} {}→{} {:.3}% age {} block(s)",
+                    opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.spread_percent, age
+                );
+                true
+            })
+            .collect();
+        self.spread_history.evict(current_block);
+        confirmed
+    }
+
     /// Check a pair using V3-only pool comparison.
     /// Returns ALL profitable fee tier combinations (not just the best) so the
     /// executor can fall through Quoter-rejected thin pools to viable ones.
@@ -116,111 +418,49 @@ impl OpportunityDetector {
     ///
     /// Execute flow: token0 → token1 on buy_pool, then token1 → token0 on sell_pool.
     fn check_pair_unified(&self, pair_symbol: &str) -> Vec<ArbitrageOpportunity> {
-        // Collect ALL pools (V3 + V2) into unified format for cross-protocol comparison
+        // Collect ALL pools (V3 + V2 + Curve) into unified format for cross-protocol comparison.
+        // Every adapter's spot_price() is token1 per token0, decimal-adjusted (V3
+        // tick-based format; V2 price_adjusted(); Curve marginal get_dy before the
+        // fee), which is what makes cross-protocol comparison correct.
         let mut unified_pools: Vec<UnifiedPool> = Vec::new();
+        let captured_at = unix_now();
 
-        // Add V3 pools with whitelist + liquidity filtering (Phase 1.1)
-        for pool in self.state_manager.get_v3_pools_for_pair(pair_symbol) {
-            let price = pool.price();
-            if price <= 0.0 || price >= 1e15 {
-                continue; // Sanity check
-            }
-
-            // Phase 1.1: Whitelist/blacklist check (covers fee tier blacklist,
-            // pool blacklist, pair blacklist, and strict whitelist enforcement).
-            // This supersedes the old `fee >= 10000` check — the 1% tier is
-            // blacklisted in the whitelist config.
-            if !self.whitelist.is_pool_allowed(&pool.address, pool.fee, pair_symbol) {
+        for adapter in self.state_manager.adapters_for_pair(pair_symbol) {
+            // Curve stable pools only with CURVE_ENABLED
+            if adapter.dex().is_curve() && !self.config.curve_enabled {
                 continue;
             }
 
-            let fee_percent = pool.fee as f64 / 10000.0;  // 500 -> 0.05%
+            let price = adapter.spot_price();
+            let price_x96 = adapter.spot_price_x96();
+            if price <= 0.0 || price >= 1e15 || price_x96.is_zero() {
+                continue; // Sanity check
+            }
 
-            // Phase 1.1: Per-pool / per-tier minimum liquidity
-            // Replaces the old flat `< 1000` check with tier-aware thresholds.
-            let min_liq = self.whitelist.min_liquidity_for(&pool.address, pool.fee);
-            if pool.liquidity < min_liq {
-                debug!(
-                    "Skipping {} {:?} - liquidity {} below threshold {} (fee tier {})",
-                    pair_symbol, pool.dex, pool.liquidity, min_liq, pool.fee
-                );
+            if !self.pool_eligible(adapter.as_ref(), pair_symbol) {
                 continue;
             }
 
             // Determine which quote token this pool uses (USDC.e or native USDC).
-            // V3 pools sort token0 < token1 by address. The quote token is whichever
+            // Pools sort token0 < token1 by address. The quote token is whichever
             // of token0/token1 matches a recognized quote token address.
-            let qt = if self.config.is_quote_token(&pool.pair.token0) {
-                pool.pair.token0
-            } else if self.config.is_quote_token(&pool.pair.token1) {
-                pool.pair.token1
+            let pair = adapter.pair();
+            let qt = if self.config.is_quote_token(&pair.token0) {
+                pair.token0
+            } else if self.config.is_quote_token(&pair.token1) {
+                pair.token1
             } else {
                 continue; // Neither token is a known quote token — skip
             };
 
             unified_pools.push(UnifiedPool {
-                dex: pool.dex,
                 price,
-                fee_percent,
-                address: pool.address,
-                pair: pool.pair.clone(),
-                token0_decimals: pool.token0_decimals,
-                token1_decimals: pool.token1_decimals,
-                liquidity: pool.liquidity,
-                quote_token: qt,
-            });
-        }
-
-        // Add V2 pools (V2↔V3 cross-protocol arbitrage).
-        // V2 price uses decimal-adjusted reserves → same format as V3 tick-based price.
-        // V2 fee is always 0.30% (hardcoded in constant-product formula).
-        for pool in self.state_manager.get_pools_for_pair(pair_symbol) {
-            // Only include new V2 DEX types (QuickSwapV2, SushiSwapV2)
-            // Legacy Uniswap/Sushiswap/Quickswap/Apeswap variants are not V2↔V3 aware
-            if !matches!(pool.dex, DexType::QuickSwapV2 | DexType::SushiSwapV2) {
-                continue;
-            }
-
-            // Whitelist check (V2 pools use fee_tier=3000 in whitelist JSON)
-            if !self.whitelist.is_pool_allowed(&pool.address, 3000, pair_symbol) {
-                continue;
-            }
-
-            // Decimal-adjusted price — same format as V3 tick-based price.
-            // CRITICAL: Uses price_adjusted(), NOT price() (raw ratio without decimals).
-            // This is what makes V2↔V3 cross-protocol comparison correct.
-            let price = pool.price_adjusted();
-            if price <= 0.0 || price >= 1e15 {
-                continue; // Sanity check
-            }
-
-            // V2 "liquidity" proxy: smaller raw reserve value.
-            // V2 pools are pre-verified by verify_v2_pools.py (whitelist v2_ready),
-            // so this is a rough floor check only.
-            let liquidity = std::cmp::min(
-                pool.reserve0.low_u128(),
-                pool.reserve1.low_u128(),
-            );
-
-            // Determine quote token for this V2 pool
-            let qt = if self.config.is_quote_token(&pool.pair.token0) {
-                pool.pair.token0
-            } else if self.config.is_quote_token(&pool.pair.token1) {
-                pool.pair.token1
-            } else {
-                continue; // Neither token is a known quote token — skip
-            };
-
-            unified_pools.push(UnifiedPool {
-                dex: pool.dex,
-                price,
-                fee_percent: V2_FEE_PERCENT, // 0.30 = 0.30% (same format as V3: 500bps → 0.05)
-                address: pool.address,
-                pair: pool.pair.clone(),
-                token0_decimals: pool.token0_decimals,
-                token1_decimals: pool.token1_decimals,
-                liquidity,
+                price_x96,
+                fee_percent: adapter.fee_fraction() * 100.0, // 500bps → 0.05
+                fee_wad: adapter.fee_wad(),
+                liquidity: adapter.liquidity(),
                 quote_token: qt,
+                adapter,
             });
         }
 
@@ -244,9 +484,17 @@ impl OpportunityDetector {
                     continue;
                 }
 
+                // Curve pools only hold stablecoins: compare them strictly
+                // within the same stablecoin pair (both token addresses equal)
+                let (pair_a, pair_b) = (pool_a.adapter.pair(), pool_b.adapter.pair());
+                let curve_leg = pool_a.adapter.dex().is_curve() || pool_b.adapter.dex().is_curve();
+                if curve_leg && (pair_a.token0, pair_a.token1) != (pair_b.token0, pair_b.token1) {
+                    continue;
+                }
+
                 // Determine if the quote token is V3 token0 for this pool pair.
                 // Both pools share the same quote token, so checking pool_a suffices.
-                let quote_is_token0 = pool_a.pair.token0 == pool_a.quote_token;
+                let quote_is_token0 = pair_a.token0 == pool_a.quote_token;
 
                 // V3 price = token1/token0
                 //
@@ -259,46 +507,58 @@ impl OpportunityDetector {
                 //   Higher price = more quote per base = expensive base → SELL here
                 let (buy_pool, sell_pool) = if quote_is_token0 {
                     // Current behavior: buy where price is higher
-                    if pool_a.price > pool_b.price {
+                    if pool_a.price_x96 > pool_b.price_x96 {
                         (pool_a, pool_b)
                     } else {
                         (pool_b, pool_a)
                     }
                 } else {
                     // Reversed: buy where price is lower (cheaper base in quote terms)
-                    if pool_a.price < pool_b.price {
+                    if pool_a.price_x96 < pool_b.price_x96 {
                         (pool_a, pool_b)
                     } else {
                         (pool_b, pool_a)
                     }
                 };
 
-                // Calculate midmarket spread (before fees)
-                // Always positive: distance between buy and sell prices
-                let midmarket_spread = if quote_is_token0 {
-                    // buy has higher price
-                    (buy_pool.price - sell_pool.price) / sell_pool.price
-                } else {
-                    // sell has higher price
-                    (sell_pool.price - buy_pool.price) / buy_pool.price
-                };
-
-                // Calculate round-trip fee
-                let round_trip_fee = (buy_pool.fee_percent + sell_pool.fee_percent) / 100.0;
-
-                // Calculate executable spread (after fees)
-                let executable_spread = midmarket_spread - round_trip_fee;
-
-                if executable_spread <= 0.0 {
+                // Executable spread: midmarket distance between the two prices
+                // (always relative to the lower one) less the round-trip fee,
+                // exact and rounded down (WAD)
+                let round_trip_fee = buy_pool.fee_wad + sell_pool.fee_wad;
+                let Some(executable_spread) = executable_spread_wad(buy_pool.price_x96, sell_pool.price_x96, round_trip_fee) else {
                     continue;
-                }
+                };
 
-                // Estimate profit
-                let gross = executable_spread * self.config.max_trade_size_usd;
-                let slippage_estimate = gross * 0.01;  // 1% slippage estimate (V3 concentrated liquidity has <0.01% at $140-500)
-                let net_profit = gross - self.config.estimated_gas_cost_usd - slippage_estimate;
+                // Quote raw units at the quote token's decimals (USDC = 6, DAI = 18)
+                let (buy, sell) = (buy_pool.adapter.as_ref(), sell_pool.adapter.as_ref());
+                let limits = self.route_limits(&[pair_symbol], &[buy.address(), sell.address()]);
+                let (token0_decimals, token1_decimals) = buy.decimals();
+                let quote_decimals = if quote_is_token0 { token0_decimals } else { token1_decimals };
+                let quote_price_usd = self.config.quote_price_usd(&pool_a.quote_token);
+                let trade_size = buy.pair().quote(quote_is_token0, quote_decimals)
+                    .from_usd(limits.max_trade_size_usd, quote_price_usd);
+                let trade_size_usd = trade_size.to_usd(quote_price_usd);
+
+                // Estimate profit at the actual trade size: both legs swapped on
+                // their pool depth (fees and price impact included). The flat 1%
+                // slippage estimate only stands in when a leg has no depth data.
+                let (executable_spread, net_profit) = match round_trip_out(buy, sell, trade_size.raw(), pool_a.quote_token) {
+                    Some(out) => {
+                        let Some(spread) = return_wad(trade_size.raw(), out) else {
+                            continue; // the round trip loses at this size
+                        };
+                        let spread = wad_to_f64(spread);
+                        (spread, spread * trade_size_usd - self.config.estimated_gas_cost_usd)
+                    }
+                    None => {
+                        let spread = wad_to_f64(executable_spread);
+                        let gross = spread * limits.max_trade_size_usd;
+                        let slippage_estimate = gross * 0.01;
+                        (spread, gross - self.config.estimated_gas_cost_usd - slippage_estimate)
+                    }
+                };
 
-                if net_profit < self.config.min_profit_usd {
+                if net_profit < limits.min_profit_usd {
                     continue;
                 }
 
@@ -306,53 +566,357 @@ impl OpportunityDetector {
                 // Ensure both pools can absorb the trade size
                 // V3 liquidity is in sqrt(token0 * token1) units (not USD)
                 // A rough minimum: trade_size_usd * 1e6 as a very conservative floor
-                let min_liquidity = (self.config.max_trade_size_usd * 1e6) as u128;
+                let min_liquidity = (limits.max_trade_size_usd * 1e6) as u128;
                 if buy_pool.liquidity < min_liquidity || sell_pool.liquidity < min_liquidity {
                     debug!(
                         "Skipping {} {:?}<->{:?} - pool liquidity too low for ${:.0} trade: buy_liq={}, sell_liq={}",
-                        pair_symbol, buy_pool.dex, sell_pool.dex,
-                        self.config.max_trade_size_usd, buy_pool.liquidity, sell_pool.liquidity
+                        pair_symbol, buy.dex(), sell.dex(),
+                        limits.max_trade_size_usd, buy_pool.liquidity, sell_pool.liquidity
                     );
                     continue;
                 }
 
                 info!(
-                    "🎯 V3 OPPORTUNITY: {} | Buy {:?} ({:.2}%) @ {:.6} | Sell {:?} ({:.2}%) @ {:.6} | Spread {:.2}% | Net ${:.2}",
+                    "🎯 V3 OPPORTUNITY: {} | Buy {:?} ({:.2}%) @ {:.6} | Sell {:?} ({:.2}%) @ {:.6} | Spread {:.2}% | Size {} | Net {}",
                     pair_symbol,
-                    buy_pool.dex, buy_pool.fee_percent,
+                    buy.dex(), buy_pool.fee_percent,
                     buy_pool.price,
-                    sell_pool.dex, sell_pool.fee_percent,
+                    sell.dex(), sell_pool.fee_percent,
                     sell_pool.price,
                     executable_spread * 100.0,
-                    net_profit
+                    format_usd(trade_size_usd),
+                    format_usd(net_profit)
                 );
 
-                results.push(ArbitrageOpportunity {
-                    pair: buy_pool.pair.clone(),
-                    buy_dex: buy_pool.dex,
-                    sell_dex: sell_pool.dex,
-                    buy_price: buy_pool.price,
-                    sell_price: sell_pool.price,
-                    spread_percent: executable_spread * 100.0,
-                    estimated_profit: net_profit,
-                    trade_size: U256::from((self.config.max_trade_size_usd * 1e6) as u64),  // USDC has 6 decimals
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                    buy_pool_address: Some(buy_pool.address),
-                    sell_pool_address: Some(sell_pool.address),
-                    token0_decimals: buy_pool.token0_decimals,
-                    token1_decimals: buy_pool.token1_decimals,
-                    buy_pool_liquidity: Some(buy_pool.liquidity),
-                    quote_token_is_token0: quote_is_token0,
-                });
+                let mut opp = unified_opportunity(
+                    buy_pool, sell_pool, quote_is_token0, trade_size, trade_size_usd,
+                    executable_spread, net_profit, captured_at,
+                );
+                self.stamp_limits(&mut opp, &limits);
+                results.push(opp);
+            }
+        }
+
+        if self.config.split_routing {
+            results.extend(self.check_pair_split(pair_symbol, &unified_pools, captured_at));
+        }
+
+        results
+    }
+
+    /// SPLIT_ROUTING: per buy pool, when its best single sell pool would lose
+    /// more than MAX_SLIPPAGE_PERCENT to price impact at the full trade size,
+    /// the route with the base sold across that pool and the partner pool
+    /// whose optimal split returns the most. Emitted alongside the single-sell
+    /// routes (same registry key, so a merge keeps the more profitable one).
+    fn check_pair_split(&self, pair_symbol: &str, pools: &[UnifiedPool], captured_at: u64) -> Vec<ArbitrageOpportunity> {
+        let mut results = Vec::new();
+        // A split trade sends three swaps, like a triangle
+        let gas_cost_usd = self.config.estimated_gas_cost_usd * SPLIT_GAS_FACTOR;
+        // Pools are sized for the buy pool's limits; the three-pool route's own
+        // (tighter) limits are checked once the partner is known
+        let pool_limits = |pool: &UnifiedPool| self.route_limits(&[pair_symbol], &[pool.adapter.address()]);
+        let liquid = |pool: &UnifiedPool, size_usd: f64| pool.liquidity >= (size_usd * 1e6) as u128;
+
+        // Detection-only DEXes (Curve, Kyber) and legacy-only DODO have no executeArbSplit leg
+        let executable = |pool: &UnifiedPool| {
+            let dex = pool.adapter.dex();
+            !dex.is_detection_only() && !dex.is_legacy_only()
+        };
+        for (i, buy_pool) in pools.iter().enumerate().filter(|(_, p)| executable(p)) {
+            let size_usd = pool_limits(buy_pool).max_trade_size_usd;
+            if !liquid(buy_pool, size_usd) {
+                continue;
+            }
+            let buy = buy_pool.adapter.as_ref();
+            let pair = buy.pair();
+            let quote_is_token0 = pair.token0 == buy_pool.quote_token;
+            let base = if quote_is_token0 { pair.token1 } else { pair.token0 };
+            let (token0_decimals, token1_decimals) = buy.decimals();
+            let quote_decimals = if quote_is_token0 { token0_decimals } else { token1_decimals };
+            let quote_price_usd = self.config.quote_price_usd(&buy_pool.quote_token);
+            let trade_size = pair.quote(quote_is_token0, quote_decimals).from_usd(size_usd, quote_price_usd);
+            let Some(base_out) = buy.amount_out(trade_size.raw(), buy_pool.quote_token) else {
+                continue;
+            };
+
+            // Sell pools by single-sell output, best first
+            let mut sells: Vec<(&UnifiedPool, U256)> = pools
+                .iter()
+                .enumerate()
+                .filter(|&(j, p)| j != i && p.quote_token == buy_pool.quote_token && executable(p) && liquid(p, size_usd))
+                .filter_map(|(_, p)| p.adapter.amount_out(base_out, base).map(|out| (p, out)))
+                .collect();
+            if sells.len() < 2 {
+                continue;
+            }
+            sells.sort_by_key(|&(_, out)| std::cmp::Reverse(out));
+            let (sell_pool, single_out) = sells[0];
+            let impact = split::price_impact(sell_pool.adapter.as_ref(), base_out, base).unwrap_or(0.0);
+            let single_limits = self.route_limits(&[pair_symbol], &[buy.address(), sell_pool.adapter.address()]);
+            if impact * 100.0 <= single_limits.max_slippage_percent {
+                continue; // the best sell pool absorbs the full size
+            }
+
+            let best = sells[1..]
+                .iter()
+                .filter_map(|&(partner, _)| {
+                    split::optimal_split(sell_pool.adapter.as_ref(), partner.adapter.as_ref(), base_out, base)
+                        .filter(|&(share, _)| share > 0 && share < split::SPLIT_SCALE_BPS)
+                        .map(|(share, out)| (partner, share, out))
+                })
+                .max_by_key(|&(_, _, out)| out);
+            let Some((partner, share_bps, split_out)) = best.filter(|&(_, _, out)| out > single_out) else {
+                continue;
+            };
+            let limits = self.route_limits(
+                &[pair_symbol],
+                &[buy.address(), sell_pool.adapter.address(), partner.adapter.address()],
+            );
+            if limits.max_trade_size_usd < size_usd {
+                debug!(
+                    "Skipping {} split via {:?} - partner limits the size to ${:.0}",
+                    pair_symbol, partner.adapter.dex(), limits.max_trade_size_usd
+                );
+                continue;
+            }
+
+            let trade_size_usd = trade_size.to_usd(quote_price_usd);
+            let Some(spread) = return_wad(trade_size.raw(), split_out).map(wad_to_f64) else {
+                continue;
+            };
+            let net_profit = spread * trade_size_usd - gas_cost_usd;
+            if net_profit < limits.min_profit_usd {
+                continue;
+            }
+
+            info!(
+                "🎯 V3 SPLIT OPPORTUNITY: {} | Buy {:?} @ {:.6} | Sell {:?} {:.1}% + {:?} {:.1}% (single sell impact {:.2}%) | Spread {:.2}% | Size {} | Net {}",
+                pair_symbol,
+                buy.dex(), buy_pool.price,
+                sell_pool.adapter.dex(), f64::from(split::SPLIT_SCALE_BPS - share_bps) / 100.0,
+                partner.adapter.dex(), f64::from(share_bps) / 100.0,
+                impact * 100.0,
+                spread * 100.0,
+                format_usd(trade_size_usd),
+                format_usd(net_profit)
+            );
+            let mut opp = unified_opportunity(
+                buy_pool, sell_pool, quote_is_token0, trade_size, trade_size_usd, spread, net_profit, captured_at,
+            );
+            opp.split_sell = Some(SplitSell {
+                dex: partner.adapter.dex(),
+                pool_address: partner.adapter.address(),
+                price: partner.price,
+                liquidity: Some(partner.liquidity),
+                share_bps,
+            });
+            self.stamp_limits(&mut opp, &limits);
+            results.push(opp);
+        }
+        results
+    }
+
+    /// Phase 1.1 filters: not stale after a reorg or quarantined; V2 only
+    /// QuickSwapV2/SushiSwapV2 (legacy Uniswap/Sushiswap/Quickswap/Apeswap
+    /// variants are not V2↔V3 aware); whitelist/blacklist at the pool's fee
+    /// tier (V2: 3000, the V2 entry in the whitelist JSON; the 1% tier is
+    /// blacklisted in the whitelist config); V3 also the per-pool / per-tier
+    /// minimum liquidity.
+    fn pool_eligible(&self, pool: &dyn PoolAdapter, pair_symbol: &str) -> bool {
+        let (address, dex) = (pool.address(), pool.dex());
+        if dex.is_v2() && !matches!(dex, DexType::QuickSwapV2 | DexType::SushiSwapV2) {
+            return false;
+        }
+        if self.state_manager.is_pool_stale(&address) {
+            debug!("Skipping {} {:?} - stale after reorg", pair_symbol, dex);
+            return false;
+        }
+        if self.state_manager.is_pool_quarantined(&address) {
+            debug!("Skipping {} {:?} - quarantined (pool health)", pair_symbol, dex);
+            return false;
+        }
+        if !self.whitelist.is_pool_allowed(&address, pool.fee_tier(), pair_symbol) {
+            return false;
+        }
+        if dex.is_v3() {
+            let min_liq = self.whitelist.min_liquidity_for(&address, pool.fee_tier());
+            if pool.liquidity() < min_liq {
+                debug!(
+                    "Skipping {} {:?} - liquidity {} below threshold {} (fee tier {})",
+                    pair_symbol, dex, pool.liquidity(), min_liq, pool.fee_tier()
+                );
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Scan for triangular cycles quote → A → B → quote over three pools, on
+    /// one DEX or across DEXes. Returns opportunities sorted by estimated
+    /// profit (highest first).
+    ///
+    /// Each leg trades at the pool's decimal-adjusted price (token0→token1 at
+    /// `price`, token1→token0 at `1/price`) less its fee; the cycle pays when
+    /// the product of the three exceeds 1 by more than gas and slippage.
+    ///
+    /// Quote isolation as in check_pair_unified: a route starts and ends in
+    /// the same quote token and never passes through another quote token
+    /// (USDC.e → WETH → USDC → … is not one silo). Pools come from the
+    /// configured pairs, so A/B legs need a configured non-quote pair
+    /// (e.g. WMATIC/WETH). Gated by TRIANGULAR_ENABLED in the main loop.
+    pub fn scan_triangular_opportunities(&self) -> Vec<TriangularOpportunity> {
+        let pools = self.triangle_pools(unix_now());
+        if pools.len() < 3 {
+            return Vec::new();
+        }
+        let symbols = token_symbols(&pools, &self.config, self.tokens.as_deref());
+        let quotes = std::iter::once(self.config.quote_token_address)
+            .chain(self.config.quote_token_address_native);
+
+        let mut results = Vec::new();
+        for quote in quotes {
+            for (i, first) in pools.iter().enumerate() {
+                let Some(a) = first.other(&quote) else { continue };
+                if self.config.is_quote_token(&a) {
+                    continue;
+                }
+                for (j, second) in pools.iter().enumerate() {
+                    if j == i {
+                        continue;
+                    }
+                    let Some(b) = second.other(&a) else { continue };
+                    if self.config.is_quote_token(&b) {
+                        continue;
+                    }
+                    for (k, third) in pools.iter().enumerate() {
+                        if k == i || k == j || third.other(&b) != Some(quote) {
+                            continue;
+                        }
+                        let legs = vec![first.leg(quote), second.leg(a), third.leg(b)];
+                        let pairs = [first.pair.symbol.as_str(), second.pair.symbol.as_str(), third.pair.symbol.as_str()];
+                        if let Some(opp) = self.evaluate_triangle(legs, &pairs, first.decimals_of(&quote), &symbols) {
+                            results.push(opp);
+                        }
+                    }
+                }
             }
         }
 
+        results.sort_by(|a, b| {
+            b.estimated_profit
+                .partial_cmp(&a.estimated_profit)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
         results
     }
 
+    /// Every eligible pool of the configured pairs (same filters as
+    /// check_pair_unified, no quote token requirement)
+    fn triangle_pools(&self, captured_at: u64) -> Vec<TrianglePool> {
+        let mut pools: Vec<TrianglePool> = Vec::new();
+        let mut seen = HashSet::new();
+
+        for pair_config in &self.config.pairs {
+            let pair_symbol = &pair_config.symbol;
+            // Curve, Kyber and DODO pools are pair legs only, never triangle legs
+            for pool in self.state_manager.adapters_for_pair(pair_symbol) {
+                if pool.dex().is_detection_only() || pool.dex().is_legacy_only() {
+                    continue;
+                }
+                let price = pool.spot_price();
+                if price <= 0.0 || price >= 1e15 || !self.pool_eligible(pool.as_ref(), pair_symbol) {
+                    continue;
+                }
+                if !seen.insert(pool.address()) {
+                    continue;
+                }
+                let (token0_decimals, token1_decimals) = pool.decimals();
+                pools.push(TrianglePool {
+                    dex: pool.dex(),
+                    address: pool.address(),
+                    pair: pool.pair().clone(),
+                    price,
+                    fee_percent: pool.fee_fraction() * 100.0,
+                    token0_decimals,
+                    token1_decimals,
+                    liquidity: pool.liquidity(),
+                    snapshot: pool.snapshot(captured_at),
+                });
+            }
+        }
+
+        pools
+    }
+
+    /// Profit, liquidity and sizing checks for one cycle (None = not worth it)
+    fn evaluate_triangle(
+        &self,
+        legs: Vec<TriangularLeg>,
+        pairs: &[&str],
+        quote_decimals: u8,
+        symbols: &HashMap<Address, String>,
+    ) -> Option<TriangularOpportunity> {
+        let cycle_rate: f64 = legs.iter().map(TriangularLeg::net_rate).product();
+        let executable_spread = cycle_rate - 1.0;
+        if !executable_spread.is_finite() || executable_spread <= 0.0 {
+            return None;
+        }
+
+        // Same estimate as the two-leg detector, with gas for a third swap
+        let pools: Vec<Address> = legs.iter().map(|leg| leg.pool_address).collect();
+        let limits = self.route_limits(pairs, &pools);
+        let gross = executable_spread * limits.max_trade_size_usd;
+        let slippage_estimate = gross * 0.01;
+        let gas_cost = self.config.estimated_gas_cost_usd * TRIANGULAR_GAS_FACTOR;
+        let net_profit = gross - gas_cost - slippage_estimate;
+        if net_profit < limits.min_profit_usd {
+            return None;
+        }
+
+        let symbol = |token: &Address| symbols.get(token).cloned().unwrap_or_else(|| format!("{:?}", token));
+        let route = std::iter::once(symbol(&legs[0].token_in))
+            .chain(legs.iter().map(|leg| symbol(&leg.token_out)))
+            .collect::<Vec<_>>()
+            .join("→");
+
+        let min_liquidity = (limits.max_trade_size_usd * 1e6) as u128;
+        if legs.iter().any(|leg| leg.liquidity < min_liquidity) {
+            debug!(
+                "Skipping triangle {} - pool liquidity too low for ${:.0} trade",
+                route, limits.max_trade_size_usd
+            );
+            return None;
+        }
+
+        let quote_address = legs[0].token_in;
+        let quote = QuoteToken::new(quote_address, quote_decimals, &symbol(&quote_address));
+        let quote_price_usd = self.config.quote_price_usd(&quote_address);
+        let trade_size = quote.from_usd(limits.max_trade_size_usd, quote_price_usd);
+        let trade_size_usd = trade_size.to_usd(quote_price_usd);
+        let min_profit_raw = quote.from_usd(limits.min_profit_usd, quote_price_usd).raw();
+
+        let opportunity = TriangularOpportunity {
+            legs,
+            route,
+            spread_percent: executable_spread * 100.0,
+            estimated_profit: net_profit,
+            trade_size,
+            trade_size_usd,
+            min_profit_raw,
+            timestamp: unix_now(),
+        };
+        info!(
+            "🔺 TRIANGULAR OPPORTUNITY: {} | {} | Spread {:.2}% | Size {} | Net {}",
+            opportunity.route,
+            opportunity.dex_path(),
+            opportunity.spread_percent,
+            format_usd(trade_size_usd),
+            format_usd(net_profit)
+        );
+        Some(opportunity)
+    }
+
     /// Check a specific pair for arbitrage opportunity
     /// Returns Some(opportunity) if profitable, None otherwise
     pub fn check_pair(&self, pair_symbol: &str) -> Option<ArbitrageOpportunity> {
@@ -365,6 +929,7 @@ impl OpportunityDetector {
 
         // Find best buy (lowest price) and best sell (highest price)
         let (buy_pool, sell_pool) = self.find_best_pools(&pools)?;
+        let captured_at = unix_now();
 
         // Calculate spread
         let spread_percent = self.calculate_spread(buy_pool.price(), sell_pool.price());
@@ -389,17 +954,20 @@ impl OpportunityDetector {
         );
 
         // Calculate optimal trade size and actual profit
+        let limits = self.route_limits(&[pair_symbol], &[buy_pool.address, sell_pool.address]);
+        let quote = buy_pool.pair.quote(true, buy_pool.token0_decimals);
+        let quote_price_usd = self.config.quote_price_usd(&buy_pool.pair.token0);
         let (trade_size, profit_usd) =
-            self.calculate_profit(&buy_pool, &sell_pool, pair_symbol)?;
+            self.calculate_profit(buy_pool, sell_pool, quote, quote_price_usd, limits.max_trade_size_usd)?;
 
         // Net profit after gas (chain-specific gas cost from config)
         let net_profit_usd = profit_usd - self.config.estimated_gas_cost_usd;
 
         // Filter by minimum profit threshold
-        if net_profit_usd < self.config.min_profit_usd {
+        if net_profit_usd < limits.min_profit_usd {
             debug!(
                 "{}: net profit ${:.2} < ${:.2} minimum",
-                pair_symbol, net_profit_usd, self.config.min_profit_usd
+                pair_symbol, net_profit_usd, limits.min_profit_usd
             );
             return None;
         }
@@ -415,7 +983,8 @@ impl OpportunityDetector {
             net_profit_usd
         );
 
-        Some(ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity {
+            id: next_opportunity_id(),
             pair: buy_pool.pair.clone(),
             buy_dex: buy_pool.dex,
             sell_dex: sell_pool.dex,
@@ -430,11 +999,29 @@ impl OpportunityDetector {
                 .as_secs(),
             buy_pool_address: Some(buy_pool.address),
             sell_pool_address: Some(sell_pool.address),
-            token0_decimals: 18, // V2 pools don't track decimals, default 18
-            token1_decimals: 18,
+            token0_decimals: buy_pool.token0_decimals,
+            token1_decimals: buy_pool.token1_decimals,
             buy_pool_liquidity: None,
+            sell_pool_liquidity: None,
             quote_token_is_token0: true, // V2 pools: default assumption (USDC is token0)
-        })
+            pool_snapshots: Some(LegSnapshots {
+                buy: PoolStateSnapshot::from_v2(buy_pool, captured_at),
+                sell: PoolStateSnapshot::from_v2(sell_pool, captured_at),
+            }),
+            trade_size_usd: trade_size.to_usd(quote_price_usd),
+            quoted_legs: None,
+            exposure_pacing: None,
+            win_probability: None,
+            route_legs: None,
+            flash_eligible: false,
+            flash_v2_eligible: false,
+            split_sell: None,
+            min_profit_usd: None,
+            max_slippage_percent: None,
+            anticipatory: None,
+        };
+        self.stamp_limits(&mut opp, &limits);
+        Some(opp)
     }
 
     /// Find the best buy pool (lowest price) and sell pool (highest price)
@@ -472,13 +1059,17 @@ impl OpportunityDetector {
         ((sell_price - buy_price) / buy_price) * 100.0
     }
 
-    /// Calculate optimal trade size and expected profit in USD
+    /// Calculate optimal trade size and expected profit in USD. The legacy
+    /// path trades token0 as the quote token: sizes and profit are token0 raw
+    /// units at the pool's token0 decimals, priced by `quote_price_usd`.
     fn calculate_profit(
         &self,
         buy_pool: &PoolState,
         sell_pool: &PoolState,
-        pair_symbol: &str,
-    ) -> Option<(U256, f64)> {
+        quote: QuoteToken,
+        quote_price_usd: f64,
+        max_trade_size_usd: f64,
+    ) -> Option<(QuoteAmount, f64)> {
         // Get token addresses
         let token_in = buy_pool.pair.token0;
 
@@ -486,8 +1077,8 @@ impl OpportunityDetector {
         let trade_size = PriceCalculator::optimal_trade_size(buy_pool, sell_pool, token_in);
 
         // Enforce max trade size from config
-        let max_trade_size = self.max_trade_size_wei(pair_symbol);
-        let trade_size = std::cmp::min(trade_size, max_trade_size);
+        let max_trade_size = quote.from_usd(max_trade_size_usd, quote_price_usd).raw();
+        let trade_size = quote.amount(std::cmp::min(trade_size, max_trade_size));
 
         if trade_size.is_zero() {
             return None;
@@ -495,65 +1086,135 @@ impl OpportunityDetector {
 
         // Simulate the arbitrage
         let (amount_out, profit_wei) =
-            PriceCalculator::simulate_arbitrage(buy_pool, sell_pool, trade_size, token_in);
+            PriceCalculator::simulate_arbitrage(buy_pool, sell_pool, trade_size.raw(), token_in);
 
         if profit_wei.is_zero() {
             return None;
         }
 
         // Convert profit to USD
-        let profit_usd = self.wei_to_usd(profit_wei, pair_symbol);
+        let profit = quote.amount(profit_wei);
+        let profit_usd = profit.to_usd(quote_price_usd);
 
         debug!(
-            "{}: trade_size={}, amount_out={}, profit_wei={}, profit_usd=${:.2}",
-            pair_symbol, trade_size, amount_out, profit_wei, profit_usd
+            "{}: trade_size={}, amount_out={}, profit={} ({})",
+            buy_pool.pair.symbol,
+            trade_size,
+            quote.amount(amount_out),
+            profit,
+            format_usd(profit_usd)
         );
 
         Some((trade_size, profit_usd))
     }
+}
 
-    /// Convert trade size to Wei based on pair
-    fn max_trade_size_wei(&self, pair_symbol: &str) -> U256 {
-        // Max trade size in USD from config
-        let max_usd = self.config.max_trade_size_usd;
-
-        // Rough conversion based on pair (can be improved with price oracle)
-        if pair_symbol.starts_with("WETH") {
-            // Assume ETH ~$3300
-            let eth_amount = max_usd / 3300.0;
-            U256::from((eth_amount * 1e18) as u128)
-        } else if pair_symbol.starts_with("WMATIC") {
-            // Assume MATIC ~$0.50
-            let matic_amount = max_usd / 0.50;
-            U256::from((matic_amount * 1e18) as u128)
-        } else {
-            // Default: assume 18 decimals, $1 = 1 token
-            U256::from((max_usd * 1e18) as u128)
-        }
+/// Two-leg opportunity for a unified-scan route at `trade_size`
+#[allow(clippy::too_many_arguments)]
+fn unified_opportunity(
+    buy_pool: &UnifiedPool,
+    sell_pool: &UnifiedPool,
+    quote_is_token0: bool,
+    trade_size: QuoteAmount,
+    trade_size_usd: f64,
+    executable_spread: f64,
+    net_profit: f64,
+    captured_at: u64,
+) -> ArbitrageOpportunity {
+    let (buy, sell) = (buy_pool.adapter.as_ref(), sell_pool.adapter.as_ref());
+    let (token0_decimals, token1_decimals) = buy.decimals();
+    ArbitrageOpportunity {
+        id: next_opportunity_id(),
+        pair: buy.pair().clone(),
+        buy_dex: buy.dex(),
+        sell_dex: sell.dex(),
+        buy_price: buy_pool.price,
+        sell_price: sell_pool.price,
+        spread_percent: executable_spread * 100.0,
+        estimated_profit: net_profit,
+        trade_size,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        buy_pool_address: Some(buy.address()),
+        sell_pool_address: Some(sell.address()),
+        token0_decimals,
+        token1_decimals,
+        buy_pool_liquidity: Some(buy_pool.liquidity),
+        sell_pool_liquidity: Some(sell_pool.liquidity),
+        quote_token_is_token0: quote_is_token0,
+        pool_snapshots: Some(LegSnapshots {
+            buy: buy.snapshot(captured_at),
+            sell: sell.snapshot(captured_at),
+        }),
+        trade_size_usd,
+        quoted_legs: None,
+        exposure_pacing: None,
+        win_probability: None,
+        route_legs: None,
+        flash_eligible: false,
+        flash_v2_eligible: false,
+        split_sell: None,
+        min_profit_usd: None,
+        max_slippage_percent: None,
+        anticipatory: None,
     }
+}
 
-    /// Convert Wei profit to USD based on pair
-    fn wei_to_usd(&self, wei: U256, pair_symbol: &str) -> f64 {
-        let wei_f = wei.low_u128() as f64;
+/// Quote raw returned by swapping `trade_size` quote through the buy leg and
+/// the base it yields through the sell leg, each on its pool's depth.
+/// None when either leg has no depth model (Curve, unsynced V3 sqrtPriceX96).
+fn round_trip_out(buy: &dyn PoolAdapter, sell: &dyn PoolAdapter, trade_size: U256, quote: Address) -> Option<U256> {
+    let pair = buy.pair();
+    let base = if pair.token0 == quote { pair.token1 } else { pair.token0 };
+    let base_out = buy.amount_out(trade_size, quote)?;
+    sell.amount_out(base_out, base)
+}
 
-        // Convert based on token (can be improved with price oracle)
-        if pair_symbol.starts_with("WETH") {
-            // ETH: 18 decimals, ~$3300
-            (wei_f / 1e18) * 3300.0
-        } else if pair_symbol.starts_with("WMATIC") {
-            // MATIC: 18 decimals, ~$0.50
-            (wei_f / 1e18) * 0.50
+/// Token symbols for triangular route labels: the registry's when given,
+/// else taken from quote pairs ("WETH/USDC": WETH is the base, USDC the
+/// quote). Tokens only seen in non-quote pairs fall back to their address.
+fn token_symbols(pools: &[TrianglePool], config: &BotConfig, tokens: Option<&TokenRegistry>) -> HashMap<Address, String> {
+    let mut symbols = HashMap::new();
+    if let Some(tokens) = tokens {
+        for pool in pools {
+            for token in [pool.pair.token0, pool.pair.token1] {
+                if let Ok(symbol) = tokens.symbol(&token) {
+                    symbols.insert(token, symbol.to_string());
+                }
+            }
+        }
+    }
+    for pool in pools {
+        let (quote, base) = if config.is_quote_token(&pool.pair.token0) {
+            (pool.pair.token0, pool.pair.token1)
+        } else if config.is_quote_token(&pool.pair.token1) {
+            (pool.pair.token1, pool.pair.token0)
         } else {
-            // Default: assume 18 decimals, $1 = 1 token
-            wei_f / 1e18
+            continue;
+        };
+        let mut parts = pool.pair.symbol.split('/');
+        if let (Some(base_symbol), Some(quote_symbol)) = (parts.next(), parts.next()) {
+            symbols.entry(base).or_insert_with(|| base_symbol.to_string());
+            symbols.entry(quote).or_insert_with(|| quote_symbol.to_string());
         }
     }
+    symbols
+}
+
+/// Unix seconds (snapshot capture time)
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use crate::types::{DexType, TradingPair};
+    use crate::types::{CurvePoolState, DexType, TradingPair, TradingPairConfig, V3PoolState};
     use ethers::types::Address;
 
     fn create_test_pool(
@@ -574,7 +1235,7 @@ mod tests {
         }
     }
 
-    fn create_test_config() -> BotConfig {
+    pub(crate) fn create_test_config() -> BotConfig {
         use std::str::FromStr;
         BotConfig {
             rpc_url: String::new(),
@@ -583,6 +1244,7 @@ mod tests {
             quote_token_address: Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap(),
             estimated_gas_cost_usd: 0.05,
             private_key: String::new(),
+            private_keys: vec![],
             min_profit_usd: 5.0,
             max_trade_size_usd: 500.0,
             max_slippage_percent: 0.5,
@@ -601,28 +1263,187 @@ mod tests {
             quickswap_v3_factory: None,
             quickswap_v3_router: None,
             quickswap_v3_quoter: None,
+            kyber_elastic_factory: None,
+            kyber_elastic_router: None,
+            kyber_elastic_quoter: None,
+            dodo_v2_proxy: None,
+            dodo_approve: None,
             uniswap_v3_quoter_is_v2: false,
             pairs: vec![],
             poll_interval_ms: 1000,
             max_gas_price_gwei: 100,
             tax_log_dir: None,
             tax_log_enabled: false,
+            tax_log_strict: false,
             live_mode: false,
             pool_state_file: None,
             whitelist_file: None,
             price_log_enabled: false,
             price_log_dir: None,
+            gas_history_enabled: false,
+            gas_history_sample_blocks: 30,
+            price_log_queue_capacity: 64,
+            tax_write_queue_capacity: 256,
+            tax_enqueue_timeout_ms: 50,
+            io_write_timeout_ms: 2000,
+            prescreen_export_enabled: false,
+            opportunity_archive_enabled: false,
+            opportunity_archive_expiry_blocks: 3,
+            opportunity_archive_retain_days: 14,
+            execution_constraints_enabled: false,
+            watchdog_enabled: false,
+            watchdog_check_secs: 5,
+            watchdog_block_stall_secs: 60,
+            watchdog_hard_limit_secs: 300,
+            watchdog_exit_enabled: false,
+            watchdog_reconnect_attempts: 3,
             arb_executor_address: None,
+            arb_executor_multi: false,
+            flashloan_enabled: false,
+            aave_pool_address: None,
+            flash_v2_enabled: false,
+            permit2_address: None,
             skip_multicall_prescreen: false,
+            prescreen_skip_enabled: false,
+            prescreen_skip_streak: 30,
+            prescreen_reverify_every: 10,
+            burst_threshold: 25,
+            burst_top_k: 5,
+            burst_min_spread_percent: 0.3,
+            burst_exit_blocks: 3,
+            shadow_role: "off".to_string(),
+            shadow_feed_dir: None,
+            shadow_match_lag_blocks: 5,
+            shadow_config: None,
+            shadow_verify_max: 5,
+            competition_model_enabled: false,
+            competition_min_samples: 20,
+            competition_gas_refund_fraction: 0.0,
+            competitor_addresses: Vec::new(),
+            triangular_enabled: false,
+            optimal_sizing: false,
             route_cooldown_blocks: 10,
+            route_cooldown_size_buckets_usd: Vec::new(),
+            route_cooldown_mempool_bypass_failures: 3,
             private_rpc_url: None,
+            bundle_rpc_url: None,
+            bundle_rpc_method: "pfl_addSearcherBundle".to_string(),
+            bundle_params_template: r#"["{trigger_hash}","{backrun_raw}"]"#.to_string(),
             mempool_monitor_mode: "off".to_string(),
             mempool_min_profit_usd: 0.05,
             mempool_gas_limit: 500_000,
             mempool_min_priority_gwei: 1000,
             mempool_gas_profit_cap: 0.50,
             native_token_price_usd: 0.50,
+            native_price_pair: None,
             quote_token_address_native: None,
+            quote_token_price_usd: 1.0,
+            preflight_batch_enabled: true,
+            canary_max_trade_size_usd: 25.0,
+            canary_min_win_rate: 0.5,
+            canary_min_net_profit_usd: 0.0,
+            canary_min_trades: 3,
+            stranded_check_interval_blocks: 150,
+            stranded_dust_usd: 1.0,
+            auto_rescue: false,
+            alert_webhook_url: None,
+            migration_collapse_blocks: 60,
+            migration_collapse_ratio: 0.05,
+            auto_migrate: false,
+            token_revalidation_hours: 0,
+            anomaly_monitor_enabled: false,
+            anomaly_window_secs: 900,
+            anomaly_sigma: 3.0,
+            anomaly_min_duration_secs: 1800,
+            inflight_journal_enabled: false,
+            inflight_recovery_policy: "discard".to_string(),
+            min_pool_liquidity: 0,
+            min_pool_liquidity_overrides: std::collections::HashMap::new(),
+            max_liquidity_fraction: 0.0,
+            liquidity_guard_mode: "reject".to_string(),
+            telemetry_enabled: false,
+            telemetry_endpoint: None,
+            telemetry_interval_secs: 60,
+            telemetry_include_addresses: false,
+            api_port: None,
+            api_bind: "127.0.0.1".to_string(),
+            api_recent_opportunities: 100,
+            deadman_url: None,
+            deadman_fail_url: None,
+            deadman_method: "post".to_string(),
+            deadman_interval_secs: 60,
+            deadman_max_block_age_secs: 60,
+            deadman_criteria: "heartbeat,block_age,breaker,tax".to_string(),
+            slippage_feedback_enabled: false,
+            slippage_feedback_min_samples: 20,
+            slippage_feedback_percentile: 95.0,
+            slippage_feedback_buffer_percent: 0.1,
+            confirmation_depth: 0,
+            exposure_pacing_enabled: false,
+            exposure_max_trade_fraction: 0.4,
+            exposure_max_inflight_fraction: 0.8,
+            exposure_overrides: std::collections::HashMap::new(),
+            exposure_min_notional_usd: 10.0,
+            chaos_mode: false,
+            chaos_config_file: None,
+            stats_report_interval: 500,
+            latency_report_interval: 300,
+            reorg_track_blocks: 32,
+            alert_trades: true,
+            alert_halts: true,
+            alert_ws: true,
+            alert_daily_summary: true,
+            alert_canary: true,
+            alert_rate_limit_secs: 3600,
+            balance_clamp_enabled: false,
+            balance_usable_fraction: 0.98,
+            min_quote_balance_usd: 10.0,
+            curve_enabled: false,
+            tax_lot_method: "fifo".to_string(),
+            multicall3_address: None,
+            gas_oracle_enabled: true,
+            gas_oracle_percentile: 90.0,
+            gas_oracle_bump_percent: 10.0,
+            gas_oracle_window_blocks: 20,
+            gas_oracle_sample_blocks: 5,
+            min_priority_gwei: 30,
+            max_priority_gwei: 5000,
+            pool_health_enabled: true,
+            pool_health_max_idle_blocks: 43_200,
+            pool_health_min_v3_liquidity: 1_000_000_000,
+            pool_health_min_v2_tvl_usd: 1_000.0,
+            pool_health_max_quoter_rejections: 5,
+            pool_health_rejection_quarantine_blocks: 1800,
+            max_parallel_submissions: 1,
+            cross_chain_state_file: None,
+            cross_chain_divergence_bps: 20.0,
+            cross_chain_max_age_secs: 30,
+            log_format: "text".to_string(),
+            mempool_calibration_k: 1.0,
+            mempool_calibration_prior_bps: 5.0,
+            gas_cache_enabled: false,
+            gas_cache_multiplier: 1.2,
+            max_pool_age_blocks: 2,
+            max_opportunity_age_secs: 4,
+            spread_confirm_blocks: 1,
+            spread_history_blocks: 8,
+            opportunity_dedup_ttl_blocks: 2,
+            inventory_check_interval_blocks: 1800,
+            inventory_rebalance_threshold_usd: 25.0,
+            auto_rebalance: false,
+            rpc_urls: vec![],
+            ws_rpc_url: None,
+            split_routing: false,
+            price_log_format: "csv".to_string(),
+            price_log_compress: true,
+            price_log_retain_days: 0,
+            price_log_blocks: false,
+            min_profit_adaptive: false,
+            min_profit_floor_usd: 0.0,
+            min_profit_ceiling_usd: 1.0,
+            algebra_fee_refresh_blocks: 10,
+            midblock_scan: false,
+            midblock_scan_max_per_block: 3,
         }
     }
 
@@ -663,4 +1484,640 @@ mod tests {
         assert_eq!(buy.dex, DexType::Uniswap); // Lower price = buy here
         assert_eq!(sell.dex, DexType::Sushiswap); // Higher price = sell here
     }
+
+    #[test]
+    fn test_quote_price_usd_primary_vs_native() {
+        let mut config = create_test_config();
+        let native = Address::from_low_u64_be(0xc359);
+        config.quote_token_address_native = Some(native);
+        // Native USDC is always priced at $1; primary follows QUOTE_TOKEN_PRICE_USD
+        assert_eq!(config.quote_price_usd(&native), 1.0);
+        assert_eq!(config.quote_price_usd(&config.quote_token_address), 1.0);
+        config.quote_token_price_usd = 3000.0;
+        assert_eq!(config.quote_price_usd(&config.quote_token_address), 3000.0);
+        assert_eq!(config.quote_price_usd(&native), 1.0);
+    }
+
+    #[test]
+    fn test_curve_pool_compared_only_within_its_stablecoin_pair() {
+        let mut config = create_test_config();
+        let usdc = config.quote_token_address;
+        let usdt = Address::from_low_u64_be(0xc2);
+        let lookalike = Address::from_low_u64_be(0xc3); // another token filed as "USDT/USDC"
+        let sorted = |a: Address, b: Address| if a < b { (a, b) } else { (b, a) };
+        let (t0, t1) = sorted(usdc, usdt);
+        let (l0, l1) = sorted(usdc, lookalike);
+
+        // Curve coins [USDC, USDT], USDC-heavy: 1 USDC buys ~0.9857 USDT
+        let usdc_is_token0 = t0 == usdc;
+        let curve = CurvePoolState {
+            address: Address::from_low_u64_be(0xc0),
+            dex: DexType::CurveStable,
+            pair: TradingPair::new(t0, t1, "USDT/USDC".to_string()),
+            index0: if usdc_is_token0 { 0 } else { 1 },
+            index1: if usdc_is_token0 { 1 } else { 0 },
+            balances: vec![U256::from(8_000_000u64) * U256::exp10(6), U256::from(2_000_000u64) * U256::exp10(6)],
+            decimals: vec![6, 6],
+            amp: U256::from(200),
+            fee: U256::from(1_000_000), // 0.01%
+            token0_decimals: 6,
+            token1_decimals: 6,
+            last_updated: 100,
+        };
+
+        let state_manager = PoolStateManager::new();
+        state_manager.update_curve_pool(curve);
+        // Par-priced V3 pools: the real USDT pair and the lookalike
+        let pair = TradingPair::new(t0, t1, "USDT/USDC".to_string());
+        state_manager.update_v3_pool(tri_pool(0xd1, DexType::UniswapV3_001, pair, (6, 6), 100, 1.0));
+        let pair = TradingPair::new(l0, l1, "USDT/USDC".to_string());
+        state_manager.update_v3_pool(tri_pool(0xd2, DexType::SushiV3_001, pair, (6, 6), 100, 1.0));
+
+        let detector = OpportunityDetector::new(config.clone(), state_manager.clone());
+        assert!(detector.check_pair_unified("USDT/USDC").is_empty(), "CURVE_ENABLED off");
+
+        config.curve_enabled = true;
+        let detector = OpportunityDetector::new(config, state_manager);
+        let opps = detector.check_pair_unified("USDT/USDC");
+        assert_eq!(opps.len(), 1, "lookalike pair never meets the Curve pool");
+        let opp = &opps[0];
+        assert!(opp.buy_dex.is_curve() || opp.sell_dex.is_curve());
+        assert!(!opp.buy_dex.is_curve() || !opp.sell_dex.is_curve());
+        let other = if opp.buy_dex.is_curve() { opp.sell_pool_address } else { opp.buy_pool_address };
+        assert_eq!(other, Some(Address::from_low_u64_be(0xd1)));
+        // ~1.43% mid spread less 0.01% + 0.01% fees
+        assert!(opp.spread_percent > 1.3 && opp.spread_percent < 1.5, "spread {}", opp.spread_percent);
+    }
+
+    #[test]
+    fn test_thin_pool_estimate_below_flat_slippage_model() {
+        let mut config = create_test_config();
+        config.min_profit_usd = 0.5;
+        let usdc = config.quote_token_address;
+        let pair = TradingPair::new(usdc, Address::from_low_u64_be(WETH), "WETH/USDC".to_string());
+
+        // WETH at $1960 (buy) vs $2000 (sell): ~2% mid spread, 0.05% fee each
+        let pool = |addr: u64, dex: DexType, price: f64, liquidity: u128| {
+            let mut pool = tri_pool(addr, dex, pair.clone(), (6, 18), 500, 1.0 / price);
+            pool.sqrt_price_x96 = crate::mempool::ticks::sqrt_ratio_at_tick(pool.tick).unwrap();
+            pool.liquidity = liquidity;
+            pool
+        };
+        let estimate = |buy_liquidity: u128| {
+            let state_manager = PoolStateManager::new();
+            state_manager.update_v3_pool(pool(1, DexType::UniswapV3_005, 1960.0, buy_liquidity));
+            state_manager.update_v3_pool(pool(2, DexType::SushiV3_005, 2000.0, 10u128.pow(20)));
+            let opps = OpportunityDetector::new(config.clone(), state_manager).check_pair_unified("WETH/USDC");
+            assert_eq!(opps.len(), 1);
+            assert_eq!(opps[0].buy_pool_address, Some(Address::from_low_u64_be(1)));
+            (opps[0].estimated_profit, opps[0].spread_percent)
+        };
+
+        // What the flat model books for this route at $500
+        let mid = 2000.0 / 1960.0 - 1.0;
+        let flat = (mid - 0.001) * 500.0 * 0.99 - config.estimated_gas_cost_usd;
+
+        // Deep pools: price impact is noise, the estimate is the fee-adjusted spread
+        let (deep, deep_spread) = estimate(10u128.pow(20));
+        assert!(deep > flat && deep < flat / 0.99, "deep {} vs flat {}", deep, flat);
+
+        // Thin buy pool (~$44k of in-range USDC depth): $500 costs ~1.1% in impact
+        let (thin, thin_spread) = estimate(10u128.pow(15));
+        assert!(thin < flat - 3.0, "thin {} vs flat {}", thin, flat);
+        assert!(thin_spread < deep_spread);
+        assert!((thin + config.estimated_gas_cost_usd - thin_spread / 100.0 * 500.0).abs() < 1e-6);
+
+        // No sqrtPriceX96 (depth unknown): the flat estimate stands in
+        let state_manager = PoolStateManager::new();
+        let mut unsynced = pool(1, DexType::UniswapV3_005, 1960.0, 10u128.pow(20));
+        unsynced.sqrt_price_x96 = U256::zero();
+        state_manager.update_v3_pool(unsynced);
+        state_manager.update_v3_pool(pool(2, DexType::SushiV3_005, 2000.0, 10u128.pow(20)));
+        let opps = OpportunityDetector::new(config.clone(), state_manager).check_pair_unified("WETH/USDC");
+        assert!((opps[0].estimated_profit - flat).abs() < 0.05, "{} vs {}", opps[0].estimated_profit, flat);
+    }
+
+    #[test]
+    fn test_split_sell_when_best_sell_pool_too_thin() {
+        let mut config = create_test_config();
+        let usdc = config.quote_token_address;
+        let pair = TradingPair::new(usdc, Address::from_low_u64_be(WETH), "WETH/USDC".to_string());
+        let pool = |addr: u64, dex: DexType, fee: u32, price: f64, liquidity: u128| {
+            let mut pool = tri_pool(addr, dex, pair.clone(), (6, 18), fee, 1.0 / price);
+            pool.sqrt_price_x96 = crate::mempool::ticks::sqrt_ratio_at_tick(pool.tick).unwrap();
+            pool.liquidity = liquidity;
+            pool
+        };
+        let state_manager = PoolStateManager::new();
+        // Buy WETH at $1960 (deep); sell on the thin 0.05% tier at $2000
+        // (~0.8% impact for the $500 size) or the deep 0.30% tier at $1985
+        state_manager.update_v3_pool(pool(1, DexType::SushiV3_005, 500, 1960.0, 10u128.pow(20)));
+        state_manager.update_v3_pool(pool(2, DexType::UniswapV3_005, 500, 2000.0, 15 * 10u128.pow(14)));
+        state_manager.update_v3_pool(pool(3, DexType::UniswapV3_030, 3000, 1985.0, 10u128.pow(20)));
+
+        let single = OpportunityDetector::new(config.clone(), state_manager.clone()).check_pair_unified("WETH/USDC");
+        assert!(single.iter().all(|o| o.split_sell.is_none()));
+        let thin_route = single.iter().find(|o| o.sell_pool_address == Some(Address::from_low_u64_be(2))).unwrap();
+
+        config.split_routing = true;
+        let opps = OpportunityDetector::new(config, state_manager).check_pair_unified("WETH/USDC");
+        let split: Vec<_> = opps.iter().filter(|o| o.split_sell.is_some()).collect();
+        assert_eq!(split.len(), 1);
+        let (opp, leg) = (split[0], split[0].split_sell.unwrap());
+        // Thin tier keeps most of the base, the deep tier takes the rest
+        assert_eq!(opp.buy_pool_address, Some(Address::from_low_u64_be(1)));
+        assert_eq!(opp.sell_pool_address, Some(Address::from_low_u64_be(2)));
+        assert_eq!((leg.dex, leg.pool_address), (DexType::UniswapV3_030, Address::from_low_u64_be(3)));
+        assert!(leg.share_bps > 2_000 && leg.share_bps < 5_000, "share {}", leg.share_bps);
+        // Worth the third swap's gas over the single thin-tier sell
+        assert!(opp.estimated_profit > thin_route.estimated_profit, "{} vs {}", opp.estimated_profit, thin_route.estimated_profit);
+    }
+
+    #[test]
+    fn test_algebra_live_fee_flips_route_to_filtered() {
+        let mut config = create_test_config();
+        config.min_profit_usd = 0.5;
+        let usdc = config.quote_token_address;
+        let pair = TradingPair::new(usdc, Address::from_low_u64_be(WETH), "WETH/USDC".to_string());
+        let pool = |addr: u64, dex: DexType, fee: u32, price: f64| {
+            let mut pool = tri_pool(addr, dex, pair.clone(), (6, 18), fee, 1.0 / price);
+            pool.sqrt_price_x96 = crate::mempool::ticks::sqrt_ratio_at_tick(pool.tick).unwrap();
+            pool.liquidity = 10u128.pow(20);
+            pool
+        };
+        // WETH at $1990 on UniV3 0.05% vs $2000 on QuickSwap V3: ~0.5% mid spread
+        let algebra = Address::from_low_u64_be(2);
+        let state_manager = PoolStateManager::new();
+        state_manager.update_v3_pool(pool(1, DexType::UniswapV3_005, 500, 1990.0));
+        state_manager.update_v3_pool(pool(2, DexType::QuickswapV3, 100, 2000.0));
+        let detector = OpportunityDetector::new(config, state_manager.clone());
+
+        // 0.01% dynamic fee: 0.06% round trip leaves the route profitable
+        let opps = detector.check_pair_unified("WETH/USDC");
+        assert_eq!(opps.len(), 1);
+        assert_eq!((opps[0].buy_dex, opps[0].sell_dex), (DexType::UniswapV3_005, DexType::QuickswapV3));
+        let cheap_spread = opps[0].spread_percent;
+        assert!(cheap_spread > 0.4 && cheap_spread < 0.45, "spread {}", cheap_spread);
+
+        // globalState() now reports 0.45%: same prices, the route is filtered
+        assert_eq!(state_manager.set_v3_fee(&algebra, 4500), Some(100));
+        assert_eq!(state_manager.get_v3_pool_at(&algebra).unwrap().last_updated, 100);
+        assert!(detector.check_pair_unified("WETH/USDC").is_empty());
+
+        // And back
+        state_manager.set_v3_fee(&algebra, 100);
+        assert_eq!(detector.check_pair_unified("WETH/USDC").len(), 1);
+    }
+
+    /// Advisory whitelist carrying only trade limit overrides
+    fn limits_whitelist(pools: &str, pair_limits: &str) -> WhitelistFilter {
+        let json = format!(
+            r#"{{
+                "version": "1.0",
+                "last_updated": "2026-10-16T00:00:00Z",
+                "config": {{ "default_min_liquidity": 0, "whitelist_enforcement": "advisory" }},
+                "whitelist": {{ "pools": [{}], "pair_limits": [{}] }},
+                "blacklist": {{ "pools": [], "fee_tiers": [], "pairs": [] }}
+            }}"#,
+            pools, pair_limits
+        );
+        WhitelistFilter::from_config(serde_json::from_str(&json).unwrap())
+    }
+
+    #[test]
+    fn test_pair_limits_override_global_on_unified_route() {
+        let mut config = create_test_config();
+        config.min_profit_usd = 0.5;
+        let usdc = config.quote_token_address;
+        let pair = TradingPair::new(usdc, Address::from_low_u64_be(WETH), "WETH/USDC".to_string());
+        let pool = |addr: u64, dex: DexType, fee: u32, price: f64| {
+            let mut pool = tri_pool(addr, dex, pair.clone(), (6, 18), fee, 1.0 / price);
+            pool.sqrt_price_x96 = crate::mempool::ticks::sqrt_ratio_at_tick(pool.tick).unwrap();
+            pool.liquidity = 10u128.pow(20);
+            pool
+        };
+        let state_manager = PoolStateManager::new();
+        state_manager.update_v3_pool(pool(1, DexType::UniswapV3_005, 500, 1990.0));
+        state_manager.update_v3_pool(pool(2, DexType::QuickswapV3, 100, 2000.0));
+        let mut detector = OpportunityDetector::new(config, state_manager);
+
+        // No overrides: global size, nothing stamped for the executor
+        let opps = detector.check_pair_unified("WETH/USDC");
+        assert_eq!(opps.len(), 1);
+        assert!((opps[0].trade_size_usd - 500.0).abs() < 1e-6);
+        assert_eq!((opps[0].min_profit_usd, opps[0].max_slippage_percent), (None, None));
+
+        // Pair caps size and raises minProfit; the sell pool tightens slippage
+        detector.whitelist = limits_whitelist(
+            r#"{ "address": "0x0000000000000000000000000000000000000002", "pair": "WETH/USDC",
+                 "dex": "QuickswapV3", "fee_tier": 100, "status": "active", "max_slippage_percent": 0.2 }"#,
+            r#"{ "pair": "WETH/USDC", "max_trade_size_usd": 250.0, "min_profit_usd": 0.75 }"#,
+        );
+        let opps = detector.check_pair_unified("WETH/USDC");
+        assert_eq!(opps.len(), 1);
+        assert!((opps[0].trade_size_usd - 250.0).abs() < 1e-6, "size {}", opps[0].trade_size_usd);
+        assert_eq!(opps[0].min_profit_usd, Some(0.75));
+        assert_eq!(opps[0].max_slippage_percent, Some(0.2));
+
+        // Looser than global: ignored
+        detector.whitelist = limits_whitelist("", r#"{ "pair": "WETH/USDC", "max_trade_size_usd": 5000.0, "min_profit_usd": 0.1 }"#);
+        let opps = detector.check_pair_unified("WETH/USDC");
+        assert!((opps[0].trade_size_usd - 500.0).abs() < 1e-6);
+        assert_eq!(opps[0].min_profit_usd, None);
+
+        // A pair minProfit above the route's profit filters it
+        detector.whitelist = limits_whitelist("", r#"{ "pair": "WETH/USDC", "min_profit_usd": 50.0 }"#);
+        assert!(detector.check_pair_unified("WETH/USDC").is_empty());
+    }
+
+    #[test]
+    fn test_weth_quoted_route_sized_at_the_weth_price() {
+        // WMATIC (0x3a) < WETH (0xe7): WETH is token1 and the quote, at $3000
+        let mut config = create_test_config();
+        config.min_profit_usd = 0.5;
+        let weth = Address::from_low_u64_be(WETH);
+        config.quote_token_address = weth;
+        config.quote_token_price_usd = 3000.0;
+        let pair = TradingPair::new(Address::from_low_u64_be(WMATIC), weth, "WMATIC/WETH".to_string());
+        let pool = |addr: u64, dex: DexType, fee: u32, wmatic_per_weth: f64| {
+            let mut pool = tri_pool(addr, dex, pair.clone(), (18, 18), fee, 1.0 / wmatic_per_weth);
+            pool.sqrt_price_x96 = crate::mempool::ticks::sqrt_ratio_at_tick(pool.tick).unwrap();
+            pool.liquidity = 10u128.pow(24);
+            pool
+        };
+        let state_manager = PoolStateManager::new();
+        state_manager.update_v3_pool(pool(1, DexType::UniswapV3_005, 500, 6000.0));
+        state_manager.update_v3_pool(pool(2, DexType::QuickswapV3, 100, 5940.0));
+        let detector = OpportunityDetector::new(config, state_manager);
+
+        let opps = detector.check_pair_unified("WMATIC/WETH");
+        assert_eq!(opps.len(), 1);
+        let opp = &opps[0];
+        // $500 is 1/6 WETH, not 500 WETH
+        assert_eq!(opp.quote_symbol(), "WETH");
+        assert_eq!(opp.trade_size.raw(), crate::types::usd_to_raw(500.0 / 3000.0, 18));
+        assert!((opp.trade_size_usd - 500.0).abs() < 1e-6, "size {}", opp.trade_size_usd);
+        // ~1% spread less fees on $500, not on $1.5M
+        assert!(opp.estimated_profit > 0.5 && opp.estimated_profit < 5.0, "profit {}", opp.estimated_profit);
+    }
+
+    #[test]
+    fn test_stale_legs_dropped_by_block_age() {
+        let config = create_test_config();
+        let usdc = config.quote_token_address;
+        let pair = TradingPair::new(usdc, Address::from_low_u64_be(WETH), "WETH/USDC".to_string());
+        let pool = |addr: u64, dex: DexType, fee: u32, price: f64, last_updated: u64| {
+            let mut pool = tri_pool(addr, dex, pair.clone(), (6, 18), fee, 1.0 / price);
+            pool.sqrt_price_x96 = crate::mempool::ticks::sqrt_ratio_at_tick(pool.tick).unwrap();
+            pool.liquidity = 10u128.pow(20);
+            pool.last_updated = last_updated;
+            pool
+        };
+        // Current block 110: pool 1 fresh, pool 2 one block behind, pool 3 five behind
+        let state_manager = PoolStateManager::new();
+        state_manager.update_v3_pool(pool(1, DexType::UniswapV3_005, 500, 1960.0, 110));
+        state_manager.update_v3_pool(pool(2, DexType::SushiV3_005, 500, 2000.0, 109));
+        state_manager.update_v3_pool(pool(3, DexType::UniswapV3_030, 3000, 2040.0, 105));
+        let detector = OpportunityDetector::new(config, state_manager.clone());
+
+        let legs = |opp: &ArbitrageOpportunity| [opp.buy_pool_address.unwrap(), opp.sell_pool_address.unwrap()];
+        let stale = Address::from_low_u64_be(3);
+        let opps = detector.check_pair_unified("WETH/USDC");
+        assert!(opps.iter().any(|o| legs(o).contains(&stale)));
+        assert!(opps.iter().any(|o| !legs(o).contains(&stale)));
+
+        let kept = detector.drop_stale_opportunities(opps.clone(), 110);
+        assert!(!kept.is_empty());
+        assert!(kept.iter().all(|o| !legs(o).contains(&stale)));
+        assert_eq!(kept.len(), opps.iter().filter(|o| !legs(o).contains(&stale)).count());
+        assert_eq!(state_manager.aged_pool_count(110, 2), 1);
+
+        // Event sync saw no log for pool 3 this block: unchanged, so current
+        state_manager.mark_synced(&[stale], 110);
+        assert_eq!(detector.drop_stale_opportunities(opps.clone(), 110).len(), opps.len());
+        // Everything ages out when sync stops
+        assert!(detector.drop_stale_opportunities(opps, 113).is_empty());
+    }
+
+    const WETH: u64 = 0xe7;
+    const WMATIC: u64 = 0x3a;
+
+    /// V3 pool at the tick nearest `price` (token1 per token0, human units)
+    fn tri_pool(pool: u64, dex: DexType, pair: TradingPair, decimals: (u8, u8), fee: u32, price: f64) -> V3PoolState {
+        let scale = 10f64.powi(decimals.0 as i32 - decimals.1 as i32);
+        V3PoolState {
+            address: Address::from_low_u64_be(pool),
+            dex,
+            pair,
+            sqrt_price_x96: U256::zero(),
+            tick: ((price / scale).ln() / 1.0001f64.ln()).round() as i32,
+            fee,
+            liquidity: 1_000_000_000_000_000,
+            token0_decimals: decimals.0,
+            token1_decimals: decimals.1,
+            last_updated: 100,
+        }
+    }
+
+    /// USDC → WETH → WMATIC → USDC with WETH at $2000, WMATIC at $0.50 and the
+    /// WMATIC/WETH pool quoting `wmatic_per_weth` (fair = 4000)
+    fn triangle_detector(wmatic_per_weth: f64) -> OpportunityDetector {
+        let mut config = create_test_config();
+        let usdc = config.quote_token_address;
+        let (weth, wmatic) = (Address::from_low_u64_be(WETH), Address::from_low_u64_be(WMATIC));
+        let pairs = [
+            TradingPair::new(usdc, weth, "WETH/USDC".to_string()),
+            TradingPair::new(weth, wmatic, "WMATIC/WETH".to_string()),
+            TradingPair::new(wmatic, usdc, "WMATIC/USDC".to_string()),
+        ];
+        config.pairs = pairs.iter().map(|pair| TradingPairConfig {
+            token0: format!("{:?}", pair.token0),
+            token1: format!("{:?}", pair.token1),
+            symbol: pair.symbol.clone(),
+        }).collect();
+
+        let state_manager = PoolStateManager::new();
+        let [usdc_weth, weth_wmatic, wmatic_usdc] = pairs;
+        state_manager.update_v3_pool(tri_pool(1, DexType::UniswapV3_005, usdc_weth, (6, 18), 500, 1.0 / 2000.0));
+        state_manager.update_v3_pool(tri_pool(2, DexType::UniswapV3_030, weth_wmatic, (18, 18), 3000, wmatic_per_weth));
+        state_manager.update_v3_pool(tri_pool(3, DexType::SushiV3_005, wmatic_usdc, (18, 6), 500, 0.5));
+        OpportunityDetector::new(config, state_manager)
+    }
+
+    #[test]
+    fn test_triangular_cycle_profitable_after_fees() {
+        // WMATIC/WETH 2.5% rich: 1.025 × (1 − 0.0005)(1 − 0.003)(1 − 0.0005) ≈ 1.0209
+        let detector = triangle_detector(4100.0);
+        let opps = detector.scan_triangular_opportunities();
+        assert_eq!(opps.len(), 1, "only the forward direction pays");
+        let opp = &opps[0];
+
+        let usdc = detector.config.quote_token_address;
+        let (weth, wmatic) = (Address::from_low_u64_be(WETH), Address::from_low_u64_be(WMATIC));
+        let path: Vec<(Address, Address)> = opp.legs.iter().map(|l| (l.token_in, l.token_out)).collect();
+        assert_eq!(path, vec![(usdc, weth), (weth, wmatic), (wmatic, usdc)]);
+        assert_eq!(opp.route, "USDC→WETH→WMATIC→USDC");
+        assert_eq!(opp.dex_path(), "UniswapV3_005→UniswapV3_030→SushiV3_005");
+
+        let expected_spread = (1.025 * 0.9995 * 0.997 * 0.9995 - 1.0) * 100.0;
+        assert!((opp.spread_percent - expected_spread).abs() < 0.05, "spread {}", opp.spread_percent);
+        assert!((opp.cycle_rate() - 1.0 - opp.spread_percent / 100.0).abs() < 1e-12);
+        // $500 and the $5 minProfit at USDC's 6 decimals
+        assert_eq!(opp.trade_size.raw(), U256::from(500_000_000u64));
+        assert_eq!(opp.min_profit_raw, U256::from(5_000_000u64));
+        let gross = opp.spread_percent / 100.0 * 500.0;
+        assert!((opp.estimated_profit - (gross * 0.99 - 0.05 * TRIANGULAR_GAS_FACTOR)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_triangular_min_profit_raw_from_pair_limits() {
+        // Any leg's pair tightens the whole cycle
+        let mut detector = triangle_detector(4100.0);
+        detector.whitelist = limits_whitelist(
+            "",
+            r#"{ "pair": "WETH/USDC", "max_trade_size_usd": 400.0 },
+               { "pair": "WMATIC/WETH", "min_profit_usd": 6.0 }"#,
+        );
+        let opps = detector.scan_triangular_opportunities();
+        assert_eq!(opps.len(), 1);
+        assert_eq!(opps[0].trade_size.raw(), U256::from(400_000_000u64));
+        assert_eq!(opps[0].min_profit_raw, U256::from(6_000_000u64));
+    }
+
+    #[test]
+    fn test_triangular_cycle_unprofitable_after_fees() {
+        // 0.25% mispricing is below the 0.40% paid in fees over three legs
+        assert!(triangle_detector(4010.0).scan_triangular_opportunities().is_empty());
+        // Fair prices: neither direction pays
+        assert!(triangle_detector(4000.0).scan_triangular_opportunities().is_empty());
+    }
+
+    /// Captured multi-protocol state: V3 (Uniswap, Sushi, Algebra), V2
+    /// (allowed and legacy DEXes), Curve, both quote token variants, an
+    /// unsynced sqrtPriceX96, and stale / quarantined / thin pools that must
+    /// stay out of detection.
+    fn golden_detector() -> OpportunityDetector {
+        let (config, sm) = golden_state();
+        OpportunityDetector::new(config, sm)
+    }
+
+    /// Golden detector with SPREAD_CONFIRM_BLOCKS = 2 over a 3-block history
+    fn confirming_detector() -> (OpportunityDetector, Vec<ArbitrageOpportunity>) {
+        let (mut config, sm) = golden_state();
+        config.spread_confirm_blocks = 2;
+        config.spread_history_blocks = 3;
+        let detector = OpportunityDetector::new(config, sm);
+        let opps = detector.scan_opportunities();
+        assert!(!opps.is_empty());
+        (detector, opps)
+    }
+
+    #[test]
+    fn test_single_block_spread_suppressed() {
+        let (mut detector, opps) = confirming_detector();
+        assert!(detector.confirm_spreads(opps.clone(), 100).is_empty());
+        // A gap resets the count: seen at 100, gone at 101, back at 102
+        assert!(detector.confirm_spreads(vec![], 101).is_empty());
+        assert!(detector.confirm_spreads(opps, 102).is_empty());
+
+        // Default (1 block) passes everything through
+        let detector = &mut golden_detector();
+        let opps = detector.scan_opportunities();
+        assert_eq!(detector.confirm_spreads(opps.clone(), 100).len(), opps.len());
+    }
+
+    #[test]
+    fn test_spread_persisting_two_blocks_emitted() {
+        let (mut detector, opps) = confirming_detector();
+        assert!(detector.confirm_spreads(opps.clone(), 100).is_empty());
+        // Rescanning the same block doesn't age the spread
+        assert!(detector.confirm_spreads(opps.clone(), 100).is_empty());
+        assert_eq!(detector.confirm_spreads(opps.clone(), 101).len(), opps.len());
+        assert_eq!(detector.confirm_spreads(opps.clone(), 102).len(), opps.len());
+    }
+
+    #[test]
+    fn test_spread_history_evicts_quiet_routes() {
+        let (mut detector, opps) = confirming_detector();
+        detector.confirm_spreads(opps.clone(), 100);
+        let routes = detector.spread_history.routes.len();
+        assert!(routes > 0);
+        // Within the 3-block window the routes are remembered
+        detector.confirm_spreads(vec![], 102);
+        assert_eq!(detector.spread_history.routes.len(), routes);
+        // Quiet for the whole window: evicted, confirmation starts over
+        detector.confirm_spreads(vec![], 103);
+        assert_eq!(detector.spread_history.routes.len(), 0);
+        assert!(detector.confirm_spreads(opps, 104).is_empty());
+
+        // The ring buffer holds at most the window per route
+        let mut history = SpreadHistory::new(2, 3);
+        let route = ("WETH/USDC".to_string(), DexType::UniswapV3_005, DexType::UniswapV3_030);
+        let ages: Vec<u64> = (10..16).map(|block| history.record(route.clone(), block, 0.5)).collect();
+        assert_eq!(ages, vec![1, 2, 3, 3, 3, 3]);
+        assert_eq!(history.routes[&route].len(), 3);
+    }
+
+    #[test]
+    fn test_flash_v2_flags_only_v2_buys_above_the_floor() {
+        let (mut config, sm) = golden_state();
+        config.flash_v2_enabled = true;
+        let opps = OpportunityDetector::new(config.clone(), sm).scan_opportunities();
+        assert!(opps.iter().any(|opp| opp.flash_v2_eligible));
+        for opp in &opps {
+            let floor = opp.min_profit_usd.unwrap_or(config.min_profit_usd);
+            let cleared = flashloan::flash_v2_terms(opp)
+                .is_some_and(|terms| flashloan::flash_v2_net_profit_usd(opp, &terms) >= floor);
+            assert_eq!(opp.flash_v2_eligible, cleared, "{:?} → {:?}", opp.buy_dex, opp.sell_dex);
+            assert!(!opp.flash_v2_eligible || opp.buy_dex.is_v2());
+        }
+
+        // A floor above every route's profit flags none
+        let (mut config, sm) = golden_state();
+        config.flash_v2_enabled = true;
+        config.min_profit_usd = 1e9;
+        let mut opps = OpportunityDetector::new(config, sm).scan_opportunities();
+        assert_eq!(flashloan::mark_flash_v2_eligible(&mut opps, 1e9), 0);
+    }
+
+    /// Config and pool states behind golden_detector() (also replayed
+    /// through the paper engine's live-parity strategy)
+    pub(crate) fn golden_state() -> (BotConfig, PoolStateManager) {
+        let mut config = create_test_config();
+        config.curve_enabled = true;
+        config.min_profit_usd = 1.0;
+        let usdc = config.quote_token_address;
+        let native = Address::from_low_u64_be(0xc359);
+        config.quote_token_address_native = Some(native);
+        let (weth, wmatic, usdt) = (Address::from_low_u64_be(WETH), Address::from_low_u64_be(WMATIC), Address::from_low_u64_be(0xc2));
+
+        let weth_usdc = TradingPair::new(usdc, weth, "WETH/USDC".to_string());
+        let weth_native = TradingPair::new(native, weth, "WETH/USDC".to_string());
+        let wmatic_usdc = TradingPair::new(wmatic, usdc, "WMATIC/USDC".to_string());
+        let wmatic_weth = TradingPair::new(weth, wmatic, "WMATIC/WETH".to_string());
+        let (t0, t1) = if usdc < usdt { (usdc, usdt) } else { (usdt, usdc) };
+        let usdt_usdc = TradingPair::new(t0, t1, "USDT/USDC".to_string());
+        config.pairs = [&weth_usdc, &wmatic_usdc, &wmatic_weth, &usdt_usdc].iter().map(|pair| TradingPairConfig {
+            token0: format!("{:?}", pair.token0),
+            token1: format!("{:?}", pair.token1),
+            symbol: pair.symbol.clone(),
+        }).collect();
+
+        let v3 = |addr: u64, dex: DexType, pair: &TradingPair, decimals: (u8, u8), fee: u32, price: f64, liquidity: u128| {
+            let mut pool = tri_pool(addr, dex, pair.clone(), decimals, fee, price);
+            pool.sqrt_price_x96 = crate::mempool::ticks::sqrt_ratio_at_tick(pool.tick).unwrap();
+            pool.liquidity = liquidity;
+            pool
+        };
+        let v2 = |addr: u64, dex: DexType, pair: &TradingPair, decimals: (u8, u8), reserve0: u128, reserve1: u128| PoolState {
+            address: Address::from_low_u64_be(addr),
+            dex,
+            pair: pair.clone(),
+            reserve0: U256::from(reserve0),
+            reserve1: U256::from(reserve1),
+            last_updated: 100,
+            token0_decimals: decimals.0,
+            token1_decimals: decimals.1,
+        };
+        let e18 = 10u128.pow(18);
+        let e6 = 10u128.pow(6);
+
+        let sm = PoolStateManager::new();
+        // WETH/USDC (USDC is token0)
+        sm.update_v3_pool(v3(0x11, DexType::UniswapV3_005, &weth_usdc, (6, 18), 500, 1.0 / 1960.0, 10u128.pow(20)));
+        sm.update_v3_pool(v3(0x12, DexType::SushiV3_005, &weth_usdc, (6, 18), 500, 1.0 / 2000.0, 10u128.pow(20)));
+        sm.update_v3_pool(v3(0x13, DexType::UniswapV3_030, &weth_usdc, (6, 18), 3000, 1.0 / 1985.0, 10u128.pow(15)));
+        sm.update_v3_pool(v3(0x14, DexType::QuickswapV3, &weth_usdc, (6, 18), 900, 1.0 / 2010.0, 10u128.pow(18)));
+        let mut unsynced = v3(0x15, DexType::UniswapV3_001, &weth_usdc, (6, 18), 100, 1.0 / 1990.0, 10u128.pow(19));
+        unsynced.sqrt_price_x96 = U256::zero();
+        sm.update_v3_pool(unsynced);
+        sm.update_v3_pool(v3(0x16, DexType::SushiV3_030, &weth_usdc, (6, 18), 3000, 1.0 / 1800.0, 10u128.pow(20)));
+        sm.update_v3_pool(v3(0x17, DexType::SushiV3_001, &weth_usdc, (6, 18), 100, 1.0 / 2200.0, 10u128.pow(20)));
+        sm.update_v3_pool(v3(0x18, DexType::UniswapV3_100, &weth_usdc, (6, 18), 500, 1.0 / 2300.0, 500_000_000));
+        sm.mark_stale(&[Address::from_low_u64_be(0x16)]);
+        sm.quarantine_pool(Address::from_low_u64_be(0x17));
+        sm.update_pool(v2(0x21, DexType::QuickSwapV2, &weth_usdc, (6, 18), 2_000_000 * e6, 1010 * e18));
+        sm.update_pool(v2(0x22, DexType::SushiSwapV2, &weth_usdc, (6, 18), 50_000 * e6, 245 * e18 / 10));
+        sm.update_pool(v2(0x23, DexType::Uniswap, &weth_usdc, (6, 18), 1_000_000 * e6, 450 * e18));
+        // WETH/USDC on native USDC: only compared with each other
+        sm.update_v3_pool(v3(0x31, DexType::UniswapV3_005, &weth_native, (6, 18), 500, 1.0 / 1900.0, 10u128.pow(20)));
+        sm.update_v3_pool(v3(0x32, DexType::SushiV3_005, &weth_native, (6, 18), 500, 1.0 / 1950.0, 10u128.pow(20)));
+        // WMATIC/USDC (USDC is token1)
+        sm.update_v3_pool(v3(0x41, DexType::UniswapV3_005, &wmatic_usdc, (18, 6), 500, 0.500, 10u128.pow(18)));
+        sm.update_v3_pool(v3(0x42, DexType::UniswapV3_030, &wmatic_usdc, (18, 6), 3000, 0.508, 10u128.pow(18)));
+        sm.update_pool(v2(0x43, DexType::QuickSwapV2, &wmatic_usdc, (18, 6), 4_000_000 * e18, 1_988_000 * e6));
+        // WMATIC/WETH (triangles only)
+        sm.update_v3_pool(v3(0x51, DexType::UniswapV3_030, &wmatic_weth, (18, 18), 3000, 4100.0, 10u128.pow(22)));
+        sm.update_pool(v2(0x52, DexType::SushiSwapV2, &wmatic_weth, (18, 18), 100 * e18, 395_000 * e18));
+        // USDT/USDC: Curve vs V3
+        let usdc_is_token0 = t0 == usdc;
+        sm.update_curve_pool(CurvePoolState {
+            address: Address::from_low_u64_be(0x61),
+            dex: DexType::CurveStable,
+            pair: usdt_usdc.clone(),
+            index0: if usdc_is_token0 { 0 } else { 1 },
+            index1: if usdc_is_token0 { 1 } else { 0 },
+            balances: vec![U256::from(8_000_000u64) * U256::exp10(6), U256::from(2_000_000u64) * U256::exp10(6)],
+            decimals: vec![6, 6],
+            amp: U256::from(200),
+            fee: U256::from(1_000_000),
+            token0_decimals: 6,
+            token1_decimals: 6,
+            last_updated: 100,
+        });
+        sm.update_v3_pool(v3(0x62, DexType::UniswapV3_001, &usdt_usdc, (6, 6), 100, 1.0, 10u128.pow(15)));
+
+        (config, sm)
+    }
+
+    /// One canonical line per detection (order-independent, wall-clock free)
+    fn golden_lines(detector: &OpportunityDetector) -> Vec<String> {
+        let mut lines: Vec<String> = detector
+            .scan_opportunities()
+            .iter()
+            .map(|o| {
+                let snaps = o.pool_snapshots.as_ref().unwrap();
+                format!(
+                    "pair {} {:?}->{:?} {:?}/{:?} buy={:.12e} sell={:.12e} spread={:.9} profit={:.6} size={} usd={:.6} \
+                     liq={:?}/{:?} q0={} dec={}/{} fees={}/{} state={:?}|{:?}",
+                    o.pair.symbol, o.buy_dex, o.sell_dex, o.buy_pool_address.unwrap(), o.sell_pool_address.unwrap(),
+                    o.buy_price, o.sell_price, o.spread_percent, o.estimated_profit, o.trade_size.raw(), o.trade_size_usd,
+                    o.buy_pool_liquidity.unwrap(), o.sell_pool_liquidity.unwrap(), o.quote_token_is_token0,
+                    o.token0_decimals, o.token1_decimals, snaps.buy.fee, snaps.sell.fee, snaps.buy.state, snaps.sell.state,
+                )
+            })
+            .chain(detector.scan_triangular_opportunities().iter().map(|t| {
+                let pools: Vec<String> = t.legs.iter().map(|l| format!("{:?}@{:.12e}", l.pool_address, l.rate)).collect();
+                format!(
+                    "tri {} {} {} spread={:.9} profit={:.6} size={}",
+                    t.route, t.dex_path(), pools.join(","), t.spread_percent, t.estimated_profit, t.trade_size.raw()
+                )
+            }))
+            .collect();
+        lines.sort();
+        lines
+    }
+
+    /// Scan time on the golden state (`cargo test --release -- --ignored bench_scan`)
+    #[test]
+    #[ignore]
+    fn bench_scan_on_golden_state() {
+        let detector = golden_detector();
+        let runs = 2_000;
+        let start = std::time::Instant::now();
+        for _ in 0..runs {
+            std::hint::black_box(detector.scan_opportunities());
+        }
+        println!("scan_opportunities: {:.1}µs/scan over {} scans", start.elapsed().as_secs_f64() * 1e6 / runs as f64, runs);
+    }
+
+    #[test]
+    fn test_golden_detection_on_captured_state() {
+        let expected: Vec<&str> = include_str!("testdata/detector_golden.txt").lines().collect();
+        let lines = golden_lines(&golden_detector());
+        for (got, want) in lines.iter().zip(&expected) {
+            assert_eq!(got, want);
+        }
+        assert_eq!(lines.len(), expected.len(), "{:#?}", lines);
+    }
 }
@@ -0,0 +1,415 @@
+//! Execution-Constraint Analyzer (research)
+//!
+//! Measures the profit left on the table by executing one two-leg, single-pair
+//! arb per block. For every block where more than one opportunity passed the
+//! multicall pre-screen it compares:
+//!   gross_quoted — sum of all verified quoted profits
+//!   combinable   — best set of mutually non-conflicting opportunities (what a
+//!                  multi-pool executor could have bundled)
+//!   realized     — gross profit of the trade(s) we actually took
+//!   unrealized   — combinable − realized, floored at 0 (what a fancier contract
+//!                  could recover)
+//!
+//! Conflicts: two opportunities conflict when they touch the same pool (buy or
+//! sell leg). Trading a pool moves its price, so the second quote is stale and
+//! cannot be counted. Opportunities without a pool address fall back to
+//! (pair, DEX) as the pool identity. The combinable set is the exact
+//! maximum-weight conflict-free subset (branch and bound) for up to
+//! EXACT_SEARCH_LIMIT candidates, greedy by quoted profit beyond that.
+//!
+//! Running totals are reported once per UTC day; every analyzed block is
+//! appended to data/{chain}/research/execution_constraints.csv.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::types::{ArbitrageOpportunity, DexType};
+use chrono::{NaiveDate, Utc};
+use ethers::types::{Address, U256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// CSV header for execution_constraints.csv
+const CSV_HEADER: &str = "timestamp,block,verified,conflicting_pairs,gross_quoted_usd,combinable_usd,\
+combinable_count,realized_usd,taken,unrealized_usd,cumulative_unrealized_usd";
+
+/// Candidate count up to which the conflict-free optimum is searched exactly
+pub const EXACT_SEARCH_LIMIT: usize = 24;
+
+/// Pool identity for conflict detection
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PoolId {
+    Address(Address),
+    /// No address known: (pair symbol, DEX/fee tier)
+    Route(String, DexType),
+}
+
+/// One pre-screen-verified opportunity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub buy_pool: PoolId,
+    pub sell_pool: PoolId,
+    pub quoted_profit_usd: f64,
+}
+
+impl Candidate {
+    pub fn new(opp: &ArbitrageOpportunity, quoted_profit_raw: i128) -> Self {
+        let pool = |addr: Option<Address>, dex| match addr {
+            Some(a) => PoolId::Address(a),
+            None => PoolId::Route(opp.pair.symbol.clone(), dex),
+        };
+        Self {
+            buy_pool: pool(opp.buy_pool_address, opp.buy_dex),
+            sell_pool: pool(opp.sell_pool_address, opp.sell_dex),
+            quoted_profit_usd: opp.quote_to_usd(opp.quote_amount(U256::from(quoted_profit_raw.max(0) as u128))),
+        }
+    }
+
+    /// True if both would trade through a common pool
+    pub fn conflicts_with(&self, other: &Candidate) -> bool {
+        self.buy_pool == other.buy_pool
+            || self.buy_pool == other.sell_pool
+            || self.sell_pool == other.buy_pool
+            || self.sell_pool == other.sell_pool
+    }
+}
+
+/// Number of conflicting candidate pairs
+pub fn conflicting_pairs(candidates: &[Candidate]) -> usize {
+    let mut n = 0;
+    for (i, a) in candidates.iter().enumerate() {
+        n += candidates[i + 1..].iter().filter(|b| a.conflicts_with(b)).count();
+    }
+    n
+}
+
+/// Maximum-profit conflict-free subset: (indices into `candidates`, total USD).
+/// Non-positive quotes never help and are excluded.
+pub fn best_conflict_free(candidates: &[Candidate]) -> (Vec<usize>, f64) {
+    let mut order: Vec<usize> = (0..candidates.len())
+        .filter(|&i| candidates[i].quoted_profit_usd > 0.0)
+        .collect();
+    order.sort_by(|&a, &b| {
+        candidates[b]
+            .quoted_profit_usd
+            .partial_cmp(&candidates[a].quoted_profit_usd)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    if order.len() > EXACT_SEARCH_LIMIT {
+        let mut chosen: Vec<usize> = Vec::new();
+        for &i in &order {
+            if chosen.iter().all(|&j| !candidates[i].conflicts_with(&candidates[j])) {
+                chosen.push(i);
+            }
+        }
+        let total = chosen.iter().map(|&i| candidates[i].quoted_profit_usd).sum();
+        return (chosen, total);
+    }
+
+    // suffix[k] = sum of profits of order[k..] — upper bound for pruning
+    let mut suffix = vec![0.0; order.len() + 1];
+    for k in (0..order.len()).rev() {
+        suffix[k] = suffix[k + 1] + candidates[order[k]].quoted_profit_usd;
+    }
+    let mut search = Search { candidates, order: &order, suffix: &suffix, best: (Vec::new(), 0.0) };
+    search.run(0, &mut Vec::new(), 0.0);
+    search.best
+}
+
+struct Search<'a> {
+    candidates: &'a [Candidate],
+    order: &'a [usize],
+    suffix: &'a [f64],
+    best: (Vec<usize>, f64),
+}
+
+impl Search<'_> {
+    fn run(&mut self, k: usize, chosen: &mut Vec<usize>, total: f64) {
+        if total > self.best.1 {
+            self.best = (chosen.clone(), total);
+        }
+        if k == self.order.len() || total + self.suffix[k] <= self.best.1 {
+            return;
+        }
+        let i = self.order[k];
+        if chosen.iter().all(|&j| !self.candidates[i].conflicts_with(&self.candidates[j])) {
+            chosen.push(i);
+            self.run(k + 1, chosen, total + self.candidates[i].quoted_profit_usd);
+            chosen.pop();
+        }
+        self.run(k + 1, chosen, total);
+    }
+}
+
+/// One analyzed block
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockConstraintRecord {
+    pub block: u64,
+    pub verified: usize,
+    pub conflicting_pairs: usize,
+    pub gross_quoted_usd: f64,
+    pub combinable_usd: f64,
+    pub combinable_count: usize,
+    pub realized_usd: f64,
+    pub taken: usize,
+    pub unrealized_usd: f64,
+}
+
+/// Running totals since start (or since the last daily report)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConstraintTotals {
+    pub blocks: u64,
+    pub gross_quoted_usd: f64,
+    pub combinable_usd: f64,
+    pub realized_usd: f64,
+    pub unrealized_usd: f64,
+}
+
+impl ConstraintTotals {
+    fn add(&mut self, r: &BlockConstraintRecord) {
+        self.blocks += 1;
+        self.gross_quoted_usd += r.gross_quoted_usd;
+        self.combinable_usd += r.combinable_usd;
+        self.realized_usd += r.realized_usd;
+        self.unrealized_usd += r.unrealized_usd;
+    }
+}
+
+/// Accumulates per-block execution-constraint measurements
+pub struct ExecutionConstraintAnalyzer {
+    csv_path: Option<PathBuf>,
+    day: Option<NaiveDate>,
+    daily: ConstraintTotals,
+    cumulative: ConstraintTotals,
+}
+
+impl Default for ExecutionConstraintAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExecutionConstraintAnalyzer {
+    pub fn new() -> Self {
+        Self { csv_path: None, day: None, daily: ConstraintTotals::default(), cumulative: ConstraintTotals::default() }
+    }
+
+    /// Append analyzed blocks to `{research_dir}/execution_constraints.csv`
+    pub fn with_persistence(mut self, research_dir: &str) -> Self {
+        if let Err(e) = fs::create_dir_all(research_dir) {
+            warn!("Execution constraints: failed to create {}: {}", research_dir, e);
+        }
+        self.csv_path = Some(PathBuf::from(research_dir).join("execution_constraints.csv"));
+        self
+    }
+
+    pub fn cumulative(&self) -> &ConstraintTotals {
+        &self.cumulative
+    }
+
+    /// Record one block. `candidates` = verified, quoted-profitable opportunities;
+    /// `realized_usd` = gross profit of the trades taken (0 for failed attempts).
+    /// Blocks with fewer than two candidates are not analyzed (no constraint bites).
+    pub fn record_block(
+        &mut self,
+        block: u64,
+        candidates: &[Candidate],
+        realized_usd: f64,
+        taken: usize,
+    ) -> Option<BlockConstraintRecord> {
+        self.record_block_on(Utc::now().date_naive(), block, candidates, realized_usd, taken)
+    }
+
+    fn record_block_on(
+        &mut self,
+        today: NaiveDate,
+        block: u64,
+        candidates: &[Candidate],
+        realized_usd: f64,
+        taken: usize,
+    ) -> Option<BlockConstraintRecord> {
+        self.roll_day(today);
+        if candidates.len() < 2 {
+            return None;
+        }
+
+        let (combinable, combinable_usd) = best_conflict_free(candidates);
+        let record = BlockConstraintRecord {
+            block,
+            verified: candidates.len(),
+            conflicting_pairs: conflicting_pairs(candidates),
+            gross_quoted_usd: candidates.iter().map(|c| c.quoted_profit_usd.max(0.0)).sum(),
+            combinable_usd,
+            combinable_count: combinable.len(),
+            realized_usd,
+            taken,
+            unrealized_usd: (combinable_usd - realized_usd).max(0.0),
+        };
+        self.daily.add(&record);
+        self.cumulative.add(&record);
+        self.append_csv(&record);
+        Some(record)
+    }
+
+    /// Report and reset the daily totals when the UTC date changes
+    fn roll_day(&mut self, today: NaiveDate) {
+        match self.day {
+            Some(day) if day != today => {
+                info!("{}", self.daily_report(day));
+                self.daily = ConstraintTotals::default();
+                self.day = Some(today);
+            }
+            None => self.day = Some(today),
+            _ => {}
+        }
+    }
+
+    /// One-line daily summary
+    pub fn daily_report(&self, day: NaiveDate) -> String {
+        let d = &self.daily;
+        format!(
+            "📐 Execution constraints {}: {} multi-opp blocks | quoted ${:.2} | combinable ${:.2} | realized ${:.2} | \
+             unrealized ${:.2} (cumulative ${:.2})",
+            day, d.blocks, d.gross_quoted_usd, d.combinable_usd, d.realized_usd, d.unrealized_usd,
+            self.cumulative.unrealized_usd
+        )
+    }
+
+    fn append_csv(&self, r: &BlockConstraintRecord) {
+        let path = match self.csv_path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let is_new = !path.exists();
+        let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(f) => f,
+            Err(e) => {
+                warn!("Execution constraints: failed to open {}: {}", path.display(), e);
+                return;
+            }
+        };
+        if is_new {
+            let _ = writeln!(file, "{}", CSV_HEADER);
+        }
+        let _ = writeln!(
+            file,
+            "{},{},{},{},{:.4},{:.4},{},{:.4},{},{:.4},{:.4}",
+            Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            r.block,
+            r.verified,
+            r.conflicting_pairs,
+            r.gross_quoted_usd,
+            r.combinable_usd,
+            r.combinable_count,
+            r.realized_usd,
+            r.taken,
+            r.unrealized_usd,
+            self.cumulative.unrealized_usd,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(n: u64) -> PoolId {
+        PoolId::Address(Address::from_low_u64_be(n))
+    }
+
+    fn cand(buy: u64, sell: u64, usd: f64) -> Candidate {
+        Candidate { buy_pool: pool(buy), sell_pool: pool(sell), quoted_profit_usd: usd }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, d).unwrap()
+    }
+
+    #[test]
+    fn test_conflict_free_optimum_beats_greedy() {
+        // A(1,2)=$5 conflicts with B(2,3)=$4 and C(1,4)=$4; B and C are disjoint.
+        // Greedy takes A ($5); the optimum is B+C ($8).
+        let c = vec![cand(1, 2, 5.0), cand(2, 3, 4.0), cand(1, 4, 4.0)];
+        assert_eq!(conflicting_pairs(&c), 2);
+        let (mut set, total) = best_conflict_free(&c);
+        set.sort();
+        assert_eq!((set, total), (vec![1, 2], 8.0));
+
+        // Sell-leg-only overlap also conflicts; non-positive quotes are ignored
+        let c = vec![cand(1, 9, 3.0), cand(2, 9, 2.0), cand(5, 6, -1.0)];
+        assert_eq!(best_conflict_free(&c), (vec![0], 3.0));
+    }
+
+    #[test]
+    fn test_route_identity_without_addresses() {
+        let a = Candidate {
+            buy_pool: PoolId::Route("WETH/USDC".into(), DexType::UniswapV3_005),
+            sell_pool: PoolId::Route("WETH/USDC".into(), DexType::UniswapV3_030),
+            quoted_profit_usd: 1.0,
+        };
+        let mut b = a.clone();
+        b.sell_pool = PoolId::Route("WETH/USDC".into(), DexType::SushiV3_030);
+        assert!(a.conflicts_with(&b)); // shared UniswapV3_005 buy pool
+        b.buy_pool = PoolId::Route("WBTC/USDC".into(), DexType::UniswapV3_005);
+        assert!(!a.conflicts_with(&b));
+    }
+
+    #[test]
+    fn test_accounting_across_blocks_and_days() {
+        let mut analyzer = ExecutionConstraintAnalyzer::new();
+
+        // Block 1: two disjoint opps ($3 + $2), we took the $3 one and realized $2.50
+        let r = analyzer
+            .record_block_on(day(16), 1, &[cand(1, 2, 3.0), cand(3, 4, 2.0)], 2.5, 1)
+            .unwrap();
+        assert_eq!((r.conflicting_pairs, r.combinable_count), (0, 2));
+        assert_eq!((r.gross_quoted_usd, r.combinable_usd, r.unrealized_usd), (5.0, 5.0, 2.5));
+
+        // Block 2: two opps sharing pool 1 — only the best counts; we captured it
+        let r = analyzer
+            .record_block_on(day(16), 2, &[cand(1, 2, 3.0), cand(1, 5, 2.0)], 3.0, 1)
+            .unwrap();
+        assert_eq!((r.conflicting_pairs, r.combinable_usd, r.unrealized_usd), (1, 3.0, 0.0));
+
+        // Block 3: single candidate — not analyzed
+        assert!(analyzer.record_block_on(day(16), 3, &[cand(1, 2, 9.0)], 0.0, 0).is_none());
+
+        // Block 4: realized above quote never yields negative unrealized
+        let r = analyzer
+            .record_block_on(day(16), 4, &[cand(1, 2, 1.0), cand(1, 3, 1.0)], 1.4, 1)
+            .unwrap();
+        assert_eq!(r.unrealized_usd, 0.0);
+        assert_eq!(analyzer.daily.blocks, 3);
+        assert_eq!(analyzer.daily.unrealized_usd, 2.5);
+
+        // Next UTC day: daily totals reset, cumulative keeps running
+        let r = analyzer
+            .record_block_on(day(17), 5, &[cand(1, 2, 1.0), cand(3, 4, 1.0)], 0.0, 0)
+            .unwrap();
+        assert_eq!(r.unrealized_usd, 2.0);
+        assert_eq!((analyzer.daily.blocks, analyzer.daily.unrealized_usd), (1, 2.0));
+        assert_eq!(analyzer.cumulative().blocks, 4);
+        assert_eq!(analyzer.cumulative().unrealized_usd, 4.5);
+        assert_eq!(analyzer.cumulative().realized_usd, 6.9);
+    }
+
+    #[test]
+    fn test_csv_persistence() {
+        let dir = std::env::temp_dir().join(format!("dexarb_constraints_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut analyzer = ExecutionConstraintAnalyzer::new().with_persistence(dir.to_str().unwrap());
+        analyzer.record_block_on(day(16), 10, &[cand(1, 2, 3.0), cand(3, 4, 2.0)], 0.0, 0);
+        analyzer.record_block_on(day(16), 11, &[cand(1, 2, 1.0), cand(3, 4, 1.0)], 1.0, 1);
+
+        let content = fs::read_to_string(dir.join("execution_constraints.csv")).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].ends_with(",10,2,0,5.0000,5.0000,2,0.0000,0,5.0000,5.0000"), "{}", lines[1]);
+        assert!(lines[2].ends_with(",1.0000,6.0000"), "{}", lines[2]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -12,6 +12,8 @@
 //!     Supports V3↔V3, V2↔V3, and V2↔V2 via fee sentinel routing:
 //!       fee=0 → Algebra (QuickSwap V3), fee=1..65535 → standard V3,
 //!       fee=16777215 (type(uint24).max) → V2 swapExactTokensForTokens.
+//!     Multi-leg (triangular) routes use executeArbMulti(legs[]) with the same
+//!     per-leg fee sentinels when ARB_EXECUTOR_MULTI is set.
 //!   - Legacy two-tx: Separate buy + sell transactions (has leg risk).
 //!     Used as fallback if ARB_EXECUTOR_ADDRESS is not configured.
 //!
@@ -27,15 +29,102 @@
 //! Modified: 2026-01-29 (Post-incident: decimal fix, quoter, event parsing)
 //! Modified: 2026-01-30 (Atomic execution via ArbExecutor.sol contract)
 //! Modified: 2026-01-30 (QuickSwap V3 / Algebra router + quoter support)
-
-use crate::tax::{TaxLogger, TaxRecordBuilder};
-use crate::types::{ArbitrageOpportunity, BotConfig, DexType, TradeResult};
+//! Modified: 2026-10-16 (Block-pinned preflight batch replaces per-call pre-checks)
+//! Modified: 2026-10-16 (Quote-oriented decimals for min_out/minProfit/profit/tax — 8-dec WBTC)
+//! Modified: 2026-10-16 (rescue_tokens: sweep stranded ArbExecutor balances back to the wallet)
+//! Modified: 2026-10-16 (Signer + private RPC provider built once via SigningConnections; submit-path timing)
+//! Modified: 2026-10-16 (Record atomic/mempool bids + landing for gas history)
+//! Modified: 2026-10-16 (Receipt polling via retry::receipt_poll with per-site counters)
+//! Modified: 2026-10-16 (Tax JSON record carries the opportunity's detection-time pool snapshots)
+//! Modified: 2026-10-16 (Timeouts on pre-submission RPC reads: gas price, nonce, estimateGas, Quoter, preflight)
+//! Modified: 2026-10-16 (Sizes/profits logged in token units + USD instead of raw integers)
+//! Modified: 2026-10-16 (Chaos seams: before/after send, receipt poll, quoter, nonce fetch; nonce resync on nonce errors)
+//! Modified: 2026-10-16 (In-flight journal: atomic/mempool txs journaled before send; startup recovery + nonce init)
+//! Modified: 2026-10-16 (TAX_LOG_STRICT: unwritten tax records queued for retry; live submissions halt until written)
+//! Modified: 2026-10-16 (Realized slippage vs pre-screen quotes per route; route-specific min_out allowance)
+//! Modified: 2026-10-16 (Finality: landed trades booked provisionally, re-checked at CONFIRMATION_DEPTH, reversed on reorg)
+//! Modified: 2026-10-16 (Exposure pacing: trade_size held to per-quote-token balance fractions at dispatch)
+//! Modified: 2026-10-16 (trade sizes, balances and realized profits typed as QuoteAmount)
+//! Modified: 2026-10-16 (Competition model win probability logged with atomic submissions)
+//! Modified: 2026-10-16 (Tax records/finality flags written by the lossless tax writer thread; strict halt on a failing or stalled writer)
+//! Modified: 2026-10-16 (Triangular routes: simulated in dry run, rejected live — ArbExecutor.sol is two-leg)
+//! Modified: 2026-10-16 (Multi-leg atomic execution: executeArbMulti + ArbMultiExecuted; live triangular routes with ARB_EXECUTOR_MULTI)
+//! Modified: 2026-10-16 (Live native token price (NativePrice) for gas USD and tax gas_price_usd)
+//! Modified: 2026-10-16 (Flashloan-funded atomic path: executeArbFlash when a flash-eligible route exceeds the wallet balance)
+//! Modified: 2026-10-16 (manage_approvals: batched allowance grant/revoke on tracked nonces; router_address free fn)
+//! Modified: 2026-10-16 (Balance clamp before exposure pacing: trade size ≤ 98% of the quote balance, low balance skipped)
+//! Modified: 2026-10-16 (Tax records stamped with the configured chain_name / chain_id)
+//! Modified: 2026-10-16 (Tax writer keeps a LotLedger (TAX_LOT_METHOD): records split per lot with lot cost basis)
+//! Modified: 2026-10-16 (Atomic priority fee from the gas oracle's fee percentile, clamped + profit-capped; static fee as fallback)
+//! Modified: 2026-10-16 (submit_atomic / wait_for_atomic_receipt / settle_atomic split for parallel submission; gate_live shared with execute)
+//! Modified: 2026-10-16 (Event journal: tx_submitted on atomic send, receipt_parsed on settlement)
+//! Modified: 2026-10-16 (Per-route gas estimate cache: receipt gas_used × multiplier replaces estimateGas; invalidated on revert)
+//! Modified: 2026-10-16 (Stale guard: opportunities older than MAX_OPPORTUNITY_AGE_SECS refused before submission)
+//! Modified: 2026-10-16 (Inventory rebalance: single slippage-protected swap into the quote token, tax record per swap)
+//! Modified: 2026-10-16 (Split routing: executeArbSplit sells the bought base across two pools, ArbSplitExecuted)
+//! Modified: 2026-10-16 (minProfit from the opportunity's adaptive threshold; reverted txs report the gas they burned)
+//! Modified: 2026-10-16 (WETH-quoted trades: minProfit, profit and tax USD values at the quote token's price)
+//! Modified: 2026-10-16 (fill / sign / send and block → submit timed into the shared LatencyRecorder)
+//! Modified: 2026-10-16 (mempool backruns bundled behind the trigger tx when BUNDLE_RPC_URL is set; raw-send fallback)
+//! Modified: 2026-10-16 (PRIVATE_KEYS: per-wallet nonce, balances and in-flight txs; idle wallet selected per submission)
+//! Modified: 2026-10-16 (DODO V2 legs: dodoSwapV2TokenToToken via DODO_V2_PROXY; legacy two-tx only, atomic support deferred)
+//! Modified: 2026-10-16 (Own receipts' Swap/Sync logs applied to the shared PoolStateManager on settlement)
+//! Modified: 2026-10-16 (Fee tax record for every live tx that reverts on-chain: gas only, no proceeds or basis)
+//! Modified: 2026-10-16 (PERMIT2_ADDRESS: wallet-funded atomic trades signed as Permit2 transfers via executeArbWithPermit)
+//! Modified: 2026-10-16 (Tax records take token decimals from the TokenRegistry)
+//! Modified: 2026-10-16 (FLASH_V2_ENABLED: V2-bought routes run as a pair flash swap via executeArbFlashV2)
+//! Modified: 2026-10-16 (Every TradeResult carries the opportunity id it was attempted for)
+//! Modified: 2026-10-16 (Tax writer dead-letters records that cannot be built; only I/O errors retry)
+//! Modified: 2026-10-16 (DODO routes refused before the first leg unless DODO_V2_PROXY and DODO_APPROVE are set)
+//! Modified: 2026-10-16 (ArbExecutor rescues written to the tax ledger as Transfers)
+//! Modified: 2026-10-17 (tax records carry the opportunity's trade_size_usd)
+//! Modified: 2026-10-17 (A receipt RPC error releases the tx's wallet; the journal entry stays for recovery)
+//! Modified: 2026-10-17 (In-flight recovery decodes flash, flash-V2, split and multi execution events)
+//! Modified: 2026-10-17 (ABI struct FlashArb renamed TwoLegArb, as in the contract)
+
+use super::approvals::{self, ApprovalAction, ApprovalSummary, ApprovalTarget};
+use super::connections::{SigningClient, SigningConnections};
+use super::exposure;
+use super::finality::{FinalityCheck, FinalityTracker, ProvisionalTrade, Reversal};
+use super::flashloan::{self, FlashFallback};
+use super::gas_cache::{flash_v2_gas_route_key, gas_route_key, permit_gas_route_key, GasEstimateCache};
+use super::inventory::RebalancePlan;
+use super::permit2::{self, IPermit2, Permit2State, PermitFallback, PermitTransferFrom};
+use super::inflight::{self, ChainStatus, InflightEntry, InflightJournal, RecoveryPolicy, RecoveryReport, Resolution};
+use super::preflight::{PreflightBatcher, PreflightBundle, PreflightRejection, PREFLIGHT_ERROR_PREFIX};
+use super::slippage::{SlippageConfig, SlippageLeg, SlippageTracker};
+use super::stranded::StrandedBalance;
+use super::wallets::{WalletPool, WalletSlot};
+use crate::chaos::{Fault, FaultInjector, FaultPoint, NoFaults};
+use crate::events::{self, BotEvent, EventJournal};
+use crate::gas_logger::SubmissionRecord;
+use crate::gas_oracle;
+use crate::io_writer::{IsolatedWriter, StreamContract};
+use crate::latency::{LatencyRecorder, Stage};
+use crate::mempool::bundle::{BundleInputs, BundleOutcome, BundleSubmitter, TriggerTx};
+use crate::native_price::NativePrice;
+use crate::token_registry::TokenRegistry;
+use crate::pool::dodo_syncer::IDodoV2Pool;
+use crate::pool::events as pool_events;
+use crate::pool::PoolStateManager;
+use crate::retry::{self, Attempt, RetryError, RetrySite};
+use crate::alerts::{AlertDispatcher, AlertSeverity};
+use crate::quote_amount::{QuoteAmount, QuoteToken};
+use crate::tax::{
+    LotLedger, LotMethod, PendingTaxQueue, PendingTaxRecord, RebalanceSwap, RecordFinality, TaxLogger, TaxRecord,
+    TaxRecordBuilder, TaxSource,
+};
+use crate::types::{format_usd, ArbitrageOpportunity, BotConfig, DexType, TradeResult, TriangularOpportunity};
+use crate::watchdog::{rpc_timeout, HOT_RPC_TIMEOUT};
 use anyhow::{anyhow, Result};
+use chrono::Datelike;
 use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
 use rust_decimal::Decimal;
+use std::collections::VecDeque;
+use std::future::Future;
 use std::path::PathBuf;
 use std::str::FromStr;
-use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tracing::{debug, error, info, warn};
@@ -49,6 +138,15 @@ abigen!(
     ]"#
 );
 
+// DODO V2 Proxy ABI (single- and multi-hop PMM swaps; pulls tokens via DODOApprove)
+// directions: bit i = 0 sells base, 1 sells quote on dodoPairs[i]
+abigen!(
+    IDODOV2Proxy02,
+    r#"[
+        function dodoSwapV2TokenToToken(address fromToken, address toToken, uint256 fromTokenAmount, uint256 minReturnAmount, address[] memory dodoPairs, uint256 directions, bool isIncentive, uint256 deadLine) external returns (uint256 returnAmount)
+    ]"#
+);
+
 // Uniswap V3 SwapRouter ABI (exactInputSingle for single-hop V3 swaps)
 // ExactInputSingleParams: (tokenIn, tokenOut, fee, recipient, deadline, amountIn, amountOutMinimum, sqrtPriceLimitX96)
 abigen!(
@@ -102,73 +200,591 @@ abigen!(
 
 // ArbExecutor contract ABI (atomic two-leg arbitrage)
 // Executes both V3 swaps in a single transaction. Reverts if profit < minProfit.
+// executeArbMulti: the same for an ordered leg array (triangular routes); each
+// leg's fee uses the executeArb sentinels.
+// executeArbFlash: executeArb funded by an Aave V3 flashLoanSimple (flashloan.rs).
+// executeArbSplit: one buy leg, the base sold on two pools (sellShareBps on B).
+// executeArbWithPermit: executeArb's legs, token0 pulled through a Permit2
+// signature transfer instead of an allowance (permit2.rs).
+// executeArbFlashV2: the buy leg taken from a V2 pair as a flash swap, repaid
+// from the sell leg in uniswapV2Call (flashloan.rs).
 abigen!(
     IArbExecutor,
     r#"[
+        struct Leg { address tokenIn; address tokenOut; address router; uint24 fee; }
+        struct TwoLegArb { address token0; address token1; address routerBuy; address routerSell; uint24 feeBuy; uint24 feeSell; uint256 amountIn; uint256 minProfit; }
+        struct SplitArb { address token0; address token1; address routerBuy; uint24 feeBuy; address routerSellA; uint24 feeSellA; address routerSellB; uint24 feeSellB; uint16 sellShareBps; uint256 amountIn; uint256 minProfit; }
         function executeArb(address token0, address token1, address routerBuy, address routerSell, uint24 feeBuy, uint24 feeSell, uint256 amountIn, uint256 minProfit) external returns (uint256 profit)
+        function executeArbMulti(Leg[] legs, uint256 amountIn, uint256 minProfit) external returns (uint256 profit)
+        function executeArbFlash(address aavePool, TwoLegArb arb) external returns (uint256 profit)
+        function executeArbSplit(SplitArb arb) external returns (uint256 profit)
+        function executeArbWithPermit(address permit2, TwoLegArb arb, uint256 nonce, uint256 deadline, bytes signature) external returns (uint256 profit)
+        function executeArbFlashV2(address pair, uint256 baseOut, TwoLegArb arb) external returns (uint256 profit)
+        function rescueTokens(address token) external
     ]"#
 );
 
+/// Error prefix for submissions refused while strict tax logging is halted
+pub const TAX_HALT_ERROR_PREFIX: &str = "Tax logging halt: ";
+
+/// Error prefix for opportunities refused as older than MAX_OPPORTUNITY_AGE_SECS
+pub const STALE_OPPORTUNITY_ERROR_PREFIX: &str = "STALE: ";
+
+/// tx_hash of a dry-run result (nothing was sent)
+pub const DRY_RUN_TX_HASH: &str = "DRY_RUN_NO_TX";
+
+/// Atomic (block-triggered) priority fee when the gas oracle has no fee data
+pub const ATOMIC_PRIORITY_FEE_GWEI: u64 = 5000;
+
+/// Outcome of handing an atomic arb to the contract (submit_atomic)
+pub enum AtomicSubmit {
+    /// Sent: settle with settle_atomic() once wait_for_atomic_receipt() resolves
+    Pending(Box<PendingAtomic>),
+    /// Nothing sent (dry run, halt, clamp/pacing/preflight rejection); the
+    /// tracked nonce is untouched
+    Finished(TradeResult),
+    /// Signing or sending failed — the tx may or may not have reached the
+    /// mempool, so nothing further should be laddered on top of it
+    SendFailed(TradeResult),
+}
+
+/// A sent ArbExecutor tx awaiting its receipt
+pub struct PendingAtomic {
+    /// As submitted (after balance clamp / exposure pacing)
+    pub opportunity: ArbitrageOpportunity,
+    pub tx_hash: TxHash,
+    /// Wallet that signed it
+    pub wallet: Address,
+    /// Event journal join key ("{block}:{route}", block at submission)
+    opportunity_id: String,
+    /// Gas cache route (two-leg sends); receipt gas_used / reverts feed back in
+    gas_route: Option<String>,
+    /// In-flight journal entry, cleared on settlement
+    journaled: Option<TxHash>,
+    max_fee: U256,
+    priority_fee: U256,
+    start_time: Instant,
+    parse_event: EventParser,
+}
+
+/// Flash-funded ArbExecutor call for send_atomic()
+enum FlashCall {
+    /// Aave V3 flashLoanSimple of the full size (executeArbFlash)
+    Aave(ExecuteArbFlashCall),
+    /// Buy leg as a flash swap on its V2 pair (executeArbFlashV2)
+    V2(ExecuteArbFlashV2Call, flashloan::FlashV2Terms),
+}
+
+/// What gate_live() cleared for submission (short-lived return value)
+#[allow(clippy::large_enum_variant)]
+enum LiveGate {
+    /// Flash-funded: the original opportunity, at its full size
+    Flash(FlashCall),
+    /// Wallet-funded: the clamped/paced opportunity plus its preflight bundle
+    Ready(ArbitrageOpportunity, Option<PreflightBundle>),
+}
+
+/// Reads (amountOut, profit) raw from an ArbExecutor receipt's logs
+type EventParser = fn(&[Log], Address) -> Option<(U256, U256)>;
+
+/// Every ArbExecutor execution event; topics are distinct, so at most one
+/// matches a receipt
+const EXECUTION_EVENT_PARSERS: [EventParser; 5] = [
+    parse_arb_executed,
+    parse_arb_flash_executed,
+    parse_arb_flash_v2_executed,
+    parse_arb_split_executed,
+    parse_arb_multi_executed,
+];
+
+/// How long shutdown waits for the tax writer to drain
+const TAX_SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Block → submit times kept for take_block_to_submit() (oldest dropped first)
+const BLOCK_TO_SUBMIT_KEEP: usize = 64;
+
+/// Bundle outcomes kept for take_bundle_outcome() (oldest dropped first)
+const BUNDLE_OUTCOME_KEEP: usize = 64;
+
+/// One job for the tax writer thread
+enum TaxWrite {
+    /// Build (once) and write a landed trade's record
+    Record { pending: Box<PendingTaxRecord>, provisional: bool },
+    /// Flag a trade's records final at confirmation depth
+    Final { tx: String, year: i16 },
+    /// Reorged away: append the correcting entry, flag the original reversed
+    Supersede { tx: String, year: i16, reason: String },
+}
+
 /// Trade executor for DEX arbitrage
 pub struct TradeExecutor<M: Middleware> {
     provider: Arc<M>,
-    wallet: LocalWallet,
+    /// Signing wallets (PRIVATE_KEYS), each with its own nonce, balances and
+    /// in-flight txs; one is selected per submission (wallets.rs)
+    wallets: WalletPool<M>,
     config: BotConfig,
     /// Dry run mode - simulates trades without executing
     dry_run: bool,
-    /// Tax logger for IRS compliance
-    tax_logger: Option<TaxLogger>,
-    /// Price oracle for USD conversions
-    tax_record_builder: Option<TaxRecordBuilder>,
-    /// Optional HTTP provider for private mempool tx submission.
-    /// When set, atomic arb transactions are signed via WS (estimateGas, nonce,
-    /// gas price all use Alchemy), then ONLY the raw signed bytes are sent
-    /// through this provider. This avoids burning rate limits on reads.
-    tx_client: Option<Arc<Provider<Http>>>,
+    /// Tax records, finality flags and reorg corrections for IRS compliance,
+    /// written by a dedicated thread (io_writer.rs, lossless tax stream)
+    tax_writer: Option<IsolatedWriter<TaxWrite>>,
+    /// Signing connections, built once and reused across trades: the
+    /// wallet-wrapped WS signer, and the optional private-RPC HTTP provider.
+    /// With a private RPC, atomic arb transactions are signed via WS (estimateGas,
+    /// nonce, gas price all use Alchemy), then ONLY the raw signed bytes are sent
+    /// through the private provider. This avoids burning rate limits on reads.
+    connections: SigningConnections<M>,
     /// Cached base_fee_per_gas from latest block header (A1: eliminates get_gas_price RPC).
     /// Set by set_base_fee() from main.rs on each new block.
     cached_base_fee: Option<U256>,
-    /// Locally tracked nonce (A2: eliminates nonce lookup from fill_transaction).
-    /// Initialized on first use, incremented after each successful send.
-    cached_nonce: Arc<AtomicU64>,
-    nonce_initialized: bool,
+    /// Recent blocks' priority fee at GAS_ORACLE_PERCENTILE (gas_oracle.rs).
+    /// Set by set_priority_fee_estimate() from main.rs; None = static fallback.
+    priority_fee_estimate: Option<U256>,
+    /// Single-call preflight (quotes + balance + allowance + pool state at one block).
+    /// When None, the legacy per-call Quoter/allowance checks are used.
+    preflight: Option<PreflightBatcher<M>>,
+    /// Latest block number seen by main.rs — preflight batches are pinned to it.
+    current_block: Option<u64>,
+    /// Atomic/mempool submissions since the last take_submissions() (gas history join)
+    submissions: Vec<SubmissionRecord>,
+    /// Failure injection seams (chaos.rs). NoFaults unless CHAOS_MODE=true.
+    faults: Arc<dyn FaultInjector>,
+    /// Crash-safe record of atomic/mempool txs sent but not yet processed
+    /// (inflight.rs). None = no journaling.
+    journal: Option<InflightJournal>,
+    /// TAX_LOG_STRICT: a tax record that cannot be written halts live submissions
+    tax_strict: bool,
+    /// Tax records the writer has not accepted yet (its queue was full),
+    /// handed over oldest first
+    pending_tax: PendingTaxQueue,
+    /// Why live submissions are halted (strict tax logging). None = trading.
+    submission_halt: Option<String>,
+    /// Operator alerts for the strict-mode halt / resume
+    alerts: Option<AlertDispatcher>,
+    /// Token decimals for tax records (set before tax logging is enabled)
+    tokens: Option<Arc<TokenRegistry>>,
+    /// Per-route realized slippage vs pre-screen quotes → min_out allowances
+    slippage: SlippageTracker,
+    /// Landed trades booked provisionally until CONFIRMATION_DEPTH (finality.rs)
+    finality: FinalityTracker,
+    /// Native token USD price for gas accounting; starts at NATIVE_TOKEN_PRICE_USD,
+    /// kept live by a NativePriceUpdater holding native_price()
+    native_price: NativePrice,
+    /// Structured event journal (LOG_FORMAT=json): tx_submitted / receipt_parsed
+    events: Option<EventJournal>,
+    /// Per-route gas limits from receipts (gas_cache.rs); None = always estimateGas
+    gas_cache: Option<GasEstimateCache>,
+    /// Fill / sign / send and block → submit timings (latency.rs); None = not timed
+    latency: Option<Arc<LatencyRecorder>>,
+    /// Block arrival → submit (ms) of recent atomic submissions, by tx hash
+    block_to_submit: VecDeque<(TxHash, u64)>,
+    /// Searcher bundle relay for mempool backruns (BUNDLE_RPC_URL); None = raw send
+    bundle: Option<Arc<BundleSubmitter>>,
+    /// Outcomes of recent bundled backruns, by tx hash
+    bundle_outcomes: VecDeque<(TxHash, BundleOutcome)>,
+    /// Detector's pool state: our own receipts' Swap/Sync logs are applied
+    /// on settlement, ahead of the next block's event sync. None = not applied.
+    pool_state: Option<PoolStateManager>,
+    /// Permit2 funding (PERMIT2_ADDRESS); None = allowance path only
+    permit2: Option<Permit2State>,
 }
 
 impl<M: Middleware + 'static> TradeExecutor<M> {
     /// Create a new TradeExecutor
     pub fn new(provider: Arc<M>, wallet: LocalWallet, config: BotConfig) -> Self {
+        let connections = SigningConnections::new(provider.clone(), wallet, config.chain_id);
+        let wallets = WalletPool::new(WalletSlot::new(connections.signer(), &config));
+        let slippage = SlippageTracker::new(SlippageConfig::from_bot_config(&config));
+        let finality = FinalityTracker::new(config.confirmation_depth);
+        let native_price = NativePrice::new(config.native_token_price_usd);
+        let permit2 = config.permit2_address.map(Permit2State::new);
         Self {
             provider,
-            wallet,
+            wallets,
             config,
             dry_run: true, // Default to dry run for safety
-            tax_logger: None,
-            tax_record_builder: None,
-            tx_client: None,
+            tax_writer: None,
+            connections,
             cached_base_fee: None,
-            cached_nonce: Arc::new(AtomicU64::new(0)),
-            nonce_initialized: false,
+            priority_fee_estimate: None,
+            preflight: None,
+            current_block: None,
+            submissions: Vec::new(),
+            faults: Arc::new(NoFaults),
+            journal: None,
+            tax_strict: false,
+            pending_tax: PendingTaxQueue::default(),
+            submission_halt: None,
+            alerts: None,
+            tokens: None,
+            slippage,
+            finality,
+            native_price,
+            events: None,
+            gas_cache: None,
+            latency: None,
+            block_to_submit: VecDeque::new(),
+            bundle: None,
+            bundle_outcomes: VecDeque::new(),
+            pool_state: None,
+            permit2,
+        }
+    }
+
+    /// Shared handle to the native token price used for gas accounting and
+    /// tax records — hand it to a NativePriceUpdater to keep it live
+    pub fn native_price(&self) -> NativePrice {
+        self.native_price.clone()
+    }
+
+    /// Route the executor's seams through a failure injector (CHAOS_MODE only).
+    pub fn set_fault_injector(&mut self, faults: Arc<dyn FaultInjector>) {
+        self.faults = faults;
+    }
+
+    /// Journal every atomic/mempool submission before it is sent.
+    /// Call recover_inflight() before the first trade.
+    pub fn set_inflight_journal(&mut self, journal: InflightJournal) {
+        self.journal = Some(journal);
+    }
+
+    /// Journaled submissions still awaiting receipt processing
+    pub fn inflight_entries(&self) -> &[InflightEntry] {
+        self.journal.as_ref().map(|j| j.entries()).unwrap_or(&[])
+    }
+
+    /// Sent txs no wallet has settled yet (all wallets)
+    pub fn wallet_in_flight(&self) -> usize {
+        self.wallets.in_flight()
+    }
+
+    /// Sign with `wallet` too (PRIVATE_KEYS beyond the first). It gets its own
+    /// nonce and balance cache; track_balances() covers it when called after.
+    pub fn add_wallet(&mut self, wallet: LocalWallet) {
+        let signer = Arc::new(SignerMiddleware::new(self.provider.clone(), wallet.with_chain_id(self.config.chain_id)));
+        self.wallets.add(WalletSlot::new(signer, &self.config));
+    }
+
+    /// Signing wallet addresses, primary first
+    pub fn wallet_addresses(&self) -> Vec<Address> {
+        self.wallets.addresses()
+    }
+
+    /// The active wallet's locally tracked next nonce, None until initialized
+    /// (or after a resync reset)
+    pub fn tracked_nonce(&self) -> Option<u64> {
+        self.wallets.active().tracked_nonce()
+    }
+
+    /// Pick the wallet for the next submission (idle first, round-robin)
+    fn select_wallet(&mut self) {
+        let index = self.wallets.select();
+        if self.wallets.len() > 1 {
+            debug!("Wallet {} of {} selected: {:?}", index + 1, self.wallets.len(), self.wallets.active().address());
+        }
+    }
+
+    /// A2: Initialize the active wallet's nonce from the pending count on
+    /// first use, then track locally.
+    async fn ensure_nonce(&mut self) -> Result<U256> {
+        if self.wallets.active().tracked_nonce().is_none() {
+            let fault = self.faults.inject(FaultPoint::NonceFetch);
+            if let Some(ref f @ (Fault::RpcError { .. } | Fault::Timeout)) = fault {
+                return Err(anyhow!(f.error_message("eth_getTransactionCount")));
+            }
+            let mut nonce = rpc_timeout(
+                "eth_getTransactionCount",
+                self.provider.get_transaction_count(self.wallets.active().address(), Some(BlockNumber::Pending.into())),
+            ).await?;
+            if let Some(Fault::NonceGap { offset }) = fault {
+                nonce = U256::from(nonce.as_u64().saturating_add_signed(offset));
+            }
+            self.wallets.active_mut().set_nonce(nonce.as_u64());
+            info!("Nonce initialized: {} ({:?})", nonce, self.wallets.active().address());
+        }
+        Ok(U256::from(self.wallets.active().tracked_nonce().unwrap_or_default()))
+    }
+
+    /// A send failed. A nonce error means the tracked nonce has drifted from the
+    /// chain (external tx, dropped tx) — re-read it before the next submission.
+    fn on_send_error(&mut self, err_msg: &str) {
+        if err_msg.to_lowercase().contains("nonce") {
+            warn!("Nonce error on send — resyncing nonce from chain before next tx");
+            self.wallets.active_mut().reset_nonce();
+        }
+    }
+
+    /// Injected send failure for this submission, if any
+    fn injected_send_error(&self, label: &str) -> Option<String> {
+        self.faults
+            .inject(FaultPoint::BeforeSend)
+            .map(|f| f.error_message(label))
+    }
+
+    /// Sign `tx` and journal it (nonce, hash, raw bytes, opportunity) before it
+    /// is sent. A journal write failure aborts the submission.
+    async fn sign_and_journal(
+        &mut self,
+        signer: &SigningClient<M>,
+        tx: &TypedTransaction,
+        opportunity: &ArbitrageOpportunity,
+        nonce: U256,
+        path: &str,
+    ) -> Result<(Bytes, TxHash), String> {
+        let signature = self.timed(Stage::Sign, signer.signer().sign_transaction(tx)).await
+            .map_err(|e| format!("sign failed: {}", e))?;
+        let raw_tx = tx.rlp_signed(&signature);
+        let tx_hash = tx.hash(&signature);
+        if let Some(journal) = self.journal.as_mut() {
+            let entry = InflightEntry::new(opportunity, nonce.as_u64(), tx_hash, raw_tx.clone(), path)
+                .with_wallet(self.wallets.active().address());
+            journal.record(entry).map_err(|e| format!("in-flight journal write failed, not sending: {}", e))?;
+        }
+        Ok((raw_tx, tx_hash))
+    }
+
+    /// Drop the journal entry written for this submission, if any
+    /// (receipt processed or send failed)
+    fn clear_inflight(&mut self, journaled: Option<TxHash>) {
+        if let (Some(journal), Some(tx_hash)) = (self.journal.as_mut(), journaled) {
+            if let Err(e) = journal.clear(tx_hash) {
+                error!("In-flight journal: failed to clear {:?}: {}", tx_hash, e);
+            }
+        }
+    }
+
+    /// Resolve journal entries that survived a restart, then initialize each
+    /// wallet's tracked nonce from chain state plus its surviving entries.
+    ///
+    /// Mined entries get their receipt processed (tax record) and are cleared;
+    /// unmined ones are rebroadcast or discarded per `policy` (inflight::resolve).
+    /// Must run before the first submission. The report's nonce is the
+    /// primary wallet's.
+    pub async fn recover_inflight(&mut self, policy: RecoveryPolicy) -> Result<RecoveryReport> {
+        let mut report = RecoveryReport::default();
+        for index in (0..self.wallets.len()).rev() {
+            self.wallets.activate(index);
+            self.recover_wallet_inflight(policy, &mut report).await?;
+        }
+        Ok(report)
+    }
+
+    /// recover_inflight() for the active wallet's journal entries
+    async fn recover_wallet_inflight(&mut self, policy: RecoveryPolicy, report: &mut RecoveryReport) -> Result<()> {
+        let address = self.wallets.active().address();
+        let primary = self.wallets.primary_address();
+        let entries = self.wallet_inflight_entries(address, primary);
+
+        if !entries.is_empty() {
+            let latest_nonce = rpc_timeout(
+                "eth_getTransactionCount",
+                self.provider.get_transaction_count(address, Some(BlockNumber::Latest.into())),
+            ).await?.as_u64();
+
+            for entry in entries {
+                let status = match rpc_timeout(
+                    "eth_getTransactionReceipt", self.provider.get_transaction_receipt(entry.tx_hash),
+                ).await? {
+                    Some(receipt) => ChainStatus::Mined(Box::new(receipt)),
+                    None => match rpc_timeout("eth_getTransactionByHash", self.provider.get_transaction(entry.tx_hash)).await? {
+                        Some(_) => ChainStatus::Pending,
+                        None => ChainStatus::Dropped,
+                    },
+                };
+                let resolution = inflight::resolve(&status, entry.nonce, latest_nonce, policy);
+                warn!(
+                    "In-flight recovery: {} tx {:?} nonce {} is {} → {:?}",
+                    entry.fingerprint, entry.tx_hash, entry.nonce, status.as_str(), resolution
+                );
+                match (resolution, status) {
+                    (Resolution::ProcessReceipt, ChainStatus::Mined(receipt)) => {
+                        let result = self.process_recovered_receipt(&entry, &receipt);
+                        info!(
+                            "In-flight recovery: {:?} mined in block {:?} — success={} net ${:.4}",
+                            entry.tx_hash, result.block_number, result.success, result.net_profit_usd
+                        );
+                        self.clear_inflight(Some(entry.tx_hash));
+                        report.mined += 1;
+                    }
+                    (Resolution::Resubmit, _) => {
+                        match self.provider.send_raw_transaction(entry.raw_tx.clone()).await {
+                            Ok(_) => info!("In-flight recovery: rebroadcast {:?}", entry.tx_hash),
+                            Err(e) => warn!("In-flight recovery: rebroadcast of {:?} failed: {}", entry.tx_hash, e),
+                        }
+                        report.resubmitted += 1;
+                    }
+                    _ => {
+                        self.clear_inflight(Some(entry.tx_hash));
+                        report.discarded += 1;
+                    }
+                }
+            }
+        }
+
+        let chain_pending = rpc_timeout(
+            "eth_getTransactionCount",
+            self.provider.get_transaction_count(address, Some(BlockNumber::Pending.into())),
+        ).await?.as_u64();
+        report.nonce = inflight::initial_nonce(chain_pending, &self.wallet_inflight_entries(address, primary));
+        self.wallets.active_mut().set_nonce(report.nonce);
+        info!(
+            "In-flight recovery done{}: {} mined, {} resubmitted, {} discarded | nonce {} (chain pending {})",
+            if self.wallets.len() > 1 { format!(" ({:?})", address) } else { String::new() },
+            report.mined, report.resubmitted, report.discarded, report.nonce, chain_pending
+        );
+        Ok(())
+    }
+
+    /// Journal entries signed by `wallet` (entries without a wallet predate
+    /// PRIVATE_KEYS and belong to the primary)
+    fn wallet_inflight_entries(&self, wallet: Address, primary: Address) -> Vec<InflightEntry> {
+        self.inflight_entries()
+            .iter()
+            .filter(|entry| entry.wallet.unwrap_or(primary) == wallet)
+            .cloned()
+            .collect()
+    }
+
+    /// Receipt processing for a journaled tx mined while we were down —
+    /// same accounting as execute_atomic/execute_from_mempool after the receipt.
+    /// The journal doesn't record which ArbExecutor entry point was called, so
+    /// every execution event is tried (flash amountOut nets out the premium).
+    fn process_recovered_receipt(&mut self, entry: &InflightEntry, receipt: &TransactionReceipt) -> TradeResult {
+        let opportunity = &entry.opportunity;
+        let trade_size = opportunity.trade_size;
+        let block_number = receipt.block_number.map(|bn| bn.as_u64()).unwrap_or(0);
+        let gas_used = receipt.gas_used.unwrap_or(U256::from(400_000u64));
+        let gas_cost_wei = gas_used * receipt.effective_gas_price.unwrap_or_default();
+        let gas_used_native = gas_cost_wei.low_u128() as f64 / 1e18;
+        let gas_cost_usd = gas_used_native * self.native_price.get();
+        let mut result = TradeResult {
+            opportunity: opportunity.pair.symbol.clone(),
+            opportunity_id: opportunity.id,
+            tx_hash: Some(format!("{:?}", entry.tx_hash)),
+            block_number: Some(block_number),
+            success: false,
+            profit_usd: 0.0,
+            gas_cost_usd,
+            gas_used_native,
+            net_profit_usd: -gas_cost_usd,
+            execution_time_ms: 0,
+            error: Some("Recovered tx reverted on-chain".to_string()),
+            amount_in: Some(trade_size),
+            amount_out: None,
+        };
+        if receipt.status != Some(U64::from(1)) {
+            self.log_fee_record_if_enabled(
+                opportunity, &format!("{:?}", entry.tx_hash), block_number, gas_used_native, "Recovered tx reverted on-chain",
+            );
+            return result;
         }
+
+        let (amount_out, profit) = self
+            .config
+            .arb_executor_address
+            .and_then(|arb_address| parse_execution_event(&receipt.logs, arb_address))
+            .map(|(out, profit)| (opportunity.quote_amount(out), opportunity.quote_amount(profit)))
+            .unwrap_or((trade_size, trade_size.with_raw(U256::zero())));
+        result.profit_usd = opportunity.quote_to_usd(profit);
+        result.net_profit_usd = result.profit_usd - gas_cost_usd;
+        result.success = result.net_profit_usd > 0.0;
+        result.error = None;
+        result.amount_out = Some(amount_out.raw().to_string());
+
+        self.log_tax_record_if_enabled(
+            opportunity,
+            &format!("{:?}", entry.tx_hash),
+            block_number,
+            trade_size,
+            amount_out,
+            gas_used_native,
+        );
+        self.track_landed(opportunity, entry.tx_hash, block_number, receipt.block_hash, result.net_profit_usd);
+        result
+    }
+
+    /// Wait for a submitted tx's receipt (after_send + receipt_poll seams).
+    async fn await_receipt(&self, tx_hash: TxHash, site: &RetrySite) -> Result<Option<TransactionReceipt>> {
+        receipt_with_faults(&*self.provider, &*self.faults, tx_hash, site).await
+    }
+
+    /// Enable the single-call preflight batch before every live execution.
+    pub fn set_preflight(&mut self, batcher: PreflightBatcher<M>) {
+        self.preflight = Some(batcher);
+    }
+
+    /// Update the block that preflight batches are pinned to.
+    /// Called from main.rs on each new block alongside set_base_fee().
+    pub fn set_current_block(&mut self, block: u64) {
+        self.current_block = Some(block);
     }
 
     /// Enable private mempool for transaction submission.
-    /// Creates a bare HTTP provider pointed at the private RPC.
+    /// Builds the private-RPC HTTP provider once; it is reused for every trade.
     /// Only eth_sendRawTransaction goes through this — all reads (estimateGas,
     /// nonce, gas price) stay on the main WS provider to avoid rate limits.
     pub fn set_private_rpc(&mut self, url: &str) -> Result<()> {
-        let http_provider = Provider::<Http>::try_from(url)
-            .map_err(|e| anyhow!("Failed to create HTTP provider for {}: {}", url, e))?;
-        self.tx_client = Some(Arc::new(http_provider));
+        self.connections.set_private_rpc(url)?;
         info!("Private mempool enabled: {}", url);
         Ok(())
     }
 
+    /// Drain submissions recorded since the last call (for GasHistoryLogger)
+    pub fn take_submissions(&mut self) -> Vec<SubmissionRecord> {
+        std::mem::take(&mut self.submissions)
+    }
+
+    /// Record one bid for the gas history dataset
+    fn note_submission(
+        &mut self,
+        tx_hash: Option<TxHash>,
+        path: &'static str,
+        priority_fee: U256,
+        receipt: Option<&TransactionReceipt>,
+    ) {
+        let block = self.current_block.unwrap_or(0);
+        let wallet = self.wallets.active_mut();
+        wallet.exposure_mut().invalidate_balances();
+        wallet.balances_mut().mark_stale();
+        self.submissions.push(SubmissionRecord::new(block, tx_hash, path, priority_fee, receipt));
+    }
+
+    /// Keep the private-RPC connection warm (cheap ping when idle).
+    /// Called from main.rs once per block; a no-op unless the provider is idle.
+    pub async fn keep_connections_warm(&mut self) {
+        self.connections.keep_warm().await;
+    }
+
     /// Update cached base fee from latest block header (A1).
     /// Called from main.rs on each new block. Eliminates get_gas_price() RPC.
     pub fn set_base_fee(&mut self, base_fee: U256) {
         self.cached_base_fee = Some(base_fee);
     }
 
+    /// Update the gas oracle's priority fee estimate (None = no fee data)
+    pub fn set_priority_fee_estimate(&mut self, estimate: Option<U256>) {
+        self.priority_fee_estimate = estimate;
+    }
+
+    /// Atomic priority fee before the per-trade profit cap: oracle estimate
+    /// plus GAS_ORACLE_BUMP_PERCENT (or the static fee), clamped to
+    /// [MIN_PRIORITY_GWEI, MAX_PRIORITY_GWEI]
+    pub fn atomic_priority_fee(&self) -> U256 {
+        let estimate = self.priority_fee_estimate.filter(|_| self.config.gas_oracle_enabled);
+        gas_oracle::clamp_bid(
+            estimate,
+            ATOMIC_PRIORITY_FEE_GWEI,
+            self.config.gas_oracle_bump_percent,
+            self.config.min_priority_gwei,
+            self.config.max_priority_gwei,
+        )
+    }
+
+    /// atomic_priority_fee() in gwei (competition model, gas history)
+    pub fn atomic_priority_gwei(&self) -> u64 {
+        (self.atomic_priority_fee() / U256::exp10(9)).as_u64()
+    }
+
     /// Enable or disable dry run mode
     pub fn set_dry_run(&mut self, dry_run: bool) {
         self.dry_run = dry_run;
@@ -182,18 +798,500 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
     /// Enable tax logging for IRS compliance
     ///
     /// This should be called before executing real trades.
-    /// Tax records are written to `data/tax/trades_YYYY.csv` and `.jsonl`.
+    /// Tax records are written to `data/tax/trades_YYYY.csv` and `.jsonl` by
+    /// the tax writer thread; building (price oracle reads) happens there too.
+    /// The writer's lot ledger (TAX_LOT_METHOD) is rebuilt from the JSON logs.
     pub fn enable_tax_logging(&mut self, tax_dir: &str) -> Result<()> {
         let tax_path = PathBuf::from(tax_dir);
-        self.tax_logger = Some(TaxLogger::new(&tax_path)?);
-        self.tax_record_builder = Some(TaxRecordBuilder::default());
-        info!("Tax logging enabled: {}", tax_dir);
+        let mut logger = TaxLogger::new(&tax_path)?;
+        let builder = self.tax_record_builder(TaxRecordBuilder::default());
+        let method: LotMethod = self.config.tax_lot_method.parse()?;
+        let mut ledger = LotLedger::new(method);
+        let logged = logger.read_through(chrono::Utc::now().year() as i16)?;
+        ledger.replay(&logged);
+        self.tax_writer = Some(IsolatedWriter::spawn(
+            StreamContract::tax(&self.config),
+            move |job: &mut TaxWrite| Self::run_tax_write(&mut logger, &builder, &mut ledger, job),
+        ));
+        info!("Tax logging enabled: {} ({} lots, {} records replayed)", tax_dir, method, logged.len());
         Ok(())
     }
 
+    /// Tax records on the executor's gas price, chain and token registry. A
+    /// non-stable quote token (WETH silo, QUOTE_TOKEN_PRICE_USD ≠ 1) is
+    /// recorded at the price its trades were sized and measured at, not the
+    /// oracle's.
+    fn tax_record_builder(&self, builder: TaxRecordBuilder) -> TaxRecordBuilder {
+        let mut builder = builder
+            .with_native_price(self.native_price.clone())
+            .with_chain(&self.config.chain_name, self.config.chain_id);
+        if let Some(ref tokens) = self.tokens {
+            builder = builder.with_token_registry(Arc::clone(tokens));
+        }
+        let price = self.config.quote_token_price_usd;
+        match (self.config.quote_token_symbol(), Decimal::try_from(price)) {
+            (Some(symbol), Ok(price_usd)) if price != 1.0 => builder.with_pinned_price(symbol, price_usd),
+            _ => builder,
+        }
+    }
+
+    /// Enable tax logging, honouring TAX_LOG_STRICT.
+    ///
+    /// Strict: an init failure is returned (the bot must not start). Otherwise
+    /// it is only warned about and trades go unrecorded.
+    pub fn init_tax_logging(&mut self, tax_dir: &str, strict: bool) -> Result<()> {
+        self.tax_strict = strict;
+        match self.enable_tax_logging(tax_dir) {
+            Ok(()) => Ok(()),
+            Err(e) if strict => Err(anyhow!(
+                "TAX_LOG_STRICT: cannot initialize tax logging in {}: {}", tax_dir, e
+            )),
+            Err(e) => {
+                warn!("Failed to enable tax logging: {} - trades will NOT be logged for taxes!", e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Token metadata for tax records; call before init_tax_logging().
+    pub fn set_token_registry(&mut self, tokens: Arc<TokenRegistry>) {
+        self.tokens = Some(tokens);
+    }
+
+    /// Route strict-mode halt/resume notices through the alert dispatcher.
+    pub fn set_alert_dispatcher(&mut self, alerts: AlertDispatcher) {
+        self.alerts = Some(alerts);
+    }
+
+    /// Why live submissions are halted, if they are.
+    pub fn submission_halt(&self) -> Option<&str> {
+        self.submission_halt.as_deref()
+    }
+
+    /// Tax writes not on disk yet: refused by the writer + queued in it.
+    pub fn pending_tax_records(&self) -> usize {
+        let writer = self.tax_writer.as_ref().map_or(0, |w| w.stats().pending());
+        self.pending_tax.len() + writer
+    }
+
+    /// Tax writer backpressure for the status line (None while healthy and idle).
+    pub fn tax_writer_status(&self) -> Option<String> {
+        self.tax_writer.as_ref().and_then(|w| w.status_line())
+    }
+
+    /// Wait until every accepted tax write is on disk (true) or `timeout` passes.
+    pub fn flush_tax_writes(&self, timeout: Duration) -> bool {
+        self.tax_writer.as_ref().is_none_or(|w| w.flush(timeout))
+    }
+
+    /// Replace the slippage tracker (main.rs: the persisted one).
+    pub fn set_slippage_tracker(&mut self, tracker: SlippageTracker) {
+        self.slippage = tracker;
+    }
+
+    /// Per-route realized slippage lines for the periodic status.
+    pub fn slippage_summary(&self) -> Vec<String> {
+        self.slippage.summary_lines()
+    }
+
+    /// Write the trade ledger (finality booking events) under `data_dir`.
+    pub fn set_trade_ledger(&mut self, data_dir: &str) {
+        self.finality = FinalityTracker::new(self.config.confirmation_depth).with_ledger(data_dir);
+    }
+
+    /// Journal atomic submissions and their receipts (LOG_FORMAT=json).
+    pub fn set_event_journal(&mut self, journal: EventJournal) {
+        self.events = Some(journal);
+    }
+
+    /// Reuse receipts' gas_used per route instead of estimateGas (GAS_CACHE_ENABLED).
+    pub fn set_gas_cache(&mut self, cache: GasEstimateCache) {
+        self.gas_cache = Some(cache);
+    }
+
+    /// Apply our own receipts' pool Swap/Sync logs to `state_manager` as
+    /// soon as they settle, so a spread we just closed isn't re-detected.
+    pub fn set_pool_state_manager(&mut self, state_manager: PoolStateManager) {
+        self.pool_state = Some(state_manager);
+    }
+
+    /// Our trade's effect on the pools it touched, from its receipt's logs
+    fn apply_own_receipt(&self, receipt: &TransactionReceipt) {
+        if let Some(state_manager) = self.pool_state.as_ref() {
+            let updated = pool_events::apply_receipt_logs(state_manager, receipt);
+            if !updated.is_empty() {
+                debug!("Receipt {:?}: {} pools updated from own Swap/Sync logs", receipt.transaction_hash, updated.len());
+            }
+        }
+    }
+
+    /// Time fill / sign / send and block → submit into `recorder`.
+    pub fn set_latency_recorder(&mut self, recorder: Arc<LatencyRecorder>) {
+        self.latency = Some(recorder);
+    }
+
+    /// Submit mempool backruns as bundles behind their trigger tx.
+    pub fn set_bundle_submitter(&mut self, submitter: BundleSubmitter) {
+        info!("📦 Bundle submission enabled ({})", submitter.method());
+        self.bundle = Some(Arc::new(submitter));
+    }
+
+    /// Bundle outcome of a recent mempool backrun (taken once).
+    pub fn take_bundle_outcome(&mut self, tx_hash: &str) -> Option<BundleOutcome> {
+        let hash: TxHash = tx_hash.parse().ok()?;
+        let idx = self.bundle_outcomes.iter().position(|(h, _)| *h == hash)?;
+        self.bundle_outcomes.remove(idx).map(|(_, outcome)| outcome)
+    }
+
+    fn note_bundle(&mut self, tx_hash: TxHash, outcome: BundleOutcome) {
+        info!("📦 Bundle {:?}: {:?}", tx_hash, outcome);
+        if self.bundle_outcomes.len() == BUNDLE_OUTCOME_KEEP {
+            self.bundle_outcomes.pop_front();
+        }
+        self.bundle_outcomes.push_back((tx_hash, outcome));
+    }
+
+    /// Included / landed elsewhere: our receipt's position against the trigger's
+    async fn bundle_inclusion(&self, receipt: &TransactionReceipt, trigger: TxHash) -> BundleOutcome {
+        let position = |r: &TransactionReceipt| Some((r.block_number?.as_u64(), r.transaction_index.as_u64()));
+        let trigger = match rpc_timeout("eth_getTransactionReceipt", self.provider.get_transaction_receipt(trigger)).await {
+            Ok(r) => r.as_ref().and_then(position),
+            Err(e) => {
+                debug!("Trigger receipt unavailable ({}) — bundle position unknown", e);
+                None
+            }
+        };
+        BundleOutcome::from_receipts(position(receipt), trigger)
+    }
+
+    /// Block arrival → submit (ms) for a recent atomic submission (taken once).
+    pub fn take_block_to_submit(&mut self, tx_hash: &str) -> Option<u64> {
+        let hash: TxHash = tx_hash.parse().ok()?;
+        let idx = self.block_to_submit.iter().position(|(h, _)| *h == hash)?;
+        self.block_to_submit.remove(idx).map(|(_, ms)| ms)
+    }
+
+    /// Await `fut`, timing it under `stage` when a latency recorder is set
+    async fn timed<F: Future>(&self, stage: Stage, fut: F) -> F::Output {
+        let started = Instant::now();
+        let output = fut.await;
+        if let Some(ref recorder) = self.latency {
+            recorder.record_since(stage, started);
+        }
+        output
+    }
+
+    /// Landed trades still awaiting confirmation depth.
+    pub fn provisional_trades(&self) -> usize {
+        self.finality.pending().len()
+    }
+
+    /// Book a landed trade provisionally (no-op when CONFIRMATION_DEPTH = 0).
+    fn track_landed(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        tx_hash: TxHash,
+        block_number: u64,
+        block_hash: Option<H256>,
+        net_profit_usd: f64,
+    ) {
+        if !self.finality.is_enabled() {
+            return;
+        }
+        self.finality.track(ProvisionalTrade {
+            tx_hash,
+            block_number,
+            block_hash,
+            tax_year: chrono::Utc::now().year() as i16,
+            pair: opportunity.pair.symbol.clone(),
+            buy_dex: opportunity.buy_dex,
+            sell_dex: opportunity.sell_dex,
+            trade_size_usd: opportunity.trade_size_usd,
+            net_profit_usd,
+        });
+    }
+
+    /// Re-check provisional trades that are CONFIRMATION_DEPTH deep. Still in
+    /// their block → tax records flagged final. Vanished or moved → tax record
+    /// superseded by a correcting entry, ledger reversed, critical alert; the
+    /// returned reversals let main.rs revoke the routes' cooldown success.
+    /// A failed receipt lookup is retried next block.
+    pub async fn confirm_provisional_trades(&mut self, current_block: u64) -> Vec<Reversal> {
+        let depth = self.finality.depth();
+        let mut reversals = Vec::new();
+        for trade in self.finality.take_due(current_block) {
+            let receipt = match rpc_timeout(
+                "eth_getTransactionReceipt",
+                self.provider.get_transaction_receipt(trade.tx_hash),
+            ).await {
+                Ok(r) => r,
+                Err(e) => {
+                    debug!("Finality re-check of {:?} failed: {} — retrying next block", trade.tx_hash, e);
+                    self.finality.requeue(trade);
+                    continue;
+                }
+            };
+            let tx = format!("{:?}", trade.tx_hash);
+            let check = FinalityCheck::from_receipt(&trade, receipt.as_ref());
+            if check == FinalityCheck::Final {
+                let job = TaxWrite::Final { tx: tx.clone(), year: trade.tax_year };
+                if let Err(refused) = self.send_tax_write(job) {
+                    debug!("Finality: tax writer busy ({}) — re-checking {} next block", refused, tx);
+                    self.finality.requeue(trade);
+                    continue;
+                }
+                self.finality.finalize(&trade);
+                info!("🔒 Trade final at depth {}: {} {}", depth, trade.pair, tx);
+                continue;
+            }
+
+            let reason = check.describe(&trade, depth);
+            let job = TaxWrite::Supersede { tx: tx.clone(), year: trade.tax_year, reason: reason.clone() };
+            if let Err(refused) = self.send_tax_write(job) {
+                debug!("Finality: tax writer busy ({}) — re-checking {} next block", refused, tx);
+                self.finality.requeue(trade);
+                continue;
+            }
+            let msg = format!(
+                "Trade REVERSED: {} {} — {} | {} unbooked, route cooldown success revoked",
+                trade.pair, tx, reason, format_usd(trade.net_profit_usd)
+            );
+            match self.alerts {
+                Some(ref alerts) => alerts.dispatch(AlertSeverity::Critical, &msg),
+                None => error!("{}", msg),
+            }
+            reversals.push(self.finality.reverse(&trade, &reason));
+        }
+        reversals
+    }
+
+    /// Record one leg's fill against the pre-screen quote (no quote = nothing to compare).
+    fn record_slippage(&mut self, opportunity: &ArbitrageOpportunity, leg: SlippageLeg, expected: U256, realized: U256) {
+        if let Some(pct) = self.slippage.record(opportunity, leg, expected, realized) {
+            info!("📏 Realized slippage {} {} leg: {:.3}%", opportunity.pair.symbol, leg, pct);
+        }
+    }
+
+    /// Sweep the ArbExecutor's whole balance of a stranded token back to the
+    /// wallet (owner-only `rescueTokens`). Shares the locally tracked nonce
+    /// with trade txs so the two can never collide. With tax logging on, the
+    /// sweep is recorded as a Transfer of the balance the monitor saw.
+    ///
+    /// Returns (tx hash, block number) once the rescue tx has confirmed.
+    pub async fn rescue_tokens(&mut self, balance: &StrandedBalance) -> Result<(TxHash, u64)> {
+        let token = balance.token;
+        if self.dry_run {
+            return Err(anyhow!("rescue skipped: executor in DRY RUN mode"));
+        }
+        let arb_address = self.config.arb_executor_address
+            .ok_or_else(|| anyhow!("rescue requires ARB_EXECUTOR_ADDRESS"))?;
+
+        // Owner-only: the primary wallet deployed the contract
+        self.wallets.activate(0);
+        let signer = self.wallets.active().signer();
+        let nonce = self.ensure_nonce().await?;
+
+        if let Some(err_msg) = self.injected_send_error("eth_sendRawTransaction") {
+            return Err(anyhow!("Rescue tx send failed: {}", err_msg));
+        }
+        let call = rescue_tokens_call(arb_address, token, signer).nonce(nonce);
+        let sent = call.send().await.map(|pending| pending.tx_hash());
+        let tx_hash = match sent {
+            Ok(hash) => {
+                self.wallets.active_mut().advance_nonce();
+                hash
+            }
+            Err(e) => {
+                let err_msg = format!("Rescue tx send failed: {}", e);
+                self.on_send_error(&err_msg);
+                return Err(anyhow!(err_msg));
+            }
+        };
+        warn!("🛟 Rescue tx submitted: rescueTokens({:?}) | TX: {:?}", token, tx_hash);
+
+        let receipt = match self.await_receipt(tx_hash, &RetrySite::named("receipt.rescue")).await? {
+            Some(r) => r,
+            None => return Err(anyhow!("Receipt timeout (30s) for rescue tx {:?}", tx_hash)),
+        };
+        if receipt.status != Some(U64::from(1)) {
+            return Err(anyhow!("Rescue tx {:?} reverted on-chain", tx_hash));
+        }
+        let block_number = receipt.block_number.map(|bn| bn.as_u64()).unwrap_or(0);
+        if self.tax_writer.is_some() {
+            let price_usd = balance.usd_value.filter(|_| balance.amount > 0.0).map(|usd| usd / balance.amount);
+            let pending = PendingTaxRecord::rescue(
+                &balance.symbol, balance.decimals, price_usd, &format!("{:?}", tx_hash), block_number,
+                balance.raw, receipt_gas_native(&receipt, U256::zero()),
+            )
+            .with_wallet(self.wallet_address_string());
+            if let Some(evicted) = self.pending_tax.push(pending) {
+                error!("🧾 Tax retry queue full — record DROPPED, reconstruct by hand: {}", evicted.describe());
+            }
+            self.retry_pending_tax_records();
+        }
+        Ok((tx_hash, block_number))
+    }
+
+    /// Inventory rebalance: sell `plan.amount_in` of an idle token into the
+    /// primary quote token through the plan's pool (approving the router if
+    /// needed), never below `plan.min_out`. Refused without tax logging, so
+    /// every rebalance swap gets its tax record. The approve and swap go out
+    /// through the signer's own nonce, so the tracked nonce is re-read after.
+    ///
+    /// Returns (tx hash, amount out, block number) once the swap has confirmed.
+    pub async fn rebalance(&mut self, plan: &RebalancePlan) -> Result<(TxHash, U256, u64)> {
+        if self.dry_run {
+            return Err(anyhow!("rebalance skipped: executor in DRY RUN mode"));
+        }
+        if self.tax_writer.is_none() {
+            return Err(anyhow!("rebalance refused: tax logging is off (TAX_LOG_ENABLED)"));
+        }
+        if plan.dex.is_detection_only() {
+            return Err(anyhow!("rebalance refused: {:?} is detection-only", plan.dex));
+        }
+
+        // Inventory belongs to the primary wallet
+        self.wallets.activate(0);
+        self.wallets.active_mut().reset_nonce();
+        self.ensure_approval(plan.token_in, plan.dex, plan.amount_in).await?;
+        let (tx_hash, amount_out, block_number) = self
+            .swap(plan.dex, Some(plan.pool), plan.token_in, plan.token_out, plan.amount_in, plan.min_out)
+            .await?;
+
+        // Gas estimate as for legacy swaps: ~200k gas at 50 gwei = 0.01 native
+        let gas_used_native = 0.01;
+        let pending = PendingTaxRecord::rebalance(
+            plan.tax_swap(), &format!("{:?}", tx_hash), block_number, plan.amount_in, amount_out, gas_used_native,
+        )
+        .with_wallet(self.wallet_address_string());
+        if let Some(evicted) = self.pending_tax.push(pending) {
+            error!("🧾 Tax retry queue full — record DROPPED, reconstruct by hand: {}", evicted.describe());
+        }
+        self.retry_pending_tax_records();
+        Ok((tx_hash, amount_out, block_number))
+    }
+
+    /// Bring every (token, spender) allowance in `targets` where `action` wants
+    /// it (approvals.rs), for each signing wallet in turn. All approves of a
+    /// wallet go out back to back on consecutive tracked nonces, then their
+    /// receipts are awaited. Status reads only and also works in dry run.
+    pub async fn manage_approvals(
+        &mut self,
+        targets: &[ApprovalTarget],
+        action: ApprovalAction,
+    ) -> Result<ApprovalSummary> {
+        if action != ApprovalAction::Status && self.dry_run {
+            return Err(anyhow!("approvals skipped: executor in DRY RUN mode"));
+        }
+        let mut summary = ApprovalSummary::default();
+        for index in 0..self.wallets.len() {
+            self.wallets.activate(index);
+            let wallet = self.manage_wallet_approvals(targets, action).await?;
+            if self.wallets.len() > 1 {
+                info!("Approvals for wallet {:?}: {}", self.wallets.active().address(), wallet);
+            }
+            summary.merge(&wallet);
+        }
+        Ok(summary)
+    }
+
+    /// manage_approvals() for the active wallet
+    async fn manage_wallet_approvals(
+        &mut self,
+        targets: &[ApprovalTarget],
+        action: ApprovalAction,
+    ) -> Result<ApprovalSummary> {
+        let signer = self.wallets.active().signer();
+        let wallet_address = self.wallets.active().address();
+        let mut summary = ApprovalSummary { checked: targets.len(), ..Default::default() };
+
+        // Read all allowances first; a failed read leaves that target alone
+        let mut plans = Vec::new();
+        for target in targets {
+            let token = IERC20::new(target.token, signer.clone());
+            match rpc_timeout("allowance", token.allowance(wallet_address, target.spender).call()).await {
+                Ok(allowance) => {
+                    if !approvals::is_max_approval(allowance) {
+                        info!("Allowance {} = {}", target, allowance);
+                    }
+                    let plan = approvals::approval_plan(action, allowance);
+                    if plan.is_empty() {
+                        if action == ApprovalAction::Status && !approvals::is_max_approval(allowance) {
+                            summary.missing += 1;
+                        } else {
+                            summary.unchanged += 1;
+                        }
+                    } else {
+                        plans.push((target, plan));
+                    }
+                }
+                Err(e) => {
+                    warn!("Allowance read failed for {}: {}", target, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+        if plans.is_empty() {
+            return Ok(summary);
+        }
+
+        // Batch submit: approve(0) resets precede their approve(max) by nonce
+        let mut sent: Vec<(&ApprovalTarget, Vec<TxHash>)> = Vec::new();
+        'targets: for (target, plan) in plans {
+            let token = IERC20::new(target.token, signer.clone());
+            let mut hashes = Vec::new();
+            for amount in plan {
+                let nonce = self.ensure_nonce().await?;
+                let call = token.approve(target.spender, amount).nonce(nonce);
+                match call.send().await.map(|pending| pending.tx_hash()) {
+                    Ok(hash) => {
+                        self.wallets.active_mut().advance_nonce();
+                        info!("Approval tx submitted: {} amount={} | TX: {:?}", target, amount, hash);
+                        hashes.push(hash);
+                    }
+                    Err(e) => {
+                        let err_msg = format!("Approval tx send failed for {}: {}", target, e);
+                        warn!("{}", err_msg);
+                        self.on_send_error(&err_msg);
+                        summary.failed += 1;
+                        continue 'targets;
+                    }
+                }
+            }
+            sent.push((target, hashes));
+        }
+
+        for (target, hashes) in sent {
+            let mut confirmed = true;
+            for hash in hashes {
+                match self.await_receipt(hash, &RetrySite::named("receipt.approval")).await {
+                    Ok(Some(receipt)) if receipt.status == Some(U64::from(1)) => {}
+                    Ok(Some(_)) => {
+                        warn!("Approval tx {:?} reverted for {}", hash, target);
+                        confirmed = false;
+                    }
+                    Ok(None) => {
+                        warn!("Approval tx {:?} receipt timeout for {}", hash, target);
+                        confirmed = false;
+                    }
+                    Err(e) => {
+                        warn!("Approval tx {:?} receipt failed for {}: {}", hash, target, e);
+                        confirmed = false;
+                    }
+                }
+            }
+            match (confirmed, action) {
+                (true, ApprovalAction::RevokeAll) => summary.revoked += 1,
+                (true, _) => summary.granted += 1,
+                (false, _) => summary.failed += 1,
+            }
+        }
+        Ok(summary)
+    }
+
     /// Get wallet address as string (for tax records)
     fn wallet_address_string(&self) -> String {
-        format!("{:?}", self.wallet.address())
+        format!("{:?}", self.wallets.active().address())
     }
 
     /// Execute an arbitrage opportunity
@@ -216,16 +1314,54 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         } else {
             (opportunity.pair.token1, opportunity.pair.token0)
         };
-        let trade_size = opportunity.trade_size;
 
         if self.dry_run {
             return self.simulate_execution(opportunity, start_time).await;
         }
+        if opportunity.split_sell.is_some() {
+            return self.execute_atomic_split(opportunity).await;
+        }
+        // DODO legs are sent to DODO_V2_PROXY and pull through DODO_APPROVE:
+        // without both, refuse before the first leg rather than strand it
+        if opportunity.is_legacy_only() && !self.config.dodo_configured() {
+            return Ok(TradeResult {
+                opportunity: pair_symbol.clone(),
+                opportunity_id: opportunity.id,
+                tx_hash: None,
+                block_number: None,
+                success: false,
+                profit_usd: 0.0,
+                gas_cost_usd: 0.0,
+                gas_used_native: 0.0,
+                net_profit_usd: 0.0,
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+                error: Some(format!(
+                    "{:?} → {:?}: DODO legs require DODO_V2_PROXY and DODO_APPROVE",
+                    opportunity.buy_dex, opportunity.sell_dex
+                )),
+                amount_in: Some(opportunity.trade_size),
+                amount_out: None,
+            });
+        }
+        let (ready, preflight) = match self.gate_live(opportunity, start_time).await {
+            Ok(LiveGate::Ready(ready, preflight)) => (ready, preflight),
+            Ok(LiveGate::Flash(flash)) => return self.execute_atomic(opportunity, Some(flash), start_time).await,
+            Err(rejected) => return Ok(rejected),
+        };
+        let opportunity = &ready;
+        let trade_size = opportunity.trade_size;
 
         // Route to atomic execution if ArbExecutor contract is configured.
         // Supports V3↔V3, V2↔V3, and V2↔V2 — all via fee sentinel routing in the contract.
+        // DODO legs have no ArbExecutor leg yet: those routes stay two-tx.
         if self.config.arb_executor_address.is_some() {
-            return self.execute_atomic(opportunity, start_time).await;
+            if !opportunity.is_legacy_only() {
+                return self.execute_atomic(opportunity, None, start_time).await;
+            }
+            warn!(
+                "{}: {:?} → {:?} has no ArbExecutor leg (atomic support deferred) — legacy two-tx (leg risk)",
+                pair_symbol, opportunity.buy_dex, opportunity.sell_dex
+            );
         }
 
         // Legacy two-tx execution (fallback — has leg risk)
@@ -235,20 +1371,34 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         // high gwei values. Profitability is already gated by the detector
         // (ESTIMATED_GAS_COST_USD) and the post-trade net profit check.
 
-        // Token decimals for correct slippage calculation
-        let t0_dec = opportunity.token0_decimals;
-        let t1_dec = opportunity.token1_decimals;
+        // Quote/base decimals and per-leg rates for correct slippage calculation.
+        // token0/token1 here are oriented quote/base, which may be the reverse of
+        // the pool's ordering (e.g. WBTC/USDC: pool token0 = WBTC, 8 decimals).
+        let quote_dec = opportunity.quote_decimals();
+        let base_dec = opportunity.base_decimals();
 
         // Pre-trade safety: V3 Quoter simulation (buy leg)
         // Verifies buy pool can fill order before committing capital
         // Sell leg is Quoter-checked separately after buy succeeds
+        // With a preflight bundle the buy quote is already in hand (same block).
         if opportunity.buy_dex.is_v3() {
-            if let Err(e) = self.v3_quoter_check(
-                token0, token1, opportunity.buy_dex, trade_size,
-                self.calculate_min_out(trade_size, opportunity.buy_price, t0_dec, t1_dec),
-            ).await {
+            let buy_quote_min = self.calculate_min_out(
+                opportunity, SlippageLeg::Buy, trade_size.raw(), opportunity.buy_leg_price(), quote_dec, base_dec,
+            );
+            let check = match preflight {
+                Some(ref bundle) if bundle.buy_quoted_out < buy_quote_min => Err(anyhow!(
+                    "V3 Quoter (preflight block {}): output {} < min_out {}",
+                    bundle.block_number, bundle.buy_quoted_out, buy_quote_min
+                )),
+                Some(ref bundle) => Ok(bundle.buy_quoted_out),
+                None => rpc_timeout("V3 Quoter", self.v3_quoter_check(
+                    token0, token1, opportunity.buy_dex, trade_size.raw(), buy_quote_min,
+                )).await,
+            };
+            if let Err(e) = check {
                 return Ok(TradeResult {
                     opportunity: pair_symbol.clone(),
+                    opportunity_id: opportunity.id,
                     tx_hash: None,
                     block_number: None,
                     success: false,
@@ -258,31 +1408,36 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                     net_profit_usd: 0.0,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     error: Some(format!("V3 Quoter pre-check failed: {}", e)),
-                    amount_in: Some(trade_size.to_string()),
+                    amount_in: Some(trade_size),
                     amount_out: None,
                 });
             }
         }
 
         // Step 1: Approve tokens for routers (if needed)
-        self.ensure_approval(token0, opportunity.buy_dex, trade_size)
-            .await?;
+        // Preflight already read the router allowance at the snapshot block.
+        let approved = preflight.as_ref().is_some_and(|b| b.allowance >= trade_size);
+        if !approved {
+            self.ensure_approval(token0, opportunity.buy_dex, trade_size.raw())
+                .await?;
+        }
 
         // Step 2: Execute buy swap (token0 -> token1 on buy DEX)
         // buy_dex has the HIGHER V3 price (more token1 per token0 = better entry)
         let buy_min_out = self.calculate_min_out(
-            trade_size, opportunity.buy_price, t0_dec, t1_dec,
+            opportunity, SlippageLeg::Buy, trade_size.raw(), opportunity.buy_leg_price(), quote_dec, base_dec,
         );
         info!(
-            "📈 Buy: {} token0 on {:?} | min_out: {} token1",
-            trade_size, opportunity.buy_dex, buy_min_out
+            "📈 Buy: {} on {:?} | min_out: {}",
+            opportunity.size_display(), opportunity.buy_dex, opportunity.base_amount_display(buy_min_out)
         );
         let buy_result = self
             .swap(
                 opportunity.buy_dex,
+                opportunity.buy_pool_address,
                 token0,
                 token1,
-                trade_size,
+                trade_size.raw(),
                 buy_min_out,
             )
             .await;
@@ -293,6 +1448,7 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                 error!("Buy swap failed: {}", e);
                 return Ok(TradeResult {
                     opportunity: pair_symbol.clone(),
+                    opportunity_id: opportunity.id,
                     tx_hash: None,
                     block_number: None,
                     success: false,
@@ -302,28 +1458,32 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                     net_profit_usd: 0.0,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     error: Some(format!("Buy swap failed: {}", e)),
-                    amount_in: Some(trade_size.to_string()),
+                    amount_in: Some(trade_size),
                     amount_out: None,
                 });
             }
         };
 
-        info!("✅ Buy complete: {} | Received: {}", buy_tx_hash, amount_received);
+        info!("✅ Buy complete: {} | Received: {}", buy_tx_hash, opportunity.base_amount_display(amount_received));
+        if let Some(quoted) = opportunity.quoted_legs {
+            self.record_slippage(opportunity, SlippageLeg::Buy, quoted.buy_out, amount_received);
+        }
 
         // Pre-sell safety: V3 Quoter simulation (sell leg)
         // Buy has executed — we're holding token1. Verify sell pool can return expected token0
         // before sending the sell tx. If rejected, bot stops (capital committed, manual exit needed).
         if opportunity.sell_dex.is_v3() {
             let sell_quote_min = self.calculate_min_out(
-                amount_received, 1.0 / opportunity.sell_price, t1_dec, t0_dec,
+                opportunity, SlippageLeg::Sell, amount_received, opportunity.sell_leg_price(), base_dec, quote_dec,
             );
-            if let Err(e) = self.v3_quoter_check(
+            if let Err(e) = rpc_timeout("V3 Quoter", self.v3_quoter_check(
                 token1, token0, opportunity.sell_dex, amount_received,
                 sell_quote_min,
-            ).await {
+            )).await {
                 error!("Sell swap failed: V3 Quoter rejected sell leg: {}", e);
                 return Ok(TradeResult {
                     opportunity: pair_symbol.clone(),
+                    opportunity_id: opportunity.id,
                     tx_hash: Some(format!("{:?}", buy_tx_hash)),
                     block_number: Some(buy_block),
                     success: false,
@@ -336,7 +1496,7 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                         "Sell Quoter rejected after buy executed (holding token1, manual sell needed): {}",
                         e
                     )),
-                    amount_in: Some(trade_size.to_string()),
+                    amount_in: Some(trade_size),
                     amount_out: Some(amount_received.to_string()),
                 });
             }
@@ -349,15 +1509,17 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         // Step 4: Execute sell swap (token1 -> token0 on sell DEX)
         // sell_dex has the LOWER V3 price (1/price is higher = more token0 per token1 = better exit)
         let sell_min_out = self.calculate_min_out(
-            amount_received, 1.0 / opportunity.sell_price, t1_dec, t0_dec,
+            opportunity, SlippageLeg::Sell, amount_received, opportunity.sell_leg_price(), base_dec, quote_dec,
         );
         info!(
-            "📉 Sell: {} token1 on {:?} | min_out: {} token0",
-            amount_received, opportunity.sell_dex, sell_min_out
+            "📉 Sell: {} on {:?} | min_out: {}",
+            opportunity.base_amount_display(amount_received), opportunity.sell_dex,
+            opportunity.quote_amount(sell_min_out)
         );
         let sell_result = self
             .swap(
                 opportunity.sell_dex,
+                opportunity.sell_pool_address,
                 token1,
                 token0,
                 amount_received,
@@ -371,6 +1533,7 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                 error!("Sell swap failed: {}", e);
                 return Ok(TradeResult {
                     opportunity: pair_symbol.clone(),
+                    opportunity_id: opportunity.id,
                     tx_hash: Some(buy_tx_hash.to_string()),
                     block_number: Some(buy_block),
                     success: false,
@@ -380,26 +1543,29 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                     net_profit_usd: 0.0,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     error: Some(format!("Sell swap failed (buy succeeded): {}", e)),
-                    amount_in: Some(trade_size.to_string()),
+                    amount_in: Some(trade_size),
                     amount_out: Some(amount_received.to_string()),
                 });
             }
         };
 
+        let final_amount = opportunity.quote_amount(final_amount);
         info!("✅ Sell complete: {} | Final: {}", sell_tx_hash, final_amount);
+        // Sell quote was for quoted buy_out; scale it to the base actually sold
+        if let Some(quoted) = opportunity.quoted_legs.filter(|q| !q.buy_out.is_zero()) {
+            let expected = quoted.sell_out.full_mul(amount_received) / quoted.buy_out;
+            if let Ok(expected) = U256::try_from(expected) {
+                self.record_slippage(opportunity, SlippageLeg::Sell, expected, final_amount.raw());
+            }
+        }
 
-        // Calculate profit
-        let profit_wei = if final_amount > trade_size {
-            final_amount - trade_size
-        } else {
-            U256::zero()
-        };
-        let profit_usd = self.wei_to_usd(profit_wei, pair_symbol);
+        // Calculate profit (zero when the round trip lost quote token)
+        let profit_usd = opportunity.quote_to_usd(final_amount - trade_size);
 
         // Estimate gas cost (actual cost would require receipt analysis)
         // Polygon: ~400k gas for two V3 swaps, ~50 gwei avg = 0.02 MATIC = ~$0.01
         let gas_used_native = 0.02; // ~400k gas at 50 gwei = 0.02 native
-        let gas_cost_usd = gas_used_native * self.config.native_token_price_usd;
+        let gas_cost_usd = gas_used_native * self.native_price.get();
         let net_profit_usd = profit_usd - gas_cost_usd;
 
         let success = net_profit_usd > 0.0;
@@ -416,30 +1582,131 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             );
         }
 
-        // Log to tax records (IRS compliance)
+        // Log to tax records (IRS compliance). Full hash: finality looks the record up by it.
         self.log_tax_record_if_enabled(
             opportunity,
-            &sell_tx_hash.to_string(),
+            &format!("{:?}", sell_tx_hash),
             sell_block,
             trade_size,
             final_amount,
             gas_used_native,
         );
+        self.track_landed(opportunity, sell_tx_hash, sell_block, None, net_profit_usd);
+
+        Ok(TradeResult {
+            opportunity: pair_symbol.clone(),
+            opportunity_id: opportunity.id,
+            tx_hash: Some(sell_tx_hash.to_string()),
+            block_number: Some(sell_block),
+            success,
+            profit_usd,
+            gas_cost_usd,
+            gas_used_native,
+            net_profit_usd,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            error: None,
+            amount_in: Some(trade_size),
+            amount_out: Some(final_amount.raw().to_string()),
+        })
+    }
+
+    /// Live checks ahead of any submission: halt, flashloan funding, balance
+    /// clamp, exposure pacing, then the preflight batch. Err = rejected result.
+    async fn gate_live(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        start_time: Instant,
+    ) -> std::result::Result<LiveGate, TradeResult> {
+        if let Some(halted) = self.halted_result(opportunity, start_time) {
+            return Err(halted);
+        }
+        if let Some(stale) = self.stale_result(opportunity, start_time) {
+            return Err(stale);
+        }
+        // Wallet for this submission: balance clamp, pacing and preflight read its balances
+        self.select_wallet();
+        // Flash funding: a V2-bought route swaps on its pair first, a route the
+        // wallet cannot fund borrows its full size — no pacing or preflight
+        // balance check, the wallet only pays gas
+        if let Some(flash) = self.flash_funding(opportunity).await {
+            return Ok(LiveGate::Flash(flash));
+        }
+        let clamped = self.clamp_to_balance(opportunity, self.min_profit_usd(opportunity), start_time).await?;
+        let opportunity = clamped.as_ref().unwrap_or(opportunity);
+        let paced = self.pace_exposure(opportunity, self.min_profit_usd(opportunity), start_time).await?;
+        let opportunity = paced.as_ref().unwrap_or(opportunity);
+        // Permit2 funding decides which allowance the preflight checks
+        self.refresh_permit2_allowance(opportunity.quote_token()).await;
+
+        // Preflight: both quotes, balance, allowance and pool state in one
+        // block-pinned eth_call. A rejection here costs nothing and needs no further RPCs.
+        match self.run_preflight(opportunity).await {
+            Ok(bundle) => Ok(LiveGate::Ready(opportunity.clone(), bundle)),
+            Err(reason) => Err(TradeResult {
+                opportunity: opportunity.pair.symbol.clone(),
+                opportunity_id: opportunity.id,
+                tx_hash: None,
+                block_number: None,
+                success: false,
+                profit_usd: 0.0,
+                gas_cost_usd: 0.0,
+                gas_used_native: 0.0,
+                net_profit_usd: 0.0,
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+                error: Some(format!("{}{}", PREFLIGHT_ERROR_PREFIX, reason)),
+                amount_in: Some(opportunity.trade_size),
+                amount_out: None,
+            }),
+        }
+    }
+
+    /// Sign and send an atomic arb without waiting for its receipt — the
+    /// parallel path ladders several of these on consecutive nonces, then
+    /// awaits wait_for_atomic_receipt() and settle_atomic() per tx.
+    /// Same gating as execute(); requires ARB_EXECUTOR_ADDRESS.
+    pub async fn submit_atomic(&mut self, opportunity: &ArbitrageOpportunity) -> Result<AtomicSubmit> {
+        let start_time = Instant::now();
+        if self.config.arb_executor_address.is_none() {
+            return Ok(AtomicSubmit::Finished(TradeResult {
+                opportunity: opportunity.pair.symbol.clone(),
+                opportunity_id: opportunity.id,
+                tx_hash: None,
+                block_number: None,
+                success: false,
+                profit_usd: 0.0,
+                gas_cost_usd: 0.0,
+                gas_used_native: 0.0,
+                net_profit_usd: 0.0,
+                execution_time_ms: 0,
+                error: Some("atomic submission requires ARB_EXECUTOR_ADDRESS".to_string()),
+                amount_in: Some(opportunity.trade_size),
+                amount_out: None,
+            }));
+        }
+        if self.dry_run {
+            return Ok(AtomicSubmit::Finished(self.simulate_execution(opportunity, start_time).await?));
+        }
+        if opportunity.split_sell.is_some() {
+            return self.send_atomic_split(opportunity, start_time).await;
+        }
+        match self.gate_live(opportunity, start_time).await {
+            Ok(LiveGate::Ready(ready, _)) => self.send_atomic(&ready, None, start_time).await,
+            Ok(LiveGate::Flash(flash)) => self.send_atomic(opportunity, Some(flash), start_time).await,
+            Err(rejected) => Ok(AtomicSubmit::Finished(rejected)),
+        }
+    }
 
-        Ok(TradeResult {
-            opportunity: pair_symbol.clone(),
-            tx_hash: Some(sell_tx_hash.to_string()),
-            block_number: Some(sell_block),
-            success,
-            profit_usd,
-            gas_cost_usd,
-            gas_used_native,
-            net_profit_usd,
-            execution_time_ms: start_time.elapsed().as_millis() as u64,
-            error: None,
-            amount_in: Some(trade_size.to_string()),
-            amount_out: Some(final_amount.to_string()),
-        })
+    /// Receipt poll for a submitted atomic tx that doesn't borrow the executor,
+    /// so several can be awaited concurrently (JoinSet)
+    pub fn wait_for_atomic_receipt(
+        &self,
+        tx_hash: TxHash,
+    ) -> impl Future<Output = Result<Option<TransactionReceipt>>> + Send + 'static {
+        let provider = self.provider.clone();
+        let faults = self.faults.clone();
+        async move {
+            receipt_with_faults(&*provider, &*faults, tx_hash, &RetrySite::named("receipt.atomic")).await
+        }
     }
 
     /// Execute an atomic arbitrage via the ArbExecutor contract.
@@ -448,92 +1715,392 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
     /// or net profit < minProfit, the entire tx reverts — zero risk.
     ///
     /// Token flow: wallet → contract → routerBuy(token0→token1) → routerSell(token1→token0) → wallet
+    /// Flash-funded (`flash` set): Aave pool → contract → both legs → pool repaid
+    /// amount + premium → profit to wallet (executeArbFlash, ArbFlashExecuted);
+    /// or V2 pair → token1 to contract → sell leg → pair repaid getAmountIn →
+    /// profit to wallet (executeArbFlashV2, ArbFlashV2Executed).
     async fn execute_atomic(
         &mut self,
         opportunity: &ArbitrageOpportunity,
+        flash: Option<FlashCall>,
         start_time: Instant,
     ) -> Result<TradeResult> {
+        match self.send_atomic(opportunity, flash, start_time).await? {
+            AtomicSubmit::Pending(pending) => self.await_settle(*pending).await,
+            AtomicSubmit::Finished(result) | AtomicSubmit::SendFailed(result) => Ok(result),
+        }
+    }
+
+    /// Build and send the ArbExecutor call for execute_atomic() / submit_atomic()
+    async fn send_atomic(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        flash: Option<FlashCall>,
+        start_time: Instant,
+    ) -> Result<AtomicSubmit> {
         let pair_symbol = &opportunity.pair.symbol;
         let arb_address = self.config.arb_executor_address.unwrap();
+        if opportunity.is_legacy_only() {
+            return Ok(AtomicSubmit::Finished(TradeResult {
+                opportunity: pair_symbol.clone(),
+                opportunity_id: opportunity.id,
+                tx_hash: None,
+                block_number: None,
+                success: false,
+                profit_usd: 0.0,
+                gas_cost_usd: 0.0,
+                gas_used_native: 0.0,
+                net_profit_usd: 0.0,
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+                error: Some(format!(
+                    "{:?} → {:?} has no ArbExecutor leg (atomic support deferred)",
+                    opportunity.buy_dex, opportunity.sell_dex
+                )),
+                amount_in: Some(opportunity.trade_size),
+                amount_out: None,
+            }));
+        }
 
         let mode = if opportunity.buy_dex.is_v2() || opportunity.sell_dex.is_v2() {
             "V2↔V3"
         } else {
             "V3↔V3"
         };
+        let permit = match flash {
+            Some(_) => None,
+            None => self.permit_funding(opportunity).ok(),
+        };
+        let funding = match (&flash, permit) {
+            (Some(FlashCall::Aave(_)), _) => "flash-funded",
+            (Some(FlashCall::V2(..)), _) => "V2 flash swap",
+            (None, Some(_)) => "wallet-funded, Permit2",
+            (None, None) => "wallet-funded",
+        };
+        let mut gas_route = match &flash {
+            Some(FlashCall::V2(..)) => flash_v2_gas_route_key(opportunity),
+            _ => gas_route_key(opportunity, flash.is_some()),
+        };
         info!(
-            "⚡ ATOMIC {} execution: {} | Buy {:?} → Sell {:?} via ArbExecutor {:?}",
-            mode, pair_symbol, opportunity.buy_dex, opportunity.sell_dex, arb_address
+            "⚡ ATOMIC {} execution ({}): {} | Buy {:?} → Sell {:?} via ArbExecutor {:?}",
+            mode, funding, pair_symbol, opportunity.buy_dex, opportunity.sell_dex, arb_address
         );
 
-        // A0+A1: Gas priority bump + cached base fee from block header.
-        // On Polygon, gas is negligible (~$0.01 even at 5000 gwei). Atomic reverts protect capital.
-        // Priority fee of 5000 gwei targets top ~30 block position (median is ~2134 gwei).
-        let base_fee = match self.cached_base_fee {
-            Some(bf) => bf,
-            None => {
-                // Fallback: fetch from RPC (only on first call before any block arrives)
-                self.provider.get_gas_price().await?
+        let (max_fee, priority_fee) = self.atomic_gas_fees(opportunity.estimated_profit).await?;
+
+        let args = execute_arb_args(
+            opportunity,
+            self.get_router_address(opportunity.buy_dex),
+            self.get_router_address(opportunity.sell_dex),
+            self.min_profit_usd(opportunity),
+        );
+
+        info!(
+            "  routerBuy={:?} feeBuy={} | routerSell={:?} feeSell={} | amountIn={} | minProfit={}",
+            args.router_buy, args.fee_buy, args.router_sell, args.fee_sell,
+            opportunity.size_display(), opportunity.quote_amount(args.min_profit)
+        );
+
+        let contract = IArbExecutor::new(arb_address, self.wallets.active().signer());
+        let (call, parse_event): (_, EventParser) = match flash {
+            Some(FlashCall::V2(flash, terms)) => {
+                info!(
+                    "  💸 V2 flash swap on pair {:?}: {} base out | owed {} | est. net ${:.4}",
+                    terms.pair,
+                    terms.base_out,
+                    terms.owed,
+                    flashloan::flash_v2_net_profit_usd(opportunity, &terms)
+                );
+                (contract.execute_arb_flash_v2(flash.pair, flash.base_out, flash.arb), parse_arb_flash_v2_executed)
             }
+            Some(FlashCall::Aave(flash)) => {
+                let premium = flashloan::flash_premium(opportunity.trade_size);
+                info!(
+                    "  💸 Flashloan {} from Aave pool {:?} | premium {} | est. net ${:.4}",
+                    opportunity.size_display(),
+                    flash.aave_pool,
+                    premium,
+                    flashloan::flash_net_profit_usd(opportunity)
+                );
+                (contract.execute_arb_flash(flash.aave_pool, flash.arb), parse_arb_flash_executed)
+            }
+            None => match self.sign_permit(permit, &args, arb_address).await {
+                Some((permit2, nonce, deadline, signature)) => {
+                    let arb = TwoLegArb {
+                        token_0: args.token_0,
+                        token_1: args.token_1,
+                        router_buy: args.router_buy,
+                        router_sell: args.router_sell,
+                        fee_buy: args.fee_buy,
+                        fee_sell: args.fee_sell,
+                        amount_in: args.amount_in,
+                        min_profit: args.min_profit,
+                    };
+                    gas_route = permit_gas_route_key(opportunity);
+                    (contract.execute_arb_with_permit(permit2, arb, nonce, deadline, signature), parse_arb_executed)
+                }
+                None => (
+                    contract.execute_arb(
+                        args.token_0, args.token_1, args.router_buy, args.router_sell,
+                        args.fee_buy, args.fee_sell, args.amount_in, args.min_profit,
+                    ),
+                    parse_arb_executed,
+                ),
+            },
         };
-        let priority_fee = U256::from(5_000_000_000_000u64); // 5000 gwei
-        let max_fee = base_fee + priority_fee;
+        // event ArbExecuted(token0, token1, amountIn, amountOut, profit, routerBuy, routerSell)
+        // or ArbFlashExecuted(token0, token1, amountIn, amountOut, premium, profit)
+        // or ArbFlashV2Executed(token0, token1, pair, owed, amountOut, profit)
+        self.submit_atomic_call(call, opportunity, max_fee, priority_fee, start_time, parse_event, Some(gas_route))
+            .await
+    }
 
-        // ArbExecutor.sol token0 = "base token" (start & end) = USDC (quote token)
-        // ArbExecutor.sol token1 = "intermediate token" (bought & sold)
-        // Map from V3 pool ordering to contract ordering based on quote_token_is_token0
-        let (token0, token1) = if opportunity.quote_token_is_token0 {
-            (opportunity.pair.token0, opportunity.pair.token1)
-        } else {
-            (opportunity.pair.token1, opportunity.pair.token0)
+    /// Execute a multi-leg route via ArbExecutor.executeArbMulti.
+    ///
+    /// Every leg of `opportunity.route_legs` runs in one transaction, starting and
+    /// ending in the quote token; the contract reverts if profit < minProfit.
+    /// Gas pricing, nonce handling and the private-RPC send path are the same as
+    /// execute_atomic(); profit comes from the ArbMultiExecuted event.
+    pub async fn execute_atomic_multi(&mut self, opportunity: &ArbitrageOpportunity) -> Result<TradeResult> {
+        let start_time = Instant::now();
+        let rejected = |reason: &str| TradeResult {
+            opportunity: opportunity.pair.symbol.clone(),
+            opportunity_id: opportunity.id,
+            tx_hash: None,
+            block_number: None,
+            success: false,
+            profit_usd: 0.0,
+            gas_cost_usd: 0.0,
+            gas_used_native: 0.0,
+            net_profit_usd: 0.0,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            error: Some(reason.to_string()),
+            amount_in: Some(opportunity.trade_size),
+            amount_out: None,
         };
-        let trade_size = opportunity.trade_size;
+        let Some(arb_address) = self.config.arb_executor_address else {
+            return Ok(rejected("multi-leg execution requires ARB_EXECUTOR_ADDRESS"));
+        };
+        let Some(legs) = multi_legs(opportunity, |dex| self.get_router_address(dex)) else {
+            return Ok(rejected("multi-leg execution requires a chained route_legs cycle through the quote token"));
+        };
+
+        if self.dry_run {
+            return self.simulate_execution(opportunity, start_time).await;
+        }
+        if let Some(halted) = self.halted_result(opportunity, start_time) {
+            return Ok(halted);
+        }
+        if let Some(stale) = self.stale_result(opportunity, start_time) {
+            return Ok(stale);
+        }
+        self.select_wallet();
+        let clamped = match self.clamp_to_balance(opportunity, self.min_profit_usd(opportunity), start_time).await {
+            Ok(clamped) => clamped,
+            Err(rejected) => return Ok(rejected),
+        };
+        let opportunity = clamped.as_ref().unwrap_or(opportunity);
+        let paced = match self.pace_exposure(opportunity, self.min_profit_usd(opportunity), start_time).await {
+            Ok(paced) => paced,
+            Err(rejected) => return Ok(rejected),
+        };
+        let opportunity = paced.as_ref().unwrap_or(opportunity);
+
+        info!(
+            "⚡ ATOMIC {}-leg execution: {} via ArbExecutor {:?}",
+            legs.len(), opportunity.pair.symbol, arb_address
+        );
+        let (max_fee, priority_fee) = self.atomic_gas_fees(opportunity.estimated_profit).await?;
+        let amount_in = opportunity.trade_size.raw();
+        let min_profit = opportunity.usd_to_quote(self.min_profit_usd(opportunity)).raw();
+        for (i, leg) in legs.iter().enumerate() {
+            info!("  leg {}: {:?} → {:?} router={:?} fee={}", i + 1, leg.token_in, leg.token_out, leg.router, leg.fee);
+        }
+        info!("  amountIn={} | minProfit={}", opportunity.size_display(), opportunity.quote_amount(min_profit));
+
+        let contract = IArbExecutor::new(arb_address, self.wallets.active().signer());
+        let call = contract.execute_arb_multi(legs, amount_in, min_profit);
+        match self
+            .submit_atomic_call(call, opportunity, max_fee, priority_fee, start_time, parse_arb_multi_executed, None)
+            .await?
+        {
+            AtomicSubmit::Pending(pending) => self.await_settle(*pending).await,
+            AtomicSubmit::Finished(result) | AtomicSubmit::SendFailed(result) => Ok(result),
+        }
+    }
 
-        // Get router addresses and fee sentinels.
-        // atomic_fee() returns: V2 → 16777215 (V2 sentinel), Algebra → 0, V3 → fee tier
-        let router_buy = self.get_router_address(opportunity.buy_dex);
-        let router_sell = self.get_router_address(opportunity.sell_dex);
-        let fee_buy = opportunity.buy_dex.atomic_fee();
-        let fee_sell = opportunity.sell_dex.atomic_fee();
+    /// Execute a split-sell route via ArbExecutor.executeArbSplit.
+    ///
+    /// One buy leg on buy_dex; the base it returns is sold on sell_dex and
+    /// split_sell.dex, share_bps of it on the latter. The whole route reverts
+    /// if profit < minProfit. Profit comes from the ArbSplitExecuted event.
+    pub async fn execute_atomic_split(&mut self, opportunity: &ArbitrageOpportunity) -> Result<TradeResult> {
+        let start_time = Instant::now();
+        if self.dry_run {
+            return self.simulate_execution(opportunity, start_time).await;
+        }
+        match self.send_atomic_split(opportunity, start_time).await? {
+            AtomicSubmit::Pending(pending) => self.await_settle(*pending).await,
+            AtomicSubmit::Finished(result) | AtomicSubmit::SendFailed(result) => Ok(result),
+        }
+    }
 
-        // minProfit in token0 raw units
-        // Convert min_profit_usd to token0 units (USDC = 6 dec, 1 USDC = 1e6)
-        // For non-stablecoin base tokens this would need a price oracle
-        let min_profit_raw = U256::from((self.config.min_profit_usd * 1e6) as u64);
+    /// Gate, build and send the executeArbSplit call for execute_atomic_split() /
+    /// submit_atomic(). No preflight: its sell quote is the full size on one pool,
+    /// which is exactly what the split avoids.
+    async fn send_atomic_split(&mut self, opportunity: &ArbitrageOpportunity, start_time: Instant) -> Result<AtomicSubmit> {
+        let rejected = |reason: &str| {
+            AtomicSubmit::Finished(TradeResult {
+                opportunity: opportunity.pair.symbol.clone(),
+                opportunity_id: opportunity.id,
+                tx_hash: None,
+                block_number: None,
+                success: false,
+                profit_usd: 0.0,
+                gas_cost_usd: 0.0,
+                gas_used_native: 0.0,
+                net_profit_usd: 0.0,
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+                error: Some(reason.to_string()),
+                amount_in: Some(opportunity.trade_size),
+                amount_out: None,
+            })
+        };
+        let Some(arb_address) = self.config.arb_executor_address else {
+            return Ok(rejected("split execution requires ARB_EXECUTOR_ADDRESS"));
+        };
+        let Some(split) = opportunity.split_sell else {
+            return Ok(rejected("split execution requires a split_sell leg"));
+        };
+        if [opportunity.buy_dex, opportunity.sell_dex, split.dex].iter().any(|dex| dex.is_detection_only()) {
+            return Ok(rejected("split execution: route includes a detection-only DEX"));
+        }
+        if [opportunity.buy_dex, opportunity.sell_dex, split.dex].iter().any(|dex| dex.is_legacy_only()) {
+            return Ok(rejected("split execution: route includes a legacy-only DEX (atomic support deferred)"));
+        }
+        if let Some(halted) = self.halted_result(opportunity, start_time) {
+            return Ok(AtomicSubmit::Finished(halted));
+        }
+        if let Some(stale) = self.stale_result(opportunity, start_time) {
+            return Ok(AtomicSubmit::Finished(stale));
+        }
+        self.select_wallet();
+        let clamped = match self.clamp_to_balance(opportunity, self.min_profit_usd(opportunity), start_time).await {
+            Ok(clamped) => clamped,
+            Err(rejected) => return Ok(AtomicSubmit::Finished(rejected)),
+        };
+        let opportunity = clamped.as_ref().unwrap_or(opportunity);
+        let paced = match self.pace_exposure(opportunity, self.min_profit_usd(opportunity), start_time).await {
+            Ok(paced) => paced,
+            Err(rejected) => return Ok(AtomicSubmit::Finished(rejected)),
+        };
+        let opportunity = paced.as_ref().unwrap_or(opportunity);
 
+        let Some(arb) = execute_arb_split_args(opportunity, |dex| self.get_router_address(dex), self.min_profit_usd(opportunity))
+        else {
+            return Ok(rejected("split execution requires a split_sell leg"));
+        };
         info!(
-            "  routerBuy={:?} feeBuy={} | routerSell={:?} feeSell={} | amountIn={} | minProfit={}",
-            router_buy, fee_buy, router_sell, fee_sell, trade_size, min_profit_raw
+            "⚡ ATOMIC split execution: {} | Buy {:?} → Sell {:?} {:.1}% + {:?} {:.1}% via ArbExecutor {:?}",
+            opportunity.pair.symbol,
+            opportunity.buy_dex,
+            opportunity.sell_dex,
+            100.0 - f64::from(split.share_bps) / 100.0,
+            split.dex,
+            f64::from(split.share_bps) / 100.0,
+            arb_address
+        );
+        let (max_fee, priority_fee) = self.atomic_gas_fees(opportunity.estimated_profit).await?;
+        info!(
+            "  routerBuy={:?} feeBuy={} | routerSellA={:?} feeSellA={} | routerSellB={:?} feeSellB={} | amountIn={} | minProfit={}",
+            arb.router_buy, arb.fee_buy, arb.router_sell_a, arb.fee_sell_a, arb.router_sell_b, arb.fee_sell_b,
+            opportunity.size_display(), opportunity.quote_amount(arb.min_profit)
         );
 
+        let contract = IArbExecutor::new(arb_address, self.wallets.active().signer());
+        let call = contract.execute_arb_split(arb);
+        self.submit_atomic_call(call, opportunity, max_fee, priority_fee, start_time, parse_arb_split_executed, None)
+            .await
+    }
+
+    /// Profit floor for `opportunity`: its adaptive threshold when the detector
+    /// set one (threshold.rs), else MIN_PROFIT_USD
+    fn min_profit_usd(&self, opportunity: &ArbitrageOpportunity) -> f64 {
+        opportunity.min_profit_usd.unwrap_or(self.config.min_profit_usd)
+    }
+
+    /// A0+A1: (max fee, priority fee) for atomic submissions — gas priority bump
+    /// over the cached base fee from the block header.
+    /// Priority fee: atomic_priority_fee() capped by the mempool path's profit
+    /// budget (MEMPOOL_GAS_PROFIT_CAP of est. profit over MEMPOOL_GAS_LIMIT).
+    async fn atomic_gas_fees(&self, est_profit_usd: f64) -> Result<(U256, U256)> {
+        let base_fee = match self.cached_base_fee {
+            Some(bf) => bf,
+            None => {
+                // Fallback: fetch from RPC (only on first call before any block arrives)
+                rpc_timeout("eth_gasPrice", self.provider.get_gas_price()).await?
+            }
+        };
+        let bid = self.atomic_priority_fee();
+        let priority_fee = gas_oracle::profit_cap(
+            est_profit_usd,
+            self.config.mempool_gas_profit_cap,
+            self.native_price.get(),
+            self.config.mempool_gas_limit,
+        )
+        .map_or(bid, |cap| bid.min(cap));
+        if priority_fee < bid {
+            debug!(
+                "ATOMIC GAS: bid {:.0}gwei capped to {:.0}gwei by est. profit ${:.2}",
+                bid.as_u128() as f64 / 1e9, priority_fee.as_u128() as f64 / 1e9, est_profit_usd
+            );
+        }
+        Ok((base_fee + priority_fee, priority_fee))
+    }
+
+    /// Send an ArbExecutor call: nonce, fill (estimateGas, skipped when the gas
+    /// cache has `gas_route`), sign + journal, then raw send via the private RPC
+    /// when configured, else the WS provider.
+    /// Pending = sent (journaled, nonce advanced); SendFailed = journal cleared.
+    #[allow(clippy::too_many_arguments)]
+    async fn submit_atomic_call(
+        &mut self,
+        call: ContractCall<SigningClient<M>, U256>,
+        opportunity: &ArbitrageOpportunity,
+        max_fee: U256,
+        priority_fee: U256,
+        start_time: Instant,
+        parse_event: EventParser,
+        gas_route: Option<String>,
+    ) -> Result<AtomicSubmit> {
         // Build + sign via WS provider. Gas price and nonce are pre-set (A0-A2)
         // so fill_transaction only needs to call estimateGas.
         // If private RPC is configured, send only the raw signed bytes through it.
-        let ws_signer = Arc::new(SignerMiddleware::new(
-            self.provider.clone(),
-            self.wallet.clone().with_chain_id(self.config.chain_id),
-        ));
-        let contract = IArbExecutor::new(arb_address, ws_signer.clone());
-        let call = contract.execute_arb(
-            token0, token1, router_buy, router_sell,
-            fee_buy.into(), fee_sell.into(), trade_size, min_profit_raw,
-        );
+        // Signer and private provider are pre-built (no connection setup here).
+        let submit_start = Instant::now();
+        let ws_signer = self.wallets.active().signer();
 
         // A2: Initialize nonce on first use, then track locally
-        if !self.nonce_initialized {
-            let nonce = self.provider.get_transaction_count(
-                self.wallet.address(), Some(BlockNumber::Pending.into())
-            ).await?;
-            self.cached_nonce.store(nonce.as_u64(), Ordering::SeqCst);
-            self.nonce_initialized = true;
-            info!("Nonce initialized: {}", nonce);
+        let current_nonce = self.ensure_nonce().await?;
+
+        // Cached route gas limit → fill_transaction skips estimateGas
+        let cached_gas = gas_route
+            .as_deref()
+            .and_then(|route| self.gas_cache.as_ref().and_then(|cache| cache.estimate(route)));
+        if let Some(gas) = cached_gas {
+            debug!("Gas cache hit: {} → gas limit {}", gas_route.as_deref().unwrap_or_default(), gas);
         }
-        let current_nonce = U256::from(self.cached_nonce.load(Ordering::SeqCst));
 
-        let send_result: Result<TxHash, String> = if let Some(ref tx_client) = self.tx_client {
+        let mut private_send_failed = false;
+        let mut journaled: Option<TxHash> = None;
+        let send_result: Result<TxHash, String> = if let Some(err_msg) = self.injected_send_error("eth_sendRawTransaction") {
+            Err(format!("Atomic tx send failed: {}", err_msg))
+        } else if let Some(tx_client) = self.connections.private_client() {
             // Private RPC path: pre-set gas + nonce on tx, fill only does estimateGas.
             // Then sign via WS signer, send raw bytes via private RPC.
-            info!("📡 Sending via private mempool (priority=5000gwei, nonce={})", current_nonce);
+            info!(
+                "📡 Sending via private mempool (priority={:.0}gwei, nonce={})",
+                priority_fee.as_u128() as f64 / 1e9, current_nonce
+            );
             let mut tx = call.tx.clone();
             // A0+A1+A2: Pre-set EIP-1559 gas fields and nonce to skip RPC lookups.
             // fill_transaction will still call estimateGas but skip gas/nonce fetches
@@ -543,52 +2110,77 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                 inner.max_fee_per_gas = Some(max_fee);
                 inner.max_priority_fee_per_gas = Some(priority_fee);
             });
-            match ws_signer.fill_transaction(&mut tx, None).await {
+            if let Some(gas) = cached_gas {
+                tx.set_gas(gas);
+            }
+            match self.timed(Stage::Fill, rpc_timeout("estimateGas", ws_signer.fill_transaction(&mut tx, None))).await {
                 Err(e) => Err(format!("Atomic tx fill failed (WS): {}", e)),
                 Ok(()) => {
-                    match ws_signer.signer().sign_transaction(&tx).await {
-                        Err(e) => Err(format!("Atomic tx sign failed: {}", e)),
-                        Ok(signature) => {
-                            let raw_tx = tx.rlp_signed(&signature);
-                            match tx_client.send_raw_transaction(raw_tx).await {
+                    match self.sign_and_journal(&ws_signer, &tx, opportunity, current_nonce, "atomic").await {
+                        Err(e) => Err(format!("Atomic tx {}", e)),
+                        Ok((raw_tx, hash)) => {
+                            journaled = Some(hash);
+                            match self.timed(Stage::Send, tx_client.send_raw_transaction(raw_tx)).await {
                                 Ok(pending) => {
                                     // A2: Increment nonce on successful send
-                                    self.cached_nonce.fetch_add(1, Ordering::SeqCst);
+                                    self.wallets.on_sent(pending.tx_hash());
                                     Ok(pending.tx_hash())
                                 }
-                                Err(e) => Err(format!("Atomic tx send failed (private): {}", e))
+                                Err(e) => {
+                                    private_send_failed = true;
+                                    Err(format!("Atomic tx send failed (private): {}", e))
+                                }
                             }
                         }
                     }
                 }
             }
         } else {
-            // Public WS path: set gas fields on the contract call, then send.
-            let call = call
+            // Public WS path: legacy gas price + nonce on the contract call; fill
+            // (estimateGas), sign and journal, then send the raw bytes.
+            let mut tx = call
                 .gas_price(max_fee)  // Legacy gas price fallback
-                .nonce(current_nonce);
-            // Save result in local var to ensure PendingTransaction borrow
-            // is dropped before `call` goes out of scope.
-            let result = match call.send().await {
-                Ok(pending) => {
-                    self.cached_nonce.fetch_add(1, Ordering::SeqCst);
-                    Ok(pending.tx_hash())
-                }
-                Err(e) => Err(format!("Atomic tx send failed: {}", e))
-            };
-            result
+                .nonce(current_nonce)
+                .tx;
+            if let Some(gas) = cached_gas {
+                tx.set_gas(gas);
+            }
+            match self.timed(Stage::Fill, rpc_timeout("estimateGas", ws_signer.fill_transaction(&mut tx, None))).await {
+                Err(e) => Err(format!("Atomic tx send failed: {}", e)),
+                Ok(()) => match self.sign_and_journal(&ws_signer, &tx, opportunity, current_nonce, "atomic").await {
+                    Err(e) => Err(format!("Atomic tx {}", e)),
+                    Ok((raw_tx, hash)) => {
+                        journaled = Some(hash);
+                        match self.timed(Stage::Send, ws_signer.provider().send_raw_transaction(raw_tx)).await {
+                            Ok(pending) => {
+                                self.wallets.on_sent(pending.tx_hash());
+                                Ok(pending.tx_hash())
+                            }
+                            Err(e) => Err(format!("Atomic tx send failed: {}", e)),
+                        }
+                    }
+                },
+            }
         };
 
+        if private_send_failed {
+            self.connections.on_private_error();
+        }
+
         let tx_hash = match send_result {
             Ok(hash) => hash,
             Err(err_msg) => {
+                self.on_send_error(&err_msg);
+                self.clear_inflight(journaled);
                 if err_msg.contains("InsufficientProfit") || err_msg.contains("execution reverted") {
                     info!("Atomic arb reverted (expected: insufficient profit or pool conditions changed)");
                 } else {
                     error!("{}", err_msg);
                 }
-                return Ok(TradeResult {
-                    opportunity: pair_symbol.clone(),
+                self.note_submission(None, "atomic", priority_fee, None);
+                return Ok(AtomicSubmit::SendFailed(TradeResult {
+                    opportunity: opportunity.pair.symbol.clone(),
+                    opportunity_id: opportunity.id,
                     tx_hash: None,
                     block_number: None,
                     success: false,
@@ -598,101 +2190,186 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                     net_profit_usd: 0.0,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
                     error: Some(err_msg),
-                    amount_in: Some(trade_size.to_string()),
+                    amount_in: Some(opportunity.trade_size),
                     amount_out: None,
-                });
+                }));
             }
         };
 
-        info!("⚡ Atomic arb tx submitted: {:?}", tx_hash);
+        // Competition model estimate logged with the bid for calibration
+        let win_estimate = opportunity
+            .win_probability
+            .map(|p| format!(" | bid {:.0} gwei, est. win {:.0}%", priority_fee.as_u128() as f64 / 1e9, p * 100.0))
+            .unwrap_or_default();
+        info!(
+            "⚡ Atomic arb tx submitted: {:?} (submit path {}ms){}",
+            tx_hash,
+            submit_start.elapsed().as_millis(),
+            win_estimate
+        );
+        if let Some(elapsed) = self.latency.as_ref().and_then(|l| l.record_submit()) {
+            if self.block_to_submit.len() == BLOCK_TO_SUBMIT_KEEP {
+                self.block_to_submit.pop_front();
+            }
+            self.block_to_submit.push_back((tx_hash, elapsed.as_millis() as u64));
+        }
+
+        let block = self.current_block.unwrap_or(0);
+        let opportunity_id = events::opportunity_id(block, opportunity);
+        if let Some(journal) = &self.events {
+            journal.emit(
+                block,
+                BotEvent::TxSubmitted {
+                    opportunity_id: opportunity_id.clone(),
+                    tx_hash: format!("{:?}", tx_hash),
+                    nonce: current_nonce.as_u64(),
+                    priority_fee_gwei: priority_fee.as_u128() as f64 / 1e9,
+                },
+            );
+        }
+
+        Ok(AtomicSubmit::Pending(Box::new(PendingAtomic {
+            opportunity: opportunity.clone(),
+            tx_hash,
+            wallet: self.wallets.active().address(),
+            opportunity_id,
+            gas_route,
+            journaled,
+            max_fee,
+            priority_fee,
+            start_time,
+            parse_event,
+        })))
+    }
+
+    /// Await a sent atomic tx's receipt, then settle it
+    async fn await_settle(&mut self, pending: PendingAtomic) -> Result<TradeResult> {
+        match self.await_receipt(pending.tx_hash, &RetrySite::named("receipt.atomic")).await {
+            Ok(receipt) => self.settle_atomic(pending, receipt).await,
+            Err(e) => {
+                self.release_unsettled(&pending);
+                Err(e)
+            }
+        }
+    }
+
+    /// The receipt fetch for a sent atomic tx failed: its wallet is no longer
+    /// busy with it, and the in-flight journal entry is left for
+    /// recover_inflight() to resolve on the next startup
+    pub fn release_unsettled(&mut self, pending: &PendingAtomic) {
+        self.wallets.settle(pending.tx_hash);
+        if pending.journaled.is_some() {
+            warn!("Receipt unknown for {:?} — left in the in-flight journal for startup recovery", pending.tx_hash);
+        }
+    }
+
+    /// Revert check, realized profit from the contract event
+    /// (`parse_event` → (amountOut, profit) raw), tax record and finality
+    /// tracking for a submitted ArbExecutor tx. `receipt` None = the 30s
+    /// receipt poll timed out (tx may still confirm later).
+    pub async fn settle_atomic(
+        &mut self,
+        pending: PendingAtomic,
+        receipt: Option<TransactionReceipt>,
+    ) -> Result<TradeResult> {
+        let opportunity_id = pending.opportunity_id.clone();
+        let result = self.settle_atomic_receipt(pending, receipt).await;
+        if let (Some(journal), Ok(result)) = (&self.events, &result) {
+            journal.emit(self.current_block.unwrap_or(0), BotEvent::receipt(opportunity_id, result));
+        }
+        result
+    }
+
+    async fn settle_atomic_receipt(
+        &mut self,
+        pending: PendingAtomic,
+        receipt: Option<TransactionReceipt>,
+    ) -> Result<TradeResult> {
+        let PendingAtomic { ref opportunity, tx_hash, wallet: _, opportunity_id: _, gas_route, journaled, max_fee, priority_fee, start_time, parse_event } = pending;
+        // Its wallet is no longer busy, and the tax record and balances below are that wallet's
+        self.wallets.settle(tx_hash);
+        let pair_symbol = &opportunity.pair.symbol;
+        let arb_address = self.config.arb_executor_address.unwrap();
+        let trade_size = opportunity.trade_size;
 
-        // Wait for receipt using main provider (WS — fast block notifications).
-        // Polls get_transaction_receipt since PendingTransaction types differ
+        // Receipts are polled on the main provider (WS — fast block notifications)
+        // via get_transaction_receipt, since PendingTransaction types differ
         // between WS and HTTP providers (Rust generics constraint).
         // Timeout after 30s (~15 Polygon blocks) to avoid blocking the main loop.
-        let receipt_deadline = Instant::now() + Duration::from_secs(30);
-        let receipt = loop {
-            match self.provider.get_transaction_receipt(tx_hash).await {
-                Ok(Some(r)) => break r,
-                Ok(None) => {
-                    if Instant::now() > receipt_deadline {
-                        error!("Receipt timeout (30s) for tx {:?} — tx may still confirm later", tx_hash);
-                        return Ok(TradeResult {
-                            opportunity: pair_symbol.clone(),
-                            tx_hash: Some(format!("{:?}", tx_hash)),
-                            block_number: None,
-                            success: false,
-                            profit_usd: 0.0,
-                            gas_cost_usd: 0.0,
-                            gas_used_native: 0.0,
-                            net_profit_usd: 0.0,
-                            execution_time_ms: start_time.elapsed().as_millis() as u64,
-                            error: Some("Receipt timeout — tx submitted but unconfirmed".to_string()),
-                            amount_in: Some(trade_size.to_string()),
-                            amount_out: None,
-                        });
-                    }
-                    tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
-                }
-                Err(e) => return Err(anyhow!("Failed to fetch receipt for {:?}: {}", tx_hash, e)),
+        let receipt = match receipt {
+            Some(r) => r,
+            None => {
+                error!("Receipt timeout (30s) for tx {:?} — tx may still confirm later", tx_hash);
+                self.note_submission(Some(tx_hash), "atomic", priority_fee, None);
+                return Ok(TradeResult {
+                    opportunity: pair_symbol.clone(),
+                    opportunity_id: opportunity.id,
+                    tx_hash: Some(format!("{:?}", tx_hash)),
+                    block_number: None,
+                    success: false,
+                    profit_usd: 0.0,
+                    gas_cost_usd: 0.0,
+                    gas_used_native: 0.0,
+                    net_profit_usd: 0.0,
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    error: Some("Receipt timeout — tx submitted but unconfirmed".to_string()),
+                    amount_in: Some(trade_size),
+                    amount_out: None,
+                });
             }
         };
 
+        self.note_submission(Some(tx_hash), "atomic", priority_fee, Some(&receipt));
         let block_number = receipt.block_number.map(|bn| bn.as_u64()).unwrap_or(0);
 
         if receipt.status != Some(U64::from(1)) {
             warn!("Atomic arb tx reverted on-chain (tx confirmed but failed)");
+            self.clear_inflight(journaled);
+            if let (Some(cache), Some(route)) = (self.gas_cache.as_mut(), gas_route.as_deref()) {
+                cache.invalidate(route);
+            }
+            let gas_used_native = receipt_gas_native(&receipt, max_fee);
+            let gas_cost_usd = gas_used_native * self.native_price.get();
+            self.log_fee_record_if_enabled(
+                opportunity, &format!("{:?}", tx_hash), block_number, gas_used_native, "Atomic tx reverted on-chain",
+            );
             return Ok(TradeResult {
                 opportunity: pair_symbol.clone(),
+                opportunity_id: opportunity.id,
                 tx_hash: Some(format!("{:?}", tx_hash)),
                 block_number: Some(block_number),
                 success: false,
                 profit_usd: 0.0,
-                gas_cost_usd: 0.0,
-                gas_used_native: 0.0,
-                net_profit_usd: 0.0,
+                gas_cost_usd,
+                gas_used_native,
+                net_profit_usd: -gas_cost_usd,
                 execution_time_ms: start_time.elapsed().as_millis() as u64,
                 error: Some("Atomic tx reverted on-chain".to_string()),
-                amount_in: Some(trade_size.to_string()),
+                amount_in: Some(trade_size),
                 amount_out: None,
             });
         }
 
-        // Parse profit from ArbExecuted event
-        // event ArbExecuted(token0, token1, amountIn, amountOut, profit, routerBuy, routerSell)
-        // topic0 = keccak256("ArbExecuted(address,address,uint256,uint256,uint256,address,address)")
-        let mut profit_raw = U256::zero();
-        let mut amount_out = trade_size; // fallback
-        let arb_executed_topic: H256 = ethers::utils::keccak256(
-            b"ArbExecuted(address,address,uint256,uint256,uint256,address,address)"
-        ).into();
-
-        for log in &receipt.logs {
-            if log.address == arb_address && !log.topics.is_empty() && log.topics[0] == arb_executed_topic {
-                // data layout: amountIn (32) | amountOut (32) | profit (32) | routerBuy (32) | routerSell (32)
-                if log.data.len() >= 96 {
-                    amount_out = U256::from_big_endian(&log.data[32..64]);
-                    profit_raw = U256::from_big_endian(&log.data[64..96]);
-                    debug!("Parsed ArbExecuted: amountOut={}, profit={}", amount_out, profit_raw);
-                }
-                break;
-            }
+        self.apply_own_receipt(&receipt);
+
+        // Parse (amountOut, profit) from the contract's execution event
+        let parsed = parse_event(&receipt.logs, arb_address);
+        let (amount_out, profit) = parsed
+            .map(|(out, profit)| (opportunity.quote_amount(out), opportunity.quote_amount(profit)))
+            .unwrap_or((trade_size, trade_size.with_raw(U256::zero()))); // fallback
+        debug!("Parsed execution event: amountOut={}, profit={}", amount_out, profit);
+        // Round-trip fill vs the pre-screen quote (buy-leg slippage folded in)
+        if let (Some(quoted), Some(_)) = (opportunity.quoted_legs, parsed) {
+            self.record_slippage(opportunity, SlippageLeg::Sell, quoted.sell_out, amount_out.raw());
         }
 
-        // profit_raw is in token0 (quote token = USDC) raw units.
-        // Use actual quote token decimals instead of wei_to_usd() which assumes 18-dec WETH.
-        let quote_decimals = if opportunity.quote_token_is_token0 {
-            opportunity.token0_decimals
-        } else {
-            opportunity.token1_decimals
-        };
-        let profit_usd = profit_raw.low_u128() as f64 / 10_f64.powi(quote_decimals as i32);
+        let profit_usd = opportunity.quote_to_usd(profit);
         // Actual gas from receipt
-        let gas_used = receipt.gas_used.unwrap_or(U256::from(400_000u64));
-        let effective_gas_price = receipt.effective_gas_price.unwrap_or(max_fee);
-        let gas_cost_wei = gas_used * effective_gas_price;
-        let gas_used_native = gas_cost_wei.low_u128() as f64 / 1e18;
-        let gas_cost_usd = gas_used_native * self.config.native_token_price_usd;
+        if let (Some(cache), Some(route), Some(used)) = (self.gas_cache.as_mut(), gas_route.as_deref(), receipt.gas_used) {
+            cache.record(route, used.as_u64());
+        }
+        let gas_used_native = receipt_gas_native(&receipt, max_fee);
+        let gas_cost_usd = gas_used_native * self.native_price.get();
         let net_profit_usd = profit_usd - gas_cost_usd;
 
         let success = net_profit_usd > 0.0;
@@ -718,9 +2395,12 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             amount_out,
             gas_used_native,
         );
+        self.track_landed(opportunity, tx_hash, block_number, receipt.block_hash, net_profit_usd);
+        self.clear_inflight(journaled);
 
         Ok(TradeResult {
             opportunity: pair_symbol.clone(),
+            opportunity_id: opportunity.id,
             tx_hash: Some(format!("{:?}", tx_hash)),
             block_number: Some(block_number),
             success,
@@ -730,8 +2410,8 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             net_profit_usd,
             execution_time_ms: start_time.elapsed().as_millis() as u64,
             error: None,
-            amount_in: Some(trade_size.to_string()),
-            amount_out: Some(amount_out.to_string()),
+            amount_in: Some(trade_size),
+            amount_out: Some(amount_out.raw().to_string()),
         })
     }
 
@@ -743,11 +2423,15 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
     /// 3. Lower minProfit threshold — mempool signals have higher conviction
     ///
     /// Safety: ArbExecutor.sol reverts if profit < minProfit. Only gas (~$0.01) at risk.
+    ///
+    /// With a bundle relay set and a `trigger`, the signed tx goes out as a
+    /// bundle behind the trigger; a refused bundle is raw-sent instead.
     pub async fn execute_from_mempool(
         &mut self,
         opportunity: &ArbitrageOpportunity,
         trigger_gas_price: U256,
         trigger_max_priority_fee: Option<U256>,
+        trigger: Option<&TriggerTx>,
         mempool_min_profit_usd: f64,
     ) -> Result<TradeResult> {
         let start_time = Instant::now();
@@ -757,6 +2441,7 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             None => {
                 return Ok(TradeResult {
                     opportunity: pair_symbol.clone(),
+                    opportunity_id: opportunity.id,
                     tx_hash: None, block_number: None, success: false,
                     profit_usd: 0.0, gas_cost_usd: 0.0, gas_used_native: 0.0,
                     net_profit_usd: 0.0,
@@ -770,6 +2455,23 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         if self.dry_run {
             return self.simulate_execution(opportunity, start_time).await;
         }
+        if let Some(halted) = self.halted_result(opportunity, start_time) {
+            return Ok(halted);
+        }
+        if let Some(stale) = self.stale_result(opportunity, start_time) {
+            return Ok(stale);
+        }
+        self.select_wallet();
+        let clamped = match self.clamp_to_balance(opportunity, mempool_min_profit_usd, start_time).await {
+            Ok(clamped) => clamped,
+            Err(rejected) => return Ok(rejected),
+        };
+        let opportunity = clamped.as_ref().unwrap_or(opportunity);
+        let paced = match self.pace_exposure(opportunity, mempool_min_profit_usd, start_time).await {
+            Ok(paced) => paced,
+            Err(rejected) => return Ok(rejected),
+        };
+        let opportunity = paced.as_ref().unwrap_or(opportunity);
 
         let mode = if opportunity.buy_dex.is_v2() || opportunity.sell_dex.is_v2() {
             "V2↔V3"
@@ -785,7 +2487,7 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         // Dynamic gas pricing (Phase 3: profit-aware bidding)
         let base_fee = match self.cached_base_fee {
             Some(bf) => bf,
-            None => self.provider.get_gas_price().await?,
+            None => rpc_timeout("eth_gasPrice", self.provider.get_gas_price()).await?,
         };
         let (priority_fee, max_fee) = self.calculate_mempool_gas(
             trigger_gas_price,
@@ -794,55 +2496,98 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             base_fee,
         );
 
-        // Token ordering: token0 = quote (USDC), token1 = base
-        let (token0, token1) = if opportunity.quote_token_is_token0 {
-            (opportunity.pair.token0, opportunity.pair.token1)
-        } else {
-            (opportunity.pair.token1, opportunity.pair.token0)
-        };
         let trade_size = opportunity.trade_size;
 
-        let router_buy = self.get_router_address(opportunity.buy_dex);
-        let router_sell = self.get_router_address(opportunity.sell_dex);
-        let fee_buy = opportunity.buy_dex.atomic_fee();
-        let fee_sell = opportunity.sell_dex.atomic_fee();
-
         // Lower minProfit for mempool signals (higher conviction)
-        let min_profit_raw = U256::from((mempool_min_profit_usd * 1e6) as u64);
+        let args = execute_arb_args(
+            opportunity,
+            self.get_router_address(opportunity.buy_dex),
+            self.get_router_address(opportunity.sell_dex),
+            mempool_min_profit_usd,
+        );
 
         let gas_limit = U256::from(self.config.mempool_gas_limit);
 
         info!(
             "  MEMPOOL TX: routerBuy={:?} feeBuy={} | routerSell={:?} feeSell={} | amt={} | minProfit={} | gas={}K priority={:.0}gwei",
-            router_buy, fee_buy, router_sell, fee_sell, trade_size, min_profit_raw,
+            args.router_buy, args.fee_buy, args.router_sell, args.fee_sell,
+            opportunity.size_display(), opportunity.quote_amount(args.min_profit),
             gas_limit.as_u64() / 1000,
             priority_fee.as_u128() as f64 / 1e9,
         );
 
-        // Build contract call
-        let ws_signer = Arc::new(SignerMiddleware::new(
-            self.provider.clone(),
-            self.wallet.clone().with_chain_id(self.config.chain_id),
-        ));
+        // Build contract call (signer and private provider are pre-built)
+        let submit_start = Instant::now();
+        let ws_signer = self.wallets.active().signer();
         let contract = IArbExecutor::new(arb_address, ws_signer.clone());
         let call = contract.execute_arb(
-            token0, token1, router_buy, router_sell,
-            fee_buy.into(), fee_sell.into(), trade_size, min_profit_raw,
+            args.token_0, args.token_1, args.router_buy, args.router_sell,
+            args.fee_buy, args.fee_sell, args.amount_in, args.min_profit,
         );
 
         // Initialize nonce if needed
-        if !self.nonce_initialized {
-            let nonce = self.provider.get_transaction_count(
-                self.wallet.address(), Some(BlockNumber::Pending.into())
-            ).await?;
-            self.cached_nonce.store(nonce.as_u64(), Ordering::SeqCst);
-            self.nonce_initialized = true;
-            info!("Nonce initialized: {}", nonce);
-        }
-        let current_nonce = U256::from(self.cached_nonce.load(Ordering::SeqCst));
+        let current_nonce = self.ensure_nonce().await?;
 
         // Build tx manually — skip estimateGas for speed
-        let send_result: Result<TxHash, String> = if let Some(ref tx_client) = self.tx_client {
+        let mut private_send_failed = false;
+        let mut journaled: Option<TxHash> = None;
+        // Some(true) = relay took the bundle, Some(false) = refused and raw-sent
+        let mut bundled: Option<bool> = None;
+        let bundle = self.bundle.clone();
+        let send_result: Result<TxHash, String> = if let Some(err_msg) = self.injected_send_error("eth_sendRawTransaction") {
+            Err(format!("Mempool tx send failed: {}", err_msg))
+        } else if let (Some(bundle), Some(trigger)) = (bundle, trigger) {
+            // Bundle path: same signed tx, pinned behind the trigger by the relay
+            info!("📦 MEMPOOL: bundle behind {:?} (priority={:.0}gwei, nonce={}, gas={}K)",
+                  trigger.hash, priority_fee.as_u128() as f64 / 1e9, current_nonce, gas_limit.as_u64() / 1000);
+            let mut tx = call.tx.clone();
+            tx.set_nonce(current_nonce);
+            tx.set_gas(gas_limit);
+            if let Some(inner) = tx.as_eip1559_mut() {
+                inner.chain_id = Some(self.config.chain_id.into());
+                inner.max_fee_per_gas = Some(max_fee);
+                inner.max_priority_fee_per_gas = Some(priority_fee);
+            }
+            match self.sign_and_journal(&ws_signer, &tx, opportunity, current_nonce, "mempool").await {
+                Err(e) => Err(format!("Mempool tx {}", e)),
+                Ok((raw_tx, hash)) => {
+                    journaled = Some(hash);
+                    let inputs = BundleInputs {
+                        trigger,
+                        backrun_raw: &raw_tx,
+                        backrun_hash: hash,
+                        target_block: self.current_block.map_or(0, |b| b + 1),
+                    };
+                    let sent = match self.timed(Stage::Send, bundle.submit(&inputs)).await {
+                        Ok(_) => {
+                            bundled = Some(true);
+                            Ok(hash)
+                        }
+                        Err(e) => {
+                            warn!("📦 Bundle refused ({}) — falling back to raw send", e);
+                            bundled = Some(false);
+                            match self.connections.private_client() {
+                                Some(tx_client) => match self.timed(Stage::Send, tx_client.send_raw_transaction(raw_tx)).await {
+                                    Ok(pending) => Ok(pending.tx_hash()),
+                                    Err(e) => {
+                                        private_send_failed = true;
+                                        Err(format!("Mempool tx send failed (private): {}", e))
+                                    }
+                                },
+                                None => match self.timed(Stage::Send, ws_signer.provider().send_raw_transaction(raw_tx)).await {
+                                    Ok(pending) => Ok(pending.tx_hash()),
+                                    Err(e) => Err(format!("Mempool tx send failed: {}", e)),
+                                },
+                            }
+                        }
+                    };
+                    if sent.is_ok() {
+                        self.wallets.active_mut().advance_nonce();
+                    }
+                    sent
+                }
+            }
+        } else if let Some(tx_client) = self.connections.private_client() {
             // Private RPC path: pre-set all fields, sign, send raw
             info!("📡 MEMPOOL: private RPC (priority={:.0}gwei, nonce={}, gas={}K)",
                   priority_fee.as_u128() as f64 / 1e9, current_nonce, gas_limit.as_u64() / 1000);
@@ -855,16 +2600,19 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                 inner.max_priority_fee_per_gas = Some(priority_fee);
             });
             // Sign directly — no fill_transaction (which would call estimateGas)
-            match ws_signer.signer().sign_transaction(&tx).await {
-                Err(e) => Err(format!("Mempool tx sign failed: {}", e)),
-                Ok(signature) => {
-                    let raw_tx = tx.rlp_signed(&signature);
-                    match tx_client.send_raw_transaction(raw_tx).await {
+            match self.sign_and_journal(&ws_signer, &tx, opportunity, current_nonce, "mempool").await {
+                Err(e) => Err(format!("Mempool tx {}", e)),
+                Ok((raw_tx, hash)) => {
+                    journaled = Some(hash);
+                    match self.timed(Stage::Send, tx_client.send_raw_transaction(raw_tx)).await {
                         Ok(pending) => {
-                            self.cached_nonce.fetch_add(1, Ordering::SeqCst);
+                            self.wallets.active_mut().advance_nonce();
                             Ok(pending.tx_hash())
                         }
-                        Err(e) => Err(format!("Mempool tx send failed (private): {}", e))
+                        Err(e) => {
+                            private_send_failed = true;
+                            Err(format!("Mempool tx send failed (private): {}", e))
+                        }
                     }
                 }
             }
@@ -880,13 +2628,13 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
                 inner.max_fee_per_gas = Some(max_fee);
                 inner.max_priority_fee_per_gas = Some(priority_fee);
             });
-            match ws_signer.signer().sign_transaction(&tx).await {
-                Err(e) => Err(format!("Mempool tx sign failed: {}", e)),
-                Ok(signature) => {
-                    let raw_tx = tx.rlp_signed(&signature);
-                    match ws_signer.provider().send_raw_transaction(raw_tx).await {
+            match self.sign_and_journal(&ws_signer, &tx, opportunity, current_nonce, "mempool").await {
+                Err(e) => Err(format!("Mempool tx {}", e)),
+                Ok((raw_tx, hash)) => {
+                    journaled = Some(hash);
+                    match self.timed(Stage::Send, ws_signer.provider().send_raw_transaction(raw_tx)).await {
                         Ok(pending) => {
-                            self.cached_nonce.fetch_add(1, Ordering::SeqCst);
+                            self.wallets.active_mut().advance_nonce();
                             Ok(pending.tx_hash())
                         }
                         Err(e) => Err(format!("Mempool tx send failed: {}", e))
@@ -895,98 +2643,110 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             }
         };
 
+        if private_send_failed {
+            self.connections.on_private_error();
+        }
+
         let tx_hash = match send_result {
             Ok(hash) => hash,
             Err(err_msg) => {
+                self.on_send_error(&err_msg);
+                self.clear_inflight(journaled);
                 if err_msg.contains("InsufficientProfit") || err_msg.contains("execution reverted") {
                     info!("MEMPOOL: atomic revert (expected — pool conditions changed)");
                 } else {
                     error!("MEMPOOL: {}", err_msg);
                 }
+                self.note_submission(None, "mempool", priority_fee, None);
+                return Ok(TradeResult {
+                    opportunity: pair_symbol.clone(),
+                    opportunity_id: opportunity.id,
+                    tx_hash: None, block_number: None, success: false,
+                    profit_usd: 0.0, gas_cost_usd: 0.0, gas_used_native: 0.0,
+                    net_profit_usd: 0.0,
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    error: Some(err_msg),
+                    amount_in: Some(trade_size), amount_out: None,
+                });
+            }
+        };
+
+        info!(
+            "⚡ MEMPOOL tx submitted: {:?} ({}ms from signal, submit path {}ms)",
+            tx_hash, start_time.elapsed().as_millis(), submit_start.elapsed().as_millis()
+        );
+        if bundled == Some(false) {
+            self.note_bundle(tx_hash, BundleOutcome::Fallback);
+        }
+
+        // Wait for receipt (identical to execute_atomic)
+        let receipt = match self.await_receipt(tx_hash, &RetrySite::named("receipt.mempool")).await? {
+            Some(r) => r,
+            None => {
+                error!("MEMPOOL: receipt timeout (30s) for {:?}", tx_hash);
+                self.note_submission(Some(tx_hash), "mempool", priority_fee, None);
+                if bundled == Some(true) {
+                    // A dropped bundle never used its nonce — re-read it before the next tx
+                    self.note_bundle(tx_hash, BundleOutcome::NotIncluded);
+                    self.wallets.active_mut().reset_nonce();
+                }
                 return Ok(TradeResult {
                     opportunity: pair_symbol.clone(),
-                    tx_hash: None, block_number: None, success: false,
+                    opportunity_id: opportunity.id,
+                    tx_hash: Some(format!("{:?}", tx_hash)),
+                    block_number: None, success: false,
                     profit_usd: 0.0, gas_cost_usd: 0.0, gas_used_native: 0.0,
                     net_profit_usd: 0.0,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
-                    error: Some(err_msg),
-                    amount_in: Some(trade_size.to_string()), amount_out: None,
+                    error: Some("Receipt timeout — tx submitted but unconfirmed".to_string()),
+                    amount_in: Some(trade_size), amount_out: None,
                 });
             }
         };
 
-        info!("⚡ MEMPOOL tx submitted: {:?} ({}ms from signal)", tx_hash, start_time.elapsed().as_millis());
-
-        // Wait for receipt (identical to execute_atomic)
-        let receipt_deadline = Instant::now() + Duration::from_secs(30);
-        let receipt = loop {
-            match self.provider.get_transaction_receipt(tx_hash).await {
-                Ok(Some(r)) => break r,
-                Ok(None) => {
-                    if Instant::now() > receipt_deadline {
-                        error!("MEMPOOL: receipt timeout (30s) for {:?}", tx_hash);
-                        return Ok(TradeResult {
-                            opportunity: pair_symbol.clone(),
-                            tx_hash: Some(format!("{:?}", tx_hash)),
-                            block_number: None, success: false,
-                            profit_usd: 0.0, gas_cost_usd: 0.0, gas_used_native: 0.0,
-                            net_profit_usd: 0.0,
-                            execution_time_ms: start_time.elapsed().as_millis() as u64,
-                            error: Some("Receipt timeout — tx submitted but unconfirmed".to_string()),
-                            amount_in: Some(trade_size.to_string()), amount_out: None,
-                        });
-                    }
-                    tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
-                }
-                Err(e) => return Err(anyhow!("Failed to fetch receipt for {:?}: {}", tx_hash, e)),
-            }
-        };
-
+        self.note_submission(Some(tx_hash), "mempool", priority_fee, Some(&receipt));
+        if let (Some(true), Some(trigger)) = (bundled, trigger) {
+            let outcome = self.bundle_inclusion(&receipt, trigger.hash).await;
+            self.note_bundle(tx_hash, outcome);
+        }
         let block_number = receipt.block_number.map(|bn| bn.as_u64()).unwrap_or(0);
 
         if receipt.status != Some(U64::from(1)) {
             warn!("MEMPOOL: tx reverted on-chain (gas burned, no capital loss)");
+            self.clear_inflight(journaled);
+            let gas_used_native = receipt_gas_native(&receipt, max_fee);
+            let gas_cost_usd = gas_used_native * self.native_price.get();
+            self.log_fee_record_if_enabled(
+                opportunity, &format!("{:?}", tx_hash), block_number, gas_used_native, "Mempool tx reverted on-chain",
+            );
             return Ok(TradeResult {
                 opportunity: pair_symbol.clone(),
+                opportunity_id: opportunity.id,
                 tx_hash: Some(format!("{:?}", tx_hash)),
                 block_number: Some(block_number), success: false,
-                profit_usd: 0.0, gas_cost_usd: 0.0, gas_used_native: 0.0,
-                net_profit_usd: 0.0,
+                profit_usd: 0.0, gas_cost_usd, gas_used_native,
+                net_profit_usd: -gas_cost_usd,
                 execution_time_ms: start_time.elapsed().as_millis() as u64,
                 error: Some("Mempool tx reverted on-chain".to_string()),
-                amount_in: Some(trade_size.to_string()), amount_out: None,
+                amount_in: Some(trade_size), amount_out: None,
             });
         }
 
-        // Parse profit from ArbExecuted event (identical to execute_atomic)
-        let mut profit_raw = U256::zero();
-        let mut amount_out = trade_size;
-        let arb_executed_topic: H256 = ethers::utils::keccak256(
-            b"ArbExecuted(address,address,uint256,uint256,uint256,address,address)"
-        ).into();
+        self.apply_own_receipt(&receipt);
 
-        for log in &receipt.logs {
-            if log.address == arb_address && !log.topics.is_empty() && log.topics[0] == arb_executed_topic {
-                if log.data.len() >= 96 {
-                    amount_out = U256::from_big_endian(&log.data[32..64]);
-                    profit_raw = U256::from_big_endian(&log.data[64..96]);
-                    debug!("MEMPOOL: ArbExecuted amountOut={}, profit={}", amount_out, profit_raw);
-                }
-                break;
-            }
+        // Parse profit from ArbExecuted event (identical to execute_atomic)
+        let parsed = parse_arb_executed(&receipt.logs, arb_address);
+        let (amount_out, profit) = parsed
+            .map(|(out, profit)| (opportunity.quote_amount(out), opportunity.quote_amount(profit)))
+            .unwrap_or((trade_size, trade_size.with_raw(U256::zero())));
+        debug!("MEMPOOL: ArbExecuted amountOut={}, profit={}", amount_out, profit);
+        if let (Some(quoted), Some(_)) = (opportunity.quoted_legs, parsed) {
+            self.record_slippage(opportunity, SlippageLeg::Sell, quoted.sell_out, amount_out.raw());
         }
 
-        let quote_decimals = if opportunity.quote_token_is_token0 {
-            opportunity.token0_decimals
-        } else {
-            opportunity.token1_decimals
-        };
-        let profit_usd = profit_raw.low_u128() as f64 / 10_f64.powi(quote_decimals as i32);
-        let gas_used = receipt.gas_used.unwrap_or(U256::from(400_000u64));
-        let effective_gas_price = receipt.effective_gas_price.unwrap_or(max_fee);
-        let gas_cost_wei = gas_used * effective_gas_price;
-        let gas_used_native = gas_cost_wei.low_u128() as f64 / 1e18;
-        let gas_cost_usd = gas_used_native * self.config.native_token_price_usd;
+        let profit_usd = opportunity.quote_to_usd(profit);
+        let gas_used_native = receipt_gas_native(&receipt, max_fee);
+        let gas_cost_usd = gas_used_native * self.native_price.get();
         let net_profit_usd = profit_usd - gas_cost_usd;
 
         let success = net_profit_usd > 0.0;
@@ -1012,9 +2772,12 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             amount_out,
             gas_used_native,
         );
+        self.track_landed(opportunity, tx_hash, block_number, receipt.block_hash, net_profit_usd);
+        self.clear_inflight(journaled);
 
         Ok(TradeResult {
             opportunity: pair_symbol.clone(),
+            opportunity_id: opportunity.id,
             tx_hash: Some(format!("{:?}", tx_hash)),
             block_number: Some(block_number),
             success,
@@ -1024,8 +2787,8 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             net_profit_usd,
             execution_time_ms: start_time.elapsed().as_millis() as u64,
             error: None,
-            amount_in: Some(trade_size.to_string()),
-            amount_out: Some(amount_out.to_string()),
+            amount_in: Some(trade_size),
+            amount_out: Some(amount_out.raw().to_string()),
         })
     }
 
@@ -1063,16 +2826,8 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         let match_trigger = trigger_priority * U256::from(105u64) / U256::from(100u64);
 
         // 2. Profit cap: max gas spend = est_profit * gas_profit_cap / native_token_price / gas_limit
-        // Convert: profit_usd * cap → max_gas_cost_usd → max_gas_cost_native → max_gas_per_unit
-        let native_price = self.config.native_token_price_usd;
-        let max_gas_budget_usd = est_profit_usd * gas_profit_cap;
-        let max_gas_budget_matic = max_gas_budget_usd / native_price;
-        let max_gas_budget_wei = (max_gas_budget_matic * 1e18) as u128;
-        let profit_cap = if gas_limit > 0 {
-            U256::from(max_gas_budget_wei) / U256::from(gas_limit)
-        } else {
-            gwei * min_priority_gwei
-        };
+        let profit_cap = gas_oracle::profit_cap(est_profit_usd, gas_profit_cap, self.native_price.get(), gas_limit)
+            .unwrap_or(gwei * min_priority_gwei);
 
         // 3. Floor
         let floor = gwei * min_priority_gwei;
@@ -1097,43 +2852,723 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         (priority_fee, max_fee)
     }
 
-    /// Log a tax record if tax logging is enabled
+    /// Log a tax record if tax logging is enabled.
+    ///
+    /// The record is handed to the tax writer thread, which retries it until
+    /// it is on disk — it is never dropped. If the writer's queue is full the
+    /// record waits here (behind any older refused ones). Strict: a failing or
+    /// stalled writer halts live submissions until `retry_pending_tax_records`
+    /// sees it recover.
     fn log_tax_record_if_enabled(
         &mut self,
         opportunity: &ArbitrageOpportunity,
         tx_hash: &str,
         block_number: u64,
-        amount_in: U256,
-        amount_out: U256,
+        amount_in: QuoteAmount,
+        amount_out: QuoteAmount,
+        gas_native: f64,
+    ) {
+        if self.tax_writer.is_none() {
+            return; // Tax logging not enabled
+        }
+        let pending = PendingTaxRecord::new(
+            opportunity, tx_hash, block_number, amount_in.raw(), amount_out.raw(), gas_native,
+        )
+        .with_wallet(self.wallet_address_string());
+        if let Some(evicted) = self.pending_tax.push(pending) {
+            error!("🧾 Tax retry queue full — record DROPPED, reconstruct by hand: {}", evicted.describe());
+        }
+        self.retry_pending_tax_records();
+    }
+
+    /// Log a gas-only Fee record for a live tx that confirmed without trading
+    /// (status 0): the gas it burned is deductible. Dry runs never send one.
+    fn log_fee_record_if_enabled(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        tx_hash: &str,
+        block_number: u64,
         gas_native: f64,
+        reason: &str,
     ) {
-        // Get wallet address first (immutable borrow)
-        let wallet_address = format!("{:?}", self.wallet.address());
+        if self.tax_writer.is_none() || self.dry_run {
+            return;
+        }
+        let note = format!(
+            "{}: {} {}→{}", reason, opportunity.pair.symbol, opportunity.buy_dex, opportunity.sell_dex,
+        );
+        let pending = PendingTaxRecord::fee(note, tx_hash, block_number, gas_native)
+            .with_wallet(self.wallet_address_string());
+        if let Some(evicted) = self.pending_tax.push(pending) {
+            error!("🧾 Tax retry queue full — record DROPPED, reconstruct by hand: {}", evicted.describe());
+        }
+        self.retry_pending_tax_records();
+    }
+
+    /// Hand refused tax records to the writer (oldest first, stopping when it
+    /// is still full), then check its health. Strict: a failing/stalled writer
+    /// or refused records halt live submissions; the halt lifts once neither
+    /// holds. Returns the number of tax writes not yet on disk.
+    pub fn retry_pending_tax_records(&mut self) -> usize {
+        let provisional = self.finality.is_enabled();
+        let writer = match self.tax_writer {
+            Some(ref w) => w,
+            None => return self.pending_tax.len(),
+        };
+        while let Some(pending) = self.pending_tax.pop_front() {
+            let job = TaxWrite::Record { pending: Box::new(pending), provisional };
+            if let Err(refused) = writer.send(job) {
+                if let TaxWrite::Record { pending, .. } = refused.into_inner() {
+                    self.pending_tax.requeue_front(*pending);
+                }
+                break;
+            }
+        }
+
+        let blocked = writer.blocked_reason().or_else(|| {
+            self.pending_tax.front().map(|p| format!("tax writer queue full, record for tx {} waiting", p.tx_hash))
+        });
+        let unwritten = self.pending_tax_records();
+        match blocked {
+            Some(reason) if self.tax_strict => self.halt_submissions(format!("{} unwritten tax write(s): {}", unwritten, reason)),
+            Some(reason) => debug!("Tax writes behind ({} unwritten): {}", unwritten, reason),
+            None => {
+                if let Some(reason) = self.submission_halt.take() {
+                    let msg = format!("Tax records written — live submissions resumed (was: {})", reason);
+                    info!("🧾 {}", msg);
+                    if let Some(ref alerts) = self.alerts {
+                        alerts.dispatch(AlertSeverity::Info, &msg);
+                    }
+                }
+            }
+        }
+        unwritten
+    }
+
+    /// Shutdown: one last hand-over, wait (bounded) for the writer to drain,
+    /// then every record still unwritten is logged in full so it can be
+    /// entered by hand. Returns how many were lost.
+    pub fn flush_pending_tax_records(&mut self) -> usize {
+        self.retry_pending_tax_records();
+        if self.flush_tax_writes(TAX_SHUTDOWN_FLUSH_TIMEOUT) && !self.pending_tax.is_empty() {
+            // The drain made room for the records it had refused
+            self.retry_pending_tax_records();
+            self.flush_tax_writes(TAX_SHUTDOWN_FLUSH_TIMEOUT);
+        }
+        let lost: Vec<PendingTaxRecord> = self.pending_tax.drain().collect();
+        for pending in &lost {
+            error!("🧾 UNWRITTEN tax record at shutdown: {}", pending.describe());
+        }
+        let in_writer = self.tax_writer.as_ref().map_or(0, |w| w.stats().pending());
+        if in_writer > 0 {
+            error!("🧾 {} tax write(s) still in the writer at shutdown — see its last error above", in_writer);
+        }
+        lost.len() + in_writer
+    }
+
+    /// Send one finality job to the tax writer (no writer = nothing to do).
+    fn send_tax_write(&self, job: TaxWrite) -> std::result::Result<(), String> {
+        match self.tax_writer {
+            Some(ref writer) => writer.send(job).map_err(|e| e.to_string()),
+            None => Ok(()),
+        }
+    }
+
+    /// Writer thread: run one tax job. A record is built (and matched to tax
+    /// lots) once; a record whose CSV row is already on disk only retries the
+    /// JSON backup. Only I/O errors are retried: a record that cannot be built
+    /// is dead-lettered (see write_tax_record).
+    fn run_tax_write(
+        logger: &mut TaxLogger,
+        builder: &TaxRecordBuilder,
+        ledger: &mut LotLedger,
+        job: &mut TaxWrite,
+    ) -> Result<()> {
+        match job {
+            TaxWrite::Record { pending, provisional } => {
+                pending.attempts += 1;
+                Self::write_tax_record(logger, builder, ledger, pending, *provisional).map_err(|e| {
+                    pending.last_error = e.to_string();
+                    warn!("🧾 Tax record for tx {} not written (attempt {}): {}", pending.tx_hash, pending.attempts, e);
+                    e.context(format!("tax record for tx {}", pending.tx_hash))
+                })
+            }
+            TaxWrite::Final { tx, year } => {
+                logger.set_finality(tx, *year, RecordFinality::Final)?;
+                Ok(())
+            }
+            TaxWrite::Supersede { tx, year, reason } => {
+                let corrections = logger.supersede(tx, *year, reason)?;
+                if corrections.is_empty() {
+                    warn!("Finality: no tax record for {} to reverse", tx);
+                }
+                for correction in &corrections {
+                    // Unwind the reversed record's lots
+                    ledger.apply(correction);
+                    info!(
+                        "🧾 Tax correcting entry {} supersedes {}", correction.trade_id,
+                        correction.supersedes.as_deref().unwrap_or_default()
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Build (once), split per tax lot (once) and write the record(s).
+    ///
+    /// Building reads only prices and token metadata, so a failure (e.g. a
+    /// symbol with no known decimals) would fail every retry too and hold the
+    /// records queued behind it. It is logged in full once and the job ends.
+    fn write_tax_record(
+        logger: &mut TaxLogger,
+        builder: &TaxRecordBuilder,
+        ledger: &mut LotLedger,
+        pending: &mut PendingTaxRecord,
+        provisional: bool,
+    ) -> Result<()> {
+        if pending.records.is_empty() {
+            let record = match Self::build_pending_tax_record(builder, pending, provisional) {
+                Ok(record) => record,
+                Err(e) => {
+                    pending.last_error = e.to_string();
+                    error!("🧾 Tax record cannot be built — DEAD-LETTERED, reconstruct by hand: {}", pending.describe());
+                    return Ok(());
+                }
+            };
+            pending.records = ledger.apply(&record);
+        }
+
+        // Log to CSV and JSON, resuming after the last record fully written
+        while let Some(record) = pending.records.get(pending.written) {
+            logger.log_resumable(record, &mut pending.csv_written)?;
+            pending.written += 1;
+            pending.csv_written = false;
+            info!("📋 Tax record logged: {} -> {} | ${:.2} gain (lot {})",
+                  record.asset_sent, record.asset_received, record.capital_gain_loss,
+                  record.lot_id.as_deref().unwrap_or("spot"));
+        }
+        Ok(())
+    }
+
+    /// The record a pending tax write describes (arbitrage, rebalance or fee)
+    fn build_pending_tax_record(
+        builder: &TaxRecordBuilder,
+        pending: &PendingTaxRecord,
+        provisional: bool,
+    ) -> Result<TaxRecord> {
+        let wallet_address = pending.wallet.as_str();
+        Ok(match pending.source {
+            TaxSource::Arbitrage(ref opportunity) => {
+                let record = Self::build_tax_record(
+                    opportunity,
+                    &pending.tx_hash,
+                    pending.block_number,
+                    pending.amount_in,
+                    pending.amount_out,
+                    pending.gas_native,
+                    wallet_address,
+                    builder,
+                )?;
+                if provisional { record.provisional() } else { record }
+            }
+            // Rebalances aren't tracked by finality: final as written
+            TaxSource::Rebalance(ref swap) => Self::build_rebalance_tax_record(
+                swap,
+                &pending.tx_hash,
+                pending.block_number,
+                pending.amount_in,
+                pending.amount_out,
+                pending.gas_native,
+                wallet_address,
+                builder,
+            )?,
+            // Reverted txs aren't tracked by finality either
+            TaxSource::Fee { ref note } => builder.build_fee_record(
+                Decimal::from_str(&pending.gas_native.to_string()).unwrap_or(Decimal::ZERO),
+                pending.tx_hash.clone(),
+                pending.block_number,
+                wallet_address.to_string(),
+                note,
+            )?,
+            // An own-address sweep: a Transfer, not tracked by finality
+            TaxSource::Rescue { ref symbol, decimals, price_usd } => builder.build_transfer_record(
+                symbol,
+                Decimal::try_from_i128_with_scale(pending.amount_in.low_u128() as i128, decimals as u32)
+                    .unwrap_or(Decimal::ZERO),
+                decimals,
+                price_usd.and_then(|p| Decimal::try_from(p).ok()),
+                Decimal::from_str(&pending.gas_native.to_string()).unwrap_or(Decimal::ZERO),
+                pending.tx_hash.clone(),
+                pending.block_number,
+                wallet_address.to_string(),
+                "ArbExecutor rescue",
+            )?,
+        })
+    }
+
+    /// Strict mode: stop live submissions (first failure alerts; repeats only debug-log).
+    fn halt_submissions(&mut self, reason: String) {
+        if self.submission_halt.is_some() {
+            debug!("Tax logging still failing: {}", reason);
+            self.submission_halt = Some(reason);
+            return;
+        }
+        let msg = format!("TAX_LOG_STRICT: live submissions HALTED — {}", reason);
+        error!("🧾 {}", msg);
+        if let Some(ref alerts) = self.alerts {
+            alerts.dispatch(AlertSeverity::Critical, &msg);
+        }
+        self.submission_halt = Some(reason);
+    }
+
+    /// Quote tokens to read at startup (and log); see balance::quote_tokens
+    pub fn track_balances(&mut self, tokens: Vec<QuoteToken>) {
+        for wallet in self.wallets.slots_mut() {
+            wallet.balances_mut().track(tokens.clone());
+        }
+    }
+
+    /// Read every wallet's balance of every tracked quote token (startup). A
+    /// failed read is logged; that token is read again at its first dispatch.
+    pub async fn refresh_balances(&mut self) {
+        let several = self.wallets.len() > 1;
+        for wallet in self.wallets.slots_mut() {
+            let wallet_address = wallet.address();
+            for quote in wallet.balances().tokens().to_vec() {
+                let erc20 = IERC20::new(quote.address, self.provider.clone());
+                match rpc_timeout("quote balanceOf", erc20.balance_of(wallet_address).call()).await {
+                    Ok(raw) => {
+                        let balance = quote.amount(raw);
+                        if several {
+                            info!("💰 Wallet balance: {} ({:?})", balance, wallet_address);
+                        } else {
+                            info!("💰 Wallet balance: {}", balance);
+                        }
+                        wallet.balances_mut().observe(balance);
+                    }
+                    Err(e) => warn!("Wallet balance read failed for {}: {}", quote, e),
+                }
+            }
+        }
+    }
+
+    /// Last read balance of a quote token (raw) in the active wallet, None until read
+    pub fn available(&self, quote_token: Address) -> Option<U256> {
+        self.wallets.active().balances().available(quote_token)
+    }
+
+    /// Balance clamp at dispatch (before exposure pacing). Ok(None) = disabled
+    /// or the trade fits, Ok(Some) = clamped opportunity, Err = rejected result.
+    /// A failed balance read dispatches unclamped (preflight checks the balance).
+    async fn clamp_to_balance(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        min_profit_usd: f64,
+        start_time: Instant,
+    ) -> std::result::Result<Option<ArbitrageOpportunity>, TradeResult> {
+        if !self.wallets.active().balances().is_enabled() {
+            return Ok(None);
+        }
+        let quote = opportunity.quote();
+        let balance = match self.wallets.active().balances().fresh_balance(quote) {
+            Some(balance) => balance,
+            None => {
+                let erc20 = IERC20::new(quote.address, self.provider.clone());
+                match rpc_timeout("quote balanceOf", erc20.balance_of(self.wallets.active().address()).call()).await {
+                    Ok(raw) => {
+                        let balance = quote.amount(raw);
+                        let wallet = self.wallets.active_mut();
+                        wallet.balances_mut().observe(balance);
+                        wallet.exposure_mut().observe_balance(balance);
+                        balance
+                    }
+                    Err(e) => {
+                        warn!("Balance clamp: balance read failed ({}) — dispatching unclamped", e);
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+
+        let mut clamped = opportunity.clone();
+        let balances = self.wallets.active_mut().balances_mut();
+        let outcome = balances.clamp(&mut clamped, balance, min_profit_usd);
+        if !matches!(outcome, Ok(None)) && balances.should_warn_low(quote.address, Instant::now()) {
+            warn!(
+                "💰 Low wallet balance: {} — trades in {} are clamped or skipped (warning repeats hourly)",
+                balance, quote.symbol
+            );
+        }
+        match outcome {
+            Ok(None) => Ok(None),
+            Ok(Some(requested)) => {
+                info!(
+                    "💰 Balance clamp {}: size {} → {} (balance {})",
+                    opportunity.pair.symbol, requested, clamped.size_display(), balance
+                );
+                Ok(Some(clamped))
+            }
+            Err(rejection) => {
+                info!("💰 {} {} (balance {})", opportunity.pair.symbol, rejection, balance);
+                Err(TradeResult {
+                    opportunity: opportunity.pair.symbol.clone(),
+                    opportunity_id: opportunity.id,
+                    tx_hash: None,
+                    block_number: None,
+                    success: false,
+                    profit_usd: 0.0,
+                    gas_cost_usd: 0.0,
+                    gas_used_native: 0.0,
+                    net_profit_usd: 0.0,
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    error: Some(rejection.to_string()),
+                    amount_in: Some(opportunity.trade_size),
+                    amount_out: None,
+                })
+            }
+        }
+    }
+
+    /// Exposure pacing at dispatch. Ok(None) = pacing disabled, Ok(Some) = the
+    /// opportunity to dispatch (possibly scaled down), Err = rejected result.
+    /// Without a cached balance one balanceOf is read; if that fails the trade
+    /// is dispatched unpaced (preflight still checks the balance covers it).
+    async fn pace_exposure(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        min_profit_usd: f64,
+        start_time: Instant,
+    ) -> std::result::Result<Option<ArbitrageOpportunity>, TradeResult> {
+        if !self.wallets.active().exposure().is_enabled() {
+            return Ok(None);
+        }
+        let quote = opportunity.quote();
+        let balance = match self.quote_balance(opportunity).await {
+            Ok(balance) => balance,
+            Err(e) => {
+                warn!("Exposure pacing: balance read failed ({}) — dispatching unpaced", e);
+                return Ok(None);
+            }
+        };
+        // This wallet's own in-flight trades: each wallet spends its own balance
+        let (wallet, primary) = (self.wallets.active().address(), self.wallets.primary_address());
+        let committed = exposure::committed(&self.wallet_inflight_entries(wallet, primary), quote);
+
+        let mut paced = opportunity.clone();
+        match self.wallets.active().exposure().pace(&mut paced, balance, committed, min_profit_usd) {
+            Ok(decision) if decision.scaled() => {
+                info!(
+                    "⚖️ Exposure pacing {}: size {} → {} (balance {}, in flight {}, limits {:.0}%/{:.0}%)",
+                    opportunity.pair.symbol,
+                    opportunity.size_display(),
+                    paced.size_display(),
+                    balance,
+                    committed,
+                    decision.max_trade_fraction * 100.0,
+                    decision.max_inflight_fraction * 100.0
+                );
+                Ok(Some(paced))
+            }
+            Ok(_) => {
+                debug!(
+                    "Exposure pacing {}: {} within limits (balance {}, in flight {})",
+                    opportunity.pair.symbol,
+                    opportunity.size_display(),
+                    balance,
+                    committed
+                );
+                Ok(Some(paced))
+            }
+            Err(rejection) => {
+                info!(
+                    "⚖️ {} {} (balance {}, in flight {})",
+                    opportunity.pair.symbol,
+                    rejection,
+                    balance,
+                    committed
+                );
+                Err(TradeResult {
+                    opportunity: opportunity.pair.symbol.clone(),
+                    opportunity_id: opportunity.id,
+                    tx_hash: None,
+                    block_number: None,
+                    success: false,
+                    profit_usd: 0.0,
+                    gas_cost_usd: 0.0,
+                    gas_used_native: 0.0,
+                    net_profit_usd: 0.0,
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    error: Some(rejection.to_string()),
+                    amount_in: Some(opportunity.trade_size),
+                    amount_out: None,
+                })
+            }
+        }
+    }
+
+    /// Wallet balance of the trade's quote token: the exposure cache, else one balanceOf read
+    async fn quote_balance(&mut self, opportunity: &ArbitrageOpportunity) -> Result<QuoteAmount> {
+        let quote = opportunity.quote();
+        if let Some(balance) = self.wallets.active().exposure().cached_balance(quote) {
+            return Ok(balance);
+        }
+        let erc20 = IERC20::new(quote.address, self.provider.clone());
+        let raw = rpc_timeout("quote balanceOf", erc20.balance_of(self.wallets.active().address()).call()).await?;
+        let balance = quote.amount(raw);
+        self.wallets.active_mut().exposure_mut().observe_balance(balance);
+        Ok(balance)
+    }
+
+    /// PERMIT2_ADDRESS: check once that the deployed ArbExecutor has
+    /// executeArbWithPermit. A zero-amount eth_call must get as far as its
+    /// ZeroAmount() revert; anything else keeps every trade on the allowance path.
+    pub async fn probe_permit2(&mut self) {
+        let (permit2, arb_address) = match (self.permit2.as_ref(), self.config.arb_executor_address) {
+            (Some(state), Some(arb)) => (state.address, arb),
+            _ => return,
+        };
+        let contract = IArbExecutor::new(arb_address, self.wallets.active().signer());
+        let probe = TwoLegArb {
+            token_0: Address::zero(),
+            token_1: Address::zero(),
+            router_buy: Address::zero(),
+            router_sell: Address::zero(),
+            fee_buy: 0,
+            fee_sell: 0,
+            amount_in: U256::zero(),
+            min_profit: U256::zero(),
+        };
+        let call = contract.execute_arb_with_permit(permit2, probe, U256::zero(), U256::zero(), Bytes::new());
+        // Not rpc_timeout(): the revert data is needed, not just the message
+        let supported = match tokio::time::timeout(HOT_RPC_TIMEOUT, call.call()).await {
+            Ok(Ok(_)) => Some(false),
+            Ok(Err(e)) => match e.as_revert() {
+                Some(data) => Some(permit2::probe_supports_permit(Some(data))),
+                None => {
+                    warn!("🔏 Permit2 probe failed: {} — allowance path until restart", e);
+                    None
+                }
+            },
+            Err(_) => {
+                warn!("🔏 Permit2 probe timed out — allowance path until restart");
+                None
+            }
+        };
+        match supported {
+            Some(true) => info!("🔏 Permit2 {:?}: ArbExecutor has executeArbWithPermit — signature transfers enabled", permit2),
+            Some(false) => warn!("🔏 Permit2 {:?}: ArbExecutor has no executeArbWithPermit — allowance path", permit2),
+            None => {}
+        }
+        if let Some(state) = self.permit2.as_mut() {
+            state.contract_supported = supported;
+        }
+    }
+
+    /// Read the active wallet's allowance to Permit2 for `token` once per
+    /// session (Permit2 approvals are max approvals). No-op unless permits are usable.
+    async fn refresh_permit2_allowance(&mut self, token: Address) {
+        let wallet = self.wallets.active().address();
+        let permit2 = match self.permit2.as_ref() {
+            Some(state) if state.contract_supported == Some(true) && state.allowance(wallet, token).is_none() => {
+                state.address
+            }
+            _ => return,
+        };
+        let erc20 = IERC20::new(token, self.provider.clone());
+        match rpc_timeout("permit2 allowance", erc20.allowance(wallet, permit2).call()).await {
+            Ok(allowance) => {
+                if let Some(state) = self.permit2.as_mut() {
+                    state.set_allowance(wallet, token, allowance);
+                }
+            }
+            Err(e) => warn!("🔏 Permit2 allowance read failed for {:?}: {}", token, e),
+        }
+    }
+
+    /// Permit2 contract to sign this wallet-funded trade for, or why it keeps
+    /// the allowance path (executeArb)
+    fn permit_funding(&self, opportunity: &ArbitrageOpportunity) -> std::result::Result<Address, PermitFallback> {
+        let state = self.permit2.as_ref().ok_or(PermitFallback::NotConfigured)?;
+        permit2::permit_decision(
+            Some(state.address),
+            state.contract_supported,
+            state.allowance(self.wallets.active().address(), opportunity.quote_token()),
+            opportunity.trade_size.raw(),
+        )
+    }
+
+    /// Next unused Permit2 nonce of `owner`, reading nonceBitmap words as needed
+    async fn permit_nonce(&mut self, owner: Address) -> Result<U256> {
+        /// Used-up words skipped before giving up
+        const MAX_WORD_READS: usize = 4;
+        for _ in 0..MAX_WORD_READS {
+            let state = self.permit2.as_mut().ok_or_else(|| anyhow!("PERMIT2_ADDRESS not set"))?;
+            if let Some(nonce) = state.take_nonce(owner) {
+                return Ok(nonce);
+            }
+            let word = state.next_word(owner);
+            let permit2 = IPermit2::new(state.address, self.provider.clone());
+            let bitmap = rpc_timeout("permit2 nonceBitmap", permit2.nonce_bitmap(owner, word).call()).await?;
+            if let Some(state) = self.permit2.as_mut() {
+                state.load_nonces(owner, word, bitmap);
+            }
+        }
+        Err(anyhow!("no free Permit2 nonce in {} words", MAX_WORD_READS))
+    }
+
+    /// Sign the PermitTransferFrom of a wallet-funded executeArb: (Permit2,
+    /// nonce, deadline, signature). None = send executeArb on the allowance.
+    async fn sign_permit(
+        &mut self,
+        permit2: Option<Address>,
+        args: &ExecuteArbCall,
+        spender: Address,
+    ) -> Option<(Address, U256, U256, Bytes)> {
+        let permit2 = permit2?;
+        let signer = self.wallets.active().signer();
+        let nonce = match self.permit_nonce(signer.address()).await {
+            Ok(nonce) => nonce,
+            Err(e) => {
+                warn!("🔏 Permit2 nonce unavailable: {} — executeArb on the allowance", e);
+                return None;
+            }
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let permit = PermitTransferFrom {
+            token: args.token_0,
+            amount: args.amount_in,
+            spender,
+            nonce,
+            deadline: U256::from(now + permit2::PERMIT_DEADLINE_SECS),
+        };
+        match permit.sign(signer.signer(), self.config.chain_id, permit2) {
+            Ok(signature) => {
+                info!("  🔏 Permit2 transfer signed | nonce {} | deadline {}", permit.nonce, permit.deadline);
+                Some((permit2, permit.nonce, permit.deadline, signature))
+            }
+            Err(e) => {
+                warn!("🔏 Permit2 signing failed: {} — executeArb on the allowance", e);
+                None
+            }
+        }
+    }
 
-        // Check if tax logging is enabled
-        let (logger, builder) = match (&mut self.tax_logger, &self.tax_record_builder) {
-            (Some(l), Some(b)) => (l, b),
-            _ => return, // Tax logging not enabled
+    /// Flashloan funding for this trade: the executeArbFlash arguments when a
+    /// flash-eligible route exceeds the wallet balance and the call encodes,
+    /// None = wallet-funded (the reason is logged unless the route was never eligible)
+    async fn flash_funding(&mut self, opportunity: &ArbitrageOpportunity) -> Option<FlashCall> {
+        if let Some(flash) = self.flash_v2_funding(opportunity) {
+            return Some(flash);
+        }
+        if !self.config.flashloan_enabled || !opportunity.flash_eligible {
+            return None;
+        }
+        let balance = self.quote_balance(opportunity).await.ok();
+        let aave_pool = match flashloan::flash_decision(&self.config, opportunity, balance) {
+            Ok(pool) => pool,
+            Err(FlashFallback::Incomplete(missing)) => {
+                warn!("💸 Flashloan skipped for {}: {} not set — wallet-funded", opportunity.pair.symbol, missing);
+                return None;
+            }
+            Err(fallback) => {
+                debug!("💸 Flashloan skipped for {}: {} — wallet-funded", opportunity.pair.symbol, fallback);
+                return None;
+            }
         };
+        let args = execute_arb_flash_args(
+            opportunity,
+            aave_pool,
+            self.get_router_address(opportunity.buy_dex),
+            self.get_router_address(opportunity.sell_dex),
+            self.min_profit_usd(opportunity),
+        );
+        let arb_address = self.config.arb_executor_address?;
+        let encoded = IArbExecutor::new(arb_address, self.wallets.active().signer())
+            .execute_arb_flash(args.aave_pool, args.arb.clone())
+            .calldata();
+        if encoded.is_none() {
+            warn!("💸 executeArbFlash encoding failed for {} — wallet-funded with capped size", opportunity.pair.symbol);
+            return None;
+        }
+        Some(FlashCall::Aave(args))
+    }
 
-        if let Err(e) = Self::build_and_log_tax_record(
+    /// V2 flash swap for a route bought on a V2 pair (FLASH_V2_ENABLED), the
+    /// pair taken from buy_pool_address. None = the standard atomic path.
+    fn flash_v2_funding(&self, opportunity: &ArbitrageOpportunity) -> Option<FlashCall> {
+        if !self.config.flash_v2_enabled || !opportunity.buy_dex.is_v2() {
+            return None;
+        }
+        let Some(terms) = flashloan::flash_v2_decision(&self.config, opportunity) else {
+            debug!(
+                "💸 V2 flash swap skipped for {}: not eligible, or no pair address/reserves — standard atomic path",
+                opportunity.pair.symbol
+            );
+            return None;
+        };
+        let args = execute_arb_flash_v2_args(
             opportunity,
-            tx_hash,
-            block_number,
-            amount_in,
-            amount_out,
-            gas_native,
-            &wallet_address,
-            builder,
-            logger,
-        ) {
-            error!("Failed to log tax record: {}", e);
+            &terms,
+            self.get_router_address(opportunity.sell_dex),
+            self.min_profit_usd(opportunity),
+        );
+        Some(FlashCall::V2(args, terms))
+    }
+
+    /// Refusal when the opportunity is older than MAX_OPPORTUNITY_AGE_SECS
+    /// (a slow block loop or a queued mempool signal: its prices have moved on)
+    fn stale_result(&self, opportunity: &ArbitrageOpportunity, start_time: Instant) -> Option<TradeResult> {
+        let max_age = self.config.max_opportunity_age_secs;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let age = now.saturating_sub(opportunity.timestamp);
+        if max_age == 0 || age <= max_age {
+            return None;
         }
+        warn!("STALE: {} opportunity is {}s old (max {}s) — not submitting", opportunity.pair.symbol, age, max_age);
+        Some(TradeResult {
+            opportunity: opportunity.pair.symbol.clone(),
+            opportunity_id: opportunity.id,
+            tx_hash: None,
+            block_number: None,
+            success: false,
+            profit_usd: 0.0,
+            gas_cost_usd: 0.0,
+            gas_used_native: 0.0,
+            net_profit_usd: 0.0,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            error: Some(format!("{}opportunity {}s old (max {}s)", STALE_OPPORTUNITY_ERROR_PREFIX, age, max_age)),
+            amount_in: Some(opportunity.trade_size),
+            amount_out: None,
+        })
+    }
+
+    /// Failed result for a submission refused by the strict tax halt.
+    fn halted_result(&self, opportunity: &ArbitrageOpportunity, start_time: Instant) -> Option<TradeResult> {
+        let reason = self.submission_halt.as_ref()?;
+        Some(TradeResult {
+            opportunity: opportunity.pair.symbol.clone(),
+            opportunity_id: opportunity.id,
+            tx_hash: None,
+            block_number: None,
+            success: false,
+            profit_usd: 0.0,
+            gas_cost_usd: 0.0,
+            gas_used_native: 0.0,
+            net_profit_usd: 0.0,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            error: Some(format!("{}{}", TAX_HALT_ERROR_PREFIX, reason)),
+            amount_in: Some(opportunity.trade_size),
+            amount_out: None,
+        })
     }
 
-    /// Build and log a tax record for IRS compliance
+    /// Build a tax record for IRS compliance (fetches USD prices)
     #[allow(clippy::too_many_arguments)]
-    fn build_and_log_tax_record(
+    fn build_tax_record(
         opportunity: &ArbitrageOpportunity,
         tx_hash: &str,
         block_number: u64,
@@ -1142,22 +3577,13 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         gas_native: f64,
         wallet_address: &str,
         builder: &TaxRecordBuilder,
-        logger: &mut TaxLogger,
-    ) -> Result<()> {
-        // Parse token symbols from pair (e.g., "WETH/USDC" -> "WETH", "USDC")
-        let pair_parts: Vec<&str> = opportunity.pair.symbol.split('/').collect();
-        let (asset_sent, asset_received) = if pair_parts.len() == 2 {
-            (pair_parts[0], pair_parts[1])
-        } else {
-            ("UNKNOWN", "UNKNOWN")
-        };
-
-        // Convert U256 amounts to Decimal
-        // Note: This assumes 18 decimals - in production, use actual token decimals
-        let amount_sent = Decimal::from_str(&amount_in.to_string())
-            .unwrap_or(Decimal::ZERO);
-        let amount_received = Decimal::from_str(&amount_out.to_string())
-            .unwrap_or(Decimal::ZERO);
+    ) -> Result<TaxRecord> {
+        // Both legs start and end in the quote token: amount_in / amount_out are
+        // quote raw units, converted to human units at the quote token's decimals.
+        let (quote_symbol, amount_sent, amount_received) =
+            tax_amounts(opportunity, amount_in, amount_out);
+        let asset_sent = quote_symbol.as_str();
+        let asset_received = quote_symbol.as_str();
 
         // DEX fee (typically 0.30% for V2)
         let dex_fee_percent = if opportunity.buy_dex.is_v3() {
@@ -1192,14 +3618,125 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             pool_sell,
             Decimal::from_str(&opportunity.spread_percent.to_string()).unwrap_or(Decimal::ZERO),
             false, // is_paper_trade = false for real execution
+        )?
+        .with_trade_size_usd(opportunity.trade_size_usd)
+        .with_pool_snapshots(opportunity.pool_snapshots.clone());
+
+        // Audit: the exposure pacing decision behind this trade's size
+        let record = match opportunity.exposure_pacing {
+            Some(ref pacing) => record.with_note(&format!(
+                "exposure pacing: size {} of requested {} (balance {}, in flight {}, limits {:.0}%/{:.0}%, minProfit {})",
+                opportunity.quote_amount(pacing.allowed),
+                opportunity.quote_amount(pacing.requested),
+                opportunity.quote_amount(pacing.balance),
+                opportunity.quote_amount(pacing.committed),
+                pacing.max_trade_fraction * 100.0,
+                pacing.max_inflight_fraction * 100.0,
+                opportunity.quote_amount(pacing.min_profit_raw)
+            )),
+            None => record,
+        };
+
+        Ok(record)
+    }
+
+    /// Build the tax record of an inventory rebalance swap: a disposal of
+    /// `swap.symbol_in` for the quote token, through one pool
+    #[allow(clippy::too_many_arguments)]
+    fn build_rebalance_tax_record(
+        swap: &RebalanceSwap,
+        tx_hash: &str,
+        block_number: u64,
+        amount_in: U256,
+        amount_out: U256,
+        gas_native: f64,
+        wallet_address: &str,
+        builder: &TaxRecordBuilder,
+    ) -> Result<TaxRecord> {
+        let to_decimal = |raw: U256, decimals: u8| {
+            Decimal::try_from_i128_with_scale(raw.low_u128() as i128, decimals as u32).unwrap_or(Decimal::ZERO)
+        };
+        let pool = format!("{:?}", swap.pool);
+        let record = builder.build_arbitrage_record(
+            &swap.symbol_in,
+            to_decimal(amount_in, swap.decimals_in),
+            &swap.symbol_out,
+            to_decimal(amount_out, swap.decimals_out),
+            Decimal::from_str(&gas_native.to_string()).unwrap_or(Decimal::ZERO),
+            Decimal::from_str(&(swap.fee_percent / 100.0).to_string()).unwrap_or(Decimal::ZERO),
+            tx_hash.to_string(),
+            block_number,
+            wallet_address.to_string(),
+            swap.dex.to_string(),
+            swap.dex.to_string(),
+            pool.clone(),
+            pool,
+            Decimal::ZERO,
+            false,
         )?;
+        Ok(record.with_note("inventory rebalance"))
+    }
+
+    /// Run the preflight batch if configured.
+    ///
+    /// Ok(Some) = snapshot approves the trade, Ok(None) = no batcher or the
+    /// batch RPC itself failed (fall back to per-call checks), Err = rejected.
+    async fn run_preflight(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+    ) -> std::result::Result<Option<PreflightBundle>, String> {
+        let batcher = match self.preflight {
+            Some(ref b) => b,
+            None => return Ok(None),
+        };
+        // The batch quotes V2/V3 legs only; DODO routes use per-call checks
+        if opportunity.is_legacy_only() {
+            return Ok(None);
+        }
+
+        // Atomic: ArbExecutor pulls the quote token, so it must already be approved
+        // (to Permit2 instead when the trade goes out as a signature transfer).
+        // Legacy: the buy router is approved on demand by ensure_approval().
+        let (spender, require_allowance) = match self.config.arb_executor_address {
+            Some(arb) => (self.permit_funding(opportunity).unwrap_or(arb), true),
+            None => (self.get_router_address(opportunity.buy_dex), false),
+        };
 
-        // Log to CSV and JSON
-        logger.log(&record)?;
-        info!("📋 Tax record logged: {} -> {} | ${:.2} gain",
-              asset_sent, asset_received, record.capital_gain_loss);
+        match self.faults.inject(FaultPoint::Quoter) {
+            Some(Fault::Revert) => {
+                return Err(PreflightRejection::BuyQuoteFailed(Fault::Revert.error_message("quoteExactInputSingle")).to_string());
+            }
+            Some(f) => {
+                warn!("Preflight batch failed: {} — falling back to per-call checks", f.error_message("preflight batch"));
+                return Ok(None);
+            }
+            None => {}
+        }
 
-        Ok(())
+        match rpc_timeout(
+            "preflight batch",
+            batcher.run(opportunity, self.wallets.active().address(), spender, require_allowance, self.current_block),
+        )
+        .await
+        {
+            Ok(Ok(bundle)) => {
+                let wallet = self.wallets.active_mut();
+                wallet.exposure_mut().observe_balance(bundle.quote_balance);
+                wallet.balances_mut().observe(bundle.quote_balance);
+                info!(
+                    "Preflight OK @ block {}: quoted_profit={} balance={}",
+                    bundle.block_number,
+                    opportunity.quote_delta_display(bundle.quoted_profit_raw),
+                    bundle.quote_balance
+                );
+                Ok(Some(bundle))
+            }
+            Ok(Err(rejection)) => Err(rejection.to_string()),
+            Err(e) => {
+                warn!("Preflight batch failed: {} — falling back to per-call checks", e);
+                Ok(None)
+            }
+        }
     }
 
     /// Simulate execution without actual trades (dry run)
@@ -1212,8 +3749,8 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
 
         info!("🔬 DRY RUN: Simulating arbitrage for {}", pair_symbol);
         info!(
-            "   Would buy {} on {:?} @ {:.6}",
-            opportunity.trade_size, opportunity.buy_dex, opportunity.buy_price
+            "   Would buy with {} on {:?} @ {:.6}",
+            opportunity.size_display(), opportunity.buy_dex, opportunity.buy_price
         );
         info!(
             "   Would sell on {:?} @ {:.6}",
@@ -1229,30 +3766,97 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
 
         Ok(TradeResult {
             opportunity: pair_symbol.clone(),
-            tx_hash: Some("DRY_RUN_NO_TX".to_string()),
+            opportunity_id: opportunity.id,
+            tx_hash: Some(DRY_RUN_TX_HASH.to_string()),
             block_number: Some(0),
             success: true,
-            profit_usd: opportunity.estimated_profit + self.config.native_token_price_usd, // Add back gas for simulation
-            gas_cost_usd: self.config.native_token_price_usd,
+            profit_usd: opportunity.estimated_profit + self.native_price.get(), // Add back gas for simulation
+            gas_cost_usd: self.native_price.get(),
             gas_used_native: 0.001,
             net_profit_usd: opportunity.estimated_profit,
             execution_time_ms: start_time.elapsed().as_millis() as u64,
             error: None,
-            amount_in: Some(opportunity.trade_size.to_string()),
+            amount_in: Some(opportunity.trade_size),
             amount_out: None, // Unknown in simulation
         })
     }
 
-    /// Execute a single swap on a DEX (routes to V2 or V3 based on DexType)
+    /// Attempt a triangular route (detector::scan_triangular_opportunities).
+    /// Dry run simulates it like simulate_execution. Live, it goes through
+    /// execute_atomic_multi() when ARB_EXECUTOR_MULTI is set; otherwise it is
+    /// rejected without sending — executeArb routes exactly two legs.
+    pub async fn execute_triangular(&mut self, opportunity: &TriangularOpportunity) -> Result<TradeResult> {
+        let start_time = Instant::now();
+        let quote = opportunity.quote();
+        let mut result = TradeResult {
+            opportunity: opportunity.route.clone(),
+            opportunity_id: 0,
+            tx_hash: None,
+            block_number: None,
+            success: false,
+            profit_usd: 0.0,
+            gas_cost_usd: 0.0,
+            gas_used_native: 0.0,
+            net_profit_usd: 0.0,
+            execution_time_ms: 0,
+            error: None,
+            amount_in: Some(opportunity.trade_size),
+            amount_out: None,
+        };
+
+        if !self.dry_run && self.config.arb_executor_multi {
+            return self.execute_atomic_multi(&opportunity.to_atomic_opportunity()).await;
+        }
+        if !self.dry_run {
+            result.error = Some("triangular routes not supported by ArbExecutor (two-leg contract)".to_string());
+            result.execution_time_ms = start_time.elapsed().as_millis() as u64;
+            return Ok(result);
+        }
+
+        info!("🔬 DRY RUN: Simulating triangular arbitrage {}", opportunity.route);
+        for (i, leg) in opportunity.legs.iter().enumerate() {
+            info!(
+                "   Leg {}: {:?} pool {:?} | {:?} → {:?} @ {:.6} ({:.2}% fee)",
+                i + 1, leg.dex, leg.pool_address, leg.token_in, leg.token_out, leg.rate, leg.fee_percent
+            );
+        }
+        info!(
+            "   Size {} ({}) | min profit {} | est. profit {} (spread: {:.2}%)",
+            format_usd(opportunity.trade_size_usd),
+            opportunity.trade_size,
+            quote.amount(opportunity.min_profit_raw),
+            format_usd(opportunity.estimated_profit),
+            opportunity.spread_percent
+        );
+
+        result.tx_hash = Some(DRY_RUN_TX_HASH.to_string());
+        result.block_number = Some(0);
+        result.success = true;
+        result.profit_usd = opportunity.estimated_profit;
+        result.net_profit_usd = opportunity.estimated_profit;
+        result.execution_time_ms = start_time.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+
+    /// Execute a single swap on a DEX (routes to V2, V3 or DODO based on DexType)
+    /// `pool` is required for DODO legs (the proxy routes by pool address).
     /// Returns (tx_hash, amount_out, block_number)
     async fn swap(
         &self,
         dex: DexType,
+        pool: Option<Address>,
         token_in: Address,
         token_out: Address,
         amount_in: U256,
         min_amount_out: U256,
     ) -> Result<(TxHash, U256, u64)> {
+        if let Some(err_msg) = self.injected_send_error("eth_sendTransaction") {
+            return Err(anyhow!("Send failed: {}", err_msg));
+        }
+        if dex.is_dodo() {
+            let pool = pool.ok_or_else(|| anyhow!("DODO swap requires the pool address"))?;
+            return self.swap_dodo(pool, token_in, token_out, amount_in, min_amount_out).await;
+        }
         if dex.is_v3() {
             return self.swap_v3(dex, token_in, token_out, amount_in, min_amount_out).await;
         }
@@ -1271,12 +3875,8 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
     ) -> Result<(TxHash, U256, u64)> {
         let router_address = self.get_router_address(dex);
 
-        // Create signer client
-        let client = SignerMiddleware::new(
-            self.provider.clone(),
-            self.wallet.clone().with_chain_id(self.config.chain_id),
-        );
-        let client = Arc::new(client);
+        // Shared signer client (built once)
+        let client = self.wallets.active().signer();
 
         let router = IUniswapV2Router02::new(router_address, client.clone());
 
@@ -1290,7 +3890,7 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             .as_secs()
             + 300;
 
-        let wallet_address = self.wallet.address();
+        let wallet_address = self.wallets.active().address();
 
         debug!(
             "V2 Swap: {} {} -> {} on {:?}",
@@ -1353,12 +3953,8 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
     ) -> Result<(TxHash, U256, u64)> {
         let router_address = self.get_router_address(dex);
 
-        // Create signer client
-        let client = SignerMiddleware::new(
-            self.provider.clone(),
-            self.wallet.clone().with_chain_id(self.config.chain_id),
-        );
-        let client = Arc::new(client);
+        // Shared signer client (built once)
+        let client = self.wallets.active().signer();
 
         // Set deadline (current time + 5 minutes)
         let deadline = SystemTime::now()
@@ -1367,7 +3963,7 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
             .as_secs()
             + 300;
 
-        let wallet_address = self.wallet.address();
+        let wallet_address = self.wallets.active().address();
 
         // Route to correct router and wait for receipt
         // Each branch handles the full send+confirm flow to avoid lifetime issues
@@ -1451,6 +4047,81 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         }
     }
 
+    /// Execute a DODO V2 swap (DODOV2Proxy02.dodoSwapV2TokenToToken)
+    /// Direction bit 0 per pool: 0 = sellBase, 1 = sellQuote — read from the
+    /// pool's _BASE_TOKEN_. The proxy pulls token_in through DODOApprove.
+    async fn swap_dodo(
+        &self,
+        pool: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        min_amount_out: U256,
+    ) -> Result<(TxHash, U256, u64)> {
+        let proxy_address = self
+            .config
+            .dodo_v2_proxy
+            .ok_or_else(|| anyhow!("DODO swap requires DODO_V2_PROXY"))?;
+
+        // Shared signer client (built once)
+        let client = self.wallets.active().signer();
+
+        let base = IDodoV2Pool::new(pool, client.clone())
+            .base_token()
+            .call()
+            .await
+            .map_err(|e| anyhow!("DODO _BASE_TOKEN_ failed for {:?}: {}", pool, e))?;
+        let directions = if token_in == base { U256::zero() } else { U256::one() };
+
+        // Set deadline (current time + 5 minutes)
+        let deadline = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 300;
+
+        let wallet_address = self.wallets.active().address();
+
+        debug!(
+            "DODO Swap: {} {} -> {} via pool {:?} ({})",
+            amount_in, token_in, token_out, pool,
+            if directions.is_zero() { "sellBase" } else { "sellQuote" }
+        );
+        debug!("  Min out: {}, Deadline: {}", min_amount_out, deadline);
+
+        let proxy = IDODOV2Proxy02::new(proxy_address, client.clone());
+        let tx = proxy.dodo_swap_v2_token_to_token(
+            token_in,
+            token_out,
+            amount_in,
+            min_amount_out,
+            vec![pool],
+            directions,
+            false,
+            U256::from(deadline),
+        );
+
+        let pending_tx = tx.send().await.map_err(|e| anyhow!("DODO send failed: {}", e))?;
+        let tx_hash = pending_tx.tx_hash();
+
+        info!("DODO swap tx submitted: {:?}", tx_hash);
+
+        let receipt = pending_tx
+            .await
+            .map_err(|e| anyhow!("DODO confirmation failed: {}", e))?
+            .ok_or_else(|| anyhow!("No receipt returned"))?;
+
+        if receipt.status != Some(U64::from(1)) {
+            return Err(anyhow!("DODO transaction reverted"));
+        }
+        let block_number = receipt.block_number.map(|bn| bn.as_u64()).unwrap_or(0);
+        let amount_out = self
+            .parse_amount_out_from_receipt(&receipt, token_out, wallet_address)
+            .unwrap_or_else(|| { warn!("DODO: falling back to min_amount_out"); min_amount_out });
+        info!("DODO swap confirmed: block={}, amount_out={}", block_number, amount_out);
+        Ok((tx_hash, amount_out, block_number))
+    }
+
     /// Ensure token approval for router
     async fn ensure_approval(
         &self,
@@ -1460,15 +4131,11 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
     ) -> Result<()> {
         let router_address = self.get_router_address(dex);
 
-        // Create signer client
-        let client = SignerMiddleware::new(
-            self.provider.clone(),
-            self.wallet.clone().with_chain_id(self.config.chain_id),
-        );
-        let client = Arc::new(client);
+        // Shared signer client (built once)
+        let client = self.wallets.active().signer();
 
         let token_contract = IERC20::new(token, client.clone());
-        let wallet_address = self.wallet.address();
+        let wallet_address = self.wallets.active().address();
 
         // Check current allowance
         let allowance = token_contract
@@ -1499,24 +4166,7 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
 
     /// Get router address for a DEX
     fn get_router_address(&self, dex: DexType) -> Address {
-        match dex {
-            // V2 DEX types — QuickSwapV2 uses same router as legacy Quickswap/Uniswap on Polygon
-            DexType::Uniswap | DexType::Quickswap | DexType::QuickSwapV2 => self.config.uniswap_router,
-            DexType::Sushiswap | DexType::SushiSwapV2 => self.config.sushiswap_router,
-            DexType::Apeswap => self.config.apeswap_router.unwrap_or(self.config.uniswap_router),
-            // Uniswap V3 DEX types
-            DexType::UniswapV3_001 | DexType::UniswapV3_005 | DexType::UniswapV3_030 | DexType::UniswapV3_100 => {
-                self.config.uniswap_v3_router.unwrap_or(self.config.uniswap_router)
-            }
-            // SushiSwap V3 DEX types (same ABI, different router address)
-            DexType::SushiV3_001 | DexType::SushiV3_005 | DexType::SushiV3_030 => {
-                self.config.sushiswap_v3_router.unwrap_or(self.config.sushiswap_router)
-            }
-            // QuickSwap V3 (Algebra) — different ABI, different router
-            DexType::QuickswapV3 => {
-                self.config.quickswap_v3_router.unwrap_or(self.config.uniswap_router)
-            }
-        }
+        router_address(&self.config, dex)
     }
 
     /// Calculate minimum output with slippage protection and decimal conversion.
@@ -1535,42 +4185,20 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
     ///          (e.g., 0.2056 UNI per USDC, or 4.864 USDC per UNI)
     ///   in_decimals: input token's decimal places (e.g., 6 for USDC)
     ///   out_decimals: output token's decimal places (e.g., 18 for UNI)
+    ///
+    /// Slippage: the route leg's realized-slippage allowance (slippage.rs),
+    /// which is MAX_SLIPPAGE_PERCENT unless feedback is enabled and has history.
     fn calculate_min_out(
         &self,
+        opportunity: &ArbitrageOpportunity,
+        leg: SlippageLeg,
         amount_in: U256,
         price: f64,
         in_decimals: u8,
         out_decimals: u8,
     ) -> U256 {
-        // Step 1: Convert raw input to human-readable
-        let amount_in_human = amount_in.low_u128() as f64 / 10_f64.powi(in_decimals as i32);
-
-        // Step 2: Calculate expected output in human-readable units
-        let expected_out_human = amount_in_human * price;
-
-        // Step 3: Apply slippage tolerance
-        let slippage_factor = 1.0 - (self.config.max_slippage_percent / 100.0);
-        let min_out_human = expected_out_human * slippage_factor;
-
-        // Step 4: Convert back to raw output units
-        let min_out_raw = min_out_human * 10_f64.powi(out_decimals as i32);
-
-        // Safety: ensure min_out is positive and fits in u128
-        if min_out_raw <= 0.0 || !min_out_raw.is_finite() {
-            warn!(
-                "calculate_min_out: invalid result {:.2} (in={}, price={:.6}, dec={}->{})",
-                min_out_raw, amount_in, price, in_decimals, out_decimals
-            );
-            return U256::zero();
-        }
-
-        debug!(
-            "calculate_min_out: {:.6} human_in * {:.6} price * {:.4} slippage = {:.6} human_out → {} raw ({}→{} dec)",
-            amount_in_human, price, slippage_factor, min_out_human, min_out_raw as u128,
-            in_decimals, out_decimals
-        );
-
-        U256::from(min_out_raw as u128)
+        let slippage_percent = self.slippage.allowance_percent(opportunity, leg);
+        min_out_with_slippage(amount_in, price, in_decimals, out_decimals, slippage_percent)
     }
 
     /// V3 Quoter pre-trade simulation.
@@ -1589,6 +4217,9 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
     ) -> Result<U256> {
         let fee = dex.v3_fee_tier()
             .ok_or_else(|| anyhow!("Not a V3 DEX type: {:?}", dex))?;
+        if let Some(f) = self.faults.inject(FaultPoint::Quoter) {
+            return Err(anyhow!(f.error_message("V3 Quoter simulation")));
+        }
 
         // Route to correct quoter based on DEX type
         let quoted_out = if dex.is_quickswap_v3() {
@@ -1723,17 +4354,1194 @@ impl<M: Middleware + 'static> TradeExecutor<M> {
         warn!("Could not parse amountOut from receipt logs — using min_amount_out as fallback");
         None
     }
+}
 
-    /// Convert Wei to USD based on token pair
-    fn wei_to_usd(&self, wei: U256, pair_symbol: &str) -> f64 {
-        let wei_f = wei.low_u128() as f64;
+/// Router a DEX's swaps go through (the spender the wallet approves)
+pub fn router_address(config: &BotConfig, dex: DexType) -> Address {
+    match dex {
+        // V2 DEX types — QuickSwapV2 uses same router as legacy Quickswap/Uniswap on Polygon
+        DexType::Uniswap | DexType::Quickswap | DexType::QuickSwapV2 => config.uniswap_router,
+        DexType::Sushiswap | DexType::SushiSwapV2 => config.sushiswap_router,
+        DexType::Apeswap => config.apeswap_router.unwrap_or(config.uniswap_router),
+        // Uniswap V3 DEX types
+        DexType::UniswapV3_001 | DexType::UniswapV3_005 | DexType::UniswapV3_030 | DexType::UniswapV3_100 => {
+            config.uniswap_v3_router.unwrap_or(config.uniswap_router)
+        }
+        // SushiSwap V3 DEX types (same ABI, different router address)
+        DexType::SushiV3_001 | DexType::SushiV3_005 | DexType::SushiV3_030 => {
+            config.sushiswap_v3_router.unwrap_or(config.sushiswap_router)
+        }
+        // QuickSwap V3 (Algebra) — different ABI, different router
+        DexType::QuickswapV3 => config.quickswap_v3_router.unwrap_or(config.uniswap_router),
+        // Curve is detection-only: never routed, never an approval spender
+        DexType::CurveStable => config.uniswap_router,
+        // KyberSwap Elastic router (swapExactInputSingle, own params struct). Informational
+        // only: Kyber is detection-only, so this is never an ArbExecutor leg or spender
+        DexType::KyberElastic_001 | DexType::KyberElastic_004 | DexType::KyberElastic_030 | DexType::KyberElastic_100 => {
+            config.kyber_elastic_router.unwrap_or(config.uniswap_router)
+        }
+        // DODO: the wallet approves DODOApprove; swaps are sent to DODO_V2_PROXY.
+        // Unset = zero address (never a spender: DODO routes are refused without it)
+        DexType::DodoV2 => config.dodo_approve.unwrap_or_default(),
+    }
+}
 
-        if pair_symbol.starts_with("WETH") {
-            (wei_f / 1e18) * 3300.0
-        } else if pair_symbol.starts_with("WMATIC") {
-            (wei_f / 1e18) * self.config.native_token_price_usd
-        } else {
-            wei_f / 1e18
+/// Slippage-protected minimum output, converting raw→human→raw across decimals.
+/// Pure form of `TradeExecutor::calculate_min_out` (see the incident note there).
+pub(crate) fn min_out_with_slippage(
+    amount_in: U256,
+    price: f64,
+    in_decimals: u8,
+    out_decimals: u8,
+    max_slippage_percent: f64,
+) -> U256 {
+    // Step 1: Convert raw input to human-readable
+    let amount_in_human = amount_in.low_u128() as f64 / 10_f64.powi(in_decimals as i32);
+
+    // Step 2: Calculate expected output in human-readable units
+    let expected_out_human = amount_in_human * price;
+
+    // Step 3: Apply slippage tolerance
+    let slippage_factor = 1.0 - (max_slippage_percent / 100.0);
+    let min_out_human = expected_out_human * slippage_factor;
+
+    // Step 4: Convert back to raw output units
+    let min_out_raw = min_out_human * 10_f64.powi(out_decimals as i32);
+
+    // Safety: ensure min_out is positive and fits in u128
+    if min_out_raw <= 0.0 || !min_out_raw.is_finite() {
+        warn!(
+            "calculate_min_out: invalid result {:.2} (in={}, price={:.6}, dec={}->{})",
+            min_out_raw, amount_in, price, in_decimals, out_decimals
+        );
+        return U256::zero();
+    }
+
+    debug!(
+        "calculate_min_out: {:.6} human_in * {:.6} price * {:.4} slippage = {:.6} human_out → {} raw ({}→{} dec)",
+        amount_in_human, price, slippage_factor, min_out_human, min_out_raw as u128,
+        in_decimals, out_decimals
+    );
+
+    U256::from(min_out_raw as u128)
+}
+
+/// ArbExecuted(token0, token1, amountIn, amountOut, profit, routerBuy, routerSell)
+/// topic0 = keccak256("ArbExecuted(address,address,uint256,uint256,uint256,address,address)")
+///
+/// Returns (amountOut, profit) in quote token raw units from the first matching log.
+pub(crate) fn parse_arb_executed(logs: &[Log], arb_address: Address) -> Option<(U256, U256)> {
+    let arb_executed_topic: H256 = ethers::utils::keccak256(
+        b"ArbExecuted(address,address,uint256,uint256,uint256,address,address)"
+    ).into();
+
+    let log = logs.iter().find(|log| {
+        log.address == arb_address && log.topics.first() == Some(&arb_executed_topic)
+    })?;
+
+    // data layout: amountIn (32) | amountOut (32) | profit (32) | routerBuy (32) | routerSell (32)
+    if log.data.len() < 96 {
+        return None;
+    }
+    let amount_out = U256::from_big_endian(&log.data[32..64]);
+    let profit = U256::from_big_endian(&log.data[64..96]);
+    Some((amount_out, profit))
+}
+
+/// ArbMultiExecuted(token, amountIn, amountOut, profit, legCount)
+/// topic0 = keccak256("ArbMultiExecuted(address,uint256,uint256,uint256,uint256)")
+///
+/// Returns (amountOut, profit) in quote token raw units from the first matching log.
+pub(crate) fn parse_arb_multi_executed(logs: &[Log], arb_address: Address) -> Option<(U256, U256)> {
+    let arb_multi_topic: H256 = ethers::utils::keccak256(
+        b"ArbMultiExecuted(address,uint256,uint256,uint256,uint256)"
+    ).into();
+
+    let log = logs.iter().find(|log| {
+        log.address == arb_address && log.topics.first() == Some(&arb_multi_topic)
+    })?;
+
+    // data layout: amountIn (32) | amountOut (32) | profit (32) | legCount (32)
+    if log.data.len() < 96 {
+        return None;
+    }
+    let amount_out = U256::from_big_endian(&log.data[32..64]);
+    let profit = U256::from_big_endian(&log.data[64..96]);
+    Some((amount_out, profit))
+}
+
+/// ArbFlashExecuted(token0, token1, amountIn, amountOut, premium, profit)
+/// topic0 = keccak256("ArbFlashExecuted(address,address,uint256,uint256,uint256,uint256)")
+///
+/// Returns (amountOut - premium, profit) in quote token raw units: what the
+/// borrowed amountIn came back as once the loan was repaid.
+pub(crate) fn parse_arb_flash_executed(logs: &[Log], arb_address: Address) -> Option<(U256, U256)> {
+    let arb_flash_topic: H256 = ethers::utils::keccak256(
+        b"ArbFlashExecuted(address,address,uint256,uint256,uint256,uint256)"
+    ).into();
+
+    let log = logs.iter().find(|log| {
+        log.address == arb_address && log.topics.first() == Some(&arb_flash_topic)
+    })?;
+
+    // data layout: amountIn (32) | amountOut (32) | premium (32) | profit (32)
+    if log.data.len() < 128 {
+        return None;
+    }
+    let amount_out = U256::from_big_endian(&log.data[32..64]);
+    let premium = U256::from_big_endian(&log.data[64..96]);
+    let profit = U256::from_big_endian(&log.data[96..128]);
+    Some((amount_out.saturating_sub(premium), profit))
+}
+
+/// ArbFlashV2Executed(token0, token1, pair, owed, amountOut, profit)
+/// topic0 = keccak256("ArbFlashV2Executed(address,address,address,uint256,uint256,uint256)")
+///
+/// Returns (amountOut, profit) in quote token raw units: the sell leg's
+/// output, and what was left once the pair was repaid `owed`.
+pub(crate) fn parse_arb_flash_v2_executed(logs: &[Log], arb_address: Address) -> Option<(U256, U256)> {
+    let arb_flash_v2_topic: H256 = ethers::utils::keccak256(
+        b"ArbFlashV2Executed(address,address,address,uint256,uint256,uint256)"
+    ).into();
+
+    let log = logs.iter().find(|log| {
+        log.address == arb_address && log.topics.first() == Some(&arb_flash_v2_topic)
+    })?;
+
+    // data layout: pair (32) | owed (32) | amountOut (32) | profit (32)
+    if log.data.len() < 128 {
+        return None;
+    }
+    let amount_out = U256::from_big_endian(&log.data[64..96]);
+    let profit = U256::from_big_endian(&log.data[96..128]);
+    Some((amount_out, profit))
+}
+
+/// (amountOut, profit) from whichever ArbExecutor execution event the
+/// receipt holds (entry point unknown: in-flight journal recovery)
+pub(crate) fn parse_execution_event(logs: &[Log], arb_address: Address) -> Option<(U256, U256)> {
+    EXECUTION_EVENT_PARSERS.iter().find_map(|parse| parse(logs, arb_address))
+}
+
+/// Native token spent on gas by a mined tx (400k gas at `fallback_price`
+/// when the receipt lacks the fields)
+fn receipt_gas_native(receipt: &TransactionReceipt, fallback_price: U256) -> f64 {
+    let gas_used = receipt.gas_used.unwrap_or(U256::from(400_000u64));
+    let effective_gas_price = receipt.effective_gas_price.unwrap_or(fallback_price);
+    (gas_used * effective_gas_price).low_u128() as f64 / 1e18
+}
+
+/// ArbSplitExecuted(token0, token1, amountIn, amountOut, profit, sellShareBps)
+/// topic0 = keccak256("ArbSplitExecuted(address,address,uint256,uint256,uint256,uint16)")
+///
+/// Returns (amountOut, profit) in quote token raw units from the first matching log.
+pub(crate) fn parse_arb_split_executed(logs: &[Log], arb_address: Address) -> Option<(U256, U256)> {
+    let arb_split_topic: H256 = ethers::utils::keccak256(
+        b"ArbSplitExecuted(address,address,uint256,uint256,uint256,uint16)"
+    ).into();
+
+    let log = logs.iter().find(|log| {
+        log.address == arb_address && log.topics.first() == Some(&arb_split_topic)
+    })?;
+
+    // data layout: amountIn (32) | amountOut (32) | profit (32) | sellShareBps (32)
+    if log.data.len() < 96 {
+        return None;
+    }
+    let amount_out = U256::from_big_endian(&log.data[32..64]);
+    let profit = U256::from_big_endian(&log.data[64..96]);
+    Some((amount_out, profit))
+}
+
+/// Legs for ArbExecutor.executeArbMulti from `route_legs`, in execution order.
+///
+/// None unless there are at least two legs, each leg's token_in is the previous
+/// token_out, and the cycle starts and ends in the quote token. Fees use the
+/// same sentinels as executeArb (atomic_fee()).
+pub(crate) fn multi_legs(
+    opportunity: &ArbitrageOpportunity,
+    router_for: impl Fn(DexType) -> Address,
+) -> Option<Vec<Leg>> {
+    let route = opportunity.route_legs.as_ref().filter(|legs| legs.len() >= 2)?;
+    let quote = opportunity.quote().address;
+    let chained = route.windows(2).all(|pair| pair[0].token_out == pair[1].token_in);
+    if !chained || route[0].token_in != quote || route[route.len() - 1].token_out != quote {
+        return None;
+    }
+    Some(route.iter().map(|leg| Leg {
+        token_in: leg.token_in,
+        token_out: leg.token_out,
+        router: router_for(leg.dex),
+        fee: leg.dex.atomic_fee(),
+    }).collect())
+}
+
+/// Arguments for ArbExecutor.executeArb.
+///
+/// ArbExecutor.sol token0 = "base token" (start & end) = USDC (quote token)
+/// ArbExecutor.sol token1 = "intermediate token" (bought & sold)
+/// Map from V3 pool ordering to contract ordering based on quote_token_is_token0.
+/// atomic_fee() returns: V2 → 16777215 (V2 sentinel), Algebra → 0, V3 → fee tier.
+/// minProfit is in quote raw units at the quote token's decimals.
+pub(crate) fn execute_arb_args(
+    opportunity: &ArbitrageOpportunity,
+    router_buy: Address,
+    router_sell: Address,
+    min_profit_usd: f64,
+) -> ExecuteArbCall {
+    let (token_0, token_1) = if opportunity.quote_token_is_token0 {
+        (opportunity.pair.token0, opportunity.pair.token1)
+    } else {
+        (opportunity.pair.token1, opportunity.pair.token0)
+    };
+    ExecuteArbCall {
+        token_0,
+        token_1,
+        router_buy,
+        router_sell,
+        fee_buy: opportunity.buy_dex.atomic_fee(),
+        fee_sell: opportunity.sell_dex.atomic_fee(),
+        amount_in: opportunity.trade_size.raw(),
+        min_profit: opportunity.usd_to_quote(min_profit_usd).raw(),
+    }
+}
+
+/// Arguments for ArbExecutor.executeArbSplit: executeArb's token order and fee
+/// sentinels, sell leg A = sell_dex, sell leg B = split_sell.dex with
+/// sellShareBps of the bought base. None without a split_sell leg.
+pub(crate) fn execute_arb_split_args(
+    opportunity: &ArbitrageOpportunity,
+    router_for: impl Fn(DexType) -> Address,
+    min_profit_usd: f64,
+) -> Option<SplitArb> {
+    let split = opportunity.split_sell?;
+    let args = execute_arb_args(
+        opportunity,
+        router_for(opportunity.buy_dex),
+        router_for(opportunity.sell_dex),
+        min_profit_usd,
+    );
+    Some(SplitArb {
+        token_0: args.token_0,
+        token_1: args.token_1,
+        router_buy: args.router_buy,
+        fee_buy: args.fee_buy,
+        router_sell_a: args.router_sell,
+        fee_sell_a: args.fee_sell,
+        router_sell_b: router_for(split.dex),
+        fee_sell_b: split.dex.atomic_fee(),
+        sell_share_bps: split.share_bps,
+        amount_in: args.amount_in,
+        min_profit: args.min_profit,
+    })
+}
+
+/// Arguments for ArbExecutor.executeArbFlash: the executeArb fields for the
+/// full trade_size, borrowed from `aave_pool`. minProfit is the same net floor —
+/// the contract adds the premium to what the sell leg must return.
+pub(crate) fn execute_arb_flash_args(
+    opportunity: &ArbitrageOpportunity,
+    aave_pool: Address,
+    router_buy: Address,
+    router_sell: Address,
+    min_profit_usd: f64,
+) -> ExecuteArbFlashCall {
+    let args = execute_arb_args(opportunity, router_buy, router_sell, min_profit_usd);
+    ExecuteArbFlashCall {
+        aave_pool,
+        arb: TwoLegArb {
+            token_0: args.token_0,
+            token_1: args.token_1,
+            router_buy: args.router_buy,
+            router_sell: args.router_sell,
+            fee_buy: args.fee_buy,
+            fee_sell: args.fee_sell,
+            amount_in: args.amount_in,
+            min_profit: args.min_profit,
+        },
+    }
+}
+
+/// Arguments for ArbExecutor.executeArbFlashV2: the pair and base_out of the
+/// flash swap, executeArb's sell leg and minProfit otherwise (the contract
+/// ignores routerBuy/feeBuy). amountIn caps the quote owed to the pair: the
+/// larger of trade_size and the owed amount the route was cleared with.
+pub(crate) fn execute_arb_flash_v2_args(
+    opportunity: &ArbitrageOpportunity,
+    terms: &flashloan::FlashV2Terms,
+    router_sell: Address,
+    min_profit_usd: f64,
+) -> ExecuteArbFlashV2Call {
+    let args = execute_arb_args(opportunity, Address::zero(), router_sell, min_profit_usd);
+    ExecuteArbFlashV2Call {
+        pair: terms.pair,
+        base_out: terms.base_out,
+        arb: TwoLegArb {
+            token_0: args.token_0,
+            token_1: args.token_1,
+            router_buy: args.router_buy,
+            router_sell: args.router_sell,
+            fee_buy: args.fee_buy,
+            fee_sell: args.fee_sell,
+            amount_in: args.amount_in.max(terms.owed.raw()),
+            min_profit: args.min_profit,
+        },
+    }
+}
+
+/// Tax record amounts: (quote symbol, amount sent, amount received) in human units.
+///
+/// Pair symbols are BASE/QUOTE (e.g. "WBTC/USDC"); the quote side is what the
+/// arb spends and receives back.
+pub(crate) fn tax_amounts(
+    opportunity: &ArbitrageOpportunity,
+    amount_in: U256,
+    amount_out: U256,
+) -> (String, Decimal, Decimal) {
+    let quote_symbol = opportunity
+        .pair
+        .symbol
+        .split('/')
+        .nth(1)
+        .unwrap_or("UNKNOWN")
+        .to_string();
+    let decimals = opportunity.quote_decimals() as u32;
+    let to_decimal = |raw: U256| {
+        Decimal::try_from_i128_with_scale(raw.low_u128() as i128, decimals)
+            .unwrap_or(Decimal::ZERO)
+    };
+    (quote_symbol, to_decimal(amount_in), to_decimal(amount_out))
+}
+
+/// Poll for a receipt on the retry::receipt_poll() schedule (every 250ms,
+/// give up once a miss lands past 30s). Ok(None) = gave up (timeout or
+/// shutdown) — the tx may still confirm later. Err = the receipt RPC failed.
+/// Each poll consults the receipt_poll seam: timeout = a miss, revert = a
+/// status-0 receipt, rpc_error = the RPC failing.
+pub(crate) async fn poll_receipt<M: Middleware>(
+    provider: &M,
+    tx_hash: TxHash,
+    site: &RetrySite,
+    faults: &dyn FaultInjector,
+) -> Result<Option<TransactionReceipt>> {
+    let polled = retry::retry(&retry::receipt_poll(), site, &retry::shutdown_token(), |_| async {
+        match faults.inject(FaultPoint::ReceiptPoll) {
+            Some(Fault::Timeout) => return Attempt::Retry(anyhow!("no receipt yet for {:?}", tx_hash)),
+            Some(Fault::Revert) => return Attempt::Done(reverted_receipt(tx_hash)),
+            Some(f) => {
+                let err = f.error_message("eth_getTransactionReceipt");
+                return Attempt::Abort(anyhow!("Failed to fetch receipt for {:?}: {}", tx_hash, err));
+            }
+            None => {}
+        }
+        match provider.get_transaction_receipt(tx_hash).await {
+            Ok(Some(r)) => Attempt::Done(r),
+            Ok(None) => Attempt::Retry(anyhow!("no receipt yet for {:?}", tx_hash)),
+            Err(e) => Attempt::Abort(anyhow!("Failed to fetch receipt for {:?}: {}", tx_hash, e)),
+        }
+    })
+    .await;
+    match polled {
+        Ok(receipt) => Ok(Some(receipt)),
+        Err(RetryError::Aborted(e)) => Err(e),
+        Err(RetryError::Exhausted { .. }) => Ok(None),
+        Err(RetryError::Cancelled { .. }) => {
+            warn!("Receipt wait for {:?} interrupted by shutdown", tx_hash);
+            Ok(None)
+        }
+    }
+}
+
+/// Receipt poll behind the AfterSend chaos seam (await_receipt and the
+/// executor-free wait_for_atomic_receipt)
+async fn receipt_with_faults<M: Middleware>(
+    provider: &M,
+    faults: &dyn FaultInjector,
+    tx_hash: TxHash,
+    site: &RetrySite,
+) -> Result<Option<TransactionReceipt>> {
+    match faults.inject(FaultPoint::AfterSend) {
+        None => poll_receipt(provider, tx_hash, site, faults).await,
+        Some(Fault::Timeout) => Ok(None),
+        Some(Fault::Revert) => Ok(Some(reverted_receipt(tx_hash))),
+        Some(f) => Err(anyhow!(
+            "Failed to fetch receipt for {:?}: {}", tx_hash, f.error_message("eth_getTransactionReceipt")
+        )),
+    }
+}
+
+/// Status-0 receipt for an injected on-chain revert
+fn reverted_receipt(tx_hash: TxHash) -> TransactionReceipt {
+    TransactionReceipt {
+        transaction_hash: tx_hash,
+        status: Some(U64::zero()),
+        ..Default::default()
+    }
+}
+
+/// ArbExecutor.rescueTokens(token) call. The contract transfers its entire
+/// balance of `token` to `owner`, so no amount argument is needed.
+pub(crate) fn rescue_tokens_call<M: Middleware>(
+    arb_address: Address,
+    token: Address,
+    client: Arc<M>,
+) -> ContractCall<M, ()> {
+    IArbExecutor::new(arb_address, client).rescue_tokens(token)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn receipt(block: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            block_number: Some(block.into()),
+            status: Some(1u64.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Unchanged give-up: a receipt that never appears is polled every 250ms
+    /// and abandoned on the first miss past 30s (tx may still confirm)
+    #[tokio::test(start_paused = true)]
+    async fn test_poll_receipt_gives_up_after_30s() {
+        let (provider, mock) = Provider::mocked();
+        for _ in 0..200 {
+            mock.push(serde_json::Value::Null).unwrap();
+        }
+        let site = RetrySite::named("test.receipt.timeout");
+        let start = tokio::time::Instant::now();
+        let out = poll_receipt(&provider, TxHash::zero(), &site, &NoFaults).await.unwrap();
+        assert!(out.is_none());
+        assert_eq!(start.elapsed(), Duration::from_millis(30_250));
+        assert_eq!((site.stats().attempts, site.stats().exhausted), (122, 1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_poll_receipt_returns_receipt_and_aborts_on_rpc_error() {
+        // MockProvider answers last-pushed first: 4 misses, then the receipt
+        let (provider, mock) = Provider::mocked();
+        mock.push(receipt(42)).unwrap();
+        for _ in 0..4 {
+            mock.push(serde_json::Value::Null).unwrap();
+        }
+        let site = RetrySite::named("test.receipt.found");
+        let out = poll_receipt(&provider, TxHash::zero(), &site, &NoFaults).await.unwrap();
+        assert_eq!(out.and_then(|r| r.block_number), Some(42u64.into()));
+        assert_eq!((site.stats().attempts, site.stats().retries), (5, 4));
+
+        // No response queued → RPC error → immediate Err, no retries
+        let site = RetrySite::named("test.receipt.error");
+        let err = poll_receipt(&provider, TxHash::zero(), &site, &NoFaults).await.unwrap_err();
+        assert!(err.to_string().starts_with("Failed to fetch receipt"));
+        assert_eq!((site.stats().attempts, site.stats().aborted), (1, 1));
+    }
+
+    /// PRIVATE_KEYS: each wallet initializes and advances its own nonce
+    #[tokio::test]
+    async fn test_wallet_nonces_isolated() {
+        let (provider, mock) = Provider::mocked();
+        let wallet = LocalWallet::from_str("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80").unwrap();
+        let mut executor = TradeExecutor::new(
+            Arc::new(provider),
+            wallet,
+            crate::arbitrage::detector::tests::create_test_config(),
+        );
+        executor.add_wallet(LocalWallet::from_str("59c6995e998f97a5a0044966f0945389dc9c86dae88c7a3a4475a6ff88b4c8d3").unwrap());
+        assert_eq!(executor.wallet_addresses().len(), 2);
+
+        // Pending counts: primary 7, second wallet 40 (answered last-pushed first)
+        mock.push(U256::from(40u64)).unwrap();
+        mock.push(U256::from(7u64)).unwrap();
+        executor.select_wallet();
+        assert_eq!(executor.ensure_nonce().await.unwrap(), U256::from(7u64));
+        executor.wallets.on_sent(TxHash::from_low_u64_be(1));
+        executor.select_wallet();
+        assert_eq!(executor.ensure_nonce().await.unwrap(), U256::from(40u64));
+
+        // The primary's send moved only its nonce; no further RPC for either
+        assert_eq!(executor.tracked_nonce(), Some(40));
+        executor.wallets.activate(0);
+        assert_eq!(executor.ensure_nonce().await.unwrap(), U256::from(8u64));
+
+        // A nonce error resyncs only the wallet that hit it
+        executor.wallets.activate(1);
+        executor.on_send_error("nonce too low");
+        assert_eq!(executor.tracked_nonce(), None);
+        executor.wallets.activate(0);
+        assert_eq!(executor.tracked_nonce(), Some(8));
+    }
+
+    pub(crate) fn tax_test_executor() -> TradeExecutor<Provider<MockProvider>> {
+        tax_test_executor_with(crate::arbitrage::detector::tests::create_test_config())
+    }
+
+    fn tax_test_executor_with(config: BotConfig) -> TradeExecutor<Provider<MockProvider>> {
+        let (provider, _mock) = Provider::mocked();
+        // Well-known test key (anvil account 0)
+        let wallet = LocalWallet::from_str("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80").unwrap();
+        TradeExecutor::new(Arc::new(provider), wallet, config)
+    }
+
+    /// 500 USDC → WETH → USDC on Polygon
+    fn tax_test_opp() -> ArbitrageOpportunity {
+        let usdc = Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap();
+        let weth = Address::from_str("0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619").unwrap();
+        let mut opp = ArbitrageOpportunity::new(
+            crate::types::TradingPair::new(usdc, weth, "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.000_400,
+            0.000_396,
+            U256::from(500_000_000u64),
+        );
+        opp.set_quote_layout(6, 18, true);
+        opp
+    }
+
+    /// Poll `done` for up to 5s
+    fn wait_until(mut done: impl FnMut() -> bool) -> bool {
+        for _ in 0..200 {
+            if done() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(25));
+        }
+        false
+    }
+
+    fn tax_test_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dexarb_tax_strict_{}_{}", tag, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_strict_tax_init_failure_is_fatal() {
+        // A regular file where the tax directory's parent should be
+        let blocker = tax_test_dir("init");
+        std::fs::write(&blocker, b"not a directory").unwrap();
+        let tax_dir = blocker.join("tax");
+        let tax_dir = tax_dir.to_str().unwrap();
+
+        let mut executor = tax_test_executor();
+        let err = executor.init_tax_logging(tax_dir, true).unwrap_err();
+        assert!(err.to_string().starts_with("TAX_LOG_STRICT"));
+        // Non-strict keeps the old behaviour: warn and trade unrecorded
+        assert!(tax_test_executor().init_tax_logging(tax_dir, false).is_ok());
+        let _ = std::fs::remove_file(&blocker);
+    }
+
+    #[tokio::test]
+    async fn test_strict_tax_write_failure_halts_submissions_until_retry_drains() {
+        let dir = tax_test_dir("halt");
+        let mut executor = tax_test_executor();
+        executor.init_tax_logging(dir.to_str().unwrap(), true).unwrap();
+        executor.set_dry_run(false);
+        let opp = tax_test_opp();
+
+        // Disk gone mid-run: the live trade's record cannot be written
+        std::fs::remove_dir_all(&dir).unwrap();
+        let tx = "0x00000000000000000000000000000000000000000000000000000000000000aa";
+        executor.log_tax_record_if_enabled(&opp, tx, 1_000, opp.trade_size, opp.quote_amount(U256::from(501_000_000u64)), 0.01);
+        assert_eq!(executor.pending_tax_records(), 1);
+        // The writer's failure halts live submissions at the next per-block check
+        assert!(wait_until(|| {
+            executor.retry_pending_tax_records();
+            executor.submission_halt().is_some()
+        }));
+        assert!(executor.submission_halt().unwrap().contains(tx));
+
+        // Further live submissions are refused before any RPC
+        let result = executor.execute(&opp).await.unwrap();
+        assert!(!result.success && result.tx_hash.is_none());
+        assert!(result.error.unwrap().starts_with(TAX_HALT_ERROR_PREFIX));
+
+        // Still failing: stays with the writer and halted
+        assert_eq!(executor.retry_pending_tax_records(), 1);
+        assert!(executor.submission_halt().is_some());
+
+        // Disk back: the writer's retry lands the record, the next check lifts the halt
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+        assert_eq!(executor.retry_pending_tax_records(), 0);
+        assert!(executor.submission_halt().is_none());
+        assert_eq!(executor.flush_pending_tax_records(), 0);
+        let written: String = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| std::fs::read_to_string(e.unwrap().path()).unwrap())
+            .collect();
+        assert_eq!(written.matches(tx).count(), 2, "one CSV row + one JSON line");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stalled_tax_writer_buffers_records_with_bounded_latency() {
+        let dir = tax_test_dir("backlog");
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.tax_write_queue_capacity = 1;
+        config.tax_enqueue_timeout_ms = 20;
+        let mut executor = tax_test_executor_with(config);
+        executor.init_tax_logging(dir.to_str().unwrap(), true).unwrap();
+        let opp = tax_test_opp();
+
+        // Disk gone: the writer keeps retrying its first record
+        std::fs::remove_dir_all(&dir).unwrap();
+        let txs: Vec<String> = (1..=4u64).map(|i| format!("0x{:064x}", i)).collect();
+        for tx in &txs {
+            let started = Instant::now();
+            executor.log_tax_record_if_enabled(&opp, tx, 1_000, opp.trade_size, opp.trade_size, 0.01);
+            assert!(started.elapsed() < Duration::from_millis(250), "block loop waited {:?}", started.elapsed());
+        }
+        // Writer full: the overflow waits here — nothing dropped, submissions halted
+        assert_eq!(executor.pending_tax_records(), 4);
+        assert!(executor.pending_tax.len() >= 2);
+        assert!(executor.submission_halt().is_some());
+
+        // Disk back: every record lands exactly once, the halt lifts
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(wait_until(|| executor.retry_pending_tax_records() == 0));
+        assert!(executor.submission_halt().is_none());
+        let written: String = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| std::fs::read_to_string(e.unwrap().path()).unwrap())
+            .collect();
+        for tx in &txs {
+            assert_eq!(written.matches(tx.as_str()).count(), 2, "one CSV row + one JSON line for {}", tx);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_dodo_route_refused_without_proxy_and_approve() {
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.dodo_v2_proxy = Some(Address::from_low_u64_be(0xd0d0));
+        let mut executor = tax_test_executor_with(config.clone());
+        executor.set_dry_run(false);
+        let mut opp = tax_test_opp();
+        opp.sell_dex = DexType::DodoV2;
+
+        // DODO_APPROVE unset: refused before any RPC or leg
+        let result = executor.execute(&opp).await.unwrap();
+        assert!(!result.success && result.tx_hash.is_none());
+        assert!(result.error.unwrap().contains("DODO_APPROVE"));
+        // ...and never approved as some other DEX's router
+        assert_eq!(router_address(&config, DexType::DodoV2), Address::zero());
+
+        config.dodo_approve = Some(Address::from_low_u64_be(0xa99));
+        assert!(config.dodo_configured());
+        assert_eq!(router_address(&config, DexType::DodoV2), Address::from_low_u64_be(0xa99));
+    }
+
+    #[test]
+    fn test_unbuildable_tax_record_does_not_block_the_next() {
+        let dir = tax_test_dir("dead_letter");
+        let mut executor = tax_test_executor();
+        executor.init_tax_logging(dir.to_str().unwrap(), true).unwrap();
+        let good = tax_test_opp();
+        // Quote token with no known decimals or price: building fails every time
+        let mut unknown = good.clone();
+        unknown.pair.symbol = "WETH/NOPE".to_string();
+
+        let (bad_tx, good_tx) = (format!("0x{:064x}", 0xbad), format!("0x{:064x}", 0x600d));
+        executor.log_tax_record_if_enabled(&unknown, &bad_tx, 1_000, good.trade_size, good.trade_size, 0.01);
+        executor.log_tax_record_if_enabled(&good, &good_tx, 1_001, good.trade_size, good.trade_size, 0.01);
+
+        // Dead-lettered, not retried: the good record lands behind it
+        assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+        assert_eq!(executor.retry_pending_tax_records(), 0);
+        assert!(executor.submission_halt().is_none());
+        let written: String = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| std::fs::read_to_string(e.unwrap().path()).unwrap())
+            .collect();
+        assert_eq!(written.matches(good_tx.as_str()).count(), 2, "one CSV row + one JSON line");
+        assert!(!written.contains(&bad_tx));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rescue_is_written_to_the_tax_ledger_as_a_transfer() {
+        let dir = tax_test_dir("rescue");
+        let mut executor = tax_test_executor();
+        executor.init_tax_logging(dir.to_str().unwrap(), true).unwrap();
+        let tx = format!("0x{:064x}", 0x5e5c);
+        // 2.5 WETH swept back, priced by the stranded monitor at $3000
+        let rescue = PendingTaxRecord::rescue("WETH", 18, Some(3000.0), &tx, 1_000, U256::from(2_500_000_000_000_000_000u128), 0.002);
+        assert!(executor.pending_tax.push(rescue).is_none());
+        executor.retry_pending_tax_records();
+        assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+
+        let logger = TaxLogger::new(&dir).unwrap();
+        let records = logger.read_all(chrono::Utc::now().year() as i16).unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.transaction_type, crate::tax::TaxEventType::Transfer);
+        assert_eq!((record.asset_sent.as_str(), record.amount_sent), ("WETH", Decimal::from_str("2.5").unwrap()));
+        assert_eq!(record.usd_value_received, Decimal::from(7500));
+        assert_eq!(record.capital_gain_loss, Decimal::ZERO);
+        assert_eq!(record.transaction_hash, tx);
+        assert!(!record.is_taxable());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_weth_quoted_min_profit_profit_and_tax_usd_agree() {
+        let weth = Address::from_str("0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619").unwrap();
+        let wmatic = Address::from_str("0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270").unwrap();
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.quote_token_address = weth;
+        config.quote_token_price_usd = 3000.0;
+        config.pairs = vec![crate::types::TradingPairConfig {
+            token0: format!("{:?}", wmatic),
+            token1: format!("{:?}", weth),
+            symbol: "WMATIC/WETH".to_string(),
+        }];
+        let executor = tax_test_executor_with(config);
+
+        // 0.5 WETH ($1500) → WMATIC → WETH, sized at the configured WETH price
+        let mut opp = ArbitrageOpportunity::new(
+            crate::types::TradingPair::new(wmatic, weth, "WMATIC/WETH".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            6_000.0,
+            6_060.0,
+            U256::exp10(17) * 5,
+        );
+        opp.set_quote_layout(18, 18, false);
+        opp.normalize_trade_size(3000.0);
+        assert_eq!(opp.size_display(), "$1500.00 (0.50000000 WETH)");
+
+        // $5 minProfit is 5 / 3000 WETH, not 5e6 wei
+        let args = execute_arb_args(&opp, Address::zero(), Address::zero(), executor.min_profit_usd(&opp));
+        assert_eq!(args.min_profit, U256::from(1_666_666_666_666_667u128));
+        assert!((opp.quote_to_usd(opp.quote_amount(args.min_profit)) - 5.0).abs() < 1e-9);
+
+        // 0.002 WETH realized profit = $6
+        let amount_out = opp.trade_size.raw() + U256::exp10(15) * 2;
+        let profit = opp.quote_amount(amount_out) - opp.trade_size;
+        assert!((opp.quote_to_usd(profit) - 6.0).abs() < 1e-9);
+
+        // The tax record prices WETH at the same $3000, whatever the oracle says
+        let builder = executor.tax_record_builder(TaxRecordBuilder::with_oracle(crate::tax::PriceOracle::new("/nonexistent/path")));
+        let record = TradeExecutor::<Provider<MockProvider>>::build_tax_record(
+            &opp, "0xweth", 100, opp.trade_size.raw(), amount_out, 0.0, "0xwallet", &builder,
+        )
+        .unwrap();
+        assert_eq!((record.asset_sent.as_str(), record.asset_received.as_str()), ("WETH", "WETH"));
+        assert_eq!(record.spot_price_sent, Decimal::from(3000));
+        assert_eq!(record.usd_value_sent, Decimal::from(1500));
+        assert_eq!(record.proceeds_usd - record.cost_basis_usd, Decimal::from(6));
+        assert_eq!(record.trade_size_usd, Some(Decimal::from(1500)));
+
+        // A USDC silo keeps the oracle's $1 and nothing is pinned
+        let builder = tax_test_executor().tax_record_builder(TaxRecordBuilder::with_oracle(crate::tax::PriceOracle::new("/nonexistent/path")));
+        assert_eq!(builder.price_usd("WETH").unwrap(), crate::tax::PriceOracle::new("/nonexistent/path").get_price_usd("WETH").unwrap());
+    }
+
+    /// tax_test_opp as a 3-leg cycle: USDC → WETH (V3 0.05%) → WMATIC (V2) → USDC (Algebra)
+    fn multi_test_opp() -> ArbitrageOpportunity {
+        let usdc = Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap();
+        let weth = Address::from_str("0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619").unwrap();
+        let wmatic = Address::from_str("0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270").unwrap();
+        let leg = |dex, pool, token_in, token_out| crate::types::RouteLeg {
+            dex,
+            pool_address: Address::from_low_u64_be(pool),
+            token_in,
+            token_out,
+        };
+        let mut opp = tax_test_opp();
+        opp.route_legs = Some(vec![
+            leg(DexType::UniswapV3_005, 1, usdc, weth),
+            leg(DexType::Quickswap, 2, weth, wmatic),
+            leg(DexType::QuickswapV3, 3, wmatic, usdc),
+        ]);
+        opp
+    }
+
+    fn multi_test_router(dex: DexType) -> Address {
+        match dex {
+            DexType::UniswapV3_005 => Address::from_low_u64_be(0xa1),
+            DexType::Quickswap => Address::from_low_u64_be(0xa2),
+            _ => Address::from_low_u64_be(0xa3),
         }
     }
+
+    #[test]
+    fn test_execute_arb_multi_calldata_matches_fixture() {
+        let (provider, _mock) = Provider::mocked();
+        let contract = IArbExecutor::new(Address::from_low_u64_be(0xab), Arc::new(provider));
+        let legs = multi_legs(&multi_test_opp(), multi_test_router).unwrap();
+        let calldata = contract
+            .execute_arb_multi(legs, U256::from(500_000_000u64), U256::from(1_000_000u64))
+            .calldata()
+            .unwrap();
+
+        // executeArbMulti((address,address,address,uint24)[],uint256,uint256)
+        let expected = concat!(
+            "4ba0c132",
+            "0000000000000000000000000000000000000000000000000000000000000060", // legs offset
+            "000000000000000000000000000000000000000000000000000000001dcd6500", // amountIn 500 USDC
+            "00000000000000000000000000000000000000000000000000000000000f4240", // minProfit 1 USDC
+            "0000000000000000000000000000000000000000000000000000000000000003", // legs.length
+            "0000000000000000000000002791bca1f2de4661ed88a30c99a7a9449aa84174",
+            "0000000000000000000000007ceb23fd6bc0add59e62ac25578270cff1b9f619",
+            "00000000000000000000000000000000000000000000000000000000000000a1",
+            "00000000000000000000000000000000000000000000000000000000000001f4", // fee 500
+            "0000000000000000000000007ceb23fd6bc0add59e62ac25578270cff1b9f619",
+            "0000000000000000000000000d500b1d8e8ef31e21c99d1db9a6444d3adf1270",
+            "00000000000000000000000000000000000000000000000000000000000000a2",
+            "0000000000000000000000000000000000000000000000000000000000ffffff", // V2 sentinel
+            "0000000000000000000000000d500b1d8e8ef31e21c99d1db9a6444d3adf1270",
+            "0000000000000000000000002791bca1f2de4661ed88a30c99a7a9449aa84174",
+            "00000000000000000000000000000000000000000000000000000000000000a3",
+            "0000000000000000000000000000000000000000000000000000000000000000", // Algebra
+        );
+        assert_eq!(ethers::utils::hex::encode(&calldata), expected);
+    }
+
+    #[test]
+    fn test_multi_legs_require_a_quote_token_cycle() {
+        let mut opp = multi_test_opp();
+        let fees: Vec<u32> = multi_legs(&opp, multi_test_router).unwrap().iter().map(|l| l.fee).collect();
+        assert_eq!(fees, vec![500, crate::types::V2_FEE_SENTINEL, 0]);
+
+        // Broken chain: second leg no longer starts where the first ended
+        let legs = opp.route_legs.as_mut().unwrap();
+        legs[1].token_in = Address::from_low_u64_be(9);
+        assert!(multi_legs(&opp, multi_test_router).is_none());
+
+        // Two-leg opportunity (no route_legs) and a one-leg route
+        opp.route_legs = None;
+        assert!(multi_legs(&opp, multi_test_router).is_none());
+        let mut one = multi_test_opp();
+        one.route_legs.as_mut().unwrap().truncate(1);
+        assert!(multi_legs(&one, multi_test_router).is_none());
+    }
+
+    #[test]
+    fn test_parse_arb_multi_executed() {
+        let arb = Address::from_low_u64_be(0xab);
+        let word = |v: u64| H256::from_low_u64_be(v).as_bytes().to_vec();
+        let topic: H256 = ethers::utils::keccak256(b"ArbMultiExecuted(address,uint256,uint256,uint256,uint256)").into();
+        let multi_log = Log {
+            address: arb,
+            topics: vec![topic, H256::from(Address::from_low_u64_be(1))],
+            data: [word(500_000_000), word(503_000_000), word(3_000_000), word(3)].concat().into(),
+            ..Default::default()
+        };
+        // A two-leg ArbExecuted log and a foreign emitter are ignored
+        let two_leg = Log {
+            topics: vec![ethers::utils::keccak256(b"ArbExecuted(address,address,uint256,uint256,uint256,address,address)").into()],
+            ..multi_log.clone()
+        };
+        let foreign = Log { address: Address::from_low_u64_be(0xcd), ..multi_log.clone() };
+
+        assert_eq!(
+            parse_arb_multi_executed(&[two_leg.clone(), foreign, multi_log], arb),
+            Some((U256::from(503_000_000u64), U256::from(3_000_000u64)))
+        );
+        assert_eq!(parse_arb_multi_executed(&[two_leg], arb), None);
+    }
+
+    #[tokio::test]
+    async fn test_execute_atomic_multi_rejects_without_contract_or_legs() {
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.arb_executor_address = None;
+        let mut executor = tax_test_executor_with(config.clone());
+        let result = executor.execute_atomic_multi(&multi_test_opp()).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("ARB_EXECUTOR_ADDRESS"));
+
+        config.arb_executor_address = Some(Address::from_low_u64_be(0xab));
+        let mut executor = tax_test_executor_with(config);
+        let result = executor.execute_atomic_multi(&tax_test_opp()).await.unwrap();
+        assert!(!result.success && result.tx_hash.is_none());
+        assert!(result.error.unwrap().contains("route_legs"));
+    }
+
+    /// tax_test_opp with 30% of the bought WETH sold on SushiV3 0.30%
+    fn split_test_opp() -> ArbitrageOpportunity {
+        let mut opp = tax_test_opp();
+        opp.split_sell = Some(crate::types::SplitSell {
+            dex: DexType::SushiV3_030,
+            pool_address: Address::from_low_u64_be(3),
+            price: 0.000_397,
+            liquidity: Some(10u128.pow(18)),
+            share_bps: 3_000,
+        });
+        opp
+    }
+
+    #[test]
+    fn test_execute_arb_split_args_and_event() {
+        let router = |dex| match dex {
+            DexType::UniswapV3_005 => Address::from_low_u64_be(0xa1),
+            DexType::UniswapV3_030 => Address::from_low_u64_be(0xa2),
+            _ => Address::from_low_u64_be(0xa3),
+        };
+        let arb = execute_arb_split_args(&split_test_opp(), router, 1.0).unwrap();
+        assert_eq!(arb.token_0, Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap());
+        assert_eq!((arb.router_buy, arb.fee_buy), (Address::from_low_u64_be(0xa1), 500));
+        assert_eq!((arb.router_sell_a, arb.fee_sell_a), (Address::from_low_u64_be(0xa2), 3000));
+        assert_eq!((arb.router_sell_b, arb.fee_sell_b), (Address::from_low_u64_be(0xa3), 3000));
+        assert_eq!(arb.sell_share_bps, 3_000);
+        assert_eq!((arb.amount_in, arb.min_profit), (U256::from(500_000_000u64), U256::from(1_000_000u64)));
+        assert!(execute_arb_split_args(&tax_test_opp(), router, 1.0).is_none());
+
+        let address = Address::from_low_u64_be(0xab);
+        let word = |v: u64| H256::from_low_u64_be(v).as_bytes().to_vec();
+        let topic: H256 =
+            ethers::utils::keccak256(b"ArbSplitExecuted(address,address,uint256,uint256,uint256,uint16)").into();
+        let split_log = Log {
+            address,
+            topics: vec![topic, H256::from(Address::from_low_u64_be(1)), H256::from(Address::from_low_u64_be(2))],
+            data: [word(500_000_000), word(502_500_000), word(2_500_000), word(3_000)].concat().into(),
+            ..Default::default()
+        };
+        let two_leg = Log {
+            topics: vec![ethers::utils::keccak256(b"ArbExecuted(address,address,uint256,uint256,uint256,address,address)").into()],
+            ..split_log.clone()
+        };
+        assert_eq!(
+            parse_arb_split_executed(&[two_leg.clone(), split_log], address),
+            Some((U256::from(502_500_000u64), U256::from(2_500_000u64)))
+        );
+        assert_eq!(parse_arb_split_executed(&[two_leg], address), None);
+    }
+
+    #[tokio::test]
+    async fn test_execute_atomic_split_rejects_before_any_rpc() {
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.arb_executor_address = None;
+        let mut executor = tax_test_executor_with(config.clone());
+        executor.set_dry_run(false);
+        let result = executor.execute(&split_test_opp()).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("ARB_EXECUTOR_ADDRESS"));
+
+        // Detection-only second sell pool: never sent
+        config.arb_executor_address = Some(Address::from_low_u64_be(0xab));
+        let mut executor = tax_test_executor_with(config);
+        executor.set_dry_run(false);
+        let mut opp = split_test_opp();
+        opp.split_sell.as_mut().unwrap().dex = DexType::CurveStable;
+        let result = executor.execute_atomic_split(&opp).await.unwrap();
+        assert!(!result.success && result.tx_hash.is_none());
+        assert!(result.error.unwrap().contains("detection-only"));
+    }
+
+    #[test]
+    fn test_execute_arb_flash_calldata_matches_fixture() {
+        let (provider, _mock) = Provider::mocked();
+        let contract = IArbExecutor::new(Address::from_low_u64_be(0xab), Arc::new(provider));
+        let args = execute_arb_flash_args(
+            &tax_test_opp(),
+            Address::from_low_u64_be(0xaa),
+            Address::from_low_u64_be(0xa1),
+            Address::from_low_u64_be(0xa2),
+            1.0,
+        );
+        let calldata = contract.execute_arb_flash(args.aave_pool, args.arb).calldata().unwrap();
+
+        // executeArbFlash(address,(address,address,address,address,uint24,uint24,uint256,uint256)) —
+        // the static struct is encoded inline
+        let expected = concat!(
+            "a95e3acd",
+            "00000000000000000000000000000000000000000000000000000000000000aa", // aavePool
+            "0000000000000000000000002791bca1f2de4661ed88a30c99a7a9449aa84174", // token0 USDC
+            "0000000000000000000000007ceb23fd6bc0add59e62ac25578270cff1b9f619", // token1 WETH
+            "00000000000000000000000000000000000000000000000000000000000000a1",
+            "00000000000000000000000000000000000000000000000000000000000000a2",
+            "00000000000000000000000000000000000000000000000000000000000001f4", // feeBuy 500
+            "0000000000000000000000000000000000000000000000000000000000000bb8", // feeSell 3000
+            "000000000000000000000000000000000000000000000000000000001dcd6500", // amountIn 500 USDC
+            "00000000000000000000000000000000000000000000000000000000000f4240", // minProfit 1 USDC
+        );
+        assert_eq!(ethers::utils::hex::encode(&calldata), expected);
+    }
+
+    #[test]
+    fn test_parse_arb_flash_executed_nets_out_the_premium() {
+        let arb = Address::from_low_u64_be(0xab);
+        let word = |v: u64| H256::from_low_u64_be(v).as_bytes().to_vec();
+        let topic: H256 =
+            ethers::utils::keccak256(b"ArbFlashExecuted(address,address,uint256,uint256,uint256,uint256)").into();
+        let flash_log = Log {
+            address: arb,
+            topics: vec![topic, H256::from(Address::from_low_u64_be(1)), H256::from(Address::from_low_u64_be(2))],
+            data: [word(500_000_000), word(503_250_000), word(250_000), word(3_000_000)].concat().into(),
+            ..Default::default()
+        };
+        let wallet_funded = Log {
+            topics: vec![ethers::utils::keccak256(b"ArbExecuted(address,address,uint256,uint256,uint256,address,address)").into()],
+            ..flash_log.clone()
+        };
+
+        assert_eq!(
+            parse_arb_flash_executed(&[wallet_funded.clone(), flash_log], arb),
+            Some((U256::from(503_000_000u64), U256::from(3_000_000u64)))
+        );
+        assert_eq!(parse_arb_flash_executed(&[wallet_funded], arb), None);
+    }
+
+    #[test]
+    fn test_recovered_flash_receipt_books_the_premium() {
+        let arb = Address::from_low_u64_be(0xab);
+        let dir = tax_test_dir("recover_flash");
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.arb_executor_address = Some(arb);
+        let mut executor = tax_test_executor_with(config);
+        executor.init_tax_logging(dir.to_str().unwrap(), true).unwrap();
+
+        // 500 USDC borrowed, 503.25 out, 0.25 premium repaid, 3 USDC profit
+        let word = |v: u64| H256::from_low_u64_be(v).as_bytes().to_vec();
+        let flash_log = Log {
+            address: arb,
+            topics: vec![
+                ethers::utils::keccak256(b"ArbFlashExecuted(address,address,uint256,uint256,uint256,uint256)").into(),
+            ],
+            data: [word(500_000_000), word(503_250_000), word(250_000), word(3_000_000)].concat().into(),
+            ..Default::default()
+        };
+        let receipt = TransactionReceipt {
+            status: Some(U64::from(1)),
+            block_number: Some(U64::from(1_000u64)),
+            gas_used: Some(U256::from(300_000u64)),
+            effective_gas_price: Some(U256::from(50_000_000_000u64)),
+            logs: vec![flash_log],
+            ..Default::default()
+        };
+        let entry = InflightEntry::new(&tax_test_opp(), 5, TxHash::from_low_u64_be(0x105), Bytes::from(vec![0x02]), "atomic");
+
+        let result = executor.process_recovered_receipt(&entry, &receipt);
+        assert_eq!(result.amount_out.as_deref(), Some("503000000"));
+        assert!((result.profit_usd - 3.0).abs() < 1e-9, "profit {}", result.profit_usd);
+        assert!(result.success);
+
+        // The tax record nets out the premium, as the live flash path does
+        assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+        let records = TaxLogger::new(&dir).unwrap().read_all(chrono::Utc::now().year() as i16).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].amount_received, Decimal::from(503));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// tax_test_opp bought on a QuickSwap V2 pair (2,000,000 USDC / 1,010 WETH)
+    fn flash_v2_test_opp() -> ArbitrageOpportunity {
+        let mut opp = tax_test_opp();
+        opp.buy_dex = DexType::QuickSwapV2;
+        opp.buy_pool_address = Some(Address::from_low_u64_be(0x21));
+        let snapshot = crate::types::PoolStateSnapshot {
+            address: Address::from_low_u64_be(0x21),
+            dex: DexType::QuickSwapV2,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            fee: 3000,
+            state: crate::types::PoolStateKind::V2 {
+                reserve0: U256::from(2_000_000_000_000u64),
+                reserve1: U256::exp10(18) * 1010,
+            },
+            block: 100,
+            captured_at: 0,
+        };
+        opp.pool_snapshots = Some(crate::types::LegSnapshots { buy: snapshot.clone(), sell: snapshot });
+        opp.estimated_profit = 3.0;
+        opp.flash_v2_eligible = true;
+        opp
+    }
+
+    #[test]
+    fn test_execute_arb_flash_v2_calldata_matches_fixture() {
+        let (provider, _mock) = Provider::mocked();
+        let contract = IArbExecutor::new(Address::from_low_u64_be(0xab), Arc::new(provider));
+        let opp = flash_v2_test_opp();
+        let terms = flashloan::flash_v2_terms(&opp).unwrap();
+        let args = execute_arb_flash_v2_args(&opp, &terms, Address::from_low_u64_be(0xa2), 1.0);
+        let calldata = contract.execute_arb_flash_v2(args.pair, args.base_out, args.arb).calldata().unwrap();
+
+        let selector = ethers::utils::id(
+            "executeArbFlashV2(address,uint256,(address,address,address,address,uint24,uint24,uint256,uint256))",
+        );
+        assert_eq!(calldata[..4], selector[..]);
+        // Static struct encoded inline after pair and baseOut
+        let expected = concat!(
+            "0000000000000000000000000000000000000000000000000000000000000021", // pair
+            "000000000000000000000000000000000000000000000000037e256a9186dcbf", // baseOut = getAmountOut(500 USDC)
+            "0000000000000000000000002791bca1f2de4661ed88a30c99a7a9449aa84174", // token0 USDC
+            "0000000000000000000000007ceb23fd6bc0add59e62ac25578270cff1b9f619", // token1 WETH
+            "0000000000000000000000000000000000000000000000000000000000000000", // routerBuy unused
+            "00000000000000000000000000000000000000000000000000000000000000a2",
+            "0000000000000000000000000000000000000000000000000000000000ffffff", // feeBuy V2 sentinel
+            "0000000000000000000000000000000000000000000000000000000000000bb8", // feeSell 3000
+            "000000000000000000000000000000000000000000000000000000001dcd6500", // amountIn 500 USDC (owed cap)
+            "00000000000000000000000000000000000000000000000000000000000f4240", // minProfit 1 USDC
+        );
+        assert_eq!(ethers::utils::hex::encode(&calldata[4..]), expected);
+
+        let v2_flash_log = Log {
+            address: Address::from_low_u64_be(0xab),
+            topics: vec![
+                ethers::utils::keccak256(b"ArbFlashV2Executed(address,address,address,uint256,uint256,uint256)").into(),
+            ],
+            data: [0x21u64, 500_000_000, 503_000_000, 3_000_000]
+                .iter()
+                .flat_map(|v| H256::from_low_u64_be(*v).as_bytes().to_vec())
+                .collect::<Vec<u8>>()
+                .into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_arb_flash_v2_executed(&[v2_flash_log], Address::from_low_u64_be(0xab)),
+            Some((U256::from(503_000_000u64), U256::from(3_000_000u64)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flash_v2_selected_for_v2_buys_with_a_pair_address() {
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.flash_v2_enabled = true;
+        config.arb_executor_address = Some(Address::from_low_u64_be(0xab));
+        config.min_profit_usd = 1.0;
+        let mut executor = tax_test_executor_with(config.clone());
+        let opp = flash_v2_test_opp();
+        let Some(FlashCall::V2(flash, terms)) = executor.flash_funding(&opp).await else {
+            panic!("expected a V2 flash swap");
+        };
+        assert_eq!(flash.pair, Address::from_low_u64_be(0x21));
+        assert_eq!(flash.base_out, terms.base_out);
+
+        // No pool address, or FLASH_V2_ENABLED off: standard atomic path
+        let mut no_pair = opp.clone();
+        no_pair.buy_pool_address = None;
+        assert!(executor.flash_funding(&no_pair).await.is_none());
+        config.flash_v2_enabled = false;
+        let mut executor = tax_test_executor_with(config);
+        assert!(executor.flash_funding(&opp).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_flash_funding_falls_back_to_wallet_on_incomplete_config() {
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.flashloan_enabled = true;
+        config.arb_executor_address = Some(Address::from_low_u64_be(0xab));
+        config.aave_pool_address = None;
+        let mut executor = tax_test_executor_with(config.clone());
+        let mut opp = tax_test_opp();
+        opp.flash_eligible = true;
+        // Wallet holds 100 USDC of the 500 USDC size
+        executor.wallets.active_mut().exposure_mut().observe_balance(opp.quote_amount(U256::from(100_000_000u64)));
+        assert!(executor.flash_funding(&opp).await.is_none());
+
+        config.aave_pool_address = Some(Address::from_low_u64_be(0xaa));
+        config.min_profit_usd = 0.0;
+        let mut executor = tax_test_executor_with(config);
+        executor.wallets.active_mut().exposure_mut().observe_balance(opp.quote_amount(U256::from(100_000_000u64)));
+        opp.estimated_profit = 5.0;
+        let Some(FlashCall::Aave(flash)) = executor.flash_funding(&opp).await else {
+            panic!("expected an Aave flashloan");
+        };
+        assert_eq!(flash.aave_pool, Address::from_low_u64_be(0xaa));
+        assert_eq!(flash.arb.amount_in, opp.trade_size.raw());
+    }
 }
@@ -0,0 +1,312 @@
+//! Exposure Pacing — per-quote-token wallet exposure limits at dispatch
+//!
+//! Several trades in quick succession in one silo can draw the quote balance
+//! near zero, leaving nothing for the better opportunity a block later. At
+//! execution dispatch every trade is held to two fractions of the wallet's
+//! current quote token balance:
+//!   single    — trade_size ≤ balance × EXPOSURE_MAX_TRADE_FRACTION (0.4)
+//!   in-flight — committed + trade_size ≤ balance × EXPOSURE_MAX_INFLIGHT_FRACTION (0.8)
+//! `committed` sums the trade_size of in-flight journal entries in the same
+//! quote token (sent, receipt not yet processed). The wallet still holds those
+//! funds until the tx lands, so both limits are fractions of the same balance.
+//!
+//! A trade over either limit is scaled down to the tighter one: estimated
+//! profit and trade_size_usd are prorated (as the liquidity guard's downsize
+//! does) and the minProfit floor is recomputed at the quote token's decimals.
+//! It is rejected (TradeOutcome::ExposureLimited) when the scaled notional
+//! falls below EXPOSURE_MIN_NOTIONAL_USD or its profit below the minimum.
+//! Per-quote limits: EXPOSURE_OVERRIDES="USDC:0.4:0.8,WETH:0.25:0.5".
+//!
+//! Balances are cached per token from each preflight bundle and dropped on
+//! every submission; a miss costs one balanceOf.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Balances, committed and sizes as QuoteAmount (a cached balance
+//!                        only serves the same quote token at the same decimals)
+
+use super::inflight::InflightEntry;
+use crate::quote_amount::{QuoteAmount, QuoteToken};
+use crate::types::{ArbitrageOpportunity, BotConfig, ExposurePacing};
+use ethers::types::Address;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Prefix of every pacing rejection message (see TradeOutcome::classify)
+pub const EXPOSURE_PACING_PREFIX: &str = "Exposure pacing:";
+
+#[derive(Debug, Clone)]
+pub struct ExposureConfig {
+    pub enabled: bool,
+    pub max_trade_fraction: f64,
+    pub max_inflight_fraction: f64,
+    /// Quote symbol → (trade fraction, in-flight fraction)
+    pub overrides: HashMap<String, (f64, f64)>,
+    pub min_notional_usd: f64,
+}
+
+impl ExposureConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            enabled: config.exposure_pacing_enabled,
+            max_trade_fraction: config.exposure_max_trade_fraction,
+            max_inflight_fraction: config.exposure_max_inflight_fraction,
+            overrides: config.exposure_overrides.clone(),
+            min_notional_usd: config.exposure_min_notional_usd,
+        }
+    }
+
+    /// (trade fraction, in-flight fraction) for a quote token symbol
+    pub fn limits_for(&self, quote_symbol: &str) -> (f64, f64) {
+        self.overrides
+            .get(quote_symbol)
+            .copied()
+            .unwrap_or((self.max_trade_fraction, self.max_inflight_fraction))
+    }
+}
+
+/// Why a paced trade was not dispatched
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExposureRejection {
+    /// Scaled trade is smaller than the minimum viable notional
+    BelowMinNotional { notional_usd: f64, min_usd: f64 },
+    /// Scaled trade no longer clears the minimum profit
+    ScaledUnprofitable { estimated_profit: f64 },
+}
+
+impl fmt::Display for ExposureRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExposureRejection::BelowMinNotional { notional_usd, min_usd } => write!(
+                f, "{} scaled trade ${:.2} below minimum notional ${:.2}",
+                EXPOSURE_PACING_PREFIX, notional_usd, min_usd
+            ),
+            ExposureRejection::ScaledUnprofitable { estimated_profit } => write!(
+                f, "{} scaled trade est. profit ${:.4} below minimum",
+                EXPOSURE_PACING_PREFIX, estimated_profit
+            ),
+        }
+    }
+}
+
+/// Quote token committed to in-flight trades
+pub fn committed(entries: &[InflightEntry], quote: QuoteToken) -> QuoteAmount {
+    entries
+        .iter()
+        .filter(|e| e.opportunity.quote() == quote)
+        .fold(quote.zero(), |sum, e| sum + e.opportunity.trade_size)
+}
+
+/// Exposure limits + per-token balance cache
+pub struct ExposurePacer {
+    config: ExposureConfig,
+    balances: HashMap<Address, QuoteAmount>,
+}
+
+impl ExposurePacer {
+    pub fn new(config: ExposureConfig) -> Self {
+        Self { config, balances: HashMap::new() }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn cached_balance(&self, quote: QuoteToken) -> Option<QuoteAmount> {
+        self.balances.get(&quote.address).copied().filter(|b| b.token() == quote)
+    }
+
+    /// Wallet balance of its quote token as last read (preflight bundle / balanceOf)
+    pub fn observe_balance(&mut self, balance: QuoteAmount) {
+        self.balances.insert(balance.token().address, balance);
+    }
+
+    /// A submission moves funds: the next dispatch re-reads balances
+    pub fn invalidate_balances(&mut self) {
+        self.balances.clear();
+    }
+
+    /// Hold `opp` to the exposure limits, scaling trade_size down in place.
+    /// `min_profit_usd` is the minProfit the dispatch path will send.
+    pub fn pace(
+        &self,
+        opp: &mut ArbitrageOpportunity,
+        balance: QuoteAmount,
+        committed: QuoteAmount,
+        min_profit_usd: f64,
+    ) -> Result<ExposurePacing, ExposureRejection> {
+        let (max_trade_fraction, max_inflight_fraction) = self.config.limits_for(opp.quote_symbol());
+        let single_cap = balance.fraction(max_trade_fraction);
+        let inflight_cap = balance.fraction(max_inflight_fraction) - committed;
+        let requested = opp.trade_size;
+        let allowed = requested.min(single_cap).min(inflight_cap);
+
+        if allowed < requested {
+            let ratio = if requested.is_zero() {
+                0.0
+            } else {
+                allowed.raw().low_u128() as f64 / requested.raw().low_u128() as f64
+            };
+            let notional_usd = opp.trade_size_usd * ratio;
+            if notional_usd < self.config.min_notional_usd {
+                return Err(ExposureRejection::BelowMinNotional {
+                    notional_usd,
+                    min_usd: self.config.min_notional_usd,
+                });
+            }
+            let estimated_profit = opp.estimated_profit * ratio;
+            if estimated_profit < min_profit_usd {
+                return Err(ExposureRejection::ScaledUnprofitable { estimated_profit });
+            }
+            opp.trade_size = allowed;
+            opp.trade_size_usd = notional_usd;
+            opp.estimated_profit = estimated_profit;
+            // Pre-screen leg outputs were quoted for the full size
+            opp.quoted_legs = None;
+        }
+
+        let decision = ExposurePacing {
+            balance: balance.raw(),
+            committed: committed.raw(),
+            requested: requested.raw(),
+            allowed: allowed.raw(),
+            max_trade_fraction,
+            max_inflight_fraction,
+            min_profit_raw: opp.usd_to_quote(min_profit_usd).raw(),
+        };
+        opp.exposure_pacing = Some(decision.clone());
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::types::{DexType, TradingPair};
+    use ethers::types::{Bytes, TxHash, U256};
+
+    const USDC_DEC: u32 = 6;
+    const DAI_DEC: u32 = 18;
+
+    fn pacer() -> ExposurePacer {
+        let mut config = create_test_config();
+        config.exposure_pacing_enabled = true;
+        config.exposure_overrides.insert("DAI".to_string(), (0.25, 0.5));
+        ExposurePacer::new(ExposureConfig::from_bot_config(&config))
+    }
+
+    fn units(amount: u64, decimals: u32) -> U256 {
+        U256::from(amount) * U256::exp10(decimals as usize)
+    }
+
+    /// `whole` tokens of the quote token opp() uses for `quote`
+    fn amount(quote: &str, decimals: u8, whole: u64) -> QuoteAmount {
+        opp(quote, decimals, 0).quote_amount(units(whole, decimals as u32))
+    }
+
+    /// WETH/{quote} opportunity of `size` whole quote tokens, est. profit $2
+    fn opp(quote: &str, decimals: u8, size: u64) -> ArbitrageOpportunity {
+        let quote_addr = Address::from_low_u64_be(if quote == "DAI" { 0xda1 } else { 0x05dc });
+        let mut opp = ArbitrageOpportunity::new(
+            TradingPair::new(quote_addr, Address::from_low_u64_be(0xe7), format!("WETH/{}", quote)),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.000_400,
+            0.000_396,
+            units(size, decimals as u32),
+        );
+        opp.set_quote_layout(decimals, 18, true);
+        opp.trade_size_usd = size as f64;
+        opp.estimated_profit = 2.0;
+        opp
+    }
+
+    fn inflight(opp: &ArbitrageOpportunity, tx: u64) -> InflightEntry {
+        InflightEntry::new(opp, tx, TxHash::from_low_u64_be(tx), Bytes::default(), "atomic")
+    }
+
+    #[test]
+    fn test_single_trade_fraction() {
+        let pacer = pacer();
+        let balance = amount("USDC", 6, 1_000);
+        let none = balance.token().zero();
+
+        // 300 of 1000 USDC: within 40%
+        let mut small = opp("USDC", 6, 300);
+        let decision = pacer.pace(&mut small, balance, none, 0.5).unwrap();
+        assert!(!decision.scaled());
+        assert_eq!(small.trade_size, amount("USDC", 6, 300));
+        assert_eq!(small.exposure_pacing, Some(decision));
+
+        // 500 of 1000 USDC: capped at 400
+        let mut full = opp("USDC", 6, 500);
+        let decision = pacer.pace(&mut full, balance, none, 0.5).unwrap();
+        assert!(decision.scaled());
+        assert_eq!(decision.requested, units(500, USDC_DEC));
+        assert_eq!(full.trade_size.raw(), units(400, USDC_DEC));
+        assert!((full.trade_size_usd - 400.0).abs() < 1e-9);
+        assert!((full.estimated_profit - 1.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cumulative_inflight_accounting() {
+        let pacer = pacer();
+        let balance = amount("USDC", 6, 1_000);
+        let usdc = balance.token();
+
+        // Two 350 USDC trades in flight + one DAI trade (other quote token, not counted)
+        let entries = vec![
+            inflight(&opp("USDC", 6, 350), 1),
+            inflight(&opp("USDC", 6, 350), 2),
+            inflight(&opp("DAI", 18, 900), 3),
+        ];
+        let committed = committed(&entries, usdc);
+        assert_eq!(committed, amount("USDC", 6, 700));
+
+        // 80% of 1000 = 800, 700 committed → 100 left
+        let mut next = opp("USDC", 6, 300);
+        let decision = pacer.pace(&mut next, balance, committed, 0.5).unwrap();
+        assert_eq!(decision.allowed, units(100, USDC_DEC));
+        assert_eq!(next.trade_size, amount("USDC", 6, 100));
+
+        // A third commitment exhausts the budget: nothing left → below min notional
+        let entries = [entries, vec![inflight(&next, 4)]].concat();
+        let mut last = opp("USDC", 6, 300);
+        let rejected = pacer.pace(&mut last, balance, super::committed(&entries, usdc), 0.5).unwrap_err();
+        assert_eq!(rejected, ExposureRejection::BelowMinNotional { notional_usd: 0.0, min_usd: 10.0 });
+        assert!(rejected.to_string().starts_with(EXPOSURE_PACING_PREFIX));
+        // Rejected trades are left untouched
+        assert_eq!(last.trade_size, amount("USDC", 6, 300));
+        assert!(last.exposure_pacing.is_none());
+    }
+
+    #[test]
+    fn test_scale_down_recomputes_min_profit_per_decimals() {
+        let pacer = pacer();
+
+        // 6-decimal USDC: 40% of 1000
+        let mut usdc = opp("USDC", 6, 1_000);
+        let balance = amount("USDC", 6, 1_000);
+        let decision = pacer.pace(&mut usdc, balance, balance.token().zero(), 0.5).unwrap();
+        assert_eq!(usdc.trade_size.raw(), U256::from(400_000_000u64));
+        assert_eq!(decision.min_profit_raw, U256::from(500_000u64));
+        assert!((usdc.estimated_profit - 0.8).abs() < 1e-9);
+
+        // 18-decimal DAI with its override: 25% of 1000
+        let mut dai = opp("DAI", 18, 1_000);
+        let balance = amount("DAI", 18, 1_000);
+        let decision = pacer.pace(&mut dai, balance, balance.token().zero(), 0.25).unwrap();
+        assert_eq!((decision.max_trade_fraction, decision.max_inflight_fraction), (0.25, 0.5));
+        assert_eq!(dai.trade_size.raw(), units(250, DAI_DEC));
+        assert_eq!(decision.min_profit_raw, U256::from(250_000_000_000_000_000u64));
+        assert!((dai.trade_size_usd - 250.0).abs() < 1e-9);
+        assert!((dai.estimated_profit - 0.5).abs() < 1e-9);
+        assert!(dai.quoted_legs.is_none());
+
+        // Prorated profit below the path's minProfit → rejected
+        let mut thin = opp("DAI", 18, 1_000);
+        let rejected = pacer.pace(&mut thin, balance, balance.token().zero(), 1.0).unwrap_err();
+        assert!(matches!(rejected, ExposureRejection::ScaledUnprofitable { .. }));
+    }
+}
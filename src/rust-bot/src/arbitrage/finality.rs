@@ -0,0 +1,354 @@
+//! Trade Record Finality — provisional at first receipt, final at depth
+//!
+//! A receipt at depth 1 can still be reorged away (rare on Polygon, but it
+//! happens), yet profit, tax record and cooldown success used to be booked
+//! immediately. Now a landed trade is booked provisionally and re-checked once
+//! it is CONFIRMATION_DEPTH blocks deep:
+//!   Final    — receipt still in the recorded block: tax record flagged final,
+//!              ledger row "final"
+//!   Vanished — no receipt any more
+//!   Moved    — receipt now in another block (hash or number changed)
+//! Vanished/Moved reverse the provisional records: a correcting tax entry
+//! supersedes the original (flagged reversed), the ledger books the negated
+//! profit, main.rs revokes the route's cooldown success, and a critical alert
+//! is sent (a moved tx may have landed again and needs a manual look).
+//!
+//! Trade ledger: data/{chain}/trade_ledger.csv, append-only. Each row is a
+//! booking event; net_profit_usd is the amount the row books (provisional
+//! +net, final 0, reversed -net), so the column sums to booked profit.
+//!
+//! Pending trades: data/{chain}/finality_pending.json, rewritten whenever the
+//! set changes and restored by with_ledger, so a restart (graceful or not)
+//! still re-checks the trades it had booked provisionally.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - ProvisionalTrade carries the trade size (route cooldown buckets)
+//! Modified: 2026-10-16 - Pending trades persisted (finality_pending.json) and restored at startup
+
+use crate::types::DexType;
+use ethers::types::{TransactionReceipt, TxHash, H256};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+const LEDGER_HEADER: &str =
+    "timestamp,tx_hash,block_number,block_hash,pair,buy_dex,sell_dex,net_profit_usd,finality,note";
+
+/// A landed trade whose records are not final yet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvisionalTrade {
+    pub tx_hash: TxHash,
+    pub block_number: u64,
+    /// None when the path didn't keep the receipt (legacy two-tx)
+    pub block_hash: Option<H256>,
+    /// Year of the tax file the record went to
+    pub tax_year: i16,
+    pub pair: String,
+    pub buy_dex: DexType,
+    pub sell_dex: DexType,
+    /// Route cooldown size bucket a reversal revokes the success in
+    pub trade_size_usd: f64,
+    pub net_profit_usd: f64,
+}
+
+/// Verdict of the re-check at confirmation depth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalityCheck {
+    Final,
+    Vanished,
+    Moved { block_number: Option<u64> },
+}
+
+impl FinalityCheck {
+    /// Compare the receipt now with what was recorded at first receipt
+    pub fn from_receipt(trade: &ProvisionalTrade, receipt: Option<&TransactionReceipt>) -> Self {
+        let receipt = match receipt {
+            Some(r) => r,
+            None => return FinalityCheck::Vanished,
+        };
+        let block_number = receipt.block_number.map(|b| b.as_u64());
+        let hash_changed = trade.block_hash.is_some() && receipt.block_hash != trade.block_hash;
+        if hash_changed || block_number != Some(trade.block_number) {
+            FinalityCheck::Moved { block_number }
+        } else {
+            FinalityCheck::Final
+        }
+    }
+
+    /// Reversal reason for the tax note, ledger and alert
+    pub fn describe(self, trade: &ProvisionalTrade, depth: u64) -> String {
+        match self {
+            FinalityCheck::Final => format!("final at depth {}", depth),
+            FinalityCheck::Vanished => format!(
+                "tx vanished by depth {} (was in block {})", depth, trade.block_number
+            ),
+            FinalityCheck::Moved { block_number } => format!(
+                "tx moved from block {} to {} (reorg)",
+                trade.block_number,
+                block_number.map(|b| b.to_string()).unwrap_or_else(|| "pending".to_string())
+            ),
+        }
+    }
+}
+
+/// A provisional trade whose records were reversed
+#[derive(Debug, Clone)]
+pub struct Reversal {
+    pub trade: ProvisionalTrade,
+    pub reason: String,
+}
+
+/// Provisional trades awaiting depth + the trade ledger
+pub struct FinalityTracker {
+    depth: u64,
+    pending: Vec<ProvisionalTrade>,
+    ledger_path: Option<PathBuf>,
+    /// Handed out by take_due, not finalized/reversed/requeued yet
+    taken: Vec<ProvisionalTrade>,
+    /// finality_pending.json (None = not persisted)
+    pending_path: Option<PathBuf>,
+}
+
+impl FinalityTracker {
+    /// `depth` = 0 disables tracking (records final at first receipt)
+    pub fn new(depth: u64) -> Self {
+        Self { depth, pending: Vec::new(), ledger_path: None, taken: Vec::new(), pending_path: None }
+    }
+
+    /// Append booking events to `{data_dir}/trade_ledger.csv` and keep the
+    /// pending trades in `{data_dir}/finality_pending.json` (restored here)
+    pub fn with_ledger(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Finality: failed to create {}: {}", data_dir, e);
+        }
+        self.ledger_path = Some(dir.join("trade_ledger.csv"));
+        let path = dir.join("finality_pending.json");
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<Vec<ProvisionalTrade>>(&content) {
+                Ok(trades) => {
+                    if !trades.is_empty() {
+                        info!("Finality: {} provisional trade(s) restored from {}", trades.len(), path.display());
+                    }
+                    self.pending = trades;
+                }
+                Err(e) => warn!("Finality: ignoring unreadable {}: {}", path.display(), e),
+            }
+        }
+        self.pending_path = Some(path);
+        self
+    }
+
+    pub fn depth(&self) -> u64 {
+        self.depth
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.depth > 0
+    }
+
+    pub fn pending(&self) -> &[ProvisionalTrade] {
+        &self.pending
+    }
+
+    /// Book a landed trade provisionally
+    pub fn track(&mut self, trade: ProvisionalTrade) {
+        self.append(&trade, trade.net_profit_usd, "provisional", "");
+        self.pending.push(trade);
+        self.save_pending();
+    }
+
+    /// Remove and return the trades at least `depth` blocks deep. Each one
+    /// must be finalized, reversed or requeued; until then it stays in
+    /// finality_pending.json (a crash mid-check re-checks it after restart).
+    pub fn take_due(&mut self, current_block: u64) -> Vec<ProvisionalTrade> {
+        let depth = self.depth;
+        let (due, waiting) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|t| current_block >= t.block_number + depth);
+        self.pending = waiting;
+        self.taken.extend(due.iter().cloned());
+        due
+    }
+
+    /// Re-check failed (RPC): try again next block
+    pub fn requeue(&mut self, trade: ProvisionalTrade) {
+        self.settle(&trade);
+        self.pending.push(trade);
+    }
+
+    pub fn finalize(&mut self, trade: &ProvisionalTrade) {
+        self.append(trade, 0.0, "final", "");
+        self.settle(trade);
+        self.save_pending();
+    }
+
+    pub fn reverse(&mut self, trade: &ProvisionalTrade, reason: &str) -> Reversal {
+        self.append(trade, -trade.net_profit_usd, "reversed", reason);
+        self.settle(trade);
+        self.save_pending();
+        Reversal { trade: trade.clone(), reason: reason.to_string() }
+    }
+
+    /// Drop a trade handed out by take_due from the unsettled set
+    fn settle(&mut self, trade: &ProvisionalTrade) {
+        self.taken.retain(|t| t.tx_hash != trade.tx_hash);
+    }
+
+    /// Rewrite finality_pending.json: pending plus taken-but-unsettled trades
+    fn save_pending(&self) {
+        let path = match self.pending_path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let trades: Vec<&ProvisionalTrade> = self.pending.iter().chain(&self.taken).collect();
+        let json = match serde_json::to_string_pretty(&trades) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Finality: failed to serialize pending trades: {}", e);
+                return;
+            }
+        };
+        // Write-then-rename: a crash mid-write leaves the previous file intact
+        let tmp = path.with_extension("json.tmp");
+        if let Err(e) = std::fs::write(&tmp, json).and_then(|()| std::fs::rename(&tmp, path)) {
+            warn!("Finality: failed to write {}: {}", path.display(), e);
+        }
+    }
+
+    fn append(&self, trade: &ProvisionalTrade, booked_usd: f64, finality: &str, note: &str) {
+        let path = match self.ledger_path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let new_file = !path.exists();
+        let row = format!(
+            "{},{:?},{},{},{},{:?},{:?},{:.6},{},\"{}\"",
+            chrono::Utc::now().to_rfc3339(),
+            trade.tx_hash,
+            trade.block_number,
+            trade.block_hash.map(|h| format!("{:?}", h)).unwrap_or_default(),
+            trade.pair,
+            trade.buy_dex,
+            trade.sell_dex,
+            booked_usd,
+            finality,
+            note.replace('"', "'")
+        );
+        let written = OpenOptions::new().create(true).append(true).open(path).and_then(|mut f| {
+            if new_file {
+                writeln!(f, "{}", LEDGER_HEADER)?;
+            }
+            writeln!(f, "{}", row)
+        });
+        if let Err(e) = written {
+            warn!("Finality: failed to append to {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U64;
+
+    fn trade(block: u64) -> ProvisionalTrade {
+        ProvisionalTrade {
+            tx_hash: TxHash::from_low_u64_be(0xbeef),
+            block_number: block,
+            block_hash: Some(H256::from_low_u64_be(0xb1)),
+            tax_year: 2026,
+            pair: "WETH/USDC".to_string(),
+            buy_dex: DexType::UniswapV3_005,
+            sell_dex: DexType::UniswapV3_030,
+            trade_size_usd: 500.0,
+            net_profit_usd: 1.25,
+        }
+    }
+
+    fn receipt(block: u64, hash: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            block_number: Some(U64::from(block)),
+            block_hash: Some(H256::from_low_u64_be(hash)),
+            status: Some(U64::from(1)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_recheck_verdicts() {
+        let t = trade(1_000);
+        assert_eq!(FinalityCheck::from_receipt(&t, Some(&receipt(1_000, 0xb1))), FinalityCheck::Final);
+        assert_eq!(FinalityCheck::from_receipt(&t, None), FinalityCheck::Vanished);
+        // Same height, different block (sibling won the reorg)
+        assert_eq!(
+            FinalityCheck::from_receipt(&t, Some(&receipt(1_000, 0xb2))),
+            FinalityCheck::Moved { block_number: Some(1_000) }
+        );
+        assert_eq!(
+            FinalityCheck::from_receipt(&t, Some(&receipt(1_003, 0xb3))),
+            FinalityCheck::Moved { block_number: Some(1_003) }
+        );
+        // No recorded hash (legacy path): only the block number is compared
+        let legacy = ProvisionalTrade { block_hash: None, ..trade(1_000) };
+        assert_eq!(FinalityCheck::from_receipt(&legacy, Some(&receipt(1_000, 0xb2))), FinalityCheck::Final);
+        assert!(FinalityCheck::Vanished.describe(&t, 10).contains("vanished by depth 10"));
+    }
+
+    #[test]
+    fn test_due_at_depth_and_ledger_nets_reversal() {
+        let dir = std::env::temp_dir().join(format!("dexarb_finality_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut tracker = FinalityTracker::new(10).with_ledger(dir.to_str().unwrap());
+        tracker.track(trade(1_000));
+        tracker.track(ProvisionalTrade { tx_hash: TxHash::from_low_u64_be(0xcafe), ..trade(1_005) });
+
+        assert!(tracker.take_due(1_009).is_empty());
+        let due = tracker.take_due(1_010);
+        assert_eq!(due.len(), 1);
+        assert_eq!(tracker.pending().len(), 1);
+
+        let reversal = tracker.reverse(&due[0], "tx vanished by depth 10 (was in block 1000)");
+        assert_eq!(reversal.trade.block_number, 1_000);
+        let later = tracker.take_due(1_015).pop().unwrap();
+        tracker.finalize(&later);
+
+        let ledger = std::fs::read_to_string(dir.join("trade_ledger.csv")).unwrap();
+        let rows: Vec<Vec<&str>> = ledger.lines().skip(1).map(|l| l.split(',').collect()).collect();
+        assert_eq!(ledger.lines().next(), Some(LEDGER_HEADER));
+        let finality: Vec<&str> = rows.iter().map(|r| r[8]).collect();
+        assert_eq!(finality, vec!["provisional", "provisional", "reversed", "final"]);
+        // Booked profit: the reversed trade nets to zero, the final one stays
+        let booked: f64 = rows.iter().map(|r| r[7].parse::<f64>().unwrap()).sum();
+        assert!((booked - 1.25).abs() < 1e-9);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_pending_trades_survive_restart() {
+        let dir = std::env::temp_dir().join(format!("dexarb_finality_restart_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let data_dir = dir.to_str().unwrap();
+        let later = ProvisionalTrade { tx_hash: TxHash::from_low_u64_be(0xcafe), ..trade(1_005) };
+        let mut tracker = FinalityTracker::new(10).with_ledger(data_dir);
+        tracker.track(trade(1_000));
+        tracker.track(later.clone());
+        // Killed mid re-check: the taken trade was never settled
+        assert_eq!(tracker.take_due(1_010).len(), 1);
+        drop(tracker);
+
+        let mut restarted = FinalityTracker::new(10).with_ledger(data_dir);
+        assert_eq!(restarted.pending(), &[trade(1_000), later.clone()]);
+        let due = restarted.take_due(1_010).pop().unwrap();
+        restarted.finalize(&due);
+        drop(restarted);
+
+        // Only the unsettled trade comes back the second time
+        let again = FinalityTracker::new(10).with_ledger(data_dir);
+        assert_eq!(again.pending(), &[later]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
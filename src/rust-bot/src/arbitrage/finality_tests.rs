@@ -0,0 +1,270 @@
+//! Trade finality pipeline tests
+//!
+//! Drives a live atomic TradeExecutor with tax logging, the trade ledger and
+//! CONFIRMATION_DEPTH = 10 over a MockProvider: a landed trade is booked
+//! provisionally, and the re-check at depth either flags it final or — when
+//! the tx has vanished — writes the correcting tax entry, books the ledger
+//! reversal and posts a critical alert to the webhook.
+//!
+//! A tx that reverts on-chain books no trade: only a gas-only Fee record.
+//!
+//! MockProvider answers the last-pushed response first, so each test pushes
+//! the RPC responses it expects in reverse call order.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - reverted tx → one Fee tax record, counted in the yearly summary
+
+use super::detector::tests::create_test_config;
+use super::executor::TradeExecutor;
+use crate::alerts::AlertDispatcher;
+use crate::tax::{RecordFinality, TaxEventType, TaxRecord, TaxSummary};
+use crate::types::{ArbitrageOpportunity, DexType, TradingPair};
+use ethers::prelude::*;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+const WETH: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+/// Well-known test key (anvil account 0)
+const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const LANDED_BLOCK: u64 = 1_000;
+const DEPTH: u64 = 10;
+
+type MockExecutor = TradeExecutor<Provider<MockProvider>>;
+
+fn temp_dir(tag: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("dexarb_finality_exec_{}_{}", tag, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Live atomic executor: tax records in `dir/tax`, ledger in `dir`
+fn executor(dir: &Path) -> (MockExecutor, MockProvider) {
+    let mut config = create_test_config();
+    config.arb_executor_address = Some(Address::from_low_u64_be(0xa4b));
+    config.confirmation_depth = DEPTH;
+    let (provider, mock) = Provider::mocked();
+    let wallet = LocalWallet::from_str(TEST_KEY).unwrap();
+    let mut executor = TradeExecutor::new(Arc::new(provider), wallet, config);
+    executor.set_dry_run(false);
+    executor.set_base_fee(U256::from(30_000_000_000u64));
+    executor.init_tax_logging(dir.join("tax").to_str().unwrap(), false).unwrap();
+    executor.set_trade_ledger(dir.to_str().unwrap());
+    (executor, mock)
+}
+
+fn opportunity() -> ArbitrageOpportunity {
+    let usdc = Address::from_str(USDC).unwrap();
+    let weth = Address::from_str(WETH).unwrap();
+    let mut opp = ArbitrageOpportunity::new(
+        TradingPair::new(usdc, weth, "WETH/USDC".to_string()),
+        DexType::UniswapV3_005,
+        DexType::UniswapV3_030,
+        0.000_400,
+        0.000_396,
+        U256::from(500_000_000u64),
+    );
+    opp.set_quote_layout(6, 18, true);
+    opp
+}
+
+fn receipt(block_hash: u64) -> TransactionReceipt {
+    TransactionReceipt {
+        status: Some(U64::from(1u64)),
+        block_number: Some(U64::from(LANDED_BLOCK)),
+        block_hash: Some(H256::from_low_u64_be(block_hash)),
+        gas_used: Some(U256::from(300_000u64)),
+        effective_gas_price: Some(U256::from(50_000_000_000u64)),
+        ..Default::default()
+    }
+}
+
+/// Land one atomic trade in LANDED_BLOCK; returns its tx hash
+async fn land_trade(executor: &mut MockExecutor, mock: &MockProvider, tx: u64, first: bool) -> TxHash {
+    mock.push(receipt(0xb1)).unwrap();
+    mock.push(H256::from_low_u64_be(tx)).unwrap(); // eth_sendRawTransaction
+    mock.push(U256::from(350_000u64)).unwrap(); // eth_estimateGas
+    if first {
+        mock.push(U256::from(7u64)).unwrap(); // eth_getTransactionCount (then cached)
+    }
+    let result = executor.execute(&opportunity()).await.unwrap();
+    assert_eq!(result.block_number, Some(LANDED_BLOCK));
+    result.tx_hash.unwrap().parse().unwrap()
+}
+
+fn tax_records(dir: &Path) -> Vec<TaxRecord> {
+    let tax_dir = dir.join("tax");
+    std::fs::read_dir(&tax_dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|x| x == "jsonl"))
+        .flat_map(|p| {
+            std::fs::read_to_string(p)
+                .unwrap()
+                .lines()
+                .map(|l| serde_json::from_str::<TaxRecord>(l).unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Accept one webhook POST and return the raw request
+async fn receive_webhook(listener: &TcpListener) -> String {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = socket.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&request);
+        let complete = text.split_once("\r\n\r\n").is_some_and(|(head, body)| {
+            head.lines()
+                .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                .and_then(|v| v.parse::<usize>().ok())
+                .is_some_and(|len| body.len() >= len)
+        });
+        if n == 0 || complete {
+            break;
+        }
+    }
+    socket.write_all(b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\n\r\n").await.unwrap();
+    String::from_utf8_lossy(&request).to_string()
+}
+
+/// The tx vanished by depth: correcting tax entry, ledger reversal, critical alert
+#[tokio::test]
+async fn test_vanished_trade_reversed_at_depth() {
+    let dir = temp_dir("vanished");
+    let (mut executor, mock) = executor(&dir);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/alert", listener.local_addr().unwrap());
+    executor.set_alert_dispatcher(AlertDispatcher::new(Some(url)));
+
+    let tx_hash = land_trade(&mut executor, &mock, 0xbeef, true).await;
+    assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+    let records = tax_records(&dir);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].finality, RecordFinality::Provisional);
+    assert_eq!(executor.provisional_trades(), 1);
+
+    // Not deep enough yet: no re-check RPC at all
+    assert!(executor.confirm_provisional_trades(LANDED_BLOCK + DEPTH - 1).await.is_empty());
+    assert_eq!(executor.provisional_trades(), 1);
+
+    // At depth the receipt is gone
+    mock.push(serde_json::Value::Null).unwrap();
+    let reversals = executor.confirm_provisional_trades(LANDED_BLOCK + DEPTH).await;
+    assert_eq!(reversals.len(), 1);
+    assert_eq!(reversals[0].trade.tx_hash, tx_hash);
+    assert_eq!(reversals[0].trade.pair, "WETH/USDC");
+    assert!(reversals[0].reason.contains("vanished by depth 10"));
+    assert_eq!(executor.provisional_trades(), 0);
+
+    // Tax: original flagged reversed, correcting entry supersedes it and nets to zero
+    assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+    let records = tax_records(&dir);
+    assert_eq!(records.len(), 2);
+    let (original, correction) = (&records[0], &records[1]);
+    assert_eq!(original.finality, RecordFinality::Reversed);
+    assert_eq!(correction.supersedes.as_deref(), Some(original.trade_id.as_str()));
+    assert_eq!(correction.transaction_hash, format!("{:?}", tx_hash));
+    assert_eq!(original.capital_gain_loss + correction.capital_gain_loss, rust_decimal::Decimal::ZERO);
+    assert!(correction.notes.as_deref().unwrap().contains("vanished"));
+    assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+    let csv: String = std::fs::read_dir(dir.join("tax"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|x| x == "csv"))
+        .map(|p| std::fs::read_to_string(p).unwrap())
+        .collect();
+    assert!(csv.lines().nth(1).unwrap().ends_with(",reversed,"));
+    assert!(csv.lines().nth(2).unwrap().ends_with(&format!(",final,{}", original.trade_id)));
+
+    // Ledger: provisional then reversed, booked profit back to zero
+    let ledger = std::fs::read_to_string(dir.join("trade_ledger.csv")).unwrap();
+    let rows: Vec<Vec<&str>> = ledger.lines().skip(1).map(|l| l.split(',').collect()).collect();
+    assert_eq!(rows.iter().map(|r| r[8]).collect::<Vec<_>>(), vec!["provisional", "reversed"]);
+    assert!(rows.iter().all(|r| r[1] == format!("{:?}", tx_hash)));
+    let booked: f64 = rows.iter().map(|r| r[7].parse::<f64>().unwrap()).sum();
+    assert!(booked.abs() < 1e-9);
+
+    // Critical alert reached the webhook
+    let request = tokio::time::timeout(Duration::from_secs(5), receive_webhook(&listener)).await.unwrap();
+    assert!(request.starts_with("POST /alert"));
+    assert!(request.contains("[CRITICAL] Trade REVERSED"), "{}", request);
+    assert!(request.contains(&format!("{:?}", tx_hash)));
+
+    // A second trade that stays in its block finalizes quietly
+    let second = land_trade(&mut executor, &mock, 0xcafe, false).await;
+    mock.push(TransactionReceipt { transaction_hash: second, ..receipt(0xb1) }).unwrap();
+    assert!(executor.confirm_provisional_trades(LANDED_BLOCK + DEPTH).await.is_empty());
+    assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+    let records = tax_records(&dir);
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[2].finality, RecordFinality::Final);
+    assert_eq!(records[2].transaction_hash, format!("{:?}", second));
+    let ledger = std::fs::read_to_string(dir.join("trade_ledger.csv")).unwrap();
+    assert!(ledger.lines().last().unwrap().contains(",final,"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// A failed re-check lookup keeps the trade provisional for the next block
+#[tokio::test]
+async fn test_recheck_rpc_error_retries_next_block() {
+    let dir = temp_dir("rpc_error");
+    let (mut executor, mock) = executor(&dir);
+    land_trade(&mut executor, &mock, 0xbeef, true).await;
+
+    // Nothing queued → the mock errors
+    assert!(executor.confirm_provisional_trades(LANDED_BLOCK + DEPTH).await.is_empty());
+    assert_eq!(executor.provisional_trades(), 1);
+    assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+    assert_eq!(tax_records(&dir)[0].finality, RecordFinality::Provisional);
+
+    mock.push(receipt(0xb1)).unwrap();
+    assert!(executor.confirm_provisional_trades(LANDED_BLOCK + DEPTH + 1).await.is_empty());
+    assert_eq!(executor.provisional_trades(), 0);
+    assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+    assert_eq!(tax_records(&dir)[0].finality, RecordFinality::Final);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// A reverted tx writes one Fee record (gas only) and no provisional trade
+#[tokio::test]
+async fn test_reverted_tx_books_fee_record() {
+    let dir = temp_dir("reverted");
+    let (mut executor, mock) = executor(&dir);
+    mock.push(TransactionReceipt { status: Some(U64::from(0u64)), ..receipt(0xb1) }).unwrap();
+    mock.push(H256::from_low_u64_be(0xdead)).unwrap(); // eth_sendRawTransaction
+    mock.push(U256::from(350_000u64)).unwrap(); // eth_estimateGas
+    mock.push(U256::from(7u64)).unwrap(); // eth_getTransactionCount
+    let result = executor.execute(&opportunity()).await.unwrap();
+    assert!(!result.success);
+    assert_eq!(executor.provisional_trades(), 0);
+
+    assert!(executor.flush_tax_writes(Duration::from_secs(5)));
+    let records = tax_records(&dir);
+    assert_eq!(records.len(), 1);
+    let fee = &records[0];
+    assert_eq!(fee.transaction_type, TaxEventType::Fee);
+    assert_eq!(fee.finality, RecordFinality::Final);
+    assert_eq!(fee.transaction_hash, format!("{:?}", H256::from_low_u64_be(0xdead)));
+    assert_eq!(fee.block_number, LANDED_BLOCK);
+    // 300_000 gas × 50 gwei at the test config's 0.50 USD native price
+    let gas_native = rust_decimal::Decimal::from_str("0.015").unwrap();
+    assert_eq!(fee.gas_fee_native, gas_native);
+    assert_eq!(fee.gas_fee_usd, rust_decimal::Decimal::from_str("0.0075").unwrap());
+    assert_eq!((fee.proceeds_usd, fee.cost_basis_usd), (rust_decimal::Decimal::ZERO, rust_decimal::Decimal::ZERO));
+    assert!(fee.notes.as_deref().unwrap().contains("reverted"));
+
+    let mut summary = TaxSummary::new(fee.tax_year);
+    summary.add_record(fee);
+    assert_eq!(summary.total_fees, fee.gas_fee_usd);
+    assert_eq!(summary.total_proceeds, rust_decimal::Decimal::ZERO);
+    let _ = std::fs::remove_dir_all(&dir);
+}
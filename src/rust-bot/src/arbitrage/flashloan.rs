@@ -0,0 +1,352 @@
+//! Flashloan Funding — Aave V3 flashLoanSimple around the atomic arb
+//!
+//! Wallet-funded trades are capped by the wallet's quote balance (exposure
+//! pacing, preflight balance check). With FLASHLOAN_ENABLED a route the wallet
+//! cannot fund borrows its whole size instead: ArbExecutor.executeArbFlash
+//! takes a flashLoanSimple from the Aave V3 pool (AAVE_POOL_ADDRESS), runs
+//! both legs in the callback and repays amount + premium. The contract
+//! reverts unless amountOut >= amountIn + premium + minProfit, so minProfit
+//! stays the same net floor as a wallet-funded trade.
+//!
+//! The 0.05% premium is a cost of the route: the detector marks an
+//! opportunity flash_eligible only if its estimated profit still clears
+//! MIN_PROFIT_USD after it, and the executor re-checks at dispatch (sizing
+//! may have changed the size since). Anything that rules the flashloan out
+//! leaves the trade on the wallet-funded path with its capped size.
+//!
+//! V2 flash swap (FLASH_V2_ENABLED): when the buy leg is a V2 pair no loan is
+//! needed at all. ArbExecutor.executeArbFlashV2 asks the pair for the base
+//! first (swap with callback data), sells it in uniswapV2Call and pays the
+//! pair back in the quote token. The pair's k check charges its 0.3% on that
+//! repayment, so the quote owed for `base_out` is getAmountIn — in effect
+//! amount_out × 1000/997 at the pair's price. That repayment replaces the
+//! buy leg's cost: a route is flash-V2 eligible only if its estimated profit
+//! clears the floor after any excess of the owed amount over trade_size.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Legacy-only (DODO) routes never flash-eligible
+//! Modified: 2026-10-16 - V2 flash swap: owed amount from the buy pair's reserves, flash_v2_eligible routes
+
+use crate::quote_amount::QuoteAmount;
+use crate::types::{ArbitrageOpportunity, BotConfig, PoolStateKind};
+use ethers::types::{Address, U256};
+use std::fmt;
+
+/// Aave V3 flashloan premium (FLASHLOAN_PREMIUM_TOTAL = 5 bps = 0.05%)
+pub const AAVE_FLASHLOAN_PREMIUM_BPS: u64 = 5;
+
+/// Premium Aave charges for borrowing `amount` (percentMul: rounded half up)
+pub fn flash_premium(amount: QuoteAmount) -> QuoteAmount {
+    let premium = (amount.raw() * U256::from(AAVE_FLASHLOAN_PREMIUM_BPS) + U256::from(5_000u64)) / U256::from(10_000u64);
+    amount.with_raw(premium)
+}
+
+/// Estimated profit (USD) left after the flashloan premium on the full size
+pub fn flash_net_profit_usd(opp: &ArbitrageOpportunity) -> f64 {
+    opp.estimated_profit - opp.quote_to_usd(flash_premium(opp.trade_size))
+}
+
+/// Detector pass: flag routes that stay profitable when borrowed.
+/// Returns how many were flagged.
+pub fn mark_flash_eligible(opportunities: &mut [ArbitrageOpportunity], min_profit_usd: f64) -> usize {
+    let mut marked = 0;
+    for opp in opportunities.iter_mut() {
+        // Flash funding runs through ArbExecutor: legacy-only routes never qualify
+        opp.flash_eligible = !opp.is_legacy_only()
+            && flash_net_profit_usd(opp) >= opp.min_profit_usd.unwrap_or(min_profit_usd);
+        marked += opp.flash_eligible as usize;
+    }
+    marked
+}
+
+/// Uniswap V2 pair fee on the input side: 997 / 1000 (0.3%)
+const V2_FEE_NUMERATOR: u64 = 997;
+const V2_FEE_DENOMINATOR: u64 = 1000;
+
+/// UniswapV2Library.getAmountOut: base out of the pair for `amount_in` quote
+pub fn v2_amount_out(amount_in: U256, reserve_in: U256, reserve_out: U256) -> U256 {
+    let amount_in_with_fee = amount_in * U256::from(V2_FEE_NUMERATOR);
+    let denominator = reserve_in * U256::from(V2_FEE_DENOMINATOR) + amount_in_with_fee;
+    (amount_in_with_fee * reserve_out).checked_div(denominator).unwrap_or_default()
+}
+
+/// UniswapV2Library.getAmountIn: quote the pair must receive to release
+/// `amount_out` base — what a flash swap owes back. None when the pair
+/// cannot release that much.
+pub fn v2_flash_owed(amount_out: U256, reserve_in: U256, reserve_out: U256) -> Option<U256> {
+    if amount_out.is_zero() || amount_out >= reserve_out || reserve_in.is_zero() {
+        return None;
+    }
+    let numerator = reserve_in * amount_out * U256::from(V2_FEE_DENOMINATOR);
+    let denominator = (reserve_out - amount_out) * U256::from(V2_FEE_NUMERATOR);
+    Some(numerator / denominator + U256::one())
+}
+
+/// One V2 flash swap: the buy pair, the base it releases and the quote owed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlashV2Terms {
+    pub pair: Address,
+    pub base_out: U256,
+    pub owed: QuoteAmount,
+}
+
+/// Flash swap terms for a route bought on a V2 pair, from the buy leg's pool
+/// snapshot. base_out is the pre-screen buy quote when there is one (the
+/// amount the sell leg was quoted on), else getAmountOut(trade_size). None
+/// for non-V2 buys and routes without the pair address or its reserves.
+pub fn flash_v2_terms(opp: &ArbitrageOpportunity) -> Option<FlashV2Terms> {
+    if !opp.buy_dex.is_v2() || opp.is_legacy_only() {
+        return None;
+    }
+    let pair = opp.buy_pool_address?;
+    let snapshot = &opp.pool_snapshots.as_ref()?.buy;
+    let PoolStateKind::V2 { reserve0, reserve1 } = snapshot.state else {
+        return None;
+    };
+    let (reserve_quote, reserve_base) = if opp.quote_token_is_token0 {
+        (reserve0, reserve1)
+    } else {
+        (reserve1, reserve0)
+    };
+    let base_out = match opp.quoted_legs.filter(|q| !q.buy_out.is_zero()) {
+        Some(quoted) => quoted.buy_out,
+        None => v2_amount_out(opp.trade_size.raw(), reserve_quote, reserve_base),
+    };
+    let owed = v2_flash_owed(base_out, reserve_quote, reserve_base)?;
+    Some(FlashV2Terms { pair, base_out, owed: opp.trade_size.with_raw(owed) })
+}
+
+/// Estimated profit (USD) once the pair is repaid: the estimate already paid
+/// trade_size for the buy, so only an owed amount above it is a further cost
+pub fn flash_v2_net_profit_usd(opp: &ArbitrageOpportunity, terms: &FlashV2Terms) -> f64 {
+    let extra = terms.owed.raw().saturating_sub(opp.trade_size.raw());
+    opp.estimated_profit - opp.quote_to_usd(opp.trade_size.with_raw(extra))
+}
+
+/// Detector pass: flag V2-bought routes that stay profitable as a flash swap.
+/// Returns how many were flagged.
+pub fn mark_flash_v2_eligible(opportunities: &mut [ArbitrageOpportunity], min_profit_usd: f64) -> usize {
+    let mut marked = 0;
+    for opp in opportunities.iter_mut() {
+        opp.flash_v2_eligible = flash_v2_terms(opp).is_some_and(|terms| {
+            flash_v2_net_profit_usd(opp, &terms) >= opp.min_profit_usd.unwrap_or(min_profit_usd)
+        });
+        marked += opp.flash_v2_eligible as usize;
+    }
+    marked
+}
+
+/// The V2 flash swap to run, or None for the standard atomic path
+/// (FLASH_V2_ENABLED off, route not flagged, or no pair/reserves to swap on)
+pub fn flash_v2_decision(config: &BotConfig, opp: &ArbitrageOpportunity) -> Option<FlashV2Terms> {
+    if !config.flash_v2_enabled || !opp.flash_v2_eligible || config.arb_executor_address.is_none() {
+        return None;
+    }
+    let terms = flash_v2_terms(opp)?;
+    (flash_v2_net_profit_usd(opp, &terms) >= opp.min_profit_usd.unwrap_or(config.min_profit_usd)).then_some(terms)
+}
+
+/// Why a flash-eligible trade stays wallet-funded
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlashFallback {
+    /// FLASHLOAN_ENABLED off, or the detector did not flag the route
+    NotEligible,
+    /// Flashloans enabled but a required address is missing
+    Incomplete(&'static str),
+    /// Wallet balance could not be read — the capped path checks it instead
+    BalanceUnknown,
+    /// The wallet covers the full size; no premium to pay
+    WalletCovers { balance: QuoteAmount },
+    /// The premium at the current size eats the profit floor
+    PremiumUnprofitable { net_profit_usd: f64 },
+}
+
+impl fmt::Display for FlashFallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FlashFallback::NotEligible => write!(f, "not flash-eligible"),
+            FlashFallback::Incomplete(missing) => write!(f, "flashloan config incomplete: {} not set", missing),
+            FlashFallback::BalanceUnknown => write!(f, "wallet balance unknown"),
+            FlashFallback::WalletCovers { balance } => write!(f, "wallet balance {} covers the size", balance),
+            FlashFallback::PremiumUnprofitable { net_profit_usd } => {
+                write!(f, "net ${:.4} after the flashloan premium", net_profit_usd)
+            }
+        }
+    }
+}
+
+/// The Aave pool to borrow from, or why the trade stays wallet-funded.
+/// `wallet_balance` = the quote token balance (None = read failed).
+pub fn flash_decision(
+    config: &BotConfig,
+    opp: &ArbitrageOpportunity,
+    wallet_balance: Option<QuoteAmount>,
+) -> Result<Address, FlashFallback> {
+    if !config.flashloan_enabled || !opp.flash_eligible {
+        return Err(FlashFallback::NotEligible);
+    }
+    if config.arb_executor_address.is_none() {
+        return Err(FlashFallback::Incomplete("ARB_EXECUTOR_ADDRESS"));
+    }
+    let aave_pool = config.aave_pool_address.ok_or(FlashFallback::Incomplete("AAVE_POOL_ADDRESS"))?;
+    let balance = wallet_balance.ok_or(FlashFallback::BalanceUnknown)?;
+    if balance >= opp.trade_size {
+        return Err(FlashFallback::WalletCovers { balance });
+    }
+    let net_profit_usd = flash_net_profit_usd(opp);
+    if net_profit_usd < config.min_profit_usd {
+        return Err(FlashFallback::PremiumUnprofitable { net_profit_usd });
+    }
+    Ok(aave_pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::types::{DexType, LegSnapshots, PoolStateSnapshot, QuotedLegs, TradingPair};
+
+    /// 10,000 USDC route with $8 estimated profit
+    fn opp() -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.000_400,
+            0.000_396,
+            U256::from(10_000_000_000u64),
+        );
+        opp.set_quote_layout(6, 18, true);
+        opp.estimated_profit = 8.0;
+        opp
+    }
+
+    fn flash_config() -> BotConfig {
+        let mut config = create_test_config();
+        config.flashloan_enabled = true;
+        config.arb_executor_address = Some(Address::from_low_u64_be(0xab));
+        config.aave_pool_address = Some(Address::from_low_u64_be(0xaa));
+        config.min_profit_usd = 2.0;
+        config
+    }
+
+    #[test]
+    fn test_premium_rounds_half_up_and_reduces_profit() {
+        let o = opp();
+        assert_eq!(flash_premium(o.trade_size).raw(), U256::from(5_000_000u64)); // 5 USDC
+        assert_eq!(flash_premium(o.quote_amount(U256::from(1_000u64))).raw(), U256::from(1u64)); // 0.5 → 1
+        assert_eq!(flash_premium(o.quote_amount(U256::from(999u64))).raw(), U256::zero());
+        assert!((flash_net_profit_usd(&o) - 3.0).abs() < 1e-9);
+
+        let mut opps = vec![o.clone(), o];
+        opps[1].estimated_profit = 6.0; // $1 after the premium
+        assert_eq!(mark_flash_eligible(&mut opps, 2.0), 1);
+        assert!(opps[0].flash_eligible && !opps[1].flash_eligible);
+    }
+
+    #[test]
+    fn test_flash_decision_borrows_only_what_the_wallet_cannot_fund() {
+        let config = flash_config();
+        let mut o = opp();
+        o.flash_eligible = true;
+        let quote = o.quote();
+        let balance = |raw: u64| Some(quote.amount(U256::from(raw)));
+
+        assert_eq!(flash_decision(&config, &o, balance(1_000_000_000)), Ok(Address::from_low_u64_be(0xaa)));
+        assert!(matches!(
+            flash_decision(&config, &o, balance(10_000_000_000)),
+            Err(FlashFallback::WalletCovers { .. })
+        ));
+        assert_eq!(flash_decision(&config, &o, None), Err(FlashFallback::BalanceUnknown));
+
+        // Incomplete config or an unflagged route: wallet-funded
+        let mut no_pool = config.clone();
+        no_pool.aave_pool_address = None;
+        assert_eq!(flash_decision(&no_pool, &o, balance(0)), Err(FlashFallback::Incomplete("AAVE_POOL_ADDRESS")));
+        let mut no_arb = config.clone();
+        no_arb.arb_executor_address = None;
+        assert_eq!(flash_decision(&no_arb, &o, balance(0)), Err(FlashFallback::Incomplete("ARB_EXECUTOR_ADDRESS")));
+        let mut unflagged = o.clone();
+        unflagged.flash_eligible = false;
+        assert_eq!(flash_decision(&config, &unflagged, balance(0)), Err(FlashFallback::NotEligible));
+
+        // Sizing since detection: the premium now eats the floor
+        o.estimated_profit = 6.5;
+        assert!(matches!(
+            flash_decision(&config, &o, balance(0)),
+            Err(FlashFallback::PremiumUnprofitable { .. })
+        ));
+    }
+
+    /// 500 USDC bought on a QuickSwap V2 pair holding 2,000,000 USDC / 1,010 WETH
+    fn v2_opp() -> ArbitrageOpportunity {
+        let mut o = opp();
+        o.buy_dex = DexType::QuickSwapV2;
+        o.trade_size = o.quote_amount(U256::from(500_000_000u64));
+        o.trade_size_usd = 500.0;
+        o.estimated_profit = 3.0;
+        o.buy_pool_address = Some(Address::from_low_u64_be(0x21));
+        let snapshot = |address: u64, dex: DexType| PoolStateSnapshot {
+            address: Address::from_low_u64_be(address),
+            dex,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            fee: 3000,
+            state: PoolStateKind::V2 {
+                reserve0: U256::from(2_000_000_000_000u64),
+                reserve1: U256::exp10(18) * 1010,
+            },
+            block: 100,
+            captured_at: 0,
+        };
+        o.pool_snapshots = Some(LegSnapshots {
+            buy: snapshot(0x21, DexType::QuickSwapV2),
+            sell: snapshot(0x22, DexType::SushiSwapV2),
+        });
+        o
+    }
+
+    #[test]
+    fn test_v2_flash_owed_is_get_amount_in() {
+        // 1000 * 100 * 1000 / (900 * 997) + 1 = 111 + 1
+        assert_eq!(v2_flash_owed(U256::from(100), U256::from(1_000), U256::from(1_000)), Some(U256::from(112)));
+        assert_eq!(v2_flash_owed(U256::from(1_000), U256::from(1_000), U256::from(1_000)), None);
+        assert_eq!(v2_flash_owed(U256::zero(), U256::from(1_000), U256::from(1_000)), None);
+
+        // Owed for getAmountOut(x) is x: the pair's fee is the buy leg's own fee
+        let terms = flash_v2_terms(&v2_opp()).unwrap();
+        assert_eq!(terms.pair, Address::from_low_u64_be(0x21));
+        assert_eq!(terms.base_out, U256::from(251_679_768_817_622_207u64));
+        assert_eq!(terms.owed.raw(), U256::from(500_000_000u64));
+        assert!((flash_v2_net_profit_usd(&v2_opp(), &terms) - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_flash_v2_cutoff_counts_owed_above_trade_size() {
+        let mut opps = vec![v2_opp(), v2_opp(), v2_opp(), v2_opp()];
+        // Sell leg quoted on 0.3% more base than 500 USDC buys: 501.500375 USDC owed
+        opps[1].quoted_legs = Some(QuotedLegs {
+            buy_out: U256::from(252_434_808_124_075_073u64),
+            sell_out: U256::from(504_000_000u64),
+        });
+        let terms = flash_v2_terms(&opps[1]).unwrap();
+        assert_eq!(terms.owed.raw(), U256::from(501_500_375u64));
+        assert!((flash_v2_net_profit_usd(&opps[1], &terms) - 1.499_625).abs() < 1e-9);
+        // No pair address: standard atomic path
+        opps[2].buy_pool_address = None;
+        // Not bought on V2
+        opps[3].buy_dex = DexType::UniswapV3_005;
+
+        assert_eq!(mark_flash_v2_eligible(&mut opps, 2.0), 1);
+        assert!(opps[0].flash_v2_eligible);
+        assert!(!opps[1].flash_v2_eligible && !opps[2].flash_v2_eligible && !opps[3].flash_v2_eligible);
+
+        let mut config = flash_config();
+        config.flash_v2_enabled = true;
+        assert_eq!(flash_v2_decision(&config, &opps[0]).map(|t| t.pair), Some(Address::from_low_u64_be(0x21)));
+        assert_eq!(flash_v2_decision(&config, &opps[2]), None);
+        config.flash_v2_enabled = false;
+        assert_eq!(flash_v2_decision(&config, &opps[0]), None);
+    }
+}
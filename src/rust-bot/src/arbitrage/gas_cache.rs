@@ -0,0 +1,227 @@
+//! Gas Estimate Cache — per-route gas limits from our own receipts
+//!
+//! Every atomic submission used to call estimateGas inside fill_transaction
+//! (~50–80ms and one RPC per trade), yet the gas a given route burns through
+//! ArbExecutor barely moves between trades. This cache keeps the last
+//! GAS_CACHE_WINDOW receipts' gas_used per route and hands out
+//!
+//!   gas limit = max(window) × GAS_CACHE_MULTIPLIER
+//!
+//! as the tx's gas field, so fill_transaction skips estimateGas. A miss (new
+//! route, or cache disabled) falls back to estimateGas as before.
+//!
+//! Route key: slippage::route_key ("PAIR BuyDex→SellDex"), with " flash"
+//! appended for flash-funded sends (the Aave callback adds a lot of gas).
+//! A route's entry is dropped when one of its txs reverts on-chain, so the
+//! next attempt re-estimates instead of trusting a possibly stale limit.
+//!
+//! Persisted to data/{chain}/gas_cache.json after every change.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - permit_gas_route_key (" permit" routes: Permit2 transfer gas)
+//! Modified: 2026-10-16 - flash_v2_gas_route_key (" flash-v2" routes: pair swap + callback gas)
+
+use super::slippage::route_key;
+use crate::types::ArbitrageOpportunity;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+use tracing::{debug, info, warn};
+
+/// Receipts kept per route
+pub const GAS_CACHE_WINDOW: usize = 20;
+
+/// Default safety multiplier on the largest observed gas_used
+pub const GAS_CACHE_MULTIPLIER: f64 = 1.2;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GasCacheFile {
+    saved_at: String,
+    routes: BTreeMap<String, VecDeque<u64>>,
+}
+
+/// Cache key for an atomic send of `opp` (wallet- or flash-funded)
+pub fn gas_route_key(opp: &ArbitrageOpportunity, flash: bool) -> String {
+    if flash {
+        format!("{} flash", route_key(opp))
+    } else {
+        route_key(opp)
+    }
+}
+
+/// executeArbWithPermit: executeArb plus Permit2's signature check and transfer
+pub fn permit_gas_route_key(opp: &ArbitrageOpportunity) -> String {
+    format!("{} permit", route_key(opp))
+}
+
+/// executeArbFlashV2: the buy leg is the pair's swap and uniswapV2Call
+pub fn flash_v2_gas_route_key(opp: &ArbitrageOpportunity) -> String {
+    format!("{} flash-v2", route_key(opp))
+}
+
+/// Observed gas_used per route → gas limits that skip estimateGas
+pub struct GasEstimateCache {
+    multiplier: f64,
+    routes: BTreeMap<String, VecDeque<u64>>,
+    path: Option<PathBuf>,
+}
+
+impl GasEstimateCache {
+    pub fn new(multiplier: f64) -> Self {
+        Self { multiplier: multiplier.max(1.0), routes: BTreeMap::new(), path: None }
+    }
+
+    /// Load and save the cache in `{data_dir}/gas_cache.json`
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Gas cache: failed to create {}: {}", data_dir, e);
+        }
+        let path = dir.join("gas_cache.json");
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<GasCacheFile>(&content) {
+                Ok(file) => {
+                    info!("Gas cache: {} route(s) restored (saved {})", file.routes.len(), file.saved_at);
+                    self.routes = file.routes;
+                }
+                Err(e) => warn!("Gas cache: ignoring unreadable {}: {}", path.display(), e),
+            }
+        }
+        self.path = Some(path);
+        self
+    }
+
+    /// Gas limit for `route`, or None (miss → estimateGas)
+    pub fn estimate(&self, route: &str) -> Option<u64> {
+        let max = self.routes.get(route)?.iter().copied().max()?;
+        Some((max as f64 * self.multiplier).round() as u64)
+    }
+
+    /// Gas used by a successful tx on `route`
+    pub fn record(&mut self, route: &str, gas_used: u64) {
+        if gas_used == 0 {
+            return;
+        }
+        let samples = self.routes.entry(route.to_string()).or_default();
+        samples.push_back(gas_used);
+        while samples.len() > GAS_CACHE_WINDOW {
+            samples.pop_front();
+        }
+        self.save();
+    }
+
+    /// A tx on `route` reverted: forget it so the next send re-estimates
+    pub fn invalidate(&mut self, route: &str) {
+        if self.routes.remove(route).is_some() {
+            debug!("Gas cache: {} invalidated after revert", route);
+            self.save();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    fn save(&self) {
+        let path = match self.path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let file = GasCacheFile {
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            routes: self.routes.clone(),
+        };
+        match serde_json::to_string_pretty(&file) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    warn!("Gas cache: failed to write {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => warn!("Gas cache: failed to serialize: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+    use ethers::types::{Address, U256};
+
+    fn opp() -> ArbitrageOpportunity {
+        ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::QuickswapV3,
+            0.000_400,
+            0.000_396,
+            U256::from(500_000_000u64),
+        )
+    }
+
+    #[test]
+    fn test_multiplier_window_and_revert_invalidation() {
+        let mut cache = GasEstimateCache::new(GAS_CACHE_MULTIPLIER);
+        let route = gas_route_key(&opp(), false);
+        let flash = gas_route_key(&opp(), true);
+        assert_eq!(route, "WETH/USDC UniswapV3_005→QuickswapV3");
+        assert_eq!(cache.estimate(&route), None);
+
+        // Largest recent gas_used × 1.2
+        cache.record(&route, 180_000);
+        cache.record(&route, 200_000);
+        cache.record(&route, 0); // no receipt gas: ignored
+        assert_eq!(cache.estimate(&route), Some(240_000));
+        assert_eq!(cache.estimate(&flash), None);
+
+        // The spike ages out of the window
+        for _ in 0..GAS_CACHE_WINDOW {
+            cache.record(&route, 150_000);
+        }
+        assert_eq!(cache.estimate(&route), Some(180_000));
+
+        // Revert on the route: miss until a new receipt lands
+        cache.record(&flash, 300_000);
+        cache.invalidate(&route);
+        assert_eq!(cache.estimate(&route), None);
+        assert_eq!(cache.estimate(&flash), Some(360_000));
+
+        // Multiplier never below 1×
+        let mut tight = GasEstimateCache::new(0.5);
+        tight.record(&route, 100_000);
+        assert_eq!(tight.estimate(&route), Some(100_000));
+    }
+
+    #[test]
+    fn test_persistence_round_trip() {
+        let dir = std::env::temp_dir().join(format!("dexarb_gas_cache_test_{}", std::process::id()));
+        let dir_str = dir.to_string_lossy().to_string();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let route = gas_route_key(&opp(), false);
+        let mut cache = GasEstimateCache::new(1.2).with_persistence(&dir_str);
+        cache.record(&route, 210_000);
+        cache.record(&gas_route_key(&opp(), true), 320_000);
+
+        let restored = GasEstimateCache::new(1.2).with_persistence(&dir_str);
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored.estimate(&route), Some(252_000));
+
+        // Invalidation persists too
+        let mut restored = restored;
+        restored.invalidate(&route);
+        let reloaded = GasEstimateCache::new(1.2).with_persistence(&dir_str);
+        assert_eq!(reloaded.estimate(&route), None);
+        assert_eq!(reloaded.len(), 1);
+
+        // Unreadable file: empty cache
+        std::fs::write(dir.join("gas_cache.json"), "{oops").unwrap();
+        assert!(GasEstimateCache::new(1.2).with_persistence(&dir_str).is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
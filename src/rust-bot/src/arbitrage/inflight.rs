@@ -0,0 +1,330 @@
+//! In-Flight Transaction Journal
+//!
+//! If the process dies after eth_sendRawTransaction succeeds but before the
+//! receipt is processed, a restart has no memory of the tx: it may detect the
+//! same (partially consumed) spread and submit again, reuse the nonce, and the
+//! trade never reaches the tax ledger.
+//!
+//! Before every atomic/mempool submission the executor persists one entry —
+//! opportunity fingerprint, nonce, signed tx hash, raw signed bytes, timestamp,
+//! and the opportunity itself (for the tax record) — to
+//! data/{chain}/inflight_journal.json. Every write goes to a temp file that is
+//! fsync'd and renamed over the journal, so a crash leaves either the old or
+//! the new journal, never a torn one. A failed journal write aborts the
+//! submission. The entry is cleared when the receipt is fully processed
+//! (including tax logging) or the send fails; a receipt timeout leaves it.
+//!
+//! On startup TradeExecutor::recover_inflight() resolves surviving entries
+//! before any trading (see resolve()):
+//!   Mined    — receipt found: resume receipt processing (tax record), clear
+//!   Pending  — node knows the tx, unmined: rebroadcast or discard per policy
+//!   Dropped  — node doesn't know it: rebroadcast or discard per policy;
+//!              always discarded once another tx has used its nonce
+//! and only then initializes the tracked nonce from chain state plus the
+//! surviving entries (initial_nonce()). With several wallets (PRIVATE_KEYS)
+//! each entry records its signer and every wallet's nonce is recovered from
+//! its own entries.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Entries carry their signing wallet (per-wallet recovery)
+
+use crate::types::ArbitrageOpportunity;
+use anyhow::{anyhow, Context, Result};
+use ethers::types::{Address, Bytes, TransactionReceipt, TxHash};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tracing::info;
+
+/// Journal file name inside the chain data directory
+pub const JOURNAL_FILE: &str = "inflight_journal.json";
+
+/// What to do with a surviving entry whose tx is not mined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryPolicy {
+    /// Rebroadcast the journaled raw bytes (same nonce, same hash) and keep
+    /// the entry. Atomic txs revert if the spread is gone — gas only.
+    Resubmit,
+    /// Forget the entry. A still-pending tx keeps its nonce via the chain's
+    /// pending count, but a late receipt will not be tax-logged.
+    Discard,
+}
+
+impl FromStr for RecoveryPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "resubmit" => Ok(RecoveryPolicy::Resubmit),
+            "discard" => Ok(RecoveryPolicy::Discard),
+            other => Err(anyhow!("unknown in-flight recovery policy '{}' (resubmit|discard)", other)),
+        }
+    }
+}
+
+/// One submitted-but-unprocessed transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InflightEntry {
+    /// pair:buy->sell:size:detected_at — identifies the opportunity in logs
+    pub fingerprint: String,
+    pub nonce: u64,
+    pub tx_hash: TxHash,
+    pub raw_tx: Bytes,
+    /// Unix seconds when the entry was written (just before the send)
+    pub submitted_at: u64,
+    /// "atomic" or "mempool"
+    pub path: String,
+    pub opportunity: ArbitrageOpportunity,
+    /// Signing wallet; None in entries written before PRIVATE_KEYS (primary)
+    #[serde(default)]
+    pub wallet: Option<Address>,
+}
+
+impl InflightEntry {
+    pub fn new(
+        opportunity: &ArbitrageOpportunity,
+        nonce: u64,
+        tx_hash: TxHash,
+        raw_tx: Bytes,
+        path: &str,
+    ) -> Self {
+        Self {
+            fingerprint: fingerprint(opportunity),
+            nonce,
+            tx_hash,
+            raw_tx,
+            submitted_at: chrono::Utc::now().timestamp() as u64,
+            path: path.to_string(),
+            opportunity: opportunity.clone(),
+            wallet: None,
+        }
+    }
+
+    pub fn with_wallet(mut self, wallet: Address) -> Self {
+        self.wallet = Some(wallet);
+        self
+    }
+}
+
+/// Stable identity of an opportunity for journal entries and logs
+pub fn fingerprint(opp: &ArbitrageOpportunity) -> String {
+    format!(
+        "{}:{:?}->{:?}:{}:{}",
+        opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.trade_size.raw(), opp.timestamp
+    )
+}
+
+/// Where a journaled tx stands on-chain at startup
+#[derive(Debug, Clone)]
+pub enum ChainStatus {
+    Mined(Box<TransactionReceipt>),
+    /// Known to the node, not yet mined
+    Pending,
+    /// Unknown to the node (dropped, or never propagated)
+    Dropped,
+}
+
+impl ChainStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChainStatus::Mined(_) => "mined",
+            ChainStatus::Pending => "pending",
+            ChainStatus::Dropped => "dropped",
+        }
+    }
+}
+
+/// What recovery does with one entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Process the receipt (tax record), then clear
+    ProcessReceipt,
+    /// Rebroadcast the raw bytes, keep the entry
+    Resubmit,
+    /// Clear the entry
+    Discard,
+}
+
+/// Decide what to do with a surviving entry. `latest_nonce` is the wallet's
+/// mined tx count: above the entry's nonce with no receipt for our hash means
+/// another tx took the nonce, so rebroadcasting can never land.
+pub fn resolve(status: &ChainStatus, entry_nonce: u64, latest_nonce: u64, policy: RecoveryPolicy) -> Resolution {
+    match status {
+        ChainStatus::Mined(_) => Resolution::ProcessReceipt,
+        ChainStatus::Dropped if latest_nonce > entry_nonce => Resolution::Discard,
+        ChainStatus::Pending | ChainStatus::Dropped => match policy {
+            RecoveryPolicy::Resubmit => Resolution::Resubmit,
+            RecoveryPolicy::Discard => Resolution::Discard,
+        },
+    }
+}
+
+/// First nonce to use after recovery: the chain's pending count, or past the
+/// highest surviving entry if the node hasn't seen it (a rebroadcast that
+/// hasn't propagated yet must not be overwritten).
+pub fn initial_nonce(chain_pending: u64, surviving: &[InflightEntry]) -> u64 {
+    surviving
+        .iter()
+        .map(|e| e.nonce + 1)
+        .fold(chain_pending, u64::max)
+}
+
+/// Outcome of startup recovery, for the startup log
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RecoveryReport {
+    pub mined: usize,
+    pub resubmitted: usize,
+    pub discarded: usize,
+    /// Tracked nonce after recovery
+    pub nonce: u64,
+}
+
+/// Fsync'd journal of submitted transactions awaiting receipt processing
+pub struct InflightJournal {
+    path: PathBuf,
+    entries: Vec<InflightEntry>,
+}
+
+impl InflightJournal {
+    /// Open (or create) `{data_dir}/inflight_journal.json`. An unreadable
+    /// journal is an error — starting blind is exactly what it exists to prevent.
+    pub fn open(data_dir: &str) -> Result<Self> {
+        let dir = PathBuf::from(data_dir);
+        fs::create_dir_all(&dir).with_context(|| format!("creating {}", data_dir))?;
+        let path = dir.join(JOURNAL_FILE);
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) if !content.trim().is_empty() => serde_json::from_str(&content)
+                .with_context(|| format!("unreadable in-flight journal {}", path.display()))?,
+            Ok(_) => Vec::new(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(anyhow!("reading {}: {}", path.display(), e)),
+        };
+        let journal = Self { path, entries };
+        if !journal.entries.is_empty() {
+            info!("In-flight journal: {} surviving entries in {}", journal.entries.len(), journal.path.display());
+        }
+        Ok(journal)
+    }
+
+    pub fn entries(&self) -> &[InflightEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Persist an entry. Must succeed before the tx is sent.
+    pub fn record(&mut self, entry: InflightEntry) -> Result<()> {
+        self.entries.retain(|e| e.tx_hash != entry.tx_hash);
+        self.entries.push(entry);
+        self.persist()
+    }
+
+    /// Drop the entry for `tx_hash` (receipt processed, send failed, or discarded)
+    pub fn clear(&mut self, tx_hash: TxHash) -> Result<()> {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.tx_hash != tx_hash);
+        if self.entries.len() == before {
+            return Ok(());
+        }
+        self.persist()
+    }
+
+    /// Write to a temp file, fsync, rename over the journal, fsync the directory
+    fn persist(&self) -> Result<()> {
+        let tmp = self.path.with_extension("json.tmp");
+        let json = serde_json::to_vec_pretty(&self.entries)?;
+        let mut file = File::create(&tmp).with_context(|| format!("creating {}", tmp.display()))?;
+        file.write_all(&json)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path).with_context(|| format!("renaming to {}", self.path.display()))?;
+        if let Some(dir) = self.path.parent().and_then(|d| File::open(d).ok()) {
+            dir.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+    use ethers::types::{Address, U256};
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dexarb_inflight_{}_{}", tag, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn entry(nonce: u64) -> InflightEntry {
+        let opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.000_400,
+            0.000_396,
+            U256::from(500_000_000u64),
+        );
+        InflightEntry::new(&opp, nonce, TxHash::from_low_u64_be(nonce), Bytes::from(vec![0x02, nonce as u8]), "atomic")
+    }
+
+    #[test]
+    fn test_journal_write_clear_and_reopen() {
+        let dir = temp_dir("rw");
+        let mut journal = InflightJournal::open(dir.to_str().unwrap()).unwrap();
+        assert!(journal.is_empty());
+        journal.record(entry(7)).unwrap();
+        journal.record(entry(8)).unwrap();
+
+        // Survives a restart byte-for-byte
+        let reopened = InflightJournal::open(dir.to_str().unwrap()).unwrap();
+        assert_eq!(reopened.entries().len(), 2);
+        assert_eq!(reopened.entries()[0].raw_tx, Bytes::from(vec![0x02, 7]));
+        assert_eq!(reopened.entries()[0].opportunity.pair.symbol, "WETH/USDC");
+        assert!(reopened.entries()[0].fingerprint.starts_with("WETH/USDC:UniswapV3_005->UniswapV3_030:500000000:"));
+
+        journal.clear(TxHash::from_low_u64_be(7)).unwrap();
+        let reopened = InflightJournal::open(dir.to_str().unwrap()).unwrap();
+        assert_eq!(reopened.entries().iter().map(|e| e.nonce).collect::<Vec<_>>(), vec![8]);
+        assert!(!dir.join("inflight_journal.json.tmp").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_corrupt_journal_refuses_to_open() {
+        let dir = temp_dir("corrupt");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(JOURNAL_FILE), "[{\"nonce\": 7").unwrap();
+        assert!(InflightJournal::open(dir.to_str().unwrap()).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_resolution_policy() {
+        let mined = ChainStatus::Mined(Box::default());
+        for policy in [RecoveryPolicy::Resubmit, RecoveryPolicy::Discard] {
+            assert_eq!(resolve(&mined, 7, 8, policy), Resolution::ProcessReceipt);
+        }
+        assert_eq!(resolve(&ChainStatus::Pending, 7, 7, RecoveryPolicy::Resubmit), Resolution::Resubmit);
+        assert_eq!(resolve(&ChainStatus::Pending, 7, 7, RecoveryPolicy::Discard), Resolution::Discard);
+        assert_eq!(resolve(&ChainStatus::Dropped, 7, 7, RecoveryPolicy::Resubmit), Resolution::Resubmit);
+        // Nonce taken by another tx: rebroadcast can never land
+        assert_eq!(resolve(&ChainStatus::Dropped, 7, 8, RecoveryPolicy::Resubmit), Resolution::Discard);
+        assert_eq!("Resubmit".parse::<RecoveryPolicy>().unwrap(), RecoveryPolicy::Resubmit);
+        assert!("retry".parse::<RecoveryPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_initial_nonce_covers_surviving_entries() {
+        assert_eq!(initial_nonce(7, &[]), 7);
+        // Node already counts our pending tx
+        assert_eq!(initial_nonce(9, &[entry(8)]), 9);
+        // Rebroadcast not yet visible to the node
+        assert_eq!(initial_nonce(7, &[entry(7), entry(8)]), 9);
+    }
+}
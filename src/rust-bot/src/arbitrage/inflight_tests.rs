@@ -0,0 +1,228 @@
+//! In-flight journal pipeline tests
+//!
+//! Drives a live-mode TradeExecutor with a journal over a MockProvider:
+//! entries are written before the send and cleared once the receipt is
+//! processed (kept on a receipt timeout), and startup recovery resolves
+//! mined, pending and dropped survivors before initializing the nonce.
+//!
+//! MockProvider answers the last-pushed response first, so each test pushes
+//! the RPC responses it expects in reverse call order. Recovery calls:
+//!   eth_getTransactionCount(latest) → per entry: eth_getTransactionReceipt
+//!   [→ eth_getTransactionByHash [→ eth_sendRawTransaction]]
+//!   → eth_getTransactionCount(pending)
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use super::detector::tests::create_test_config;
+use super::executor::TradeExecutor;
+use super::inflight::{InflightEntry, InflightJournal, RecoveryPolicy, RecoveryReport};
+use crate::chaos::{ChaosConfig, ChaosInjector, Fault, FaultPoint, FaultRule};
+use crate::types::{ArbitrageOpportunity, DexType, TradingPair};
+use ethers::prelude::*;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+const WETH: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+/// Well-known test key (anvil account 0)
+const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const PENDING_NONCE: u64 = 7;
+
+type MockExecutor = TradeExecutor<Provider<MockProvider>>;
+
+fn temp_dir(tag: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("dexarb_inflight_exec_{}_{}", tag, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Live atomic executor journaling into `dir`
+fn executor(dir: &Path) -> (MockExecutor, MockProvider) {
+    let mut config = create_test_config();
+    config.arb_executor_address = Some(Address::from_low_u64_be(0xa4b));
+    let (provider, mock) = Provider::mocked();
+    let wallet = LocalWallet::from_str(TEST_KEY).unwrap();
+    let mut executor = TradeExecutor::new(Arc::new(provider), wallet, config);
+    executor.set_dry_run(false);
+    executor.set_base_fee(U256::from(30_000_000_000u64));
+    executor.set_inflight_journal(InflightJournal::open(dir.to_str().unwrap()).unwrap());
+    (executor, mock)
+}
+
+fn opportunity() -> ArbitrageOpportunity {
+    let usdc = Address::from_str(USDC).unwrap();
+    let weth = Address::from_str(WETH).unwrap();
+    let mut opp = ArbitrageOpportunity::new(
+        TradingPair::new(usdc, weth, "WETH/USDC".to_string()),
+        DexType::UniswapV3_005,
+        DexType::UniswapV3_030,
+        0.000_400,
+        0.000_396,
+        U256::from(500_000_000u64),
+    );
+    opp.set_quote_layout(6, 18, true);
+    opp
+}
+
+fn receipt(status: u64) -> TransactionReceipt {
+    TransactionReceipt {
+        status: Some(U64::from(status)),
+        block_number: Some(U64::from(1_000u64)),
+        gas_used: Some(U256::from(300_000u64)),
+        effective_gas_price: Some(U256::from(50_000_000_000u64)),
+        ..Default::default()
+    }
+}
+
+/// Queue a successful public-path send (reverse call order)
+fn push_send(mock: &MockProvider) {
+    mock.push(H256::from_low_u64_be(0xbeef)).unwrap(); // eth_sendRawTransaction
+    mock.push(U256::from(350_000u64)).unwrap(); // eth_estimateGas
+    mock.push(U256::from(PENDING_NONCE)).unwrap(); // eth_getTransactionCount
+}
+
+/// Journal entry as a previous process would have left it
+fn survivor(journal_dir: &Path, nonce: u64) -> InflightEntry {
+    let entry = InflightEntry::new(
+        &opportunity(),
+        nonce,
+        TxHash::from_low_u64_be(0x100 + nonce),
+        Bytes::from(vec![0x02, nonce as u8]),
+        "atomic",
+    );
+    let mut journal = InflightJournal::open(journal_dir.to_str().unwrap()).unwrap();
+    journal.record(entry.clone()).unwrap();
+    entry
+}
+
+fn no_tx(mock: &MockProvider) {
+    mock.push(serde_json::Value::Null).unwrap();
+}
+
+/// Processed receipt clears the entry; a receipt timeout keeps it
+#[tokio::test(start_paused = true)]
+async fn test_submission_journaled_and_cleared() {
+    let dir = temp_dir("submit");
+    let (mut executor, mock) = executor(&dir);
+    let opp = opportunity();
+
+    mock.push(receipt(1)).unwrap();
+    push_send(&mock);
+    let result = executor.execute(&opp).await.unwrap();
+    assert!(result.tx_hash.is_some() && result.block_number == Some(1_000));
+    assert!(executor.inflight_entries().is_empty());
+
+    // Nonce 8, no receipt within 30s → survives on disk for the next start
+    executor.set_fault_injector(Arc::new(ChaosInjector::new(ChaosConfig {
+        seed: 0,
+        rules: vec![FaultRule {
+            point: FaultPoint::AfterSend,
+            fault: Fault::Timeout,
+            on_calls: vec![],
+            every: None,
+            probability: None,
+            max_fires: None,
+        }],
+    })));
+    mock.push(H256::from_low_u64_be(0xbeef)).unwrap();
+    mock.push(U256::from(350_000u64)).unwrap();
+    let result = executor.execute(&opp).await.unwrap();
+    assert!(result.error.unwrap().contains("Receipt timeout"));
+    let journal = InflightJournal::open(dir.to_str().unwrap()).unwrap();
+    assert_eq!(journal.entries().len(), 1);
+    let entry = &journal.entries()[0];
+    assert_eq!(entry.nonce, PENDING_NONCE + 1);
+    assert_eq!(entry.path, "atomic");
+    assert_eq!(entry.opportunity.pair.symbol, "WETH/USDC");
+    assert_eq!(ethers::utils::keccak256(&entry.raw_tx), entry.tx_hash.0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// A send failure leaves nothing in the journal
+#[tokio::test(start_paused = true)]
+async fn test_failed_send_clears_entry() {
+    let dir = temp_dir("sendfail");
+    let (mut executor, mock) = executor(&dir);
+
+    mock.push_response(MockResponse::Error(JsonRpcError {
+        code: -32000,
+        message: "nonce too low".into(),
+        data: None,
+    }));
+    mock.push(U256::from(350_000u64)).unwrap();
+    mock.push(U256::from(PENDING_NONCE)).unwrap();
+    let result = executor.execute(&opportunity()).await.unwrap();
+    assert!(result.tx_hash.is_none());
+    assert!(executor.inflight_entries().is_empty());
+    assert!(InflightJournal::open(dir.to_str().unwrap()).unwrap().is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Mined → processed + cleared; pending and dropped → rebroadcast and kept;
+/// nonce starts past the surviving entries the node hasn't counted yet.
+#[tokio::test(start_paused = true)]
+async fn test_recovery_resubmit_policy() {
+    let dir = temp_dir("resubmit");
+    let mined = survivor(&dir, 5);
+    let pending = survivor(&dir, 6);
+    let dropped = survivor(&dir, 7);
+    let (mut executor, mock) = executor(&dir);
+
+    mock.push(U256::from(7u64)).unwrap(); // pending count: 5 mined + 6 pending
+    mock.push(dropped.tx_hash).unwrap(); // rebroadcast 7
+    no_tx(&mock); // 7 unknown to the node
+    no_tx(&mock); // 7 no receipt
+    mock.push(pending.tx_hash).unwrap(); // rebroadcast 6
+    mock.push(Transaction { hash: pending.tx_hash, ..Default::default() }).unwrap();
+    no_tx(&mock); // 6 no receipt
+    mock.push(receipt(1)).unwrap(); // 5 mined
+    mock.push(U256::from(6u64)).unwrap(); // latest count
+
+    let report = executor.recover_inflight(RecoveryPolicy::Resubmit).await.unwrap();
+    assert_eq!(report, RecoveryReport { mined: 1, resubmitted: 2, discarded: 0, nonce: 8 });
+    assert_eq!(executor.tracked_nonce(), Some(8));
+    let left: Vec<_> = executor.inflight_entries().iter().map(|e| e.tx_hash).collect();
+    assert_eq!(left, vec![pending.tx_hash, dropped.tx_hash]);
+    assert!(!left.contains(&mined.tx_hash));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Discard forgets unmined entries; a dropped entry whose nonce another tx
+/// used is discarded without consulting the policy. Nonce = chain pending.
+#[tokio::test(start_paused = true)]
+async fn test_recovery_discard_policy_and_nonce_taken() {
+    let dir = temp_dir("discard");
+    let replaced = survivor(&dir, 5);
+    let pending = survivor(&dir, 6);
+    let (mut executor, mock) = executor(&dir);
+
+    mock.push(U256::from(7u64)).unwrap(); // pending count
+    mock.push(Transaction { hash: pending.tx_hash, ..Default::default() }).unwrap();
+    no_tx(&mock); // 6 no receipt
+    no_tx(&mock); // 5 unknown
+    no_tx(&mock); // 5 no receipt
+    mock.push(U256::from(6u64)).unwrap(); // latest: nonce 5 used by another tx
+
+    let report = executor.recover_inflight(RecoveryPolicy::Discard).await.unwrap();
+    assert_eq!(report, RecoveryReport { mined: 0, resubmitted: 0, discarded: 2, nonce: 7 });
+    assert!(executor.inflight_entries().is_empty());
+    assert!(InflightJournal::open(dir.to_str().unwrap()).unwrap().is_empty());
+    assert_ne!(replaced.tx_hash, pending.tx_hash);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Empty journal: nonce initialized from the pending count alone
+#[tokio::test(start_paused = true)]
+async fn test_recovery_empty_journal_initializes_nonce() {
+    let dir = temp_dir("empty");
+    let (mut executor, mock) = executor(&dir);
+    assert_eq!(executor.tracked_nonce(), None);
+
+    mock.push(U256::from(PENDING_NONCE)).unwrap();
+    let report = executor.recover_inflight(RecoveryPolicy::Discard).await.unwrap();
+    assert_eq!(report.nonce, PENDING_NONCE);
+    assert_eq!(executor.tracked_nonce(), Some(PENDING_NONCE));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,675 @@
+//! Inventory Manager — wallet token drift alerts and auto-conversion
+//!
+//! Purpose:
+//!     After a few hundred trades the wallet collects WETH, WMATIC, USDT and
+//!     the like from partial fills and gas-negative legacy trades, while the
+//!     quote token balance the bot actually trades with shrinks. This module
+//!     finds that idle inventory and plans (or executes) swaps back into the
+//!     primary quote token.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Design:
+//!     - Every INVENTORY_CHECK_INTERVAL_BLOCKS blocks, one Multicall3 aggregate3
+//!       reads token.balanceOf(wallet) for the pool universe (the stranded
+//!       alarm's token_universe: symbols, decimals, USD from synced pools)
+//!     - The configured quote tokens are what the bot trades with and are never
+//!       rebalanced. Any other token worth more than
+//!       INVENTORY_REBALANCE_THRESHOLD_USD is excess; tokens no pool prices in
+//!       USD can't be valued or converted and are skipped.
+//!     - Plan per excess token: sell the whole balance into the primary quote
+//!       token through the cheapest (lowest fee) synced pool pairing the two
+//!       directly, executable DEXes only. Min out = pool price net of the pool
+//!       fee, less MAX_SLIPPAGE_PERCENT.
+//!     - Alerts fire once per incident (stranded.rs dedup): log + webhook with
+//!       the suggested conversion; inventory.json is rewritten every check
+//!     - AUTO_REBALANCE=true in live mode: newly alerted plans are executed via
+//!       TradeExecutor::rebalance (tax record per swap), appended to
+//!       rebalances.csv. Dry run only logs the plan.
+
+use super::executor::min_out_with_slippage;
+use super::multicall_quoter::MulticallQuoter;
+use super::preflight::{decode_word, encode_balance_of};
+use super::stranded::WatchedToken;
+use crate::alerts::{AlertDispatcher, AlertSeverity};
+use crate::pool::PoolStateManager;
+use crate::tax::RebalanceSwap;
+use crate::types::{BotConfig, DexType};
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::{debug, info, warn};
+
+/// Check cadence, threshold and execution toggles
+#[derive(Debug, Clone)]
+pub struct InventoryConfig {
+    /// Blocks between balance checks (0 = disabled)
+    pub check_interval_blocks: u64,
+    /// Non-primary balances worth more than this (USD) are rebalanced
+    pub threshold_usd: f64,
+    /// Execute plans instead of only alerting
+    pub auto_rebalance: bool,
+    /// Slippage allowance applied to each plan's min out (percent)
+    pub max_slippage_percent: f64,
+    /// Primary quote token (rebalance target)
+    pub primary_quote: Address,
+    /// Every configured quote token (never rebalanced)
+    pub quote_tokens: Vec<Address>,
+    /// Not live: plans are logged, never executed
+    pub dry_run: bool,
+}
+
+impl InventoryConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            check_interval_blocks: config.inventory_check_interval_blocks,
+            threshold_usd: config.inventory_rebalance_threshold_usd,
+            auto_rebalance: config.auto_rebalance,
+            max_slippage_percent: config.max_slippage_percent,
+            primary_quote: config.quote_token_address,
+            quote_tokens: std::iter::once(config.quote_token_address)
+                .chain(config.quote_token_address_native)
+                .collect(),
+            dry_run: !config.live_mode,
+        }
+    }
+}
+
+/// A non-primary wallet balance above the threshold
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InventoryBalance {
+    pub token: Address,
+    pub symbol: String,
+    pub decimals: u8,
+    pub raw: U256,
+    /// Human units (raw / 10^decimals)
+    pub amount: f64,
+    pub usd_value: f64,
+}
+
+/// Outcome of one balance check
+#[derive(Debug, Clone, Default)]
+pub struct InventoryReport {
+    pub block: u64,
+    pub tokens_checked: usize,
+    /// Every non-primary token currently above the threshold
+    pub excess: Vec<InventoryBalance>,
+    /// Subset of `excess` that is new (or grew) since the last alert
+    pub new_alerts: Vec<InventoryBalance>,
+    /// Tokens that were alerted before and are now back under the threshold
+    pub cleared: Vec<String>,
+}
+
+/// Swap of one excess balance into the primary quote token
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RebalancePlan {
+    pub token_in: Address,
+    pub symbol_in: String,
+    pub decimals_in: u8,
+    pub token_out: Address,
+    pub symbol_out: String,
+    pub decimals_out: u8,
+    pub amount_in: U256,
+    pub dex: DexType,
+    pub pool: Address,
+    /// Pool fee (percent)
+    pub fee_percent: f64,
+    /// Quote token per token_in at the pool's price (human units)
+    pub price: f64,
+    /// Output at the pool price net of the fee
+    pub expected_out: U256,
+    /// expected_out less the slippage allowance — the swap's amountOutMinimum
+    pub min_out: U256,
+    pub usd_value: f64,
+}
+
+impl RebalancePlan {
+    /// Tax record inputs for the executed swap
+    pub fn tax_swap(&self) -> RebalanceSwap {
+        RebalanceSwap {
+            symbol_in: self.symbol_in.clone(),
+            decimals_in: self.decimals_in,
+            symbol_out: self.symbol_out.clone(),
+            decimals_out: self.decimals_out,
+            dex: self.dex,
+            pool: self.pool,
+            fee_percent: self.fee_percent,
+        }
+    }
+
+    fn human(raw: U256, decimals: u8) -> f64 {
+        raw.low_u128() as f64 / 10_f64.powi(decimals as i32)
+    }
+
+    /// One-line description for logs and alerts
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} (${:.2}) → ≥{:.2} {} via {} pool {:?} ({:.2}% fee)",
+            Self::human(self.amount_in, self.decimals_in),
+            self.symbol_in,
+            self.usd_value,
+            Self::human(self.min_out, self.decimals_out),
+            self.symbol_out,
+            self.dex,
+            self.pool,
+            self.fee_percent,
+        )
+    }
+}
+
+/// A synced pool that can swap `token` directly into `quote`
+#[derive(Debug, Clone, PartialEq)]
+struct ConversionPool {
+    dex: DexType,
+    address: Address,
+    fee_percent: f64,
+    /// quote per token (human units)
+    price: f64,
+}
+
+/// Executable pools pairing `token` with `quote`, priced token → quote
+fn conversion_pools(state: &PoolStateManager, token: Address, quote: Address) -> Vec<ConversionPool> {
+    let orient = |token0: Address, token1: Address, price: f64| {
+        if token0 == token && token1 == quote {
+            Some(price)
+        } else if token0 == quote && token1 == token && price > 0.0 {
+            Some(1.0 / price)
+        } else {
+            None
+        }
+    };
+
+    let v3 = state.get_all_v3_pools().into_iter().filter(|p| p.liquidity > 0).filter_map(|p| {
+        orient(p.pair.token0, p.pair.token1, p.price())
+            .map(|price| ConversionPool { dex: p.dex, address: p.address, fee_percent: p.fee_percent(), price })
+    });
+    let v2 = state.get_all_pools().into_iter().filter(|p| !p.reserve0.is_zero() && !p.reserve1.is_zero()).filter_map(|p| {
+        orient(p.pair.token0, p.pair.token1, p.price_adjusted()).map(|price| ConversionPool {
+            dex: p.dex,
+            address: p.address,
+            fee_percent: p.dex.fee_percent().unwrap_or(0.30),
+            price,
+        })
+    });
+    v3.chain(v2)
+        .filter(|p| !p.dex.is_detection_only() && p.price.is_finite() && p.price > 0.0)
+        .collect()
+}
+
+/// Slippage-protected plan selling `balance` into the primary quote token
+/// through the cheapest pool. None = no synced pool pairs them directly.
+pub fn plan_rebalance(
+    balance: &InventoryBalance,
+    quote: &WatchedToken,
+    state: &PoolStateManager,
+    max_slippage_percent: f64,
+) -> Option<RebalancePlan> {
+    let pool = conversion_pools(state, balance.token, quote.address)
+        .into_iter()
+        .min_by(|a, b| a.fee_percent.total_cmp(&b.fee_percent))?;
+    let net_price = pool.price * (1.0 - pool.fee_percent / 100.0);
+    Some(RebalancePlan {
+        token_in: balance.token,
+        symbol_in: balance.symbol.clone(),
+        decimals_in: balance.decimals,
+        token_out: quote.address,
+        symbol_out: quote.symbol.clone(),
+        decimals_out: quote.decimals,
+        amount_in: balance.raw,
+        dex: pool.dex,
+        pool: pool.address,
+        fee_percent: pool.fee_percent,
+        price: pool.price,
+        expected_out: min_out_with_slippage(balance.raw, net_price, balance.decimals, quote.decimals, 0.0),
+        min_out: min_out_with_slippage(balance.raw, net_price, balance.decimals, quote.decimals, max_slippage_percent),
+        usd_value: balance.usd_value,
+    })
+}
+
+/// Status JSON written after every check
+#[derive(Debug, Serialize)]
+struct InventoryStatus<'a> {
+    checked_at: String,
+    block: u64,
+    wallet: Address,
+    threshold_usd: f64,
+    tokens_checked: usize,
+    excess: &'a [InventoryBalance],
+}
+
+/// Periodic balanceOf watch over the trading wallet
+pub struct InventoryManager {
+    wallet: Address,
+    config: InventoryConfig,
+    /// Raw balance at the last alert per token (dedup state)
+    alerted: HashMap<Address, U256>,
+    last_check_block: Option<u64>,
+    /// inventory.json
+    status_path: Option<PathBuf>,
+    /// rebalances.csv
+    ledger_path: Option<PathBuf>,
+    alerts: Option<AlertDispatcher>,
+}
+
+impl InventoryManager {
+    pub fn new(wallet: Address, config: InventoryConfig) -> Self {
+        info!(
+            "Inventory manager: wallet {:?} every {} blocks | threshold ${:.2} | auto rebalance {}",
+            wallet, config.check_interval_blocks, config.threshold_usd,
+            match (config.auto_rebalance, config.dry_run) {
+                (false, _) => "off",
+                (true, true) => "ON (dry run: plans logged only)",
+                (true, false) => "ON",
+            }
+        );
+        Self {
+            wallet,
+            config,
+            alerted: HashMap::new(),
+            last_check_block: None,
+            status_path: None,
+            ledger_path: None,
+            alerts: None,
+        }
+    }
+
+    /// Write the status JSON and rebalance ledger under `data_dir`
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Inventory: failed to create {}: {}", data_dir, e);
+        }
+        self.status_path = Some(dir.join("inventory.json"));
+        self.ledger_path = Some(dir.join("rebalances.csv"));
+        self
+    }
+
+    pub fn with_alerts(mut self, alerts: AlertDispatcher) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    /// True when a check should run at `block`
+    pub fn is_due(&self, block: u64) -> bool {
+        let interval = self.config.check_interval_blocks;
+        interval > 0 && self.last_check_block.is_none_or(|last| block >= last + interval)
+    }
+
+    /// Read all wallet balances in one aggregate3 and evaluate them
+    pub async fn check<M: Middleware + 'static>(
+        &mut self,
+        quoter: &MulticallQuoter<M>,
+        tokens: &[WatchedToken],
+        block: u64,
+    ) -> Result<InventoryReport> {
+        // Mark the check up front so a failing RPC is retried next interval, not next block
+        self.last_check_block = Some(block);
+        if tokens.is_empty() {
+            return Ok(InventoryReport { block, ..Default::default() });
+        }
+        let calls: Vec<(Address, Vec<u8>)> = tokens.iter().map(|t| (t.address, encode_balance_of(self.wallet))).collect();
+        let results = quoter.aggregate3_at(&calls, None).await?;
+        let report = self.evaluate(block, tokens, &results)?;
+        self.write_status(&report);
+        Ok(report)
+    }
+
+    /// Classify balances against the threshold and apply alert dedup
+    pub(crate) fn evaluate(
+        &mut self,
+        block: u64,
+        tokens: &[WatchedToken],
+        results: &[(bool, Vec<u8>)],
+    ) -> Result<InventoryReport> {
+        if results.len() != tokens.len() {
+            return Err(anyhow!("balance batch returned {} results for {} tokens", results.len(), tokens.len()));
+        }
+
+        let mut report = InventoryReport { block, tokens_checked: tokens.len(), ..Default::default() };
+        for (token, result) in tokens.iter().zip(results) {
+            if self.config.quote_tokens.contains(&token.address) {
+                continue;
+            }
+            let raw = match decode_word(result) {
+                Some(raw) => raw,
+                None => {
+                    debug!("Inventory: balanceOf failed for {} — skipped", token.symbol);
+                    continue;
+                }
+            };
+            match self.classify(token, raw) {
+                Some(balance) => {
+                    let is_new = self.alerted.get(&token.address).is_none_or(|prev| raw > *prev);
+                    if is_new {
+                        self.alerted.insert(token.address, raw);
+                        report.new_alerts.push(balance.clone());
+                    }
+                    report.excess.push(balance);
+                }
+                None => {
+                    if self.alerted.remove(&token.address).is_some() {
+                        report.cleared.push(token.symbol.clone());
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Some(balance) if `raw` is worth more than the threshold
+    fn classify(&self, token: &WatchedToken, raw: U256) -> Option<InventoryBalance> {
+        let price = token.price_usd?;
+        let amount = raw.low_u128() as f64 / 10_f64.powi(token.decimals as i32);
+        let usd_value = amount * price;
+        (usd_value > self.config.threshold_usd).then(|| InventoryBalance {
+            token: token.address,
+            symbol: token.symbol.clone(),
+            decimals: token.decimals,
+            raw,
+            amount,
+            usd_value,
+        })
+    }
+
+    /// Conversion plans for the newly alerted balances (Err = no direct pool, by symbol)
+    pub fn plans(
+        &self,
+        report: &InventoryReport,
+        tokens: &[WatchedToken],
+        state: &PoolStateManager,
+    ) -> Vec<std::result::Result<RebalancePlan, String>> {
+        let quote = match tokens.iter().find(|t| t.address == self.config.primary_quote) {
+            Some(q) => q,
+            None => return Vec::new(),
+        };
+        report
+            .new_alerts
+            .iter()
+            .map(|b| {
+                plan_rebalance(b, quote, state, self.config.max_slippage_percent).ok_or_else(|| b.symbol.clone())
+            })
+            .collect()
+    }
+
+    /// Log and alert every new excess balance with its suggested conversion
+    pub fn alert(&self, report: &InventoryReport, plans: &[std::result::Result<RebalancePlan, String>]) {
+        for symbol in &report.cleared {
+            info!("Inventory: {} balance back under ${:.2} — alert re-armed", symbol, self.config.threshold_usd);
+        }
+        for plan in plans {
+            let msg = match plan {
+                Ok(plan) => format!("💱 Inventory rebalance suggested: {}", plan.describe()),
+                Err(symbol) => format!(
+                    "💱 Idle {} in the wallet above ${:.2}, but no synced pool swaps it directly into the quote token",
+                    symbol, self.config.threshold_usd
+                ),
+            };
+            self.dispatch(AlertSeverity::Warning, &msg);
+        }
+    }
+
+    /// Plans to execute now: AUTO_REBALANCE in live mode. In dry run the
+    /// plans are only logged.
+    pub fn execution_targets(&self, plans: &[std::result::Result<RebalancePlan, String>]) -> Vec<RebalancePlan> {
+        if !self.config.auto_rebalance {
+            return Vec::new();
+        }
+        let plans: Vec<RebalancePlan> = plans.iter().filter_map(|p| p.as_ref().ok()).cloned().collect();
+        if self.config.dry_run {
+            for plan in &plans {
+                info!("🔬 DRY RUN: rebalance not sent: {}", plan.describe());
+            }
+            return Vec::new();
+        }
+        plans
+    }
+
+    /// Append a completed rebalance to rebalances.csv and announce it
+    pub fn record_rebalance(&self, plan: &RebalancePlan, tx_hash: TxHash, amount_out: U256, block: u64) {
+        self.dispatch(
+            AlertSeverity::Info,
+            &format!("💱 Rebalanced {} | received {} raw | TX: {:?}", plan.describe(), amount_out, tx_hash),
+        );
+
+        let path = match self.ledger_path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let is_new = !path.exists();
+        let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(f) => f,
+            Err(e) => {
+                warn!("Inventory: failed to open ledger {}: {}", path.display(), e);
+                return;
+            }
+        };
+        if is_new {
+            let _ = writeln!(file, "timestamp,block,tx_hash,token_in,symbol_in,amount_in_raw,token_out,amount_out_raw,min_out_raw,dex,pool,usd_value");
+        }
+        let _ = writeln!(
+            file,
+            "{},{},{:?},{:?},{},{},{:?},{},{},{},{:?},{:.2}",
+            chrono::Utc::now().to_rfc3339(),
+            block,
+            tx_hash,
+            plan.token_in,
+            plan.symbol_in,
+            plan.amount_in,
+            plan.token_out,
+            amount_out,
+            plan.min_out,
+            plan.dex,
+            plan.pool,
+            plan.usd_value,
+        );
+    }
+
+    /// Announce a failed rebalance (the balance is still idle)
+    pub fn rebalance_failed(&self, plan: &RebalancePlan, err: &anyhow::Error) {
+        self.dispatch(AlertSeverity::Warning, &format!("Rebalance failed: {} — {}", plan.describe(), err));
+    }
+
+    fn dispatch(&self, severity: AlertSeverity, msg: &str) {
+        match self.alerts {
+            Some(ref alerts) => alerts.dispatch(severity, msg),
+            None => warn!("{}", msg),
+        }
+    }
+
+    fn write_status(&self, report: &InventoryReport) {
+        let path = match self.status_path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let status = InventoryStatus {
+            checked_at: chrono::Utc::now().to_rfc3339(),
+            block: report.block,
+            wallet: self.wallet,
+            threshold_usd: self.config.threshold_usd,
+            tokens_checked: report.tokens_checked,
+            excess: &report.excess,
+        };
+        match serde_json::to_string_pretty(&status) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    warn!("Inventory: failed to write status: {}", e);
+                }
+            }
+            Err(e) => warn!("Inventory: failed to serialize status: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{PoolState, TradingPair, V3PoolState};
+    use ethers::abi::{self, Token};
+    use std::str::FromStr;
+
+    const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+    const WETH: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+    const WMATIC: &str = "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270";
+    const USDC_NATIVE: &str = "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359";
+
+    fn addr(s: &str) -> Address {
+        Address::from_str(s).unwrap()
+    }
+
+    fn config(auto_rebalance: bool, dry_run: bool) -> InventoryConfig {
+        InventoryConfig {
+            check_interval_blocks: 1_800,
+            threshold_usd: 25.0,
+            auto_rebalance,
+            max_slippage_percent: 0.5,
+            primary_quote: addr(USDC),
+            quote_tokens: vec![addr(USDC), addr(USDC_NATIVE)],
+            dry_run,
+        }
+    }
+
+    fn manager(auto_rebalance: bool, dry_run: bool) -> InventoryManager {
+        InventoryManager::new(Address::from_low_u64_be(0xBEEF), config(auto_rebalance, dry_run))
+    }
+
+    fn tokens() -> Vec<WatchedToken> {
+        vec![
+            WatchedToken { address: addr(USDC), symbol: "USDC".into(), decimals: 6, price_usd: Some(1.0) },
+            WatchedToken { address: addr(USDC_NATIVE), symbol: "USDC.n".into(), decimals: 6, price_usd: Some(1.0) },
+            WatchedToken { address: addr(WETH), symbol: "WETH".into(), decimals: 18, price_usd: Some(3000.0) },
+            WatchedToken { address: addr(WMATIC), symbol: "WMATIC".into(), decimals: 18, price_usd: Some(0.5) },
+            WatchedToken { address: Address::from_low_u64_be(7), symbol: "XYZ".into(), decimals: 18, price_usd: None },
+        ]
+    }
+
+    fn balances(raw: [u128; 5]) -> Vec<(bool, Vec<u8>)> {
+        raw.iter().map(|v| (true, abi::encode(&[Token::Uint(U256::from(*v))]))).collect()
+    }
+
+    const ONE: u128 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn test_threshold_detection_skips_quote_and_unpriced_tokens() {
+        let mut m = manager(false, true);
+        // 900 USDC + 500 USDC.n (quote, never excess), 0.01 WETH = $30 (excess),
+        // 40 WMATIC = $20 (under threshold), 1e6 XYZ (no price)
+        let raw = [900_000_000, 500_000_000, ONE / 100, 40 * ONE, 1_000_000 * ONE];
+        let report = m.evaluate(100, &tokens(), &balances(raw)).unwrap();
+        assert_eq!(report.tokens_checked, 5);
+        let symbols: Vec<&str> = report.excess.iter().map(|b| b.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["WETH"]);
+        assert!((report.excess[0].usd_value - 30.0).abs() < 1e-9);
+        assert_eq!(report.new_alerts.len(), 1);
+
+        // Persistent: not re-alerted; growth past the alerted amount is a new incident
+        assert!(m.evaluate(200, &tokens(), &balances(raw)).unwrap().new_alerts.is_empty());
+        let grown = [0, 0, ONE / 50, 60 * ONE, 0];
+        let report = m.evaluate(300, &tokens(), &balances(grown)).unwrap();
+        let symbols: Vec<&str> = report.new_alerts.iter().map(|b| b.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["WETH", "WMATIC"]);
+
+        // Converted away: cleared and re-armed
+        let report = m.evaluate(400, &tokens(), &balances([0, 0, 0, 60 * ONE, 0])).unwrap();
+        assert_eq!(report.cleared, vec!["WETH".to_string()]);
+
+        assert!(m.evaluate(500, &tokens(), &balances(raw)[..4]).is_err());
+    }
+
+    fn v3_pool(address: u64, dex: DexType, fee: u32, tick: i32) -> V3PoolState {
+        // WETH (0x7ceb…) > USDC (0x2791…): token0 = USDC, token1 = WETH
+        V3PoolState {
+            address: Address::from_low_u64_be(address),
+            dex,
+            pair: TradingPair::new(addr(USDC), addr(WETH), "WETH/USDC".to_string()),
+            sqrt_price_x96: U256::zero(),
+            tick,
+            fee,
+            liquidity: 1,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 1,
+        }
+    }
+
+    #[test]
+    fn test_plan_picks_cheapest_executable_pool_with_slippage_floor() {
+        let state = PoolStateManager::new();
+        // tick 195_303 ≈ 1/3300 WETH per USDC → 3300 USDC per WETH
+        state.update_v3_pool(v3_pool(1, DexType::UniswapV3_030, 3000, 195_303));
+        state.update_v3_pool(v3_pool(2, DexType::UniswapV3_005, 500, 195_303));
+        // Cheaper, but detection-only: never planned
+        state.update_v3_pool(v3_pool(3, DexType::KyberElastic_001, 100, 195_303));
+        // Wrong pair: ignored
+        state.update_pool(PoolState {
+            address: Address::from_low_u64_be(4),
+            dex: DexType::Quickswap,
+            pair: TradingPair::new(addr(WMATIC), addr(WETH), "WMATIC/WETH".to_string()),
+            reserve0: U256::from(ONE),
+            reserve1: U256::from(ONE),
+            last_updated: 1,
+            token0_decimals: 18,
+            token1_decimals: 18,
+        });
+
+        let tokens = tokens();
+        let weth = InventoryBalance {
+            token: addr(WETH),
+            symbol: "WETH".into(),
+            decimals: 18,
+            raw: U256::from(ONE / 10),
+            amount: 0.1,
+            usd_value: 330.0,
+        };
+        let plan = plan_rebalance(&weth, &tokens[0], &state, 0.5).unwrap();
+        assert_eq!(plan.pool, Address::from_low_u64_be(2));
+        assert_eq!(plan.dex, DexType::UniswapV3_005);
+        assert_eq!(plan.token_out, addr(USDC));
+        assert_eq!(plan.amount_in, U256::from(ONE / 10));
+        assert!((plan.price - 3300.0).abs() < 5.0, "price {}", plan.price);
+
+        // 0.1 WETH × price, net of 0.05% fee; min out 0.5% under that
+        let expected = 0.1 * plan.price * (1.0 - 0.0005);
+        let expected_out = plan.expected_out.as_u128() as f64 / 1e6;
+        let min_out = plan.min_out.as_u128() as f64 / 1e6;
+        assert!((expected_out - expected).abs() < 0.01, "expected {} got {}", expected, expected_out);
+        assert!((min_out - expected * 0.995).abs() < 0.01, "min out {}", min_out);
+        assert!(plan.min_out < plan.expected_out);
+
+        // No pool pairs WMATIC with USDC directly: no plan, reported by symbol
+        let mut m = manager(true, false);
+        let report = m.evaluate(1, &tokens, &balances([0, 0, ONE / 10, 100 * ONE, 0])).unwrap();
+        let plans = m.plans(&report, &tokens, &state);
+        assert_eq!(plans.len(), 2);
+        assert_eq!(plans[0].as_ref().unwrap().pool, Address::from_low_u64_be(2));
+        assert_eq!(plans[1], Err("WMATIC".to_string()));
+        assert_eq!(m.execution_targets(&plans).len(), 1);
+        assert_eq!(plans[0].as_ref().unwrap().tax_swap().symbol_out, "USDC");
+    }
+
+    #[test]
+    fn test_execution_gated_by_auto_rebalance_and_dry_run() {
+        let state = PoolStateManager::new();
+        state.update_v3_pool(v3_pool(2, DexType::UniswapV3_005, 500, 195_303));
+        let tokens = tokens();
+        let raw = balances([0, 0, ONE / 10, 0, 0]);
+
+        for (auto, dry_run, executes) in [(false, false, false), (true, true, false), (true, false, true)] {
+            let mut m = manager(auto, dry_run);
+            let report = m.evaluate(1, &tokens, &raw).unwrap();
+            let plans = m.plans(&report, &tokens, &state);
+            assert_eq!(plans.len(), 1);
+            assert_eq!(m.execution_targets(&plans).is_empty(), !executes, "auto {} dry run {}", auto, dry_run);
+        }
+
+        let mut m = manager(false, false);
+        assert!(m.is_due(10));
+        m.last_check_block = Some(10);
+        assert!(!m.is_due(1_809));
+        assert!(m.is_due(1_810));
+    }
+}
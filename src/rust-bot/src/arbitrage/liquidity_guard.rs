@@ -0,0 +1,417 @@
+//! Execution-Time Liquidity Guard
+//!
+//! Thin V3 pools pass the midmarket spread check, then the Quoter (or the
+//! on-chain trade) reveals there is no depth — a wasted pre-screen slot or gas.
+//! Before pre-screen, every opportunity is checked against the liquidity the
+//! detector recorded for both legs:
+//!
+//!   1. Floor: V3 in-range liquidity (L) of each leg must be at least
+//!      MIN_POOL_LIQUIDITY (per-chain default, per-pair override).
+//!   2. Depth: the trade must not consume more than MAX_LIQUIDITY_FRACTION of
+//!      either leg's in-range quote reserve. For V3 the virtual reserve follows
+//!      from L and the leg's price:
+//!      P_raw = price × 10^(dec1 − dec0)      (token1/token0, raw units)
+//!      quote = token0 → x = L / √P_raw
+//!      quote = token1 → y = L × √P_raw
+//!      V2 and DODO legs use the quote reserve from the pool snapshot.
+//!
+//! Depth failures are rejected or downsized to the allowed fraction
+//! (LIQUIDITY_GUARD_MODE). Rejections are structural — they last until
+//! liquidity returns — so they never escalate the route cooldown
+//! (TradeOutcome::InsufficientLiquidity).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Downsized verdict carries a QuoteAmount
+//! Modified: 2026-10-16 - Split routes: sell-leg depth checked for the primary pool's share only
+//! Modified: 2026-10-16 - DODO legs: quote reserve from the PMM snapshot
+
+use super::split::SPLIT_SCALE_BPS;
+use crate::quote_amount::QuoteAmount;
+use crate::types::{ArbitrageOpportunity, BotConfig, PoolStateKind};
+use ethers::types::U256;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Prefix of every guard rejection message (see TradeOutcome::classify)
+pub const LIQUIDITY_GUARD_PREFIX: &str = "Liquidity guard:";
+
+/// What to do with a trade that would consume too much of a leg's depth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardMode {
+    Reject,
+    /// Shrink trade_size to the allowed fraction (rejected if that leaves
+    /// estimated profit below MIN_PROFIT_USD)
+    Downsize,
+}
+
+impl GuardMode {
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "downsize" => GuardMode::Downsize,
+            _ => GuardMode::Reject,
+        }
+    }
+}
+
+/// Which leg of the route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Leg {
+    Buy,
+    Sell,
+}
+
+impl Leg {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Leg::Buy => "buy",
+            Leg::Sell => "sell",
+        }
+    }
+}
+
+/// Why an opportunity was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiquidityRejection {
+    /// Recorded V3 liquidity below the floor
+    BelowMinimum { leg: Leg, liquidity: u128, min: u128 },
+    /// Trade would consume more than the allowed fraction of the leg's depth
+    TooDeep { leg: Leg, fraction: f64, max: f64 },
+    /// Downsized trade no longer clears the minimum profit
+    DownsizedUnprofitable { estimated_profit: f64 },
+}
+
+impl fmt::Display for LiquidityRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiquidityRejection::BelowMinimum { leg, liquidity, min } => write!(
+                f, "{} {} pool liquidity {} below minimum {}",
+                LIQUIDITY_GUARD_PREFIX, leg.as_str(), liquidity, min
+            ),
+            LiquidityRejection::TooDeep { leg, fraction, max } => write!(
+                f, "{} trade consumes {:.2}% of {} pool in-range liquidity (max {:.2}%)",
+                LIQUIDITY_GUARD_PREFIX, fraction * 100.0, leg.as_str(), max * 100.0
+            ),
+            LiquidityRejection::DownsizedUnprofitable { estimated_profit } => write!(
+                f, "{} downsized trade est. profit ${:.4} below minimum",
+                LIQUIDITY_GUARD_PREFIX, estimated_profit
+            ),
+        }
+    }
+}
+
+/// Result of checking one opportunity
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiquidityVerdict {
+    Pass,
+    /// trade_size reduced in place (from the original size)
+    Downsized { from: QuoteAmount, fraction: f64 },
+    Rejected(LiquidityRejection),
+}
+
+/// Guard counters, logged with the periodic status line
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LiquidityGuardStats {
+    pub checked: u64,
+    pub downsized: u64,
+    pub rejected_min: u64,
+    pub rejected_depth: u64,
+}
+
+/// Per-opportunity liquidity floor + depth check
+pub struct LiquidityGuard {
+    min_liquidity: u128,
+    pair_min_liquidity: HashMap<String, u128>,
+    max_fraction: f64,
+    mode: GuardMode,
+    min_profit_usd: f64,
+    stats: LiquidityGuardStats,
+}
+
+impl LiquidityGuard {
+    pub fn new(config: &BotConfig) -> Self {
+        Self {
+            min_liquidity: config.min_pool_liquidity,
+            pair_min_liquidity: config.min_pool_liquidity_overrides.clone(),
+            max_fraction: config.max_liquidity_fraction,
+            mode: GuardMode::parse(&config.liquidity_guard_mode),
+            min_profit_usd: config.min_profit_usd,
+            stats: LiquidityGuardStats::default(),
+        }
+    }
+
+    pub fn mode(&self) -> GuardMode {
+        self.mode
+    }
+
+    pub fn stats(&self) -> &LiquidityGuardStats {
+        &self.stats
+    }
+
+    /// Liquidity floor for a pair (override, else chain default)
+    pub fn min_liquidity_for(&self, pair_symbol: &str) -> u128 {
+        self.pair_min_liquidity
+            .get(pair_symbol)
+            .copied()
+            .unwrap_or(self.min_liquidity)
+    }
+
+    /// Check (and in downsize mode, resize) one opportunity
+    pub fn check(&mut self, opp: &mut ArbitrageOpportunity) -> LiquidityVerdict {
+        self.stats.checked += 1;
+        let verdict = self.evaluate(opp);
+        match &verdict {
+            LiquidityVerdict::Pass => {}
+            LiquidityVerdict::Downsized { .. } => self.stats.downsized += 1,
+            LiquidityVerdict::Rejected(LiquidityRejection::BelowMinimum { .. }) => self.stats.rejected_min += 1,
+            LiquidityVerdict::Rejected(_) => self.stats.rejected_depth += 1,
+        }
+        verdict
+    }
+
+    fn evaluate(&self, opp: &mut ArbitrageOpportunity) -> LiquidityVerdict {
+        let min = self.min_liquidity_for(&opp.pair.symbol);
+        for leg in [Leg::Buy, Leg::Sell] {
+            let (dex, liquidity) = match leg {
+                Leg::Buy => (opp.buy_dex, opp.buy_pool_liquidity),
+                Leg::Sell => (opp.sell_dex, opp.sell_pool_liquidity),
+            };
+            if let Some(liquidity) = liquidity.filter(|_| dex.is_v3()) {
+                if liquidity < min {
+                    return LiquidityVerdict::Rejected(LiquidityRejection::BelowMinimum { leg, liquidity, min });
+                }
+            }
+        }
+
+        if self.max_fraction <= 0.0 || opp.trade_size.is_zero() {
+            return LiquidityVerdict::Pass;
+        }
+        // Tightest leg decides
+        let deepest = [Leg::Buy, Leg::Sell]
+            .into_iter()
+            .filter_map(|leg| liquidity_fraction(opp, leg).map(|f| (leg, f)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        let (leg, fraction) = match deepest {
+            Some((leg, fraction)) if fraction > self.max_fraction => (leg, fraction),
+            _ => return LiquidityVerdict::Pass,
+        };
+
+        match self.mode {
+            GuardMode::Reject => LiquidityVerdict::Rejected(LiquidityRejection::TooDeep {
+                leg,
+                fraction,
+                max: self.max_fraction,
+            }),
+            GuardMode::Downsize => {
+                let ratio = self.max_fraction / fraction;
+                let estimated_profit = opp.estimated_profit * ratio;
+                if estimated_profit < self.min_profit_usd {
+                    return LiquidityVerdict::Rejected(LiquidityRejection::DownsizedUnprofitable { estimated_profit });
+                }
+                let from = opp.trade_size;
+                opp.trade_size = from.with_raw(U256::from((from.raw().low_u128() as f64 * ratio) as u128));
+                opp.estimated_profit = estimated_profit;
+                opp.trade_size_usd *= ratio;
+                LiquidityVerdict::Downsized { from, fraction }
+            }
+        }
+    }
+}
+
+/// In-range quote-token reserve of one leg, in raw quote units.
+/// None when the leg's depth is unknown (no recorded liquidity / snapshot).
+pub fn leg_quote_reserve(opp: &ArbitrageOpportunity, leg: Leg) -> Option<f64> {
+    let (dex, price, liquidity, snapshot) = match leg {
+        Leg::Buy => (opp.buy_dex, opp.buy_price, opp.buy_pool_liquidity, opp.pool_snapshots.as_ref().map(|s| &s.buy)),
+        Leg::Sell => (opp.sell_dex, opp.sell_price, opp.sell_pool_liquidity, opp.pool_snapshots.as_ref().map(|s| &s.sell)),
+    };
+
+    if dex.is_v2() {
+        return match snapshot.map(|s| &s.state) {
+            Some(PoolStateKind::V2 { reserve0, reserve1 }) => {
+                let reserve = if opp.quote_token_is_token0 { reserve0 } else { reserve1 };
+                Some(reserve.to_string().parse::<f64>().unwrap_or(0.0))
+            }
+            _ => None,
+        };
+    }
+    if dex.is_dodo() {
+        return match snapshot.map(|s| &s.state) {
+            Some(PoolStateKind::Dodo { pmm, base_is_token0 }) => {
+                // The route's quote token may be the DODO pool's base token
+                let reserve = if opp.quote_token_is_token0 == *base_is_token0 { pmm.b } else { pmm.q };
+                Some(reserve.to_string().parse::<f64>().unwrap_or(0.0))
+            }
+            _ => None,
+        };
+    }
+
+    let liquidity = liquidity? as f64;
+    if price <= 0.0 || !price.is_finite() {
+        return None;
+    }
+    let price_raw = price * 10_f64.powi(opp.token1_decimals as i32 - opp.token0_decimals as i32);
+    let sqrt_price = price_raw.sqrt();
+    Some(if opp.quote_token_is_token0 {
+        liquidity / sqrt_price
+    } else {
+        liquidity * sqrt_price
+    })
+}
+
+/// Fraction of the leg's in-range quote reserve the trade would consume
+pub fn liquidity_fraction(opp: &ArbitrageOpportunity, leg: Leg) -> Option<f64> {
+    let reserve = leg_quote_reserve(opp, leg)?;
+    if reserve <= 0.0 {
+        return Some(f64::INFINITY);
+    }
+    // A split route sells only its primary share on sell_dex
+    let share = match (leg, opp.split_sell) {
+        (Leg::Sell, Some(split)) => 1.0 - f64::from(split.share_bps) / f64::from(SPLIT_SCALE_BPS),
+        _ => 1.0,
+    };
+    Some(opp.trade_size.raw().low_u128() as f64 * share / reserve)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::types::{DexType, TradingPair};
+    use ethers::types::Address;
+
+    /// USDC (6) = token0, WETH (18) = token1, price = WETH per USDC
+    fn usdc_weth(liquidity: u128, trade_usdc: u64) -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.000_4,
+            0.000_4,
+            U256::from(trade_usdc) * U256::exp10(6),
+        );
+        opp.set_quote_layout(6, 18, true);
+        opp.buy_pool_liquidity = Some(liquidity);
+        opp.sell_pool_liquidity = Some(liquidity);
+        opp.estimated_profit = 2.0;
+        opp.trade_size_usd = trade_usdc as f64;
+        opp
+    }
+
+    /// WETH (18) = token0, DAI (18) = token1, price = DAI per WETH
+    fn weth_dai(liquidity: u128, trade_dai: u64) -> ArbitrageOpportunity {
+        let mut opp = usdc_weth(liquidity, 0);
+        opp.pair.symbol = "WETH/DAI".to_string();
+        opp.buy_price = 2_500.0;
+        opp.sell_price = 2_500.0;
+        opp.set_quote_layout(18, 18, false);
+        opp.trade_size = opp.quote_amount(U256::from(trade_dai) * U256::exp10(18));
+        opp.trade_size_usd = trade_dai as f64;
+        opp
+    }
+
+    fn guard(mode: &str, max_fraction: f64) -> LiquidityGuard {
+        let mut config = create_test_config();
+        config.min_pool_liquidity = 1_000_000_000;
+        config.min_pool_liquidity_overrides.insert("WETH/DAI".to_string(), 10u128.pow(20));
+        config.max_liquidity_fraction = max_fraction;
+        config.liquidity_guard_mode = mode.to_string();
+        config.min_profit_usd = 0.10;
+        LiquidityGuard::new(&config)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn test_fraction_6_and_18_decimal_quotes() {
+        // $500k virtual USDC: L / √(0.0004 × 1e12) = 1e16 / 2e4 = 5e11 raw
+        let opp = usdc_weth(10u128.pow(16), 500);
+        assert!(close(leg_quote_reserve(&opp, Leg::Buy).unwrap(), 5e11));
+        assert!(close(liquidity_fraction(&opp, Leg::Buy).unwrap(), 0.001));
+
+        // 500k virtual DAI: L × √2500 = 1e22 × 50 = 5e23 raw
+        let opp = weth_dai(10u128.pow(22), 500);
+        assert!(close(leg_quote_reserve(&opp, Leg::Sell).unwrap(), 5e23));
+        assert!(close(liquidity_fraction(&opp, Leg::Sell).unwrap(), 0.001));
+
+        // Split route: 40% of the base sold elsewhere, primary sell leg sees 60%
+        let mut opp = usdc_weth(10u128.pow(16), 500);
+        opp.split_sell = Some(crate::types::SplitSell {
+            dex: DexType::SushiV3_030,
+            pool_address: Address::from_low_u64_be(0xc),
+            price: 0.000_4,
+            liquidity: Some(10u128.pow(16)),
+            share_bps: 4_000,
+        });
+        assert!(close(liquidity_fraction(&opp, Leg::Sell).unwrap(), 0.000_6));
+        assert!(close(liquidity_fraction(&opp, Leg::Buy).unwrap(), 0.001));
+
+        // Unknown sell-leg depth is skipped, not failed
+        let mut opp = usdc_weth(10u128.pow(16), 500);
+        opp.sell_pool_liquidity = None;
+        assert_eq!(liquidity_fraction(&opp, Leg::Sell), None);
+    }
+
+    #[test]
+    fn test_floor_checks_both_legs_with_pair_override() {
+        let mut g = guard("reject", 0.05);
+        let mut opp = usdc_weth(10u128.pow(16), 500);
+        opp.sell_pool_liquidity = Some(999_999_999);
+        assert_eq!(
+            g.check(&mut opp),
+            LiquidityVerdict::Rejected(LiquidityRejection::BelowMinimum {
+                leg: Leg::Sell,
+                liquidity: 999_999_999,
+                min: 1_000_000_000,
+            })
+        );
+
+        // WETH/DAI override (1e20) is stricter than the chain default
+        let mut opp = weth_dai(10u128.pow(19), 1);
+        assert!(matches!(
+            g.check(&mut opp),
+            LiquidityVerdict::Rejected(LiquidityRejection::BelowMinimum { leg: Leg::Buy, .. })
+        ));
+        assert_eq!(g.stats().rejected_min, 2);
+    }
+
+    #[test]
+    fn test_reject_vs_downsize_mode() {
+        // $500 against $5k virtual depth = 10% > 5%
+        let mut g = guard("reject", 0.05);
+        let mut opp = usdc_weth(10u128.pow(14), 500);
+        let verdict = g.check(&mut opp);
+        match &verdict {
+            LiquidityVerdict::Rejected(r @ LiquidityRejection::TooDeep { fraction, .. }) => {
+                assert!(close(*fraction, 0.10));
+                assert!(r.to_string().starts_with(LIQUIDITY_GUARD_PREFIX));
+            }
+            other => panic!("expected TooDeep, got {:?}", other),
+        }
+        assert_eq!(opp.trade_size.raw(), U256::from(500_000_000u64));
+
+        // Downsize: halved to 5% of depth, profit and USD size scale with it
+        let mut g = guard("downsize", 0.05);
+        let mut opp = usdc_weth(10u128.pow(14), 500);
+        assert!(matches!(g.check(&mut opp), LiquidityVerdict::Downsized { .. }));
+        assert_eq!(opp.trade_size.raw(), U256::from(250_000_000u64));
+        assert!(close(opp.estimated_profit, 1.0));
+        assert!(close(opp.trade_size_usd, 250.0));
+
+        // Downsized below MIN_PROFIT_USD → rejected, size untouched
+        let mut opp = usdc_weth(10u128.pow(13), 500);
+        opp.estimated_profit = 0.5;
+        assert!(matches!(
+            g.check(&mut opp),
+            LiquidityVerdict::Rejected(LiquidityRejection::DownsizedUnprofitable { .. })
+        ));
+        assert_eq!(opp.trade_size.raw(), U256::from(500_000_000u64));
+        assert_eq!(g.stats(), &LiquidityGuardStats { checked: 2, downsized: 1, rejected_min: 0, rejected_depth: 1 });
+
+        // Within the fraction passes untouched
+        let mut opp = usdc_weth(10u128.pow(16), 500);
+        assert_eq!(g.check(&mut opp), LiquidityVerdict::Pass);
+    }
+}
@@ -0,0 +1,656 @@
+//! Pool Migration Detector — dead pools and redeployed replacements
+//!
+//! Purpose:
+//!     A DEX-side incident can move a pool's liquidity to a redeployed pool at
+//!     a new address (proxy upgrade, factory redeploy). The old address keeps
+//!     answering calls with near-zero liquidity and the bot keeps syncing the
+//!     corpse. This module notices the collapse, asks the factory which address
+//!     is canonical now, and proposes (or applies) the swap.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Kyber Elastic depth (baseL + reinvestL) and factory lookup
+//!
+//! Design:
+//!     - Every CHECK_INTERVAL_BLOCKS blocks, one Multicall3 aggregate3 reads
+//!       liquidity() (V3; Kyber Elastic: getLiquidityState()) or getReserves()
+//!       (V2) for every synced pool. Reading
+//!       on-chain matters: event sync only refreshes a pool on Swap/Sync, and a
+//!       drained pool emits neither.
+//!     - Depth = V3 liquidity, or sqrt(reserve0 × reserve1) for V2 (the same
+//!       L = sqrt(x·y) quantity). Each pool remembers its peak depth.
+//!     - A pool is collapsed while depth < MIGRATION_COLLAPSE_RATIO × peak. Once
+//!       collapsed for MIGRATION_COLLAPSE_BLOCKS blocks it becomes a candidate
+//!       and the factory is queried (getPool / poolByPair / getPair); repeat
+//!       lookups for the same pool are spaced MIGRATION_COLLAPSE_BLOCKS apart.
+//!     - A different canonical address whose depth is ≥ HEALTHY_RATIO × the old
+//!       peak is a migration suggestion: appended to pending_pool_changes.json,
+//!       logged, and sent to ALERT_WEBHOOK_URL. One suggestion per old address.
+//!     - AUTO_MIGRATE=true: the caller hot-swaps the pool (state manager, event
+//!       sync address list, whitelist + history) and calls record_applied().
+
+use super::multicall_quoter::MulticallQuoter;
+use crate::pool::kyber_syncer::{decode_liquidity_state, fee_to_units, GET_LIQUIDITY_STATE_SELECTOR};
+use crate::pool::syncer::IUniswapV2Factory;
+use crate::pool::v3_syncer::UniswapV3Factory;
+use crate::pool::PoolStateManager;
+use crate::types::{BotConfig, DexType, TradingPair};
+use anyhow::{anyhow, Context, Result};
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+// Algebra (QuickSwap V3) factory: one pool per pair, no fee argument
+abigen!(
+    AlgebraFactory,
+    r#"[
+        function poolByPair(address tokenA, address tokenB) external view returns (address pool)
+    ]"#
+);
+
+/// Blocks between depth reads (~20s on Polygon)
+pub const CHECK_INTERVAL_BLOCKS: u64 = 10;
+
+/// Minimum depth of the replacement pool, as a fraction of the old pool's peak
+pub const HEALTHY_RATIO: f64 = 0.25;
+
+/// liquidity() — Uniswap/Sushi V3 and Algebra pools
+const LIQUIDITY_SELECTOR: [u8; 4] = [0x1a, 0x68, 0x65, 0x02];
+
+/// getReserves() — V2 pairs
+const GET_RESERVES_SELECTOR: [u8; 4] = [0x09, 0x02, 0xf1, 0xac];
+
+/// Collapse threshold and auto-migrate toggle
+#[derive(Debug, Clone)]
+pub struct MigrationConfig {
+    /// Blocks a pool must stay collapsed before the factory is queried (0 = disabled)
+    pub collapse_blocks: u64,
+    /// Collapsed = depth below this fraction of the pool's observed peak
+    pub collapse_ratio: f64,
+    /// Hot-swap suggested migrations instead of only reporting them
+    pub auto_migrate: bool,
+}
+
+impl MigrationConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            collapse_blocks: config.migration_collapse_blocks,
+            collapse_ratio: config.migration_collapse_ratio,
+            auto_migrate: config.auto_migrate,
+        }
+    }
+}
+
+/// One synced pool whose depth is tracked
+#[derive(Debug, Clone)]
+pub struct WatchedPool {
+    pub address: Address,
+    pub dex: DexType,
+    pub pair: TradingPair,
+    /// V3 fee tier (0 for V2 and Algebra)
+    pub fee: u32,
+}
+
+impl WatchedPool {
+    /// liquidity() or getReserves() sub-call for this pool
+    fn depth_call(&self) -> (Address, Vec<u8>) {
+        let selector = if self.dex.is_kyber_elastic() {
+            GET_LIQUIDITY_STATE_SELECTOR
+        } else if self.dex.is_v3() {
+            LIQUIDITY_SELECTOR
+        } else {
+            GET_RESERVES_SELECTOR
+        };
+        (self.address, selector.to_vec())
+    }
+}
+
+/// Every V3 and V2 pool currently in the state manager
+pub fn watched_pools(state: &PoolStateManager) -> Vec<WatchedPool> {
+    let v3 = state.get_all_v3_pools().into_iter().map(|p| WatchedPool {
+        address: p.address,
+        dex: p.dex,
+        pair: p.pair,
+        fee: if p.dex.is_quickswap_v3() { 0 } else { p.fee },
+    });
+    let v2 = state.get_all_pools().into_iter().map(|p| WatchedPool {
+        address: p.address,
+        dex: p.dex,
+        pair: p.pair,
+        fee: 0,
+    });
+    v3.chain(v2).collect()
+}
+
+/// Depth from a liquidity() or getReserves() result (None = call failed)
+fn decode_depth(dex: DexType, result: &(bool, Vec<u8>)) -> Option<u128> {
+    let (success, data) = result;
+    if !success || data.len() < 32 {
+        return None;
+    }
+    if dex.is_kyber_elastic() {
+        return decode_liquidity_state(data);
+    }
+    let word0 = U256::from_big_endian(&data[..32]);
+    if dex.is_v3() {
+        return Some(word0.low_u128());
+    }
+    if data.len() < 64 {
+        return None;
+    }
+    let reserve1 = U256::from_big_endian(&data[32..64]);
+    // uint112 × uint112 fits in 224 bits — no overflow
+    Some((word0 * reserve1).integer_sqrt().low_u128())
+}
+
+/// A pool that has stayed collapsed long enough to ask the factory about
+#[derive(Debug, Clone)]
+pub struct MigrationCandidate {
+    pub pool: WatchedPool,
+    pub peak_depth: u128,
+    pub depth: u128,
+    /// First block the collapse was observed
+    pub collapsed_since: u64,
+}
+
+/// Proposed (or applied) old → new address change, as written to
+/// pending_pool_changes.json
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationSuggestion {
+    pub detected_at: String,
+    pub block: u64,
+    pub pair: String,
+    pub dex: String,
+    pub fee_tier: u32,
+    pub old_address: Address,
+    pub new_address: Address,
+    pub peak_depth: u128,
+    pub old_depth: u128,
+    pub new_depth: u128,
+    pub collapsed_since: u64,
+    pub applied: bool,
+}
+
+/// Per-pool depth history
+#[derive(Debug, Default)]
+struct DepthTrack {
+    peak: u128,
+    collapsed_since: Option<u64>,
+    last_lookup: Option<u64>,
+}
+
+/// Collapse detector + suggestion ledger
+pub struct PoolMigrationMonitor {
+    config: MigrationConfig,
+    tracked: HashMap<Address, DepthTrack>,
+    /// Old addresses that already have a suggestion (one per incident)
+    suggested: HashSet<Address>,
+    suggestions: Vec<MigrationSuggestion>,
+    last_check_block: Option<u64>,
+    /// pending_pool_changes.json
+    pending_path: Option<PathBuf>,
+    webhook_url: Option<String>,
+    client: reqwest::Client,
+}
+
+impl PoolMigrationMonitor {
+    pub fn new(config: MigrationConfig) -> Self {
+        info!(
+            "Pool migration detector: depth < {:.0}% of peak for {} blocks | auto migrate {}",
+            config.collapse_ratio * 100.0, config.collapse_blocks,
+            if config.auto_migrate { "ON" } else { "off" }
+        );
+        Self {
+            config,
+            tracked: HashMap::new(),
+            suggested: HashSet::new(),
+            suggestions: Vec::new(),
+            last_check_block: None,
+            pending_path: None,
+            webhook_url: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Keep suggestions in `data_dir`/pending_pool_changes.json. Existing
+    /// entries are loaded so a restart does not re-suggest the same migration.
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Migration: failed to create {}: {}", data_dir, e);
+        }
+        let path = dir.join("pending_pool_changes.json");
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<Vec<MigrationSuggestion>>(&content) {
+                Ok(existing) => {
+                    self.suggested.extend(existing.iter().map(|s| s.old_address));
+                    self.suggestions = existing;
+                }
+                Err(e) => warn!("Migration: ignoring unreadable {}: {}", path.display(), e),
+            }
+        }
+        self.pending_path = Some(path);
+        self
+    }
+
+    pub fn with_webhook(mut self, url: Option<String>) -> Self {
+        self.webhook_url = url;
+        self
+    }
+
+    pub fn auto_migrate(&self) -> bool {
+        self.config.auto_migrate
+    }
+
+    /// True when a depth read should run at `block`
+    pub fn is_due(&self, block: u64) -> bool {
+        self.config.collapse_blocks > 0
+            && self.last_check_block.is_none_or(|last| block >= last + CHECK_INTERVAL_BLOCKS)
+    }
+
+    /// Read every pool's depth in one aggregate3 and return new candidates
+    pub async fn check<M: Middleware + 'static>(
+        &mut self,
+        quoter: &MulticallQuoter<M>,
+        pools: &[WatchedPool],
+        block: u64,
+    ) -> Result<Vec<MigrationCandidate>> {
+        self.last_check_block = Some(block);
+        if pools.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sub_calls: Vec<_> = pools.iter().map(WatchedPool::depth_call).collect();
+        let results = quoter.aggregate3_at(&sub_calls, None).await?;
+        self.observe(block, pools, &results)
+    }
+
+    /// Current depth of a single pool (used on the factory's answer)
+    pub async fn depth_of<M: Middleware + 'static>(
+        quoter: &MulticallQuoter<M>,
+        pool: &WatchedPool,
+    ) -> Result<u128> {
+        let results = quoter.aggregate3_at(&[pool.depth_call()], None).await?;
+        results.first()
+            .and_then(|r| decode_depth(pool.dex, r))
+            .ok_or_else(|| anyhow!("depth read failed for {:?}", pool.address))
+    }
+
+    /// Update peaks / collapse timers and return pools due for a factory lookup
+    pub(crate) fn observe(
+        &mut self,
+        block: u64,
+        pools: &[WatchedPool],
+        results: &[(bool, Vec<u8>)],
+    ) -> Result<Vec<MigrationCandidate>> {
+        if results.len() != pools.len() {
+            return Err(anyhow!(
+                "depth batch returned {} results for {} pools",
+                results.len(), pools.len()
+            ));
+        }
+
+        let mut candidates = Vec::new();
+        for (pool, result) in pools.iter().zip(results) {
+            let depth = match decode_depth(pool.dex, result) {
+                Some(depth) => depth,
+                None => {
+                    debug!("Migration: depth read failed for {} {:?} — skipped", pool.pair.symbol, pool.address);
+                    continue;
+                }
+            };
+            let track = self.tracked.entry(pool.address).or_default();
+            track.peak = track.peak.max(depth);
+            let floor = track.peak as f64 * self.config.collapse_ratio;
+            if track.peak == 0 || depth as f64 >= floor {
+                if track.collapsed_since.take().is_some() {
+                    info!("Migration: {} {:?} depth recovered", pool.pair.symbol, pool.address);
+                }
+                continue;
+            }
+
+            let since = *track.collapsed_since.get_or_insert_with(|| {
+                warn!(
+                    "Migration: {} {:?} depth collapsed to {} (peak {})",
+                    pool.pair.symbol, pool.address, depth, track.peak
+                );
+                block
+            });
+            let collapsed_long_enough = block.saturating_sub(since) >= self.config.collapse_blocks;
+            let lookup_due = track.last_lookup
+                .is_none_or(|last| block.saturating_sub(last) >= self.config.collapse_blocks);
+            if collapsed_long_enough && lookup_due && !self.suggested.contains(&pool.address) {
+                track.last_lookup = Some(block);
+                candidates.push(MigrationCandidate {
+                    pool: pool.clone(),
+                    peak_depth: track.peak,
+                    depth,
+                    collapsed_since: since,
+                });
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Compare the factory's canonical address with the collapsed pool.
+    /// Some(suggestion) when it points elsewhere and the new pool is healthy.
+    pub fn evaluate(
+        &mut self,
+        candidate: &MigrationCandidate,
+        canonical: Address,
+        new_depth: u128,
+        block: u64,
+    ) -> Option<MigrationSuggestion> {
+        let old = candidate.pool.address;
+        if canonical.is_zero() || canonical == old {
+            info!(
+                "Migration: factory still maps {} to {:?} — collapse is not a migration",
+                candidate.pool.pair.symbol, old
+            );
+            return None;
+        }
+        if (new_depth as f64) < candidate.peak_depth as f64 * HEALTHY_RATIO {
+            info!(
+                "Migration: {} canonical pool {:?} is not healthy yet (depth {} vs peak {})",
+                candidate.pool.pair.symbol, canonical, new_depth, candidate.peak_depth
+            );
+            return None;
+        }
+        self.suggested.insert(old);
+        Some(MigrationSuggestion {
+            detected_at: chrono::Utc::now().to_rfc3339(),
+            block,
+            pair: candidate.pool.pair.symbol.clone(),
+            dex: format!("{:?}", candidate.pool.dex),
+            fee_tier: candidate.pool.fee,
+            old_address: old,
+            new_address: canonical,
+            peak_depth: candidate.peak_depth,
+            old_depth: candidate.depth,
+            new_depth,
+            collapsed_since: candidate.collapsed_since,
+            applied: false,
+        })
+    }
+
+    /// Record a suggestion in the pending-changes file and alert
+    pub async fn suggest(&mut self, suggestion: &MigrationSuggestion) {
+        let msg = format!(
+            "POOL MIGRATION: {} ({} {}) {:?} → {:?} | depth {} (peak {}) → {} | {}",
+            suggestion.pair, suggestion.dex, suggestion.fee_tier,
+            suggestion.old_address, suggestion.new_address,
+            suggestion.old_depth, suggestion.peak_depth, suggestion.new_depth,
+            if self.config.auto_migrate { "auto-migrating" } else { "review pending_pool_changes.json" }
+        );
+        error!("{}", msg);
+        self.suggestions.push(suggestion.clone());
+        self.write_pending();
+        self.post_webhook(&msg).await;
+    }
+
+    /// The caller hot-swapped `old` → `new`: mark the suggestion applied and
+    /// start tracking the new address from scratch
+    pub async fn record_applied(&mut self, old: Address, new: Address) {
+        self.tracked.remove(&old);
+        for s in self.suggestions.iter_mut().filter(|s| s.old_address == old && s.new_address == new) {
+            s.applied = true;
+        }
+        self.write_pending();
+        let msg = format!("POOL MIGRATION applied: {:?} → {:?}", old, new);
+        info!("{}", msg);
+        self.post_webhook(&msg).await;
+    }
+
+    /// All suggestions made so far (loaded + this run)
+    pub fn suggestions(&self) -> &[MigrationSuggestion] {
+        &self.suggestions
+    }
+
+    fn write_pending(&self) {
+        let path = match self.pending_path {
+            Some(ref p) => p,
+            None => return,
+        };
+        match serde_json::to_string_pretty(&self.suggestions) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    warn!("Migration: failed to write {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => warn!("Migration: failed to serialize suggestions: {}", e),
+        }
+    }
+
+    async fn post_webhook(&self, content: &str) {
+        let url = match self.webhook_url {
+            Some(ref u) => u,
+            None => return,
+        };
+        let body = serde_json::json!({ "content": content });
+        let sent = self.client.post(url)
+            .json(&body)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await;
+        if let Err(e) = sent {
+            warn!("Migration: webhook alert failed: {}", e);
+        }
+    }
+}
+
+/// Ask the pool's factory which address it maps (pair, fee) to now
+pub async fn canonical_pool<M: Middleware + 'static>(
+    provider: Arc<M>,
+    config: &BotConfig,
+    pool: &WatchedPool,
+) -> Result<Address> {
+    let (token_a, token_b) = (pool.pair.token0, pool.pair.token1);
+    let missing = || anyhow!("no factory configured for {:?}", pool.dex);
+    let address = match pool.dex {
+        DexType::UniswapV3_001 | DexType::UniswapV3_005 | DexType::UniswapV3_030 | DexType::UniswapV3_100 => {
+            let factory = config.uniswap_v3_factory.ok_or_else(missing)?;
+            UniswapV3Factory::new(factory, provider).get_pool(token_a, token_b, pool.fee).call().await
+        }
+        DexType::SushiV3_001 | DexType::SushiV3_005 | DexType::SushiV3_030 => {
+            let factory = config.sushiswap_v3_factory.ok_or_else(missing)?;
+            UniswapV3Factory::new(factory, provider).get_pool(token_a, token_b, pool.fee).call().await
+        }
+        DexType::QuickswapV3 => {
+            let factory = config.quickswap_v3_factory.ok_or_else(missing)?;
+            AlgebraFactory::new(factory, provider).pool_by_pair(token_a, token_b).call().await
+        }
+        DexType::Uniswap | DexType::Quickswap | DexType::QuickSwapV2 => {
+            IUniswapV2Factory::new(config.uniswap_factory, provider).get_pair(token_a, token_b).call().await
+        }
+        DexType::Sushiswap | DexType::SushiSwapV2 => {
+            IUniswapV2Factory::new(config.sushiswap_factory, provider).get_pair(token_a, token_b).call().await
+        }
+        DexType::Apeswap => {
+            let factory = config.apeswap_factory.ok_or_else(missing)?;
+            IUniswapV2Factory::new(factory, provider).get_pair(token_a, token_b).call().await
+        }
+        // Kyber's factory getPool(tokenA, tokenB, swapFeeUnits) has the Uniswap V3 signature
+        DexType::KyberElastic_001 | DexType::KyberElastic_004 | DexType::KyberElastic_030 | DexType::KyberElastic_100 => {
+            let factory = config.kyber_elastic_factory.ok_or_else(missing)?;
+            UniswapV3Factory::new(factory, provider).get_pool(token_a, token_b, fee_to_units(pool.fee)).call().await
+        }
+        // Curve and DODO pools are whitelisted by address; there is no factory mapping to follow
+        DexType::CurveStable | DexType::DodoV2 => return Err(missing()),
+    };
+    address.with_context(|| format!("factory lookup failed for {} {:?}", pool.pair.symbol, pool.dex))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_POOL: &str = "0x45dda9cb7c25131df268515131f647d726f50608";
+    const NEW_POOL: &str = "0x00000000000000000000000000000000000000aa";
+
+    fn test_config(auto_migrate: bool) -> MigrationConfig {
+        MigrationConfig { collapse_blocks: 30, collapse_ratio: 0.05, auto_migrate }
+    }
+
+    fn weth_usdc_pool() -> WatchedPool {
+        WatchedPool {
+            address: OLD_POOL.parse().unwrap(),
+            dex: DexType::UniswapV3_005,
+            pair: TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            fee: 500,
+        }
+    }
+
+    fn liquidity_result(liquidity: u128) -> (bool, Vec<u8>) {
+        let mut word = [0u8; 32];
+        U256::from(liquidity).to_big_endian(&mut word);
+        (true, word.to_vec())
+    }
+
+    /// Feed one depth reading per CHECK_INTERVAL_BLOCKS from `start`
+    fn feed(
+        monitor: &mut PoolMigrationMonitor,
+        pool: &WatchedPool,
+        start: u64,
+        depths: &[u128],
+    ) -> Vec<MigrationCandidate> {
+        let mut out = Vec::new();
+        for (i, depth) in depths.iter().enumerate() {
+            let block = start + i as u64 * CHECK_INTERVAL_BLOCKS;
+            out.extend(monitor.observe(block, std::slice::from_ref(pool), &[liquidity_result(*depth)]).unwrap());
+        }
+        out
+    }
+
+    #[test]
+    fn test_selectors_and_depth_decoding() {
+        assert_eq!(ethers::utils::id("liquidity()"), LIQUIDITY_SELECTOR);
+        assert_eq!(ethers::utils::id("getReserves()"), GET_RESERVES_SELECTOR);
+
+        assert_eq!(decode_depth(DexType::UniswapV3_005, &liquidity_result(42)), Some(42));
+        assert_eq!(decode_depth(DexType::UniswapV3_005, &(false, vec![])), None);
+
+        // V2: sqrt(4e18 × 9e6) = 6e12
+        let mut data = vec![0u8; 96];
+        U256::from(4_000_000_000_000_000_000u128).to_big_endian(&mut data[..32]);
+        U256::from(9_000_000u64).to_big_endian(&mut data[32..64]);
+        assert_eq!(decode_depth(DexType::QuickSwapV2, &(true, data)), Some(6_000_000_000_000));
+    }
+
+    #[test]
+    fn test_collapse_must_persist_before_candidate() {
+        let mut monitor = PoolMigrationMonitor::new(test_config(false));
+        let pool = weth_usdc_pool();
+
+        // Healthy, then a brief dip that recovers: no candidate
+        assert!(feed(&mut monitor, &pool, 1_000, &[1_000_000, 1_000_000, 10_000, 900_000]).is_empty());
+        // Depth 6% of peak is above the 5% floor: not collapsed
+        assert!(feed(&mut monitor, &pool, 1_040, &[60_000; 5]).is_empty());
+
+        // Collapsed at 1_100; candidate once 30 blocks have passed (1_130)
+        let candidates = feed(&mut monitor, &pool, 1_100, &[10_000, 10_000, 10_000, 10_000, 10_000]);
+        assert_eq!(candidates.len(), 1);
+        let c = &candidates[0];
+        assert_eq!((c.peak_depth, c.depth, c.collapsed_since), (1_000_000, 10_000, 1_100));
+
+        // Next lookup only after another collapse_blocks (1_160)
+        assert!(feed(&mut monitor, &pool, 1_150, &[10_000]).is_empty());
+        assert_eq!(feed(&mut monitor, &pool, 1_160, &[10_000]).len(), 1);
+
+        // A pool never seen liquid is not a candidate
+        let mut fresh = PoolMigrationMonitor::new(test_config(false));
+        assert!(feed(&mut fresh, &pool, 1_000, &[0; 10]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_factory_new_address_produces_suggestion_once() {
+        let dir = std::env::temp_dir().join(format!("dexarb_migration_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut monitor = PoolMigrationMonitor::new(test_config(false))
+            .with_persistence(dir.to_str().unwrap());
+        let pool = weth_usdc_pool();
+        let candidate = feed(&mut monitor, &pool, 1_000, &[1_000_000, 10_000, 10_000, 10_000, 10_000])
+            .pop()
+            .unwrap();
+        let new: Address = NEW_POOL.parse().unwrap();
+
+        // Factory still returns the old address, zero, or an unhealthy pool: nothing
+        assert!(monitor.evaluate(&candidate, pool.address, 0, 1_040).is_none());
+        assert!(monitor.evaluate(&candidate, Address::zero(), 0, 1_040).is_none());
+        assert!(monitor.evaluate(&candidate, new, 200_000, 1_040).is_none());
+
+        // Healthy new address: suggestion, persisted, and only once
+        let suggestion = monitor.evaluate(&candidate, new, 800_000, 1_040).unwrap();
+        assert_eq!((suggestion.old_address, suggestion.new_address), (pool.address, new));
+        assert!(!suggestion.applied);
+        monitor.suggest(&suggestion).await;
+        assert!(feed(&mut monitor, &pool, 1_100, &[10_000; 5]).is_empty());
+
+        let reloaded = PoolMigrationMonitor::new(test_config(false))
+            .with_persistence(dir.to_str().unwrap());
+        assert_eq!(reloaded.suggestions(), &[suggestion]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_auto_migrate_hot_swaps_state_and_marks_applied() {
+        use crate::filters::WhitelistFilter;
+        use crate::types::V3PoolState;
+
+        let dir = std::env::temp_dir().join(format!("dexarb_migration_auto_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut monitor = PoolMigrationMonitor::new(test_config(true))
+            .with_persistence(dir.to_str().unwrap());
+        assert!(monitor.auto_migrate());
+
+        let pool = weth_usdc_pool();
+        let new: Address = NEW_POOL.parse().unwrap();
+        let state = PoolStateManager::new();
+        let old_state = V3PoolState {
+            address: pool.address,
+            dex: pool.dex,
+            pair: pool.pair.clone(),
+            sqrt_price_x96: U256::from(1u64) << 96,
+            tick: 0,
+            fee: 500,
+            liquidity: 10_000,
+            token0_decimals: 18,
+            token1_decimals: 6,
+            last_updated: 1_000,
+        };
+        state.update_v3_pool(old_state.clone());
+        let mut whitelist: WhitelistFilter = serde_json::from_str::<crate::filters::PoolWhitelist>(&format!(
+            r#"{{"version":"1.0","last_updated":"","config":{{"default_min_liquidity":0,"whitelist_enforcement":"strict"}},
+               "whitelist":{{"pools":[{{"address":"{}","pair":"WETH/USDC","dex":"UniswapV3","fee_tier":500,"status":"active"}}]}},
+               "blacklist":{{"pools":[],"fee_tiers":[]}}}}"#,
+            OLD_POOL
+        )).map(WhitelistFilter::from_config).unwrap();
+
+        let candidate = feed(&mut monitor, &pool, 1_000, &[1_000_000, 10_000, 10_000, 10_000, 10_000])
+            .pop()
+            .unwrap();
+        let suggestion = monitor.evaluate(&candidate, new, 900_000, 1_040).unwrap();
+        monitor.suggest(&suggestion).await;
+
+        // What main does on AUTO_MIGRATE: swap state + whitelist, then record
+        let new_state = V3PoolState { address: new, liquidity: 900_000, ..old_state };
+        assert!(state.replace_v3_pool(&pool.address, new_state).is_some());
+        whitelist.migrate_pool(&pool.address, &new, 1_040, "collapsed").unwrap();
+        monitor.record_applied(pool.address, new).await;
+
+        let pools = state.get_all_v3_pools();
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].address, new);
+        assert!(whitelist.is_pool_allowed(&new, 500, "WETH/USDC"));
+        assert_eq!(whitelist.raw.history.len(), 1);
+        assert!(monitor.suggestions()[0].applied);
+
+        let persisted: Vec<MigrationSuggestion> = serde_json::from_str(
+            &std::fs::read_to_string(dir.join("pending_pool_changes.json")).unwrap(),
+        ).unwrap();
+        assert!(persisted[0].applied);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
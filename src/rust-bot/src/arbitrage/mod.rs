@@ -5,13 +5,112 @@
 //! Author: AI-Generated
 //! Created: 2026-01-27
 //! Modified: 2026-01-28 - Added executor (Day 4)
+//! Modified: 2026-10-16 - Added preflight (snapshot-consistent pre-execution batch)
+//! Modified: 2026-10-16 - Added canary (size-capped trial window for new pools)
+//! Modified: 2026-10-16 - WBTC/USDC (8 vs 6 decimals) end-to-end pipeline tests
+//! Modified: 2026-10-16 - Added stranded (ArbExecutor balance alarm + rescue)
+//! Modified: 2026-10-16 - Added connections (signing connections built once, kept warm)
+//! Modified: 2026-10-16 - Added migration (collapsed pool → factory canonical address)
+//! Modified: 2026-10-16 - Added prescreen_export (pre-screen verdicts + pool snapshots JSONL)
+//! Modified: 2026-10-16 - Added outcome (trade result classification) + chaos injection tests
+//! Modified: 2026-10-16 - Added execution_constraints (multi-opp block capture analysis)
+//! Modified: 2026-10-16 - Added token_validation (decimals/symbol drift quarantine)
+//! Modified: 2026-10-16 - Added inflight (crash-safe submission journal + startup recovery)
+//! Modified: 2026-10-16 - Added liquidity_guard (per-leg liquidity floor + depth check before pre-screen)
+//! Modified: 2026-10-16 - Added slippage (per-route realized slippage → min_out allowance)
+//! Modified: 2026-10-16 - Added finality (provisional trade records until confirmation depth; reorg reversal)
+//! Modified: 2026-10-16 - Added exposure (per-quote-token wallet exposure pacing at dispatch)
+//! Modified: 2026-10-16 - Added cooldown_sim (replay recorded route attempts under candidate cooldown policies)
+//! Modified: 2026-10-16 - Added prescreen_policy (per-route pre-screen skipping earned by success streaks)
+//! Modified: 2026-10-16 - Added burst (triage mode for large-move blocks: spread floor + per-silo top-K)
+//! Modified: 2026-10-16 - Added competition (winning priority fee distributions → win probability, EV ranking)
+//! Modified: 2026-10-16 - Added sizing (closed-form V2 / quoted-ladder V3 optimal trade size)
+//! Modified: 2026-10-16 - Added flashloan (Aave V3 flashloan funding for routes beyond the wallet balance)
+//! Modified: 2026-10-16 - Added stats (per-pair / per-route P&L counters, periodic report, stats.json)
+//! Modified: 2026-10-16 - Added approvals (startup allowance pre-flight, grant/revoke-all planning)
+//! Modified: 2026-10-16 - Added balance (quote token balance cache; trade size clamped to the wallet balance)
+//! Modified: 2026-10-16 - Added parallel (same-block top-K submission on disjoint pools, nonce ladder)
+//! Modified: 2026-10-16 - Added gas_cache (per-route gas limits from receipts, skips estimateGas)
+//! Modified: 2026-10-16 - Added registry (opportunity dedup across block loop + mempool, same-scan merge)
+//! Modified: 2026-10-16 - Added inventory (wallet token drift alerts, rebalance plans, AUTO_REBALANCE swaps)
+//! Modified: 2026-10-16 - Added split (two-pool sell split across fee tiers, SPLIT_ROUTING)
+//! Modified: 2026-10-16 - Added threshold (per-route minimum profit from revert rate and gas)
+//! Modified: 2026-10-16 - Added wallets (PRIVATE_KEYS: per-wallet nonce, balances, in-flight txs; idle wallet per submission)
+//! Modified: 2026-10-16 - Added permit2 (EIP-712 signature transfers for wallet-funded atomic trades)
+//! Modified: 2026-10-16 - Added archive (every detected opportunity + outcome label, daily JSONL)
 
+pub mod approvals;
+pub mod archive;
+pub mod balance;
+pub mod burst;
+pub mod canary;
+pub mod competition;
+pub mod connections;
 pub mod cooldown;
+pub mod cooldown_sim;
 pub mod detector;
+pub mod execution_constraints;
 pub mod executor;
+pub mod exposure;
+pub mod finality;
+pub mod flashloan;
+pub mod gas_cache;
+pub mod inflight;
+pub mod inventory;
+pub mod liquidity_guard;
+pub mod migration;
 pub mod multicall_quoter;
+pub mod outcome;
+pub mod parallel;
+pub mod permit2;
+pub mod preflight;
+pub mod prescreen_export;
+pub mod prescreen_policy;
+pub mod registry;
+pub mod sizing;
+pub mod slippage;
+pub mod split;
+pub mod stats;
+pub mod stranded;
+pub mod threshold;
+pub mod token_validation;
+pub mod wallets;
 
-pub use cooldown::RouteCooldown;
+#[cfg(test)]
+mod chaos_tests;
+#[cfg(test)]
+mod finality_tests;
+#[cfg(test)]
+mod inflight_tests;
+#[cfg(test)]
+mod wbtc_pipeline_tests;
+
+pub use approvals::{ApprovalAction, ApprovalSummary, ApprovalTarget};
+pub use archive::OpportunityArchive;
+pub use balance::{BalanceConfig, BalanceTracker};
+pub use burst::{BurstConfig, BurstTransition, BurstTriage};
+pub use canary::{CanaryConfig, CanaryTracker};
+pub use competition::{CompetitionConfig, CompetitionModel, EvCandidate, LostRace};
+pub use cooldown::{CooldownSource, RouteCooldown};
 pub use detector::OpportunityDetector;
+pub use execution_constraints::ExecutionConstraintAnalyzer;
 pub use executor::TradeExecutor;
+pub use exposure::{ExposureConfig, ExposurePacer};
+pub use finality::{FinalityTracker, Reversal};
+pub use flashloan::FlashFallback;
+pub use inflight::{InflightJournal, RecoveryPolicy, RecoveryReport};
+pub use liquidity_guard::{LiquidityGuard, LiquidityVerdict};
+pub use migration::{MigrationConfig, PoolMigrationMonitor};
 pub use multicall_quoter::{MulticallQuoter, VerifiedOpportunity};
+pub use outcome::{CooldownEffect, NextStep, TradeOutcome};
+pub use parallel::{select_disjoint, ParallelBatch};
+pub use permit2::PermitFallback;
+pub use preflight::{PreflightBatcher, PreflightBundle, PreflightRejection};
+pub use prescreen_export::{AttemptRecord, PrescreenExporter};
+pub use prescreen_policy::{PrescreenDecision, PrescreenPolicy, PrescreenPolicyConfig};
+pub use registry::OpportunityRegistry;
+pub use sizing::{OptimalSizer, SizingVerdict};
+pub use slippage::{SlippageConfig, SlippageTracker};
+pub use stats::StatsTracker;
+pub use stranded::{StrandedConfig, StrandedFundsMonitor};
+pub use token_validation::{TokenMetadataValidator, TokenValidationConfig};
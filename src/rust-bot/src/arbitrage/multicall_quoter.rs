@@ -1,8 +1,10 @@
 //! Multicall3 Batch Quoter Pre-Screening
 //!
-//! Batch-verifies arbitrage opportunities by encoding buy+sell Quoter calls
-//! into a single Multicall3 `aggregate3` RPC call. Filters out opportunities
-//! where either leg cannot be filled, then ranks survivors by quoted profit.
+//! Batch-verifies arbitrage opportunities with two Multicall3 `aggregate3`
+//! RPC calls for the whole set: the first quotes every buy leg at trade_size,
+//! the second quotes each sell leg on exactly the amount its buy leg returned.
+//! Filters out opportunities where either leg cannot be filled, then ranks
+//! survivors by quoted profit (sell_out - trade_size).
 //!
 //! This is a PRE-SCREENING step only. The executor retains its own per-leg
 //! Quoter safety checks during actual execution.
@@ -20,13 +22,26 @@
 //! Author: AI-Generated
 //! Created: 2026-01-29
 //! Modified: 2026-01-30 - Cross-DEX: tri-quoter (V1 Uni, V2 Sushi, Algebra QuickSwap)
-
+//! Modified: 2026-10-16 - Expose encoders + block-pinned aggregate3 for preflight batching
+//! Modified: 2026-10-16 - Quote/base amounts logged in token units (size in USD)
+//! Modified: 2026-10-16 - trade_size is a QuoteAmount (raw at the ABI boundary)
+//! Modified: 2026-10-16 - MULTICALL3_ADDRESS overrides the canonical Multicall3 deployment
+//! Modified: 2026-10-16 - KyberSwap Elastic QuoterV2 (fee in swapFeeUnits, amountOut in word 1)
+//! Modified: 2026-10-16 - Split-sell routes passed through (not quotable as one sell leg)
+//! Modified: 2026-10-16 - batch_verify timed into the shared LatencyRecorder (with_latency)
+//! Modified: 2026-10-16 - QuoterV1 / QuoterV2 encoders crate-visible (config_check quoter flavor probe)
+//! Modified: 2026-10-16 - Two-pass verification: sell legs quoted on the buy legs' quoted output, not an estimate
+//! Modified: 2026-10-16 - DODO routes pass through (PMM output deterministic from synced state)
+
+use crate::latency::{LatencyRecorder, Stage};
+use crate::pool::kyber_syncer::fee_to_units;
 use crate::types::{ArbitrageOpportunity, BotConfig, DexType};
 use anyhow::{anyhow, Context, Result};
 use ethers::abi::{self, ParamType, Token};
 use ethers::prelude::*;
 use ethers::types::{Address, Bytes, U256};
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, info, warn};
 
 /// Multicall3 deployed address (same on all EVM chains including Polygon)
@@ -59,10 +74,14 @@ const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
 
 /// Sell-leg estimate factor. Previously 0.95 (5% haircut) which created a phantom
 /// ~5% loss in profit calculation, rejecting every opportunity with <5% spread.
-/// Now 1.0: the pre-screen uses the midmarket estimate directly. The executor's
-/// own Quoter safety check provides the real protection.
+/// Now 1.0: estimates use the midmarket price directly. The pre-screen no longer
+/// estimates (its sell legs are chained on the quoted buy output); preflight
+/// and sizing still do.
 const SELL_ESTIMATE_FACTOR: f64 = 1.0;
 
+/// One quoter leg: (token in, token out, V3 fee tier)
+type LegCall = (Address, Address, u32);
+
 /// Result of batch verification for a single opportunity
 #[derive(Debug, Clone)]
 pub struct VerifiedOpportunity {
@@ -70,7 +89,8 @@ pub struct VerifiedOpportunity {
     pub original_index: usize,
     /// Quoted buy leg output (token1 amount received from buying)
     pub buy_quoted_out: U256,
-    /// Quoted sell leg output (token0 amount received from selling)
+    /// Quoted sell leg output (token0 amount received from selling), quoted on
+    /// exactly buy_quoted_out
     pub sell_quoted_out: U256,
     /// Net quoted profit in token0 raw units (sell_out - trade_size)
     pub quoted_profit_raw: i128,
@@ -103,8 +123,11 @@ pub struct MulticallQuoter<M: Middleware> {
     uniswap_quoter_address: Address,
     sushiswap_quoter_address: Option<Address>,
     quickswap_quoter_address: Option<Address>,
+    kyber_quoter_address: Option<Address>,
     /// When true, Uniswap V3 quoter uses V2 ABI (Base). Default false (V1, Polygon).
     uniswap_quoter_is_v2: bool,
+    /// batch_verify() durations (Stage::Verify); None = not timed
+    latency: Option<Arc<LatencyRecorder>>,
 }
 
 impl<M: Middleware + 'static> MulticallQuoter<M> {
@@ -112,9 +135,12 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
     /// Uniswap V3 QuoterV1 is required; SushiSwap V3 QuoterV2 and
     /// QuickSwap V3 (Algebra) QuoterV2 are optional.
     pub fn new(provider: Arc<M>, config: &BotConfig) -> Result<Self> {
-        let multicall_address: Address = MULTICALL3_ADDRESS
-            .parse()
-            .context("Invalid Multicall3 address constant")?;
+        let multicall_address: Address = match config.multicall3_address {
+            Some(address) => address,
+            None => MULTICALL3_ADDRESS
+                .parse()
+                .context("Invalid Multicall3 address constant")?,
+        };
 
         let uniswap_quoter_address = config
             .uniswap_v3_quoter
@@ -122,6 +148,7 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
 
         let sushiswap_quoter_address = config.sushiswap_v3_quoter;
         let quickswap_quoter_address = config.quickswap_v3_quoter;
+        let kyber_quoter_address = config.kyber_elastic_quoter;
         let uniswap_quoter_is_v2 = config.uniswap_v3_quoter_is_v2;
 
         info!(
@@ -137,14 +164,25 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
             uniswap_quoter_address,
             sushiswap_quoter_address,
             quickswap_quoter_address,
+            kyber_quoter_address,
             uniswap_quoter_is_v2,
+            latency: None,
         })
     }
 
+    /// Time every batch_verify() into `recorder`
+    pub fn with_latency(mut self, recorder: Arc<LatencyRecorder>) -> Self {
+        self.latency = Some(recorder);
+        self
+    }
+
     /// Get the correct quoter address for a DexType.
-    /// QuickSwap V3 → Algebra QuoterV2; SushiSwap V3 → SushiSwap QuoterV2; all else → Uniswap QuoterV1.
-    fn quoter_for_dex(&self, dex: DexType) -> Address {
-        if dex.is_quickswap_v3() {
+    /// QuickSwap V3 → Algebra QuoterV2; SushiSwap V3 → SushiSwap QuoterV2;
+    /// Kyber Elastic → Kyber QuoterV2; all else → Uniswap QuoterV1.
+    pub(crate) fn quoter_for_dex(&self, dex: DexType) -> Address {
+        if dex.is_kyber_elastic() {
+            self.kyber_quoter_address.unwrap_or(self.uniswap_quoter_address)
+        } else if dex.is_quickswap_v3() {
             self.quickswap_quoter_address.unwrap_or(self.uniswap_quoter_address)
         } else if dex.is_sushi_v3() {
             self.sushiswap_quoter_address.unwrap_or(self.uniswap_quoter_address)
@@ -153,18 +191,66 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
         }
     }
 
-    /// Batch verify all opportunities with a single RPC call.
+    /// Multicall3 contract address (target for getBlockNumber() sub-calls).
+    pub(crate) fn multicall_address(&self) -> Address {
+        self.multicall_address
+    }
+
+    /// Execute an arbitrary aggregate3 batch in one eth_call, optionally pinned
+    /// to a specific block so every sub-call reads the same state snapshot.
+    pub(crate) async fn aggregate3_at(
+        &self,
+        sub_calls: &[(Address, Vec<u8>)],
+        block: Option<u64>,
+    ) -> Result<Vec<(bool, Vec<u8>)>> {
+        let calldata = Self::build_aggregate3_calldata(sub_calls);
+        let tx = TransactionRequest::new()
+            .to(self.multicall_address)
+            .data(calldata);
+        let block_id = block.map(|n| BlockId::Number(BlockNumber::Number(n.into())));
+
+        let response = self
+            .provider
+            .call(&tx.into(), block_id)
+            .await
+            .context("Multicall3 aggregate3 eth_call failed")?;
+
+        let results = Self::decode_aggregate3_response(&response)
+            .context("Failed to decode Multicall3 response")?;
+        if results.len() != sub_calls.len() {
+            return Err(anyhow!(
+                "Multicall3 returned {} results, expected {}",
+                results.len(),
+                sub_calls.len()
+            ));
+        }
+        Ok(results)
+    }
+
+    /// Batch verify all opportunities with two RPC calls.
     ///
-    /// For each opportunity, encodes 2 Quoter sub-calls (buy leg + sell leg)
-    /// into a Multicall3 `aggregate3` batch. Returns verification results
-    /// in the same order as the input opportunities.
+    /// Pass 1 batches every buy leg Quoter call into one Multicall3
+    /// `aggregate3`; pass 2 batches the sell legs, each quoting the exact
+    /// amountOut of its buy leg. A route whose buy leg fails is not quoted in
+    /// pass 2. Returns verification results in the same order as the input
+    /// opportunities.
     ///
     /// Cross-DEX: routes each leg to the correct quoter contract (V1 or V2)
     /// based on the leg's DexType. Multicall3 supports mixed target addresses.
-    ///
-    /// The sell leg uses an estimated buy output (conservative 95% haircut)
-    /// since we don't know the actual buy output until execution.
     pub async fn batch_verify(
+        &self,
+        opportunities: &[ArbitrageOpportunity],
+        config: &BotConfig,
+    ) -> Result<Vec<VerifiedOpportunity>> {
+        let started = Instant::now();
+        let verified = self.verify_batch(opportunities, config).await;
+        if let (Some(recorder), false) = (&self.latency, opportunities.is_empty()) {
+            recorder.record_since(Stage::Verify, started);
+        }
+        verified
+    }
+
+    async fn verify_batch(
         &self,
         opportunities: &[ArbitrageOpportunity],
         _config: &BotConfig,
@@ -173,228 +259,208 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
             return Ok(Vec::new());
         }
 
-        // Build all sub-calls: 2 per opportunity (buy leg + sell leg)
-        // Each sub-call is (target_address, encoded_calldata)
-        // V2 legs are passed through — V2 output is deterministic from reserves,
-        // so on-chain quoter verification is unnecessary. Only V3 legs need quoter.
-        let mut sub_calls: Vec<(Address, Vec<u8>)> = Vec::with_capacity(opportunities.len() * 2);
-
-        // Track which opportunities are V3-only vs have V2 legs
-        // V2 opportunities get passthrough results (executor handles its own validation)
-        let mut v2_passthrough_indices: Vec<usize> = Vec::new();
-
+        // V3-only routes are quoted; the rest pass through.
+        // V2 legs: V2 output is deterministic from reserves (x*y=k), so no
+        // on-chain quoter is needed — the executor's own min_out slippage check
+        // protects V2 legs during execution.
+        // Split-sell routes too: the full buy output quoted on one sell
+        // pool is what the split avoids; executeArbSplit's minProfit guards them.
+        // DODO legs likewise: PMM output is deterministic from the synced state.
+        let mut quoted: Vec<(usize, [LegCall; 2])> = Vec::with_capacity(opportunities.len());
         for (idx, opp) in opportunities.iter().enumerate() {
-            // V2 legs: passthrough — V2 output is deterministic from reserves (x*y=k).
-            // No on-chain quoter needed. The executor's own min_out slippage check
-            // protects V2 legs during execution.
-            if opp.buy_dex.is_v2() || opp.sell_dex.is_v2() {
-                v2_passthrough_indices.push(idx);
+            if opp.buy_dex.is_v2() || opp.sell_dex.is_v2() || opp.is_legacy_only() || opp.split_sell.is_some() {
                 continue;
             }
-
-            let buy_fee = opp
-                .buy_dex
-                .v3_fee_tier()
-                .ok_or_else(|| anyhow!("Buy DEX {:?} is not V3", opp.buy_dex))?;
-            let sell_fee = opp
-                .sell_dex
-                .v3_fee_tier()
-                .ok_or_else(|| anyhow!("Sell DEX {:?} is not V3", opp.sell_dex))?;
-
-            // Swap direction depends on which token is the quote (USDC):
-            //   quote=token0: buy token0→token1, sell token1→token0
-            //   quote=token1: buy token1→token0, sell token0→token1
-            let (buy_token_in, buy_token_out, sell_token_in, sell_token_out) =
-                if opp.quote_token_is_token0 {
-                    (opp.pair.token0, opp.pair.token1, opp.pair.token1, opp.pair.token0)
-                } else {
-                    (opp.pair.token1, opp.pair.token0, opp.pair.token0, opp.pair.token1)
-                };
-
-            // Buy leg: quote_token → base_token on buy pool
-            let buy_quoter = self.quoter_for_dex(opp.buy_dex);
-            let buy_call = self.encode_quoter_for_dex(
-                opp.buy_dex,
-                buy_token_in,
-                buy_token_out,
-                buy_fee,
-                opp.trade_size,
-            );
-
-            // Sell leg: base_token → quote_token on sell pool
-            // Use estimated buy output since we don't have actual yet
-            let sell_quoter = self.quoter_for_dex(opp.sell_dex);
-            let estimated_buy_out = Self::estimate_buy_output(opp);
-            let sell_call = self.encode_quoter_for_dex(
-                opp.sell_dex,
-                sell_token_in,
-                sell_token_out,
-                sell_fee,
-                estimated_buy_out,
-            );
-
-            sub_calls.push((buy_quoter, buy_call));
-            sub_calls.push((sell_quoter, sell_call));
+            quoted.push((idx, Self::leg_calls(opp)?));
         }
 
-        let num_subcalls = sub_calls.len();
-        let v2_count = v2_passthrough_indices.len();
         debug!(
-            "Multicall batch: {} opportunities → {} V3 sub-calls + {} V2 passthroughs",
+            "Multicall batch: {} opportunities → {} V3 routes (2 passes) + {} V2 passthroughs",
             opportunities.len(),
-            num_subcalls,
-            v2_count
+            quoted.len(),
+            opportunities.len() - quoted.len()
         );
 
         // If ALL opportunities have V2 legs, skip multicall entirely
-        if sub_calls.is_empty() {
+        if quoted.is_empty() {
             debug!("All {} opportunities have V2 legs — returning passthroughs", opportunities.len());
             return Ok(opportunities.iter().enumerate()
                 .map(|(i, _)| VerifiedOpportunity::passthrough(i))
                 .collect());
         }
 
-        // Build Multicall3 aggregate3 calldata
-        let calldata = Self::build_aggregate3_calldata(&sub_calls);
-
-        // Execute single eth_call to Multicall3
-        let tx = TransactionRequest::new()
-            .to(self.multicall_address)
-            .data(calldata);
-
-        let response = self
-            .provider
-            .call(&tx.into(), None)
-            .await
-            .context("Multicall3 aggregate3 eth_call failed")?;
+        // Pass 1: every buy leg at trade_size
+        let buy_calls: Vec<(Address, Vec<u8>)> = quoted
+            .iter()
+            .map(|&(idx, [buy, _])| {
+                let opp = &opportunities[idx];
+                (self.quoter_for_dex(opp.buy_dex), self.encode_leg(opp.buy_dex, buy, opp.trade_size.raw()))
+            })
+            .collect();
+        let buy_results = self.aggregate3_at(&buy_calls, None).await?;
+        let buy_outs: Vec<Result<U256>> = quoted
+            .iter()
+            .zip(&buy_results)
+            .map(|(&(idx, _), (success, data))| {
+                Self::decode_quoter_result_for_dex(opportunities[idx].buy_dex, *success, data)
+            })
+            .collect();
 
-        // Decode response
-        let results = Self::decode_aggregate3_response(&response)
-            .context("Failed to decode Multicall3 response")?;
+        // Pass 2: each sell leg on exactly what its buy leg returned; routes
+        // whose buy leg failed are not quoted again
+        let chained: Vec<(usize, U256)> = quoted
+            .iter()
+            .zip(&buy_outs)
+            .enumerate()
+            .filter_map(|(n, (_, buy_out))| buy_out.as_ref().ok().map(|out| (n, *out)))
+            .collect();
+        let sell_calls: Vec<(Address, Vec<u8>)> = chained
+            .iter()
+            .map(|&(n, buy_out)| {
+                let (idx, [_, sell]) = quoted[n];
+                let opp = &opportunities[idx];
+                (self.quoter_for_dex(opp.sell_dex), self.encode_leg(opp.sell_dex, sell, buy_out))
+            })
+            .collect();
+        let sell_results = if sell_calls.is_empty() {
+            Vec::new()
+        } else {
+            self.aggregate3_at(&sell_calls, None).await?
+        };
+        let mut sell_outs: Vec<Option<Result<U256>>> = (0..quoted.len()).map(|_| None).collect();
+        for (&(n, _), (success, data)) in chained.iter().zip(&sell_results) {
+            let opp = &opportunities[quoted[n].0];
+            sell_outs[n] = Some(Self::decode_quoter_result_for_dex(opp.sell_dex, *success, data));
+        }
 
-        if results.len() != num_subcalls {
-            return Err(anyhow!(
-                "Multicall3 returned {} results, expected {}",
-                results.len(),
-                num_subcalls
-            ));
+        // Merge the V3 results with the V2 passthroughs, in input order
+        let mut results: Vec<Option<VerifiedOpportunity>> = (0..opportunities.len()).map(|_| None).collect();
+        for ((&(idx, _), buy_out), sell_out) in quoted.iter().zip(buy_outs).zip(sell_outs) {
+            results[idx] = Some(Self::verify_quotes(idx, &opportunities[idx], buy_out, sell_out));
         }
+        Ok(results
+            .into_iter()
+            .enumerate()
+            .map(|(i, verified)| {
+                verified.unwrap_or_else(|| {
+                    // V2 leg: passthrough — executor validates via min_out slippage check
+                    let opp = &opportunities[i];
+                    debug!(
+                        "Multicall passthrough [{}]: {} — V2 leg ({:?}/{:?})",
+                        i, opp.pair.symbol, opp.buy_dex, opp.sell_dex
+                    );
+                    VerifiedOpportunity::passthrough(i)
+                })
+            })
+            .collect())
+    }
 
-        // Process results: merge V3 decoded results with V2 passthroughs.
-        // V2-passthrough indices were skipped during sub-call encoding,
-        // so we track a separate running index into the multicall results.
-        let mut verified = Vec::with_capacity(opportunities.len());
-        let v2_set: std::collections::HashSet<usize> = v2_passthrough_indices.iter().copied().collect();
-        let mut v3_result_idx: usize = 0; // Running index into multicall result pairs
+    /// Buy and sell leg (token in, token out, fee) of a V3-only route.
+    /// Swap direction depends on which token is the quote (USDC):
+    ///   quote=token0: buy token0→token1, sell token1→token0
+    ///   quote=token1: buy token1→token0, sell token0→token1
+    fn leg_calls(opp: &ArbitrageOpportunity) -> Result<[LegCall; 2]> {
+        let buy_fee = opp
+            .buy_dex
+            .v3_fee_tier()
+            .ok_or_else(|| anyhow!("Buy DEX {:?} is not V3", opp.buy_dex))?;
+        let sell_fee = opp
+            .sell_dex
+            .v3_fee_tier()
+            .ok_or_else(|| anyhow!("Sell DEX {:?} is not V3", opp.sell_dex))?;
+        let (quote, base) = if opp.quote_token_is_token0 {
+            (opp.pair.token0, opp.pair.token1)
+        } else {
+            (opp.pair.token1, opp.pair.token0)
+        };
+        Ok([(quote, base, buy_fee), (base, quote, sell_fee)])
+    }
+
+    fn encode_leg(&self, dex: DexType, (token_in, token_out, fee): LegCall, amount_in: U256) -> Vec<u8> {
+        self.encode_quoter_for_dex(dex, token_in, token_out, fee, amount_in)
+    }
 
-        for (i, opp) in opportunities.iter().enumerate() {
-            if v2_set.contains(&i) {
-                // V2 leg: passthrough — executor validates via min_out slippage check
+    /// One route's verdict from its buy quote and its chained sell quote
+    /// (None = not quoted: the buy leg failed)
+    fn verify_quotes(
+        i: usize,
+        opp: &ArbitrageOpportunity,
+        buy_result: Result<U256>,
+        sell_result: Option<Result<U256>>,
+    ) -> VerifiedOpportunity {
+        let rejected = |buy_out: U256, error: String| VerifiedOpportunity {
+            original_index: i,
+            buy_quoted_out: buy_out,
+            sell_quoted_out: U256::zero(),
+            quoted_profit_raw: 0,
+            both_legs_valid: false,
+            error: Some(error),
+        };
+        let buy_out = match buy_result {
+            Ok(out) => out,
+            Err(buy_err) => {
+                debug!("Multicall rejected [{}]: {} — buy leg failed: {}", i, opp.pair.symbol, buy_err);
+                return rejected(U256::zero(), format!("Buy leg: {}", buy_err));
+            }
+        };
+        let sell_out = match sell_result {
+            Some(Ok(out)) => out,
+            Some(Err(sell_err)) => {
                 debug!(
-                    "Multicall passthrough [{}]: {} — V2 leg ({:?}/{:?})",
-                    i, opp.pair.symbol, opp.buy_dex, opp.sell_dex
+                    "Multicall rejected [{}]: {} — sell leg failed: {} (buy_out={})",
+                    i, opp.pair.symbol, sell_err, buy_out
                 );
-                verified.push(VerifiedOpportunity::passthrough(i));
-                continue;
+                return rejected(buy_out, format!("Sell leg: {}", sell_err));
             }
+            None => return rejected(buy_out, "Sell leg: not quoted".to_string()),
+        };
 
-            // V3-only: decode from multicall results
-            let buy_idx = v3_result_idx * 2;
-            let sell_idx = v3_result_idx * 2 + 1;
-            v3_result_idx += 1;
-
-            let (buy_success, ref buy_data) = results[buy_idx];
-            let (sell_success, ref sell_data) = results[sell_idx];
-
-            let buy_result = Self::decode_quoter_result(buy_success, buy_data);
-            let sell_result = Self::decode_quoter_result(sell_success, sell_data);
-
-            match (buy_result, sell_result) {
-                (Ok(buy_out), Ok(sell_out)) => {
-                    // Both legs valid — calculate profit in token0 raw units
-                    // Guard: if either value exceeds u128, the quoter returned garbage
-                    let u128_max = U256::from(u128::MAX);
-                    if opp.trade_size > u128_max || sell_out > u128_max {
-                        warn!(
-                            "Multicall overflow guard [{}]: {} — trade_size={} sell_out={} exceed u128",
-                            i, opp.pair.symbol, opp.trade_size, sell_out
-                        );
-                        verified.push(VerifiedOpportunity {
-                            original_index: i,
-                            buy_quoted_out: buy_out,
-                            sell_quoted_out: sell_out,
-                            quoted_profit_raw: 0,
-                            both_legs_valid: false,
-                            error: Some("u128 overflow in profit calculation".to_string()),
-                        });
-                        continue;
-                    }
-                    let trade_size_i128 = opp.trade_size.as_u128() as i128;
-                    let sell_out_i128 = sell_out.as_u128() as i128;
-                    let profit = sell_out_i128 - trade_size_i128;
-
-                    let is_profitable = profit > 0;
-
-                    if is_profitable {
-                        debug!(
-                            "Multicall verified [{}]: {} buy_out={} sell_out={} profit_raw={}",
-                            i, opp.pair.symbol, buy_out, sell_out, profit
-                        );
-                    } else {
-                        info!(
-                            "Multicall rejected [{}]: {} — quoted loss (profit_raw={}, buy_out={}, sell_out={}, trade_size={})",
-                            i, opp.pair.symbol, profit, buy_out, sell_out, opp.trade_size
-                        );
-                    }
-
-                    verified.push(VerifiedOpportunity {
-                        original_index: i,
-                        buy_quoted_out: buy_out,
-                        sell_quoted_out: sell_out,
-                        quoted_profit_raw: profit,
-                        both_legs_valid: is_profitable,
-                        error: if is_profitable { None } else { Some(format!("Quoted loss: {}", profit)) },
-                    });
-                }
-                (Err(buy_err), _) => {
-                    debug!(
-                        "Multicall rejected [{}]: {} — buy leg failed: {}",
-                        i, opp.pair.symbol, buy_err
-                    );
-                    verified.push(VerifiedOpportunity {
-                        original_index: i,
-                        buy_quoted_out: U256::zero(),
-                        sell_quoted_out: U256::zero(),
-                        quoted_profit_raw: 0,
-                        both_legs_valid: false,
-                        error: Some(format!("Buy leg: {}", buy_err)),
-                    });
-                }
-                (Ok(buy_out), Err(sell_err)) => {
-                    debug!(
-                        "Multicall rejected [{}]: {} — sell leg failed: {} (buy_out={})",
-                        i, opp.pair.symbol, sell_err, buy_out
-                    );
-                    verified.push(VerifiedOpportunity {
-                        original_index: i,
-                        buy_quoted_out: buy_out,
-                        sell_quoted_out: U256::zero(),
-                        quoted_profit_raw: 0,
-                        both_legs_valid: false,
-                        error: Some(format!("Sell leg: {}", sell_err)),
-                    });
-                }
-            }
+        // Both legs valid — calculate profit in quote token raw units
+        // Guard: if either value exceeds u128, the quoter returned garbage
+        let u128_max = U256::from(u128::MAX);
+        if opp.trade_size.raw() > u128_max || sell_out > u128_max {
+            warn!(
+                "Multicall overflow guard [{}]: {} — trade_size={} sell_out={} (raw units) exceed u128",
+                i, opp.pair.symbol, opp.trade_size.raw(), sell_out
+            );
+            return VerifiedOpportunity {
+                sell_quoted_out: sell_out,
+                ..rejected(buy_out, "u128 overflow in profit calculation".to_string())
+            };
+        }
+        let profit = sell_out.as_u128() as i128 - opp.trade_size.raw().as_u128() as i128;
+        let is_profitable = profit > 0;
+
+        if is_profitable {
+            debug!(
+                "Multicall verified [{}]: {} buy_out={} sell_out={} profit={}",
+                i, opp.pair.symbol, opp.base_amount_display(buy_out),
+                opp.quote_amount(sell_out), opp.quote_delta_display(profit)
+            );
+        } else {
+            info!(
+                "Multicall rejected [{}]: {} — quoted loss (profit={}, buy_out={}, sell_out={}, size={})",
+                i, opp.pair.symbol, opp.quote_delta_display(profit),
+                opp.base_amount_display(buy_out), opp.quote_amount(sell_out), opp.size_display()
+            );
         }
 
-        Ok(verified)
+        VerifiedOpportunity {
+            original_index: i,
+            buy_quoted_out: buy_out,
+            sell_quoted_out: sell_out,
+            quoted_profit_raw: profit,
+            both_legs_valid: is_profitable,
+            error: if is_profitable { None } else { Some(format!("Quoted loss: {}", profit)) },
+        }
     }
 
     /// Route to the correct quoter encoding based on DexType.
     /// QuickSwap V3 → Algebra QuoterV2 (no fee param),
     /// SushiSwap V3 → QuoterV2 (tuple struct param),
+    /// Kyber Elastic → QuoterV2 tuple with the fee in swapFeeUnits (1e-5),
     /// Uniswap V3 → QuoterV1 (flat params) or QuoterV2 if uniswap_quoter_is_v2.
-    fn encode_quoter_for_dex(
+    pub(crate) fn encode_quoter_for_dex(
         &self,
         dex: DexType,
         token_in: Address,
@@ -404,6 +470,9 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
     ) -> Vec<u8> {
         if dex.is_quickswap_v3() {
             Self::encode_quoter_algebra_call(token_in, token_out, amount_in)
+        } else if dex.is_kyber_elastic() {
+            // Same tuple layout as QuoterV2 (identical selector), fee as swapFeeUnits
+            Self::encode_quoter_v2_call(token_in, token_out, fee_to_units(fee), amount_in)
         } else if dex.is_sushi_v3() {
             Self::encode_quoter_v2_call(token_in, token_out, fee, amount_in)
         } else if self.uniswap_quoter_is_v2 {
@@ -419,7 +488,7 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
     /// Selector: 0xf7729d43
     /// Params: (address tokenIn, address tokenOut, uint24 fee, uint256 amountIn, uint160 sqrtPriceLimitX96)
     /// sqrtPriceLimitX96 = 0 (no price limit)
-    fn encode_quoter_v1_call(
+    pub(crate) fn encode_quoter_v1_call(
         token_in: Address,
         token_out: Address,
         fee: u32,
@@ -450,7 +519,7 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
     ///
     /// Return: (uint256 amountOut, uint160 sqrtPriceX96After, uint32 initializedTicksCrossed, uint256 gasEstimate)
     /// First 32 bytes of returnData = amountOut (same as V1), so decode_quoter_result works for both.
-    fn encode_quoter_v2_call(
+    pub(crate) fn encode_quoter_v2_call(
         token_in: Address,
         token_out: Address,
         fee: u32,
@@ -507,7 +576,7 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
     /// Each sub-call is wrapped as: (target: address, allowFailure: true, callData: bytes)
     /// The aggregate3 function takes a single parameter: an array of Call3 structs.
     /// Cross-DEX: each sub-call can target a different quoter contract.
-    fn build_aggregate3_calldata(sub_calls: &[(Address, Vec<u8>)]) -> Bytes {
+    pub(crate) fn build_aggregate3_calldata(sub_calls: &[(Address, Vec<u8>)]) -> Bytes {
         let calls: Vec<Token> = sub_calls
             .iter()
             .map(|(target, call_data)| {
@@ -530,7 +599,7 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
     /// Decode Multicall3 `aggregate3` response into individual (success, returnData) pairs.
     ///
     /// Response ABI: (bool success, bytes returnData)[]
-    fn decode_aggregate3_response(response: &[u8]) -> Result<Vec<(bool, Vec<u8>)>> {
+    pub(crate) fn decode_aggregate3_response(response: &[u8]) -> Result<Vec<(bool, Vec<u8>)>> {
         // The response is ABI-encoded as: array of (bool, bytes) tuples
         let decoded = abi::decode(
             &[ParamType::Array(Box::new(ParamType::Tuple(vec![
@@ -573,7 +642,7 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
     /// - success=false, returnData=abi.encode(uint256 amountOut) → valid quote
     /// - success=false, returnData starts with 0x08c379a2 → Error(string), real failure
     /// - success=false, returnData is empty → real failure (no liquidity)
-    fn decode_quoter_result(success: bool, return_data: &[u8]) -> Result<U256> {
+    pub(crate) fn decode_quoter_result(success: bool, return_data: &[u8]) -> Result<U256> {
         // QuoterV1 always "fails" (reverts to return data).
         // A successful sub-call would be unexpected but we handle it gracefully.
         if success && return_data.len() >= 32 {
@@ -622,6 +691,30 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
         Ok(amount_out)
     }
 
+    /// Decode a quoter sub-call result for `dex`.
+    ///
+    /// Kyber Elastic's QuoterV2 returns normally (success=true) with
+    /// (uint256 usedAmount, uint256 returnedAmount, uint160 afterSqrtP, ...):
+    /// amountOut is the SECOND word. Every other quoter → decode_quoter_result.
+    pub(crate) fn decode_quoter_result_for_dex(dex: DexType, success: bool, return_data: &[u8]) -> Result<U256> {
+        if !dex.is_kyber_elastic() {
+            return Self::decode_quoter_result(success, return_data);
+        }
+        if !success || return_data.len() < 64 {
+            // Reverted: reuse the Error/Panic decoding for the message
+            let reason = Self::decode_quoter_result(false, return_data)
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| format!("{} bytes returned", return_data.len()));
+            return Err(anyhow!("Kyber quoter failed: {}", reason));
+        }
+        let amount_out = U256::from_big_endian(&return_data[32..64]);
+        if amount_out.is_zero() {
+            return Err(anyhow!("Quoter returned zero — pool has no executable depth"));
+        }
+        Ok(amount_out)
+    }
+
     /// Estimate buy leg output for sell-leg pre-screening.
     ///
     /// The buy leg converts quote_token → base_token. The estimate depends on
@@ -630,17 +723,15 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
     ///                 → expected_out = amount_in * buy_price (in token1 units)
     ///   quote=token1: amount_in in token1 decimals, buy_price = token1/token0
     ///                 → expected_out = amount_in / buy_price (in token0 units)
-    fn estimate_buy_output(opp: &ArbitrageOpportunity) -> U256 {
+    pub(crate) fn estimate_buy_output(opp: &ArbitrageOpportunity) -> U256 {
         let (in_decimals, out_decimals, expected_out_human) = if opp.quote_token_is_token0 {
             // quote=token0: trade_size is in token0 units, output is token1
-            let amount_in_human =
-                opp.trade_size.low_u128() as f64 / 10_f64.powi(opp.token0_decimals as i32);
+            let amount_in_human = opp.trade_size.to_human();
             let out = amount_in_human * opp.buy_price * SELL_ESTIMATE_FACTOR;
             (opp.token0_decimals, opp.token1_decimals, out)
         } else {
             // quote=token1: trade_size is in token1 units (USDC), output is token0
-            let amount_in_human =
-                opp.trade_size.low_u128() as f64 / 10_f64.powi(opp.token1_decimals as i32);
+            let amount_in_human = opp.trade_size.to_human();
             // buy_price = token1/token0 (e.g., 82000 USDC per WBTC)
             // To buy token0: amount_in / buy_price
             let out = if opp.buy_price > 0.0 {
@@ -655,8 +746,8 @@ impl<M: Middleware + 'static> MulticallQuoter<M> {
 
         if raw <= 0.0 || !raw.is_finite() {
             warn!(
-                "estimate_buy_output: invalid result {:.2} for {} (trade_size={}, buy_price={:.6}, quote_is_t0={})",
-                raw, opp.pair.symbol, opp.trade_size, opp.buy_price, opp.quote_token_is_token0
+                "estimate_buy_output: invalid result {:.2} for {} (size={}, buy_price={:.6}, quote_is_t0={})",
+                raw, opp.pair.symbol, opp.size_display(), opp.buy_price, opp.quote_token_is_token0
             );
             return U256::zero();
         }
@@ -805,4 +896,134 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), amount);
     }
+
+    #[test]
+    fn test_kyber_quoter_result_second_word() {
+        // QuoteOutput: usedAmount, returnedAmount, afterSqrtP, initializedTicksCrossed, gasEstimate
+        let return_data = abi::encode(&[
+            Token::Uint(U256::from(1_000_000u64)),
+            Token::Uint(U256::from(412_345_678_901_234u64)),
+            Token::Uint(U256::from(1u128 << 96)),
+            Token::Uint(U256::from(1u64)),
+            Token::Uint(U256::from(90_000u64)),
+        ]);
+        let decode = MulticallQuoter::<Provider<Ws>>::decode_quoter_result_for_dex;
+        assert_eq!(decode(DexType::KyberElastic_004, true, &return_data).unwrap(), U256::from(412_345_678_901_234u64));
+        // Non-Kyber DEX keeps the first-word decoding
+        assert_eq!(decode(DexType::SushiV3_005, true, &return_data).unwrap(), U256::from(1_000_000u64));
+
+        let mut revert = ERROR_SELECTOR.to_vec();
+        revert.extend_from_slice(&abi::encode(&[Token::String("SPL".to_string())]));
+        let err = decode(DexType::KyberElastic_004, false, &revert).unwrap_err().to_string();
+        assert!(err.contains("SPL"), "{}", err);
+        assert!(decode(DexType::KyberElastic_004, true, &return_data[..32]).is_err());
+    }
+
+    /// USDC(6)/WETH(18), USDC = token0: 1000 USDC bought on Uni 0.05% at
+    /// 0.0004 WETH/USDC (estimated buy output 0.4 WETH), sold on Uni 0.30%
+    fn chained_opp(n: u64) -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::new(
+            crate::types::TradingPair::new(
+                Address::from_low_u64_be(2 * n),
+                Address::from_low_u64_be(2 * n + 1),
+                format!("WETH{}/USDC", n),
+            ),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.0004,
+            0.00039,
+            U256::from(1_000_000_000u64),
+        );
+        opp.set_quote_layout(6, 18, true);
+        opp
+    }
+
+    /// aggregate3 return data: (success, returnData)[]
+    fn aggregate3_response(results: &[(bool, Vec<u8>)]) -> Bytes {
+        let tuples = results
+            .iter()
+            .map(|(ok, data)| Token::Tuple(vec![Token::Bool(*ok), Token::Bytes(data.clone())]))
+            .collect();
+        abi::encode(&[Token::Array(tuples)]).into()
+    }
+
+    /// QuoterV1 amountOut (revert payload)
+    fn quoted(amount: U256) -> (bool, Vec<u8>) {
+        (false, abi::encode(&[Token::Uint(amount)]))
+    }
+
+    /// Sell pool that pays `usdc_per_weth` for any amount: USDC raw out
+    fn sell_model(usdc_per_weth: u64, weth_in: U256) -> U256 {
+        weth_in * U256::from(usdc_per_weth) / U256::exp10(12)
+    }
+
+    /// Two routes whose buy legs fill worse than the midmarket estimate by
+    /// different amounts: the single-pass estimate ranks A first, the chained
+    /// quote ranks B first. A third route's buy leg fails and is not re-quoted.
+    #[tokio::test]
+    async fn test_chained_sell_quote_changes_ranking() {
+        let (provider, mock) = Provider::mocked();
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.uniswap_v3_quoter = Some(Address::from_low_u64_be(0x9000));
+        let quoter = MulticallQuoter::new(Arc::new(provider), &config).unwrap();
+
+        let opps = vec![chained_opp(1), chained_opp(2), chained_opp(3)];
+        let sell_prices = [2530u64, 2520, 2600];
+        let weth = |milli: u64| U256::exp10(15) * U256::from(milli);
+        let buy_outs = [weth(396), weth(399)];
+
+        // Single-pass: each sell leg quoted at the midmarket estimate (0.4 WETH)
+        let estimate = MulticallQuoter::<Provider<MockProvider>>::estimate_buy_output(&opps[0]);
+        assert_eq!(estimate, weth(400));
+        let single_pass: Vec<U256> = sell_prices[..2].iter().map(|&p| sell_model(p, estimate)).collect();
+        assert!(single_pass[0] > single_pass[1], "estimate ranks A first");
+
+        // Pass 1 answers: A and B fill below the estimate, C's buy reverts
+        let mut failed = ERROR_SELECTOR.to_vec();
+        failed.extend(abi::encode(&[Token::String("SPL".to_string())]));
+        let pass1 = [quoted(buy_outs[0]), quoted(buy_outs[1]), (false, failed)];
+        // Pass 2 answers: the sell pools priced on the chained buy outputs
+        let chained_sells: Vec<U256> = (0..2).map(|i| sell_model(sell_prices[i], buy_outs[i])).collect();
+        let pass2: Vec<(bool, Vec<u8>)> = chained_sells.iter().map(|&out| quoted(out)).collect();
+        // MockProvider answers the last-pushed response first
+        mock.push::<Bytes, _>(aggregate3_response(&pass2)).unwrap();
+        mock.push::<Bytes, _>(aggregate3_response(&pass1)).unwrap();
+
+        let verified = quoter.batch_verify(&opps, &config).await.unwrap();
+
+        // Two round trips; the second quotes A and B's sells on their buy outputs only
+        let call = |sub_calls: &[(Address, Vec<u8>)]| {
+            let tx: ethers::types::transaction::eip2718::TypedTransaction = TransactionRequest::new()
+                .to(quoter.multicall_address())
+                .data(MulticallQuoter::<Provider<MockProvider>>::build_aggregate3_calldata(sub_calls))
+                .into();
+            (tx, "latest")
+        };
+        let uni = config.uniswap_v3_quoter.unwrap();
+        let buy_calls: Vec<(Address, Vec<u8>)> = opps
+            .iter()
+            .map(|o| (uni, quoter.encode_quoter_for_dex(o.buy_dex, o.pair.token0, o.pair.token1, 500, o.trade_size.raw())))
+            .collect();
+        let sell_calls: Vec<(Address, Vec<u8>)> = opps[..2]
+            .iter()
+            .zip(buy_outs)
+            .map(|(o, out)| (uni, quoter.encode_quoter_for_dex(o.sell_dex, o.pair.token1, o.pair.token0, 3000, out)))
+            .collect();
+        mock.assert_request("eth_call", call(&buy_calls)).unwrap();
+        mock.assert_request("eth_call", call(&sell_calls)).unwrap();
+
+        assert_eq!(verified.len(), 3);
+        for (v, (&buy_out, &sell_out)) in verified.iter().zip(buy_outs.iter().zip(&chained_sells)) {
+            assert!(v.both_legs_valid);
+            assert_eq!((v.buy_quoted_out, v.sell_quoted_out), (buy_out, sell_out));
+            assert_eq!(v.quoted_profit_raw, sell_out.as_u128() as i128 - 1_000_000_000);
+        }
+        assert!(!verified[2].both_legs_valid);
+        assert!(verified[2].error.as_deref().unwrap().starts_with("Buy leg"));
+
+        // Ranked by quoted profit: B ahead of A, reversing the estimate's order
+        let mut ranked: Vec<&VerifiedOpportunity> = verified.iter().filter(|v| v.both_legs_valid).collect();
+        ranked.sort_by_key(|v| std::cmp::Reverse(v.quoted_profit_raw));
+        assert_eq!(ranked.iter().map(|v| v.original_index).collect::<Vec<_>>(), vec![1, 0]);
+    }
 }
@@ -0,0 +1,283 @@
+//! Trade Outcome Classification
+//!
+//! Maps one executor.execute() result to what the block loop does next:
+//! route cooldown, whether to try the next ranked opportunity, and whether the
+//! failure halts trading for this block and needs an operator.
+//!
+//! Failure classes:
+//!   Success          — cooldown reset, stop (one trade per block)
+//!   ReceiptTimeout   — tx submitted, unconfirmed: unknown state → HALT, operator
+//!   AtomicRevert     — atomic tx reverted on-chain: gas only → cooldown, next route
+//!   GasNegative      — atomic trade landed, gas > profit → cooldown, stop
+//!   CapitalAtRisk    — legacy two-tx: buy on-chain, sell failed → HALT, operator
+//!   RouteRejected    — pre-trade Quoter/gas/preflight route rejection → cooldown, next route
+//!   PreTradeFailure  — other pre-trade failure, nothing sent → cooldown, stop
+//!   ExecutionError   — executor returned Err (RPC failure mid-pipeline) → cooldown, stop
+//!   InsufficientLiquidity — liquidity guard rejection (structural) → no cooldown, next route
+//!   TaxLoggingHalted — TAX_LOG_STRICT halt, nothing sent (not a route property) → no cooldown, stop
+//!   ExposureLimited  — exposure pacing / balance clamp left no viable size (wallet state) → no cooldown, next route
+//!   StaleOpportunity — older than MAX_OPPORTUNITY_AGE_SECS, nothing sent (timing, not the route) → no cooldown, stop
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - InsufficientLiquidity (liquidity guard, never escalates cooldown)
+//! Modified: 2026-10-16 - TaxLoggingHalted (strict tax logging halt, never escalates cooldown)
+//! Modified: 2026-10-16 - ExposureLimited (exposure pacing rejection, never escalates cooldown)
+//! Modified: 2026-10-16 - CooldownEffect::apply (shared with the cooldown policy simulator)
+//! Modified: 2026-10-16 - Balance clamp rejections classify as ExposureLimited
+//! Modified: 2026-10-16 - StaleOpportunity (opportunity age refusal, never escalates cooldown)
+//! Modified: 2026-10-16 - Cooldown applied at the opportunity's trade size (size buckets)
+
+use super::balance::BALANCE_CLAMP_PREFIX;
+use super::cooldown::RouteCooldown;
+use super::executor::{STALE_OPPORTUNITY_ERROR_PREFIX, TAX_HALT_ERROR_PREFIX};
+use super::exposure::EXPOSURE_PACING_PREFIX;
+use super::liquidity_guard::LIQUIDITY_GUARD_PREFIX;
+use super::preflight::is_route_rejection;
+use crate::types::{ArbitrageOpportunity, DexType, TradeResult};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Failure class of one execution attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeOutcome {
+    Success,
+    ReceiptTimeout,
+    AtomicRevert,
+    GasNegative,
+    CapitalAtRisk,
+    RouteRejected,
+    PreTradeFailure,
+    ExecutionError,
+    InsufficientLiquidity,
+    TaxLoggingHalted,
+    ExposureLimited,
+    StaleOpportunity,
+}
+
+/// What the block loop does after an attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextStep {
+    /// Try the next ranked opportunity this block
+    TryNext,
+    /// Stop trying this block
+    StopBlock,
+    /// Stop this block and flag for manual check (unknown or committed state)
+    Halt,
+}
+
+/// Route cooldown effect of an attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CooldownEffect {
+    Reset,
+    Escalate,
+    Unchanged,
+}
+
+impl CooldownEffect {
+    /// Apply to one route at a trade size (also used by the cooldown policy simulator)
+    pub fn apply(
+        self,
+        cooldown: &mut RouteCooldown,
+        pair: &str,
+        buy_dex: DexType,
+        sell_dex: DexType,
+        size_usd: f64,
+        block: u64,
+    ) {
+        match self {
+            CooldownEffect::Reset => cooldown.record_success(pair, buy_dex, sell_dex, size_usd),
+            CooldownEffect::Escalate => cooldown.record_failure(pair, buy_dex, sell_dex, size_usd, block),
+            CooldownEffect::Unchanged => {}
+        }
+    }
+}
+
+impl TradeOutcome {
+    /// Classify an execute() result. `atomic` = ArbExecutor configured.
+    pub fn classify(result: &Result<TradeResult>, atomic: bool) -> Self {
+        let result = match result {
+            Ok(r) => r,
+            Err(_) => return TradeOutcome::ExecutionError,
+        };
+        if result.success {
+            return TradeOutcome::Success;
+        }
+        let error_msg = result.error.as_deref().unwrap_or("Unknown");
+
+        // On-chain tx was submitted — distinguish safe (atomic) from dangerous (legacy)
+        if result.tx_hash.is_some() {
+            if error_msg.contains("Receipt timeout") {
+                return TradeOutcome::ReceiptTimeout;
+            }
+            if !atomic {
+                return TradeOutcome::CapitalAtRisk;
+            }
+            return if error_msg.contains("reverted") {
+                TradeOutcome::AtomicRevert
+            } else {
+                TradeOutcome::GasNegative
+            };
+        }
+
+        // No tx submitted = pre-trade rejection (zero capital risk)
+        if error_msg.starts_with(LIQUIDITY_GUARD_PREFIX) {
+            return TradeOutcome::InsufficientLiquidity;
+        }
+        if error_msg.starts_with(TAX_HALT_ERROR_PREFIX) {
+            return TradeOutcome::TaxLoggingHalted;
+        }
+        if error_msg.starts_with(STALE_OPPORTUNITY_ERROR_PREFIX) {
+            return TradeOutcome::StaleOpportunity;
+        }
+        if error_msg.starts_with(EXPOSURE_PACING_PREFIX) || error_msg.starts_with(BALANCE_CLAMP_PREFIX) {
+            return TradeOutcome::ExposureLimited;
+        }
+        if error_msg.contains("Quoter") || error_msg.contains("Gas price") || is_route_rejection(error_msg) {
+            TradeOutcome::RouteRejected
+        } else {
+            TradeOutcome::PreTradeFailure
+        }
+    }
+
+    pub fn next_step(self) -> NextStep {
+        match self {
+            TradeOutcome::AtomicRevert
+            | TradeOutcome::RouteRejected
+            | TradeOutcome::InsufficientLiquidity
+            | TradeOutcome::ExposureLimited => NextStep::TryNext,
+            TradeOutcome::ReceiptTimeout | TradeOutcome::CapitalAtRisk => NextStep::Halt,
+            TradeOutcome::Success
+            | TradeOutcome::GasNegative
+            | TradeOutcome::PreTradeFailure
+            | TradeOutcome::ExecutionError
+            | TradeOutcome::TaxLoggingHalted
+            | TradeOutcome::StaleOpportunity => NextStep::StopBlock,
+        }
+    }
+
+    pub fn cooldown(self) -> CooldownEffect {
+        match self {
+            TradeOutcome::Success => CooldownEffect::Reset,
+            // Unknown / committed state is not a route property — don't suppress it
+            TradeOutcome::ReceiptTimeout | TradeOutcome::CapitalAtRisk => CooldownEffect::Unchanged,
+            // Thin pool is structural until liquidity returns — backoff would only hide the recovery
+            TradeOutcome::InsufficientLiquidity => CooldownEffect::Unchanged,
+            // Every route is refused until the tax records are on disk
+            TradeOutcome::TaxLoggingHalted => CooldownEffect::Unchanged,
+            // Wallet state, not the route — a smaller-quote route may still fit
+            TradeOutcome::ExposureLimited => CooldownEffect::Unchanged,
+            // The block loop or signal was late, not the route — later ranks are no fresher
+            TradeOutcome::StaleOpportunity => CooldownEffect::Unchanged,
+            _ => CooldownEffect::Escalate,
+        }
+    }
+
+    /// Apply cooldown() to the opportunity's route at its trade size
+    pub fn apply_cooldown(self, cooldown: &mut RouteCooldown, opp: &ArbitrageOpportunity, block: u64) {
+        self.cooldown().apply(cooldown, &opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.trade_size_usd, block);
+    }
+
+    /// True when the outcome needs a human (logged at error level as HALT)
+    pub fn alerts_operator(self) -> bool {
+        self.next_step() == NextStep::Halt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::preflight::PREFLIGHT_ERROR_PREFIX;
+    use anyhow::anyhow;
+
+    fn failed(tx: Option<&str>, error: Option<&str>) -> Result<TradeResult> {
+        Ok(TradeResult {
+            opportunity: "WETH/USDC".to_string(),
+            opportunity_id: 0,
+            tx_hash: tx.map(str::to_string),
+            block_number: None,
+            success: false,
+            profit_usd: 0.0,
+            gas_cost_usd: 0.0,
+            gas_used_native: 0.0,
+            net_profit_usd: 0.0,
+            execution_time_ms: 0,
+            error: error.map(str::to_string),
+            amount_in: None,
+            amount_out: None,
+        })
+    }
+
+    #[test]
+    fn test_classification_matrix() {
+        let tx = Some("0xabc");
+        let cases = [
+            (failed(tx, Some("Receipt timeout — tx submitted but unconfirmed")), true, TradeOutcome::ReceiptTimeout),
+            (failed(tx, Some("Atomic tx reverted on-chain")), true, TradeOutcome::AtomicRevert),
+            (failed(tx, None), true, TradeOutcome::GasNegative),
+            (failed(tx, Some("Sell swap failed (buy succeeded): x")), false, TradeOutcome::CapitalAtRisk),
+            (failed(None, Some("V3 Quoter pre-check failed: x")), false, TradeOutcome::RouteRejected),
+            (
+                failed(None, Some(&format!("{}buy leg Quoter failed: x", PREFLIGHT_ERROR_PREFIX))),
+                true,
+                TradeOutcome::RouteRejected,
+            ),
+            (
+                failed(None, Some(&format!("{}wallet quote balance 1 < trade size 2", PREFLIGHT_ERROR_PREFIX))),
+                true,
+                TradeOutcome::PreTradeFailure,
+            ),
+            (failed(None, Some("Atomic tx send failed: nonce too low")), true, TradeOutcome::PreTradeFailure),
+            (Err(anyhow!("eth_getTransactionCount timed out after 10s")), true, TradeOutcome::ExecutionError),
+            (
+                failed(None, Some(&format!("{} sell pool liquidity 5 below minimum 10", LIQUIDITY_GUARD_PREFIX))),
+                true,
+                TradeOutcome::InsufficientLiquidity,
+            ),
+            (
+                failed(None, Some(&format!("{}tax record for tx 0x1 not written", TAX_HALT_ERROR_PREFIX))),
+                true,
+                TradeOutcome::TaxLoggingHalted,
+            ),
+            (
+                failed(None, Some(&format!("{} scaled trade $4.00 below minimum notional $10.00", EXPOSURE_PACING_PREFIX))),
+                true,
+                TradeOutcome::ExposureLimited,
+            ),
+            (
+                failed(None, Some(&format!("{} wallet balance $5.00 below minimum $10.00", BALANCE_CLAMP_PREFIX))),
+                true,
+                TradeOutcome::ExposureLimited,
+            ),
+            (
+                failed(None, Some(&format!("{}opportunity 9s old (max 4s)", STALE_OPPORTUNITY_ERROR_PREFIX))),
+                true,
+                TradeOutcome::StaleOpportunity,
+            ),
+        ];
+        for (result, atomic, expected) in cases {
+            assert_eq!(TradeOutcome::classify(&result, atomic), expected, "{:?}", result.ok());
+        }
+    }
+
+    #[test]
+    fn test_halt_and_cooldown_policy() {
+        assert_eq!(TradeOutcome::ReceiptTimeout.next_step(), NextStep::Halt);
+        assert_eq!(TradeOutcome::CapitalAtRisk.next_step(), NextStep::Halt);
+        assert!(TradeOutcome::ReceiptTimeout.alerts_operator());
+        assert_eq!(TradeOutcome::ReceiptTimeout.cooldown(), CooldownEffect::Unchanged);
+        assert_eq!(TradeOutcome::AtomicRevert.next_step(), NextStep::TryNext);
+        assert_eq!(TradeOutcome::RouteRejected.cooldown(), CooldownEffect::Escalate);
+        assert_eq!(TradeOutcome::Success.cooldown(), CooldownEffect::Reset);
+        assert!(!TradeOutcome::PreTradeFailure.alerts_operator());
+        assert_eq!(TradeOutcome::InsufficientLiquidity.cooldown(), CooldownEffect::Unchanged);
+        assert_eq!(TradeOutcome::InsufficientLiquidity.next_step(), NextStep::TryNext);
+        assert_eq!(TradeOutcome::TaxLoggingHalted.cooldown(), CooldownEffect::Unchanged);
+        assert_eq!(TradeOutcome::TaxLoggingHalted.next_step(), NextStep::StopBlock);
+        assert_eq!(TradeOutcome::ExposureLimited.cooldown(), CooldownEffect::Unchanged);
+        assert_eq!(TradeOutcome::ExposureLimited.next_step(), NextStep::TryNext);
+        assert_eq!(TradeOutcome::StaleOpportunity.cooldown(), CooldownEffect::Unchanged);
+        assert_eq!(TradeOutcome::StaleOpportunity.next_step(), NextStep::StopBlock);
+    }
+}
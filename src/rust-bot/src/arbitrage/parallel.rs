@@ -0,0 +1,219 @@
+//! Parallel Submission — same-block nonce ladder of independent atomic arbs
+//!
+//! With MAX_PARALLEL_SUBMISSIONS > 1 and ArbExecutor configured, the top-K
+//! verified opportunities whose pools are pairwise disjoint (no buy or sell
+//! pool shared with a better-ranked pick) are submitted back-to-back via
+//! submit_atomic() on consecutive nonces. Their receipts are awaited
+//! concurrently in a JoinSet and each is settled as it lands, so the later
+//! opportunities no longer go stale behind the earlier receipts. Disjoint
+//! pools keep one trade's price impact out of another's quote.
+//!
+//! Nonce gaps: the tracked nonce only advances on a successful send, so a
+//! rejection before signing (halt, pacing, preflight) keeps the ladder
+//! contiguous and the next pick goes out on the same nonce. A failed send
+//! may or may not have reached the mempool, so laddering stops for the block
+//! there — nothing is stacked on a nonce that might never be mined.
+//!
+//! With several wallets (PRIVATE_KEYS) each submission picks an idle wallet
+//! first, so a batch spreads across wallets before it ladders nonces within
+//! one, and a stuck tx holds up only its own wallet's queue.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Batches spread across PRIVATE_KEYS wallets; sent log counts wallets
+//! Modified: 2026-10-17 - Receipt RPC errors release the tx's wallet (executor.release_unsettled)
+
+use super::executor::{AtomicSubmit, PendingAtomic, TradeExecutor};
+use crate::types::{ArbitrageOpportunity, TradeResult};
+use anyhow::Result;
+use ethers::prelude::*;
+use futures::FutureExt;
+use std::collections::{HashMap, HashSet, VecDeque};
+use tokio::task::JoinSet;
+use tracing::{error, info, warn};
+
+/// (rank in the execution order, opportunity index, outcome)
+pub type ParallelAttempt = (usize, usize, Result<TradeResult>);
+
+/// Ranks (positions in `order`, best first) of up to `k` verified entries
+/// whose buy/sell pools are disjoint from every better-ranked pick. Entries
+/// without a quoted profit or without known pool addresses are skipped.
+pub fn select_disjoint(
+    order: &[(usize, Option<i128>)],
+    opportunities: &[ArbitrageOpportunity],
+    k: usize,
+) -> Vec<usize> {
+    let mut picks = Vec::new();
+    let mut used: HashSet<Address> = HashSet::new();
+    for (rank, (idx, quoted_profit)) in order.iter().enumerate() {
+        if picks.len() >= k {
+            break;
+        }
+        if quoted_profit.is_none() {
+            continue;
+        }
+        let opp = &opportunities[*idx];
+        let (Some(buy), Some(sell)) = (opp.buy_pool_address, opp.sell_pool_address) else {
+            continue;
+        };
+        if used.contains(&buy) || used.contains(&sell) {
+            continue;
+        }
+        used.insert(buy);
+        used.insert(sell);
+        picks.push(rank);
+    }
+    picks
+}
+
+/// One block's laddered submissions, drained with next_result()
+pub struct ParallelBatch {
+    /// Sent txs awaiting their receipt: tx hash → (rank, index, pending)
+    pending: HashMap<TxHash, (usize, usize, PendingAtomic)>,
+    receipts: JoinSet<(TxHash, Result<Option<TransactionReceipt>>)>,
+    /// Attempts already final at submit time (rejections, failed sends)
+    finished: VecDeque<ParallelAttempt>,
+}
+
+impl ParallelBatch {
+    /// Submit `picks` (rank, opportunity index) back-to-back, best first
+    pub async fn submit<M: Middleware + 'static>(
+        executor: &mut TradeExecutor<M>,
+        opportunities: &[ArbitrageOpportunity],
+        picks: &[(usize, usize)],
+    ) -> Self {
+        let mut batch = Self {
+            pending: HashMap::new(),
+            receipts: JoinSet::new(),
+            finished: VecDeque::new(),
+        };
+        let symbols: Vec<&str> = picks.iter().map(|&(_, idx)| opportunities[idx].pair.symbol.as_str()).collect();
+        info!("⚡ PARALLEL SUBMIT x{}: {}", picks.len(), symbols.join(", "));
+
+        for (n, &(rank, idx)) in picks.iter().enumerate() {
+            match executor.submit_atomic(&opportunities[idx]).await {
+                Ok(AtomicSubmit::Pending(pending)) => {
+                    let tx_hash = pending.tx_hash;
+                    batch
+                        .receipts
+                        .spawn(executor.wait_for_atomic_receipt(tx_hash).map(move |receipt| (tx_hash, receipt)));
+                    batch.pending.insert(tx_hash, (rank, idx, *pending));
+                }
+                Ok(AtomicSubmit::Finished(result)) => batch.finished.push_back((rank, idx, Ok(result))),
+                Ok(AtomicSubmit::SendFailed(result)) => {
+                    batch.finished.push_back((rank, idx, Ok(result)));
+                    batch.stop_ladder(picks.len() - n - 1);
+                    break;
+                }
+                Err(e) => {
+                    batch.finished.push_back((rank, idx, Err(e)));
+                    batch.stop_ladder(picks.len() - n - 1);
+                    break;
+                }
+            }
+        }
+
+        let wallets: HashSet<Address> = batch.pending.values().map(|(_, _, pending)| pending.wallet).collect();
+        info!(
+            "⚡ PARALLEL SUBMIT x{}: {} sent from {} wallet(s)",
+            picks.len(),
+            batch.pending.len(),
+            wallets.len()
+        );
+        batch
+    }
+
+    fn stop_ladder(&self, skipped: usize) {
+        if skipped > 0 {
+            warn!("⚡ PARALLEL SUBMIT: send failed — ladder stopped, {} pick(s) not sent this block", skipped);
+        }
+    }
+
+    /// Next finished attempt: rejections first, then settled receipts in
+    /// landing order. None once every submission is accounted for.
+    pub async fn next_result<M: Middleware + 'static>(
+        &mut self,
+        executor: &mut TradeExecutor<M>,
+    ) -> Option<ParallelAttempt> {
+        if let Some(attempt) = self.finished.pop_front() {
+            return Some(attempt);
+        }
+        loop {
+            match self.receipts.join_next().await? {
+                Ok((tx_hash, receipt)) => {
+                    let Some((rank, idx, pending)) = self.pending.remove(&tx_hash) else { continue };
+                    let result = match receipt {
+                        Ok(receipt) => executor.settle_atomic(pending, receipt).await,
+                        Err(e) => {
+                            executor.release_unsettled(&pending);
+                            Err(e)
+                        }
+                    };
+                    return Some((rank, idx, result));
+                }
+                // The in-flight journal still holds the tx for startup recovery
+                Err(e) => error!("Parallel receipt task failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+
+    fn opp(buy_pool: u64, sell_pool: u64) -> ArbitrageOpportunity {
+        ArbitrageOpportunity::with_pool_addresses(
+            TradingPair::new(Address::zero(), Address::zero(), "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.0004,
+            0.00041,
+            U256::from(500_000_000u64),
+            Address::from_low_u64_be(buy_pool),
+            Address::from_low_u64_be(sell_pool),
+        )
+    }
+
+    #[test]
+    fn test_select_disjoint() {
+        let opportunities = vec![
+            opp(1, 2), // 0
+            opp(2, 3), // 1: shares pool 2 with #0
+            opp(4, 5), // 2
+            opp(6, 7), // 3: unverified
+            opp(5, 8), // 4: shares pool 5 with #2 (as sell vs buy)
+            opp(9, 10), // 5
+            opp(11, 12), // 6
+        ];
+        let order: Vec<(usize, Option<i128>)> = vec![
+            (0, Some(900)),
+            (1, Some(800)),
+            (2, Some(700)),
+            (3, None),
+            (4, Some(500)),
+            (5, Some(400)),
+            (6, Some(300)),
+        ];
+
+        // Ranks, not indices: conflicts and unverified entries fall through
+        assert_eq!(select_disjoint(&order, &opportunities, 3), vec![0, 2, 5]);
+        assert_eq!(select_disjoint(&order, &opportunities, 10), vec![0, 2, 5, 6]);
+        assert_eq!(select_disjoint(&order, &opportunities, 1), vec![0]);
+
+        // The better-ranked route keeps a contested pool
+        let reordered = vec![(1, Some(900)), (0, Some(800)), (2, Some(700))];
+        assert_eq!(select_disjoint(&reordered, &opportunities, 3), vec![0, 2]);
+
+        // Unknown pool addresses can't be proven disjoint
+        let mut unknown = opportunities.clone();
+        unknown[0].sell_pool_address = None;
+        assert_eq!(select_disjoint(&order, &unknown, 3), vec![1, 2, 5]);
+
+        // Same pool on both legs of one route conflicts only with others
+        let same = vec![opp(1, 1), opp(1, 3), opp(4, 5)];
+        let order = vec![(0, Some(3)), (1, Some(2)), (2, Some(1))];
+        assert_eq!(select_disjoint(&order, &same, 3), vec![0, 2]);
+    }
+}
@@ -0,0 +1,356 @@
+//! Permit2 Funding — signed PermitTransferFrom instead of an ArbExecutor allowance
+//!
+//! Atomic trades pull the quote token from the wallet with transferFrom, so
+//! every wallet max-approves ArbExecutor per quote token: a standing allowance
+//! on a hot wallet, and approval txs before a new wallet's first trade. With
+//! PERMIT2_ADDRESS set, a wallet-funded trade instead carries an EIP-712
+//! PermitTransferFrom signed off-chain for exactly the trade size, and
+//! ArbExecutor.executeArbWithPermit has Permit2 move the tokens (the wallet
+//! approves Permit2 once per token; nothing else holds an allowance).
+//!
+//! Signature-transfer nonces are unordered: a 256-bit bitmap per (owner, word),
+//! nonce = word << 8 | bit, any unused bit is valid. NonceBitmap hands out the
+//! free bits of one word read from nonceBitmap(); a full word moves to the next.
+//! A bit taken for a tx that reverts is simply skipped (the revert undoes the
+//! on-chain use, the local bitmap keeps it).
+//!
+//! Falls back to the allowance path (executeArb) when the deployed contract has
+//! no executeArbWithPermit (probed once at startup: a zero-amount call must
+//! revert ZeroAmount()) or the wallet hasn't approved Permit2 for the token.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use anyhow::Result;
+use ethers::abi::{encode, Token};
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use std::collections::HashMap;
+use std::fmt;
+
+// Permit2 SignatureTransfer: per-owner unordered nonce bitmap words
+abigen!(
+    IPermit2,
+    r#"[
+        function nonceBitmap(address owner, uint256 wordPos) external view returns (uint256)
+    ]"#
+);
+
+/// Seconds a signed permit stays valid (the swap legs' own deadline is 120s too)
+pub const PERMIT_DEADLINE_SECS: u64 = 120;
+
+/// Permit2's EIP-712 domain: name "Permit2", no version
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,uint256 chainId,address verifyingContract)";
+const DOMAIN_NAME: &str = "Permit2";
+
+/// ISignatureTransfer typestrings (referenced struct appended, per EIP-712)
+pub const TOKEN_PERMISSIONS_TYPE: &str = "TokenPermissions(address token,uint256 amount)";
+pub const PERMIT_TRANSFER_FROM_TYPE: &str =
+    "PermitTransferFrom(TokenPermissions permitted,address spender,uint256 nonce,uint256 deadline)TokenPermissions(address token,uint256 amount)";
+
+/// One signature transfer: `spender` (ArbExecutor) may pull `amount` of `token`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermitTransferFrom {
+    pub token: Address,
+    pub amount: U256,
+    pub spender: Address,
+    pub nonce: U256,
+    pub deadline: U256,
+}
+
+impl PermitTransferFrom {
+    /// hashStruct(PermitTransferFrom), as PermitHash.hash() computes it
+    pub fn struct_hash(&self) -> H256 {
+        let permitted = keccak256(encode(&[
+            Token::FixedBytes(keccak256(TOKEN_PERMISSIONS_TYPE).to_vec()),
+            Token::Address(self.token),
+            Token::Uint(self.amount),
+        ]));
+        keccak256(encode(&[
+            Token::FixedBytes(keccak256(PERMIT_TRANSFER_FROM_TYPE).to_vec()),
+            Token::FixedBytes(permitted.to_vec()),
+            Token::Address(self.spender),
+            Token::Uint(self.nonce),
+            Token::Uint(self.deadline),
+        ]))
+        .into()
+    }
+
+    /// EIP-712 digest the owner signs: keccak256(0x1901 ‖ domainSeparator ‖ structHash)
+    pub fn digest(&self, chain_id: u64, permit2: Address) -> H256 {
+        let mut preimage = Vec::with_capacity(66);
+        preimage.extend_from_slice(&[0x19, 0x01]);
+        preimage.extend_from_slice(domain_separator(chain_id, permit2).as_bytes());
+        preimage.extend_from_slice(self.struct_hash().as_bytes());
+        keccak256(preimage).into()
+    }
+
+    /// 65-byte r ‖ s ‖ v signature for executeArbWithPermit
+    pub fn sign(&self, wallet: &LocalWallet, chain_id: u64, permit2: Address) -> Result<Bytes> {
+        let signature = wallet.sign_hash(self.digest(chain_id, permit2))?;
+        Ok(Bytes::from(signature.to_vec()))
+    }
+}
+
+/// Permit2's DOMAIN_SEPARATOR on `chain_id`
+pub fn domain_separator(chain_id: u64, permit2: Address) -> H256 {
+    keccak256(encode(&[
+        Token::FixedBytes(keccak256(DOMAIN_TYPE).to_vec()),
+        Token::FixedBytes(keccak256(DOMAIN_NAME).to_vec()),
+        Token::Uint(U256::from(chain_id)),
+        Token::Address(permit2),
+    ]))
+    .into()
+}
+
+/// One word of an owner's unordered nonce bitmap (set bits = used)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceBitmap {
+    word: U256,
+    bitmap: U256,
+}
+
+impl NonceBitmap {
+    /// Word `word` as nonceBitmap(owner, word) returned it
+    pub fn new(word: U256, bitmap: U256) -> Self {
+        Self { word, bitmap }
+    }
+
+    pub fn word(&self) -> U256 {
+        self.word
+    }
+
+    /// Take the lowest unused nonce of the word; None once all 256 are used
+    pub fn take(&mut self) -> Option<U256> {
+        let bit = (0..256usize).find(|&bit| !self.bitmap.bit(bit))?;
+        self.bitmap |= U256::one() << bit;
+        Some((self.word << 8) | U256::from(bit))
+    }
+}
+
+/// Executor-side Permit2 state for one session
+#[derive(Debug, Clone)]
+pub struct Permit2State {
+    pub address: Address,
+    /// Startup probe verdict; None until probed (or the probe's RPC failed)
+    pub contract_supported: Option<bool>,
+    /// (wallet, token) → allowance to Permit2, read once per session
+    allowances: HashMap<(Address, Address), U256>,
+    /// Wallet → nonce word in use
+    nonces: HashMap<Address, NonceBitmap>,
+}
+
+impl Permit2State {
+    pub fn new(address: Address) -> Self {
+        Self { address, contract_supported: None, allowances: HashMap::new(), nonces: HashMap::new() }
+    }
+
+    pub fn allowance(&self, wallet: Address, token: Address) -> Option<U256> {
+        self.allowances.get(&(wallet, token)).copied()
+    }
+
+    pub fn set_allowance(&mut self, wallet: Address, token: Address, allowance: U256) {
+        self.allowances.insert((wallet, token), allowance);
+    }
+
+    /// Next unused nonce of `owner`; None until a word is loaded or once it's used up
+    pub fn take_nonce(&mut self, owner: Address) -> Option<U256> {
+        self.nonces.get_mut(&owner)?.take()
+    }
+
+    /// Word to read next for `owner`: 0 first, then the one after the word in use
+    pub fn next_word(&self, owner: Address) -> U256 {
+        self.nonces.get(&owner).map_or(U256::zero(), |n| n.word() + 1)
+    }
+
+    pub fn load_nonces(&mut self, owner: Address, word: U256, bitmap: U256) {
+        self.nonces.insert(owner, NonceBitmap::new(word, bitmap));
+    }
+}
+
+/// Why a wallet-funded atomic trade stays on the allowance path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermitFallback {
+    /// PERMIT2_ADDRESS unset
+    NotConfigured,
+    /// The startup probe couldn't tell whether executeArbWithPermit exists
+    SupportUnknown,
+    /// The deployed ArbExecutor has no executeArbWithPermit
+    ContractUnsupported,
+    /// The wallet's Permit2 allowance for the token could not be read
+    AllowanceUnknown,
+    /// The wallet hasn't approved Permit2 for (at least) the trade size
+    NotApproved { allowance: U256 },
+}
+
+impl fmt::Display for PermitFallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PermitFallback::NotConfigured => write!(f, "PERMIT2_ADDRESS not set"),
+            PermitFallback::SupportUnknown => write!(f, "executeArbWithPermit support unknown"),
+            PermitFallback::ContractUnsupported => write!(f, "ArbExecutor has no executeArbWithPermit"),
+            PermitFallback::AllowanceUnknown => write!(f, "Permit2 allowance unknown"),
+            PermitFallback::NotApproved { allowance } => write!(f, "Permit2 allowance {} below trade size", allowance),
+        }
+    }
+}
+
+/// The Permit2 contract to sign for, or why the trade keeps the allowance path.
+/// `contract_supported` = startup probe verdict (None = not known);
+/// `permit2_allowance` = the wallet's token allowance to Permit2 (None = read failed).
+pub fn permit_decision(
+    permit2: Option<Address>,
+    contract_supported: Option<bool>,
+    permit2_allowance: Option<U256>,
+    amount: U256,
+) -> Result<Address, PermitFallback> {
+    let permit2 = permit2.ok_or(PermitFallback::NotConfigured)?;
+    match contract_supported {
+        None => return Err(PermitFallback::SupportUnknown),
+        Some(false) => return Err(PermitFallback::ContractUnsupported),
+        Some(true) => {}
+    }
+    let allowance = permit2_allowance.ok_or(PermitFallback::AllowanceUnknown)?;
+    if allowance < amount {
+        return Err(PermitFallback::NotApproved { allowance });
+    }
+    Ok(permit2)
+}
+
+/// Startup probe verdict from a zero-amount executeArbWithPermit eth_call: the
+/// function exists iff it got as far as ArbExecutor's ZeroAmount() revert
+/// (an unknown selector reverts with no data)
+pub fn probe_supports_permit(revert_data: Option<&[u8]>) -> bool {
+    revert_data.is_some_and(|data| data.starts_with(&ethers::utils::id("ZeroAmount()")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::transaction::eip712::{Eip712, TypedData};
+    use std::str::FromStr;
+
+    /// Canonical Permit2 deployment (same address on every chain)
+    const PERMIT2: &str = "0x000000000022D473030F116dDEE9F6B43aC78BA3";
+    /// Well-known test key (anvil account 0)
+    const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    fn permit() -> PermitTransferFrom {
+        PermitTransferFrom {
+            token: Address::from_str("0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359").unwrap(),
+            amount: U256::from(500_000_000u64),
+            spender: Address::from_low_u64_be(0xa4b),
+            nonce: U256::from(0x105u64),
+            deadline: U256::from(1_790_000_000u64),
+        }
+    }
+
+    #[test]
+    fn test_digest_matches_known_vector() {
+        let permit2 = Address::from_str(PERMIT2).unwrap();
+        // Permit2.DOMAIN_SEPARATOR() as deployed on Ethereum mainnet
+        assert_eq!(
+            format!("{:?}", domain_separator(1, permit2)),
+            "0x866a5aba21966af95d6c7ab78eb2b2fc913915c28be3b9aa07cc04ff903e3f28"
+        );
+        assert_eq!(
+            format!("{:?}", permit().digest(137, permit2)),
+            "0xc7f11d50238fdea6711c3363d0d4191f4c149e6a8c33b2a94678f7c68991d567"
+        );
+
+        // Same digest from ethers' generic EIP-712 encoder over the typed data
+        let typed: TypedData = serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "PermitTransferFrom": [
+                    { "name": "permitted", "type": "TokenPermissions" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" }
+                ],
+                "TokenPermissions": [
+                    { "name": "token", "type": "address" },
+                    { "name": "amount", "type": "uint256" }
+                ]
+            },
+            "primaryType": "PermitTransferFrom",
+            "domain": { "name": "Permit2", "chainId": 137, "verifyingContract": PERMIT2 },
+            "message": {
+                "permitted": { "token": "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359", "amount": "500000000" },
+                "spender": format!("{:?}", Address::from_low_u64_be(0xa4b)),
+                "nonce": "261",
+                "deadline": "1790000000"
+            }
+        }))
+        .unwrap();
+        assert_eq!(H256::from(typed.encode_eip712().unwrap()), permit().digest(137, permit2));
+    }
+
+    #[test]
+    fn test_signature_recovers_wallet() {
+        let permit2 = Address::from_str(PERMIT2).unwrap();
+        let wallet = LocalWallet::from_str(TEST_KEY).unwrap();
+        let bytes = permit().sign(&wallet, 137, permit2).unwrap();
+        assert_eq!(bytes.len(), 65);
+        let signature = Signature::try_from(bytes.as_ref()).unwrap();
+        assert_eq!(signature.recover(permit().digest(137, permit2)).unwrap(), wallet.address());
+        // Bound to the chain: the same permit on another chain is another digest
+        assert_ne!(permit().digest(1, permit2), permit().digest(137, permit2));
+    }
+
+    #[test]
+    fn test_nonce_bitmap_takes_free_bits_then_fills() {
+        // Word 1 with bits 0, 1 and 3 already used on-chain
+        let mut nonces = NonceBitmap::new(U256::one(), U256::from(0b1011u64));
+        assert_eq!(nonces.take(), Some(U256::from(0x102u64)));
+        assert_eq!(nonces.take(), Some(U256::from(0x104u64)));
+        assert_eq!(nonces.word(), U256::one());
+
+        let mut full = NonceBitmap::new(U256::from(2u64), U256::MAX ^ (U256::one() << 255));
+        assert_eq!(full.take(), Some(U256::from(0x2ffu64)));
+        assert_eq!(full.take(), None);
+
+        // Per-wallet words: word 0 first, a used-up word moves on to the next
+        let (owner, other) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let mut state = Permit2State::new(Address::from_str(PERMIT2).unwrap());
+        assert_eq!((state.take_nonce(owner), state.next_word(owner)), (None, U256::zero()));
+        state.load_nonces(owner, U256::zero(), U256::MAX ^ U256::one());
+        assert_eq!(state.take_nonce(owner), Some(U256::zero()));
+        assert_eq!((state.take_nonce(owner), state.next_word(owner)), (None, U256::one()));
+        assert_eq!(state.take_nonce(other), None);
+    }
+
+    #[test]
+    fn test_permit_decision_falls_back_to_allowance_path() {
+        let permit2 = Address::from_str(PERMIT2).unwrap();
+        let size = U256::from(500_000_000u64);
+        assert_eq!(permit_decision(Some(permit2), Some(true), Some(U256::MAX), size), Ok(permit2));
+        assert_eq!(permit_decision(Some(permit2), Some(true), Some(size), size), Ok(permit2));
+
+        assert_eq!(permit_decision(None, Some(true), Some(U256::MAX), size), Err(PermitFallback::NotConfigured));
+        assert_eq!(permit_decision(Some(permit2), None, Some(U256::MAX), size), Err(PermitFallback::SupportUnknown));
+        assert_eq!(
+            permit_decision(Some(permit2), Some(false), Some(U256::MAX), size),
+            Err(PermitFallback::ContractUnsupported)
+        );
+        assert_eq!(permit_decision(Some(permit2), Some(true), None, size), Err(PermitFallback::AllowanceUnknown));
+        assert_eq!(
+            permit_decision(Some(permit2), Some(true), Some(size - 1), size),
+            Err(PermitFallback::NotApproved { allowance: size - 1 })
+        );
+    }
+
+    #[test]
+    fn test_probe_verdict() {
+        let zero_amount = ethers::utils::id("ZeroAmount()");
+        assert!(probe_supports_permit(Some(&zero_amount)));
+        // Unknown selector: empty revert; OnlyOperator: wallet not allowed
+        assert!(!probe_supports_permit(Some(&[])));
+        assert!(!probe_supports_permit(Some(&ethers::utils::id("OnlyOperator()"))));
+        assert!(!probe_supports_permit(None));
+    }
+}
@@ -0,0 +1,668 @@
+//! Snapshot-Consistent Execution Preflight
+//!
+//! Collapses the executor's separate pre-flight reads (per-leg Quoter checks,
+//! allowance lookup, pool state) into ONE Multicall3 `aggregate3` eth_call
+//! pinned to a single block. Every field of the resulting `PreflightBundle`
+//! describes the same chain state, so quotes, balance and allowance can no
+//! longer disagree the way independent RPCs at implicit "latest" blocks could.
+//!
+//! Batch layout (per opportunity about to execute):
+//!   [0] Multicall3.getBlockNumber()        — block the snapshot was read at
+//!   [1] buy pool state                     — slot0() / globalState() / getReserves()
+//!   [2] sell pool state
+//!   [3] quoteToken.balanceOf(wallet)
+//!   [4] quoteToken.allowance(wallet, spender)
+//!   [5] buy leg Quoter call                (V3 legs only)
+//!   [6] sell leg Quoter call               (V3 legs only)
+//!
+//! V2 legs have no Quoter: their output is computed from the reserves read in
+//! the same batch (x*y=k, 0.30% fee), which is exact for the snapshot block.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Quote balance, allowance and their rejections as QuoteAmount (units in messages)
+//! Modified: 2026-10-16 - Kyber Elastic pool state via getPoolState(), quotes via decode_quoter_result_for_dex
+//! Modified: 2026-10-16 - Balance/allowance shortfalls boxed (QuoteShortfall) to keep the Err variant small
+
+use super::multicall_quoter::MulticallQuoter;
+use crate::pool::kyber_syncer::GET_POOL_STATE_SELECTOR;
+use crate::quote_amount::QuoteAmount;
+use crate::types::{ArbitrageOpportunity, BotConfig, DexType};
+use anyhow::{anyhow, Result};
+use ethers::abi::{self, Token};
+use ethers::prelude::*;
+use ethers::types::{Address, U256};
+use std::fmt;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Multicall3 getBlockNumber() selector
+const GET_BLOCK_NUMBER_SELECTOR: [u8; 4] = [0x42, 0xcb, 0xb1, 0x5c];
+
+/// Uniswap V3 pool slot0() selector
+const SLOT0_SELECTOR: [u8; 4] = [0x38, 0x50, 0xc7, 0xbd];
+
+/// Algebra pool globalState() selector (QuickSwap V3 has no slot0)
+const GLOBAL_STATE_SELECTOR: [u8; 4] = [0xe7, 0x6c, 0x01, 0xe4];
+
+/// Uniswap V2 pair getReserves() selector
+const GET_RESERVES_SELECTOR: [u8; 4] = [0x09, 0x02, 0xf1, 0xac];
+
+/// ERC20 balanceOf(address) selector
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
+/// ERC20 allowance(address,address) selector
+const ALLOWANCE_SELECTOR: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+
+/// Prefix on TradeResult errors produced by a preflight rejection.
+/// main.rs matches on it to decide whether to try the next route.
+pub const PREFLIGHT_ERROR_PREFIX: &str = "Preflight rejected: ";
+
+/// Pool state read inside the preflight batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolSnapshot {
+    /// V3 / Algebra: sqrtPriceX96 and current tick
+    V3 { sqrt_price_x96: U256, tick: i32 },
+    /// V2: raw reserves in pool token order
+    V2 { reserve0: U256, reserve1: U256 },
+}
+
+/// Everything the executor needs to know before submitting, read at one block.
+#[derive(Debug, Clone)]
+pub struct PreflightBundle {
+    /// Block the aggregate3 call was evaluated at (from Multicall3.getBlockNumber)
+    pub block_number: u64,
+    /// Buy leg output (base token raw units)
+    pub buy_quoted_out: U256,
+    /// Sell leg output (quote token raw units)
+    pub sell_quoted_out: U256,
+    /// sell_quoted_out - trade_size in quote token raw units
+    pub quoted_profit_raw: i128,
+    /// Wallet balance of the quote token
+    pub quote_balance: QuoteAmount,
+    /// Quote token allowance granted by the wallet to `spender`
+    pub allowance: QuoteAmount,
+    /// Contract the allowance was read for (ArbExecutor or buy router)
+    pub spender: Address,
+    pub buy_pool: PoolSnapshot,
+    pub sell_pool: PoolSnapshot,
+}
+
+/// Quote token held vs needed for trade_size (boxed in PreflightRejection:
+/// two QuoteAmounts would make every preflight Err that large)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteShortfall {
+    pub available: QuoteAmount,
+    pub required: QuoteAmount,
+}
+
+/// Why a preflight bundle disqualified the trade
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreflightRejection {
+    /// Sub-call result could not be decoded (layout mismatch, short data)
+    MalformedResponse(String),
+    /// slot0/globalState/getReserves failed or returned an empty pool
+    PoolStateUnavailable { leg: &'static str, pool: Address },
+    /// Buy leg quote reverted or returned zero
+    BuyQuoteFailed(String),
+    /// Sell leg quote reverted or returned zero
+    SellQuoteFailed(String),
+    /// Both legs quoted but the round trip loses money
+    QuotedLoss { profit_raw: i128 },
+    /// Wallet does not hold enough quote token for trade_size
+    InsufficientBalance(Box<QuoteShortfall>),
+    /// Spender is not approved for trade_size
+    InsufficientAllowance(Box<QuoteShortfall>),
+}
+
+impl PreflightRejection {
+    /// True when the rejection is about this route only (another route may
+    /// still be tradeable this block). Wallet-level failures are not.
+    pub fn is_route_specific(&self) -> bool {
+        !matches!(
+            self,
+            PreflightRejection::InsufficientBalance(_) | PreflightRejection::InsufficientAllowance(_)
+        )
+    }
+}
+
+impl fmt::Display for PreflightRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PreflightRejection::MalformedResponse(msg) => write!(f, "malformed multicall response: {}", msg),
+            PreflightRejection::PoolStateUnavailable { leg, pool } => {
+                write!(f, "{} pool state unavailable ({:?})", leg, pool)
+            }
+            PreflightRejection::BuyQuoteFailed(msg) => write!(f, "buy leg Quoter failed: {}", msg),
+            PreflightRejection::SellQuoteFailed(msg) => write!(f, "sell leg Quoter failed: {}", msg),
+            PreflightRejection::QuotedLoss { profit_raw } => write!(f, "quoted loss (profit_raw={})", profit_raw),
+            PreflightRejection::InsufficientBalance(s) => {
+                write!(f, "wallet quote balance {} < trade size {}", s.available, s.required)
+            }
+            PreflightRejection::InsufficientAllowance(s) => {
+                write!(f, "wallet allowance {} < trade size {}", s.available, s.required)
+            }
+        }
+    }
+}
+
+/// True if a TradeResult error came from a route-specific preflight rejection.
+/// Wallet-level rejections (balance/allowance) render as "wallet ..." and
+/// return false so the caller stops trying further routes this block.
+pub fn is_route_rejection(error_msg: &str) -> bool {
+    error_msg
+        .strip_prefix(PREFLIGHT_ERROR_PREFIX)
+        .is_some_and(|rest| !rest.starts_with("wallet"))
+}
+
+/// Outcome of evaluating a preflight batch: approved bundle or rejection reason
+pub type PreflightOutcome = std::result::Result<PreflightBundle, PreflightRejection>;
+
+/// One aggregate3 sub-call: (target, calldata)
+type SubCall = (Address, Vec<u8>);
+
+/// Sub-call positions for one opportunity's batch
+#[derive(Debug, Clone)]
+pub(crate) struct PreflightLayout {
+    pub(crate) buy_dex: DexType,
+    pub(crate) sell_dex: DexType,
+    pub(crate) buy_pool: Address,
+    pub(crate) sell_pool: Address,
+    pub(crate) buy_quote_idx: Option<usize>,
+    pub(crate) sell_quote_idx: Option<usize>,
+}
+
+const BLOCK_IDX: usize = 0;
+const BUY_POOL_IDX: usize = 1;
+const SELL_POOL_IDX: usize = 2;
+const BALANCE_IDX: usize = 3;
+const ALLOWANCE_IDX: usize = 4;
+
+/// Builds and evaluates the single-call preflight batch.
+pub struct PreflightBatcher<M: Middleware> {
+    quoter: MulticallQuoter<M>,
+}
+
+impl<M: Middleware + 'static> PreflightBatcher<M> {
+    /// Create a batcher sharing the MulticallQuoter's quoter routing.
+    /// Fails if UNISWAP_V3_QUOTER is not configured.
+    pub fn new(provider: Arc<M>, config: &BotConfig) -> Result<Self> {
+        let quoter = MulticallQuoter::new(provider, config)?;
+        info!("Preflight batcher initialized (quotes + balance + allowance + pool state in 1 call)");
+        Ok(Self { quoter })
+    }
+
+    /// Run the preflight batch for one opportunity.
+    ///
+    /// Outer `Err` = the batch itself could not be issued or decoded (RPC error,
+    /// missing pool address) — callers fall back to per-call checks.
+    /// Inner `Err` = the snapshot disqualifies the trade.
+    ///
+    /// `require_allowance`: atomic mode needs the ArbExecutor approved up front;
+    /// legacy mode can still approve the router itself, so it only records it.
+    pub async fn run(
+        &self,
+        opp: &ArbitrageOpportunity,
+        wallet: Address,
+        spender: Address,
+        require_allowance: bool,
+        block: Option<u64>,
+    ) -> Result<PreflightOutcome> {
+        let (sub_calls, layout) = self.build_sub_calls(opp, wallet, spender)?;
+        let results = self.quoter.aggregate3_at(&sub_calls, block).await?;
+        let outcome = evaluate(opp, &layout, &results, spender, require_allowance);
+        match &outcome {
+            Ok(bundle) => debug!(
+                "Preflight OK: {} @ block {} | buy_out={} sell_out={} profit_raw={} balance={} allowance={}",
+                opp.pair.symbol, bundle.block_number, bundle.buy_quoted_out, bundle.sell_quoted_out,
+                bundle.quoted_profit_raw, bundle.quote_balance, bundle.allowance
+            ),
+            Err(reason) => info!("Preflight rejected {}: {}", opp.pair.symbol, reason),
+        }
+        Ok(outcome)
+    }
+
+    /// Encode the batch for one opportunity. See module docs for the layout.
+    pub(crate) fn build_sub_calls(
+        &self,
+        opp: &ArbitrageOpportunity,
+        wallet: Address,
+        spender: Address,
+    ) -> Result<(Vec<SubCall>, PreflightLayout)> {
+        let buy_pool = opp
+            .buy_pool_address
+            .ok_or_else(|| anyhow!("Preflight: buy pool address missing for {}", opp.pair.symbol))?;
+        let sell_pool = opp
+            .sell_pool_address
+            .ok_or_else(|| anyhow!("Preflight: sell pool address missing for {}", opp.pair.symbol))?;
+
+        let (quote_token, base_token) = if opp.quote_token_is_token0 {
+            (opp.pair.token0, opp.pair.token1)
+        } else {
+            (opp.pair.token1, opp.pair.token0)
+        };
+
+        let mut sub_calls: Vec<SubCall> = Vec::with_capacity(7);
+        sub_calls.push((self.quoter.multicall_address(), GET_BLOCK_NUMBER_SELECTOR.to_vec()));
+        sub_calls.push((buy_pool, encode_pool_state_call(opp.buy_dex)));
+        sub_calls.push((sell_pool, encode_pool_state_call(opp.sell_dex)));
+        sub_calls.push((quote_token, encode_balance_of(wallet)));
+        sub_calls.push((quote_token, encode_allowance(wallet, spender)));
+
+        let buy_quote_idx = if opp.buy_dex.is_v3() {
+            let fee = opp.buy_dex.v3_fee_tier().unwrap_or(0);
+            let call = self.quoter.encode_quoter_for_dex(
+                opp.buy_dex, quote_token, base_token, fee, opp.trade_size.raw(),
+            );
+            sub_calls.push((self.quoter.quoter_for_dex(opp.buy_dex), call));
+            Some(sub_calls.len() - 1)
+        } else {
+            None
+        };
+
+        // Sell leg is quoted with the estimated buy output (actual is unknown at encode time)
+        let sell_quote_idx = if opp.sell_dex.is_v3() {
+            let sell_quote_amount_in = MulticallQuoter::<M>::estimate_buy_output(opp);
+            let fee = opp.sell_dex.v3_fee_tier().unwrap_or(0);
+            let call = self.quoter.encode_quoter_for_dex(
+                opp.sell_dex, base_token, quote_token, fee, sell_quote_amount_in,
+            );
+            sub_calls.push((self.quoter.quoter_for_dex(opp.sell_dex), call));
+            Some(sub_calls.len() - 1)
+        } else {
+            None
+        };
+
+        let layout = PreflightLayout {
+            buy_dex: opp.buy_dex,
+            sell_dex: opp.sell_dex,
+            buy_pool,
+            sell_pool,
+            buy_quote_idx,
+            sell_quote_idx,
+        };
+        Ok((sub_calls, layout))
+    }
+}
+
+/// Decode a batch result and decide whether the trade may proceed.
+///
+/// Check order (first failure wins): response shape → pool state →
+/// balance → allowance → buy quote → sell quote → quoted profit.
+pub(crate) fn evaluate(
+    opp: &ArbitrageOpportunity,
+    layout: &PreflightLayout,
+    results: &[(bool, Vec<u8>)],
+    spender: Address,
+    require_allowance: bool,
+) -> PreflightOutcome {
+    let expected = 5 + layout.buy_quote_idx.is_some() as usize + layout.sell_quote_idx.is_some() as usize;
+    if results.len() != expected {
+        return Err(PreflightRejection::MalformedResponse(format!(
+            "{} results, expected {}",
+            results.len(),
+            expected
+        )));
+    }
+
+    let block_number = decode_word(&results[BLOCK_IDX])
+        .ok_or_else(|| PreflightRejection::MalformedResponse("getBlockNumber".to_string()))?
+        .low_u64();
+
+    let buy_pool = decode_pool_state(layout.buy_dex, &results[BUY_POOL_IDX])
+        .ok_or(PreflightRejection::PoolStateUnavailable { leg: "buy", pool: layout.buy_pool })?;
+    let sell_pool = decode_pool_state(layout.sell_dex, &results[SELL_POOL_IDX])
+        .ok_or(PreflightRejection::PoolStateUnavailable { leg: "sell", pool: layout.sell_pool })?;
+
+    let quote_balance = decode_word(&results[BALANCE_IDX])
+        .map(|raw| opp.quote_amount(raw))
+        .ok_or_else(|| PreflightRejection::MalformedResponse("balanceOf".to_string()))?;
+    if quote_balance < opp.trade_size {
+        return Err(PreflightRejection::InsufficientBalance(Box::new(QuoteShortfall {
+            available: quote_balance,
+            required: opp.trade_size,
+        })));
+    }
+
+    let allowance = decode_word(&results[ALLOWANCE_IDX])
+        .map(|raw| opp.quote_amount(raw))
+        .ok_or_else(|| PreflightRejection::MalformedResponse("allowance".to_string()))?;
+    if require_allowance && allowance < opp.trade_size {
+        return Err(PreflightRejection::InsufficientAllowance(Box::new(QuoteShortfall {
+            available: allowance,
+            required: opp.trade_size,
+        })));
+    }
+
+    // Buy leg: quote → base
+    let buy_quoted_out = match layout.buy_quote_idx {
+        Some(idx) => {
+            let (success, ref data) = results[idx];
+            MulticallQuoter::<Provider<Http>>::decode_quoter_result_for_dex(opp.buy_dex, success, data)
+                .map_err(|e| PreflightRejection::BuyQuoteFailed(e.to_string()))?
+        }
+        None => {
+            let out = v2_leg_output(&buy_pool, opp.trade_size.raw(), opp.quote_token_is_token0);
+            if out.is_zero() {
+                return Err(PreflightRejection::BuyQuoteFailed("V2 reserves yield zero output".to_string()));
+            }
+            out
+        }
+    };
+
+    // Sell leg: base → quote. V2 sells chain off the exact buy output;
+    // V3 sells were quoted with the estimated buy output.
+    let sell_quoted_out = match layout.sell_quote_idx {
+        Some(idx) => {
+            let (success, ref data) = results[idx];
+            MulticallQuoter::<Provider<Http>>::decode_quoter_result_for_dex(opp.sell_dex, success, data)
+                .map_err(|e| PreflightRejection::SellQuoteFailed(e.to_string()))?
+        }
+        None => {
+            let out = v2_leg_output(&sell_pool, buy_quoted_out, !opp.quote_token_is_token0);
+            if out.is_zero() {
+                return Err(PreflightRejection::SellQuoteFailed("V2 reserves yield zero output".to_string()));
+            }
+            out
+        }
+    };
+
+    let u128_max = U256::from(u128::MAX);
+    if opp.trade_size.raw() > u128_max || sell_quoted_out > u128_max {
+        return Err(PreflightRejection::MalformedResponse("u128 overflow in profit calculation".to_string()));
+    }
+    let quoted_profit_raw = sell_quoted_out.as_u128() as i128 - opp.trade_size.raw().as_u128() as i128;
+    if quoted_profit_raw <= 0 {
+        return Err(PreflightRejection::QuotedLoss { profit_raw: quoted_profit_raw });
+    }
+
+    Ok(PreflightBundle {
+        block_number,
+        buy_quoted_out,
+        sell_quoted_out,
+        quoted_profit_raw,
+        quote_balance,
+        allowance,
+        spender,
+        buy_pool,
+        sell_pool,
+    })
+}
+
+/// Pool state getter for a DEX type: Algebra → globalState(), V3 → slot0(), V2 → getReserves().
+fn encode_pool_state_call(dex: DexType) -> Vec<u8> {
+    if dex.is_kyber_elastic() {
+        // (sqrtP, currentTick, ...) — same leading words as slot0()
+        GET_POOL_STATE_SELECTOR.to_vec()
+    } else if dex.is_quickswap_v3() {
+        GLOBAL_STATE_SELECTOR.to_vec()
+    } else if dex.is_v3() {
+        SLOT0_SELECTOR.to_vec()
+    } else {
+        GET_RESERVES_SELECTOR.to_vec()
+    }
+}
+
+pub(crate) fn encode_balance_of(owner: Address) -> Vec<u8> {
+    let mut data = BALANCE_OF_SELECTOR.to_vec();
+    data.extend_from_slice(&abi::encode(&[Token::Address(owner)]));
+    data
+}
+
+fn encode_allowance(owner: Address, spender: Address) -> Vec<u8> {
+    let mut data = ALLOWANCE_SELECTOR.to_vec();
+    data.extend_from_slice(&abi::encode(&[Token::Address(owner), Token::Address(spender)]));
+    data
+}
+
+/// First 32-byte word of a successful sub-call
+pub(crate) fn decode_word(result: &(bool, Vec<u8>)) -> Option<U256> {
+    let (success, data) = result;
+    if !success || data.len() < 32 {
+        return None;
+    }
+    Some(U256::from_big_endian(&data[..32]))
+}
+
+/// Decode slot0/globalState (sqrtPrice, tick, ...) or getReserves (r0, r1, ts).
+/// Returns None for failed calls or empty pools.
+fn decode_pool_state(dex: DexType, result: &(bool, Vec<u8>)) -> Option<PoolSnapshot> {
+    let (success, data) = result;
+    if !success || data.len() < 64 {
+        return None;
+    }
+    let word0 = U256::from_big_endian(&data[..32]);
+    if dex.is_v3() {
+        if word0.is_zero() {
+            return None;
+        }
+        // int24 tick is sign-extended to 32 bytes — the low 4 bytes are a valid i32
+        let tick = i32::from_be_bytes([data[60], data[61], data[62], data[63]]);
+        Some(PoolSnapshot::V3 { sqrt_price_x96: word0, tick })
+    } else {
+        let reserve1 = U256::from_big_endian(&data[32..64]);
+        if word0.is_zero() || reserve1.is_zero() {
+            return None;
+        }
+        Some(PoolSnapshot::V2 { reserve0: word0, reserve1 })
+    }
+}
+
+/// Constant-product output for a V2 leg at the snapshot reserves (0.30% fee).
+/// `input_is_token0`: whether amount_in is denominated in pool token0.
+fn v2_leg_output(snapshot: &PoolSnapshot, amount_in: U256, input_is_token0: bool) -> U256 {
+    let (reserve0, reserve1) = match snapshot {
+        PoolSnapshot::V2 { reserve0, reserve1 } => (*reserve0, *reserve1),
+        PoolSnapshot::V3 { .. } => return U256::zero(),
+    };
+    let (reserve_in, reserve_out) = if input_is_token0 {
+        (reserve0, reserve1)
+    } else {
+        (reserve1, reserve0)
+    };
+    if reserve_in.is_zero() || reserve_out.is_zero() {
+        return U256::zero();
+    }
+    let amount_in_with_fee = amount_in * U256::from(997);
+    let numerator = amount_in_with_fee * reserve_out;
+    let denominator = reserve_in * U256::from(1000) + amount_in_with_fee;
+    numerator / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TradingPair;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    /// USDC(6)/WETH(18) opportunity, USDC = token0, 1000 USDC trade
+    fn test_opp(buy_dex: DexType, sell_dex: DexType) -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::with_pool_addresses(
+            TradingPair::new(addr(1), addr(2), "WETH/USDC".to_string()),
+            buy_dex,
+            sell_dex,
+            0.0004,
+            0.00039,
+            U256::from(1_000_000_000u64),
+            addr(10),
+            addr(11),
+        );
+        opp.set_quote_layout(6, 18, true);
+        opp.normalize_trade_size(1.0);
+        opp
+    }
+
+    fn layout_for(opp: &ArbitrageOpportunity) -> PreflightLayout {
+        let mut next = 5;
+        let mut slot = |v3: bool| {
+            if v3 {
+                next += 1;
+                Some(next - 1)
+            } else {
+                None
+            }
+        };
+        let buy_quote_idx = slot(opp.buy_dex.is_v3());
+        let sell_quote_idx = slot(opp.sell_dex.is_v3());
+        PreflightLayout {
+            buy_dex: opp.buy_dex,
+            sell_dex: opp.sell_dex,
+            buy_pool: opp.buy_pool_address.unwrap(),
+            sell_pool: opp.sell_pool_address.unwrap(),
+            buy_quote_idx,
+            sell_quote_idx,
+        }
+    }
+
+    fn word(v: u128) -> Vec<u8> {
+        abi::encode(&[Token::Uint(U256::from(v))])
+    }
+
+    /// slot0 response: sqrtPriceX96, tick (negative to exercise sign handling)
+    fn slot0_ok() -> (bool, Vec<u8>) {
+        let mut data = word(1u128 << 96);
+        data.extend_from_slice(&abi::encode(&[Token::Int(U256::MAX - U256::from(199))])); // tick -200
+        (true, data)
+    }
+
+    fn reserves_ok(r0: u128, r1: u128) -> (bool, Vec<u8>) {
+        (true, abi::encode(&[Token::Uint(r0.into()), Token::Uint(r1.into()), Token::Uint(0u64.into())]))
+    }
+
+    /// QuoterV1 returns amountOut via revert payload (success=false)
+    fn quote(v: u128) -> (bool, Vec<u8>) {
+        (false, word(v))
+    }
+
+    /// Mocked aggregate3 results for a V3↔V3 opportunity that passes every check
+    fn passing_v3_results() -> Vec<(bool, Vec<u8>)> {
+        vec![
+            (true, word(50_000_000)),
+            slot0_ok(),
+            slot0_ok(),
+            (true, word(5_000_000_000)),
+            (true, word(u128::MAX)),
+            quote(400_000_000_000_000_000),
+            quote(1_002_000_000),
+        ]
+    }
+
+    #[test]
+    fn test_selectors_match_signatures() {
+        assert_eq!(ethers::utils::id("getBlockNumber()"), GET_BLOCK_NUMBER_SELECTOR);
+        assert_eq!(ethers::utils::id("slot0()"), SLOT0_SELECTOR);
+        assert_eq!(ethers::utils::id("globalState()"), GLOBAL_STATE_SELECTOR);
+        assert_eq!(ethers::utils::id("getReserves()"), GET_RESERVES_SELECTOR);
+        assert_eq!(ethers::utils::id("balanceOf(address)"), BALANCE_OF_SELECTOR);
+        assert_eq!(ethers::utils::id("allowance(address,address)"), ALLOWANCE_SELECTOR);
+    }
+
+    #[test]
+    fn test_preflight_passes_v3() {
+        let opp = test_opp(DexType::UniswapV3_005, DexType::SushiV3_030);
+        let bundle = evaluate(&opp, &layout_for(&opp), &passing_v3_results(), addr(99), true).unwrap();
+        assert_eq!(bundle.block_number, 50_000_000);
+        assert_eq!(bundle.quoted_profit_raw, 2_000_000);
+        assert_eq!(bundle.spender, addr(99));
+        assert_eq!(
+            bundle.buy_pool,
+            PoolSnapshot::V3 { sqrt_price_x96: U256::from(1u128 << 96), tick: -200 }
+        );
+    }
+
+    #[test]
+    fn test_preflight_rejects_result_count_mismatch() {
+        let opp = test_opp(DexType::UniswapV3_005, DexType::SushiV3_030);
+        let mut results = passing_v3_results();
+        results.pop();
+        let err = evaluate(&opp, &layout_for(&opp), &results, addr(99), true).unwrap_err();
+        assert!(matches!(err, PreflightRejection::MalformedResponse(_)));
+    }
+
+    #[test]
+    fn test_preflight_rejects_pool_state() {
+        let opp = test_opp(DexType::UniswapV3_005, DexType::SushiV3_030);
+        let mut results = passing_v3_results();
+        results[SELL_POOL_IDX] = (false, Vec::new());
+        let err = evaluate(&opp, &layout_for(&opp), &results, addr(99), true).unwrap_err();
+        assert_eq!(err, PreflightRejection::PoolStateUnavailable { leg: "sell", pool: addr(11) });
+        assert!(err.is_route_specific());
+    }
+
+    #[test]
+    fn test_preflight_rejects_balance() {
+        let opp = test_opp(DexType::UniswapV3_005, DexType::SushiV3_030);
+        let mut results = passing_v3_results();
+        results[BALANCE_IDX] = (true, word(999_999_999));
+        let err = evaluate(&opp, &layout_for(&opp), &results, addr(99), true).unwrap_err();
+        assert!(matches!(err, PreflightRejection::InsufficientBalance(_)));
+        assert!(!err.is_route_specific());
+        assert!(!is_route_rejection(&format!("{}{}", PREFLIGHT_ERROR_PREFIX, err)));
+    }
+
+    #[test]
+    fn test_preflight_allowance_only_enforced_when_required() {
+        let opp = test_opp(DexType::UniswapV3_005, DexType::SushiV3_030);
+        let mut results = passing_v3_results();
+        results[ALLOWANCE_IDX] = (true, word(0));
+        let err = evaluate(&opp, &layout_for(&opp), &results, addr(99), true).unwrap_err();
+        assert!(matches!(err, PreflightRejection::InsufficientAllowance(_)));
+
+        // Legacy mode approves the router itself — allowance is informational
+        let bundle = evaluate(&opp, &layout_for(&opp), &results, addr(99), false).unwrap();
+        assert!(bundle.allowance.is_zero());
+    }
+
+    #[test]
+    fn test_preflight_rejects_buy_quote() {
+        let opp = test_opp(DexType::UniswapV3_005, DexType::SushiV3_030);
+        let mut results = passing_v3_results();
+        results[5] = (false, Vec::new());
+        let err = evaluate(&opp, &layout_for(&opp), &results, addr(99), true).unwrap_err();
+        assert!(matches!(err, PreflightRejection::BuyQuoteFailed(_)));
+        assert!(is_route_rejection(&format!("{}{}", PREFLIGHT_ERROR_PREFIX, err)));
+    }
+
+    #[test]
+    fn test_preflight_rejects_sell_quote() {
+        let opp = test_opp(DexType::UniswapV3_005, DexType::SushiV3_030);
+        let mut results = passing_v3_results();
+        results[6] = quote(0);
+        let err = evaluate(&opp, &layout_for(&opp), &results, addr(99), true).unwrap_err();
+        assert!(matches!(err, PreflightRejection::SellQuoteFailed(_)));
+    }
+
+    #[test]
+    fn test_preflight_rejects_quoted_loss() {
+        let opp = test_opp(DexType::UniswapV3_005, DexType::SushiV3_030);
+        let mut results = passing_v3_results();
+        results[6] = quote(998_000_000);
+        let err = evaluate(&opp, &layout_for(&opp), &results, addr(99), true).unwrap_err();
+        assert_eq!(err, PreflightRejection::QuotedLoss { profit_raw: -2_000_000 });
+    }
+
+    #[test]
+    fn test_preflight_v2_sell_leg_from_reserves() {
+        // Buy V3, sell V2: no sell Quoter sub-call, sell priced off snapshot reserves
+        let opp = test_opp(DexType::UniswapV3_005, DexType::QuickSwapV2);
+        let layout = layout_for(&opp);
+        assert!(layout.sell_quote_idx.is_none());
+
+        // Pool: 2.6M USDC (token0) / 1000 WETH (token1) → ~2600 USDC/WETH
+        let mut results = passing_v3_results();
+        results.pop();
+        results[SELL_POOL_IDX] = reserves_ok(2_600_000_000_000, 1_000_000_000_000_000_000_000);
+        let bundle = evaluate(&opp, &layout, &results, addr(99), true).unwrap();
+        // 0.4 WETH → ~1036.6 USDC after fee and impact
+        assert!(bundle.sell_quoted_out > U256::from(1_030_000_000u64));
+        assert!(bundle.quoted_profit_raw > 0);
+
+        // Drained V2 pool → pool state rejection
+        results[SELL_POOL_IDX] = reserves_ok(0, 0);
+        let err = evaluate(&opp, &layout, &results, addr(99), true).unwrap_err();
+        assert!(matches!(err, PreflightRejection::PoolStateUnavailable { leg: "sell", .. }));
+    }
+}
@@ -0,0 +1,323 @@
+//! Multicall Pre-screen Export
+//!
+//! Appends one JSON line per pre-screened opportunity to
+//! data/{chain}/research/prescreen_YYYYMMDD.jsonl: the detector's estimate,
+//! the batch Quoter verdict, and both legs' pool state at detection time.
+//! Lets a post-mortem recompute the detector's math against the exact
+//! sqrtPriceX96/tick/liquidity (or reserves) it priced from, and compare it
+//! with what the Quoter returned.
+//!
+//! Cooldown policy research (see cooldown_sim.rs) adds two streams:
+//!   - routes suppressed by the route cooldown are quoted after the block's
+//!     trades and exported with `cooled: true` — the counterfactual of what
+//!     the suppressed attempt would have been quoted at
+//!   - route_attempts_YYYYMMDD.jsonl: one line per executed attempt (outcome
+//!     class, cooldown effect, gas, net profit) and per reorg reversal
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Cooled-route counterfactual records + route attempt ledger
+
+use crate::arbitrage::multicall_quoter::VerifiedOpportunity;
+use crate::arbitrage::outcome::{CooldownEffect, TradeOutcome};
+use crate::types::{ArbitrageOpportunity, DexType, LegSnapshots, TradeResult};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use ethers::types::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// One pre-screened opportunity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrescreenRecord {
+    pub timestamp: String,
+    /// Head block the pre-screen ran at
+    pub block: u64,
+    pub pair: String,
+    pub buy_dex: DexType,
+    pub sell_dex: DexType,
+    pub buy_pool: Option<Address>,
+    pub sell_pool: Option<Address>,
+    pub trade_size: U256,
+    pub trade_size_usd: f64,
+    pub estimated_profit_usd: f64,
+    pub spread_percent: f64,
+    pub both_legs_valid: bool,
+    pub buy_quoted_out: U256,
+    pub sell_quoted_out: U256,
+    pub quoted_profit_raw: i128,
+    pub error: Option<String>,
+    pub pool_snapshots: Option<LegSnapshots>,
+    /// Route was suppressed by the cooldown — quoted for research only, never executed
+    #[serde(default)]
+    pub cooled: bool,
+}
+
+impl PrescreenRecord {
+    pub fn new(opp: &ArbitrageOpportunity, verified: &VerifiedOpportunity, block: u64) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            block,
+            pair: opp.pair.symbol.clone(),
+            buy_dex: opp.buy_dex,
+            sell_dex: opp.sell_dex,
+            buy_pool: opp.buy_pool_address,
+            sell_pool: opp.sell_pool_address,
+            trade_size: opp.trade_size.raw(),
+            trade_size_usd: opp.trade_size_usd,
+            estimated_profit_usd: opp.estimated_profit,
+            spread_percent: opp.spread_percent,
+            both_legs_valid: verified.both_legs_valid,
+            buy_quoted_out: verified.buy_quoted_out,
+            sell_quoted_out: verified.sell_quoted_out,
+            quoted_profit_raw: verified.quoted_profit_raw,
+            error: verified.error.clone(),
+            pool_snapshots: opp.pool_snapshots.clone(),
+            cooled: false,
+        }
+    }
+
+    /// Quoted profit in USD (quote units × the trade's USD per quote unit)
+    pub fn quoted_profit_usd(&self) -> f64 {
+        if self.trade_size.is_zero() {
+            return 0.0;
+        }
+        self.quoted_profit_raw as f64 * self.trade_size_usd / self.trade_size.low_u128() as f64
+    }
+}
+
+/// One executed attempt (or reorg reversal) and what it did to the route cooldown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttemptRecord {
+    pub timestamp: String,
+    pub block: u64,
+    pub pair: String,
+    pub buy_dex: DexType,
+    pub sell_dex: DexType,
+    /// TradeOutcome name, or "Reversed"
+    pub outcome: String,
+    pub cooldown: CooldownEffect,
+    pub tx_hash: Option<String>,
+    pub gas_cost_usd: f64,
+    pub net_profit_usd: f64,
+}
+
+impl AttemptRecord {
+    pub fn new(opp: &ArbitrageOpportunity, block: u64, outcome: TradeOutcome, result: Option<&TradeResult>) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            block,
+            pair: opp.pair.symbol.clone(),
+            buy_dex: opp.buy_dex,
+            sell_dex: opp.sell_dex,
+            outcome: format!("{:?}", outcome),
+            cooldown: outcome.cooldown(),
+            tx_hash: result.and_then(|r| r.tx_hash.clone()),
+            gas_cost_usd: result.map_or(0.0, |r| r.gas_cost_usd),
+            net_profit_usd: result.map_or(0.0, |r| r.net_profit_usd),
+        }
+    }
+
+    /// A landed trade reversed by a reorg: its success is revoked (escalate)
+    /// and its booked profit taken back
+    pub fn reversal(pair: &str, buy_dex: DexType, sell_dex: DexType, tx_hash: String, net_profit_usd: f64, block: u64) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            block,
+            pair: pair.to_string(),
+            buy_dex,
+            sell_dex,
+            outcome: "Reversed".to_string(),
+            cooldown: CooldownEffect::Escalate,
+            tx_hash: Some(tx_hash),
+            gas_cost_usd: 0.0,
+            net_profit_usd: -net_profit_usd,
+        }
+    }
+
+    pub fn is_reversal(&self) -> bool {
+        self.outcome == "Reversed"
+    }
+}
+
+/// Daily-rotated JSONL writer for pre-screen results
+pub struct PrescreenExporter {
+    dir: PathBuf,
+}
+
+impl PrescreenExporter {
+    pub fn new(dir: &str) -> Self {
+        if let Err(e) = fs::create_dir_all(dir) {
+            warn!("Prescreen export: failed to create {}: {}", dir, e);
+        }
+        Self { dir: PathBuf::from(dir) }
+    }
+
+    fn path_for_today(&self) -> PathBuf {
+        self.dir.join(format!("prescreen_{}.jsonl", Utc::now().format("%Y%m%d")))
+    }
+
+    fn attempts_path_for_today(&self) -> PathBuf {
+        self.dir.join(format!("route_attempts_{}.jsonl", Utc::now().format("%Y%m%d")))
+    }
+
+    /// Append one line per verified entry. `verified[i].original_index` indexes `opportunities`.
+    pub fn export(
+        &self,
+        opportunities: &[ArbitrageOpportunity],
+        verified: &[VerifiedOpportunity],
+        block: u64,
+    ) -> Result<usize> {
+        self.write_records(opportunities, verified, block, false)
+    }
+
+    /// Same as export(), for routes the cooldown suppressed (`cooled: true`)
+    pub fn export_cooled(
+        &self,
+        opportunities: &[ArbitrageOpportunity],
+        verified: &[VerifiedOpportunity],
+        block: u64,
+    ) -> Result<usize> {
+        self.write_records(opportunities, verified, block, true)
+    }
+
+    /// Append one attempt to the route attempt ledger
+    pub fn export_attempt(&self, record: &AttemptRecord) -> Result<()> {
+        let path = self.attempts_path_for_today();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("open {}", path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+        Ok(())
+    }
+
+    fn write_records(
+        &self,
+        opportunities: &[ArbitrageOpportunity],
+        verified: &[VerifiedOpportunity],
+        block: u64,
+        cooled: bool,
+    ) -> Result<usize> {
+        let path = self.path_for_today();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("open {}", path.display()))?;
+
+        let mut written = 0;
+        for v in verified {
+            let Some(opp) = opportunities.get(v.original_index) else {
+                continue;
+            };
+            let record = PrescreenRecord { cooled, ..PrescreenRecord::new(opp, v, block) };
+            let line = serde_json::to_string(&record)?;
+            writeln!(file, "{}", line)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+}
+
+/// Read every record from a prescreen JSONL file (post-mortem tooling)
+pub fn load_records(path: &Path) -> Result<Vec<PrescreenRecord>> {
+    let content = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| serde_json::from_str(l).context("parse prescreen record"))
+        .collect()
+}
+
+/// Read every record from a route attempt ledger file
+pub fn load_attempts(path: &Path) -> Result<Vec<AttemptRecord>> {
+    let content = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| serde_json::from_str(l).context("parse route attempt record"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{PoolStateSnapshot, TradingPair, V3PoolState};
+
+    fn v3_pool(n: u64, dex: DexType, fee: u32, tick: i32) -> V3PoolState {
+        V3PoolState {
+            address: Address::from_low_u64_be(n),
+            dex,
+            pair: TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            sqrt_price_x96: U256::from(1u128 << 96) * U256::from(n),
+            tick,
+            fee,
+            liquidity: u128::MAX - n as u128,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 1_000 + n,
+        }
+    }
+
+    #[test]
+    fn test_export_round_trips_snapshots() {
+        let dir = std::env::temp_dir().join(format!("dexarb_prescreen_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let exporter = PrescreenExporter::new(dir.to_str().unwrap());
+
+        let buy = v3_pool(10, DexType::UniswapV3_005, 500, -197_000);
+        let sell = v3_pool(11, DexType::UniswapV3_030, 3000, -197_100);
+        let mut opp = ArbitrageOpportunity::with_pool_addresses(
+            buy.pair.clone(),
+            buy.dex,
+            sell.dex,
+            0.0004,
+            0.00039,
+            U256::from(1_000_000_000u64),
+            buy.address,
+            sell.address,
+        );
+        opp.pool_snapshots = Some(LegSnapshots {
+            buy: PoolStateSnapshot::from_v3(&buy, 1_700_000_000),
+            sell: PoolStateSnapshot::from_v3(&sell, 1_700_000_000),
+        });
+        let verified = vec![
+            VerifiedOpportunity {
+                original_index: 0,
+                buy_quoted_out: U256::from(400_000_000_000_000_000u128),
+                sell_quoted_out: U256::from(1_001_000_000u64),
+                quoted_profit_raw: 1_000_000,
+                both_legs_valid: true,
+                error: None,
+            },
+            // Out-of-range index is skipped, not an error
+            VerifiedOpportunity::passthrough(7),
+        ];
+
+        assert_eq!(exporter.export(&[opp.clone()], &verified, 1_234).unwrap(), 1);
+
+        let records = load_records(&exporter.path_for_today()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].block, 1_234);
+        assert_eq!(records[0].quoted_profit_raw, 1_000_000);
+        assert_eq!(records[0].pool_snapshots, opp.pool_snapshots);
+        assert!(!records[0].cooled);
+
+        // Cooled counterfactual + attempt ledger
+        assert_eq!(exporter.export_cooled(&[opp.clone()], &verified, 1_235).unwrap(), 1);
+        let records = load_records(&exporter.path_for_today()).unwrap();
+        assert!(records[1].cooled);
+        let attempt = AttemptRecord::new(&opp, 1_235, TradeOutcome::AtomicRevert, None);
+        exporter.export_attempt(&attempt).unwrap();
+        let attempts = load_attempts(&exporter.attempts_path_for_today()).unwrap();
+        assert_eq!(attempts, vec![attempt]);
+        assert_eq!(attempts[0].cooldown, CooldownEffect::Escalate);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
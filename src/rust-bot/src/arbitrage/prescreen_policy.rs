@@ -0,0 +1,378 @@
+//! Adaptive Pre-screen Policy — per-route pre-screen skipping earned by streaks
+//!
+//! The multicall pre-screen costs one batch RPC per block and mostly confirms
+//! what deep, stable routes at our sizes already show: the trade lands. This
+//! module learns, per route, whether the pre-screen still adds information:
+//!
+//!   - a route's streak counts consecutive executed trades whose outcome agreed
+//!     with going ahead (TradeOutcome::Success), pre-screened or not
+//!   - at PRESCREEN_SKIP_STREAK the route is promoted: its opportunities go
+//!     direct to the executor (whose Quoter + estimateGas still protect capital)
+//!   - every PRESCREEN_REVERIFY_EVERY-th trade of a promoted route is
+//!     pre-screened anyway, to catch drift
+//!   - any disagreement — a revert, Quoter/preflight rejection or other failure
+//!     after going ahead, or a pre-screen rejection — resets the streak and
+//!     pre-screening is mandatory again
+//!
+//! Outcomes that are not route properties (thin liquidity, exposure pacing, tax
+//! halt, RPC errors) neither extend nor break a streak. Canary routes are always
+//! pre-screened regardless of policy (see main.rs).
+//!
+//! State persists to data/{chain}/prescreen_policy.json after every change and
+//! is reported per route in the periodic status line. PRESCREEN_SKIP_ENABLED=false
+//! (default) disables the policy entirely: nothing is tracked or skipped.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use super::outcome::TradeOutcome;
+use super::slippage::route_key;
+use crate::types::{ArbitrageOpportunity, BotConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Streak / re-verification policy (BotConfig prescreen_skip_*)
+#[derive(Debug, Clone)]
+pub struct PrescreenPolicyConfig {
+    pub enabled: bool,
+    /// Consecutive agreeing trades before a route may skip its pre-screen
+    pub streak: u32,
+    /// Every K-th trade of a promoted route is pre-screened (1 = never skip)
+    pub reverify_every: u32,
+}
+
+impl PrescreenPolicyConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            enabled: config.prescreen_skip_enabled,
+            streak: config.prescreen_skip_streak.max(1),
+            reverify_every: config.prescreen_reverify_every.max(1),
+        }
+    }
+}
+
+/// Whether one opportunity goes through the multicall pre-screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrescreenDecision {
+    /// Route has not earned skipping (or the policy is disabled)
+    Screen,
+    /// Promoted route, but this is its periodic re-verification
+    Reverify,
+    /// Promoted route — direct to the executor
+    Skip,
+}
+
+impl PrescreenDecision {
+    pub fn prescreen(self) -> bool {
+        self != PrescreenDecision::Skip
+    }
+}
+
+/// Streak state and history of one route
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutePrescreenState {
+    /// Consecutive executed trades that agreed with going ahead
+    pub streak: u32,
+    /// Trades executed without a pre-screen since the last pre-screened one
+    pub since_verify: u32,
+    /// Executed trades that agreed (lifetime)
+    pub agreed: u64,
+    /// Executed trades that disagreed (lifetime)
+    pub disagreed: u64,
+    /// Pre-screen rejections (lifetime)
+    pub rejected: u64,
+    /// Trades that skipped the pre-screen (lifetime)
+    pub skipped: u64,
+    /// Times the route lost its promotion
+    pub demotions: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PolicyFile {
+    saved_at: String,
+    routes: BTreeMap<String, RoutePrescreenState>,
+}
+
+/// Agreement of an executed trade with the decision to go ahead
+/// (None = not a route property; leaves the streak alone)
+fn agreement(outcome: TradeOutcome) -> Option<bool> {
+    match outcome {
+        TradeOutcome::Success => Some(true),
+        TradeOutcome::InsufficientLiquidity
+        | TradeOutcome::ExposureLimited
+        | TradeOutcome::TaxLoggingHalted
+        | TradeOutcome::StaleOpportunity
+        | TradeOutcome::ExecutionError => None,
+        TradeOutcome::ReceiptTimeout
+        | TradeOutcome::AtomicRevert
+        | TradeOutcome::GasNegative
+        | TradeOutcome::CapitalAtRisk
+        | TradeOutcome::RouteRejected
+        | TradeOutcome::PreTradeFailure => Some(false),
+    }
+}
+
+/// Per-route pre-screen skipping policy
+pub struct PrescreenPolicy {
+    config: PrescreenPolicyConfig,
+    routes: BTreeMap<String, RoutePrescreenState>,
+    path: Option<PathBuf>,
+}
+
+impl PrescreenPolicy {
+    pub fn new(config: PrescreenPolicyConfig) -> Self {
+        Self { config, routes: BTreeMap::new(), path: None }
+    }
+
+    /// Load and save state in `{data_dir}/prescreen_policy.json`
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Pre-screen policy: failed to create {}: {}", data_dir, e);
+        }
+        let path = dir.join("prescreen_policy.json");
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<PolicyFile>(&content) {
+                Ok(file) => {
+                    info!("Pre-screen policy: {} route(s) restored (saved {})", file.routes.len(), file.saved_at);
+                    self.routes = file.routes;
+                }
+                Err(e) => warn!("Pre-screen policy: ignoring unreadable {}: {}", path.display(), e),
+            }
+        }
+        self.path = Some(path);
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn route(&self, opp: &ArbitrageOpportunity) -> Option<&RoutePrescreenState> {
+        self.routes.get(&route_key(opp))
+    }
+
+    fn is_promoted(&self, state: &RoutePrescreenState) -> bool {
+        state.streak >= self.config.streak
+    }
+
+    pub fn decide(&self, opp: &ArbitrageOpportunity) -> PrescreenDecision {
+        if !self.config.enabled {
+            return PrescreenDecision::Screen;
+        }
+        match self.routes.get(&route_key(opp)) {
+            Some(state) if self.is_promoted(state) => {
+                if state.since_verify + 1 >= self.config.reverify_every {
+                    PrescreenDecision::Reverify
+                } else {
+                    PrescreenDecision::Skip
+                }
+            }
+            _ => PrescreenDecision::Screen,
+        }
+    }
+
+    /// Record an executed attempt. `prescreened` = it went through the pre-screen.
+    pub fn record_outcome(&mut self, opp: &ArbitrageOpportunity, prescreened: bool, outcome: TradeOutcome) {
+        if !self.config.enabled {
+            return;
+        }
+        let agreed = match agreement(outcome) {
+            Some(agreed) => agreed,
+            None => return,
+        };
+        let key = route_key(opp);
+        let threshold = self.config.streak;
+        let state = self.routes.entry(key.clone()).or_default();
+        if agreed {
+            state.agreed += 1;
+            state.streak = state.streak.saturating_add(1);
+            if prescreened {
+                state.since_verify = 0;
+            } else {
+                state.skipped += 1;
+                state.since_verify += 1;
+            }
+            if state.streak == threshold {
+                info!("🎯 Pre-screen policy: {} promoted after {} agreeing trades — pre-screen skipped", key, threshold);
+            }
+        } else {
+            state.disagreed += 1;
+            Self::demote(state, threshold, &key, &format!("{:?}", outcome));
+        }
+        self.save();
+    }
+
+    /// Record a pre-screen rejection of the route (quoted unprofitable or a leg failed)
+    pub fn record_rejected(&mut self, opp: &ArbitrageOpportunity) {
+        if !self.config.enabled {
+            return;
+        }
+        let key = route_key(opp);
+        let threshold = self.config.streak;
+        let state = self.routes.entry(key.clone()).or_default();
+        state.rejected += 1;
+        Self::demote(state, threshold, &key, "pre-screen rejection");
+        self.save();
+    }
+
+    fn demote(state: &mut RoutePrescreenState, threshold: u32, key: &str, reason: &str) {
+        if state.streak >= threshold {
+            state.demotions += 1;
+            warn!("Pre-screen policy: {} demoted ({}) — pre-screen mandatory again", key, reason);
+        }
+        state.streak = 0;
+        state.since_verify = 0;
+    }
+
+    /// One line per route: mode, streak and lifetime counts
+    pub fn summary_lines(&self) -> Vec<String> {
+        self.routes
+            .iter()
+            .map(|(key, s)| {
+                let mode = if self.is_promoted(s) { "SKIP" } else { "screen" };
+                format!(
+                    "{} | {} streak {}/{} | agreed {} disagreed {} rejected {} skipped {} demotions {}",
+                    key, mode, s.streak, self.config.streak, s.agreed, s.disagreed, s.rejected, s.skipped, s.demotions
+                )
+            })
+            .collect()
+    }
+
+    fn save(&self) {
+        let path = match self.path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let file = PolicyFile {
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            routes: self.routes.clone(),
+        };
+        match serde_json::to_string_pretty(&file) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    warn!("Pre-screen policy: failed to write {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => warn!("Pre-screen policy: failed to serialize state: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+    use ethers::types::{Address, U256};
+
+    fn policy(enabled: bool) -> PrescreenPolicy {
+        PrescreenPolicy::new(PrescreenPolicyConfig { enabled, streak: 3, reverify_every: 4 })
+    }
+
+    fn opp(symbol: &str) -> ArbitrageOpportunity {
+        ArbitrageOpportunity::new(
+            TradingPair::new(Address::zero(), Address::zero(), symbol.to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            1.0,
+            1.01,
+            U256::from(1_000_000u64),
+        )
+    }
+
+    /// Run one trade the way the block loop does: decide, then record
+    fn trade(p: &mut PrescreenPolicy, o: &ArbitrageOpportunity, outcome: TradeOutcome) -> PrescreenDecision {
+        let decision = p.decide(o);
+        p.record_outcome(o, decision.prescreen(), outcome);
+        decision
+    }
+
+    #[test]
+    fn test_streak_counting() {
+        let mut p = policy(true);
+        let o = opp("WETH/USDC");
+        for _ in 0..2 {
+            assert_eq!(trade(&mut p, &o, TradeOutcome::Success), PrescreenDecision::Screen);
+        }
+        // Not route properties: streak neither grows nor resets
+        trade(&mut p, &o, TradeOutcome::InsufficientLiquidity);
+        trade(&mut p, &o, TradeOutcome::ExposureLimited);
+        trade(&mut p, &o, TradeOutcome::ExecutionError);
+        assert_eq!(p.route(&o).unwrap().streak, 2);
+        assert_eq!(p.route(&o).unwrap().agreed, 2);
+        // Other routes are independent
+        assert!(p.route(&opp("WBTC/USDC")).is_none());
+    }
+
+    #[test]
+    fn test_skip_after_streak() {
+        let mut p = policy(true);
+        let o = opp("WETH/USDC");
+        for _ in 0..3 {
+            assert_eq!(trade(&mut p, &o, TradeOutcome::Success), PrescreenDecision::Screen);
+        }
+        assert_eq!(p.decide(&o), PrescreenDecision::Skip);
+        assert_eq!(p.decide(&opp("WBTC/USDC")), PrescreenDecision::Screen);
+        assert!(p.summary_lines()[0].contains("SKIP streak 3/3"));
+    }
+
+    #[test]
+    fn test_periodic_reverification() {
+        let mut p = policy(true);
+        let o = opp("WETH/USDC");
+        for _ in 0..3 {
+            trade(&mut p, &o, TradeOutcome::Success);
+        }
+        // reverify_every = 4: three skipped trades, then one pre-screened
+        let decisions: Vec<PrescreenDecision> = (0..8).map(|_| trade(&mut p, &o, TradeOutcome::Success)).collect();
+        use PrescreenDecision::*;
+        assert_eq!(decisions, vec![Skip, Skip, Skip, Reverify, Skip, Skip, Skip, Reverify]);
+        assert_eq!(p.route(&o).unwrap().skipped, 6);
+        assert_eq!(p.route(&o).unwrap().since_verify, 0);
+    }
+
+    #[test]
+    fn test_demotion_on_failure() {
+        let mut p = policy(true);
+        let o = opp("WETH/USDC");
+        for _ in 0..3 {
+            trade(&mut p, &o, TradeOutcome::Success);
+        }
+        // A revert while skipping restores mandatory pre-screening at once
+        assert_eq!(trade(&mut p, &o, TradeOutcome::AtomicRevert), PrescreenDecision::Skip);
+        assert_eq!(p.decide(&o), PrescreenDecision::Screen);
+        let state = p.route(&o).unwrap();
+        assert_eq!((state.streak, state.demotions, state.disagreed), (0, 1, 1));
+
+        // So does a pre-screen rejection at re-verification
+        for _ in 0..3 {
+            trade(&mut p, &o, TradeOutcome::Success);
+        }
+        assert_eq!(p.decide(&o), PrescreenDecision::Skip);
+        p.record_rejected(&o);
+        assert_eq!(p.decide(&o), PrescreenDecision::Screen);
+        assert_eq!(p.route(&o).unwrap().demotions, 2);
+    }
+
+    #[test]
+    fn test_disabled_and_persistence() {
+        let mut off = policy(false);
+        let o = opp("WETH/USDC");
+        for _ in 0..5 {
+            assert_eq!(trade(&mut off, &o, TradeOutcome::Success), PrescreenDecision::Screen);
+        }
+        assert!(off.summary_lines().is_empty());
+
+        let dir = std::env::temp_dir().join(format!("prescreen_policy_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut p = policy(true).with_persistence(dir.to_str().unwrap());
+        for _ in 0..3 {
+            trade(&mut p, &o, TradeOutcome::Success);
+        }
+        let restored = policy(true).with_persistence(dir.to_str().unwrap());
+        assert_eq!(restored.decide(&o), PrescreenDecision::Skip);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,201 @@
+//! Opportunity Registry — one submission per spread across detection sources
+//!
+//! Purpose:
+//!     Right after a large swap confirms, the mempool simulator (which saw it
+//!     pending) and the block-loop detector (which sees it landed) flag the
+//!     same spread. Sending both means the second tx reverts: gas burned and
+//!     the route cooled down for nothing. Both paths run on the main loop, so
+//!     they share one registry: the block loop and the mempool dispatch each
+//!     skip an opportunity equivalent to one submitted within the TTL, and
+//!     record their own submissions.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Design:
+//!     - Key: (pair, buy DEX + pool, sell DEX + pool). The DEX is part of the
+//!       key so mempool opportunities with an unresolved pool address don't
+//!       collide across DEXes.
+//!     - Submitted at block B suppresses equivalents while current < B + TTL
+//!       (OPPORTUNITY_DEDUP_TTL_BLOCKS; 0 = disabled). evict() drops expired
+//!       entries each block, so the map stays as small as the recent sends.
+//!     - merge(): duplicates within one scan collapse to the most profitable
+//!       (quoted profit when pre-screened, estimated profit otherwise).
+
+use crate::types::{ArbitrageOpportunity, DexType};
+use ethers::types::Address;
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Identity of an opportunity for deduplication
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OpportunityKey {
+    pub pair: String,
+    pub buy: (DexType, Option<Address>),
+    pub sell: (DexType, Option<Address>),
+}
+
+impl OpportunityKey {
+    pub fn of(opp: &ArbitrageOpportunity) -> Self {
+        Self {
+            pair: opp.pair.symbol.clone(),
+            buy: (opp.buy_dex, opp.buy_pool_address),
+            sell: (opp.sell_dex, opp.sell_pool_address),
+        }
+    }
+}
+
+/// Profit (USD) an opportunity is ranked by when merging: the pre-screen
+/// quote when present, else the detector's estimate
+fn merge_profit_usd(opp: &ArbitrageOpportunity) -> f64 {
+    match opp.quoted_legs {
+        Some(ref legs) => opp.quote_to_usd(opp.quote_amount(legs.sell_out)) - opp.quote_to_usd(opp.trade_size),
+        None => opp.estimated_profit,
+    }
+}
+
+/// Recently submitted opportunities, shared by the block loop and mempool dispatch
+pub struct OpportunityRegistry {
+    ttl_blocks: u64,
+    /// Key → block it was submitted at
+    submitted: HashMap<OpportunityKey, u64>,
+}
+
+impl OpportunityRegistry {
+    /// `ttl_blocks` = blocks a submission suppresses its equivalents (0 = disabled)
+    pub fn new(ttl_blocks: u64) -> Self {
+        Self { ttl_blocks, submitted: HashMap::new() }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.ttl_blocks > 0
+    }
+
+    /// An equivalent opportunity was submitted within the TTL
+    pub fn is_duplicate(&self, opp: &ArbitrageOpportunity, current_block: u64) -> bool {
+        self.is_enabled()
+            && self
+                .submitted
+                .get(&OpportunityKey::of(opp))
+                .is_some_and(|&block| current_block < block + self.ttl_blocks)
+    }
+
+    /// A tx for `opp` went out at `block`
+    pub fn record_submission(&mut self, opp: &ArbitrageOpportunity, block: u64) {
+        if self.is_enabled() {
+            self.submitted.insert(OpportunityKey::of(opp), block);
+        }
+    }
+
+    /// Drop submissions whose TTL has run out
+    pub fn evict(&mut self, current_block: u64) {
+        let ttl = self.ttl_blocks;
+        let before = self.submitted.len();
+        self.submitted.retain(|_, &mut block| current_block < block + ttl);
+        if self.submitted.len() < before {
+            debug!("Opportunity registry: {} expired submission(s) evicted", before - self.submitted.len());
+        }
+    }
+
+    /// Collapse duplicates within one scan to the most profitable of each key
+    /// (first-seen order of the keys is kept)
+    pub fn merge(&self, opportunities: Vec<ArbitrageOpportunity>) -> Vec<ArbitrageOpportunity> {
+        let mut index: HashMap<OpportunityKey, usize> = HashMap::new();
+        let mut merged: Vec<ArbitrageOpportunity> = Vec::with_capacity(opportunities.len());
+        for opp in opportunities {
+            match index.get(&OpportunityKey::of(&opp)) {
+                Some(&i) => {
+                    if merge_profit_usd(&opp) > merge_profit_usd(&merged[i]) {
+                        merged[i] = opp;
+                    }
+                }
+                None => {
+                    index.insert(OpportunityKey::of(&opp), merged.len());
+                    merged.push(opp);
+                }
+            }
+        }
+        merged
+    }
+
+    pub fn len(&self) -> usize {
+        self.submitted.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.submitted.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{QuotedLegs, TradingPair};
+    use ethers::types::U256;
+
+    fn opp(buy_pool: u64, sell_pool: u64, estimated_profit: f64) -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::QuickswapV3,
+            2500.0,
+            2510.0,
+            U256::from(500_000_000u64),
+        );
+        opp.buy_pool_address = Some(Address::from_low_u64_be(buy_pool));
+        opp.sell_pool_address = Some(Address::from_low_u64_be(sell_pool));
+        opp.estimated_profit = estimated_profit;
+        opp
+    }
+
+    #[test]
+    fn test_duplicate_suppressed_within_ttl_then_expires() {
+        let mut registry = OpportunityRegistry::new(2);
+        let block_loop = opp(0xa, 0xb, 1.5);
+        assert!(!registry.is_duplicate(&block_loop, 100));
+        registry.record_submission(&block_loop, 100);
+
+        // The mempool path flags the same spread: suppressed for 2 blocks
+        let mut mempool = opp(0xa, 0xb, 2.0);
+        mempool.spread_percent = 0.9;
+        assert!(registry.is_duplicate(&mempool, 100));
+        assert!(registry.is_duplicate(&mempool, 101));
+        // Other pools, reversed direction: independent
+        assert!(!registry.is_duplicate(&opp(0xa, 0xc, 1.0), 100));
+        assert!(!registry.is_duplicate(&opp(0xb, 0xa, 1.0), 100));
+
+        // TTL over: allowed again, and evicted from the map
+        assert!(!registry.is_duplicate(&mempool, 102));
+        registry.evict(101);
+        assert_eq!(registry.len(), 1);
+        registry.evict(102);
+        assert!(registry.is_empty());
+
+        // TTL 0: registry off
+        let mut off = OpportunityRegistry::new(0);
+        off.record_submission(&block_loop, 100);
+        assert!(!off.is_duplicate(&block_loop, 100));
+        assert!(off.is_empty());
+    }
+
+    #[test]
+    fn test_merge_keeps_most_profitable_duplicate() {
+        let registry = OpportunityRegistry::new(2);
+        let mut quoted_low = opp(0xa, 0xb, 9.0);
+        // Quoted: 500 USDC in → 501 out = $1, whatever the estimate says
+        quoted_low.quoted_legs = Some(QuotedLegs { buy_out: U256::exp10(17), sell_out: U256::from(501_000_000u64) });
+        let mut quoted_high = opp(0xa, 0xb, 0.5);
+        quoted_high.quoted_legs = Some(QuotedLegs { buy_out: U256::exp10(17), sell_out: U256::from(503_000_000u64) });
+        let other = opp(0xc, 0xd, 0.7);
+
+        let merged = registry.merge(vec![quoted_low, other.clone(), quoted_high]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].quoted_legs.as_ref().unwrap().sell_out, U256::from(503_000_000u64));
+        assert_eq!(merged[1].buy_pool_address, other.buy_pool_address);
+
+        // Unquoted duplicates rank by estimated profit
+        let merged = registry.merge(vec![opp(0xa, 0xb, 1.0), opp(0xa, 0xb, 3.0), opp(0xa, 0xb, 2.0)]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].estimated_profit, 3.0);
+    }
+}
@@ -0,0 +1,402 @@
+//! Optimal Trade Sizing
+//!
+//! The detector sizes every opportunity at MAX_TRADE_SIZE_USD, which on thin
+//! pools overshoots the depth the spread actually pays for — the Quoter
+//! rejects it or the trade reverts. Before pre-screen, each opportunity is
+//! resized to the input that maximizes round-trip profit, never above the
+//! size it arrived with (canary caps and liquidity guard downsizing stand):
+//!
+//!   V2↔V2: closed form from the snapshot reserves. With quote reserves a1/a2,
+//!          base reserves b1/b2 and fee factor γ, x quote in returns
+//!          z(x) = A·x / (B + C·x),  A = γ²·b1·a2,  B = a1·b2,  C = γ·(b2 + γ·b1)
+//!          and profit z(x) − x peaks at x* = (√(A·B) − B) / C (only if A > B).
+//!
+//!   V3 legs: a halving ladder (size, size/2, size/4, size/8) of every route is
+//!          quoted in ONE Multicall3 batch. The sell leg is quoted on the
+//!          midmarket buy estimate, so its output is scaled by the quoted buy
+//!          output to carry the buy leg's price impact into the profit.
+//!
+//! Mixed V2/V3 routes keep their size (the V3 quoters cannot quote a V2 leg).
+//! The chosen size writes back trade_size, trade_size_usd and estimated_profit
+//! (quoted profit − gas). A route whose best size does not clear MIN_PROFIT_USD
+//! is dropped: the contract's minProfit would revert it at any size.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::arbitrage::multicall_quoter::MulticallQuoter;
+use crate::quote_amount::QuoteAmount;
+use crate::types::{ArbitrageOpportunity, BotConfig, PoolStateKind, PoolStateSnapshot};
+use anyhow::{anyhow, Result};
+use ethers::prelude::Middleware;
+use ethers::types::{Address, U256};
+use tracing::{debug, warn};
+
+/// Ladder rungs per V3 route (size / 2^k for k in 0..LADDER_STEPS)
+pub const LADDER_STEPS: u32 = 4;
+
+/// How a size was chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizingMethod {
+    V2ClosedForm,
+    QuoterLadder,
+}
+
+impl SizingMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SizingMethod::V2ClosedForm => "closed-form",
+            SizingMethod::QuoterLadder => "quoter ladder",
+        }
+    }
+}
+
+/// Outcome of sizing one opportunity
+#[derive(Debug, Clone, PartialEq)]
+pub enum SizingVerdict {
+    /// trade_size rewritten in place (from the incoming size)
+    Sized {
+        method: SizingMethod,
+        from: QuoteAmount,
+        quoted_profit_raw: i128,
+    },
+    /// No sizing information (mixed route, no snapshot, quoter unavailable)
+    Unchanged,
+    /// No size clears the minimum profit — drop the route this block
+    Unprofitable { method: SizingMethod, best_profit_raw: i128 },
+}
+
+/// Per-block sizing pass over the surviving opportunities
+pub struct OptimalSizer {
+    gas_cost_usd: f64,
+    min_profit_usd: f64,
+}
+
+impl OptimalSizer {
+    pub fn new(config: &BotConfig) -> Self {
+        Self {
+            gas_cost_usd: config.estimated_gas_cost_usd,
+            min_profit_usd: config.min_profit_usd,
+        }
+    }
+
+    /// Size every opportunity in place. Returns one verdict per opportunity
+    /// (same order); the caller drops `Unprofitable` ones. A failed ladder
+    /// batch leaves every V3 route `Unchanged`.
+    pub async fn size_all<M: Middleware + 'static>(
+        &self,
+        opportunities: &mut [ArbitrageOpportunity],
+        quoter: &MulticallQuoter<M>,
+    ) -> Vec<SizingVerdict> {
+        let mut verdicts = vec![SizingVerdict::Unchanged; opportunities.len()];
+
+        // V2↔V2: no RPC needed
+        for (opp, verdict) in opportunities.iter_mut().zip(verdicts.iter_mut()) {
+            if opp.buy_dex.is_v2() && opp.sell_dex.is_v2() {
+                if let Some((size, profit)) = v2_optimal_size(opp) {
+                    *verdict = self.apply(opp, SizingMethod::V2ClosedForm, size, profit);
+                }
+            }
+        }
+
+        // V3↔V3: one ladder batch for all routes
+        let v3_idx: Vec<usize> = opportunities.iter().enumerate()
+            .filter(|(_, o)| o.buy_dex.is_v3() && o.sell_dex.is_v3() && !o.trade_size.is_zero())
+            .map(|(i, _)| i)
+            .collect();
+        if v3_idx.is_empty() {
+            return verdicts;
+        }
+        let ladders: Vec<Vec<U256>> = v3_idx.iter()
+            .map(|&i| ladder(opportunities[i].trade_size.raw(), LADDER_STEPS))
+            .collect();
+        let mut sub_calls = Vec::new();
+        for (&i, sizes) in v3_idx.iter().zip(&ladders) {
+            for &size in sizes {
+                match ladder_calls(quoter, &opportunities[i], size) {
+                    Ok(calls) => sub_calls.extend(calls),
+                    Err(e) => {
+                        warn!("Sizing ladder encode failed for {}: {} — sizes unchanged", opportunities[i].pair.symbol, e);
+                        return verdicts;
+                    }
+                }
+            }
+        }
+        let results = match quoter.aggregate3_at(&sub_calls, None).await {
+            Ok(r) => r,
+            Err(e) => {
+                warn!("Sizing ladder batch failed: {} — {} V3 route(s) keep their size", e, v3_idx.len());
+                return verdicts;
+            }
+        };
+        debug!("Sizing ladder: {} V3 route(s) → {} sub-calls", v3_idx.len(), sub_calls.len());
+
+        let mut results = results.chunks(2);
+        for (&i, sizes) in v3_idx.iter().zip(&ladders) {
+            let opp = &mut opportunities[i];
+            let candidates: Vec<(U256, i128)> = sizes.iter()
+                .filter_map(|&size| {
+                    let pair = results.next()?;
+                    let buy_out = MulticallQuoter::<M>::decode_quoter_result_for_dex(opp.buy_dex, pair[0].0, &pair[0].1).ok()?;
+                    let sell_out = MulticallQuoter::<M>::decode_quoter_result_for_dex(opp.sell_dex, pair[1].0, &pair[1].1).ok()?;
+                    let estimated = MulticallQuoter::<M>::estimate_buy_output(&sized(opp, size));
+                    Some((size, ladder_profit(size, buy_out, estimated, sell_out)?))
+                })
+                .collect();
+            verdicts[i] = match best_candidate(&candidates) {
+                Some((size, profit)) => self.apply(opp, SizingMethod::QuoterLadder, size, profit),
+                // Every rung failed to quote: nothing at any size fills
+                None => SizingVerdict::Unprofitable { method: SizingMethod::QuoterLadder, best_profit_raw: 0 },
+            };
+        }
+        verdicts
+    }
+
+    /// Write the chosen size back, or reject it below the minimum profit
+    fn apply(&self, opp: &mut ArbitrageOpportunity, method: SizingMethod, size: U256, profit_raw: i128) -> SizingVerdict {
+        let profit_usd = opp.quote_to_usd(opp.quote_amount(U256::from(profit_raw.max(0) as u128)));
+        if profit_raw <= 0 || profit_usd < self.min_profit_usd {
+            return SizingVerdict::Unprofitable { method, best_profit_raw: profit_raw };
+        }
+        let from = opp.trade_size;
+        let ratio = size.low_u128() as f64 / from.raw().low_u128().max(1) as f64;
+        opp.trade_size = from.with_raw(size);
+        opp.trade_size_usd *= ratio;
+        opp.estimated_profit = profit_usd - self.gas_cost_usd;
+        SizingVerdict::Sized { method, from, quoted_profit_raw: profit_raw }
+    }
+}
+
+/// Candidate sizes: max, max/2, … (max / 2^(steps−1)), zero rungs dropped
+pub fn ladder(max: U256, steps: u32) -> Vec<U256> {
+    (0..steps).map(|k| max >> k).filter(|s| !s.is_zero()).collect()
+}
+
+/// Highest quoted profit; ties go to the smaller size (less capital at risk)
+pub fn best_candidate(candidates: &[(U256, i128)]) -> Option<(U256, i128)> {
+    candidates.iter().copied().fold(None, |best, c| match best {
+        Some(b) if b.1 > c.1 || (b.1 == c.1 && b.0 <= c.0) => Some(b),
+        _ => Some(c),
+    })
+}
+
+/// Round-trip profit of one ladder rung. The sell quote is for the midmarket
+/// buy estimate; scale it by quoted / estimated buy output.
+fn ladder_profit(size: U256, buy_out: U256, estimated_buy_out: U256, sell_out: U256) -> Option<i128> {
+    if estimated_buy_out.is_zero() {
+        return None;
+    }
+    let realized = sell_out.saturating_mul(buy_out) / estimated_buy_out;
+    let u128_max = U256::from(u128::MAX);
+    if realized > u128_max || size > u128_max {
+        return None;
+    }
+    Some(realized.as_u128() as i128 - size.as_u128() as i128)
+}
+
+fn sized(opp: &ArbitrageOpportunity, size: U256) -> ArbitrageOpportunity {
+    let mut candidate = opp.clone();
+    candidate.trade_size = opp.trade_size.with_raw(size);
+    candidate
+}
+
+/// Buy + sell Quoter sub-calls for one ladder rung
+fn ladder_calls<M: Middleware + 'static>(
+    quoter: &MulticallQuoter<M>,
+    opp: &ArbitrageOpportunity,
+    size: U256,
+) -> Result<[(Address, Vec<u8>); 2]> {
+    let buy_fee = opp.buy_dex.v3_fee_tier().ok_or_else(|| anyhow!("Buy DEX {:?} is not V3", opp.buy_dex))?;
+    let sell_fee = opp.sell_dex.v3_fee_tier().ok_or_else(|| anyhow!("Sell DEX {:?} is not V3", opp.sell_dex))?;
+    let (quote, base) = if opp.quote_token_is_token0 {
+        (opp.pair.token0, opp.pair.token1)
+    } else {
+        (opp.pair.token1, opp.pair.token0)
+    };
+    let estimated_buy_out = MulticallQuoter::<M>::estimate_buy_output(&sized(opp, size));
+    Ok([
+        (quoter.quoter_for_dex(opp.buy_dex), quoter.encode_quoter_for_dex(opp.buy_dex, quote, base, buy_fee, size)),
+        (quoter.quoter_for_dex(opp.sell_dex), quoter.encode_quoter_for_dex(opp.sell_dex, base, quote, sell_fee, estimated_buy_out)),
+    ])
+}
+
+/// (quote reserve, base reserve, fee factor γ) of a V2 snapshot
+fn v2_leg(snapshot: &PoolStateSnapshot, quote_is_token0: bool) -> Option<(f64, f64, f64)> {
+    let PoolStateKind::V2 { reserve0, reserve1 } = &snapshot.state else {
+        return None;
+    };
+    let r0 = reserve0.to_string().parse::<f64>().ok()?;
+    let r1 = reserve1.to_string().parse::<f64>().ok()?;
+    let gamma = 1.0 - snapshot.fee as f64 / 1_000_000.0;
+    Some(if quote_is_token0 { (r0, r1, gamma) } else { (r1, r0, gamma) })
+}
+
+/// Closed-form optimum for a V2↔V2 route, capped at the current trade_size.
+/// Returns (size raw, profit raw); None without V2 snapshots for both legs.
+pub fn v2_optimal_size(opp: &ArbitrageOpportunity) -> Option<(U256, i128)> {
+    let snapshots = opp.pool_snapshots.as_ref()?;
+    let (a1, b1, g1) = v2_leg(&snapshots.buy, opp.quote_token_is_token0)?;
+    let (a2, b2, g2) = v2_leg(&snapshots.sell, opp.quote_token_is_token0)?;
+    let x = v2_optimal_input(a1, b1, a2, b2, g1, g2)
+        .min(opp.trade_size.raw().low_u128() as f64)
+        .floor();
+    let profit = v2_round_trip(x, a1, b1, a2, b2, g1, g2) - x;
+    Some((U256::from(x as u128), profit.floor() as i128))
+}
+
+/// x* maximizing z(x) − x for quote → base on pool 1, base → quote on pool 2
+/// (0 when no positive size is profitable)
+pub fn v2_optimal_input(a1: f64, b1: f64, a2: f64, b2: f64, g1: f64, g2: f64) -> f64 {
+    let a = g1 * g2 * b1 * a2;
+    let b = a1 * b2;
+    let c = g1 * (b2 + g2 * b1);
+    if a <= b || c <= 0.0 {
+        return 0.0;
+    }
+    ((a * b).sqrt() - b) / c
+}
+
+/// Quote out of the round trip for x quote in (constant-product, both legs)
+pub fn v2_round_trip(x: f64, a1: f64, b1: f64, a2: f64, b2: f64, g1: f64, g2: f64) -> f64 {
+    let y = g1 * x * b1 / (a1 + g1 * x);
+    g2 * y * a2 / (b2 + g2 * y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::types::{DexType, LegSnapshots, PoolState, TradingPair};
+
+    const G: f64 = 0.997;
+
+    /// Brute-force argmax of z(x) − x over [0, max] at `step` resolution
+    fn brute_force(a1: f64, b1: f64, a2: f64, b2: f64, max: f64, step: f64) -> (f64, f64) {
+        let mut best = (0.0, 0.0);
+        let mut x = 0.0;
+        while x <= max {
+            let profit = v2_round_trip(x, a1, b1, a2, b2, G, G) - x;
+            if profit > best.1 {
+                best = (x, profit);
+            }
+            x += step;
+        }
+        best
+    }
+
+    #[test]
+    fn test_v2_closed_form_matches_brute_force() {
+        // (quote reserve, base reserve) of each pool, 6-decimal quote / 18-decimal base
+        let cases = [
+            // 2% price gap, deep pools
+            ((1e12, 4e20), (1.02e12, 4e20)),
+            // 3% gap, thin buy pool
+            ((5e10, 2e19), (1.03e12, 4e20)),
+            // 0.8% gap, sell pool shallow
+            ((2e12, 8e20), (2.5e10, 5e18 / 1.008)),
+        ];
+        for ((a1, b1), (a2, b2)) in cases {
+            let x = v2_optimal_input(a1, b1, a2, b2, G, G);
+            let (bx, bp) = brute_force(a1, b1, a2, b2, 4.0 * x.max(1.0), x.max(1.0) / 2_000.0);
+            let profit = v2_round_trip(x, a1, b1, a2, b2, G, G) - x;
+            assert!(x > 0.0, "case should be profitable");
+            assert!((x - bx).abs() <= x / 1_000.0, "x*={} brute={}", x, bx);
+            assert!(profit >= bp - 1e-9 * bp, "closed form {} below brute force {}", profit, bp);
+        }
+
+        // Gap smaller than two 0.3% fees: no profitable size
+        assert_eq!(v2_optimal_input(1e12, 4e20, 1.004e12, 4e20, G, G), 0.0);
+    }
+
+    fn v2_snapshot(dex: DexType, reserve0: u128, reserve1: u128) -> PoolStateSnapshot {
+        let pool = PoolState {
+            address: Address::from_low_u64_be(7),
+            dex,
+            pair: TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            reserve0: U256::from(reserve0),
+            reserve1: U256::from(reserve1),
+            last_updated: 1,
+            token0_decimals: 6,
+            token1_decimals: 18,
+        };
+        PoolStateSnapshot::from_v2(&pool, 0)
+    }
+
+    /// USDC (6) = token0, WETH (18) = token1; $5k max size
+    fn v2_opp(buy: (u128, u128), sell: (u128, u128)) -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            DexType::QuickSwapV2,
+            DexType::SushiSwapV2,
+            0.0004,
+            0.0004,
+            U256::from(5_000u64) * U256::exp10(6),
+        );
+        opp.set_quote_layout(6, 18, true);
+        opp.trade_size_usd = 5_000.0;
+        opp.pool_snapshots = Some(LegSnapshots {
+            buy: v2_snapshot(DexType::QuickSwapV2, buy.0, buy.1),
+            sell: v2_snapshot(DexType::SushiSwapV2, sell.0, sell.1),
+        });
+        opp
+    }
+
+    #[test]
+    fn test_v2_thin_pool_sized_below_max() {
+        let mut config = create_test_config();
+        config.min_profit_usd = 0.10;
+        config.estimated_gas_cost_usd = 0.05;
+        let sizer = OptimalSizer::new(&config);
+
+        // $20k buy pool at a 2% discount: optimum is far below the $5k max
+        let mut opp = v2_opp((20_000_000_000, 8_160_000_000_000_000_000), (1_000_000_000_000, 400_000_000_000_000_000_000));
+        let (size, profit) = v2_optimal_size(&opp).unwrap();
+        assert!(size < opp.trade_size.raw() && !size.is_zero());
+        match sizer.apply(&mut opp, SizingMethod::V2ClosedForm, size, profit) {
+            SizingVerdict::Sized { from, .. } => assert_eq!(from.raw(), U256::from(5_000_000_000u64)),
+            other => panic!("expected Sized, got {:?}", other),
+        }
+        assert_eq!(opp.trade_size.raw(), size);
+        assert!((opp.trade_size_usd - size.as_u128() as f64 / 1e6).abs() < 1e-6);
+        assert!((opp.estimated_profit - (profit as f64 / 1e6 - 0.05)).abs() < 1e-6);
+
+        // Deep pools with a wide gap: capped at the incoming size
+        let opp = v2_opp((10_000_000_000_000, 4_400_000_000_000_000_000_000), (10_000_000_000_000, 4_000_000_000_000_000_000_000));
+        assert_eq!(v2_optimal_size(&opp).unwrap().0, opp.trade_size.raw());
+
+        // No gap: rejected below the minimum profit
+        let mut opp = v2_opp((1_000_000_000_000, 400_000_000_000_000_000_000), (1_000_000_000_000, 400_000_000_000_000_000_000));
+        let (size, profit) = v2_optimal_size(&opp).unwrap();
+        assert!(matches!(
+            sizer.apply(&mut opp, SizingMethod::V2ClosedForm, size, profit),
+            SizingVerdict::Unprofitable { .. }
+        ));
+        assert_eq!(opp.trade_size.raw(), U256::from(5_000_000_000u64));
+    }
+
+    #[test]
+    fn test_ladder_and_candidate_selection() {
+        let max = U256::from(5_000_000_000u64);
+        assert_eq!(
+            ladder(max, LADDER_STEPS),
+            vec![max, max / 2, max / 4, max / 8]
+        );
+        assert_eq!(ladder(U256::from(2u64), LADDER_STEPS), vec![U256::from(2u64), U256::one()]);
+
+        // Thin pool: full size quotes a loss, a quarter is best
+        let candidates = [(max, -3_000_000), (max / 2, 400_000), (max / 4, 900_000), (max / 8, 600_000)];
+        assert_eq!(best_candidate(&candidates), Some((max / 4, 900_000)));
+        // Ties go to the smaller size
+        assert_eq!(best_candidate(&[(max, 5), (max / 2, 5)]), Some((max / 2, 5)));
+        assert_eq!(best_candidate(&[]), None);
+    }
+
+    #[test]
+    fn test_ladder_profit_scales_sell_quote_by_buy_impact() {
+        // Buy quoted 2% below midmarket → sell output scaled down 2%
+        let size = U256::from(1_000_000_000u64);
+        let profit = ladder_profit(size, U256::from(98u64), U256::from(100u64), U256::from(1_030_000_000u64));
+        assert_eq!(profit, Some(9_400_000));
+        assert_eq!(ladder_profit(size, U256::one(), U256::zero(), U256::one()), None);
+    }
+}
@@ -0,0 +1,356 @@
+//! Realized Slippage Feedback — per-route min_out allowances from our own fills
+//!
+//! MAX_SLIPPAGE_PERCENT is one global number, but realized slippage differs a
+//! lot by route and size: too loose and bad fills are absorbed, too tight and
+//! legitimate fills revert. This module measures every landed trade against
+//! the multicall pre-screen quote (ArbitrageOpportunity::quoted_legs) and keeps
+//! a rolling window of realized slippage per route leg.
+//!
+//! Realized slippage (%) = (expected - realized) / expected × 100, negative when
+//! the fill beat the quote:
+//!   Buy  — legacy two-tx path: base received vs quoted buy_out
+//!   Sell — legacy: quote received vs quoted sell_out scaled to the base actually
+//!          sold; atomic: ArbExecuted amountOut vs quoted sell_out (the buy leg
+//!          is not observable on-chain, so its slippage is folded in)
+//!
+//! With SLIPPAGE_FEEDBACK_ENABLED, calculate_min_out uses a route leg's
+//! allowance = p{PERCENTILE}(window, floored at 0) + BUFFER_PERCENT once it has
+//! MIN_SAMPLES trades, capped by MAX_SLIPPAGE_PERCENT. Routes without enough
+//! history keep the global value. The atomic path has no per-leg min_out:
+//! ArbExecutor enforces a fixed minProfit floor, which is not a slippage margin,
+//! so there the stats are collected but not applied.
+//!
+//! Stats persist to data/{chain}/slippage_stats.json after every recorded trade
+//! and are reported per route in the periodic status line.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - allowance capped by the opportunity's whitelist max_slippage_percent
+
+use crate::types::{ArbitrageOpportunity, BotConfig};
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::path::PathBuf;
+use tracing::{debug, info, warn};
+
+/// Recent trades kept per route leg
+pub const SLIPPAGE_WINDOW: usize = 200;
+
+/// Which leg a realized slippage sample belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlippageLeg {
+    Buy,
+    Sell,
+}
+
+impl fmt::Display for SlippageLeg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlippageLeg::Buy => write!(f, "buy"),
+            SlippageLeg::Sell => write!(f, "sell"),
+        }
+    }
+}
+
+/// Allowance policy (BotConfig slippage_feedback_* + max_slippage_percent)
+#[derive(Debug, Clone)]
+pub struct SlippageConfig {
+    pub enabled: bool,
+    pub min_samples: usize,
+    pub percentile: f64,
+    pub buffer_percent: f64,
+    /// Global MAX_SLIPPAGE_PERCENT — default and upper bound
+    pub max_percent: f64,
+}
+
+impl SlippageConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            enabled: config.slippage_feedback_enabled,
+            min_samples: config.slippage_feedback_min_samples.max(1),
+            percentile: config.slippage_feedback_percentile.clamp(0.0, 100.0),
+            buffer_percent: config.slippage_feedback_buffer_percent.max(0.0),
+            max_percent: config.max_slippage_percent,
+        }
+    }
+}
+
+/// Rolling samples of one route (percent, newest last)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteSlippage {
+    pub buy: VecDeque<f64>,
+    pub sell: VecDeque<f64>,
+}
+
+impl RouteSlippage {
+    fn samples(&self, leg: SlippageLeg) -> &VecDeque<f64> {
+        match leg {
+            SlippageLeg::Buy => &self.buy,
+            SlippageLeg::Sell => &self.sell,
+        }
+    }
+
+    fn samples_mut(&mut self, leg: SlippageLeg) -> &mut VecDeque<f64> {
+        match leg {
+            SlippageLeg::Buy => &mut self.buy,
+            SlippageLeg::Sell => &mut self.sell,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SlippageFile {
+    saved_at: String,
+    routes: BTreeMap<String, RouteSlippage>,
+}
+
+/// Route key: "PAIR BuyDex→SellDex" (same route identity as RouteCooldown)
+pub fn route_key(opp: &ArbitrageOpportunity) -> String {
+    format!("{} {:?}→{:?}", opp.pair.symbol, opp.buy_dex, opp.sell_dex)
+}
+
+/// Realized slippage in percent of `expected` (None if nothing was expected)
+pub fn realized_slippage_percent(expected: U256, realized: U256) -> Option<f64> {
+    if expected.is_zero() {
+        return None;
+    }
+    let expected = expected.as_u128() as f64;
+    let realized = realized.as_u128() as f64;
+    Some((expected - realized) / expected * 100.0)
+}
+
+/// Nearest-rank percentile (p in 0..=100) of unsorted samples
+fn percentile(samples: &VecDeque<f64>, p: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<f64> = samples.iter().copied().collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Per-route realized slippage statistics and min_out allowances
+pub struct SlippageTracker {
+    config: SlippageConfig,
+    routes: BTreeMap<String, RouteSlippage>,
+    path: Option<PathBuf>,
+}
+
+impl SlippageTracker {
+    pub fn new(config: SlippageConfig) -> Self {
+        Self { config, routes: BTreeMap::new(), path: None }
+    }
+
+    /// Load and save stats in `{data_dir}/slippage_stats.json`
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Slippage: failed to create {}: {}", data_dir, e);
+        }
+        let path = dir.join("slippage_stats.json");
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<SlippageFile>(&content) {
+                Ok(file) => {
+                    info!("Slippage: {} route(s) restored (saved {})", file.routes.len(), file.saved_at);
+                    self.routes = file.routes;
+                }
+                Err(e) => warn!("Slippage: ignoring unreadable {}: {}", path.display(), e),
+            }
+        }
+        self.path = Some(path);
+        self
+    }
+
+    pub fn route(&self, opp: &ArbitrageOpportunity) -> Option<&RouteSlippage> {
+        self.routes.get(&route_key(opp))
+    }
+
+    /// Record one leg of a landed trade. Returns the sample (percent) if recorded.
+    pub fn record(
+        &mut self,
+        opp: &ArbitrageOpportunity,
+        leg: SlippageLeg,
+        expected: U256,
+        realized: U256,
+    ) -> Option<f64> {
+        let slippage = realized_slippage_percent(expected, realized)?;
+        let samples = self.routes.entry(route_key(opp)).or_default().samples_mut(leg);
+        samples.push_back(slippage);
+        while samples.len() > SLIPPAGE_WINDOW {
+            samples.pop_front();
+        }
+        debug!("Slippage {} {} leg: {:.4}% (expected {} got {})", route_key(opp), leg, slippage, expected, realized);
+        self.save();
+        Some(slippage)
+    }
+
+    /// min_out slippage allowance (percent) for one route leg
+    /// (never above the route's whitelist max_slippage_percent)
+    pub fn allowance_percent(&self, opp: &ArbitrageOpportunity, leg: SlippageLeg) -> f64 {
+        let allowance = match self.routes.get(&route_key(opp)) {
+            Some(route) => self.allowance_from(route.samples(leg)),
+            None => self.config.max_percent,
+        };
+        opp.max_slippage_percent.map_or(allowance, |cap| allowance.min(cap))
+    }
+
+    fn allowance_from(&self, samples: &VecDeque<f64>) -> f64 {
+        if !self.config.enabled || samples.len() < self.config.min_samples {
+            return self.config.max_percent;
+        }
+        let observed = percentile(samples, self.config.percentile).unwrap_or(0.0).max(0.0);
+        (observed + self.config.buffer_percent).min(self.config.max_percent)
+    }
+
+    /// One line per route: samples, p50/p{pct} per leg and the allowance in use
+    pub fn summary_lines(&self) -> Vec<String> {
+        let pct = self.config.percentile;
+        self.routes
+            .iter()
+            .map(|(key, route)| {
+                let legs: Vec<String> = [SlippageLeg::Buy, SlippageLeg::Sell]
+                    .iter()
+                    .filter(|&&leg| !route.samples(leg).is_empty())
+                    .map(|&leg| {
+                        let samples = route.samples(leg);
+                        format!(
+                            "{} n={} p50 {:.3}% p{:.0} {:.3}% → allow {:.3}%",
+                            leg,
+                            samples.len(),
+                            percentile(samples, 50.0).unwrap_or(0.0),
+                            pct,
+                            percentile(samples, pct).unwrap_or(0.0),
+                            self.allowance_from(samples)
+                        )
+                    })
+                    .collect();
+                format!("{} | {}", key, legs.join(" | "))
+            })
+            .collect()
+    }
+
+    fn save(&self) {
+        let path = match self.path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let file = SlippageFile {
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            routes: self.routes.clone(),
+        };
+        match serde_json::to_string_pretty(&file) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    warn!("Slippage: failed to write {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => warn!("Slippage: failed to serialize stats: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::executor::min_out_with_slippage;
+    use crate::types::{DexType, TradingPair};
+    use ethers::types::Address;
+
+    fn config(enabled: bool) -> SlippageConfig {
+        SlippageConfig { enabled, min_samples: 10, percentile: 95.0, buffer_percent: 0.1, max_percent: 0.5 }
+    }
+
+    fn opp(buy: DexType, sell: DexType) -> ArbitrageOpportunity {
+        ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            buy,
+            sell,
+            0.000_400,
+            0.000_396,
+            U256::from(500_000_000u64),
+        )
+    }
+
+    /// Record `bps` (basis points of slippage) against a 1_000_000 quote
+    fn record_bps(tracker: &mut SlippageTracker, o: &ArbitrageOpportunity, leg: SlippageLeg, bps: &[i64]) {
+        for &b in bps {
+            let realized = (1_000_000i64 - b * 100) as u64;
+            tracker.record(o, leg, U256::from(1_000_000u64), U256::from(realized));
+        }
+    }
+
+    #[test]
+    fn test_allowance_from_route_history() {
+        let mut tracker = SlippageTracker::new(config(true));
+        let tight = opp(DexType::UniswapV3_005, DexType::UniswapV3_030);
+        let loose = opp(DexType::UniswapV3_030, DexType::UniswapV3_005);
+
+        // Below min_samples: global MAX_SLIPPAGE_PERCENT
+        record_bps(&mut tracker, &tight, SlippageLeg::Buy, &[1; 9]);
+        assert_eq!(tracker.allowance_percent(&tight, SlippageLeg::Buy), 0.5);
+
+        // 20 fills: 18 at 1bp, 2 at 4bp → p95 = 0.04% + 0.1% buffer
+        record_bps(&mut tracker, &tight, SlippageLeg::Buy, &[1, 1, 1, 1, 1, 1, 1, 1, 1, 4, 4]);
+        let allowance = tracker.allowance_percent(&tight, SlippageLeg::Buy);
+        assert!((allowance - 0.14).abs() < 1e-9, "{}", allowance);
+        // Other leg / other route untouched
+        assert_eq!(tracker.allowance_percent(&tight, SlippageLeg::Sell), 0.5);
+        assert_eq!(tracker.allowance_percent(&loose, SlippageLeg::Buy), 0.5);
+
+        // Wild route: p95 + buffer above the global cap → capped
+        record_bps(&mut tracker, &loose, SlippageLeg::Buy, &[30, 45, 60, 80, 20, 35, 70, 55, 40, 65]);
+        assert_eq!(tracker.allowance_percent(&loose, SlippageLeg::Buy), 0.5);
+
+        // Fills better than quoted floor at zero: allowance = buffer only
+        record_bps(&mut tracker, &tight, SlippageLeg::Sell, &[-3; 10]);
+        assert!((tracker.allowance_percent(&tight, SlippageLeg::Sell) - 0.1).abs() < 1e-9);
+
+        // The allowance is what min_out is computed with: tighter than global
+        let route_min = min_out_with_slippage(U256::from(500_000_000u64), 0.0004, 6, 18, allowance);
+        let global_min = min_out_with_slippage(U256::from(500_000_000u64), 0.0004, 6, 18, 0.5);
+        assert!(route_min > global_min);
+
+        // Disabled: stats kept, allowance always global
+        let mut off = SlippageTracker::new(config(false));
+        record_bps(&mut off, &tight, SlippageLeg::Buy, &[1; 20]);
+        assert_eq!(off.allowance_percent(&tight, SlippageLeg::Buy), 0.5);
+        assert_eq!(off.route(&tight).unwrap().buy.len(), 20);
+        assert!(off.summary_lines()[0].starts_with("WETH/USDC UniswapV3_005→UniswapV3_030 | buy n=20"));
+    }
+
+    #[test]
+    fn test_window_bound_and_realized_math() {
+        assert_eq!(realized_slippage_percent(U256::zero(), U256::from(5u64)), None);
+        let s = realized_slippage_percent(U256::from(1_000_000u64), U256::from(997_500u64)).unwrap();
+        assert!((s - 0.25).abs() < 1e-9);
+        let improved = realized_slippage_percent(U256::from(1_000_000u64), U256::from(1_001_000u64)).unwrap();
+        assert!((improved + 0.1).abs() < 1e-9);
+
+        // Old history ages out: after a regime change only recent fills count
+        let mut tracker = SlippageTracker::new(config(true));
+        let o = opp(DexType::UniswapV3_005, DexType::UniswapV3_030);
+        record_bps(&mut tracker, &o, SlippageLeg::Sell, &[40; SLIPPAGE_WINDOW]);
+        record_bps(&mut tracker, &o, SlippageLeg::Sell, &[2; SLIPPAGE_WINDOW]);
+        assert_eq!(tracker.route(&o).unwrap().sell.len(), SLIPPAGE_WINDOW);
+        assert!((tracker.allowance_percent(&o, SlippageLeg::Sell) - 0.12).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stats_persist_across_restart() {
+        let dir = std::env::temp_dir().join(format!("dexarb_slippage_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let o = opp(DexType::UniswapV3_005, DexType::UniswapV3_030);
+
+        let mut tracker = SlippageTracker::new(config(true)).with_persistence(dir.to_str().unwrap());
+        record_bps(&mut tracker, &o, SlippageLeg::Buy, &[2; 12]);
+        let before = tracker.allowance_percent(&o, SlippageLeg::Buy);
+
+        let restarted = SlippageTracker::new(config(true)).with_persistence(dir.to_str().unwrap());
+        assert_eq!(restarted.route(&o).unwrap().buy.len(), 12);
+        assert_eq!(restarted.allowance_percent(&o, SlippageLeg::Buy), before);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,162 @@
+//! Split Routing — one buy leg, the sell side split across two pools
+//!
+//! Purpose:
+//!     Cross-fee-tier spreads (e.g. UniV3 0.05% vs 0.30% on one pair) often
+//!     sit on a thin tier whose price collapses after a small size. Selling
+//!     part of the base on the next-best pool keeps each sell leg on the flat
+//!     part of its curve, so a larger total size can still clear the minimum
+//!     profit. Detection is behind SPLIT_ROUTING; execution goes through
+//!     ArbExecutor.executeArbSplit (TradeExecutor::execute_atomic_split).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Design:
+//!     - Share = bps of the base sold on the second pool (B); the first pool
+//!       (A, the best single sell) takes the rest. The contract splits the
+//!       base it actually received with the same bps.
+//!     - Each leg's output is the adapter's depth model (V3: in-range L held
+//!       constant, V2: constant product). Both are concave in the input, so
+//!       the combined output has one maximum; the search is a coarse grid
+//!       (every SPLIT_GRID_BPS) refined bps by bps around the best point,
+//!       which also survives a leg that can't fill part of the range (None).
+//!     - A split is only worth building when the best single sell pool loses
+//!       more than MAX_SLIPPAGE_PERCENT to price impact at the full size.
+
+use crate::pool::PoolAdapter;
+use crate::types::raw_to_human;
+use ethers::types::{Address, U256};
+
+/// Share scale: 10_000 bps = all of the base on the second pool
+pub const SPLIT_SCALE_BPS: u16 = 10_000;
+
+/// Coarse search step (bps)
+const SPLIT_GRID_BPS: u16 = 100;
+
+/// Price impact probe: 1/PROBE_DIVISOR of the amount stands in for the spot rate
+const PROBE_DIVISOR: u64 = 1_000;
+
+/// Quote out of selling `base_in` of `base`: `share_bps` on `b`, the rest on
+/// `a`. None when a leg with a non-zero share can't fill it.
+pub fn split_out(a: &dyn PoolAdapter, b: &dyn PoolAdapter, base_in: U256, base: Address, share_bps: u16) -> Option<U256> {
+    let to_b = base_in * U256::from(share_bps.min(SPLIT_SCALE_BPS)) / U256::from(SPLIT_SCALE_BPS);
+    let to_a = base_in - to_b;
+    let leg = |pool: &dyn PoolAdapter, amount: U256| {
+        if amount.is_zero() {
+            Some(U256::zero())
+        } else {
+            pool.amount_out(amount, base)
+        }
+    };
+    leg(a, to_a)?.checked_add(leg(b, to_b)?)
+}
+
+/// Share (bps on `b`) maximizing the quote out of selling `base_in`, with
+/// that output. 0 / SPLIT_SCALE_BPS = one pool alone is best. None when no
+/// share can be filled.
+pub fn optimal_split(a: &dyn PoolAdapter, b: &dyn PoolAdapter, base_in: U256, base: Address) -> Option<(u16, U256)> {
+    let best_of = |shares: &mut dyn Iterator<Item = u16>| {
+        shares
+            .filter_map(|share| split_out(a, b, base_in, base, share).map(|out| (share, out)))
+            // Ties keep the smaller share (less on the second pool)
+            .fold(None, |best: Option<(u16, U256)>, (share, out)| match best {
+                Some((_, best_out)) if best_out >= out => best,
+                _ => Some((share, out)),
+            })
+    };
+    let (coarse, _) = best_of(&mut (0..=SPLIT_SCALE_BPS).step_by(SPLIT_GRID_BPS as usize))?;
+    let from = coarse.saturating_sub(SPLIT_GRID_BPS);
+    let to = (coarse + SPLIT_GRID_BPS).min(SPLIT_SCALE_BPS);
+    best_of(&mut (from..=to))
+}
+
+/// Fraction of the output lost to price impact when selling `amount_in` of
+/// `token_in` on `pool`, against the rate of a small probe (fee excluded,
+/// since the probe pays it too). 1.0 = the pool can't fill the amount.
+pub fn price_impact(pool: &dyn PoolAdapter, amount_in: U256, token_in: Address) -> Option<f64> {
+    let probe = amount_in / U256::from(PROBE_DIVISOR);
+    if probe.is_zero() {
+        return Some(0.0);
+    }
+    let probe_out = raw_to_human(pool.amount_out(probe, token_in)?, 0);
+    let Some(out) = pool.amount_out(amount_in, token_in) else {
+        return Some(1.0);
+    };
+    let ideal = probe_out * raw_to_human(amount_in, 0) / raw_to_human(probe, 0);
+    Some((1.0 - raw_to_human(out, 0) / ideal).max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair, V3PoolState};
+
+    const USDC: u64 = 0x1;
+    const WETH: u64 = 0x2;
+
+    /// USDC (token0, 6 dec) / WETH (token1, 18 dec) pool at `usd_per_weth`
+    fn pool(addr: u64, dex: DexType, fee: u32, usd_per_weth: f64, liquidity: u128) -> V3PoolState {
+        let pair = TradingPair::new(Address::from_low_u64_be(USDC), Address::from_low_u64_be(WETH), "WETH/USDC".to_string());
+        // price = WETH per USDC in raw units
+        let price_raw = 1e12 / usd_per_weth;
+        let tick = (price_raw.ln() / 1.0001f64.ln()).round() as i32;
+        V3PoolState {
+            address: Address::from_low_u64_be(addr),
+            dex,
+            pair,
+            sqrt_price_x96: crate::mempool::ticks::sqrt_ratio_at_tick(tick).unwrap(),
+            tick,
+            fee,
+            liquidity,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 100,
+        }
+    }
+
+    fn weth(amount: f64) -> U256 {
+        U256::from((amount * 1e18) as u128)
+    }
+
+    #[test]
+    fn test_optimal_split_maximizes_output() {
+        let base = Address::from_low_u64_be(WETH);
+        // Same price and fee: the optimum splits in proportion to depth (1:3)
+        let thin = pool(10, DexType::UniswapV3_005, 500, 2000.0, 10u128.pow(15));
+        let deep = pool(11, DexType::SushiV3_005, 500, 2000.0, 3 * 10u128.pow(15));
+        let base_in = weth(5.0);
+        let (share, out) = optimal_split(&thin, &deep, base_in, base).unwrap();
+        assert!((share as i32 - 7_500).abs() <= 10, "share {}", share);
+
+        // No other share does better, and both single-pool sells are worse
+        for s in (0..=SPLIT_SCALE_BPS).step_by(50) {
+            assert!(split_out(&thin, &deep, base_in, base, s).unwrap() <= out, "share {} beats {}", s, share);
+        }
+        assert!(out > thin.amount_out(base_in, base).unwrap());
+        assert!(out > deep.amount_out(base_in, base).unwrap());
+
+        // Thin 0.05% tier priced higher vs deep 0.30% tier: a small size goes
+        // entirely to the thin tier, a larger one spills into the deep tier
+        let thin_rich = pool(12, DexType::UniswapV3_005, 500, 2_010.0, 10u128.pow(15));
+        let deep_cheap = pool(13, DexType::UniswapV3_030, 3000, 2_000.0, 10u128.pow(17));
+        let (small, _) = optimal_split(&thin_rich, &deep_cheap, weth(0.05), base).unwrap();
+        let (large, large_out) = optimal_split(&thin_rich, &deep_cheap, weth(20.0), base).unwrap();
+        assert_eq!(small, 0);
+        assert!(large > 5_000 && large < SPLIT_SCALE_BPS, "share {}", large);
+        for s in [large - 1, large + 1] {
+            assert!(split_out(&thin_rich, &deep_cheap, weth(20.0), base, s).unwrap() <= large_out);
+        }
+    }
+
+    #[test]
+    fn test_price_impact_grows_with_size_and_depth() {
+        let base = Address::from_low_u64_be(WETH);
+        let thin = pool(10, DexType::UniswapV3_005, 500, 2000.0, 10u128.pow(15));
+        let deep = pool(11, DexType::UniswapV3_030, 3000, 2000.0, 10u128.pow(18));
+        let small = price_impact(&thin, weth(0.01), base).unwrap();
+        let large = price_impact(&thin, weth(5.0), base).unwrap();
+        assert!(small < 0.001, "{}", small);
+        assert!(large > 0.01 && large < 1.0, "{}", large);
+        assert!(price_impact(&deep, weth(5.0), base).unwrap() < large / 100.0);
+    }
+}
@@ -0,0 +1,478 @@
+//! Route P&L Statistics — per-pair and per-route trade results
+//!
+//! Purpose:
+//!     The tax log records every completed trade, but not which routes keep
+//!     reverting or burn gas for nothing. `StatsTracker` folds every execute()
+//!     result into per-route counters keyed by (pair_symbol, buy_dex, sell_dex):
+//!     attempts, successes, on-chain reverts, pre-trade rejections, gross /
+//!     gas / net USD and average execution time. The block loop logs the table
+//!     every STATS_REPORT_INTERVAL iterations and rewrites data/{chain}/stats.json
+//!     (a rolling snapshot, also written at shutdown).
+//!
+//! Counters start at zero each run; stats.json is an output, never read back.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - RouteStats::since (daily deltas for the live alert summary)
+//! Modified: 2026-10-16 - Average block arrival → tx submitted per route (latency.rs)
+//! Modified: 2026-10-16 - Mempool bundle hit rate per route (included behind the trigger / bundles sent)
+
+use super::outcome::TradeOutcome;
+use crate::mempool::BundleOutcome;
+use crate::types::{format_usd, DexType, TradeResult};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Unique identifier for a route: (pair_symbol, buy_dex, sell_dex)
+type RouteKey = (String, DexType, DexType);
+
+/// Counters for one route (or a pair / overall total)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RouteStats {
+    /// Every execute() call, including pre-trade rejections and errors
+    pub attempts: u64,
+    pub successes: u64,
+    /// Atomic tx reverted on-chain (gas spent, nothing traded)
+    pub reverts: u64,
+    /// Submitted but not a success or revert (receipt timeout, gas-negative, legacy capital at risk)
+    pub other_failures: u64,
+    /// Nothing sent: pre-trade rejection or executor error
+    pub rejected: u64,
+    pub gross_usd: f64,
+    pub gas_usd: f64,
+    pub net_usd: f64,
+    /// Mempool backruns the bundle relay accepted
+    pub bundles: u64,
+    /// ... of which landed directly behind their trigger
+    pub bundles_included: u64,
+    /// Relay refused, raw-sent instead
+    pub bundle_fallbacks: u64,
+    /// Sum over results with an execution time (average = total / timed)
+    #[serde(skip)]
+    total_exec_ms: u64,
+    #[serde(skip)]
+    timed: u64,
+    /// Block arrival → tx submitted, over submitted trades (average = total / submits_timed)
+    #[serde(skip)]
+    total_block_to_submit_ms: u64,
+    #[serde(skip)]
+    submits_timed: u64,
+}
+
+impl RouteStats {
+    fn record(&mut self, exec: &Result<TradeResult>, outcome: TradeOutcome) {
+        self.attempts += 1;
+        match outcome {
+            TradeOutcome::Success => self.successes += 1,
+            TradeOutcome::AtomicRevert => self.reverts += 1,
+            TradeOutcome::ReceiptTimeout | TradeOutcome::GasNegative | TradeOutcome::CapitalAtRisk => {
+                self.other_failures += 1
+            }
+            _ => self.rejected += 1,
+        }
+        if let Ok(result) = exec {
+            self.gross_usd += result.profit_usd;
+            self.gas_usd += result.gas_cost_usd;
+            self.net_usd += result.net_profit_usd;
+            self.total_exec_ms += result.execution_time_ms;
+            self.timed += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &RouteStats) {
+        self.attempts += other.attempts;
+        self.successes += other.successes;
+        self.reverts += other.reverts;
+        self.other_failures += other.other_failures;
+        self.rejected += other.rejected;
+        self.gross_usd += other.gross_usd;
+        self.gas_usd += other.gas_usd;
+        self.net_usd += other.net_usd;
+        self.bundles += other.bundles;
+        self.bundles_included += other.bundles_included;
+        self.bundle_fallbacks += other.bundle_fallbacks;
+        self.total_exec_ms += other.total_exec_ms;
+        self.timed += other.timed;
+        self.total_block_to_submit_ms += other.total_block_to_submit_ms;
+        self.submits_timed += other.submits_timed;
+    }
+
+    /// Counters accumulated since `earlier` (a previous copy of the same totals)
+    pub fn since(&self, earlier: &RouteStats) -> RouteStats {
+        RouteStats {
+            attempts: self.attempts.saturating_sub(earlier.attempts),
+            successes: self.successes.saturating_sub(earlier.successes),
+            reverts: self.reverts.saturating_sub(earlier.reverts),
+            other_failures: self.other_failures.saturating_sub(earlier.other_failures),
+            rejected: self.rejected.saturating_sub(earlier.rejected),
+            gross_usd: self.gross_usd - earlier.gross_usd,
+            gas_usd: self.gas_usd - earlier.gas_usd,
+            net_usd: self.net_usd - earlier.net_usd,
+            bundles: self.bundles.saturating_sub(earlier.bundles),
+            bundles_included: self.bundles_included.saturating_sub(earlier.bundles_included),
+            bundle_fallbacks: self.bundle_fallbacks.saturating_sub(earlier.bundle_fallbacks),
+            total_exec_ms: self.total_exec_ms.saturating_sub(earlier.total_exec_ms),
+            timed: self.timed.saturating_sub(earlier.timed),
+            total_block_to_submit_ms: self.total_block_to_submit_ms.saturating_sub(earlier.total_block_to_submit_ms),
+            submits_timed: self.submits_timed.saturating_sub(earlier.submits_timed),
+        }
+    }
+
+    /// Average execution_time_ms over the results that reported one
+    pub fn avg_execution_ms(&self) -> f64 {
+        if self.timed == 0 {
+            0.0
+        } else {
+            self.total_exec_ms as f64 / self.timed as f64
+        }
+    }
+
+    /// Average block arrival → tx submitted over the submissions that reported one
+    pub fn avg_block_to_submit_ms(&self) -> f64 {
+        if self.submits_timed == 0 {
+            0.0
+        } else {
+            self.total_block_to_submit_ms as f64 / self.submits_timed as f64
+        }
+    }
+
+    /// Bundles included behind their trigger per bundle sent (percent)
+    pub fn bundle_hit_rate(&self) -> f64 {
+        if self.bundles == 0 {
+            0.0
+        } else {
+            self.bundles_included as f64 / self.bundles as f64 * 100.0
+        }
+    }
+
+    /// Successes per attempt (percent)
+    pub fn success_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            self.successes as f64 / self.attempts as f64 * 100.0
+        }
+    }
+}
+
+/// One route row in stats.json
+#[derive(Debug, Serialize)]
+struct RouteRow<'a> {
+    pair: &'a str,
+    buy_dex: DexType,
+    sell_dex: DexType,
+    avg_execution_ms: f64,
+    avg_block_to_submit_ms: f64,
+    bundle_hit_rate: f64,
+    #[serde(flatten)]
+    stats: &'a RouteStats,
+}
+
+/// One pair row in stats.json
+#[derive(Debug, Serialize)]
+struct PairRow<'a> {
+    pair: &'a str,
+    avg_execution_ms: f64,
+    avg_block_to_submit_ms: f64,
+    bundle_hit_rate: f64,
+    #[serde(flatten)]
+    stats: &'a RouteStats,
+}
+
+#[derive(Debug, Serialize)]
+struct StatsFile<'a> {
+    saved_at: String,
+    since: String,
+    total: &'a RouteStats,
+    pairs: Vec<PairRow<'a>>,
+    routes: Vec<RouteRow<'a>>,
+}
+
+/// Per-route trade statistics for this run
+pub struct StatsTracker {
+    routes: HashMap<RouteKey, RouteStats>,
+    since: chrono::DateTime<chrono::Utc>,
+    /// stats.json (None = no snapshot)
+    path: Option<PathBuf>,
+}
+
+impl Default for StatsTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatsTracker {
+    pub fn new() -> Self {
+        Self { routes: HashMap::new(), since: chrono::Utc::now(), path: None }
+    }
+
+    /// Write `write_snapshot` output to `data_dir`/stats.json
+    pub fn with_snapshot(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Route stats: failed to create {}: {}", data_dir, e);
+        }
+        self.path = Some(dir.join("stats.json"));
+        self
+    }
+
+    /// Record one execute() result and its classification
+    pub fn record(
+        &mut self,
+        pair: &str,
+        buy_dex: DexType,
+        sell_dex: DexType,
+        exec: &Result<TradeResult>,
+        outcome: TradeOutcome,
+    ) {
+        self.routes
+            .entry((pair.to_string(), buy_dex, sell_dex))
+            .or_default()
+            .record(exec, outcome);
+    }
+
+    /// Record what became of one bundled mempool backrun
+    pub fn record_bundle(&mut self, pair: &str, buy_dex: DexType, sell_dex: DexType, outcome: BundleOutcome) {
+        let stats = self.routes.entry((pair.to_string(), buy_dex, sell_dex)).or_default();
+        match outcome {
+            BundleOutcome::Fallback => stats.bundle_fallbacks += 1,
+            BundleOutcome::Included => {
+                stats.bundles += 1;
+                stats.bundles_included += 1;
+            }
+            BundleOutcome::Landed | BundleOutcome::NotIncluded => stats.bundles += 1,
+        }
+    }
+
+    /// Record one submitted trade's block arrival → tx submitted time
+    pub fn record_block_to_submit(&mut self, pair: &str, buy_dex: DexType, sell_dex: DexType, ms: u64) {
+        let stats = self.routes.entry((pair.to_string(), buy_dex, sell_dex)).or_default();
+        stats.total_block_to_submit_ms += ms;
+        stats.submits_timed += 1;
+    }
+
+    pub fn route(&self, pair: &str, buy_dex: DexType, sell_dex: DexType) -> Option<&RouteStats> {
+        self.routes.get(&(pair.to_string(), buy_dex, sell_dex))
+    }
+
+    /// Routes ordered by pair, then net USD (best first)
+    fn sorted_routes(&self) -> Vec<(&RouteKey, &RouteStats)> {
+        let mut routes: Vec<_> = self.routes.iter().collect();
+        routes.sort_by(|(a, sa), (b, sb)| a.0.cmp(&b.0).then(sb.net_usd.total_cmp(&sa.net_usd)));
+        routes
+    }
+
+    /// Totals per pair, ordered by pair symbol
+    pub fn pair_totals(&self) -> Vec<(String, RouteStats)> {
+        let mut pairs: HashMap<&str, RouteStats> = HashMap::new();
+        for ((pair, _, _), stats) in &self.routes {
+            pairs.entry(pair.as_str()).or_default().merge(stats);
+        }
+        let mut pairs: Vec<_> = pairs.into_iter().map(|(p, s)| (p.to_string(), s)).collect();
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        pairs
+    }
+
+    pub fn total(&self) -> RouteStats {
+        let mut total = RouteStats::default();
+        for stats in self.routes.values() {
+            total.merge(stats);
+        }
+        total
+    }
+
+    /// Formatted table: one row per route, a subtotal per pair, the run total
+    pub fn report(&self) -> String {
+        const RULE: &str = "═════════════════════════════════════════════════════════════════════════════════════════════════════════";
+        let header = format!(
+            "{:<44} {:>5} {:>5} {:>4} {:>4} {:>4} {:>10} {:>9} {:>10} {:>7} {:>7}",
+            "Route", "Att", "Win", "Rev", "Oth", "Rej", "Gross", "Gas", "Net", "Avg ms", "Blk→tx"
+        );
+        let row = |label: &str, s: &RouteStats| {
+            format!(
+                "{:<44} {:>5} {:>5} {:>4} {:>4} {:>4} {:>10} {:>9} {:>10} {:>7.0} {:>7.0}",
+                label, s.attempts, s.successes, s.reverts, s.other_failures, s.rejected,
+                format_usd(s.gross_usd), format_usd(s.gas_usd), format_usd(s.net_usd), s.avg_execution_ms(),
+                s.avg_block_to_submit_ms()
+            )
+        };
+
+        let mut lines = vec![
+            String::new(),
+            RULE.to_string(),
+            format!("           ROUTE P&L STATISTICS (since {})", self.since.format("%Y-%m-%d %H:%M:%S UTC")),
+            RULE.to_string(),
+            String::new(),
+            header,
+        ];
+        let routes = self.sorted_routes();
+        for (pair, pair_stats) in self.pair_totals() {
+            for ((_, buy_dex, sell_dex), stats) in routes.iter().filter(|((p, _, _), _)| *p == pair) {
+                lines.push(row(&format!("  {} {}→{}", pair, buy_dex, sell_dex), stats));
+            }
+            lines.push(row(&format!("{} total", pair), &pair_stats));
+        }
+        let total = self.total();
+        lines.push(String::new());
+        lines.push(row("TOTAL", &total));
+        lines.push(format!("Success rate:        {:.1}%", total.success_rate()));
+        if total.bundles + total.bundle_fallbacks > 0 {
+            lines.push(format!(
+                "Bundle hit rate:     {:.1}% ({}/{} included behind trigger, {} raw fallback)",
+                total.bundle_hit_rate(), total.bundles_included, total.bundles, total.bundle_fallbacks
+            ));
+        }
+        lines.push(RULE.to_string());
+        lines.join("\n")
+    }
+
+    /// Rewrite stats.json (tmp + rename, so readers never see a partial file).
+    /// No-op without a snapshot path.
+    pub fn write_snapshot(&self) {
+        let path = match self.path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let pairs = self.pair_totals();
+        let total = self.total();
+        let file = StatsFile {
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            since: self.since.to_rfc3339(),
+            total: &total,
+            pairs: pairs
+                .iter()
+                .map(|(pair, stats)| PairRow {
+                    pair,
+                    avg_execution_ms: stats.avg_execution_ms(),
+                    avg_block_to_submit_ms: stats.avg_block_to_submit_ms(),
+                    bundle_hit_rate: stats.bundle_hit_rate(),
+                    stats,
+                })
+                .collect(),
+            routes: self
+                .sorted_routes()
+                .into_iter()
+                .map(|((pair, buy_dex, sell_dex), stats)| RouteRow {
+                    pair,
+                    buy_dex: *buy_dex,
+                    sell_dex: *sell_dex,
+                    avg_execution_ms: stats.avg_execution_ms(),
+                    avg_block_to_submit_ms: stats.avg_block_to_submit_ms(),
+                    bundle_hit_rate: stats.bundle_hit_rate(),
+                    stats,
+                })
+                .collect(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        let written = serde_json::to_string_pretty(&file)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(&tmp, json)?))
+            .and_then(|()| Ok(std::fs::rename(&tmp, path)?));
+        match written {
+            Ok(()) => info!("Route stats: {} route(s) written to {}", file.routes.len(), path.display()),
+            Err(e) => warn!("Route stats: failed to write {}: {}", path.display(), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(success: bool, tx: bool, profit: f64, gas: f64, ms: u64, error: Option<&str>) -> Result<TradeResult> {
+        Ok(TradeResult {
+            opportunity: "WETH/USDC".to_string(),
+            opportunity_id: 0,
+            tx_hash: tx.then(|| "0xabc".to_string()),
+            block_number: None,
+            success,
+            profit_usd: profit,
+            gas_cost_usd: gas,
+            gas_used_native: 0.0,
+            net_profit_usd: profit - gas,
+            execution_time_ms: ms,
+            error: error.map(str::to_string),
+            amount_in: None,
+            amount_out: None,
+        })
+    }
+
+    #[test]
+    fn test_wins_losses_and_reverts_per_route_and_pair() {
+        let (a, b, c) = (DexType::UniswapV3_005, DexType::UniswapV3_030, DexType::QuickswapV3);
+        let mut stats = StatsTracker::new();
+        let mut feed = |pair: &str, buy, sell, exec: Result<TradeResult>| {
+            let outcome = TradeOutcome::classify(&exec, true);
+            stats.record(pair, buy, sell, &exec, outcome);
+        };
+
+        // Route 1: two wins, one on-chain revert
+        feed("WETH/USDC", a, b, result(true, true, 3.0, 0.5, 800, None));
+        feed("WETH/USDC", a, b, result(true, true, 2.0, 0.5, 1_200, None));
+        feed("WETH/USDC", a, b, result(false, true, 0.0, 0.4, 1_000, Some("Atomic tx reverted")));
+        // Route 2: landed at a loss, then an executor error
+        feed("WETH/USDC", b, c, result(false, true, 0.1, 0.6, 900, Some("Net profit negative after gas")));
+        feed("WETH/USDC", b, c, Err(anyhow::anyhow!("rpc down")));
+        // Other pair: pre-trade rejection (nothing sent, no gas)
+        feed("WMATIC/USDC", a, c, result(false, false, 0.0, 0.0, 50, Some("Quoter rejected buy leg")));
+        // Block arrival → submit for the three route-1 submissions and one route-2 submission
+        for ms in [120, 180, 150] {
+            stats.record_block_to_submit("WETH/USDC", a, b, ms);
+        }
+        stats.record_block_to_submit("WETH/USDC", b, c, 400);
+        // Route-1 backruns: two bundles taken by the relay (one landed behind the trigger), one fallback
+        stats.record_bundle("WETH/USDC", a, b, BundleOutcome::Included);
+        stats.record_bundle("WETH/USDC", a, b, BundleOutcome::NotIncluded);
+        stats.record_bundle("WETH/USDC", a, b, BundleOutcome::Fallback);
+
+        let r1 = stats.route("WETH/USDC", a, b).unwrap();
+        assert_eq!((r1.attempts, r1.successes, r1.reverts, r1.other_failures, r1.rejected), (3, 2, 1, 0, 0));
+        assert!((r1.gross_usd - 5.0).abs() < 1e-9);
+        assert!((r1.gas_usd - 1.4).abs() < 1e-9);
+        assert!((r1.net_usd - 3.6).abs() < 1e-9);
+        assert!((r1.avg_execution_ms() - 1_000.0).abs() < 1e-9);
+        assert!((r1.avg_block_to_submit_ms() - 150.0).abs() < 1e-9);
+        assert_eq!((r1.bundles, r1.bundles_included, r1.bundle_fallbacks), (2, 1, 1));
+        assert!((r1.bundle_hit_rate() - 50.0).abs() < 1e-9);
+
+        let r2 = stats.route("WETH/USDC", b, c).unwrap();
+        assert_eq!((r2.attempts, r2.successes, r2.other_failures, r2.rejected), (2, 0, 1, 1));
+        assert!((r2.net_usd + 0.5).abs() < 1e-9);
+        assert!((r2.avg_execution_ms() - 900.0).abs() < 1e-9, "errors carry no execution time");
+
+        let pairs = stats.pair_totals();
+        assert_eq!(pairs.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>(), vec!["WETH/USDC", "WMATIC/USDC"]);
+        assert_eq!(pairs[0].1.attempts, 5);
+        assert!((pairs[0].1.net_usd - 3.1).abs() < 1e-9);
+        assert_eq!(pairs[1].1.rejected, 1);
+
+        let total = stats.total();
+        assert_eq!((total.attempts, total.successes), (6, 2));
+        assert!((total.avg_block_to_submit_ms() - 212.5).abs() < 1e-9);
+        assert_eq!(stats.route("WMATIC/USDC", a, c).unwrap().avg_block_to_submit_ms(), 0.0);
+        assert!((total.net_usd - 3.1).abs() < 1e-9);
+
+        let report = stats.report();
+        assert!(report.contains("ROUTE P&L STATISTICS"));
+        assert!(report.contains("WETH/USDC UniswapV3_0.05%→UniswapV3_0.30%"));
+        assert!(report.contains("WMATIC/USDC total"));
+        assert!(report.contains("-$0.50"));
+        assert!(report.contains("Bundle hit rate"));
+
+        let dir = std::env::temp_dir().join(format!("dexarb_stats_{}", std::process::id()));
+        let stats = stats.with_snapshot(dir.to_str().unwrap());
+        stats.write_snapshot();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("stats.json")).unwrap()).unwrap();
+        assert_eq!(json["total"]["attempts"], 6);
+        assert_eq!(json["pairs"][0]["avg_block_to_submit_ms"], 212.5);
+        assert_eq!(json["routes"].as_array().unwrap().len(), 3);
+        assert_eq!(json["routes"][0]["buy_dex"], serde_json::json!(a));
+        assert_eq!(json["routes"][0]["bundle_hit_rate"], 50.0);
+        assert_eq!(json["pairs"][1]["pair"], "WMATIC/USDC");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
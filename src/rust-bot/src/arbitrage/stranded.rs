@@ -0,0 +1,630 @@
+//! Stranded-Funds Alarm — ArbExecutor token balance watch
+//!
+//! Purpose:
+//!     The atomic ArbExecutor should never hold tokens between transactions:
+//!     executeArb pulls amountIn from the wallet and returns everything at the
+//!     end. A token quirk (fee-on-transfer, rebasing, a sweep that misses the
+//!     output) can leave funds stranded inside the contract, and nothing on the
+//!     trade path would notice. This module watches for that.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Quote token decimals from the TokenRegistry (no USDC assumption)
+//! Modified: 2026-10-16 - Rescues recorded in the tax ledger (TaxSource::Rescue)
+//!
+//! Design:
+//!     - Every STRANDED_CHECK_INTERVAL_BLOCKS blocks, one Multicall3 aggregate3
+//!       reads token.balanceOf(arbExecutor) for every token in the pool universe
+//!       (both sides of every synced pool) plus the quote tokens
+//!     - Balances worth more than STRANDED_DUST_USD are stranded. Tokens with no
+//!       known USD price count as stranded at any nonzero balance.
+//!     - Alerts fire once per incident: a persistent balance stays quiet; a
+//!       balance that grows past the last alerted amount alerts again; a balance
+//!       that drops below dust is logged as cleared and re-arms the alarm
+//!     - Alert = error log + webhook (ALERT_WEBHOOK_URL) + status JSON
+//!       (arb_executor_balances.json, rewritten on every check)
+//!     - AUTO_RESCUE=true: newly alerted tokens are swept back to the wallet via
+//!       rescueTokens(token); every rescue is appended to rescues.csv and, like
+//!       every other token movement, written to the tax ledger (a Transfer)
+
+use super::multicall_quoter::MulticallQuoter;
+use super::preflight::{decode_word, encode_balance_of};
+use crate::pool::PoolStateManager;
+use crate::token_registry::TokenRegistry;
+use crate::types::{BotConfig, TradingPair};
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Check cadence, dust threshold and rescue toggle
+#[derive(Debug, Clone)]
+pub struct StrandedConfig {
+    /// Blocks between balance checks (0 = disabled)
+    pub check_interval_blocks: u64,
+    /// Balances worth at most this much (USD) are dust and ignored
+    pub dust_usd: f64,
+    /// Sweep newly stranded balances back to the wallet via rescueTokens()
+    pub auto_rescue: bool,
+}
+
+impl StrandedConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self {
+            check_interval_blocks: config.stranded_check_interval_blocks,
+            dust_usd: config.stranded_dust_usd,
+            auto_rescue: config.auto_rescue,
+        }
+    }
+}
+
+/// One token whose ArbExecutor balance is checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedToken {
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+    /// USD per whole token (None = no pool prices it against a quote token)
+    pub price_usd: Option<f64>,
+}
+
+/// A non-dust balance sitting in the ArbExecutor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrandedBalance {
+    pub token: Address,
+    pub symbol: String,
+    pub decimals: u8,
+    pub raw: U256,
+    /// Human units (raw / 10^decimals)
+    pub amount: f64,
+    pub usd_value: Option<f64>,
+}
+
+/// Outcome of one balance check
+#[derive(Debug, Clone, Default)]
+pub struct StrandedReport {
+    pub block: u64,
+    pub tokens_checked: usize,
+    /// Every token currently above dust
+    pub stranded: Vec<StrandedBalance>,
+    /// Subset of `stranded` that is new (or grew) since the last alert
+    pub new_alerts: Vec<StrandedBalance>,
+    /// Tokens that were alerted before and are now back at dust
+    pub cleared: Vec<String>,
+}
+
+/// Status JSON written after every check
+#[derive(Debug, Serialize)]
+struct StrandedStatus<'a> {
+    checked_at: String,
+    block: u64,
+    arb_executor: Address,
+    dust_usd: f64,
+    tokens_checked: usize,
+    stranded: &'a [StrandedBalance],
+}
+
+/// Build the token universe: both sides of every synced pool plus the quote tokens.
+///
+/// Symbols come from the configured pairs (TRADING_PAIRS is BASE:QUOTE:SYMBOL),
+/// decimals from the synced pools, or the token registry for a quote token no
+/// synced pool holds. USD prices are read off V3 pools that price the token
+/// against a quote token; quote tokens are $1.
+pub fn token_universe(state: &PoolStateManager, config: &BotConfig, registry: &TokenRegistry) -> Vec<WatchedToken> {
+    let mut symbols: HashMap<Address, String> = HashMap::new();
+    for pair in &config.pairs {
+        let mut parts = pair.symbol.split('/');
+        for (addr, sym) in [(&pair.token0, parts.next()), (&pair.token1, parts.next())] {
+            if let (Ok(a), Some(s)) = (addr.parse::<Address>(), sym) {
+                symbols.entry(a).or_insert_with(|| s.to_string());
+            }
+        }
+    }
+
+    let mut tokens: BTreeMap<Address, WatchedToken> = BTreeMap::new();
+    let mut add = |address: Address, decimals: u8| {
+        tokens.entry(address).or_insert_with(|| WatchedToken {
+            address,
+            symbol: symbols.get(&address).cloned().unwrap_or_else(|| format!("{:?}", address)),
+            decimals,
+            price_usd: None,
+        });
+    };
+
+    let v3_pools = state.get_all_v3_pools();
+    let pools: Vec<(&TradingPair, u8, u8)> = v3_pools.iter()
+        .map(|p| (&p.pair, p.token0_decimals, p.token1_decimals))
+        .collect();
+    let v2_pools = state.get_all_pools();
+    for (pair, d0, d1) in pools.into_iter()
+        .chain(v2_pools.iter().map(|p| (&p.pair, p.token0_decimals, p.token1_decimals)))
+    {
+        add(pair.token0, d0);
+        add(pair.token1, d1);
+    }
+    // Quote tokens are watched even when no synced pool holds them
+    for quote in std::iter::once(config.quote_token_address).chain(config.quote_token_address_native) {
+        match registry.decimals(&quote) {
+            Ok(decimals) => add(quote, decimals),
+            Err(e) => debug!("Stranded: quote token not watched: {}", e),
+        }
+    }
+
+    for token in tokens.values_mut() {
+        if config.is_quote_token(&token.address) {
+            token.price_usd = Some(1.0);
+        }
+    }
+    for pool in &v3_pools {
+        let price = pool.price();
+        if !(price.is_finite() && price > 0.0) {
+            continue;
+        }
+        let priced = if config.is_quote_token(&pool.pair.token1) {
+            Some((pool.pair.token0, price))
+        } else if config.is_quote_token(&pool.pair.token0) {
+            Some((pool.pair.token1, 1.0 / price))
+        } else {
+            None
+        };
+        if let Some((addr, usd)) = priced {
+            if let Some(token) = tokens.get_mut(&addr) {
+                token.price_usd.get_or_insert(usd);
+            }
+        }
+    }
+
+    tokens.into_values().collect()
+}
+
+/// Periodic balanceOf watch over the ArbExecutor contract
+pub struct StrandedFundsMonitor {
+    arb_address: Address,
+    config: StrandedConfig,
+    /// Raw balance at the last alert per token (dedup state)
+    alerted: HashMap<Address, U256>,
+    last_check_block: Option<u64>,
+    /// arb_executor_balances.json
+    status_path: Option<PathBuf>,
+    /// rescues.csv
+    ledger_path: Option<PathBuf>,
+    webhook_url: Option<String>,
+    client: reqwest::Client,
+}
+
+impl StrandedFundsMonitor {
+    pub fn new(arb_address: Address, config: StrandedConfig) -> Self {
+        info!(
+            "Stranded-funds alarm: ArbExecutor {:?} every {} blocks | dust ${:.2} | auto rescue {}",
+            arb_address, config.check_interval_blocks, config.dust_usd,
+            if config.auto_rescue { "ON" } else { "off" }
+        );
+        Self {
+            arb_address,
+            config,
+            alerted: HashMap::new(),
+            last_check_block: None,
+            status_path: None,
+            ledger_path: None,
+            webhook_url: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Write the status JSON and rescue ledger under `data_dir`
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Stranded: failed to create {}: {}", data_dir, e);
+        }
+        self.status_path = Some(dir.join("arb_executor_balances.json"));
+        self.ledger_path = Some(dir.join("rescues.csv"));
+        self
+    }
+
+    pub fn with_webhook(mut self, url: Option<String>) -> Self {
+        self.webhook_url = url;
+        self
+    }
+
+    /// True when a check should run at `block`
+    pub fn is_due(&self, block: u64) -> bool {
+        let interval = self.config.check_interval_blocks;
+        interval > 0
+            && self.last_check_block.is_none_or(|last| block >= last + interval)
+    }
+
+    /// One balanceOf(arbExecutor) sub-call per token, in `tokens` order
+    pub(crate) fn build_sub_calls(&self, tokens: &[WatchedToken]) -> Vec<(Address, Vec<u8>)> {
+        tokens.iter()
+            .map(|t| (t.address, encode_balance_of(self.arb_address)))
+            .collect()
+    }
+
+    /// Read all balances in one aggregate3 and evaluate them
+    pub async fn check<M: Middleware + 'static>(
+        &mut self,
+        quoter: &MulticallQuoter<M>,
+        tokens: &[WatchedToken],
+        block: u64,
+    ) -> Result<StrandedReport> {
+        // Mark the check up front so a failing RPC is retried next interval, not next block
+        self.last_check_block = Some(block);
+        if tokens.is_empty() {
+            return Ok(StrandedReport { block, ..Default::default() });
+        }
+        let results = quoter.aggregate3_at(&self.build_sub_calls(tokens), None).await?;
+        let report = self.evaluate(block, tokens, &results)?;
+        self.write_status(&report);
+        Ok(report)
+    }
+
+    /// Classify balances and apply alert dedup
+    pub(crate) fn evaluate(
+        &mut self,
+        block: u64,
+        tokens: &[WatchedToken],
+        results: &[(bool, Vec<u8>)],
+    ) -> Result<StrandedReport> {
+        if results.len() != tokens.len() {
+            return Err(anyhow!(
+                "balance batch returned {} results for {} tokens",
+                results.len(), tokens.len()
+            ));
+        }
+
+        let mut report = StrandedReport {
+            block,
+            tokens_checked: tokens.len(),
+            ..Default::default()
+        };
+        for (token, result) in tokens.iter().zip(results) {
+            let raw = match decode_word(result) {
+                Some(raw) => raw,
+                None => {
+                    debug!("Stranded: balanceOf failed for {} — skipped", token.symbol);
+                    continue;
+                }
+            };
+            match self.classify(token, raw) {
+                Some(balance) => {
+                    let is_new = self.alerted.get(&token.address).is_none_or(|prev| raw > *prev);
+                    if is_new {
+                        self.alerted.insert(token.address, raw);
+                        report.new_alerts.push(balance.clone());
+                    }
+                    report.stranded.push(balance);
+                }
+                None => {
+                    if self.alerted.remove(&token.address).is_some() {
+                        report.cleared.push(token.symbol.clone());
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Some(balance) if `raw` is above dust for this token
+    fn classify(&self, token: &WatchedToken, raw: U256) -> Option<StrandedBalance> {
+        if raw.is_zero() {
+            return None;
+        }
+        let amount = raw.low_u128() as f64 / 10_f64.powi(token.decimals as i32);
+        let usd_value = token.price_usd.map(|p| amount * p);
+        if usd_value.is_some_and(|usd| usd <= self.config.dust_usd) {
+            return None;
+        }
+        Some(StrandedBalance {
+            token: token.address,
+            symbol: token.symbol.clone(),
+            decimals: token.decimals,
+            raw,
+            amount,
+            usd_value,
+        })
+    }
+
+    /// Newly alerted balances to sweep (empty unless AUTO_RESCUE=true)
+    pub fn rescue_targets(&self, report: &StrandedReport) -> Vec<StrandedBalance> {
+        if self.config.auto_rescue {
+            report.new_alerts.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Log and webhook every new alert; log cleared tokens
+    pub async fn alert(&self, report: &StrandedReport) {
+        for symbol in &report.cleared {
+            info!("Stranded: {} balance in ArbExecutor back below dust — alarm re-armed", symbol);
+        }
+        for balance in &report.new_alerts {
+            let msg = format!(
+                "🚨 STRANDED FUNDS in ArbExecutor {:?}: {} {} (raw {}){} at block {}",
+                self.arb_address,
+                balance.amount,
+                balance.symbol,
+                balance.raw,
+                balance.usd_value.map(|v| format!(" ≈ ${:.2}", v)).unwrap_or_default(),
+                report.block,
+            );
+            error!("{}", msg);
+            self.post_webhook(&msg).await;
+        }
+    }
+
+    /// Append a completed rescue to rescues.csv and announce it
+    pub async fn record_rescue(&self, balance: &StrandedBalance, tx_hash: TxHash, block: u64) {
+        let msg = format!(
+            "🛟 Rescued {} {} from ArbExecutor {:?} | TX: {:?}",
+            balance.amount, balance.symbol, self.arb_address, tx_hash
+        );
+        warn!("{}", msg);
+        self.post_webhook(&msg).await;
+
+        let path = match self.ledger_path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let is_new = !path.exists();
+        let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(f) => f,
+            Err(e) => {
+                warn!("Stranded: failed to open ledger {}: {}", path.display(), e);
+                return;
+            }
+        };
+        if is_new {
+            let _ = writeln!(file, "timestamp,block,tx_hash,arb_executor,token,symbol,amount_raw,amount,usd_value");
+        }
+        let _ = writeln!(
+            file,
+            "{},{},{:?},{:?},{:?},{},{},{},{}",
+            chrono::Utc::now().to_rfc3339(),
+            block,
+            tx_hash,
+            self.arb_address,
+            balance.token,
+            balance.symbol,
+            balance.raw,
+            balance.amount,
+            balance.usd_value.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+        );
+    }
+
+    /// Announce a failed rescue (funds are still stranded)
+    pub async fn rescue_failed(&self, balance: &StrandedBalance, err: &anyhow::Error) {
+        let msg = format!(
+            "Rescue of {} {} from ArbExecutor failed: {} — manual rescueTokens({:?}) needed",
+            balance.amount, balance.symbol, err, balance.token
+        );
+        error!("{}", msg);
+        self.post_webhook(&msg).await;
+    }
+
+    fn write_status(&self, report: &StrandedReport) {
+        let path = match self.status_path {
+            Some(ref p) => p,
+            None => return,
+        };
+        let status = StrandedStatus {
+            checked_at: chrono::Utc::now().to_rfc3339(),
+            block: report.block,
+            arb_executor: self.arb_address,
+            dust_usd: self.config.dust_usd,
+            tokens_checked: report.tokens_checked,
+            stranded: &report.stranded,
+        };
+        match serde_json::to_string_pretty(&status) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    warn!("Stranded: failed to write status: {}", e);
+                }
+            }
+            Err(e) => warn!("Stranded: failed to serialize status: {}", e),
+        }
+    }
+
+    async fn post_webhook(&self, content: &str) {
+        let url = match self.webhook_url {
+            Some(ref u) => u,
+            None => return,
+        };
+        let body = serde_json::json!({ "content": content });
+        let sent = self.client.post(url)
+            .json(&body)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await;
+        if let Err(e) = sent {
+            warn!("Stranded: webhook alert failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::arbitrage::executor::rescue_tokens_call;
+    use crate::token_registry::TokenInfo;
+    use crate::types::{DexType, TradingPairConfig, V3PoolState};
+    use ethers::abi::{self, Token};
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+    const WETH: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+
+    fn addr(s: &str) -> Address {
+        Address::from_str(s).unwrap()
+    }
+
+    fn monitor(auto_rescue: bool) -> StrandedFundsMonitor {
+        StrandedFundsMonitor::new(
+            Address::from_low_u64_be(0xA4B),
+            StrandedConfig { check_interval_blocks: 100, dust_usd: 1.0, auto_rescue },
+        )
+    }
+
+    fn tokens() -> Vec<WatchedToken> {
+        vec![
+            WatchedToken { address: addr(USDC), symbol: "USDC".into(), decimals: 6, price_usd: Some(1.0) },
+            WatchedToken { address: addr(WETH), symbol: "WETH".into(), decimals: 18, price_usd: Some(3000.0) },
+            WatchedToken { address: Address::from_low_u64_be(7), symbol: "XYZ".into(), decimals: 18, price_usd: None },
+        ]
+    }
+
+    fn balances(usdc: u128, weth: u128, xyz: u128) -> Vec<(bool, Vec<u8>)> {
+        [usdc, weth, xyz].iter()
+            .map(|v| (true, abi::encode(&[Token::Uint(U256::from(*v))])))
+            .collect()
+    }
+
+    #[test]
+    fn test_dust_and_stranded_detection() {
+        let mut m = monitor(false);
+        // 0.5 USDC (dust), 0.001 WETH = $3 (stranded), 1 wei of unpriced XYZ (stranded)
+        let report = m.evaluate(10, &tokens(), &balances(500_000, 1_000_000_000_000_000, 1)).unwrap();
+        let symbols: Vec<&str> = report.stranded.iter().map(|b| b.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["WETH", "XYZ"]);
+        assert_eq!(report.new_alerts.len(), 2);
+        assert_eq!(report.stranded[0].amount, 0.001);
+        assert_eq!(report.stranded[0].usd_value, Some(3.0));
+        assert_eq!(report.stranded[1].usd_value, None);
+
+        // Failed sub-call is skipped, result count mismatch is an error
+        let mut results = balances(0, 0, 0);
+        results[1] = (false, vec![]);
+        assert!(m.evaluate(11, &tokens(), &results).unwrap().stranded.is_empty());
+        assert!(m.evaluate(12, &tokens(), &results[..2]).is_err());
+    }
+
+    #[test]
+    fn test_alert_dedup() {
+        let mut m = monitor(false);
+        let five_usdc = balances(5_000_000, 0, 0);
+
+        let first = m.evaluate(100, &tokens(), &five_usdc).unwrap();
+        assert_eq!(first.new_alerts.len(), 1);
+
+        // Persistent balance: still reported as stranded, not re-alerted
+        let second = m.evaluate(200, &tokens(), &five_usdc).unwrap();
+        assert_eq!(second.stranded.len(), 1);
+        assert!(second.new_alerts.is_empty());
+
+        // Shrinking is quiet, growing past the alerted amount is a new incident
+        assert!(m.evaluate(300, &tokens(), &balances(4_000_000, 0, 0)).unwrap().new_alerts.is_empty());
+        assert_eq!(m.evaluate(400, &tokens(), &balances(6_000_000, 0, 0)).unwrap().new_alerts.len(), 1);
+
+        // Back to dust clears and re-arms
+        let cleared = m.evaluate(500, &tokens(), &balances(0, 0, 0)).unwrap();
+        assert_eq!(cleared.cleared, vec!["USDC".to_string()]);
+        assert_eq!(m.evaluate(600, &tokens(), &five_usdc).unwrap().new_alerts.len(), 1);
+    }
+
+    #[test]
+    fn test_check_interval() {
+        let mut m = monitor(false);
+        assert!(m.is_due(1_000));
+        m.last_check_block = Some(1_000);
+        assert!(!m.is_due(1_099));
+        assert!(m.is_due(1_100));
+
+        let mut off = monitor(false);
+        off.config.check_interval_blocks = 0;
+        assert!(!off.is_due(1_000));
+    }
+
+    #[test]
+    fn test_balance_batch_layout() {
+        let m = monitor(false);
+        let calls = m.build_sub_calls(&tokens());
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[1].0, addr(WETH));
+        assert_eq!(&calls[1].1[..4], &ethers::utils::id("balanceOf(address)"));
+        assert_eq!(&calls[1].1[16..36], Address::from_low_u64_be(0xA4B).as_bytes());
+    }
+
+    #[test]
+    fn test_rescue_targets_and_call() {
+        let report = monitor(true).evaluate(10, &tokens(), &balances(5_000_000, 0, 0)).unwrap();
+        assert!(monitor(false).rescue_targets(&report).is_empty());
+        let mut auto = monitor(true);
+        let report = auto.evaluate(10, &tokens(), &balances(5_000_000, 0, 0)).unwrap();
+        let targets = auto.rescue_targets(&report);
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].token, addr(USDC));
+
+        // Persistent balance after a rescue attempt is not rescued again
+        let again = auto.evaluate(110, &tokens(), &balances(5_000_000, 0, 0)).unwrap();
+        assert!(auto.rescue_targets(&again).is_empty());
+
+        let client = Arc::new(Provider::<Http>::try_from("http://127.0.0.1:8545").unwrap());
+        let arb = Address::from_low_u64_be(0xA4B);
+        let call = rescue_tokens_call(arb, targets[0].token, client);
+        assert_eq!(call.tx.to_addr(), Some(&arb));
+        let data = call.calldata().unwrap();
+        assert_eq!(&data[..4], &ethers::utils::id("rescueTokens(address)"));
+        assert_eq!(data.len(), 36);
+        assert_eq!(&data[16..36], addr(USDC).as_bytes());
+    }
+
+    #[test]
+    fn test_token_universe_prices_and_symbols() {
+        let mut config = create_test_config();
+        config.pairs = vec![TradingPairConfig {
+            token0: WETH.to_string(),
+            token1: USDC.to_string(),
+            symbol: "WETH/USDC".to_string(),
+        }];
+        let state = PoolStateManager::new();
+        // WETH (0x7ceb…) > USDC (0x2791…): pool token0 = USDC, token1 = WETH
+        // price() = WETH per USDC = 1.0001^tick * 10^(6-18); tick 195_303 ≈ 1/3300
+        state.update_v3_pool(V3PoolState {
+            address: Address::from_low_u64_be(1),
+            dex: DexType::UniswapV3_005,
+            pair: TradingPair::new(addr(USDC), addr(WETH), "WETH/USDC".to_string()),
+            sqrt_price_x96: U256::zero(),
+            tick: 195_303,
+            fee: 500,
+            liquidity: 1,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 1,
+        });
+
+        let tokens = token_universe(&state, &config, &TokenRegistry::new(config.chain_id));
+        assert_eq!(tokens.len(), 2);
+        let usdc = tokens.iter().find(|t| t.address == addr(USDC)).unwrap();
+        let weth = tokens.iter().find(|t| t.address == addr(WETH)).unwrap();
+        assert_eq!(usdc.symbol, "USDC");
+        assert_eq!(usdc.price_usd, Some(1.0));
+        assert_eq!(weth.symbol, "WETH");
+        assert_eq!(weth.decimals, 18);
+        let weth_usd = weth.price_usd.unwrap();
+        assert!((weth_usd - 3300.0).abs() < 5.0, "WETH price {}", weth_usd);
+    }
+
+    #[test]
+    fn test_unpooled_quote_token_takes_registry_decimals() {
+        // WETH-quoted silo with no synced pool yet: not assumed to be 6-decimal USDC
+        let mut config = create_test_config();
+        config.quote_token_address = addr(WETH);
+        config.quote_token_address_native = None;
+        let state = PoolStateManager::new();
+        assert!(token_universe(&state, &config, &TokenRegistry::new(config.chain_id)).is_empty());
+
+        let mut registry = TokenRegistry::new(config.chain_id);
+        registry.insert(TokenInfo { address: addr(WETH), symbol: "WETH".into(), decimals: 18 });
+        let tokens = token_universe(&state, &config, &registry);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!((tokens[0].address, tokens[0].decimals), (addr(WETH), 18));
+    }
+}
@@ -0,0 +1,235 @@
+//! Adaptive Minimum Profit — per-route break-even from revert rate and gas
+//!
+//! Purpose:
+//!     MIN_PROFIT_USD is a static floor, but a route whose atomic txs revert
+//!     burns gas for nothing on every revert. With revert rate r and average
+//!     gas cost g per landed tx, a route breaks even only if each success
+//!     also pays for the r/(1−r) reverts expected alongside it:
+//!     effective = MIN_PROFIT_USD + r/(1−r) × g, clamped to
+//!     [MIN_PROFIT_FLOOR_USD, MIN_PROFIT_CEILING_USD].
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - apply() keeps a higher whitelist min_profit_usd already stamped
+//!
+//! Design:
+//!     - Per route (pair, buy_dex, sell_dex, as in stats.rs), the last
+//!       THRESHOLD_WINDOW landed txs: Success / GasNegative count as landed,
+//!       AtomicRevert as reverted. Nothing else burned gas, so nothing else
+//!       moves the rate.
+//!     - r is capped at MAX_REVERT_RATE so r/(1−r) stays finite; the ceiling
+//!       bounds the result anyway.
+//!     - ThresholdModel is a shared handle (Arc<RwLock>): main.rs records
+//!       every TradeResult, the detector reads thresholds in scan_opportunities()
+//!       and stamps each opportunity's min_profit_usd, which the executor turns
+//!       into the contract's minProfit.
+
+use super::outcome::TradeOutcome;
+use crate::types::{ArbitrageOpportunity, BotConfig, DexType, TradeResult};
+use anyhow::Result;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use tracing::debug;
+
+/// Landed txs remembered per route
+pub const THRESHOLD_WINDOW: usize = 20;
+
+/// Revert rate used at most (r/(1−r) = 9 at 0.9)
+pub const MAX_REVERT_RATE: f64 = 0.9;
+
+type RouteKey = (String, DexType, DexType);
+
+/// One landed tx: reverted, gas cost (USD)
+#[derive(Debug, Default)]
+struct RouteHistory {
+    landed: VecDeque<(bool, f64)>,
+}
+
+impl RouteHistory {
+    fn push(&mut self, reverted: bool, gas_usd: f64) {
+        if self.landed.len() == THRESHOLD_WINDOW {
+            self.landed.pop_front();
+        }
+        self.landed.push_back((reverted, gas_usd));
+    }
+
+    fn revert_rate(&self) -> f64 {
+        if self.landed.is_empty() {
+            return 0.0;
+        }
+        let reverts = self.landed.iter().filter(|(reverted, _)| *reverted).count();
+        (reverts as f64 / self.landed.len() as f64).min(MAX_REVERT_RATE)
+    }
+
+    fn avg_gas_usd(&self) -> f64 {
+        if self.landed.is_empty() {
+            return 0.0;
+        }
+        self.landed.iter().map(|(_, gas)| gas).sum::<f64>() / self.landed.len() as f64
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    base_usd: f64,
+    floor_usd: f64,
+    ceiling_usd: f64,
+    routes: HashMap<RouteKey, RouteHistory>,
+}
+
+/// Shared per-route minimum profit model
+#[derive(Debug, Clone)]
+pub struct ThresholdModel(Arc<RwLock<Inner>>);
+
+impl ThresholdModel {
+    /// `base_usd` = MIN_PROFIT_USD; results are clamped to [floor, ceiling]
+    pub fn new(base_usd: f64, floor_usd: f64, ceiling_usd: f64) -> Self {
+        Self(Arc::new(RwLock::new(Inner {
+            base_usd,
+            floor_usd,
+            ceiling_usd: ceiling_usd.max(floor_usd),
+            routes: HashMap::new(),
+        })))
+    }
+
+    pub fn from_config(config: &BotConfig) -> Self {
+        Self::new(config.min_profit_usd, config.min_profit_floor_usd, config.min_profit_ceiling_usd)
+    }
+
+    /// Fold one execution result in (main.rs, after every TradeResult)
+    pub fn record(&self, pair: &str, buy_dex: DexType, sell_dex: DexType, exec: &Result<TradeResult>, outcome: TradeOutcome) {
+        let reverted = match outcome {
+            TradeOutcome::AtomicRevert => true,
+            TradeOutcome::Success | TradeOutcome::GasNegative => false,
+            _ => return,
+        };
+        let gas_usd = exec.as_ref().map_or(0.0, |result| result.gas_cost_usd);
+        let mut inner = self.0.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        inner.routes.entry((pair.to_string(), buy_dex, sell_dex)).or_default().push(reverted, gas_usd);
+    }
+
+    /// (revert rate, average gas USD) over the route's window; (0, 0) = no history
+    pub fn route_history(&self, pair: &str, buy_dex: DexType, sell_dex: DexType) -> (f64, f64) {
+        let inner = self.0.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        inner
+            .routes
+            .get(&(pair.to_string(), buy_dex, sell_dex))
+            .map_or((0.0, 0.0), |history| (history.revert_rate(), history.avg_gas_usd()))
+    }
+
+    /// Effective minimum profit (USD) for a route
+    pub fn min_profit_usd(&self, pair: &str, buy_dex: DexType, sell_dex: DexType) -> f64 {
+        let (revert_rate, avg_gas_usd) = self.route_history(pair, buy_dex, sell_dex);
+        let inner = self.0.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let effective = inner.base_usd + revert_rate / (1.0 - revert_rate) * avg_gas_usd;
+        effective.clamp(inner.floor_usd, inner.ceiling_usd)
+    }
+
+    /// Stamp each opportunity's min_profit_usd and drop those whose estimated
+    /// profit no longer clears it. A higher stamp already there (a whitelist
+    /// pair/pool override) stays.
+    pub fn apply(&self, opportunities: Vec<ArbitrageOpportunity>) -> Vec<ArbitrageOpportunity> {
+        opportunities
+            .into_iter()
+            .filter_map(|mut opp| {
+                let adaptive = self.min_profit_usd(&opp.pair.symbol, opp.buy_dex, opp.sell_dex);
+                let threshold = opp.min_profit_usd.map_or(adaptive, |stamped| stamped.max(adaptive));
+                if opp.estimated_profit < threshold {
+                    debug!(
+                        "Adaptive min profit: {} {:?}→{:?} est ${:.4} < ${:.4} — skipped",
+                        opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.estimated_profit, threshold
+                    );
+                    return None;
+                }
+                opp.min_profit_usd = Some(threshold);
+                Some(opp)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn landed(gas_cost_usd: f64, reverted: bool) -> (Result<TradeResult>, TradeOutcome) {
+        let result = TradeResult {
+            opportunity: "WETH/USDC".to_string(),
+            opportunity_id: 0,
+            tx_hash: Some("0xabc".to_string()),
+            block_number: Some(1),
+            success: !reverted,
+            profit_usd: if reverted { 0.0 } else { 0.5 },
+            gas_cost_usd,
+            gas_used_native: 0.0,
+            net_profit_usd: 0.0,
+            execution_time_ms: 0,
+            error: reverted.then(|| "Atomic tx reverted on-chain".to_string()),
+            amount_in: None,
+            amount_out: None,
+        };
+        (Ok(result), if reverted { TradeOutcome::AtomicRevert } else { TradeOutcome::Success })
+    }
+
+    #[test]
+    fn test_half_reverting_route_doubles_required_profit() {
+        let model = ThresholdModel::new(0.02, 0.01, 1.0);
+        let (dirty, clean) = (DexType::UniswapV3_005, DexType::UniswapV3_030);
+        for i in 0..10 {
+            let (exec, outcome) = landed(0.02, i % 2 == 0);
+            model.record("WETH/USDC", dirty, clean, &exec, outcome);
+            let (exec, outcome) = landed(0.02, false);
+            model.record("WETH/USDC", clean, dirty, &exec, outcome);
+        }
+        // Pre-trade rejections never landed: no effect on the rate
+        let rejected = Ok(TradeResult { tx_hash: None, ..landed(0.0, true).0.unwrap() });
+        model.record("WETH/USDC", clean, dirty, &rejected, TradeOutcome::RouteRejected);
+
+        let (revert_rate, avg_gas_usd) = model.route_history("WETH/USDC", dirty, clean);
+        assert_eq!(revert_rate, 0.5);
+        assert!((avg_gas_usd - 0.02).abs() < 1e-12);
+        let clean_min = model.min_profit_usd("WETH/USDC", clean, dirty);
+        let dirty_min = model.min_profit_usd("WETH/USDC", dirty, clean);
+        assert!((clean_min - 0.02).abs() < 1e-12);
+        // 0.02 + 0.5/0.5 × 0.02
+        assert!((dirty_min / clean_min - 2.0).abs() < 1e-9, "{} vs {}", dirty_min, clean_min);
+        // Unknown route: base
+        assert_eq!(model.min_profit_usd("WBTC/USDC", dirty, clean), 0.02);
+    }
+
+    #[test]
+    fn test_threshold_clamped_and_applied_to_opportunities() {
+        let model = ThresholdModel::new(0.02, 0.05, 0.10);
+        let (buy, sell) = (DexType::UniswapV3_005, DexType::UniswapV3_030);
+        // Base below the floor: raised to it
+        assert_eq!(model.min_profit_usd("WETH/USDC", buy, sell), 0.05);
+        // Every tx reverting: rate capped, result capped at the ceiling
+        for _ in 0..THRESHOLD_WINDOW {
+            let (exec, outcome) = landed(0.5, true);
+            model.record("WETH/USDC", buy, sell, &exec, outcome);
+        }
+        assert_eq!(model.route_history("WETH/USDC", buy, sell).0, MAX_REVERT_RATE);
+        assert_eq!(model.min_profit_usd("WETH/USDC", buy, sell), 0.10);
+
+        let opp = |pair: &str, estimated_profit: f64| {
+            let mut opp = ArbitrageOpportunity::new(
+                crate::types::TradingPair::new(
+                    ethers::types::Address::from_low_u64_be(1),
+                    ethers::types::Address::from_low_u64_be(2),
+                    pair.to_string(),
+                ),
+                buy,
+                sell,
+                2500.0,
+                2510.0,
+                ethers::types::U256::from(500_000_000u64),
+            );
+            opp.estimated_profit = estimated_profit;
+            opp
+        };
+        let kept = model.apply(vec![opp("WETH/USDC", 0.08), opp("WETH/USDC", 0.12), opp("WBTC/USDC", 0.06)]);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].min_profit_usd, Some(0.10));
+        assert_eq!(kept[1].min_profit_usd, Some(0.05));
+    }
+}
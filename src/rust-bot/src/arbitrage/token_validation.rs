@@ -0,0 +1,581 @@
+//! Token Metadata Re-validation — decimals/symbol drift quarantine
+//!
+//! Purpose:
+//!     Token contracts change under us: proxies get upgraded, tickers get reused,
+//!     and on Polygon a token's decimals() has changed after an upgrade. Every
+//!     cached price, trade size and min_out assumes the decimals read at pool
+//!     sync, so a whitelist entry validated months ago can silently turn into a
+//!     mis-sized trade. This module re-reads token metadata periodically and
+//!     takes every pool touching a changed token out of trading until a human
+//!     approves it again.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Design:
+//!     - Token registry (data/{chain}/token_registry.json): symbol, decimals,
+//!       first_validated, last_validated per token. Seeded on first sight from
+//!       the on-chain symbol and the decimals the pool sync cached.
+//!     - Every TOKEN_REVALIDATION_HOURS hours (and once at startup), one
+//!       Multicall3 aggregate3 reads decimals() and symbol() for every token in
+//!       the pool universe. A match refreshes last_validated.
+//!     - Any decimals or symbol change quarantines the token: every route whose
+//!       pair includes it is filtered out of detection (same place canary
+//!       demotions are dropped), an error + webhook alert fires, and the change
+//!       is written to data/{chain}/token_quarantine.json.
+//!     - Re-approval: remove the token's entry from token_quarantine.json (or
+//!       delete the file). The file is polled every APPROVAL_POLL_BLOCKS blocks;
+//!       an approved token's registry record adopts the on-chain values and the
+//!       caller re-syncs the affected pools so cached decimals are refreshed.
+
+use super::migration::WatchedPool;
+use super::multicall_quoter::MulticallQuoter;
+use super::preflight::decode_word;
+use super::stranded::WatchedToken;
+use crate::types::{ArbitrageOpportunity, BotConfig};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// decimals()
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+/// symbol()
+const SYMBOL_SELECTOR: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+
+/// Blocks between re-reads of token_quarantine.json while anything is quarantined
+pub const APPROVAL_POLL_BLOCKS: u64 = 10;
+
+/// Re-validation cadence
+#[derive(Debug, Clone)]
+pub struct TokenValidationConfig {
+    /// Hours between metadata checks (0 = disabled)
+    pub interval_hours: u64,
+}
+
+impl TokenValidationConfig {
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        Self { interval_hours: config.token_revalidation_hours }
+    }
+}
+
+/// Last accepted metadata for one token (token_registry.json)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenRecord {
+    pub symbol: String,
+    pub decimals: u8,
+    pub first_validated: String,
+    pub last_validated: String,
+}
+
+/// A detected metadata change, as written to token_quarantine.json
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataChange {
+    pub detected_at: String,
+    pub block: u64,
+    pub token: Address,
+    pub cached_symbol: String,
+    pub cached_decimals: u8,
+    /// None = symbol() unreadable (decimals change alone triggered)
+    pub onchain_symbol: Option<String>,
+    pub onchain_decimals: u8,
+    /// Synced pools that include the token
+    pub pools: Vec<Address>,
+}
+
+impl MetadataChange {
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.cached_decimals != self.onchain_decimals {
+            parts.push(format!("decimals {} → {}", self.cached_decimals, self.onchain_decimals));
+        }
+        if let Some(ref sym) = self.onchain_symbol {
+            if *sym != self.cached_symbol {
+                parts.push(format!("symbol {} → {}", self.cached_symbol, sym));
+            }
+        }
+        parts.join(", ")
+    }
+}
+
+/// Outcome of one metadata check
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub block: u64,
+    pub tokens_checked: usize,
+    /// decimals() failed — token left as-is
+    pub unreadable: Vec<Address>,
+    /// Newly quarantined tokens
+    pub changes: Vec<MetadataChange>,
+}
+
+/// Periodic decimals()/symbol() re-validation with quarantine
+pub struct TokenMetadataValidator {
+    config: TokenValidationConfig,
+    registry: BTreeMap<Address, TokenRecord>,
+    quarantined: BTreeMap<Address, MetadataChange>,
+    last_check: Option<DateTime<Utc>>,
+    last_approval_poll: Option<u64>,
+    /// token_registry.json
+    registry_path: Option<PathBuf>,
+    /// token_quarantine.json (the re-approval flag file)
+    quarantine_path: Option<PathBuf>,
+    webhook_url: Option<String>,
+    client: reqwest::Client,
+}
+
+impl TokenMetadataValidator {
+    pub fn new(config: TokenValidationConfig) -> Self {
+        info!("Token re-validation: decimals()/symbol() every {}h", config.interval_hours);
+        Self {
+            config,
+            registry: BTreeMap::new(),
+            quarantined: BTreeMap::new(),
+            last_check: None,
+            last_approval_poll: None,
+            registry_path: None,
+            quarantine_path: None,
+            webhook_url: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Load and persist the registry and quarantine file under `data_dir`.
+    /// A quarantine survives restarts until the operator clears it.
+    pub fn with_persistence(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Token validation: failed to create {}: {}", data_dir, e);
+        }
+        let registry_path = dir.join("token_registry.json");
+        if let Ok(content) = std::fs::read_to_string(&registry_path) {
+            match serde_json::from_str(&content) {
+                Ok(registry) => self.registry = registry,
+                Err(e) => warn!("Token validation: ignoring unreadable {}: {}", registry_path.display(), e),
+            }
+        }
+        let quarantine_path = dir.join("token_quarantine.json");
+        if let Some(quarantined) = read_quarantine(&quarantine_path) {
+            for change in quarantined.values() {
+                warn!(
+                    "Token validation: {:?} ({}) still quarantined since {} — {}",
+                    change.token, change.cached_symbol, change.detected_at, change.describe()
+                );
+            }
+            self.quarantined = quarantined;
+        }
+        self.registry_path = Some(registry_path);
+        self.quarantine_path = Some(quarantine_path);
+        self
+    }
+
+    pub fn with_webhook(mut self, url: Option<String>) -> Self {
+        self.webhook_url = url;
+        self
+    }
+
+    /// True when a metadata check should run at `now`
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        let hours = self.config.interval_hours;
+        hours > 0
+            && self.last_check.is_none_or(|last| now >= last + chrono::Duration::hours(hours as i64))
+    }
+
+    pub fn registry(&self) -> &BTreeMap<Address, TokenRecord> {
+        &self.registry
+    }
+
+    pub fn is_quarantined(&self, token: &Address) -> bool {
+        self.quarantined.contains_key(token)
+    }
+
+    /// True if either side of the opportunity's pair is quarantined
+    pub fn is_quarantined_route(&self, opp: &ArbitrageOpportunity) -> bool {
+        self.is_quarantined(&opp.pair.token0) || self.is_quarantined(&opp.pair.token1)
+    }
+
+    /// decimals() and symbol() sub-calls, two per token in `tokens` order
+    pub(crate) fn build_sub_calls(tokens: &[WatchedToken]) -> Vec<(Address, Vec<u8>)> {
+        tokens.iter()
+            .flat_map(|t| [(t.address, DECIMALS_SELECTOR.to_vec()), (t.address, SYMBOL_SELECTOR.to_vec())])
+            .collect()
+    }
+
+    /// Read all token metadata in one aggregate3 and evaluate it
+    pub async fn check<M: Middleware + 'static>(
+        &mut self,
+        quoter: &MulticallQuoter<M>,
+        tokens: &[WatchedToken],
+        pools: &[WatchedPool],
+        block: u64,
+    ) -> Result<ValidationReport> {
+        let now = Utc::now();
+        // Mark up front so a failing RPC is retried next interval, not next block
+        self.last_check = Some(now);
+        if tokens.is_empty() {
+            return Ok(ValidationReport { block, ..Default::default() });
+        }
+        let results = quoter.aggregate3_at(&Self::build_sub_calls(tokens), None).await?;
+        Ok(self.evaluate(now, block, tokens, pools, &results))
+    }
+
+    /// Compare on-chain metadata to the registry; quarantine changed tokens
+    pub(crate) fn evaluate(
+        &mut self,
+        now: DateTime<Utc>,
+        block: u64,
+        tokens: &[WatchedToken],
+        pools: &[WatchedPool],
+        results: &[(bool, Vec<u8>)],
+    ) -> ValidationReport {
+        let mut report = ValidationReport { block, tokens_checked: tokens.len(), ..Default::default() };
+        let stamp = now.to_rfc3339();
+
+        for (token, calls) in tokens.iter().zip(results.chunks(2)) {
+            let decimals = match calls.first().and_then(decode_word) {
+                Some(d) if d <= U256::from(u8::MAX) => d.as_u32() as u8,
+                _ => {
+                    debug!("Token validation: decimals() failed for {} — skipped", token.symbol);
+                    report.unreadable.push(token.address);
+                    continue;
+                }
+            };
+            let symbol = calls.get(1).and_then(decode_symbol);
+            if self.is_quarantined(&token.address) {
+                continue;
+            }
+
+            // First sight: baseline is the decimals the pool sync cached
+            let record = self.registry.entry(token.address).or_insert_with(|| TokenRecord {
+                symbol: symbol.clone().unwrap_or_else(|| token.symbol.clone()),
+                decimals: token.decimals,
+                first_validated: stamp.clone(),
+                last_validated: stamp.clone(),
+            });
+            let symbol_changed = symbol.as_ref().is_some_and(|s| *s != record.symbol);
+            if decimals == record.decimals && !symbol_changed {
+                record.last_validated = stamp.clone();
+                continue;
+            }
+
+            let change = MetadataChange {
+                detected_at: stamp.clone(),
+                block,
+                token: token.address,
+                cached_symbol: record.symbol.clone(),
+                cached_decimals: record.decimals,
+                onchain_symbol: symbol,
+                onchain_decimals: decimals,
+                pools: pools.iter()
+                    .filter(|p| p.pair.token0 == token.address || p.pair.token1 == token.address)
+                    .map(|p| p.address)
+                    .collect(),
+            };
+            self.quarantined.insert(token.address, change.clone());
+            report.changes.push(change);
+        }
+
+        self.write_registry();
+        if !report.changes.is_empty() {
+            self.write_quarantine();
+        }
+        report
+    }
+
+    /// Error log + webhook for every newly quarantined token
+    pub async fn alert(&self, report: &ValidationReport) {
+        for change in &report.changes {
+            let msg = format!(
+                "🚨 TOKEN METADATA CHANGED {:?} ({}): {} at block {} — {} pool(s) quarantined; \
+                 remove the entry from token_quarantine.json to re-approve",
+                change.token, change.cached_symbol, change.describe(), change.block, change.pools.len()
+            );
+            error!("{}", msg);
+            self.post_webhook(&msg).await;
+        }
+    }
+
+    /// Re-read token_quarantine.json (every APPROVAL_POLL_BLOCKS blocks while
+    /// anything is quarantined). Tokens whose entry the operator removed are
+    /// released; their registry record adopts the on-chain metadata. Returns
+    /// the released changes so the caller can re-sync the affected pools.
+    pub fn poll_approvals(&mut self, block: u64) -> Vec<MetadataChange> {
+        if self.quarantined.is_empty()
+            || self.last_approval_poll.is_some_and(|last| block < last + APPROVAL_POLL_BLOCKS)
+        {
+            return Vec::new();
+        }
+        self.last_approval_poll = Some(block);
+        let path = match self.quarantine_path {
+            Some(ref p) => p,
+            None => return Vec::new(),
+        };
+        let still_quarantined = if path.exists() {
+            match read_quarantine(path) {
+                Some(q) => q,
+                // Unreadable file (mid-edit?) — keep everything quarantined
+                None => return Vec::new(),
+            }
+        } else {
+            BTreeMap::new()
+        };
+
+        let approved: Vec<Address> = self.quarantined.keys()
+            .filter(|t| !still_quarantined.contains_key(t))
+            .copied()
+            .collect();
+        let now = Utc::now().to_rfc3339();
+        let mut released = Vec::new();
+        for token in approved {
+            let change = match self.quarantined.remove(&token) {
+                Some(c) => c,
+                None => continue,
+            };
+            let record = self.registry.entry(token).or_insert_with(|| TokenRecord {
+                symbol: change.cached_symbol.clone(),
+                decimals: change.cached_decimals,
+                first_validated: now.clone(),
+                last_validated: now.clone(),
+            });
+            record.decimals = change.onchain_decimals;
+            if let Some(ref sym) = change.onchain_symbol {
+                record.symbol = sym.clone();
+            }
+            record.last_validated = now.clone();
+            info!(
+                "Token validation: {:?} re-approved ({}) — {} pool(s) released",
+                token, change.describe(), change.pools.len()
+            );
+            released.push(change);
+        }
+        if !released.is_empty() {
+            self.write_registry();
+        }
+        released
+    }
+
+    fn write_registry(&self) {
+        if let Some(ref path) = self.registry_path {
+            write_json(path, &self.registry);
+        }
+    }
+
+    fn write_quarantine(&self) {
+        if let Some(ref path) = self.quarantine_path {
+            write_json(path, &self.quarantined);
+        }
+    }
+
+    async fn post_webhook(&self, content: &str) {
+        let url = match self.webhook_url {
+            Some(ref u) => u,
+            None => return,
+        };
+        let body = serde_json::json!({ "content": content });
+        let sent = self.client.post(url)
+            .json(&body)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await;
+        if let Err(e) = sent {
+            warn!("Token validation: webhook alert failed: {}", e);
+        }
+    }
+}
+
+fn read_quarantine(path: &PathBuf) -> Option<BTreeMap<Address, MetadataChange>> {
+    let content = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(q) => Some(q),
+        Err(e) => {
+            warn!("Token validation: unreadable {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn write_json<T: Serialize>(path: &PathBuf, value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(path, json) {
+                warn!("Token validation: failed to write {}: {}", path.display(), e);
+            }
+        }
+        Err(e) => warn!("Token validation: failed to serialize {}: {}", path.display(), e),
+    }
+}
+
+/// symbol() result: ABI string, or bytes32 for old tokens (MKR-style)
+pub(crate) fn decode_symbol(result: &(bool, Vec<u8>)) -> Option<String> {
+    let (success, data) = result;
+    if !success {
+        return None;
+    }
+    let raw = if data.len() >= 64 && U256::from_big_endian(&data[..32]) == U256::from(32) {
+        let len = U256::from_big_endian(&data[32..64]);
+        if len > U256::from(data.len() - 64) {
+            return None;
+        }
+        &data[64..64 + len.as_usize()]
+    } else if data.len() == 32 {
+        let end = data.iter().position(|b| *b == 0).unwrap_or(32);
+        &data[..end]
+    } else {
+        return None;
+    };
+    String::from_utf8(raw.to_vec()).ok().filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+    use ethers::abi::{self, Token};
+
+    fn token(n: u64, symbol: &str, decimals: u8) -> WatchedToken {
+        WatchedToken { address: Address::from_low_u64_be(n), symbol: symbol.to_string(), decimals, price_usd: None }
+    }
+
+    fn pool(n: u64, t0: u64, t1: u64) -> WatchedPool {
+        WatchedPool {
+            address: Address::from_low_u64_be(n),
+            dex: DexType::UniswapV3_005,
+            pair: TradingPair::new(Address::from_low_u64_be(t0), Address::from_low_u64_be(t1), "X/Y".to_string()),
+            fee: 500,
+        }
+    }
+
+    /// decimals() + symbol() results for one token
+    fn metadata(decimals: u8, symbol: &str) -> Vec<(bool, Vec<u8>)> {
+        vec![
+            (true, abi::encode(&[Token::Uint(U256::from(decimals))])),
+            (true, abi::encode(&[Token::String(symbol.to_string())])),
+        ]
+    }
+
+    fn opp_on(t0: u64, t1: u64) -> ArbitrageOpportunity {
+        ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(t0), Address::from_low_u64_be(t1), "X/Y".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            1.0,
+            1.01,
+            U256::from(1_000_000u64),
+        )
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dexarb_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn validator(dir: &std::path::Path) -> TokenMetadataValidator {
+        TokenMetadataValidator::new(TokenValidationConfig { interval_hours: 6 })
+            .with_persistence(dir.to_str().unwrap())
+    }
+
+    #[test]
+    fn test_decode_symbol_string_and_bytes32() {
+        assert_eq!(decode_symbol(&(true, abi::encode(&[Token::String("WETH".into())]))), Some("WETH".into()));
+        let mut word = [0u8; 32];
+        word[..3].copy_from_slice(b"MKR");
+        assert_eq!(decode_symbol(&(true, word.to_vec())), Some("MKR".into()));
+        assert_eq!(decode_symbol(&(false, Vec::new())), None);
+        assert_eq!(decode_symbol(&(true, vec![0u8; 5])), None);
+    }
+
+    #[test]
+    fn test_is_due_every_interval() {
+        let mut v = TokenMetadataValidator::new(TokenValidationConfig { interval_hours: 6 });
+        let t0 = Utc::now();
+        assert!(v.is_due(t0));
+        v.last_check = Some(t0);
+        assert!(!v.is_due(t0 + chrono::Duration::hours(5)));
+        assert!(v.is_due(t0 + chrono::Duration::hours(6)));
+        let off = TokenMetadataValidator::new(TokenValidationConfig { interval_hours: 0 });
+        assert!(!off.is_due(t0));
+    }
+
+    #[test]
+    fn test_decimals_change_quarantines_until_reapproved() {
+        let dir = temp_dir("token_validation");
+        let mut v = validator(&dir);
+        let tokens = [token(1, "WBTC", 8), token(2, "USDC", 6)];
+        let pools = [pool(100, 1, 2), pool(101, 1, 3), pool(102, 2, 3)];
+        let now = Utc::now();
+
+        // First check seeds the registry from the synced decimals
+        let results: Vec<_> = metadata(8, "WBTC").into_iter().chain(metadata(6, "USDC")).collect();
+        let report = v.evaluate(now, 1000, &tokens, &pools, &results);
+        assert!(report.changes.is_empty());
+        assert_eq!(v.registry()[&tokens[0].address].decimals, 8);
+        assert!(dir.join("token_registry.json").exists());
+
+        // Proxy upgrade: WBTC now reports 18 decimals
+        let results: Vec<_> = metadata(18, "WBTC").into_iter().chain(metadata(6, "USDC")).collect();
+        let report = v.evaluate(now, 2000, &tokens, &pools, &results);
+        assert_eq!(report.changes.len(), 1);
+        let change = &report.changes[0];
+        assert_eq!((change.cached_decimals, change.onchain_decimals), (8, 18));
+        assert_eq!(change.pools, vec![Address::from_low_u64_be(100), Address::from_low_u64_be(101)]);
+        assert!(v.is_quarantined_route(&opp_on(1, 2)));
+        assert!(v.is_quarantined_route(&opp_on(3, 1)));
+        assert!(!v.is_quarantined_route(&opp_on(2, 3)));
+        // Registry still holds the old, approved values
+        assert_eq!(v.registry()[&tokens[0].address].decimals, 8);
+
+        // Repeated checks don't re-alert
+        assert!(v.evaluate(now, 2100, &tokens, &pools, &results).changes.is_empty());
+
+        // Quarantine survives a restart
+        let mut v = validator(&dir);
+        assert!(v.is_quarantined(&tokens[0].address));
+
+        // File untouched → still quarantined
+        assert!(v.poll_approvals(2200).is_empty());
+        assert!(v.is_quarantined(&tokens[0].address));
+
+        // Operator clears the entry → released, registry adopts on-chain decimals
+        std::fs::write(dir.join("token_quarantine.json"), "{}").unwrap();
+        assert!(v.poll_approvals(2205).is_empty()); // within APPROVAL_POLL_BLOCKS
+        let released = v.poll_approvals(2200 + APPROVAL_POLL_BLOCKS);
+        assert_eq!(released.len(), 1);
+        assert!(!v.is_quarantined_route(&opp_on(1, 2)));
+        assert_eq!(v.registry()[&tokens[0].address].decimals, 18);
+
+        // New value is the baseline now
+        assert!(v.evaluate(now, 3000, &tokens, &pools, &results).changes.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_symbol_change_and_unreadable_tokens() {
+        let mut v = TokenMetadataValidator::new(TokenValidationConfig { interval_hours: 6 });
+        let tokens = [token(1, "MATIC", 18), token(2, "USDC", 6)];
+        let now = Utc::now();
+        let results: Vec<_> = metadata(18, "MATIC").into_iter().chain(metadata(6, "USDC")).collect();
+        v.evaluate(now, 1, &tokens, &[], &results);
+
+        // Ticker reused: same decimals, new symbol; USDC unreadable this round
+        let results: Vec<_> = metadata(18, "POL").into_iter()
+            .chain([(false, Vec::new()), (false, Vec::new())])
+            .collect();
+        let report = v.evaluate(now, 2, &tokens, &[], &results);
+        assert_eq!(report.unreadable, vec![tokens[1].address]);
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(report.changes[0].describe(), "symbol MATIC → POL");
+        assert!(v.is_quarantined(&tokens[0].address));
+        assert!(!v.is_quarantined(&tokens[1].address));
+
+        // Seed mismatch: pool sync cached 6 decimals but chain says 18
+        let mut v = TokenMetadataValidator::new(TokenValidationConfig { interval_hours: 6 });
+        let report = v.evaluate(now, 3, &[token(5, "FOO", 6)], &[], &metadata(18, "FOO"));
+        assert_eq!(report.changes[0].describe(), "decimals 6 → 18");
+    }
+}
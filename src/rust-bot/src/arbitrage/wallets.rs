@@ -0,0 +1,293 @@
+//! Multi-Wallet Submission — per-wallet nonce, balances and in-flight txs
+//!
+//! One wallet is one nonce sequence: the parallel path can only ladder
+//! nonces, and a tx stuck in the mempool holds up everything queued behind
+//! it. With PRIVATE_KEYS (comma-separated, backward compatible with the
+//! single PRIVATE_KEY) the executor signs for several funded wallets, each
+//! with its own:
+//!   - signer (wallet-wrapped WS client, built once)
+//!   - locally tracked nonce (initialized from the pending count on first use)
+//!   - quote token balance cache and exposure pacer (balance.rs, exposure.rs)
+//!   - in-flight txs (sent, receipt not yet settled)
+//!
+//! Every submission selects its wallet: the next idle one (nothing in flight)
+//! round-robin; when all are busy, the one with the fewest txs in flight, so
+//! the new tx queues behind that wallet's pending nonces. A parallel batch
+//! therefore spreads across wallets before it ladders nonces within one.
+//!
+//! ArbExecutor pulls from and pays back to msg.sender, so each wallet trades
+//! its own balance. Extra wallets must be allowed by the contract owner
+//! (setOperator) and need their own allowances (--approvals runs per
+//! wallet). Owner-only calls (rescueTokens) and inventory rebalances stay on
+//! the primary wallet, the first key.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use super::balance::{BalanceConfig, BalanceTracker};
+use super::connections::SigningClient;
+use super::exposure::{ExposureConfig, ExposurePacer};
+use crate::types::BotConfig;
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Every wallet key, primary first: PRIVATE_KEYS (comma-separated) with
+/// PRIVATE_KEY leading when it isn't already listed; duplicates dropped
+pub fn parse_private_keys(single: Option<&str>, list: Option<&str>) -> Result<Vec<String>> {
+    let mut keys: Vec<String> = Vec::new();
+    let listed = list.unwrap_or_default().split(',').map(str::trim);
+    for key in single.map(str::trim).into_iter().chain(listed) {
+        if !key.is_empty() && !keys.iter().any(|k| k == key) {
+            keys.push(key.to_string());
+        }
+    }
+    if keys.is_empty() {
+        return Err(anyhow!("no wallet key: set PRIVATE_KEY or PRIVATE_KEYS"));
+    }
+    Ok(keys)
+}
+
+/// One wallet's signing state
+pub struct WalletSlot<M: Middleware> {
+    signer: Arc<SigningClient<M>>,
+    /// Locally tracked next nonce; None until initialized (or after a resync reset)
+    nonce: Option<u64>,
+    /// Sent txs whose receipt hasn't been settled
+    in_flight: HashSet<TxHash>,
+    balances: BalanceTracker,
+    exposure: ExposurePacer,
+}
+
+impl<M: Middleware> WalletSlot<M> {
+    pub fn new(signer: Arc<SigningClient<M>>, config: &BotConfig) -> Self {
+        Self {
+            signer,
+            nonce: None,
+            in_flight: HashSet::new(),
+            balances: BalanceTracker::new(BalanceConfig::from_bot_config(config)),
+            exposure: ExposurePacer::new(ExposureConfig::from_bot_config(config)),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    /// Shared signing client (cheap Arc clone)
+    pub fn signer(&self) -> Arc<SigningClient<M>> {
+        self.signer.clone()
+    }
+
+    pub fn tracked_nonce(&self) -> Option<u64> {
+        self.nonce
+    }
+
+    pub fn set_nonce(&mut self, nonce: u64) {
+        self.nonce = Some(nonce);
+    }
+
+    /// A tx went out on the tracked nonce
+    pub fn advance_nonce(&mut self) {
+        if let Some(nonce) = self.nonce.as_mut() {
+            *nonce += 1;
+        }
+    }
+
+    /// Re-read the nonce from chain before the next tx
+    pub fn reset_nonce(&mut self) {
+        self.nonce = None;
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    pub fn balances(&self) -> &BalanceTracker {
+        &self.balances
+    }
+
+    pub fn balances_mut(&mut self) -> &mut BalanceTracker {
+        &mut self.balances
+    }
+
+    pub fn exposure(&self) -> &ExposurePacer {
+        &self.exposure
+    }
+
+    pub fn exposure_mut(&mut self) -> &mut ExposurePacer {
+        &mut self.exposure
+    }
+}
+
+/// The executor's wallets; the active one signs the current submission
+pub struct WalletPool<M: Middleware> {
+    slots: Vec<WalletSlot<M>>,
+    active: usize,
+    /// Where the next round-robin selection starts
+    next: usize,
+}
+
+impl<M: Middleware> WalletPool<M> {
+    /// Pool of the primary wallet alone
+    pub fn new(primary: WalletSlot<M>) -> Self {
+        Self { slots: vec![primary], active: 0, next: 0 }
+    }
+
+    pub fn add(&mut self, slot: WalletSlot<M>) {
+        self.slots.push(slot);
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Txs awaiting settlement across every wallet
+    pub fn in_flight(&self) -> usize {
+        self.slots.iter().map(WalletSlot::in_flight).sum()
+    }
+
+    pub fn addresses(&self) -> Vec<Address> {
+        self.slots.iter().map(WalletSlot::address).collect()
+    }
+
+    pub fn primary_address(&self) -> Address {
+        self.slots[0].address()
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active(&self) -> &WalletSlot<M> {
+        &self.slots[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut WalletSlot<M> {
+        &mut self.slots[self.active]
+    }
+
+    /// Sign with wallet `index` (0 = primary) until the next selection
+    pub fn activate(&mut self, index: usize) {
+        assert!(index < self.slots.len(), "wallet {} of {}", index, self.slots.len());
+        self.active = index;
+    }
+
+    /// Wallet for the next submission: the first idle one from the
+    /// round-robin cursor, else the one with the fewest txs in flight
+    /// (lowest index on a tie). Becomes the active wallet.
+    pub fn select(&mut self) -> usize {
+        let n = self.slots.len();
+        let idle = (0..n).map(|i| (self.next + i) % n).find(|&i| self.slots[i].in_flight.is_empty());
+        let chosen = idle.unwrap_or_else(|| {
+            (0..n).min_by_key(|&i| self.slots[i].in_flight.len()).unwrap_or(0)
+        });
+        self.active = chosen;
+        self.next = (chosen + 1) % n;
+        chosen
+    }
+
+    /// The active wallet sent `tx_hash` on its tracked nonce
+    pub fn on_sent(&mut self, tx_hash: TxHash) {
+        let slot = self.active_mut();
+        slot.advance_nonce();
+        slot.in_flight.insert(tx_hash);
+    }
+
+    /// `tx_hash`'s receipt is being settled: no longer in flight, and its
+    /// wallet becomes the active one (tax record, balance cache). None =
+    /// not a tracked tx (the active wallet is kept).
+    pub fn settle(&mut self, tx_hash: TxHash) -> Option<Address> {
+        let index = self.slots.iter().position(|slot| slot.in_flight.contains(&tx_hash))?;
+        self.slots[index].in_flight.remove(&tx_hash);
+        self.active = index;
+        Some(self.slots[index].address())
+    }
+
+    pub fn slots_mut(&mut self) -> impl Iterator<Item = &mut WalletSlot<M>> {
+        self.slots.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+
+    const KEYS: [&str; 3] = [
+        "0x0123456789012345678901234567890123456789012345678901234567890123",
+        "0x1123456789012345678901234567890123456789012345678901234567890123",
+        "0x2123456789012345678901234567890123456789012345678901234567890123",
+    ];
+
+    fn wallet_pool(wallets: usize) -> WalletPool<Provider<MockProvider>> {
+        let (provider, _mock) = Provider::mocked();
+        let provider = Arc::new(provider);
+        let config = create_test_config();
+        let slot = |key: &str| {
+            let wallet: LocalWallet = key.parse().unwrap();
+            WalletSlot::new(Arc::new(SignerMiddleware::new(provider.clone(), wallet)), &config)
+        };
+        let mut pool = WalletPool::new(slot(KEYS[0]));
+        for key in &KEYS[1..wallets] {
+            pool.add(slot(key));
+        }
+        pool
+    }
+
+    #[test]
+    fn test_round_robin_selection() {
+        let mut pool = wallet_pool(3);
+        let picks: Vec<usize> = (0..7).map(|_| pool.select()).collect();
+        assert_eq!(picks, vec![0, 1, 2, 0, 1, 2, 0]);
+
+        // One wallet: always the primary
+        let mut single = wallet_pool(1);
+        assert_eq!((0..3).map(|_| single.select()).collect::<Vec<_>>(), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_busy_wallets_skipped_then_least_loaded() {
+        let mut pool = wallet_pool(3);
+        let tx = TxHash::from_low_u64_be;
+
+        // A parallel batch: each pick lands on a different wallet first
+        for n in 0..3 {
+            let wallet = pool.select();
+            assert_eq!(wallet, n);
+            pool.on_sent(tx(n as u64));
+        }
+        // All busy: queue on the wallet with the fewest in flight
+        assert_eq!(pool.select(), 0);
+        pool.on_sent(tx(10));
+        assert_eq!(pool.select(), 1);
+        pool.on_sent(tx(11));
+
+        // Wallet 2 settles: the only idle wallet is picked whatever the cursor
+        assert_eq!(pool.settle(tx(2)), Some(pool.addresses()[2]));
+        assert_eq!(pool.active_index(), 2);
+        pool.activate(0);
+        assert_eq!(pool.select(), 2);
+        assert_eq!(pool.select(), 2);
+
+        // Unknown tx: nothing settles, active wallet kept
+        assert_eq!(pool.settle(tx(99)), None);
+        assert_eq!(pool.active_index(), 2);
+        assert_eq!(pool.slots.iter().map(WalletSlot::in_flight).collect::<Vec<_>>(), vec![2, 2, 0]);
+    }
+
+    #[test]
+    fn test_parse_private_keys() {
+        assert_eq!(parse_private_keys(Some("a"), None).unwrap(), vec!["a"]);
+        assert_eq!(parse_private_keys(None, Some("a, b ,c")).unwrap(), vec!["a", "b", "c"]);
+        // PRIVATE_KEY leads; listed twice counts once
+        assert_eq!(parse_private_keys(Some("b"), Some("a,b,,a")).unwrap(), vec!["b", "a"]);
+        assert!(parse_private_keys(None, Some(" , ")).is_err());
+        assert!(parse_private_keys(Some(""), None).is_err());
+    }
+}
@@ -0,0 +1,308 @@
+//! WBTC/USDC (8 vs 6 decimals) end-to-end pipeline tests
+//!
+//! Runs one synthetic WBTC/USDC opportunity through every stage that touches
+//! token decimals and checks each intermediate value against hand-computed
+//! expectations:
+//!   detection → min_out (both legs) → executeArb calldata →
+//!   ArbExecuted receipt parsing → tax record amounts
+//!
+//! Polygon ordering: WBTC (0x1bfd…) < USDC.e (0x2791…), so the V3 pool has
+//! token0 = WBTC (8 dec), token1 = USDC (6 dec) and quote_token_is_token0 = false.
+//! This is the orientation most 6-vs-18 tests never exercise.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Both legs' pool liquidity recorded by the detector
+
+use super::detector::tests::create_test_config;
+use super::detector::OpportunityDetector;
+use super::executor::{
+    execute_arb_args, min_out_with_slippage, parse_arb_executed, tax_amounts, ExecuteArbCall,
+};
+use super::liquidity_guard::{liquidity_fraction, Leg};
+use super::multicall_quoter::MulticallQuoter;
+use crate::pool::PoolStateManager;
+use crate::tax::{PriceOracle, TaxRecordBuilder};
+use crate::types::{
+    ArbitrageOpportunity, DexType, LegSnapshots, PoolStateSnapshot, TradingPairConfig, TradingPair,
+    V3PoolState,
+};
+use ethers::abi::{AbiDecode, AbiEncode};
+use ethers::prelude::*;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+const WBTC: &str = "0x1BFD67037B42Cf73acF2047067bd4F2C47D9BfD6";
+const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+
+/// 1.0001^66850 * 10^(8-6) ≈ 80,004.32 USDC per WBTC
+const TICK_CHEAP: i32 = 66_850;
+/// 200 ticks higher ≈ 81,620.44 USDC per WBTC (+2.02%)
+const TICK_RICH: i32 = 67_050;
+
+fn addr(s: &str) -> Address {
+    Address::from_str(s).unwrap()
+}
+
+fn wbtc_pool(pool: u64, dex: DexType, fee: u32, tick: i32) -> V3PoolState {
+    V3PoolState {
+        address: Address::from_low_u64_be(pool),
+        dex,
+        pair: TradingPair::new(addr(WBTC), addr(USDC), "WBTC/USDC".to_string()),
+        sqrt_price_x96: U256::zero(),
+        tick,
+        fee,
+        liquidity: 1_000_000_000_000_000,
+        token0_decimals: 8,
+        token1_decimals: 6,
+        last_updated: 100,
+    }
+}
+
+/// Stage 1: detection over two V3 pools (0.05% cheap, 0.30% rich)
+fn detect() -> ArbitrageOpportunity {
+    let mut config = create_test_config();
+    config.pairs = vec![TradingPairConfig {
+        token0: WBTC.to_string(),
+        token1: USDC.to_string(),
+        symbol: "WBTC/USDC".to_string(),
+    }];
+
+    let state_manager = PoolStateManager::new();
+    state_manager.update_v3_pool(wbtc_pool(1, DexType::UniswapV3_005, 500, TICK_CHEAP));
+    state_manager.update_v3_pool(wbtc_pool(2, DexType::UniswapV3_030, 3000, TICK_RICH));
+
+    let detector = OpportunityDetector::new(config, state_manager);
+    let mut opps = detector.scan_opportunities();
+    assert_eq!(opps.len(), 1, "exactly one WBTC/USDC route expected");
+    opps.remove(0)
+}
+
+fn assert_close(actual: f64, expected: f64, tol: f64, what: &str) {
+    assert!(
+        (actual - expected).abs() <= tol,
+        "{}: expected {} ± {}, got {}",
+        what, expected, tol, actual
+    );
+}
+
+fn assert_raw_close(actual: U256, expected: u128, what: &str) {
+    let a = actual.as_u128();
+    assert!(
+        a.abs_diff(expected) <= 1,
+        "{}: expected {} ± 1 raw, got {}",
+        what, expected, a
+    );
+}
+
+#[test]
+fn test_wbtc_detection_math() {
+    let opp = detect();
+
+    // Quote = token1 → buy where the price (USDC per WBTC) is lower
+    assert!(!opp.quote_token_is_token0);
+    assert_eq!(opp.buy_dex, DexType::UniswapV3_005);
+    assert_eq!(opp.sell_dex, DexType::UniswapV3_030);
+    assert_eq!(opp.token0_decimals, 8);
+    assert_eq!(opp.token1_decimals, 6);
+    assert_eq!(opp.quote_decimals(), 6);
+    assert_eq!(opp.base_decimals(), 8);
+
+    assert_close(opp.buy_price, 80_004.323_7, 1e-3, "buy_price");
+    assert_close(opp.sell_price, 81_620.436_6, 1e-3, "sell_price");
+
+    // midmarket 2.0200% - round trip 0.35% = 1.6700% executable
+    assert_close(opp.spread_percent, 1.670_032, 1e-5, "spread_percent");
+    // 500 * 1.6700% = 8.3502 gross - 0.05 gas - 1% slippage = 8.2167
+    assert_close(opp.estimated_profit, 8.216_658, 1e-5, "estimated_profit");
+
+    // $500 at USDC's 6 decimals — NOT at WBTC's 8
+    assert_eq!(opp.trade_size.raw(), U256::from(500_000_000u64));
+    assert_eq!(opp.trade_size.to_string(), "500.000000 USDC");
+    assert_eq!(opp.trade_size.token(), opp.quote());
+}
+
+#[test]
+fn test_wbtc_detection_snapshots() {
+    let opp = detect();
+    let snaps = opp.pool_snapshots.clone().expect("detector attaches pool snapshots");
+
+    // Each leg's snapshot is the exact state the detector priced from
+    let cheap = wbtc_pool(1, DexType::UniswapV3_005, 500, TICK_CHEAP);
+    let rich = wbtc_pool(2, DexType::UniswapV3_030, 3000, TICK_RICH);
+    assert_eq!(snaps.buy, PoolStateSnapshot::from_v3(&cheap, snaps.buy.captured_at));
+    assert_eq!(snaps.sell, PoolStateSnapshot::from_v3(&rich, snaps.sell.captured_at));
+    assert_eq!(snaps.buy.block, 100);
+    assert_eq!(snaps.buy.captured_at, snaps.sell.captured_at);
+    assert_eq!(Some(snaps.buy.address), opp.buy_pool_address);
+    assert_eq!(Some(snaps.sell.address), opp.sell_pool_address);
+
+    // Survives the JSON audit trail unchanged
+    let json = serde_json::to_string(&snaps).unwrap();
+    let back: LegSnapshots = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, snaps);
+}
+
+#[test]
+fn test_wbtc_detection_records_both_leg_liquidity() {
+    let mut config = create_test_config();
+    config.pairs = vec![TradingPairConfig {
+        token0: WBTC.to_string(),
+        token1: USDC.to_string(),
+        symbol: "WBTC/USDC".to_string(),
+    }];
+    let state_manager = PoolStateManager::new();
+    state_manager.update_v3_pool(wbtc_pool(1, DexType::UniswapV3_005, 500, TICK_CHEAP));
+    let mut rich = wbtc_pool(2, DexType::UniswapV3_030, 3000, TICK_RICH);
+    rich.liquidity = 40_000_000_000_000;
+    state_manager.update_v3_pool(rich);
+
+    let opp = OpportunityDetector::new(config, state_manager).scan_opportunities().remove(0);
+    assert_eq!(opp.buy_pool_liquidity, Some(1_000_000_000_000_000));
+    assert_eq!(opp.sell_pool_liquidity, Some(40_000_000_000_000));
+
+    // Sell leg (quote = token1, 6 dec): y = L·√(81,620 × 10^-2) ≈ 1.14e15 raw
+    // ≈ $1.14B virtual depth → $500 is a vanishing fraction
+    let fraction = liquidity_fraction(&opp, Leg::Sell).unwrap();
+    assert_close(fraction, 500e6 / (4e13 * (opp.sell_price * 1e-2).sqrt()), 1e-12, "sell fraction");
+}
+
+#[test]
+fn test_wbtc_min_out_both_legs() {
+    let opp = detect();
+    let slippage = 0.5; // create_test_config max_slippage_percent
+
+    // Buy leg: 500 USDC → WBTC at 1/80,004.32 = 0.00624966 WBTC
+    // * 0.995 = 0.00621841 WBTC = 621,841 sats (8 decimals)
+    let buy_min = min_out_with_slippage(
+        opp.trade_size.raw(), opp.buy_leg_price(), opp.quote_decimals(), opp.base_decimals(), slippage,
+    );
+    assert_raw_close(buy_min, 621_841, "buy leg min_out (sats)");
+
+    // Pre-screen estimate (no slippage haircut) uses the same orientation
+    let estimate = MulticallQuoter::<Provider<Http>>::estimate_buy_output(&opp);
+    assert_raw_close(estimate, 624_966, "estimate_buy_output (sats)");
+
+    // Sell leg: 624,966 sats = 0.00624966 WBTC * 81,620.44 = 510.0999 USDC
+    // * 0.995 = 507.549478 USDC = 507,549,478 raw (6 decimals)
+    let sell_min = min_out_with_slippage(
+        estimate, opp.sell_leg_price(), opp.base_decimals(), opp.quote_decimals(), slippage,
+    );
+    assert_raw_close(sell_min, 507_549_478, "sell leg min_out (USDC raw)");
+}
+
+#[test]
+fn test_wbtc_execute_arb_calldata() {
+    let opp = detect();
+    let router = Address::from_low_u64_be(0xAAAA);
+    let args = execute_arb_args(&opp, router, router, 5.0);
+
+    // Contract token0 = quote (USDC) even though the pool's token0 is WBTC
+    assert_eq!(args.token_0, addr(USDC));
+    assert_eq!(args.token_1, addr(WBTC));
+    assert_eq!(args.fee_buy, 500);
+    assert_eq!(args.fee_sell, 3000);
+    assert_eq!(args.amount_in, U256::from(500_000_000u64));
+    // $5 minProfit at 6 decimals
+    assert_eq!(args.min_profit, U256::from(5_000_000u64));
+
+    let calldata = args.clone().encode();
+    let selector = &ethers::utils::keccak256(
+        b"executeArb(address,address,address,address,uint24,uint24,uint256,uint256)",
+    )[..4];
+    assert_eq!(&calldata[..4], selector);
+    assert_eq!(calldata.len(), 4 + 8 * 32);
+
+    let word = |i: usize| U256::from_big_endian(&calldata[4 + i * 32..4 + (i + 1) * 32]);
+    assert_eq!(word(0), U256::from_big_endian(addr(USDC).as_bytes()));
+    assert_eq!(word(4), U256::from(500u64));
+    assert_eq!(word(5), U256::from(3000u64));
+    assert_eq!(word(6), U256::from(500_000_000u64));
+    assert_eq!(word(7), U256::from(5_000_000u64));
+
+    assert_eq!(ExecuteArbCall::decode(&calldata).unwrap(), args);
+}
+
+#[test]
+fn test_wbtc_arb_executed_parsing() {
+    let opp = detect();
+    let arb_address = Address::from_low_u64_be(0xBEEF);
+    let topic0: H256 = ethers::utils::keccak256(
+        b"ArbExecuted(address,address,uint256,uint256,uint256,address,address)",
+    )
+    .into();
+
+    // amountIn 500 USDC | amountOut 508.216 USDC | profit 8.216 USDC | routers
+    let mut data = Vec::new();
+    for word in [500_000_000u64, 508_216_000, 8_216_000, 0xAAAA, 0xAAAA] {
+        data.extend_from_slice(&U256::from(word).encode());
+    }
+    let unrelated = Log {
+        address: addr(USDC),
+        topics: vec![H256::zero()],
+        data: Bytes::from(vec![0u8; 96]),
+        ..Default::default()
+    };
+    let arb_log = Log {
+        address: arb_address,
+        topics: vec![topic0],
+        data: Bytes::from(data),
+        ..Default::default()
+    };
+
+    let (amount_out, profit_raw) =
+        parse_arb_executed(&[unrelated, arb_log], arb_address).expect("ArbExecuted present");
+    assert_eq!(amount_out, U256::from(508_216_000u64));
+    assert_eq!(profit_raw, U256::from(8_216_000u64));
+
+    // Profit is quote raw → /1e6, not /1e8 (WBTC) or /1e18 (old wei_to_usd)
+    assert_eq!(opp.quote_to_usd(opp.quote_amount(profit_raw)), 8.216);
+
+    // No matching log → caller falls back
+    assert!(parse_arb_executed(&[], arb_address).is_none());
+}
+
+#[test]
+fn test_wbtc_tax_record_amounts() {
+    let opp = detect();
+    let (asset, sent, received) =
+        tax_amounts(&opp, opp.trade_size.raw(), U256::from(508_216_000u64));
+
+    assert_eq!(asset, "USDC");
+    assert_eq!(sent, Decimal::from_str("500.000000").unwrap());
+    assert_eq!(received, Decimal::from_str("508.216000").unwrap());
+
+    let builder = TaxRecordBuilder::with_oracle(PriceOracle::new("/nonexistent/path"));
+    let record = builder
+        .build_arbitrage_record(
+            &asset,
+            sent,
+            &asset,
+            received,
+            Decimal::from_str("0.02").unwrap(),
+            Decimal::from_str("0.0005").unwrap(),
+            "0xabc".to_string(),
+            100,
+            "0xwallet".to_string(),
+            opp.buy_dex.to_string(),
+            opp.sell_dex.to_string(),
+            String::new(),
+            String::new(),
+            Decimal::ZERO,
+            false,
+        )
+        .unwrap()
+        .with_pool_snapshots(opp.pool_snapshots.clone());
+
+    assert_eq!(record.token_sent_decimals, 6);
+    assert_eq!(record.token_received_decimals, 6);
+    // USDC = $1: USD values equal the human amounts (not 5e8 / 95,000 × raw)
+    assert_eq!(record.usd_value_sent, Decimal::from_str("500").unwrap());
+    assert_eq!(record.usd_value_received, Decimal::from_str("508.216").unwrap());
+
+    // JSON backup carries the detection-time pool state
+    let json = serde_json::to_string(&record).unwrap();
+    let back: crate::tax::TaxRecord = serde_json::from_str(&json).unwrap();
+    assert!(back.pool_snapshots.is_some());
+    assert_eq!(back.pool_snapshots, opp.pool_snapshots);
+}
@@ -0,0 +1,613 @@
+//! Backtest — replay PriceLogger CSVs through the detector
+//!
+//! Reads the daily prices_YYYYMMDD.csv(.gz) files written by PriceLogger, rebuilds
+//! each logged block's V3PoolState set in a PoolStateManager and runs
+//! OpportunityDetector::scan_opportunities() once per block for every
+//! strategy (paper trading presets or a paper_trading.toml). No RPC.
+//!
+//! The price log carries no token addresses or decimals: tokens come from
+//! TRADING_PAIRS (by pair symbol, sorted into V3 token0 < token1 order),
+//! the quote token's decimals from TOKEN_DECIMALS, and the other side's from
+//! the logged price/tick pair (price = 1.0001^tick * 10^(dec0 - dec1)). Only
+//! V3 pools are logged, so V2 legs never appear in a replay.
+//!
+//! Fill model: each block the strategy fills its best opportunity at the
+//! detector's executable spread (fees already out) less FillModel slippage
+//! and gas. A route that stays best over consecutive blocks is one persisting
+//! spread and is filled once. Paper trading risk limits (daily trades, daily
+//! loss, consecutive losses) stop fills as they would live.
+//!
+//! Log gaps: PriceLogger drops whole blocks under disk backpressure and the
+//! bot has downtime, so block numbers may skip — gaps are counted, and state
+//! is never carried across one into a persisting-route fill. A pool missing
+//! from a logged block left the synced set (migration, restart with a new
+//! whitelist) and is removed until it is logged again.
+//!
+//! Parquet price logs (PRICE_LOG_FORMAT=parquet) are not replayed.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Reads gzipped (closed) price log days
+
+use crate::arbitrage::OpportunityDetector;
+use crate::paper_trading::{PaperTradingConfig, SimulatedTradeResult, TraderMetrics};
+use crate::pool::PoolStateManager;
+use crate::price_logger::CSV_HEADER;
+use crate::tax::TOKEN_DECIMALS;
+use crate::types::{ArbitrageOpportunity, BotConfig, DexType, TradingPair, V3PoolState};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use ethers::types::{Address, U256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use flate2::read::MultiGzDecoder;
+use std::io::{BufRead, BufReader, Lines, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::{debug, info, warn};
+
+/// Every DexType, for parsing the logged `dex` column (its Display form)
+const DEX_TYPES: [DexType; 14] = [
+    DexType::Uniswap,
+    DexType::Sushiswap,
+    DexType::Quickswap,
+    DexType::Apeswap,
+    DexType::UniswapV3_001,
+    DexType::UniswapV3_005,
+    DexType::UniswapV3_030,
+    DexType::UniswapV3_100,
+    DexType::SushiV3_001,
+    DexType::SushiV3_005,
+    DexType::SushiV3_030,
+    DexType::QuickswapV3,
+    DexType::QuickSwapV2,
+    DexType::SushiSwapV2,
+];
+
+/// CSV header for the backtest report
+const REPORT_HEADER: &str = "strategy,blocks,missing_blocks,opportunities,trades,wins,losses,win_rate,net_pnl_usd,gas_usd,avg_pnl_per_trade_usd,largest_win_usd,largest_loss_usd";
+
+/// Simulated execution: the quoted (executable) spread less slippage and gas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillModel {
+    /// Price impact + drift between detection and inclusion, in bps of size
+    pub slippage_bps: f64,
+    /// Gas per fill in USD
+    pub gas_cost_usd: f64,
+}
+
+impl FillModel {
+    /// Simulated result of filling `opp` in full at `timestamp`
+    pub fn fill(&self, opp: &ArbitrageOpportunity, timestamp: DateTime<Utc>) -> SimulatedTradeResult {
+        let profit_usd = opp.trade_size_usd * (opp.spread_percent / 100.0 - self.slippage_bps / 10_000.0);
+        let net_profit_usd = profit_usd - self.gas_cost_usd;
+        SimulatedTradeResult {
+            pair: opp.pair.symbol.clone(),
+            success: net_profit_usd > 0.0,
+            profit_usd,
+            gas_cost_usd: self.gas_cost_usd,
+            net_profit_usd,
+            execution_time_ms: 0,
+            error: None,
+            timestamp,
+        }
+    }
+}
+
+/// All logged pools as of one block
+#[derive(Debug, Clone)]
+pub struct BlockSnapshot {
+    pub block: u64,
+    pub timestamp: DateTime<Utc>,
+    pub pools: Vec<V3PoolState>,
+}
+
+/// Tokens of a configured pair, in V3 (sorted) order
+#[derive(Debug, Clone)]
+struct ResolvedPair {
+    pair: TradingPair,
+    /// Quote token decimals, if one side is a quote token
+    quote: Option<(bool, u8)>,
+}
+
+/// Price log rows → V3PoolState, using TRADING_PAIRS for the token side
+pub struct PairResolver {
+    pairs: HashMap<String, ResolvedPair>,
+}
+
+impl PairResolver {
+    pub fn new(config: &BotConfig) -> Self {
+        let mut pairs = HashMap::new();
+        for pair_config in &config.pairs {
+            let (Ok(a), Ok(b)) = (Address::from_str(&pair_config.token0), Address::from_str(&pair_config.token1)) else {
+                warn!("Backtest: {} has an unparseable token address — its rows are skipped", pair_config.symbol);
+                continue;
+            };
+            // Symbol parts follow TRADING_PAIRS order: "WETH/USDC" = token0 WETH, token1 USDC
+            let mut symbols = pair_config.symbol.split('/');
+            let (symbol_a, symbol_b) = (symbols.next().unwrap_or(""), symbols.next().unwrap_or(""));
+            let ((token0, symbol0), (token1, symbol1)) = if a < b {
+                ((a, symbol_a), (b, symbol_b))
+            } else {
+                ((b, symbol_b), (a, symbol_a))
+            };
+            let quote = if config.is_quote_token(&token0) {
+                Some((true, symbol_decimals(symbol0).unwrap_or(6)))
+            } else if config.is_quote_token(&token1) {
+                Some((false, symbol_decimals(symbol1).unwrap_or(6)))
+            } else {
+                None
+            };
+            // First entry wins: the log row names the pair by symbol only
+            pairs.entry(pair_config.symbol.clone()).or_insert(ResolvedPair {
+                pair: TradingPair::new(token0, token1, pair_config.symbol.clone()),
+                quote,
+            });
+        }
+        Self { pairs }
+    }
+
+    /// Parse one price log row (CSV_HEADER order). Err = the row is skipped.
+    pub fn parse_row(&self, line: &str) -> Result<(u64, DateTime<Utc>, V3PoolState)> {
+        let cols: Vec<&str> = line.trim_end().split(',').collect();
+        if cols.len() != 10 {
+            return Err(anyhow!("expected 10 columns, got {}", cols.len()));
+        }
+        let timestamp = DateTime::parse_from_rfc3339(cols[0]).context("timestamp")?.with_timezone(&Utc);
+        let block: u64 = cols[1].parse().context("block")?;
+        let resolved = self.pairs.get(cols[2]).ok_or_else(|| anyhow!("pair {} not in TRADING_PAIRS", cols[2]))?;
+        let dex = parse_dex(cols[3]).ok_or_else(|| anyhow!("unknown dex {}", cols[3]))?;
+        let fee: u32 = cols[4].parse().context("fee")?;
+        let price: f64 = cols[5].parse().context("price")?;
+        let tick: i32 = cols[6].parse().context("tick")?;
+        let liquidity: u128 = cols[7].parse().context("liquidity")?;
+        let sqrt_price_x96 = U256::from_dec_str(cols[8]).map_err(|e| anyhow!("sqrt_price_x96: {}", e))?;
+        let address = Address::from_str(cols[9]).context("address")?;
+        let (token0_decimals, token1_decimals) = resolve_decimals(resolved, price, tick);
+
+        Ok((
+            block,
+            timestamp,
+            V3PoolState {
+                address,
+                dex,
+                pair: resolved.pair.clone(),
+                sqrt_price_x96,
+                tick,
+                fee,
+                liquidity,
+                token0_decimals,
+                token1_decimals,
+                last_updated: block,
+            },
+        ))
+    }
+}
+
+fn parse_dex(name: &str) -> Option<DexType> {
+    DEX_TYPES.iter().copied().find(|dex| dex.to_string() == name)
+}
+
+/// TOKEN_DECIMALS by symbol ("USDC.e" → USDC)
+fn symbol_decimals(symbol: &str) -> Option<u8> {
+    let base = symbol.split('.').next().unwrap_or(symbol).to_uppercase();
+    TOKEN_DECIMALS.iter().find(|(s, _)| *s == base).map(|(_, d)| *d)
+}
+
+/// (token0, token1) decimals: the quote side from TOKEN_DECIMALS, the other
+/// from the logged price/tick; both from TOKEN_DECIMALS (else 18) when the
+/// price was logged as zero
+fn resolve_decimals(resolved: &ResolvedPair, price: f64, tick: i32) -> (u8, u8) {
+    let mut symbols = resolved.pair.symbol.split('/');
+    let fallback = |s: Option<&str>| s.and_then(symbol_decimals).unwrap_or(18);
+    let by_symbol = (fallback(symbols.next()), fallback(symbols.next()));
+    let diff = (price / 1.0001f64.powi(tick)).log10().round();
+    let Some((quote_is_token0, quote_decimals)) = resolved.quote.filter(|_| diff.is_finite()) else {
+        return by_symbol;
+    };
+    let other = |d: f64| d.clamp(0.0, u8::MAX as f64) as u8;
+    if quote_is_token0 {
+        (quote_decimals, other(quote_decimals as f64 - diff))
+    } else {
+        (other(quote_decimals as f64 + diff), quote_decimals)
+    }
+}
+
+/// prices_*.csv and prices_*.csv.gz files in `dir`, oldest first (the date is in the name)
+pub fn price_log_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Backtest: cannot read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("prices_") && (n.ends_with(".csv") || n.ends_with(".csv.gz")))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Streams BlockSnapshots out of price log files, one block at a time
+pub struct PriceLogReader {
+    resolver: PairResolver,
+    files: VecDeque<PathBuf>,
+    lines: Option<Lines<BufReader<Box<dyn Read>>>>,
+    /// First row of the next block (read while closing the current one)
+    pending: Option<(u64, DateTime<Utc>, V3PoolState)>,
+    /// Rows that could not be parsed or resolved
+    pub skipped_rows: u64,
+}
+
+impl PriceLogReader {
+    pub fn new(resolver: PairResolver, files: Vec<PathBuf>) -> Self {
+        Self { resolver, files: files.into(), lines: None, pending: None, skipped_rows: 0 }
+    }
+
+    /// Next parsed row across file boundaries (None = all files read)
+    fn next_row(&mut self) -> Option<Result<(u64, DateTime<Utc>, V3PoolState)>> {
+        loop {
+            let Some(lines) = self.lines.as_mut() else {
+                let path = self.files.pop_front()?;
+                match File::open(&path) {
+                    Ok(file) => {
+                        info!("Backtest: reading {}", path.display());
+                        let input: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+                            Box::new(MultiGzDecoder::new(file))
+                        } else {
+                            Box::new(file)
+                        };
+                        self.lines = Some(BufReader::new(input).lines());
+                    }
+                    Err(e) => return Some(Err(anyhow!("Backtest: cannot open {}: {}", path.display(), e))),
+                }
+                continue;
+            };
+            let line = match lines.next() {
+                None => {
+                    self.lines = None;
+                    continue;
+                }
+                Some(Err(e)) => return Some(Err(e.into())),
+                Some(Ok(line)) => line,
+            };
+            if line.trim().is_empty() || line.starts_with(CSV_HEADER) {
+                continue;
+            }
+            match self.resolver.parse_row(&line) {
+                Ok(row) => return Some(Ok(row)),
+                Err(e) => {
+                    self.skipped_rows += 1;
+                    debug!("Backtest: skipping row ({}): {}", e, line);
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for PriceLogReader {
+    type Item = Result<BlockSnapshot>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (block, timestamp, pool) = match self.pending.take().map(Ok).or_else(|| self.next_row())? {
+            Ok(row) => row,
+            Err(e) => return Some(Err(e)),
+        };
+        let mut snapshot = BlockSnapshot { block, timestamp, pools: vec![pool] };
+        while let Some(row) = self.next_row() {
+            match row {
+                Ok((b, _, pool)) if b == block => snapshot.pools.push(pool),
+                Ok(row) => {
+                    self.pending = Some(row);
+                    break;
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        Some(Ok(snapshot))
+    }
+}
+
+/// Replay bookkeeping
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayStats {
+    pub blocks: u64,
+    /// Block numbers skipped between logged blocks
+    pub missing_blocks: u64,
+    /// Blocks at or below the last replayed block (restart overlap), ignored
+    pub out_of_order_blocks: u64,
+    pub pools_added: u64,
+    pub pools_removed: u64,
+    pub first_block: Option<u64>,
+    pub last_block: Option<u64>,
+}
+
+/// One strategy's detector and results
+struct StrategyRun {
+    config: PaperTradingConfig,
+    detector: OpportunityDetector,
+    metrics: TraderMetrics,
+    /// Route filled in the previous block: (pair, buy dex, sell dex)
+    last_route: Option<(String, DexType, DexType)>,
+}
+
+impl StrategyRun {
+    fn on_block(&mut self, snapshot: &BlockSnapshot, contiguous: bool, fill: &FillModel) {
+        self.metrics.check_daily_reset_at(snapshot.timestamp);
+        let opportunities = self.detector.scan_opportunities();
+        for _ in &opportunities {
+            self.metrics.record_detected_opportunity();
+        }
+        let previous = self.last_route.take();
+        let Some(best) = opportunities.first() else {
+            return;
+        };
+        let route = (best.pair.symbol.clone(), best.buy_dex, best.sell_dex);
+        if contiguous && previous.as_ref() == Some(&route) {
+            // Same spread still open: already filled
+            self.last_route = Some(route);
+            return;
+        }
+        if self.risk_halted() {
+            return;
+        }
+        self.metrics.record_trade(fill.fill(best, snapshot.timestamp));
+        self.last_route = Some(route);
+    }
+
+    /// Paper trading risk limits (PaperTradingStrategy::should_skip)
+    fn risk_halted(&self) -> bool {
+        self.config.max_daily_trades.is_some_and(|max| self.metrics.daily_trades() >= max)
+            || self.config.daily_loss_limit_usd.is_some_and(|max| self.metrics.daily_loss() >= max)
+            || self.config.max_consecutive_losses.is_some_and(|max| self.metrics.consecutive_losses() >= max)
+    }
+}
+
+/// Replays blocks into a shared PoolStateManager and every strategy's detector
+pub struct Backtest {
+    state: PoolStateManager,
+    live_pools: HashSet<Address>,
+    runs: Vec<StrategyRun>,
+    fill: FillModel,
+    stats: ReplayStats,
+}
+
+impl Backtest {
+    /// One detector per strategy over `base`, with the strategy's profit
+    /// floor, trade size and pairs (empty = all configured pairs)
+    pub fn new(base: &BotConfig, strategies: &[PaperTradingConfig], fill: FillModel) -> Self {
+        let state = PoolStateManager::new();
+        let runs = strategies
+            .iter()
+            .map(|strategy| {
+                let mut config = base.clone();
+                config.min_profit_usd = strategy.min_profit_usd;
+                config.max_trade_size_usd = strategy.max_trade_size_usd;
+                config.estimated_gas_cost_usd = fill.gas_cost_usd;
+                if !strategy.pairs.is_empty() {
+                    config.pairs.retain(|p| strategy.pairs.contains(&p.symbol));
+                }
+                StrategyRun {
+                    config: strategy.clone(),
+                    detector: OpportunityDetector::new(config, state.clone()),
+                    metrics: TraderMetrics::new_at(strategy.name.clone(), DateTime::<Utc>::UNIX_EPOCH),
+                    last_route: None,
+                }
+            })
+            .collect();
+        Self { state, live_pools: HashSet::new(), runs, fill, stats: ReplayStats::default() }
+    }
+
+    /// Apply one logged block and scan it with every strategy
+    pub fn apply_block(&mut self, snapshot: &BlockSnapshot) {
+        let contiguous = match self.stats.last_block {
+            Some(last) if snapshot.block <= last => {
+                self.stats.out_of_order_blocks += 1;
+                return;
+            }
+            Some(last) => {
+                self.stats.missing_blocks += snapshot.block - last - 1;
+                snapshot.block == last + 1
+            }
+            None => {
+                for run in &mut self.runs {
+                    run.metrics = TraderMetrics::new_at(run.config.name.clone(), snapshot.timestamp);
+                }
+                self.stats.first_block = Some(snapshot.block);
+                false
+            }
+        };
+        self.stats.blocks += 1;
+        self.stats.last_block = Some(snapshot.block);
+
+        let present: HashSet<Address> = snapshot.pools.iter().map(|p| p.address).collect();
+        for gone in self.live_pools.difference(&present) {
+            self.state.remove_v3_pool(gone);
+            self.stats.pools_removed += 1;
+        }
+        for pool in &snapshot.pools {
+            if !self.live_pools.contains(&pool.address) {
+                self.stats.pools_added += 1;
+            }
+            self.state.update_v3_pool(pool.clone());
+        }
+        self.live_pools = present;
+
+        for run in &mut self.runs {
+            run.on_block(snapshot, contiguous, &self.fill);
+        }
+    }
+
+    /// Replay every block from `blocks`
+    pub fn run(&mut self, blocks: impl IntoIterator<Item = Result<BlockSnapshot>>) -> Result<()> {
+        for snapshot in blocks {
+            self.apply_block(&snapshot?);
+            if self.stats.blocks.is_multiple_of(10_000) {
+                info!("Backtest: {} blocks replayed (block {})", self.stats.blocks, self.stats.last_block.unwrap_or(0));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> &ReplayStats {
+        &self.stats
+    }
+
+    /// Per-strategy results, in strategy order
+    pub fn metrics(&self) -> Vec<&TraderMetrics> {
+        self.runs.iter().map(|run| &run.metrics).collect()
+    }
+
+    /// Write one summary row per strategy
+    pub fn write_report(&self, path: &Path) -> Result<()> {
+        let mut file = File::create(path).with_context(|| format!("Backtest: cannot create {}", path.display()))?;
+        writeln!(file, "{}", REPORT_HEADER)?;
+        for m in self.metrics() {
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{},{:.4},{:.4},{:.4},{:.4},{:.4},{:.4}",
+                m.config_name,
+                self.stats.blocks,
+                self.stats.missing_blocks,
+                m.opportunities_detected,
+                m.total_trades,
+                m.winning_trades,
+                m.losing_trades,
+                m.win_rate,
+                m.net_profit_usd,
+                m.total_gas_usd,
+                m.avg_profit_per_trade,
+                m.largest_win,
+                m.largest_loss,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_logger::csv_row;
+    use crate::types::TradingPairConfig;
+
+    const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+    const WETH: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+
+    fn config() -> BotConfig {
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.pairs = vec![TradingPairConfig {
+            token0: WETH.to_string(),
+            token1: USDC.to_string(),
+            symbol: "WETH/USDC".to_string(),
+        }];
+        config
+    }
+
+    /// USDC (token0, 6 dec) / WETH (token1, 18 dec) pool at `price` WETH per USDC
+    fn pool(address: u64, dex: DexType, fee: u32, price: f64) -> V3PoolState {
+        let tick = ((price * 1e12).ln() / 1.0001f64.ln()).round() as i32;
+        V3PoolState {
+            address: Address::from_low_u64_be(address),
+            dex,
+            pair: TradingPair::new(
+                Address::from_str(USDC).unwrap(),
+                Address::from_str(WETH).unwrap(),
+                "WETH/USDC".to_string(),
+            ),
+            sqrt_price_x96: crate::mempool::ticks::sqrt_ratio_at_tick(tick).unwrap(),
+            tick,
+            fee,
+            liquidity: 1_000_000_000_000_000_000,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 0,
+        }
+    }
+
+    fn log(dir: &Path, file: &str, blocks: &[(u64, Vec<V3PoolState>)]) {
+        let mut body = format!("{}\n", CSV_HEADER);
+        for (block, pools) in blocks {
+            let timestamp = format!("2026-10-16T00:{:02}:00.000Z", block % 60);
+            for pool in pools {
+                body.push_str(&csv_row(&timestamp, *block, pool));
+            }
+        }
+        std::fs::write(dir.join(file), body).unwrap();
+    }
+
+    fn strategy(name: &str) -> PaperTradingConfig {
+        PaperTradingConfig {
+            name: name.to_string(),
+            min_profit_usd: 1.0,
+            max_trade_size_usd: 1_000.0,
+            pairs: vec![],
+            max_daily_trades: None,
+            max_consecutive_losses: None,
+            daily_loss_limit_usd: None,
+            ..PaperTradingConfig::moderate()
+        }
+    }
+
+    #[test]
+    fn test_rows_round_trip_through_the_price_logger_format() {
+        let resolver = PairResolver::new(&config());
+        let logged = pool(1, DexType::UniswapV3_005, 500, 0.000_400);
+        let row = csv_row("2026-10-16T12:00:00.000Z", 42, &logged);
+        let (block, _, parsed) = resolver.parse_row(&row).unwrap();
+
+        assert_eq!(block, 42);
+        assert_eq!((parsed.pair.token0, parsed.pair.token1), (logged.pair.token0, logged.pair.token1));
+        assert_eq!((parsed.token0_decimals, parsed.token1_decimals), (6, 18));
+        assert_eq!((parsed.dex, parsed.tick, parsed.liquidity), (logged.dex, logged.tick, logged.liquidity));
+        assert!((parsed.price() - logged.price()).abs() < 1e-12);
+
+        // Unconfigured pair: skipped
+        assert!(resolver.parse_row(&row.replace("WETH/USDC", "LINK/USDC")).is_err());
+    }
+
+    #[test]
+    fn test_replay_handles_gaps_and_pools_leaving_the_log() {
+        let dir = std::env::temp_dir().join(format!("dexarb_backtest_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cheap = pool(1, DexType::UniswapV3_005, 500, 0.000_400);
+        let dear = pool(2, DexType::UniswapV3_030, 3000, 0.000_395);
+        let flat = pool(2, DexType::UniswapV3_030, 3000, 0.000_400);
+        // 100-101: one 0.92% spread held over two blocks (one fill); 102 missing;
+        // 103: pool 2 gone; 104 (next day's file): spread back
+        log(&dir, "prices_20261016.csv", &[
+            (100, vec![cheap.clone(), dear.clone()]),
+            (101, vec![cheap.clone(), dear.clone()]),
+            (103, vec![cheap.clone()]),
+        ]);
+        log(&dir, "prices_20261017.csv", &[(104, vec![cheap.clone(), dear.clone()]), (105, vec![cheap, flat])]);
+        // The closed day as the logger's maintenance leaves it
+        crate::price_logger::rotation::compress_file(&dir.join("prices_20261016.csv")).unwrap();
+
+        let config = config();
+        let reader = PriceLogReader::new(PairResolver::new(&config), price_log_files(&dir).unwrap());
+        let fill = FillModel { slippage_bps: 10.0, gas_cost_usd: 0.5 };
+        let mut backtest = Backtest::new(&config, &[strategy("loose"), PaperTradingConfig { min_profit_usd: 50.0, ..strategy("strict") }], fill);
+        backtest.run(reader).unwrap();
+
+        let stats = backtest.stats();
+        assert_eq!((stats.blocks, stats.missing_blocks), (5, 1));
+        assert_eq!((stats.pools_added, stats.pools_removed), (3, 1));
+
+        let metrics = backtest.metrics();
+        let loose = metrics[0];
+        assert_eq!(loose.opportunities_detected, 3);
+        assert_eq!((loose.total_trades, loose.winning_trades), (2, 2));
+        // (1.266% spread − 0.35% fees − 0.10% slippage) × $1000 − $0.50 gas
+        let per_fill = 1_000.0 * ((0.000_400 / 0.000_395 - 1.0) - 0.0035 - 0.001) - 0.5;
+        assert!((loose.net_profit_usd - 2.0 * per_fill).abs() < 0.25, "net {}", loose.net_profit_usd);
+        assert_eq!(metrics[1].total_trades, 0);
+
+        let report = dir.join("report.csv");
+        backtest.write_report(&report).unwrap();
+        let body = std::fs::read_to_string(&report).unwrap();
+        assert_eq!(body.lines().count(), 3);
+        assert!(body.lines().nth(1).unwrap().starts_with("loose,5,1,3,2,2,0,1.0000,"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
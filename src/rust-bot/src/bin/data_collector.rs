@@ -11,11 +11,13 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - RPC_URL may be a local node's IPC socket (WS_RPC_URL fallback)
 
 use anyhow::Result;
 use dexarb_bot::config::load_config;
 use dexarb_bot::data_collector::{run_data_collector, DEFAULT_STATE_PATH};
-use ethers::prelude::*;
+use dexarb_bot::rpc_bench::endpoint_label;
+use dexarb_bot::transport::connect_provider;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::info;
@@ -39,10 +41,10 @@ async fn main() -> Result<()> {
     let config = load_config()?;
     info!("Configuration loaded");
 
-    // Create provider
-    let provider = Provider::<Ws>::connect(&config.rpc_url).await?;
+    // Create provider (WS, or IPC with the WS_RPC_URL fallback)
+    let provider = connect_provider(&config.rpc_url, config.ws_rpc_url.as_deref()).await?;
     let provider = Arc::new(provider);
-    info!("Connected to RPC: {}", &config.rpc_url[..50.min(config.rpc_url.len())]);
+    info!("Connected to RPC: {}", endpoint_label(&config.rpc_url));
 
     // Get state file path from env or use default
     let state_path = std::env::var("STATE_FILE")
@@ -0,0 +1,24 @@
+//! dexarb-tax — offline tax reports
+//!
+//! Subcommands over the bot's tax directory (TAX_LOG_DIR or --tax-dir); no
+//! private key needed, and no RPC connection except for reconcile. See
+//! dexarb_bot::tax::cli.
+//!
+//! Usage:
+//!   cargo run --bin dexarb-tax -- summary --year 2026 --chain polygon
+//!   cargo run --bin dexarb-tax -- export-rp2 --year 2026 --out /tmp/rp2
+//!   cargo run --bin dexarb-tax -- verify --tx 0xabc...
+//!   cargo run --bin dexarb-tax -- reconcile --from-block 60000000 --to-block 60100000
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - reconcile subcommand
+
+use anyhow::Result;
+use clap::Parser;
+use dexarb_bot::tax::cli::TaxCli;
+
+fn main() -> Result<()> {
+    let cli = TaxCli::parse();
+    cli.run(&mut std::io::stdout().lock())
+}
@@ -23,13 +23,17 @@
 //! Author: AI-Generated
 //! Created: 2026-01-28
 //! Modified: 2026-01-28 (V3 arbitrage support)
+//! Modified: 2026-10-16 (seeded competition rolls / logical clock via [general] seed)
+//! Modified: 2026-10-16 (error restarts via retry::paper_restart)
+//! Modified: 2026-10-16 (parity strategies skipped: they need the engine's live detector)
 
 use anyhow::{Context, Result};
 use chrono::Utc;
 use dexarb_bot::data_collector::SharedPoolState;
+use dexarb_bot::retry::{self, Backoffed, Retrier, RetrySite};
 use dexarb_bot::paper_trading::{
     AggregatedOpportunity, MetricsAggregator, OpportunityBatcher, PaperTradingConfig,
-    SimulatedTradeAction, SimulatedExecutor, StrategyMatch, TraderMetrics, TomlConfig,
+    SimRng, SimulatedTradeAction, SimulatedExecutor, StrategyKind, StrategyMatch, TraderMetrics, TomlConfig,
 };
 use futures::StreamExt;
 use signal_hook::consts::SIGHUP;
@@ -85,10 +89,13 @@ async fn main() -> Result<()> {
         }
     });
 
-    // Main loop - supports restarts on config reload
+    // Main loop - supports restarts on config reload; errors restart after a backoff
+    let mut restart = Retrier::new(retry::paper_restart(), RetrySite::named("paper_restart"), retry::shutdown_token());
     loop {
+        restart.attempt();
         match run_paper_trading(&config_path).await {
             Ok(should_restart) => {
+                restart.succeeded();
                 if should_restart {
                     info!("Restarting with new configuration...");
                     continue;
@@ -99,7 +106,9 @@ async fn main() -> Result<()> {
             }
             Err(e) => {
                 error!("Paper trading error: {}", e);
-                tokio::time::sleep(Duration::from_secs(5)).await;
+                if restart.failed().await != Backoffed::Retry {
+                    break;
+                }
             }
         }
     }
@@ -116,8 +125,13 @@ async fn run_paper_trading(config_path: &PathBuf) -> Result<bool> {
     let state_file = PathBuf::from(&config.general.state_file);
     info!("State file: {}", state_file.display());
 
-    // Get enabled strategies
-    let strategies = config.get_enabled_strategies();
+    // Get enabled strategies (parity runs in the paper engine, not on the state file)
+    let (parity, strategies): (Vec<_>, Vec<_>) = config.get_enabled_strategies()
+        .into_iter()
+        .partition(|s| s.strategy == StrategyKind::Parity);
+    for s in &parity {
+        warn!("Strategy {} is kind = \"parity\" — skipped (run it through paper_trading::run_paper_trading_custom)", s.name);
+    }
     info!("Loaded {} enabled strategies:", strategies.len());
     for s in &strategies {
         info!("  - {} (pairs: {:?}, min_profit: ${:.2})",
@@ -127,12 +141,26 @@ async fn run_paper_trading(config_path: &PathBuf) -> Result<bool> {
     // Initialize metrics for each strategy
     let mut strategy_metrics: HashMap<String, Arc<RwLock<TraderMetrics>>> = HashMap::new();
     let mut executors: HashMap<String, SimulatedExecutor> = HashMap::new();
+    let mut competition_rngs: HashMap<String, SimRng> = HashMap::new();
+
+    let determinism = config.general.determinism();
+    if let Some(seed) = determinism.seed {
+        info!("Deterministic seed: {} (logical clock: {})", seed, determinism.logical_clock);
+    }
 
     for strategy in &strategies {
-        let metrics = Arc::new(RwLock::new(TraderMetrics::new(strategy.name.clone())));
-        let executor = SimulatedExecutor::new(strategy.clone(), Arc::clone(&metrics));
+        let metrics = Arc::new(RwLock::new(TraderMetrics::new_at(
+            strategy.name.clone(),
+            determinism.start_time(),
+        )));
+        let executor =
+            SimulatedExecutor::with_determinism(strategy.clone(), Arc::clone(&metrics), determinism);
         strategy_metrics.insert(strategy.name.clone(), metrics);
         executors.insert(strategy.name.clone(), executor);
+        competition_rngs.insert(
+            strategy.name.clone(),
+            determinism.rng_for(&format!("strategy:{}", strategy.name)),
+        );
     }
 
     // Initialize Discord opportunity batcher (15 minute batches by default)
@@ -200,7 +228,8 @@ async fn run_paper_trading(config_path: &PathBuf) -> Result<bool> {
         let mut all_opportunities: Vec<RawOpportunity> = Vec::new();
 
         for strategy_config in &strategies {
-            let opps = scan_for_opportunities_detailed(&shared_state, strategy_config, iteration);
+            let rng = &competition_rngs[&strategy_config.name];
+            let opps = scan_for_opportunities_detailed(&shared_state, strategy_config, rng, iteration);
             all_opportunities.extend(opps);
         }
 
@@ -225,6 +254,8 @@ async fn run_paper_trading(config_path: &PathBuf) -> Result<bool> {
                         buy_dex: raw_opp.buy_dex.clone(),
                         sell_dex: raw_opp.sell_dex.clone(),
                         lost_to_competition: raw_opp.lost_to_competition,
+                        event_sequence: iteration,
+                        event_timestamp: shared_state.last_updated.timestamp() as u64,
                     };
                     let _result = executor.simulate_trade(&action).await;
                 }
@@ -359,6 +390,7 @@ struct RawOpportunity {
 fn scan_for_opportunities_detailed(
     shared_state: &SharedPoolState,
     config: &PaperTradingConfig,
+    rng: &SimRng,
     _iteration: u64,
 ) -> Vec<RawOpportunity> {
     let mut opportunities = Vec::new();
@@ -507,9 +539,7 @@ fn scan_for_opportunities_detailed(
 
                 // Simulate competition loss
                 let lost_to_competition = if config.simulate_competition {
-                    let seed = Utc::now().timestamp_nanos_opt().unwrap_or(0) as f64;
-                    let roll = (seed % 1000.0) / 1000.0;
-                    roll < config.competition_rate
+                    rng.next_f64() < config.competition_rate
                 } else {
                     false
                 };
@@ -9,19 +9,21 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - One RP2 file per (chain, wallet); config sections per chain
+//! Modified: 2026-10-16 - Summaries report realized gains from the tax lot ledger
 
 use anyhow::{Context, Result};
 use chrono::Datelike;
 use dexarb_bot::tax::{
-    export_to_rp2, generate_rp2_config, validate_rp2_export, TaxJsonLogger, TaxSummary,
+    export_grouped_to_rp2, generate_rp2_config, group_for_rp2, rp2_sections, validate_rp2_export,
+    LotLedger, TaxJsonLogger, TaxRecord, TaxSummary,
 };
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{error, info};
 
 /// Default paths
 const DEFAULT_TAX_DIR: &str = "/home/botuser/bots/dexarb/data/tax";
-const DEFAULT_HOLDER: &str = "DexArbBot";
 
 fn main() -> Result<()> {
     // Initialize logging
@@ -55,8 +57,14 @@ fn main() -> Result<()> {
             validate_file(file)?;
         }
         "--config" | "-c" => {
-            let method = args.get(2).unwrap_or(&"fifo".to_string()).clone();
-            generate_config(&method)?;
+            let method = args
+                .get(2)
+                .filter(|a| !a.starts_with('-'))
+                .cloned()
+                .unwrap_or_else(|| "fifo".to_string());
+            let year = parse_year(&args)?;
+            let output = parse_output(&args)?;
+            generate_config(&method, year, &output)?;
         }
         "--list" | "-l" => {
             list_available_years()?;
@@ -80,16 +88,19 @@ USAGE:
 
 COMMANDS:
     --year, -y <YEAR>      Export records for a specific tax year
-                           Options: --output <PATH>  Output file path
-                                    --holder <NAME>  Wallet holder name
+                           Options: --output <PATH>  Output path; one file is
+                                    written per chain and wallet as
+                                    <stem>_<chain>_<wallet>.csv
 
     --summary, -s          Print tax summary for a year
                            Options: --year <YEAR>  Tax year (default: current)
 
     --validate, -v <FILE>  Validate an RP2 export file
 
-    --config, -c [METHOD]  Generate RP2 config.ini file
+    --config, -c [METHOD]  Generate RP2 config.ini file (one exchange
+                           section per chain in the year's records)
                            Methods: fifo (default), lifo, hifo
+                           Options: --year <YEAR> --output <PATH>
 
     --list, -l             List available tax years with records
 
@@ -114,6 +125,7 @@ EXAMPLES:
 NOTES:
     - Tax records are read from: {}
     - RP2 exports create 2 rows per trade (BUY + SELL)
+    - Each wallet on each chain gets its own file (holder = wallet address)
     - Paper trades are automatically excluded from exports
 "#,
         DEFAULT_TAX_DIR
@@ -147,15 +159,14 @@ fn parse_output(args: &[String]) -> Result<PathBuf> {
     Ok(PathBuf::from(format!("{}/rp2_export_{}.csv", DEFAULT_TAX_DIR, year)))
 }
 
-fn parse_holder(args: &[String]) -> String {
-    for i in 0..args.len() {
-        if args[i] == "--holder" {
-            if let Some(holder) = args.get(i + 1) {
-                return holder.clone();
-            }
-        }
-    }
-    DEFAULT_HOLDER.to_string()
+/// Output directory and file prefix from the --output path
+fn split_output(output: &Path) -> (PathBuf, String) {
+    let dir = output.parent().map(Path::to_path_buf).unwrap_or_default();
+    let prefix = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "rp2_export".to_string());
+    (dir, prefix)
 }
 
 fn export_year(year: i16, output: &PathBuf) -> Result<()> {
@@ -181,29 +192,28 @@ fn export_year(year: i16, output: &PathBuf) -> Result<()> {
         records.len() - real_records.len()
     );
 
-    let holder = parse_holder(&env::args().collect::<Vec<_>>());
-    let row_count = export_to_rp2(&real_records, output, &holder)?;
-
-    info!("Exported {} rows to {:?}", row_count, output);
-    info!("Holder name: {}", holder);
-
-    // Validate the export
-    let validation = validate_rp2_export(output)?;
-    if validation.valid {
-        info!("Export validation: PASSED");
-    } else {
-        error!("Export validation: FAILED");
-        for err in &validation.errors {
-            error!("  - {}", err);
+    let (dir, prefix) = split_output(output);
+    let groups = group_for_rp2(&real_records);
+    let sections = rp2_sections(&groups, &prefix);
+    let files = export_grouped_to_rp2(&groups, &dir, &prefix)?;
+
+    for (path, row_count) in &files {
+        info!("Exported {} rows to {:?}", row_count, path);
+
+        // Validate the export
+        let validation = validate_rp2_export(path, &sections)?;
+        if validation.valid {
+            info!("Export validation: PASSED");
+        } else {
+            error!("Export validation: FAILED");
+            for err in &validation.errors {
+                error!("  - {}", err);
+            }
         }
     }
 
     // Print summary
-    let mut summary = TaxSummary::new(year);
-    for record in &real_records {
-        summary.add_record(record);
-    }
-
+    let summary = ledger_summary(&logger, year)?;
     println!("\n{}", summary.report());
 
     Ok(())
@@ -220,18 +230,11 @@ fn print_summary(year: i16) -> Result<()> {
         return Ok(());
     }
 
-    // Filter out paper trades for tax summary
-    let real_records: Vec<_> = records.iter().filter(|r| !r.is_paper_trade).collect();
-
-    let mut summary = TaxSummary::new(year);
-    for record in &real_records {
-        summary.add_record(record);
-    }
-
+    let summary = ledger_summary(&logger, year)?;
     println!("{}", summary.report());
 
     // Also show paper trade stats
-    let paper_count = records.len() - real_records.len();
+    let paper_count = records.iter().filter(|r| r.is_paper_trade).count();
     if paper_count > 0 {
         println!("Note: {} paper trades excluded from summary", paper_count);
     }
@@ -239,10 +242,19 @@ fn print_summary(year: i16) -> Result<()> {
     Ok(())
 }
 
+/// Realized gains for `year` from the lot ledger, rebuilt from every year's
+/// records through `year` (lots acquired earlier are still open). Replay uses
+/// the lot ids the bot logged, so no lot method is needed here.
+fn ledger_summary(logger: &TaxJsonLogger, year: i16) -> Result<TaxSummary> {
+    let mut ledger = LotLedger::default();
+    ledger.replay(&logger.read_through(year)?);
+    Ok(TaxSummary::from_ledger(year, &ledger))
+}
+
 fn validate_file(file: &str) -> Result<()> {
     info!("Validating RP2 export file: {}", file);
 
-    let result = validate_rp2_export(file)?;
+    let result = validate_rp2_export(file, &[])?;
 
     println!("\nValidation Results:");
     println!("─────────────────────────────────────");
@@ -260,15 +272,29 @@ fn validate_file(file: &str) -> Result<()> {
     Ok(())
 }
 
-fn generate_config(method: &str) -> Result<()> {
+fn generate_config(method: &str, year: i16, export_output: &Path) -> Result<()> {
     let output = PathBuf::from(DEFAULT_TAX_DIR).join("rp2_config.ini");
 
+    // Sections list the files --year writes for the same --output
+    let logger = TaxJsonLogger::new(PathBuf::from(DEFAULT_TAX_DIR))?;
+    let records: Vec<TaxRecord> = logger.read_all(year)?;
+    let (_, prefix) = split_output(export_output);
+    let sections = rp2_sections(&group_for_rp2(&records), &prefix);
+
     info!("Generating RP2 config with {} accounting method", method);
-    generate_rp2_config(&output, method)?;
+    generate_rp2_config(&output, method, &sections)?;
 
     info!("Config file created: {:?}", output);
     println!("\nRP2 config generated at: {:?}", output);
     println!("Accounting method: {}", method.to_uppercase());
+    for section in &sections {
+        println!(
+            "Exchange {} (chain {}): {} wallet(s)",
+            section.chain_name,
+            section.chain_id,
+            section.holders.len()
+        );
+    }
 
     Ok(())
 }
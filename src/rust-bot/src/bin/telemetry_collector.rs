@@ -0,0 +1,52 @@
+//! Telemetry Collector — reference sink for TELEMETRY_ENDPOINT
+//!
+//! Receives snapshots POSTed by bot instances (schema checked, other versions
+//! rejected with 400), appends each to a JSONL file and serves the aggregate
+//! view (totals + each instance's latest snapshot) on GET.
+//!
+//! Usage:
+//!   cargo run --bin telemetry-collector -- --listen 127.0.0.1:9464 --out data/telemetry.jsonl
+//!   curl -s http://127.0.0.1:9464/ | jq .
+//!
+//! Instances: TELEMETRY_ENABLED=true TELEMETRY_ENDPOINT=http://127.0.0.1:9464/push
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use anyhow::Result;
+use clap::Parser;
+use dexarb_bot::telemetry::{serve_collector, CollectorState, TELEMETRY_SCHEMA_VERSION};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tracing::info;
+
+#[derive(Parser, Debug)]
+#[command(name = "telemetry-collector", about = "Aggregate telemetry snapshots from bot instances")]
+struct Args {
+    /// Address to listen on (keep it private: loopback or VPN)
+    #[arg(long, default_value = "127.0.0.1:9464")]
+    listen: String,
+
+    /// JSONL file every accepted snapshot is appended to
+    #[arg(long, default_value = "/home/botuser/bots/dexarb/data/telemetry.jsonl")]
+    out: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .with_target(false)
+        .init();
+
+    let args = Args::parse();
+    info!(
+        "Telemetry collector: schema v{} → {}",
+        TELEMETRY_SCHEMA_VERSION,
+        args.out.display()
+    );
+    let listener = TcpListener::bind(&args.listen).await?;
+    let state = Arc::new(Mutex::new(CollectorState::new(Some(args.out))));
+    serve_collector(listener, state).await
+}
@@ -0,0 +1,640 @@
+//! Chain Config — structured TOML alternative to the flat .env file
+//!
+//! config/{chain}.toml groups the live bot settings into sections:
+//!   [network]          chain id/name, RPC endpoints, gas and native price defaults
+//!   [routers.<dex>]    router / factory / quoter per DEX (uniswap, sushiswap,
+//!                      apeswap, uniswap_v3, sushiswap_v3, quickswap_v3)
+//!   [[quote_tokens]]   quote tokens by priority (1 = primary, 2 = native variant)
+//!                      and where their USD price comes from
+//!   [trading]          pairs, profit/size/slippage limits, executor, state files
+//!   [mempool]          A4 mempool monitor mode and execution gas budget
+//!   [tax]              tax logging
+//!   [env]              any other setting, keyed by its env var name
+//!
+//! Every field maps onto the env var config.rs already reads, so both formats
+//! build the same BotConfig. TOML values take precedence; whatever the file
+//! leaves out falls back to the process env — PRIVATE_KEY belongs in the env
+//! file, not in a TOML that gets copied between hosts.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::types::{BotConfig, TradingPairConfig};
+use anyhow::{Context, Result};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env::VarError;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Settings the bot can't start without: (TOML field, env var)
+pub const REQUIRED_FIELDS: &[(&str, &str)] = &[
+    ("network.rpc_url", "RPC_URL"),
+    ("network.chain_id", "CHAIN_ID"),
+    ("network.private_key", "PRIVATE_KEY"),
+    ("routers.uniswap.router", "UNISWAP_ROUTER"),
+    ("routers.uniswap.factory", "UNISWAP_FACTORY"),
+    ("routers.sushiswap.router", "SUSHISWAP_ROUTER"),
+    ("routers.sushiswap.factory", "SUSHISWAP_FACTORY"),
+    ("trading.pairs", "TRADING_PAIRS"),
+    ("trading.min_profit_usd", "MIN_PROFIT_USD"),
+    ("trading.max_trade_size_usd", "MAX_TRADE_SIZE_USD"),
+    ("trading.max_slippage_percent", "MAX_SLIPPAGE_PERCENT"),
+    ("trading.poll_interval_ms", "POLL_INTERVAL_MS"),
+];
+
+/// Required settings missing from `var`, as "[section] field (ENV_VAR)"
+pub fn missing_required(var: &dyn Fn(&str) -> Result<String, VarError>) -> Vec<String> {
+    REQUIRED_FIELDS
+        .iter()
+        .filter(|(_, key)| var(key).is_err())
+        .map(|(field, key)| {
+            let (section, name) = field.rsplit_once('.').unwrap_or(("", field));
+            format!("[{}] {} ({})", section, name, key)
+        })
+        .collect()
+}
+
+/// Top-level config/{chain}.toml
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainConfig {
+    #[serde(default)]
+    pub network: NetworkSection,
+    #[serde(default)]
+    pub routers: RoutersSection,
+    #[serde(default)]
+    pub quote_tokens: Vec<QuoteTokenSection>,
+    #[serde(default)]
+    pub trading: TradingSection,
+    #[serde(default)]
+    pub mempool: MempoolSection,
+    #[serde(default)]
+    pub tax: TaxSection,
+    /// Settings without a section, by env var name (GAS_ORACLE_PERCENTILE = 75)
+    #[serde(default)]
+    pub env: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkSection {
+    pub chain_name: Option<String>,
+    pub chain_id: Option<u64>,
+    pub rpc_url: Option<String>,
+    pub private_rpc_url: Option<String>,
+    /// Prefer PRIVATE_KEY in the env file
+    pub private_key: Option<String>,
+    pub multicall3_address: Option<String>,
+    pub estimated_gas_cost_usd: Option<f64>,
+    pub native_token_price_usd: Option<f64>,
+    /// "" = static native price only
+    pub native_price_pair: Option<String>,
+    pub confirmation_depth: Option<u64>,
+}
+
+/// One table per DEX; V2 DEXs take router + factory only
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutersSection {
+    pub uniswap: Option<DexSection>,
+    pub sushiswap: Option<DexSection>,
+    pub apeswap: Option<DexSection>,
+    pub uniswap_v3: Option<DexSection>,
+    pub sushiswap_v3: Option<DexSection>,
+    pub quickswap_v3: Option<DexSection>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DexSection {
+    pub router: Option<String>,
+    pub factory: Option<String>,
+    pub quoter: Option<String>,
+    /// Uniswap V3 only: QuoterV2 ABI (Base) instead of QuoterV1 (Polygon)
+    pub quoter_is_v2: Option<bool>,
+}
+
+/// Where a quote token's USD price comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsdPriceSource {
+    /// Stablecoin at $1.00
+    #[default]
+    Peg,
+    /// Static `usd_price` (e.g. WETH as the quote token)
+    Fixed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuoteTokenSection {
+    /// Informational (logs, reviews); pairs carry their own symbols
+    pub symbol: Option<String>,
+    pub address: String,
+    /// 1 = primary quote token, 2 = native variant (e.g. native USDC beside USDC.e)
+    pub priority: u8,
+    #[serde(default)]
+    pub usd_price_source: UsdPriceSource,
+    pub usd_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TradingSection {
+    pub pairs: Option<Vec<TradingPairConfig>>,
+    pub min_profit_usd: Option<f64>,
+    pub max_trade_size_usd: Option<f64>,
+    pub max_slippage_percent: Option<f64>,
+    pub poll_interval_ms: Option<u64>,
+    pub max_gas_price_gwei: Option<u64>,
+    pub route_cooldown_blocks: Option<u64>,
+    pub live_mode: Option<bool>,
+    pub arb_executor_address: Option<String>,
+    pub whitelist_file: Option<String>,
+    pub pool_state_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MempoolSection {
+    /// off | observe | execute
+    pub mode: Option<String>,
+    pub min_profit_usd: Option<f64>,
+    pub gas_limit: Option<u64>,
+    pub min_priority_gwei: Option<u64>,
+    pub gas_profit_cap: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaxSection {
+    pub enabled: Option<bool>,
+    pub log_dir: Option<String>,
+    pub strict: Option<bool>,
+    /// fifo | lifo | hifo | specid
+    pub lot_method: Option<String>,
+}
+
+/// Env var map under construction; malformed fields are collected, not fatal
+#[derive(Default)]
+struct EnvVars {
+    vars: BTreeMap<String, String>,
+    errors: Vec<String>,
+}
+
+impl EnvVars {
+    fn set<T: ToString>(&mut self, key: &str, value: Option<T>) {
+        if let Some(value) = value {
+            self.vars.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    fn address(&mut self, field: &str, key: &str, value: Option<&String>) {
+        match value {
+            Some(s) if Address::from_str(s).is_err() => {
+                self.errors.push(format!("{}: invalid address {:?}", field, s));
+            }
+            _ => self.set(key, value),
+        }
+    }
+}
+
+impl ChainConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read chain config: {}", path.display()))?;
+        Self::parse(&contents)
+            .with_context(|| format!("Failed to parse chain config: {}", path.display()))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    pub fn to_toml_string(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// Flatten to env var name → value. Every malformed field is reported in
+    /// one error; missing required fields are checked after the env merge
+    /// (see missing_required).
+    pub fn to_env_vars(&self) -> Result<BTreeMap<String, String>> {
+        let mut env = EnvVars::default();
+
+        let n = &self.network;
+        env.set("CHAIN_NAME", n.chain_name.as_ref());
+        env.set("CHAIN_ID", n.chain_id);
+        env.set("RPC_URL", n.rpc_url.as_ref());
+        env.set("PRIVATE_RPC_URL", n.private_rpc_url.as_ref());
+        env.set("PRIVATE_KEY", n.private_key.as_ref());
+        env.address("network.multicall3_address", "MULTICALL3_ADDRESS", n.multicall3_address.as_ref());
+        env.set("ESTIMATED_GAS_COST_USD", n.estimated_gas_cost_usd);
+        env.set("NATIVE_TOKEN_PRICE_USD", n.native_token_price_usd);
+        env.set("NATIVE_PRICE_PAIR", n.native_price_pair.as_ref());
+        env.set("CONFIRMATION_DEPTH", n.confirmation_depth);
+
+        let r = &self.routers;
+        let dexes = [
+            ("uniswap", "UNISWAP", false, &r.uniswap),
+            ("sushiswap", "SUSHISWAP", false, &r.sushiswap),
+            ("apeswap", "APESWAP", false, &r.apeswap),
+            ("uniswap_v3", "UNISWAP_V3", true, &r.uniswap_v3),
+            ("sushiswap_v3", "SUSHISWAP_V3", true, &r.sushiswap_v3),
+            ("quickswap_v3", "QUICKSWAP_V3", true, &r.quickswap_v3),
+        ];
+        for (name, prefix, is_v3, dex) in dexes {
+            let Some(dex) = dex else { continue };
+            let field = format!("routers.{}", name);
+            env.address(&format!("{}.router", field), &format!("{}_ROUTER", prefix), dex.router.as_ref());
+            env.address(&format!("{}.factory", field), &format!("{}_FACTORY", prefix), dex.factory.as_ref());
+            if is_v3 {
+                env.address(&format!("{}.quoter", field), &format!("{}_QUOTER", prefix), dex.quoter.as_ref());
+            } else if dex.quoter.is_some() {
+                env.errors.push(format!("{}.quoter: V2 DEXs have no quoter", field));
+            }
+            match (name, dex.quoter_is_v2) {
+                ("uniswap_v3", is_v2) => env.set("UNISWAP_V3_QUOTER_IS_V2", is_v2),
+                (_, Some(_)) => env.errors.push(format!("{}.quoter_is_v2: only supported for uniswap_v3", field)),
+                _ => {}
+            }
+        }
+
+        let mut priorities = BTreeSet::new();
+        for (i, token) in self.quote_tokens.iter().enumerate() {
+            let field = format!("quote_tokens[{}]", i);
+            if !priorities.insert(token.priority) {
+                env.errors.push(format!("{}.priority: duplicate priority {}", field, token.priority));
+                continue;
+            }
+            let usd_price = match (token.usd_price_source, token.usd_price) {
+                (UsdPriceSource::Peg, None) => 1.0,
+                (UsdPriceSource::Fixed, Some(price)) if price > 0.0 => price,
+                (UsdPriceSource::Peg, Some(_)) => {
+                    env.errors.push(format!("{}.usd_price: only used with usd_price_source = \"fixed\"", field));
+                    continue;
+                }
+                (UsdPriceSource::Fixed, _) => {
+                    env.errors.push(format!("{}.usd_price: usd_price_source = \"fixed\" needs a positive usd_price", field));
+                    continue;
+                }
+            };
+            match token.priority {
+                1 => {
+                    env.address(&format!("{}.address", field), "QUOTE_TOKEN_ADDRESS", Some(&token.address));
+                    env.set("QUOTE_TOKEN_PRICE_USD", Some(usd_price));
+                }
+                // BotConfig::quote_price_usd prices the native variant at $1
+                2 if usd_price == 1.0 => {
+                    env.address(&format!("{}.address", field), "QUOTE_TOKEN_ADDRESS_NATIVE", Some(&token.address));
+                }
+                2 => env.errors.push(format!("{}.usd_price_source: the priority 2 quote token must be pegged at $1", field)),
+                p => env.errors.push(format!("{}.priority: {} not supported (1 = primary, 2 = native variant)", field, p)),
+            }
+        }
+
+        let t = &self.trading;
+        match &t.pairs {
+            Some(pairs) if pairs.is_empty() => env.errors.push("trading.pairs: at least one pair required".to_string()),
+            Some(pairs) => env.set(
+                "TRADING_PAIRS",
+                Some(
+                    pairs
+                        .iter()
+                        .map(|p| format!("{}:{}:{}", p.token0, p.token1, p.symbol))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                ),
+            ),
+            None => {}
+        }
+        env.set("MIN_PROFIT_USD", t.min_profit_usd);
+        env.set("MAX_TRADE_SIZE_USD", t.max_trade_size_usd);
+        env.set("MAX_SLIPPAGE_PERCENT", t.max_slippage_percent);
+        env.set("POLL_INTERVAL_MS", t.poll_interval_ms);
+        env.set("MAX_GAS_PRICE_GWEI", t.max_gas_price_gwei);
+        env.set("ROUTE_COOLDOWN_BLOCKS", t.route_cooldown_blocks);
+        env.set("LIVE_MODE", t.live_mode);
+        env.address("trading.arb_executor_address", "ARB_EXECUTOR_ADDRESS", t.arb_executor_address.as_ref());
+        env.set("WHITELIST_FILE", t.whitelist_file.as_ref());
+        env.set("POOL_STATE_FILE", t.pool_state_file.as_ref());
+
+        let m = &self.mempool;
+        env.set("MEMPOOL_MONITOR", m.mode.as_ref());
+        env.set("MEMPOOL_MIN_PROFIT_USD", m.min_profit_usd);
+        env.set("MEMPOOL_GAS_LIMIT", m.gas_limit);
+        env.set("MEMPOOL_MIN_PRIORITY_GWEI", m.min_priority_gwei);
+        env.set("MEMPOOL_GAS_PROFIT_CAP", m.gas_profit_cap);
+
+        let x = &self.tax;
+        env.set("TAX_LOG_ENABLED", x.enabled);
+        env.set("TAX_LOG_DIR", x.log_dir.as_ref());
+        env.set("TAX_LOG_STRICT", x.strict);
+        env.set("TAX_LOT_METHOD", x.lot_method.as_ref());
+
+        for (key, value) in &self.env {
+            if env.vars.contains_key(key) {
+                env.errors.push(format!("env.{}: already set by a section field", key));
+                continue;
+            }
+            let value = match value {
+                toml::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            env.vars.insert(key.clone(), value);
+        }
+
+        if !env.errors.is_empty() {
+            anyhow::bail!("Invalid chain config ({} error(s)): {}", env.errors.len(), env.errors.join("; "));
+        }
+        Ok(env.vars)
+    }
+
+    /// Sectioned view of a loaded config (migrating an .env deployment).
+    /// PRIVATE_KEY and unsectioned settings are left out.
+    pub fn from_bot_config(config: &BotConfig) -> Self {
+        let addr = |a: Address| format!("{:?}", a);
+        let dex = |router: Option<Address>, factory: Option<Address>, quoter: Option<Address>| {
+            (router.is_some() || factory.is_some() || quoter.is_some()).then(|| DexSection {
+                router: router.map(addr),
+                factory: factory.map(addr),
+                quoter: quoter.map(addr),
+                quoter_is_v2: None,
+            })
+        };
+
+        let mut uniswap_v3 = dex(config.uniswap_v3_router, config.uniswap_v3_factory, config.uniswap_v3_quoter);
+        if uniswap_v3.is_some() || config.uniswap_v3_quoter_is_v2 {
+            uniswap_v3.get_or_insert_with(DexSection::default).quoter_is_v2 = Some(config.uniswap_v3_quoter_is_v2);
+        }
+
+        let mut quote_tokens = vec![QuoteTokenSection {
+            symbol: None,
+            address: addr(config.quote_token_address),
+            priority: 1,
+            usd_price_source: if config.quote_token_price_usd == 1.0 {
+                UsdPriceSource::Peg
+            } else {
+                UsdPriceSource::Fixed
+            },
+            usd_price: (config.quote_token_price_usd != 1.0).then_some(config.quote_token_price_usd),
+        }];
+        if let Some(native) = config.quote_token_address_native {
+            quote_tokens.push(QuoteTokenSection {
+                symbol: None,
+                address: addr(native),
+                priority: 2,
+                usd_price_source: UsdPriceSource::Peg,
+                usd_price: None,
+            });
+        }
+
+        Self {
+            network: NetworkSection {
+                chain_name: Some(config.chain_name.clone()),
+                chain_id: Some(config.chain_id),
+                rpc_url: Some(config.rpc_url.clone()),
+                private_rpc_url: config.private_rpc_url.clone(),
+                private_key: None,
+                multicall3_address: config.multicall3_address.map(addr),
+                estimated_gas_cost_usd: Some(config.estimated_gas_cost_usd),
+                native_token_price_usd: Some(config.native_token_price_usd),
+                // Explicit "" keeps a disabled live price from reverting to the chain default
+                native_price_pair: Some(config.native_price_pair.clone().unwrap_or_default()),
+                confirmation_depth: Some(config.confirmation_depth),
+            },
+            routers: RoutersSection {
+                uniswap: dex(Some(config.uniswap_router), Some(config.uniswap_factory), None),
+                sushiswap: dex(Some(config.sushiswap_router), Some(config.sushiswap_factory), None),
+                apeswap: dex(config.apeswap_router, config.apeswap_factory, None),
+                uniswap_v3,
+                sushiswap_v3: dex(config.sushiswap_v3_router, config.sushiswap_v3_factory, config.sushiswap_v3_quoter),
+                quickswap_v3: dex(config.quickswap_v3_router, config.quickswap_v3_factory, config.quickswap_v3_quoter),
+            },
+            quote_tokens,
+            trading: TradingSection {
+                pairs: Some(config.pairs.clone()),
+                min_profit_usd: Some(config.min_profit_usd),
+                max_trade_size_usd: Some(config.max_trade_size_usd),
+                max_slippage_percent: Some(config.max_slippage_percent),
+                poll_interval_ms: Some(config.poll_interval_ms),
+                max_gas_price_gwei: Some(config.max_gas_price_gwei),
+                route_cooldown_blocks: Some(config.route_cooldown_blocks),
+                live_mode: Some(config.live_mode),
+                arb_executor_address: config.arb_executor_address.map(addr),
+                whitelist_file: config.whitelist_file.clone(),
+                pool_state_file: config.pool_state_file.clone(),
+            },
+            mempool: MempoolSection {
+                mode: Some(config.mempool_monitor_mode.clone()),
+                min_profit_usd: Some(config.mempool_min_profit_usd),
+                gas_limit: Some(config.mempool_gas_limit),
+                min_priority_gwei: Some(config.mempool_min_priority_gwei),
+                gas_profit_cap: Some(config.mempool_gas_profit_cap),
+            },
+            tax: TaxSection {
+                enabled: Some(config.tax_log_enabled),
+                log_dir: config.tax_log_dir.clone(),
+                strict: Some(config.tax_log_strict),
+                lot_method: Some(config.tax_lot_method.clone()),
+            },
+            env: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::config::config_from_vars;
+
+    const POLYGON_TOML: &str = r#"
+[network]
+chain_name = "polygon"
+chain_id = 137
+rpc_url = "wss://polygon.example/ws"
+multicall3_address = "0xcA11bde05977b3631167028862bE2a173976CA11"
+
+[routers.uniswap]
+router = "0xE592427A0AEce92De3Edee1F18E0157C05861564"
+factory = "0x1F98431c8aD98523631AE4a59f267346ea31F984"
+
+[routers.sushiswap]
+router = "0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506"
+factory = "0xc35DADB65012eC5796536bD9864eD8773aBc74C4"
+
+[routers.uniswap_v3]
+factory = "0x1F98431c8aD98523631AE4a59f267346ea31F984"
+quoter = "0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6"
+
+[[quote_tokens]]
+symbol = "USDC.e"
+address = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174"
+priority = 1
+
+[[quote_tokens]]
+symbol = "USDC"
+address = "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359"
+priority = 2
+
+[trading]
+min_profit_usd = 0.1
+max_trade_size_usd = 500.0
+max_slippage_percent = 0.5
+poll_interval_ms = 100
+
+[[trading.pairs]]
+token0 = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619"
+token1 = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174"
+symbol = "WETH/USDC"
+
+[mempool]
+mode = "observe"
+
+[tax]
+lot_method = "hifo"
+
+[env]
+GAS_ORACLE_PERCENTILE = 75
+"#;
+
+    fn lookup<'a>(
+        toml_vars: &'a BTreeMap<String, String>,
+        env: &'a [(&'a str, &'a str)],
+    ) -> impl Fn(&str) -> Result<String, VarError> + 'a {
+        move |key| match toml_vars.get(key) {
+            Some(value) => Ok(value.clone()),
+            None => env
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+                .ok_or(VarError::NotPresent),
+        }
+    }
+
+    #[test]
+    fn test_toml_builds_config_with_env_fallback() {
+        let vars = ChainConfig::parse(POLYGON_TOML).unwrap().to_env_vars().unwrap();
+        // TOML wins over the env; PRIVATE_KEY only lives in the env
+        let env = [("PRIVATE_KEY", "abc123"), ("RPC_URL", "wss://stale/ws"), ("ROUTE_COOLDOWN_BLOCKS", "3")];
+        let config = config_from_vars(&lookup(&vars, &env)).unwrap();
+
+        assert_eq!(config.rpc_url, "wss://polygon.example/ws");
+        assert_eq!(config.private_key, "abc123");
+        assert_eq!(config.route_cooldown_blocks, 3);
+        assert_eq!(config.chain_id, 137);
+        assert_eq!(config.quote_token_address_native, Some(Address::from_str("0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359").unwrap()));
+        assert_eq!(config.quote_token_price_usd, 1.0);
+        assert_eq!(config.uniswap_v3_router, None);
+        assert!(config.uniswap_v3_quoter.is_some());
+        assert_eq!(config.pairs.len(), 1);
+        assert_eq!(config.pairs[0].symbol, "WETH/USDC");
+        assert_eq!(config.mempool_monitor_mode, "observe");
+        assert_eq!(config.tax_lot_method, "hifo");
+        assert_eq!(config.gas_oracle_percentile, 75.0);
+        // Unset fields keep the env-path defaults (polygon confirmation depth)
+        assert_eq!(config.confirmation_depth, 10);
+
+        // The shipped example stays loadable
+        let example = ChainConfig::parse(include_str!("../../../config/polygon.example.toml")).unwrap();
+        let vars = example.to_env_vars().unwrap();
+        let config = config_from_vars(&lookup(&vars, &env)).unwrap();
+        assert_eq!(config.pairs.len(), 2);
+        assert!(config.tax_log_enabled);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut original = create_test_config();
+        original.rpc_url = "wss://rpc.example/ws".to_string();
+        original.chain_name = "base".to_string();
+        original.quote_token_price_usd = 3000.0;
+        original.quote_token_address_native = Some(Address::from_low_u64_be(0xabc));
+        original.uniswap_v3_quoter_is_v2 = true;
+        original.arb_executor_address = Some(Address::from_low_u64_be(0xdef));
+        original.whitelist_file = Some("/tmp/whitelist.json".to_string());
+        original.tax_log_enabled = true;
+        original.mempool_monitor_mode = "execute".to_string();
+        original.pairs = vec![TradingPairConfig {
+            token0: "0x4200000000000000000000000000000000000006".to_string(),
+            token1: "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913".to_string(),
+            symbol: "WETH/USDC".to_string(),
+        }];
+
+        let toml = ChainConfig::from_bot_config(&original).to_toml_string().unwrap();
+        let parsed = ChainConfig::parse(&toml).unwrap();
+        assert_eq!(parsed, ChainConfig::from_bot_config(&original));
+
+        let vars = parsed.to_env_vars().unwrap();
+        assert!(!vars.contains_key("PRIVATE_KEY"));
+        let env = [("PRIVATE_KEY", "secret")];
+        let loaded = config_from_vars(&lookup(&vars, &env)).unwrap();
+
+        assert_eq!(ChainConfig::from_bot_config(&loaded), parsed);
+        assert_eq!(loaded.private_key, "secret");
+        assert_eq!(loaded.rpc_url, original.rpc_url);
+        assert_eq!(loaded.chain_name, "base");
+        assert_eq!(loaded.quote_token_price_usd, 3000.0);
+        assert_eq!(loaded.quote_token_address_native, original.quote_token_address_native);
+        assert_eq!(loaded.uniswap_v3_factory, original.uniswap_v3_factory);
+        assert!(loaded.uniswap_v3_quoter_is_v2);
+        assert_eq!(loaded.arb_executor_address, original.arb_executor_address);
+        assert_eq!(loaded.native_price_pair, original.native_price_pair);
+        assert_eq!(loaded.pairs.len(), original.pairs.len());
+    }
+
+    #[test]
+    fn test_validation_reports_every_problem() {
+        // Sparse TOML, empty env: every missing required field named at once
+        let partial = ChainConfig::parse("[network]\nchain_id = 8453\n\n[trading]\npoll_interval_ms = 50\n").unwrap();
+        let vars = partial.to_env_vars().unwrap();
+        let err = config_from_vars(&lookup(&vars, &[])).unwrap_err().to_string();
+        for expected in [
+            "[network] rpc_url (RPC_URL)",
+            "[network] private_key (PRIVATE_KEY)",
+            "[routers.uniswap] router (UNISWAP_ROUTER)",
+            "[routers.sushiswap] factory (SUSHISWAP_FACTORY)",
+            "[trading] pairs (TRADING_PAIRS)",
+            "[trading] max_slippage_percent (MAX_SLIPPAGE_PERCENT)",
+        ] {
+            assert!(err.contains(expected), "{} missing from: {}", expected, err);
+        }
+        assert!(err.starts_with("Missing 10 required setting(s)"), "{}", err);
+        assert!(!err.contains("CHAIN_ID") && !err.contains("POLL_INTERVAL_MS"));
+
+        // Malformed fields are collected too
+        let bad = ChainConfig::parse(
+            r#"
+[routers.sushiswap]
+router = "0xnope"
+quoter = "0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506"
+
+[[quote_tokens]]
+address = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174"
+priority = 1
+usd_price_source = "fixed"
+
+[trading]
+pairs = []
+poll_interval_ms = 100
+
+[env]
+POLL_INTERVAL_MS = 100
+"#,
+        )
+        .unwrap();
+        let err = bad.to_env_vars().unwrap_err().to_string();
+        assert!(err.starts_with("Invalid chain config (5 error(s))"), "{}", err);
+        assert!(err.contains("routers.sushiswap.router: invalid address"));
+        assert!(err.contains("routers.sushiswap.quoter: V2 DEXs have no quoter"));
+        assert!(err.contains("quote_tokens[0].usd_price"));
+        assert!(err.contains("trading.pairs: at least one pair required"));
+        assert!(err.contains("env.POLL_INTERVAL_MS: already set by a section field"));
+
+        // Typos are rejected by name rather than silently ignored
+        let err = ChainConfig::parse("[trading]\nmin_profit = 1.0\n").unwrap_err().to_string();
+        assert!(err.contains("min_profit"), "{}", err);
+    }
+}
@@ -0,0 +1,382 @@
+//! Failure Injection (chaos testing)
+//!
+//! Compiled in, inert unless CHAOS_MODE=true. TradeExecutor consults a
+//! FaultInjector at five seams of the execution pipeline; the production
+//! injector (NoFaults) always answers None, so with chaos off every code path
+//! is the one that runs live.
+//!
+//! Seams (FaultPoint):
+//!   before_send   — tx built, not yet handed to the RPC (atomic, mempool, rescue, legacy swap)
+//!   after_send    — tx hash in hand, before receipt polling starts (atomic, mempool, rescue)
+//!   receipt_poll  — each eth_getTransactionReceipt poll (same paths as after_send)
+//!   quoter        — preflight batch and per-call V3 Quoter checks
+//!   nonce_fetch   — eth_getTransactionCount on nonce initialization
+//!
+//! Faults:
+//!   rpc_error {message}  — the seam's RPC returns an error
+//!   timeout              — the seam's RPC never answers (receipt: tx unconfirmed)
+//!   revert               — the tx / quote reverts (receipt status 0, quote rejection)
+//!   nonce_gap {offset}   — nonce_fetch only: tracked nonce = pending + offset
+//!
+//! Triggers (CHAOS_CONFIG_FILE, JSON): `on_calls` lists 1-based call numbers
+//! at that seam ([3] = the 3rd submission), `every` fires on every Nth call,
+//! `probability` rolls a seeded RNG (reproducible for a given `seed`).
+//! `max_fires` caps a rule. A rule with no trigger fires on every call; the
+//! first matching rule for a seam wins.
+//!
+//! ```json
+//! {"seed": 7, "rules": [
+//!   {"point": "before_send", "fault": {"kind": "rpc_error", "message": "nonce too low"}, "on_calls": [3]},
+//!   {"point": "receipt_poll", "fault": {"kind": "timeout"}, "probability": 0.5, "max_fires": 20}
+//! ]}
+//! ```
+//!
+//! Rehearse against a fork or testnet: faults only fire on live (non dry-run)
+//! submissions, since dry run never reaches the seams.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::paper_trading::determinism::SimRng;
+use crate::types::BotConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Suffix on every injected error so logs and ledgers show the fault was synthetic
+pub const CHAOS_TAG: &str = "[chaos]";
+
+/// Well-defined seam in the execution pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultPoint {
+    BeforeSend,
+    AfterSend,
+    ReceiptPoll,
+    Quoter,
+    NonceFetch,
+}
+
+impl FaultPoint {
+    pub const ALL: [FaultPoint; 5] = [
+        FaultPoint::BeforeSend,
+        FaultPoint::AfterSend,
+        FaultPoint::ReceiptPoll,
+        FaultPoint::Quoter,
+        FaultPoint::NonceFetch,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FaultPoint::BeforeSend => "before_send",
+            FaultPoint::AfterSend => "after_send",
+            FaultPoint::ReceiptPoll => "receipt_poll",
+            FaultPoint::Quoter => "quoter",
+            FaultPoint::NonceFetch => "nonce_fetch",
+        }
+    }
+}
+
+impl fmt::Display for FaultPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What an injected failure looks like to the seam
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Fault {
+    RpcError { message: String },
+    Timeout,
+    Revert,
+    NonceGap { offset: i64 },
+}
+
+impl Fault {
+    /// Error text for a seam whose RPC `label` was hit by this fault. Mirrors
+    /// what the real failure says (rpc_timeout wording, node revert wording) so
+    /// the caller's classification sees the same strings it would in production.
+    pub fn error_message(&self, label: &str) -> String {
+        match self {
+            Fault::RpcError { message } => format!("{} failed: {} {}", label, message, CHAOS_TAG),
+            Fault::Timeout => format!("{} timed out after 10s {}", label, CHAOS_TAG),
+            Fault::Revert => format!("{}: execution reverted: InsufficientProfit {}", label, CHAOS_TAG),
+            Fault::NonceGap { offset } => format!("{} nonce gap {:+} {}", label, offset, CHAOS_TAG),
+        }
+    }
+
+    fn valid_at(&self, point: FaultPoint) -> bool {
+        match self {
+            Fault::NonceGap { .. } => point == FaultPoint::NonceFetch,
+            Fault::Revert => point != FaultPoint::NonceFetch,
+            Fault::RpcError { .. } | Fault::Timeout => true,
+        }
+    }
+}
+
+/// One injection rule: a fault at a seam plus when it fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaultRule {
+    pub point: FaultPoint,
+    pub fault: Fault,
+    /// 1-based call numbers at this seam that fire
+    #[serde(default)]
+    pub on_calls: Vec<u64>,
+    /// Fire on every Nth call at this seam
+    #[serde(default)]
+    pub every: Option<u64>,
+    /// Fire with this probability per call (seeded RNG)
+    #[serde(default)]
+    pub probability: Option<f64>,
+    /// Stop firing after this many injections
+    #[serde(default)]
+    pub max_fires: Option<u64>,
+}
+
+impl FaultRule {
+    fn unconditional(&self) -> bool {
+        self.on_calls.is_empty() && self.every.is_none() && self.probability.is_none()
+    }
+}
+
+/// CHAOS_CONFIG_FILE contents
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    #[serde(default)]
+    pub seed: u64,
+    pub rules: Vec<FaultRule>,
+}
+
+impl ChaosConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("read chaos config {}", path.display()))?;
+        let config: ChaosConfig = serde_json::from_str(&content)
+            .with_context(|| format!("parse chaos config {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject rules that could never fire or make no sense at their seam
+    pub fn validate(&self) -> Result<()> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if !rule.fault.valid_at(rule.point) {
+                bail!("chaos rule {}: {:?} cannot be injected at {}", i, rule.fault, rule.point);
+            }
+            if rule.every == Some(0) || rule.on_calls.contains(&0) {
+                bail!("chaos rule {}: call numbers are 1-based", i);
+            }
+            if let Some(p) = rule.probability {
+                if !(0.0..=1.0).contains(&p) {
+                    bail!("chaos rule {}: probability {} outside [0, 1]", i, p);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Consulted by the executor at every seam. None = proceed normally.
+pub trait FaultInjector: Send + Sync {
+    fn inject(&self, point: FaultPoint) -> Option<Fault>;
+}
+
+/// Production injector: never fires
+pub struct NoFaults;
+
+impl FaultInjector for NoFaults {
+    #[inline]
+    fn inject(&self, _point: FaultPoint) -> Option<Fault> {
+        None
+    }
+}
+
+/// Rule-driven injector loaded from CHAOS_CONFIG_FILE
+pub struct ChaosInjector {
+    rules: Vec<FaultRule>,
+    /// Calls seen per seam (FaultPoint::index)
+    calls: [AtomicU64; 5],
+    /// Injections so far per rule
+    rule_fires: Vec<AtomicU64>,
+    /// Injections so far per seam
+    point_fires: [AtomicU64; 5],
+    rng: SimRng,
+}
+
+impl ChaosInjector {
+    pub fn new(config: ChaosConfig) -> Self {
+        let rule_fires = config.rules.iter().map(|_| AtomicU64::new(0)).collect();
+        Self {
+            rng: SimRng::from_seed(config.seed),
+            rules: config.rules,
+            calls: Default::default(),
+            rule_fires,
+            point_fires: Default::default(),
+        }
+    }
+
+    /// Calls seen at a seam (fired or not)
+    pub fn calls(&self, point: FaultPoint) -> u64 {
+        self.calls[point.index()].load(Ordering::SeqCst)
+    }
+
+    /// Faults injected at a seam
+    pub fn fired(&self, point: FaultPoint) -> u64 {
+        self.point_fires[point.index()].load(Ordering::SeqCst)
+    }
+
+    /// "before_send 1/3, receipt_poll 0/0, ..." (fired/calls) for the status log
+    pub fn summary(&self) -> String {
+        FaultPoint::ALL
+            .iter()
+            .map(|p| format!("{} {}/{}", p, self.fired(*p), self.calls(*p)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn rule_fires(&self, rule: &FaultRule, call: u64) -> bool {
+        if rule.unconditional() {
+            return true;
+        }
+        // Roll only when a probability is configured, so deterministic rules
+        // never consume RNG draws and shift the sequence of random ones.
+        rule.on_calls.contains(&call)
+            || rule.every.is_some_and(|n| call.is_multiple_of(n))
+            || rule.probability.is_some_and(|p| self.rng.next_f64() < p)
+    }
+}
+
+impl FaultInjector for ChaosInjector {
+    fn inject(&self, point: FaultPoint) -> Option<Fault> {
+        let call = self.calls[point.index()].fetch_add(1, Ordering::SeqCst) + 1;
+        for (i, rule) in self.rules.iter().enumerate() {
+            if rule.point != point {
+                continue;
+            }
+            if rule.max_fires.is_some_and(|max| self.rule_fires[i].load(Ordering::SeqCst) >= max) {
+                continue;
+            }
+            if !self.rule_fires(rule, call) {
+                continue;
+            }
+            self.rule_fires[i].fetch_add(1, Ordering::SeqCst);
+            self.point_fires[point.index()].fetch_add(1, Ordering::SeqCst);
+            warn!("CHAOS: injecting {:?} at {} (call #{})", rule.fault, point, call);
+            return Some(rule.fault.clone());
+        }
+        None
+    }
+}
+
+/// Injector for this run: NoFaults unless CHAOS_MODE=true, in which case
+/// CHAOS_CONFIG_FILE is required and must parse.
+pub fn injector_from_config(config: &BotConfig) -> Result<Option<Arc<ChaosInjector>>> {
+    if !config.chaos_mode {
+        return Ok(None);
+    }
+    let path = config
+        .chaos_config_file
+        .as_deref()
+        .ok_or_else(|| anyhow!("CHAOS_MODE=true requires CHAOS_CONFIG_FILE"))?;
+    let chaos = ChaosConfig::load(Path::new(path))?;
+    warn!(
+        "⚠️ CHAOS MODE: {} fault rule(s) from {} (seed {}) — injected failures are tagged {}",
+        chaos.rules.len(),
+        path,
+        chaos.seed,
+        CHAOS_TAG
+    );
+    Ok(Some(Arc::new(ChaosInjector::new(chaos))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(point: FaultPoint, fault: Fault) -> FaultRule {
+        FaultRule { point, fault, on_calls: vec![], every: None, probability: None, max_fires: None }
+    }
+
+    fn fires(injector: &ChaosInjector, point: FaultPoint, calls: u64) -> Vec<u64> {
+        (1..=calls).filter(|_| injector.inject(point).is_some()).collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn test_no_faults_is_inert() {
+        for point in FaultPoint::ALL {
+            assert_eq!(NoFaults.inject(point), None);
+        }
+    }
+
+    #[test]
+    fn test_deterministic_triggers() {
+        let mut third = rule(FaultPoint::BeforeSend, Fault::Revert);
+        third.on_calls = vec![3];
+        let mut every_other = rule(FaultPoint::ReceiptPoll, Fault::Timeout);
+        every_other.every = Some(2);
+        every_other.max_fires = Some(2);
+        let injector = ChaosInjector::new(ChaosConfig { seed: 0, rules: vec![third, every_other] });
+
+        // Fail the 3rd submission only
+        assert_eq!(fires(&injector, FaultPoint::BeforeSend, 6), vec![3]);
+        // Every 2nd poll, capped at 2 injections
+        assert_eq!(fires(&injector, FaultPoint::ReceiptPoll, 8), vec![2, 4]);
+        // Seams without rules are counted but never fire
+        assert_eq!(fires(&injector, FaultPoint::Quoter, 3), Vec::<u64>::new());
+        assert_eq!((injector.fired(FaultPoint::BeforeSend), injector.calls(FaultPoint::BeforeSend)), (1, 6));
+        assert_eq!(injector.calls(FaultPoint::Quoter), 3);
+    }
+
+    #[test]
+    fn test_probability_is_reproducible_per_seed() {
+        let config = |seed| {
+            let mut r = rule(FaultPoint::Quoter, Fault::Timeout);
+            r.probability = Some(0.3);
+            ChaosConfig { seed, rules: vec![r] }
+        };
+        let a = fires(&ChaosInjector::new(config(42)), FaultPoint::Quoter, 200);
+        let b = fires(&ChaosInjector::new(config(42)), FaultPoint::Quoter, 200);
+        assert_eq!(a, b);
+        assert!(a.len() > 30 && a.len() < 90, "~30% of 200, got {}", a.len());
+    }
+
+    #[test]
+    fn test_config_parse_and_validate() {
+        let json = r#"{"seed": 7, "rules": [
+            {"point": "before_send", "fault": {"kind": "rpc_error", "message": "nonce too low"}, "on_calls": [3]},
+            {"point": "nonce_fetch", "fault": {"kind": "nonce_gap", "offset": -1}}
+        ]}"#;
+        let config: ChaosConfig = serde_json::from_str(json).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.rules[0].fault, Fault::RpcError { message: "nonce too low".into() });
+        assert_eq!(config.rules[1].fault, Fault::NonceGap { offset: -1 });
+
+        let bad = |r: FaultRule| ChaosConfig { seed: 0, rules: vec![r] }.validate().is_err();
+        assert!(bad(rule(FaultPoint::BeforeSend, Fault::NonceGap { offset: 1 })));
+        assert!(bad(rule(FaultPoint::NonceFetch, Fault::Revert)));
+        let mut zero = rule(FaultPoint::Quoter, Fault::Timeout);
+        zero.on_calls = vec![0];
+        assert!(bad(zero));
+        let mut p = rule(FaultPoint::Quoter, Fault::Timeout);
+        p.probability = Some(1.5);
+        assert!(bad(p));
+    }
+
+    #[test]
+    fn test_error_messages_match_production_wording() {
+        // Classification keys on these substrings
+        assert!(Fault::Timeout.error_message("eth_getTransactionCount").contains("timed out after 10s"));
+        assert!(Fault::Revert.error_message("Atomic tx send").contains("execution reverted"));
+        let rpc = Fault::RpcError { message: "nonce too low".into() }.error_message("eth_sendRawTransaction");
+        assert!(rpc.contains("nonce too low") && rpc.ends_with(CHAOS_TAG));
+    }
+}
@@ -5,32 +5,170 @@
 //! - load_config(): loads from .env (data collector, dev/paper workflows)
 //! - load_config_from_file(): loads from a specific env file (live bot uses .env.live)
 //!
+//! Either can be layered under a chain TOML (see chain_config): CONFIG_TOML=path,
+//! or load_config_with_toml(). TOML values take precedence over the env.
+//!
 //! Modified: 2026-01-29 - Added load_config_from_file() for live/dev config separation
 //! Modified: 2026-01-31 - Multi-chain: chain_name, quote_token_address, estimated_gas_cost_usd
+//! Modified: 2026-10-16 - PREFLIGHT_BATCH toggle
+//! Modified: 2026-10-16 - Canary mode thresholds (CANARY_*)
+//! Modified: 2026-10-16 - Stranded-funds alarm (STRANDED_*, AUTO_RESCUE, ALERT_WEBHOOK_URL)
+//! Modified: 2026-10-16 - Gas history logging (GAS_HISTORY_ENABLED, GAS_HISTORY_SAMPLE_BLOCKS)
+//! Modified: 2026-10-16 - Pool migration detector (MIGRATION_*, AUTO_MIGRATE)
+//! Modified: 2026-10-16 - Pre-screen export (PRESCREEN_EXPORT_ENABLED)
+//! Modified: 2026-10-16 - Watchdog (WATCHDOG_ENABLED, WATCHDOG_*_SECS, WATCHDOG_EXIT)
+//! Modified: 2026-10-16 - QUOTE_TOKEN_PRICE_USD (trade_size_usd normalization)
+//! Modified: 2026-10-16 - CHAOS_MODE, CHAOS_CONFIG_FILE (failure injection)
+//! Modified: 2026-10-16 - EXECUTION_CONSTRAINTS_ENABLED (multi-opp capture research)
+//! Modified: 2026-10-16 - TOKEN_REVALIDATION_HOURS (token metadata drift quarantine)
+//! Modified: 2026-10-16 - ANOMALY_MONITOR_ENABLED, ANOMALY_WINDOW_SECS, ANOMALY_SIGMA, ANOMALY_MIN_DURATION_SECS
+//! Modified: 2026-10-16 - INFLIGHT_JOURNAL_ENABLED, INFLIGHT_RECOVERY_POLICY (crash-safe submissions)
+//! Modified: 2026-10-16 - MIN_POOL_LIQUIDITY(_OVERRIDES), MAX_LIQUIDITY_FRACTION, LIQUIDITY_GUARD_MODE
+//! Modified: 2026-10-16 - TELEMETRY_ENABLED, TELEMETRY_ENDPOINT, TELEMETRY_INTERVAL_SECS, TELEMETRY_INCLUDE_ADDRESSES
+//! Modified: 2026-10-16 - TAX_LOG_STRICT
+//! Modified: 2026-10-16 - SLIPPAGE_FEEDBACK_ENABLED, _MIN_SAMPLES, _PERCENTILE, _BUFFER_PERCENT
+//! Modified: 2026-10-16 - CONFIRMATION_DEPTH (per-chain default: polygon 10, base 2)
+//! Modified: 2026-10-16 - EXPOSURE_PACING_ENABLED, EXPOSURE_MAX_*_FRACTION, EXPOSURE_OVERRIDES, EXPOSURE_MIN_NOTIONAL_USD
+//! Modified: 2026-10-16 - DEADMAN_URL, DEADMAN_FAIL_URL, DEADMAN_METHOD, DEADMAN_INTERVAL_SECS, DEADMAN_MAX_BLOCK_AGE_SECS, DEADMAN_CRITERIA
+//! Modified: 2026-10-16 - PRESCREEN_SKIP_ENABLED, PRESCREEN_SKIP_STREAK, PRESCREEN_REVERIFY_EVERY
+//! Modified: 2026-10-16 - BURST_THRESHOLD, BURST_TOP_K, BURST_MIN_SPREAD_PERCENT, BURST_EXIT_BLOCKS
+//! Modified: 2026-10-16 - SHADOW_ROLE, SHADOW_FEED_DIR, SHADOW_MATCH_LAG_BLOCKS
+//! Modified: 2026-10-16 - COMPETITION_MODEL_ENABLED, COMPETITION_MIN_SAMPLES, COMPETITION_GAS_REFUND_FRACTION, COMPETITOR_ADDRESSES
+//! Modified: 2026-10-16 - PRICE_LOG_QUEUE_CAPACITY, TAX_WRITE_QUEUE_CAPACITY, TAX_ENQUEUE_TIMEOUT_MS, IO_WRITE_TIMEOUT_MS
+//! Modified: 2026-10-16 - TRIANGULAR_ENABLED
+//! Modified: 2026-10-16 - OPTIMAL_SIZING
+//! Modified: 2026-10-16 - API_PORT, API_BIND, API_RECENT_OPPORTUNITIES
+//! Modified: 2026-10-16 - ARB_EXECUTOR_MULTI
+//! Modified: 2026-10-16 - NATIVE_PRICE_PAIR
+//! Modified: 2026-10-16 - FLASHLOAN_ENABLED, AAVE_POOL_ADDRESS
+//! Modified: 2026-10-16 - STATS_REPORT_INTERVAL
+//! Modified: 2026-10-16 - REORG_TRACK_BLOCKS
+//! Modified: 2026-10-16 - ALERT_TRADES, ALERT_HALTS, ALERT_WS, ALERT_DAILY_SUMMARY, ALERT_RATE_LIMIT_SECS
+//! Modified: 2026-10-16 - BALANCE_CLAMP_ENABLED, BALANCE_USABLE_FRACTION, MIN_QUOTE_BALANCE_USD
+//! Modified: 2026-10-16 - CURVE_ENABLED
+//! Modified: 2026-10-16 - TAX_LOT_METHOD
+//! Modified: 2026-10-16 - MULTICALL3_ADDRESS
+//! Modified: 2026-10-16 - validate_config(): MEMPOOL_MONITOR=execute requires ARB_EXECUTOR_ADDRESS
+//! Modified: 2026-10-16 - GAS_ORACLE_ENABLED, GAS_ORACLE_PERCENTILE, GAS_ORACLE_BUMP_PERCENT, GAS_ORACLE_WINDOW_BLOCKS, GAS_ORACLE_SAMPLE_BLOCKS, MIN_PRIORITY_GWEI, MAX_PRIORITY_GWEI
+//! Modified: 2026-10-16 - POOL_HEALTH_ENABLED, POOL_HEALTH_MAX_IDLE_BLOCKS, POOL_HEALTH_MIN_V3_LIQUIDITY, POOL_HEALTH_MIN_V2_TVL_USD, POOL_HEALTH_MAX_QUOTER_REJECTIONS, POOL_HEALTH_REJECTION_QUARANTINE_BLOCKS
+//! Modified: 2026-10-16 - Chain TOML config (CONFIG_TOML, load_config_with_toml); all missing required settings reported together
+//! Modified: 2026-10-16 - MAX_PARALLEL_SUBMISSIONS
+//! Modified: 2026-10-16 - CROSS_CHAIN_STATE_FILE, CROSS_CHAIN_DIVERGENCE_BPS, CROSS_CHAIN_MAX_AGE_SECS
+//! Modified: 2026-10-16 - LOG_FORMAT
+//! Modified: 2026-10-16 - MEMPOOL_CALIBRATION_K, MEMPOOL_CALIBRATION_PRIOR_BPS
+//! Modified: 2026-10-16 - GAS_CACHE_ENABLED, GAS_CACHE_MULTIPLIER
+//! Modified: 2026-10-16 - MAX_POOL_AGE_BLOCKS, MAX_OPPORTUNITY_AGE_SECS
+//! Modified: 2026-10-16 - KYBER_ELASTIC_FACTORY, KYBER_ELASTIC_ROUTER, KYBER_ELASTIC_QUOTER
+//! Modified: 2026-10-16 - OPPORTUNITY_DEDUP_TTL_BLOCKS
+//! Modified: 2026-10-16 - INVENTORY_CHECK_INTERVAL_BLOCKS, INVENTORY_REBALANCE_THRESHOLD_USD, AUTO_REBALANCE
+//! Modified: 2026-10-16 - RPC_URLS
+//! Modified: 2026-10-16 - SPLIT_ROUTING
+//! Modified: 2026-10-16 - PRICE_LOG_FORMAT, PRICE_LOG_COMPRESS, PRICE_LOG_RETAIN_DAYS
+//! Modified: 2026-10-16 - MIN_PROFIT_ADAPTIVE, MIN_PROFIT_FLOOR_USD, MIN_PROFIT_CEILING_USD
+//! Modified: 2026-10-16 - ALGEBRA_FEE_REFRESH_BLOCKS
+//! Modified: 2026-10-16 - LATENCY_REPORT_INTERVAL
+//! Modified: 2026-10-16 - BUNDLE_RPC_URL, BUNDLE_RPC_METHOD, BUNDLE_PARAMS_TEMPLATE
+//! Modified: 2026-10-16 - SPREAD_CONFIRM_BLOCKS, SPREAD_HISTORY_BLOCKS
+//! Modified: 2026-10-16 - PRIVATE_KEYS (extra submission wallets; stands in for PRIVATE_KEY)
+//! Modified: 2026-10-16 - PRICE_LOG_BLOCKS
+//! Modified: 2026-10-16 - DODO_V2_PROXY, DODO_APPROVE
+//! Modified: 2026-10-16 - MIDBLOCK_SCAN, MIDBLOCK_SCAN_MAX_PER_BLOCK (validated against MEMPOOL_MONITOR)
+//! Modified: 2026-10-16 - ROUTE_COOLDOWN_SIZE_BUCKETS_USD
+//! Modified: 2026-10-16 - PERMIT2_ADDRESS
+//! Modified: 2026-10-16 - WS_RPC_URL (subscriptions beside an IPC RPC_URL); IPC requires it
+//! Modified: 2026-10-16 - SHADOW_CONFIG / SHADOW_VERIFY_MAX; load_shadow_config() (overrides TOML over live)
+//! Modified: 2026-10-16 - FLASH_V2_ENABLED (V2 pair flash swaps via executeArbFlashV2)
+//! Modified: 2026-10-16 - WATCHDOG_STALL_BLOCKS × EXPECTED_BLOCK_TIME_SECS stall interval; WATCHDOG_RECONNECT_ATTEMPTS
+//! Modified: 2026-10-17 - ALERT_CANARY (canary promote/demote webhook alerts)
 
+use crate::chain_config::{missing_required, ChainConfig};
+use crate::mempool::MempoolMode;
 use crate::types::TradingPairConfig;
 use anyhow::{Context, Result};
 
 // Re-export BotConfig for external access
 pub use crate::types::BotConfig;
 use ethers::types::Address;
+use std::collections::{BTreeMap, HashMap};
+use std::env::VarError;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 /// Load config from default .env file (used by data collector, dev tools)
 pub fn load_config() -> Result<BotConfig> {
     dotenv::dotenv().ok();
-    load_config_inner()
+    load_config_inner(toml_path_from_env().as_deref())
 }
 
 /// Load config from a specific env file (used by live bot with .env.live)
 pub fn load_config_from_file(filename: &str) -> Result<BotConfig> {
     dotenv::from_filename(filename).ok();
-    load_config_inner()
+    load_config_inner(toml_path_from_env().as_deref())
+}
+
+/// Load config from a chain TOML (when given) layered over an env file
+/// (live bot: config/{chain}.toml + .env.{chain} for secrets)
+pub fn load_config_with_toml(env_file: &str, toml_path: Option<&Path>) -> Result<BotConfig> {
+    dotenv::from_filename(env_file).ok();
+    load_config_inner(toml_path)
+}
+
+/// CONFIG_TOML points the env-file loaders at a chain TOML
+fn toml_path_from_env() -> Option<PathBuf> {
+    std::env::var("CONFIG_TOML")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Experimental config of the A/B shadow detector (SHADOW_CONFIG): the
+/// overrides TOML on top of the live config's own TOML and env
+pub fn load_shadow_config(live_toml: Option<&Path>, shadow_toml: &Path) -> Result<BotConfig> {
+    let mut layers = vec![shadow_toml];
+    layers.extend(live_toml);
+    let mut config = load_config_layers(&layers)?;
+    // Never executes, whatever the overrides say
+    config.live_mode = false;
+    Ok(config)
+}
+
+fn load_config_inner(toml_path: Option<&Path>) -> Result<BotConfig> {
+    load_config_layers(&toml_path.into_iter().collect::<Vec<_>>())
 }
 
-fn load_config_inner() -> Result<BotConfig> {
+/// TOML files in precedence order, then the env
+fn load_config_layers(toml_paths: &[&Path]) -> Result<BotConfig> {
+    // TOML fields win; whatever the files leave out (PRIVATE_KEY, unsectioned
+    // settings) still comes from the env
+    let mut toml_vars = BTreeMap::new();
+    for path in toml_paths.iter().rev() {
+        toml_vars.extend(ChainConfig::load(path)?.to_env_vars()?);
+    }
+    config_from_vars(&|key| match toml_vars.get(key) {
+        Some(value) => Ok(value.clone()),
+        None => std::env::var(key),
+    })
+}
+
+/// Build BotConfig from a settings lookup keyed by env var name
+pub(crate) fn config_from_vars(var: &dyn Fn(&str) -> Result<String, VarError>) -> Result<BotConfig> {
+    // Name every missing required setting at once, not just the first
+    // PRIVATE_KEYS stands in for PRIVATE_KEY
+    let missing = missing_required(&|key| match key {
+        "PRIVATE_KEY" => var(key).or_else(|_| var("PRIVATE_KEYS")),
+        _ => var(key),
+    });
+    if !missing.is_empty() {
+        anyhow::bail!("Missing {} required setting(s): {}", missing.len(), missing.join(", "));
+    }
+
+    let private_keys = crate::arbitrage::wallets::parse_private_keys(
+        var("PRIVATE_KEY").ok().as_deref(),
+        var("PRIVATE_KEYS").ok().as_deref(),
+    )?;
+
     let trading_pairs_str =
-        std::env::var("TRADING_PAIRS").context("TRADING_PAIRS not set")?;
+        var("TRADING_PAIRS").context("TRADING_PAIRS not set")?;
 
     let pairs: Vec<TradingPairConfig> = trading_pairs_str
         .split(',')
@@ -49,94 +187,140 @@ fn load_config_inner() -> Result<BotConfig> {
         .collect();
 
     // Load optional ApeSwap addresses
-    let apeswap_router = std::env::var("APESWAP_ROUTER")
+    let apeswap_router = var("APESWAP_ROUTER")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
-    let apeswap_factory = std::env::var("APESWAP_FACTORY")
+    let apeswap_factory = var("APESWAP_FACTORY")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
 
     // Load optional Uniswap V3 addresses (Phase 2)
-    let uniswap_v3_factory = std::env::var("UNISWAP_V3_FACTORY")
+    let uniswap_v3_factory = var("UNISWAP_V3_FACTORY")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
-    let uniswap_v3_router = std::env::var("UNISWAP_V3_ROUTER")
+    let uniswap_v3_router = var("UNISWAP_V3_ROUTER")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
-    let uniswap_v3_quoter = std::env::var("UNISWAP_V3_QUOTER")
+    let uniswap_v3_quoter = var("UNISWAP_V3_QUOTER")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
 
     // Load optional SushiSwap V3 addresses (cross-DEX arb)
-    let sushiswap_v3_factory = std::env::var("SUSHISWAP_V3_FACTORY")
+    let sushiswap_v3_factory = var("SUSHISWAP_V3_FACTORY")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
-    let sushiswap_v3_router = std::env::var("SUSHISWAP_V3_ROUTER")
+    let sushiswap_v3_router = var("SUSHISWAP_V3_ROUTER")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
-    let sushiswap_v3_quoter = std::env::var("SUSHISWAP_V3_QUOTER")
+    let sushiswap_v3_quoter = var("SUSHISWAP_V3_QUOTER")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
 
     // Load optional QuickSwap V3 (Algebra) addresses (cross-DEX arb)
-    let quickswap_v3_factory = std::env::var("QUICKSWAP_V3_FACTORY")
+    let quickswap_v3_factory = var("QUICKSWAP_V3_FACTORY")
+        .ok()
+        .and_then(|s| Address::from_str(&s).ok());
+    let quickswap_v3_router = var("QUICKSWAP_V3_ROUTER")
+        .ok()
+        .and_then(|s| Address::from_str(&s).ok());
+    let quickswap_v3_quoter = var("QUICKSWAP_V3_QUOTER")
+        .ok()
+        .and_then(|s| Address::from_str(&s).ok());
+
+    // Load optional KyberSwap Elastic addresses (detection-only)
+    let kyber_elastic_factory = var("KYBER_ELASTIC_FACTORY")
+        .ok()
+        .and_then(|s| Address::from_str(&s).ok());
+    let kyber_elastic_router = var("KYBER_ELASTIC_ROUTER")
+        .ok()
+        .and_then(|s| Address::from_str(&s).ok());
+    let kyber_elastic_quoter = var("KYBER_ELASTIC_QUOTER")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
-    let quickswap_v3_router = std::env::var("QUICKSWAP_V3_ROUTER")
+
+    // Load optional DODO V2 addresses (legacy two-tx execution)
+    let dodo_v2_proxy = var("DODO_V2_PROXY")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
-    let quickswap_v3_quoter = std::env::var("QUICKSWAP_V3_QUOTER")
+    let dodo_approve = var("DODO_APPROVE")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
 
     // Multi-chain fields with backwards-compatible defaults (Polygon)
-    let chain_name = std::env::var("CHAIN_NAME")
+    let chain_name = var("CHAIN_NAME")
         .unwrap_or_else(|_| "polygon".to_string());
 
     // Default: Polygon USDC.e (0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174)
-    let quote_token_address = std::env::var("QUOTE_TOKEN_ADDRESS")
+    let quote_token_address = var("QUOTE_TOKEN_ADDRESS")
         .ok()
         .and_then(|s| Address::from_str(&s).ok())
         .unwrap_or_else(|| Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap());
 
-    let estimated_gas_cost_usd: f64 = std::env::var("ESTIMATED_GAS_COST_USD")
+    let estimated_gas_cost_usd: f64 = var("ESTIMATED_GAS_COST_USD")
         .ok()
         .and_then(|v| v.parse().ok())
         .unwrap_or(0.05);
 
     // Secondary quote token (native USDC on Polygon)
-    let quote_token_address_native = std::env::var("QUOTE_TOKEN_ADDRESS_NATIVE")
+    let quote_token_address_native = var("QUOTE_TOKEN_ADDRESS_NATIVE")
         .ok()
         .and_then(|s| Address::from_str(&s).ok());
 
     // Native token price — must resolve before chain_name is moved into BotConfig
-    let native_token_price_usd: f64 = std::env::var("NATIVE_TOKEN_PRICE_USD")
+    let native_token_price_usd: f64 = var("NATIVE_TOKEN_PRICE_USD")
         .ok()
         .and_then(|v| v.parse().ok())
-        .unwrap_or_else(|| {
-            match chain_name.as_str() {
-                "polygon" => 0.50,
-                "base" | "ethereum" => 3300.0,
-                _ => 1.0,
-            }
+        .unwrap_or(match chain_name.as_str() {
+            "polygon" => 0.50,
+            "base" | "ethereum" => 3300.0,
+            _ => 1.0,
+        });
+    // Reference pool pair for the live native price ("" = static price only)
+    let native_price_pair: Option<String> = match var("NATIVE_PRICE_PAIR") {
+        Ok(pair) => Some(pair).filter(|p| !p.trim().is_empty()),
+        Err(_) => match chain_name.as_str() {
+            "polygon" => Some("WMATIC/USDC".to_string()),
+            "base" | "ethereum" => Some("WETH/USDC".to_string()),
+            _ => None,
+        },
+    };
+
+    // Liquidity guard floor — same per-chain scale as the whitelist default_min_liquidity
+    let min_pool_liquidity: u128 = var("MIN_POOL_LIQUIDITY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(match chain_name.as_str() {
+            "polygon" => 1_000_000_000,
+            "base" => 1_000_000_000_000,
+            _ => 0,
+        });
+    // Blocks a landed trade must be buried under before its records are final
+    let confirmation_depth: u64 = var("CONFIRMATION_DEPTH")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(match chain_name.as_str() {
+            "polygon" => 10,
+            "base" => 2,
+            _ => 5,
         });
 
-    Ok(BotConfig {
-        rpc_url: std::env::var("RPC_URL")?,
-        chain_id: std::env::var("CHAIN_ID")?.parse()?,
+    let config = BotConfig {
+        rpc_url: var("RPC_URL")?,
+        chain_id: var("CHAIN_ID")?.parse()?,
         chain_name,
         quote_token_address,
         estimated_gas_cost_usd,
-        private_key: std::env::var("PRIVATE_KEY")?,
+        private_key: private_keys[0].clone(),
+        private_keys,
 
-        min_profit_usd: std::env::var("MIN_PROFIT_USD")?.parse()?,
-        max_trade_size_usd: std::env::var("MAX_TRADE_SIZE_USD")?.parse()?,
-        max_slippage_percent: std::env::var("MAX_SLIPPAGE_PERCENT")?.parse()?,
+        min_profit_usd: var("MIN_PROFIT_USD")?.parse()?,
+        max_trade_size_usd: var("MAX_TRADE_SIZE_USD")?.parse()?,
+        max_slippage_percent: var("MAX_SLIPPAGE_PERCENT")?.parse()?,
 
-        uniswap_router: Address::from_str(&std::env::var("UNISWAP_ROUTER")?)?,
-        sushiswap_router: Address::from_str(&std::env::var("SUSHISWAP_ROUTER")?)?,
-        uniswap_factory: Address::from_str(&std::env::var("UNISWAP_FACTORY")?)?,
-        sushiswap_factory: Address::from_str(&std::env::var("SUSHISWAP_FACTORY")?)?,
+        uniswap_router: Address::from_str(&var("UNISWAP_ROUTER")?)?,
+        sushiswap_router: Address::from_str(&var("SUSHISWAP_ROUTER")?)?,
+        uniswap_factory: Address::from_str(&var("UNISWAP_FACTORY")?)?,
+        sushiswap_factory: Address::from_str(&var("SUSHISWAP_FACTORY")?)?,
 
         apeswap_router,
         apeswap_factory,
@@ -153,85 +337,745 @@ fn load_config_inner() -> Result<BotConfig> {
         quickswap_v3_router,
         quickswap_v3_quoter,
 
+        kyber_elastic_factory,
+        kyber_elastic_router,
+        kyber_elastic_quoter,
+
+        dodo_v2_proxy,
+        dodo_approve,
+
         // Base uses QuoterV2 for Uniswap V3; Polygon uses QuoterV1
-        uniswap_v3_quoter_is_v2: std::env::var("UNISWAP_V3_QUOTER_IS_V2")
+        uniswap_v3_quoter_is_v2: var("UNISWAP_V3_QUOTER_IS_V2")
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(false),
 
         pairs,
 
-        poll_interval_ms: std::env::var("POLL_INTERVAL_MS")?.parse()?,
+        poll_interval_ms: var("POLL_INTERVAL_MS")?.parse()?,
         // Gas cap no longer enforced in executor — kept for config compatibility
-        max_gas_price_gwei: std::env::var("MAX_GAS_PRICE_GWEI")
+        max_gas_price_gwei: var("MAX_GAS_PRICE_GWEI")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(0),
 
         // Tax logging configuration
-        tax_log_dir: std::env::var("TAX_LOG_DIR").ok(),
-        tax_log_enabled: std::env::var("TAX_LOG_ENABLED")
+        tax_log_dir: var("TAX_LOG_DIR").ok(),
+        tax_log_enabled: var("TAX_LOG_ENABLED")
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(true), // Default to enabled for safety
+        tax_log_strict: var("TAX_LOG_STRICT")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
 
         // Live trading mode (default to false for safety)
-        live_mode: std::env::var("LIVE_MODE")
+        live_mode: var("LIVE_MODE")
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(false),
 
         // Shared pool state file (data collector writes, live bot reads)
-        pool_state_file: std::env::var("POOL_STATE_FILE").ok(),
+        pool_state_file: var("POOL_STATE_FILE").ok(),
 
         // Pool whitelist/blacklist config (Phase 1.1)
-        whitelist_file: std::env::var("WHITELIST_FILE").ok(),
+        whitelist_file: var("WHITELIST_FILE").ok(),
 
         // Historical price logging (research)
-        price_log_enabled: std::env::var("PRICE_LOG_ENABLED")
+        price_log_enabled: var("PRICE_LOG_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        price_log_dir: var("PRICE_LOG_DIR").ok(),
+
+        // Historical gas logging (research)
+        gas_history_enabled: var("GAS_HISTORY_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        gas_history_sample_blocks: var("GAS_HISTORY_SAMPLE_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30),
+
+        // Isolated log writers: bounded queues + writer threads
+        price_log_queue_capacity: var("PRICE_LOG_QUEUE_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(64),
+        tax_write_queue_capacity: var("TAX_WRITE_QUEUE_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(256),
+        tax_enqueue_timeout_ms: var("TAX_ENQUEUE_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(50),
+        io_write_timeout_ms: var("IO_WRITE_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2000),
+
+        // Multicall pre-screen export (research)
+        prescreen_export_enabled: var("PRESCREEN_EXPORT_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+
+        // Opportunity archive (research)
+        opportunity_archive_enabled: var("OPPORTUNITY_ARCHIVE_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        opportunity_archive_expiry_blocks: var("OPPORTUNITY_ARCHIVE_EXPIRY_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3),
+        opportunity_archive_retain_days: var("OPPORTUNITY_ARCHIVE_RETAIN_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(14),
+
+        // Execution-constraint analysis (research)
+        execution_constraints_enabled: var("EXECUTION_CONSTRAINTS_ENABLED")
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(false),
-        price_log_dir: std::env::var("PRICE_LOG_DIR").ok(),
+
+        // Watchdog (stall self-detection)
+        watchdog_enabled: var("WATCHDOG_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        watchdog_check_secs: var("WATCHDOG_CHECK_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5),
+        // Block loop stall interval: explicit seconds, else WATCHDOG_STALL_BLOCKS
+        // expected block times (EXPECTED_BLOCK_TIME_SECS; 30 × 2s = 60s)
+        watchdog_block_stall_secs: var("WATCHDOG_BLOCK_STALL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_else(|| {
+                let blocks: f64 = var("WATCHDOG_STALL_BLOCKS").ok().and_then(|v| v.parse().ok()).unwrap_or(30.0);
+                let block_time: f64 = var("EXPECTED_BLOCK_TIME_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(2.0);
+                (blocks * block_time).ceil().max(1.0) as u64
+            }),
+        watchdog_hard_limit_secs: var("WATCHDOG_HARD_LIMIT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300),
+        watchdog_exit_enabled: var("WATCHDOG_EXIT")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        watchdog_reconnect_attempts: var("WATCHDOG_RECONNECT_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3),
 
         // Atomic arbitrage executor contract
-        arb_executor_address: std::env::var("ARB_EXECUTOR_ADDRESS")
+        arb_executor_address: var("ARB_EXECUTOR_ADDRESS")
+            .ok()
+            .and_then(|s| Address::from_str(&s).ok()),
+        // executeArbMulti deployed (multi-leg routes go atomic; default false)
+        arb_executor_multi: var("ARB_EXECUTOR_MULTI")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        // Aave V3 flashloan funding via executeArbFlash (default false; needs AAVE_POOL_ADDRESS)
+        flashloan_enabled: var("FLASHLOAN_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        aave_pool_address: var("AAVE_POOL_ADDRESS")
+            .ok()
+            .and_then(|s| Address::from_str(&s).ok()),
+        // V2 flash swaps via executeArbFlashV2 for V2-bought routes (default false)
+        flash_v2_enabled: var("FLASH_V2_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        // Permit2 signature transfers via executeArbWithPermit (unset = allowance path only)
+        permit2_address: var("PERMIT2_ADDRESS")
             .ok()
             .and_then(|s| Address::from_str(&s).ok()),
 
         // Skip Multicall3 batch pre-screen (default false — existing behavior preserved)
-        skip_multicall_prescreen: std::env::var("SKIP_MULTICALL_PRESCREEN")
+        skip_multicall_prescreen: var("SKIP_MULTICALL_PRESCREEN")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+
+        // Per-route pre-screen skipping earned by success streaks (default off)
+        prescreen_skip_enabled: var("PRESCREEN_SKIP_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        prescreen_skip_streak: var("PRESCREEN_SKIP_STREAK")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30),
+        prescreen_reverify_every: var("PRESCREEN_REVERIFY_EVERY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10),
+
+        // Burst triage: bounded pre-screen on large-move blocks (0 = disabled)
+        burst_threshold: var("BURST_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(25),
+        burst_top_k: var("BURST_TOP_K")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5),
+        burst_min_spread_percent: var("BURST_MIN_SPREAD_PERCENT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.3),
+        burst_exit_blocks: var("BURST_EXIT_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3),
+
+        // Shadow validation: off | primary | follower (default off)
+        shadow_role: var("SHADOW_ROLE").unwrap_or_else(|_| "off".to_string()),
+        shadow_feed_dir: var("SHADOW_FEED_DIR").ok().filter(|s| !s.is_empty()),
+        shadow_match_lag_blocks: var("SHADOW_MATCH_LAG_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5),
+        // Strategy A/B shadow detector: TOML overrides (unset = off)
+        shadow_config: var("SHADOW_CONFIG").ok().filter(|s| !s.trim().is_empty()),
+        shadow_verify_max: var("SHADOW_VERIFY_MAX")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5),
+
+        // Competition model: EV ranking by win probability of our priority fee (default off)
+        competition_model_enabled: var("COMPETITION_MODEL_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        competition_min_samples: var("COMPETITION_MIN_SAMPLES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(20),
+        competition_gas_refund_fraction: var("COMPETITION_GAS_REFUND_FRACTION")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.0),
+        competitor_addresses: var("COMPETITOR_ADDRESSES")
+            .map(|v| parse_address_list(&v))
+            .unwrap_or_default(),
+
+        // Triangular detection: three-pool quote → A → B → quote cycles (default off)
+        triangular_enabled: var("TRIANGULAR_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+
+        // Optimal sizing: closed-form (V2) / quoted ladder (V3) size search (default off)
+        optimal_sizing: var("OPTIMAL_SIZING")
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(false),
 
         // Route cooldown: suppress failed routes for N blocks (default 10, 0 = disabled)
-        route_cooldown_blocks: std::env::var("ROUTE_COOLDOWN_BLOCKS")
+        route_cooldown_blocks: var("ROUTE_COOLDOWN_BLOCKS")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(10),
+        // Route cooldown size buckets: "500,1500" USD boundaries (empty = whole route)
+        route_cooldown_size_buckets_usd: var("ROUTE_COOLDOWN_SIZE_BUCKETS_USD")
+            .map(|v| v.split(',').filter_map(|t| t.trim().parse().ok()).collect())
+            .unwrap_or_default(),
+        // Mempool signals bypass cooldowns with fewer failures than this (default 3, 0 = never)
+        route_cooldown_mempool_bypass_failures: var("ROUTE_COOLDOWN_MEMPOOL_BYPASS_FAILURES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3),
 
         // Private RPC for tx submission (Polygon Fastlane — optional)
-        private_rpc_url: std::env::var("PRIVATE_RPC_URL").ok(),
+        private_rpc_url: var("PRIVATE_RPC_URL").ok(),
+
+        // Searcher bundle relay for mempool backruns (optional; raw send when unset)
+        bundle_rpc_url: var("BUNDLE_RPC_URL").ok().filter(|v| !v.trim().is_empty()),
+        bundle_rpc_method: var("BUNDLE_RPC_METHOD")
+            .unwrap_or_else(|_| "pfl_addSearcherBundle".to_string()),
+        bundle_params_template: var("BUNDLE_PARAMS_TEMPLATE")
+            .unwrap_or_else(|_| r#"["{trigger_hash}","{backrun_raw}"]"#.to_string()),
 
         // A4 Mempool Monitor mode (default: off)
-        mempool_monitor_mode: std::env::var("MEMPOOL_MONITOR")
+        mempool_monitor_mode: var("MEMPOOL_MONITOR")
             .unwrap_or_else(|_| "off".to_string()),
 
         // A4 Phase 3: Mempool execution parameters
-        mempool_min_profit_usd: std::env::var("MEMPOOL_MIN_PROFIT_USD")
+        mempool_min_profit_usd: var("MEMPOOL_MIN_PROFIT_USD")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(0.05),
-        mempool_gas_limit: std::env::var("MEMPOOL_GAS_LIMIT")
+        mempool_gas_limit: var("MEMPOOL_GAS_LIMIT")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(500_000),
-        mempool_min_priority_gwei: std::env::var("MEMPOOL_MIN_PRIORITY_GWEI")
+        mempool_min_priority_gwei: var("MEMPOOL_MIN_PRIORITY_GWEI")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(1000),
-        mempool_gas_profit_cap: std::env::var("MEMPOOL_GAS_PROFIT_CAP")
+        mempool_gas_profit_cap: var("MEMPOOL_GAS_PROFIT_CAP")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(0.50),
 
         native_token_price_usd,
+        native_price_pair,
         quote_token_address_native,
-    })
+        quote_token_price_usd: var("QUOTE_TOKEN_PRICE_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.0),
+
+        // Block-pinned preflight batch before execution (default true)
+        preflight_batch_enabled: var("PREFLIGHT_BATCH")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+
+        // Canary mode thresholds (whitelist entries with canary_until / canary_trades)
+        canary_max_trade_size_usd: var("CANARY_MAX_TRADE_SIZE_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(25.0),
+        canary_min_win_rate: var("CANARY_MIN_WIN_RATE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.5),
+        canary_min_net_profit_usd: var("CANARY_MIN_NET_PROFIT_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.0),
+        canary_min_trades: var("CANARY_MIN_TRADES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3),
+
+        // Stranded-funds alarm on the ArbExecutor contract (~5 min on Polygon)
+        stranded_check_interval_blocks: var("STRANDED_CHECK_INTERVAL_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(150),
+        stranded_dust_usd: var("STRANDED_DUST_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.0),
+        // Auto rescue sends transactions — default off
+        auto_rescue: var("AUTO_RESCUE")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        alert_webhook_url: var("ALERT_WEBHOOK_URL")
+            .or_else(|_| var("DISCORD_WEBHOOK"))
+            .ok(),
+
+        // Pool migration detector (~2 min on Polygon below 5% of peak depth)
+        migration_collapse_blocks: var("MIGRATION_COLLAPSE_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60),
+        migration_collapse_ratio: var("MIGRATION_COLLAPSE_RATIO")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.05),
+        // Auto migrate rewrites the whitelist — default off (suggestions only)
+        auto_migrate: var("AUTO_MIGRATE")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+
+        // Token metadata re-validation (cheap: one multicall per check)
+        token_revalidation_hours: var("TOKEN_REVALIDATION_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(6),
+
+        // Anomaly monitor (alerts only, never changes trading)
+        anomaly_monitor_enabled: var("ANOMALY_MONITOR_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        anomaly_window_secs: var("ANOMALY_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(900),
+        anomaly_sigma: var("ANOMALY_SIGMA")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3.0),
+        anomaly_min_duration_secs: var("ANOMALY_MIN_DURATION_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1800),
+
+        // In-flight tx journal + startup recovery (live mode only)
+        inflight_journal_enabled: var("INFLIGHT_JOURNAL_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        inflight_recovery_policy: var("INFLIGHT_RECOVERY_POLICY")
+            .unwrap_or_else(|_| "discard".to_string()),
+
+        // Execution-time liquidity guard (before pre-screen)
+        min_pool_liquidity,
+        min_pool_liquidity_overrides: var("MIN_POOL_LIQUIDITY_OVERRIDES")
+            .map(|v| parse_pair_overrides(&v))
+            .unwrap_or_default(),
+        max_liquidity_fraction: var("MAX_LIQUIDITY_FRACTION")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.05),
+        liquidity_guard_mode: var("LIQUIDITY_GUARD_MODE")
+            .unwrap_or_else(|_| "reject".to_string()),
+
+        // Cross-instance telemetry push — default off, no addresses
+        telemetry_enabled: var("TELEMETRY_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        telemetry_endpoint: var("TELEMETRY_ENDPOINT").ok(),
+        telemetry_interval_secs: var("TELEMETRY_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60),
+        telemetry_include_addresses: var("TELEMETRY_INCLUDE_ADDRESSES")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+
+        // Live state API — off unless API_PORT is set (and built with --features api)
+        api_port: var("API_PORT")
+            .ok()
+            .and_then(|v| v.trim().parse().ok()),
+        api_bind: var("API_BIND")
+            .unwrap_or_else(|_| "127.0.0.1".to_string()),
+        api_recent_opportunities: var("API_RECENT_OPPORTUNITIES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(100),
+
+        // Dead-man's switch for an external uptime monitor — off unless DEADMAN_URL is set
+        deadman_url: var("DEADMAN_URL").ok().filter(|v| !v.trim().is_empty()),
+        deadman_fail_url: var("DEADMAN_FAIL_URL").ok().filter(|v| !v.trim().is_empty()),
+        deadman_method: var("DEADMAN_METHOD")
+            .unwrap_or_else(|_| "post".to_string()),
+        deadman_interval_secs: var("DEADMAN_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60),
+        deadman_max_block_age_secs: var("DEADMAN_MAX_BLOCK_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60),
+        deadman_criteria: var("DEADMAN_CRITERIA")
+            .unwrap_or_else(|_| "heartbeat,block_age,breaker,tax".to_string()),
+
+        // Per-route realized slippage → min_out allowance (stats always kept)
+        slippage_feedback_enabled: var("SLIPPAGE_FEEDBACK_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        slippage_feedback_min_samples: var("SLIPPAGE_FEEDBACK_MIN_SAMPLES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(20),
+        slippage_feedback_percentile: var("SLIPPAGE_FEEDBACK_PERCENTILE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(95.0),
+        slippage_feedback_buffer_percent: var("SLIPPAGE_FEEDBACK_BUFFER_PERCENT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.1),
+
+        // Trade record finality (0 = records final at first receipt)
+        confirmation_depth,
+
+        // Per-quote-token exposure pacing at dispatch — default off
+        exposure_pacing_enabled: var("EXPOSURE_PACING_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        exposure_max_trade_fraction: var("EXPOSURE_MAX_TRADE_FRACTION")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.4),
+        exposure_max_inflight_fraction: var("EXPOSURE_MAX_INFLIGHT_FRACTION")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.8),
+        exposure_overrides: var("EXPOSURE_OVERRIDES")
+            .map(|v| parse_exposure_overrides(&v))
+            .unwrap_or_default(),
+        exposure_min_notional_usd: var("EXPOSURE_MIN_NOTIONAL_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10.0),
+
+        // Failure injection (rehearsal only) — default off
+        chaos_mode: var("CHAOS_MODE")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        chaos_config_file: var("CHAOS_CONFIG_FILE").ok(),
+
+        // Per-route P&L report + stats.json snapshot (~17 min on Polygon)
+        stats_report_interval: var("STATS_REPORT_INTERVAL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(500),
+
+        // Per-stage latency table + latency.json snapshot (~10 min on Polygon)
+        latency_report_interval: var("LATENCY_REPORT_INTERVAL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300),
+
+        // Block headers kept for reorg detection (Polygon reorgs are 1-5 blocks)
+        reorg_track_blocks: var("REORG_TRACK_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(32),
+
+        // Live alert categories (only sent when ALERT_WEBHOOK_URL is set)
+        alert_trades: var("ALERT_TRADES")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        alert_halts: var("ALERT_HALTS")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        alert_ws: var("ALERT_WS")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        alert_daily_summary: var("ALERT_DAILY_SUMMARY")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        alert_canary: var("ALERT_CANARY")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        // One alert per route per hour for repeated reverts/halts
+        alert_rate_limit_secs: var("ALERT_RATE_LIMIT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3600),
+
+        // Balance clamping: a trade above the wallet balance is a guaranteed revert
+        balance_clamp_enabled: var("BALANCE_CLAMP_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        balance_usable_fraction: var("BALANCE_USABLE_FRACTION")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.98),
+        min_quote_balance_usd: var("MIN_QUOTE_BALANCE_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10.0),
+        // Curve stable pools: off until whitelisted pools are verified
+        curve_enabled: var("CURVE_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        // Tax lot selection for cost basis (validated when tax logging starts)
+        tax_lot_method: var("TAX_LOT_METHOD")
+            .unwrap_or_else(|_| "fifo".to_string()),
+        // Multicall3 pool sync (unset = per-pool poll sync)
+        multicall3_address: var("MULTICALL3_ADDRESS")
+            .ok()
+            .and_then(|s| Address::from_str(&s).ok()),
+        // Gas oracle: p90 of the last 20 blocks + 10%, within 30..5000 gwei
+        gas_oracle_enabled: var("GAS_ORACLE_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        gas_oracle_percentile: var("GAS_ORACLE_PERCENTILE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(90.0),
+        gas_oracle_bump_percent: var("GAS_ORACLE_BUMP_PERCENT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10.0),
+        gas_oracle_window_blocks: var("GAS_ORACLE_WINDOW_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(20),
+        gas_oracle_sample_blocks: var("GAS_ORACLE_SAMPLE_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5),
+        min_priority_gwei: var("MIN_PRIORITY_GWEI")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30),
+        max_priority_gwei: var("MAX_PRIORITY_GWEI")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5000),
+        // Pool health quarantine: idle ~1 day (Polygon), liquidity floor on the
+        // same per-chain scale as MIN_POOL_LIQUIDITY
+        pool_health_enabled: var("POOL_HEALTH_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        pool_health_max_idle_blocks: var("POOL_HEALTH_MAX_IDLE_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(43_200),
+        pool_health_min_v3_liquidity: var("POOL_HEALTH_MIN_V3_LIQUIDITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(min_pool_liquidity),
+        pool_health_min_v2_tvl_usd: var("POOL_HEALTH_MIN_V2_TVL_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1_000.0),
+        pool_health_max_quoter_rejections: var("POOL_HEALTH_MAX_QUOTER_REJECTIONS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5),
+        pool_health_rejection_quarantine_blocks: var("POOL_HEALTH_REJECTION_QUARANTINE_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1800),
+        // Parallel submission: off (serial) unless raised above 1
+        max_parallel_submissions: var("MAX_PARALLEL_SUBMISSIONS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1),
+        // Cross-chain divergence monitor: off unless a remote state file is given
+        cross_chain_state_file: var("CROSS_CHAIN_STATE_FILE").ok().filter(|s| !s.is_empty()),
+        cross_chain_divergence_bps: var("CROSS_CHAIN_DIVERGENCE_BPS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(20.0),
+        cross_chain_max_age_secs: var("CROSS_CHAIN_MAX_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30),
+        log_format: var("LOG_FORMAT").unwrap_or_else(|_| "text".to_string()),
+        mempool_calibration_k: var("MEMPOOL_CALIBRATION_K")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.0),
+        mempool_calibration_prior_bps: var("MEMPOOL_CALIBRATION_PRIOR_BPS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5.0),
+        gas_cache_enabled: var("GAS_CACHE_ENABLED")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        gas_cache_multiplier: var("GAS_CACHE_MULTIPLIER")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.2),
+        max_pool_age_blocks: var("MAX_POOL_AGE_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2),
+        max_opportunity_age_secs: var("MAX_OPPORTUNITY_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(4),
+        spread_confirm_blocks: var("SPREAD_CONFIRM_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1),
+        spread_history_blocks: var("SPREAD_HISTORY_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(8),
+        opportunity_dedup_ttl_blocks: var("OPPORTUNITY_DEDUP_TTL_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2),
+        // Wallet inventory check (~1h on Polygon)
+        inventory_check_interval_blocks: var("INVENTORY_CHECK_INTERVAL_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1800),
+        inventory_rebalance_threshold_usd: var("INVENTORY_REBALANCE_THRESHOLD_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(25.0),
+        // Auto rebalance sends transactions — default off
+        auto_rebalance: var("AUTO_REBALANCE")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        // Failover endpoints after RPC_URL (empty = RPC_URL only)
+        rpc_urls: var("RPC_URLS")
+            .map(|v| v.split(',').map(|u| u.trim().to_string()).filter(|u| !u.is_empty()).collect())
+            .unwrap_or_default(),
+        // WS endpoint beside an IPC RPC_URL: subscriptions + read fallback
+        ws_rpc_url: var("WS_RPC_URL").ok().filter(|v| !v.trim().is_empty()),
+        // Split-sell routes need executeArbSplit on the deployed ArbExecutor — default off
+        split_routing: var("SPLIT_ROUTING")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        // Price log output: CSV, closed days gzipped, kept forever
+        price_log_format: var("PRICE_LOG_FORMAT").unwrap_or_else(|_| "csv".to_string()),
+        price_log_compress: var("PRICE_LOG_COMPRESS")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(true),
+        price_log_retain_days: var("PRICE_LOG_RETAIN_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0),
+        price_log_blocks: var("PRICE_LOG_BLOCKS")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        // Adaptive minimum profit: off; no floor beyond MIN_PROFIT_USD, ceiling $1
+        min_profit_adaptive: var("MIN_PROFIT_ADAPTIVE")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        min_profit_floor_usd: var("MIN_PROFIT_FLOOR_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.0),
+        min_profit_ceiling_usd: var("MIN_PROFIT_CEILING_USD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.0),
+        // Algebra dynamic fee re-read under event sync: every 10 blocks
+        algebra_fee_refresh_blocks: var("ALGEBRA_FEE_REFRESH_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10),
+        // Mid-block re-scans on simulated pending swaps: off; 3 per block when on
+        midblock_scan: var("MIDBLOCK_SCAN")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false),
+        midblock_scan_max_per_block: var("MIDBLOCK_SCAN_MAX_PER_BLOCK")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3),
+    };
+    validate_config(&config)?;
+    Ok(config)
+}
+
+/// Reject settings that can't run as configured (checked on every load)
+pub fn validate_config(config: &BotConfig) -> Result<()> {
+    // Mempool execute mode submits through ArbExecutor.sol only — no two-leg fallback
+    if MempoolMode::from_env(&config.mempool_monitor_mode) == MempoolMode::Execute
+        && config.arb_executor_address.is_none()
+    {
+        anyhow::bail!("MEMPOOL_MONITOR=execute requires ARB_EXECUTOR_ADDRESS");
+    }
+    // Mid-block re-scans are driven by the mempool monitor's simulations
+    if config.midblock_scan && !MempoolMode::from_env(&config.mempool_monitor_mode).is_active() {
+        anyhow::bail!("MIDBLOCK_SCAN=true requires MEMPOOL_MONITOR=observe or execute");
+    }
+    // Subscriptions never go over the IPC socket
+    if config.ws_rpc_url.is_none() && config.ws_endpoints().iter().any(|url| crate::transport::is_ipc(url)) {
+        anyhow::bail!("IPC endpoint in RPC_URL/RPC_URLS requires WS_RPC_URL (block + mempool subscriptions)");
+    }
+    Ok(())
+}
+
+/// "USDC:0.4:0.8,WETH:0.25:0.5" → quote symbol → (trade fraction, in-flight fraction)
+fn parse_exposure_overrides(s: &str) -> HashMap<String, (f64, f64)> {
+    s.split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.trim().split(':');
+            let symbol = parts.next()?.trim().to_string();
+            let trade = parts.next()?.trim().parse().ok()?;
+            let inflight = parts.next()?.trim().parse().ok()?;
+            Some((symbol, (trade, inflight)))
+        })
+        .collect()
+}
+
+/// Parse "0xabc…,0xdef…" (malformed entries skipped)
+fn parse_address_list(s: &str) -> Vec<Address> {
+    s.split(',').filter_map(|entry| entry.trim().parse().ok()).collect()
+}
+
+/// Parse "SYMBOL:value,SYMBOL:value" per-pair overrides (malformed entries skipped)
+fn parse_pair_overrides(s: &str) -> HashMap<String, u128> {
+    s.split(',')
+        .filter_map(|entry| {
+            let (symbol, value) = entry.trim().rsplit_once(':')?;
+            Some((symbol.trim().to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
 }
@@ -0,0 +1,552 @@
+//! Config Check — the configured contracts validated against the chain
+//!
+//! Purpose:
+//!     Every new deployment misconfigures something (the QuoterV1 vs V2 flag,
+//!     a missing Algebra router) and the failure shows up hours later as
+//!     Quoter errors. `dexarb-bot --check-config --chain polygon` loads the
+//!     config as the bot would, probes every configured contract with cheap
+//!     reads, logs a pass/fail table and exits nonzero on any failure.
+//!
+//! Checks (one function each, reads only):
+//!     code            eth_getCode non-empty (every check starts with it)
+//!     router          factory() answers and matches the DEX's configured factory
+//!     factory         V2 allPairsLength(); Uniswap/Sushi V3 feeAmountTickSpacing(500)
+//!                     = 10; Algebra poolDeployer(); Kyber Elastic code only
+//!     quoter flavor   the Uniswap V3 quoter answers the ABI UNISWAP_V3_QUOTER_IS_V2
+//!                     selects (QuoterV1 and QuoterV2 both tried on configured pairs)
+//!     decimals        decimals() of each quote token vs its symbol's
+//!     arb executor    executeArb selector (PUSH4) in the contract's bytecode
+//!     contract        code only: Multicall3, Aave pool, DODO proxy / DODOApprove
+//!
+//! Unset optional addresses (and zero addresses) are skipped, not failed.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - DODO_V2_PROXY / DODO_APPROVE code checks
+
+use crate::arbitrage::multicall_quoter::MulticallQuoter;
+use crate::types::BotConfig;
+use crate::watchdog::rpc_timeout;
+use anyhow::Result;
+use ethers::abi::{self, Token};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::{hex, id};
+use std::fmt;
+
+/// executeArb's signature (IArbExecutor in executor.rs)
+const EXECUTE_ARB_SIGNATURE: &str = "executeArb(address,address,address,address,uint24,uint24,uint256,uint256)";
+
+/// PUSH4: how the Solidity dispatcher loads a selector to compare
+const PUSH4: u8 = 0x63;
+
+/// Fee tiers tried for the quoter flavor probe
+const PROBE_FEE_TIERS: [u32; 2] = [500, 3000];
+
+/// Quote amount for the quoter flavor probe (raw quote token units)
+const PROBE_AMOUNT_RAW: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "PASS"),
+            CheckStatus::Fail => write!(f, "FAIL"),
+            CheckStatus::Skip => write!(f, "SKIP"),
+        }
+    }
+}
+
+/// One row of the report
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    /// Check kind (router, factory, quoter, decimals, ...)
+    pub check: &'static str,
+    /// Config variable checked (UNISWAP_V3_ROUTER)
+    pub name: String,
+    pub address: Option<Address>,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(check: &'static str, name: &str, address: Option<Address>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { check, name: name.to_string(), address, status, detail: detail.into() }
+    }
+
+    fn pass(check: &'static str, name: &str, address: Address, detail: impl Into<String>) -> Self {
+        Self::new(check, name, Some(address), CheckStatus::Pass, detail)
+    }
+
+    fn fail(check: &'static str, name: &str, address: Address, detail: impl Into<String>) -> Self {
+        Self::new(check, name, Some(address), CheckStatus::Fail, detail)
+    }
+}
+
+/// Every check of one run
+#[derive(Debug, Default)]
+pub struct ConfigCheckReport {
+    pub results: Vec<CheckResult>,
+}
+
+impl ConfigCheckReport {
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.results.iter().filter(|r| r.status == status).count()
+    }
+
+    pub fn failures(&self) -> usize {
+        self.count(CheckStatus::Fail)
+    }
+
+    /// Pass/fail table for the log
+    pub fn format_table(&self) -> String {
+        let mut out = format!("{:<6} {:<13} {:<26} {:<42} {}\n", "STATUS", "CHECK", "NAME", "ADDRESS", "DETAIL");
+        for r in &self.results {
+            let address = r.address.map(|a| format!("{:?}", a)).unwrap_or_else(|| "-".to_string());
+            out.push_str(&format!("{:<6} {:<13} {:<26} {:<42} {}\n", r.status, r.check, r.name, address, r.detail));
+        }
+        out
+    }
+}
+
+/// Kind of factory, for its view probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FactoryKind {
+    /// allPairsLength()
+    V2,
+    /// feeAmountTickSpacing(500) = 10
+    UniswapV3,
+    /// poolDeployer()
+    Algebra,
+    /// No cheap view probed: code only
+    KyberElastic,
+}
+
+/// Decimals a quote token symbol must report (None = not checked against a value)
+pub fn expected_decimals(symbol: &str) -> Option<u8> {
+    match symbol.to_uppercase().as_str() {
+        "USDC" | "USDC.E" | "USDT" | "USDBC" => Some(6),
+        "WBTC" => Some(8),
+        "WETH" | "WMATIC" | "WPOL" | "DAI" => Some(18),
+        _ => None,
+    }
+}
+
+async fn view<M: Middleware>(provider: &M, to: Address, data: Vec<u8>) -> Result<Bytes> {
+    let tx: TypedTransaction = TransactionRequest::new().to(to).data(data).into();
+    rpc_timeout("eth_call", provider.call(&tx, None)).await
+}
+
+fn selector_call(signature: &str, args: &[Token]) -> Vec<u8> {
+    let mut data = id(signature).to_vec();
+    data.extend(abi::encode(args));
+    data
+}
+
+fn first_word(data: &[u8]) -> Option<U256> {
+    (data.len() >= 32).then(|| U256::from_big_endian(&data[..32]))
+}
+
+fn word_address(data: &[u8]) -> Option<Address> {
+    (data.len() >= 32).then(|| Address::from_slice(&data[12..32]))
+}
+
+/// The contract's bytecode, or the failed row when there is none
+async fn code_or_fail<M: Middleware>(provider: &M, check: &'static str, name: &str, address: Address) -> Result<Bytes, CheckResult> {
+    match rpc_timeout("eth_getCode", provider.get_code(address, None)).await {
+        Ok(code) if !code.is_empty() => Ok(code),
+        Ok(_) => Err(CheckResult::fail(check, name, address, "no code at address")),
+        Err(e) => Err(CheckResult::fail(check, name, address, format!("eth_getCode failed: {}", e))),
+    }
+}
+
+/// Code only
+pub async fn check_contract<M: Middleware>(provider: &M, check: &'static str, name: &str, address: Address) -> CheckResult {
+    match code_or_fail(provider, check, name, address).await {
+        Ok(code) => CheckResult::pass(check, name, address, format!("{} bytes of code", code.len())),
+        Err(failed) => failed,
+    }
+}
+
+/// factory() answers; it must be `factory` (the DEX's configured factory) when known
+pub async fn check_router<M: Middleware>(provider: &M, name: &str, router: Address, factory: Option<Address>) -> CheckResult {
+    const CHECK: &str = "router";
+    if let Err(failed) = code_or_fail(provider, CHECK, name, router).await {
+        return failed;
+    }
+    let answer = view(provider, router, selector_call("factory()", &[])).await;
+    match (answer.map(|data| word_address(&data)), factory) {
+        (Ok(Some(got)), Some(want)) if got != want => {
+            CheckResult::fail(CHECK, name, router, format!("factory() = {:?}, configured factory {:?}", got, want))
+        }
+        (Ok(Some(got)), _) => CheckResult::pass(CHECK, name, router, format!("factory() = {:?}", got)),
+        (Ok(None), _) => CheckResult::fail(CHECK, name, router, "factory() returned no address"),
+        (Err(e), _) => CheckResult::fail(CHECK, name, router, format!("factory() failed: {}", e)),
+    }
+}
+
+/// Code plus the kind's view probe
+pub async fn check_factory<M: Middleware>(provider: &M, name: &str, factory: Address, kind: FactoryKind) -> CheckResult {
+    const CHECK: &str = "factory";
+    let code = match code_or_fail(provider, CHECK, name, factory).await {
+        Ok(code) => code,
+        Err(failed) => return failed,
+    };
+    let (signature, args) = match kind {
+        FactoryKind::V2 => ("allPairsLength()", vec![]),
+        FactoryKind::UniswapV3 => ("feeAmountTickSpacing(uint24)", vec![Token::Uint(U256::from(500))]),
+        FactoryKind::Algebra => ("poolDeployer()", vec![]),
+        FactoryKind::KyberElastic => {
+            return CheckResult::pass(CHECK, name, factory, format!("{} bytes of code (no view probed)", code.len()));
+        }
+    };
+    let word = match view(provider, factory, selector_call(signature, &args)).await {
+        Ok(data) => first_word(&data),
+        Err(e) => return CheckResult::fail(CHECK, name, factory, format!("{} failed: {}", signature, e)),
+    };
+    match (kind, word) {
+        (_, None) => CheckResult::fail(CHECK, name, factory, format!("{} returned nothing", signature)),
+        (FactoryKind::UniswapV3, Some(spacing)) if spacing != U256::from(10) => {
+            CheckResult::fail(CHECK, name, factory, format!("feeAmountTickSpacing(500) = {}, expected 10", spacing))
+        }
+        (FactoryKind::Algebra, Some(deployer)) if deployer.is_zero() => {
+            CheckResult::fail(CHECK, name, factory, "poolDeployer() is the zero address")
+        }
+        (_, Some(value)) => CheckResult::pass(CHECK, name, factory, format!("{} = {}", signature, value)),
+    }
+}
+
+/// Which of QuoterV1 / QuoterV2 the Uniswap V3 quoter answers, vs the flag.
+/// Each (token_in, token_out) probe is tried at PROBE_FEE_TIERS until one
+/// ABI returns a quote; both answering (or neither, for every probe) is
+/// reported as such.
+pub async fn check_quoter_flavor<M: Middleware + 'static>(
+    provider: &M,
+    quoter: Address,
+    quoter_is_v2: bool,
+    probes: &[(Address, Address)],
+) -> CheckResult {
+    const CHECK: &str = "quoter flavor";
+    const NAME: &str = "UNISWAP_V3_QUOTER";
+    if let Err(failed) = code_or_fail(provider, CHECK, NAME, quoter).await {
+        return failed;
+    }
+    let amount = U256::from(PROBE_AMOUNT_RAW);
+    let quote = |data: std::result::Result<Bytes, anyhow::Error>| {
+        data.ok().and_then(|bytes| MulticallQuoter::<M>::decode_quoter_result(true, &bytes).ok())
+    };
+    for &(token_in, token_out) in probes {
+        for fee in PROBE_FEE_TIERS {
+            let v1 = quote(view(provider, quoter, MulticallQuoter::<M>::encode_quoter_v1_call(token_in, token_out, fee, amount)).await);
+            let v2 = quote(view(provider, quoter, MulticallQuoter::<M>::encode_quoter_v2_call(token_in, token_out, fee, amount)).await);
+            let answers_v2 = match (v1.is_some(), v2.is_some()) {
+                (false, false) => continue,
+                (true, true) => {
+                    return CheckResult::pass(CHECK, NAME, quoter, format!("both QuoterV1 and QuoterV2 ABIs answer (fee {})", fee));
+                }
+                (_, answers_v2) => answers_v2,
+            };
+            let flavor = if answers_v2 { "QuoterV2" } else { "QuoterV1" };
+            return if answers_v2 == quoter_is_v2 {
+                CheckResult::pass(CHECK, NAME, quoter, format!("{} (UNISWAP_V3_QUOTER_IS_V2={})", flavor, quoter_is_v2))
+            } else {
+                CheckResult::fail(
+                    CHECK, NAME, quoter,
+                    format!("{} answers but UNISWAP_V3_QUOTER_IS_V2={}: set it to {}", flavor, quoter_is_v2, answers_v2),
+                )
+            };
+        }
+    }
+    CheckResult::fail(CHECK, NAME, quoter, format!("neither ABI quoted any of {} probe pair(s)", probes.len()))
+}
+
+/// decimals() answers, and equals `expected` when given
+pub async fn check_decimals<M: Middleware>(provider: &M, name: &str, token: Address, expected: Option<u8>) -> CheckResult {
+    const CHECK: &str = "decimals";
+    if let Err(failed) = code_or_fail(provider, CHECK, name, token).await {
+        return failed;
+    }
+    let decimals = match view(provider, token, selector_call("decimals()", &[])).await {
+        Ok(data) => first_word(&data),
+        Err(e) => return CheckResult::fail(CHECK, name, token, format!("decimals() failed: {}", e)),
+    };
+    match (decimals, expected) {
+        (None, _) => CheckResult::fail(CHECK, name, token, "decimals() returned nothing"),
+        (Some(got), Some(want)) if got != U256::from(want) => {
+            CheckResult::fail(CHECK, name, token, format!("decimals() = {}, expected {}", got, want))
+        }
+        (Some(got), Some(_)) => CheckResult::pass(CHECK, name, token, format!("decimals() = {}", got)),
+        (Some(got), None) => CheckResult::pass(CHECK, name, token, format!("decimals() = {} (symbol not checked)", got)),
+    }
+}
+
+/// executeArb's selector pushed by the contract's dispatcher
+pub async fn check_arb_executor<M: Middleware>(provider: &M, address: Address) -> CheckResult {
+    const CHECK: &str = "arb executor";
+    const NAME: &str = "ARB_EXECUTOR_ADDRESS";
+    let code = match code_or_fail(provider, CHECK, NAME, address).await {
+        Ok(code) => code,
+        Err(failed) => return failed,
+    };
+    let selector = id(EXECUTE_ARB_SIGNATURE);
+    let exposed = code.windows(5).any(|w| w[0] == PUSH4 && w[1..] == selector);
+    if exposed {
+        CheckResult::pass(CHECK, NAME, address, format!("executeArb 0x{} in dispatcher", hex::encode(selector)))
+    } else {
+        CheckResult::fail(CHECK, NAME, address, format!("executeArb selector 0x{} not in bytecode", hex::encode(selector)))
+    }
+}
+
+/// Quoter probe pairs: configured pairs holding the quote token, quote → base
+fn quoter_probes(config: &BotConfig) -> Vec<(Address, Address)> {
+    config
+        .pairs
+        .iter()
+        .filter_map(|pair| Some((pair.token0.parse::<Address>().ok()?, pair.token1.parse::<Address>().ok()?)))
+        .filter_map(|(t0, t1)| {
+            if config.is_quote_token(&t0) {
+                Some((t0, t1))
+            } else if config.is_quote_token(&t1) {
+                Some((t1, t0))
+            } else {
+                None
+            }
+        })
+        .take(2)
+        .collect()
+}
+
+/// Every check for `config`, in table order
+pub async fn run<M: Middleware + 'static>(provider: &M, config: &BotConfig) -> ConfigCheckReport {
+    let configured = |address: Option<Address>| address.filter(|a| !a.is_zero());
+    let mut results = Vec::new();
+
+    // Routers against their DEX's factory
+    let routers = [
+        ("UNISWAP_ROUTER", Some(config.uniswap_router), Some(config.uniswap_factory)),
+        ("SUSHISWAP_ROUTER", Some(config.sushiswap_router), Some(config.sushiswap_factory)),
+        ("APESWAP_ROUTER", config.apeswap_router, config.apeswap_factory),
+        ("UNISWAP_V3_ROUTER", config.uniswap_v3_router, config.uniswap_v3_factory),
+        ("SUSHISWAP_V3_ROUTER", config.sushiswap_v3_router, config.sushiswap_v3_factory),
+        ("QUICKSWAP_V3_ROUTER", config.quickswap_v3_router, config.quickswap_v3_factory),
+        ("KYBER_ELASTIC_ROUTER", config.kyber_elastic_router, config.kyber_elastic_factory),
+    ];
+    for (name, router, factory) in routers {
+        match configured(router) {
+            Some(router) => results.push(check_router(provider, name, router, configured(factory)).await),
+            None => results.push(CheckResult::new("router", name, None, CheckStatus::Skip, "not configured")),
+        }
+    }
+
+    let factories = [
+        ("UNISWAP_FACTORY", Some(config.uniswap_factory), FactoryKind::V2),
+        ("SUSHISWAP_FACTORY", Some(config.sushiswap_factory), FactoryKind::V2),
+        ("APESWAP_FACTORY", config.apeswap_factory, FactoryKind::V2),
+        ("UNISWAP_V3_FACTORY", config.uniswap_v3_factory, FactoryKind::UniswapV3),
+        ("SUSHISWAP_V3_FACTORY", config.sushiswap_v3_factory, FactoryKind::UniswapV3),
+        ("QUICKSWAP_V3_FACTORY", config.quickswap_v3_factory, FactoryKind::Algebra),
+        ("KYBER_ELASTIC_FACTORY", config.kyber_elastic_factory, FactoryKind::KyberElastic),
+    ];
+    for (name, factory, kind) in factories {
+        match configured(factory) {
+            Some(factory) => results.push(check_factory(provider, name, factory, kind).await),
+            None => results.push(CheckResult::new("factory", name, None, CheckStatus::Skip, "not configured")),
+        }
+    }
+
+    // Quoters: the Uniswap one's ABI flavor, the rest exist
+    match configured(config.uniswap_v3_quoter) {
+        Some(quoter) => {
+            let probes = quoter_probes(config);
+            results.push(check_quoter_flavor(provider, quoter, config.uniswap_v3_quoter_is_v2, &probes).await);
+        }
+        None => results.push(CheckResult::new("quoter flavor", "UNISWAP_V3_QUOTER", None, CheckStatus::Skip, "not configured")),
+    }
+    let quoters = [
+        ("SUSHISWAP_V3_QUOTER", config.sushiswap_v3_quoter),
+        ("QUICKSWAP_V3_QUOTER", config.quickswap_v3_quoter),
+        ("KYBER_ELASTIC_QUOTER", config.kyber_elastic_quoter),
+    ];
+    for (name, quoter) in quoters {
+        match configured(quoter) {
+            Some(quoter) => results.push(check_contract(provider, "quoter", name, quoter).await),
+            None => results.push(CheckResult::new("quoter", name, None, CheckStatus::Skip, "not configured")),
+        }
+    }
+
+    // Quote tokens (native USDC is USDC)
+    let primary_expected = config.quote_token_symbol().and_then(expected_decimals);
+    results.push(check_decimals(provider, "QUOTE_TOKEN_ADDRESS", config.quote_token_address, primary_expected).await);
+    if let Some(native) = configured(config.quote_token_address_native) {
+        results.push(check_decimals(provider, "QUOTE_TOKEN_ADDRESS_NATIVE", native, Some(6)).await);
+    }
+
+    match configured(config.arb_executor_address) {
+        Some(executor) => results.push(check_arb_executor(provider, executor).await),
+        None => results.push(CheckResult::new("arb executor", "ARB_EXECUTOR_ADDRESS", None, CheckStatus::Skip, "not configured")),
+    }
+    let contracts = [
+        ("MULTICALL3_ADDRESS", config.multicall3_address),
+        ("AAVE_POOL_ADDRESS", config.aave_pool_address),
+        ("DODO_V2_PROXY", config.dodo_v2_proxy),
+        ("DODO_APPROVE", config.dodo_approve),
+    ];
+    for (name, address) in contracts {
+        if let Some(address) = configured(address) {
+            results.push(check_contract(provider, "contract", name, address).await);
+        }
+    }
+
+    ConfigCheckReport { results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn word(value: U256) -> Bytes {
+        Bytes::from(abi::encode(&[Token::Uint(value)]))
+    }
+
+    fn code() -> Bytes {
+        Bytes::from(vec![0x60, 0x80, 0x60, 0x40])
+    }
+
+    fn rpc_error(mock: &MockProvider) {
+        mock.push_response(MockResponse::Error(JsonRpcError { code: 3, message: "execution reverted".into(), data: None }));
+    }
+
+    // MockProvider answers the last-pushed response first: every test pushes
+    // the eth_call answers before the eth_getCode one
+
+    #[tokio::test]
+    async fn test_router_factory_must_match() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(Bytes::from(abi::encode(&[Token::Address(addr(0xfa))]))).unwrap();
+        mock.push::<Bytes, _>(code()).unwrap();
+        let ok = check_router(&provider, "UNISWAP_V3_ROUTER", addr(0xa1), Some(addr(0xfa))).await;
+        assert_eq!(ok.status, CheckStatus::Pass, "{}", ok.detail);
+
+        mock.push::<Bytes, _>(Bytes::from(abi::encode(&[Token::Address(addr(0xfb))]))).unwrap();
+        mock.push::<Bytes, _>(code()).unwrap();
+        let wrong = check_router(&provider, "QUICKSWAP_V3_ROUTER", addr(0xa2), Some(addr(0xfa))).await;
+        assert_eq!(wrong.status, CheckStatus::Fail);
+        assert!(wrong.detail.contains("configured factory"), "{}", wrong.detail);
+
+        // An EOA / wrong network: no code, no eth_call made
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        let empty = check_router(&provider, "SUSHISWAP_ROUTER", addr(0xa3), None).await;
+        assert_eq!((empty.status, empty.detail.as_str()), (CheckStatus::Fail, "no code at address"));
+    }
+
+    #[tokio::test]
+    async fn test_factory_probes() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(word(U256::from(10))).unwrap();
+        mock.push::<Bytes, _>(code()).unwrap();
+        assert_eq!(check_factory(&provider, "UNISWAP_V3_FACTORY", addr(1), FactoryKind::UniswapV3).await.status, CheckStatus::Pass);
+
+        mock.push::<Bytes, _>(word(U256::zero())).unwrap();
+        mock.push::<Bytes, _>(code()).unwrap();
+        let algebra = check_factory(&provider, "QUICKSWAP_V3_FACTORY", addr(2), FactoryKind::Algebra).await;
+        assert_eq!(algebra.status, CheckStatus::Fail, "{}", algebra.detail);
+
+        rpc_error(&mock);
+        mock.push::<Bytes, _>(code()).unwrap();
+        assert_eq!(check_factory(&provider, "UNISWAP_FACTORY", addr(3), FactoryKind::V2).await.status, CheckStatus::Fail);
+
+        mock.push::<Bytes, _>(code()).unwrap();
+        assert_eq!(check_factory(&provider, "KYBER_ELASTIC_FACTORY", addr(4), FactoryKind::KyberElastic).await.status, CheckStatus::Pass);
+    }
+
+    #[tokio::test]
+    async fn test_quoter_flavor_against_flag() {
+        let probes = [(addr(0xc1), addr(0xe1))];
+        let quoter = addr(0x99);
+        // fee 500: neither ABI quotes; fee 3000: V1 quotes, V2 reverts
+        let (provider, mock) = Provider::mocked();
+        rpc_error(&mock);
+        mock.push::<Bytes, _>(word(U256::from(420_000u64))).unwrap();
+        rpc_error(&mock);
+        rpc_error(&mock);
+        mock.push::<Bytes, _>(code()).unwrap();
+        let v1 = check_quoter_flavor(&provider, quoter, false, &probes).await;
+        assert_eq!(v1.status, CheckStatus::Pass, "{}", v1.detail);
+        assert!(v1.detail.starts_with("QuoterV1"));
+
+        // Same chain answers, flag says V2
+        rpc_error(&mock);
+        mock.push::<Bytes, _>(word(U256::from(420_000u64))).unwrap();
+        mock.push::<Bytes, _>(code()).unwrap();
+        let wrong = check_quoter_flavor(&provider, quoter, true, &probes).await;
+        assert_eq!(wrong.status, CheckStatus::Fail);
+        assert!(wrong.detail.contains("set it to false"), "{}", wrong.detail);
+
+        // No pool answers either ABI
+        for _ in 0..4 {
+            rpc_error(&mock);
+        }
+        mock.push::<Bytes, _>(code()).unwrap();
+        let silent = check_quoter_flavor(&provider, quoter, false, &probes).await;
+        assert!(silent.status == CheckStatus::Fail && silent.detail.starts_with("neither ABI"));
+    }
+
+    #[tokio::test]
+    async fn test_decimals_and_arb_executor_selector() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(word(U256::from(6))).unwrap();
+        mock.push::<Bytes, _>(code()).unwrap();
+        assert_eq!(check_decimals(&provider, "QUOTE_TOKEN_ADDRESS", addr(1), expected_decimals("USDC")).await.status, CheckStatus::Pass);
+        mock.push::<Bytes, _>(word(U256::from(18))).unwrap();
+        mock.push::<Bytes, _>(code()).unwrap();
+        let wrong = check_decimals(&provider, "QUOTE_TOKEN_ADDRESS", addr(1), Some(6)).await;
+        assert_eq!((wrong.status, wrong.detail.as_str()), (CheckStatus::Fail, "decimals() = 18, expected 6"));
+
+        let selector = id(EXECUTE_ARB_SIGNATURE);
+        let mut dispatcher = vec![0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c, 0x80, PUSH4];
+        dispatcher.extend_from_slice(&selector);
+        dispatcher.extend_from_slice(&[0x14, 0x61, 0x00, 0x4a, 0x57]);
+        mock.push::<Bytes, _>(Bytes::from(dispatcher)).unwrap();
+        assert_eq!(check_arb_executor(&provider, addr(0xe0)).await.status, CheckStatus::Pass);
+        mock.push::<Bytes, _>(code()).unwrap();
+        assert_eq!(check_arb_executor(&provider, addr(0xe0)).await.status, CheckStatus::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_run_skips_unset_and_reports_failures() {
+        let mut config = create_test_config();
+        config.apeswap_router = None;
+        config.apeswap_factory = None;
+        config.uniswap_v3_quoter = None;
+        config.sushiswap_v3_quoter = None;
+        config.quickswap_v3_quoter = None;
+        config.kyber_elastic_quoter = None;
+        config.quote_token_address_native = None;
+        config.arb_executor_address = None;
+        config.multicall3_address = None;
+        config.aave_pool_address = None;
+        // No answers queued: every configured contract fails its code read
+        let (provider, _mock) = Provider::mocked();
+        let report = run(&provider, &config).await;
+
+        let configured = report.results.iter().filter(|r| r.address.is_some()).count();
+        assert_eq!(report.failures(), configured);
+        assert!(report.results.iter().any(|r| r.name == "APESWAP_ROUTER" && r.status == CheckStatus::Skip));
+        assert!(report.results.iter().any(|r| r.name == "QUOTE_TOKEN_ADDRESS" && r.status == CheckStatus::Fail));
+        assert_eq!(report.count(CheckStatus::Pass), 0);
+        let table = report.format_table();
+        assert!(table.starts_with("STATUS"));
+        assert_eq!(table.lines().count(), report.results.len() + 1);
+    }
+}
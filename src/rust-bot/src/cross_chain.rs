@@ -0,0 +1,405 @@
+//! Cross-Chain Price Divergence Monitor (research only — nothing is bridged or executed)
+//!
+//! Each chain runs its own instance, so a WETH/USDC gap between Polygon and
+//! Base goes unnoticed. With CROSS_CHAIN_STATE_FILE pointing at the other
+//! chain's data collector state file (SharedPoolState JSON), every block the
+//! bot compares its own pools against it:
+//!   - prices are normalized to quote per base (USDC per WETH), oriented by
+//!     each chain's quote tokens — token0/token1 order differs by chain
+//!   - a pair's price is the median over its pools; pairs match by symbol
+//!   - divergence_bps = (remote - local) / local × 10_000
+//!
+//! Every compared pair is appended to data/cross_chain/divergence_YYYYMMDD.csv;
+//! a pair crossing CROSS_CHAIN_DIVERGENCE_BPS is logged once when it crosses
+//! and once when it comes back. A remote file older than
+//! CROSS_CHAIN_MAX_AGE_SECS (its collector stalled) is skipped with a warning.
+//! The remote file is re-read only when its mtime changes, with
+//! SharedPoolState::read_compatible() so a collector on another build still
+//! reads. Schema version 1 files carry no quote tokens: their pairs can't be
+//! oriented and are skipped.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::data_collector::SharedPoolState;
+use crate::pool::PoolStateManager;
+use crate::types::BotConfig;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tracing::{debug, info, warn};
+
+/// Default output directory for the divergence CSVs
+pub const DEFAULT_CROSS_CHAIN_DIR: &str = "/home/botuser/bots/dexarb/data/cross_chain";
+
+/// One common pair compared across the two chains
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairDivergence {
+    pub pair: String,
+    /// Quote per base on this chain
+    pub local_price: f64,
+    /// Quote per base on the remote chain
+    pub remote_price: f64,
+    /// (remote - local) / local in basis points
+    pub divergence_bps: f64,
+}
+
+/// Quote-per-base price of each pair in `state`: the median over its V2 and
+/// V3 pools. Pools with neither token a quote token of the writing chain (or
+/// without a usable price) are left out.
+pub fn pair_prices(state: &SharedPoolState) -> BTreeMap<String, f64> {
+    let v2 = state.pools.values().map(|p| (&p.pair_symbol, &p.token0, &p.token1, p.price));
+    let v3 = state
+        .v3_pools
+        .values()
+        .map(|p| (&p.pair_symbol, &p.token0, &p.token1, p.validated_price()));
+
+    let mut by_pair: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for (pair, token0, token1, price) in v2.chain(v3) {
+        if !(price.is_finite() && price > 0.0) {
+            continue;
+        }
+        // Stored price is token1 per token0
+        let normalized = if state.is_quote_token(token1) {
+            price
+        } else if state.is_quote_token(token0) {
+            1.0 / price
+        } else {
+            continue;
+        };
+        by_pair.entry(pair.clone()).or_default().push(normalized);
+    }
+
+    by_pair
+        .into_iter()
+        .map(|(pair, mut prices)| {
+            prices.sort_by(|a, b| a.total_cmp(b));
+            let mid = prices.len() / 2;
+            let median = if prices.len() % 2 == 0 { (prices[mid - 1] + prices[mid]) / 2.0 } else { prices[mid] };
+            (pair, median)
+        })
+        .collect()
+}
+
+/// Pairs present on both chains, in symbol order
+pub fn compare(local: &BTreeMap<String, f64>, remote: &BTreeMap<String, f64>) -> Vec<PairDivergence> {
+    local
+        .iter()
+        .filter_map(|(pair, &local_price)| {
+            let &remote_price = remote.get(pair)?;
+            Some(PairDivergence {
+                pair: pair.clone(),
+                local_price,
+                remote_price,
+                divergence_bps: (remote_price - local_price) / local_price * 10_000.0,
+            })
+        })
+        .collect()
+}
+
+/// This chain's live pools in the shared state format, so both sides are
+/// normalized by the same code
+pub fn local_state(manager: &PoolStateManager, config: &BotConfig, block: u64) -> SharedPoolState {
+    let mut state = SharedPoolState::new(config.chain_id)
+        .with_quote_tokens(std::iter::once(config.quote_token_address).chain(config.quote_token_address_native));
+    state.block_number = block;
+    for pool in manager.get_all_pools() {
+        state.update_pool(&pool);
+    }
+    for pool in manager.get_all_v3_pools() {
+        state.update_v3_pool(&pool);
+    }
+    state
+}
+
+/// Per-block comparison against the remote chain's state file
+pub struct CrossChainMonitor {
+    remote_path: PathBuf,
+    out_dir: PathBuf,
+    threshold_bps: f64,
+    max_age_secs: i64,
+    /// Last read remote state and the file mtime it was read at
+    remote: Option<(SystemTime, SharedPoolState)>,
+    /// Stale/unreadable remote already warned about (reset once it reads fresh)
+    remote_warned: bool,
+    /// Pairs currently beyond the threshold
+    alerting: HashSet<String>,
+}
+
+impl CrossChainMonitor {
+    pub fn new(remote_path: &str, out_dir: &str, threshold_bps: f64, max_age_secs: u64) -> Self {
+        Self {
+            remote_path: PathBuf::from(remote_path),
+            out_dir: PathBuf::from(out_dir),
+            threshold_bps,
+            max_age_secs: max_age_secs as i64,
+            remote: None,
+            remote_warned: false,
+            alerting: HashSet::new(),
+        }
+    }
+
+    /// None unless CROSS_CHAIN_STATE_FILE is set
+    pub fn from_config(config: &BotConfig) -> Option<Self> {
+        let path = config.cross_chain_state_file.as_deref()?;
+        Some(Self::new(
+            path,
+            DEFAULT_CROSS_CHAIN_DIR,
+            config.cross_chain_divergence_bps,
+            config.cross_chain_max_age_secs,
+        ))
+    }
+
+    fn warn_remote(&mut self, msg: String) {
+        if !self.remote_warned {
+            warn!("Cross-chain: {} — skipping comparison until it updates", msg);
+            self.remote_warned = true;
+        } else {
+            debug!("Cross-chain: {}", msg);
+        }
+    }
+
+    /// Re-read the remote file if it changed; the fresh remote state, if any
+    fn refresh_remote(&mut self) -> Option<&SharedPoolState> {
+        let mtime = match std::fs::metadata(&self.remote_path).and_then(|m| m.modified()) {
+            Ok(t) => t,
+            Err(e) => {
+                self.warn_remote(format!("remote state {} unavailable: {}", self.remote_path.display(), e));
+                return None;
+            }
+        };
+        if self.remote.as_ref().map(|(t, _)| *t) != Some(mtime) {
+            match SharedPoolState::read_compatible(&self.remote_path) {
+                Ok(state) => self.remote = Some((mtime, state)),
+                Err(e) => {
+                    self.remote = None;
+                    self.warn_remote(format!("remote state unreadable: {:#}", e));
+                    return None;
+                }
+            }
+        }
+
+        let age = self.remote.as_ref().map(|(_, s)| Utc::now().signed_duration_since(s.last_updated).num_seconds());
+        match age {
+            Some(age) if age > self.max_age_secs => {
+                self.warn_remote(format!("remote state is {}s old (max {}s)", age, self.max_age_secs));
+                None
+            }
+            Some(_) => {
+                if self.remote_warned {
+                    info!("Cross-chain: remote state fresh again — comparison resumed");
+                    self.remote_warned = false;
+                }
+                self.remote.as_ref().map(|(_, s)| s)
+            }
+            None => None,
+        }
+    }
+
+    /// Compare `local` against the remote chain, log threshold crossings and
+    /// append the comparison to the day's CSV. Empty when the remote is
+    /// missing, stale or has no pair in common.
+    pub fn check(&mut self, local: &SharedPoolState) -> Result<Vec<PairDivergence>> {
+        let Some(remote) = self.refresh_remote() else {
+            return Ok(Vec::new());
+        };
+        if remote.chain_id == local.chain_id {
+            let msg = format!("remote state is this chain ({})", local.chain_id);
+            self.warn_remote(msg);
+            return Ok(Vec::new());
+        }
+        let (remote_chain, remote_block) = (remote.chain_id, remote.block_number);
+        let remote_age = Utc::now().signed_duration_since(remote.last_updated).num_seconds();
+        let divergences = compare(&pair_prices(local), &pair_prices(remote));
+
+        for d in &divergences {
+            let beyond = d.divergence_bps.abs() >= self.threshold_bps;
+            if beyond && self.alerting.insert(d.pair.clone()) {
+                warn!(
+                    "🌉 CROSS-CHAIN DIVERGENCE: {} {:+.1} bps | chain {} {:.6} vs chain {} {:.6} (block {} / {})",
+                    d.pair, d.divergence_bps, local.chain_id, d.local_price, remote_chain, d.remote_price,
+                    local.block_number, remote_block
+                );
+            } else if !beyond && self.alerting.remove(&d.pair) {
+                info!("🌉 Cross-chain: {} back within {:.0} bps ({:+.1} bps)", d.pair, self.threshold_bps, d.divergence_bps);
+            }
+        }
+
+        if !divergences.is_empty() {
+            self.write(local, remote_chain, remote_block, remote_age, &divergences)?;
+        }
+        Ok(divergences)
+    }
+
+    fn write(
+        &self,
+        local: &SharedPoolState,
+        remote_chain: u64,
+        remote_block: u64,
+        remote_age: i64,
+        divergences: &[PairDivergence],
+    ) -> Result<()> {
+        std::fs::create_dir_all(&self.out_dir).with_context(|| format!("create {}", self.out_dir.display()))?;
+        let path = self.out_dir.join(format!("divergence_{}.csv", Utc::now().format("%Y%m%d")));
+        let is_new = !path.exists();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("open {}", path.display()))?;
+        if is_new {
+            writeln!(
+                file,
+                "timestamp,pair,local_chain_id,local_block,local_price,remote_chain_id,remote_block,remote_age_secs,remote_price,divergence_bps,alert"
+            )?;
+        }
+        let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+        for d in divergences {
+            writeln!(
+                file,
+                "{},{},{},{},{:.8},{},{},{},{:.8},{:.2},{}",
+                now,
+                d.pair,
+                local.chain_id,
+                local.block_number,
+                d.local_price,
+                remote_chain,
+                remote_block,
+                remote_age,
+                d.remote_price,
+                d.divergence_bps,
+                d.divergence_bps.abs() >= self.threshold_bps
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_collector::SerializableV3PoolState;
+    use ethers::types::Address;
+
+    const POLYGON_USDC: &str = "0x2791bca1f2de4661ed88a30c99a7a9449aa84174";
+    const POLYGON_WETH: &str = "0x7ceb23fd6bc0add59e62ac25578270cff1b9f619";
+    const BASE_WETH: &str = "0x4200000000000000000000000000000000000006";
+    const BASE_USDC: &str = "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+
+    fn v3(pair: &str, fee: u32, token0: &str, token1: &str, price: f64) -> SerializableV3PoolState {
+        SerializableV3PoolState {
+            dex: format!("UniswapV3_{}", fee),
+            pair_symbol: pair.to_string(),
+            address: format!("{:?}", Address::from_low_u64_be(fee as u64)),
+            token0: token0.to_string(),
+            token1: token1.to_string(),
+            sqrt_price_x96: "0".to_string(),
+            tick: 0,
+            fee,
+            liquidity: "1".to_string(),
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 1,
+            price,
+        }
+    }
+
+    fn state(chain_id: u64, quote: &str, pools: Vec<SerializableV3PoolState>) -> SharedPoolState {
+        let mut state = SharedPoolState::new(chain_id).with_quote_tokens([quote.parse::<Address>().unwrap()]);
+        state.block_number = chain_id * 1000;
+        for pool in pools {
+            state.v3_pools.insert(format!("{}:{}", pool.dex, pool.pair_symbol), pool);
+        }
+        state
+    }
+
+    /// Polygon: USDC.e is token0 (price = WETH per USDC); Base: WETH is token0
+    fn polygon() -> SharedPoolState {
+        state(
+            137,
+            POLYGON_USDC,
+            vec![
+                v3("WETH/USDC", 500, POLYGON_USDC, POLYGON_WETH, 1.0 / 2500.0),
+                v3("WETH/USDC", 3000, POLYGON_USDC, POLYGON_WETH, 1.0 / 2490.0),
+                v3("WETH/USDC", 100, POLYGON_USDC, POLYGON_WETH, 1.0 / 2510.0),
+                v3("WBTC/USDC", 500, POLYGON_USDC, "0x1bfd67037b42cf73acf2047067bd4f2c47d9bfd6", 1.0 / 60000.0),
+            ],
+        )
+    }
+
+    fn base() -> SharedPoolState {
+        state(8453, BASE_USDC, vec![v3("WETH/USDC", 500, BASE_WETH, BASE_USDC, 2506.0)])
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dexarb_cross_chain_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_pair_prices_normalized_across_orientations() {
+        let polygon = pair_prices(&polygon());
+        assert!((polygon["WETH/USDC"] - 2500.0).abs() < 1e-6, "median of three pools");
+        assert!((pair_prices(&base())["WETH/USDC"] - 2506.0).abs() < 1e-9);
+
+        let d = compare(&polygon, &pair_prices(&base()));
+        assert_eq!(d.len(), 1, "WBTC/USDC only exists on Polygon");
+        assert!((d[0].divergence_bps - 24.0).abs() < 1e-6);
+
+        // A pool with no quote token (or a v1 file without quote tokens) is not guessed at
+        let mut legacy = base();
+        legacy.quote_tokens.clear();
+        assert!(pair_prices(&legacy).is_empty());
+    }
+
+    #[test]
+    fn test_monitor_against_state_files() {
+        let dir = test_dir("files");
+        let remote_path = dir.join("base_pool_state.json");
+        base().write_to_file(&remote_path).unwrap();
+
+        let mut monitor = CrossChainMonitor::new(remote_path.to_str().unwrap(), dir.to_str().unwrap(), 20.0, 30);
+        let local = polygon();
+        local.write_to_file(dir.join("polygon_pool_state.json")).unwrap();
+        let local = SharedPoolState::read_compatible(dir.join("polygon_pool_state.json")).unwrap();
+
+        let d = monitor.check(&local).unwrap();
+        assert_eq!(d.len(), 1);
+        assert!(monitor.alerting.contains("WETH/USDC"));
+
+        let csv = std::fs::read_to_string(dir.join(format!("divergence_{}.csv", Utc::now().format("%Y%m%d")))).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains(",WETH/USDC,137,137000,2500.00000000,8453,8453000,"));
+        assert!(lines[1].ends_with(",24.00,true"));
+
+        // Remote moves back in line: alert clears, still recorded
+        let mut closer = base();
+        closer.v3_pools.values_mut().for_each(|p| p.price = 2501.0);
+        closer.write_to_file(&remote_path).unwrap();
+        monitor.remote = None;
+        let d = monitor.check(&local).unwrap();
+        assert!((d[0].divergence_bps - 4.0).abs() < 1e-6);
+        assert!(monitor.alerting.is_empty());
+
+        // Stale remote file: skipped, nothing written
+        let mut stale = base();
+        stale.last_updated = Utc::now() - chrono::Duration::seconds(120);
+        stale.write_to_file(&remote_path).unwrap();
+        monitor.remote = None;
+        assert!(monitor.check(&local).unwrap().is_empty());
+        assert!(monitor.remote_warned);
+        let csv = std::fs::read_to_string(dir.join(format!("divergence_{}.csv", Utc::now().format("%Y%m%d")))).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+
+        // Missing remote file
+        let mut missing = CrossChainMonitor::new(dir.join("nope.json").to_str().unwrap(), dir.to_str().unwrap(), 20.0, 30);
+        assert!(missing.check(&local).unwrap().is_empty());
+    }
+}
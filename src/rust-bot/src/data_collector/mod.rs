@@ -14,10 +14,13 @@
 //! Modified: 2026-01-28 (added V3 pool support)
 //! Modified: 2026-01-28 (staggered V3 sync to avoid rate limiting)
 //! Modified: 2026-01-30 (V3-only whitelist sync — removed V2, added parallel refresh)
+//! Modified: 2026-10-16 (state file records the chain's quote tokens for cross-chain readers)
 
 pub mod shared_state;
 
-pub use shared_state::{SerializablePoolState, SerializableV3PoolState, SharedPoolState, SyncStats};
+pub use shared_state::{
+    SerializablePoolState, SerializableV3PoolState, SharedPoolState, SyncStats, SHARED_STATE_SCHEMA_VERSION,
+};
 
 use crate::filters::WhitelistFilter;
 use crate::pool::{V3PoolSyncer, V3_FEE_TIERS};
@@ -73,7 +76,8 @@ where
         }
     };
 
-    let mut shared_state = SharedPoolState::new(config.chain_id);
+    let mut shared_state = SharedPoolState::new(config.chain_id)
+        .with_quote_tokens(std::iter::once(config.quote_token_address).chain(config.quote_token_address_native));
     let poll_interval = Duration::from_millis(config.poll_interval_ms);
 
     // V2 sync: SKIPPED — live bot reads V3 data only
@@ -10,14 +10,25 @@
 //! Author: AI-Generated
 //! Created: 2026-01-28
 //! Modified: 2026-01-28 (added V3 pool support)
+//! Modified: 2026-10-16 (schema_version + quote_tokens, tolerant read_compatible() for cross-chain readers)
 
 use crate::types::{DexType, PoolState, TradingPair, V3PoolState};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use ethers::types::{Address, U256};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use tracing::warn;
+
+/// Current shared state file format. Files without the field are version 1.
+///   2: schema_version, quote_tokens
+pub const SHARED_STATE_SCHEMA_VERSION: u32 = 2;
+
+fn legacy_schema_version() -> u32 {
+    1
+}
 
 /// Serializable pool state for JSON storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -176,6 +187,9 @@ impl SerializableV3PoolState {
 /// Shared state file format
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SharedPoolState {
+    /// File format version (SHARED_STATE_SCHEMA_VERSION when written by this build)
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     /// Last update timestamp
     pub last_updated: DateTime<Utc>,
     /// Current block number
@@ -189,6 +203,10 @@ pub struct SharedPoolState {
     pub v3_pools: HashMap<String, SerializableV3PoolState>,
     /// Sync statistics
     pub stats: SyncStats,
+    /// Quote token addresses of the writing chain — orients pool prices for
+    /// readers that don't share its config (empty before schema version 2)
+    #[serde(default)]
+    pub quote_tokens: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -202,6 +220,7 @@ pub struct SyncStats {
 impl SharedPoolState {
     pub fn new(chain_id: u64) -> Self {
         Self {
+            schema_version: SHARED_STATE_SCHEMA_VERSION,
             last_updated: Utc::now(),
             block_number: 0,
             chain_id,
@@ -211,6 +230,21 @@ impl SharedPoolState {
                 start_time: Some(Utc::now()),
                 ..Default::default()
             },
+            quote_tokens: Vec::new(),
+        }
+    }
+
+    /// Record the writing chain's quote tokens (see `quote_tokens`)
+    pub fn with_quote_tokens(mut self, tokens: impl IntoIterator<Item = Address>) -> Self {
+        self.quote_tokens = tokens.into_iter().map(|a| format!("{:?}", a)).collect();
+        self
+    }
+
+    /// Whether `addr` is one of the writing chain's quote tokens
+    pub fn is_quote_token(&self, addr: &str) -> bool {
+        match addr.parse::<Address>() {
+            Ok(addr) => self.quote_tokens.iter().any(|q| q.parse::<Address>().ok() == Some(addr)),
+            Err(_) => false,
         }
     }
 
@@ -293,6 +327,65 @@ impl SharedPoolState {
         Ok(state)
     }
 
+    /// Read a file written by any collector build: a newer schema version is
+    /// read with a warning (unknown fields ignored), and pool entries that
+    /// don't parse are skipped individually instead of failing the file.
+    /// Only the header (last_updated, block_number, chain_id) is required.
+    pub fn read_compatible<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = std::fs::read_to_string(path.as_ref())
+            .with_context(|| format!("Failed to read shared state file {}", path.as_ref().display()))?;
+        Self::from_json_compatible(&json)
+    }
+
+    /// Parse side of read_compatible()
+    pub fn from_json_compatible(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .context("Failed to parse shared state JSON")?;
+        let obj = value.as_object().context("Shared state is not a JSON object")?;
+
+        let schema_version = obj
+            .get("schema_version")
+            .and_then(|v| v.as_u64())
+            .map_or(legacy_schema_version(), |v| v as u32);
+        if schema_version > SHARED_STATE_SCHEMA_VERSION {
+            warn!(
+                "Shared state schema version {} is newer than supported ({}) — reading known fields only",
+                schema_version, SHARED_STATE_SCHEMA_VERSION
+            );
+        }
+
+        fn field<T: DeserializeOwned>(obj: &serde_json::Map<String, serde_json::Value>, name: &str) -> Result<T> {
+            let v = obj.get(name).with_context(|| format!("Shared state missing '{}'", name))?;
+            serde_json::from_value(v.clone()).with_context(|| format!("Shared state '{}' invalid", name))
+        }
+        fn entries<T: DeserializeOwned>(obj: &serde_json::Map<String, serde_json::Value>, name: &str) -> HashMap<String, T> {
+            let Some(map) = obj.get(name).and_then(|v| v.as_object()) else {
+                return HashMap::new();
+            };
+            let mut out = HashMap::new();
+            for (key, v) in map {
+                match serde_json::from_value(v.clone()) {
+                    Ok(entry) => {
+                        out.insert(key.clone(), entry);
+                    }
+                    Err(e) => warn!("Shared state: skipping unreadable {} entry '{}': {}", name, key, e),
+                }
+            }
+            out
+        }
+
+        Ok(Self {
+            schema_version,
+            last_updated: field(obj, "last_updated")?,
+            block_number: field(obj, "block_number")?,
+            chain_id: field(obj, "chain_id")?,
+            pools: entries(obj, "pools"),
+            v3_pools: entries(obj, "v3_pools"),
+            stats: field(obj, "stats").unwrap_or_default(),
+            quote_tokens: field(obj, "quote_tokens").unwrap_or_default(),
+        })
+    }
+
     /// Check if state is stale (older than threshold)
     pub fn is_stale(&self, max_age_secs: i64) -> bool {
         let age = Utc::now().signed_duration_since(self.last_updated);
@@ -334,4 +427,42 @@ mod tests {
         assert_eq!(restored.block_number, 12345);
         assert_eq!(restored.pools.len(), 1);
     }
+
+    #[test]
+    fn test_read_compatible_tolerates_versions() {
+        // Version 1 file: no schema_version / quote_tokens, one malformed V3 entry
+        let legacy = r#"{
+            "last_updated": "2026-10-16T12:00:00Z",
+            "block_number": 100,
+            "chain_id": 8453,
+            "pools": {},
+            "v3_pools": {
+                "bad": {"dex": "UniswapV3_0.05%"}
+            },
+            "stats": {"total_syncs": 1, "successful_syncs": 1, "failed_syncs": 0, "start_time": null}
+        }"#;
+        assert!(serde_json::from_str::<SharedPoolState>(legacy).is_err());
+        let state = SharedPoolState::from_json_compatible(legacy).unwrap();
+        assert_eq!(state.schema_version, 1);
+        assert_eq!(state.block_number, 100);
+        assert!(state.v3_pools.is_empty());
+        assert!(state.quote_tokens.is_empty());
+
+        // Newer version with unknown fields and no stats still reads
+        let newer = r#"{
+            "schema_version": 9,
+            "last_updated": "2026-10-16T12:00:00Z",
+            "block_number": 7,
+            "chain_id": 137,
+            "pools": {},
+            "future_field": [1, 2, 3],
+            "quote_tokens": ["0x2791bca1f2de4661ed88a30c99a7a9449aa84174"]
+        }"#;
+        let state = SharedPoolState::from_json_compatible(newer).unwrap();
+        assert_eq!(state.schema_version, 9);
+        assert!(state.is_quote_token("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174"));
+
+        // The header is required
+        assert!(SharedPoolState::from_json_compatible(r#"{"block_number": 1}"#).is_err());
+    }
 }
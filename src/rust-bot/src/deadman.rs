@@ -0,0 +1,468 @@
+//! Dead-Man's Switch — progress pings for an external uptime monitor
+//!
+//! A healthchecks.io-style service pages the operator when an expected ping
+//! stops arriving, without any access to the box. The bot pings DEADMAN_URL
+//! every DEADMAN_INTERVAL_SECS, but only while every configured health
+//! criterion passes (DEADMAN_CRITERIA, default all four):
+//!
+//!   heartbeat — no critical watchdog component (the block loop) is stale
+//!   block_age — a new block arrived within DEADMAN_MAX_BLOCK_AGE_SECS
+//!   breaker   — no halt outcome (receipt timeout / capital at risk) since start
+//!   tax       — no strict tax logging submission halt
+//!
+//! A stalled or degraded bot simply goes silent and the monitor alarms. With
+//! DEADMAN_FAIL_URL set (healthchecks.io: `<url>/fail`), a failing check pings
+//! that URL instead so the alarm is immediate and carries the reason.
+//!
+//! Payload: current block + a compact status string ("ok" or the failed
+//! criteria) — DEADMAN_METHOD=get sends them as `?block=…&status=…`, post as
+//! a text body "block=… status=…".
+//!
+//! The pinger is its own tokio task (a stuck block loop cannot also stop the
+//! failure ping); each ping is fire-and-forget with the `deadman_ping()`
+//! retry policy, skipped while the previous one is still in flight.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::retry::{self, retry, Attempt, RetryError, RetryPolicy, RetrySite};
+use crate::types::BotConfig;
+use crate::watchdog::{self, ComponentHealth};
+use anyhow::{bail, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Per-request HTTP timeout
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest status string sent (monitors show it in their event log)
+const MAX_STATUS_LEN: usize = 200;
+
+/// One health check the ping is gated on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthCriterion {
+    Heartbeat,
+    BlockAge,
+    Breaker,
+    TaxLog,
+}
+
+impl HealthCriterion {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "heartbeat" => Ok(HealthCriterion::Heartbeat),
+            "block_age" => Ok(HealthCriterion::BlockAge),
+            "breaker" => Ok(HealthCriterion::Breaker),
+            "tax" => Ok(HealthCriterion::TaxLog),
+            other => bail!("unknown dead-man criterion '{}' (heartbeat|block_age|breaker|tax)", other),
+        }
+    }
+
+    /// Comma-separated list; empty entries ignored
+    pub fn parse_list(s: &str) -> Result<Vec<Self>> {
+        s.split(',').filter(|c| !c.trim().is_empty()).map(Self::parse).collect()
+    }
+}
+
+/// How the ping is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PingMethod {
+    Get,
+    Post,
+}
+
+impl PingMethod {
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "get" => PingMethod::Get,
+            _ => PingMethod::Post,
+        }
+    }
+}
+
+/// Dead-man's switch settings (from BotConfig)
+#[derive(Debug, Clone)]
+pub struct DeadmanConfig {
+    pub url: String,
+    pub fail_url: Option<String>,
+    pub method: PingMethod,
+    pub interval: Duration,
+    pub max_block_age: Duration,
+    pub criteria: Vec<HealthCriterion>,
+}
+
+impl DeadmanConfig {
+    /// None unless DEADMAN_URL is set
+    pub fn from_bot_config(config: &BotConfig) -> Result<Option<Self>> {
+        let url = match config.deadman_url {
+            Some(ref url) => url.trim().to_string(),
+            None => return Ok(None),
+        };
+        Ok(Some(Self {
+            url,
+            fail_url: config.deadman_fail_url.as_ref().map(|u| u.trim().to_string()),
+            method: PingMethod::parse(&config.deadman_method),
+            interval: Duration::from_secs(config.deadman_interval_secs.max(10)),
+            max_block_age: Duration::from_secs(config.deadman_max_block_age_secs),
+            criteria: HealthCriterion::parse_list(&config.deadman_criteria)?,
+        }))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Health state
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct Progress {
+    block: u64,
+    block_at: Option<Instant>,
+    breaker: Option<String>,
+    tax_halt: Option<String>,
+}
+
+/// Progress and halt state written by the block loop, read by the pinger
+#[derive(Debug, Default)]
+pub struct DeadmanState {
+    progress: Mutex<Progress>,
+}
+
+impl DeadmanState {
+    pub fn on_block(&self, block: u64) {
+        let mut p = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        p.block = block;
+        p.block_at = Some(Instant::now());
+    }
+
+    /// A halt outcome: stays tripped until restart (an operator has to look)
+    pub fn trip_breaker(&self, reason: String) {
+        let mut p = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        if p.breaker.is_none() {
+            p.breaker = Some(reason);
+        }
+    }
+
+    /// Mirror of the executor's strict tax logging halt
+    pub fn set_tax_halt(&self, reason: Option<&str>) {
+        let mut p = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        p.tax_halt = reason.map(str::to_string);
+    }
+
+    /// Everything the criteria look at, as of `now`
+    pub fn inputs(&self, components: Vec<ComponentHealth>, now: Instant) -> HealthInputs {
+        let p = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        HealthInputs {
+            components,
+            block: p.block,
+            block_age: p.block_at.map(|t| now.saturating_duration_since(t)),
+            breaker: p.breaker.clone(),
+            tax_halt: p.tax_halt.clone(),
+        }
+    }
+}
+
+/// Health inputs at one ping
+#[derive(Debug, Clone, Default)]
+pub struct HealthInputs {
+    pub components: Vec<ComponentHealth>,
+    pub block: u64,
+    /// None = no block seen yet
+    pub block_age: Option<Duration>,
+    pub breaker: Option<String>,
+    pub tax_halt: Option<String>,
+}
+
+/// Failed criteria, as short reasons (empty = healthy)
+pub fn evaluate(config: &DeadmanConfig, inputs: &HealthInputs) -> Vec<String> {
+    let mut failures = Vec::new();
+    for criterion in &config.criteria {
+        match criterion {
+            HealthCriterion::Heartbeat => {
+                for h in inputs.components.iter().filter(|h| h.critical && h.stale) {
+                    failures.push(format!("{} stale {}s", h.name, h.age_ms / 1000));
+                }
+            }
+            HealthCriterion::BlockAge => match inputs.block_age {
+                None => failures.push("no block yet".to_string()),
+                Some(age) if age > config.max_block_age => {
+                    failures.push(format!("last block {}s ago", age.as_secs()))
+                }
+                Some(_) => {}
+            },
+            HealthCriterion::Breaker => {
+                if let Some(ref reason) = inputs.breaker {
+                    failures.push(format!("halted: {}", reason));
+                }
+            }
+            HealthCriterion::TaxLog => {
+                if let Some(ref reason) = inputs.tax_halt {
+                    failures.push(format!("tax halt: {}", reason));
+                }
+            }
+        }
+    }
+    failures
+}
+
+/// One ping to send
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ping {
+    pub url: String,
+    pub healthy: bool,
+    pub block: u64,
+    pub status: String,
+}
+
+/// The ping for these inputs: to DEADMAN_URL when healthy, to DEADMAN_FAIL_URL
+/// when failing, or None (silence) when failing without a fail URL
+pub fn assemble(config: &DeadmanConfig, inputs: &HealthInputs) -> Option<Ping> {
+    let failures = evaluate(config, inputs);
+    let healthy = failures.is_empty();
+    let url = if healthy { Some(&config.url) } else { config.fail_url.as_ref() }?;
+    let mut status = if healthy { "ok".to_string() } else { failures.join("; ") };
+    if status.len() > MAX_STATUS_LEN {
+        let mut end = MAX_STATUS_LEN;
+        while !status.is_char_boundary(end) {
+            end -= 1;
+        }
+        status.truncate(end);
+    }
+    Some(Ping { url: url.clone(), healthy, block: inputs.block, status })
+}
+
+impl Ping {
+    /// POST body
+    pub fn body(&self) -> String {
+        format!("block={} status={}", self.block, self.status)
+    }
+
+    /// GET query parameters
+    pub fn query(&self) -> [(&'static str, String); 2] {
+        [("block", self.block.to_string()), ("status", self.status.clone())]
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pinger
+// ---------------------------------------------------------------------------
+
+/// Sends pings with retry/backoff, one at a time
+#[derive(Clone)]
+pub struct DeadmanPinger {
+    method: PingMethod,
+    policy: RetryPolicy,
+    site: RetrySite,
+    client: reqwest::Client,
+    in_flight: Arc<AtomicBool>,
+}
+
+impl DeadmanPinger {
+    pub fn new(method: PingMethod) -> Self {
+        Self {
+            method,
+            policy: retry::deadman_ping(),
+            site: RetrySite::named("deadman"),
+            client: reqwest::Client::new(),
+            in_flight: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Replace the retry policy (tests)
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Deliver one ping; Ok(attempts used)
+    pub async fn send(&self, ping: &Ping) -> std::result::Result<u32, RetryError<String>> {
+        retry(&self.policy, &self.site, &retry::shutdown_token(), |attempt| async move {
+            match self.send_once(ping).await {
+                Ok(()) => Attempt::Done(attempt),
+                Err(e) => e,
+            }
+        })
+        .await
+    }
+
+    async fn send_once(&self, ping: &Ping) -> std::result::Result<(), Attempt<u32, String>> {
+        let request = match self.method {
+            PingMethod::Get => self.client.get(&ping.url).query(&ping.query()),
+            PingMethod::Post => self.client.post(&ping.url).body(ping.body()),
+        };
+        let response = request
+            .timeout(PING_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| Attempt::Retry(e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else if status.is_client_error() {
+            // Wrong check URL — retrying cannot help
+            Err(Attempt::Abort(format!("monitor rejected ping: HTTP {}", status)))
+        } else {
+            Err(Attempt::Retry(format!("HTTP {}", status)))
+        }
+    }
+
+    /// Ping in the background; skipped while the previous ping is still running.
+    /// Must be called from within a tokio runtime.
+    pub fn spawn_send(&self, ping: Ping) {
+        if self.in_flight.swap(true, Ordering::AcqRel) {
+            debug!("Dead-man: previous ping still in flight — skipping");
+            return;
+        }
+        let pinger = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = pinger.send(&ping).await {
+                warn!("Dead-man ping failed: {:?}", e);
+            }
+            pinger.in_flight.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// Start the ping task: every interval, evaluate the criteria over `state`
+/// and the watchdog registry and ping (or stay silent). Stops on shutdown.
+pub fn spawn(config: DeadmanConfig, state: Arc<DeadmanState>) {
+    info!(
+        "Dead-man's switch: {:?} ping every {}s while {:?} pass (fail ping {})",
+        config.method,
+        config.interval.as_secs(),
+        config.criteria,
+        if config.fail_url.is_some() { "ENABLED" } else { "disabled — silent on failure" }
+    );
+    let pinger = DeadmanPinger::new(config.method);
+    let shutdown = retry::shutdown_token();
+    tokio::spawn(async move {
+        let mut was_healthy = true;
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(config.interval) => {}
+            }
+            let inputs = state.inputs(watchdog::registry().check(), Instant::now());
+            let failures = evaluate(&config, &inputs);
+            if failures.is_empty() != was_healthy {
+                was_healthy = failures.is_empty();
+                if was_healthy {
+                    info!("Dead-man: health restored — pinging again");
+                } else {
+                    warn!("Dead-man: health check failed ({}) — {}", failures.join("; "),
+                          if config.fail_url.is_some() { "sending fail pings" } else { "pings stopped" });
+                }
+            }
+            if let Some(ping) = assemble(&config, &inputs) {
+                pinger.spawn_send(ping);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn config(criteria: &str, fail_url: Option<&str>) -> DeadmanConfig {
+        let mut bot = create_test_config();
+        bot.deadman_url = Some("http://monitor/ping/abc".to_string());
+        bot.deadman_fail_url = fail_url.map(str::to_string);
+        bot.deadman_criteria = criteria.to_string();
+        DeadmanConfig::from_bot_config(&bot).unwrap().unwrap()
+    }
+
+    fn component(name: &'static str, age_s: u64, critical: bool) -> ComponentHealth {
+        ComponentHealth {
+            name,
+            age_ms: age_s * 1000,
+            max_interval_ms: 60_000,
+            critical,
+            stale: age_s > 60,
+            beats: 1,
+        }
+    }
+
+    fn healthy() -> HealthInputs {
+        HealthInputs {
+            components: vec![component("block_loop", 2, true), component("mempool", 500, false)],
+            block: 64_000_123,
+            block_age: Some(Duration::from_secs(2)),
+            breaker: None,
+            tax_halt: None,
+        }
+    }
+
+    #[test]
+    fn test_no_ping_when_a_criterion_fails() {
+        let all = config("heartbeat,block_age,breaker,tax", None);
+        // A stale non-critical component does not gate the ping
+        assert!(assemble(&all, &healthy()).unwrap().healthy);
+
+        let failing = [
+            HealthInputs { components: vec![component("block_loop", 95, true)], ..healthy() },
+            HealthInputs { block_age: Some(Duration::from_secs(61)), ..healthy() },
+            HealthInputs { block_age: None, ..healthy() },
+            HealthInputs { breaker: Some("receipt timeout".to_string()), ..healthy() },
+            HealthInputs { tax_halt: Some("tax record for tx 0x1 not written".to_string()), ..healthy() },
+        ];
+        for inputs in &failing {
+            assert_eq!(evaluate(&all, inputs).len(), 1, "{:?}", inputs);
+            assert!(assemble(&all, inputs).is_none(), "{:?}", inputs);
+        }
+
+        // Composition: a criterion left out of DEADMAN_CRITERIA no longer gates
+        let no_tax = config("heartbeat, block_age ,breaker", None);
+        assert!(assemble(&no_tax, &failing[4]).unwrap().healthy);
+        assert!(assemble(&no_tax, &failing[3]).is_none());
+        assert!(HealthCriterion::parse_list("heartbeat,uptime").is_err());
+    }
+
+    #[test]
+    fn test_payload_assembly() {
+        let with_fail = config("heartbeat,block_age,breaker,tax", Some("http://monitor/ping/abc/fail"));
+        let ok = assemble(&with_fail, &healthy()).unwrap();
+        assert_eq!(ok.url, "http://monitor/ping/abc");
+        assert_eq!(ok.body(), "block=64000123 status=ok");
+        assert_eq!(ok.query(), [("block", "64000123".to_string()), ("status", "ok".to_string())]);
+
+        // Failing variant goes to the fail URL with every failed criterion
+        let inputs = HealthInputs {
+            components: vec![component("block_loop", 95, true)],
+            block_age: Some(Duration::from_secs(95)),
+            ..healthy()
+        };
+        let fail = assemble(&with_fail, &inputs).unwrap();
+        assert!(!fail.healthy);
+        assert_eq!(fail.url, "http://monitor/ping/abc/fail");
+        assert_eq!(fail.body(), "block=64000123 status=block_loop stale 95s; last block 95s ago");
+
+        // Long reasons are capped
+        let inputs = HealthInputs { breaker: Some("x".repeat(500)), ..healthy() };
+        assert_eq!(assemble(&with_fail, &inputs).unwrap().status.len(), MAX_STATUS_LEN);
+    }
+
+    #[tokio::test]
+    async fn test_get_ping_carries_block_and_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/ping/abc", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            socket.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nOK").await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        let ping = Ping { url, healthy: true, block: 42, status: "ok".to_string() };
+        let pinger = DeadmanPinger::new(PingMethod::Get)
+            .with_policy(RetryPolicy::fixed(Duration::from_millis(10)).with_max_attempts(2));
+        assert_eq!(pinger.send(&ping).await.unwrap(), 1);
+        let request = server.await.unwrap();
+        assert!(request.starts_with("GET /ping/abc?block=42&status=ok "), "{}", request);
+    }
+}
@@ -0,0 +1,392 @@
+//! Structured Event Journal — machine-readable trail of significant events
+//!
+//! The tracing lines are written for people; answering "what happened to the
+//! WETH/USDC route at block N" after the fact means grepping them apart. With
+//! LOG_FORMAT=json the tracing output itself becomes JSON (one object per
+//! line), and the bot additionally appends one JSON object per significant
+//! event to data/{chain}/events_YYYYMMDD.jsonl:
+//!
+//!   opportunity_detected  detector output (route, pools, size, estimate)
+//!   verify_result         multicall pre-screen verdict
+//!   tx_submitted          atomic tx sent (hash, nonce, priority fee)
+//!   receipt_parsed        atomic tx settled (inclusion block, realized profit)
+//!   cooldown_applied      route cooldown reset/escalated by an attempt
+//!   ws_reconnect          block subscription lost (stream end, stale, failures)
+//!
+//! Every record carries the chain, the block being processed and an RFC 3339
+//! timestamp. opportunity_id ("{block}:{route}") joins the detection, verify,
+//! submission, receipt and cooldown records of one opportunity; tx_hash joins
+//! submission and receipt.
+//!
+//! Records are serialized on the caller's thread and written by the events
+//! writer thread (io_writer.rs, lossy): a stalled disk drops the oldest
+//! queued events, it never holds up the block loop.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::arbitrage::multicall_quoter::VerifiedOpportunity;
+use crate::arbitrage::outcome::{CooldownEffect, TradeOutcome};
+use crate::arbitrage::slippage::route_key;
+use crate::io_writer::{IsolatedWriter, StreamContract};
+use crate::types::{ArbitrageOpportunity, DexType, TradeResult};
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, SecondsFormat, Utc};
+use ethers::types::Address;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Tracing output format (LOG_FORMAT)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-formatted lines (default)
+    Text,
+    /// One JSON object per log line, plus the event journal
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "json" => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// Join key for every record about one opportunity: detection block + route
+pub fn opportunity_id(block: u64, opp: &ArbitrageOpportunity) -> String {
+    format!("{}:{}", block, route_key(opp))
+}
+
+/// One significant event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BotEvent {
+    OpportunityDetected {
+        opportunity_id: String,
+        pair: String,
+        buy_dex: DexType,
+        sell_dex: DexType,
+        buy_pool: Option<Address>,
+        sell_pool: Option<Address>,
+        trade_size_usd: f64,
+        estimated_profit_usd: f64,
+        spread_percent: f64,
+    },
+    VerifyResult {
+        opportunity_id: String,
+        both_legs_valid: bool,
+        quoted_profit_raw: i128,
+        error: Option<String>,
+    },
+    TxSubmitted {
+        opportunity_id: String,
+        tx_hash: String,
+        nonce: u64,
+        priority_fee_gwei: f64,
+    },
+    ReceiptParsed {
+        opportunity_id: String,
+        tx_hash: Option<String>,
+        success: bool,
+        /// Block the tx was included in (None: receipt timeout)
+        included_block: Option<u64>,
+        net_profit_usd: f64,
+        gas_cost_usd: f64,
+        error: Option<String>,
+    },
+    CooldownApplied {
+        opportunity_id: String,
+        /// TradeOutcome name
+        outcome: String,
+        cooldown: CooldownEffect,
+    },
+    WsReconnect {
+        /// "stream_end", "timeout", "connect_failed" or "subscribe_failed"
+        reason: String,
+        /// Consecutive failed attempts so far (0 for a lost stream)
+        failures: u32,
+        error: Option<String>,
+    },
+}
+
+impl BotEvent {
+    pub fn detected(block: u64, opp: &ArbitrageOpportunity) -> Self {
+        BotEvent::OpportunityDetected {
+            opportunity_id: opportunity_id(block, opp),
+            pair: opp.pair.symbol.clone(),
+            buy_dex: opp.buy_dex,
+            sell_dex: opp.sell_dex,
+            buy_pool: opp.buy_pool_address,
+            sell_pool: opp.sell_pool_address,
+            trade_size_usd: opp.trade_size_usd,
+            estimated_profit_usd: opp.estimated_profit,
+            spread_percent: opp.spread_percent,
+        }
+    }
+
+    pub fn verified(block: u64, opp: &ArbitrageOpportunity, verified: &VerifiedOpportunity) -> Self {
+        BotEvent::VerifyResult {
+            opportunity_id: opportunity_id(block, opp),
+            both_legs_valid: verified.both_legs_valid,
+            quoted_profit_raw: verified.quoted_profit_raw,
+            error: verified.error.clone(),
+        }
+    }
+
+    pub fn receipt(opportunity_id: String, result: &TradeResult) -> Self {
+        BotEvent::ReceiptParsed {
+            opportunity_id,
+            tx_hash: result.tx_hash.clone(),
+            success: result.success,
+            included_block: result.block_number,
+            net_profit_usd: result.net_profit_usd,
+            gas_cost_usd: result.gas_cost_usd,
+            error: result.error.clone(),
+        }
+    }
+
+    pub fn ws_reconnect(reason: &str, failures: u32, error: Option<String>) -> Self {
+        BotEvent::WsReconnect { reason: reason.to_string(), failures, error }
+    }
+
+    pub fn cooldown(block: u64, opp: &ArbitrageOpportunity, outcome: TradeOutcome) -> Self {
+        BotEvent::CooldownApplied {
+            opportunity_id: opportunity_id(block, opp),
+            outcome: format!("{:?}", outcome),
+            cooldown: outcome.cooldown(),
+        }
+    }
+}
+
+/// One journal line
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventRecord {
+    pub timestamp: String,
+    pub chain: String,
+    /// Block being processed when the event happened
+    pub block: u64,
+    #[serde(flatten)]
+    pub event: BotEvent,
+}
+
+/// A serialized record, routed to its day's file by the writer thread
+pub struct EventLine {
+    date: NaiveDate,
+    json: String,
+}
+
+/// Handle to the event journal; clones share one writer thread
+#[derive(Clone)]
+pub struct EventJournal {
+    chain: String,
+    writer: Arc<IsolatedWriter<EventLine>>,
+}
+
+impl EventJournal {
+    /// Journal under `dir` (events_YYYYMMDD.jsonl)
+    pub fn new(dir: &str, chain: &str, contract: StreamContract) -> Self {
+        if let Err(e) = fs::create_dir_all(dir) {
+            warn!("Event journal: failed to create {}: {}", dir, e);
+        }
+        let dir = PathBuf::from(dir);
+        Self::spawn(chain, contract, move |line: &mut EventLine| {
+            let path = dir.join(format!("events_{}.jsonl", line.date.format("%Y%m%d")));
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("open {}", path.display()))?;
+            writeln!(file, "{}", line.json)?;
+            Ok(())
+        })
+    }
+
+    fn spawn<W>(chain: &str, contract: StreamContract, write: W) -> Self
+    where
+        W: FnMut(&mut EventLine) -> Result<()> + Send + 'static,
+    {
+        Self {
+            chain: chain.to_string(),
+            writer: Arc::new(IsolatedWriter::spawn(contract, write)),
+        }
+    }
+
+    /// Queue one event; never waits (a full queue drops its oldest event)
+    pub fn emit(&self, block: u64, event: BotEvent) {
+        let now = Utc::now();
+        let record = EventRecord {
+            timestamp: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+            chain: self.chain.clone(),
+            block,
+            event,
+        };
+        match serde_json::to_string(&record) {
+            Ok(json) => {
+                if let Err(e) = self.writer.send(EventLine { date: now.date_naive(), json }) {
+                    warn!("Event journal: {} — event dropped", e);
+                }
+            }
+            Err(e) => warn!("Event journal: failed to serialize event: {}", e),
+        }
+    }
+
+    /// Backpressure summary for the periodic status (None while quiet)
+    pub fn status_line(&self) -> Option<String> {
+        self.writer.status_line()
+    }
+
+    /// Drain queued events (shutdown)
+    pub fn flush(&self, timeout: Duration) -> bool {
+        self.writer.flush(timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TradingPair;
+    use ethers::types::U256;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    fn opp() -> ArbitrageOpportunity {
+        ArbitrageOpportunity::with_pool_addresses(
+            TradingPair::new(Address::zero(), Address::zero(), "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.0004,
+            0.00041,
+            U256::from(500_000_000u64),
+            Address::from_low_u64_be(1),
+            Address::from_low_u64_be(2),
+        )
+    }
+
+    fn result() -> TradeResult {
+        TradeResult {
+            opportunity: "WETH/USDC".to_string(),
+            opportunity_id: 0,
+            tx_hash: Some("0xabc".to_string()),
+            block_number: Some(101),
+            success: true,
+            profit_usd: 1.2,
+            gas_cost_usd: 0.02,
+            gas_used_native: 0.01,
+            net_profit_usd: 1.18,
+            execution_time_ms: 900,
+            error: None,
+            amount_in: None,
+            amount_out: None,
+        }
+    }
+
+    #[test]
+    fn test_every_variant_serializes_with_its_tag() {
+        let opp = opp();
+        let id = opportunity_id(100, &opp);
+        assert_eq!(id, "100:WETH/USDC UniswapV3_005→UniswapV3_030");
+
+        let verified = VerifiedOpportunity {
+            original_index: 0,
+            both_legs_valid: false,
+            buy_quoted_out: U256::zero(),
+            sell_quoted_out: U256::zero(),
+            quoted_profit_raw: -1_500,
+            error: Some("Sell leg: STF".to_string()),
+        };
+        let events = vec![
+            ("opportunity_detected", BotEvent::detected(100, &opp)),
+            ("verify_result", BotEvent::verified(100, &opp, &verified)),
+            (
+                "tx_submitted",
+                BotEvent::TxSubmitted { opportunity_id: id.clone(), tx_hash: "0xabc".to_string(), nonce: 7, priority_fee_gwei: 35.0 },
+            ),
+            ("receipt_parsed", BotEvent::receipt(id.clone(), &result())),
+            ("cooldown_applied", BotEvent::cooldown(100, &opp, TradeOutcome::AtomicRevert)),
+            ("ws_reconnect", BotEvent::ws_reconnect("timeout", 0, None)),
+        ];
+
+        for (tag, event) in events {
+            let record = EventRecord {
+                timestamp: "2026-10-16T12:00:00.000Z".to_string(),
+                chain: "polygon".to_string(),
+                block: 100,
+                event,
+            };
+            let line = serde_json::to_string(&record).unwrap();
+            assert!(!line.contains('\n'));
+            let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(value["event"], tag);
+            assert_eq!((value["chain"].as_str(), value["block"].as_u64()), (Some("polygon"), Some(100)));
+            if tag != "ws_reconnect" {
+                assert_eq!(value["opportunity_id"], id.as_str(), "{} joins on opportunity_id", tag);
+            }
+            match tag {
+                "opportunity_detected" => assert_eq!(value["buy_dex"], "UniswapV3_005"),
+                "verify_result" => assert_eq!(value["quoted_profit_raw"], -1_500),
+                "receipt_parsed" => assert_eq!((value["tx_hash"].as_str(), value["included_block"].as_u64()), (Some("0xabc"), Some(101))),
+                "cooldown_applied" => assert_eq!((value["outcome"].as_str(), value["cooldown"].as_str()), (Some("AtomicRevert"), Some("escalate"))),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_full_queue_drops_instead_of_blocking() {
+        // Writer stuck on its first event until released
+        let (release, gate) = mpsc::channel::<()>();
+        let (written, seen) = mpsc::channel::<String>();
+        let contract = StreamContract::lossy("events", 4, Duration::from_secs(60));
+        let journal = EventJournal::spawn("polygon", contract, move |line: &mut EventLine| {
+            let _ = gate.recv();
+            written.send(line.json.clone()).unwrap();
+            Ok(())
+        });
+
+        let start = Instant::now();
+        for block in 0..50 {
+            journal.emit(block, BotEvent::ws_reconnect("timeout", 0, None));
+        }
+        assert!(start.elapsed() < Duration::from_secs(1), "emit never waits on the writer");
+
+        let stats = journal.writer.stats();
+        assert_eq!(stats.queued, 4);
+        assert!(stats.dropped >= 45, "dropped {}", stats.dropped);
+
+        // Released: the newest four are written (after the event the writer
+        // may already have been holding)
+        for _ in 0..5 {
+            release.send(()).unwrap();
+        }
+        assert!(journal.flush(Duration::from_secs(5)));
+        let blocks: Vec<u64> = seen
+            .try_iter()
+            .map(|json| serde_json::from_str::<serde_json::Value>(&json).unwrap()["block"].as_u64().unwrap())
+            .collect();
+        assert!(blocks.len() == 4 || blocks.len() == 5, "{:?}", blocks);
+        assert_eq!(&blocks[blocks.len() - 4..], &[46, 47, 48, 49]);
+    }
+
+    #[test]
+    fn test_journal_file_per_day() {
+        let dir = std::env::temp_dir().join(format!("dexarb_events_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let journal = EventJournal::new(dir.to_str().unwrap(), "base", StreamContract::lossy("events", 16, Duration::from_secs(1)));
+        journal.emit(5, BotEvent::detected(5, &opp()));
+        journal.emit(5, BotEvent::cooldown(5, &opp(), TradeOutcome::Success));
+        assert!(journal.flush(Duration::from_secs(5)));
+
+        let path = dir.join(format!("events_{}.jsonl", Utc::now().format("%Y%m%d")));
+        let body = fs::read_to_string(path).unwrap();
+        assert_eq!(body.lines().count(), 2);
+        assert!(body.lines().all(|l| l.contains("\"chain\":\"base\"") && l.contains("\"opportunity_id\":\"5:WETH/USDC")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,455 @@
+//! Whitelist Discovery — factory scan for pools worth whitelisting
+//!
+//! Purpose:
+//!     The whitelist only grows when someone goes looking for pools by hand.
+//!     This on-demand scan asks every configured factory which pools exist for
+//!     the configured pairs, reads their depth, and writes the ones holding at
+//!     least --min-tvl-usd as candidate whitelist entries for review.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Design:
+//!     - Run with `dexarb-bot --discover [--min-tvl-usd N]`; the process writes
+//!       the candidate file and exits without trading.
+//!     - Lookups reuse the migration detector's factory calls (canonical_pool):
+//!       getPool(a, b, fee) per fee tier on Uniswap V3 / SushiSwap V3 / Kyber
+//!       Elastic, poolByPair on Algebra (QuickSwap V3), getPair on the V2
+//!       factories. Factories not configured for the chain are skipped.
+//!     - Found pools are read with the startup syncers (V3PoolSyncer /
+//!       V2PoolSyncer sync_pool_by_address): token order, decimals, reserves or
+//!       sqrtPrice + in-range liquidity.
+//!     - TVL = 2 × quote-side amount × quote price. V2 uses the reserves; V3
+//!       uses the virtual reserves of the in-range liquidity at the current
+//!       price (L / √P, L·√P) — the depth the bot actually trades against, not
+//!       the full position value. Pools without a quote token are unpriced and
+//!       never qualify.
+//!     - Pools already in the whitelist, blacklist or observation section are
+//!       not repeated. Each candidate carries its price (quote per base) vs the
+//!       reference pool: the deepest covered (active / v2_ready) pool on the
+//!       pair, else the deepest non-blacklisted pool found.
+//!     - Output: data/{chain}/research/whitelist_candidates_YYYYMMDD.json —
+//!       whitelist entries with status "candidate" (ignored by the loader)
+//!       plus decimals / TVL / price annotations, ranked by TVL.
+
+use super::whitelist::{PoolWhitelist, TradeLimits, WhitelistPool};
+use crate::arbitrage::migration::{canonical_pool, WatchedPool};
+use crate::pool::{V2PoolSyncer, V3PoolSyncer, KYBER_ELASTIC_FEE_TIERS, SUSHI_V3_FEE_TIERS, V3_FEE_TIERS};
+use crate::types::{BotConfig, DexType, TradingPair};
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Status written on every discovered entry
+pub const CANDIDATE_STATUS: &str = "candidate";
+
+/// Pool depth as read on-chain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PoolReading {
+    /// V2 reserves (raw units)
+    V2 { reserve0: U256, reserve1: U256 },
+    /// V3 / Algebra / Kyber Elastic: sqrtPriceX96 and in-range liquidity
+    V3 { sqrt_price_x96: U256, liquidity: u128 },
+}
+
+fn to_f64(v: U256) -> f64 {
+    v.to_string().parse::<f64>().unwrap_or(0.0)
+}
+
+impl PoolReading {
+    /// Raw (token0, token1) amounts; V3 = virtual reserves at the current price
+    pub fn token_amounts(&self) -> (f64, f64) {
+        match *self {
+            PoolReading::V2 { reserve0, reserve1 } => (to_f64(reserve0), to_f64(reserve1)),
+            PoolReading::V3 { sqrt_price_x96, liquidity } => {
+                let sqrt_price = to_f64(sqrt_price_x96) / 2f64.powi(96);
+                if sqrt_price == 0.0 {
+                    return (0.0, 0.0);
+                }
+                let l = liquidity as f64;
+                (l / sqrt_price, l * sqrt_price)
+            }
+        }
+    }
+
+    /// token1 per token0 in raw units
+    pub fn raw_price(&self) -> f64 {
+        match *self {
+            PoolReading::V2 { reserve0, reserve1 } if !reserve0.is_zero() => to_f64(reserve1) / to_f64(reserve0),
+            PoolReading::V2 { .. } => 0.0,
+            PoolReading::V3 { sqrt_price_x96, .. } => (to_f64(sqrt_price_x96) / 2f64.powi(96)).powi(2),
+        }
+    }
+}
+
+/// One pool returned by a factory, with its on-chain reading
+#[derive(Debug, Clone)]
+pub struct FoundPool {
+    pub address: Address,
+    pub pair: String,
+    pub dex: DexType,
+    /// Whitelist `dex` label (UniswapV3, QuickSwapV2, ...)
+    pub dex_label: &'static str,
+    /// V3 fee tier (0 for V2 and Algebra)
+    pub fee_tier: u32,
+    pub token0_decimals: u8,
+    pub token1_decimals: u8,
+    pub reading: PoolReading,
+    /// None = neither token is a quote token (unpriced)
+    pub quote_is_token0: Option<bool>,
+    pub quote_price_usd: f64,
+}
+
+impl FoundPool {
+    /// 2 × quote-side amount in USD; None when unpriced
+    pub fn tvl_usd(&self) -> Option<f64> {
+        let quote_is_token0 = self.quote_is_token0?;
+        let (amount0, amount1) = self.reading.token_amounts();
+        let (amount, decimals) = if quote_is_token0 {
+            (amount0, self.token0_decimals)
+        } else {
+            (amount1, self.token1_decimals)
+        };
+        Some(2.0 * amount / 10f64.powi(decimals as i32) * self.quote_price_usd)
+    }
+
+    /// Quote per base in human units; None when unpriced or empty
+    pub fn price(&self) -> Option<f64> {
+        let quote_is_token0 = self.quote_is_token0?;
+        let price = self.reading.raw_price()
+            * 10f64.powi(self.token0_decimals as i32 - self.token1_decimals as i32);
+        if price <= 0.0 || !price.is_finite() {
+            return None;
+        }
+        Some(if quote_is_token0 { 1.0 / price } else { price })
+    }
+}
+
+/// Candidate whitelist entry plus the numbers behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateEntry {
+    #[serde(flatten)]
+    pub pool: WhitelistPool,
+    pub token0_decimals: u8,
+    pub token1_decimals: u8,
+    pub tvl_usd: f64,
+    /// Quote per base
+    pub price: f64,
+    /// Deepest covered pool on the pair (else the deepest non-blacklisted pool found)
+    pub reference_pool: Option<String>,
+    pub reference_price: Option<f64>,
+    /// (price − reference_price) / reference_price × 100
+    pub price_vs_reference_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryReport {
+    pub generated_at: String,
+    pub min_tvl_usd: f64,
+    /// Pools returned by the factories (known ones included)
+    pub pools_found: usize,
+    /// Ranked by TVL
+    pub candidates: Vec<CandidateEntry>,
+}
+
+/// Factory lookups per pair: (dex, whitelist label, fee tier). Only factories
+/// configured for the chain are included.
+pub fn factory_queries(config: &BotConfig) -> Vec<(DexType, &'static str, u32)> {
+    let mut queries = Vec::new();
+    if config.uniswap_v3_factory.is_some() {
+        queries.extend(V3_FEE_TIERS.iter().map(|(fee, dex)| (*dex, "UniswapV3", *fee)));
+    }
+    if config.sushiswap_v3_factory.is_some() {
+        queries.extend(SUSHI_V3_FEE_TIERS.iter().map(|(fee, dex)| (*dex, "SushiswapV3", *fee)));
+    }
+    if config.quickswap_v3_factory.is_some() {
+        queries.push((DexType::QuickswapV3, "QuickswapV3", 0));
+    }
+    if config.kyber_elastic_factory.is_some() {
+        queries.extend(KYBER_ELASTIC_FEE_TIERS.iter().map(|(fee, dex)| (*dex, "KyberElastic", *fee)));
+    }
+    if !config.uniswap_factory.is_zero() {
+        queries.push((DexType::QuickSwapV2, "QuickSwapV2", 0));
+    }
+    if !config.sushiswap_factory.is_zero() {
+        queries.push((DexType::SushiSwapV2, "SushiSwapV2", 0));
+    }
+    queries
+}
+
+/// Every address the whitelist file already knows (whitelist, blacklist, observation)
+fn known_addresses(whitelist: &PoolWhitelist) -> BTreeSet<String> {
+    whitelist.whitelist.pools.iter().map(|p| p.address.to_lowercase())
+        .chain(whitelist.blacklist.pools.iter().map(|p| p.address.to_lowercase()))
+        .chain(whitelist.observation.iter().flat_map(|o| o.pools.iter()).map(|p| p.address.to_lowercase()))
+        .collect()
+}
+
+/// Filter by TVL, drop known pools, attach the reference price and rank
+pub fn build_report(found: &[FoundPool], whitelist: &PoolWhitelist, min_tvl_usd: f64, today: &str) -> DiscoveryReport {
+    let known = known_addresses(whitelist);
+    let covered: BTreeSet<String> = whitelist.whitelist.pools.iter()
+        .filter(|p| p.status == "active" || p.status == "v2_ready")
+        .map(|p| p.address.to_lowercase())
+        .collect();
+    let priced: Vec<(&FoundPool, f64, f64)> = found.iter()
+        .filter_map(|p| Some((p, p.tvl_usd()?, p.price()?)))
+        .collect();
+
+    let blacklisted: BTreeSet<String> = whitelist.blacklist.pools.iter().map(|p| p.address.to_lowercase()).collect();
+
+    // Reference per pair: deepest covered pool, else deepest non-blacklisted pool found
+    let mut reference: HashMap<&str, (bool, f64, &FoundPool, f64)> = HashMap::new();
+    for (pool, tvl, price) in priced.iter().filter(|(p, _, _)| !blacklisted.contains(&format!("{:?}", p.address))) {
+        let is_covered = covered.contains(&format!("{:?}", pool.address));
+        let better = match reference.get(pool.pair.as_str()) {
+            Some((ref_covered, ref_tvl, _, _)) => (is_covered, *tvl) > (*ref_covered, *ref_tvl),
+            None => true,
+        };
+        if better {
+            reference.insert(pool.pair.as_str(), (is_covered, *tvl, pool, *price));
+        }
+    }
+
+    let mut candidates: Vec<CandidateEntry> = priced.iter()
+        .filter(|(pool, tvl, _)| *tvl >= min_tvl_usd && !known.contains(&format!("{:?}", pool.address)))
+        .map(|(pool, tvl, price)| {
+            let reference = reference.get(pool.pair.as_str()).map(|(_, _, p, price)| (*p, *price));
+            CandidateEntry {
+                pool: WhitelistPool {
+                    address: format!("{:?}", pool.address),
+                    pair: pool.pair.clone(),
+                    dex: pool.dex_label.to_string(),
+                    fee_tier: pool.fee_tier,
+                    status: CANDIDATE_STATUS.to_string(),
+                    min_liquidity: None,
+                    notes: Some(format!("Discovered — TVL ~${:.0}", tvl)),
+                    added: Some(today.to_string()),
+                    last_verified: None,
+                    canary_until: None,
+                    canary_trades: None,
+                    limits: TradeLimits::default(),
+                },
+                token0_decimals: pool.token0_decimals,
+                token1_decimals: pool.token1_decimals,
+                tvl_usd: *tvl,
+                price: *price,
+                reference_pool: reference.map(|(p, _)| format!("{:?}", p.address)),
+                reference_price: reference.map(|(_, price)| price),
+                price_vs_reference_pct: reference.map(|(_, ref_price)| (price - ref_price) / ref_price * 100.0),
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.tvl_usd.partial_cmp(&a.tvl_usd).unwrap_or(std::cmp::Ordering::Equal));
+
+    DiscoveryReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        min_tvl_usd,
+        pools_found: found.len(),
+        candidates,
+    }
+}
+
+/// Query every configured factory for every configured pair and read the pools found
+pub async fn scan<M: Middleware + 'static>(provider: Arc<M>, config: &BotConfig) -> Result<Vec<FoundPool>> {
+    let queries = factory_queries(config);
+    let mut v3_syncer = V3PoolSyncer::new(Arc::clone(&provider), config.clone());
+    let v2_syncer = V2PoolSyncer::new(Arc::clone(&provider));
+    let mut found = Vec::new();
+
+    for pair_config in &config.pairs {
+        let token0: Address = pair_config.token0.parse().context("Invalid token0 address")?;
+        let token1: Address = pair_config.token1.parse().context("Invalid token1 address")?;
+        let pair = TradingPair::new(token0, token1, pair_config.symbol.clone());
+
+        for (dex, dex_label, fee_tier) in &queries {
+            let lookup = WatchedPool { address: Address::zero(), dex: *dex, pair: pair.clone(), fee: *fee_tier };
+            let address = match canonical_pool(Arc::clone(&provider), config, &lookup).await {
+                Ok(a) if !a.is_zero() => a,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("Discovery: {} {} lookup failed: {}", pair.symbol, dex_label, e);
+                    continue;
+                }
+            };
+
+            let read = if dex.is_v2() {
+                v2_syncer.sync_pool_by_address(address, *dex).await.map(|s| {
+                    (s.pair.token0, s.pair.token1, s.token0_decimals, s.token1_decimals,
+                     PoolReading::V2 { reserve0: s.reserve0, reserve1: s.reserve1 })
+                })
+            } else {
+                v3_syncer.sync_pool_by_address(address, *dex).await.map(|s| {
+                    (s.pair.token0, s.pair.token1, s.token0_decimals, s.token1_decimals,
+                     PoolReading::V3 { sqrt_price_x96: s.sqrt_price_x96, liquidity: s.liquidity })
+                })
+            };
+            let (pool_token0, pool_token1, token0_decimals, token1_decimals, reading) = match read {
+                Ok(r) => r,
+                Err(e) => {
+                    warn!("Discovery: {} {} {:?} unreadable: {}", pair.symbol, dex_label, address, e);
+                    continue;
+                }
+            };
+            let quote = if config.is_quote_token(&pool_token0) {
+                Some((true, pool_token0))
+            } else if config.is_quote_token(&pool_token1) {
+                Some((false, pool_token1))
+            } else {
+                None
+            };
+            debug!("Discovery: {} {} fee={} → {:?}", pair.symbol, dex_label, fee_tier, address);
+            found.push(FoundPool {
+                address,
+                pair: pair.symbol.clone(),
+                dex: *dex,
+                dex_label,
+                fee_tier: *fee_tier,
+                token0_decimals,
+                token1_decimals,
+                reading,
+                quote_is_token0: quote.map(|(is_token0, _)| is_token0),
+                quote_price_usd: quote.map_or(1.0, |(_, token)| config.quote_price_usd(&token)),
+            });
+        }
+    }
+    info!("Discovery: {} pairs × {} factory lookups → {} pools", config.pairs.len(), queries.len(), found.len());
+    Ok(found)
+}
+
+/// Write `{research_dir}/whitelist_candidates_YYYYMMDD.json`; returns the path
+pub fn write_report(research_dir: &str, report: &DiscoveryReport) -> Result<PathBuf> {
+    std::fs::create_dir_all(research_dir).with_context(|| format!("creating {}", research_dir))?;
+    let path = PathBuf::from(research_dir)
+        .join(format!("whitelist_candidates_{}.json", chrono::Utc::now().format("%Y%m%d")));
+    std::fs::write(&path, serde_json::to_string_pretty(report)?)
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    /// WETH(18)/USDC(6) pool, USDC = token1 unless `quote_is_token0`
+    fn found(n: u64, dex_label: &'static str, reading: PoolReading, quote_is_token0: Option<bool>) -> FoundPool {
+        let (token0_decimals, token1_decimals) = if quote_is_token0 == Some(true) { (6, 18) } else { (18, 6) };
+        FoundPool {
+            address: addr(n),
+            pair: "WETH/USDC".to_string(),
+            dex: DexType::UniswapV3_005,
+            dex_label,
+            fee_tier: 500,
+            token0_decimals,
+            token1_decimals,
+            reading,
+            quote_is_token0,
+            quote_price_usd: 1.0,
+        }
+    }
+
+    /// WETH(18)/USDC(6) at 2500 USDC/WETH: raw price 2.5e-9 → √P = 5e-5
+    fn v3_at_2500(liquidity: u128) -> PoolReading {
+        PoolReading::V3 { sqrt_price_x96: U256::from(2u128.pow(96)) * 5 / 100_000, liquidity }
+    }
+
+    fn whitelist(pools_json: &str) -> PoolWhitelist {
+        serde_json::from_str(&format!(
+            r#"{{ "version": "1", "last_updated": "", "config": {{ "default_min_liquidity": 0, "whitelist_enforcement": "strict" }},
+                 "whitelist": {{ "pools": [{}] }},
+                 "blacklist": {{ "pools": [{{ "address": "{:?}", "pair": "WETH/USDC", "dex": "UniswapV3", "fee_tier": 10000,
+                                              "reason": "phantom", "date_added": "2026-01-29" }}], "fee_tiers": [] }} }}"#,
+            pools_json, addr(9)
+        )).unwrap()
+    }
+
+    #[test]
+    fn test_tvl_and_price_v2_and_v3() {
+        // V2: 500k USDC (token0) vs 200 WETH → $1M TVL, 2500 USDC/WETH
+        let v2 = found(1, "QuickSwapV2", PoolReading::V2 {
+            reserve0: U256::from(500_000_000_000u64),
+            reserve1: U256::from(200u64) * U256::exp10(18),
+        }, Some(true));
+        assert!((v2.tvl_usd().unwrap() - 1_000_000.0).abs() < 1e-3);
+        assert!((v2.price().unwrap() - 2_500.0).abs() < 1e-6);
+
+        // V3: L = 2e16 at √P = 5e-5 → 1M USDC virtual → $2M TVL
+        let v3 = found(2, "UniswapV3", v3_at_2500(20_000_000_000_000_000), Some(false));
+        assert!((v3.tvl_usd().unwrap() - 2_000_000.0).abs() < 1.0);
+        assert!((v3.price().unwrap() - 2_500.0).abs() < 1e-6);
+
+        // Quote priced at $0.5 halves the TVL; no quote token → unpriced
+        let half = FoundPool { quote_price_usd: 0.5, ..v3.clone() };
+        assert!((half.tvl_usd().unwrap() - 1_000_000.0).abs() < 1.0);
+        let unpriced = found(3, "UniswapV3", v3_at_2500(1), None);
+        assert_eq!((unpriced.tvl_usd(), unpriced.price()), (None, None));
+        assert_eq!(found(4, "UniswapV3", v3_at_2500(0), Some(false)).tvl_usd(), Some(0.0));
+    }
+
+    #[test]
+    fn test_report_skips_known_and_shallow_pools_and_compares_to_reference() {
+        let wl = whitelist(&format!(
+            r#"{{ "address": "{:?}", "pair": "WETH/USDC", "dex": "UniswapV3", "fee_tier": 500, "status": "active" }}"#,
+            addr(1)
+        ));
+        let pools = vec![
+            found(1, "UniswapV3", v3_at_2500(10_000_000_000_000_000), Some(false)), // covered, $1M: reference
+            // $3M at 2525: deeper than the reference but not covered
+            found(2, "SushiSwapV2", PoolReading::V2 {
+                reserve0: U256::from(600u64) * U256::exp10(18),
+                reserve1: U256::from(1_515_000_000_000u64),
+            }, Some(false)),
+            found(3, "QuickswapV3", v3_at_2500(500_000_000_000_000), Some(false)),   // $50k: too shallow
+            found(9, "UniswapV3", v3_at_2500(50_000_000_000_000_000), Some(false)),  // blacklisted
+            found(5, "UniswapV3", v3_at_2500(50_000_000_000_000_000), None),         // unpriced
+            found(6, "QuickSwapV2", PoolReading::V2 {
+                reserve0: U256::from(100u64) * U256::exp10(18),
+                reserve1: U256::from(250_000_000_000u64),
+            }, Some(false)),                                                          // $500k at 2500
+        ];
+        let report = build_report(&pools, &wl, 100_000.0, "2026-10-16");
+
+        assert_eq!(report.pools_found, 6);
+        let addrs: Vec<&str> = report.candidates.iter().map(|c| c.pool.address.as_str()).collect();
+        assert_eq!(addrs, vec![format!("{:?}", addr(2)), format!("{:?}", addr(6))]);
+        let top = &report.candidates[0];
+        assert_eq!(top.reference_pool, Some(format!("{:?}", addr(1))));
+        assert!((top.price_vs_reference_pct.unwrap() - 1.0).abs() < 1e-6);
+        assert!(report.candidates[1].price_vs_reference_pct.unwrap().abs() < 1e-6);
+
+        // Without a covered pool the deepest pool found is the reference (never a blacklisted one)
+        let report = build_report(&pools, &whitelist(""), 100_000.0, "2026-10-16");
+        assert!(report.candidates.iter().all(|c| c.reference_pool == Some(format!("{:?}", addr(2)))));
+    }
+
+    #[test]
+    fn test_candidate_json_is_a_whitelist_entry() {
+        let pools = vec![found(2, "UniswapV3", v3_at_2500(20_000_000_000_000_000), Some(false))];
+        let report = build_report(&pools, &whitelist(""), 100_000.0, "2026-10-16");
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        let entry = &json["candidates"][0];
+        assert_eq!(entry["status"], "candidate");
+        assert_eq!(entry["dex"], "UniswapV3");
+        assert_eq!(entry["fee_tier"], 500);
+        assert_eq!(entry["token0_decimals"], 18);
+        assert_eq!(entry["token1_decimals"], 6);
+        assert_eq!(entry["added"], "2026-10-16");
+        assert!(entry.get("max_trade_size_usd").is_none());
+        assert!((entry["price_vs_reference_pct"].as_f64().unwrap()).abs() < 1e-9);
+
+        // Pasted into pools_whitelist.json it loads as an inert entry
+        let as_pool: WhitelistPool = serde_json::from_value(entry.clone()).unwrap();
+        assert_eq!(as_pool.status, CANDIDATE_STATUS);
+        assert_eq!(as_pool.address, format!("{:?}", addr(2)));
+        let back: DiscoveryReport = serde_json::from_value(json).unwrap();
+        assert_eq!(back.candidates.len(), 1);
+    }
+}
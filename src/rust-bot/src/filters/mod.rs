@@ -5,7 +5,11 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-29
+//! Modified: 2026-10-16 - Added pool_revenue (Swap volume / fee revenue report for whitelist decisions)
+//! Modified: 2026-10-16 - Added discovery (factory scan for candidate whitelist pools)
 
+pub mod discovery;
+pub mod pool_revenue;
 pub mod whitelist;
 
 pub use whitelist::{PoolWhitelist, WhitelistFilter};
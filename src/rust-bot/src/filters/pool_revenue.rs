@@ -0,0 +1,625 @@
+//! Pool Fee Revenue Estimates — which candidate pools to whitelist
+//!
+//! Purpose:
+//!     Swap volume (and therefore how often the price moves) is the best
+//!     predictor of arb opportunities on a pool. This on-demand analysis samples
+//!     Swap events over a lookback window, estimates each pool's daily volume and
+//!     LP fee revenue, and cross-references the result against the pools we
+//!     already trade on the same pair.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Design:
+//!     - Run with `dexarb-bot --pool-revenue-report [--candidates FILE]`; the
+//!       process writes the report and exits without trading.
+//!     - Refuses to run while a live bot on the same chain is writing its
+//!       heartbeat file (LIVE_BOT_HEARTBEAT_MAX_AGE) unless --force — the
+//!       eth_getLogs sweep competes with the live bot for the RPC rate limit.
+//!     - Sampled pools: covered pools (whitelist status active / v2_ready), every
+//!       other whitelist entry, the observation section, and the candidate file
+//!       (JSON array of {address, pair, dex, fee_tier}).
+//!     - One Multicall3 aggregate3 reads token0()/token1() per pool, a second
+//!       reads decimals() of the quote tokens. Pools without a quote token are
+//!       reported unpriced.
+//!     - Swap logs (V3/Algebra and V2 topics) are fetched in bounded batches of
+//!       block_batch blocks × address_batch pools, paced by `pace` between calls.
+//!       There is no RPC budgeter in this tree; max_log_calls caps the sweep.
+//!     - Volume = |quote-side amount| per swap (V2: quote in + quote out), scaled
+//!       to a day by the sampled blocks' timestamps. Fee revenue = volume × fee
+//!       tier (Algebra fee_tier 0 → nominal ALGEBRA_NOMINAL_FEE).
+//!     - Suggestions: add = uncovered with daily volume ≥ min_add_volume_usd;
+//!       remove = covered with daily volume < max_remove_volume_usd.
+//!     - Output: data/{chain}/research/pool_revenue_YYYYMMDD.json, ranked by
+//!       estimated daily fee revenue.
+
+use super::whitelist::PoolWhitelist;
+use crate::arbitrage::multicall_quoter::MulticallQuoter;
+use crate::arbitrage::preflight::decode_word;
+use crate::types::BotConfig;
+use anyhow::{anyhow, Context, Result};
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Swap(address,address,int256,int256,uint160,uint128,int24) — Uniswap/Sushi V3 and Algebra
+pub const V3_SWAP_SIGNATURE: &str = "Swap(address,address,int256,int256,uint160,uint128,int24)";
+
+/// Swap(address,uint256,uint256,uint256,uint256,address) — Uniswap V2 forks
+pub const V2_SWAP_SIGNATURE: &str = "Swap(address,uint256,uint256,uint256,uint256,address)";
+
+/// Algebra pools report fee_tier 0 (dynamic fee) — nominal rate for revenue estimates
+pub const ALGEBRA_NOMINAL_FEE: f64 = 0.0005;
+
+/// A heartbeat file younger than this means a live bot is running
+pub const LIVE_BOT_HEARTBEAT_MAX_AGE: Duration = Duration::from_secs(120);
+
+/// token0()
+const TOKEN0_SELECTOR: [u8; 4] = [0x0d, 0xfe, 0x16, 0x81];
+
+/// token1()
+const TOKEN1_SELECTOR: [u8; 4] = [0xd2, 0x12, 0x20, 0xa7];
+
+/// decimals()
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+/// Sweep bounds and suggestion thresholds
+#[derive(Debug, Clone)]
+pub struct RevenueConfig {
+    pub lookback_blocks: u64,
+    /// Blocks per eth_getLogs call
+    pub block_batch: u64,
+    /// Pool addresses per eth_getLogs call
+    pub address_batch: usize,
+    /// Delay between eth_getLogs calls
+    pub pace: Duration,
+    /// Refuse a sweep needing more eth_getLogs calls than this
+    pub max_log_calls: usize,
+    pub min_add_volume_usd: f64,
+    pub max_remove_volume_usd: f64,
+}
+
+impl Default for RevenueConfig {
+    fn default() -> Self {
+        Self {
+            lookback_blocks: 43_200, // ~1 day on Polygon (2s blocks)
+            block_batch: 2_000,
+            address_batch: 50,
+            pace: Duration::from_millis(250),
+            max_log_calls: 500,
+            min_add_volume_usd: 50_000.0,
+            max_remove_volume_usd: 5_000.0,
+        }
+    }
+}
+
+/// Pool to sample (candidate file entry, or derived from the whitelist)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidatePool {
+    pub address: String,
+    pub pair: String,
+    #[serde(default)]
+    pub dex: String,
+    #[serde(default)]
+    pub fee_tier: u32,
+    /// Whitelist status active / v2_ready (set from the whitelist, not the file)
+    #[serde(default, skip_deserializing)]
+    pub covered: bool,
+}
+
+impl CandidatePool {
+    pub fn is_v2(&self) -> bool {
+        self.dex.to_lowercase().ends_with("v2")
+    }
+
+    /// LP fee as a fraction of volume
+    pub fn fee_rate(&self) -> f64 {
+        match self.fee_tier {
+            0 if self.is_v2() => 0.003,
+            0 => ALGEBRA_NOMINAL_FEE,
+            tier => tier as f64 / 1_000_000.0,
+        }
+    }
+}
+
+/// Which side of a pool is the quote token, resolved on-chain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolMeta {
+    pub is_v2: bool,
+    pub quote_is_token0: bool,
+    pub quote_decimals: u8,
+    pub quote_price_usd: f64,
+}
+
+/// Swap count and quote-side volume for one pool over the sampled window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SwapVolume {
+    pub swaps: u64,
+    pub quote_volume_raw: U256,
+}
+
+/// One pool's line in the ranked report
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PoolRevenue {
+    pub address: String,
+    pub pair: String,
+    pub dex: String,
+    pub fee_tier: u32,
+    pub covered: bool,
+    pub swaps: u64,
+    /// None = pool has no quote token (volume not priced)
+    pub daily_volume_usd: Option<f64>,
+    pub daily_fee_usd: Option<f64>,
+}
+
+/// Whitelist change suggestion
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Suggestion {
+    /// "add" or "remove"
+    pub action: String,
+    pub address: String,
+    pub pair: String,
+    pub daily_volume_usd: f64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevenueReport {
+    pub generated_at: String,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub span_secs: u64,
+    /// Ranked by estimated daily fee revenue (unpriced pools last)
+    pub pools: Vec<PoolRevenue>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Sampled pool set: every whitelist entry (covered = status active / v2_ready),
+/// the observation section, then `extra` candidates. Deduplicated by address.
+pub fn sample_set(whitelist: &PoolWhitelist, extra: &[CandidatePool]) -> Vec<CandidatePool> {
+    let mut seen = BTreeSet::new();
+    let mut pools = Vec::new();
+    let wl = whitelist.whitelist.pools.iter().map(|p| CandidatePool {
+        address: p.address.clone(),
+        pair: p.pair.clone(),
+        dex: p.dex.clone(),
+        fee_tier: p.fee_tier,
+        covered: p.status == "active" || p.status == "v2_ready",
+    });
+    let observed = whitelist.observation.iter().flat_map(|o| o.pools.iter()).map(|p| CandidatePool {
+        address: p.address.clone(),
+        pair: p.pair.clone(),
+        dex: String::new(),
+        fee_tier: p.fee_tier,
+        covered: false,
+    });
+    for pool in wl.chain(observed).chain(extra.iter().cloned()) {
+        if seen.insert(pool.address.to_lowercase()) {
+            pools.push(pool);
+        }
+    }
+    pools
+}
+
+/// Load a candidate file (JSON array of {address, pair, dex, fee_tier})
+pub fn load_candidates(path: &str) -> Result<Vec<CandidatePool>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("reading candidates {}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("parsing candidates {}", path))
+}
+
+/// True when the live bot's heartbeat file was written within `max_age`
+pub fn live_bot_running(heartbeat_file: &Path, max_age: Duration) -> bool {
+    std::fs::metadata(heartbeat_file)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .is_some_and(|age| age < max_age)
+}
+
+/// |int256| of a two's-complement word
+fn abs_i256(word: &[u8]) -> U256 {
+    let v = U256::from_big_endian(word);
+    if word[0] & 0x80 != 0 {
+        (!v).overflowing_add(U256::one()).0
+    } else {
+        v
+    }
+}
+
+/// Quote-side amount moved by one Swap log, None if undecodable
+pub fn swap_quote_amount(log: &Log, meta: &PoolMeta) -> Option<U256> {
+    let data = &log.data;
+    if meta.is_v2 {
+        // amount0In, amount1In, amount0Out, amount1Out
+        if data.len() < 128 {
+            return None;
+        }
+        let (inn, out) = if meta.quote_is_token0 { (0, 64) } else { (32, 96) };
+        let amount_in = U256::from_big_endian(&data[inn..inn + 32]);
+        let amount_out = U256::from_big_endian(&data[out..out + 32]);
+        Some(amount_in.saturating_add(amount_out))
+    } else {
+        // amount0 (int256), amount1 (int256), price, liquidity, tick
+        if data.len() < 64 {
+            return None;
+        }
+        let off = if meta.quote_is_token0 { 0 } else { 32 };
+        Some(abs_i256(&data[off..off + 32]))
+    }
+}
+
+/// Sum swaps per pool. Logs from unknown pools or with other topics are ignored.
+pub fn aggregate_swaps(logs: &[Log], metas: &HashMap<Address, PoolMeta>) -> HashMap<Address, SwapVolume> {
+    let v3_topic = H256::from(ethers::utils::keccak256(V3_SWAP_SIGNATURE));
+    let v2_topic = H256::from(ethers::utils::keccak256(V2_SWAP_SIGNATURE));
+    let mut volumes: HashMap<Address, SwapVolume> = HashMap::new();
+    for log in logs {
+        let meta = match metas.get(&log.address) {
+            Some(m) => m,
+            None => continue,
+        };
+        let expected = if meta.is_v2 { v2_topic } else { v3_topic };
+        if log.topics.first() != Some(&expected) {
+            continue;
+        }
+        if let Some(amount) = swap_quote_amount(log, meta) {
+            let v = volumes.entry(log.address).or_default();
+            v.swaps += 1;
+            v.quote_volume_raw = v.quote_volume_raw.saturating_add(amount);
+        }
+    }
+    volumes
+}
+
+/// Daily USD volume for a window of `span_secs`
+pub fn daily_volume_usd(volume: &SwapVolume, meta: &PoolMeta, span_secs: u64) -> f64 {
+    if span_secs == 0 {
+        return 0.0;
+    }
+    let human = volume.quote_volume_raw.to_string().parse::<f64>().unwrap_or(0.0)
+        / 10f64.powi(meta.quote_decimals as i32);
+    human * meta.quote_price_usd * 86_400.0 / span_secs as f64
+}
+
+/// Rank pools by daily fee revenue and derive add/remove suggestions
+pub fn build_report(
+    pools: &[CandidatePool],
+    metas: &HashMap<Address, PoolMeta>,
+    volumes: &HashMap<Address, SwapVolume>,
+    (from_block, to_block, span_secs): (u64, u64, u64),
+    config: &RevenueConfig,
+) -> RevenueReport {
+    let mut rows: Vec<PoolRevenue> = pools
+        .iter()
+        .map(|p| {
+            let addr = Address::from_str(&p.address).ok();
+            let meta = addr.and_then(|a| metas.get(&a));
+            let volume = addr.and_then(|a| volumes.get(&a)).cloned().unwrap_or_default();
+            let daily = meta.map(|m| daily_volume_usd(&volume, m, span_secs));
+            PoolRevenue {
+                address: p.address.to_lowercase(),
+                pair: p.pair.clone(),
+                dex: p.dex.clone(),
+                fee_tier: p.fee_tier,
+                covered: p.covered,
+                swaps: volume.swaps,
+                daily_volume_usd: daily,
+                daily_fee_usd: daily.map(|d| d * p.fee_rate()),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        let key = |r: &PoolRevenue| r.daily_fee_usd.unwrap_or(f64::NEG_INFINITY);
+        key(b).partial_cmp(&key(a)).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    // Covered pools per pair, for the cross-reference in each suggestion
+    let mut coverage: BTreeMap<&str, (usize, f64)> = BTreeMap::new();
+    for r in rows.iter().filter(|r| r.covered) {
+        let c = coverage.entry(r.pair.as_str()).or_default();
+        c.0 += 1;
+        c.1 += r.daily_volume_usd.unwrap_or(0.0);
+    }
+
+    let mut suggestions = Vec::new();
+    for r in &rows {
+        let daily = match r.daily_volume_usd {
+            Some(d) => d,
+            None => continue,
+        };
+        let (covered_pools, covered_volume) = coverage.get(r.pair.as_str()).copied().unwrap_or_default();
+        if !r.covered && daily >= config.min_add_volume_usd {
+            let reason = if covered_pools == 0 {
+                format!("${:.0}/day, pair not covered", daily)
+            } else {
+                format!(
+                    "${:.0}/day vs {} covered pool(s) on {} at ${:.0}/day combined",
+                    daily, covered_pools, r.pair, covered_volume
+                )
+            };
+            suggestions.push(Suggestion {
+                action: "add".to_string(),
+                address: r.address.clone(),
+                pair: r.pair.clone(),
+                daily_volume_usd: daily,
+                reason,
+            });
+        } else if r.covered && daily < config.max_remove_volume_usd {
+            suggestions.push(Suggestion {
+                action: "remove".to_string(),
+                address: r.address.clone(),
+                pair: r.pair.clone(),
+                daily_volume_usd: daily,
+                reason: format!(
+                    "${:.0}/day ({} swaps) < ${:.0}/day threshold",
+                    daily, r.swaps, config.max_remove_volume_usd
+                ),
+            });
+        }
+    }
+
+    RevenueReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        from_block,
+        to_block,
+        span_secs,
+        pools: rows,
+        suggestions,
+    }
+}
+
+/// Resolve quote side + decimals for every pool (two aggregate3 calls)
+async fn resolve_metas<M: Middleware + 'static>(
+    quoter: &MulticallQuoter<M>,
+    pools: &[(Address, &CandidatePool)],
+    bot_config: &BotConfig,
+) -> Result<HashMap<Address, PoolMeta>> {
+    let calls: Vec<(Address, Vec<u8>)> = pools
+        .iter()
+        .flat_map(|(a, _)| [(*a, TOKEN0_SELECTOR.to_vec()), (*a, TOKEN1_SELECTOR.to_vec())])
+        .collect();
+    let results = quoter.aggregate3_at(&calls, None).await?;
+    let as_address = |r: &(bool, Vec<u8>)| decode_word(r).map(|w| Address::from(H256::from_uint(&w)));
+
+    let mut quote_of: Vec<(Address, bool, Address)> = Vec::new();
+    for (i, (addr, pool)) in pools.iter().enumerate() {
+        match (as_address(&results[2 * i]), as_address(&results[2 * i + 1])) {
+            (Some(t0), _) if bot_config.is_quote_token(&t0) => quote_of.push((*addr, true, t0)),
+            (_, Some(t1)) if bot_config.is_quote_token(&t1) => quote_of.push((*addr, false, t1)),
+            _ => warn!("Pool revenue: {} ({}) has no readable quote token — unpriced", pool.address, pool.pair),
+        }
+    }
+
+    let quote_tokens: Vec<Address> = quote_of.iter().map(|q| q.2).collect::<BTreeSet<_>>().into_iter().collect();
+    let calls: Vec<(Address, Vec<u8>)> = quote_tokens.iter().map(|t| (*t, DECIMALS_SELECTOR.to_vec())).collect();
+    let decimals: HashMap<Address, u8> = quote_tokens
+        .iter()
+        .zip(quoter.aggregate3_at(&calls, None).await?.iter())
+        .filter_map(|(t, r)| decode_word(r).map(|d| (*t, d.low_u32() as u8)))
+        .collect();
+
+    let v2_by_addr: HashMap<Address, bool> = pools.iter().map(|(a, p)| (*a, p.is_v2())).collect();
+    Ok(quote_of
+        .into_iter()
+        .filter_map(|(addr, quote_is_token0, token)| {
+            decimals.get(&token).map(|d| {
+                (addr, PoolMeta {
+                    is_v2: v2_by_addr[&addr],
+                    quote_is_token0,
+                    quote_decimals: *d,
+                    quote_price_usd: bot_config.quote_price_usd(&token),
+                })
+            })
+        })
+        .collect())
+}
+
+/// Sample Swap logs for `pools` over the lookback window and build the report
+pub async fn run<M: Middleware + 'static>(
+    provider: Arc<M>,
+    bot_config: &BotConfig,
+    pools: &[CandidatePool],
+    config: &RevenueConfig,
+) -> Result<RevenueReport> {
+    let parsed: Vec<(Address, &CandidatePool)> = pools
+        .iter()
+        .filter_map(|p| match Address::from_str(&p.address) {
+            Ok(a) => Some((a, p)),
+            Err(_) => {
+                warn!("Pool revenue: skipping unparseable address '{}'", p.address);
+                None
+            }
+        })
+        .collect();
+    let block_calls = config.lookback_blocks.div_ceil(config.block_batch.max(1)) as usize;
+    let log_calls = block_calls * parsed.len().div_ceil(config.address_batch.max(1));
+    if log_calls > config.max_log_calls {
+        return Err(anyhow!(
+            "sweep needs {} eth_getLogs calls (> {}) — shorten the lookback or the candidate list",
+            log_calls, config.max_log_calls
+        ));
+    }
+
+    let quoter = MulticallQuoter::new(provider.clone(), bot_config)?;
+    let metas = resolve_metas(&quoter, &parsed, bot_config).await?;
+
+    let to_block = provider.get_block_number().await.map_err(|e| anyhow!("eth_blockNumber: {}", e))?.as_u64();
+    let from_block = to_block.saturating_sub(config.lookback_blocks);
+    info!(
+        "Pool revenue: {} pools ({} priced) | blocks {}..{} | {} eth_getLogs calls",
+        parsed.len(), metas.len(), from_block, to_block, log_calls
+    );
+
+    let topics = vec![
+        H256::from(ethers::utils::keccak256(V3_SWAP_SIGNATURE)),
+        H256::from(ethers::utils::keccak256(V2_SWAP_SIGNATURE)),
+    ];
+    let addresses: Vec<Address> = parsed.iter().map(|(a, _)| *a).filter(|a| metas.contains_key(a)).collect();
+    let mut logs = Vec::new();
+    let mut start = from_block;
+    while start <= to_block {
+        let end = (start + config.block_batch - 1).min(to_block);
+        for chunk in addresses.chunks(config.address_batch.max(1)) {
+            let filter = Filter::new()
+                .from_block(start)
+                .to_block(end)
+                .address(chunk.to_vec())
+                .topic0(topics.clone());
+            let batch = provider.get_logs(&filter).await
+                .map_err(|e| anyhow!("eth_getLogs {}..{}: {}", start, end, e))?;
+            logs.extend(batch);
+            tokio::time::sleep(config.pace).await;
+        }
+        start = end + 1;
+    }
+
+    let timestamp = |b: u64| {
+        let provider = provider.clone();
+        async move {
+            provider.get_block(b).await
+                .map_err(|e| anyhow!("eth_getBlockByNumber {}: {}", b, e))?
+                .map(|blk| blk.timestamp.as_u64())
+                .ok_or_else(|| anyhow!("block {} not found", b))
+        }
+    };
+    let span_secs = timestamp(to_block).await?.saturating_sub(timestamp(from_block).await?);
+
+    let volumes = aggregate_swaps(&logs, &metas);
+    Ok(build_report(pools, &metas, &volumes, (from_block, to_block, span_secs), config))
+}
+
+/// Write `{research_dir}/pool_revenue_YYYYMMDD.json`; returns the path
+pub fn write_report(research_dir: &str, report: &RevenueReport) -> Result<PathBuf> {
+    std::fs::create_dir_all(research_dir).with_context(|| format!("creating {}", research_dir))?;
+    let path = PathBuf::from(research_dir)
+        .join(format!("pool_revenue_{}.json", chrono::Utc::now().format("%Y%m%d")));
+    std::fs::write(&path, serde_json::to_string_pretty(report)?)
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn word(v: U256) -> Vec<u8> {
+        let mut buf = [0u8; 32];
+        v.to_big_endian(&mut buf);
+        buf.to_vec()
+    }
+
+    fn neg(v: u64) -> U256 {
+        (!U256::from(v)).overflowing_add(U256::one()).0
+    }
+
+    fn v3_log(pool: Address, amount0: U256, amount1: U256) -> Log {
+        let mut data = word(amount0);
+        data.extend(word(amount1));
+        data.extend(vec![0u8; 96]);
+        Log {
+            address: pool,
+            topics: vec![H256::from(ethers::utils::keccak256(V3_SWAP_SIGNATURE))],
+            data: data.into(),
+            ..Default::default()
+        }
+    }
+
+    fn v2_log(pool: Address, amounts: [u64; 4]) -> Log {
+        Log {
+            address: pool,
+            topics: vec![H256::from(ethers::utils::keccak256(V2_SWAP_SIGNATURE))],
+            data: amounts.iter().flat_map(|a| word(U256::from(*a))).collect::<Vec<_>>().into(),
+            ..Default::default()
+        }
+    }
+
+    fn meta(is_v2: bool, quote_is_token0: bool, quote_decimals: u8) -> PoolMeta {
+        PoolMeta { is_v2, quote_is_token0, quote_decimals, quote_price_usd: 1.0 }
+    }
+
+    fn candidate(n: u64, pair: &str, dex: &str, fee_tier: u32, covered: bool) -> CandidatePool {
+        CandidatePool { address: format!("{:?}", addr(n)), pair: pair.to_string(), dex: dex.to_string(), fee_tier, covered }
+    }
+
+    #[test]
+    fn test_aggregate_v3_and_v2_swaps() {
+        let metas: HashMap<_, _> = [
+            (addr(1), meta(false, true, 6)),   // V3, USDC = token0
+            (addr(2), meta(false, false, 6)),  // V3, USDC = token1
+            (addr(3), meta(true, false, 6)),   // V2, USDC = token1
+        ].into_iter().collect();
+        let logs = vec![
+            // USDC in (+1000) / out (-2500): both count by magnitude
+            v3_log(addr(1), U256::from(1_000_000_000u64), neg(5)),
+            v3_log(addr(1), neg(2_500_000_000), U256::from(7)),
+            v3_log(addr(2), neg(3), U256::from(400_000_000u64)),
+            // V2: amount1In=0, amount1Out=600 USDC (token1)
+            v2_log(addr(3), [10, 0, 0, 600_000_000]),
+            // Wrong topic for a V2 pool, and an unknown pool: ignored
+            v3_log(addr(3), U256::from(1u64), U256::from(1u64)),
+            v3_log(addr(9), U256::from(1u64), U256::from(1u64)),
+        ];
+        let volumes = aggregate_swaps(&logs, &metas);
+        assert_eq!(volumes[&addr(1)], SwapVolume { swaps: 2, quote_volume_raw: U256::from(3_500_000_000u64) });
+        assert_eq!(volumes[&addr(2)].quote_volume_raw, U256::from(400_000_000u64));
+        assert_eq!(volumes[&addr(3)], SwapVolume { swaps: 1, quote_volume_raw: U256::from(600_000_000u64) });
+        assert!(!volumes.contains_key(&addr(9)));
+
+        // 3500 USDC over 12h → $7000/day
+        let daily = daily_volume_usd(&volumes[&addr(1)], &metas[&addr(1)], 43_200);
+        assert!((daily - 7_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ranking_and_suggestions() {
+        let pools = vec![
+            candidate(1, "WETH/USDC", "UniswapV3", 500, true),     // covered, busy
+            candidate(2, "WETH/USDC", "SushiswapV3", 3000, false), // uncovered, busy → add
+            candidate(3, "LINK/USDC", "UniswapV3", 3000, true),    // covered, dead → remove
+            candidate(4, "AAVE/USDC", "QuickSwapV2", 3000, false), // uncovered, quiet
+            candidate(5, "WETH/WMATIC", "UniswapV3", 500, false),  // no quote token
+        ];
+        let metas: HashMap<_, _> = (1..=4).map(|n| (addr(n), meta(n == 4, true, 6))).collect();
+        let usdc = |v: u64| SwapVolume { swaps: 10, quote_volume_raw: U256::from(v) * U256::exp10(6) };
+        let volumes: HashMap<_, _> = [
+            (addr(1), usdc(2_000_000)),
+            (addr(2), usdc(500_000)),
+            (addr(3), usdc(1_000)),
+            (addr(4), usdc(20_000)),
+        ].into_iter().collect();
+        let report = build_report(&pools, &metas, &volumes, (100, 43_300, 86_400), &RevenueConfig::default());
+
+        // Fee revenue: 1 → $1000 (0.05%), 2 → $1500 (0.30%), 4 → $60, 3 → $3; unpriced last
+        let order: Vec<_> = report.pools.iter().map(|r| r.address.clone()).collect();
+        let expected: Vec<_> = [2, 1, 4, 3, 5].iter().map(|n| format!("{:?}", addr(*n))).collect();
+        assert_eq!(order, expected);
+        assert!((report.pools[0].daily_fee_usd.unwrap() - 1_500.0).abs() < 1e-6);
+        assert_eq!(report.pools[4].daily_volume_usd, None);
+
+        let actions: Vec<_> = report.suggestions.iter().map(|s| (s.action.as_str(), s.address.clone())).collect();
+        assert_eq!(actions, vec![("add", format!("{:?}", addr(2))), ("remove", format!("{:?}", addr(3)))]);
+        assert!(report.suggestions[0].reason.contains("1 covered pool(s) on WETH/USDC at $2000000/day"));
+
+        let dir = std::env::temp_dir().join(format!("dexarb_pool_revenue_{}", std::process::id()));
+        let path = write_report(dir.to_str().unwrap(), &report).unwrap();
+        let back: RevenueReport = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(back.pools, report.pools);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_fee_rates() {
+        assert_eq!(candidate(1, "A/B", "UniswapV3", 500, false).fee_rate(), 0.0005);
+        assert_eq!(candidate(1, "A/B", "QuickswapV3", 0, false).fee_rate(), ALGEBRA_NOMINAL_FEE);
+        assert_eq!(candidate(1, "A/B", "QuickSwapV2", 0, false).fee_rate(), 0.003);
+    }
+}
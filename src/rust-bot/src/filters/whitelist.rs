@@ -8,11 +8,17 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-29
-
-use anyhow::{Context, Result};
+//! Modified: 2026-10-16 - canary_until / canary_trades fields on whitelist entries
+//! Modified: 2026-10-16 - history section + migrate_pool() for pool address migrations
+//! Modified: 2026-10-16 - per-pool / per-pair trade limits (max_trade_size_usd, min_profit_usd, max_slippage_percent)
+//! Modified: 2026-10-16 - "dodo_ready" DODO V2 pools ("dex": "DodoV2") count as whitelisted
+//! Modified: 2026-10-17 - EffectiveLimits::global (shared by the detector and mempool dispatch)
+
+use crate::types::BotConfig;
+use anyhow::{anyhow, Context, Result};
 use ethers::types::Address;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use tracing::{debug, info, warn};
 
 // ---------------------------------------------------------------------------
@@ -28,6 +34,9 @@ pub struct PoolWhitelist {
     pub blacklist: BlacklistSection,
     #[serde(default)]
     pub observation: Option<ObservationSection>,
+    /// Address changes applied by the bot (pool migrations), oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<WhitelistHistoryEntry>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -58,6 +67,77 @@ pub struct LiquidityThresholds {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct WhitelistSection {
     pub pools: Vec<WhitelistPool>,
+    /// Pair-wide trade limits (apply to every route of the pair)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pair_limits: Vec<PairLimitsEntry>,
+}
+
+/// Optional trade limit overrides. Each one can only tighten the global
+/// value (MAX_TRADE_SIZE_USD, MIN_PROFIT_USD, MAX_SLIPPAGE_PERCENT).
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub struct TradeLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_trade_size_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_profit_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_slippage_percent: Option<f64>,
+}
+
+impl TradeLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Most restrictive of both: smaller size and slippage, larger min profit
+    pub fn tighten(self, other: TradeLimits) -> Self {
+        fn pick(a: Option<f64>, b: Option<f64>, f: fn(f64, f64) -> f64) -> Option<f64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(f(a, b)),
+                (a, b) => a.or(b),
+            }
+        }
+        Self {
+            max_trade_size_usd: pick(self.max_trade_size_usd, other.max_trade_size_usd, f64::min),
+            min_profit_usd: pick(self.min_profit_usd, other.min_profit_usd, f64::max),
+            max_slippage_percent: pick(self.max_slippage_percent, other.max_slippage_percent, f64::min),
+        }
+    }
+
+    /// These overrides applied to the global limits
+    pub fn effective(&self, global: EffectiveLimits) -> EffectiveLimits {
+        EffectiveLimits {
+            max_trade_size_usd: self.max_trade_size_usd.map_or(global.max_trade_size_usd, |v| v.min(global.max_trade_size_usd)),
+            min_profit_usd: self.min_profit_usd.map_or(global.min_profit_usd, |v| v.max(global.min_profit_usd)),
+            max_slippage_percent: self.max_slippage_percent.map_or(global.max_slippage_percent, |v| v.min(global.max_slippage_percent)),
+        }
+    }
+}
+
+/// Limits a route trades under
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectiveLimits {
+    pub max_trade_size_usd: f64,
+    pub min_profit_usd: f64,
+    pub max_slippage_percent: f64,
+}
+
+impl EffectiveLimits {
+    /// MAX_TRADE_SIZE_USD / MIN_PROFIT_USD / MAX_SLIPPAGE_PERCENT
+    pub fn global(config: &BotConfig) -> Self {
+        Self {
+            max_trade_size_usd: config.max_trade_size_usd,
+            min_profit_usd: config.min_profit_usd,
+            max_slippage_percent: config.max_slippage_percent,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PairLimitsEntry {
+    pub pair: String,
+    #[serde(flatten)]
+    pub limits: TradeLimits,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -75,6 +155,16 @@ pub struct WhitelistPool {
     pub added: Option<String>,
     #[serde(default)]
     pub last_verified: Option<String>,
+    /// Canary window end (RFC3339 timestamp or YYYY-MM-DD). While set and in the
+    /// future, routes through this pool trade at capped size (see arbitrage::canary).
+    #[serde(default)]
+    pub canary_until: Option<String>,
+    /// Canary window length in submitted trades (ends at whichever limit hits first)
+    #[serde(default)]
+    pub canary_trades: Option<u32>,
+    /// Limits on every route through this pool
+    #[serde(flatten)]
+    pub limits: TradeLimits,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -134,6 +224,20 @@ pub struct ObservationPool {
     pub added: String,
 }
 
+/// One applied whitelist change (written by the pool migration detector)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct WhitelistHistoryEntry {
+    pub date: String,
+    pub action: String,
+    pub pair: String,
+    pub fee_tier: u32,
+    pub old_address: String,
+    pub new_address: String,
+    pub block: u64,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
 // ---------------------------------------------------------------------------
 // Precomputed lookup sets (built once at load time)
 // ---------------------------------------------------------------------------
@@ -153,6 +257,10 @@ pub struct WhitelistFilter {
     pool_min_liquidity: std::collections::HashMap<String, u128>,
     /// Per-tier minimum liquidity defaults
     tier_min_liquidity: std::collections::HashMap<u32, u128>,
+    /// Per-pool trade limits (lowercase hex → limits)
+    pool_limits: HashMap<String, TradeLimits>,
+    /// Per-pair trade limits (uppercased symbol → limits)
+    pair_limits: HashMap<String, TradeLimits>,
     /// Default minimum liquidity
     default_min_liquidity: u128,
     /// "strict" or "advisory"
@@ -180,12 +288,13 @@ impl WhitelistFilter {
 
     /// Build from an already-parsed config.
     pub fn from_config(raw: PoolWhitelist) -> Self {
-        // Whitelisted addresses (both "active" V3 pools and "v2_ready" V2 pools)
+        // Whitelisted addresses ("active" V3 pools, "v2_ready" V2 pools and
+        // "dodo_ready" DODO V2 pools)
         let whitelisted_addrs: HashSet<String> = raw
             .whitelist
             .pools
             .iter()
-            .filter(|p| p.status == "active" || p.status == "v2_ready")
+            .filter(|p| matches!(p.status.as_str(), "active" | "v2_ready" | "dodo_ready"))
             .map(|p| normalize_addr(&p.address))
             .collect();
 
@@ -237,6 +346,18 @@ impl WhitelistFilter {
             }
         }
 
+        // Trade limits; duplicate entries combine to the most restrictive
+        let mut pool_limits: HashMap<String, TradeLimits> = HashMap::new();
+        for p in raw.whitelist.pools.iter().filter(|p| !p.limits.is_empty()) {
+            let entry = pool_limits.entry(normalize_addr(&p.address)).or_default();
+            *entry = entry.tighten(p.limits);
+        }
+        let mut pair_limits: HashMap<String, TradeLimits> = HashMap::new();
+        for p in raw.whitelist.pair_limits.iter() {
+            let entry = pair_limits.entry(p.pair.to_uppercase()).or_default();
+            *entry = entry.tighten(p.limits);
+        }
+
         let default_min_liquidity = raw.config.default_min_liquidity;
         let enforcement = raw.config.whitelist_enforcement.clone();
 
@@ -255,6 +376,8 @@ impl WhitelistFilter {
             blacklisted_pairs,
             pool_min_liquidity,
             tier_min_liquidity,
+            pool_limits,
+            pair_limits,
             default_min_liquidity,
             enforcement,
             raw,
@@ -318,6 +441,19 @@ impl WhitelistFilter {
         self.default_min_liquidity
     }
 
+    /// Trade limit overrides of a route: its pairs' and pools' entries,
+    /// most restrictive first (empty = the global limits apply)
+    pub fn limits_for(&self, pairs: &[&str], pools: &[Address]) -> TradeLimits {
+        let pair_limits = pairs.iter().filter_map(|pair| self.pair_limits.get(&pair.to_uppercase()));
+        let pool_limits = pools.iter().filter_map(|pool| self.pool_limits.get(&format!("{:?}", pool).to_lowercase()));
+        pair_limits.chain(pool_limits).fold(TradeLimits::default(), |acc, limits| acc.tighten(*limits))
+    }
+
+    /// Pools with trade limit overrides of their own
+    pub fn pool_limit_count(&self) -> usize {
+        self.pool_limits.len()
+    }
+
     /// Number of active whitelisted pools.
     pub fn active_pool_count(&self) -> usize {
         self.whitelisted_addrs.len()
@@ -327,6 +463,97 @@ impl WhitelistFilter {
     pub fn is_strict(&self) -> bool {
         self.enforcement == "strict"
     }
+
+    // ------------------------------------------------------------------
+    // Mutation
+    // ------------------------------------------------------------------
+
+    /// Move a whitelisted pool to a new address (factory redeploy / migration).
+    /// The entry keeps its pair, dex, tier, status and per-pool overrides; the
+    /// change is appended to `raw.history`. Errors if `old` is not whitelisted.
+    pub fn migrate_pool(
+        &mut self,
+        old: &Address,
+        new: &Address,
+        block: u64,
+        reason: &str,
+    ) -> Result<WhitelistHistoryEntry> {
+        let old_addr = format!("{:?}", old).to_lowercase();
+        let new_addr = format!("{:?}", new).to_lowercase();
+
+        let entry = self
+            .raw
+            .whitelist
+            .pools
+            .iter_mut()
+            .find(|p| normalize_addr(&p.address) == old_addr)
+            .ok_or_else(|| anyhow!("pool {} not found in whitelist", old_addr))?;
+        entry.address = new_addr.clone();
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        entry.last_verified = Some(today.clone());
+
+        let history = WhitelistHistoryEntry {
+            date: today,
+            action: "migrate".to_string(),
+            pair: entry.pair.clone(),
+            fee_tier: entry.fee_tier,
+            old_address: old_addr.clone(),
+            new_address: new_addr.clone(),
+            block,
+            reason: Some(reason.to_string()),
+        };
+
+        if self.whitelisted_addrs.remove(&old_addr) {
+            self.whitelisted_addrs.insert(new_addr.clone());
+        }
+        if let Some(liq) = self.pool_min_liquidity.remove(&old_addr) {
+            self.pool_min_liquidity.insert(new_addr.clone(), liq);
+        }
+        if let Some(limits) = self.pool_limits.remove(&old_addr) {
+            self.pool_limits.insert(new_addr, limits);
+        }
+        self.raw.history.push(history.clone());
+        Ok(history)
+    }
+}
+
+/// Apply a migration to the whitelist file: rewrite the entry's address and
+/// append to the top-level "history" array. Edits the raw JSON so fields the
+/// Rust structs do not model survive (same approach as the canary rewrite).
+pub fn persist_migration(path: &str, change: &WhitelistHistoryEntry) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read whitelist file: {}", path))?;
+    let mut json: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse whitelist JSON: {}", path))?;
+
+    let entry = json
+        .pointer_mut("/whitelist/pools")
+        .and_then(|v| v.as_array_mut())
+        .ok_or_else(|| anyhow!("whitelist.pools missing"))?
+        .iter_mut()
+        .find(|p| {
+            p.get("address").and_then(|a| a.as_str()).map(normalize_addr).as_deref()
+                == Some(change.old_address.as_str())
+        })
+        .and_then(|p| p.as_object_mut())
+        .ok_or_else(|| anyhow!("pool {} not found in whitelist", change.old_address))?;
+    entry.insert("address".to_string(), serde_json::Value::String(change.new_address.clone()));
+    entry.insert("last_verified".to_string(), serde_json::Value::String(change.date.clone()));
+
+    let root = json
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("whitelist root is not an object"))?;
+    let history = root
+        .entry("history")
+        .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+    history
+        .as_array_mut()
+        .ok_or_else(|| anyhow!("whitelist.history is not an array"))?
+        .push(serde_json::to_value(change)?);
+
+    std::fs::write(path, serde_json::to_string_pretty(&json)?)
+        .with_context(|| format!("Failed to write whitelist file: {}", path))?;
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
@@ -344,7 +571,7 @@ impl Default for WhitelistFilter {
                 whitelist_enforcement: "advisory".to_string(),
                 liquidity_thresholds: None,
             },
-            whitelist: WhitelistSection { pools: Vec::new() },
+            whitelist: WhitelistSection { pools: Vec::new(), pair_limits: Vec::new() },
             blacklist: BlacklistSection {
                 pools: Vec::new(),
                 fee_tiers: vec![BlacklistTier {
@@ -357,6 +584,7 @@ impl Default for WhitelistFilter {
                 pairs: Vec::new(),
             },
             observation: None,
+            history: Vec::new(),
         };
         Self::from_config(raw)
     }
@@ -472,4 +700,84 @@ mod tests {
         let addr = Address::from_str("0x0000000000000000000000000000000000000001").unwrap();
         assert_eq!(f.min_liquidity_for(&addr, 500), 1_000_000_000);
     }
+
+    #[test]
+    fn test_migrate_pool_moves_entry_and_records_history() {
+        let mut f = test_filter();
+        let old = Address::from_str("0x45dda9cb7c25131df268515131f647d726f50608").unwrap();
+        let new = Address::from_str("0x00000000000000000000000000000000000000aa").unwrap();
+
+        let change = f.migrate_pool(&old, &new, 1_000, "liquidity collapsed").unwrap();
+        assert_eq!(change.old_address, "0x45dda9cb7c25131df268515131f647d726f50608");
+        assert_eq!(change.new_address, "0x00000000000000000000000000000000000000aa");
+
+        assert!(!f.is_pool_allowed(&old, 500, "WETH/USDC"));
+        assert!(f.is_pool_allowed(&new, 500, "WETH/USDC"));
+        assert_eq!(f.min_liquidity_for(&new, 500), 5_000_000_000);
+        assert_eq!(f.raw.history, vec![change]);
+        assert!(f.migrate_pool(&old, &new, 1_001, "again").is_err());
+    }
+
+    #[test]
+    fn test_trade_limits_absent_in_legacy_file() {
+        let f = test_filter();
+        let pool = Address::from_str("0x45dda9cb7c25131df268515131f647d726f50608").unwrap();
+        assert!(f.limits_for(&["WETH/USDC"], &[pool]).is_empty());
+        assert_eq!(f.pool_limit_count(), 0);
+    }
+
+    #[test]
+    fn test_trade_limits_most_restrictive_wins() {
+        let json = r#"{
+            "version": "1.0",
+            "last_updated": "2026-10-16T00:00:00Z",
+            "config": { "default_min_liquidity": 1000, "whitelist_enforcement": "strict" },
+            "whitelist": {
+                "pools": [
+                    {
+                        "address": "0x45dda9cb7c25131df268515131f647d726f50608",
+                        "pair": "WETH/USDC",
+                        "dex": "UniswapV3",
+                        "fee_tier": 500,
+                        "status": "active",
+                        "max_trade_size_usd": 200.0,
+                        "max_slippage_percent": 0.3
+                    }
+                ],
+                "pair_limits": [
+                    { "pair": "weth/usdc", "max_trade_size_usd": 300.0, "min_profit_usd": 2.0 }
+                ]
+            },
+            "blacklist": { "pools": [], "fee_tiers": [], "pairs": [] }
+        }"#;
+        let raw: PoolWhitelist = serde_json::from_str(json).unwrap();
+        let f = WhitelistFilter::from_config(raw.clone());
+        let pool = Address::from_str("0x45dda9cb7c25131df268515131f647d726f50608").unwrap();
+        let global = EffectiveLimits { max_trade_size_usd: 500.0, min_profit_usd: 1.0, max_slippage_percent: 0.5 };
+
+        // Pair only
+        let pair = f.limits_for(&["WETH/USDC"], &[]);
+        assert_eq!(pair.max_trade_size_usd, Some(300.0));
+        assert_eq!(pair.max_slippage_percent, None);
+        assert_eq!(
+            pair.effective(global),
+            EffectiveLimits { max_trade_size_usd: 300.0, min_profit_usd: 2.0, max_slippage_percent: 0.5 }
+        );
+        // Pool tightens the pair further
+        let route = f.limits_for(&["WETH/USDC"], &[pool]);
+        assert_eq!(
+            route.effective(global),
+            EffectiveLimits { max_trade_size_usd: 200.0, min_profit_usd: 2.0, max_slippage_percent: 0.3 }
+        );
+        // An override looser than global never loosens it
+        let strict = EffectiveLimits { max_trade_size_usd: 100.0, min_profit_usd: 5.0, max_slippage_percent: 0.1 };
+        assert_eq!(route.effective(strict), strict);
+        assert_eq!(f.pool_limit_count(), 1);
+
+        // Round-trips through the file format
+        let saved = serde_json::to_string(&raw).unwrap();
+        assert!(saved.contains("\"pair_limits\""));
+        let reloaded = WhitelistFilter::from_config(serde_json::from_str(&saved).unwrap());
+        assert_eq!(reloaded.limits_for(&["WETH/USDC"], &[pool]), route);
+    }
 }
@@ -0,0 +1,451 @@
+//! Historical Gas Price Logger
+//!
+//! Records per-block gas conditions next to our own bids for post-hoc gas
+//! bidding analysis. One row per block (plus one extra row per additional
+//! submission in that block), appended to data/{chain}/research/gas_history.csv.
+//!
+//! Per block:
+//!   - base fee (from the block header) and our bidding parameters at that block
+//!   - priority fee distribution of the block (p10/p25/p50/p75/p90), from
+//!     eth_feeHistory reward percentiles. One feeHistory call covers the last
+//!     GAS_HISTORY_SAMPLE_BLOCKS blocks, so RPC cost is 1 call per N blocks.
+//!   - our submission(s) made while that block was the head (left join):
+//!     priority fee, whether the tx landed, landing block and tx index
+//!
+//! Rows are buffered until the feeHistory call that covers them, then handed
+//! to the gas_log writer thread (io_writer.rs, lossy).
+//!
+//! Output format (CSV):
+//!   timestamp, block, base_fee_gwei, gas_used_ratio, p10..p90_gwei,
+//!   bid_atomic_priority_gwei, bid_mempool_min_priority_gwei, bid_mempool_gas_profit_cap,
+//!   our_tx_hash, our_path, our_priority_gwei, our_status, our_landed, our_landed_block, our_tx_index
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - CSV appends moved to an isolated writer thread (lossy stream)
+
+use crate::io_writer::{IsolatedWriter, StreamContract};
+use crate::types::BotConfig;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use ethers::types::{FeeHistory, TransactionReceipt, TxHash, U256, U64};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Reward percentiles requested from eth_feeHistory
+pub const FEE_HISTORY_PERCENTILES: [f64; 5] = [10.0, 25.0, 50.0, 75.0, 90.0];
+
+/// Most nodes cap eth_feeHistory blockCount at 1024
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
+/// CSV header for gas_history.csv
+const CSV_HEADER: &str = "timestamp,block,base_fee_gwei,gas_used_ratio,p10_gwei,p25_gwei,p50_gwei,p75_gwei,p90_gwei,\
+bid_atomic_priority_gwei,bid_mempool_min_priority_gwei,bid_mempool_gas_profit_cap,\
+our_tx_hash,our_path,our_priority_gwei,our_status,our_landed,our_landed_block,our_tx_index";
+
+/// Our gas bidding parameters in force at a block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BidParams {
+    pub atomic_priority_gwei: u64,
+    pub mempool_min_priority_gwei: u64,
+    pub mempool_gas_profit_cap: f64,
+}
+
+impl BidParams {
+    pub fn from_config(config: &BotConfig, atomic_priority_gwei: u64) -> Self {
+        Self {
+            atomic_priority_gwei,
+            mempool_min_priority_gwei: config.mempool_min_priority_gwei,
+            mempool_gas_profit_cap: config.mempool_gas_profit_cap,
+        }
+    }
+}
+
+/// Per-block fee data parsed from an eth_feeHistory response
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockFees {
+    pub block: u64,
+    pub base_fee: U256,
+    pub gas_used_ratio: f64,
+    /// Effective priority fees at FEE_HISTORY_PERCENTILES
+    pub rewards: Vec<U256>,
+}
+
+/// One of our transactions, as bid and as it landed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmissionRecord {
+    /// Head block when the tx was sent
+    pub submitted_block: u64,
+    pub tx_hash: Option<TxHash>,
+    /// "atomic" or "mempool"
+    pub path: &'static str,
+    pub priority_fee: U256,
+    /// success | reverted | pending (receipt timeout) | send_failed
+    pub status: &'static str,
+    pub landed_block: Option<u64>,
+    pub tx_index: Option<u64>,
+}
+
+impl SubmissionRecord {
+    /// Build from the outcome of a send: no receipt = not (yet) landed
+    pub fn new(
+        submitted_block: u64,
+        tx_hash: Option<TxHash>,
+        path: &'static str,
+        priority_fee: U256,
+        receipt: Option<&TransactionReceipt>,
+    ) -> Self {
+        let status = match (tx_hash, receipt) {
+            (None, _) => "send_failed",
+            (Some(_), None) => "pending",
+            (Some(_), Some(r)) if r.status == Some(U64::from(1)) => "success",
+            (Some(_), Some(_)) => "reverted",
+        };
+        Self {
+            submitted_block,
+            tx_hash,
+            path,
+            priority_fee,
+            status,
+            landed_block: receipt.and_then(|r| r.block_number).map(|b| b.as_u64()),
+            tx_index: receipt.map(|r| r.transaction_index.as_u64()),
+        }
+    }
+
+    pub fn landed(&self) -> bool {
+        self.landed_block.is_some()
+    }
+}
+
+/// Split an eth_feeHistory response into per-block rows.
+///
+/// base_fee_per_gas has blockCount + 1 entries (the last is the *next* block's
+/// base fee); gas_used_ratio and reward have blockCount entries.
+pub fn parse_fee_history(history: &FeeHistory) -> Vec<BlockFees> {
+    let oldest = history.oldest_block.as_u64();
+    history
+        .gas_used_ratio
+        .iter()
+        .enumerate()
+        .map(|(i, ratio)| BlockFees {
+            block: oldest + i as u64,
+            base_fee: history.base_fee_per_gas.get(i).copied().unwrap_or_default(),
+            gas_used_ratio: *ratio,
+            rewards: history.reward.get(i).cloned().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// A block awaiting its feeHistory data
+#[derive(Debug, Clone)]
+struct PendingBlock {
+    timestamp: String,
+    base_fee: U256,
+    bid: BidParams,
+    submissions: Vec<SubmissionRecord>,
+}
+
+/// Per-block gas recorder — buffers blocks, joins submissions, writes on each feeHistory sample.
+pub struct GasHistoryLogger {
+    writer: IsolatedWriter<Vec<String>>,
+    /// Blocks covered by one feeHistory call (= RPC sampling rate)
+    sample_blocks: u64,
+    pending: BTreeMap<u64, PendingBlock>,
+    last_sample_block: Option<u64>,
+}
+
+impl GasHistoryLogger {
+    /// Create a logger writing to `{data_dir}/gas_history.csv`.
+    pub fn new(data_dir: &str, sample_blocks: u64, contract: StreamContract) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = fs::create_dir_all(&dir) {
+            warn!("Failed to create gas history directory {}: {}", data_dir, e);
+        }
+        let sample_blocks = sample_blocks.clamp(1, MAX_FEE_HISTORY_BLOCKS);
+        info!("GasHistoryLogger initialized: {}/gas_history.csv (feeHistory every {} blocks)", data_dir, sample_blocks);
+        let path = dir.join("gas_history.csv");
+        Self {
+            writer: IsolatedWriter::spawn(contract, move |lines: &mut Vec<String>| append(&path, lines)),
+            sample_blocks,
+            pending: BTreeMap::new(),
+            last_sample_block: None,
+        }
+    }
+
+    /// Record the head block's base fee and our bidding parameters
+    pub fn record_block(&mut self, block: u64, base_fee: U256, bid: BidParams) {
+        // First block starts the first sampling window
+        self.last_sample_block.get_or_insert(block.saturating_sub(1));
+        self.pending.entry(block).or_insert_with(|| PendingBlock {
+            timestamp: Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+            base_fee,
+            bid,
+            submissions: Vec::new(),
+        });
+    }
+
+    /// Attach one of our submissions to the block it was sent in
+    pub fn record_submission(&mut self, submission: SubmissionRecord) {
+        match self.pending.get_mut(&submission.submitted_block) {
+            Some(b) => b.submissions.push(submission),
+            None => warn!(
+                "GasHistoryLogger: submission at unrecorded block {} dropped",
+                submission.submitted_block
+            ),
+        }
+    }
+
+    /// True when a feeHistory call should be made covering blocks up to `block`
+    pub fn sample_due(&self, block: u64) -> bool {
+        self.last_sample_block
+            .is_some_and(|last| block >= last + self.sample_blocks)
+    }
+
+    /// blockCount for the feeHistory call at `block`: everything since the last
+    /// sample, capped at the node limit
+    pub fn sample_block_count(&self, block: u64) -> u64 {
+        match self.last_sample_block {
+            Some(last) => (block - last).clamp(1, MAX_FEE_HISTORY_BLOCKS),
+            None => self.sample_blocks,
+        }
+    }
+
+    /// Join a feeHistory response (None if the call failed) into the buffered
+    /// blocks up to `block` and append them to the CSV.
+    pub fn flush_sample(&mut self, block: u64, history: Option<&FeeHistory>) {
+        self.last_sample_block = Some(block);
+        let fees: BTreeMap<u64, BlockFees> = history
+            .map(parse_fee_history)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|f| (f.block, f))
+            .collect();
+
+        let later = self.pending.split_off(&(block + 1));
+        let ready = std::mem::replace(&mut self.pending, later);
+        let mut lines = Vec::new();
+        for (number, pending) in &ready {
+            lines.extend(format_rows(*number, pending, fees.get(number)));
+        }
+        self.enqueue(lines);
+    }
+
+    fn enqueue(&self, lines: Vec<String>) {
+        if lines.is_empty() {
+            return;
+        }
+        if let Err(e) = self.writer.send(lines) {
+            warn!("GasHistoryLogger: rows not queued: {}", e);
+        }
+    }
+
+    /// Writer backpressure for the status line (None while healthy and idle)
+    pub fn status_line(&self) -> Option<String> {
+        self.writer.status_line()
+    }
+
+    /// Wait for queued rows to reach the disk (shutdown, tests)
+    pub fn flush(&self, timeout: Duration) -> bool {
+        self.writer.flush(timeout)
+    }
+}
+
+/// Writer thread: append rows, header first on a new file
+fn append(path: &Path, lines: &[String]) -> Result<()> {
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("GasHistoryLogger: failed to open {}", path.display()))?;
+    if is_new {
+        writeln!(file, "{}", CSV_HEADER)?;
+    }
+    for line in lines {
+        writeln!(file, "{}", line).context("GasHistoryLogger write")?;
+    }
+    Ok(())
+}
+
+fn gwei(wei: U256) -> String {
+    format!("{:.3}", wei.as_u128() as f64 / 1e9)
+}
+
+/// One row per submission in the block, or one row with empty our_* columns
+fn format_rows(block: u64, pending: &PendingBlock, fees: Option<&BlockFees>) -> Vec<String> {
+    // Header base fee is authoritative; feeHistory only fills it if missing
+    let base_fee = if pending.base_fee.is_zero() {
+        fees.map(|f| f.base_fee).unwrap_or_default()
+    } else {
+        pending.base_fee
+    };
+    let ratio = fees.map(|f| format!("{:.4}", f.gas_used_ratio)).unwrap_or_default();
+    let percentiles: Vec<String> = (0..FEE_HISTORY_PERCENTILES.len())
+        .map(|i| fees.and_then(|f| f.rewards.get(i)).map(|r| gwei(*r)).unwrap_or_default())
+        .collect();
+    let prefix = format!(
+        "{},{},{},{},{},{},{},{:.2}",
+        pending.timestamp,
+        block,
+        gwei(base_fee),
+        ratio,
+        percentiles.join(","),
+        pending.bid.atomic_priority_gwei,
+        pending.bid.mempool_min_priority_gwei,
+        pending.bid.mempool_gas_profit_cap,
+    );
+
+    if pending.submissions.is_empty() {
+        return vec![format!("{},,,,,,,", prefix)];
+    }
+    pending
+        .submissions
+        .iter()
+        .map(|s| {
+            format!(
+                "{},{},{},{},{},{},{},{}",
+                prefix,
+                s.tx_hash.map(|h| format!("{:?}", h)).unwrap_or_default(),
+                s.path,
+                gwei(s.priority_fee),
+                s.status,
+                s.landed(),
+                s.landed_block.map(|b| b.to_string()).unwrap_or_default(),
+                s.tx_index.map(|i| i.to_string()).unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn bid() -> BidParams {
+        BidParams { atomic_priority_gwei: 5000, mempool_min_priority_gwei: 1000, mempool_gas_profit_cap: 0.5 }
+    }
+
+    /// feeHistory for blocks 100..=102 (3 blocks, 4 base fees)
+    fn history() -> FeeHistory {
+        let g = |v: u64| U256::from(v * GWEI);
+        FeeHistory {
+            base_fee_per_gas: vec![g(30), g(31), g(32), g(33)],
+            gas_used_ratio: vec![0.5, 0.75, 0.25],
+            oldest_block: U256::from(100),
+            reward: vec![
+                vec![g(30), g(100), g(500), g(2000), g(5000)],
+                vec![g(31), g(101), g(501), g(2001), g(5001)],
+                vec![g(32), g(102), g(502), g(2002), g(5002)],
+            ],
+        }
+    }
+
+    fn receipt(block: u64, index: u64, success: bool) -> TransactionReceipt {
+        TransactionReceipt {
+            block_number: Some(U64::from(block)),
+            transaction_index: U64::from(index),
+            status: Some(U64::from(success as u64)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_fee_history() {
+        let blocks = parse_fee_history(&history());
+        assert_eq!(blocks.len(), 3); // trailing next-block base fee is not a row
+        assert_eq!(blocks[0].block, 100);
+        assert_eq!(blocks[2].block, 102);
+        assert_eq!(blocks[1].base_fee, U256::from(31 * GWEI));
+        assert_eq!(blocks[1].gas_used_ratio, 0.75);
+        assert_eq!(blocks[1].rewards[2], U256::from(501 * GWEI)); // p50
+
+        // Node returned no rewards (empty percentiles) → empty reward vectors
+        let mut bare = history();
+        bare.reward.clear();
+        assert!(parse_fee_history(&bare).iter().all(|b| b.rewards.is_empty()));
+    }
+
+    #[test]
+    fn test_submission_status() {
+        let hash = Some(TxHash::repeat_byte(1));
+        let fee = U256::from(5000 * GWEI);
+        let landed = SubmissionRecord::new(101, hash, "atomic", fee, Some(&receipt(102, 7, true)));
+        assert_eq!(landed.status, "success");
+        assert!(landed.landed());
+        assert_eq!((landed.landed_block, landed.tx_index), (Some(102), Some(7)));
+
+        assert_eq!(SubmissionRecord::new(101, hash, "atomic", fee, Some(&receipt(102, 0, false))).status, "reverted");
+        let pending = SubmissionRecord::new(101, hash, "atomic", fee, None);
+        assert_eq!(pending.status, "pending");
+        assert!(!pending.landed());
+        assert_eq!(SubmissionRecord::new(101, None, "mempool", fee, None).status, "send_failed");
+    }
+
+    #[test]
+    fn test_submission_join() {
+        let dir = std::env::temp_dir().join(format!("gas_history_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let contract = StreamContract::lossy("gas_log", 4, Duration::from_secs(1));
+        let mut logger = GasHistoryLogger::new(dir.to_str().unwrap(), 3, contract);
+
+        for block in 100..=103 {
+            logger.record_block(block, U256::from((30 + block - 100) * GWEI), bid());
+        }
+        // Two submissions while 101 was head, one landed at index 7 of block 102
+        logger.record_submission(SubmissionRecord::new(
+            101, Some(TxHash::repeat_byte(1)), "atomic", U256::from(5000 * GWEI), Some(&receipt(102, 7, true)),
+        ));
+        logger.record_submission(SubmissionRecord::new(101, None, "mempool", U256::from(1200 * GWEI), None));
+        // Unknown block: dropped, not attached elsewhere
+        logger.record_submission(SubmissionRecord::new(99, None, "atomic", U256::zero(), None));
+
+        assert!(!logger.sample_due(101));
+        assert!(logger.sample_due(102));
+        assert_eq!(logger.sample_block_count(102), 3);
+        logger.flush_sample(102, Some(&history()));
+
+        // Block 103 stays buffered for the next sample
+        assert_eq!(logger.pending.keys().copied().collect::<Vec<_>>(), vec![103]);
+        assert!(!logger.sample_due(104));
+        assert!(logger.sample_due(105));
+        assert_eq!(logger.sample_block_count(105), 3);
+
+        assert!(logger.flush(Duration::from_secs(5)));
+        let csv = fs::read_to_string(dir.join("gas_history.csv")).unwrap();
+        let rows: Vec<Vec<&str>> = csv.lines().skip(1).map(|l| l.split(',').collect()).collect();
+        let header_cols = CSV_HEADER.split(',').count();
+        assert!(rows.iter().all(|r| r.len() == header_cols));
+        assert_eq!(rows.len(), 4); // 100, 101 ×2, 102
+
+        let blocks: Vec<&str> = rows.iter().map(|r| r[1]).collect();
+        assert_eq!(blocks, vec!["100", "101", "101", "102"]);
+
+        // Block 100: base fee from header, p50 from feeHistory, no submission
+        assert_eq!(rows[0][2], "30.000");
+        assert_eq!(rows[0][6], "500.000");
+        assert_eq!(rows[0][9], "5000");
+        assert_eq!(rows[0][12], "");
+
+        // Block 101: landed atomic tx — priority, status, landing block and index
+        assert_eq!(rows[1][13], "atomic");
+        assert_eq!(rows[1][14], "5000.000");
+        assert_eq!(&rows[1][15..], &["success", "true", "102", "7"]);
+        // Failed mempool send — bid recorded, never landed
+        assert_eq!(&rows[2][13..], &["mempool", "1200.000", "send_failed", "false", "", ""]);
+
+        // feeHistory failure still writes base fees, with empty percentiles
+        logger.flush_sample(105, None);
+        assert!(logger.flush(Duration::from_secs(5)));
+        let csv = fs::read_to_string(dir.join("gas_history.csv")).unwrap();
+        let last: Vec<&str> = csv.lines().last().unwrap().split(',').collect();
+        assert_eq!((last[1], last[2], last[6]), ("103", "33.000", ""));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,240 @@
+//! Gas Price Oracle — percentile-based EIP-1559 priority fees
+//!
+//! Replaces the fixed ATOMIC_PRIORITY_FEE_GWEI bid on the atomic (block-
+//! triggered) path. Keeps the effective priority fee distribution of the last
+//! GAS_ORACLE_WINDOW_BLOCKS blocks from eth_feeHistory (one call every
+//! GAS_ORACLE_SAMPLE_BLOCKS blocks, shared with the gas history logger when
+//! both are due).
+//!
+//! priority_fee_for_percentile(p): each block's reward at p, linearly
+//! interpolated between the FEE_HISTORY_PERCENTILES columns, then the median
+//! across the window — one spiky block doesn't move the bid.
+//!
+//! Atomic bid = estimate × (1 + GAS_ORACLE_BUMP_PERCENT), clamped to
+//! [MIN_PRIORITY_GWEI, MAX_PRIORITY_GWEI], then capped by the same profit
+//! budget as the mempool path. With no fee data (feeHistory failing, window
+//! aged out, oracle disabled) the static ATOMIC_PRIORITY_FEE_GWEI is bid,
+//! clamped and capped the same way.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::gas_logger::{parse_fee_history, FEE_HISTORY_PERCENTILES};
+use crate::types::BotConfig;
+use ethers::types::{FeeHistory, U256};
+use std::collections::BTreeMap;
+
+const GWEI: u64 = 1_000_000_000;
+
+/// Rolling window of per-block priority fee percentiles
+pub struct GasOracle {
+    /// block → effective priority fees at FEE_HISTORY_PERCENTILES
+    window: BTreeMap<u64, Vec<U256>>,
+    window_blocks: u64,
+    sample_blocks: u64,
+    last_sample_block: Option<u64>,
+}
+
+impl GasOracle {
+    pub fn new(window_blocks: u64, sample_blocks: u64) -> Self {
+        Self {
+            window: BTreeMap::new(),
+            window_blocks: window_blocks.max(1),
+            sample_blocks: sample_blocks.max(1),
+            last_sample_block: None,
+        }
+    }
+
+    pub fn from_config(config: &BotConfig) -> Self {
+        Self::new(config.gas_oracle_window_blocks, config.gas_oracle_sample_blocks)
+    }
+
+    /// True when a feeHistory call should be made ending at `block`
+    /// (immediately on the first block)
+    pub fn sample_due(&self, block: u64) -> bool {
+        self.last_sample_block
+            .is_none_or(|last| block >= last + self.sample_blocks)
+    }
+
+    /// blockCount the oracle wants from the feeHistory call
+    pub fn sample_block_count(&self) -> u64 {
+        self.window_blocks
+    }
+
+    /// Merge a feeHistory response ending at `block` (None = the call failed)
+    /// and drop blocks that fell out of the window. Repeated failures age the
+    /// window out, so the bid falls back to the static fee rather than going stale.
+    pub fn record(&mut self, block: u64, history: Option<&FeeHistory>) {
+        self.last_sample_block = Some(block);
+        for fees in history.map(parse_fee_history).unwrap_or_default() {
+            if fees.rewards.len() == FEE_HISTORY_PERCENTILES.len() {
+                self.window.insert(fees.block, fees.rewards);
+            }
+        }
+        let oldest = (block + 1).saturating_sub(self.window_blocks);
+        self.window = self.window.split_off(&oldest);
+    }
+
+    /// Blocks with fee data in the window
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    /// Priority fee (wei) at percentile `p` (0–100) of recent blocks; None
+    /// without fee data
+    pub fn priority_fee_for_percentile(&self, p: f64) -> Option<U256> {
+        let mut per_block: Vec<U256> = self
+            .window
+            .values()
+            .filter_map(|rewards| interpolate(rewards, &FEE_HISTORY_PERCENTILES, p))
+            .collect();
+        if per_block.is_empty() {
+            return None;
+        }
+        per_block.sort();
+        let mid = per_block.len() / 2;
+        Some(if per_block.len().is_multiple_of(2) {
+            (per_block[mid - 1] + per_block[mid]) / 2
+        } else {
+            per_block[mid]
+        })
+    }
+}
+
+/// Reward at percentile `p` from rewards sampled at ascending `percentiles`:
+/// linear between the bracketing columns, clamped to the first/last column.
+pub fn interpolate(rewards: &[U256], percentiles: &[f64], p: f64) -> Option<U256> {
+    if rewards.is_empty() || rewards.len() != percentiles.len() {
+        return None;
+    }
+    let last = rewards.len() - 1;
+    if p <= percentiles[0] {
+        return Some(rewards[0]);
+    }
+    if p >= percentiles[last] {
+        return Some(rewards[last]);
+    }
+    let upper = percentiles.iter().position(|q| *q >= p)?;
+    let (p0, p1) = (percentiles[upper - 1], percentiles[upper]);
+    let (r0, r1) = (rewards[upper - 1], rewards[upper]);
+    // Weight in basis points keeps the math in U256
+    let weight = U256::from(((p - p0) / (p1 - p0) * 10_000.0).round() as u64);
+    let bps = U256::from(10_000u64);
+    Some(if r1 >= r0 {
+        r0 + (r1 - r0) * weight / bps
+    } else {
+        r0 - (r0 - r1) * weight / bps
+    })
+}
+
+/// Atomic priority fee before the profit cap: `estimate` (or the static
+/// `fallback_gwei`) bumped by `bump_percent`, clamped to [min_gwei, max_gwei]
+pub fn clamp_bid(estimate: Option<U256>, fallback_gwei: u64, bump_percent: f64, min_gwei: u64, max_gwei: u64) -> U256 {
+    let gwei = U256::from(GWEI);
+    let bid = match estimate {
+        Some(fee) => fee * U256::from((10_000.0 + bump_percent * 100.0).max(0.0).round() as u64) / U256::from(10_000u64),
+        None => U256::from(fallback_gwei) * gwei,
+    };
+    let floor = U256::from(min_gwei) * gwei;
+    let ceiling = U256::from(max_gwei.max(min_gwei)) * gwei;
+    bid.clamp(floor, ceiling)
+}
+
+/// Highest priority fee (wei per gas) that keeps gas spend within
+/// `gas_profit_cap` of the expected profit. None when gas_limit is 0.
+pub fn profit_cap(est_profit_usd: f64, gas_profit_cap: f64, native_price_usd: f64, gas_limit: u64) -> Option<U256> {
+    if gas_limit == 0 {
+        return None;
+    }
+    // profit_usd * cap → max_gas_cost_usd → max_gas_cost_native → max_gas_per_unit
+    let max_gas_budget_native = (est_profit_usd * gas_profit_cap).max(0.0) / native_price_usd;
+    let max_gas_budget_wei = (max_gas_budget_native * 1e18) as u128;
+    Some(U256::from(max_gas_budget_wei) / U256::from(gas_limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn g(v: u64) -> U256 {
+        U256::from(v * GWEI)
+    }
+
+    /// feeHistory for blocks first..first+n, reward columns scaled by block
+    fn history(first: u64, scales: &[u64]) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: vec![g(30); scales.len() + 1],
+            gas_used_ratio: vec![0.5; scales.len()],
+            oldest_block: U256::from(first),
+            reward: scales
+                .iter()
+                .map(|s| vec![g(30 * s), g(40 * s), g(50 * s), g(100 * s), g(200 * s)])
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_percentile_math() {
+        let rewards = [g(30), g(40), g(50), g(100), g(200)];
+        // Exact columns
+        assert_eq!(interpolate(&rewards, &FEE_HISTORY_PERCENTILES, 90.0), Some(g(200)));
+        assert_eq!(interpolate(&rewards, &FEE_HISTORY_PERCENTILES, 50.0), Some(g(50)));
+        // Between 75 (100) and 90 (200): 80 is a third of the way
+        assert_eq!(interpolate(&rewards, &FEE_HISTORY_PERCENTILES, 80.0), Some(U256::from(133_330_000_000u64)));
+        // Outside the sampled range: clamped to the end columns
+        assert_eq!(interpolate(&rewards, &FEE_HISTORY_PERCENTILES, 99.0), Some(g(200)));
+        assert_eq!(interpolate(&rewards, &FEE_HISTORY_PERCENTILES, 1.0), Some(g(30)));
+        assert_eq!(interpolate(&rewards[..3], &FEE_HISTORY_PERCENTILES, 50.0), None);
+
+        // Median across blocks: one 10× spike doesn't move p90
+        let mut oracle = GasOracle::new(20, 5);
+        assert!(oracle.sample_due(100));
+        assert_eq!(oracle.priority_fee_for_percentile(90.0), None);
+        oracle.record(102, Some(&history(100, &[1, 10, 2])));
+        assert_eq!(oracle.len(), 3);
+        assert_eq!(oracle.priority_fee_for_percentile(90.0), Some(g(400)));
+        // Even count: mean of the middle two
+        oracle.record(103, Some(&history(103, &[3])));
+        assert_eq!(oracle.priority_fee_for_percentile(50.0), Some(g(125)));
+
+        assert!(!oracle.sample_due(107));
+        assert!(oracle.sample_due(108));
+    }
+
+    #[test]
+    fn test_window_ages_out_without_fee_data() {
+        let mut oracle = GasOracle::new(10, 5);
+        oracle.record(109, Some(&history(100, &[1; 10])));
+        assert_eq!(oracle.len(), 10);
+        // Failed samples keep trimming: blocks older than the window drop out
+        oracle.record(114, None);
+        assert_eq!(oracle.len(), 5);
+        oracle.record(119, None);
+        assert!(oracle.is_empty());
+        assert_eq!(oracle.priority_fee_for_percentile(90.0), None);
+    }
+
+    #[test]
+    fn test_clamp_bid_and_profit_cap() {
+        // p90 of 200 gwei + 10% bump
+        assert_eq!(clamp_bid(Some(g(200)), 5000, 10.0, 30, 5000), g(220));
+        // Quiet blocks: floor
+        assert_eq!(clamp_bid(Some(g(1)), 5000, 10.0, 30, 5000), g(30));
+        // Gas spike: ceiling
+        assert_eq!(clamp_bid(Some(g(9000)), 5000, 10.0, 30, 5000), g(5000));
+        // No fee data: static fallback, still clamped
+        assert_eq!(clamp_bid(None, 5000, 10.0, 30, 5000), g(5000));
+        assert_eq!(clamp_bid(None, 5000, 10.0, 30, 2000), g(2000));
+        // Misconfigured max < min: min wins
+        assert_eq!(clamp_bid(None, 5000, 10.0, 30, 10), g(30));
+
+        // $1 profit, 50% cap, $0.50 native, 500k gas: 1 native / 500k = 2000 gwei
+        assert_eq!(profit_cap(1.0, 0.5, 0.5, 500_000), Some(g(2000)));
+        assert_eq!(profit_cap(-1.0, 0.5, 0.5, 500_000), Some(U256::zero()));
+        assert_eq!(profit_cap(1.0, 0.5, 0.5, 0), None);
+    }
+}
@@ -0,0 +1,519 @@
+//! Isolated I/O Writers — bounded queues drained by dedicated writer threads
+//!
+//! A slow disk, a full filesystem or an NFS hiccup must not add latency to
+//! block processing. File writes reachable from the block loop and the
+//! execution path go through an `IsolatedWriter`: the caller only enqueues, a
+//! dedicated thread does the I/O. Each stream's `StreamContract` fixes what
+//! happens under backpressure:
+//!
+//!   stream      contract   full queue                      failed write
+//!   price_log   lossy      oldest batch dropped            batch dropped
+//!   gas_log     lossy      oldest batch dropped            batch dropped
+//!   events      lossy      oldest event dropped            event dropped
+//!   opportunity_archive
+//!               lossy      oldest line dropped             line dropped
+//!   canary      lossy      oldest write dropped            write dropped
+//!   tax         lossless   enqueue waits ≤ enqueue timeout, retried until written
+//!                          then refused (caller keeps the
+//!                          record — see tax/pending.rs)
+//!
+//! Nothing can interrupt a write blocked in the kernel, so IO_WRITE_TIMEOUT_MS
+//! is an observation threshold: a write taking longer counts as slow, and a
+//! write in progress for longer marks the stream STALLED in the status line
+//! (strict tax logging halts live submissions on it). The enqueue side is
+//! always bounded: lossy streams never wait, lossless ones wait at most
+//! TAX_ENQUEUE_TIMEOUT_MS.
+//!
+//! Audit (block loop + execution path): price history, gas history and tax
+//! records (including finality flags and reorg corrections) are isolated here.
+//! The remaining writes are small JSON state files rewritten on events (route
+//! stats, journals) or research exports off the per-trade path; they stay
+//! synchronous.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - opportunity_archive stream (lossy)
+//! Modified: 2026-10-17 - canary stream (lossy): canary_state.json snapshots, canary_trades.csv rows
+
+use crate::types::BotConfig;
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Queue capacity of the gas history stream (batches of rows)
+pub const GAS_LOG_QUEUE_CAPACITY: usize = 16;
+
+/// Queue capacity of the event journal stream (one JSON line each)
+pub const EVENT_JOURNAL_QUEUE_CAPACITY: usize = 1024;
+
+/// Queue capacity of the opportunity archive stream (one JSON line each)
+pub const OPPORTUNITY_ARCHIVE_QUEUE_CAPACITY: usize = 4096;
+
+/// Queue capacity of the canary stream (state snapshots and report rows)
+pub const CANARY_QUEUE_CAPACITY: usize = 256;
+
+/// Pause before a lossless stream retries a failed write
+pub const LOSSLESS_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// What `send` does when the queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Make room by dropping the oldest queued item (never waits)
+    DropOldest,
+    /// Wait up to the timeout for room, then hand the item back
+    BlockWithTimeout(Duration),
+}
+
+/// Per-stream behavioural contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamContract {
+    pub name: &'static str,
+    pub capacity: usize,
+    pub overflow: Overflow,
+    /// Writes slower than this count as slow; one in progress longer is a stall
+    pub write_timeout: Duration,
+    /// Lossless streams keep a failed item and retry it; lossy ones drop it
+    pub lossless: bool,
+}
+
+impl StreamContract {
+    /// May drop: oldest item on overflow, failed items after one attempt
+    pub fn lossy(name: &'static str, capacity: usize, write_timeout: Duration) -> Self {
+        Self { name, capacity: capacity.max(1), overflow: Overflow::DropOldest, write_timeout, lossless: false }
+    }
+
+    /// Never drops: bounded wait on overflow, failed items retried until written
+    pub fn lossless(name: &'static str, capacity: usize, enqueue_timeout: Duration, write_timeout: Duration) -> Self {
+        Self {
+            name,
+            capacity: capacity.max(1),
+            overflow: Overflow::BlockWithTimeout(enqueue_timeout),
+            write_timeout,
+            lossless: true,
+        }
+    }
+
+    pub fn may_drop(&self) -> bool {
+        !self.lossless
+    }
+
+    /// Price history batches (one per block)
+    pub fn price_log(config: &BotConfig) -> Self {
+        Self::lossy("price_log", config.price_log_queue_capacity, write_timeout(config))
+    }
+
+    /// Gas history row batches (one per feeHistory sample)
+    pub fn gas_log(config: &BotConfig) -> Self {
+        Self::lossy("gas_log", GAS_LOG_QUEUE_CAPACITY, write_timeout(config))
+    }
+
+    /// Structured event journal lines (events.rs)
+    pub fn events(config: &BotConfig) -> Self {
+        Self::lossy("events", EVENT_JOURNAL_QUEUE_CAPACITY, write_timeout(config))
+    }
+
+    /// Opportunity archive lines (arbitrage/archive.rs)
+    pub fn opportunity_archive(config: &BotConfig) -> Self {
+        Self::lossy("opportunity_archive", OPPORTUNITY_ARCHIVE_QUEUE_CAPACITY, write_timeout(config))
+    }
+
+    /// Canary state snapshots and report rows (arbitrage/canary.rs)
+    pub fn canary(config: &BotConfig) -> Self {
+        Self::lossy("canary", CANARY_QUEUE_CAPACITY, write_timeout(config))
+    }
+
+    /// Tax records, finality flags and reorg corrections
+    pub fn tax(config: &BotConfig) -> Self {
+        Self::lossless(
+            "tax",
+            config.tax_write_queue_capacity,
+            Duration::from_millis(config.tax_enqueue_timeout_ms),
+            write_timeout(config),
+        )
+    }
+}
+
+fn write_timeout(config: &BotConfig) -> Duration {
+    Duration::from_millis(config.io_write_timeout_ms.max(1))
+}
+
+/// `send` refused the item; it is handed back
+#[derive(Debug)]
+pub enum SendError<T> {
+    /// Lossless stream still full after the enqueue timeout
+    Full(T),
+    /// Writer shut down
+    Closed(T),
+}
+
+impl<T> SendError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            SendError::Full(item) | SendError::Closed(item) => item,
+        }
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendError::Full(_) => write!(f, "writer queue full"),
+            SendError::Closed(_) => write!(f, "writer closed"),
+        }
+    }
+}
+
+/// Counters and current state of one stream
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WriterStats {
+    pub queued: usize,
+    /// An item is being written (or, lossless, retried)
+    pub in_progress: bool,
+    pub written: u64,
+    /// Lossy only: overflow drops + failed writes
+    pub dropped: u64,
+    pub errors: u64,
+    pub slow_writes: u64,
+    /// Lossless only: sends refused after the enqueue timeout
+    pub refused: u64,
+    /// Lossless: the item in progress has failed since the last success
+    pub failing: Option<String>,
+    /// The write in progress has exceeded write_timeout for this long
+    pub stalled_for: Option<Duration>,
+}
+
+impl WriterStats {
+    /// Items accepted but not yet written (or dropped)
+    pub fn pending(&self) -> usize {
+        self.queued + usize::from(self.in_progress)
+    }
+}
+
+struct State<T> {
+    items: VecDeque<T>,
+    closed: bool,
+    /// Start of the current write attempt
+    busy_since: Option<Instant>,
+    stats: WriterStats,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    /// Signalled when an item is queued or the writer closes
+    work: Condvar,
+    /// Signalled when room frees up or the writer goes idle
+    space: Condvar,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Bounded queue + dedicated writer thread for one stream
+pub struct IsolatedWriter<T: Send + 'static> {
+    contract: StreamContract,
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Send + 'static> IsolatedWriter<T> {
+    /// Start the writer thread; `write` runs on it for every item. Lossless
+    /// streams pass the same item again after a failure (it may keep progress).
+    pub fn spawn<W>(contract: StreamContract, mut write: W) -> Self
+    where
+        W: FnMut(&mut T) -> Result<()> + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                items: VecDeque::new(),
+                closed: false,
+                busy_since: None,
+                stats: WriterStats::default(),
+            }),
+            work: Condvar::new(),
+            space: Condvar::new(),
+        });
+        let worker = shared.clone();
+        let c = contract.clone();
+        let spawned = thread::Builder::new().name(format!("writer-{}", c.name)).spawn(move || loop {
+            let mut item = {
+                let mut state = worker.lock();
+                while state.items.is_empty() && !state.closed {
+                    state = worker.work.wait(state).unwrap_or_else(|p| p.into_inner());
+                }
+                match state.items.pop_front() {
+                    Some(item) => {
+                        state.busy_since = Some(Instant::now());
+                        state.stats.in_progress = true;
+                        item
+                    }
+                    None => return, // closed and drained
+                }
+            };
+            loop {
+                let started = Instant::now();
+                let result = write(&mut item);
+                let mut state = worker.lock();
+                if started.elapsed() > c.write_timeout {
+                    state.stats.slow_writes += 1;
+                    warn!("Writer {}: write took {}ms", c.name, started.elapsed().as_millis());
+                }
+                match result {
+                    Ok(()) => state.stats.written += 1,
+                    Err(e) => {
+                        state.stats.errors += 1;
+                        if c.lossless {
+                            debug!("Writer {}: write failed, retrying: {}", c.name, e);
+                            state.stats.failing = Some(e.to_string());
+                            state.busy_since = Some(Instant::now());
+                            drop(state);
+                            thread::sleep(LOSSLESS_RETRY_BACKOFF);
+                            continue;
+                        }
+                        warn!("Writer {}: write failed, item dropped: {}", c.name, e);
+                        state.stats.dropped += 1;
+                    }
+                }
+                state.stats.failing = None;
+                state.stats.in_progress = false;
+                state.busy_since = None;
+                worker.space.notify_all();
+                break;
+            }
+        });
+        if let Err(e) = spawned {
+            warn!("Writer {}: failed to start thread: {} — items will queue unwritten", contract.name, e);
+        }
+        Self { contract, shared }
+    }
+
+    pub fn contract(&self) -> &StreamContract {
+        &self.contract
+    }
+
+    /// Enqueue without touching the disk. Lossy: never waits (drops the oldest
+    /// when full). Lossless: waits at most the enqueue timeout for room.
+    pub fn send(&self, item: T) -> std::result::Result<(), SendError<T>> {
+        let mut state = self.shared.lock();
+        if state.closed {
+            return Err(SendError::Closed(item));
+        }
+        if state.items.len() >= self.contract.capacity {
+            match self.contract.overflow {
+                Overflow::DropOldest => {
+                    state.items.pop_front();
+                    state.stats.dropped += 1;
+                }
+                Overflow::BlockWithTimeout(timeout) => {
+                    let capacity = self.contract.capacity;
+                    state = self
+                        .shared
+                        .space
+                        .wait_timeout_while(state, timeout, |s| s.items.len() >= capacity && !s.closed)
+                        .map(|(s, _)| s)
+                        .unwrap_or_else(|p| p.into_inner().0);
+                    if state.closed {
+                        return Err(SendError::Closed(item));
+                    }
+                    if state.items.len() >= capacity {
+                        state.stats.refused += 1;
+                        return Err(SendError::Full(item));
+                    }
+                }
+            }
+        }
+        state.items.push_back(item);
+        self.shared.work.notify_one();
+        Ok(())
+    }
+
+    pub fn stats(&self) -> WriterStats {
+        let state = self.shared.lock();
+        let mut stats = state.stats.clone();
+        stats.queued = state.items.len();
+        stats.stalled_for = state
+            .busy_since
+            .map(|t| t.elapsed())
+            .filter(|elapsed| *elapsed > self.contract.write_timeout);
+        stats
+    }
+
+    /// Why items are not reaching the disk right now (failing or stalled write)
+    pub fn blocked_reason(&self) -> Option<String> {
+        let stats = self.stats();
+        match (stats.failing, stats.stalled_for) {
+            (Some(error), _) => Some(format!("{} writer failing: {}", self.contract.name, error)),
+            (None, Some(stalled)) => Some(format!("{} writer stalled for {:.1}s", self.contract.name, stalled.as_secs_f64())),
+            (None, None) => None,
+        }
+    }
+
+    /// Wait until everything accepted so far is written (true) or `timeout` passes
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let state = self.shared.lock();
+        let (state, _) = self
+            .shared
+            .space
+            .wait_timeout_while(state, timeout, |s| !s.items.is_empty() || s.stats.in_progress)
+            .unwrap_or_else(|p| p.into_inner());
+        state.items.is_empty() && !state.stats.in_progress
+    }
+
+    /// Backpressure summary; None while the stream has nothing to report
+    pub fn status_line(&self) -> Option<String> {
+        let s = self.stats();
+        if s.pending() == 0 && s.dropped + s.errors + s.slow_writes + s.refused == 0 {
+            return None;
+        }
+        let mut line = format!(
+            "{} ({}): {}/{} queued | {} written, {} dropped, {} refused, {} errors, {} slow",
+            self.contract.name,
+            if self.contract.lossless { "lossless" } else { "lossy" },
+            s.queued,
+            self.contract.capacity,
+            s.written,
+            s.dropped,
+            s.refused,
+            s.errors,
+            s.slow_writes
+        );
+        if let Some(stalled) = s.stalled_for {
+            line.push_str(&format!(" | STALLED {:.1}s", stalled.as_secs_f64()));
+        }
+        if let Some(ref error) = s.failing {
+            line.push_str(&format!(" | FAILING: {}", error));
+        }
+        Some(line)
+    }
+}
+
+impl<T: Send + 'static> Drop for IsolatedWriter<T> {
+    /// The thread drains what is queued and exits; it is not joined, so a
+    /// stalled disk cannot hang shutdown (callers flush with a timeout first)
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.work.notify_all();
+        self.shared.space.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Writer whose disk "hangs" while `stall` is set
+    fn stallable(contract: StreamContract) -> (IsolatedWriter<u32>, Arc<AtomicBool>, Arc<Mutex<Vec<u32>>>) {
+        let stall = Arc::new(AtomicBool::new(true));
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (s, w) = (stall.clone(), written.clone());
+        let writer = IsolatedWriter::spawn(contract, move |item: &mut u32| {
+            while s.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            w.lock().unwrap().push(*item);
+            Ok(())
+        });
+        (writer, stall, written)
+    }
+
+    #[test]
+    fn test_lossy_stream_drops_oldest_without_blocking() {
+        let contract = StreamContract::lossy("price_log", 4, Duration::from_millis(20));
+        assert!(contract.may_drop());
+        let (writer, stall, written) = stallable(contract);
+
+        writer.send(0).unwrap();
+        thread::sleep(Duration::from_millis(20)); // writer picks up 0 and hangs
+        // Disk hung: per-block sends must each return immediately
+        let started = Instant::now();
+        for block in 1..50 {
+            let send = Instant::now();
+            writer.send(block).unwrap();
+            assert!(send.elapsed() < Duration::from_millis(20));
+        }
+        assert!(started.elapsed() < Duration::from_millis(200));
+        thread::sleep(Duration::from_millis(40));
+        let stats = writer.stats();
+        assert_eq!(stats.queued, 4);
+        assert!(stats.stalled_for.is_some());
+        assert_eq!(stats.dropped, 50 - 4 - 1); // one is stuck in the write
+        assert!(writer.status_line().unwrap().contains("STALLED"));
+
+        // Disk back: the newest batches are written, in order
+        stall.store(false, Ordering::SeqCst);
+        assert!(writer.flush(Duration::from_secs(5)));
+        let written = written.lock().unwrap().clone();
+        assert_eq!(written, vec![0, 46, 47, 48, 49]);
+    }
+
+    #[test]
+    fn test_lossless_stream_never_drops() {
+        let contract = StreamContract::lossless("tax", 2, Duration::from_millis(30), Duration::from_millis(20));
+        assert!(!contract.may_drop());
+        let (writer, stall, written) = stallable(contract);
+
+        writer.send(1).unwrap();
+        thread::sleep(Duration::from_millis(20)); // writer picks up 1 and hangs
+        writer.send(2).unwrap();
+        writer.send(3).unwrap();
+        // Full: the wait is bounded and the item comes back to the caller
+        let send = Instant::now();
+        let refused = writer.send(4).unwrap_err();
+        assert!(send.elapsed() >= Duration::from_millis(30) && send.elapsed() < Duration::from_millis(500));
+        assert!(matches!(refused, SendError::Full(4)));
+        assert_eq!(writer.stats().refused, 1);
+        assert!(writer.blocked_reason().unwrap().contains("stalled"));
+
+        stall.store(false, Ordering::SeqCst);
+        writer.send(refused.into_inner()).unwrap();
+        assert!(writer.flush(Duration::from_secs(5)));
+        assert_eq!(*written.lock().unwrap(), vec![1, 2, 3, 4]);
+        assert_eq!(writer.stats().dropped, 0);
+    }
+
+    #[test]
+    fn test_failed_writes_by_contract() {
+        let disk_ok = Arc::new(AtomicBool::new(false));
+        let attempts = Arc::new(Mutex::new(Vec::new()));
+
+        let writer_for = |contract: StreamContract| {
+            let (ok, log) = (disk_ok.clone(), attempts.clone());
+            IsolatedWriter::spawn(contract, move |item: &mut u32| {
+                log.lock().unwrap().push(*item);
+                if ok.load(Ordering::SeqCst) {
+                    Ok(())
+                } else {
+                    Err(anyhow!("No space left on device"))
+                }
+            })
+        };
+
+        // Lossy: one attempt, then dropped
+        let lossy = writer_for(StreamContract::lossy("gas_log", 4, Duration::from_secs(1)));
+        lossy.send(7).unwrap();
+        assert!(lossy.flush(Duration::from_secs(5)));
+        let stats = lossy.stats();
+        assert_eq!((stats.written, stats.dropped, stats.errors), (0, 1, 1));
+
+        // Lossless: retried (same item) until the disk recovers
+        let lossless = writer_for(StreamContract::lossless("tax", 4, Duration::from_millis(10), Duration::from_secs(1)));
+        lossless.send(9).unwrap();
+        thread::sleep(LOSSLESS_RETRY_BACKOFF + Duration::from_millis(200));
+        assert!(lossless.blocked_reason().unwrap().contains("No space left"));
+        assert_eq!(lossless.stats().pending(), 1);
+        disk_ok.store(true, Ordering::SeqCst);
+        assert!(lossless.flush(Duration::from_secs(5)));
+        let stats = lossless.stats();
+        assert_eq!((stats.written, stats.dropped), (1, 0));
+        assert!(stats.errors >= 2);
+        assert!(lossless.blocked_reason().is_none());
+        assert!(attempts.lock().unwrap().iter().filter(|&&i| i == 9).count() >= 3);
+    }
+}
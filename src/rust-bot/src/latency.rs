@@ -0,0 +1,413 @@
+//! Latency Instrumentation — per-stage timing histograms for the hot path
+//!
+//! Where does the time go between a block arriving and our tx leaving?
+//! `LatencyRecorder` times each stage of the block path into a fixed-bucket
+//! histogram: header → arrival lag, pool sync, detection, Multicall3
+//! pre-screen, estimateGas fill, signing, eth_sendRawTransaction, and the
+//! end-to-end block arrival → tx submitted per executed trade.
+//!
+//! - t0 is the arrival instant of the block being processed (mark_block);
+//!   the header timestamp only has second resolution, so it is used for the
+//!   arrival lag stage alone.
+//! - Overhead: every histogram is pre-allocated atomics (no lock, no
+//!   allocation per sample). One recorder is shared as an Arc by the block
+//!   loop, MulticallQuoter and the executor.
+//! - Buckets: BUCKET_BOUNDS_US upper bounds plus one open-ended bucket.
+//!   Percentiles are nearest-rank over buckets and report the bucket's upper
+//!   bound (capped at the observed max), so they are upper estimates.
+//! - The block loop logs the table and rewrites data/{chain}/latency.json
+//!   every LATENCY_REPORT_INTERVAL iterations (0 = only at shutdown).
+//!   Histograms cover the whole run.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Bucket upper bounds in microseconds (250µs … 30s)
+pub const BUCKET_BOUNDS_US: [u64; 16] = [
+    250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000,
+    2_500_000, 5_000_000, 10_000_000, 30_000_000,
+];
+
+/// Bounded buckets plus the open-ended one
+const BUCKETS: usize = BUCKET_BOUNDS_US.len() + 1;
+
+/// Timed stages of the block → submission path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Block header timestamp → block received over WS (second resolution)
+    BlockArrival,
+    /// Pool state sync (event logs or poll)
+    Sync,
+    /// detector.scan_opportunities()
+    Detect,
+    /// MulticallQuoter::batch_verify (buy-leg and chained sell-leg aggregate3 eth_calls)
+    Verify,
+    /// Transaction fill (estimateGas unless the gas cache hits)
+    Fill,
+    /// Local signing
+    Sign,
+    /// eth_sendRawTransaction
+    Send,
+    /// Block arrival → tx submitted, per executed trade
+    BlockToSubmit,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 8] = [
+        Stage::BlockArrival,
+        Stage::Sync,
+        Stage::Detect,
+        Stage::Verify,
+        Stage::Fill,
+        Stage::Sign,
+        Stage::Send,
+        Stage::BlockToSubmit,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stage::BlockArrival => "block_arrival",
+            Stage::Sync => "sync",
+            Stage::Detect => "detect",
+            Stage::Verify => "verify",
+            Stage::Fill => "fill",
+            Stage::Sign => "sign",
+            Stage::Send => "send",
+            Stage::BlockToSubmit => "block_to_submit",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Lock-free fixed-bucket histogram of durations
+#[derive(Debug)]
+pub struct Histogram {
+    buckets: [AtomicU64; BUCKETS],
+    count: AtomicU64,
+    sum_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            sum_us: AtomicU64::new(0),
+            max_us: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Histogram {
+    pub fn record(&self, duration: Duration) {
+        let us = duration.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = BUCKET_BOUNDS_US.partition_point(|bound| *bound < us);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            buckets: std::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed)),
+            count: self.count.load(Ordering::Relaxed),
+            sum_us: self.sum_us.load(Ordering::Relaxed),
+            max_us: self.max_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Point-in-time copy of a histogram
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramSnapshot {
+    pub buckets: [u64; BUCKETS],
+    pub count: u64,
+    pub sum_us: u64,
+    pub max_us: u64,
+}
+
+impl HistogramSnapshot {
+    /// Nearest-rank percentile `p` (0–100): upper bound of the bucket holding
+    /// that rank, capped at the observed max. None when empty.
+    pub fn percentile_us(&self, p: f64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((p / 100.0) * self.count as f64).ceil().clamp(1.0, self.count as f64) as u64;
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                let bound = BUCKET_BOUNDS_US.get(i).copied().unwrap_or(self.max_us);
+                return Some(bound.min(self.max_us));
+            }
+        }
+        Some(self.max_us)
+    }
+
+    pub fn mean_us(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum_us as f64 / self.count as f64
+        }
+    }
+}
+
+/// One stage's row in latency.json
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageSummary {
+    pub stage: &'static str,
+    pub count: u64,
+    pub mean_ms: f64,
+    pub p50_ms: Option<f64>,
+    pub p90_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+    pub max_ms: f64,
+    /// Counts per bucket, aligned with bucket_bounds_us (+ the open-ended bucket)
+    pub buckets: Vec<u64>,
+}
+
+impl StageSummary {
+    fn new(stage: Stage, snap: &HistogramSnapshot) -> Self {
+        let ms = |us: u64| us as f64 / 1_000.0;
+        Self {
+            stage: stage.label(),
+            count: snap.count,
+            mean_ms: snap.mean_us() / 1_000.0,
+            p50_ms: snap.percentile_us(50.0).map(ms),
+            p90_ms: snap.percentile_us(90.0).map(ms),
+            p99_ms: snap.percentile_us(99.0).map(ms),
+            max_ms: ms(snap.max_us),
+            buckets: snap.buckets.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LatencyFile {
+    saved_at: String,
+    since: String,
+    bucket_bounds_us: &'static [u64],
+    stages: Vec<StageSummary>,
+}
+
+/// Shared per-stage recorder (one per process, held as an Arc)
+#[derive(Debug)]
+pub struct LatencyRecorder {
+    stages: [Histogram; Stage::ALL.len()],
+    /// Reference for block_arrival_us
+    epoch: Instant,
+    /// Current block's arrival, µs since epoch + 1 (0 = no block yet)
+    block_arrival_us: AtomicU64,
+    since: chrono::DateTime<chrono::Utc>,
+    /// latency.json (None = no snapshot)
+    path: Option<PathBuf>,
+}
+
+impl Default for LatencyRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyRecorder {
+    pub fn new() -> Self {
+        Self {
+            stages: std::array::from_fn(|_| Histogram::default()),
+            epoch: Instant::now(),
+            block_arrival_us: AtomicU64::new(0),
+            since: chrono::Utc::now(),
+            path: None,
+        }
+    }
+
+    /// Write `write_snapshot` output to `data_dir`/latency.json
+    pub fn with_snapshot(mut self, data_dir: &str) -> Self {
+        let dir = PathBuf::from(data_dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Latency: failed to create {}: {}", data_dir, e);
+        }
+        self.path = Some(dir.join("latency.json"));
+        self
+    }
+
+    pub fn record(&self, stage: Stage, duration: Duration) {
+        self.stages[stage.index()].record(duration);
+    }
+
+    /// Record `start.elapsed()` under `stage`
+    pub fn record_since(&self, stage: Stage, start: Instant) {
+        self.record(stage, start.elapsed());
+    }
+
+    /// A new block arrived: t0 for block_to_submit, plus the header → arrival lag
+    pub fn mark_block(&self, arrived: Instant, header_timestamp_secs: u64) {
+        let us = arrived.saturating_duration_since(self.epoch).as_micros() as u64;
+        self.block_arrival_us.store(us + 1, Ordering::Relaxed);
+        let now_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        let lag_ms = now_ms.saturating_sub(header_timestamp_secs.saturating_mul(1_000));
+        self.record(Stage::BlockArrival, Duration::from_millis(lag_ms));
+    }
+
+    /// Time since the current block arrived (None before the first block)
+    pub fn since_block(&self) -> Option<Duration> {
+        match self.block_arrival_us.load(Ordering::Relaxed) {
+            0 => None,
+            us => Some(self.epoch.elapsed().saturating_sub(Duration::from_micros(us - 1))),
+        }
+    }
+
+    /// A tx was just submitted: record and return block arrival → now
+    pub fn record_submit(&self) -> Option<Duration> {
+        let elapsed = self.since_block()?;
+        self.record(Stage::BlockToSubmit, elapsed);
+        Some(elapsed)
+    }
+
+    pub fn snapshot(&self, stage: Stage) -> HistogramSnapshot {
+        self.stages[stage.index()].snapshot()
+    }
+
+    pub fn summaries(&self) -> Vec<StageSummary> {
+        Stage::ALL.iter().map(|s| StageSummary::new(*s, &self.snapshot(*s))).collect()
+    }
+
+    /// Formatted table: one row per stage with samples
+    pub fn report(&self) -> String {
+        let fmt = |ms: Option<f64>| ms.map_or_else(|| "-".to_string(), |v| format!("{:.1}", v));
+        let mut lines = vec![format!(
+            "Latency (since {}): {:<16} {:>7} {:>9} {:>9} {:>9} {:>9} {:>9}",
+            self.since.format("%H:%M:%S"), "stage", "n", "mean ms", "p50", "p90", "p99", "max"
+        )];
+        for s in self.summaries().iter().filter(|s| s.count > 0) {
+            lines.push(format!(
+                "  {:<16} {:>7} {:>9.1} {:>9} {:>9} {:>9} {:>9.1}",
+                s.stage, s.count, s.mean_ms, fmt(s.p50_ms), fmt(s.p90_ms), fmt(s.p99_ms), s.max_ms
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /// Rewrite latency.json (tmp + rename). No-op without a snapshot path.
+    pub fn write_snapshot(&self) {
+        if let Some(ref path) = self.path {
+            match self.write_to(path) {
+                Ok(()) => info!("Latency: histograms written to {}", path.display()),
+                Err(e) => warn!("Latency: failed to write {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    fn write_to(&self, path: &Path) -> anyhow::Result<()> {
+        let file = LatencyFile {
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            since: self.since.to_rfc3339(),
+            bucket_bounds_us: &BUCKET_BOUNDS_US,
+            stages: self.summaries(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&file)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn us(n: u64) -> Duration {
+        Duration::from_micros(n)
+    }
+
+    #[test]
+    fn test_bucket_accounting() {
+        let h = Histogram::default();
+        // Bounds are inclusive upper limits: 250 → bucket 0, 251 → bucket 1
+        for d in [0, 250, 251, 1_000, 40_000, 31_000_000] {
+            h.record(us(d));
+        }
+        let snap = h.snapshot();
+        assert_eq!(snap.count, 6);
+        assert_eq!(snap.buckets[0], 2);
+        assert_eq!(snap.buckets[1], 1);
+        assert_eq!(snap.buckets[2], 1);
+        assert_eq!(snap.buckets[7], 1, "40ms lands in the ≤50ms bucket");
+        assert_eq!(snap.buckets[BUCKETS - 1], 1, "beyond 30s → open-ended bucket");
+        assert_eq!(snap.buckets.iter().sum::<u64>(), snap.count);
+        assert_eq!(snap.sum_us, 250 + 251 + 1_000 + 40_000 + 31_000_000);
+        assert_eq!(snap.max_us, 31_000_000);
+    }
+
+    #[test]
+    fn test_percentiles_from_buckets() {
+        let h = Histogram::default();
+        assert_eq!(h.snapshot().percentile_us(50.0), None);
+
+        // 90 fast samples (~2ms) and 10 slow ones (~80ms)
+        for _ in 0..90 {
+            h.record(us(2_000));
+        }
+        for _ in 0..10 {
+            h.record(us(80_000));
+        }
+        let snap = h.snapshot();
+        assert_eq!(snap.percentile_us(50.0), Some(2_500));
+        assert_eq!(snap.percentile_us(90.0), Some(2_500), "rank 90 is the last fast sample");
+        assert_eq!(snap.percentile_us(91.0), Some(80_000), "≤100ms bucket, capped at the max seen");
+        assert_eq!(snap.percentile_us(100.0), Some(80_000));
+        assert_eq!(snap.percentile_us(0.0), Some(2_500), "rank clamps to the first sample");
+        assert!((snap.mean_us() - 9_800.0).abs() < 1e-9);
+
+        // Open-ended bucket reports the max
+        h.record(us(45_000_000));
+        assert_eq!(h.snapshot().percentile_us(100.0), Some(45_000_000));
+    }
+
+    #[test]
+    fn test_recorder_stages_block_to_submit_and_snapshot() {
+        let recorder = LatencyRecorder::new();
+        assert_eq!(recorder.record_submit(), None, "no block yet");
+
+        let header_secs = chrono::Utc::now().timestamp() as u64;
+        recorder.mark_block(Instant::now(), header_secs);
+        recorder.record(Stage::Verify, Duration::from_millis(12));
+        std::thread::sleep(Duration::from_millis(5));
+        let submitted = recorder.record_submit().unwrap();
+        assert!(submitted >= Duration::from_millis(5));
+
+        assert_eq!(recorder.snapshot(Stage::BlockArrival).count, 1);
+        assert_eq!(recorder.snapshot(Stage::Verify).percentile_us(50.0), Some(12_000));
+        assert_eq!(recorder.snapshot(Stage::BlockToSubmit).count, 1);
+        assert_eq!(recorder.snapshot(Stage::Sign).count, 0);
+
+        let report = recorder.report();
+        assert!(report.contains("verify") && report.contains("block_to_submit"));
+        assert!(!report.contains("sign"), "stages without samples are omitted");
+
+        let dir = std::env::temp_dir().join(format!("dexarb_latency_{}", std::process::id()));
+        let recorder = recorder.with_snapshot(dir.to_str().unwrap());
+        recorder.record(Stage::Verify, Duration::from_millis(12));
+        recorder.write_snapshot();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("latency.json")).unwrap()).unwrap();
+        assert_eq!(json["bucket_bounds_us"].as_array().unwrap().len(), BUCKET_BOUNDS_US.len());
+        assert_eq!(json["stages"].as_array().unwrap().len(), Stage::ALL.len());
+        assert_eq!(json["stages"][3]["stage"], "verify");
+        assert_eq!(json["stages"][3]["count"], 2);
+        assert_eq!(json["stages"][3]["buckets"].as_array().unwrap().len(), BUCKETS);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -5,17 +5,69 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - Added retry (shared retry/backoff utility)
+//! Modified: 2026-10-16 - Added watchdog (component heartbeats + stall exit)
+//! Modified: 2026-10-16 - Added chaos (failure injection at execution seams, CHAOS_MODE)
+//! Modified: 2026-10-16 - Added alerts (severity dispatcher) + anomaly (behavioral baselines)
+//! Modified: 2026-10-16 - Added telemetry (opt-in cross-instance snapshot push + reference collector)
+//! Modified: 2026-10-16 - Added rpc_bench (per-endpoint latency of the bot's own call mix)
+//! Modified: 2026-10-16 - Added deadman (health-gated pings for an external uptime monitor)
+//! Modified: 2026-10-16 - Added quote_amount (raw quote token amounts bound to their token)
+//! Modified: 2026-10-16 - Added shadow (primary decisions feed + follower divergence diffing)
+//! Modified: 2026-10-16 - Added io_writer (bounded queues + writer threads for hot-path log I/O)
+//! Modified: 2026-10-16 - Added api (live pools/opportunities/status snapshot; HTTP + WS server behind `api`)
+//! Modified: 2026-10-16 - Added native_price (live native token USD price from a reference pool)
+//! Modified: 2026-10-16 - Added backtest (replay PriceLogger CSVs through the detector; backtest binary)
+//! Modified: 2026-10-16 - Added shutdown (signal handling + in-flight trade draining)
+//! Modified: 2026-10-16 - Added live_alerts (webhook alerts for live trades, halts, WS exhaustion, daily summary)
+//! Modified: 2026-10-16 - Added gas_oracle (eth_feeHistory percentile priority fees for the atomic path)
+//! Modified: 2026-10-16 - Added chain_config (sectioned config/{chain}.toml for the live bot)
+//! Modified: 2026-10-16 - Added cross_chain (price divergence vs another chain's collector state)
+//! Modified: 2026-10-16 - Added events (LOG_FORMAT=json structured event journal)
+//! Modified: 2026-10-16 - Added provider_manager (RPC_URLS endpoint health + WS failover)
+//! Modified: 2026-10-16 - Added latency (per-stage timing histograms, block → tx submitted)
+//! Modified: 2026-10-16 - Added config_check (configured contracts validated against the chain)
+//! Modified: 2026-10-16 - Added transport (local node IPC reads with WS fallback)
+//! Modified: 2026-10-16 - Added ab_shadow (experimental SHADOW_CONFIG detector beside the live one)
+//! Modified: 2026-10-16 - Added token_registry (on-chain symbol/decimals by address, cached per chain)
 
+pub mod ab_shadow;
+pub mod alerts;
+pub mod anomaly;
+pub mod api;
 pub mod arbitrage;
+pub mod backtest;
+pub mod chain_config;
+pub mod chaos;
 pub mod config;
+pub mod config_check;
+pub mod cross_chain;
+pub mod events;
 pub mod data_collector;
+pub mod deadman;
 pub mod filters;
+pub mod gas_logger;
+pub mod gas_oracle;
+pub mod io_writer;
+pub mod latency;
+pub mod live_alerts;
 pub mod mempool;
+pub mod native_price;
 pub mod paper_trading;
 pub mod pool;
 pub mod price_logger;
+pub mod provider_manager;
+pub mod quote_amount;
+pub mod retry;
+pub mod rpc_bench;
+pub mod shadow;
+pub mod shutdown;
 pub mod tax;
+pub mod telemetry;
+pub mod token_registry;
+pub mod transport;
 pub mod types;
+pub mod watchdog;
 
 // Re-export commonly used types
 pub use config::load_config;
@@ -0,0 +1,386 @@
+//! Live Alerts — webhook notifications for live trade outcomes and bot health
+//!
+//! Paper trading posts to Discord (paper_trading::discord_alerts); the live
+//! bot only logged, so a HALT was found hours later. `LiveAlerter` turns live
+//! events into operator alerts through the AlertDispatcher webhook plumbing
+//! (spawned send with a timeout — never delays the block loop):
+//!
+//!   trades (ALERT_TRADES)        — successful trade with net profit; atomic
+//!                                  reverts (one per route per rate-limit window)
+//!   halts (ALERT_HALTS)          — receipt timeout, legacy capital committed
+//!   ws (ALERT_WS)                — WS reconnects exhausted, bot exiting
+//!   daily (ALERT_DAILY_SUMMARY)  — previous UTC day's totals from StatsTracker
+//!   canary (ALERT_CANARY)        — canary pool promoted, or demoted to observation
+//!
+//! Repeated identical alerts (same category + route) are muted for
+//! ALERT_RATE_LIMIT_SECS. Built only when ALERT_WEBHOOK_URL is set: without a
+//! webhook the block loop's own logging already covers every event.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-17 - Canary promote/demote decisions (ALERT_CANARY)
+
+use crate::alerts::{AlertDispatcher, AlertSeverity};
+use crate::arbitrage::canary::CanaryDecision;
+use crate::arbitrage::outcome::TradeOutcome;
+use crate::arbitrage::stats::{RouteStats, StatsTracker};
+use crate::types::{format_usd, BotConfig, DexType, TradeResult};
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Where alerts go (AlertDispatcher live, a recorder in tests)
+pub trait AlertSink {
+    fn send(&self, severity: AlertSeverity, message: &str);
+}
+
+impl AlertSink for AlertDispatcher {
+    fn send(&self, severity: AlertSeverity, message: &str) {
+        self.dispatch(severity, message);
+    }
+}
+
+/// Per-category switches + repeat window
+#[derive(Debug, Clone)]
+pub struct LiveAlertConfig {
+    pub trades: bool,
+    pub halts: bool,
+    pub ws: bool,
+    pub daily_summary: bool,
+    pub canary: bool,
+    pub rate_limit: Duration,
+}
+
+impl LiveAlertConfig {
+    pub fn from_config(config: &BotConfig) -> Self {
+        Self {
+            trades: config.alert_trades,
+            halts: config.alert_halts,
+            ws: config.alert_ws,
+            daily_summary: config.alert_daily_summary,
+            canary: config.alert_canary,
+            rate_limit: Duration::from_secs(config.alert_rate_limit_secs),
+        }
+    }
+}
+
+/// Live event → alert, with per-key rate limiting and the daily summary clock
+pub struct LiveAlerter<S: AlertSink = AlertDispatcher> {
+    sink: S,
+    config: LiveAlertConfig,
+    /// Rate-limit key → last send
+    last_sent: HashMap<String, Instant>,
+    /// Day the running summary covers, and the totals when it began
+    summary_day: Option<NaiveDate>,
+    summary_baseline: RouteStats,
+}
+
+fn route_label(pair: &str, buy_dex: DexType, sell_dex: DexType) -> String {
+    format!("{} {}→{}", pair, buy_dex, sell_dex)
+}
+
+impl<S: AlertSink> LiveAlerter<S> {
+    pub fn new(sink: S, config: LiveAlertConfig) -> Self {
+        Self {
+            sink,
+            config,
+            last_sent: HashMap::new(),
+            summary_day: None,
+            summary_baseline: RouteStats::default(),
+        }
+    }
+
+    /// Send unless `key` was sent within the rate-limit window. True = sent.
+    fn send_limited(&mut self, key: String, now: Instant, severity: AlertSeverity, message: &str) -> bool {
+        if let Some(last) = self.last_sent.get(&key) {
+            if now.duration_since(*last) < self.config.rate_limit {
+                return false;
+            }
+        }
+        self.last_sent.insert(key, now);
+        self.sink.send(severity, message);
+        true
+    }
+
+    /// Alert on one classified execute() result. True = an alert was sent.
+    pub fn trade_outcome(
+        &mut self,
+        pair: &str,
+        buy_dex: DexType,
+        sell_dex: DexType,
+        exec: &Result<TradeResult>,
+        outcome: TradeOutcome,
+    ) -> bool {
+        self.trade_outcome_at(pair, buy_dex, sell_dex, exec, outcome, Instant::now())
+    }
+
+    fn trade_outcome_at(
+        &mut self,
+        pair: &str,
+        buy_dex: DexType,
+        sell_dex: DexType,
+        exec: &Result<TradeResult>,
+        outcome: TradeOutcome,
+        now: Instant,
+    ) -> bool {
+        let Ok(result) = exec else {
+            return false;
+        };
+        let route = route_label(pair, buy_dex, sell_dex);
+        let tx = result.tx_hash.as_deref().unwrap_or("?");
+        match outcome {
+            TradeOutcome::Success if self.config.trades => {
+                self.sink.send(
+                    AlertSeverity::Info,
+                    &format!(
+                        "✅ Trade {} | net {} (gross {}, gas {}) | {}ms | TX {}",
+                        route,
+                        format_usd(result.net_profit_usd),
+                        format_usd(result.profit_usd),
+                        format_usd(result.gas_cost_usd),
+                        result.execution_time_ms,
+                        tx
+                    ),
+                );
+                true
+            }
+            TradeOutcome::AtomicRevert if self.config.trades => {
+                let message = format!(
+                    "Atomic revert {} | gas {} | TX {} (further reverts on this route muted {}m)",
+                    route,
+                    format_usd(result.gas_cost_usd),
+                    tx,
+                    self.config.rate_limit.as_secs() / 60
+                );
+                self.send_limited(format!("revert:{}", route), now, AlertSeverity::Warning, &message)
+            }
+            TradeOutcome::ReceiptTimeout if self.config.halts => {
+                let message = format!(
+                    "HALT: receipt timeout {} | TX {} — tx may still confirm, manual check needed",
+                    route, tx
+                );
+                self.send_limited(format!("halt-timeout:{}", route), now, AlertSeverity::Critical, &message)
+            }
+            TradeOutcome::CapitalAtRisk if self.config.halts => {
+                let message = format!(
+                    "HALT: legacy two-tx capital committed {} | {} | TX {} — manual recovery needed",
+                    route,
+                    result.error.as_deref().unwrap_or("Unknown"),
+                    tx
+                );
+                self.send_limited(format!("halt-capital:{}", route), now, AlertSeverity::Critical, &message)
+            }
+            _ => false,
+        }
+    }
+
+    /// WS reconnects exhausted: the bot is about to exit for a supervisor restart
+    pub fn ws_exhausted(&mut self, attempts: u32, error: &str) -> bool {
+        if !self.config.ws {
+            return false;
+        }
+        let message = format!(
+            "WS reconnect exhausted after {} attempts ({}) — bot exiting for supervisor restart",
+            attempts, error
+        );
+        self.send_limited("ws-exhausted".to_string(), Instant::now(), AlertSeverity::Critical, &message)
+    }
+
+    /// A closed canary window: promotion (Info) or demotion to observation
+    /// (Warning — the pool needs review). True = an alert was sent.
+    pub fn canary_decision(&mut self, decision: &CanaryDecision) -> bool {
+        if !self.config.canary {
+            return false;
+        }
+        let (severity, message) = match decision {
+            CanaryDecision::Promote { address, pair, stats } => (
+                AlertSeverity::Info,
+                format!(
+                    "🐤 Canary promoted {} {:?} | {} submitted, win rate {:.0}%, net {}",
+                    pair, address, stats.submitted, stats.win_rate() * 100.0, format_usd(stats.net_profit_usd)
+                ),
+            ),
+            CanaryDecision::Demote { address, pair, stats, reason } => (
+                AlertSeverity::Warning,
+                format!(
+                    "🐤 Canary demoted to observation {} {:?} | {} | {} submitted, net {} — review needed",
+                    pair, address, reason, stats.submitted, format_usd(stats.net_profit_usd)
+                ),
+            ),
+        };
+        self.sink.send(severity, &message);
+        true
+    }
+
+    /// Call every iteration with the current UTC date: on the first call of a
+    /// new day, sends the previous day's totals. True = a summary was sent.
+    pub fn daily_summary(&mut self, today: NaiveDate, stats: &StatsTracker) -> bool {
+        let total = stats.total();
+        let Some(day) = self.summary_day else {
+            self.summary_day = Some(today);
+            self.summary_baseline = total;
+            return false;
+        };
+        if today == day {
+            return false;
+        }
+        let delta = total.since(&self.summary_baseline);
+        self.summary_day = Some(today);
+        self.summary_baseline = total;
+        if !self.config.daily_summary {
+            return false;
+        }
+        self.sink.send(AlertSeverity::Info, &Self::format_summary(day, &delta));
+        true
+    }
+
+    pub fn format_summary(day: NaiveDate, stats: &RouteStats) -> String {
+        format!(
+            "📊 Daily summary {} | {} attempts, {} successes ({:.1}%), {} reverts, {} other failures | net {} (gross {}, gas {})",
+            day,
+            stats.attempts,
+            stats.successes,
+            stats.success_rate(),
+            stats.reverts,
+            stats.other_failures,
+            format_usd(stats.net_usd),
+            format_usd(stats.gross_usd),
+            format_usd(stats.gas_usd)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<(AlertSeverity, String)>>);
+
+    impl AlertSink for &Recorder {
+        fn send(&self, severity: AlertSeverity, message: &str) {
+            self.0.borrow_mut().push((severity, message.to_string()));
+        }
+    }
+
+    fn config() -> LiveAlertConfig {
+        LiveAlertConfig {
+            trades: true,
+            halts: true,
+            ws: true,
+            daily_summary: true,
+            canary: true,
+            rate_limit: Duration::from_secs(3600),
+        }
+    }
+
+    fn result(success: bool, tx: Option<&str>, error: Option<&str>) -> Result<TradeResult> {
+        Ok(TradeResult {
+            opportunity: "WETH/USDC".to_string(),
+            opportunity_id: 0,
+            tx_hash: tx.map(String::from),
+            block_number: Some(100),
+            success,
+            profit_usd: 2.5,
+            gas_cost_usd: 0.04,
+            gas_used_native: 0.1,
+            net_profit_usd: 2.46,
+            execution_time_ms: 850,
+            error: error.map(String::from),
+            amount_in: None,
+            amount_out: None,
+        })
+    }
+
+    #[test]
+    fn test_trade_and_halt_messages_with_per_route_rate_limit() {
+        let recorder = Recorder::default();
+        let mut alerter = LiveAlerter::new(&recorder, config());
+        let (uni, sushi) = (DexType::UniswapV3_005, DexType::SushiV3_030);
+        let t0 = Instant::now();
+
+        let win = result(true, Some("0xabc"), None);
+        assert!(alerter.trade_outcome_at("WETH/USDC", uni, sushi, &win, TradeOutcome::Success, t0));
+        assert!(alerter.trade_outcome_at("WETH/USDC", uni, sushi, &win, TradeOutcome::Success, t0), "successes never muted");
+
+        let revert = result(false, Some("0xdef"), Some("reverted"));
+        assert!(alerter.trade_outcome_at("WETH/USDC", uni, sushi, &revert, TradeOutcome::AtomicRevert, t0));
+        let later = t0 + Duration::from_secs(600);
+        assert!(!alerter.trade_outcome_at("WETH/USDC", uni, sushi, &revert, TradeOutcome::AtomicRevert, later));
+        assert!(alerter.trade_outcome_at("WETH/USDC", sushi, uni, &revert, TradeOutcome::AtomicRevert, later), "other route");
+        let hour_on = t0 + Duration::from_secs(3601);
+        assert!(alerter.trade_outcome_at("WETH/USDC", uni, sushi, &revert, TradeOutcome::AtomicRevert, hour_on));
+
+        let timeout = result(false, Some("0x123"), Some("Receipt timeout (30s)"));
+        assert!(alerter.trade_outcome_at("WETH/USDC", uni, sushi, &timeout, TradeOutcome::ReceiptTimeout, later));
+        assert!(!alerter.trade_outcome_at("WETH/USDC", uni, sushi, &result(false, None, Some("Quoter")), TradeOutcome::RouteRejected, later));
+
+        let sent = recorder.0.borrow();
+        assert_eq!(sent.len(), 6);
+        assert_eq!(sent[0].0, AlertSeverity::Info);
+        assert_eq!(
+            sent[0].1,
+            "✅ Trade WETH/USDC UniswapV3_0.05%→SushiV3_0.30% | net $2.46 (gross $2.50, gas $0.04) | 850ms | TX 0xabc"
+        );
+        assert_eq!(sent[2].0, AlertSeverity::Warning);
+        assert!(sent[2].1.contains("muted 60m"));
+        assert_eq!(sent[5].0, AlertSeverity::Critical);
+        assert!(sent[5].1.starts_with("HALT: receipt timeout WETH/USDC"), "{}", sent[5].1);
+    }
+
+    #[test]
+    fn test_category_flags_and_daily_summary_delta() {
+        let recorder = Recorder::default();
+        let mut alerter = LiveAlerter::new(&recorder, LiveAlertConfig { trades: false, ..config() });
+        let (uni, sushi) = (DexType::UniswapV3_005, DexType::SushiV3_030);
+        let mut stats = StatsTracker::new();
+        let win = result(true, Some("0xabc"), None);
+        stats.record("WETH/USDC", uni, sushi, &win, TradeOutcome::Success);
+
+        assert!(!alerter.trade_outcome("WETH/USDC", uni, sushi, &win, TradeOutcome::Success));
+        let day1 = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        assert!(!alerter.daily_summary(day1, &stats), "first call starts the day");
+
+        stats.record("WETH/USDC", uni, sushi, &win, TradeOutcome::Success);
+        stats.record("WETH/USDC", uni, sushi, &result(false, Some("0xdef"), Some("reverted")), TradeOutcome::AtomicRevert);
+        assert!(!alerter.daily_summary(day1, &stats));
+        assert!(alerter.daily_summary(day1.succ_opt().unwrap(), &stats));
+
+        let sent = recorder.0.borrow();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].1.starts_with("📊 Daily summary 2026-10-16 | 2 attempts, 1 successes (50.0%), 1 reverts"), "{}", sent[0].1);
+        assert!(sent[0].1.contains("net $4.92"), "{}", sent[0].1);
+    }
+
+    #[test]
+    fn test_canary_decisions_alerted() {
+        use crate::arbitrage::canary::CanaryStats;
+        use ethers::types::Address;
+
+        let recorder = Recorder::default();
+        let mut alerter = LiveAlerter::new(&recorder, config());
+        let stats = CanaryStats { attempts: 3, submitted: 2, wins: 0, net_profit_usd: -0.4, volume_usd: 50.0 };
+        let demote = CanaryDecision::Demote {
+            address: Address::from_low_u64_be(1),
+            pair: "WETH/USDC".to_string(),
+            stats: stats.clone(),
+            reason: "win rate 0% < 50%".to_string(),
+        };
+        assert!(alerter.canary_decision(&demote));
+        let promote = CanaryDecision::Promote { address: Address::from_low_u64_be(2), pair: "WETH/USDC".to_string(), stats };
+        assert!(alerter.canary_decision(&promote));
+
+        let mut muted = LiveAlerter::new(&recorder, LiveAlertConfig { canary: false, ..config() });
+        assert!(!muted.canary_decision(&demote));
+
+        let sent = recorder.0.borrow();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].0, AlertSeverity::Warning);
+        assert!(sent[0].1.starts_with("🐤 Canary demoted to observation WETH/USDC"), "{}", sent[0].1);
+        assert!(sent[0].1.contains("win rate 0% < 50% | 2 submitted, net -$0.40"), "{}", sent[0].1);
+        assert_eq!(sent[1].0, AlertSeverity::Info);
+        assert!(sent[1].1.starts_with("🐤 Canary promoted WETH/USDC"), "{}", sent[1].1);
+    }
+}
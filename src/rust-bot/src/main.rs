@@ -29,26 +29,169 @@
 //! Modified: 2026-01-31 - Route cooldown: escalating backoff suppresses stale/dead spreads
 //! Modified: 2026-02-01 - WS timeout+reconnect: 30s timeout on block_stream.next(), auto-reconnect loop
 //! Modified: 2026-02-01 - A4 mempool monitor: observation mode (decode pending swaps, CSV log, cross-ref)
+//! Modified: 2026-10-16 - Block-pinned preflight batch (quotes + balance + allowance + pool state)
+//! Modified: 2026-10-16 - Canary mode: size cap + forced pre-screen + auto promote/demote for new pools
+//! Modified: 2026-10-16 - Stranded-funds alarm: periodic ArbExecutor balanceOf batch + optional auto rescue
+//! Modified: 2026-10-16 - Keep executor's private-RPC connection warm once per block
+//! Modified: 2026-10-16 - Gas history logging: per-block base fee, feeHistory percentiles, our bids
+//! Modified: 2026-10-16 - Pool migration detector: factory lookup on collapse, optional hot-swap
+//! Modified: 2026-10-16 - WS reconnect via retry::ws_reconnect; SIGINT/SIGTERM shutdown token; retry counters in status
+//! Modified: 2026-10-16 - Optional pre-screen JSONL export with detection-time pool snapshots
+//! Modified: 2026-10-16 - Watchdog heartbeats (block loop) + timeouts on hot-path RPC calls
+//! Modified: 2026-10-16 - Opportunity sizes/profits logged in USD + token units (trade_size_usd)
+//! Modified: 2026-10-16 - CHAOS_MODE fault injector; post-trade handling via TradeOutcome classification
+//! Modified: 2026-10-16 - Execution-constraint analysis (EXECUTION_CONSTRAINTS_ENABLED)
+//! Modified: 2026-10-16 - Token metadata re-validation + quarantine (TOKEN_REVALIDATION_HOURS)
+//! Modified: 2026-10-16 - Anomaly monitor over behavioral metrics (alerts via AlertDispatcher)
+//! Modified: 2026-10-16 - In-flight tx journal: startup recovery before nonce init (INFLIGHT_*)
+//! Modified: 2026-10-16 - --pool-revenue-report: on-demand Swap volume / fee revenue whitelist report
+//! Modified: 2026-10-16 - Liquidity guard before pre-screen (MIN_POOL_LIQUIDITY, MAX_LIQUIDITY_FRACTION)
+//! Modified: 2026-10-16 - Opt-in telemetry push to our own collector (TELEMETRY_*)
+//! Modified: 2026-10-16 - TAX_LOG_STRICT: fatal tax-log init, per-block retry of unwritten records, shutdown flush
+//! Modified: 2026-10-16 - Pre-screen leg quotes carried to execution; per-route realized slippage stats (persisted, in status)
+//! Modified: 2026-10-16 - Provisional trade records re-checked at CONFIRMATION_DEPTH; reorg reversal revokes cooldown success
+//! Modified: 2026-10-16 - --bench-rpc: per-endpoint latency of the bot's own call mix, report and exit
+//! Modified: 2026-10-16 - Exposure pacing startup line; ExposureLimited outcome (no cooldown, next route)
+//! Modified: 2026-10-16 - Dead-man's switch pinger (DEADMAN_*): block progress, halts and tax halt feed its health gate
+//! Modified: 2026-10-16 - --simulate-cooldown: replay route attempts under candidate cooldown policies; attempt + cooled-route export
+//! Modified: 2026-10-16 - Replayed opportunities bind trade_size to the quote token via set_quote_layout
+//! Modified: 2026-10-16 - Adaptive pre-screen policy: promoted routes skip the batch pre-screen; policy lines in status
+//! Modified: 2026-10-16 - Burst triage on large-move blocks (spread floor, per-silo top-K, summary logging; status line)
+//! Modified: 2026-10-16 - SHADOW_ROLE: primary decisions feed / detection-only follower diffing against it
+//! Modified: 2026-10-16 - Competition model: EV ranking by bid win probability; races recorded, lost races searched for the winning bid
+//! Modified: 2026-10-16 - Price/gas/tax log writers isolated on writer threads; backpressure in the status output, bounded shutdown flush
+//! Modified: 2026-10-16 - Triangular detection pass (TRIANGULAR_ENABLED): logged each block, simulated in dry run
+//! Modified: 2026-10-16 - Optimal sizing before pre-screen (OPTIMAL_SIZING): resized / dropped routes logged
+//! Modified: 2026-10-16 - Live state API (API_PORT, --features api): per-block snapshot publish, trade counters
+//! Modified: 2026-10-16 - Native token price refreshed each block from the NATIVE_PRICE_PAIR pool (gas USD, tax records)
+//! Modified: 2026-10-16 - Graceful shutdown: in-flight trades drained, mempool monitor cancelled, route cooldowns persisted
+//! Modified: 2026-10-16 - Per-route P&L stats: table every STATS_REPORT_INTERVAL iterations, stats.json snapshot
+//! Modified: 2026-10-16 - Token approval pre-flight at live startup; --approvals status|grant|revoke-all
+//! Modified: 2026-10-16 - Reorg detection (parent hash chain): orphaned-range pools marked stale, poll-resynced before detection
+//! Modified: 2026-10-16 - Live webhook alerts: trade outcomes, HALTs, WS reconnect exhaustion, daily summary
+//! Modified: 2026-10-16 - Quote token balances read at live startup for trade size clamping
+//! Modified: 2026-10-16 - Curve stable pools (CURVE_ENABLED): initial sync, per-block poll, detection-only routes
+//! Modified: 2026-10-16 - Poll sync via one Multicall3 aggregate3 per block when MULTICALL3_ADDRESS is set
+//! Modified: 2026-10-16 - Mempool signals go through mempool::dispatch_signal (adds route cooldown gate)
+//! Modified: 2026-10-16 - Gas oracle fed from eth_feeHistory (shared with gas history) sets the atomic priority fee
+//! Modified: 2026-10-16 - Pool health checks each block: quarantine/reinstate pools, quoter rejections per leg, stats report
+//! Modified: 2026-10-16 - --config / config/{chain}.toml chain TOML layered over .env.{chain}
+//! Modified: 2026-10-16 - MAX_PARALLEL_SUBMISSIONS: disjoint-pool top-K laddered per block, receipts settled as they land
+//! Modified: 2026-10-16 - CROSS_CHAIN_STATE_FILE: per-block price divergence vs another chain (research CSV)
+//! Modified: 2026-10-16 - LOG_FORMAT=json: JSON tracing output + event journal (detections, verify, tx, receipt, cooldown, WS reconnect)
+//! Modified: 2026-10-16 - Gas estimate cache (GAS_CACHE_ENABLED): atomic sends skip estimateGas on known routes
+//! Modified: 2026-10-16 - Stale-state guard: legs older than MAX_POOL_AGE_BLOCKS dropped (STALE), stale count in status
+//! Modified: 2026-10-16 - KyberSwap Elastic whitelist entries ("dex": "KyberElastic"), detection-only partition
+//! Modified: 2026-10-16 - DODO V2 PMM pools ("dodo_ready", "dex": "DodoV2"): initial sync, per-block poll
+//! Modified: 2026-10-16 - Event-sync decoding via pool_events::decode_pool_event; executor applies own receipts' logs
+//! Modified: 2026-10-16 - Opportunity registry: same-scan merge, dedup against recent block-loop/mempool submissions
+//! Modified: 2026-10-16 - Inventory manager: periodic wallet balance batch, rebalance alerts, optional AUTO_REBALANCE swaps
+//! Modified: 2026-10-16 - WS failover: sync provider + block subscription through ProviderManager (RPC_URLS), per-endpoint status
+//! Modified: 2026-10-16 - Price logger output options (PRICE_LOG_FORMAT, gzip of closed days, PRICE_LOG_RETAIN_DAYS)
+//! Modified: 2026-10-16 - Adaptive min profit (MIN_PROFIT_ADAPTIVE): ThresholdModel fed every trade result, read by the detector
+//! Modified: 2026-10-16 - Event sync keeps Algebra's dynamic fee current (ALGEBRA_FEE_REFRESH_BLOCKS globalState() reads)
+//! Modified: 2026-10-16 - Effective per-pair trade limits (whitelist overrides) logged at startup
+//! Modified: 2026-10-16 - --discover: factory scan writes candidate whitelist entries (--min-tvl-usd)
+//! Modified: 2026-10-16 - Per-stage latency histograms (LATENCY_REPORT_INTERVAL, latency.json), block → submit per route
+//! Modified: 2026-10-16 - Mempool backruns bundled via BUNDLE_RPC_URL; bundle hit rate in route stats
+//! Modified: 2026-10-16 - --check-config: configured contracts validated against the chain, pass/fail table
+//! Modified: 2026-10-16 - SPREAD_CONFIRM_BLOCKS: opportunities pass the detector's multi-block spread confirmation
+//! Modified: 2026-10-16 - PRIVATE_KEYS: extra submission wallets added to the executor
+//! Modified: 2026-10-16 - PRICE_LOG_BLOCKS: per-block annotation row (detected, cooldown-suppressed, submitted, net) after execution
+//! Modified: 2026-10-16 - MIDBLOCK_SCAN: simulated pending swaps re-scanned on shadow state, anticipatory routes pre-screened and executed
+//! Modified: 2026-10-16 - Route cooldown per trade-size bucket (ROUTE_COOLDOWN_SIZE_BUCKETS_USD)
+//! Modified: 2026-10-16 - Permit2: ArbExecutor permit support probed at boot (PERMIT2_ADDRESS)
+//! Modified: 2026-10-16 - IPC RPC_URL (local Bor node) for reads; block subscription on WS_RPC_URL
+//! Modified: 2026-10-16 - SHADOW_CONFIG: A/B shadow detector scanned each block, decisions + divergences vs live logged
+//! Modified: 2026-10-16 - Token registry resolved at startup (tokens.json cache); shared with detector labels and tax records
+//! Modified: 2026-10-16 - Block loop beats per new block, writes heartbeat.json each block; watchdog-forced reconnects
+//! Modified: 2026-10-16 - Opportunity archive (OPPORTUNITY_ARCHIVE_ENABLED): detections past cooldown, verdicts, outcome labels
+//! Modified: 2026-10-16 - DODO pools synced only with both DODO_V2_PROXY and DODO_APPROVE set
+//! Modified: 2026-10-17 - Mempool dispatch gets the canary, liquidity guard and whitelist route limits
+//! Modified: 2026-10-17 - Canary decisions sent to the live alerter; canary files written by an isolated writer
 
 use anyhow::Result;
 use clap::Parser;
-use dexarb_bot::arbitrage::{MulticallQuoter, OpportunityDetector, RouteCooldown, TradeExecutor, VerifiedOpportunity};
-use dexarb_bot::config::load_config_from_file;
+use dexarb_bot::arbitrage::approvals::{approval_targets, ApprovalAction};
+use dexarb_bot::arbitrage::balance;
+use dexarb_bot::arbitrage::competition;
+use dexarb_bot::arbitrage::cooldown_sim::{self, AttemptHistory, CooldownPolicy};
+use dexarb_bot::arbitrage::migration::{canonical_pool, watched_pools, WatchedPool};
+use dexarb_bot::arbitrage::gas_cache::GasEstimateCache;
+use dexarb_bot::arbitrage::slippage::route_key;
+use dexarb_bot::arbitrage::inventory::{InventoryConfig, InventoryManager};
+use dexarb_bot::arbitrage::stranded::token_universe;
+use dexarb_bot::arbitrage::threshold::ThresholdModel;
+use dexarb_bot::arbitrage::execution_constraints::Candidate as ConstraintCandidate;
+use dexarb_bot::arbitrage::{
+    AttemptRecord, BurstConfig, BurstTransition, BurstTriage, CanaryConfig, CompetitionConfig, CompetitionModel, EvCandidate, LostRace, PrescreenPolicy, PrescreenPolicyConfig, CanaryTracker, InflightJournal, LiquidityGuard, LiquidityVerdict, MigrationConfig, MulticallQuoter, NextStep, OpportunityDetector, OptimalSizer,
+    PoolMigrationMonitor, PreflightBatcher, RecoveryPolicy, TokenMetadataValidator, TokenValidationConfig, PrescreenExporter, ExecutionConstraintAnalyzer, CooldownSource, OpportunityArchive, RouteCooldown, StatsTracker, StrandedConfig, StrandedFundsMonitor,
+    SizingVerdict, SlippageConfig, SlippageTracker, TradeExecutor, TradeOutcome, VerifiedOpportunity,
+    ParallelBatch, select_disjoint, OpportunityRegistry,
+};
+use dexarb_bot::alerts::AlertDispatcher;
+use dexarb_bot::live_alerts::{LiveAlertConfig, LiveAlerter};
+use dexarb_bot::api::ApiPublisher;
+use dexarb_bot::anomaly::{AnomalyConfig, AnomalyMonitor};
+use dexarb_bot::chaos;
+use dexarb_bot::ab_shadow::AbShadow;
+use dexarb_bot::config::{load_config_with_toml, load_shadow_config};
+use dexarb_bot::token_registry::{self, TokenRegistry};
+use dexarb_bot::config_check::{self, CheckStatus};
+use dexarb_bot::cross_chain::{self, CrossChainMonitor};
+use dexarb_bot::events::{BotEvent, EventJournal, LogFormat};
+use dexarb_bot::deadman::{self, DeadmanConfig, DeadmanState};
+use dexarb_bot::shadow::{self, BlockDecisions, DecisionFeed, ShadowComparator, ShadowRole};
+use dexarb_bot::filters::discovery;
+use dexarb_bot::filters::pool_revenue::{self, RevenueConfig};
+use dexarb_bot::filters::whitelist::persist_migration;
 use dexarb_bot::filters::WhitelistFilter;
-use dexarb_bot::mempool::{MempoolMode, MempoolSignal};
-use dexarb_bot::pool::{PoolStateManager, V2PoolSyncer, V3PoolSyncer, SUSHI_V3_FEE_TIERS, V3_FEE_TIERS};
-use dexarb_bot::types::{ArbitrageOpportunity, DexType, PoolState, TradingPair, V3PoolState};
+use dexarb_bot::mempool::midblock;
+use dexarb_bot::mempool::{
+    dispatch_signal, BundleSubmitter, DispatchContext, MempoolDispatch, MempoolMode, MempoolSignal, MidblockBudget,
+    MidblockUpdate,
+};
+use dexarb_bot::native_price::NativePriceUpdater;
+use dexarb_bot::pool::events::{self as pool_events, PoolEvent};
+use dexarb_bot::pool::{
+    CurvePoolSyncer, DodoPoolSyncer, HealthTransition, MulticallSyncer, PoolHealth, PoolHealthConfig, PoolStateManager, ReorgDetector, V2PoolSyncer, V3PoolSyncer, KYBER_ELASTIC_FEE_TIERS, SUSHI_V3_FEE_TIERS, V3_FEE_TIERS,
+};
+use dexarb_bot::pool::v3_syncer::read_algebra_fees;
+use dexarb_bot::types::{
+    format_usd, ArbitrageOpportunity, CurvePoolState, DexType, DodoPoolState, PoolState, QuotedLegs, TradeResult,
+    TradingPair, V3PoolState,
+};
 use std::collections::HashMap;
-use dexarb_bot::price_logger::PriceLogger;
+use dexarb_bot::io_writer::StreamContract;
+use dexarb_bot::latency::{LatencyRecorder, Stage};
+use dexarb_bot::price_logger::{BlockAnnotations, PriceLogOptions, PriceLogger};
+use dexarb_bot::retry::{self, Backoffed};
+use dexarb_bot::provider_manager::{EndpointFailure, ProviderManager};
+use dexarb_bot::shutdown::{ShutdownController, SHUTDOWN_DRAIN_TIMEOUT};
+use dexarb_bot::rpc_bench::{self, BenchCall, BenchConfig, BenchReport, BenchWorkload, EndpointResult};
+use dexarb_bot::telemetry::{StatusCounters, Telemetry, TelemetryConfig};
+use dexarb_bot::transport;
+use dexarb_bot::gas_logger::{BidParams, GasHistoryLogger, FEE_HISTORY_PERCENTILES};
+use dexarb_bot::gas_oracle::GasOracle;
+use dexarb_bot::arbitrage::executor::{router_address, ATOMIC_PRIORITY_FEE_GWEI};
+use dexarb_bot::watchdog::{self, rpc_timeout, WatchdogConfig, HOT_RPC_TIMEOUT};
 use ethers::prelude::*;
 use futures::StreamExt;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::mpsc;
 use tokio::time::{timeout, Duration};
-use tracing::{error, info, warn, Level};
+use tracing::{debug, error, info, warn, Level};
 use tracing_subscriber;
 use chrono;
 
+/// How long shutdown waits for the price/gas history writers to drain
+const LOG_WRITER_SHUTDOWN_FLUSH: Duration = Duration::from_secs(5);
+
+/// Default location of the per-chain TOML configs
+const CHAIN_CONFIG_DIR: &str = "/home/botuser/bots/dexarb/config";
+
 /// DEX Arbitrage Bot — Multi-Chain (Polygon, Base)
 #[derive(Parser)]
 #[command(name = "dexarb-bot")]
@@ -56,16 +199,87 @@ struct Args {
     /// Chain to run on (polygon, base)
     #[arg(short, long, env = "CHAIN", default_value = "polygon")]
     chain: String,
+
+    /// Chain TOML config, layered over .env.{chain} (default: config/{chain}.toml when present)
+    #[arg(long, env = "CONFIG_TOML", value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Write the pool fee revenue report (whitelist add/remove suggestions) and exit
+    #[arg(long)]
+    pool_revenue_report: bool,
+
+    /// Extra candidate pools for the revenue report (JSON array of {address, pair, dex, fee_tier})
+    #[arg(long)]
+    candidates: Option<String>,
+
+    /// Revenue report lookback window in blocks
+    #[arg(long, default_value_t = 43_200)]
+    lookback_blocks: u64,
+
+    /// Run the revenue report even while a live bot's heartbeat is fresh
+    #[arg(long)]
+    force: bool,
+
+    /// Scan the configured factories for pools of the configured pairs, write
+    /// candidate whitelist entries and exit
+    #[arg(long)]
+    discover: bool,
+
+    /// Minimum estimated TVL (USD) for a discovered pool to become a candidate
+    #[arg(long, default_value_t = 100_000.0)]
+    min_tvl_usd: f64,
+
+    /// Benchmark these RPC endpoints (repeatable) with the bot's call mix, write the report and exit
+    #[arg(long = "bench-rpc", value_name = "URL")]
+    bench_rpc: Vec<String>,
+
+    /// Requests per call type per endpoint
+    #[arg(long, default_value_t = 20)]
+    bench_samples: usize,
+
+    /// Opportunities per benchmarked multicall pre-screen batch
+    #[arg(long, default_value_t = 4)]
+    bench_prescreen_size: usize,
+
+    /// Also time eth_sendRawTransaction with zero-value self-transfers (refused on mainnets)
+    #[arg(long)]
+    bench_send: bool,
+
+    /// newHeads lag reference endpoint (default: RPC_URL)
+    #[arg(long)]
+    bench_reference: Option<String>,
+
+    /// Blocks to observe for the newHeads lag
+    #[arg(long, default_value_t = 10)]
+    bench_lag_blocks: usize,
+
+    /// Replay recorded route attempts under this cooldown policy (initial[:factor[:max]] blocks,
+    /// repeatable; the live ROUTE_COOLDOWN_BLOCKS policy is always included), write the report and exit
+    #[arg(long = "simulate-cooldown", value_name = "POLICY")]
+    simulate_cooldown: Vec<CooldownPolicy>,
+
+    /// First day replayed (YYYYMMDD, default yesterday)
+    #[arg(long)]
+    sim_from: Option<String>,
+
+    /// Last day replayed (YYYYMMDD, default --sim-from)
+    #[arg(long)]
+    sim_to: Option<String>,
+
+    /// Check (status), grant or revoke (revoke-all) the token allowances the
+    /// bot's routers and ArbExecutor use, print the summary and exit
+    #[arg(long, value_name = "ACTION")]
+    approvals: Option<ApprovalAction>,
+
+    /// Validate the configured routers, factories, quoters, quote tokens and
+    /// ArbExecutor against the chain, print a pass/fail table and exit
+    /// (nonzero on any failure)
+    #[arg(long)]
+    check_config: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_max_level(Level::INFO)
-        .with_target(false)
-        .init();
-
     // Parse CLI args (--chain polygon|base, or CHAIN env var)
     let args = Args::parse();
     let chain = args.chain.to_lowercase();
@@ -76,12 +290,33 @@ async fn main() -> Result<()> {
         _ => anyhow::bail!("Unsupported chain: '{}'. Supported: polygon, base", chain),
     }
 
-    info!("DEX Arbitrage Bot Starting — chain: {} (V3+V2 cross-protocol arb)...", chain);
-
-    // Load chain-specific .env file (e.g., .env.polygon, .env.base)
+    // Load chain-specific .env file (e.g., .env.polygon, .env.base), with
+    // config/{chain}.toml on top when present
     let env_file = format!(".env.{}", chain);
-    let config = load_config_from_file(&env_file)?;
-    info!("Configuration loaded from {} (chain_id: {})", env_file, config.chain_id);
+    let toml_file = args
+        .config
+        .clone()
+        .or_else(|| Some(PathBuf::from(format!("{}/{}.toml", CHAIN_CONFIG_DIR, chain))).filter(|p| p.exists()));
+    let mut config = load_config_with_toml(&env_file, toml_file.as_deref())?;
+
+    // Initialize logging (LOG_FORMAT comes from the config, so this follows it)
+    let log_format = LogFormat::parse(&config.log_format);
+    let subscriber = tracing_subscriber::fmt().with_max_level(Level::INFO).with_target(false);
+    match log_format {
+        LogFormat::Json => subscriber.json().init(),
+        LogFormat::Text => subscriber.init(),
+    }
+
+    info!("DEX Arbitrage Bot Starting — chain: {} (V3+V2 cross-protocol arb)...", chain);
+    let shadow_role = ShadowRole::parse(&config.shadow_role);
+    if shadow_role == ShadowRole::Follower && config.live_mode {
+        warn!("SHADOW_ROLE=follower: LIVE_MODE ignored — a follower never trades");
+        config.live_mode = false;
+    }
+    match &toml_file {
+        Some(path) => info!("Configuration loaded from {} + {} (chain_id: {})", path.display(), env_file, config.chain_id),
+        None => info!("Configuration loaded from {} (chain_id: {})", env_file, config.chain_id),
+    }
     info!("RPC URL: {}", &config.rpc_url[..40.min(config.rpc_url.len())]);
     info!("Quote token: {:?}", config.quote_token_address);
     if let Some(native) = config.quote_token_address_native {
@@ -91,23 +326,139 @@ async fn main() -> Result<()> {
     info!("Trading pairs: {}", config.pairs.len());
     info!("Poll interval: {}ms", config.poll_interval_ms);
 
+    // Cooldown policy simulator: offline replay of the research files, no RPC
+    if !args.simulate_cooldown.is_empty() {
+        let parse_day = |s: &str| chrono::NaiveDate::parse_from_str(s, "%Y%m%d")
+            .map_err(|e| anyhow::anyhow!("bad date '{}' (YYYYMMDD): {}", s, e));
+        let from = match args.sim_from {
+            Some(ref s) => parse_day(s)?,
+            None => chrono::Utc::now().date_naive() - chrono::Duration::days(1),
+        };
+        let to = match args.sim_to {
+            Some(ref s) => parse_day(s)?,
+            None => from,
+        };
+        let research_dir = format!("/home/botuser/bots/dexarb/data/{}/research", config.chain_name);
+        let history = AttemptHistory::load(Path::new(&research_dir), from, to, config.estimated_gas_cost_usd)?;
+        if history.is_empty() {
+            anyhow::bail!("No route attempts recorded in {} for {}..={} (PRESCREEN_EXPORT_ENABLED)", research_dir, from, to);
+        }
+        let mut policies = vec![CooldownPolicy::live(config.route_cooldown_blocks)];
+        for policy in &args.simulate_cooldown {
+            if !policies.contains(policy) {
+                policies.push(*policy);
+            }
+        }
+        let report = cooldown_sim::simulate(&history, &policies, from, to);
+        info!("Cooldown policy simulation: {} events, {}..={} (live policy {})\n{}",
+              report.events, report.from, report.to, policies[0], cooldown_sim::format_table(&report));
+        let path = cooldown_sim::write_report(&research_dir, &report)?;
+        info!("Cooldown simulation report written to {}", path.display());
+        return Ok(());
+    }
+
+    // SIGINT/SIGTERM cancel the shutdown token: retry waits end early, no new
+    // opportunities are taken and the block loop exits at its next event once
+    // in-flight trades finish. A second signal exits immediately.
+    let shutdown = ShutdownController::new(retry::shutdown_token());
+    shutdown.install_signal_handlers()?;
+
+    // WS endpoints (RPC_URL + RPC_URLS): per-endpoint health, rotation on failure.
+    // Connect + subscribe failures count per endpoint (~4 min of retries each);
+    // the bot exits only once every endpoint is exhausted.
+    // An IPC RPC_URL (local Bor node) carries reads; WS_RPC_URL the subscriptions.
+    let mut ws_endpoints = ProviderManager::new(config.ws_endpoints(), retry::ws_reconnect(), shutdown.token())
+        .with_ws_fallback(config.ws_rpc_url.clone());
+
     // Initialize provider for RPC calls (sync, Quoter, execution).
     // A separate WS connection for block subscription is created in the reconnect loop below.
-    info!("Connecting to {} via WebSocket (RPC provider)...", config.chain_name);
-    let provider = Provider::<Ws>::connect(&config.rpc_url).await?;
+    let transport_kind = if transport::is_ipc(ws_endpoints.current_url()) { "IPC" } else { "WebSocket" };
+    info!("Connecting to {} via {} (RPC provider, {} endpoint(s))...", config.chain_name, transport_kind, ws_endpoints.len());
+    let provider = ws_endpoints.connect().await?;
     let provider = Arc::new(provider);
 
     // Verify connection
     let block = provider.get_block_number().await?;
     info!("Connected! Current block: {}", block);
 
+    // Config validation against the chain: reads only, before anything else is loaded
+    if args.check_config {
+        let report = config_check::run(provider.as_ref(), &config).await;
+        info!(
+            "Config check: {} pass, {} fail, {} skipped\n{}",
+            report.count(CheckStatus::Pass), report.failures(), report.count(CheckStatus::Skip), report.format_table()
+        );
+        if report.failures() > 0 {
+            anyhow::bail!("{} config check(s) failed", report.failures());
+        }
+        return Ok(());
+    }
+
     // Load whitelist (chain-specific default: config/{chain}/pools_whitelist.json)
     let default_whitelist = format!("/home/botuser/bots/dexarb/config/{}/pools_whitelist.json", config.chain_name);
     let whitelist_path = config.whitelist_file.as_deref()
         .unwrap_or(&default_whitelist);
-    let whitelist = WhitelistFilter::load(whitelist_path)?;
+    let mut whitelist = WhitelistFilter::load(whitelist_path)?;
     info!("Whitelist loaded: {} active pools from {}", whitelist.active_pool_count(), whitelist_path);
 
+    // On-demand pool fee revenue report: sample Swap logs, write suggestions, exit
+    if args.pool_revenue_report {
+        let data_dir = format!("/home/botuser/bots/dexarb/data/{}", config.chain_name);
+        let heartbeat = PathBuf::from(format!("{}/heartbeat.json", data_dir));
+        if !args.force && pool_revenue::live_bot_running(&heartbeat, pool_revenue::LIVE_BOT_HEARTBEAT_MAX_AGE) {
+            anyhow::bail!("Live bot heartbeat is fresh ({}) — not sweeping logs during trading (use --force)", heartbeat.display());
+        }
+        let extra = match args.candidates {
+            Some(ref path) => pool_revenue::load_candidates(path)?,
+            None => Vec::new(),
+        };
+        let pools = pool_revenue::sample_set(&whitelist.raw, &extra);
+        let revenue_config = RevenueConfig { lookback_blocks: args.lookback_blocks, ..RevenueConfig::default() };
+        let report = pool_revenue::run(Arc::clone(&provider), &config, &pools, &revenue_config).await?;
+        for row in report.pools.iter().take(25) {
+            info!(
+                "  {} {:<14} {:<12} fee={:<5} swaps={:<6} vol/day={} fees/day={}",
+                if row.covered { "●" } else { "○" }, row.pair, row.dex, row.fee_tier, row.swaps,
+                row.daily_volume_usd.map(format_usd).unwrap_or_else(|| "n/a".to_string()),
+                row.daily_fee_usd.map(format_usd).unwrap_or_else(|| "n/a".to_string()),
+            );
+        }
+        for s in &report.suggestions {
+            info!("  SUGGEST {} {} {} — {}", s.action.to_uppercase(), s.pair, s.address, s.reason);
+        }
+        let path = pool_revenue::write_report(&format!("{}/research", data_dir), &report)?;
+        info!("Pool revenue report written: {}", path.display());
+        return Ok(());
+    }
+
+    // Whitelist discovery: factory lookups for every configured pair, write candidates, exit
+    if args.discover {
+        let found = discovery::scan(Arc::clone(&provider), &config).await?;
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let report = discovery::build_report(&found, &whitelist.raw, args.min_tvl_usd, &today);
+        for c in &report.candidates {
+            info!(
+                "  CANDIDATE {:<14} {:<12} fee={:<5} {} tvl={} price={:.6} vs ref {}",
+                c.pool.pair, c.pool.dex, c.pool.fee_tier, c.pool.address, format_usd(c.tvl_usd), c.price,
+                c.price_vs_reference_pct.map(|p| format!("{:+.2}%", p)).unwrap_or_else(|| "n/a".to_string()),
+            );
+        }
+        let research_dir = format!("/home/botuser/bots/dexarb/data/{}/research", config.chain_name);
+        let path = discovery::write_report(&research_dir, &report)?;
+        info!("Discovery: {} candidate(s) ≥ {} of {} pools found, written to {}",
+              report.candidates.len(), format_usd(args.min_tvl_usd), report.pools_found, path.display());
+        return Ok(());
+    }
+
+    // Canary mode: pools carrying canary_until / canary_trades trade at capped size
+    // until their window closes, then are auto-promoted or demoted to observation.
+    let mut canary = CanaryTracker::from_whitelist(&whitelist.raw, CanaryConfig::from_bot_config(&config))
+        .with_persistence(
+            &format!("/home/botuser/bots/dexarb/data/{}/canary", config.chain_name),
+            whitelist_path,
+            StreamContract::canary(&config),
+        );
+
     // Initial V3 sync: discover full state for each whitelisted pool
     let mut v3_syncer = V3PoolSyncer::new(Arc::clone(&provider), config.clone());
     info!("Initial V3 sync: discovering {} whitelisted pools...", whitelist.active_pool_count());
@@ -128,6 +479,10 @@ async fn main() -> Result<()> {
                 .map(|(_, dt)| *dt),
             // QuickSwap V3 (Algebra): no fee tiers — single pool per pair, dynamic fees
             "QuickswapV3" => Some(DexType::QuickswapV3),
+            // KyberSwap Elastic: detection-only (fee_tier in V3 units, swapFeeUnits × 10)
+            "KyberElastic" => KYBER_ELASTIC_FEE_TIERS.iter()
+                .find(|(fee, _)| *fee == wl_pool.fee_tier)
+                .map(|(_, dt)| *dt),
             other => {
                 warn!("Unknown dex '{}' for {} — skipping", other, wl_pool.pair);
                 continue;
@@ -213,6 +568,183 @@ async fn main() -> Result<()> {
         info!("Initial V2 sync complete: {}/{} pools discovered", v2_pools.len(), v2_ready_whitelist.len());
     }
 
+    // Initial Curve sync (CURVE_ENABLED): whitelisted "curve_ready" stable
+    // pools. Coins and decimals are discovered on-chain; pools with 3+ coins
+    // track the two tokens the matching PAIRS entry names.
+    let curve_syncer = CurvePoolSyncer::new(Arc::clone(&provider));
+    // Poll sync in one aggregate3 per block (MULTICALL3_ADDRESS); per-pool otherwise
+    let multicall_syncer = config.multicall3_address.map(|address| {
+        info!("Poll sync via Multicall3 at {:?}", address);
+        MulticallSyncer::new(Arc::clone(&provider), address)
+    });
+    let mut curve_pools: Vec<CurvePoolState> = Vec::new();
+    if config.curve_enabled {
+        let curve_whitelist: Vec<_> = whitelist.raw.whitelist.pools.iter()
+            .filter(|p| p.status == "curve_ready")
+            .collect();
+        info!("Initial Curve sync: {} curve_ready pools to discover...", curve_whitelist.len());
+
+        for wl_pool in &curve_whitelist {
+            if wl_pool.dex != "CurveStable" {
+                warn!("Unknown Curve dex '{}' for {} — skipping", wl_pool.dex, wl_pool.pair);
+                continue;
+            }
+            let pool_address: Address = match wl_pool.address.parse() {
+                Ok(addr) => addr,
+                Err(e) => {
+                    warn!("Invalid Curve address '{}' for {} — skipping: {}", wl_pool.address, wl_pool.pair, e);
+                    continue;
+                }
+            };
+            let tokens = config.pairs.iter()
+                .find(|p| p.symbol == wl_pool.pair)
+                .and_then(|p| Some((p.token0.parse::<Address>().ok()?, p.token1.parse::<Address>().ok()?)));
+
+            match curve_syncer.sync_pool_by_address(pool_address, tokens).await {
+                Ok(mut pool_state) => {
+                    pool_state.pair.symbol = wl_pool.pair.clone();
+                    info!(
+                        "  Curve synced: {} | {} coins, A={} fee={:.2}% price={:.6}",
+                        wl_pool.pair, pool_state.balances.len(), pool_state.amp,
+                        pool_state.fee_percent(), pool_state.price_adjusted()
+                    );
+                    curve_pools.push(pool_state);
+                }
+                Err(e) => {
+                    warn!("  Curve failed: {} ({}): {}", wl_pool.pair, wl_pool.address, e);
+                }
+            }
+        }
+        info!("Initial Curve sync complete: {}/{} pools discovered (detection-only)", curve_pools.len(), curve_whitelist.len());
+    }
+
+    // Initial DODO sync: whitelisted "dodo_ready" PMM pools. Base/quote tokens
+    // and decimals are discovered on-chain; routes trade two-tx only.
+    let dodo_syncer = DodoPoolSyncer::new(Arc::clone(&provider));
+    let mut dodo_pools: Vec<DodoPoolState> = Vec::new();
+    let dodo_whitelist: Vec<_> = whitelist.raw.whitelist.pools.iter()
+        .filter(|p| p.status == "dodo_ready")
+        .collect();
+    if !dodo_whitelist.is_empty() && !config.dodo_configured() {
+        warn!(
+            "{} dodo_ready pool(s) left out of detection — DODO routes need both DODO_V2_PROXY and DODO_APPROVE",
+            dodo_whitelist.len()
+        );
+    } else if !dodo_whitelist.is_empty() {
+        info!("Initial DODO sync: {} dodo_ready pools to discover...", dodo_whitelist.len());
+
+        for wl_pool in &dodo_whitelist {
+            if wl_pool.dex != "DodoV2" {
+                warn!("Unknown DODO dex '{}' for {} — skipping", wl_pool.dex, wl_pool.pair);
+                continue;
+            }
+            let pool_address: Address = match wl_pool.address.parse() {
+                Ok(addr) => addr,
+                Err(e) => {
+                    warn!("Invalid DODO address '{}' for {} — skipping: {}", wl_pool.address, wl_pool.pair, e);
+                    continue;
+                }
+            };
+
+            match dodo_syncer.sync_pool_by_address(pool_address).await {
+                Ok(mut pool_state) => {
+                    pool_state.pair.symbol = wl_pool.pair.clone();
+                    info!(
+                        "  DODO synced: {} | R={:?} K={} fee={:.2}% price={:.6}",
+                        wl_pool.pair, pool_state.pmm.r, pool_state.pmm.k,
+                        pool_state.fee_percent(), pool_state.price_adjusted()
+                    );
+                    dodo_pools.push(pool_state);
+                }
+                Err(e) => {
+                    warn!("  DODO failed: {} ({}): {}", wl_pool.pair, wl_pool.address, e);
+                }
+            }
+        }
+        info!("Initial DODO sync complete: {}/{} pools discovered", dodo_pools.len(), dodo_whitelist.len());
+        if config.arb_executor_address.is_some() && !dodo_pools.is_empty() {
+            warn!("DODO routes have no ArbExecutor leg (atomic support deferred) — they execute two-tx via DODO_V2_PROXY");
+        }
+    }
+
+    // On-demand RPC benchmark: the synced pools define the request mix; no trading
+    if !args.bench_rpc.is_empty() {
+        let bench = BenchConfig {
+            samples: args.bench_samples,
+            prescreen_size: args.bench_prescreen_size,
+            send_raw: args.bench_send,
+            lag_blocks: args.bench_lag_blocks,
+            ..BenchConfig::default()
+        };
+        let workload = BenchWorkload::from_pools(&v3_pools, &v2_pools, &config, bench.prescreen_size);
+        let wallet = config.private_key.parse::<LocalWallet>().ok();
+        info!(
+            "RPC benchmark: {} endpoint(s), {} samples, {} log-filter pools, {} pre-screen opportunities",
+            args.bench_rpc.len(), bench.samples, workload.pools.len(), workload.opportunities.len()
+        );
+
+        let mut endpoints = Vec::new();
+        for url in &args.bench_rpc {
+            info!("  Benchmarking {}...", rpc_bench::endpoint_label(url));
+            let result = if rpc_bench::is_ws(url) {
+                match Provider::<Ws>::connect(url).await {
+                    Ok(p) => rpc_bench::bench_endpoint(url, Arc::new(p), &config, &workload, &bench, wallet.as_ref()).await,
+                    Err(e) => Err(e.into()),
+                }
+            } else {
+                match Provider::<Http>::try_from(url.as_str()) {
+                    Ok(p) => rpc_bench::bench_endpoint(url, Arc::new(p), &config, &workload, &bench, wallet.as_ref()).await,
+                    Err(e) => Err(e.into()),
+                }
+            };
+            endpoints.push(result.unwrap_or_else(|e| {
+                warn!("  {} failed: {}", rpc_bench::endpoint_label(url), e);
+                let mut failed = EndpointResult { endpoint: rpc_bench::endpoint_label(url), ..Default::default() };
+                for call in BenchCall::ALL {
+                    failed.skipped.insert(call, format!("endpoint failed: {}", e));
+                }
+                failed
+            }));
+        }
+
+        // newHeads lag: all ws endpoints subscribed concurrently against the reference
+        let reference = args.bench_reference.clone().unwrap_or_else(|| config.rpc_url.clone());
+        let ws_urls: Vec<String> = args.bench_rpc.iter().filter(|u| rpc_bench::is_ws(u)).cloned().collect();
+        if !ws_urls.is_empty() && rpc_bench::is_ws(&reference) {
+            info!("  Observing {} blocks for newHeads lag...", bench.lag_blocks);
+            let lags = rpc_bench::bench_block_lag(&ws_urls, &reference, bench.lag_blocks).await;
+            let ws_endpoints = args.bench_rpc.iter().zip(endpoints.iter_mut()).filter(|(u, _)| rpc_bench::is_ws(u));
+            for ((_, endpoint), lag) in ws_endpoints.zip(lags) {
+                endpoint.skipped.remove(&BenchCall::BlockLag);
+                match lag {
+                    Ok(stats) => { endpoint.calls.insert(BenchCall::BlockLag, stats); }
+                    Err(e) => { endpoint.skipped.insert(BenchCall::BlockLag, e.to_string()); }
+                }
+            }
+        }
+        for (url, endpoint) in args.bench_rpc.iter().zip(endpoints.iter_mut()) {
+            if !endpoint.calls.contains_key(&BenchCall::BlockLag) && !endpoint.skipped.contains_key(&BenchCall::BlockLag) {
+                let reason = if rpc_bench::is_ws(url) { "reference is not ws/wss" } else { "http endpoint (no subscriptions)" };
+                endpoint.skipped.insert(BenchCall::BlockLag, reason.to_string());
+            }
+        }
+
+        let report = BenchReport {
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            chain: config.chain_name.clone(),
+            samples: bench.samples,
+            log_filter_pools: workload.pools.len(),
+            prescreen_opportunities: workload.opportunities.len(),
+            reference: rpc_bench::endpoint_label(&reference),
+            endpoints,
+        };
+        for line in rpc_bench::format_table(&report) {
+            info!("{}", line);
+        }
+        rpc_bench::write_report(&format!("/home/botuser/bots/dexarb/data/{}/research", config.chain_name), &report)?;
+        return Ok(());
+    }
+
     // Initialize pool state manager and populate with initial sync data
     let state_manager = PoolStateManager::new();
     for pool in &v3_pools {
@@ -221,9 +753,15 @@ async fn main() -> Result<()> {
     for pool in &v2_pools {
         state_manager.update_pool(pool.clone());
     }
+    for pool in &curve_pools {
+        state_manager.update_curve_pool(pool.clone());
+    }
+    for pool in &dodo_pools {
+        state_manager.update_dodo_pool(pool.clone());
+    }
     info!(
-        "Pool state manager initialized: {} V3 + {} V2 pools",
-        v3_pools.len(), v2_pools.len()
+        "Pool state manager initialized: {} V3 + {} V2 + {} Curve + {} DODO pools",
+        v3_pools.len(), v2_pools.len(), curve_pools.len(), dodo_pools.len()
     );
 
     // Startup cross-check: compare V2 and V3 prices for same pairs.
@@ -275,8 +813,23 @@ async fn main() -> Result<()> {
         info!("=== End cross-check ===");
     }
 
-    // Initialize opportunity detector
-    let detector = OpportunityDetector::new(config.clone(), state_manager.clone());
+    // Token metadata of every configured token: tokens.json, else symbol()/decimals()
+    // on-chain. A token that can't be read stops the bot here, not in a tax record.
+    let token_registry = Arc::new(
+        TokenRegistry::resolve(provider.as_ref(), &config, &token_registry::tokens_path(&config)).await?,
+    );
+
+    // Initialize opportunity detector; with MIN_PROFIT_ADAPTIVE it shares the
+    // threshold model that every trade result below updates
+    let threshold_model = config.min_profit_adaptive.then(|| ThresholdModel::from_config(&config));
+    let mut detector = OpportunityDetector::new(config.clone(), state_manager.clone())
+        .with_token_registry(Arc::clone(&token_registry));
+    if let Some(ref model) = threshold_model {
+        info!("Adaptive min profit ENABLED: MIN_PROFIT_USD ${:.2} + r/(1-r) × avg gas per route, clamped to ${:.2}..${:.2}",
+              config.min_profit_usd, config.min_profit_floor_usd, config.min_profit_ceiling_usd);
+        detector = detector.with_threshold_model(model.clone());
+    }
+    detector.log_pair_limits();
     info!("Opportunity detector initialized");
 
     // Initialize trade executor
@@ -285,8 +838,26 @@ async fn main() -> Result<()> {
         .parse::<LocalWallet>()?
         .with_chain_id(config.chain_id);
     info!("Wallet loaded: {:?}", wallet.address());
+    let wallet_address = wallet.address();
 
     let mut executor = TradeExecutor::new(Arc::clone(&provider), wallet, config.clone());
+    executor.set_token_registry(Arc::clone(&token_registry));
+    for key in config.private_keys.iter().skip(1) {
+        executor.add_wallet(key.parse::<LocalWallet>()?);
+    }
+    if config.private_keys.len() > 1 {
+        info!("Submission wallets: {} ({:?})", config.private_keys.len(), executor.wallet_addresses());
+    }
+    // Per-stage latency histograms shared by the block loop, pre-screen and executor
+    let latency = Arc::new(
+        LatencyRecorder::new().with_snapshot(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name)),
+    );
+    executor.set_latency_recorder(Arc::clone(&latency));
+    // Own receipts' Swap/Sync logs applied right away (no re-detect of a closed spread)
+    executor.set_pool_state_manager(state_manager.clone());
+    // Live native token price for gas USD + tax records (NATIVE_PRICE_PAIR pool,
+    // NATIVE_TOKEN_PRICE_USD fallback), refreshed each block after pool sync
+    let mut native_price_updater = NativePriceUpdater::new(&config, executor.native_price());
 
     // Set live/dry run mode based on config
     if config.live_mode {
@@ -300,35 +871,227 @@ async fn main() -> Result<()> {
     if let Some(ref private_url) = config.private_rpc_url {
         executor.set_private_rpc(private_url)?;
     }
+    // Mempool backruns go to the searcher relay as a bundle behind the trigger (BUNDLE_RPC_URL)
+    if let Some(bundle) = BundleSubmitter::from_config(&config)? {
+        info!("Bundle relay: {} ({})", config.bundle_rpc_url.as_deref().unwrap_or_default(), bundle.method());
+        executor.set_bundle_submitter(bundle);
+    }
+
+    // Every (token, spender) allowance the synced pools' routes spend through
+    let approval_pools: Vec<(TradingPair, DexType)> = v3_pools.iter().map(|p| (p.pair.clone(), p.dex))
+        .chain(v2_pools.iter().map(|p| (p.pair.clone(), p.dex)))
+        .collect();
+    let approval_targets = approval_targets(&config, &approval_pools, |dex| router_address(&config, dex));
+
+    if let Some(action) = args.approvals {
+        if action != ApprovalAction::Status {
+            executor.set_dry_run(false);
+        }
+        info!("Approvals {:?}: {} (token, spender) pairs", action, approval_targets.len());
+        let summary = executor.manage_approvals(&approval_targets, action).await?;
+        info!("Approvals: {}", summary);
+        return Ok(());
+    }
+
+    // Failure injection (CHAOS_MODE=true only) — rehearsal against a fork/testnet
+    let chaos_injector = chaos::injector_from_config(&config)?;
+    if let Some(ref injector) = chaos_injector {
+        executor.set_fault_injector(injector.clone());
+    }
 
     // Initialize Multicall3 batch Quoter pre-screener (Phase 2.1)
-    // Batch-verifies all detected opportunities in 1 RPC call before execution.
+    // Batch-verifies all detected opportunities in 2 RPC calls before execution.
     // Falls back to unfiltered execution if Multicall fails.
-    let multicall_quoter = MulticallQuoter::new(Arc::clone(&provider), &config)?;
+    let multicall_quoter = MulticallQuoter::new(Arc::clone(&provider), &config)?.with_latency(Arc::clone(&latency));
+
+    // Block-pinned preflight: one aggregate3 per execution attempt replaces the
+    // executor's separate Quoter/allowance reads. Falls back to per-call checks if unavailable.
+    if config.preflight_batch_enabled {
+        match PreflightBatcher::new(Arc::clone(&provider), &config) {
+            Ok(batcher) => executor.set_preflight(batcher),
+            Err(e) => warn!("Preflight batcher unavailable: {} — using per-call pre-checks", e),
+        }
+    } else {
+        info!("Preflight batch DISABLED — executor uses per-call pre-checks");
+    }
 
     // Enable tax logging for IRS compliance
     if config.tax_log_enabled {
         let tax_dir = config.tax_log_dir.clone()
             .unwrap_or_else(|| format!("/home/botuser/bots/dexarb/data/{}/tax", config.chain_name));
-        match executor.enable_tax_logging(&tax_dir) {
-            Ok(_) => info!("Tax logging enabled: {}", tax_dir),
-            Err(e) => warn!("Failed to enable tax logging: {} - trades will NOT be logged for taxes!", e),
+        // TAX_LOG_STRICT: refuse to start rather than trade unrecorded
+        executor.init_tax_logging(&tax_dir, config.tax_log_strict)?;
+        if config.tax_log_strict {
+            info!("Tax logging STRICT: unwritten records halt live submissions");
         }
+    } else if config.tax_log_strict && config.live_mode {
+        anyhow::bail!("TAX_LOG_STRICT=true requires TAX_LOG_ENABLED=true in live mode");
     } else {
         warn!("Tax logging DISABLED - trades will NOT be logged for IRS compliance!");
     }
 
+    // Finality: landed trades stay provisional until CONFIRMATION_DEPTH deep
+    executor.set_trade_ledger(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name));
+
+    // Structured event journal (LOG_FORMAT=json)
+    let event_journal = (log_format == LogFormat::Json).then(|| {
+        let dir = format!("/home/botuser/bots/dexarb/data/{}", config.chain_name);
+        info!("Event journal: {}/events_YYYYMMDD.jsonl", dir);
+        EventJournal::new(&dir, &config.chain_name, StreamContract::events(&config))
+    });
+    if let Some(ref journal) = event_journal {
+        executor.set_event_journal(journal.clone());
+    }
+    if config.confirmation_depth > 0 {
+        info!("Trade finality: records provisional until {} confirmations", config.confirmation_depth);
+    } else {
+        info!("Trade finality DISABLED — records final at first receipt");
+    }
+
+    // In-flight journal: resolve txs a previous process sent but never processed
+    // (receipts → tax records), then initialize the nonce — before any trade.
+    if config.live_mode && config.inflight_journal_enabled {
+        let policy: RecoveryPolicy = config.inflight_recovery_policy.parse()?;
+        let journal = InflightJournal::open(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name))?;
+        executor.set_inflight_journal(journal);
+        let report = executor.recover_inflight(policy).await?;
+        if report.mined + report.resubmitted + report.discarded > 0 {
+            warn!(
+                "Recovered in-flight txs from previous run: {} mined, {} resubmitted, {} discarded",
+                report.mined, report.resubmitted, report.discarded
+            );
+        }
+    }
+
+    // Approval pre-flight: max-approve every missing allowance up front so no
+    // trade pays for (or waits on) an approve tx. Lazy approval stays as fallback.
+    if config.live_mode {
+        match executor.manage_approvals(&approval_targets, ApprovalAction::Grant).await {
+            Ok(summary) if summary.failed > 0 => warn!("Approval pre-flight: {}", summary),
+            Ok(summary) => info!("Approval pre-flight: {}", summary),
+            Err(e) => warn!("Approval pre-flight failed (lazy approvals remain): {}", e),
+        }
+        // PERMIT2_ADDRESS: one eth_call tells whether the deployed ArbExecutor
+        // has executeArbWithPermit; until it does, trades use the allowance path
+        executor.probe_permit2().await;
+    }
+
+    // Quote token balances: read once now, re-read after each submission;
+    // live trades are clamped to them (BALANCE_CLAMP_ENABLED)
+    if config.live_mode && config.balance_clamp_enabled {
+        executor.track_balances(balance::quote_tokens(&state_manager, &config, &token_registry));
+        executor.refresh_balances().await;
+    }
+
     // Initialize historical price logger (research)
     let mut price_logger: Option<PriceLogger> = if config.price_log_enabled {
         let log_dir = config.price_log_dir.clone()
             .unwrap_or_else(|| format!("/home/botuser/bots/dexarb/data/{}/price_history", config.chain_name));
         info!("Price logging enabled: {}", log_dir);
-        Some(PriceLogger::new(&log_dir))
+        Some(PriceLogger::new(&log_dir, StreamContract::price_log(&config), PriceLogOptions::from_config(&config)))
     } else {
         info!("Price logging disabled");
         None
     };
 
+    // Initialize historical gas logger (research)
+    let mut gas_logger: Option<GasHistoryLogger> = if config.gas_history_enabled {
+        let log_dir = format!("/home/botuser/bots/dexarb/data/{}/research", config.chain_name);
+        Some(GasHistoryLogger::new(&log_dir, config.gas_history_sample_blocks, StreamContract::gas_log(&config)))
+    } else {
+        info!("Gas history logging disabled");
+        None
+    };
+
+    // Pool health: automatic quarantine of idle/drained pools
+    let mut pool_health = if config.pool_health_enabled {
+        let health_config = PoolHealthConfig::from_config(&config);
+        info!(
+            "Pool health: quarantine after {} idle blocks, V3 liquidity < {}, V2 TVL < ${:.0}, {} consecutive quoter rejections",
+            health_config.max_idle_blocks, health_config.min_v3_liquidity,
+            health_config.min_v2_tvl_usd, health_config.max_quoter_rejections
+        );
+        Some(PoolHealth::new(health_config))
+    } else {
+        info!("Pool health quarantine disabled");
+        None
+    };
+
+    // Gas oracle: atomic priority fee from recent blocks' fee percentiles
+    let mut gas_oracle = GasOracle::from_config(&config);
+    if config.gas_oracle_enabled {
+        info!(
+            "Gas oracle: p{:.0} of last {} blocks + {:.0}%, clamped {}..{} gwei (feeHistory every {} blocks)",
+            config.gas_oracle_percentile, config.gas_oracle_window_blocks, config.gas_oracle_bump_percent,
+            config.min_priority_gwei, config.max_priority_gwei, config.gas_oracle_sample_blocks
+        );
+    } else {
+        info!("Gas oracle disabled — static {} gwei atomic priority fee", ATOMIC_PRIORITY_FEE_GWEI);
+    }
+
+    // Cross-chain divergence monitor (research): compares against another chain's collector state
+    let mut cross_chain_monitor = CrossChainMonitor::from_config(&config);
+    if let Some(ref path) = config.cross_chain_state_file {
+        info!(
+            "Cross-chain monitor: {} | alert at {:.0} bps | remote max age {}s | CSV → {}",
+            path, config.cross_chain_divergence_bps, config.cross_chain_max_age_secs, cross_chain::DEFAULT_CROSS_CHAIN_DIR
+        );
+    }
+
+    // Initialize multicall pre-screen export (research)
+    let prescreen_export: Option<PrescreenExporter> = if config.prescreen_export_enabled {
+        let export_dir = format!("/home/botuser/bots/dexarb/data/{}/research", config.chain_name);
+        info!("Pre-screen export enabled: {}/prescreen_YYYYMMDD.jsonl", export_dir);
+        Some(PrescreenExporter::new(&export_dir))
+    } else {
+        None
+    };
+
+    // Initialize opportunity archive (research)
+    let mut opportunity_archive: Option<OpportunityArchive> = if config.opportunity_archive_enabled {
+        let archive_dir = format!("/home/botuser/bots/dexarb/data/{}/research", config.chain_name);
+        info!("Opportunity archive enabled: {}/opportunities_YYYYMMDD.jsonl (unexecuted expire after {} blocks, {} days kept)",
+              archive_dir, config.opportunity_archive_expiry_blocks, config.opportunity_archive_retain_days);
+        Some(OpportunityArchive::new(
+            &archive_dir,
+            &config.chain_name,
+            config.opportunity_archive_expiry_blocks,
+            config.opportunity_archive_retain_days,
+            StreamContract::opportunity_archive(&config),
+        ))
+    } else {
+        None
+    };
+
+    // Initialize execution-constraint analyzer (research)
+    let mut constraint_analyzer: Option<ExecutionConstraintAnalyzer> = if config.execution_constraints_enabled {
+        let research_dir = format!("/home/botuser/bots/dexarb/data/{}/research", config.chain_name);
+        info!("Execution-constraint analysis enabled: {}/execution_constraints.csv", research_dir);
+        Some(ExecutionConstraintAnalyzer::new().with_persistence(&research_dir))
+    } else {
+        None
+    };
+
+    // Watchdog: component heartbeats, heartbeat file, forced reconnects and
+    // exit on a stalled block loop. The block loop beats per new block and
+    // writes the heartbeat file itself as well.
+    let block_heartbeat = watchdog::heartbeat(
+        "block_loop",
+        Duration::from_secs(config.watchdog_block_stall_secs),
+        true,
+    );
+    let heartbeat_file = config.watchdog_enabled.then(|| {
+        PathBuf::from(format!("/home/botuser/bots/dexarb/data/{}/heartbeat.json", config.chain_name))
+    });
+    if config.watchdog_enabled {
+        watchdog::spawn(
+            watchdog::registry(),
+            WatchdogConfig::from_bot_config(&config, heartbeat_file.clone()),
+        )?;
+    } else {
+        info!("Watchdog disabled (WATCHDOG_ENABLED=false)");
+    }
+
     // Log atomic executor status
     if let Some(addr) = config.arb_executor_address {
         info!("⚡ Atomic executor ENABLED: {:?}", addr);
@@ -336,6 +1099,124 @@ async fn main() -> Result<()> {
         info!("Atomic executor disabled (legacy two-tx mode)");
     }
 
+    // Stranded-funds alarm: periodic balanceOf batch over the ArbExecutor contract
+    let mut stranded_monitor = match config.arb_executor_address {
+        Some(arb) if config.stranded_check_interval_blocks > 0 => Some(
+            StrandedFundsMonitor::new(arb, StrandedConfig::from_bot_config(&config))
+                .with_persistence(&format!("/home/botuser/bots/dexarb/data/{}/stranded", config.chain_name))
+                .with_webhook(config.alert_webhook_url.clone()),
+        ),
+        _ => None,
+    };
+
+    // Pool migration detector: collapsed pool → factory's canonical address
+    let mut migration_monitor = if config.migration_collapse_blocks > 0 {
+        Some(
+            PoolMigrationMonitor::new(MigrationConfig::from_bot_config(&config))
+                .with_persistence(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name))
+                .with_webhook(config.alert_webhook_url.clone()),
+        )
+    } else {
+        None
+    };
+
+    // Token metadata re-validation: decimals()/symbol() drift → route quarantine
+    let mut token_validator = if config.token_revalidation_hours > 0 {
+        Some(
+            TokenMetadataValidator::new(TokenValidationConfig::from_bot_config(&config))
+                .with_persistence(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name))
+                .with_webhook(config.alert_webhook_url.clone()),
+        )
+    } else {
+        None
+    };
+
+    // Anomaly monitor: rolling baselines over our own behavior → alert dispatcher
+    let alert_dispatcher = AlertDispatcher::new(config.alert_webhook_url.clone());
+    executor.set_slippage_tracker(
+        SlippageTracker::new(SlippageConfig::from_bot_config(&config))
+            .with_persistence(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name)),
+    );
+    if config.gas_cache_enabled {
+        executor.set_gas_cache(
+            GasEstimateCache::new(config.gas_cache_multiplier)
+                .with_persistence(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name)),
+        );
+    }
+    executor.set_alert_dispatcher(alert_dispatcher.clone());
+    // Inventory manager: idle non-quote tokens in the wallet → alert / rebalance swap
+    let mut inventory_manager = if config.inventory_check_interval_blocks > 0 {
+        Some(
+            InventoryManager::new(wallet_address, InventoryConfig::from_bot_config(&config))
+                .with_persistence(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name))
+                .with_alerts(alert_dispatcher.clone()),
+        )
+    } else {
+        None
+    };
+    // Live trade/halt/WS/daily alerts — webhook only (the loop logs them anyway)
+    let mut live_alerter = config.alert_webhook_url.as_ref().map(|_| {
+        info!("Live alerts → webhook (rate limit {}s)", config.alert_rate_limit_secs);
+        LiveAlerter::new(alert_dispatcher.clone(), LiveAlertConfig::from_config(&config))
+    });
+    let mut anomaly_monitor = if config.anomaly_monitor_enabled {
+        Some(
+            AnomalyMonitor::new(AnomalyConfig::from_bot_config(&config))
+                .with_persistence(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name)),
+        )
+    } else {
+        None
+    };
+
+    // Telemetry push (opt-in): compact snapshots to our own collector
+    let mut telemetry = match TelemetryConfig::from_bot_config(&config)? {
+        Some(tc) => {
+            let mode = if config.live_mode { "live" } else { "dry-run" };
+            let t = Telemetry::new(tc, &config, mode, Some(wallet_address), chrono::Utc::now().timestamp() as u64);
+            info!(
+                "📡 Telemetry ENABLED: instance {} → {:?} every {}s (addresses {})",
+                t.instance_id(), t.sink(), config.telemetry_interval_secs.max(10),
+                if config.telemetry_include_addresses { "INCLUDED" } else { "omitted" }
+            );
+            Some(t)
+        }
+        None => None,
+    };
+
+    // Live state API (opt-in): snapshot swapped in once per block, served off the hot path
+    let mut api: Option<ApiPublisher> = match config.api_port {
+        Some(port) => {
+            let mode = if config.live_mode { "live" } else { "dry-run" };
+            let publisher = ApiPublisher::new(mode, &config.chain_name, config.api_recent_opportunities, chrono::Utc::now().timestamp() as u64);
+            #[cfg(feature = "api")]
+            {
+                let listener = tokio::net::TcpListener::bind((config.api_bind.as_str(), port)).await?;
+                let shared = publisher.shared();
+                tokio::spawn(async move {
+                    if let Err(e) = dexarb_bot::api::server::serve(listener, shared).await {
+                        error!("Live state API stopped: {}", e);
+                    }
+                });
+                info!("🛰  Live state API on {}:{} — /pools /opportunities /status /ws", config.api_bind, port);
+                Some(publisher)
+            }
+            #[cfg(not(feature = "api"))]
+            {
+                warn!("API_PORT={} ignored — built without the `api` feature (cargo build --features api)", port);
+                drop(publisher);
+                None
+            }
+        }
+        None => None,
+    };
+
+    // Dead-man's switch: pings an external uptime monitor only while healthy
+    let deadman_state = Arc::new(DeadmanState::default());
+    match DeadmanConfig::from_bot_config(&config)? {
+        Some(dc) => deadman::spawn(dc, Arc::clone(&deadman_state)),
+        None => debug!("Dead-man's switch disabled (DEADMAN_URL not set)"),
+    }
+
     // Log multicall pre-screen status
     if config.skip_multicall_prescreen {
         info!("Multicall pre-screen DISABLED — opportunities go direct to executor");
@@ -343,6 +1224,29 @@ async fn main() -> Result<()> {
         info!("Multicall pre-screen enabled (batch Quoter verification)");
     }
 
+    // Adaptive pre-screen policy — routes earn skipping with agreeing streaks
+    let mut prescreen_policy = PrescreenPolicy::new(PrescreenPolicyConfig::from_bot_config(&config));
+    if prescreen_policy.is_enabled() {
+        prescreen_policy = prescreen_policy
+            .with_persistence(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name));
+        info!(
+            "Pre-screen policy ENABLED: skip after {} agreeing trades, re-verify every {}th",
+            config.prescreen_skip_streak, config.prescreen_reverify_every
+        );
+    }
+
+    // Competition model — winning bid distributions, EV ranking by win probability
+    let mut competition = CompetitionModel::new(CompetitionConfig::from_bot_config(&config));
+    if competition.is_enabled() {
+        competition = competition.with_persistence(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name));
+        info!(
+            "Competition model ENABLED: min {} samples/route, gas refund {:.0}%, {} known competitor(s)",
+            config.competition_min_samples,
+            config.competition_gas_refund_fraction * 100.0,
+            config.competitor_addresses.len()
+        );
+    }
+
     // Log private mempool status
     if let Some(ref url) = config.private_rpc_url {
         info!("🔒 Private mempool ENABLED: {}", url);
@@ -358,16 +1262,36 @@ async fn main() -> Result<()> {
     // Phase 3: Execute mode sends MempoolSignal to main loop via mpsc channel.
     let mempool_mode = MempoolMode::from_env(&config.mempool_monitor_mode);
     let mut mempool_receiver: Option<mpsc::Receiver<MempoolSignal>> = None;
+    // Cancelled at shutdown (child of the shutdown token); the task is awaited before exit
+    let mempool_cancel = shutdown.token().child_token();
+    let mut mempool_task: Option<tokio::task::JoinHandle<()>> = None;
+    // MIDBLOCK_SCAN: every simulated pending swap comes back for a shadow-state re-scan
+    let mut midblock_receiver: Option<mpsc::Receiver<MidblockUpdate>> = None;
+    let midblock_tx = if config.midblock_scan && mempool_mode.is_active() {
+        let (tx, rx) = mpsc::channel::<MidblockUpdate>(8);
+        midblock_receiver = Some(rx);
+        info!("Mid-block re-scan ENABLED: up to {} per block on simulated pending swaps", config.midblock_scan_max_per_block);
+        Some(tx)
+    } else {
+        None
+    };
+    let mut midblock_budget = MidblockBudget::new(config.midblock_scan_max_per_block);
     match &mempool_mode {
         MempoolMode::Observe => {
             let mempool_config = config.clone();
             let mempool_pool_state = state_manager.clone();
-            tokio::spawn(async move {
+            let cancel = mempool_cancel.clone();
+            mempool_task = Some(tokio::spawn(async move {
                 info!("A4: Mempool monitor starting (observation + simulation mode)...");
-                if let Err(e) = dexarb_bot::mempool::run_observation(mempool_config, mempool_pool_state).await {
-                    error!("A4: Mempool monitor exited with error: {}", e);
+                tokio::select! {
+                    result = dexarb_bot::mempool::run_observation(mempool_config, mempool_pool_state, midblock_tx) => {
+                        if let Err(e) = result {
+                            error!("A4: Mempool monitor exited with error: {}", e);
+                        }
+                    }
+                    _ = cancel.cancelled() => info!("A4: Mempool monitor stopped (shutdown)"),
                 }
-            });
+            }));
             info!("A4: Mempool monitor spawned (observation + Phase 2 simulation)");
         }
         MempoolMode::Execute => {
@@ -375,14 +1299,18 @@ async fn main() -> Result<()> {
             mempool_receiver = Some(rx);
             let mempool_config = config.clone();
             let mempool_pool_state = state_manager.clone();
-            tokio::spawn(async move {
+            let cancel = mempool_cancel.clone();
+            mempool_task = Some(tokio::spawn(async move {
                 info!("A4: Mempool monitor starting (EXECUTION mode)...");
-                if let Err(e) = dexarb_bot::mempool::run_execution(
-                    mempool_config, mempool_pool_state, mempool_tx,
-                ).await {
-                    error!("A4: Mempool execution monitor exited: {}", e);
+                tokio::select! {
+                    result = dexarb_bot::mempool::run_execution(mempool_config, mempool_pool_state, mempool_tx, midblock_tx) => {
+                        if let Err(e) = result {
+                            error!("A4: Mempool execution monitor exited: {}", e);
+                        }
+                    }
+                    _ = cancel.cancelled() => info!("A4: Mempool monitor stopped (shutdown)"),
                 }
-            });
+            }));
             info!("A4: Mempool monitor spawned (Phase 3 EXECUTION mode — signals → main loop)");
         }
         MempoolMode::Off => {
@@ -391,25 +1319,111 @@ async fn main() -> Result<()> {
     }
 
     // Route cooldown tracker — suppresses stale/dead spreads with escalating backoff
-    let mut route_cooldown = RouteCooldown::new(config.route_cooldown_blocks);
+    let mut route_cooldown = RouteCooldown::new(config.route_cooldown_blocks)
+        .with_size_buckets(&config.route_cooldown_size_buckets_usd)
+        .with_mempool_bypass(config.route_cooldown_mempool_bypass_failures)
+        .with_persistence(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name));
     if config.route_cooldown_blocks > 0 {
         info!("Route cooldown ENABLED: initial {} blocks, escalating 5× per failure (max ~1hr)",
               config.route_cooldown_blocks);
+        if !config.route_cooldown_size_buckets_usd.is_empty() {
+            info!("Route cooldown size buckets at ${:?}: failures cool their size and larger",
+                  config.route_cooldown_size_buckets_usd);
+        }
+        if config.route_cooldown_mempool_bypass_failures > 0 {
+            info!("Route cooldown mempool bypass: signals ignore cooldowns under {} failure(s) (capped routes stay blocked)",
+                  config.route_cooldown_mempool_bypass_failures);
+        }
     } else {
         info!("Route cooldown DISABLED (ROUTE_COOLDOWN_BLOCKS=0)");
     }
 
+    // Opportunity registry — one submission per spread across block loop + mempool
+    let mut opportunity_registry = OpportunityRegistry::new(config.opportunity_dedup_ttl_blocks);
+    if opportunity_registry.is_enabled() {
+        info!("Opportunity dedup ENABLED: equivalent opportunities suppressed for {} block(s) after a submission",
+              config.opportunity_dedup_ttl_blocks);
+    }
+
+    // Per-route P&L stats — every trade result, reported every STATS_REPORT_INTERVAL iterations
+    let mut route_stats = StatsTracker::new()
+        .with_snapshot(&format!("/home/botuser/bots/dexarb/data/{}", config.chain_name));
+
+    // Burst triage — bounded pre-screen while a large move floods the detector
+    let mut burst_triage = BurstTriage::new(BurstConfig::from_bot_config(&config));
+    if burst_triage.is_enabled() {
+        info!(
+            "Burst triage ENABLED: > {} opportunities/block → spread ≥ {:.2}%, top {} per silo, exit after {} calm blocks",
+            config.burst_threshold, config.burst_min_spread_percent, config.burst_top_k, config.burst_exit_blocks
+        );
+    } else {
+        info!("Burst triage DISABLED (BURST_THRESHOLD=0)");
+    }
+    if config.triangular_enabled {
+        info!("Triangular detection ENABLED: quote → A → B → quote over configured pairs ({} only)",
+              if config.live_mode { "logged" } else { "logged + dry-run simulated" });
+    }
+    let optimal_sizer = OptimalSizer::new(&config);
+    if config.optimal_sizing {
+        info!("Optimal sizing ENABLED: V2↔V2 closed form, V3 ladder of {} sizes per route in one Multicall3 batch",
+              dexarb_bot::arbitrage::sizing::LADDER_STEPS);
+    }
+
+    // Shadow validation — primary publishes per-block decisions, follower diffs against them
+    let shadow_out_dir = shadow::shadow_dir(&config);
+    let decision_feed = (shadow_role == ShadowRole::Primary).then(|| DecisionFeed::new(&shadow_out_dir));
+    let mut shadow_comparator = (shadow_role == ShadowRole::Follower).then(|| {
+        let feed_dir = config.shadow_feed_dir.clone().unwrap_or_else(|| shadow_out_dir.clone());
+        ShadowComparator::new(&feed_dir, &shadow_out_dir, config.shadow_match_lag_blocks)
+    });
+    match shadow_role {
+        ShadowRole::Primary => info!("Shadow PRIMARY: decisions feed → {}/decisions_YYYYMMDD.jsonl", shadow_out_dir),
+        ShadowRole::Follower => info!(
+            "Shadow FOLLOWER (detection-only): diffing against {} → {}/divergence_YYYYMMDD.csv",
+            config.shadow_feed_dir.as_deref().unwrap_or(&shadow_out_dir), shadow_out_dir
+        ),
+        ShadowRole::Off => {}
+    }
+    // Strategy A/B — experimental config scanned beside the live one, never executed
+    let mut ab_shadow = match config.shadow_config.as_deref() {
+        Some(path) => {
+            let shadow_config = load_shadow_config(toml_file.as_deref(), Path::new(path))?;
+            info!(
+                "A/B shadow: {} (min profit {} vs live {}, {} pairs) → {}/decisions_YYYYMMDD.csv, quotes when ≤{} routes",
+                path, format_usd(shadow_config.min_profit_usd), format_usd(config.min_profit_usd),
+                shadow_config.pairs.len(), shadow_out_dir, config.shadow_verify_max
+            );
+            Some(AbShadow::new(shadow_config, state_manager.clone(), &shadow_out_dir, config.shadow_verify_max))
+        }
+        None => None,
+    };
+
+    // Liquidity guard — thin legs rejected/downsized before pre-screen (no cooldown)
+    let mut liquidity_guard = LiquidityGuard::new(&config);
+    info!(
+        "Liquidity guard: min liquidity {} ({} pair overrides), max {:.1}% of in-range depth, mode {:?}",
+        config.min_pool_liquidity,
+        config.min_pool_liquidity_overrides.len(),
+        config.max_liquidity_fraction * 100.0,
+        liquidity_guard.mode()
+    );
+    if config.exposure_pacing_enabled {
+        info!(
+            "Exposure pacing: max {:.0}% of quote balance per trade, {:.0}% in flight ({} quote overrides), min notional ${:.2}",
+            config.exposure_max_trade_fraction * 100.0,
+            config.exposure_max_inflight_fraction * 100.0,
+            config.exposure_overrides.len(),
+            config.exposure_min_notional_usd
+        );
+    }
+
     // A3: Event-driven pool state — setup
     // Build lookup map from pool address → metadata for event parsing.
     // V3 Swap events give us (sqrtPriceX96, liquidity, tick) directly.
     // V2 Sync events give us (reserve0, reserve1) directly.
     // One eth_getLogs call per block replaces ~21 per-pool RPC calls.
-    let v3_swap_topic: H256 = ethers::utils::keccak256(
-        b"Swap(address,address,int256,int256,uint160,uint128,int24)"
-    ).into();
-    let v2_sync_topic: H256 = ethers::utils::keccak256(
-        b"Sync(uint112,uint112)"
-    ).into();
+    // Decoding (pool_events::decode_pool_event) is shared with the executor's
+    // own-receipt fast path.
 
     struct PoolMeta {
         dex: DexType,
@@ -441,7 +1455,7 @@ async fn main() -> Result<()> {
             is_v3: false,
         });
     }
-    let pool_addresses: Vec<Address> = pool_lookup.keys().cloned().collect();
+    let mut pool_addresses: Vec<Address> = pool_lookup.keys().cloned().collect();
 
     let use_event_sync = std::env::var("EVENT_SYNC")
         .map(|v| v.to_lowercase() == "true")
@@ -452,6 +1466,8 @@ async fn main() -> Result<()> {
     } else {
         info!("Event-driven sync disabled: poll-based sync (~400ms/block). Set EVENT_SYNC=true to enable.");
     }
+    // Algebra dynamic fee under event sync: last globalState() re-read block
+    let mut algebra_fee_read_block = 0u64;
 
     // Mempool execution CSV (Phase 3) — log all mempool-sourced trade attempts
     let mut mempool_exec_csv: Option<std::fs::File> = if mempool_receiver.is_some() {
@@ -492,56 +1508,73 @@ async fn main() -> Result<()> {
     // If subscription drops, bot exits (restart via tmux/supervisor).
     let mut iteration = 0u64;
 
+    // Reorg detection: processed header hashes; pools touched by orphaned
+    // blocks are marked stale and poll-resynced before detection
+    let mut reorg_detector = ReorgDetector::new(config.reorg_track_blocks);
+
     // Block subscription with timeout + reconnect.
     // ethers-rs WS streams can silently stall (observed on Alchemy Base/Polygon).
     // Timeout detects dead streams; outer loop reconnects the subscription provider.
     let block_timeout = Duration::from_secs(30); // generous: Base=2s, Polygon=2s blocks
-    let mut ws_reconnects = 0u32;
-    const MAX_WS_RECONNECTS: u32 = 50; // ~25 min of retries before full exit
 
     'reconnect: loop {
+    if shutdown.is_shutting_down() {
+        break 'reconnect;
+    }
+    ws_endpoints.attempt();
+    // A fresh connection answers any watchdog reconnect request still pending
+    watchdog::registry().take_reconnect();
     // Create a fresh WS connection for block subscription each reconnect cycle.
     // Scoped to this loop iteration so borrow checker is satisfied when we `break`.
-    let sub_provider = match Provider::<Ws>::connect(&config.rpc_url).await {
+    let sub_provider = match rpc_timeout("WS connect", Provider::<Ws>::connect(ws_endpoints.subscription_url())).await {
         Ok(p) => p,
         Err(e) => {
-            ws_reconnects += 1;
-            if ws_reconnects > MAX_WS_RECONNECTS {
-                error!("WS connect failed after {} attempts: {} — exiting", ws_reconnects, e);
-                break 'reconnect;
+            let failure = ws_endpoint_failed(
+                &mut ws_endpoints, EndpointFailure::Connect, &e.to_string(),
+                event_journal.as_ref(), live_alerter.as_mut(), last_block,
+            );
+            match failure {
+                Some(delay) if ws_endpoints.wait(delay).await == Backoffed::Retry => continue 'reconnect,
+                _ => break 'reconnect,
             }
-            warn!("WS connect failed (attempt {}): {} — retrying in 5s...", ws_reconnects, e);
-            tokio::time::sleep(Duration::from_secs(5)).await;
-            continue 'reconnect;
         }
     };
 
-    info!("Subscribing to new blocks via WebSocket (dedicated connection)...");
-    let mut block_stream = match sub_provider.subscribe_blocks().await {
+    info!("Subscribing to new blocks via {} (dedicated connection)...", ws_endpoints.subscription_label());
+    let mut block_stream = match rpc_timeout("WS subscribe", sub_provider.subscribe_blocks()).await {
         Ok(stream) => stream,
         Err(e) => {
-            ws_reconnects += 1;
-            if ws_reconnects > MAX_WS_RECONNECTS {
-                error!("WS subscribe failed after {} attempts: {} — exiting", ws_reconnects, e);
-                break 'reconnect;
+            let failure = ws_endpoint_failed(
+                &mut ws_endpoints, EndpointFailure::Subscribe, &e.to_string(),
+                event_journal.as_ref(), live_alerter.as_mut(), last_block,
+            );
+            match failure {
+                Some(delay) if ws_endpoints.wait(delay).await == Backoffed::Retry => continue 'reconnect,
+                _ => break 'reconnect,
             }
-            warn!("WS subscribe failed (attempt {}): {} — retrying in 5s...", ws_reconnects, e);
-            tokio::time::sleep(Duration::from_secs(5)).await;
-            continue 'reconnect;
         }
     };
     info!("WS block subscription active — reacting to blocks in real-time");
-    ws_reconnects = 0; // reset on successful subscribe
+    // The endpoint's failure streak closes on its first block, not here
+    ws_endpoints.subscribed();
 
     // LoopEvent: block-reactive or mempool-sourced signal
     enum LoopEvent {
         Block(Block<TxHash>),
         Mempool(MempoolSignal),
+        Midblock(MidblockUpdate),
         StreamEnd,
         Timeout,
+        /// The watchdog saw no block for the stall interval
+        ForcedReconnect,
+        Shutdown,
     }
 
     loop { // inner block-processing loop
+    if shutdown.is_shutting_down() {
+        info!("Shutdown requested — leaving block loop");
+        break 'reconnect;
+    }
 
     // Select between block events and mempool signals
     let event = if let Some(ref mut rx) = mempool_receiver {
@@ -553,6 +1586,18 @@ async fn main() -> Result<()> {
                     Err(_) => LoopEvent::Timeout,
                 }
             }
+            _ = shutdown.cancelled() => LoopEvent::Shutdown,
+            _ = watchdog::registry().reconnect_requested() => LoopEvent::ForcedReconnect,
+            update = recv_midblock(&mut midblock_receiver) => {
+                match update {
+                    Some(u) => LoopEvent::Midblock(u),
+                    None => {
+                        warn!("Mid-block update channel closed — mid-block re-scans off");
+                        midblock_receiver = None;
+                        continue;
+                    }
+                }
+            }
             signal = rx.recv() => {
                 match signal {
                     Some(s) => LoopEvent::Mempool(s),
@@ -567,46 +1612,93 @@ async fn main() -> Result<()> {
         }
     } else {
         // No mempool execution — original timeout behavior
-        match timeout(block_timeout, block_stream.next()).await {
-            Ok(Some(b)) => LoopEvent::Block(b),
-            Ok(None) => LoopEvent::StreamEnd,
-            Err(_) => LoopEvent::Timeout,
-        }
-    };
-
-    // Handle mempool signal (Phase 3)
-    if let LoopEvent::Mempool(signal) = event {
-        let lead_ms = signal.seen_at.elapsed().as_millis() as u64;
-        if lead_ms > 10_000 {
-            info!("MEMPOOL SKIP: stale signal ({}ms > 10s)", lead_ms);
-            continue;
-        }
+        tokio::select! {
+            result = timeout(block_timeout, block_stream.next()) => {
+                match result {
+                    Ok(Some(b)) => LoopEvent::Block(b),
+                    Ok(None) => LoopEvent::StreamEnd,
+                    Err(_) => LoopEvent::Timeout,
+                }
+            }
+            _ = shutdown.cancelled() => LoopEvent::Shutdown,
+            _ = watchdog::registry().reconnect_requested() => LoopEvent::ForcedReconnect,
+            update = recv_midblock(&mut midblock_receiver) => {
+                match update {
+                    Some(u) => LoopEvent::Midblock(u),
+                    None => {
+                        warn!("Mid-block update channel closed — mid-block re-scans off");
+                        midblock_receiver = None;
+                        continue;
+                    }
+                }
+            }
+        }
+    };
 
+    // Handle mempool signal (Phase 3)
+    if let LoopEvent::Mempool(signal) = event {
         let opp = &signal.opportunity;
         info!(
             "MEMPOOL EXEC: processing signal | {} | ${:.2} | {:.3}% | lead={}ms",
-            opp.pair_symbol, opp.arb_est_profit_usd, opp.arb_spread_pct, lead_ms
+            opp.pair_symbol, opp.arb_est_profit_usd, opp.arb_spread_pct,
+            signal.seen_at.elapsed().as_millis()
         );
 
-        // Convert SimulatedOpportunity → ArbitrageOpportunity
-        let arb_opp = match build_mempool_arb_opportunity(&signal, &state_manager, &config) {
-            Some(o) => o,
-            None => {
-                warn!("MEMPOOL SKIP: pool data resolution failed for {}", opp.pair_symbol);
+        // Gates (stale, unresolved, quarantined, canary, liquidity, cooldown, shutdown) + execute
+        let quarantined = |arb: &ArbitrageOpportunity| {
+            token_validator.as_ref().is_some_and(|v| v.is_quarantined_route(arb))
+        };
+        let route_limits = |pair: &str, pools: &[Address]| whitelist.limits_for(&[pair], pools);
+        let ctx = DispatchContext {
+            state_manager: &state_manager,
+            config: &config,
+            route_cooldown: &mut route_cooldown,
+            registry: &mut opportunity_registry,
+            current_block: last_block,
+            shutdown: &shutdown,
+            quarantined: &quarantined,
+            canary: &canary,
+            liquidity_guard: &mut liquidity_guard,
+            route_limits: &route_limits,
+        };
+        let (arb, exec, lead_ms, exec_ms) = match dispatch_signal(&signal, ctx, &mut executor).await {
+            MempoolDispatch::Skipped(reason) => {
+                info!("MEMPOOL SKIP: {} | {}", opp.pair_symbol, reason);
                 continue;
             }
+            MempoolDispatch::Executed(execution) => {
+                (execution.opportunity, execution.result, execution.lead_ms, execution.exec_ms)
+            }
         };
 
-        // Execute via mempool-specific path (skip estimateGas, dynamic gas)
-        let exec_start = std::time::Instant::now();
-        match executor.execute_from_mempool(
-            &arb_opp,
-            signal.trigger_gas_price,
-            signal.trigger_max_priority_fee,
-            config.mempool_min_profit_usd,
-        ).await {
+        let outcome = TradeOutcome::classify(&exec, config.arb_executor_address.is_some());
+        if let Some(ref mut archive) = opportunity_archive {
+            archive.record_detected(last_block, &arb);
+            archive.record_execution(last_block, &arb, &exec, outcome);
+        }
+        route_stats.record(&opp.pair_symbol, opp.arb_buy_dex, opp.arb_sell_dex, &exec, outcome);
+        let submitted = exec.as_ref().ok().and_then(|r| r.tx_hash.as_deref());
+        if let Some(bundle) = submitted.and_then(|h| executor.take_bundle_outcome(h)) {
+            route_stats.record_bundle(&opp.pair_symbol, opp.arb_buy_dex, opp.arb_sell_dex, bundle);
+        }
+        if let Some(ref model) = threshold_model {
+            model.record(&opp.pair_symbol, opp.arb_buy_dex, opp.arb_sell_dex, &exec, outcome);
+        }
+        if let Some(ref mut alerter) = live_alerter {
+            alerter.trade_outcome(&opp.pair_symbol, opp.arb_buy_dex, opp.arb_sell_dex, &exec, outcome);
+        }
+        match exec {
             Ok(result) => {
-                let exec_ms = exec_start.elapsed().as_millis() as u64;
+                if let Some(ref mut monitor) = anomaly_monitor {
+                    monitor.record_trade(&result);
+                }
+                if let Some(ref mut t) = telemetry {
+                    t.record_trade(&result);
+                }
+                if let Some(ref mut a) = api {
+                    a.record_trade(&result);
+                }
+                watchdog::registry().progress().record_trade(&result);
                 let result_str = if result.success { "SUCCESS" } else { "FAIL" };
                 info!(
                     "MEMPOOL {}: {} | ${:.4} net | {}ms exec | {}ms lead | tx={}",
@@ -615,17 +1707,6 @@ async fn main() -> Result<()> {
                     result.tx_hash.as_deref().unwrap_or("none"),
                 );
 
-                // Route cooldown on failure
-                if !result.success {
-                    route_cooldown.record_failure(
-                        &opp.pair_symbol, opp.arb_buy_dex, opp.arb_sell_dex, last_block
-                    );
-                } else {
-                    route_cooldown.record_success(
-                        &opp.pair_symbol, opp.arb_buy_dex, opp.arb_sell_dex
-                    );
-                }
-
                 // Log to mempool execution CSV
                 if let Some(ref mut csv) = mempool_exec_csv {
                     use std::io::Write;
@@ -651,31 +1732,163 @@ async fn main() -> Result<()> {
             }
             Err(e) => {
                 error!("MEMPOOL EXEC ERROR: {}", e);
-                route_cooldown.record_failure(
-                    &opp.pair_symbol, opp.arb_buy_dex, opp.arb_sell_dex, last_block
-                );
+                if let Some(ref mut t) = telemetry {
+                    t.record_execution_error();
+                }
+                if let Some(ref mut a) = api {
+                    a.record_execution_error();
+                }
             }
         }
 
         continue; // Back to select! loop — don't fall through to block processing
     }
 
+    // Mid-block re-scan (MIDBLOCK_SCAN): detection on the simulated post-swap state
+    if let LoopEvent::Midblock(update) = event {
+        if shutdown.is_shutting_down() || !midblock_budget.try_acquire(last_block) {
+            debug!("MIDBLOCK: budget spent at block {} — {:?} not re-scanned", last_block, update.tx_hash);
+            continue;
+        }
+        let Some((shadow, trigger_pool)) = midblock::shadow_state(&state_manager, &update.simulated) else {
+            continue;
+        };
+        let scanned = midblock::tag_anticipatory(detector.scan_opportunities_on(shadow), trigger_pool, update.tx_hash);
+        let scanned = detector.drop_stale_opportunities(scanned, last_block);
+        // Same gates as the block loop's routes (spread confirmation aside)
+        let mut opportunities: Vec<ArbitrageOpportunity> = scanned.into_iter()
+            .filter(|opp| !opp.buy_dex.is_detection_only() && !opp.sell_dex.is_detection_only())
+            .filter(|opp| !route_cooldown.is_cooled_down_for_source(
+                &opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.trade_size_usd, last_block, CooldownSource::Mempool
+            ))
+            .filter(|opp| !opportunity_registry.is_duplicate(opp, last_block))
+            .filter(|opp| !canary.is_demoted_route(opp))
+            .filter(|opp| !token_validator.as_ref().is_some_and(|v| v.is_quarantined_route(opp)))
+            .collect();
+        for opp in opportunities.iter_mut() {
+            canary.cap_trade_size(opp);
+        }
+        opportunities.retain_mut(|opp| !matches!(liquidity_guard.check(opp), LiquidityVerdict::Rejected(_)));
+        if opportunities.is_empty() {
+            continue;
+        }
+        if let Some(ref mut archive) = opportunity_archive {
+            for opp in &opportunities {
+                archive.record_detected(last_block, opp);
+            }
+        }
+        info!(
+            "🔮 MIDBLOCK {}/{}: {} anticipatory route(s) behind {:?} ({:?} {}) | {}ms since sim",
+            midblock_budget.used(), config.midblock_scan_max_per_block, opportunities.len(), update.tx_hash,
+            update.simulated.dex, update.simulated.pair_symbol, update.seen_at.elapsed().as_millis()
+        );
+
+        // Reality check: anticipatory routes are never executed unquoted
+        let verified = match rpc_timeout("midblock pre-screen", multicall_quoter.batch_verify(&opportunities, &config)).await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("MIDBLOCK: multicall verify failed: {} — routes dropped", e);
+                continue;
+            }
+        };
+        let Some(best) = verified.iter()
+            .filter(|v| v.both_legs_valid && v.quoted_profit_raw > 0)
+            .max_by_key(|v| v.quoted_profit_raw)
+        else {
+            info!("🔮 MIDBLOCK: none of {} route(s) verified on chain", opportunities.len());
+            continue;
+        };
+        let mut opp = opportunities[best.original_index].clone();
+        opp.quoted_legs = Some(QuotedLegs { buy_out: best.buy_quoted_out, sell_out: best.sell_quoted_out });
+        route_cooldown.note_bypass_attempt(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.trade_size_usd, last_block);
+
+        let exec = shutdown.track(executor.execute(&opp)).await;
+        if let Some(result) = exec.as_ref().ok().filter(|r| r.tx_hash.is_some()) {
+            opportunity_registry.record_submission(&opp, last_block);
+            if let Some(ref mut t) = telemetry {
+                t.record_trade(result);
+            }
+            if let Some(ref mut a) = api {
+                a.record_trade(result);
+            }
+            watchdog::registry().progress().record_trade(result);
+        }
+        let outcome = TradeOutcome::classify(&exec, config.arb_executor_address.is_some());
+        outcome.apply_cooldown(&mut route_cooldown, &opp, last_block);
+        if let Some(ref mut archive) = opportunity_archive {
+            archive.record_execution(last_block, &opp, &exec, outcome);
+        }
+        route_stats.record(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, &exec, outcome);
+        if let Some(ref model) = threshold_model {
+            model.record(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, &exec, outcome);
+        }
+        if let Some(ref mut alerter) = live_alerter {
+            alerter.trade_outcome(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, &exec, outcome);
+        }
+        match exec {
+            Ok(ref result) => log_trade_outcome(result, outcome, 0),
+            Err(ref e) => error!("MIDBLOCK execution error: {}", e),
+        }
+        if outcome.next_step() == NextStep::Halt {
+            deadman_state.trip_breaker(format!("{:?} on {} (mid-block) at block {}", outcome, opp.pair.symbol, last_block));
+        }
+        continue;
+    }
+
     // Handle stream end / timeout
     let block = match event {
         LoopEvent::Block(b) => b,
-        LoopEvent::StreamEnd => {
-            warn!("WS block stream ended (None) — reconnecting...");
-            break;
+        LoopEvent::StreamEnd | LoopEvent::Timeout | LoopEvent::ForcedReconnect => {
+            let (failure, error) = match event {
+                LoopEvent::StreamEnd => (EndpointFailure::StreamEnd, "block stream ended".to_string()),
+                LoopEvent::ForcedReconnect => (
+                    EndpointFailure::Stale,
+                    format!("watchdog: no block processed in {}s", config.watchdog_block_stall_secs),
+                ),
+                _ => (EndpointFailure::Stale, format!("no block received in {}s", block_timeout.as_secs())),
+            };
+            let failure = ws_endpoint_failed(
+                &mut ws_endpoints, failure, &error, event_journal.as_ref(), live_alerter.as_mut(), last_block,
+            );
+            match failure {
+                Some(delay) if ws_endpoints.wait(delay).await == Backoffed::Retry => break,
+                _ => break 'reconnect,
+            }
         }
-        LoopEvent::Timeout => {
-            warn!("No block received in {}s — WS stale, reconnecting...", block_timeout.as_secs());
-            break;
+        LoopEvent::Shutdown => {
+            info!("Shutdown requested — leaving block loop");
+            break 'reconnect;
         }
-        LoopEvent::Mempool(_) => unreachable!(), // handled above
+        LoopEvent::Mempool(_) | LoopEvent::Midblock(_) => unreachable!(), // handled above
     };
 
     {
+            let arrived = Instant::now();
             let current_block = block.number.map(|n| n.as_u64()).unwrap_or(last_block + 1);
+            ws_endpoints.record_block(block.timestamp.low_u64(), chrono::Utc::now().timestamp_millis().max(0) as u64);
+
+            // Reorg check: parent hash vs the processed chain. Walk back to the
+            // fork point, then distrust every pool state written since.
+            if let Some(hash) = block.hash {
+                if let Some(mut reorg) = reorg_detector.observe(current_block, hash, block.parent_hash) {
+                    while let Some(below) = reorg_detector.unconfirmed_below(&reorg) {
+                        let canonical = match rpc_timeout("eth_getBlockByNumber", provider.get_block(below)).await {
+                            Ok(Some(b)) => b.hash,
+                            _ => None,
+                        };
+                        // Unknown canonical hash: assume it was orphaned too (more resync, never less)
+                        if !reorg_detector.confirm(&mut reorg, canonical.unwrap_or_default()) {
+                            break;
+                        }
+                    }
+                    let stale = state_manager.pools_updated_since(reorg.fork_block);
+                    warn!(
+                        "REORG detected depth={} (blocks {}-{} orphaned, new head {}) — {} pools stale until resync",
+                        reorg.depth(), reorg.fork_block, reorg.tip, current_block, stale.len()
+                    );
+                    state_manager.mark_stale(&stale);
+                }
+            }
 
             iteration += 1;
             total_scans += 1;
@@ -686,10 +1899,77 @@ async fn main() -> Result<()> {
                 route_cooldown.cleanup(current_block);
                 let (v2_count, v3_count, min_block, max_block) = state_manager.combined_stats();
                 let cd_count = route_cooldown.active_count();
+                let aged = state_manager.aged_pool_count(current_block, config.max_pool_age_blocks);
                 info!(
-                    "Iteration {} (WS) | {} V3 + {} V2 pools | blocks {}-{} | {} opps found / {} scans | {} routes cooled | block {}",
-                    iteration, v3_count, v2_count, min_block, max_block, total_opportunities, total_scans, cd_count, current_block
+                    "Iteration {} (WS) | {} V3 + {} V2 pools | blocks {}-{} | {} stale (>{} blocks) | {} opps found / {} scans | {} routes cooled | block {}",
+                    iteration, v3_count, v2_count, min_block, max_block, aged, config.max_pool_age_blocks,
+                    total_opportunities, total_scans, cd_count, current_block
                 );
+                let retries = retry::status_line();
+                if !retries.is_empty() {
+                    info!("Retries: {}", retries);
+                }
+                info!("WS endpoints: {}", ws_endpoints.status_line());
+                let bypass = route_cooldown.bypass_stats();
+                if bypass.bypasses > 0 {
+                    info!(
+                        "Cooldown bypass (mempool): {} bypassed | {} succeeded / {} failed | success rate {}",
+                        bypass.bypasses, bypass.successes, bypass.failures,
+                        bypass.success_rate().map_or("n/a".to_string(), |r| format!("{:.0}%", r * 100.0))
+                    );
+                }
+                let lg = liquidity_guard.stats();
+                if lg.downsized + lg.rejected_min + lg.rejected_depth > 0 {
+                    info!(
+                        "Liquidity guard: {} checked | {} downsized | {} below min | {} too deep",
+                        lg.checked, lg.downsized, lg.rejected_min, lg.rejected_depth
+                    );
+                }
+                for line in executor.slippage_summary() {
+                    info!("Slippage: {}", line);
+                }
+                let writers = [
+                    executor.tax_writer_status(),
+                    price_logger.as_ref().and_then(|l| l.status_line()),
+                    gas_logger.as_ref().and_then(|l| l.status_line()),
+                    event_journal.as_ref().and_then(|j| j.status_line()),
+                    opportunity_archive.as_ref().and_then(|a| a.status_line()),
+                ];
+                for line in writers.into_iter().flatten() {
+                    warn!("Log writer: {}", line);
+                }
+                for line in prescreen_policy.summary_lines() {
+                    info!("Pre-screen policy: {}", line);
+                }
+                for line in competition.summary_lines() {
+                    info!("Competition: {}", line);
+                }
+                if let Some(line) = burst_triage.status_line() {
+                    info!("Burst triage: {}", line);
+                }
+                if let Some(ref comparator) = shadow_comparator {
+                    info!("Shadow (today): {}", comparator.status_line());
+                }
+                if let Some(ref ab) = ab_shadow {
+                    info!("A/B shadow: {}", ab.status_line());
+                }
+                if let Some(ref injector) = chaos_injector {
+                    warn!("CHAOS injected (fired/calls): {}", injector.summary());
+                }
+            }
+            if config.stats_report_interval > 0 && iteration.is_multiple_of(config.stats_report_interval) {
+                info!("{}", route_stats.report());
+                route_stats.write_snapshot();
+                if let Some(ref health) = pool_health {
+                    info!("{}", health.report());
+                }
+            }
+            if config.latency_report_interval > 0 && iteration.is_multiple_of(config.latency_report_interval) {
+                info!("{}", latency.report());
+                latency.write_snapshot();
+            }
+            if let Some(ref mut alerter) = live_alerter {
+                alerter.daily_summary(chrono::Utc::now().date_naive(), &route_stats);
             }
 
             // Skip duplicate blocks (WS can deliver same block twice)
@@ -697,46 +1977,130 @@ async fn main() -> Result<()> {
                 continue;
             }
             last_block = current_block;
+            latency.mark_block(arrived, block.timestamp.low_u64());
+            block_heartbeat.beat();
+            let (v2_synced, v3_synced, _, _) = state_manager.combined_stats();
+            watchdog::registry().progress().record_block(current_block, v2_synced + v3_synced);
+            if let Some(ref path) = heartbeat_file {
+                if let Err(e) = watchdog::write_heartbeat(path) {
+                    warn!("Heartbeat file write failed: {}", e);
+                }
+            }
+
+            // Strict tax logging: retry unwritten records (lifts the submission halt once drained)
+            if executor.pending_tax_records() > 0 {
+                executor.retry_pending_tax_records();
+            }
+            deadman_state.on_block(current_block);
+            deadman_state.set_tax_halt(executor.submission_halt());
+
+            // Finality: re-check provisional trades at depth; a reorged-away trade
+            // was booked as a route success — take that back
+            for reversal in executor.confirm_provisional_trades(current_block).await {
+                let t = &reversal.trade;
+                route_cooldown.revoke_success(&t.pair, t.buy_dex, t.sell_dex, t.trade_size_usd, current_block);
+                if let Some(ref export) = prescreen_export {
+                    let record = AttemptRecord::reversal(
+                        &t.pair, t.buy_dex, t.sell_dex, format!("{:?}", t.tx_hash), t.net_profit_usd, current_block,
+                    );
+                    if let Err(e) = export.export_attempt(&record) {
+                        warn!("Route attempt export failed: {}", e);
+                    }
+                }
+            }
+
+            // Telemetry: same counters as the status line, pushed when due
+            if let Some(ref mut t) = telemetry {
+                let status = StatusCounters {
+                    block_height: current_block,
+                    scans: total_scans,
+                    opportunities: total_opportunities,
+                    routes_cooled: route_cooldown.active_count() as u64,
+                };
+                t.tick(&status, chrono::Utc::now().timestamp() as u64);
+            }
 
             // A1: Update executor's cached base fee from block header.
             // Eliminates get_gas_price() RPC call during execution (~50ms savings).
             if let Some(base_fee) = block.base_fee_per_gas {
                 executor.set_base_fee(base_fee);
             }
+            executor.set_current_block(current_block);
+            executor.keep_connections_warm().await;
+
+            // Gas oracle + gas history share one eth_feeHistory call over the
+            // sealed blocks whenever either is due
+            let sealed = current_block - 1;
+            let log_due = gas_logger.as_ref().is_some_and(|l| l.sample_due(sealed));
+            let oracle_due = config.gas_oracle_enabled && gas_oracle.sample_due(sealed);
+            let history = if log_due || oracle_due {
+                let log_count = gas_logger.as_ref().filter(|_| log_due).map_or(0, |l| l.sample_block_count(sealed));
+                let oracle_count = if oracle_due { gas_oracle.sample_block_count() } else { 0 };
+                let history = rpc_timeout(
+                    "eth_feeHistory",
+                    provider.fee_history(log_count.max(oracle_count), BlockNumber::Number(sealed.into()), &FEE_HISTORY_PERCENTILES),
+                )
+                .await;
+                if let Err(ref e) = history {
+                    warn!("eth_feeHistory failed: {} — no fee percentiles this sample", e);
+                }
+                history.ok()
+            } else {
+                None
+            };
+            if oracle_due {
+                gas_oracle.record(sealed, history.as_ref());
+                executor.set_priority_fee_estimate(gas_oracle.priority_fee_for_percentile(config.gas_oracle_percentile));
+            }
+
+            // Gas history: join submissions made under the previous head, write
+            // the sampled blocks when due, then record this block
+            if let Some(ref mut gas_log) = gas_logger {
+                for submission in executor.take_submissions() {
+                    gas_log.record_submission(submission);
+                }
+                if log_due {
+                    gas_log.flush_sample(sealed, history.as_ref());
+                }
+                gas_log.record_block(
+                    current_block,
+                    block.base_fee_per_gas.unwrap_or_default(),
+                    BidParams::from_config(&config, executor.atomic_priority_gwei()),
+                );
+            }
 
             // --- Pool state sync ---
+            let sync_started = Instant::now();
             // A3: Event-driven sync uses single eth_getLogs call (~50ms, 75 CU)
             // Poll fallback uses per-pool RPC calls (~400ms, ~1100 CU).
             // Toggle: EVENT_SYNC=true in .env to enable event-driven mode.
             let sync_ok = if use_event_sync {
-                let filter = Filter::new()
-                    .from_block(current_block)
-                    .to_block(current_block)
-                    .address(pool_addresses.clone())
-                    .topic0(vec![v3_swap_topic, v2_sync_topic]);
+                let filter = pool_events::event_sync_filter(&pool_addresses, current_block);
 
-                match provider.get_logs(&filter).await {
+                match rpc_timeout("eth_getLogs", provider.get_logs(&filter)).await {
                     Ok(logs) => {
                         let mut v3_updated = 0u32;
                         let mut v2_updated = 0u32;
 
                         for log in &logs {
                             if let Some(meta) = pool_lookup.get(&log.address) {
-                                if meta.is_v3 && !log.topics.is_empty() && log.topics[0] == v3_swap_topic {
-                                    // V3 Swap event data layout (160 bytes):
-                                    //   [0..32]   amount0 (int256)
-                                    //   [32..64]  amount1 (int256)
-                                    //   [64..96]  sqrtPriceX96 (uint160)
-                                    //   [96..128] liquidity (uint128)
-                                    //   [128..160] tick (int24 as int256)
-                                    if log.data.len() >= 160 {
-                                        let sqrt_price_x96 = U256::from_big_endian(&log.data[64..96]);
-                                        let liquidity = U256::from_big_endian(&log.data[96..128]).low_u128();
-                                        // tick: int24 sign-extended to int256; last 4 bytes = valid i32
-                                        let tick = i32::from_be_bytes([
-                                            log.data[156], log.data[157],
-                                            log.data[158], log.data[159],
-                                        ]);
+                                match pool_events::decode_pool_event(log) {
+                                    Some(PoolEvent::V3Swap { sqrt_price_x96, mut liquidity, tick }) if meta.is_v3 => {
+                                        // Kyber Elastic emits the same Swap signature, but its liquidity
+                                        // field is baseL only — keep the last baseL + reinvestL read
+                                        if meta.dex.is_kyber_elastic() {
+                                            if let Some(prev) = state_manager.get_v3_pool(meta.dex, &meta.pair.symbol) {
+                                                liquidity = prev.liquidity;
+                                            }
+                                        }
+
+                                        // Algebra's dynamic fee isn't in the event either — keep
+                                        // the last globalState() read, not the startup fee
+                                        let fee = if meta.dex.is_quickswap_v3() {
+                                            state_manager.get_v3_pool_at(&log.address).map_or(meta.fee, |prev| prev.fee)
+                                        } else {
+                                            meta.fee
+                                        };
 
                                         state_manager.update_v3_pool(V3PoolState {
                                             address: log.address,
@@ -744,7 +2108,7 @@ async fn main() -> Result<()> {
                                             pair: meta.pair.clone(),
                                             sqrt_price_x96,
                                             tick,
-                                            fee: meta.fee,
+                                            fee,
                                             liquidity,
                                             token0_decimals: meta.token0_decimals,
                                             token1_decimals: meta.token1_decimals,
@@ -752,14 +2116,7 @@ async fn main() -> Result<()> {
                                         });
                                         v3_updated += 1;
                                     }
-                                } else if !meta.is_v3 && !log.topics.is_empty() && log.topics[0] == v2_sync_topic {
-                                    // V2 Sync event data layout (64 bytes):
-                                    //   [0..32]  reserve0 (uint112)
-                                    //   [32..64] reserve1 (uint112)
-                                    if log.data.len() >= 64 {
-                                        let reserve0 = U256::from_big_endian(&log.data[0..32]);
-                                        let reserve1 = U256::from_big_endian(&log.data[32..64]);
-
+                                    Some(PoolEvent::V2Sync { reserve0, reserve1 }) if !meta.is_v3 => {
                                         state_manager.update_pool(PoolState {
                                             address: log.address,
                                             dex: meta.dex,
@@ -772,10 +2129,41 @@ async fn main() -> Result<()> {
                                         });
                                         v2_updated += 1;
                                     }
+                                    _ => {}
+                                }
+                            }
+                        }
+
+                        // Algebra fees move without a log of their own: one globalState()
+                        // read per QuickSwap V3 pool every ALGEBRA_FEE_REFRESH_BLOCKS
+                        if config.algebra_fee_refresh_blocks > 0
+                            && current_block >= algebra_fee_read_block + config.algebra_fee_refresh_blocks
+                        {
+                            let algebra_pools: Vec<Address> = pool_lookup
+                                .iter()
+                                .filter(|(_, meta)| meta.dex.is_quickswap_v3())
+                                .map(|(address, _)| *address)
+                                .collect();
+                            if !algebra_pools.is_empty() {
+                                match timeout(HOT_RPC_TIMEOUT, read_algebra_fees(Arc::clone(&provider), &algebra_pools)).await {
+                                    Ok(fees) => {
+                                        for (address, fee) in fees {
+                                            if let Some(previous) = state_manager.set_v3_fee(&address, fee) {
+                                                if previous != fee {
+                                                    debug!("Algebra fee {:?}: {} → {} (block {})", address, previous, fee, current_block);
+                                                }
+                                            }
+                                        }
+                                    }
+                                    Err(_) => warn!("Algebra fee read timed out after {}s — last fees kept", HOT_RPC_TIMEOUT.as_secs()),
                                 }
                             }
+                            algebra_fee_read_block = current_block;
                         }
 
+                        // Every watched pool is current through this block (no log = unchanged)
+                        state_manager.mark_synced(&pool_addresses, current_block);
+
                         if v3_updated > 0 || v2_updated > 0 {
                             info!(
                                 "Event sync: {} V3 + {} V2 pools updated ({} logs, block {})",
@@ -800,9 +2188,47 @@ async fn main() -> Result<()> {
                 false // poll sync path
             };
 
-            if !sync_ok {
-                // Poll-based sync (original path, or fallback when eth_getLogs fails)
-                let updated = v3_syncer.sync_known_pools_parallel(&v3_pools).await;
+            // Poll-based sync (original path, or fallback when eth_getLogs fails):
+            // one Multicall3 batch when configured, per-pool calls otherwise or
+            // when the batch itself fails
+            let batched = match multicall_syncer {
+                Some(ref syncer) if !sync_ok => {
+                    match timeout(HOT_RPC_TIMEOUT, syncer.sync_known_pools(&v3_pools, &v2_pools)).await {
+                        Ok(Ok(sync)) => {
+                            sync.apply(&state_manager);
+                            if !sync.failed.is_empty() {
+                                warn!(
+                                    "Multicall sync: {} pools failed — previous state kept: {:?}",
+                                    sync.failed.len(), sync.failed
+                                );
+                            }
+                            v3_pools = sync.v3;
+                            v2_pools = sync.v2;
+                            if let Some(ref mut logger) = price_logger {
+                                logger.log_prices(current_block, &v3_pools);
+                            }
+                            true
+                        }
+                        Ok(Err(e)) => {
+                            warn!("Multicall sync failed: {} — per-pool sync this block", e);
+                            false
+                        }
+                        Err(_) => {
+                            warn!("Multicall sync timed out after {}s — per-pool sync this block", HOT_RPC_TIMEOUT.as_secs());
+                            false
+                        }
+                    }
+                }
+                _ => false,
+            };
+
+            if !sync_ok && !batched {
+                let updated = timeout(HOT_RPC_TIMEOUT, v3_syncer.sync_known_pools_parallel(&v3_pools))
+                    .await
+                    .unwrap_or_else(|_| {
+                        warn!("Parallel V3 sync timed out after {}s", HOT_RPC_TIMEOUT.as_secs());
+                        Vec::new()
+                    });
                 if !updated.is_empty() {
                     v3_pools = updated;
                     for pool in &v3_pools {
@@ -817,48 +2243,478 @@ async fn main() -> Result<()> {
                 }
 
                 if !v2_pools.is_empty() {
-                    let updated_v2 = v2_syncer.sync_known_pools_parallel(&v2_pools).await;
-                    v2_pools = updated_v2;
-                    for pool in &v2_pools {
-                        state_manager.update_pool(pool.clone());
+                    match timeout(HOT_RPC_TIMEOUT, v2_syncer.sync_known_pools_parallel(&v2_pools)).await {
+                        Ok(updated_v2) => {
+                            v2_pools = updated_v2;
+                            for pool in &v2_pools {
+                                state_manager.update_pool(pool.clone());
+                            }
+                        }
+                        Err(_) => warn!(
+                            "Parallel V2 sync timed out after {}s — keeping previous state",
+                            HOT_RPC_TIMEOUT.as_secs()
+                        ),
+                    }
+                }
+            }
+
+            // Curve stable pools: polled every block regardless of EVENT_SYNC
+            // (a few view calls per pool); a fresh read also clears reorg staleness
+            if !curve_pools.is_empty() {
+                match timeout(HOT_RPC_TIMEOUT, curve_syncer.sync_known_pools_parallel(&curve_pools)).await {
+                    Ok(updated) => {
+                        let refreshed: Vec<Address> = updated.iter()
+                            .filter(|p| p.last_updated >= current_block)
+                            .map(|p| p.address)
+                            .collect();
+                        curve_pools = updated;
+                        for pool in &curve_pools {
+                            state_manager.update_curve_pool(pool.clone());
+                        }
+                        state_manager.clear_stale(&refreshed);
+                    }
+                    Err(_) => warn!(
+                        "Curve sync timed out after {}s — keeping previous state",
+                        HOT_RPC_TIMEOUT.as_secs()
+                    ),
+                }
+            }
+
+            // DODO PMM pools: polled every block regardless of EVENT_SYNC
+            if !dodo_pools.is_empty() {
+                match timeout(HOT_RPC_TIMEOUT, dodo_syncer.sync_known_pools_parallel(&dodo_pools)).await {
+                    Ok(updated) => {
+                        let refreshed: Vec<Address> = updated.iter()
+                            .filter(|p| p.last_updated >= current_block)
+                            .map(|p| p.address)
+                            .collect();
+                        dodo_pools = updated;
+                        for pool in &dodo_pools {
+                            state_manager.update_dodo_pool(pool.clone());
+                        }
+                        state_manager.clear_stale(&refreshed);
+                    }
+                    Err(_) => warn!(
+                        "DODO sync timed out after {}s — keeping previous state",
+                        HOT_RPC_TIMEOUT.as_secs()
+                    ),
+                }
+            }
+
+            latency.record_since(Stage::Sync, sync_started);
+
+            // Reorg resync: stale pools are re-read from the chain (poll path,
+            // regardless of EVENT_SYNC) and skipped by the detector until then
+            let stale = state_manager.stale_pools();
+            if !stale.is_empty() {
+                let stale_v3: Vec<V3PoolState> = v3_pools.iter().filter(|p| stale.contains(&p.address)).cloned().collect();
+                let stale_v2: Vec<PoolState> = v2_pools.iter().filter(|p| stale.contains(&p.address)).cloned().collect();
+                let resync = async {
+                    tokio::join!(
+                        v3_syncer.sync_known_pools_parallel(&stale_v3),
+                        futures::future::join_all(stale_v2.iter().map(|p| v2_syncer.sync_reserves(p))),
+                    )
+                };
+                match timeout(HOT_RPC_TIMEOUT, resync).await {
+                    Ok((v3_fresh, v2_fresh)) => {
+                        let mut resynced = Vec::new();
+                        for pool in v3_fresh {
+                            resynced.push(pool.address);
+                            state_manager.update_v3_pool(pool);
+                        }
+                        for pool in v2_fresh.into_iter().flatten() {
+                            resynced.push(pool.address);
+                            state_manager.update_pool(pool);
+                        }
+                        state_manager.clear_stale(&resynced);
+                        info!("Reorg resync: {}/{} stale pools refreshed", resynced.len(), stale.len());
+                    }
+                    Err(_) => warn!(
+                        "Reorg resync timed out after {}s — {} pools stay stale",
+                        HOT_RPC_TIMEOUT.as_secs(), stale.len()
+                    ),
+                }
+            }
+
+            // Stranded-funds alarm (every N blocks, one aggregate3 for all tokens)
+            if let Some(ref mut monitor) = stranded_monitor {
+                if monitor.is_due(current_block) {
+                    let tokens = token_universe(&state_manager, &config, &token_registry);
+                    match rpc_timeout("stranded check", monitor.check(&multicall_quoter, &tokens, current_block)).await {
+                        Ok(report) => {
+                            monitor.alert(&report).await;
+                            for balance in monitor.rescue_targets(&report) {
+                                match executor.rescue_tokens(&balance).await {
+                                    Ok((tx_hash, block)) => monitor.record_rescue(&balance, tx_hash, block).await,
+                                    Err(e) => monitor.rescue_failed(&balance, &e).await,
+                                }
+                            }
+                        }
+                        Err(e) => warn!("Stranded-funds check failed: {}", e),
+                    }
+                }
+            }
+
+            // Inventory manager (every N blocks, one aggregate3 of wallet balances)
+            if let Some(ref mut manager) = inventory_manager {
+                if manager.is_due(current_block) {
+                    let tokens = token_universe(&state_manager, &config, &token_registry);
+                    match rpc_timeout("inventory check", manager.check(&multicall_quoter, &tokens, current_block)).await {
+                        Ok(report) => {
+                            let plans = manager.plans(&report, &tokens, &state_manager);
+                            manager.alert(&report, &plans);
+                            for plan in manager.execution_targets(&plans) {
+                                match executor.rebalance(&plan).await {
+                                    Ok((tx_hash, amount_out, block)) => manager.record_rebalance(&plan, tx_hash, amount_out, block),
+                                    Err(e) => manager.rebalance_failed(&plan, &e),
+                                }
+                            }
+                        }
+                        Err(e) => warn!("Inventory check failed: {}", e),
+                    }
+                }
+            }
+
+            // Token metadata re-validation (one aggregate3 of decimals/symbol for all tokens)
+            if let Some(ref mut validator) = token_validator {
+                if validator.is_due(chrono::Utc::now()) {
+                    let tokens = token_universe(&state_manager, &config, &token_registry);
+                    let pools = watched_pools(&state_manager);
+                    match rpc_timeout("token validation", validator.check(&multicall_quoter, &tokens, &pools, current_block)).await {
+                        Ok(report) => validator.alert(&report).await,
+                        Err(e) => warn!("Token metadata check failed: {}", e),
+                    }
+                }
+                // Re-approved tokens: re-sync their pools so cached decimals are current
+                for released in validator.poll_approvals(current_block) {
+                    for pool in watched_pools(&state_manager).into_iter().filter(|p| released.pools.contains(&p.address)) {
+                        let resynced = if pool.dex.is_v3() {
+                            v3_syncer.sync_pool_by_address(pool.address, pool.dex).await.map(|mut new_state| {
+                                new_state.pair.symbol = pool.pair.symbol.clone();
+                                state_manager.replace_v3_pool(&pool.address, new_state.clone());
+                                v3_pools.retain(|p| p.address != pool.address);
+                                v3_pools.push(new_state);
+                            })
+                        } else {
+                            v2_syncer.sync_pool_by_address(pool.address, pool.dex).await.map(|mut new_state| {
+                                new_state.pair.symbol = pool.pair.symbol.clone();
+                                state_manager.update_pool(new_state.clone());
+                                v2_pools.retain(|p| p.address != pool.address);
+                                v2_pools.push(new_state);
+                            })
+                        };
+                        if let Err(e) = resynced {
+                            warn!("Token validation: re-sync of {:?} failed: {}", pool.address, e);
+                        }
+                    }
+                }
+            }
+
+            // Pool migration detector (one aggregate3 of liquidity/getReserves for all pools)
+            if let Some(ref mut migrations) = migration_monitor {
+                if migrations.is_due(current_block) {
+                    let candidates = match rpc_timeout(
+                        "migration check",
+                        migrations.check(&multicall_quoter, &watched_pools(&state_manager), current_block),
+                    )
+                    .await
+                    {
+                        Ok(candidates) => candidates,
+                        Err(e) => {
+                            warn!("Pool migration check failed: {}", e);
+                            Vec::new()
+                        }
+                    };
+                    for candidate in candidates {
+                        let old = candidate.pool.address;
+                        let canonical = match canonical_pool(Arc::clone(&provider), &config, &candidate.pool).await {
+                            Ok(addr) => addr,
+                            Err(e) => {
+                                warn!("Migration: {}", e);
+                                continue;
+                            }
+                        };
+                        let new_depth = if canonical.is_zero() || canonical == old {
+                            0
+                        } else {
+                            let probe = WatchedPool { address: canonical, ..candidate.pool.clone() };
+                            PoolMigrationMonitor::depth_of(&multicall_quoter, &probe).await.unwrap_or_else(|e| {
+                                warn!("Migration: {}", e);
+                                0
+                            })
+                        };
+                        let suggestion = match migrations.evaluate(&candidate, canonical, new_depth, current_block) {
+                            Some(s) => s,
+                            None => continue,
+                        };
+                        migrations.suggest(&suggestion).await;
+                        if !migrations.auto_migrate() {
+                            continue;
+                        }
+
+                        // AUTO_MIGRATE: full state for the new address, then swap it into
+                        // the state manager, the poll/event sync lists and the whitelist
+                        let symbol = candidate.pool.pair.symbol.clone();
+                        let swapped = if candidate.pool.dex.is_v3() {
+                            match v3_syncer.sync_pool_by_address(canonical, candidate.pool.dex).await {
+                                Ok(mut new_state) => {
+                                    new_state.pair.symbol = symbol;
+                                    state_manager.replace_v3_pool(&old, new_state.clone());
+                                    v3_pools.retain(|p| p.address != old);
+                                    v3_pools.push(new_state);
+                                    true
+                                }
+                                Err(e) => {
+                                    warn!("Migration: sync of {:?} failed — not migrating: {}", canonical, e);
+                                    false
+                                }
+                            }
+                        } else {
+                            match v2_syncer.sync_pool_by_address(canonical, candidate.pool.dex).await {
+                                Ok(mut new_state) => {
+                                    new_state.pair.symbol = symbol;
+                                    state_manager.remove_pool(&old);
+                                    state_manager.update_pool(new_state.clone());
+                                    v2_pools.retain(|p| p.address != old);
+                                    v2_pools.push(new_state);
+                                    true
+                                }
+                                Err(e) => {
+                                    warn!("Migration: sync of {:?} failed — not migrating: {}", canonical, e);
+                                    false
+                                }
+                            }
+                        };
+                        if !swapped {
+                            continue;
+                        }
+                        if let Some(meta) = pool_lookup.remove(&old) {
+                            pool_lookup.insert(canonical, meta);
+                        }
+                        pool_addresses = pool_lookup.keys().cloned().collect();
+                        match whitelist.migrate_pool(&old, &canonical, current_block, "liquidity collapsed; factory returns new address") {
+                            Ok(change) => {
+                                if let Err(e) = persist_migration(whitelist_path, &change) {
+                                    warn!("Migration: whitelist file not updated: {}", e);
+                                }
+                            }
+                            Err(e) => warn!("Migration: in-memory whitelist not updated: {}", e),
+                        }
+                        migrations.record_applied(old, canonical).await;
+                    }
+                }
+            }
+
+            // Pool health: quarantine idle/drained/rejected pools from detection,
+            // reinstate recovered ones
+            if let Some(ref mut health) = pool_health {
+                for transition in health.check(&state_manager, current_block) {
+                    match transition {
+                        HealthTransition::Quarantined { pool, pair, dex, issue } => {
+                            warn!("POOL QUARANTINED: {} {:?} {:?} — {}", pair, dex, pool, issue)
+                        }
+                        HealthTransition::Reinstated { pool, pair, dex, after_blocks } => {
+                            info!("Pool reinstated: {} {:?} {:?} (healthy again after {} blocks)", pair, dex, pool, after_blocks)
+                        }
                     }
                 }
             }
 
+            // Gas accounting: native token price from the freshly synced reference pool
+            native_price_updater.refresh(&state_manager);
+
+            // Cross-chain research: this chain's prices vs the remote collector's
+            if let Some(ref mut monitor) = cross_chain_monitor {
+                let local = cross_chain::local_state(&state_manager, &config, current_block);
+                if let Err(e) = monitor.check(&local) {
+                    warn!("Cross-chain divergence write failed: {}", e);
+                }
+            }
+
             // Scan for opportunities
-            let all_opportunities = detector.scan_opportunities();
-
-            // Filter out routes that are in cooldown (recently failed, likely stale/dead)
-            let suppressed = all_opportunities.iter()
-                .filter(|opp| route_cooldown.is_cooled_down(
-                    &opp.pair.symbol, opp.buy_dex, opp.sell_dex, current_block
-                ))
-                .count();
-            let opportunities: Vec<_> = all_opportunities.into_iter()
-                .filter(|opp| !route_cooldown.is_cooled_down(
-                    &opp.pair.symbol, opp.buy_dex, opp.sell_dex, current_block
-                ))
+            // Curve and Kyber Elastic routes are detection-only: logged here, never executed
+            let detect_started = Instant::now();
+            let (detection_only_opportunities, all_opportunities): (Vec<_>, Vec<_>) = detector
+                .scan_opportunities()
+                .into_iter()
+                .partition(|o| o.buy_dex.is_detection_only() || o.sell_dex.is_detection_only());
+            latency.record_since(Stage::Detect, detect_started);
+            // A/B shadow scans the same state; raw scans compared before the live gates
+            if let Some(ref mut ab) = ab_shadow {
+                let divergences = ab.observe(current_block, &all_opportunities, config.min_profit_usd);
+                if !divergences.is_empty() {
+                    debug!("A/B shadow: {} divergence(s) vs live at block {}", divergences.len(), current_block);
+                }
+            }
+            let detected = detection_only_opportunities.len() + all_opportunities.len();
+            if let Some(ref journal) = event_journal {
+                for opp in detection_only_opportunities.iter().chain(&all_opportunities) {
+                    journal.emit(current_block, BotEvent::detected(current_block, opp));
+                }
+            }
+            for opp in &detection_only_opportunities {
+                info!(
+                    "🧪 DETECTION-ONLY: {} | Buy {} @ {:.6} | Sell {} @ {:.6} | Spread {:.3}% | Est {}",
+                    opp.pair.symbol, opp.buy_dex, opp.buy_price, opp.sell_dex, opp.sell_price,
+                    opp.spread_percent, format_usd(opp.estimated_profit)
+                );
+            }
+            // Stale-state guard: no leg older than MAX_POOL_AGE_BLOCKS
+            let all_opportunities = detector.drop_stale_opportunities(all_opportunities, current_block);
+            // Spread confirmation: route's spread seen SPREAD_CONFIRM_BLOCKS blocks running
+            let all_opportunities = detector.confirm_spreads(all_opportunities, current_block);
+            // Same spread found twice in this scan: keep the more profitable one
+            let all_opportunities = opportunity_registry.merge(all_opportunities);
+            opportunity_registry.evict(current_block);
+            // Live state API: publish the prices this scan read + its detections
+            if let Some(ref mut a) = api {
+                a.record_opportunities(current_block, &all_opportunities);
+                let status = StatusCounters {
+                    block_height: current_block,
+                    scans: total_scans,
+                    opportunities: total_opportunities,
+                    routes_cooled: route_cooldown.active_count() as u64,
+                };
+                a.publish_block(&state_manager, &route_cooldown, &status, chrono::Utc::now().timestamp() as u64);
+            }
+            let mut block_decisions = (shadow_role != ShadowRole::Off)
+                .then(|| BlockDecisions::from_detected(current_block, &all_opportunities, config.min_profit_usd));
+
+            // Anomaly monitor: feed detection count, close the window when due
+            if let Some(ref mut monitor) = anomaly_monitor {
+                monitor.record_opportunities(all_opportunities.len());
+                for flag in monitor.tick(chrono::Utc::now().timestamp() as u64) {
+                    alert_dispatcher.dispatch(flag.severity, &flag.message());
+                }
+            }
+
+            match burst_triage.observe(all_opportunities.len(), current_block) {
+                Some(BurstTransition::Entered { count }) => warn!(
+                    "🌊 Burst: {} opportunities at block {} — triage mode ON (spread floor + top {} per silo)",
+                    count, current_block, config.burst_top_k
+                ),
+                Some(BurstTransition::Exited { blocks }) => info!(
+                    "🌊 Burst over at block {} after {} block(s) — triage mode OFF", current_block, blocks
+                ),
+                None => {}
+            }
+
+            // Filter out routes that are in cooldown (recently failed, likely stale/dead).
+            // Suppressed routes are kept for the cooled counterfactual export.
+            let (cooled, opportunities): (Vec<_>, Vec<_>) = all_opportunities.into_iter()
+                .partition(|opp| route_cooldown.is_cooled_down_for_source(
+                    &opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.trade_size_usd, current_block, CooldownSource::BlockScan
+                ));
+            if !cooled.is_empty() {
+                info!("🧊 {} routes suppressed (cooldown), {} remaining", cooled.len(), opportunities.len());
+            }
+            if let Some(ref mut archive) = opportunity_archive {
+                archive.expire(current_block);
+                for opp in &opportunities {
+                    archive.record_detected(current_block, opp);
+                }
+            }
+            // Already submitted within the dedup TTL (this loop or a mempool backrun)
+            let before_dedup = opportunities.len();
+            let opportunities: Vec<_> = opportunities.into_iter()
+                .filter(|opp| !opportunity_registry.is_duplicate(opp, current_block))
+                .collect();
+            if opportunities.len() < before_dedup {
+                info!("🔁 {} opportunities already submitted (dedup), {} remaining", before_dedup - opportunities.len(), opportunities.len());
+            }
+
+            // Canary: close finished windows, drop demoted pools, cap canary route sizes
+            for decision in canary.due_decisions(chrono::Utc::now().timestamp()) {
+                canary.apply_decision(&decision);
+                if let Some(ref mut alerter) = live_alerter {
+                    alerter.canary_decision(&decision);
+                }
+                info!("Canary report:\n{}", canary.report());
+            }
+            let mut opportunities: Vec<_> = opportunities.into_iter()
+                .filter(|opp| !canary.is_demoted_route(opp))
+                .filter(|opp| !token_validator.as_ref().is_some_and(|v| v.is_quarantined_route(opp)))
                 .collect();
-            if suppressed > 0 {
-                info!("🧊 {} routes suppressed (cooldown), {} remaining", suppressed, opportunities.len());
+            for opp in opportunities.iter_mut() {
+                if canary.cap_trade_size(opp) {
+                    info!("🐤 Canary route {} {:?}→{:?}: size capped to {}", opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.size_display());
+                }
             }
 
+            // Liquidity guard: structural rejection (TradeOutcome::InsufficientLiquidity) —
+            // route cooldown is left untouched so the route returns with its liquidity
+            opportunities.retain_mut(|opp| match liquidity_guard.check(opp) {
+                LiquidityVerdict::Pass => true,
+                LiquidityVerdict::Downsized { fraction, .. } => {
+                    info!("💧 {} {:?}→{:?}: downsized to {} (would consume {:.1}% of depth)",
+                          opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.size_display(), fraction * 100.0);
+                    true
+                }
+                LiquidityVerdict::Rejected(reason) => {
+                    info!("💧 {} {:?}→{:?} skipped — {}", opp.pair.symbol, opp.buy_dex, opp.sell_dex, reason);
+                    false
+                }
+            });
+
+            // Burst triage: marginal spreads and all but the top-K per silo never
+            // reach the pre-screen (counted apart from pre-screen rejections)
+            let (mut opportunities, triage) = burst_triage.triage(opportunities);
+            if burst_triage.is_active() {
+                info!(
+                    "🌊 Triage block {}: {} kept across {} silo(s) | {} below spread floor | {} beyond top-K",
+                    current_block, triage.kept, triage.silos, triage.below_spread, triage.beyond_top_k
+                );
+            }
+
+            // Optimal sizing: shrink each route to its profit-maximizing size
+            // (never above the incoming size); routes no size makes pay are dropped
+            if config.optimal_sizing && !opportunities.is_empty() {
+                let verdicts = optimal_sizer.size_all(&mut opportunities, &multicall_quoter).await;
+                for (opp, verdict) in opportunities.iter().zip(&verdicts) {
+                    match verdict {
+                        SizingVerdict::Sized { method, from, .. } if *from != opp.trade_size => {
+                            info!("📐 {} {:?}→{:?}: sized {} → {} ({}, est. profit {})",
+                                  opp.pair.symbol, opp.buy_dex, opp.sell_dex, from, opp.size_display(),
+                                  method.as_str(), format_usd(opp.estimated_profit));
+                        }
+                        SizingVerdict::Unprofitable { method, best_profit_raw } => {
+                            info!("📐 {} {:?}→{:?} skipped — best {} size profit {} below minimum",
+                                  opp.pair.symbol, opp.buy_dex, opp.sell_dex, method.as_str(),
+                                  opp.quote_delta_display(*best_profit_raw));
+                        }
+                        _ => {}
+                    }
+                }
+                let mut verdicts = verdicts.into_iter();
+                opportunities.retain(|_| !matches!(verdicts.next(), Some(SizingVerdict::Unprofitable { .. })));
+            }
+
+            // Block annotations (PRICE_LOG_BLOCKS): txs sent this block and their net
+            let mut block_submitted = 0usize;
+            let mut block_net_usd = 0.0;
             if !opportunities.is_empty() {
                 total_opportunities += opportunities.len() as u64;
 
-                for opp in &opportunities {
+                // Per-opportunity lines are summarized by the triage line during a burst
+                for opp in opportunities.iter().filter(|_| !burst_triage.is_active()) {
                     info!(
-                        "📊 {} | Spread: {:.2}% | Est. Profit: ${:.2} | Size: {}",
+                        "📊 {} | Spread: {:.2}% | Est. Profit: {} | Size: {}",
                         opp.pair.symbol,
                         opp.spread_percent,
-                        opp.estimated_profit,
-                        opp.trade_size
+                        format_usd(opp.estimated_profit),
+                        opp.size_display()
                     );
                 }
 
                 // Build execution order: either multicall-verified or estimated-profit-sorted
                 // Vec of (original_index, optional quoted_profit_raw for logging)
-                let execution_order: Vec<(usize, Option<i128>)> = if config.skip_multicall_prescreen {
+                // `unscreened`: indices that reach the executor without a pre-screen verdict
+                let mut unscreened: std::collections::HashSet<usize> = if config.skip_multicall_prescreen {
+                    (0..opportunities.len()).collect()
+                } else {
+                    std::collections::HashSet::new()
+                };
+                let mut execution_order: Vec<(usize, Option<i128>)> = if config.skip_multicall_prescreen {
                     // Direct path: skip batch_verify(), sort by estimated_profit descending
                     // Executor's own Quoter + eth_estimateGas still protects capital.
                     let mut indices: Vec<usize> = (0..opportunities.len()).collect();
@@ -867,23 +2723,97 @@ async fn main() -> Result<()> {
                             .partial_cmp(&opportunities[*a].estimated_profit)
                             .unwrap_or(std::cmp::Ordering::Equal)
                     });
+                    // Canary routes are always pre-screened, even when the pre-screen is skipped
+                    let canary_idx: Vec<usize> = indices.iter().copied()
+                        .filter(|&i| canary.is_canary_route(&opportunities[i]))
+                        .collect();
+                    if !canary_idx.is_empty() {
+                        let canary_opps: Vec<ArbitrageOpportunity> = canary_idx.iter()
+                            .map(|&i| opportunities[i].clone())
+                            .collect();
+                        let rejected: std::collections::HashSet<usize> =
+                            match rpc_timeout("canary pre-screen", multicall_quoter.batch_verify(&canary_opps, &config)).await {
+                                Ok(verified) => verified.iter()
+                                    .filter(|v| !(v.both_legs_valid && v.quoted_profit_raw > 0))
+                                    .map(|v| canary_idx[v.original_index])
+                                    .collect(),
+                                Err(e) => {
+                                    warn!("Canary pre-screen failed: {} — skipping canary routes this block", e);
+                                    canary_idx.iter().copied().collect()
+                                }
+                            };
+                        indices.retain(|i| !rejected.contains(i));
+                    }
                     info!(
                         "Multicall pre-screen SKIPPED — {} opportunities sorted by est. profit, direct to executor",
                         indices.len()
                     );
                     indices.into_iter().map(|i| (i, None)).collect()
                 } else {
-                    // Multicall3 batch pre-screen: verify all opportunities in 1 RPC call
-                    let verified = match multicall_quoter.batch_verify(&opportunities, &config).await {
-                        Ok(v) => v,
-                        Err(e) => {
-                            warn!("Multicall batch verify failed: {} — falling back to unfiltered", e);
-                            // Fallback: pass all opps through (executor's own Quoter checks still apply)
-                            opportunities.iter().enumerate()
-                                .map(|(i, _)| VerifiedOpportunity::passthrough(i))
-                                .collect()
+                    // Adaptive pre-screen policy: routes with a long agreeing streak go
+                    // direct to the executor (canary routes are always pre-screened)
+                    let (screen_idx, direct_idx): (Vec<usize>, Vec<usize>) = (0..opportunities.len())
+                        .partition(|&i| {
+                            canary.is_canary_route(&opportunities[i])
+                                || prescreen_policy.decide(&opportunities[i]).prescreen()
+                        });
+                    unscreened.extend(direct_idx.iter().copied());
+                    if !direct_idx.is_empty() {
+                        info!("🎯 Pre-screen policy: {} promoted route(s) direct to executor", direct_idx.len());
+                    }
+                    let screen_opps: Vec<ArbitrageOpportunity> = screen_idx.iter()
+                        .map(|&i| opportunities[i].clone())
+                        .collect();
+
+                    // Multicall3 batch pre-screen: buy legs, then chained sell legs (2 RPC calls)
+                    let mut prescreen_ran = !screen_opps.is_empty();
+                    let verified = if screen_opps.is_empty() {
+                        Vec::new()
+                    } else {
+                        match rpc_timeout("multicall pre-screen", multicall_quoter.batch_verify(&screen_opps, &config)).await {
+                            Ok(v) => v,
+                            Err(e) => {
+                                prescreen_ran = false;
+                                warn!("Multicall batch verify failed: {} — falling back to unfiltered", e);
+                                // Fallback: pass all opps through (executor's own Quoter checks still apply)
+                                screen_opps.iter().enumerate()
+                                    .map(|(i, _)| VerifiedOpportunity::passthrough(i))
+                                    .collect()
+                            }
                         }
                     };
+                    // Batch indices → indices into `opportunities`
+                    let verified: Vec<VerifiedOpportunity> = verified.into_iter()
+                        .map(|mut v| {
+                            v.original_index = screen_idx[v.original_index];
+                            v
+                        })
+                        .collect();
+
+                    if let (Some(ref journal), true) = (&event_journal, prescreen_ran) {
+                        for v in &verified {
+                            journal.emit(current_block, BotEvent::verified(current_block, &opportunities[v.original_index], v));
+                        }
+                    }
+                    if let (Some(ref archive), true) = (&opportunity_archive, prescreen_ran) {
+                        for v in &verified {
+                            archive.record_verified(current_block, &opportunities[v.original_index], v);
+                        }
+                    }
+
+                    if let Some(ref export) = prescreen_export {
+                        if let Err(e) = export.export(&opportunities, &verified, current_block) {
+                            warn!("Pre-screen export failed: {}", e);
+                        }
+                    }
+
+                    // Carry both leg quotes to execution (realized slippage is measured against them)
+                    for v in verified.iter().filter(|v| v.both_legs_valid && !v.buy_quoted_out.is_zero()) {
+                        opportunities[v.original_index].quoted_legs = Some(QuotedLegs {
+                            buy_out: v.buy_quoted_out,
+                            sell_out: v.sell_quoted_out,
+                        });
+                    }
 
                     // Filter to verified-only AND quoted-profitable, rank by quoted profit
                     let mut ranked: Vec<&VerifiedOpportunity> = verified.iter()
@@ -891,204 +2821,485 @@ async fn main() -> Result<()> {
                         .collect();
                     ranked.sort_by(|a, b| b.quoted_profit_raw.cmp(&a.quoted_profit_raw));
 
-                    let filtered_count = opportunities.len() - ranked.len();
+                    if let Some(monitor) = anomaly_monitor.as_mut().filter(|_| prescreen_ran) {
+                        monitor.record_prescreen(ranked.len(), screen_opps.len());
+                    }
+                    if prescreen_ran {
+                        for v in verified.iter().filter(|v| !(v.both_legs_valid && v.quoted_profit_raw > 0)) {
+                            prescreen_policy.record_rejected(&opportunities[v.original_index]);
+                        }
+                        // Pool health: quoter failures charged to the failing leg's pool
+                        if let Some(ref mut health) = pool_health {
+                            for v in &verified {
+                                let opp = &opportunities[v.original_index];
+                                let error = v.error.as_deref().unwrap_or("");
+                                for (pool, leg) in [(opp.buy_pool_address, "Buy leg"), (opp.sell_pool_address, "Sell leg")] {
+                                    let Some(pool) = pool else { continue };
+                                    if v.both_legs_valid {
+                                        health.record_quoter_success(pool);
+                                    } else if error.starts_with(leg) {
+                                        health.record_quoter_rejection(pool, current_block);
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    let filtered_count = screen_opps.len() - ranked.len();
                     if filtered_count > 0 {
                         info!(
                             "Multicall pre-screen: {}/{} verified, {} filtered out",
-                            ranked.len(), opportunities.len(), filtered_count
+                            ranked.len(), screen_opps.len(), filtered_count
                         );
                     }
 
-                    ranked.into_iter().map(|v| (v.original_index, Some(v.quoted_profit_raw))).collect()
+                    // Promoted routes join the ranking at their estimated profit (USD)
+                    let mut order: Vec<(usize, Option<i128>)> = ranked.into_iter()
+                        .map(|v| (v.original_index, Some(v.quoted_profit_raw)))
+                        .chain(direct_idx.into_iter().map(|i| (i, None)))
+                        .collect();
+                    let profit_usd = |&(i, qp): &(usize, Option<i128>)| {
+                        let opp = &opportunities[i];
+                        match qp {
+                            Some(qp) => opp.quote_to_usd(opp.quote_amount(U256::from(qp.max(0) as u128))),
+                            None => opp.estimated_profit,
+                        }
+                    };
+                    order.sort_by(|a, b| profit_usd(b).partial_cmp(&profit_usd(a)).unwrap_or(std::cmp::Ordering::Equal));
+                    order
                 };
 
+                // Competition model: re-rank by EV — profit × P(win) minus the gas a lost race costs
+                if competition.is_enabled() && config.arb_executor_address.is_some() {
+                    let candidates: Vec<EvCandidate> = execution_order.iter()
+                        .map(|&(i, qp)| {
+                            let opp = &opportunities[i];
+                            let profit_usd = match qp {
+                                Some(qp) => opp.quote_to_usd(opp.quote_amount(U256::from(qp.max(0) as u128))),
+                                None => opp.estimated_profit,
+                            };
+                            EvCandidate { route: route_key(opp), profit_usd }
+                        })
+                        .collect();
+                    let ranked = competition.rank(&candidates, executor.atomic_priority_gwei() as f64, config.estimated_gas_cost_usd);
+                    for r in &ranked {
+                        opportunities[execution_order[r.index].0].win_probability = Some(r.win_probability);
+                    }
+                    execution_order = ranked.iter().map(|r| execution_order[r.index]).collect();
+                }
+
                 // Try opportunities in ranked order (best first, fall through on Quoter rejections)
-                for (rank, (idx, quoted_profit)) in execution_order.iter().enumerate() {
-                    let opp = &opportunities[*idx];
+                let mut realized_usd = 0.0;
+                let mut lost_races: Vec<LostRace> = Vec::new();
+                let mut taken = 0usize;
+                let log_try = |rank: usize, opp: &ArbitrageOpportunity, quoted_profit: Option<i128>| {
                     if let Some(qp) = quoted_profit {
                         info!(
-                            "TRY #{}: {} - Buy {:?} Sell {:?} - ${:.2} (quoted_profit_raw={})",
+                            "TRY #{}: {} - Buy {:?} Sell {:?} - {} on {} (quoted profit {})",
                             rank + 1, opp.pair.symbol, opp.buy_dex, opp.sell_dex,
-                            opp.estimated_profit, qp
+                            format_usd(opp.estimated_profit), opp.size_display(), opp.quote_delta_display(qp)
                         );
                     } else {
                         info!(
-                            "TRY #{}: {} - Buy {:?} Sell {:?} - ${:.2} (est, direct to executor)",
+                            "TRY #{}: {} - Buy {:?} Sell {:?} - {} on {} (est, direct to executor)",
                             rank + 1, opp.pair.symbol, opp.buy_dex, opp.sell_dex,
-                            opp.estimated_profit
+                            format_usd(opp.estimated_profit), opp.size_display()
                         );
                     }
+                };
 
-                    match executor.execute(opp).await {
-                        Ok(result) => {
-                            if result.success {
-                                info!(
-                                    "Trade complete: {} | Net profit: ${:.2} | Time: {}ms",
-                                    result.opportunity,
-                                    result.net_profit_usd,
-                                    result.execution_time_ms
-                                );
-                                route_cooldown.record_success(&opp.pair.symbol, opp.buy_dex, opp.sell_dex);
-                                break; // Stop after successful trade
-                            } else {
-                                let error_msg = result.error.unwrap_or_else(|| "Unknown".to_string());
-
-                                // On-chain tx was submitted — distinguish safe (atomic) from dangerous (legacy).
-                                if result.tx_hash.is_some() {
-                                    // Receipt timeout: tx submitted but unconfirmed — unknown state, stop.
-                                    if error_msg.contains("Receipt timeout") {
-                                        error!(
-                                            "HALT: Receipt timeout for {} | TX: {} — tx may still confirm",
-                                            result.opportunity,
-                                            result.tx_hash.as_deref().unwrap_or("?")
-                                        );
-                                        error!("Unknown tx state — manual check needed. Stopping all trading.");
-                                        break;
-                                    }
-
-                                    // Atomic mode: on-chain reverts and gas-negative trades are SAFE.
-                                    // The atomic contract's revert protects capital — only gas is lost.
-                                    // Gas-negative trades: tokens were swapped and returned, just gas > profit.
-                                    if config.arb_executor_address.is_some() {
-                                        route_cooldown.record_failure(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, current_block);
-                                        if error_msg.contains("reverted") {
-                                            // Atomic revert: no trade happened, only gas burned. Try next route.
-                                            info!(
-                                                "Atomic tx reverted on-chain (safe, gas only): {} | TX: {}",
-                                                result.opportunity,
-                                                result.tx_hash.as_deref().unwrap_or("?")
-                                            );
-                                            continue;
-                                        } else {
-                                            // Gas-negative completed trade: arb executed but gas > profit.
-                                            // Market state changed from our trade — wait for next block.
-                                            warn!(
-                                                "Atomic trade gas-negative: {} | TX: {} | Error: {}",
-                                                result.opportunity,
-                                                result.tx_hash.as_deref().unwrap_or("?"),
-                                                error_msg
-                                            );
-                                            break;
-                                        }
-                                    }
-
-                                    // Legacy two-tx mode: capital genuinely at risk (buy on-chain, sell failed).
-                                    error!(
-                                        "HALT: On-chain tx submitted but trade failed: {} | Error: {} | TX: {}",
-                                        result.opportunity, error_msg,
-                                        result.tx_hash.as_deref().unwrap_or("?")
-                                    );
-                                    error!("Capital committed — manual recovery needed. Stopping all trading.");
-                                    break;
+                // MAX_PARALLEL_SUBMISSIONS > 1: the top-K verified routes on disjoint
+                // pools go out back-to-back; results are processed as receipts land
+                let parallel_picks: Vec<(usize, usize)> = if config.max_parallel_submissions > 1
+                    && config.arb_executor_address.is_some()
+                    && !shutdown.is_shutting_down()
+                {
+                    select_disjoint(&execution_order, &opportunities, config.max_parallel_submissions)
+                        .into_iter()
+                        .map(|rank| (rank, execution_order[rank].0))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                let mut parallel = if parallel_picks.len() > 1 {
+                    for &(rank, idx) in &parallel_picks {
+                        log_try(rank, &opportunities[idx], execution_order[rank].1);
+                    }
+                    Some(shutdown.track(ParallelBatch::submit(&mut executor, &opportunities, &parallel_picks)).await)
+                } else {
+                    None
+                };
+                let mut serial = execution_order.iter().enumerate();
+                loop {
+                    let (rank, idx, exec) = match parallel.as_mut() {
+                        Some(batch) => match shutdown.track(batch.next_result(&mut executor)).await {
+                            Some(attempt) => attempt,
+                            None => break,
+                        },
+                        None => {
+                            let Some((rank, &(idx, quoted_profit))) = serial.next() else { break };
+                            if shutdown.is_shutting_down() {
+                                info!("Shutdown requested — not taking further opportunities this block");
+                                break;
+                            }
+                            log_try(rank, &opportunities[idx], quoted_profit);
+                            (rank, idx, shutdown.track(executor.execute(&opportunities[idx])).await)
+                        }
+                    };
+                    let opp = &opportunities[idx];
+                    if let Ok(ref result) = exec {
+                        if let Some(ref mut decisions) = block_decisions {
+                            decisions.record_executed(opp, result);
+                        }
+                        canary.record_result(opp, result);
+                        if let Some(ref mut monitor) = anomaly_monitor {
+                            monitor.record_trade(result);
+                        }
+                        if let Some(ref mut t) = telemetry {
+                            t.record_trade(result);
+                        }
+                        if let Some(ref mut a) = api {
+                            a.record_trade(result);
+                        }
+                        watchdog::registry().progress().record_trade(result);
+                        if result.success {
+                            realized_usd += result.profit_usd;
+                            taken += 1;
+                        }
+                    }
+                    if exec.is_err() {
+                        if let Some(ref mut t) = telemetry {
+                            t.record_execution_error();
+                        }
+                        if let Some(ref mut a) = api {
+                            a.record_execution_error();
+                        }
+                    }
+                    if let Some(result) = exec.as_ref().ok().filter(|r| r.tx_hash.is_some()) {
+                        opportunity_registry.record_submission(opp, current_block);
+                        block_submitted += 1;
+                        block_net_usd += result.net_profit_usd;
+                    }
+                    let outcome = TradeOutcome::classify(&exec, config.arb_executor_address.is_some());
+                    outcome.apply_cooldown(&mut route_cooldown, opp, current_block);
+                    if let Some(ref mut archive) = opportunity_archive {
+                        archive.record_execution(current_block, opp, &exec, outcome);
+                    }
+                    if let Some(ref journal) = event_journal {
+                        journal.emit(current_block, BotEvent::cooldown(current_block, opp, outcome));
+                    }
+                    route_stats.record(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, &exec, outcome);
+                    let submitted = exec.as_ref().ok().and_then(|r| r.tx_hash.as_deref());
+                    if let Some(ms) = submitted.and_then(|h| executor.take_block_to_submit(h)) {
+                        route_stats.record_block_to_submit(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, ms);
+                    }
+                    if let Some(ref model) = threshold_model {
+                        model.record(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, &exec, outcome);
+                    }
+                    if let Some(ref mut alerter) = live_alerter {
+                        alerter.trade_outcome(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, &exec, outcome);
+                    }
+                    prescreen_policy.record_outcome(opp, !unscreened.contains(&idx), outcome);
+                    if let (Some(p), Ok(result)) = (opp.win_probability, exec.as_ref()) {
+                        let route = route_key(opp);
+                        match outcome {
+                            TradeOutcome::Success | TradeOutcome::GasNegative => {
+                                competition.record_race(&route, executor.atomic_priority_gwei() as f64, p, true);
+                            }
+                            TradeOutcome::AtomicRevert => {
+                                competition.record_race(&route, executor.atomic_priority_gwei() as f64, p, false);
+                                if let Some(tx_hash) = result.tx_hash.as_deref().and_then(|h| h.parse().ok()) {
+                                    let pools = [opp.buy_pool_address, opp.sell_pool_address].into_iter().flatten().collect();
+                                    lost_races.push(LostRace { route, tx_hash, pools });
                                 }
+                            }
+                            _ => {}
+                        }
+                    }
+                    if let Some(ref export) = prescreen_export {
+                        let record = AttemptRecord::new(opp, current_block, outcome, exec.as_ref().ok());
+                        if let Err(e) = export.export_attempt(&record) {
+                            warn!("Route attempt export failed: {}", e);
+                        }
+                    }
+                    match exec {
+                        Ok(ref result) => log_trade_outcome(result, outcome, rank),
+                        Err(ref e) => error!("Execution error: {}", e),
+                    }
+                    // A parallel batch is already on chain: keep settling it either way
+                    match outcome.next_step() {
+                        NextStep::TryNext => continue,
+                        // Success stops after one trade; failures stop for safety
+                        NextStep::StopBlock if parallel.is_none() => break,
+                        NextStep::StopBlock => continue,
+                        NextStep::Halt => {
+                            deadman_state.trip_breaker(format!("{:?} on {} at block {}", outcome, opp.pair.symbol, current_block));
+                            if parallel.is_none() {
+                                break;
+                            }
+                        }
+                    }
+                }
 
-                                // No tx submitted = pre-trade rejection (zero capital risk)
-                                // Record failure for cooldown (stale/dead spread suppression)
-                                route_cooldown.record_failure(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, current_block);
-                                if error_msg.contains("Quoter") || error_msg.contains("Gas price") {
-                                    info!(
-                                        "Quoter rejected #{} {} ({}), trying next...",
-                                        rank + 1, result.opportunity, error_msg
-                                    );
-                                    continue;
-                                } else {
-                                    // Unknown pre-trade failure — stop for safety
-                                    warn!(
-                                        "Trade failed: {} | Error: {}",
-                                        result.opportunity, error_msg
-                                    );
-                                    break;
-                                }
+                // Lost races: the highest bid that touched our pools ahead of us won
+                for race in &lost_races {
+                    match competition::discover_winning_bids(provider.as_ref(), race, competition.competitors()).await {
+                        Ok(bids) => match bids.iter().copied().reduce(f64::max) {
+                            Some(winning) => {
+                                info!("🏁 Lost race on {}: winning bid {:.1} gwei ({} earlier tx(s))", race.route, winning, bids.len());
+                                competition.record_winning_bid(&race.route, winning);
                             }
+                            None => debug!("Lost race on {}: no competing tx ahead of ours", race.route),
+                        },
+                        Err(e) => debug!("Winning bid discovery failed for {}: {}", race.route, e),
+                    }
+                }
+
+                // Multi-opp blocks: combinable quoted profit vs what we captured
+                if let Some(ref mut analyzer) = constraint_analyzer {
+                    let candidates: Vec<ConstraintCandidate> = execution_order.iter()
+                        .filter_map(|(idx, qp)| qp.map(|qp| ConstraintCandidate::new(&opportunities[*idx], qp)))
+                        .collect();
+                    if let Some(r) = analyzer.record_block(current_block, &candidates, realized_usd, taken) {
+                        info!(
+                            "📐 Block {}: {} verified ({} conflicting pairs) | combinable {} | realized {} | unrealized {}",
+                            r.block, r.verified, r.conflicting_pairs, format_usd(r.combinable_usd),
+                            format_usd(r.realized_usd), format_usd(r.unrealized_usd)
+                        );
+                    }
+                }
+            }
+
+            // Triangular pass (TRIANGULAR_ENABLED): three-pool cycles are logged and,
+            // in dry run, simulated; ArbExecutor.sol has no three-leg route yet
+            if config.triangular_enabled {
+                for tri in detector.scan_triangular_opportunities() {
+                    info!(
+                        "🔺 {} | {} | Spread: {:.2}% | Est. Profit: {} | Size: {} ({})",
+                        tri.route, tri.dex_path(), tri.spread_percent,
+                        format_usd(tri.estimated_profit), format_usd(tri.trade_size_usd), tri.trade_size
+                    );
+                    match shutdown.track(executor.execute_triangular(&tri)).await {
+                        Ok(result) if result.success => info!(
+                            "🔺 Triangular dry run: {} | est. net {}", result.opportunity, format_usd(result.net_profit_usd)
+                        ),
+                        Ok(result) => debug!(
+                            "Triangular {} not attempted: {}", result.opportunity, result.error.unwrap_or_default()
+                        ),
+                        Err(e) => warn!("Triangular {} failed: {}", tri.route, e),
+                    }
+                }
+            }
+
+            // Cooldown research: quote the suppressed routes after this block's
+            // trades (never executed) so policies can be replayed against them
+            if let Some(export) = prescreen_export.as_ref().filter(|_| !cooled.is_empty()) {
+                match rpc_timeout("cooled pre-screen", multicall_quoter.batch_verify(&cooled, &config)).await {
+                    Ok(verified) => {
+                        if let Err(e) = export.export_cooled(&cooled, &verified, current_block) {
+                            warn!("Cooled pre-screen export failed: {}", e);
                         }
+                    }
+                    Err(e) => debug!("Cooled pre-screen skipped: {}", e),
+                }
+            }
+
+            // Shadow validation: publish (primary) or diff against the primary (follower)
+            if let Some(decisions) = block_decisions.take() {
+                if let Some(ref feed) = decision_feed {
+                    if let Err(e) = feed.publish(&decisions) {
+                        warn!("Shadow decisions feed write failed: {}", e);
+                    }
+                }
+                if let Some(ref mut comparator) = shadow_comparator {
+                    comparator.record_own(decisions);
+                    match comparator.poll() {
+                        Ok(divergences) if !divergences.is_empty() => info!(
+                            "🪞 Shadow: {} divergence(s) vs primary (latest block {})",
+                            divergences.len(), divergences.iter().map(|d| d.block).max().unwrap_or(0)
+                        ),
+                        Ok(_) => {}
+                        Err(e) => warn!("Shadow comparison failed: {}", e),
+                    }
+                }
+            }
+
+            // A/B shadow: quote its routes when few enough, then log the block
+            if let Some(ref mut ab) = ab_shadow {
+                let to_verify = ab.to_verify();
+                let verified = if to_verify.is_empty() {
+                    Vec::new()
+                } else {
+                    match rpc_timeout("A/B shadow pre-screen", multicall_quoter.batch_verify(&to_verify, &config)).await {
+                        Ok(verified) => verified,
                         Err(e) => {
-                            route_cooldown.record_failure(&opp.pair.symbol, opp.buy_dex, opp.sell_dex, current_block);
-                            error!("Execution error: {}", e);
-                            break; // Stop on unexpected errors
+                            debug!("A/B shadow pre-screen skipped: {}", e);
+                            Vec::new()
                         }
                     }
+                };
+                if let Err(e) = ab.finish(&verified) {
+                    warn!("A/B shadow write failed: {}", e);
                 }
             }
+
+            // Research joins: one blocks_YYYYMMDD.csv row per processed block
+            if let Some(ref mut logger) = price_logger {
+                let timestamp = chrono::DateTime::from_timestamp(block.timestamp.low_u64() as i64, 0)
+                    .unwrap_or_else(chrono::Utc::now);
+                logger.log_block(
+                    BlockAnnotations::new(current_block, timestamp)
+                        .base_fee(block.base_fee_per_gas.unwrap_or_default())
+                        .detected(detected)
+                        .suppressed(cooled.len())
+                        .submitted(block_submitted)
+                        .net_profit_usd(block_net_usd),
+                );
+            }
     } // end block scope
     } // end inner block-processing loop
     } // end 'reconnect loop
 
+    // Graceful shutdown: in-flight trades first, then writers and persisted state
+    if !shutdown.drain(SHUTDOWN_DRAIN_TIMEOUT).await {
+        warn!("{} trade(s) still in flight after {}s — their journal entries are resolved at the next start",
+              shutdown.in_flight(), SHUTDOWN_DRAIN_TIMEOUT.as_secs());
+    }
+    mempool_cancel.cancel();
+    if let Some(task) = mempool_task.take() {
+        if timeout(Duration::from_secs(5), task).await.is_err() {
+            warn!("A4: Mempool monitor did not stop within 5s — abandoning it");
+        }
+    }
+    for (name, drained) in [
+        ("price history", price_logger.as_ref().is_none_or(|l| l.flush(LOG_WRITER_SHUTDOWN_FLUSH))),
+        ("gas history", gas_logger.as_ref().is_none_or(|l| l.flush(LOG_WRITER_SHUTDOWN_FLUSH))),
+        ("event journal", event_journal.as_ref().is_none_or(|j| j.flush(LOG_WRITER_SHUTDOWN_FLUSH))),
+        ("opportunity archive", opportunity_archive.as_ref().is_none_or(|a| a.flush(LOG_WRITER_SHUTDOWN_FLUSH))),
+        ("canary", canary.flush(LOG_WRITER_SHUTDOWN_FLUSH)),
+    ] {
+        if !drained {
+            warn!("{} writer not drained at shutdown — queued rows lost", name);
+        }
+    }
+    let lost = executor.flush_pending_tax_records();
+    if lost > 0 {
+        error!("🧾 {} tax record(s) could not be written — see UNWRITTEN lines above", lost);
+    }
+    route_cooldown.persist();
+    route_stats.write_snapshot();
+    latency.write_snapshot();
+
+    if shutdown.is_shutting_down() {
+        info!("Shutdown complete");
+        return Ok(());
+    }
+
     // All reconnects exhausted — exit so supervisor can restart the whole process.
     error!("WS subscription loop exited — exiting for supervisor restart");
+    if live_alerter.is_some() {
+        // Give the spawned exhaustion alert's webhook post time to go out
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
     Ok(())
 }
 
-/// Convert a MempoolSignal into an ArbitrageOpportunity that the executor can process.
-///
-/// Looks up pool addresses, token decimals, and quote_token_is_token0 from the
-/// PoolStateManager. Returns None if the pool data can't be resolved (stale or missing).
-fn build_mempool_arb_opportunity(
-    signal: &MempoolSignal,
-    state_manager: &PoolStateManager,
-    config: &dexarb_bot::types::BotConfig,
-) -> Option<ArbitrageOpportunity> {
-    let opp = &signal.opportunity;
-
-    // Resolve trading pair from pair_symbol
-    // Find any pool for this pair to get token addresses
-    let (token0, token1, t0_dec, t1_dec) = {
-        // Try buy_dex pool first, then sell_dex
-        let buy_pool = if opp.arb_buy_dex.is_v3() {
-            state_manager.get_v3_pool(opp.arb_buy_dex, &opp.pair_symbol)
-                .map(|p| (p.pair.token0, p.pair.token1, p.token0_decimals, p.token1_decimals))
-        } else {
-            state_manager.get_pool(opp.arb_buy_dex, &opp.pair_symbol)
-                .map(|p| (p.pair.token0, p.pair.token1, p.token0_decimals, p.token1_decimals))
-        };
-        let sell_pool = if opp.arb_sell_dex.is_v3() {
-            state_manager.get_v3_pool(opp.arb_sell_dex, &opp.pair_symbol)
-                .map(|p| (p.pair.token0, p.pair.token1, p.token0_decimals, p.token1_decimals))
-        } else {
-            state_manager.get_pool(opp.arb_sell_dex, &opp.pair_symbol)
-                .map(|p| (p.pair.token0, p.pair.token1, p.token0_decimals, p.token1_decimals))
-        };
-        buy_pool.or(sell_pool)?
-    };
-
-    // Get pool addresses
-    let buy_pool_addr = if opp.arb_buy_dex.is_v3() {
-        state_manager.get_v3_pool(opp.arb_buy_dex, &opp.pair_symbol).map(|p| p.address)
-    } else {
-        state_manager.get_pool(opp.arb_buy_dex, &opp.pair_symbol).map(|p| p.address)
-    };
-    let sell_pool_addr = if opp.arb_sell_dex.is_v3() {
-        state_manager.get_v3_pool(opp.arb_sell_dex, &opp.pair_symbol).map(|p| p.address)
-    } else {
-        state_manager.get_pool(opp.arb_sell_dex, &opp.pair_symbol).map(|p| p.address)
-    };
-
-    // Determine quote_token_is_token0 by comparing pool's token0 with known quote tokens.
-    // Supports both USDC.e (primary) and native USDC (secondary) on Polygon.
-    let quote_token_is_token0 = config.is_quote_token(&token0);
-
-    // Trade size: max_trade_size_usd in USDC raw units (6 decimals)
-    let quote_decimals = if quote_token_is_token0 { t0_dec } else { t1_dec };
-    let trade_size = U256::from(
-        (config.max_trade_size_usd * 10_f64.powi(quote_decimals as i32)) as u64
-    );
+/// Count a block-subscription failure on the current endpoint: journal it,
+/// log the failover, and alert when every endpoint is exhausted. Returns the
+/// delay before the next attempt; None = give up (bot exits).
+fn ws_endpoint_failed(
+    endpoints: &mut ProviderManager,
+    failure: EndpointFailure,
+    error: &str,
+    journal: Option<&EventJournal>,
+    alerter: Option<&mut LiveAlerter>,
+    last_block: u64,
+) -> Option<Duration> {
+    let label = endpoints.current_label();
+    let failover = endpoints.failed(failure, error);
+    if let Some(journal) = journal {
+        journal.emit(last_block, BotEvent::ws_reconnect(failure.reason(), failover.failures, Some(error.to_string())));
+    }
+    match failover.next {
+        Some((next, delay)) => {
+            warn!(
+                "WS {} on {} (failure {}): {} — reconnecting to {} in {:.1}s...",
+                failure.reason(), label, failover.failures, error, endpoints.endpoint(next).label(), delay.as_secs_f64()
+            );
+            Some(delay)
+        }
+        None => {
+            let attempts = endpoints.total_failures();
+            error!("WS {} on {}: {} — all {} endpoint(s) exhausted ({} failures), exiting", failure.reason(), label, error, endpoints.len(), attempts);
+            if let Some(alerter) = alerter {
+                alerter.ws_exhausted(attempts, error);
+            }
+            None
+        }
+    }
+}
 
-    let pair = TradingPair::new(token0, token1, opp.pair_symbol.clone());
+/// Next mid-block update; pending forever without a receiver (MIDBLOCK_SCAN off)
+async fn recv_midblock(receiver: &mut Option<mpsc::Receiver<MidblockUpdate>>) -> Option<MidblockUpdate> {
+    match receiver {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
 
-    let mut arb = ArbitrageOpportunity::new(
-        pair,
-        opp.arb_buy_dex,
-        opp.arb_sell_dex,
-        opp.pre_swap_price,   // buy_price
-        opp.post_swap_price,  // sell_price
-        trade_size,
-    );
-    arb.estimated_profit = opp.arb_est_profit_usd;
-    arb.spread_percent = opp.arb_spread_pct;
-    arb.buy_pool_address = buy_pool_addr;
-    arb.sell_pool_address = sell_pool_addr;
-    arb.token0_decimals = t0_dec;
-    arb.token1_decimals = t1_dec;
-    arb.quote_token_is_token0 = quote_token_is_token0;
-
-    Some(arb)
+/// Log one block-path execution result according to its TradeOutcome class
+fn log_trade_outcome(result: &TradeResult, outcome: TradeOutcome, rank: usize) {
+    let error_msg = result.error.as_deref().unwrap_or("Unknown");
+    let tx = result.tx_hash.as_deref().unwrap_or("?");
+    match outcome {
+        TradeOutcome::Success => info!(
+            "Trade complete: {} | Net profit: ${:.2} | Time: {}ms",
+            result.opportunity, result.net_profit_usd, result.execution_time_ms
+        ),
+        // Receipt timeout: tx submitted but unconfirmed — unknown state, stop.
+        TradeOutcome::ReceiptTimeout => {
+            error!("HALT: Receipt timeout for {} | TX: {} — tx may still confirm", result.opportunity, tx);
+            error!("Unknown tx state — manual check needed. Stopping all trading.");
+        }
+        // Atomic revert: no trade happened, only gas burned. Try next route.
+        TradeOutcome::AtomicRevert => {
+            info!("Atomic tx reverted on-chain (safe, gas only): {} | TX: {}", result.opportunity, tx)
+        }
+        // Gas-negative completed trade: arb executed but gas > profit.
+        // Market state changed from our trade — wait for next block.
+        TradeOutcome::GasNegative => {
+            warn!("Atomic trade gas-negative: {} | TX: {} | Error: {}", result.opportunity, tx, error_msg)
+        }
+        // Legacy two-tx mode: capital genuinely at risk (buy on-chain, sell failed).
+        TradeOutcome::CapitalAtRisk => {
+            error!(
+                "HALT: On-chain tx submitted but trade failed: {} | Error: {} | TX: {}",
+                result.opportunity, error_msg, tx
+            );
+            error!("Capital committed — manual recovery needed. Stopping all trading.");
+        }
+        TradeOutcome::RouteRejected => {
+            info!("Quoter rejected #{} {} ({}), trying next...", rank + 1, result.opportunity, error_msg)
+        }
+        // Thin pool — structural, no cooldown
+        TradeOutcome::InsufficientLiquidity => {
+            info!("Skipped #{} {} ({}), trying next...", rank + 1, result.opportunity, error_msg)
+        }
+        // Exposure pacing left no viable size for this quote token — wallet state, no cooldown
+        TradeOutcome::ExposureLimited => {
+            info!("Paced out #{} {} ({}), trying next...", rank + 1, result.opportunity, error_msg)
+        }
+        // Too old to trust — the executor already logged the age
+        TradeOutcome::StaleOpportunity => {
+            info!("Stale #{} {} ({}), stopping this block", rank + 1, result.opportunity, error_msg)
+        }
+        // Strict tax logging halt — already alerted when it began
+        TradeOutcome::TaxLoggingHalted => {
+            debug!("Submission refused: {} ({})", result.opportunity, error_msg)
+        }
+        // Unknown pre-trade failure — stop for safety
+        TradeOutcome::PreTradeFailure | TradeOutcome::ExecutionError => {
+            warn!("Trade failed: {} | Error: {}", result.opportunity, error_msg)
+        }
+    }
 }
@@ -0,0 +1,255 @@
+//! Searcher Bundle Submission (mempool backruns)
+//!
+//! Purpose:
+//!     The private RPC path sends our backrun as a lone raw tx; a searcher
+//!     relay (Polygon Fastlane) can instead pin it directly behind the trigger
+//!     transaction. `BundleSubmitter` posts the relay's bundle JSON-RPC call
+//!     with the trigger and our signed tx; execute_from_mempool() uses it when
+//!     BUNDLE_RPC_URL is set and falls back to the raw send when the relay
+//!     refuses or is unreachable.
+//!
+//! Relays differ in method name and params shape, so both are configured:
+//!     BUNDLE_RPC_METHOD       — e.g. pfl_addSearcherBundle, eth_sendBundle
+//!     BUNDLE_PARAMS_TEMPLATE  — JSON params; a string equal to a placeholder
+//!                               is replaced by its value:
+//!         {trigger_hash}  trigger tx hash
+//!         {trigger_raw}   trigger tx signed RLP (hex)
+//!         {backrun_raw}   our signed tx (hex)
+//!         {backrun_hash}  our tx hash
+//!         {block}         target block (hex: the block after the current one)
+//!         {txs}           [trigger_raw, backrun_raw]
+//!
+//! Inclusion is judged from receipts: our tx landing at the index right after
+//! the trigger in the same block is a hit (BundleOutcome::Included).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::types::BotConfig;
+use anyhow::{anyhow, Context, Result};
+use ethers::types::{Bytes, TxHash};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::debug;
+
+/// Relay request timeout: a bundle that misses the next block is worthless
+const BUNDLE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The pending transaction a backrun is pinned behind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerTx {
+    pub hash: TxHash,
+    /// Signed RLP as seen in the mempool (None when the node didn't give the signature)
+    pub raw: Option<Bytes>,
+}
+
+/// Values substituted into the params template for one bundle
+#[derive(Debug, Clone)]
+pub struct BundleInputs<'a> {
+    pub trigger: &'a TriggerTx,
+    pub backrun_raw: &'a Bytes,
+    pub backrun_hash: TxHash,
+    pub target_block: u64,
+}
+
+/// What became of a bundled backrun
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleOutcome {
+    /// Landed directly behind the trigger
+    Included,
+    /// Landed, but not right behind the trigger
+    Landed,
+    /// Relay accepted it, no receipt before the timeout
+    NotIncluded,
+    /// Relay refused or unreachable — sent as a raw tx instead
+    Fallback,
+}
+
+impl BundleOutcome {
+    /// Classify from our receipt and the trigger's, each (block, tx index)
+    pub fn from_receipts(ours: Option<(u64, u64)>, trigger: Option<(u64, u64)>) -> Self {
+        match (ours, trigger) {
+            (None, _) => BundleOutcome::NotIncluded,
+            (Some((block, index)), Some((trigger_block, trigger_index)))
+                if block == trigger_block && index == trigger_index + 1 =>
+            {
+                BundleOutcome::Included
+            }
+            (Some(_), _) => BundleOutcome::Landed,
+        }
+    }
+
+    /// Whether the relay took the bundle (the denominator of the hit rate)
+    pub fn bundled(self) -> bool {
+        self != BundleOutcome::Fallback
+    }
+}
+
+/// Replace placeholder strings in `template` (recursively through arrays and objects)
+pub fn render_params(template: &Value, inputs: &BundleInputs<'_>) -> Result<Value> {
+    Ok(match template {
+        Value::String(s) => match s.as_str() {
+            "{trigger_hash}" => json!(format!("{:?}", inputs.trigger.hash)),
+            "{trigger_raw}" => json!(trigger_raw(inputs)?),
+            "{backrun_raw}" => json!(inputs.backrun_raw.to_string()),
+            "{backrun_hash}" => json!(format!("{:?}", inputs.backrun_hash)),
+            "{block}" => json!(format!("{:#x}", inputs.target_block)),
+            "{txs}" => json!([trigger_raw(inputs)?, inputs.backrun_raw.to_string()]),
+            _ => template.clone(),
+        },
+        Value::Array(items) => Value::Array(
+            items.iter().map(|item| render_params(item, inputs)).collect::<Result<_>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), render_params(v, inputs)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn trigger_raw(inputs: &BundleInputs<'_>) -> Result<String> {
+    inputs
+        .trigger
+        .raw
+        .as_ref()
+        .map(|raw| raw.to_string())
+        .ok_or_else(|| anyhow!("trigger tx {:?} has no raw bytes", inputs.trigger.hash))
+}
+
+/// JSON-RPC 2.0 request body
+pub fn build_request(method: &str, params: Value, id: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": params,
+    })
+}
+
+/// Posts bundles to one relay
+pub struct BundleSubmitter {
+    client: reqwest::Client,
+    url: String,
+    method: String,
+    template: Value,
+    next_id: AtomicU64,
+}
+
+impl BundleSubmitter {
+    pub fn new(url: &str, method: &str, params_template: &str) -> Result<Self> {
+        let template = serde_json::from_str(params_template)
+            .with_context(|| format!("Invalid BUNDLE_PARAMS_TEMPLATE: {}", params_template))?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            method: method.to_string(),
+            template,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// BUNDLE_RPC_URL / _METHOD / _PARAMS_TEMPLATE; None when no relay is configured
+    pub fn from_config(config: &BotConfig) -> Result<Option<Self>> {
+        config
+            .bundle_rpc_url
+            .as_deref()
+            .map(|url| Self::new(url, &config.bundle_rpc_method, &config.bundle_params_template))
+            .transpose()
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The request body for one bundle (next request id)
+    pub fn request(&self, inputs: &BundleInputs<'_>) -> Result<Value> {
+        let params = render_params(&self.template, inputs)?;
+        Ok(build_request(&self.method, params, self.next_id.fetch_add(1, Ordering::Relaxed)))
+    }
+
+    /// Submit one bundle; Ok(relay result) or the relay / transport error
+    pub async fn submit(&self, inputs: &BundleInputs<'_>) -> Result<Value> {
+        let body = self.request(inputs)?;
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .timeout(BUNDLE_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| anyhow!("bundle relay unreachable: {}", e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow!("bundle relay HTTP {}", status));
+        }
+        let reply: Value = response.json().await.map_err(|e| anyhow!("bundle relay reply: {}", e))?;
+        if let Some(error) = reply.get("error") {
+            return Err(anyhow!("bundle relay error: {}", error));
+        }
+        debug!("Bundle accepted by {} ({}): {}", self.url, self.method, reply);
+        Ok(reply.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger() -> TriggerTx {
+        TriggerTx {
+            hash: TxHash::from_low_u64_be(0xaa),
+            raw: Some(Bytes::from(vec![0x02, 0xf8, 0x01])),
+        }
+    }
+
+    #[test]
+    fn test_fastlane_request_fixture() {
+        let submitter = BundleSubmitter::new("http://relay", "pfl_addSearcherBundle", r#"["{trigger_hash}","{backrun_raw}"]"#).unwrap();
+        let trigger = trigger();
+        let backrun = Bytes::from(vec![0x02, 0xf9, 0xbe, 0xef]);
+        let inputs = BundleInputs { trigger: &trigger, backrun_raw: &backrun, backrun_hash: TxHash::from_low_u64_be(0xbb), target_block: 1001 };
+        let request = serde_json::to_string(&submitter.request(&inputs).unwrap()).unwrap();
+        assert_eq!(
+            request,
+            r#"{"id":1,"jsonrpc":"2.0","method":"pfl_addSearcherBundle","params":["0x00000000000000000000000000000000000000000000000000000000000000aa","0x02f9beef"]}"#
+        );
+        // Ids advance per request
+        assert_eq!(submitter.request(&inputs).unwrap()["id"], 2);
+    }
+
+    #[test]
+    fn test_send_bundle_style_template() {
+        let template = r#"[{"txs":"{txs}","blockNumber":"{block}","revertingTxHashes":["{backrun_hash}"],"note":"keep"}]"#;
+        let submitter = BundleSubmitter::new("http://relay", "eth_sendBundle", template).unwrap();
+        let trigger = trigger();
+        let backrun = Bytes::from(vec![0xbe, 0xef]);
+        let inputs = BundleInputs { trigger: &trigger, backrun_raw: &backrun, backrun_hash: TxHash::from_low_u64_be(0xbb), target_block: 255 };
+        let request = submitter.request(&inputs).unwrap();
+        assert_eq!(
+            serde_json::to_string(&request["params"]).unwrap(),
+            r#"[{"blockNumber":"0xff","note":"keep","revertingTxHashes":["0x00000000000000000000000000000000000000000000000000000000000000bb"],"txs":["0x02f801","0xbeef"]}]"#
+        );
+
+        // A template needing the trigger's raw bytes can't be built without them
+        let unsigned = TriggerTx { raw: None, ..trigger };
+        let inputs = BundleInputs { trigger: &unsigned, ..inputs };
+        assert!(submitter.request(&inputs).is_err());
+        assert!(BundleSubmitter::new("http://relay", "eth_sendBundle", "[not json").is_err());
+    }
+
+    #[test]
+    fn test_outcome_from_receipts() {
+        assert_eq!(BundleOutcome::from_receipts(Some((10, 4)), Some((10, 3))), BundleOutcome::Included);
+        assert_eq!(BundleOutcome::from_receipts(Some((10, 7)), Some((10, 3))), BundleOutcome::Landed);
+        assert_eq!(BundleOutcome::from_receipts(Some((11, 0)), Some((10, 3))), BundleOutcome::Landed);
+        assert_eq!(BundleOutcome::from_receipts(Some((10, 4)), None), BundleOutcome::Landed);
+        assert_eq!(BundleOutcome::from_receipts(None, Some((10, 3))), BundleOutcome::NotIncluded);
+        assert!(!BundleOutcome::Fallback.bundled() && BundleOutcome::NotIncluded.bundled());
+    }
+}
@@ -0,0 +1,248 @@
+//! A4 Mempool Monitor — Simulation Calibration
+//!
+//! Purpose:
+//!     Feed the Phase 2 accuracy measurements back into Phase 3. Every
+//!     validated simulation (predicted post-swap price vs the price the next
+//!     block's event sync produced) adds its absolute error, in bps, to a
+//!     rolling window for the trigger pool's (DEX, pair). The execute-mode
+//!     signal gate then asks for net spread ≥ MEMPOOL_MIN_SPREAD_PCT + k ·
+//!     (rolling mean abs error) — arb_spread_pct is already net of both
+//!     legs' fees — so routes whose
+//!     simulations miss by more need a wider predicted spread before a
+//!     backrun is sent, and well-predicted routes get closer to the floor.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Notes:
+//!     - k = MEMPOOL_CALIBRATION_K; a (DEX, pair) with fewer than
+//!       MIN_CALIBRATION_SAMPLES validations uses MEMPOOL_CALIBRATION_PRIOR_BPS
+//!     - Persisted to data/{chain}/mempool/calibration.json, reloaded at startup
+
+use crate::types::DexType;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Validations kept per (DEX, pair)
+pub const CALIBRATION_WINDOW: usize = 200;
+
+/// Validations needed before a (DEX, pair) uses its own error
+pub const MIN_CALIBRATION_SAMPLES: usize = 5;
+
+/// Rolling absolute prediction error of one (DEX, pair)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErrorStats {
+    /// Most recent absolute errors (bps), oldest first
+    pub samples_bps: VecDeque<f64>,
+    /// Validations ever recorded (window or not)
+    pub total: u64,
+}
+
+impl ErrorStats {
+    fn record(&mut self, error_bps: f64) {
+        if self.samples_bps.len() == CALIBRATION_WINDOW {
+            self.samples_bps.pop_front();
+        }
+        self.samples_bps.push_back(error_bps);
+        self.total += 1;
+    }
+
+    /// Mean absolute error (bps) over the window; None below MIN_CALIBRATION_SAMPLES
+    pub fn mean_abs_bps(&self) -> Option<f64> {
+        (self.samples_bps.len() >= MIN_CALIBRATION_SAMPLES)
+            .then(|| self.samples_bps.iter().sum::<f64>() / self.samples_bps.len() as f64)
+    }
+}
+
+/// On-disk form (JSON object keys must be strings, hence the entry list)
+#[derive(Serialize, Deserialize)]
+struct CalibrationFile {
+    saved_at: String,
+    entries: Vec<CalibrationEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CalibrationEntry {
+    dex: DexType,
+    pair: String,
+    #[serde(flatten)]
+    stats: ErrorStats,
+}
+
+/// Per-(DEX, pair) error statistics and the adaptive spread threshold
+#[derive(Debug, Clone)]
+pub struct Calibration {
+    stats: HashMap<(DexType, String), ErrorStats>,
+    /// Error multiple added to the spread floor
+    k: f64,
+    /// Assumed error (bps) until a (DEX, pair) has enough validations
+    prior_bps: f64,
+    path: Option<PathBuf>,
+    dirty: bool,
+}
+
+impl Calibration {
+    /// In-memory only (tests, observe mode without a data dir)
+    pub fn new(k: f64, prior_bps: f64) -> Self {
+        Self { stats: HashMap::new(), k, prior_bps, path: None, dirty: false }
+    }
+
+    /// Reload the persisted stats from `path` (missing file = fresh start;
+    /// unreadable = warn and start fresh). save() writes back to it.
+    pub fn load(path: impl AsRef<Path>, k: f64, prior_bps: f64) -> Self {
+        let path = path.as_ref();
+        let mut calibration = Self::new(k, prior_bps);
+        calibration.path = Some(path.to_path_buf());
+        if !path.exists() {
+            return calibration;
+        }
+        let loaded = std::fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(serde_json::from_str::<CalibrationFile>(&json)?));
+        match loaded {
+            Ok(file) => {
+                for entry in file.entries {
+                    let mut stats = entry.stats;
+                    while stats.samples_bps.len() > CALIBRATION_WINDOW {
+                        stats.samples_bps.pop_front();
+                    }
+                    calibration.stats.insert((entry.dex, entry.pair), stats);
+                }
+                info!("Mempool calibration: {} route(s) reloaded from {}", calibration.stats.len(), path.display());
+            }
+            Err(e) => warn!("Mempool calibration: failed to read {}: {} — starting fresh", path.display(), e),
+        }
+        calibration
+    }
+
+    /// Add one validated simulation's absolute error (bps). Non-finite
+    /// errors (zero actual price) carry no information and are ignored.
+    pub fn record(&mut self, dex: DexType, pair: &str, error_bps: f64) {
+        if !error_bps.is_finite() {
+            return;
+        }
+        self.stats.entry((dex, pair.to_string())).or_default().record(error_bps.abs());
+        self.dirty = true;
+    }
+
+    pub fn stats(&self, dex: DexType, pair: &str) -> Option<&ErrorStats> {
+        self.stats.get(&(dex, pair.to_string()))
+    }
+
+    /// Rolling mean absolute error (bps), or the prior while samples are few
+    pub fn error_bps(&self, dex: DexType, pair: &str) -> f64 {
+        self.stats(dex, pair).and_then(ErrorStats::mean_abs_bps).unwrap_or(self.prior_bps)
+    }
+
+    /// Net spread (%) a simulated opportunity triggered on (dex, pair) needs
+    pub fn min_spread_pct(&self, dex: DexType, pair: &str, floor_pct: f64) -> f64 {
+        floor_pct + self.k * self.error_bps(dex, pair) / 100.0
+    }
+
+    /// Write the stats if anything changed since the last save
+    pub fn save(&mut self) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if !self.dirty {
+            return Ok(());
+        }
+        let mut entries: Vec<CalibrationEntry> = self
+            .stats
+            .iter()
+            .map(|((dex, pair), stats)| CalibrationEntry { dex: *dex, pair: pair.clone(), stats: stats.clone() })
+            .collect();
+        entries.sort_by(|a, b| (&a.pair, format!("{:?}", a.dex)).cmp(&(&b.pair, format!("{:?}", b.dex))));
+        let file = CalibrationFile { saved_at: chrono::Utc::now().to_rfc3339(), entries };
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&file)?)?;
+        std::fs::rename(&tmp, path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// One line per calibrated route for the periodic stats
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .stats
+            .iter()
+            .filter_map(|((dex, pair), stats)| {
+                stats.mean_abs_bps().map(|mae| {
+                    format!("{} {:?}: MAE {:.2}bps over {} (total {})", pair, dex, mae, stats.samples_bps.len(), stats.total)
+                })
+            })
+            .collect();
+        lines.sort();
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOOR: f64 = 0.01;
+
+    #[test]
+    fn test_threshold_rises_for_noisy_and_falls_for_accurate_pairs() {
+        let mut calibration = Calibration::new(2.0, 5.0);
+        let prior = calibration.min_spread_pct(DexType::UniswapV3_005, "WETH/USDC", FLOOR);
+        assert!((prior - (FLOOR + 2.0 * 5.0 / 100.0)).abs() < 1e-12);
+
+        // Synthetic predicted/actual price pairs: accurate WETH/USDC, noisy WMATIC/USDC
+        let error_bps = |predicted: f64, actual: f64| (predicted - actual) / actual * 10_000.0;
+        for i in 0..20 {
+            let wiggle = if i % 2 == 0 { 1.0 } else { -1.0 };
+            calibration.record(DexType::UniswapV3_005, "WETH/USDC", error_bps(2000.0 + 0.1 * wiggle, 2000.0));
+            calibration.record(DexType::UniswapV3_030, "WMATIC/USDC", error_bps(0.5 * (1.0 + 0.004 * wiggle), 0.5));
+        }
+
+        let accurate = calibration.min_spread_pct(DexType::UniswapV3_005, "WETH/USDC", FLOOR);
+        let noisy = calibration.min_spread_pct(DexType::UniswapV3_030, "WMATIC/USDC", FLOOR);
+        assert!((calibration.error_bps(DexType::UniswapV3_005, "WETH/USDC") - 0.5).abs() < 1e-6);
+        assert!((calibration.error_bps(DexType::UniswapV3_030, "WMATIC/USDC") - 40.0).abs() < 1e-6);
+        assert!(accurate < prior && accurate > FLOOR, "accurate {} vs prior {}", accurate, prior);
+        assert!(noisy > prior, "noisy {} vs prior {}", noisy, prior);
+
+        // Per DEX: another pool of the noisy pair still sits at the prior
+        assert_eq!(calibration.min_spread_pct(DexType::UniswapV3_005, "WMATIC/USDC", FLOOR), prior);
+
+        // Rolling: the noisy route recovers once its recent predictions are good
+        for _ in 0..CALIBRATION_WINDOW {
+            calibration.record(DexType::UniswapV3_030, "WMATIC/USDC", 0.2);
+        }
+        assert!(calibration.min_spread_pct(DexType::UniswapV3_030, "WMATIC/USDC", FLOOR) < prior);
+        assert_eq!(calibration.stats(DexType::UniswapV3_030, "WMATIC/USDC").unwrap().total, 20 + CALIBRATION_WINDOW as u64);
+
+        // Too few samples: prior; non-finite errors ignored
+        for _ in 0..MIN_CALIBRATION_SAMPLES - 1 {
+            calibration.record(DexType::SushiV3_005, "WETH/USDC", 300.0);
+        }
+        calibration.record(DexType::SushiV3_005, "WETH/USDC", f64::INFINITY);
+        assert_eq!(calibration.min_spread_pct(DexType::SushiV3_005, "WETH/USDC", FLOOR), prior);
+    }
+
+    #[test]
+    fn test_persists_and_reloads() {
+        let dir = std::env::temp_dir().join(format!("dexarb_calibration_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("calibration.json");
+        let _ = std::fs::remove_file(&path);
+
+        let mut calibration = Calibration::load(&path, 1.0, 5.0);
+        for bps in [10.0, 20.0, 30.0, 40.0, 50.0] {
+            calibration.record(DexType::QuickswapV3, "WETH/USDC", bps);
+        }
+        calibration.save().unwrap();
+
+        let reloaded = Calibration::load(&path, 1.0, 5.0);
+        assert_eq!(reloaded.error_bps(DexType::QuickswapV3, "WETH/USDC"), 30.0);
+        assert_eq!(reloaded.stats(DexType::QuickswapV3, "WETH/USDC").unwrap().total, 5);
+
+        // Corrupt file: fresh start at the prior
+        std::fs::write(&path, "{not json").unwrap();
+        assert_eq!(Calibration::load(&path, 1.0, 5.0).error_bps(DexType::QuickswapV3, "WETH/USDC"), 5.0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
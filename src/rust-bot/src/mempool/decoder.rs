@@ -7,6 +7,7 @@
 //! Author: AI-Generated
 //! Created: 2026-02-01
 //! Modified: 2026-02-01
+//! Modified: 2026-10-16 - SwapAmount descriptors; exact-output swaps decoded to the hop whose output is fixed
 //!
 //! Dependencies:
 //!     - ethers (abi decoding)
@@ -31,7 +32,7 @@ use ethers::abi::{decode, ParamType, Token};
 use ethers::types::{Address, U256};
 use tracing::trace;
 
-use super::types::DecodedSwap;
+use super::types::{DecodedSwap, SwapAmount};
 
 // ── V3 SwapRouter selectors ─────────────────────────────────────────
 const EXACT_INPUT_SINGLE: [u8; 4] = [0x41, 0x4b, 0xf3, 0x89];
@@ -121,6 +122,7 @@ fn decode_v3_exact_input_single(data: &[u8]) -> Option<DecodedSwap> {
         fee_tier: token_to_u32(&tokens[2]),
         amount_in: token_to_u256(&tokens[5]),
         amount_out_min: token_to_u256(&tokens[6]),
+        amount: token_to_u256(&tokens[5]).map(SwapAmount::ExactIn),
     })
 }
 
@@ -152,6 +154,7 @@ fn decode_v3_exact_input(data: &[u8]) -> Option<DecodedSwap> {
             fee_tier: Some(fee),
             amount_in: token_to_u256(&inner[3]),
             amount_out_min: token_to_u256(&inner[4]),
+            amount: token_to_u256(&inner[3]).map(SwapAmount::ExactIn),
         })
     } else {
         None
@@ -185,6 +188,10 @@ fn decode_v3_exact_output_single(data: &[u8]) -> Option<DecodedSwap> {
         // and amountOut as amount_out_min (the exact output they want).
         amount_in: token_to_u256(&tokens[6]),      // amountInMaximum
         amount_out_min: token_to_u256(&tokens[5]),  // amountOut (exact target)
+        amount: token_to_u256(&tokens[5]).map(|amount_out| SwapAmount::ExactOut {
+            amount_out,
+            max_in: token_to_u256(&tokens[6]),
+        }),
     })
 }
 
@@ -192,6 +199,10 @@ fn decode_v3_exact_output_single(data: &[u8]) -> Option<DecodedSwap> {
 /// Params struct: (bytes path, address recipient, uint256 deadline,
 ///                 uint256 amountOut, uint256 amountInMaximum)
 /// Path is REVERSED: tokenOut | fee | ... | tokenIn
+///
+/// Decoded to the first hop of the path — the pool that pays out the exact
+/// amountOut. On multi-hop paths amountInMaximum caps the final tokenIn, not
+/// that hop's input, so the descriptor carries no cap.
 fn decode_v3_exact_output(data: &[u8]) -> Option<DecodedSwap> {
     let params = vec![ParamType::Tuple(vec![
         ParamType::Bytes,     // path (reversed!)
@@ -205,16 +216,23 @@ fn decode_v3_exact_output(data: &[u8]) -> Option<DecodedSwap> {
 
     if let Token::Tuple(inner) = &tokens[0] {
         let path = token_to_bytes(&inner[0])?;
-        // Path is reversed for exactOutput: first token is tokenOut, last is tokenIn
-        let (first_token, last_token, fee) = decode_v3_path(&path)?;
+        // Path is reversed for exactOutput: first token is tokenOut, the hop's
+        // input follows the first fee
+        let (first_token, _, fee) = decode_v3_path(&path)?;
+        let hop_token_in = Address::from_slice(&path[23..43]);
+        let single_hop = path.len() == 43;
 
         Some(DecodedSwap {
             function_name: "exactOutput".to_string(),
-            token_in: Some(last_token),   // reversed
-            token_out: Some(first_token), // reversed
+            token_in: Some(hop_token_in),  // reversed
+            token_out: Some(first_token),  // reversed
             fee_tier: Some(fee),
             amount_in: token_to_u256(&inner[4]),    // amountInMaximum
             amount_out_min: token_to_u256(&inner[3]), // amountOut (exact target)
+            amount: token_to_u256(&inner[3]).map(|amount_out| SwapAmount::ExactOut {
+                amount_out,
+                max_in: token_to_u256(&inner[4]).filter(|_| single_hop),
+            }),
         })
     } else {
         None
@@ -256,6 +274,7 @@ fn decode_multicall(data: &[u8], has_deadline: bool) -> Option<DecodedSwap> {
             amount_in: None,
             amount_out_min: None,
             fee_tier: None,
+            amount: None,
         })
     } else {
         None
@@ -287,6 +306,7 @@ fn decode_algebra_exact_input_single(data: &[u8]) -> Option<DecodedSwap> {
         fee_tier: None, // Algebra uses dynamic fees
         amount_in: token_to_u256(&tokens[4]),
         amount_out_min: token_to_u256(&tokens[5]),
+        amount: token_to_u256(&tokens[4]).map(SwapAmount::ExactIn),
     })
 }
 
@@ -314,11 +334,15 @@ fn decode_v2_swap_exact_in(data: &[u8], fn_name: &str) -> Option<DecodedSwap> {
         fee_tier: None, // V2 always 0.30%
         amount_in: token_to_u256(&tokens[0]),
         amount_out_min: token_to_u256(&tokens[1]),
+        amount: token_to_u256(&tokens[0]).map(SwapAmount::ExactIn),
     })
 }
 
 /// Decode swapTokensForExactTokens
 /// Params: (uint256 amountOut, uint256 amountInMax, address[] path, address to, uint256 deadline)
+///
+/// Decoded to the last pair of the path — the one that pays out the exact
+/// amountOut; amountInMax only caps that hop on a single-pair path.
 fn decode_v2_swap_exact_out(data: &[u8]) -> Option<DecodedSwap> {
     let params = vec![
         ParamType::Uint(256),  // amountOut
@@ -330,7 +354,14 @@ fn decode_v2_swap_exact_out(data: &[u8]) -> Option<DecodedSwap> {
 
     let tokens = decode(&params, data).ok()?;
 
-    let (token_in, token_out) = extract_v2_path(&tokens[2]);
+    let (token_in, token_out, single_hop) = match &tokens[2] {
+        Token::Array(path) if path.len() >= 2 => (
+            token_to_address(&path[path.len() - 2]),
+            token_to_address(&path[path.len() - 1]),
+            path.len() == 2,
+        ),
+        _ => (None, None, false),
+    };
 
     Some(DecodedSwap {
         function_name: "swapTokensForExactTokens".to_string(),
@@ -339,6 +370,10 @@ fn decode_v2_swap_exact_out(data: &[u8]) -> Option<DecodedSwap> {
         fee_tier: None,
         amount_in: token_to_u256(&tokens[1]),    // amountInMax
         amount_out_min: token_to_u256(&tokens[0]), // amountOut (exact)
+        amount: token_to_u256(&tokens[0]).map(|amount_out| SwapAmount::ExactOut {
+            amount_out,
+            max_in: token_to_u256(&tokens[1]).filter(|_| single_hop),
+        }),
     })
 }
 
@@ -363,6 +398,7 @@ fn decode_v2_swap_eth_in(data: &[u8]) -> Option<DecodedSwap> {
         fee_tier: None,
         amount_in: None, // amountIn is msg.value, not in calldata
         amount_out_min: token_to_u256(&tokens[0]),
+        amount: None,
     })
 }
 
@@ -471,4 +507,84 @@ mod tests {
             "0x2791bca1f2de4661ed88a30c99a7a9449aa84174"
         );
     }
+
+    fn addr(hex_str: &str) -> Address {
+        Address::from_slice(&hex::decode(hex_str).unwrap())
+    }
+
+    fn calldata(selector: [u8; 4], tokens: &[Token]) -> Vec<u8> {
+        let mut data = selector.to_vec();
+        data.extend(ethers::abi::encode(tokens));
+        data
+    }
+
+    #[test]
+    fn test_decode_exact_output_single() {
+        let (usdc, weth) = (addr("2791Bca1f2de4661ED88A30C99A7a9449Aa84174"), addr("7ceB23fD6bC0adD59E62ac25578270cFf1b9f619"));
+        let data = calldata(EXACT_OUTPUT_SINGLE, &[
+            Token::Address(usdc),
+            Token::Address(weth),
+            Token::Uint(U256::from(500)),
+            Token::Address(Address::zero()),
+            Token::Uint(U256::from(1_700_000_000u64)),
+            Token::Uint(U256::exp10(18)),           // amountOut: 1 WETH
+            Token::Uint(U256::from(2_500_000_000u64)), // amountInMaximum: 2500 USDC
+            Token::Uint(U256::zero()),
+        ]);
+
+        let swap = decode_calldata(&data).unwrap();
+        assert_eq!(swap.function_name, "exactOutputSingle");
+        assert_eq!((swap.token_in, swap.token_out, swap.fee_tier), (Some(usdc), Some(weth), Some(500)));
+        assert_eq!(
+            swap.amount,
+            Some(SwapAmount::ExactOut { amount_out: U256::exp10(18), max_in: Some(U256::from(2_500_000_000u64)) })
+        );
+    }
+
+    #[test]
+    fn test_decode_exact_output_multi_hop_first_hop_only() {
+        // Reversed path WETH <-500- USDC <-3000- WMATIC: the first hop pays out WETH for USDC
+        let (weth, usdc, wmatic) = (
+            addr("7ceB23fD6bC0adD59E62ac25578270cFf1b9f619"),
+            addr("2791Bca1f2de4661ED88A30C99A7a9449Aa84174"),
+            addr("0d500b1d8e8ef31e21c99d1db9a6444d3adf1270"),
+        );
+        let mut path = weth.as_bytes().to_vec();
+        path.extend([0x00, 0x01, 0xf4]);
+        path.extend(usdc.as_bytes());
+        path.extend([0x00, 0x0b, 0xb8]);
+        path.extend(wmatic.as_bytes());
+        let data = calldata(EXACT_OUTPUT, &[Token::Tuple(vec![
+            Token::Bytes(path),
+            Token::Address(Address::zero()),
+            Token::Uint(U256::from(1_700_000_000u64)),
+            Token::Uint(U256::exp10(18)),
+            Token::Uint(U256::exp10(22)),
+        ])]);
+
+        let swap = decode_calldata(&data).unwrap();
+        assert_eq!((swap.token_in, swap.token_out, swap.fee_tier), (Some(usdc), Some(weth), Some(500)));
+        // amountInMaximum caps WMATIC, not the hop's USDC input
+        assert_eq!(swap.amount, Some(SwapAmount::ExactOut { amount_out: U256::exp10(18), max_in: None }));
+        assert_eq!(swap.amount_in, Some(U256::exp10(22)));
+    }
+
+    #[test]
+    fn test_decode_swap_tokens_for_exact_tokens() {
+        let (usdc, weth) = (addr("2791Bca1f2de4661ED88A30C99A7a9449Aa84174"), addr("7ceB23fD6bC0adD59E62ac25578270cFf1b9f619"));
+        let data = calldata(SWAP_TOKENS_FOR_EXACT_TOKENS, &[
+            Token::Uint(U256::from(1_000_000u64)), // amountOut
+            Token::Uint(U256::from(2_000_000u64)), // amountInMax
+            Token::Array(vec![Token::Address(weth), Token::Address(usdc)]),
+            Token::Address(Address::zero()),
+            Token::Uint(U256::from(1_700_000_000u64)),
+        ]);
+
+        let swap = decode_calldata(&data).unwrap();
+        assert_eq!((swap.token_in, swap.token_out), (Some(weth), Some(usdc)));
+        assert_eq!(
+            swap.amount,
+            Some(SwapAmount::ExactOut { amount_out: U256::from(1_000_000u64), max_in: Some(U256::from(2_000_000u64)) })
+        );
+    }
 }
@@ -0,0 +1,636 @@
+//! Mempool Signal Dispatch (Phase 3 execute mode)
+//!
+//! MEMPOOL_MONITOR=execute: `run_execution()` sends a MempoolSignal for each
+//! simulated backrun worth taking; the main loop hands every signal to
+//! `dispatch_signal`, which
+//!   1. gates it: stale (>10s), unresolvable pools, quarantined token,
+//!      canary-demoted pool, liquidity guard, route in cooldown (mempool
+//!      source: young cooldowns are bypassed), already submitted (opportunity
+//!      registry), shutting down. The size is the route's whitelist limit,
+//!      capped for canary pools (and downsized by the guard), as in the block
+//!      loop and mid-block paths
+//!   2. executes it via `execute_from_mempool()` at the trigger tx's gas
+//!   3. feeds the result back into route cooldown and records a sent tx in
+//!      the registry (the block loop skips the same spread while it lands)
+//!
+//! Balance clamping, exposure pacing and tax logging happen inside the
+//! executor, exactly as for block-loop trades. Logging, stats and the
+//! execution CSV stay in main.rs. The executor sits behind `MempoolExecutor`
+//! so dispatch is testable without a provider.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Opportunity registry gate (Duplicate) shared with the block loop
+//! Modified: 2026-10-16 - Trigger tx handed to the executor (bundle submission)
+//! Modified: 2026-10-16 - Route cooldown checked and recorded at the signal's trade size
+//! Modified: 2026-10-16 - Cooldown checked as CooldownSource::Mempool (young cooldowns bypassed)
+//! Modified: 2026-10-16 - Bypass noted only for signals that reach execution
+//! Modified: 2026-10-17 - Backrun size priced at the quote token's USD price (WETH quotes no longer sized at $1)
+//! Modified: 2026-10-17 - Canary (demoted routes, size cap), liquidity guard and whitelist route limits applied
+
+use super::bundle::TriggerTx;
+use super::MempoolSignal;
+use crate::arbitrage::canary::CanaryTracker;
+use crate::arbitrage::liquidity_guard::{LiquidityGuard, LiquidityRejection, LiquidityVerdict};
+use crate::arbitrage::{CooldownSource, OpportunityRegistry, RouteCooldown, TradeExecutor};
+use crate::filters::whitelist::{EffectiveLimits, TradeLimits};
+use crate::pool::PoolStateManager;
+use crate::shutdown::ShutdownController;
+use crate::types::{usd_to_raw, ArbitrageOpportunity, BotConfig, TradeResult, TradingPair};
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::prelude::*;
+use std::fmt;
+use std::time::Instant;
+
+/// Signals older than this are dropped (the trigger tx has likely landed)
+pub const MAX_SIGNAL_AGE_MS: u64 = 10_000;
+
+/// The one executor call mempool dispatch needs
+#[async_trait(?Send)]
+pub trait MempoolExecutor {
+    async fn execute_from_mempool(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        trigger_gas_price: U256,
+        trigger_max_priority_fee: Option<U256>,
+        trigger: Option<&TriggerTx>,
+        mempool_min_profit_usd: f64,
+    ) -> Result<TradeResult>;
+}
+
+#[async_trait(?Send)]
+impl<M: Middleware + 'static> MempoolExecutor for TradeExecutor<M> {
+    async fn execute_from_mempool(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        trigger_gas_price: U256,
+        trigger_max_priority_fee: Option<U256>,
+        trigger: Option<&TriggerTx>,
+        mempool_min_profit_usd: f64,
+    ) -> Result<TradeResult> {
+        TradeExecutor::execute_from_mempool(
+            self, opportunity, trigger_gas_price, trigger_max_priority_fee, trigger, mempool_min_profit_usd,
+        ).await
+    }
+}
+
+/// Why a signal was not executed
+#[derive(Debug, Clone, PartialEq)]
+pub enum MempoolSkip {
+    Stale { lead_ms: u64 },
+    Unresolved,
+    Quarantined,
+    /// A leg's pool failed its canary window
+    Demoted,
+    Liquidity(LiquidityRejection),
+    CooledDown,
+    /// An equivalent opportunity was submitted within the registry TTL
+    Duplicate,
+    ShuttingDown,
+}
+
+impl fmt::Display for MempoolSkip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MempoolSkip::Stale { lead_ms } => write!(f, "stale signal ({}ms > {}s)", lead_ms, MAX_SIGNAL_AGE_MS / 1000),
+            MempoolSkip::Unresolved => write!(f, "pool data resolution failed"),
+            MempoolSkip::Quarantined => write!(f, "quarantined token (metadata changed)"),
+            MempoolSkip::Demoted => write!(f, "canary-demoted pool"),
+            MempoolSkip::Liquidity(reason) => write!(f, "{}", reason),
+            MempoolSkip::CooledDown => write!(f, "route in cooldown"),
+            MempoolSkip::Duplicate => write!(f, "already submitted (opportunity registry)"),
+            MempoolSkip::ShuttingDown => write!(f, "shutting down"),
+        }
+    }
+}
+
+/// An executed signal
+#[derive(Debug)]
+pub struct MempoolExecution {
+    pub opportunity: ArbitrageOpportunity,
+    pub result: Result<TradeResult>,
+    /// Signal age when execution started
+    pub lead_ms: u64,
+    pub exec_ms: u64,
+}
+
+/// What happened to one signal
+#[derive(Debug)]
+pub enum MempoolDispatch {
+    Skipped(MempoolSkip),
+    Executed(Box<MempoolExecution>),
+}
+
+/// Main-loop state a dispatch reads and updates
+pub struct DispatchContext<'a> {
+    pub state_manager: &'a PoolStateManager,
+    pub config: &'a BotConfig,
+    pub route_cooldown: &'a mut RouteCooldown,
+    pub registry: &'a mut OpportunityRegistry,
+    pub current_block: u64,
+    pub shutdown: &'a ShutdownController,
+    /// Token validator's quarantine check (always false without one)
+    pub quarantined: &'a dyn Fn(&ArbitrageOpportunity) -> bool,
+    pub canary: &'a CanaryTracker,
+    pub liquidity_guard: &'a mut LiquidityGuard,
+    /// Whitelist trade limit overrides of a route (pair, pools)
+    pub route_limits: &'a dyn Fn(&str, &[Address]) -> TradeLimits,
+}
+
+/// Gate, execute and record one mempool signal
+pub async fn dispatch_signal<E: MempoolExecutor>(
+    signal: &MempoolSignal,
+    ctx: DispatchContext<'_>,
+    executor: &mut E,
+) -> MempoolDispatch {
+    let lead_ms = signal.seen_at.elapsed().as_millis() as u64;
+    if lead_ms > MAX_SIGNAL_AGE_MS {
+        return MempoolDispatch::Skipped(MempoolSkip::Stale { lead_ms });
+    }
+    let mut opportunity = match build_arb_opportunity(signal, ctx.state_manager, ctx.config, ctx.route_limits) {
+        Some(o) => o,
+        None => return MempoolDispatch::Skipped(MempoolSkip::Unresolved),
+    };
+    if (ctx.quarantined)(&opportunity) {
+        return MempoolDispatch::Skipped(MempoolSkip::Quarantined);
+    }
+    if ctx.canary.is_demoted_route(&opportunity) {
+        return MempoolDispatch::Skipped(MempoolSkip::Demoted);
+    }
+    ctx.canary.cap_trade_size(&mut opportunity);
+    if let LiquidityVerdict::Rejected(reason) = ctx.liquidity_guard.check(&mut opportunity) {
+        return MempoolDispatch::Skipped(MempoolSkip::Liquidity(reason));
+    }
+    let (pair, buy, sell) = (&opportunity.pair.symbol, opportunity.buy_dex, opportunity.sell_dex);
+    let size_usd = opportunity.trade_size_usd;
+    if ctx.route_cooldown.is_cooled_down_for_source(pair, buy, sell, size_usd, ctx.current_block, CooldownSource::Mempool) {
+        return MempoolDispatch::Skipped(MempoolSkip::CooledDown);
+    }
+    if ctx.registry.is_duplicate(&opportunity, ctx.current_block) {
+        return MempoolDispatch::Skipped(MempoolSkip::Duplicate);
+    }
+    if ctx.shutdown.is_shutting_down() {
+        return MempoolDispatch::Skipped(MempoolSkip::ShuttingDown);
+    }
+
+    ctx.route_cooldown.note_bypass_attempt(pair, buy, sell, size_usd, ctx.current_block);
+
+    // Mempool-specific path: no estimateGas, gas bid from the trigger tx
+    // (bundled behind it when a relay is configured)
+    let exec_start = Instant::now();
+    let trigger = signal.trigger();
+    let result = ctx.shutdown.track(executor.execute_from_mempool(
+        &opportunity,
+        signal.trigger_gas_price,
+        signal.trigger_max_priority_fee,
+        Some(&trigger),
+        ctx.config.mempool_min_profit_usd,
+    )).await;
+    let exec_ms = exec_start.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(ref r) if r.success => ctx.route_cooldown.record_success(pair, buy, sell, size_usd),
+        _ => ctx.route_cooldown.record_failure(pair, buy, sell, size_usd, ctx.current_block),
+    }
+    if result.as_ref().is_ok_and(|r| r.tx_hash.is_some()) {
+        ctx.registry.record_submission(&opportunity, ctx.current_block);
+    }
+
+    MempoolDispatch::Executed(Box::new(MempoolExecution { opportunity, result, lead_ms, exec_ms }))
+}
+
+/// SimulatedOpportunity → ArbitrageOpportunity, resolving tokens, decimals,
+/// pool addresses and V3 liquidity from the state manager, sized at the
+/// route's whitelist limits. None = neither leg's pool known.
+pub fn build_arb_opportunity(
+    signal: &MempoolSignal,
+    state_manager: &PoolStateManager,
+    config: &BotConfig,
+    route_limits: &dyn Fn(&str, &[Address]) -> TradeLimits,
+) -> Option<ArbitrageOpportunity> {
+    let opp = &signal.opportunity;
+
+    // Resolve trading pair from pair_symbol
+    // Find any pool for this pair to get token addresses
+    let (token0, token1, t0_dec, t1_dec) = {
+        // Try buy_dex pool first, then sell_dex
+        let buy_pool = if opp.arb_buy_dex.is_v3() {
+            state_manager.get_v3_pool(opp.arb_buy_dex, &opp.pair_symbol)
+                .map(|p| (p.pair.token0, p.pair.token1, p.token0_decimals, p.token1_decimals))
+        } else {
+            state_manager.get_pool(opp.arb_buy_dex, &opp.pair_symbol)
+                .map(|p| (p.pair.token0, p.pair.token1, p.token0_decimals, p.token1_decimals))
+        };
+        let sell_pool = if opp.arb_sell_dex.is_v3() {
+            state_manager.get_v3_pool(opp.arb_sell_dex, &opp.pair_symbol)
+                .map(|p| (p.pair.token0, p.pair.token1, p.token0_decimals, p.token1_decimals))
+        } else {
+            state_manager.get_pool(opp.arb_sell_dex, &opp.pair_symbol)
+                .map(|p| (p.pair.token0, p.pair.token1, p.token0_decimals, p.token1_decimals))
+        };
+        buy_pool.or(sell_pool)?
+    };
+
+    // Get pool addresses
+    let buy_pool_addr = if opp.arb_buy_dex.is_v3() {
+        state_manager.get_v3_pool(opp.arb_buy_dex, &opp.pair_symbol).map(|p| p.address)
+    } else {
+        state_manager.get_pool(opp.arb_buy_dex, &opp.pair_symbol).map(|p| p.address)
+    };
+    let sell_pool_addr = if opp.arb_sell_dex.is_v3() {
+        state_manager.get_v3_pool(opp.arb_sell_dex, &opp.pair_symbol).map(|p| p.address)
+    } else {
+        state_manager.get_pool(opp.arb_sell_dex, &opp.pair_symbol).map(|p| p.address)
+    };
+
+    // V3 in-range liquidity of each leg (the liquidity guard's floor and depth)
+    let buy_liquidity = if opp.arb_buy_dex.is_v3() {
+        state_manager.get_v3_pool(opp.arb_buy_dex, &opp.pair_symbol).map(|p| p.liquidity)
+    } else {
+        None
+    };
+    let sell_liquidity = if opp.arb_sell_dex.is_v3() {
+        state_manager.get_v3_pool(opp.arb_sell_dex, &opp.pair_symbol).map(|p| p.liquidity)
+    } else {
+        None
+    };
+
+    // Determine quote_token_is_token0 by comparing pool's token0 with known quote tokens.
+    // Supports both USDC.e (primary) and native USDC (secondary) on Polygon.
+    let quote_token_is_token0 = config.is_quote_token(&token0);
+
+    // Trade size: the route's max_trade_size_usd (whitelist overrides of the
+    // pair and both pools) in quote token raw units at the quote price
+    let pools: Vec<Address> = buy_pool_addr.into_iter().chain(sell_pool_addr).collect();
+    let max_trade_size_usd = route_limits(&opp.pair_symbol, &pools)
+        .effective(EffectiveLimits::global(config))
+        .max_trade_size_usd;
+    let quote_decimals = if quote_token_is_token0 { t0_dec } else { t1_dec };
+    let quote_price_usd = config.quote_price_usd(if quote_token_is_token0 { &token0 } else { &token1 });
+    let trade_size = usd_to_raw(max_trade_size_usd / quote_price_usd, quote_decimals);
+
+    let pair = TradingPair::new(token0, token1, opp.pair_symbol.clone());
+
+    let mut arb = ArbitrageOpportunity::new(
+        pair,
+        opp.arb_buy_dex,
+        opp.arb_sell_dex,
+        opp.pre_swap_price,   // buy_price
+        opp.post_swap_price,  // sell_price
+        trade_size,
+    );
+    arb.estimated_profit = opp.arb_est_profit_usd;
+    arb.spread_percent = opp.arb_spread_pct;
+    arb.buy_pool_address = buy_pool_addr;
+    arb.sell_pool_address = sell_pool_addr;
+    arb.buy_pool_liquidity = buy_liquidity;
+    arb.sell_pool_liquidity = sell_liquidity;
+    arb.set_quote_layout(t0_dec, t1_dec, quote_token_is_token0);
+    arb.normalize_trade_size(quote_price_usd);
+
+    Some(arb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::canary::{CanaryConfig, CanaryDecision};
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::arbitrage::liquidity_guard::Leg;
+    use crate::filters::PoolWhitelist;
+    use crate::config::validate_config;
+    use crate::mempool::types::SimulatedOpportunity;
+    use crate::types::{DexType, V3PoolState};
+    use anyhow::anyhow;
+    use std::time::Duration;
+    use tokio_util::sync::CancellationToken;
+
+    /// (pair, trigger gas price, trigger priority fee, trigger, mempool min profit)
+    type MockCall = (String, U256, Option<U256>, Option<TriggerTx>, f64);
+
+    /// Records every call; succeeds or fails as told
+    struct MockExecutor {
+        calls: Vec<MockCall>,
+        succeed: bool,
+    }
+
+    #[async_trait(?Send)]
+    impl MempoolExecutor for MockExecutor {
+        async fn execute_from_mempool(
+            &mut self,
+            opportunity: &ArbitrageOpportunity,
+            trigger_gas_price: U256,
+            trigger_max_priority_fee: Option<U256>,
+            trigger: Option<&TriggerTx>,
+            mempool_min_profit_usd: f64,
+        ) -> Result<TradeResult> {
+            self.calls.push((
+                opportunity.pair.symbol.clone(), trigger_gas_price, trigger_max_priority_fee, trigger.cloned(),
+                mempool_min_profit_usd,
+            ));
+            if !self.succeed {
+                return Err(anyhow!("execution reverted"));
+            }
+            Ok(trade_result(opportunity, 1.18))
+        }
+    }
+
+    /// A submitted trade of `opportunity` netting `net_profit_usd`
+    fn trade_result(opportunity: &ArbitrageOpportunity, net_profit_usd: f64) -> TradeResult {
+        TradeResult {
+            opportunity: opportunity.pair.symbol.clone(),
+            opportunity_id: opportunity.id,
+            tx_hash: Some("0xabc".to_string()),
+            block_number: Some(101),
+            success: net_profit_usd > 0.0,
+            profit_usd: net_profit_usd + 0.02,
+            gas_cost_usd: 0.02,
+            gas_used_native: 0.01,
+            net_profit_usd,
+            execution_time_ms: 900,
+            error: None,
+            amount_in: None,
+            amount_out: None,
+        }
+    }
+
+    /// Canary tracker over the buy pool (0x…01); None = no canary pools
+    fn canary_tracker(config: &BotConfig, canary_trades: Option<u32>) -> CanaryTracker {
+        let pools = canary_trades.map(|n| format!(
+            r#"{{ "address": "{:?}", "pair": "WETH/USDC", "dex": "UniswapV3", "fee_tier": 500,
+                 "status": "active", "canary_trades": {} }}"#,
+            Address::from_low_u64_be(1), n
+        )).unwrap_or_default();
+        let json = format!(r#"{{
+            "version": "1.0",
+            "last_updated": "2026-10-17T00:00:00Z",
+            "config": {{ "default_min_liquidity": 1000, "whitelist_enforcement": "strict" }},
+            "whitelist": {{ "pools": [{}] }},
+            "blacklist": {{ "pools": [], "fee_tiers": [] }}
+        }}"#, pools);
+        let whitelist: PoolWhitelist = serde_json::from_str(&json).unwrap();
+        CanaryTracker::from_whitelist(&whitelist, CanaryConfig::from_bot_config(config))
+    }
+
+    /// Global limits only (no whitelist overrides)
+    fn no_overrides(_: &str, _: &[Address]) -> TradeLimits {
+        TradeLimits::default()
+    }
+
+    fn pools(config: &BotConfig) -> PoolStateManager {
+        let state_manager = PoolStateManager::new();
+        let weth = Address::from_low_u64_be(0xe7);
+        for (n, dex) in [(1, DexType::UniswapV3_005), (2, DexType::QuickswapV3)] {
+            state_manager.update_v3_pool(V3PoolState {
+                address: Address::from_low_u64_be(n),
+                dex,
+                pair: TradingPair::new(config.quote_token_address, weth, "WETH/USDC".to_string()),
+                sqrt_price_x96: U256::from(1u128 << 96),
+                tick: 0,
+                fee: 500,
+                liquidity: 1_000_000,
+                token0_decimals: 6,
+                token1_decimals: 18,
+                last_updated: 100,
+            });
+        }
+        state_manager
+    }
+
+    fn signal(pair: &str) -> MempoolSignal {
+        MempoolSignal {
+            opportunity: SimulatedOpportunity {
+                timestamp_utc: String::new(),
+                tx_hash: TxHash::zero(),
+                trigger_dex: DexType::UniswapV3_005,
+                trigger_function: "exactInputSingle".to_string(),
+                pair_symbol: pair.to_string(),
+                zero_for_one: true,
+                amount_in: U256::exp10(9),
+                pre_swap_price: 2500.0,
+                post_swap_price: 2512.0,
+                price_impact_pct: 0.48,
+                arb_buy_dex: DexType::UniswapV3_005,
+                arb_sell_dex: DexType::QuickswapV3,
+                arb_spread_pct: 0.48,
+                arb_est_profit_usd: 1.5,
+                arb_buy_amount_out: U256::exp10(17) * 2,
+                arb_sell_amount_out: U256::from(501_500_000u64),
+                arb_residual_spread_pct: 0.1,
+            },
+            trigger_gas_price: U256::from(80_000_000_000u64),
+            trigger_max_priority_fee: Some(U256::from(30_000_000_000u64)),
+            trigger_raw_tx: Some(Bytes::from(vec![0x02, 0xf8])),
+            seen_at: Instant::now(),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn ctx<'a>(
+        state_manager: &'a PoolStateManager,
+        config: &'a BotConfig,
+        route_cooldown: &'a mut RouteCooldown,
+        registry: &'a mut OpportunityRegistry,
+        canary: &'a CanaryTracker,
+        liquidity_guard: &'a mut LiquidityGuard,
+        current_block: u64,
+        shutdown: &'a ShutdownController,
+    ) -> DispatchContext<'a> {
+        DispatchContext {
+            state_manager, config, route_cooldown, registry, current_block, shutdown,
+            quarantined: &|_| false,
+            canary,
+            liquidity_guard,
+            route_limits: &no_overrides,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_executes_at_trigger_gas_and_failure_cools_route() {
+        let mut config = create_test_config();
+        config.mempool_min_profit_usd = 0.25;
+        let state_manager = pools(&config);
+        let shutdown = ShutdownController::new(CancellationToken::new());
+        let mut cooldown = RouteCooldown::new(10);
+        let mut registry = OpportunityRegistry::new(0);
+        let canary = canary_tracker(&config, None);
+        let mut guard = LiquidityGuard::new(&config);
+        let mut executor = MockExecutor { calls: Vec::new(), succeed: false };
+
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 100, &shutdown), &mut executor).await;
+        match dispatch {
+            MempoolDispatch::Executed(execution) => {
+                assert!(execution.result.is_err());
+                assert_eq!(execution.opportunity.buy_pool_address, Some(Address::from_low_u64_be(1)));
+                assert_eq!(execution.opportunity.sell_pool_address, Some(Address::from_low_u64_be(2)));
+            }
+            other => panic!("expected execution, got {:?}", other),
+        }
+        // Gas bid, trigger tx and profit floor come from the signal and config
+        assert_eq!(executor.calls, vec![(
+            "WETH/USDC".to_string(),
+            U256::from(80_000_000_000u64),
+            Some(U256::from(30_000_000_000u64)),
+            Some(TriggerTx { hash: TxHash::zero(), raw: Some(Bytes::from(vec![0x02, 0xf8])) }),
+            0.25,
+        )]);
+
+        // The failure cooled the route: the next signal never reaches the executor
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 101, &shutdown), &mut executor).await;
+        assert!(matches!(dispatch, MempoolDispatch::Skipped(MempoolSkip::CooledDown)));
+        assert_eq!(executor.calls.len(), 1);
+
+        // Once it expires, a success clears the route
+        executor.succeed = true;
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 111, &shutdown), &mut executor).await;
+        assert!(matches!(dispatch, MempoolDispatch::Executed(ref e) if e.result.as_ref().is_ok_and(|r| r.success)));
+        assert!(!cooldown.is_cooled_down("WETH/USDC", DexType::UniswapV3_005, DexType::QuickswapV3, config.max_trade_size_usd, 112));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_gates_skip_without_executing() {
+        let config = create_test_config();
+        let state_manager = pools(&config);
+        let shutdown = ShutdownController::new(CancellationToken::new());
+        let mut cooldown = RouteCooldown::new(10);
+        let mut registry = OpportunityRegistry::new(0);
+        let canary = canary_tracker(&config, None);
+        let mut guard = LiquidityGuard::new(&config);
+        let mut executor = MockExecutor { calls: Vec::new(), succeed: true };
+
+        let mut stale = signal("WETH/USDC");
+        stale.seen_at = Instant::now() - Duration::from_millis(MAX_SIGNAL_AGE_MS + 1);
+        let dispatch = dispatch_signal(&stale, ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 100, &shutdown), &mut executor).await;
+        assert!(matches!(dispatch, MempoolDispatch::Skipped(MempoolSkip::Stale { .. })));
+
+        let dispatch = dispatch_signal(&signal("WMATIC/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 100, &shutdown), &mut executor).await;
+        assert!(matches!(dispatch, MempoolDispatch::Skipped(MempoolSkip::Unresolved)));
+
+        let mut quarantined = ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 100, &shutdown);
+        quarantined.quarantined = &|_| true;
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), quarantined, &mut executor).await;
+        assert!(matches!(dispatch, MempoolDispatch::Skipped(MempoolSkip::Quarantined)));
+
+        shutdown.request();
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 100, &shutdown), &mut executor).await;
+        assert!(matches!(dispatch, MempoolDispatch::Skipped(MempoolSkip::ShuttingDown)));
+
+        assert!(executor.calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_skips_spread_already_submitted() {
+        let config = create_test_config();
+        let state_manager = pools(&config);
+        let shutdown = ShutdownController::new(CancellationToken::new());
+        let mut cooldown = RouteCooldown::new(10);
+        let mut registry = OpportunityRegistry::new(2);
+        let canary = canary_tracker(&config, None);
+        let mut guard = LiquidityGuard::new(&config);
+        let mut executor = MockExecutor { calls: Vec::new(), succeed: true };
+
+        // The block loop already sent this spread at block 100
+        let block_loop = build_arb_opportunity(&signal("WETH/USDC"), &state_manager, &config, &no_overrides).unwrap();
+        registry.record_submission(&block_loop, 100);
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 101, &shutdown), &mut executor).await;
+        assert!(matches!(dispatch, MempoolDispatch::Skipped(MempoolSkip::Duplicate)));
+        assert!(executor.calls.is_empty());
+
+        // TTL over: executes, and its own send is registered for the block loop
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 102, &shutdown), &mut executor).await;
+        assert!(matches!(dispatch, MempoolDispatch::Executed(_)));
+        assert!(registry.is_duplicate(&block_loop, 103));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_skips_canary_demoted_and_thin_routes() {
+        let mut config = create_test_config();
+        config.canary_min_trades = 1;
+        config.canary_min_win_rate = 0.5;
+        let state_manager = pools(&config);
+        let shutdown = ShutdownController::new(CancellationToken::new());
+        let mut cooldown = RouteCooldown::new(10);
+        let mut registry = OpportunityRegistry::new(0);
+        let mut guard = LiquidityGuard::new(&config);
+        let mut executor = MockExecutor { calls: Vec::new(), succeed: true };
+
+        // The buy pool's canary window closes on one losing trade: demoted
+        let mut canary = canary_tracker(&config, Some(1));
+        let opp = build_arb_opportunity(&signal("WETH/USDC"), &state_manager, &config, &no_overrides).unwrap();
+        canary.record_result(&opp, &trade_result(&opp, -0.5));
+        assert!(matches!(canary.due_decisions(0)[..], [CanaryDecision::Demote { .. }]));
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 100, &shutdown), &mut executor).await;
+        assert!(matches!(dispatch, MempoolDispatch::Skipped(MempoolSkip::Demoted)));
+
+        // Legs below the liquidity floor (1e6 in-range L) are rejected by the guard
+        let canary = canary_tracker(&config, None);
+        config.min_pool_liquidity = 2_000_000;
+        let mut guard = LiquidityGuard::new(&config);
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 100, &shutdown), &mut executor).await;
+        assert!(matches!(
+            dispatch,
+            MempoolDispatch::Skipped(MempoolSkip::Liquidity(LiquidityRejection::BelowMinimum { leg: Leg::Buy, .. }))
+        ));
+        assert_eq!(guard.stats().rejected_min, 1);
+
+        assert!(executor.calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_sizes_at_route_limits_and_canary_cap() {
+        let mut config = create_test_config();
+        config.canary_max_trade_size_usd = 25.0;
+        let state_manager = pools(&config);
+        let shutdown = ShutdownController::new(CancellationToken::new());
+        let mut cooldown = RouteCooldown::new(10);
+        let mut registry = OpportunityRegistry::new(0);
+        let mut guard = LiquidityGuard::new(&config);
+        let mut executor = MockExecutor { calls: Vec::new(), succeed: true };
+        let size_usd = |dispatch: MempoolDispatch| match dispatch {
+            MempoolDispatch::Executed(execution) => execution.opportunity.trade_size_usd,
+            other => panic!("expected execution, got {:?}", other),
+        };
+
+        // A whitelist override below MAX_TRADE_SIZE_USD ($500) sizes the backrun
+        let canary = canary_tracker(&config, None);
+        let mut limited = ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 100, &shutdown);
+        limited.route_limits = &|_, _| TradeLimits { max_trade_size_usd: Some(200.0), ..TradeLimits::default() };
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), limited, &mut executor).await;
+        assert!((size_usd(dispatch) - 200.0).abs() < 1e-9);
+
+        // A canary buy pool caps it at CANARY_MAX_TRADE_SIZE_USD
+        let canary = canary_tracker(&config, Some(10));
+        let dispatch = dispatch_signal(&signal("WETH/USDC"), ctx(&state_manager, &config, &mut cooldown, &mut registry, &canary, &mut guard, 101, &shutdown), &mut executor).await;
+        assert!((size_usd(dispatch) - 25.0).abs() < 1e-9);
+        assert_eq!(executor.calls.len(), 2);
+    }
+
+    #[test]
+    fn test_weth_quoted_backrun_sized_at_the_weth_price() {
+        let mut config = create_test_config();
+        let state_manager = pools(&config);
+        // Same pools, WETH (token1, 18 decimals) as the quote token at $3000
+        config.quote_token_address = Address::from_low_u64_be(0xe7);
+        config.quote_token_price_usd = 3000.0;
+
+        let opp = build_arb_opportunity(&signal("WETH/USDC"), &state_manager, &config, &no_overrides).unwrap();
+        assert!(!opp.quote_token_is_token0);
+        assert_eq!(opp.trade_size.raw(), usd_to_raw(500.0 / 3000.0, 18));
+        assert!((opp.trade_size_usd - 500.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_execute_mode_requires_executor_address() {
+        let mut config = create_test_config();
+        config.mempool_monitor_mode = "execute".to_string();
+        config.arb_executor_address = None;
+        assert!(validate_config(&config).is_err());
+
+        config.arb_executor_address = Some(Address::from_low_u64_be(0xa4b));
+        assert!(validate_config(&config).is_ok());
+        config.mempool_monitor_mode = "observe".to_string();
+        config.arb_executor_address = None;
+        assert!(validate_config(&config).is_ok());
+    }
+}
@@ -0,0 +1,235 @@
+//! A4 Mempool Monitor — Mid-Block Re-Scan
+//!
+//! Purpose:
+//!     MIDBLOCK_SCAN=true: don't wait for the next block to look at a pool a
+//!     pending swap is about to move. For every swap the monitor simulates
+//!     (SimulatedPoolState) it sends a MidblockUpdate to the main loop, which
+//!       1. checks the per-block budget (MIDBLOCK_SCAN_MAX_PER_BLOCK)
+//!       2. builds a shadow PoolStateManager: a detached copy of live state
+//!          with the trigger pool moved to its simulated post-swap state
+//!       3. runs the detector against the shadow and keeps the routes that
+//!          use the trigger pool, tagged `anticipatory` with the pending tx
+//!       4. sends them through the normal multicall pre-screen — the reality
+//!          check against the chain — before any is executed
+//!
+//! Live pool state is never written: the shadow is discarded after the scan.
+//! Anticipatory routes skip SPREAD_CONFIRM_BLOCKS (their spread is one
+//! simulation old) but pass cooldown, dedup and quarantine like block routes.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use super::types::SimulatedPoolState;
+use crate::pool::PoolStateManager;
+use crate::types::{ArbitrageOpportunity, PoolState, V3PoolState};
+use ethers::types::{Address, TxHash};
+use std::time::Instant;
+
+/// One simulated pending swap, monitor → main loop
+#[derive(Debug, Clone)]
+pub struct MidblockUpdate {
+    /// Trigger pool's predicted post-swap state
+    pub simulated: SimulatedPoolState,
+    /// The pending tx that moves it
+    pub tx_hash: TxHash,
+    /// When the pending tx was simulated
+    pub seen_at: Instant,
+}
+
+/// Re-scans allowed per block (MIDBLOCK_SCAN_MAX_PER_BLOCK); resets on each new block
+#[derive(Debug, Clone)]
+pub struct MidblockBudget {
+    max_per_block: u32,
+    block: u64,
+    used: u32,
+}
+
+impl MidblockBudget {
+    pub fn new(max_per_block: u32) -> Self {
+        Self { max_per_block, block: 0, used: 0 }
+    }
+
+    /// Take one re-scan at `block`; false once the block's budget is spent
+    pub fn try_acquire(&mut self, block: u64) -> bool {
+        if block != self.block {
+            self.block = block;
+            self.used = 0;
+        }
+        if self.used >= self.max_per_block {
+            return false;
+        }
+        self.used += 1;
+        true
+    }
+
+    /// Re-scans taken at the current block
+    pub fn used(&self) -> u32 {
+        self.used
+    }
+}
+
+/// Detached copy of `live` with the trigger pool at its simulated post-swap
+/// state. Returns the shadow and the pool moved; None when the pool isn't
+/// tracked or the simulation carries no post-swap state for its kind.
+pub fn shadow_state(live: &PoolStateManager, simulated: &SimulatedPoolState) -> Option<(PoolStateManager, Address)> {
+    if simulated.is_v3 {
+        let prev = live.get_v3_pool(simulated.dex, &simulated.pair_symbol)?;
+        let sqrt_price_x96 = simulated.post_sqrt_price_x96?;
+        let pool = V3PoolState {
+            sqrt_price_x96,
+            tick: simulated.post_tick.unwrap_or(prev.tick),
+            liquidity: simulated.post_liquidity.unwrap_or(prev.liquidity),
+            ..prev
+        };
+        let address = pool.address;
+        let shadow = live.detached_copy();
+        shadow.update_v3_pool(pool);
+        Some((shadow, address))
+    } else {
+        let prev = live.get_pool(simulated.dex, &simulated.pair_symbol)?;
+        let pool = PoolState {
+            reserve0: simulated.post_reserve0?,
+            reserve1: simulated.post_reserve1?,
+            ..prev
+        };
+        let address = pool.address;
+        let shadow = live.detached_copy();
+        shadow.update_pool(pool);
+        Some((shadow, address))
+    }
+}
+
+/// Keep the shadow scan's routes with a leg on the trigger pool (the rest were
+/// already visible to the block loop) and tag them with the pending tx
+pub fn tag_anticipatory(
+    opportunities: Vec<ArbitrageOpportunity>,
+    trigger_pool: Address,
+    tx_hash: TxHash,
+) -> Vec<ArbitrageOpportunity> {
+    opportunities
+        .into_iter()
+        .filter(|opp| opp.buy_pool_address == Some(trigger_pool) || opp.sell_pool_address == Some(trigger_pool))
+        .map(|mut opp| {
+            opp.anticipatory = Some(tx_hash);
+            opp
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+    use ethers::types::U256;
+
+    fn pair() -> TradingPair {
+        TradingPair::new(
+            Address::from_low_u64_be(0xa),
+            Address::from_low_u64_be(0xb),
+            "WETH/USDC".to_string(),
+        )
+    }
+
+    fn live() -> PoolStateManager {
+        let manager = PoolStateManager::new();
+        manager.update_v3_pool(V3PoolState {
+            address: Address::from_low_u64_be(1),
+            dex: DexType::UniswapV3_005,
+            pair: pair(),
+            sqrt_price_x96: U256::from(1u128 << 96),
+            tick: 0,
+            fee: 500,
+            liquidity: 1_000,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 100,
+        });
+        manager.update_pool(PoolState {
+            address: Address::from_low_u64_be(2),
+            dex: DexType::QuickSwapV2,
+            pair: pair(),
+            reserve0: U256::from(1_000),
+            reserve1: U256::from(2_000),
+            last_updated: 100,
+            token0_decimals: 6,
+            token1_decimals: 18,
+        });
+        manager
+    }
+
+    fn simulated(dex: DexType, is_v3: bool) -> SimulatedPoolState {
+        SimulatedPoolState {
+            dex,
+            pair_symbol: "WETH/USDC".to_string(),
+            is_v3,
+            pre_swap_price: 1.0,
+            post_swap_price: 1.1,
+            post_sqrt_price_x96: is_v3.then(|| U256::from(11u128 << 93)),
+            post_reserve0: (!is_v3).then(|| U256::from(1_100)),
+            post_reserve1: (!is_v3).then(|| U256::from(1_820)),
+            post_tick: is_v3.then_some(953),
+            post_liquidity: None,
+            ticks_crossed: None,
+        }
+    }
+
+    #[test]
+    fn test_shadow_state_moves_trigger_pool_only_in_copy() {
+        let live = live();
+
+        let (shadow, pool) = shadow_state(&live, &simulated(DexType::UniswapV3_005, true)).unwrap();
+        assert_eq!(pool, Address::from_low_u64_be(1));
+        let moved = shadow.get_v3_pool_at(&pool).unwrap();
+        assert_eq!((moved.sqrt_price_x96, moved.tick), (U256::from(11u128 << 93), 953));
+        // No tick data: liquidity, fee and sync block carried over
+        assert_eq!((moved.liquidity, moved.fee, moved.last_updated), (1_000, 500, 100));
+        // Other pools copied as-is; live state untouched
+        assert_eq!(shadow.get_pool_at(&Address::from_low_u64_be(2)).unwrap().reserve0, U256::from(1_000));
+        let untouched = live.get_v3_pool_at(&pool).unwrap();
+        assert_eq!((untouched.sqrt_price_x96, untouched.tick), (U256::from(1u128 << 96), 0));
+
+        let (shadow, pool) = shadow_state(&live, &simulated(DexType::QuickSwapV2, false)).unwrap();
+        let moved = shadow.get_pool_at(&pool).unwrap();
+        assert_eq!((moved.reserve0, moved.reserve1), (U256::from(1_100), U256::from(1_820)));
+        assert_eq!(live.get_pool_at(&pool).unwrap().reserve0, U256::from(1_000));
+
+        // Untracked pool, or no post-swap state for the pool's kind
+        assert!(shadow_state(&live, &simulated(DexType::SushiV3_005, true)).is_none());
+        let mut no_post = simulated(DexType::UniswapV3_005, true);
+        no_post.post_sqrt_price_x96 = None;
+        assert!(shadow_state(&live, &no_post).is_none());
+    }
+
+    #[test]
+    fn test_tag_anticipatory_keeps_trigger_pool_routes() {
+        let trigger = Address::from_low_u64_be(1);
+        let route = |buy: u64, sell: u64| {
+            let mut opp = ArbitrageOpportunity::new(
+                pair(), DexType::UniswapV3_005, DexType::QuickSwapV2, 1.0, 1.1, U256::from(1_000),
+            );
+            opp.buy_pool_address = Some(Address::from_low_u64_be(buy));
+            opp.sell_pool_address = Some(Address::from_low_u64_be(sell));
+            opp
+        };
+        let tx_hash = TxHash::from_low_u64_be(0xfeed);
+
+        let tagged = tag_anticipatory(vec![route(1, 2), route(3, 4), route(2, 1)], trigger, tx_hash);
+        assert_eq!(tagged.len(), 2);
+        assert!(tagged.iter().all(|opp| opp.anticipatory == Some(tx_hash)));
+        assert_eq!(tagged[1].sell_pool_address, Some(trigger));
+        // Block-loop detections stay untagged
+        assert_eq!(route(1, 2).anticipatory, None);
+    }
+
+    #[test]
+    fn test_budget_resets_per_block() {
+        let mut budget = MidblockBudget::new(2);
+        assert!(budget.try_acquire(10));
+        assert!(budget.try_acquire(10));
+        assert!(!budget.try_acquire(10));
+        assert_eq!(budget.used(), 2);
+        assert!(budget.try_acquire(11));
+        assert_eq!(budget.used(), 1);
+        assert!(!MidblockBudget::new(0).try_acquire(11));
+    }
+}
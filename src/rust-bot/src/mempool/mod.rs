@@ -7,21 +7,40 @@
 //! Author: AI-Generated
 //! Created: 2026-02-01
 //! Modified: 2026-02-01
+//! Modified: 2026-10-16 - Added ticks (initialized tick fetching for cross-tick V3 simulation)
+//! Modified: 2026-10-16 - Added dispatch (Phase 3 execute mode: signal gates + execution)
+//! Modified: 2026-10-16 - Added calibration (simulation error → per-route execute threshold)
+//! Modified: 2026-10-16 - Added bundle (searcher bundle relay submission for backruns)
+//! Modified: 2026-10-16 - Added midblock (MIDBLOCK_SCAN: detection re-run on simulated shadow state)
 //!
 //! Architecture:
 //!     types.rs      — PendingSwap, DecodedSwap, MempoolMode, ConfirmationTracker, SimulationTracker
 //!     decoder.rs    — Calldata → DecodedSwap (V2/V3/Algebra router functions)
 //!     monitor.rs    — WS subscription loop, CSV logging, cross-reference tracking
 //!     simulator.rs  — Phase 2: AMM state simulation (V2 constant product, V3 sqrtPrice)
+//!     ticks.rs      — TickDataProvider: cached tickBitmap/ticks reads, TickMath
+//!     dispatch.rs   — Phase 3: MempoolSignal → gated execute_from_mempool() + route cooldown
+//!     calibration.rs — Rolling simulation error per (DEX, pair) → adaptive signal threshold
+//!     bundle.rs     — BundleSubmitter: backrun pinned behind its trigger via a searcher relay
+//!     midblock.rs   — MidblockUpdate → shadow PoolStateManager → anticipatory opportunities
 //!
 //! Usage:
-//!     Spawned as an async task from main.rs when MEMPOOL_MONITOR=observe.
+//!     Spawned as an async task from main.rs when MEMPOOL_MONITOR=observe|execute.
+//!     In execute mode, signals come back to the main loop via dispatch_signal().
 //!     Receives PoolStateManager (Arc-cloned) for Phase 2 simulation access.
 
+pub mod bundle;
+pub mod calibration;
 pub mod decoder;
+pub mod dispatch;
+pub mod midblock;
 pub mod monitor;
 pub mod simulator;
+pub mod ticks;
 pub mod types;
 
+pub use bundle::{BundleOutcome, BundleSubmitter, TriggerTx};
+pub use dispatch::{dispatch_signal, DispatchContext, MempoolDispatch, MempoolExecution, MempoolExecutor, MempoolSkip};
+pub use midblock::{MidblockBudget, MidblockUpdate};
 pub use monitor::{run_observation, run_execution};
 pub use types::{MempoolMode, MempoolSignal};
@@ -9,6 +9,16 @@
 //! Author: AI-Generated
 //! Created: 2026-02-01
 //! Modified: 2026-02-01
+//! Modified: 2026-10-16 - Reconnect loop on retry::mempool_reconnect (jittered, shutdown-aware)
+//! Modified: 2026-10-16 - Watchdog heartbeat per check tick; timeouts on cross-reference RPCs
+//! Modified: 2026-10-16 - Exact-output pending swaps simulated (amount_in column = implied input)
+//! Modified: 2026-10-16 - Cross-tick V3 simulation via TickDataProvider; accuracy CSV impact + ticks_crossed columns
+//! Modified: 2026-10-16 - Execute threshold calibrated per (DEX, pair) from simulation accuracy (calibration.json)
+//! Modified: 2026-10-16 - Simulated opportunities CSV: backrun leg outputs + residual spread columns
+//! Modified: 2026-10-16 - Trigger tx signed RLP carried on MempoolSignal (bundle submission)
+//! Modified: 2026-10-16 - Every simulated pool state forwarded as a MidblockUpdate (MIDBLOCK_SCAN)
+//! Modified: 2026-10-16 - Subscriptions on config.subscription_url() (WS_RPC_URL beside an IPC RPC_URL)
+//! Modified: 2026-10-16 - PENDING lines print token symbols and whole-token amounts from the cached TokenRegistry
 //!
 //! Dependencies:
 //!     - ethers (WS provider, subscription)
@@ -33,39 +43,57 @@ use ethers::types::Transaction;
 use futures::StreamExt;
 use std::collections::HashMap;
 use std::io::Write;
+use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc;
 use tokio::time::{interval, Duration};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 use crate::pool::PoolStateManager;
+use crate::retry::{self, Backoffed, Retrier, RetrySite, MAX_MEMPOOL_RECONNECTS};
+use crate::token_registry::{self, TokenRegistry};
 use crate::types::BotConfig;
+use crate::watchdog::{self, rpc_timeout};
 
+use super::calibration::Calibration;
 use super::decoder;
+use super::midblock::MidblockUpdate;
 use super::simulator;
-use super::types::{ConfirmationTracker, MempoolSignal, PendingSwap, SimulatedOpportunity, SimulationTracker};
+use super::ticks::TickDataProvider;
+use super::types::{
+    ConfirmationTracker, MempoolSignal, PendingSwap, SimulatedOpportunity, SimulatedPoolState, SimulationTracker,
+};
 
 /// Phase 3: Minimum spread (%) to trigger execution signal.
-/// 0.01% = 1 bps net-of-fees — filters noise.
+/// 0.01% = 1 bps net-of-fees — filters noise. The effective threshold adds
+/// k × the trigger pool's rolling simulation error (calibration.rs).
 const MEMPOOL_MIN_SPREAD_PCT: f64 = 0.01;
 
 /// Run the mempool execution monitor (Phase 3).
 /// Wraps run_observation_impl with a signal sender — sends MempoolSignal to the
 /// main loop when a simulated opportunity exceeds the execution threshold.
+/// midblock_tx (MIDBLOCK_SCAN) receives every simulated pool state.
 pub async fn run_execution(
     config: BotConfig,
     pool_state: PoolStateManager,
     signal_tx: mpsc::Sender<MempoolSignal>,
+    midblock_tx: Option<mpsc::Sender<MidblockUpdate>>,
 ) -> Result<()> {
-    run_observation_impl(config, pool_state, Some(signal_tx)).await
+    run_observation_impl(config, pool_state, Some(signal_tx), midblock_tx).await
 }
 
 /// Run the mempool observation monitor.
 /// This is the main entry point, called from main.rs via tokio::spawn.
 /// Creates its own WS connections and runs indefinitely with auto-reconnect.
 /// Phase 2: accepts PoolStateManager for AMM state simulation.
-pub async fn run_observation(config: BotConfig, pool_state: PoolStateManager) -> Result<()> {
-    run_observation_impl(config, pool_state, None).await
+/// midblock_tx (MIDBLOCK_SCAN) receives every simulated pool state.
+pub async fn run_observation(
+    config: BotConfig,
+    pool_state: PoolStateManager,
+    midblock_tx: Option<mpsc::Sender<MidblockUpdate>>,
+) -> Result<()> {
+    run_observation_impl(config, pool_state, None, midblock_tx).await
 }
 
 /// Implementation: observation + optional execution signaling.
@@ -75,6 +103,7 @@ async fn run_observation_impl(
     config: BotConfig,
     pool_state: PoolStateManager,
     signal_tx: Option<mpsc::Sender<MempoolSignal>>,
+    midblock_tx: Option<mpsc::Sender<MidblockUpdate>>,
 ) -> Result<()> {
     let chain = &config.chain_name;
 
@@ -83,6 +112,13 @@ async fn run_observation_impl(
     std::fs::create_dir_all(&data_dir)
         .with_context(|| format!("Failed to create mempool data dir: {}", data_dir))?;
 
+    // Token labels for the PENDING log: the main loop resolved tokens.json at
+    // startup. Unknown tokens (most pending swaps) print as addresses.
+    let tokens = TokenRegistry::load(&token_registry::tokens_path(&config), config.chain_id).unwrap_or_else(|e| {
+        warn!("Mempool: token registry unreadable ({}) — tokens logged by address", e);
+        TokenRegistry::new(config.chain_id)
+    });
+
     // Collect V3 router addresses for the Alchemy toAddress filter.
     // V2 routers are excluded from Phase 1 (99% of pending volume = too expensive
     // at 40 CU/tx for full objects; V3 is ~2/min = ~3.5M CU/month).
@@ -119,30 +155,54 @@ async fn run_observation_impl(
     );
 
     // Reconnect loop — if subscriptions drop, reconnect and continue
-    let mut reconnects = 0u32;
-    const MAX_RECONNECTS: u32 = 50;
+    supervise_sessions(retry::shutdown_token(), || {
+        run_observation_inner(&config, &data_dir, &router_hex, &router_lookup, &tokens, &pool_state, &signal_tx, &midblock_tx)
+    })
+    .await
+}
+
+/// Run WS sessions until one exits cleanly, reconnecting on error per
+/// retry::mempool_reconnect(). The 51st failure returns its error; shutdown
+/// during a wait stops cleanly.
+async fn supervise_sessions<F, Fut>(cancel: CancellationToken, mut session: F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let mut reconnect = Retrier::new(
+        retry::mempool_reconnect(),
+        RetrySite::named("mempool_reconnect"),
+        cancel,
+    );
 
     loop {
-        match run_observation_inner(&config, &data_dir, &router_hex, &router_lookup, &pool_state, &signal_tx).await {
+        reconnect.attempt();
+        match session().await {
             Ok(()) => {
                 // Clean exit (shouldn't happen in observe mode)
+                reconnect.succeeded();
                 info!("Mempool monitor exited cleanly");
                 break;
             }
             Err(e) => {
-                reconnects += 1;
-                if reconnects > MAX_RECONNECTS {
-                    error!(
-                        "Mempool monitor: {} reconnects exhausted — giving up: {}",
-                        MAX_RECONNECTS, e
-                    );
-                    return Err(e);
-                }
+                let delay = match reconnect.record_failure() {
+                    Some(delay) => delay,
+                    None => {
+                        error!(
+                            "Mempool monitor: {} reconnects exhausted — giving up: {}",
+                            MAX_MEMPOOL_RECONNECTS, e
+                        );
+                        return Err(e);
+                    }
+                };
                 warn!(
-                    "Mempool monitor error (reconnect {}/{}): {} — retrying in 5s...",
-                    reconnects, MAX_RECONNECTS, e
+                    "Mempool monitor error (reconnect {}/{}): {} — retrying in {:.1}s...",
+                    reconnect.failures(), MAX_MEMPOOL_RECONNECTS, e, delay.as_secs_f64()
                 );
-                tokio::time::sleep(Duration::from_secs(5)).await;
+                if reconnect.wait(delay).await == Backoffed::Cancelled {
+                    info!("Mempool monitor: shutdown requested — stopping");
+                    break;
+                }
             }
         }
     }
@@ -153,22 +213,25 @@ async fn run_observation_impl(
 /// Inner observation loop — one WS session.
 /// Returns Err on connection failure (caller retries).
 /// Phase 3: signal_tx sends MempoolSignal to main loop when in execute mode.
+#[allow(clippy::too_many_arguments)]
 async fn run_observation_inner(
     config: &BotConfig,
     data_dir: &str,
     router_hex: &[String],
     router_lookup: &HashMap<Address, String>,
+    tokens: &TokenRegistry,
     pool_state: &PoolStateManager,
     signal_tx: &Option<mpsc::Sender<MempoolSignal>>,
+    midblock_tx: &Option<mpsc::Sender<MidblockUpdate>>,
 ) -> Result<()> {
     // Create WS provider for pending tx subscription
-    let sub_provider = Provider::<Ws>::connect(&config.rpc_url)
+    let sub_provider = Provider::<Ws>::connect(config.subscription_url())
         .await
         .context("Mempool WS connect failed")?;
 
     // Create separate WS provider for RPC calls (get_block, get_block_number)
     // Avoids borrow conflicts with the subscription stream.
-    let rpc_provider = Provider::<Ws>::connect(&config.rpc_url)
+    let rpc_provider = Provider::<Ws>::connect(config.subscription_url())
         .await
         .context("Mempool RPC WS connect failed")?;
 
@@ -200,7 +263,13 @@ async fn run_observation_inner(
     let mut tracker = ConfirmationTracker::new();
 
     // Phase 2: Simulation tracker + CSV files
-    let mut sim_tracker = SimulationTracker::new();
+    let mut sim_tracker = SimulationTracker::new(Calibration::load(
+        format!("{}/calibration.json", data_dir),
+        config.mempool_calibration_k,
+        config.mempool_calibration_prior_bps,
+    ));
+    // Initialized ticks of the affected V3 pools, fetched on first simulation
+    let mut tick_data = TickDataProvider::new(Arc::new(rpc_provider.clone()));
     let sim_csv_path = format!("{}/simulated_opportunities_{}.csv", data_dir, date_str);
     let mut sim_csv_file = open_sim_csv(&sim_csv_path)?;
     let accuracy_csv_path = format!("{}/simulation_accuracy_{}.csv", data_dir, date_str);
@@ -208,7 +277,7 @@ async fn run_observation_inner(
     info!("Phase 2: simulation CSVs → {} , {}", sim_csv_path, accuracy_csv_path);
 
     // Block tracking for cross-reference
-    let mut last_checked_block = rpc_provider.get_block_number().await?.as_u64();
+    let mut last_checked_block = rpc_timeout("mempool get_block_number", rpc_provider.get_block_number()).await?.as_u64();
 
     // Stats
     let mut total_decoded = 0u64;
@@ -217,6 +286,8 @@ async fn run_observation_inner(
 
     // Periodic timer for cross-reference checks and stats
     let mut check_interval = interval(Duration::from_secs(6));
+    // Beats on every check tick; 5 missed ticks = stale (non-critical: never forces an exit)
+    let heartbeat = watchdog::heartbeat("mempool_monitor", Duration::from_secs(30), false);
     // Skip the first immediate tick
     check_interval.tick().await;
 
@@ -271,92 +342,113 @@ async fn run_observation_inner(
                                     format!("{:?}", tx.hash).chars().take(10).collect::<String>(),
                                     router_name,
                                     decoded.function_name,
-                                    decoded.token_in.map(|a| format!("{:?}", a).chars().skip(2).take(8).collect::<String>()).unwrap_or_else(|| "?".to_string()),
-                                    decoded.token_out.map(|a| format!("{:?}", a).chars().skip(2).take(8).collect::<String>()).unwrap_or_else(|| "?".to_string()),
-                                    decoded.amount_in.map(|a| a.to_string()).unwrap_or_else(|| "?".to_string()),
+                                    decoded.token_in.map(|a| tokens.label(&a)).unwrap_or_else(|| "?".to_string()),
+                                    decoded.token_out.map(|a| tokens.label(&a)).unwrap_or_else(|| "?".to_string()),
+                                    decoded
+                                        .amount_in
+                                        .map(|a| decoded.token_in.and_then(|t| tokens.format_amount(&t, a)).unwrap_or_else(|| a.to_string()))
+                                        .unwrap_or_else(|| "?".to_string()),
                                     decoded.fee_tier.map(|f| f.to_string()).unwrap_or_else(|| "dyn".to_string()),
                                     swap.gas_price_gwei,
                                 );
 
                                 // ── Phase 2: Simulate post-swap state ──
-                                if let Some(amount) = decoded.amount_in {
-                                    if let Some((dex, pair_sym, zero_for_one)) =
-                                        simulator::identify_affected_pool(&decoded, &router_name, pool_state)
-                                    {
-                                        let sim_result = if dex.is_v3() {
-                                            pool_state
-                                                .get_v3_pool(dex, &pair_sym)
-                                                .and_then(|pool| simulator::simulate_v3_swap(&pool, amount, zero_for_one))
-                                        } else {
-                                            pool_state
-                                                .get_pool(dex, &pair_sym)
-                                                .and_then(|pool| {
-                                                    let token_in = decoded.token_in.unwrap_or_default();
-                                                    simulator::simulate_v2_swap(&pool, amount, token_in)
-                                                })
-                                        };
-
-                                        if sim_result.is_none() {
-                                            debug!(
-                                                "SIM FAIL: {:?}/{} z4o={} amt={} — simulation returned None",
-                                                dex, pair_sym, zero_for_one, amount
-                                            );
+                                if let Some((dex, pair_sym, zero_for_one, swap_amount)) =
+                                    simulator::identify_affected_pool(&decoded, &router_name, pool_state)
+                                {
+                                    let ticks = match pool_state.get_v3_pool(dex, &pair_sym) {
+                                        Some(pool) => tick_data.ticks_for(&pool).await,
+                                        None => None,
+                                    };
+                                    let sim_result = simulator::simulate_swap(
+                                        pool_state, dex, &pair_sym, zero_for_one, swap_amount, ticks,
+                                    );
+
+                                    if sim_result.is_none() {
+                                        debug!(
+                                            "SIM FAIL: {:?}/{} z4o={} amt={:?} — simulation returned None",
+                                            dex, pair_sym, zero_for_one, swap_amount
+                                        );
+                                    }
+
+                                    if let Some((amount, ref simulated)) = sim_result {
+                                        debug!(
+                                            "SIM OK: {:?}/{} pre={:.6} post={:.6} impact={:.4}%",
+                                            dex, pair_sym, simulated.pre_swap_price, simulated.post_swap_price,
+                                            (simulated.post_swap_price - simulated.pre_swap_price).abs()
+                                                / simulated.pre_swap_price * 100.0
+                                        );
+                                        // Mid-block re-scan: the main loop runs detection on this state
+                                        if let Some(ref mtx) = midblock_tx {
+                                            let update = MidblockUpdate {
+                                                simulated: simulated.clone(),
+                                                tx_hash: tx.hash,
+                                                seen_at: Instant::now(),
+                                            };
+                                            if let Err(mpsc::error::TrySendError::Full(_)) = mtx.try_send(update) {
+                                                debug!("MIDBLOCK: channel full, dropping {:?}", tx.hash);
+                                            }
                                         }
+                                        let opportunities = simulator::check_post_swap_opportunities(
+                                            pool_state, simulated, config, tx.hash,
+                                            &decoded.function_name, amount, zero_for_one,
+                                            &swap.timestamp_utc,
+                                        );
 
-                                        if let Some(ref simulated) = sim_result {
-                                            debug!(
-                                                "SIM OK: {:?}/{} pre={:.6} post={:.6} impact={:.4}%",
-                                                dex, pair_sym, simulated.pre_swap_price, simulated.post_swap_price,
-                                                (simulated.post_swap_price - simulated.pre_swap_price).abs()
-                                                    / simulated.pre_swap_price * 100.0
-                                            );
-                                            let opportunities = simulator::check_post_swap_opportunities(
-                                                pool_state, simulated, config, tx.hash,
-                                                &decoded.function_name, amount, zero_for_one,
-                                                &swap.timestamp_utc,
+                                        for opp in &opportunities {
+                                            info!(
+                                                "SIM OPP: {:?} | {} | {:.3}% spread | ${:.2} est | impact={:.4}%",
+                                                tx.hash, opp.pair_symbol, opp.arb_spread_pct,
+                                                opp.arb_est_profit_usd, opp.price_impact_pct,
                                             );
+                                            if let Err(e) = write_sim_csv_row(&mut sim_csv_file, opp) {
+                                                warn!("Sim CSV write error: {}", e);
+                                            }
 
-                                            for opp in &opportunities {
-                                                info!(
-                                                    "SIM OPP: {:?} | {} | {:.3}% spread | ${:.2} est | impact={:.4}%",
-                                                    tx.hash, opp.pair_symbol, opp.arb_spread_pct,
-                                                    opp.arb_est_profit_usd, opp.price_impact_pct,
-                                                );
-                                                if let Err(e) = write_sim_csv_row(&mut sim_csv_file, opp) {
-                                                    warn!("Sim CSV write error: {}", e);
+                                            // Phase 3: Send execution signal if thresholds met
+                                            // Spread floor widened by the trigger pool's rolling simulation error
+                                            let min_spread_pct = sim_tracker.calibration.min_spread_pct(
+                                                opp.trigger_dex, &opp.pair_symbol, MEMPOOL_MIN_SPREAD_PCT,
+                                            );
+                                            if let Some(ref stx) = signal_tx {
+                                                if opp.arb_est_profit_usd >= config.mempool_min_profit_usd
+                                                    && opp.arb_spread_pct < min_spread_pct
+                                                {
+                                                    debug!(
+                                                        "MEMPOOL EXEC: {} {:.3}% below calibrated {:.3}% (sim error {:.2}bps)",
+                                                        opp.pair_symbol, opp.arb_spread_pct, min_spread_pct,
+                                                        sim_tracker.calibration.error_bps(opp.trigger_dex, &opp.pair_symbol)
+                                                    );
                                                 }
-
-                                                // Phase 3: Send execution signal if thresholds met
-                                                if let Some(ref stx) = signal_tx {
-                                                    if opp.arb_est_profit_usd >= config.mempool_min_profit_usd
-                                                        && opp.arb_spread_pct >= MEMPOOL_MIN_SPREAD_PCT
-                                                    {
-                                                        let signal = MempoolSignal {
-                                                            opportunity: opp.clone(),
-                                                            trigger_gas_price: tx.gas_price.unwrap_or_default(),
-                                                            trigger_max_priority_fee: tx.max_priority_fee_per_gas,
-                                                            seen_at: Instant::now(),
-                                                        };
-                                                        match stx.try_send(signal) {
-                                                            Ok(()) => info!(
-                                                                "MEMPOOL EXEC: signal sent | {} | ${:.2} | {:.3}%",
-                                                                opp.pair_symbol, opp.arb_est_profit_usd, opp.arb_spread_pct
-                                                            ),
-                                                            Err(mpsc::error::TrySendError::Full(_)) => warn!(
-                                                                "MEMPOOL EXEC: channel full, dropping signal"
-                                                            ),
-                                                            Err(e) => error!(
-                                                                "MEMPOOL EXEC: channel error: {}", e
-                                                            ),
-                                                        }
+                                                if opp.arb_est_profit_usd >= config.mempool_min_profit_usd
+                                                    && opp.arb_spread_pct >= min_spread_pct
+                                                {
+                                                    let signal = MempoolSignal {
+                                                        opportunity: opp.clone(),
+                                                        trigger_gas_price: tx.gas_price.unwrap_or_default(),
+                                                        trigger_max_priority_fee: tx.max_priority_fee_per_gas,
+                                                        trigger_raw_tx: Some(tx.rlp()),
+                                                        seen_at: Instant::now(),
+                                                    };
+                                                    match stx.try_send(signal) {
+                                                        Ok(()) => info!(
+                                                            "MEMPOOL EXEC: signal sent | {} | ${:.2} | {:.3}%",
+                                                            opp.pair_symbol, opp.arb_est_profit_usd, opp.arb_spread_pct
+                                                        ),
+                                                        Err(mpsc::error::TrySendError::Full(_)) => warn!(
+                                                            "MEMPOOL EXEC: channel full, dropping signal"
+                                                        ),
+                                                        Err(e) => error!(
+                                                            "MEMPOOL EXEC: channel error: {}", e
+                                                        ),
                                                     }
                                                 }
                                             }
-
-                                            // Track simulation for accuracy validation (with or without opportunity)
-                                            let best_opp = opportunities.into_iter().next();
-                                            sim_tracker.track(tx.hash, simulated.clone(), best_opp);
                                         }
+
+                                        // Track simulation for accuracy validation (with or without opportunity)
+                                        let best_opp = opportunities.into_iter().next();
+                                        sim_tracker.track(tx.hash, simulated.clone(), best_opp);
                                     }
                                 }
                             }
@@ -379,14 +471,15 @@ async fn run_observation_inner(
 
             _ = check_interval.tick() => {
                 tick_count += 1;
+                heartbeat.beat();
 
                 // Check for new blocks and cross-reference
-                match rpc_provider.get_block_number().await {
+                match rpc_timeout("mempool get_block_number", rpc_provider.get_block_number()).await {
                     Ok(current_block_num) => {
                         let current = current_block_num.as_u64();
                         // Process new blocks since last check
                         for block_num in (last_checked_block + 1)..=current {
-                            match rpc_provider.get_block(block_num).await {
+                            match rpc_timeout("mempool get_block", rpc_provider.get_block(block_num)).await {
                                 Ok(Some(block)) => {
                                     blocks_checked += 1;
                                     let tx_hashes: Vec<TxHash> = block.transactions.clone();
@@ -423,13 +516,12 @@ async fn run_observation_inner(
                                                     hash, simulated.pair_symbol, predicted, actual, error_pct, lead_time_ms
                                                 );
 
-                                                sim_tracker.record_accuracy(error_pct);
+                                                sim_tracker.record_accuracy(error_pct, &simulated);
 
                                                 let ts = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
                                                 if let Err(e) = write_accuracy_csv_row(
-                                                    &mut accuracy_csv_file, &ts, hash,
-                                                    &simulated.pair_symbol, &format!("{:?}", simulated.dex),
-                                                    predicted, actual, error_pct, *lead_time_ms,
+                                                    &mut accuracy_csv_file, &ts, hash, &simulated,
+                                                    actual, error_pct, *lead_time_ms,
                                                 ) {
                                                     warn!("Accuracy CSV write error: {}", e);
                                                 }
@@ -455,12 +547,17 @@ async fn run_observation_inner(
                 tracker.cleanup(Duration::from_secs(120));
                 sim_tracker.cleanup(Duration::from_secs(120));
 
+                // Persist calibration when validations came in
+                if let Err(e) = sim_tracker.calibration.save() {
+                    warn!("Mempool calibration write failed: {}", e);
+                }
+
                 // Report stats every ~10 minutes (100 ticks × 6s)
                 if tick_count % 100 == 0 {
                     info!(
                         "MEMPOOL STATS | decoded={} undecoded={} | confirmed={}/{} ({:.1}%) | \
                          median_lead={}ms mean_lead={}ms | tracking={} | blocks_checked={} | \
-                         sim: opps={} validated={} median_err={:.3}%",
+                         sim: opps={} validated={} median_err={:.3}% | cross-tick: {} median_err={:.3}%",
                         total_decoded,
                         total_undecoded,
                         tracker.total_confirmed,
@@ -473,7 +570,12 @@ async fn run_observation_inner(
                         sim_tracker.total_opportunities,
                         sim_tracker.total_validated,
                         sim_tracker.median_error_pct(),
+                        sim_tracker.cross_tick_error_samples.len(),
+                        sim_tracker.median_cross_tick_error_pct(),
                     );
+                    for line in sim_tracker.calibration.summary_lines() {
+                        info!("MEMPOOL CALIBRATION | {}", line);
+                    }
                 }
             }
         }
@@ -554,7 +656,8 @@ fn open_sim_csv(path: &str) -> Result<std::fs::File> {
             f,
             "timestamp_utc,tx_hash,trigger_dex,trigger_function,pair_symbol,zero_for_one,\
              amount_in,pre_swap_price,post_swap_price,price_impact_pct,\
-             arb_buy_dex,arb_sell_dex,arb_spread_pct,arb_est_profit_usd"
+             arb_buy_dex,arb_sell_dex,arb_spread_pct,arb_est_profit_usd,\
+             arb_buy_amount_out,arb_sell_amount_out,arb_residual_spread_pct"
         )?;
         Ok(f)
     } else {
@@ -575,7 +678,7 @@ fn open_accuracy_csv(path: &str) -> Result<std::fs::File> {
         let mut f = file;
         writeln!(
             f,
-            "timestamp_utc,tx_hash,pair_symbol,dex,predicted_price,actual_price,error_pct,lead_time_ms"
+            "timestamp_utc,tx_hash,pair_symbol,dex,predicted_price,actual_price,error_pct,lead_time_ms,predicted_impact_pct,ticks_crossed"
         )?;
         Ok(f)
     } else {
@@ -587,7 +690,7 @@ fn open_accuracy_csv(path: &str) -> Result<std::fs::File> {
 fn write_sim_csv_row(file: &mut std::fs::File, opp: &SimulatedOpportunity) -> Result<()> {
     writeln!(
         file,
-        "{},{:?},{:?},{},{},{},{},{:.10},{:.10},{:.6},{:?},{:?},{:.6},{:.4}",
+        "{},{:?},{:?},{},{},{},{},{:.10},{:.10},{:.6},{:?},{:?},{:.6},{:.4},{},{},{:.6}",
         opp.timestamp_utc,
         opp.tx_hash,
         opp.trigger_dex,
@@ -602,29 +705,84 @@ fn write_sim_csv_row(file: &mut std::fs::File, opp: &SimulatedOpportunity) -> Re
         opp.arb_sell_dex,
         opp.arb_spread_pct,
         opp.arb_est_profit_usd,
+        opp.arb_buy_amount_out,
+        opp.arb_sell_amount_out,
+        opp.arb_residual_spread_pct,
     )?;
     file.flush()?;
     Ok(())
 }
 
-/// Write an accuracy validation row
-#[allow(clippy::too_many_arguments)]
+/// Write an accuracy validation row. ticks_crossed is empty for within-tick
+/// approximations (no tick data), so error can be compared by swap size and method.
 fn write_accuracy_csv_row(
     file: &mut std::fs::File,
     timestamp: &str,
     tx_hash: &TxHash,
-    pair_symbol: &str,
-    dex: &str,
-    predicted: f64,
+    simulated: &SimulatedPoolState,
     actual: f64,
     error_pct: f64,
     lead_time_ms: u64,
 ) -> Result<()> {
+    let impact_pct = if simulated.pre_swap_price != 0.0 {
+        ((simulated.post_swap_price - simulated.pre_swap_price) / simulated.pre_swap_price * 100.0).abs()
+    } else {
+        0.0
+    };
     writeln!(
         file,
-        "{},{:?},{},{},{:.10},{:.10},{:.6},{}",
-        timestamp, tx_hash, pair_symbol, dex, predicted, actual, error_pct, lead_time_ms,
+        "{},{:?},{},{:?},{:.10},{:.10},{:.6},{},{:.6},{}",
+        timestamp, tx_hash, simulated.pair_symbol, simulated.dex, simulated.post_swap_price, actual,
+        error_pct, lead_time_ms, impact_pct,
+        simulated.ticks_crossed.map(|n| n.to_string()).unwrap_or_default(),
     )?;
     file.flush()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reconnect_gives_up_on_51st_failure() {
+        let mut sessions = 0u32;
+        let start = tokio::time::Instant::now();
+        let result = supervise_sessions(CancellationToken::new(), || {
+            sessions += 1;
+            let n = sessions;
+            async move { Err(anyhow!("ws dropped ({})", n)) }
+        })
+        .await;
+        assert_eq!(result.unwrap_err().to_string(), "ws dropped (51)");
+        assert_eq!(sessions, MAX_MEMPOOL_RECONNECTS + 1);
+        // 50 waits of 5s ± 20%
+        let waited = start.elapsed().as_secs_f64();
+        assert!((200.0..=300.0).contains(&waited), "waited {}s", waited);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_clean_session_exit_and_shutdown_stop_without_error() {
+        let mut sessions = 0u32;
+        let result = supervise_sessions(CancellationToken::new(), || {
+            sessions += 1;
+            let n = sessions;
+            async move { if n < 3 { Err(anyhow!("ws dropped")) } else { Ok(()) } }
+        })
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(sessions, 3);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let mut sessions = 0u32;
+        let result = supervise_sessions(cancel, || {
+            sessions += 1;
+            async { Err(anyhow!("ws dropped")) }
+        })
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(sessions, 1);
+    }
+}
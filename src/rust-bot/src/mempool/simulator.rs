@@ -8,16 +8,30 @@
 //! Author: AI-Generated
 //! Created: 2026-02-01
 //! Modified: 2026-02-01
+//! Modified: 2026-10-16 - Exact-output swaps: implied amount_in from amountOut, then the exact-input math
+//! Modified: 2026-10-16 - Cross-tick V3 swap loop (SwapMath step per tick range) when tick data is available
+//! Modified: 2026-10-16 - Exact-input SqrtPriceMath helpers moved to pool::calculator (shared with the detector)
+//! Modified: 2026-10-16 - Detection-only pools (Kyber Elastic) never paired as backrun counterpart legs
+//! Modified: 2026-10-16 - Test: post-swap check prices Algebra legs at the pool's live fee
+//! Modified: 2026-10-16 - Backrun profit from our own round trip through both post-swap pools (not spread × size)
+//! Modified: 2026-10-17 - Backrun size priced at the quote token's USD price (WETH quotes no longer sized at $1)
 //!
 //! Dependencies:
 //!     - ethers (U256 arithmetic, Address)
 //!
 //! Notes:
 //!     - V2: constant product (x * y = k) with 0.30% fee
-//!     - V3: within-tick sqrtPriceX96 math (Uniswap SqrtPriceMath formulas)
+//!     - V3: within-tick sqrtPriceX96 math (Uniswap SqrtPriceMath formulas);
+//!       with tick data (ticks.rs) the UniswapV3Pool.swap loop instead —
+//!       computeSwapStep per tick range, liquidityNet applied at each crossing
 //!     - Algebra (QuickSwap V3): same V3 math, fee from pool state (dynamic)
 //!     - Phase 2 scope: WETH/USDC and WMATIC/USDC pairs only
-//!     - Returns None on overflow or tick boundary crossing (conservative)
+//!     - Without tick data: None on overflow or >10 tick spacings crossed;
+//!       with tick data: None only past the fetched tick range
+//!     - Exact-output swaps: amount_in implied by amountOut (V2 getAmountIn,
+//!       V3 SqrtPriceMath output formulas); None past amountInMaximum
+//!     - Backrun profit: our trade at MAX_TRADE_SIZE_USD simulated leg by leg
+//!       (within-tick V3 / constant product V2), output less input
 //!
 //! References:
 //!     - Uniswap V3 SqrtPriceMath.sol: getNextSqrtPriceFromInput
@@ -26,16 +40,18 @@
 use ethers::types::{Address, TxHash, U256};
 use tracing::{debug, warn};
 
+use crate::pool::calculator::{
+    amount0_delta_rounding_down, amount1_delta_rounding_down, get_next_sqrt_price_from_amount0,
+    get_next_sqrt_price_from_amount1, Q96,
+};
 use crate::pool::PoolStateManager;
-use crate::types::{BotConfig, DexType, PoolState, V3PoolState};
+use crate::types::{raw_to_human, usd_to_raw, BotConfig, DexType, PoolState, V3PoolState};
 
-use super::types::{DecodedSwap, SimulatedOpportunity, SimulatedPoolState};
+use super::ticks::{sqrt_ratio_at_tick, tick_at_sqrt_ratio, TickLiquidity};
+use super::types::{DecodedSwap, SimulatedOpportunity, SimulatedPoolState, SwapAmount};
 
 // ── Constants ────────────────────────────────────────────────────────────────
 
-/// Q96 = 2^96, used in sqrtPriceX96 math
-const Q96: u128 = 1u128 << 96;
-
 /// V2 fee factor: 997/1000 = 0.30% fee
 const V2_FEE_NUMERATOR: u64 = 997;
 const V2_FEE_DENOMINATOR: u64 = 1000;
@@ -50,26 +66,20 @@ const USDC_E_POLYGON: &str = "2791bca1f2de4661ed88a30c99a7a9449aa84174";
 
 /// Identify which monitored pair a decoded pending swap affects.
 ///
-/// Returns (DexType, pair_symbol, zero_for_one) if the swap is:
+/// Returns (DexType, pair_symbol, zero_for_one, amount) if the swap is:
 /// - On a pair we monitor (WETH/USDC or WMATIC/USDC)
-/// - An exact-input function (not exact-output)
-/// - Has a decodable amount_in
+/// - Has a decodable amount (exact-input amount_in or exact-output amountOut)
 ///
 /// Returns None otherwise (skip simulation).
 pub fn identify_affected_pool(
     decoded: &DecodedSwap,
     router_name: &str,
     state_manager: &PoolStateManager,
-) -> Option<(DexType, String, bool)> {
-    // Must have token_in, token_out, and amount_in
+) -> Option<(DexType, String, bool, SwapAmount)> {
+    // Must have token_in, token_out, and an amount descriptor
     let token_in = decoded.token_in?;
     let token_out = decoded.token_out?;
-    let _amount_in = decoded.amount_in?;
-
-    // Skip exact-output functions
-    if is_exact_output(&decoded.function_name) {
-        return None;
-    }
+    let amount = decoded.amount?;
 
     // Normalize addresses to lowercase hex (strip 0x prefix for comparison)
     let in_hex = format!("{:x}", token_in);
@@ -119,15 +129,46 @@ pub fn identify_affected_pool(
         token_in == pool.pair.token0
     };
 
-    Some((dex, pair_symbol, zero_for_one))
+    Some((dex, pair_symbol, zero_for_one, amount))
 }
 
-/// Check if a function name represents an exact-output swap (skip for simulation)
-fn is_exact_output(function_name: &str) -> bool {
-    function_name.contains("exactOutput")
-        || function_name.contains("ForExactTokens")
-        || function_name.contains("swapExactETHForTokens")
-        || function_name == "multicall(opaque)"
+/// Simulate a swap on the affected pool (see identify_affected_pool).
+/// Returns the amount_in the swap spends (implied for exact-output) and the
+/// post-swap state. V3 pools with `ticks` covering the current tick run the
+/// cross-tick loop; otherwise the within-tick approximation.
+pub fn simulate_swap(
+    state_manager: &PoolStateManager,
+    dex: DexType,
+    pair_symbol: &str,
+    zero_for_one: bool,
+    amount: SwapAmount,
+    ticks: Option<&TickLiquidity>,
+) -> Option<(U256, SimulatedPoolState)> {
+    if dex.is_v3() {
+        let pool = state_manager.get_v3_pool(dex, pair_symbol)?;
+        if let Some(ticks) = ticks.filter(|t| t.covers(pool.tick)) {
+            return simulate_v3_swap_ticks(&pool, ticks, amount, zero_for_one);
+        }
+        match amount {
+            SwapAmount::ExactIn(amount_in) => {
+                simulate_v3_swap(&pool, amount_in, zero_for_one).map(|sim| (amount_in, sim))
+            }
+            SwapAmount::ExactOut { amount_out, max_in } => {
+                simulate_v3_swap_exact_out(&pool, amount_out, max_in, zero_for_one)
+            }
+        }
+    } else {
+        let pool = state_manager.get_pool(dex, pair_symbol)?;
+        let token_in = if zero_for_one { pool.pair.token0 } else { pool.pair.token1 };
+        match amount {
+            SwapAmount::ExactIn(amount_in) => {
+                simulate_v2_swap(&pool, amount_in, token_in).map(|sim| (amount_in, sim))
+            }
+            SwapAmount::ExactOut { amount_out, max_in } => {
+                simulate_v2_swap_exact_out(&pool, amount_out, max_in, token_in)
+            }
+        }
+    }
 }
 
 /// Identify pair symbol from token addresses (lowercase hex, no 0x prefix).
@@ -247,9 +288,51 @@ pub fn simulate_v2_swap(
         post_reserve0: Some(new_reserve0),
         post_reserve1: Some(new_reserve1),
         post_tick: None,
+        post_liquidity: None,
+        ticks_crossed: None,
     })
 }
 
+/// Simulate a V2 exact-output swap (swapTokensForExactTokens).
+///
+/// Implied input (UniswapV2Library.getAmountIn):
+///   amountIn = reserveIn * amountOut * 1000 / ((reserveOut - amountOut) * 997) + 1
+/// then the exact-input simulation. Returns None when amountOut drains the
+/// pool or the implied input exceeds `max_in` (the swap would revert).
+pub fn simulate_v2_swap_exact_out(
+    pool: &PoolState,
+    amount_out: U256,
+    max_in: Option<U256>,
+    token_in: Address,
+) -> Option<(U256, SimulatedPoolState)> {
+    if amount_out.is_zero() {
+        return None;
+    }
+
+    let (reserve_in, reserve_out) = if token_in == pool.pair.token0 {
+        (pool.reserve0, pool.reserve1)
+    } else {
+        (pool.reserve1, pool.reserve0)
+    };
+
+    if reserve_in.is_zero() || amount_out >= reserve_out {
+        return None;
+    }
+
+    let numerator = reserve_in
+        .checked_mul(amount_out)?
+        .checked_mul(U256::from(V2_FEE_DENOMINATOR))?;
+    let denominator = (reserve_out - amount_out).checked_mul(U256::from(V2_FEE_NUMERATOR))?;
+    let amount_in = (numerator / denominator).checked_add(U256::one())?;
+
+    if max_in.is_some_and(|max| amount_in > max) {
+        debug!("V2 sim: exact-output needs {} in, above amountInMax {:?}", amount_in, max_in);
+        return None;
+    }
+
+    simulate_v2_swap(pool, amount_in, token_in).map(|sim| (amount_in, sim))
+}
+
 /// V2 price adjusted for decimals: (reserve1 / reserve0) * 10^(dec0 - dec1)
 fn v2_price_adjusted(reserve0: U256, reserve1: U256, dec0: u8, dec1: u8) -> f64 {
     if reserve0.is_zero() {
@@ -343,92 +426,325 @@ pub fn simulate_v3_swap(
         post_reserve0: None,
         post_reserve1: None,
         post_tick: Some(new_tick),
+        post_liquidity: Some(pool.liquidity),
+        ticks_crossed: None,
     })
 }
 
-// ── V3 Math Helpers (from Uniswap SqrtPriceMath.sol) ─────────────────────────
-
-/// getNextSqrtPriceFromAmount0RoundingUp
-///
-/// When adding token0 (zeroForOne), sqrtPrice decreases.
-///
-/// Primary formula (precise):
-///   result = ceil(numerator1 * sqrtPX96 / (numerator1 + amount * sqrtPX96))
-///   where numerator1 = liquidity << 96
-///
-/// Fallback (avoids overflow in numerator1 * sqrtPX96):
-///   result = ceil(numerator1 / (numerator1 / sqrtPX96 + amount))
+/// Simulate a V3 exact-output swap within the current tick range.
 ///
-/// Mirrors Uniswap SqrtPriceMath.sol with FullMath overflow fallback.
-fn get_next_sqrt_price_from_amount0(
+/// The requested output moves sqrtPrice to where the pool has paid it out
+/// (SqrtPriceMath getNextSqrtPriceFromOutput); the input owed to get there
+/// (SqrtPriceMath getAmount0Delta/getAmount1Delta, rounded up) grossed up by
+/// the fee is the implied amount_in, which then runs through simulate_v3_swap.
+/// Returns None when the output exceeds the in-range liquidity, the implied
+/// input exceeds `max_in` (the swap would revert), or arithmetic overflows.
+pub fn simulate_v3_swap_exact_out(
+    pool: &V3PoolState,
+    amount_out: U256,
+    max_in: Option<U256>,
+    zero_for_one: bool,
+) -> Option<(U256, SimulatedPoolState)> {
+    if amount_out.is_zero() || pool.liquidity == 0 || pool.sqrt_price_x96.is_zero() {
+        return None;
+    }
+
+    // zeroForOne pays out token1, oneForZero pays out token0
+    let target = if zero_for_one {
+        get_next_sqrt_price_from_amount1_output(pool.sqrt_price_x96, pool.liquidity, amount_out)?
+    } else {
+        get_next_sqrt_price_from_amount0_output(pool.sqrt_price_x96, pool.liquidity, amount_out)?
+    };
+
+    let net_in = if zero_for_one {
+        amount0_delta_rounding_up(target, pool.sqrt_price_x96, pool.liquidity)?
+    } else {
+        amount1_delta_rounding_up(pool.sqrt_price_x96, target, pool.liquidity)?
+    };
+
+    // Gross up by the fee: amountIn = ceil(net * 1e6 / (1e6 - fee))
+    let fee_complement = U256::from(1_000_000u32.checked_sub(pool.fee)?);
+    if fee_complement.is_zero() {
+        return None;
+    }
+    let amount_in = net_in
+        .checked_mul(U256::from(1_000_000u32))?
+        .checked_add(fee_complement - U256::one())?
+        / fee_complement;
+
+    if max_in.is_some_and(|max| amount_in > max) {
+        debug!("V3 sim: exact-output needs {} in, above amountInMaximum {:?}", amount_in, max_in);
+        return None;
+    }
+
+    simulate_v3_swap(pool, amount_in, zero_for_one).map(|sim| (amount_in, sim))
+}
+
+// ── V3 Simulation (Cross-Tick Swap Loop) ─────────────────────────────────────
+
+/// End state of a swap run through the tick ranges
+#[derive(Debug, Clone, PartialEq)]
+struct V3SwapOutcome {
+    amount_in: U256,
+    amount_out: U256,
     sqrt_price_x96: U256,
+    tick: i32,
     liquidity: u128,
-    amount: U256,
-) -> Option<U256> {
-    if amount.is_zero() {
-        return Some(sqrt_price_x96);
+    /// Initialized ticks crossed (liquidityNet applied)
+    ticks_crossed: u32,
+}
+
+/// One SwapMath.computeSwapStep: move from `current` toward `target` with
+/// `liquidity` until the target or the remaining amount (exact-in: input
+/// incl. fee; exact-out: output) runs out.
+/// Returns (sqrt price reached, amount in, amount out, fee amount).
+fn compute_swap_step(
+    current: U256,
+    target: U256,
+    liquidity: u128,
+    remaining: U256,
+    exact_in: bool,
+    fee: u32,
+) -> Option<(U256, U256, U256, U256)> {
+    let zero_for_one = current >= target;
+    let fee_complement = U256::from(1_000_000u32.checked_sub(fee)?);
+    if fee_complement.is_zero() {
+        return None;
     }
-    if sqrt_price_x96.is_zero() {
+    let amount_in_between = |from: U256, to: U256| {
+        if zero_for_one {
+            amount0_delta_rounding_up(to, from, liquidity)
+        } else {
+            amount1_delta_rounding_up(from, to, liquidity)
+        }
+    };
+    let amount_out_between = |from: U256, to: U256| {
+        if zero_for_one {
+            amount1_delta_rounding_down(to, from, liquidity)
+        } else {
+            amount0_delta_rounding_down(from, to, liquidity)
+        }
+    };
+
+    let next = if exact_in {
+        let remaining_less_fee = remaining.checked_mul(fee_complement)? / U256::from(1_000_000u32);
+        if remaining_less_fee >= amount_in_between(current, target)? {
+            target
+        } else if zero_for_one {
+            get_next_sqrt_price_from_amount0(current, liquidity, remaining_less_fee)?
+        } else {
+            get_next_sqrt_price_from_amount1(current, liquidity, remaining_less_fee)?
+        }
+    } else if remaining >= amount_out_between(current, target)? {
+        target
+    } else if zero_for_one {
+        get_next_sqrt_price_from_amount1_output(current, liquidity, remaining)?
+    } else {
+        get_next_sqrt_price_from_amount0_output(current, liquidity, remaining)?
+    };
+
+    let reached_target = next == target;
+    let amount_in = amount_in_between(current, next)?;
+    // A partial exact-output step pays out the rest (the next price is rounded
+    // in the pool's favour; rounding dust must not leave a wei outstanding)
+    let amount_out = if !exact_in && (!reached_target || amount_out_between(current, next)? > remaining) {
+        remaining
+    } else {
+        amount_out_between(current, next)?
+    };
+    let fee_amount = if exact_in && !reached_target {
+        // Partial step: whatever input is left over pays the fee
+        remaining.checked_sub(amount_in)?
+    } else {
+        let num = amount_in.checked_mul(U256::from(fee))?;
+        (num + fee_complement - U256::one()) / fee_complement
+    };
+    Some((next, amount_in, amount_out, fee_amount))
+}
+
+/// UniswapV3Pool.swap loop over the fetched tick data: one computeSwapStep
+/// per tick range, liquidityNet applied at each initialized tick crossed.
+/// A swap that ends before the next initialized tick takes a single step —
+/// the within-tick math. None when the swap runs past the fetched range
+/// (liquidity beyond it unknown), liquidity underflows, or arithmetic overflows.
+fn v3_swap_loop(
+    pool: &V3PoolState,
+    ticks: &TickLiquidity,
+    amount: SwapAmount,
+    zero_for_one: bool,
+) -> Option<V3SwapOutcome> {
+    let (exact_in, mut remaining) = match amount {
+        SwapAmount::ExactIn(amount_in) => (true, amount_in),
+        SwapAmount::ExactOut { amount_out, .. } => (false, amount_out),
+    };
+    if remaining.is_zero() || pool.sqrt_price_x96.is_zero() {
         return None;
     }
 
-    let liquidity_u256 = U256::from(liquidity);
-    // numerator1 = liquidity << 96 (safe: u128 << 96 fits in U256)
-    let numerator1 = liquidity_u256 << 96;
-
-    // Try precise formula first: ceil(numerator1 * sqrtPX96 / denominator)
-    if let Some(product) = amount.checked_mul(sqrt_price_x96) {
-        if let Some(denominator) = numerator1.checked_add(product) {
-            if !denominator.is_zero() {
-                if let Some(full_num) = numerator1.checked_mul(sqrt_price_x96) {
-                    let result = (full_num + denominator - U256::one()) / denominator;
-                    if !result.is_zero() {
-                        return Some(result);
-                    }
-                }
+    let mut state = V3SwapOutcome {
+        amount_in: U256::zero(),
+        amount_out: U256::zero(),
+        sqrt_price_x96: pool.sqrt_price_x96,
+        tick: pool.tick,
+        liquidity: pool.liquidity,
+        ticks_crossed: 0,
+    };
+
+    while !remaining.is_zero() {
+        // Past the fetched range: the next liquidityNet is unknown
+        let exhausted = if zero_for_one { state.tick < ticks.min_tick } else { state.tick >= ticks.max_tick };
+        if exhausted {
+            debug!(
+                "V3 sim: {:?}/{} swap runs past the fetched ticks [{}, {}]",
+                pool.dex, pool.pair.symbol, ticks.min_tick, ticks.max_tick
+            );
+            return None;
+        }
+
+        let (tick_next, initialized) = ticks.next_tick(state.tick, zero_for_one);
+        let target = sqrt_ratio_at_tick(tick_next)?;
+        let start = state.sqrt_price_x96;
+        let (next, step_in, step_out, fee_amount) =
+            compute_swap_step(start, target, state.liquidity, remaining, exact_in, pool.fee)?;
+
+        state.sqrt_price_x96 = next;
+        state.amount_in = state.amount_in.checked_add(step_in)?.checked_add(fee_amount)?;
+        state.amount_out = state.amount_out.checked_add(step_out)?;
+        remaining = if exact_in {
+            remaining.checked_sub(step_in + fee_amount)?
+        } else {
+            remaining.checked_sub(step_out)?
+        };
+
+        if next == target {
+            if initialized {
+                let net = ticks.liquidity_net(tick_next);
+                let delta = if zero_for_one { net.checked_neg()? } else { net };
+                state.liquidity = state.liquidity.checked_add_signed(delta)?;
+                state.ticks_crossed += 1;
             }
+            state.tick = if zero_for_one { tick_next - 1 } else { tick_next };
+        } else if next != start {
+            state.tick = tick_at_sqrt_ratio(next)?;
         }
     }
 
-    // Fallback: ceil(numerator1 / (numerator1 / sqrtPX96 + amount))
-    // Avoids the large numerator1 * sqrtPX96 intermediate product.
-    let quotient = numerator1 / sqrt_price_x96;
-    let denominator = quotient.checked_add(amount)?;
-    if denominator.is_zero() {
-        return None;
+    Some(state)
+}
+
+/// Simulate a V3 swap across initialized ticks (exact input or output).
+/// Returns the amount_in the swap spends and the post-swap state, which
+/// carries the final tick, in-range liquidity and ticks crossed. None past
+/// the fetched tick range or when an exact-output swap needs more than
+/// amountInMaximum (it would revert).
+pub fn simulate_v3_swap_ticks(
+    pool: &V3PoolState,
+    ticks: &TickLiquidity,
+    amount: SwapAmount,
+    zero_for_one: bool,
+) -> Option<(U256, SimulatedPoolState)> {
+    let swap = v3_swap_loop(pool, ticks, amount, zero_for_one)?;
+    if let SwapAmount::ExactOut { max_in: Some(max), .. } = amount {
+        if swap.amount_in > max {
+            debug!("V3 sim: exact-output needs {} in, above amountInMaximum {}", swap.amount_in, max);
+            return None;
+        }
     }
-    let result = (numerator1 + denominator - U256::one()) / denominator;
-    if result.is_zero() {
-        return None;
+    if swap.ticks_crossed > 0 {
+        debug!(
+            "V3 sim: {:?}/{} crossed {} initialized tick(s) (tick {} → {}), liquidity {} → {}",
+            pool.dex, pool.pair.symbol, swap.ticks_crossed, pool.tick, swap.tick, pool.liquidity, swap.liquidity
+        );
     }
-    Some(result)
+
+    let post_price = price_from_sqrt_price_x96(swap.sqrt_price_x96, pool.token0_decimals, pool.token1_decimals);
+    Some((swap.amount_in, SimulatedPoolState {
+        dex: pool.dex,
+        pair_symbol: pool.pair.symbol.clone(),
+        is_v3: true,
+        pre_swap_price: pool.price(),
+        post_swap_price: post_price,
+        post_sqrt_price_x96: Some(swap.sqrt_price_x96),
+        post_reserve0: None,
+        post_reserve1: None,
+        post_tick: Some(swap.tick),
+        post_liquidity: Some(swap.liquidity),
+        ticks_crossed: Some(swap.ticks_crossed),
+    }))
 }
 
-/// getNextSqrtPriceFromAmount1RoundingDown
+// ── V3 Math Helpers (from Uniswap SqrtPriceMath.sol) ─────────────────────────
+
+/// getNextSqrtPriceFromAmount1RoundingDown (add = false)
 ///
-/// When adding token1 (oneForZero), sqrtPrice increases.
+/// When removing token1 (zeroForOne output), sqrtPrice decreases:
+///   result = sqrtPriceX96 - ceil((amount << 96) / liquidity)
+/// None if the in-range liquidity can't pay the amount out.
+fn get_next_sqrt_price_from_amount1_output(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount: U256,
+) -> Option<U256> {
+    let liquidity_u256 = U256::from(liquidity);
+    let shifted = amount.checked_mul(U256::from(Q96))?;
+    let quotient = shifted.checked_add(liquidity_u256 - U256::one())? / liquidity_u256;
+    if quotient >= sqrt_price_x96 {
+        return None;
+    }
+    Some(sqrt_price_x96 - quotient)
+}
+
+/// getNextSqrtPriceFromAmount0RoundingUp (add = false)
 ///
-/// Formula:
-///   quotient = (amount << 96) / liquidity
-///   result = sqrtPriceX96 + quotient
-fn get_next_sqrt_price_from_amount1(
+/// When removing token0 (oneForZero output), sqrtPrice increases:
+///   result = ceil(numerator1 * sqrtPX96 / (numerator1 - amount * sqrtPX96))
+/// Fallback form (overflow): ceil(numerator1 / (numerator1 / sqrtPX96 - amount)).
+/// None if the in-range liquidity can't pay the amount out.
+fn get_next_sqrt_price_from_amount0_output(
     sqrt_price_x96: U256,
     liquidity: u128,
     amount: U256,
 ) -> Option<U256> {
-    if amount.is_zero() {
-        return Some(sqrt_price_x96);
+    let numerator1 = U256::from(liquidity) << 96;
+
+    if let (Some(product), Some(full_num)) = (
+        amount.checked_mul(sqrt_price_x96),
+        numerator1.checked_mul(sqrt_price_x96),
+    ) {
+        if product >= numerator1 {
+            return None;
+        }
+        let denominator = numerator1 - product;
+        return Some((full_num + denominator - U256::one()) / denominator);
     }
 
-    let liquidity_u256 = U256::from(liquidity);
-    // quotient = (amount << 96) / liquidity
-    let shifted = amount.checked_mul(U256::from(Q96))?;
-    let quotient = shifted.checked_div(liquidity_u256)?;
+    let quotient = numerator1 / sqrt_price_x96;
+    if amount >= quotient {
+        return None;
+    }
+    let denominator = quotient - amount;
+    Some((numerator1 + denominator - U256::one()) / denominator)
+}
 
-    // result = sqrtPriceX96 + quotient
-    let result = sqrt_price_x96.checked_add(quotient)?;
+/// getAmount0Delta rounding up, for sqrt prices lower < upper:
+///   amount0 = liquidity * 2^96 * (1/lower - 1/upper)
+/// Computed as ceil(numerator1 / lower) - floor(numerator1 / upper), which
+/// never needs the 512-bit intermediate of FullMath.
+fn amount0_delta_rounding_up(lower: U256, upper: U256, liquidity: u128) -> Option<U256> {
+    if lower.is_zero() || lower > upper {
+        return None;
+    }
+    let numerator1 = U256::from(liquidity) << 96;
+    let at_lower = (numerator1 + lower - U256::one()) / lower;
+    at_lower.checked_sub(numerator1 / upper)
+}
 
-    Some(result)
+/// getAmount1Delta rounding up, for sqrt prices lower < upper:
+///   amount1 = ceil(liquidity * (upper - lower) / 2^96)
+fn amount1_delta_rounding_up(lower: U256, upper: U256, liquidity: u128) -> Option<U256> {
+    let diff = upper.checked_sub(lower)?;
+    let product = U256::from(liquidity).checked_mul(diff)?;
+    Some((product + U256::from(Q96) - U256::one()) >> 96)
 }
 
 /// Compute tick from sqrtPriceX96 using f64 approximation.
@@ -483,18 +799,122 @@ fn tick_spacing_for_fee(fee: u32) -> i32 {
 
 // ── Cross-DEX Opportunity Check ──────────────────────────────────────────────
 
+/// Pool state a backrun leg trades against (post-swap for the trigger pool)
+#[derive(Debug, Clone)]
+enum LegState {
+    V2(PoolState),
+    V3(V3PoolState),
+}
+
+impl LegState {
+    fn tokens(&self) -> (Address, Address) {
+        match self {
+            LegState::V2(pool) => (pool.pair.token0, pool.pair.token1),
+            LegState::V3(pool) => (pool.pair.token0, pool.pair.token1),
+        }
+    }
+
+    fn decimals(&self) -> (u8, u8) {
+        match self {
+            LegState::V2(pool) => (pool.token0_decimals, pool.token1_decimals),
+            LegState::V3(pool) => (pool.token0_decimals, pool.token1_decimals),
+        }
+    }
+
+    /// Our swap of `amount_in` of `token_in` through this pool:
+    /// (amount out, post-swap price). None where the simulation gives up (V3: more
+    /// than 10 tick spacings crossed, overflow).
+    fn swap(&self, amount_in: U256, token_in: Address) -> Option<(U256, f64)> {
+        match self {
+            LegState::V2(pool) => {
+                let sim = simulate_v2_swap(pool, amount_in, token_in)?;
+                let amount_out = if token_in == pool.pair.token0 {
+                    pool.reserve1.checked_sub(sim.post_reserve1?)?
+                } else {
+                    pool.reserve0.checked_sub(sim.post_reserve0?)?
+                };
+                Some((amount_out, sim.post_swap_price))
+            }
+            LegState::V3(pool) => {
+                let zero_for_one = token_in == pool.pair.token0;
+                let sim = simulate_v3_swap(pool, amount_in, zero_for_one)?;
+                let post = sim.post_sqrt_price_x96?;
+                let amount_out = if zero_for_one {
+                    amount1_delta_rounding_down(post, pool.sqrt_price_x96, pool.liquidity)?
+                } else {
+                    amount0_delta_rounding_down(pool.sqrt_price_x96, post, pool.liquidity)?
+                };
+                Some((amount_out, sim.post_swap_price))
+            }
+        }
+    }
+}
+
 /// Unified pool view for cross-DEX comparison (same as detector pattern)
 struct UnifiedPool {
     dex: DexType,
     price: f64,
     fee_percent: f64,
+    state: LegState,
+}
+
+/// Our backrun through both pools: quote → base on the buy pool, that base →
+/// quote on the sell pool
+#[derive(Debug, Clone, PartialEq)]
+struct RoundTrip {
+    /// Base received on the buy leg (raw)
+    buy_out: U256,
+    /// Quote received on the sell leg (raw)
+    sell_out: U256,
+    /// Pool prices (token1 per token0) after our legs
+    buy_post_price: f64,
+    sell_post_price: f64,
+}
+
+fn simulate_round_trip(buy: &LegState, sell: &LegState, quote: Address, base: Address, amount_in: U256) -> Option<RoundTrip> {
+    let (buy_out, buy_post_price) = buy.swap(amount_in, quote)?;
+    let (sell_out, sell_post_price) = sell.swap(buy_out, base)?;
+    Some(RoundTrip { buy_out, sell_out, buy_post_price, sell_post_price })
+}
+
+/// Mid-market spread of buying at `buy_price` and selling at `sell_price`
+/// (prices token1 per token0; positive = the buy pool is cheaper)
+fn midmarket_spread(buy_price: f64, sell_price: f64, quote_is_token0: bool) -> f64 {
+    if quote_is_token0 {
+        (buy_price - sell_price) / sell_price
+    } else {
+        (sell_price - buy_price) / buy_price
+    }
+}
+
+/// The trigger pool as the pending swap leaves it
+fn post_swap_leg(state_manager: &PoolStateManager, simulated: &SimulatedPoolState) -> Option<LegState> {
+    if simulated.is_v3 {
+        let pool = state_manager.get_v3_pool(simulated.dex, &simulated.pair_symbol)?;
+        Some(LegState::V3(V3PoolState {
+            sqrt_price_x96: simulated.post_sqrt_price_x96?,
+            tick: simulated.post_tick.unwrap_or(pool.tick),
+            liquidity: simulated.post_liquidity.unwrap_or(pool.liquidity),
+            ..pool
+        }))
+    } else {
+        let pool = state_manager.get_pool(simulated.dex, &simulated.pair_symbol)?;
+        Some(LegState::V2(PoolState {
+            reserve0: simulated.post_reserve0?,
+            reserve1: simulated.post_reserve1?,
+            ..pool
+        }))
+    }
 }
 
 /// Check for cross-DEX arbitrage opportunities using the simulated post-swap state.
 ///
 /// The pending swap moves ONE pool's price. We compare the simulated post-swap
-/// price against CURRENT prices of all OTHER pools for the same pair.
-/// If a spread exceeds round-trip fees + gas, it's a simulated opportunity.
+/// price against CURRENT prices of all OTHER pools for the same pair. A pair
+/// whose spread clears round-trip fees is then priced by simulating our own
+/// backrun at MAX_TRADE_SIZE_USD through both pools (buy leg, then the sell
+/// leg with its output): profit is the round trip's output less its input,
+/// not spread × size, so our own price impact is paid for.
 ///
 /// Mirrors the spread calculation in OpportunityDetector::check_pair_unified().
 pub fn check_post_swap_opportunities(
@@ -512,19 +932,20 @@ pub fn check_post_swap_opportunities(
     // Build unified pool list: the simulated pool + all other pools for this pair
     let mut pools: Vec<UnifiedPool> = Vec::new();
 
-    // Add the simulated pool (with post-swap price)
-    let sim_fee = if simulated.is_v3 {
-        state_manager
-            .get_v3_pool(simulated.dex, &simulated.pair_symbol)
-            .map(|p| p.fee as f64 / 10000.0)
-            .unwrap_or(0.30)
-    } else {
-        0.30 // V2 always 0.30%
+    // Add the simulated pool (with post-swap state)
+    let Some(sim_state) = post_swap_leg(state_manager, simulated) else {
+        debug!("Post-swap check: {:?}/{} not in pool state", simulated.dex, simulated.pair_symbol);
+        return opportunities;
+    };
+    let sim_fee = match &sim_state {
+        LegState::V3(pool) => pool.fee as f64 / 10000.0,
+        LegState::V2(_) => 0.30, // V2 always 0.30%
     };
     pools.push(UnifiedPool {
         dex: simulated.dex,
         price: simulated.post_swap_price,
         fee_percent: sim_fee,
+        state: sim_state,
     });
 
     // Add all OTHER V3 pools for this pair
@@ -532,6 +953,9 @@ pub fn check_post_swap_opportunities(
         if pool.dex == simulated.dex {
             continue; // Skip the pool we simulated
         }
+        if pool.dex.is_detection_only() {
+            continue; // Kyber Elastic: no ArbExecutor leg to backrun with
+        }
         if pool.liquidity == 0 {
             continue;
         }
@@ -539,6 +963,7 @@ pub fn check_post_swap_opportunities(
             dex: pool.dex,
             price: pool.price(),
             fee_percent: pool.fee as f64 / 10000.0,
+            state: LegState::V3(pool),
         });
     }
 
@@ -559,6 +984,7 @@ pub fn check_post_swap_opportunities(
                 pool.token1_decimals,
             ),
             fee_percent: 0.30, // V2 always 0.30%
+            state: LegState::V2(pool),
         });
     }
 
@@ -570,19 +996,16 @@ pub fn check_post_swap_opportunities(
     // quote_is_token0: if any recognized USDC variant is token0
     // On Polygon: USDC variants (0x2791..., 0x3c49...) < WETH (0x7ceb...) → true for WETH/USDC
     //             WMATIC (0x0d50...) < USDC variants → false for WMATIC/USDC
-    let quote_is_token0 = if simulated.is_v3 {
-        state_manager
-            .get_v3_pools_for_pair(&simulated.pair_symbol)
-            .first()
-            .map(|p| config.is_quote_token(&p.pair.token0))
-            .unwrap_or(true)
-    } else {
-        state_manager
-            .get_pools_for_pair(&simulated.pair_symbol)
-            .first()
-            .map(|p| config.is_quote_token(&p.pair.token0))
-            .unwrap_or(true)
-    };
+    let (token0, token1) = pools[0].state.tokens();
+    let quote_is_token0 = config.is_quote_token(&token0);
+    let (quote, base) = if quote_is_token0 { (token0, token1) } else { (token1, token0) };
+
+    // Our backrun size: MAX_TRADE_SIZE_USD in quote raw units at the quote
+    // price (as dispatch sizes it)
+    let (dec0, dec1) = pools[0].state.decimals();
+    let quote_decimals = if quote_is_token0 { dec0 } else { dec1 };
+    let quote_price_usd = config.quote_price_usd(&quote);
+    let trade_size = usd_to_raw(config.max_trade_size_usd / quote_price_usd, quote_decimals);
 
     // Price impact
     let price_impact = if simulated.pre_swap_price != 0.0 {
@@ -613,26 +1036,36 @@ pub fn check_post_swap_opportunities(
         };
 
         // Midmarket spread
-        let midmarket_spread = if quote_is_token0 {
-            (buy_pool.price - sell_pool.price) / sell_pool.price
-        } else {
-            (sell_pool.price - buy_pool.price) / buy_pool.price
-        };
+        let midmarket = midmarket_spread(buy_pool.price, sell_pool.price, quote_is_token0);
 
         // Round-trip fee (both legs)
         let round_trip_fee = (buy_pool.fee_percent + sell_pool.fee_percent) / 100.0;
 
         // Executable spread after fees
-        let executable_spread = midmarket_spread - round_trip_fee;
+        let executable_spread = midmarket - round_trip_fee;
 
         if executable_spread <= 0.0 {
             continue;
         }
 
-        // Profit estimate
-        let gross = executable_spread * config.max_trade_size_usd;
-        let slippage_estimate = gross * 0.01;
-        let net_profit = gross - config.estimated_gas_cost_usd - slippage_estimate;
+        // Profit from our own round trip through both pools
+        let Some(trip) = simulate_round_trip(&buy_pool.state, &sell_pool.state, quote, base, trade_size) else {
+            debug!(
+                "{}: backrun {:?}→{:?} at {} not simulable (too large for the within-tick model)",
+                simulated.pair_symbol, buy_pool.dex, sell_pool.dex, trade_size
+            );
+            continue;
+        };
+        if trip.sell_out <= trade_size {
+            debug!(
+                "{}: backrun {:?}→{:?} spread {:.3}% eaten by our own price impact",
+                simulated.pair_symbol, buy_pool.dex, sell_pool.dex, executable_spread * 100.0
+            );
+            continue;
+        }
+        let gross = raw_to_human(trip.sell_out - trade_size, quote_decimals) * quote_price_usd;
+        let net_profit = gross - config.estimated_gas_cost_usd;
+        let residual_spread = midmarket_spread(trip.buy_post_price, trip.sell_post_price, quote_is_token0);
 
         if net_profit > 0.0 {
             opportunities.push(SimulatedOpportunity {
@@ -650,6 +1083,9 @@ pub fn check_post_swap_opportunities(
                 arb_sell_dex: sell_pool.dex,
                 arb_spread_pct: executable_spread * 100.0,
                 arb_est_profit_usd: net_profit,
+                arb_buy_amount_out: trip.buy_out,
+                arb_sell_amount_out: trip.sell_out,
+                arb_residual_spread_pct: residual_spread * 100.0,
             });
         }
     }
@@ -868,4 +1304,273 @@ mod tests {
         assert_eq!(tick_spacing_for_fee(10000), 200);
         assert_eq!(tick_spacing_for_fee(0), 1); // Algebra
     }
+
+    fn v3_pool(zero_for_one_tokens: (Address, Address)) -> V3PoolState {
+        // tick 0, 1e18 liquidity, 0.05% fee
+        V3PoolState {
+            address: Address::zero(),
+            dex: DexType::UniswapV3_005,
+            pair: TradingPair::new(zero_for_one_tokens.0, zero_for_one_tokens.1, "WETH/USDC".to_string()),
+            sqrt_price_x96: q96_u256(),
+            tick: 0,
+            fee: 500,
+            liquidity: 1_000_000_000_000_000_000,
+            token0_decimals: 18,
+            token1_decimals: 18,
+            last_updated: 100,
+        }
+    }
+
+    #[test]
+    fn test_v2_exact_output_implied_input() {
+        let pool = PoolState {
+            address: Address::zero(),
+            dex: DexType::QuickSwapV2,
+            pair: TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            reserve0: U256::from(1000u64) * U256::exp10(18),
+            reserve1: U256::from(2_400_000u64) * U256::exp10(6),
+            last_updated: 100,
+            token0_decimals: 18,
+            token1_decimals: 6,
+        };
+        let token_in = pool.pair.token0;
+        let amount_out = U256::from(2_000u64) * U256::exp10(6); // 2000 USDC
+
+        let (amount_in, sim) = simulate_v2_swap_exact_out(&pool, amount_out, None, token_in).unwrap();
+        // getAmountIn: reserveIn * out * 1000 / ((reserveOut - out) * 997) + 1
+        let expected = pool.reserve0 * amount_out * U256::from(1000u64)
+            / ((pool.reserve1 - amount_out) * U256::from(997u64))
+            + U256::one();
+        assert_eq!(amount_in, expected);
+        // Same post-state as the exact-input simulation, which pays out at least amountOut
+        assert_eq!(sim.post_reserve0, Some(pool.reserve0 + amount_in));
+        assert!(pool.reserve1 - sim.post_reserve1.unwrap() >= amount_out);
+
+        // amountInMax exhausted (swap would revert), or amountOut drains the pool
+        assert!(simulate_v2_swap_exact_out(&pool, amount_out, Some(amount_in - U256::one()), token_in).is_none());
+        assert!(simulate_v2_swap_exact_out(&pool, amount_out, Some(amount_in), token_in).is_some());
+        assert!(simulate_v2_swap_exact_out(&pool, pool.reserve1, None, token_in).is_none());
+    }
+
+    #[test]
+    fn test_v3_exact_output_pays_requested_amount() {
+        let pool = v3_pool((Address::from_low_u64_be(1), Address::from_low_u64_be(2)));
+        let amount_out = U256::exp10(15); // 0.1% of in-range liquidity
+        let liquidity = U256::from(pool.liquidity);
+
+        // zeroForOne: token1 out, sqrtPrice falls
+        let (amount_in, sim) = simulate_v3_swap_exact_out(&pool, amount_out, None, true).unwrap();
+        let post = sim.post_sqrt_price_x96.unwrap();
+        let paid_out = (liquidity * (pool.sqrt_price_x96 - post)) >> 96;
+        assert!(paid_out >= amount_out && paid_out - amount_out <= U256::from(2u64), "paid {}", paid_out);
+        // ~amountOut at price 1 plus the 0.05% fee
+        assert!(amount_in > amount_out && amount_in < amount_out * U256::from(1_002u64) / U256::from(1_000u64));
+
+        // oneForZero: token0 out, sqrtPrice rises
+        let (amount_in_1, sim) = simulate_v3_swap_exact_out(&pool, amount_out, None, false).unwrap();
+        assert!(sim.post_sqrt_price_x96.unwrap() > pool.sqrt_price_x96);
+        assert!(amount_in_1 > amount_out);
+
+        // amountInMaximum exhaustion → None; more than the range holds → None
+        assert!(simulate_v3_swap_exact_out(&pool, amount_out, Some(amount_in - U256::one()), true).is_none());
+        assert!(simulate_v3_swap_exact_out(&pool, amount_out, Some(amount_in), true).is_some());
+        assert!(simulate_v3_swap_exact_out(&pool, liquidity * U256::from(2u64), None, true).is_none());
+    }
+
+    /// sqrtPriceX96 → f64 sqrt price
+    fn sqrt_f(sqrt_price_x96: U256) -> f64 {
+        sqrt_price_x96.as_u128() as f64 / 2f64.powi(96)
+    }
+
+    /// v3_pool at tick 0 with 1e18 in range, half of it in [-100, 200),
+    /// the other half wider in [-1000, 1000)
+    fn two_range_ticks() -> TickLiquidity {
+        let mut ticks = TickLiquidity::new(10, -1, 0);
+        let half: i128 = 500_000_000_000_000_000;
+        ticks.net.insert(-1_000, half);
+        ticks.net.insert(-100, half);
+        ticks.net.insert(200, -half);
+        ticks.net.insert(1_000, -half);
+        ticks
+    }
+
+    #[test]
+    fn test_v3_cross_tick_applies_liquidity_net() {
+        let pool = v3_pool((Address::from_low_u64_be(1), Address::from_low_u64_be(2)));
+        let ticks = two_range_ticks();
+        let amount_in = U256::from(20_000_000_000_000_000u64); // 2e16 token0, ~4× the range to tick -100
+
+        let (spent, sim) = simulate_v3_swap_ticks(&pool, &ticks, SwapAmount::ExactIn(amount_in), true).unwrap();
+        assert_eq!(spent, amount_in);
+        assert_eq!(sim.ticks_crossed, Some(1));
+        assert_eq!(sim.post_liquidity, Some(500_000_000_000_000_000));
+        let post = sim.post_sqrt_price_x96.unwrap();
+        assert_eq!(sim.post_tick, tick_at_sqrt_ratio(post));
+
+        // Closed form: full range to tick -100 at L, the rest (after fee) at L/2
+        let (l, fee_c) = (1e18, 0.9995);
+        let sb = 1.0001f64.powf(-50.0);
+        let in_first = l * (1.0 / sb - 1.0);
+        let expected = 1.0 / (1.0 / sb + (2e16 * fee_c - in_first) / (l / 2.0));
+        assert!((sqrt_f(post) - expected).abs() / expected < 1e-9, "{} vs {}", sqrt_f(post), expected);
+
+        // The single-tick approximation keeps L for the whole swap: it stops
+        // ~300 ticks short, and simulate_v3_swap gives up on a move this size
+        let after_fee = amount_in * U256::from(999_500u64) / U256::from(1_000_000u64);
+        let approx = get_next_sqrt_price_from_amount0(pool.sqrt_price_x96, pool.liquidity, after_fee).unwrap();
+        let approx_tick = tick_at_sqrt_ratio(approx).unwrap();
+        assert!(approx_tick > -420 && sim.post_tick.unwrap() < -650, "approx {} vs {:?}", approx_tick, sim.post_tick);
+        assert!(simulate_v3_swap(&pool, amount_in, true).is_none());
+    }
+
+    #[test]
+    fn test_v3_cross_tick_fast_path_and_exact_output() {
+        let pool = v3_pool((Address::from_low_u64_be(1), Address::from_low_u64_be(2)));
+        let ticks = two_range_ticks();
+
+        // Ends before tick 200: one step, identical to the within-tick math
+        let small = U256::exp10(15);
+        let (_, sim) = simulate_v3_swap_ticks(&pool, &ticks, SwapAmount::ExactIn(small), false).unwrap();
+        let within = simulate_v3_swap(&pool, small, false).unwrap();
+        assert_eq!(sim.post_sqrt_price_x96, within.post_sqrt_price_x96);
+        assert_eq!((sim.ticks_crossed, sim.post_liquidity), (Some(0), Some(pool.liquidity)));
+
+        // Exact output across tick 200: implied input buys back (about) the same output
+        let amount_in = U256::from(30_000_000_000_000_000u64);
+        let forward = v3_swap_loop(&pool, &ticks, SwapAmount::ExactIn(amount_in), false).unwrap();
+        assert_eq!(forward.ticks_crossed, 1);
+        let exact_out = SwapAmount::ExactOut { amount_out: forward.amount_out, max_in: None };
+        let (implied_in, sim) = simulate_v3_swap_ticks(&pool, &ticks, exact_out, false).unwrap();
+        let diff = if implied_in > amount_in { implied_in - amount_in } else { amount_in - implied_in };
+        assert!(diff <= U256::from(10u64), "implied {} vs {}", implied_in, amount_in);
+        assert_eq!(sim.ticks_crossed, Some(1));
+        let capped = SwapAmount::ExactOut { amount_out: forward.amount_out, max_in: Some(implied_in - U256::one()) };
+        assert!(simulate_v3_swap_ticks(&pool, &ticks, capped, false).is_none());
+
+        // Past the fetched range: unknown liquidity → None
+        let drain = SwapAmount::ExactIn(U256::exp10(24));
+        assert!(simulate_v3_swap_ticks(&pool, &ticks, drain, true).is_none());
+    }
+
+    #[test]
+    fn test_identify_affected_pool_accepts_exact_output() {
+        let usdc = Address::from_slice(&ethers::utils::hex::decode(USDC_E_POLYGON).unwrap());
+        let weth = Address::from_slice(&ethers::utils::hex::decode(WETH_POLYGON).unwrap());
+        let state_manager = PoolStateManager::new();
+        state_manager.update_v3_pool(v3_pool((usdc, weth)));
+
+        let amount = SwapAmount::ExactOut { amount_out: U256::exp10(15), max_in: None };
+        let decoded = DecodedSwap {
+            function_name: "exactOutputSingle".to_string(),
+            token_in: Some(weth),
+            token_out: Some(usdc),
+            amount_in: None,
+            amount_out_min: Some(U256::exp10(15)),
+            fee_tier: Some(500),
+            amount: Some(amount),
+        };
+        let (dex, pair, zero_for_one, swap_amount) =
+            identify_affected_pool(&decoded, "UniswapV3", &state_manager).unwrap();
+        assert_eq!((dex, pair.as_str(), zero_for_one, swap_amount), (DexType::UniswapV3_005, "WETH/USDC", false, amount));
+        assert!(simulate_swap(&state_manager, dex, &pair, zero_for_one, swap_amount, None).is_some());
+
+        // No amount descriptor (e.g. swapExactETHForTokens) → skip
+        let no_amount = DecodedSwap { amount: None, ..decoded };
+        assert!(identify_affected_pool(&no_amount, "UniswapV3", &state_manager).is_none());
+    }
+
+    #[test]
+    fn test_post_swap_check_uses_live_algebra_fee() {
+        let config = crate::arbitrage::detector::tests::create_test_config();
+        let tokens = (config.quote_token_address, Address::from_low_u64_be(0xe7));
+        // Deep enough that our $500 round trip stays within a tick
+        let deep = V3PoolState { liquidity: 10u128.pow(25), ..v3_pool(tokens) };
+        let state_manager = PoolStateManager::new();
+        state_manager.update_v3_pool(deep.clone());
+        // QuickSwap V3 at a ~0.5% lower price, 0.01% dynamic fee
+        let algebra = Address::from_low_u64_be(0xa1);
+        state_manager.update_v3_pool(V3PoolState {
+            address: algebra,
+            dex: DexType::QuickswapV3,
+            sqrt_price_x96: sqrt_ratio_at_tick(-50).unwrap(),
+            tick: -50,
+            fee: 100,
+            ..deep
+        });
+        let simulated = SimulatedPoolState {
+            dex: DexType::UniswapV3_005,
+            pair_symbol: "WETH/USDC".to_string(),
+            is_v3: true,
+            pre_swap_price: 1.0,
+            post_swap_price: 1.0,
+            post_sqrt_price_x96: Some(q96_u256()),
+            post_reserve0: None,
+            post_reserve1: None,
+            post_tick: Some(0),
+            post_liquidity: None,
+            ticks_crossed: None,
+        };
+        let check = || {
+            check_post_swap_opportunities(&state_manager, &simulated, &config, TxHash::zero(), "exactInputSingle", U256::zero(), true, "")
+        };
+
+        let opps = check();
+        assert_eq!(opps.len(), 1);
+        assert!(opps[0].arb_sell_dex == DexType::QuickswapV3 || opps[0].arb_buy_dex == DexType::QuickswapV3);
+
+        // The pool's fee rose to 0.45% (globalState re-read): no longer executable
+        state_manager.set_v3_fee(&algebra, 4500);
+        assert!(check().is_empty());
+    }
+
+    /// Pending swap left Uniswap V3 (deep) quoting 1.0 WETH per USDC; the
+    /// QuickSwap V2 counterpart quotes 0.99 with `usdc_reserve` depth
+    fn backrun_check(usdc_reserve: u128) -> Vec<SimulatedOpportunity> {
+        let config = crate::arbitrage::detector::tests::create_test_config();
+        let (usdc, weth) = (config.quote_token_address, Address::from_low_u64_be(0xe7));
+        let trigger = V3PoolState { liquidity: 10u128.pow(25), ..v3_pool((usdc, weth)) };
+        let state_manager = PoolStateManager::new();
+        state_manager.update_v3_pool(trigger.clone());
+        state_manager.update_pool(PoolState {
+            address: Address::from_low_u64_be(0xb2),
+            dex: DexType::Quickswap,
+            pair: TradingPair::new(usdc, weth, "WETH/USDC".to_string()),
+            reserve0: U256::from(usdc_reserve) * U256::exp10(18),
+            reserve1: U256::from(usdc_reserve * 99 / 100) * U256::exp10(18),
+            last_updated: 100,
+            token0_decimals: 18,
+            token1_decimals: 18,
+        });
+        let pending = U256::exp10(15);
+        let simulated = simulate_v3_swap(&trigger, pending, false).unwrap();
+        check_post_swap_opportunities(&state_manager, &simulated, &config, TxHash::zero(), "exactInputSingle", pending, false, "")
+    }
+
+    #[test]
+    fn test_backrun_profit_pays_our_own_price_impact() {
+        // Spread model: executable spread × $500, less 1% slippage and $0.05 gas
+        let spread_model = |opp: &SimulatedOpportunity| opp.arb_spread_pct / 100.0 * 500.0 * 0.99 - 0.05;
+        let human = |raw: U256| raw_to_human(raw, 18);
+
+        // 100M USDC deep: our $500 barely moves it, simulation ≈ spread model
+        let deep = backrun_check(100_000_000).remove(0);
+        assert_eq!((deep.arb_buy_dex, deep.arb_sell_dex), (DexType::UniswapV3_005, DexType::Quickswap));
+        assert!((deep.arb_spread_pct - 0.6601).abs() < 0.01, "spread {}", deep.arb_spread_pct);
+        assert!((deep.arb_est_profit_usd - spread_model(&deep)).abs() < 0.1, "{} vs {}", deep.arb_est_profit_usd, spread_model(&deep));
+        // ~499.7 WETH bought (0.05% fee, ~0.01% impact); profit is what the sell leg returns over 500 USDC
+        assert!((human(deep.arb_buy_amount_out) - 499.72).abs() < 0.01, "{}", human(deep.arb_buy_amount_out));
+        assert!((human(deep.arb_sell_amount_out) - 500.0 - deep.arb_est_profit_usd - 0.05).abs() < 1e-6);
+
+        // 100k USDC: same spread, but selling ~500 WETH moves the V2 pool ~0.5%
+        let thin = backrun_check(100_000).remove(0);
+        assert!((thin.arb_spread_pct - deep.arb_spread_pct).abs() < 1e-9);
+        assert!(thin.arb_est_profit_usd > 0.0);
+        assert!(thin.arb_est_profit_usd < spread_model(&thin) / 3.0, "{} vs {}", thin.arb_est_profit_usd, spread_model(&thin));
+        // Our trade closes the gap: nothing left past round-trip fees
+        assert!(thin.arb_residual_spread_pct < 0.35, "residual {}", thin.arb_residual_spread_pct);
+        assert!(deep.arb_residual_spread_pct > thin.arb_residual_spread_pct);
+
+        // 20k USDC: our own impact eats the whole spread, no signal
+        assert!(backrun_check(20_000).is_empty());
+    }
 }
@@ -0,0 +1,320 @@
+//! A4 Mempool Monitor — Initialized Tick Data (cross-tick V3 simulation)
+//!
+//! Purpose:
+//!     The within-tick simulation assumes in-range liquidity is constant for
+//!     the whole swap, which breaks exactly for the large pending swaps that
+//!     create the biggest arbs. `TickDataProvider` fetches the initialized
+//!     ticks (liquidityNet) around a pool's current tick so the simulator can
+//!     run the SwapMath loop tick range by tick range.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Fetching:
+//!     - tickBitmap(int16) for TICK_WORD_RADIUS words either side of the
+//!       current tick's word (256 tick spacings per word), then ticks(int24)
+//!       for every set bit — liquidityNet is the only field used
+//!     - Uniswap V3 / SushiSwap V3 pools only: Algebra (QuickSwap V3) has a
+//!       different tick table layout and stays on the within-tick path
+//!     - Cached per pool; refetched lazily when older than TICK_CACHE_TTL or
+//!       when the current tick has left the fetched range
+//!
+//! References:
+//!     - Uniswap V3 TickBitmap.sol, TickMath.sol (getSqrtRatioAtTick)
+
+use ethers::prelude::*;
+use futures::future::join_all;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::types::{DexType, V3PoolState};
+use crate::watchdog::rpc_timeout;
+
+abigen!(
+    UniswapV3PoolTicks,
+    r#"[
+        function tickBitmap(int16 wordPosition) external view returns (uint256)
+        function ticks(int24 tick) external view returns (uint128 liquidityGross, int128 liquidityNet, uint256 feeGrowthOutside0X128, uint256 feeGrowthOutside1X128, int56 tickCumulativeOutside, uint160 secondsPerLiquidityOutsideX128, uint32 secondsOutside, bool initialized)
+    ]"#
+);
+
+/// TickMath.MIN_TICK / MAX_TICK
+pub const MIN_TICK: i32 = -887_272;
+pub const MAX_TICK: i32 = 887_272;
+
+/// Bitmap words fetched either side of the current tick's word
+pub const TICK_WORD_RADIUS: i32 = 2;
+
+/// Refetch a pool's ticks after this long (liquidityNet only changes on mint/burn)
+pub const TICK_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Initialized ticks of one pool over a contiguous fetched range
+#[derive(Debug, Clone)]
+pub struct TickLiquidity {
+    pub tick_spacing: i32,
+    /// tick → liquidityNet, initialized ticks only
+    pub net: BTreeMap<i32, i128>,
+    /// Fetched range (inclusive); liquidity beyond it is unknown
+    pub min_tick: i32,
+    pub max_tick: i32,
+}
+
+impl TickLiquidity {
+    /// Range covering bitmap words `word_lo..=word_hi` (no initialized ticks yet)
+    pub fn new(tick_spacing: i32, word_lo: i32, word_hi: i32) -> Self {
+        Self {
+            tick_spacing,
+            net: BTreeMap::new(),
+            min_tick: (word_lo * 256 * tick_spacing).max(MIN_TICK),
+            max_tick: ((word_hi * 256 + 255) * tick_spacing).min(MAX_TICK),
+        }
+    }
+
+    pub fn covers(&self, tick: i32) -> bool {
+        tick >= self.min_tick && tick <= self.max_tick
+    }
+
+    /// Next tick the swap stops at from `tick`, and whether it is initialized.
+    /// zeroForOne searches at or below `tick`, oneForZero strictly above
+    /// (TickBitmap.nextInitializedTickWithinOneWord semantics). With no
+    /// initialized tick left in the fetched range, the range edge (uninitialized).
+    pub fn next_tick(&self, tick: i32, zero_for_one: bool) -> (i32, bool) {
+        if zero_for_one {
+            match self.net.range(self.min_tick..=tick).next_back() {
+                Some((&t, _)) => (t, true),
+                None => (self.min_tick, false),
+            }
+        } else {
+            match self.net.range(tick.saturating_add(1)..=self.max_tick).next() {
+                Some((&t, _)) => (t, true),
+                None => (self.max_tick, false),
+            }
+        }
+    }
+
+    pub fn liquidity_net(&self, tick: i32) -> i128 {
+        self.net.get(&tick).copied().unwrap_or(0)
+    }
+}
+
+/// Compressed-tick bitmap word → initialized ticks (TickBitmap.position inverse)
+pub fn bitmap_ticks(word: i32, bitmap: U256, tick_spacing: i32) -> Vec<i32> {
+    (0..256)
+        .filter(|&bit| bitmap.bit(bit))
+        .map(|bit| (word * 256 + bit as i32) * tick_spacing)
+        .collect()
+}
+
+/// Bitmap word holding `tick` (compressed = floor(tick / spacing))
+pub fn word_of(tick: i32, tick_spacing: i32) -> i32 {
+    tick.div_euclid(tick_spacing) >> 8
+}
+
+/// TickMath.getSqrtRatioAtTick: exact sqrt(1.0001^tick) * 2^96
+pub fn sqrt_ratio_at_tick(tick: i32) -> Option<U256> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return None;
+    }
+    const FACTORS: [(u32, &str); 19] = [
+        (0x2, "fff97272373d413259a46990580e213a"),
+        (0x4, "fff2e50f5f656932ef12357cf3c7fdcc"),
+        (0x8, "ffe5caca7e10e4e61c3624eaa0941cd0"),
+        (0x10, "ffcb9843d60f6159c9db58835c926644"),
+        (0x20, "ff973b41fa98c081472e6896dfb254c0"),
+        (0x40, "ff2ea16466c96a3843ec78b326b52861"),
+        (0x80, "fe5dee046a99a2a811c461f1969c3053"),
+        (0x100, "fcbe86c7900a88aedcffc83b479aa3a4"),
+        (0x200, "f987a7253ac413176f2b074cf7815e54"),
+        (0x400, "f3392b0822b70005940c7a398e4b70f3"),
+        (0x800, "e7159475a2c29b7443b29c7fa6e889d9"),
+        (0x1000, "d097f3bdfd2022b8845ad8f792aa5825"),
+        (0x2000, "a9f746462d870fdf8a65dc1f90e061e5"),
+        (0x4000, "70d869a156d2a1b890bb3df62baf32f7"),
+        (0x8000, "31be135f97d08fd981231505542fcfa6"),
+        (0x10000, "9aa508b5b7a84e1c677de54f3e99bc9"),
+        (0x20000, "5d6af8dedb81196699c329225ee604"),
+        (0x40000, "2216e584f5fa1ea926041bedfe98"),
+        (0x80000, "48a170391f7dc42444e8fa2"),
+    ];
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        U256::from_str_radix("fffcb933bd6fad37aa2d162d1a594001", 16).ok()?
+    } else {
+        U256::one() << 128
+    };
+    for (bit, factor) in FACTORS {
+        if abs_tick & bit != 0 {
+            ratio = (ratio * U256::from_str_radix(factor, 16).ok()?) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+    // Q128.128 → Q64.96, rounded up
+    let round_up = !(ratio & U256::from(u32::MAX)).is_zero();
+    Some((ratio >> 32) + U256::from(round_up as u8))
+}
+
+/// TickMath.getTickAtSqrtRatio: greatest tick whose sqrt ratio <= `sqrt_price_x96`.
+/// f64 estimate, then corrected against the exact sqrt_ratio_at_tick.
+pub fn tick_at_sqrt_ratio(sqrt_price_x96: U256) -> Option<i32> {
+    let min = sqrt_ratio_at_tick(MIN_TICK)?;
+    let max = sqrt_ratio_at_tick(MAX_TICK)?;
+    if sqrt_price_x96 < min || sqrt_price_x96 >= max {
+        return None;
+    }
+    // sqrtPrice has up to 160 bits: keep the top 128 for the f64 estimate
+    let shift = sqrt_price_x96.bits().saturating_sub(128);
+    let ratio = (sqrt_price_x96 >> shift).as_u128() as f64 * 2f64.powi(shift as i32 - 96);
+    let mut tick = ((2.0 * ratio.ln() / 1.0001f64.ln()).floor() as i32).clamp(MIN_TICK, MAX_TICK);
+    while tick > MIN_TICK && sqrt_ratio_at_tick(tick)? > sqrt_price_x96 {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && sqrt_ratio_at_tick(tick + 1)? <= sqrt_price_x96 {
+        tick += 1;
+    }
+    Some(tick)
+}
+
+/// Tick spacing of a Uniswap/Sushi V3 fee tier (None = no bitmap support here;
+/// Kyber Elastic keeps initialized ticks in a linked list, not a tickBitmap)
+pub fn tick_spacing(dex: DexType, fee: u32) -> Option<i32> {
+    if !dex.is_v3() || dex == DexType::QuickswapV3 || dex.is_kyber_elastic() {
+        return None;
+    }
+    match fee {
+        100 => Some(1),
+        500 => Some(10),
+        3000 => Some(60),
+        10000 => Some(200),
+        _ => None,
+    }
+}
+
+/// Lazily fetched, cached initialized ticks for whitelisted V3 pools
+pub struct TickDataProvider<M: Middleware> {
+    client: Arc<M>,
+    cache: HashMap<Address, (TickLiquidity, Instant)>,
+    word_radius: i32,
+    ttl: Duration,
+}
+
+impl<M: Middleware + 'static> TickDataProvider<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self { client, cache: HashMap::new(), word_radius: TICK_WORD_RADIUS, ttl: TICK_CACHE_TTL }
+    }
+
+    /// Ticks around the pool's current tick — cached, refetched when stale or
+    /// when the pool has moved out of the fetched range. None = unsupported
+    /// pool (Algebra / unknown fee) or the fetch failed.
+    pub async fn ticks_for(&mut self, pool: &V3PoolState) -> Option<&TickLiquidity> {
+        let spacing = tick_spacing(pool.dex, pool.fee)?;
+        let fresh = self.cache.get(&pool.address).is_some_and(|(ticks, fetched_at)| {
+            fetched_at.elapsed() < self.ttl && ticks.covers(pool.tick)
+        });
+        if !fresh {
+            match self.fetch(pool, spacing).await {
+                Ok(ticks) => {
+                    debug!(
+                        "Tick data: {:?}/{} {} initialized tick(s) in [{}, {}]",
+                        pool.dex, pool.pair.symbol, ticks.net.len(), ticks.min_tick, ticks.max_tick
+                    );
+                    self.cache.insert(pool.address, (ticks, Instant::now()));
+                }
+                Err(e) => {
+                    warn!("Tick data fetch failed for {:?}/{}: {}", pool.dex, pool.pair.symbol, e);
+                    self.cache.remove(&pool.address);
+                    return None;
+                }
+            }
+        }
+        self.cache.get(&pool.address).map(|(ticks, _)| ticks)
+    }
+
+    async fn fetch(&self, pool: &V3PoolState, spacing: i32) -> anyhow::Result<TickLiquidity> {
+        let contract = UniswapV3PoolTicks::new(pool.address, self.client.clone());
+        let center = word_of(pool.tick, spacing);
+        let words: Vec<i32> = (center - self.word_radius..=center + self.word_radius).collect();
+
+        let bitmap_calls = words.iter().map(|&w| contract.tick_bitmap(w as i16));
+        let bitmaps = rpc_timeout("tickBitmap batch", async {
+            join_all(bitmap_calls.map(|c| async move { c.call().await })).await.into_iter().collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
+
+        let initialized: Vec<i32> = words
+            .iter()
+            .zip(bitmaps)
+            .flat_map(|(&w, bitmap)| bitmap_ticks(w, bitmap, spacing))
+            .collect();
+        let tick_calls = initialized.iter().map(|&t| contract.ticks(t));
+        let infos = rpc_timeout("ticks batch", async {
+            join_all(tick_calls.map(|c| async move { c.call().await })).await.into_iter().collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
+
+        let mut ticks = TickLiquidity::new(spacing, words[0], words[words.len() - 1]);
+        for (tick, (_gross, liquidity_net, ..)) in initialized.into_iter().zip(infos) {
+            ticks.net.insert(tick, liquidity_net);
+        }
+        Ok(ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_ratio_at_tick_matches_tick_math() {
+        assert_eq!(sqrt_ratio_at_tick(0), Some(U256::one() << 96));
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK), Some(U256::from(4_295_128_739u64)));
+        assert_eq!(
+            sqrt_ratio_at_tick(MAX_TICK),
+            U256::from_dec_str("1461446703485210103287273052203988822378723970342").ok()
+        );
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK + 1), None);
+
+        // Every factor bit against the float formula
+        for tick in [1, -1, 7, -60, 1_000, -4_095, 50_000, -200_003, 524_287, -887_271] {
+            let exact = sqrt_ratio_at_tick(tick).unwrap();
+            let shift = exact.bits().saturating_sub(100);
+            let got = (exact >> shift).as_u128() as f64 * 2f64.powi(shift as i32 - 96);
+            let expected = 1.0001f64.powf(tick as f64 / 2.0);
+            assert!(((got - expected) / expected).abs() < 1e-9, "tick {}: {} vs {}", tick, got, expected);
+        }
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_ratio_is_exact_at_boundaries() {
+        for tick in [0, 1, -1, 887, -23_028, 200_000, MIN_TICK, MAX_TICK - 1] {
+            let at = sqrt_ratio_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_ratio(at), Some(tick));
+            assert_eq!(tick_at_sqrt_ratio(at - U256::one()), if tick == MIN_TICK { None } else { Some(tick - 1) });
+        }
+    }
+
+    #[test]
+    fn test_bitmap_words_and_next_tick() {
+        // Negative ticks round toward -inf when compressed
+        assert_eq!(word_of(-1, 10), -1);
+        assert_eq!(word_of(2_559, 10), 0);
+        assert_eq!(word_of(2_560, 10), 1);
+        let bitmap = U256::one() | (U256::one() << 255);
+        assert_eq!(bitmap_ticks(-1, bitmap, 10), vec![-2_560, -10]);
+
+        let mut ticks = TickLiquidity::new(10, -1, 0);
+        assert_eq!((ticks.min_tick, ticks.max_tick), (-2_560, 2_550));
+        ticks.net.insert(-100, 5);
+        ticks.net.insert(200, -5);
+        assert_eq!(ticks.next_tick(0, true), (-100, true));
+        assert_eq!(ticks.next_tick(-100, true), (-100, true), "at an initialized tick, zeroForOne crosses it");
+        assert_eq!(ticks.next_tick(-101, true), (-2_560, false));
+        assert_eq!(ticks.next_tick(0, false), (200, true));
+        assert_eq!(ticks.next_tick(200, false), (2_550, false));
+        assert_eq!(tick_spacing(DexType::QuickswapV3, 500), None);
+        assert_eq!(tick_spacing(DexType::SushiV3_030, 3000), Some(60));
+    }
+}
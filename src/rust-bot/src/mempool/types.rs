@@ -8,13 +8,20 @@
 //! Created: 2026-02-01
 //! Modified: 2026-02-01 — Phase 2: simulation types
 //! Modified: 2026-02-01 — Phase 3: MempoolSignal for execution pipeline
+//! Modified: 2026-10-16 — SwapAmount: exact-input / exact-output amount descriptor on DecodedSwap
+//! Modified: 2026-10-16 — SimulatedPoolState post_liquidity / ticks_crossed; cross-tick accuracy samples
+//! Modified: 2026-10-16 — SimulationTracker feeds validated errors into the per-(DEX, pair) Calibration
+//! Modified: 2026-10-16 — SimulatedOpportunity: our backrun's per-leg amount_out and residual spread
+//! Modified: 2026-10-16 — MempoolSignal carries the trigger tx's signed bytes (bundle submission)
 //!
 //! Dependencies:
 //!     - ethers (Address, TxHash, U256)
 //!     - chrono (timestamps)
 
+use super::bundle::TriggerTx;
+use super::calibration::Calibration;
 use crate::types::DexType;
-use ethers::types::{Address, TxHash, U256};
+use ethers::types::{Address, Bytes, TxHash, U256};
 use std::collections::HashMap;
 use std::time::Instant;
 
@@ -25,7 +32,7 @@ pub enum MempoolMode {
     Off,
     /// Observation only — log pending swaps, track confirmation rates
     Observe,
-    /// Speculative execution — submit backrun txs (Phase 3, not yet implemented)
+    /// Speculative execution — submit backrun txs via mempool::dispatch (requires ARB_EXECUTOR_ADDRESS)
     Execute,
 }
 
@@ -59,6 +66,23 @@ pub struct DecodedSwap {
     pub amount_out_min: Option<U256>,
     /// V3 fee tier in bps (None for V2 or Algebra)
     pub fee_tier: Option<u32>,
+    /// Which amount the caller fixed, for simulation (None = not in calldata).
+    /// For exact-output swaps amount_in holds amountInMaximum and
+    /// amount_out_min the exact amountOut, as logged to the CSV.
+    pub amount: Option<SwapAmount>,
+}
+
+/// Amount side a swap fixes: exact-input spends amount_in in full,
+/// exact-output receives exactly amount_out for at most max_in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapAmount {
+    ExactIn(U256),
+    ExactOut {
+        amount_out: U256,
+        /// amountInMaximum of the simulated hop (None = cap is on another
+        /// hop's token, i.e. multi-hop paths)
+        max_in: Option<U256>,
+    },
 }
 
 /// Full pending swap observation — decoded calldata + transaction metadata.
@@ -186,6 +210,10 @@ pub struct SimulatedPoolState {
     pub post_reserve1: Option<U256>,
     /// Post-swap tick (V3 only, derived from sqrtPriceX96)
     pub post_tick: Option<i32>,
+    /// Post-swap in-range liquidity (V3 only; unchanged without tick data)
+    pub post_liquidity: Option<u128>,
+    /// Initialized ticks crossed (V3 with tick data; None = within-tick approximation)
+    pub ticks_crossed: Option<u32>,
 }
 
 /// A simulated arbitrage opportunity created by a pending swap.
@@ -202,7 +230,7 @@ pub struct SimulatedOpportunity {
     pub pair_symbol: String,
     /// Swap direction (token0→token1 = true, token1→token0 = false)
     pub zero_for_one: bool,
-    /// Raw input amount
+    /// Raw input amount (implied by amountOut for exact-output swaps)
     pub amount_in: U256,
     /// Price before and after simulation
     pub pre_swap_price: f64,
@@ -213,7 +241,14 @@ pub struct SimulatedOpportunity {
     pub arb_buy_dex: DexType,
     pub arb_sell_dex: DexType,
     pub arb_spread_pct: f64,
+    /// Net of gas, from our simulated round trip (arb_sell_amount_out − size)
     pub arb_est_profit_usd: f64,
+    /// Our backrun through both pools at MAX_TRADE_SIZE_USD: base out of the
+    /// buy leg, quote out of the sell leg (raw)
+    pub arb_buy_amount_out: U256,
+    pub arb_sell_amount_out: U256,
+    /// Mid-market spread left between the two pools after our trade (%)
+    pub arb_residual_spread_pct: f64,
 }
 
 /// Tracks simulated opportunities for post-confirmation accuracy validation.
@@ -226,16 +261,22 @@ pub struct SimulationTracker {
     pub total_opportunities: u64,
     pub total_validated: u64,
     pub price_error_samples: Vec<f64>,
+    /// Subset of price_error_samples: simulations that crossed an initialized tick
+    pub cross_tick_error_samples: Vec<f64>,
+    /// Rolling per-(DEX, pair) error → adaptive execute threshold
+    pub calibration: Calibration,
 }
 
 impl SimulationTracker {
-    pub fn new() -> Self {
+    pub fn new(calibration: Calibration) -> Self {
         Self {
             pending: HashMap::new(),
             total_simulated: 0,
             total_opportunities: 0,
             total_validated: 0,
             price_error_samples: Vec::new(),
+            cross_tick_error_samples: Vec::new(),
+            calibration,
         }
     }
 
@@ -264,10 +305,14 @@ impl SimulationTracker {
             .map(|(state, _instant, opp)| (state, opp))
     }
 
-    /// Record an accuracy measurement
-    pub fn record_accuracy(&mut self, error_pct: f64) {
+    /// Record an accuracy measurement for a validated simulation
+    pub fn record_accuracy(&mut self, error_pct: f64, simulated: &SimulatedPoolState) {
         self.total_validated += 1;
         self.price_error_samples.push(error_pct);
+        self.calibration.record(simulated.dex, &simulated.pair_symbol, error_pct * 100.0);
+        if simulated.ticks_crossed.is_some_and(|n| n > 0) {
+            self.cross_tick_error_samples.push(error_pct);
+        }
     }
 
     /// Remove entries older than max_age
@@ -278,13 +323,22 @@ impl SimulationTracker {
 
     /// Median price prediction error (%)
     pub fn median_error_pct(&self) -> f64 {
-        if self.price_error_samples.is_empty() {
-            return 0.0;
-        }
-        let mut sorted = self.price_error_samples.clone();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-        sorted[sorted.len() / 2]
+        median(&self.price_error_samples)
+    }
+
+    /// Median price prediction error (%) of cross-tick simulations
+    pub fn median_cross_tick_error_pct(&self) -> f64 {
+        median(&self.cross_tick_error_samples)
+    }
+}
+
+fn median(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
     }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    sorted[sorted.len() / 2]
 }
 
 // ── Phase 3: Execution Signal Types ─────────────────────────────────────────
@@ -301,6 +355,18 @@ pub struct MempoolSignal {
     pub trigger_gas_price: U256,
     /// EIP-1559 max priority fee from the trigger tx (if available)
     pub trigger_max_priority_fee: Option<U256>,
+    /// Signed RLP of the trigger tx (bundle relays pin our backrun behind it)
+    pub trigger_raw_tx: Option<Bytes>,
     /// When the signal was created (for staleness detection)
     pub seen_at: Instant,
 }
+
+impl MempoolSignal {
+    /// The pending tx this backrun follows
+    pub fn trigger(&self) -> TriggerTx {
+        TriggerTx {
+            hash: self.opportunity.tx_hash,
+            raw: self.trigger_raw_tx.clone(),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Live Native Token Price — gas accounting from the bot's own pools
+//!
+//! Gas costs (TradeExecutor's USD accounting, the mempool gas budget, tax
+//! records' gas_price_usd) need the native token's USD price. A static
+//! NATIVE_TOKEN_PRICE_USD goes wrong as soon as MATIC/ETH moves, so the price
+//! is read each block from a pool the bot already syncs (NATIVE_PRICE_PAIR,
+//! e.g. WMATIC/USDC): the deepest V3 pool for that pair, else the deepest V2.
+//!
+//! The configured price stays the fallback: it is used while the reference
+//! pool is missing, and whenever the pool's price diverges more than 50% from
+//! it (a manipulated or mis-synced pool must not move gas accounting).
+//!
+//! NativePrice is a cheap shared handle (Arc<AtomicU64> of the f64 bits) —
+//! the executor owns one, the updater writes it, the tax writer thread reads it.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::pool::PoolStateManager;
+use crate::types::BotConfig;
+use ethers::types::Address;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Largest relative divergence from the configured price a pool price may show
+pub const MAX_DIVERGENCE: f64 = 0.50;
+
+/// Shared native token USD price
+#[derive(Debug, Clone)]
+pub struct NativePrice(Arc<AtomicU64>);
+
+impl NativePrice {
+    pub fn new(price_usd: f64) -> Self {
+        Self(Arc::new(AtomicU64::new(price_usd.to_bits())))
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, price_usd: f64) {
+        self.0.store(price_usd.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Where the current price came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceSource {
+    /// Read from the reference pool
+    Pool { address: Address, price_usd: f64 },
+    /// No usable reference pool (none configured or none synced)
+    NoPool,
+    /// The reference pool's price was rejected for diverging too far
+    Diverged { address: Address, pool_price_usd: f64 },
+}
+
+/// Refreshes a NativePrice from the reference pool
+pub struct NativePriceUpdater {
+    pair_symbol: Option<String>,
+    fallback_usd: f64,
+    quote_tokens: [Option<Address>; 2],
+    quote_token_price_usd: f64,
+    price: NativePrice,
+    last_source: Option<PriceSource>,
+}
+
+impl NativePriceUpdater {
+    pub fn new(config: &BotConfig, price: NativePrice) -> Self {
+        Self {
+            pair_symbol: config.native_price_pair.clone(),
+            fallback_usd: config.native_token_price_usd,
+            quote_tokens: [Some(config.quote_token_address), config.quote_token_address_native],
+            quote_token_price_usd: config.quote_token_price_usd,
+            price,
+            last_source: None,
+        }
+    }
+
+    /// Re-read the reference pool and publish the price (pool or fallback).
+    /// Logs only when the source changes kind, so a steady state stays quiet.
+    pub fn refresh(&mut self, pools: &PoolStateManager) -> PriceSource {
+        let source = match self.pool_price(pools) {
+            None => PriceSource::NoPool,
+            Some((address, price_usd)) => {
+                if (price_usd - self.fallback_usd).abs() > MAX_DIVERGENCE * self.fallback_usd {
+                    PriceSource::Diverged { address, pool_price_usd: price_usd }
+                } else {
+                    PriceSource::Pool { address, price_usd }
+                }
+            }
+        };
+        let price_usd = match source {
+            PriceSource::Pool { price_usd, .. } => price_usd,
+            _ => self.fallback_usd,
+        };
+        self.price.set(price_usd);
+
+        let changed_kind = self
+            .last_source
+            .is_none_or(|last| std::mem::discriminant(&last) != std::mem::discriminant(&source));
+        if changed_kind {
+            match source {
+                PriceSource::Pool { address, price_usd } => {
+                    info!("⛽ Native price ${:.4} from pool {:?}", price_usd, address)
+                }
+                PriceSource::NoPool if self.pair_symbol.is_some() => warn!(
+                    "⛽ Native price: no synced {} pool — using NATIVE_TOKEN_PRICE_USD ${:.4}",
+                    self.pair_symbol.as_deref().unwrap_or_default(), self.fallback_usd
+                ),
+                PriceSource::NoPool => {}
+                PriceSource::Diverged { address, pool_price_usd } => warn!(
+                    "⛽ Native price: pool {:?} says ${:.4}, >{:.0}% from NATIVE_TOKEN_PRICE_USD ${:.4} — using the configured price",
+                    address, pool_price_usd, MAX_DIVERGENCE * 100.0, self.fallback_usd
+                ),
+            }
+        }
+        self.last_source = Some(source);
+        source
+    }
+
+    /// (pool, native price in USD) from the deepest V3 pool of the reference
+    /// pair, else the deepest V2 pool (by quote-side reserve)
+    fn pool_price(&self, pools: &PoolStateManager) -> Option<(Address, f64)> {
+        let symbol = self.pair_symbol.as_deref()?;
+        let v3 = pools
+            .get_v3_pools_for_pair(symbol)
+            .into_iter()
+            .filter(|p| p.liquidity > 0)
+            .filter_map(|p| Some((p.liquidity, p.address, self.native_per_quote(p.pair.token0, p.pair.token1, p.price())?)))
+            .max_by_key(|(liquidity, _, _)| *liquidity);
+        let best = v3.map(|(_, address, price)| (address, price)).or_else(|| {
+            pools
+                .get_pools_for_pair(symbol)
+                .into_iter()
+                .filter_map(|p| {
+                    let quote_reserve = if self.is_quote(p.pair.token1) { p.reserve1 } else { p.reserve0 };
+                    Some((quote_reserve, p.address, self.native_per_quote(p.pair.token0, p.pair.token1, p.price_adjusted())?))
+                })
+                .max_by_key(|(reserve, _, _)| *reserve)
+                .map(|(_, address, price)| (address, price))
+        })?;
+        Some((best.0, best.1 * self.quote_token_price_usd))
+    }
+
+    /// Quote units per native token from a token1-per-token0 price
+    fn native_per_quote(&self, token0: Address, token1: Address, price: f64) -> Option<f64> {
+        let quote_per_native = if self.is_quote(token1) {
+            price
+        } else if self.is_quote(token0) && price > 0.0 {
+            1.0 / price
+        } else {
+            return None;
+        };
+        (quote_per_native.is_finite() && quote_per_native > 0.0).then_some(quote_per_native)
+    }
+
+    fn is_quote(&self, token: Address) -> bool {
+        self.quote_tokens.contains(&Some(token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, PoolState, TradingPair, V3PoolState};
+    use ethers::types::U256;
+    use std::str::FromStr;
+
+    fn wmatic() -> Address {
+        Address::from_str("0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270").unwrap()
+    }
+
+    fn usdc() -> Address {
+        Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap()
+    }
+
+    fn config() -> BotConfig {
+        let mut config = crate::arbitrage::detector::tests::create_test_config();
+        config.quote_token_address = usdc();
+        config.native_token_price_usd = 0.50;
+        config.native_price_pair = Some("WMATIC/USDC".to_string());
+        config
+    }
+
+    /// WMATIC (token0, 18 dec) / USDC (token1, 6 dec) V3 pool at `price` USDC per WMATIC
+    fn v3_pool(address: u64, price: f64, liquidity: u128) -> V3PoolState {
+        // price_from_tick = 1.0001^tick * 10^(18-6)
+        let tick = ((price / 1e12).ln() / 1.0001f64.ln()).round() as i32;
+        V3PoolState {
+            address: Address::from_low_u64_be(address),
+            dex: DexType::UniswapV3_005,
+            pair: TradingPair::new(wmatic(), usdc(), "WMATIC/USDC".to_string()),
+            sqrt_price_x96: U256::zero(),
+            tick,
+            fee: 500,
+            liquidity,
+            token0_decimals: 18,
+            token1_decimals: 6,
+            last_updated: 1,
+        }
+    }
+
+    #[test]
+    fn test_price_from_deepest_reference_pool() {
+        let pools = PoolStateManager::new();
+        pools.update_v3_pool(v3_pool(1, 0.40, 1_000));
+        pools.update_v3_pool(v3_pool(2, 0.65, 9_000));
+        let price = NativePrice::new(0.50);
+        let mut updater = NativePriceUpdater::new(&config(), price.clone());
+
+        match updater.refresh(&pools) {
+            PriceSource::Pool { address, price_usd } => {
+                assert_eq!(address, Address::from_low_u64_be(2));
+                assert!((price_usd - 0.65).abs() < 0.001);
+            }
+            other => panic!("expected pool price, got {:?}", other),
+        }
+        assert!((price.get() - 0.65).abs() < 0.001);
+
+        // V2 only: USDC as token1 with 6 decimals → 1000 USDC / 2000 WMATIC
+        let v2_only = PoolStateManager::new();
+        v2_only.update_pool(PoolState {
+            address: Address::from_low_u64_be(3),
+            dex: DexType::QuickSwapV2,
+            pair: TradingPair::new(wmatic(), usdc(), "WMATIC/USDC".to_string()),
+            reserve0: U256::from(2_000u64) * U256::exp10(18),
+            reserve1: U256::from(1_000u64) * U256::exp10(6),
+            last_updated: 1,
+            token0_decimals: 18,
+            token1_decimals: 6,
+        });
+        assert_eq!(
+            updater.refresh(&v2_only),
+            PriceSource::Pool { address: Address::from_low_u64_be(3), price_usd: 0.5 }
+        );
+    }
+
+    #[test]
+    fn test_falls_back_to_configured_price() {
+        let price = NativePrice::new(0.50);
+        let mut updater = NativePriceUpdater::new(&config(), price.clone());
+
+        // Reference pool not synced
+        assert_eq!(updater.refresh(&PoolStateManager::new()), PriceSource::NoPool);
+        assert_eq!(price.get(), 0.50);
+
+        // Pool 3x the configured price: rejected, configured price kept
+        let pools = PoolStateManager::new();
+        pools.update_v3_pool(v3_pool(1, 1.50, 1_000));
+        assert!(matches!(updater.refresh(&pools), PriceSource::Diverged { .. }));
+        assert_eq!(price.get(), 0.50);
+
+        // Back in range: the pool price is live again
+        pools.update_v3_pool(v3_pool(1, 0.60, 1_000));
+        assert!(matches!(updater.refresh(&pools), PriceSource::Pool { .. }));
+        assert!((price.get() - 0.60).abs() < 0.001);
+
+        // No reference pair configured: static price only
+        let mut static_config = config();
+        static_config.native_price_pair = None;
+        let mut updater = NativePriceUpdater::new(&static_config, price.clone());
+        assert_eq!(updater.refresh(&pools), PriceSource::NoPool);
+        assert_eq!(price.get(), 0.50);
+    }
+}
@@ -2,14 +2,16 @@
 //!
 //! Produces a stream of pool state update events.
 //! Wraps the existing PoolStateManager and PoolSyncer.
+//! ReplayCollector feeds recorded pool snapshots for reproducible runs.
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - Event sequence numbers, ReplayCollector
 
 use super::engine::{Collector, CollectorStream};
 use super::strategy::PoolUpdateEvent;
 use crate::pool::{PoolStateManager, PoolSyncer};
-use crate::types::BotConfig;
+use crate::types::{BotConfig, PoolState};
 use anyhow::Result;
 use async_trait::async_trait;
 use ethers::prelude::*;
@@ -81,7 +83,9 @@ where
         let config = self.config.clone();
 
         // Map interval ticks to pool update events
+        let mut sequence: u64 = 0;
         let event_stream = stream.then(move |_| {
+            sequence += 1;
             let syncer = PoolSyncer::new(
                 Arc::clone(&provider),
                 config.clone(),
@@ -103,6 +107,7 @@ where
                     .unwrap_or(0);
 
                 PoolUpdateEvent {
+                    sequence,
                     block_number,
                     timestamp: std::time::SystemTime::now()
                         .duration_since(std::time::UNIX_EPOCH)
@@ -147,7 +152,9 @@ where
 
         let provider = Arc::clone(&self.provider);
 
+        let mut sequence: u64 = 0;
         let event_stream = stream.then(move |_| {
+            sequence += 1;
             let provider = Arc::clone(&provider);
             async move {
                 let block_number = provider
@@ -157,6 +164,7 @@ where
                     .unwrap_or(0);
 
                 PoolUpdateEvent {
+                    sequence,
                     block_number,
                     timestamp: std::time::SystemTime::now()
                         .duration_since(std::time::UNIX_EPOCH)
@@ -169,3 +177,53 @@ where
         Ok(Box::pin(event_stream))
     }
 }
+
+/// One recorded step of a replay: the pool states as of a block
+#[derive(Debug, Clone)]
+pub struct ReplayFrame {
+    pub block_number: u64,
+    /// Block timestamp (unix secs)
+    pub timestamp: u64,
+    /// Pool states to apply before the event is emitted
+    pub pools: Vec<PoolState>,
+}
+
+/// Collector that replays recorded frames instead of polling the chain
+///
+/// Each frame's pools are written to the state manager lazily, when the
+/// stream is polled for that frame, so a sequential engine sees exactly the
+/// state belonging to the event it is processing.
+pub struct ReplayCollector {
+    state_manager: PoolStateManager,
+    frames: Vec<ReplayFrame>,
+}
+
+impl ReplayCollector {
+    pub fn new(state_manager: PoolStateManager, frames: Vec<ReplayFrame>) -> Self {
+        Self {
+            state_manager,
+            frames,
+        }
+    }
+}
+
+#[async_trait]
+impl Collector<PoolUpdateEvent> for ReplayCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, PoolUpdateEvent>> {
+        let state_manager = self.state_manager.clone();
+        let event_stream = tokio_stream::iter(self.frames.iter().enumerate()).map(
+            move |(index, frame)| {
+                for pool in &frame.pools {
+                    state_manager.update_pool(pool.clone());
+                }
+                PoolUpdateEvent {
+                    sequence: index as u64 + 1,
+                    block_number: frame.block_number,
+                    timestamp: frame.timestamp,
+                }
+            },
+        );
+
+        Ok(Box::pin(event_stream))
+    }
+}
@@ -5,9 +5,22 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - StrategyKind; "live-parity" preset (live detector + atomic rules)
 
 use serde::{Deserialize, Serialize};
 
+/// Detection logic a paper configuration runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrategyKind {
+    /// Paper-only spread scan over `pairs` with the thresholds below
+    #[default]
+    Spread,
+    /// The live OpportunityDetector and block-loop filters under the bot's
+    /// BotConfig (pairs and profit / size / slippage limits come from there)
+    Parity,
+}
+
 /// Configuration for a single paper trading strategy
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaperTradingConfig {
@@ -15,6 +28,9 @@ pub struct PaperTradingConfig {
     pub name: String,
     /// Whether this configuration is active
     pub enabled: bool,
+    /// Detection logic (Spread when absent)
+    #[serde(default)]
+    pub strategy: StrategyKind,
 
     // Trading parameters
     /// Minimum profit threshold in USD to consider a trade
@@ -65,6 +81,7 @@ impl PaperTradingConfig {
         Self {
             name: "Conservative".to_string(),
             enabled: true,
+            strategy: StrategyKind::Spread,
             min_profit_usd: 10.0,
             max_trade_size_usd: 500.0,
             max_slippage_percent: 0.3,
@@ -86,6 +103,7 @@ impl PaperTradingConfig {
         Self {
             name: "Moderate".to_string(),
             enabled: true,
+            strategy: StrategyKind::Spread,
             min_profit_usd: 5.0,
             max_trade_size_usd: 1000.0,
             max_slippage_percent: 0.5,
@@ -107,6 +125,7 @@ impl PaperTradingConfig {
         Self {
             name: "Aggressive".to_string(),
             enabled: true,
+            strategy: StrategyKind::Spread,
             min_profit_usd: 3.0,
             max_trade_size_usd: 2000.0,
             max_slippage_percent: 1.0,
@@ -207,6 +226,22 @@ impl PaperTradingConfig {
         config
     }
 
+    /// Live parity: the live detector, cooldown, simulated multicall
+    /// pre-screen and minProfit gate. Trading thresholds are the BotConfig's;
+    /// slippage is already in the quoted fill, so only gas and competition
+    /// are simulated on top. Not part of all_presets().
+    pub fn live_parity() -> Self {
+        let mut config = Self::moderate();
+        config.name = "live-parity".to_string();
+        config.strategy = StrategyKind::Parity;
+        config.pairs = Vec::new();
+        config.simulate_slippage = false;
+        config.max_daily_trades = None;
+        config.max_consecutive_losses = None;
+        config.daily_loss_limit_usd = None;
+        config
+    }
+
     /// Returns all 12 preset configurations
     pub fn all_presets() -> Vec<Self> {
         vec![
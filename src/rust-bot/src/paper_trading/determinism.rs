@@ -0,0 +1,173 @@
+//! Deterministic Mode for Paper Trading
+//!
+//! Paper trading comparisons are only meaningful if two runs over the same
+//! data produce the same numbers. This module provides the pieces that make
+//! that possible:
+//! - `SimRng`: a small seeded RNG handle injected into every randomized
+//!   component (competition rolls, slippage, gas variance, latency)
+//! - `DeterminismConfig`: global seed + logical-clock option
+//!
+//! With a seed set, each component derives its own RNG stream from
+//! (seed, component label), so draws do not depend on task scheduling.
+//! With the logical clock enabled, simulated latency is counted in events
+//! instead of slept in wall time, and timestamps come from the event being
+//! processed rather than `Utc::now()`.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Seeded pseudo-random number generator (SplitMix64)
+///
+/// Cheap to clone: clones share the same underlying state, so a handle can
+/// be passed around without forking the stream.
+#[derive(Debug, Clone)]
+pub struct SimRng {
+    state: Arc<Mutex<u64>>,
+}
+
+impl SimRng {
+    /// Create an RNG from an explicit seed
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(seed)),
+        }
+    }
+
+    /// Create an RNG seeded from the wall clock (non-reproducible)
+    pub fn from_entropy() -> Self {
+        let nanos = Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64;
+        Self::from_seed(nanos)
+    }
+
+    /// Next raw 64-bit value
+    pub fn next_u64(&self) -> u64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform float in [0, 1)
+    pub fn next_f64(&self) -> f64 {
+        // Top 53 bits -> exact f64 mantissa
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform integer in [low, high)
+    pub fn range_u64(&self, low: u64, high: u64) -> u64 {
+        if high <= low {
+            return low;
+        }
+        low + self.next_u64() % (high - low)
+    }
+}
+
+/// Global determinism settings for a paper trading run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeterminismConfig {
+    /// Global seed for all randomized components (None = seed from wall clock)
+    pub seed: Option<u64>,
+    /// Measure simulated latency in events and take timestamps from events
+    pub logical_clock: bool,
+}
+
+impl DeterminismConfig {
+    /// Fully reproducible settings: fixed seed + logical clock
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            logical_clock: true,
+        }
+    }
+
+    /// Whether runs under these settings are reproducible
+    pub fn is_deterministic(&self) -> bool {
+        self.seed.is_some() && self.logical_clock
+    }
+
+    /// RNG stream for a named component (e.g. "strategy:Moderate")
+    ///
+    /// Each component gets an independent stream derived from the global
+    /// seed, so the order in which components draw does not matter.
+    pub fn rng_for(&self, component: &str) -> SimRng {
+        match self.seed {
+            Some(seed) => SimRng::from_seed(seed ^ fnv1a(component)),
+            None => SimRng::from_entropy(),
+        }
+    }
+
+    /// Current time: event timestamp under the logical clock, wall time otherwise
+    pub fn now(&self, event_timestamp: u64) -> DateTime<Utc> {
+        if self.logical_clock {
+            DateTime::from_timestamp(event_timestamp as i64, 0).unwrap_or_default()
+        } else {
+            Utc::now()
+        }
+    }
+
+    /// Start time for metrics: the epoch under the logical clock, wall time otherwise
+    pub fn start_time(&self) -> DateTime<Utc> {
+        self.now(0)
+    }
+}
+
+/// FNV-1a hash (stable across Rust versions, unlike DefaultHasher)
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_stream() {
+        let a = SimRng::from_seed(42);
+        let b = SimRng::from_seed(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_component_streams_are_independent() {
+        let config = DeterminismConfig::seeded(7);
+        let a = config.rng_for("strategy:A");
+        let b = config.rng_for("strategy:B");
+        assert_ne!(a.next_u64(), b.next_u64());
+
+        // Drawing from one stream does not shift another
+        let a2 = config.rng_for("strategy:A");
+        let _ = config.rng_for("strategy:B").next_u64();
+        let a_fresh = config.rng_for("strategy:A");
+        assert_eq!(a2.next_u64(), a_fresh.next_u64());
+    }
+
+    #[test]
+    fn test_next_f64_in_unit_interval() {
+        let rng = SimRng::from_seed(1);
+        for _ in 0..1000 {
+            let x = rng.next_f64();
+            assert!((0.0..1.0).contains(&x));
+        }
+        for _ in 0..1000 {
+            let x = rng.range_u64(10, 50);
+            assert!((10..50).contains(&x));
+        }
+    }
+
+    #[test]
+    fn test_logical_clock_uses_event_time() {
+        let config = DeterminismConfig::seeded(1);
+        assert_eq!(config.now(1_700_000_000).timestamp(), 1_700_000_000);
+        assert_eq!(config.start_time().timestamp(), 0);
+    }
+}
@@ -6,8 +6,13 @@
 //!
 //! Based on: https://github.com/paradigmxyz/artemis
 //!
+//! Deterministic mode replaces the broadcast fan-out with a single
+//! sequential loop: events are delivered to strategies in registration
+//! order and every action is fully executed before the next event is read.
+//!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - Deterministic (sequential) mode
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -73,6 +78,8 @@ pub struct Engine<E, A> {
     event_channel_capacity: usize,
     /// Channel capacity for actions
     action_channel_capacity: usize,
+    /// Process events sequentially in registration order
+    deterministic: bool,
 }
 
 impl<E, A> Engine<E, A> {
@@ -83,6 +90,7 @@ impl<E, A> Engine<E, A> {
             executors: vec![],
             event_channel_capacity: 512,
             action_channel_capacity: 512,
+            deterministic: false,
         }
     }
 
@@ -96,6 +104,14 @@ impl<E, A> Engine<E, A> {
         self
     }
 
+    /// Deterministic mode: collectors are drained in registration order and each
+    /// event passes through every strategy (registration order) and executor
+    /// before the next event is pulled. Intended for finite replay collectors.
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
     /// Add a collector to the engine
     pub fn add_collector(&mut self, collector: Box<dyn Collector<E>>) {
         self.collectors.push(collector);
@@ -131,6 +147,16 @@ where
     /// 3. Strategies produce actions
     /// 4. Actions are broadcast to all executors
     pub async fn run(self) -> Result<JoinSet<()>> {
+        if self.deterministic {
+            let mut set = JoinSet::new();
+            set.spawn(async move {
+                if let Err(e) = self.run_to_completion().await {
+                    error!("Deterministic engine error: {}", e);
+                }
+            });
+            return Ok(set);
+        }
+
         let (event_sender, _): (Sender<E>, _) = broadcast::channel(self.event_channel_capacity);
         let (action_sender, _): (Sender<A>, _) = broadcast::channel(self.action_channel_capacity);
 
@@ -209,4 +235,40 @@ where
 
         Ok(set)
     }
+
+    /// Run all components sequentially until every collector stream ends
+    ///
+    /// Returns the number of events processed. Output depends only on the
+    /// collector data and the components' own (seeded) state, never on task
+    /// scheduling.
+    pub async fn run_to_completion(mut self) -> Result<u64> {
+        let mut strategies = Vec::with_capacity(self.strategies.len());
+        for mut strategy in std::mem::take(&mut self.strategies) {
+            if let Err(e) = strategy.sync_state().await {
+                error!("Failed to sync strategy {}: {}", strategy.name(), e);
+                continue;
+            }
+            strategies.push(strategy);
+        }
+
+        let mut events_processed = 0u64;
+        for collector in &self.collectors {
+            let mut event_stream = collector.get_event_stream().await?;
+            while let Some(event) = event_stream.next().await {
+                events_processed += 1;
+                for strategy in strategies.iter_mut() {
+                    for action in strategy.process_event(event.clone()).await {
+                        for executor in &self.executors {
+                            if let Err(e) = executor.execute(action.clone()).await {
+                                error!("Error executing action: {}", e);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        info!("Deterministic run complete: {} events", events_processed);
+        Ok(events_processed)
+    }
 }
@@ -8,13 +8,14 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - Seeded RNG and logical-clock latency (deterministic mode)
 
 use super::config::PaperTradingConfig;
+use super::determinism::{DeterminismConfig, SimRng};
 use super::engine::Executor;
 use super::metrics::{SimulatedTradeResult, TraderMetrics};
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::Utc;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
@@ -37,17 +38,38 @@ pub struct SimulatedTradeAction {
     pub sell_dex: String,
     /// Whether this was lost to competition (pre-determined by strategy)
     pub lost_to_competition: bool,
+    /// Sequence number of the event that produced this action
+    pub event_sequence: u64,
+    /// Timestamp (unix secs) of the event that produced this action
+    pub event_timestamp: u64,
 }
 
 /// Simulated executor that models realistic trade outcomes
 pub struct SimulatedExecutor {
     config: PaperTradingConfig,
     metrics: Arc<RwLock<TraderMetrics>>,
+    determinism: DeterminismConfig,
+    rng: SimRng,
 }
 
 impl SimulatedExecutor {
     pub fn new(config: PaperTradingConfig, metrics: Arc<RwLock<TraderMetrics>>) -> Self {
-        Self { config, metrics }
+        Self::with_determinism(config, metrics, DeterminismConfig::default())
+    }
+
+    /// Create an executor whose randomness and clock follow the given settings
+    pub fn with_determinism(
+        config: PaperTradingConfig,
+        metrics: Arc<RwLock<TraderMetrics>>,
+        determinism: DeterminismConfig,
+    ) -> Self {
+        let rng = determinism.rng_for(&format!("executor:{}", config.name));
+        Self {
+            config,
+            metrics,
+            determinism,
+            rng,
+        }
     }
 
     /// Simulate slippage loss as a percentage of expected profit
@@ -57,13 +79,10 @@ impl SimulatedExecutor {
         }
 
         // Slippage typically eats 10-30% of expected profit
-        // Use a simple deterministic model based on trade characteristics
         let base_slippage = 0.15; // 15% base slippage
         let variance = 0.10; // +/- 10% variance
 
-        // Use timestamp-based pseudo-randomness for reproducibility
-        let seed = Utc::now().timestamp_nanos_opt().unwrap_or(0) as f64;
-        let random_factor = ((seed % 1000.0) / 1000.0) * variance * 2.0 - variance;
+        let random_factor = self.rng.next_f64() * variance * 2.0 - variance;
 
         let slippage_rate = base_slippage + random_factor;
         estimated_profit * slippage_rate.max(0.05).min(0.40)
@@ -79,19 +98,31 @@ impl SimulatedExecutor {
         let base_gas = 0.50;
         let variance = 0.25;
 
-        // Use timestamp-based pseudo-randomness
-        let seed = Utc::now().timestamp_nanos_opt().unwrap_or(0) as f64;
-        let random_factor = ((seed % 1000.0) / 1000.0) * variance * 2.0 - variance;
+        let random_factor = self.rng.next_f64() * variance * 2.0 - variance;
 
         (base_gas + random_factor).max(0.20).min(1.50)
     }
 
     /// Simulate execution delay (network latency, etc.)
-    async fn simulate_execution_delay(&self) {
+    ///
+    /// Returns the simulated delay in ms. Under the logical clock the delay is
+    /// only counted, not slept, so replay speed does not affect results.
+    async fn simulate_execution_delay(&self) -> u64 {
         // Simulate 10-50ms execution delay
-        let seed = Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64;
-        let delay_ms = 10 + (seed % 40);
-        tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+        let delay_ms = self.rng.range_u64(10, 50);
+        if !self.determinism.logical_clock {
+            tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+        }
+        delay_ms
+    }
+
+    /// Elapsed execution time: simulated delay (logical clock) or measured wall time
+    fn execution_time_ms(&self, start: Instant, simulated_delay_ms: u64) -> u64 {
+        if self.determinism.logical_clock {
+            simulated_delay_ms
+        } else {
+            start.elapsed().as_millis() as u64
+        }
     }
 
     /// Execute a simulated trade
@@ -109,9 +140,9 @@ impl SimulatedExecutor {
                 profit_usd: 0.0,
                 gas_cost_usd: 0.0,
                 net_profit_usd: 0.0,
-                execution_time_ms: start.elapsed().as_millis() as u64,
+                execution_time_ms: self.execution_time_ms(start, 0),
                 error: Some("Lost to competition".to_string()),
-                timestamp: Utc::now(),
+                timestamp: self.determinism.now(action.event_timestamp),
             };
         }
 
@@ -123,7 +154,7 @@ impl SimulatedExecutor {
         let gas_cost = self.simulate_gas_cost();
 
         // Simulate execution delay
-        self.simulate_execution_delay().await;
+        let delay_ms = self.simulate_execution_delay().await;
 
         // Calculate net profit
         let net_profit = profit_after_slippage - gas_cost;
@@ -135,13 +166,13 @@ impl SimulatedExecutor {
             profit_usd: profit_after_slippage,
             gas_cost_usd: gas_cost,
             net_profit_usd: net_profit,
-            execution_time_ms: start.elapsed().as_millis() as u64,
+            execution_time_ms: self.execution_time_ms(start, delay_ms),
             error: if success {
                 None
             } else {
                 Some("Unprofitable after costs".to_string())
             },
-            timestamp: Utc::now(),
+            timestamp: self.determinism.now(action.event_timestamp),
         };
 
         // Record in metrics
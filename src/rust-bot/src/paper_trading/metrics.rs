@@ -5,6 +5,7 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - Explicit-clock variants for deterministic replay
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -82,7 +83,11 @@ impl TraderMetrics {
 
     /// Create new metrics tracker for a configuration
     pub fn new(config_name: String) -> Self {
-        let now = Utc::now();
+        Self::new_at(config_name, Utc::now())
+    }
+
+    /// Create new metrics tracker with an explicit start time (deterministic mode)
+    pub fn new_at(config_name: String, now: DateTime<Utc>) -> Self {
         Self {
             config_name,
             total_trades: 0,
@@ -117,7 +122,7 @@ impl TraderMetrics {
     pub fn record_trade(&mut self, result: SimulatedTradeResult) {
         self.total_trades += 1;
         self.daily_trades_today += 1;
-        self.last_trade_time = Some(Utc::now());
+        self.last_trade_time = Some(result.timestamp);
         self.total_gas_usd += result.gas_cost_usd;
 
         if result.success && result.net_profit_usd > 0.0 {
@@ -197,16 +202,25 @@ impl TraderMetrics {
 
     /// Reset daily counters (call at midnight)
     pub fn reset_daily(&mut self) {
+        self.reset_daily_at(Utc::now());
+    }
+
+    /// Reset daily counters at an explicit time
+    pub fn reset_daily_at(&mut self, now: DateTime<Utc>) {
         self.daily_trades_today = 0;
         self.daily_loss_today = 0.0;
-        self.last_reset_date = Utc::now();
+        self.last_reset_date = now;
     }
 
     /// Check if we should reset daily counters
     pub fn check_daily_reset(&mut self) {
-        let now = Utc::now();
+        self.check_daily_reset_at(Utc::now());
+    }
+
+    /// Check for a day rollover against an explicit time (logical clock)
+    pub fn check_daily_reset_at(&mut self, now: DateTime<Utc>) {
         if now.date_naive() != self.last_reset_date.date_naive() {
-            self.reset_daily();
+            self.reset_daily_at(now);
         }
     }
 
@@ -19,9 +19,12 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - Deterministic seeded mode + replay acceptance test
+//! Modified: 2026-10-16 - "live-parity" strategy alongside the presets; golden parity test
 
 pub mod collector;
 pub mod config;
+pub mod determinism;
 pub mod discord_alerts;
 pub mod engine;
 pub mod executor;
@@ -30,8 +33,9 @@ pub mod strategy;
 pub mod toml_config;
 
 // Re-exports for convenience
-pub use collector::{PoolStateCollector, SimpleBlockCollector};
-pub use config::PaperTradingConfig;
+pub use collector::{PoolStateCollector, ReplayCollector, ReplayFrame, SimpleBlockCollector};
+pub use config::{PaperTradingConfig, StrategyKind};
+pub use determinism::{DeterminismConfig, SimRng};
 pub use discord_alerts::{
     AggregatedOpportunity, DiscordAlerter, StrategyMatch, DailySummary, StrategyStats,
     OpportunityBatcher, BatchedOpportunitySummary,
@@ -39,7 +43,7 @@ pub use discord_alerts::{
 pub use engine::{Collector, Engine, Executor, Strategy};
 pub use executor::{MultiExecutor, SimulatedExecutor, SimulatedTradeAction};
 pub use metrics::{MetricsAggregator, SimulatedTradeResult, TraderMetrics};
-pub use strategy::{PaperTradingStrategy, PoolUpdateEvent, StrategyFactory};
+pub use strategy::{ParityStrategy, PaperTradingStrategy, PoolUpdateEvent, StrategyFactory};
 pub use toml_config::{TomlConfig, GeneralConfig, StrategyConfig};
 
 use crate::pool::PoolStateManager;
@@ -55,7 +59,8 @@ use tracing::info;
 ///
 /// This sets up:
 /// 1. A single PoolStateCollector (one data source)
-/// 2. 12 PaperTradingStrategies (one per configuration)
+/// 2. 12 PaperTradingStrategies (one per configuration) plus the
+///    "live-parity" ParityStrategy running the live detector
 /// 3. A MultiExecutor that routes actions to the correct metrics tracker
 pub async fn run_paper_trading<M>(
     provider: Arc<M>,
@@ -114,6 +119,21 @@ where
         engine.add_strategy(Box::new(strategy));
     }
 
+    // Live parity: the live detector and filters, reported next to the presets
+    let parity_config = PaperTradingConfig::live_parity();
+    let (parity, parity_metrics) = StrategyFactory::create_parity_strategy(
+        parity_config.clone(),
+        bot_config.clone(),
+        state_manager.clone(),
+        DeterminismConfig::default(),
+    );
+    multi_executor.add_executor(
+        parity.name().to_string(),
+        Arc::new(SimulatedExecutor::new(parity_config, Arc::clone(&parity_metrics))),
+    );
+    all_metrics.push(parity_metrics);
+    engine.add_strategy(Box::new(parity));
+
     // Add executor
     engine.add_executor(Box::new(multi_executor));
 
@@ -188,15 +208,19 @@ where
             continue;
         }
 
-        let (strategy, metrics) =
-            StrategyFactory::create_strategy(config.clone(), state_manager.clone());
+        let (strategy, metrics) = StrategyFactory::create_boxed(
+            config.clone(),
+            &bot_config,
+            state_manager.clone(),
+            DeterminismConfig::default(),
+        );
 
         let executor = Arc::new(SimulatedExecutor::new(config, Arc::clone(&metrics)));
 
         multi_executor.add_executor(strategy.name().to_string(), executor);
         all_metrics.push(Arc::clone(&metrics));
 
-        engine.add_strategy(Box::new(strategy));
+        engine.add_strategy(strategy);
     }
 
     engine.add_collector(Box::new(collector));
@@ -219,3 +243,208 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::{OpportunityDetector, VerifiedOpportunity};
+    use crate::types::{ArbitrageOpportunity, DexType, PoolState, TradingPair};
+
+    fn pool(address: u64, dex: DexType, reserve0: u128, reserve1: u128, block: u64) -> PoolState {
+        PoolState {
+            address: Address::from_low_u64_be(address),
+            dex,
+            pair: TradingPair::new(
+                Address::from_low_u64_be(0xA),
+                Address::from_low_u64_be(0xB),
+                "WETH/USDC".to_string(),
+            ),
+            reserve0: U256::from(reserve0),
+            reserve1: U256::from(reserve1),
+            last_updated: block,
+            token0_decimals: 18,
+            token1_decimals: 18,
+        }
+    }
+
+    /// Recorded market: two pools whose spread drifts between ~0.6% and ~3%
+    fn replay_frames() -> Vec<ReplayFrame> {
+        (0..60u64)
+            .map(|i| {
+                let block = 1_000 + i;
+                let skew = 1_006 + (i * 7) % 25; // per-mille
+                ReplayFrame {
+                    block_number: block,
+                    timestamp: 1_700_000_000 + i * 2,
+                    pools: vec![
+                        pool(1, DexType::Uniswap, 1_000_000, 1_000_000, block),
+                        pool(2, DexType::Sushiswap, 1_000_000, 1_000 * skew as u128, block),
+                    ],
+                }
+            })
+            .collect()
+    }
+
+    /// Run the replay deterministically and return the serialized metrics
+    async fn run_replay(seed: u64) -> String {
+        let determinism = DeterminismConfig::seeded(seed);
+        let state_manager = PoolStateManager::new();
+
+        let mut configs = vec![PaperTradingConfig::moderate(), PaperTradingConfig::aggressive()];
+        for config in &mut configs {
+            config.pairs = vec!["WETH/USDC".to_string()];
+            config.max_daily_trades = None;
+            config.max_consecutive_losses = None;
+            config.daily_loss_limit_usd = None;
+        }
+
+        let mut engine: Engine<PoolUpdateEvent, SimulatedTradeAction> =
+            Engine::new().with_deterministic(true);
+        let mut multi_executor = MultiExecutor::new();
+        let mut all_metrics = Vec::new();
+
+        for config in configs {
+            let (strategy, metrics) =
+                StrategyFactory::create_strategy_with(config.clone(), state_manager.clone(), determinism);
+            let executor = Arc::new(SimulatedExecutor::with_determinism(
+                config,
+                Arc::clone(&metrics),
+                determinism,
+            ));
+            multi_executor.add_executor(strategy.name().to_string(), executor);
+            all_metrics.push(metrics);
+            engine.add_strategy(Box::new(strategy));
+        }
+
+        engine.add_collector(Box::new(ReplayCollector::new(state_manager, replay_frames())));
+        engine.add_executor(Box::new(multi_executor));
+
+        let events = engine.run_to_completion().await.unwrap();
+        assert_eq!(events, 60);
+
+        let mut aggregator = MetricsAggregator::new();
+        for m in &all_metrics {
+            aggregator.add(m.read().await.clone());
+        }
+        assert!(aggregator.metrics.iter().all(|m| m.total_trades > 0));
+
+        format!(
+            "{}\n{}",
+            serde_json::to_string(&aggregator.metrics).unwrap(),
+            aggregator.generate_report()
+        )
+    }
+
+    /// Executor that keeps every action it is handed
+    struct RecordingExecutor(Arc<std::sync::Mutex<Vec<SimulatedTradeAction>>>);
+
+    #[async_trait::async_trait]
+    impl Executor<SimulatedTradeAction> for RecordingExecutor {
+        async fn execute(&self, action: SimulatedTradeAction) -> Result<()> {
+            self.0.lock().unwrap().push(action);
+            Ok(())
+        }
+    }
+
+    /// Run live-parity over the golden detector state for `blocks`; returns
+    /// the actions and the detected-opportunity count
+    async fn run_parity(config: PaperTradingConfig, blocks: &[u64]) -> (Vec<SimulatedTradeAction>, usize) {
+        let (bot_config, state_manager) = crate::arbitrage::detector::tests::golden_state();
+        let determinism = DeterminismConfig::seeded(7);
+        let (strategy, metrics) =
+            StrategyFactory::create_boxed(config, &bot_config, state_manager.clone(), determinism);
+        let actions = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut engine: Engine<PoolUpdateEvent, SimulatedTradeAction> =
+            Engine::new().with_deterministic(true);
+        engine.add_strategy(strategy);
+        let frames = blocks
+            .iter()
+            .map(|&block_number| ReplayFrame { block_number, timestamp: 1_700_000_000 + block_number, pools: vec![] })
+            .collect();
+        engine.add_collector(Box::new(ReplayCollector::new(state_manager, frames)));
+        engine.add_executor(Box::new(RecordingExecutor(Arc::clone(&actions))));
+        engine.run_to_completion().await.unwrap();
+
+        let detected = metrics.read().await.opportunities_detected;
+        let actions = actions.lock().unwrap().clone();
+        (actions, detected)
+    }
+
+    /// The live detector and filters on the golden state, outside the engine:
+    /// executable detections and the route the block loop would fill
+    fn parity_outside_engine(block: u64) -> (Vec<ArbitrageOpportunity>, Vec<VerifiedOpportunity>) {
+        let (bot_config, state_manager) = crate::arbitrage::detector::tests::golden_state();
+        let detector = OpportunityDetector::new(bot_config, state_manager);
+        let executable = detector
+            .scan_opportunities()
+            .into_iter()
+            .filter(|o| !(o.buy_dex.is_detection_only() || o.sell_dex.is_detection_only()))
+            .collect();
+        let opportunities = detector.drop_stale_opportunities(executable, block);
+        let verified = strategy::simulate_prescreen(&opportunities);
+        (opportunities, verified)
+    }
+
+    #[tokio::test]
+    async fn test_live_parity_matches_detector_outside_engine() {
+        let (opportunities, verified) = parity_outside_engine(100);
+        // Same detections as the detector golden file, less the detection-only Curve routes
+        let golden = include_str!("../arbitrage/testdata/detector_golden.txt")
+            .lines()
+            .filter(|l| l.starts_with("pair ") && !l.contains("CurveStable"))
+            .count();
+        assert_eq!(opportunities.len(), golden);
+
+        let mut config = PaperTradingConfig::live_parity();
+        config.simulate_competition = false;
+        let (actions, detected) = run_parity(config, &[100]).await;
+        assert_eq!(detected, opportunities.len());
+
+        // One fill: the top quoted route clearing minProfit
+        let min_profit_usd = crate::arbitrage::detector::tests::golden_state().0.min_profit_usd;
+        let best = strategy::rank_verified(&verified)
+            .into_iter()
+            .find(|v| {
+                let opp = &opportunities[v.original_index];
+                U256::from(v.quoted_profit_raw as u128) >= opp.usd_to_quote(opp.min_profit_usd.unwrap_or(min_profit_usd)).raw()
+            })
+            .expect("golden state has a fillable route");
+        let opp = &opportunities[best.original_index];
+        assert_eq!(actions.len(), 1);
+        let action = &actions[0];
+        assert_eq!(
+            (action.pair.as_str(), action.buy_dex.as_str(), action.sell_dex.as_str(), action.lost_to_competition),
+            (opp.pair.symbol.as_str(), opp.buy_dex.to_string().as_str(), opp.sell_dex.to_string().as_str(), false)
+        );
+        let quoted_usd = opp.quote_to_usd(opp.quote_amount(U256::from(best.quoted_profit_raw as u128)));
+        assert_eq!(action.estimated_profit, quoted_usd);
+        assert_eq!(action.trade_size, opp.trade_size_usd);
+    }
+
+    #[tokio::test]
+    async fn test_live_parity_cools_routes_lost_to_competition() {
+        let (opportunities, verified) = parity_outside_engine(100);
+        let tried = strategy::rank_verified(&verified).len();
+        assert!(tried > 0);
+
+        // Every race lost: each tried route reverts and is cooled, so the next
+        // block sees only the routes the pre-screen never passed
+        let mut config = PaperTradingConfig::live_parity();
+        config.competition_rate = 1.0;
+        let (actions, detected) = run_parity(config, &[100, 101]).await;
+        assert!(actions.iter().all(|a| a.lost_to_competition));
+        assert_eq!(actions.len(), tried);
+        assert_eq!(detected, opportunities.len() + (opportunities.len() - tried));
+    }
+
+    #[tokio::test]
+    async fn test_seeded_replay_is_reproducible() {
+        let first = run_replay(42).await;
+        let second = run_replay(42).await;
+        assert_eq!(first, second, "same seed must produce byte-identical metrics");
+
+        let other = run_replay(43).await;
+        assert_ne!(first, other, "different seed must change the outcome");
+    }
+}
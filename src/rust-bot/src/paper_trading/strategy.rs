@@ -5,16 +5,23 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - Seeded competition rolls, event sequence numbers, stable pool order
+//! Modified: 2026-10-16 - ParityStrategy: live OpportunityDetector + block-loop filters (live-parity config)
+//! Modified: 2026-10-16 - ParityStrategy cooldown sized by ROUTE_COOLDOWN_SIZE_BUCKETS_USD
+//! Modified: 2026-10-17 - Opportunities sized in USD explicitly (ArbitrageOpportunity::new leaves trade_size_usd unset)
 
-use super::config::PaperTradingConfig;
+use super::config::{PaperTradingConfig, StrategyKind};
+use super::determinism::{DeterminismConfig, SimRng};
 use super::engine::Strategy;
 use super::executor::SimulatedTradeAction;
 use super::metrics::TraderMetrics;
+use crate::arbitrage::{OpportunityDetector, RouteCooldown, TradeOutcome, VerifiedOpportunity};
+use crate::pool::calculator::snapshot_amount_out;
 use crate::pool::PoolStateManager;
-use crate::types::ArbitrageOpportunity;
+use crate::types::{ArbitrageOpportunity, BotConfig};
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::Utc;
+use ethers::types::U256;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
@@ -22,6 +29,8 @@ use tracing::{debug, info};
 /// Event representing a pool state update
 #[derive(Debug, Clone)]
 pub struct PoolUpdateEvent {
+    /// Monotonic sequence number assigned by the collector
+    pub sequence: u64,
     /// Block number
     pub block_number: u64,
     /// Timestamp
@@ -38,6 +47,10 @@ pub struct PaperTradingStrategy {
     metrics: Arc<RwLock<TraderMetrics>>,
     /// Iteration counter
     iteration: u64,
+    /// Seed / clock settings
+    determinism: DeterminismConfig,
+    /// RNG for competition rolls
+    rng: SimRng,
 }
 
 impl PaperTradingStrategy {
@@ -46,11 +59,24 @@ impl PaperTradingStrategy {
         state_manager: PoolStateManager,
         metrics: Arc<RwLock<TraderMetrics>>,
     ) -> Self {
+        Self::with_determinism(config, state_manager, metrics, DeterminismConfig::default())
+    }
+
+    /// Create a strategy whose randomness is drawn from the given seed settings
+    pub fn with_determinism(
+        config: PaperTradingConfig,
+        state_manager: PoolStateManager,
+        metrics: Arc<RwLock<TraderMetrics>>,
+        determinism: DeterminismConfig,
+    ) -> Self {
+        let rng = determinism.rng_for(&format!("strategy:{}", config.name));
         Self {
             config,
             state_manager,
             metrics,
             iteration: 0,
+            determinism,
+            rng,
         }
     }
 
@@ -88,7 +114,9 @@ impl PaperTradingStrategy {
 
         for pair_symbol in &self.config.pairs {
             // Get pools for this pair across all DEXs
-            let pools = self.state_manager.get_pools_for_pair(pair_symbol);
+            // Stable order: DashMap iteration order is not deterministic
+            let mut pools = self.state_manager.get_pools_for_pair(pair_symbol);
+            pools.sort_by_key(|p| p.address);
 
             if pools.len() < 2 {
                 continue;
@@ -114,7 +142,7 @@ impl PaperTradingStrategy {
                             let estimated_profit = self.estimate_profit(spread);
 
                             if estimated_profit >= self.config.min_profit_usd {
-                                opportunities.push(ArbitrageOpportunity::new(
+                                let mut opp = ArbitrageOpportunity::new(
                                     pool_a.pair.clone(),
                                     pool_a.dex,
                                     pool_b.dex,
@@ -123,7 +151,9 @@ impl PaperTradingStrategy {
                                     ethers::types::U256::from(
                                         (self.config.max_trade_size_usd * 1e18) as u128,
                                     ),
-                                ));
+                                );
+                                opp.trade_size_usd = self.config.max_trade_size_usd;
+                                opportunities.push(opp);
                             }
                         }
                     }
@@ -135,7 +165,7 @@ impl PaperTradingStrategy {
                             let estimated_profit = self.estimate_profit(spread);
 
                             if estimated_profit >= self.config.min_profit_usd {
-                                opportunities.push(ArbitrageOpportunity::new(
+                                let mut opp = ArbitrageOpportunity::new(
                                     pool_b.pair.clone(),
                                     pool_b.dex,
                                     pool_a.dex,
@@ -144,7 +174,9 @@ impl PaperTradingStrategy {
                                     ethers::types::U256::from(
                                         (self.config.max_trade_size_usd * 1e18) as u128,
                                     ),
-                                ));
+                                );
+                                opp.trade_size_usd = self.config.max_trade_size_usd;
+                                opportunities.push(opp);
                             }
                         }
                     }
@@ -178,11 +210,7 @@ impl PaperTradingStrategy {
             return false;
         }
 
-        // Use timestamp-based pseudo-randomness
-        let seed = Utc::now().timestamp_nanos_opt().unwrap_or(0) as f64;
-        let roll = (seed % 1000.0) / 1000.0;
-
-        roll < self.config.competition_rate
+        self.rng.next_f64() < self.config.competition_rate
     }
 
     /// Get current metrics
@@ -194,9 +222,11 @@ impl PaperTradingStrategy {
 #[async_trait]
 impl Strategy<PoolUpdateEvent, SimulatedTradeAction> for PaperTradingStrategy {
     async fn sync_state(&mut self) -> Result<()> {
-        // Reset daily counters if needed
-        let mut metrics = self.metrics.write().await;
-        metrics.check_daily_reset();
+        // Reset daily counters if needed (logical clock: checked per event instead)
+        if !self.determinism.logical_clock {
+            let mut metrics = self.metrics.write().await;
+            metrics.check_daily_reset();
+        }
 
         info!(
             "[{}] Strategy initialized with {} pairs",
@@ -210,6 +240,11 @@ impl Strategy<PoolUpdateEvent, SimulatedTradeAction> for PaperTradingStrategy {
     async fn process_event(&mut self, event: PoolUpdateEvent) -> Vec<SimulatedTradeAction> {
         self.iteration += 1;
 
+        if self.determinism.logical_clock {
+            let now = self.determinism.now(event.timestamp);
+            self.metrics.write().await.check_daily_reset_at(now);
+        }
+
         // Check if we should stop trading
         if self.should_stop_trading().await {
             if self.iteration % 100 == 0 {
@@ -256,6 +291,8 @@ impl Strategy<PoolUpdateEvent, SimulatedTradeAction> for PaperTradingStrategy {
                 buy_dex: best.buy_dex.to_string(),
                 sell_dex: best.sell_dex.to_string(),
                 lost_to_competition: lost,
+                event_sequence: event.sequence,
+                event_timestamp: event.timestamp,
             };
 
             if !lost {
@@ -279,6 +316,212 @@ impl Strategy<PoolUpdateEvent, SimulatedTradeAction> for PaperTradingStrategy {
     }
 }
 
+/// Multicall pre-screen as the live bot would see it, quoted locally: each
+/// leg swapped through its pool snapshot (V3 in-range math, V2 constant
+/// product) instead of the on-chain quoters. Same verdicts as batch_verify():
+/// a missing leg quote is invalid, a quoted loss is not both_legs_valid.
+pub fn simulate_prescreen(opportunities: &[ArbitrageOpportunity]) -> Vec<VerifiedOpportunity> {
+    opportunities
+        .iter()
+        .enumerate()
+        .map(|(i, opp)| {
+            let rejected = |error: String| VerifiedOpportunity {
+                error: Some(error),
+                both_legs_valid: false,
+                ..VerifiedOpportunity::passthrough(i)
+            };
+            let Some(snaps) = opp.pool_snapshots.as_ref() else {
+                return rejected("Buy leg: no pool snapshot".to_string());
+            };
+            // Buy leg: quote → base; sell leg: base → quote
+            let Some(buy_out) = snapshot_amount_out(&snaps.buy, opp.trade_size.raw(), opp.quote_token_is_token0) else {
+                return rejected("Buy leg: no quote from pool state".to_string());
+            };
+            let Some(sell_out) = snapshot_amount_out(&snaps.sell, buy_out, !opp.quote_token_is_token0) else {
+                return rejected("Sell leg: no quote from pool state".to_string());
+            };
+            let profit = sell_out.as_u128() as i128 - opp.trade_size.raw().as_u128() as i128;
+            VerifiedOpportunity {
+                original_index: i,
+                buy_quoted_out: buy_out,
+                sell_quoted_out: sell_out,
+                quoted_profit_raw: profit,
+                both_legs_valid: profit > 0,
+                error: (profit <= 0).then(|| format!("Quoted loss: {}", profit)),
+            }
+        })
+        .collect()
+}
+
+/// Pre-screen survivors ranked the way the block loop tries them: valid and
+/// quoted-profitable only, highest quoted profit first
+pub fn rank_verified(verified: &[VerifiedOpportunity]) -> Vec<&VerifiedOpportunity> {
+    let mut ranked: Vec<&VerifiedOpportunity> = verified
+        .iter()
+        .filter(|v| v.both_legs_valid && v.quoted_profit_raw > 0)
+        .collect();
+    ranked.sort_by_key(|v| std::cmp::Reverse(v.quoted_profit_raw));
+    ranked
+}
+
+/// Paper strategy that runs the live pipeline on the shared pool state:
+/// OpportunityDetector scan (detection-only DEXes and stale legs dropped),
+/// route cooldown, simulated multicall pre-screen, and the ArbExecutor
+/// minProfit gate (quoted profit below min_profit_raw is rejected and the
+/// route cooled, as a live Quoter rejection would be). One fill per block.
+pub struct ParityStrategy {
+    config: PaperTradingConfig,
+    bot_config: BotConfig,
+    detector: OpportunityDetector,
+    cooldown: RouteCooldown,
+    metrics: Arc<RwLock<TraderMetrics>>,
+    iteration: u64,
+    determinism: DeterminismConfig,
+    rng: SimRng,
+}
+
+impl ParityStrategy {
+    /// Wrap a live detector over `state_manager` (no provider: the collector
+    /// keeps the pools synced)
+    pub fn new(
+        config: PaperTradingConfig,
+        bot_config: BotConfig,
+        state_manager: PoolStateManager,
+        metrics: Arc<RwLock<TraderMetrics>>,
+        determinism: DeterminismConfig,
+    ) -> Self {
+        let rng = determinism.rng_for(&format!("strategy:{}", config.name));
+        Self {
+            detector: OpportunityDetector::new(bot_config.clone(), state_manager),
+            cooldown: RouteCooldown::new(bot_config.route_cooldown_blocks)
+                .with_size_buckets(&bot_config.route_cooldown_size_buckets_usd),
+            config,
+            bot_config,
+            metrics,
+            iteration: 0,
+            determinism,
+            rng,
+        }
+    }
+
+    /// What the block loop hands to the cooldown filter: executable
+    /// detections with no leg older than MAX_POOL_AGE_BLOCKS
+    pub fn detect(&self, block: u64) -> Vec<ArbitrageOpportunity> {
+        let executable = self
+            .detector
+            .scan_opportunities()
+            .into_iter()
+            .filter(|o| !(o.buy_dex.is_detection_only() || o.sell_dex.is_detection_only()))
+            .collect();
+        self.detector.drop_stale_opportunities(executable, block)
+    }
+
+    /// ArbExecutor minProfit in quote raw units (route override or MIN_PROFIT_USD)
+    fn min_profit_raw(&self, opp: &ArbitrageOpportunity) -> U256 {
+        opp.usd_to_quote(opp.min_profit_usd.unwrap_or(self.bot_config.min_profit_usd)).raw()
+    }
+
+    fn lost_to_competition(&self) -> bool {
+        self.config.simulate_competition && self.rng.next_f64() < self.config.competition_rate
+    }
+
+    fn action(&self, opp: &ArbitrageOpportunity, profit_raw: i128, lost: bool, event: &PoolUpdateEvent) -> SimulatedTradeAction {
+        SimulatedTradeAction {
+            pair: opp.pair.symbol.clone(),
+            config_name: self.config.name.clone(),
+            estimated_profit: opp.quote_to_usd(opp.quote_amount(U256::from(profit_raw.max(0) as u128))),
+            trade_size: opp.trade_size_usd,
+            buy_dex: opp.buy_dex.to_string(),
+            sell_dex: opp.sell_dex.to_string(),
+            lost_to_competition: lost,
+            event_sequence: event.sequence,
+            event_timestamp: event.timestamp,
+        }
+    }
+}
+
+#[async_trait]
+impl Strategy<PoolUpdateEvent, SimulatedTradeAction> for ParityStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        if !self.determinism.logical_clock {
+            self.metrics.write().await.check_daily_reset();
+        }
+        info!(
+            "[{}] Live-parity strategy initialized with {} pairs (route cooldown {} blocks)",
+            self.config.name,
+            self.bot_config.pairs.len(),
+            self.bot_config.route_cooldown_blocks
+        );
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: PoolUpdateEvent) -> Vec<SimulatedTradeAction> {
+        self.iteration += 1;
+        let block = event.block_number;
+
+        if self.determinism.logical_clock {
+            let now = self.determinism.now(event.timestamp);
+            self.metrics.write().await.check_daily_reset_at(now);
+        }
+        if self.iteration.is_multiple_of(100) {
+            self.cooldown.cleanup(block);
+        }
+
+        let opportunities: Vec<ArbitrageOpportunity> = self
+            .detect(block)
+            .into_iter()
+            .filter(|o| !self.cooldown.is_cooled_down(&o.pair.symbol, o.buy_dex, o.sell_dex, o.trade_size_usd, block))
+            .collect();
+        if opportunities.is_empty() {
+            return vec![];
+        }
+        {
+            let mut metrics = self.metrics.write().await;
+            for _ in &opportunities {
+                metrics.record_detected_opportunity();
+            }
+        }
+
+        // Try in quoted-profit order; rejections and lost races fall through
+        // to the next route, a fill ends the block
+        let verified = simulate_prescreen(&opportunities);
+        let mut actions = Vec::new();
+        for v in rank_verified(&verified) {
+            let opp = &opportunities[v.original_index];
+            let outcome = if U256::from(v.quoted_profit_raw as u128) < self.min_profit_raw(opp) {
+                debug!(
+                    "[{}] {} {:?}→{:?} rejected: quoted {} below minProfit",
+                    self.config.name, opp.pair.symbol, opp.buy_dex, opp.sell_dex,
+                    opp.quote_delta_display(v.quoted_profit_raw)
+                );
+                TradeOutcome::RouteRejected
+            } else if self.lost_to_competition() {
+                actions.push(self.action(opp, v.quoted_profit_raw, true, &event));
+                TradeOutcome::AtomicRevert
+            } else {
+                let action = self.action(opp, v.quoted_profit_raw, false, &event);
+                info!(
+                    "[{}] Opportunity: {} {:?}→{:?} | Spread: {:.2}% | Quoted: {} (${:.2})",
+                    self.config.name, opp.pair.symbol, opp.buy_dex, opp.sell_dex, opp.spread_percent,
+                    opp.quote_delta_display(v.quoted_profit_raw), action.estimated_profit
+                );
+                actions.push(action);
+                TradeOutcome::Success
+            };
+            outcome.apply_cooldown(&mut self.cooldown, opp, block);
+            if outcome == TradeOutcome::Success {
+                break;
+            }
+        }
+
+        actions
+    }
+
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+}
+
 /// Factory for creating strategies from configurations
 pub struct StrategyFactory;
 
@@ -286,19 +529,19 @@ impl StrategyFactory {
     /// Create all strategies from preset configurations
     pub fn create_all_strategies(
         state_manager: PoolStateManager,
+    ) -> Vec<(PaperTradingStrategy, Arc<RwLock<TraderMetrics>>)> {
+        Self::create_all_strategies_with(state_manager, DeterminismConfig::default())
+    }
+
+    /// Create all preset strategies under the given seed / clock settings
+    pub fn create_all_strategies_with(
+        state_manager: PoolStateManager,
+        determinism: DeterminismConfig,
     ) -> Vec<(PaperTradingStrategy, Arc<RwLock<TraderMetrics>>)> {
         PaperTradingConfig::all_presets()
             .into_iter()
             .filter(|config| config.enabled)
-            .map(|config| {
-                let metrics = Arc::new(RwLock::new(TraderMetrics::new(config.name.clone())));
-                let strategy = PaperTradingStrategy::new(
-                    config,
-                    state_manager.clone(),
-                    Arc::clone(&metrics),
-                );
-                (strategy, metrics)
-            })
+            .map(|config| Self::create_strategy_with(config, state_manager.clone(), determinism))
             .collect()
     }
 
@@ -307,11 +550,60 @@ impl StrategyFactory {
         config: PaperTradingConfig,
         state_manager: PoolStateManager,
     ) -> (PaperTradingStrategy, Arc<RwLock<TraderMetrics>>) {
-        let metrics = Arc::new(RwLock::new(TraderMetrics::new(config.name.clone())));
-        let strategy = PaperTradingStrategy::new(
+        Self::create_strategy_with(config, state_manager, DeterminismConfig::default())
+    }
+
+    /// Create the strategy a configuration asks for: Spread configs as
+    /// PaperTradingStrategy, Parity configs as a ParityStrategy over `bot_config`
+    pub fn create_boxed(
+        config: PaperTradingConfig,
+        bot_config: &BotConfig,
+        state_manager: PoolStateManager,
+        determinism: DeterminismConfig,
+    ) -> (Box<dyn Strategy<PoolUpdateEvent, SimulatedTradeAction>>, Arc<RwLock<TraderMetrics>>) {
+        match config.strategy {
+            StrategyKind::Spread => {
+                let (strategy, metrics) = Self::create_strategy_with(config, state_manager, determinism);
+                (Box::new(strategy), metrics)
+            }
+            StrategyKind::Parity => {
+                let (strategy, metrics) =
+                    Self::create_parity_strategy(config, bot_config.clone(), state_manager, determinism);
+                (Box::new(strategy), metrics)
+            }
+        }
+    }
+
+    /// Create a live-parity strategy running the bot's own detector config
+    pub fn create_parity_strategy(
+        config: PaperTradingConfig,
+        bot_config: BotConfig,
+        state_manager: PoolStateManager,
+        determinism: DeterminismConfig,
+    ) -> (ParityStrategy, Arc<RwLock<TraderMetrics>>) {
+        let metrics = Arc::new(RwLock::new(TraderMetrics::new_at(
+            config.name.clone(),
+            determinism.start_time(),
+        )));
+        let strategy = ParityStrategy::new(config, bot_config, state_manager, Arc::clone(&metrics), determinism);
+        (strategy, metrics)
+    }
+
+    /// Create a single strategy under the given seed / clock settings
+    pub fn create_strategy_with(
+        config: PaperTradingConfig,
+        state_manager: PoolStateManager,
+        determinism: DeterminismConfig,
+    ) -> (PaperTradingStrategy, Arc<RwLock<TraderMetrics>>) {
+        let metrics = Arc::new(RwLock::new(TraderMetrics::new_at(
+            config.name.clone(),
+            determinism.start_time(),
+        )));
+        let strategy = PaperTradingStrategy::with_determinism(
             config,
             state_manager,
             Arc::clone(&metrics),
+            determinism,
         );
         (strategy, metrics)
     }
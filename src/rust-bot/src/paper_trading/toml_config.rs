@@ -5,8 +5,11 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - seed / logical_clock general settings
+//! Modified: 2026-10-16 - per-strategy `kind` (spread / parity)
 
-use super::config::PaperTradingConfig;
+use super::config::{PaperTradingConfig, StrategyKind};
+use super::determinism::DeterminismConfig;
 use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::path::Path;
@@ -31,6 +34,22 @@ pub struct GeneralConfig {
     pub metrics_interval_secs: u64,
     #[serde(default = "default_max_state_age")]
     pub max_state_age_secs: i64,
+    /// Global RNG seed (unset = seeded from wall clock)
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Count simulated latency in events instead of sleeping
+    #[serde(default)]
+    pub logical_clock: bool,
+}
+
+impl GeneralConfig {
+    /// Seed / clock settings for the randomized components
+    pub fn determinism(&self) -> DeterminismConfig {
+        DeterminismConfig {
+            seed: self.seed,
+            logical_clock: self.logical_clock,
+        }
+    }
 }
 
 fn default_poll_interval() -> u64 { 100 }
@@ -44,6 +63,9 @@ pub struct StrategyConfig {
     pub name: String,
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// "spread" (default) or "parity" (live detector; the thresholds below are unused)
+    #[serde(default)]
+    pub kind: StrategyKind,
     pub pairs: Vec<String>,
     pub min_profit_usd: f64,
     pub max_trade_size_usd: f64,
@@ -96,6 +118,7 @@ impl StrategyConfig {
         PaperTradingConfig {
             name: self.name.clone(),
             enabled: self.enabled,
+            strategy: self.kind,
             pairs: self.pairs.clone(),
             min_profit_usd: self.min_profit_usd,
             max_trade_size_usd: self.max_trade_size_usd,
@@ -136,5 +159,6 @@ max_slippage_percent = 0.5
         let config: TomlConfig = toml::from_str(toml_str).unwrap();
         assert_eq!(config.strategies.len(), 1);
         assert_eq!(config.strategies[0].name, "Test Strategy");
+        assert_eq!(config.general.determinism(), DeterminismConfig::default());
     }
 }
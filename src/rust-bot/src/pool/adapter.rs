@@ -0,0 +1,386 @@
+//! Pool Adapter — one trait per AMM flavor for the detector
+//!
+//! The detector compares pools by price, fee, depth and swap output; it
+//! should not care whether those come from reserves, ticks or a StableSwap
+//! invariant. `PoolAdapter` is that seam: PoolStateManager hands out boxed
+//! adapters for a pair (adapters_for_pair) and the detector's unified and
+//! triangular scans iterate over them without matching on concrete structs.
+//!
+//! Implemented for:
+//!   V3PoolState    Uniswap / SushiSwap V3 and QuickSwap V3 (Algebra: dynamic
+//!                  fee from globalState, same state otherwise); KyberSwap
+//!                  Elastic (getPoolState, detection-only)
+//!   PoolState      V2 constant product (0.30%)
+//!   CurvePoolState Curve StableSwap (detection-only)
+//!   DodoPoolState  DODO V2 PMM (legacy two-tx execution only)
+//!
+//! A new protocol is one impl here plus its storage in PoolStateManager.
+//! `sync` is generic over the provider, so it is only callable on the
+//! concrete type (syncers), not through `dyn PoolAdapter`.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - spot_price_x96 / fee_wad: exact price and fee for the detector's spread
+//! Modified: 2026-10-16 - DodoPoolState (PMM curve)
+
+use super::calculator::{v3_amount_out, PriceCalculator};
+use super::curve_syncer::CurvePoolSyncer;
+use super::dodo_syncer::DodoPoolSyncer;
+use super::fixed_point::{fee_tier_wad, PriceX96};
+use super::v2_syncer::V2PoolSyncer;
+use super::v3_syncer::refresh_v3_pool;
+use crate::types::{CurvePoolState, DexType, DodoPoolState, PoolState, PoolStateSnapshot, TradingPair, V3PoolState};
+use async_trait::async_trait;
+use ethers::prelude::*;
+use std::sync::Arc;
+use tracing::warn;
+
+/// V2 constant-product swap fee (fraction)
+const V2_FEE_FRACTION: f64 = 0.003;
+
+/// V2 fee in whitelist fee-tier units (the V2 entry in the whitelist JSON)
+const V2_FEE_TIER: u32 = 3000;
+
+/// A pool as the detector sees it
+#[async_trait]
+pub trait PoolAdapter: Send + Sync {
+    fn dex(&self) -> DexType;
+    fn address(&self) -> Address;
+    fn pair(&self) -> &TradingPair;
+    /// (token0, token1) decimals
+    fn decimals(&self) -> (u8, u8);
+
+    /// token1 per token0 in human units (decimal-adjusted, before the fee)
+    fn spot_price(&self) -> f64;
+
+    /// token1 per token0 in raw units, Q64.96 rounded down (zero = no price)
+    fn spot_price_x96(&self) -> PriceX96;
+
+    /// Single-swap fee as a fraction (0.0005 = 0.05%)
+    fn fee_fraction(&self) -> f64;
+
+    /// Fee in hundredths of a bip, the whitelist's fee-tier unit
+    fn fee_tier(&self) -> u32;
+
+    /// Single-swap fee in WAD (1e18 = 100%), never rounded down
+    fn fee_wad(&self) -> U256 {
+        fee_tier_wad(self.fee_tier())
+    }
+
+    /// Depth proxy for the liquidity floor checks (protocol-specific units)
+    fn liquidity(&self) -> u128;
+
+    /// Raw output for `amount_in` raw of `token_in` against the current
+    /// state, fee included. None when the adapter has no depth model or the
+    /// pool can't fill the trade.
+    fn amount_out(&self, amount_in: U256, token_in: Address) -> Option<U256>;
+
+    /// Raw state for the opportunity's post-mortem snapshot
+    fn snapshot(&self, captured_at: u64) -> PoolStateSnapshot;
+
+    /// Re-read the pool's mutable state from chain. None = a read failed (logged).
+    async fn sync<M: Middleware + 'static>(&self, provider: Arc<M>) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+#[async_trait]
+impl PoolAdapter for V3PoolState {
+    fn dex(&self) -> DexType {
+        self.dex
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn pair(&self) -> &TradingPair {
+        &self.pair
+    }
+
+    fn decimals(&self) -> (u8, u8) {
+        (self.token0_decimals, self.token1_decimals)
+    }
+
+    fn spot_price(&self) -> f64 {
+        self.price()
+    }
+
+    fn spot_price_x96(&self) -> PriceX96 {
+        self.price_x96()
+    }
+
+    fn fee_fraction(&self) -> f64 {
+        self.fee as f64 / 1_000_000.0
+    }
+
+    fn fee_tier(&self) -> u32 {
+        self.fee
+    }
+
+    fn liquidity(&self) -> u128 {
+        self.liquidity
+    }
+
+    /// Within the current tick (no tick crossing)
+    fn amount_out(&self, amount_in: U256, token_in: Address) -> Option<U256> {
+        let zero_for_one = token_in == self.pair.token0;
+        v3_amount_out(self.sqrt_price_x96, self.liquidity, self.fee, amount_in, zero_for_one).filter(|out| !out.is_zero())
+    }
+
+    fn snapshot(&self, captured_at: u64) -> PoolStateSnapshot {
+        PoolStateSnapshot::from_v3(self, captured_at)
+    }
+
+    async fn sync<M: Middleware + 'static>(&self, provider: Arc<M>) -> Option<Self> {
+        let block = match provider.get_block_number().await {
+            Ok(bn) => bn.as_u64(),
+            Err(e) => {
+                warn!("V3 sync failed for {:?}: block number error: {}", self.address, e);
+                return None;
+            }
+        };
+        refresh_v3_pool(provider, self.clone(), block).await
+    }
+}
+
+#[async_trait]
+impl PoolAdapter for PoolState {
+    fn dex(&self) -> DexType {
+        self.dex
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn pair(&self) -> &TradingPair {
+        &self.pair
+    }
+
+    fn decimals(&self) -> (u8, u8) {
+        (self.token0_decimals, self.token1_decimals)
+    }
+
+    /// price_adjusted(), NOT price() (raw reserve ratio without decimals)
+    fn spot_price(&self) -> f64 {
+        self.price_adjusted()
+    }
+
+    fn spot_price_x96(&self) -> PriceX96 {
+        self.price_x96()
+    }
+
+    fn fee_fraction(&self) -> f64 {
+        V2_FEE_FRACTION
+    }
+
+    fn fee_tier(&self) -> u32 {
+        V2_FEE_TIER
+    }
+
+    /// Smaller raw reserve. V2 pools are pre-verified by verify_v2_pools.py
+    /// (whitelist v2_ready), so this is a rough floor check only.
+    fn liquidity(&self) -> u128 {
+        std::cmp::min(self.reserve0.low_u128(), self.reserve1.low_u128())
+    }
+
+    fn amount_out(&self, amount_in: U256, token_in: Address) -> Option<U256> {
+        let (reserve_in, reserve_out) = if token_in == self.pair.token0 {
+            (self.reserve0, self.reserve1)
+        } else {
+            (self.reserve1, self.reserve0)
+        };
+        let out = PriceCalculator::get_amount_out(amount_in, reserve_in, reserve_out);
+        (!out.is_zero()).then_some(out)
+    }
+
+    fn snapshot(&self, captured_at: u64) -> PoolStateSnapshot {
+        PoolStateSnapshot::from_v2(self, captured_at)
+    }
+
+    async fn sync<M: Middleware + 'static>(&self, provider: Arc<M>) -> Option<Self> {
+        V2PoolSyncer::new(provider).sync_reserves(self).await
+    }
+}
+
+#[async_trait]
+impl PoolAdapter for CurvePoolState {
+    fn dex(&self) -> DexType {
+        self.dex
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn pair(&self) -> &TradingPair {
+        &self.pair
+    }
+
+    fn decimals(&self) -> (u8, u8) {
+        (self.token0_decimals, self.token1_decimals)
+    }
+
+    /// Marginal get_dy price before the fee
+    fn spot_price(&self) -> f64 {
+        self.price_adjusted()
+    }
+
+    fn spot_price_x96(&self) -> PriceX96 {
+        self.price_x96()
+    }
+
+    fn fee_fraction(&self) -> f64 {
+        self.fee_tier() as f64 / 1_000_000.0
+    }
+
+    fn fee_tier(&self) -> u32 {
+        CurvePoolState::fee_tier(self)
+    }
+
+    /// fee() is at 1e10: exact in WAD, where fee_tier() truncates
+    fn fee_wad(&self) -> U256 {
+        self.fee * U256::exp10(8)
+    }
+
+    fn liquidity(&self) -> u128 {
+        CurvePoolState::liquidity(self)
+    }
+
+    /// None: Curve routes are detection-only and keep the flat slippage
+    /// estimate (get_amount_out() has the exact get_dy when needed)
+    fn amount_out(&self, _amount_in: U256, _token_in: Address) -> Option<U256> {
+        None
+    }
+
+    fn snapshot(&self, captured_at: u64) -> PoolStateSnapshot {
+        PoolStateSnapshot::from_curve(self, captured_at)
+    }
+
+    async fn sync<M: Middleware + 'static>(&self, provider: Arc<M>) -> Option<Self> {
+        CurvePoolSyncer::new(provider).sync_state(self).await
+    }
+}
+
+#[async_trait]
+impl PoolAdapter for DodoPoolState {
+    fn dex(&self) -> DexType {
+        self.dex
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn pair(&self) -> &TradingPair {
+        &self.pair
+    }
+
+    fn decimals(&self) -> (u8, u8) {
+        (self.token0_decimals, self.token1_decimals)
+    }
+
+    /// getMidPrice() before the fee
+    fn spot_price(&self) -> f64 {
+        self.price_adjusted()
+    }
+
+    fn spot_price_x96(&self) -> PriceX96 {
+        self.price_x96()
+    }
+
+    fn fee_fraction(&self) -> f64 {
+        self.pmm.fee_fraction()
+    }
+
+    fn fee_tier(&self) -> u32 {
+        DodoPoolState::fee_tier(self)
+    }
+
+    /// Fee rates are already WAD: exact, where fee_tier() truncates
+    fn fee_wad(&self) -> U256 {
+        self.pmm.lp_fee_rate + self.pmm.mt_fee_rate
+    }
+
+    fn liquidity(&self) -> u128 {
+        DodoPoolState::liquidity(self)
+    }
+
+    fn amount_out(&self, amount_in: U256, token_in: Address) -> Option<U256> {
+        let out = self.get_amount_out(amount_in, token_in);
+        (!out.is_zero()).then_some(out)
+    }
+
+    fn snapshot(&self, captured_at: u64) -> PoolStateSnapshot {
+        PoolStateSnapshot::from_dodo(self, captured_at)
+    }
+
+    async fn sync<M: Middleware + 'static>(&self, provider: Arc<M>) -> Option<Self> {
+        DodoPoolSyncer::new(provider).sync_state(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mempool::ticks::sqrt_ratio_at_tick;
+    use crate::types::PoolStateKind;
+
+    fn pair() -> TradingPair {
+        TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string())
+    }
+
+    #[test]
+    fn test_adapters_match_snapshot_math() {
+        let v3 = V3PoolState {
+            address: Address::from_low_u64_be(0x11),
+            dex: DexType::QuickswapV3,
+            pair: pair(),
+            sqrt_price_x96: sqrt_ratio_at_tick(200_000).unwrap(),
+            tick: 200_000,
+            fee: 900,
+            liquidity: 10u128.pow(18),
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 100,
+        };
+        let v2 = PoolState {
+            address: Address::from_low_u64_be(0x21),
+            dex: DexType::QuickSwapV2,
+            pair: pair(),
+            reserve0: U256::from(2_000_000_000_000u64),
+            reserve1: U256::from(10u128.pow(21)),
+            last_updated: 100,
+            token0_decimals: 6,
+            token1_decimals: 18,
+        };
+        let adapters: Vec<Box<dyn PoolAdapter>> = vec![Box::new(v3.clone()), Box::new(v2.clone())];
+        let amount = U256::from(500_000_000u64);
+        for adapter in &adapters {
+            let snapshot = adapter.snapshot(0);
+            for (token_in, zero_for_one) in [(pair().token0, true), (pair().token1, false)] {
+                assert_eq!(
+                    adapter.amount_out(amount, token_in),
+                    crate::pool::calculator::snapshot_amount_out(&snapshot, amount, zero_for_one),
+                    "{:?}", adapter.dex()
+                );
+            }
+        }
+
+        assert_eq!(adapters[0].fee_fraction(), 0.0009);
+        assert_eq!(adapters[0].spot_price(), v3.price());
+        assert!(matches!(adapters[0].snapshot(7).state, PoolStateKind::V3 { tick: 200_000, .. }));
+        assert_eq!((adapters[1].fee_fraction(), adapters[1].fee_tier()), (0.003, 3000));
+        assert_eq!(adapters[1].spot_price(), v2.price_adjusted());
+        assert_eq!(adapters[1].liquidity(), 2_000_000_000_000);
+
+        // Exact prices are raw: × 10^(dec0 - dec1) gives the human price back
+        for adapter in &adapters {
+            let human = adapter.spot_price_x96().to_f64() * 1e-12;
+            assert!((human / adapter.spot_price() - 1.0).abs() < 1e-9, "{:?}", adapter.dex());
+        }
+        assert_eq!(adapters[0].fee_wad(), U256::from(900) * U256::exp10(12));
+        assert_eq!(adapters[1].fee_wad(), U256::exp10(15) * 3);
+    }
+}
@@ -3,14 +3,26 @@
 //! Utilities for calculating prices, slippage, and trade amounts
 //! from pool reserves using constant product formula (x * y = k).
 //!
+//! V3 within-tick math (Uniswap SqrtPriceMath) is shared here by the mempool
+//! simulator and the detector's size-adjusted profit estimate.
+//!
+//! DODO V2 PMM math (PMMPricing.sol / DODOMath.sol) is here too: sell-base /
+//! sell-quote output and mid price from a pool's getPMMStateForCall().
+//!
 //! Author: AI-Generated
 //! Created: 2026-01-27
+//! Modified: 2026-10-16 - SqrtPriceMath helpers (from mempool::simulator) + leg output at trade size
+//! Modified: 2026-10-16 - DODO V2 PMM curve: amount out + mid price
 
 use crate::pool::PoolStateManager;
-use crate::types::{DexType, PoolState};
+use crate::types::{DexType, PoolState, PoolStateKind, PoolStateSnapshot};
 use ethers::types::{Address, U256};
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
+/// Q96 = 2^96, used in sqrtPriceX96 math
+pub const Q96: u128 = 1u128 << 96;
+
 /// Price calculator for DEX pools
 pub struct PriceCalculator {
     state_manager: PoolStateManager,
@@ -188,6 +200,378 @@ impl PriceCalculator {
     }
 }
 
+// ── V3 Math Helpers (from Uniswap SqrtPriceMath.sol) ─────────────────────────
+
+/// getNextSqrtPriceFromAmount0RoundingUp
+///
+/// When adding token0 (zeroForOne), sqrtPrice decreases.
+///
+/// Primary formula (precise):
+///   result = ceil(numerator1 * sqrtPX96 / (numerator1 + amount * sqrtPX96))
+///   where numerator1 = liquidity << 96
+///
+/// Fallback (avoids overflow in numerator1 * sqrtPX96):
+///   result = ceil(numerator1 / (numerator1 / sqrtPX96 + amount))
+///
+/// Mirrors Uniswap SqrtPriceMath.sol with FullMath overflow fallback.
+pub fn get_next_sqrt_price_from_amount0(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount: U256,
+) -> Option<U256> {
+    if amount.is_zero() {
+        return Some(sqrt_price_x96);
+    }
+    if sqrt_price_x96.is_zero() {
+        return None;
+    }
+
+    let liquidity_u256 = U256::from(liquidity);
+    // numerator1 = liquidity << 96 (safe: u128 << 96 fits in U256)
+    let numerator1 = liquidity_u256 << 96;
+
+    // Try precise formula first: ceil(numerator1 * sqrtPX96 / denominator)
+    if let Some(product) = amount.checked_mul(sqrt_price_x96) {
+        if let Some(denominator) = numerator1.checked_add(product) {
+            if !denominator.is_zero() {
+                if let Some(full_num) = numerator1.checked_mul(sqrt_price_x96) {
+                    let result = (full_num + denominator - U256::one()) / denominator;
+                    if !result.is_zero() {
+                        return Some(result);
+                    }
+                }
+            }
+        }
+    }
+
+    // Fallback: ceil(numerator1 / (numerator1 / sqrtPX96 + amount))
+    // Avoids the large numerator1 * sqrtPX96 intermediate product.
+    let quotient = numerator1 / sqrt_price_x96;
+    let denominator = quotient.checked_add(amount)?;
+    if denominator.is_zero() {
+        return None;
+    }
+    let result = (numerator1 + denominator - U256::one()) / denominator;
+    if result.is_zero() {
+        return None;
+    }
+    Some(result)
+}
+
+/// getNextSqrtPriceFromAmount1RoundingDown
+///
+/// When adding token1 (oneForZero), sqrtPrice increases.
+///
+/// Formula:
+///   quotient = (amount << 96) / liquidity
+///   result = sqrtPriceX96 + quotient
+pub fn get_next_sqrt_price_from_amount1(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount: U256,
+) -> Option<U256> {
+    if amount.is_zero() {
+        return Some(sqrt_price_x96);
+    }
+
+    let liquidity_u256 = U256::from(liquidity);
+    // quotient = (amount << 96) / liquidity
+    let shifted = amount.checked_mul(U256::from(Q96))?;
+    let quotient = shifted.checked_div(liquidity_u256)?;
+
+    // result = sqrtPriceX96 + quotient
+    let result = sqrt_price_x96.checked_add(quotient)?;
+
+    Some(result)
+}
+
+/// getAmount0Delta rounding down (amount paid out):
+///   floor(numerator1 / lower) - ceil(numerator1 / upper)
+pub fn amount0_delta_rounding_down(lower: U256, upper: U256, liquidity: u128) -> Option<U256> {
+    if lower.is_zero() || lower > upper {
+        return None;
+    }
+    let numerator1 = U256::from(liquidity) << 96;
+    let at_upper = (numerator1 + upper - U256::one()) / upper;
+    Some((numerator1 / lower).saturating_sub(at_upper))
+}
+
+/// getAmount1Delta rounding down (amount paid out):
+///   floor(liquidity * (upper - lower) / 2^96)
+pub fn amount1_delta_rounding_down(lower: U256, upper: U256, liquidity: u128) -> Option<U256> {
+    let diff = upper.checked_sub(lower)?;
+    Some(U256::from(liquidity).checked_mul(diff)? >> 96)
+}
+
+/// V3 exact-input output with the in-range liquidity held constant: exact
+/// while the swap stays inside the current tick range, optimistic on a thin
+/// pool's depth beyond it. None when amount, liquidity or price is zero or
+/// the math overflows.
+pub fn v3_amount_out(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    fee: u32,
+    amount_in: U256,
+    zero_for_one: bool,
+) -> Option<U256> {
+    if amount_in.is_zero() || liquidity == 0 || sqrt_price_x96.is_zero() {
+        return None;
+    }
+    // V3 fee is in millionths: 500 = 0.05%
+    let amount_after_fee = amount_in
+        .checked_mul(U256::from(1_000_000u32.checked_sub(fee)?))?
+        / U256::from(1_000_000u32);
+    if zero_for_one {
+        let next = get_next_sqrt_price_from_amount0(sqrt_price_x96, liquidity, amount_after_fee)?;
+        amount1_delta_rounding_down(next, sqrt_price_x96, liquidity)
+    } else {
+        let next = get_next_sqrt_price_from_amount1(sqrt_price_x96, liquidity, amount_after_fee)?;
+        amount0_delta_rounding_down(sqrt_price_x96, next, liquidity)
+    }
+}
+
+// ── DODO V2 PMM Math (from PMMPricing.sol / DODOMath.sol) ────────────────────
+//
+// All rates are 1e18 fixed point (DecimalMath.ONE). `i` is the oracle price,
+// raw quote per raw base × 1e18; `k` is the slippage factor in [0, 1e18]
+// (0 = constant price, 1e18 = constant product around the targets).
+
+/// DecimalMath.ONE
+const PMM_ONE: u128 = 1_000_000_000_000_000_000;
+
+/// PMM R state: where the pool sits relative to its targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PmmRState {
+    /// B = B0, Q = Q0
+    One,
+    /// Base sold to the pool's buyers: B < B0, Q > Q0
+    AboveOne,
+    /// Base bought from the pool's sellers: B > B0, Q < Q0
+    BelowOne,
+}
+
+impl PmmRState {
+    /// The uint32 R from getPMMStateForCall() (0 ONE, 1 ABOVE_ONE, 2 BELOW_ONE)
+    pub fn from_u32(r: u32) -> Option<Self> {
+        match r {
+            0 => Some(PmmRState::One),
+            1 => Some(PmmRState::AboveOne),
+            2 => Some(PmmRState::BelowOne),
+            _ => None,
+        }
+    }
+}
+
+/// PMM state of a DODO V2 pool (DVM / DPP / DSP), targets already adjusted
+/// as getPMMStateForCall() returns them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PmmState {
+    /// _I_: raw quote per raw base × 1e18
+    pub i: U256,
+    /// _K_: 1e18 fixed point
+    pub k: U256,
+    /// _BASE_RESERVE_
+    pub b: U256,
+    /// _QUOTE_RESERVE_
+    pub q: U256,
+    /// Base target B0
+    pub b0: U256,
+    /// Quote target Q0
+    pub q0: U256,
+    pub r: PmmRState,
+    /// LP fee rate, 1e18 fixed point (charged on the output)
+    pub lp_fee_rate: U256,
+    /// Maintainer fee rate, 1e18 fixed point (charged on the output)
+    pub mt_fee_rate: U256,
+}
+
+impl PmmState {
+    /// Total fee rate (LP + maintainer) as a fraction
+    pub fn fee_fraction(&self) -> f64 {
+        (self.lp_fee_rate + self.mt_fee_rate).low_u128() as f64 / PMM_ONE as f64
+    }
+}
+
+fn pmm_one() -> U256 {
+    U256::from(PMM_ONE)
+}
+
+/// DecimalMath.mulFloor
+fn mul_floor(a: U256, b: U256) -> Option<U256> {
+    Some(a.checked_mul(b)? / pmm_one())
+}
+
+/// DecimalMath.divFloor
+fn div_floor(a: U256, b: U256) -> Option<U256> {
+    a.checked_mul(pmm_one())?.checked_div(b)
+}
+
+/// DecimalMath.divCeil
+fn div_ceil(a: U256, b: U256) -> Option<U256> {
+    let numerator = a.checked_mul(pmm_one())?;
+    let quotient = numerator.checked_div(b)?;
+    Some(if quotient * b < numerator { quotient + 1 } else { quotient })
+}
+
+/// DecimalMath.reciprocalFloor: 1e36 / target
+fn reciprocal_floor(target: U256) -> Option<U256> {
+    (pmm_one() * pmm_one()).checked_div(target)
+}
+
+/// DODOMath._GeneralIntegrate: output for moving the pay side from V2 to V1
+/// against target V0, i·(V1 - V2)·(1 - k + k·V0²/(V1·V2))
+fn general_integrate(v0: U256, v1: U256, v2: U256, i: U256, k: U256) -> Option<U256> {
+    if v0.is_zero() || v2.is_zero() || k > pmm_one() {
+        return None;
+    }
+    let fair_amount = i.checked_mul(v1.checked_sub(v2)?)?;
+    if k.is_zero() {
+        return Some(fair_amount / pmm_one());
+    }
+    let v0v0v1v2 = div_floor(v0.checked_mul(v0)? / v1, v2)?;
+    let penalty = mul_floor(k, v0v0v1v2)?;
+    Some((pmm_one() - k).checked_add(penalty)?.checked_mul(fair_amount)? / (pmm_one() * pmm_one()))
+}
+
+/// DODOMath._SolveQuadraticFunctionForTrade: output side's decrease from V1
+/// (target V0) when `delta` is paid in on the other side at price `i`
+fn solve_quadratic_for_trade(v0: U256, v1: U256, delta: U256, i: U256, k: U256) -> Option<U256> {
+    if v0.is_zero() || k > pmm_one() {
+        return None;
+    }
+    if delta.is_zero() {
+        return Some(U256::zero());
+    }
+    if k.is_zero() {
+        return Some(mul_floor(i, delta)?.min(v1));
+    }
+    if k == pmm_one() {
+        // Q2 = Q1 / (1 + i·ΔB·Q1/Q0²): Q1 - Q2 = Q1·temp / (1 + temp)
+        let idelta = i.checked_mul(delta)?;
+        let temp = if idelta.is_zero() {
+            U256::zero()
+        } else if let Some(product) = idelta.checked_mul(v1) {
+            product / v0.checked_mul(v0)?
+        } else {
+            delta.checked_mul(v1)? / v0 * i / v0
+        };
+        return Some(v1.checked_mul(temp)? / temp.checked_add(pmm_one())?);
+    }
+
+    // b = k·Q0²/Q1 - i·ΔB - (1-k)·Q1, solved for Q2 by the quadratic formula
+    let part2 = k.checked_mul(v0)?.checked_div(v1)?.checked_mul(v0)?.checked_add(i.checked_mul(delta)?)?;
+    let mut b_abs = (pmm_one() - k).checked_mul(v1)?;
+    let b_sig = if b_abs >= part2 {
+        b_abs -= part2;
+        false
+    } else {
+        b_abs = part2 - b_abs;
+        true
+    };
+    b_abs /= pmm_one();
+
+    // sqrt(b² + 4(1-k)·k·Q0²)
+    let four_ac = mul_floor((pmm_one() - k) * 4, mul_floor(k, v0)?.checked_mul(v0)?)?;
+    let square_root = b_abs.checked_mul(b_abs)?.checked_add(four_ac)?.integer_sqrt();
+
+    let denominator = (pmm_one() - k) * 2;
+    let numerator = if b_sig { square_root.checked_sub(b_abs)? } else { b_abs.checked_add(square_root)? };
+    let v2 = div_ceil(numerator, denominator)?;
+    Some(v1.saturating_sub(v2))
+}
+
+/// PMMPricing.sellBaseToken: quote received for `pay_base`, before fees
+fn pmm_sell_base(state: &PmmState, pay_base: U256) -> Option<U256> {
+    match state.r {
+        PmmRState::One => solve_quadratic_for_trade(state.q0, state.q0, pay_base, state.i, state.k),
+        PmmRState::AboveOne => {
+            let back_to_one_pay_base = state.b0.checked_sub(state.b)?;
+            let back_to_one_receive_quote = state.q.checked_sub(state.q0)?;
+            if pay_base < back_to_one_pay_base {
+                let receive = general_integrate(state.b0, state.b.checked_add(pay_base)?, state.b, state.i, state.k)?;
+                Some(receive.min(back_to_one_receive_quote))
+            } else if pay_base == back_to_one_pay_base {
+                Some(back_to_one_receive_quote)
+            } else {
+                let rest = solve_quadratic_for_trade(state.q0, state.q0, pay_base - back_to_one_pay_base, state.i, state.k)?;
+                back_to_one_receive_quote.checked_add(rest)
+            }
+        }
+        PmmRState::BelowOne => solve_quadratic_for_trade(state.q0, state.q, pay_base, state.i, state.k),
+    }
+}
+
+/// PMMPricing.sellQuoteToken: base received for `pay_quote`, before fees
+fn pmm_sell_quote(state: &PmmState, pay_quote: U256) -> Option<U256> {
+    let i_inverse = reciprocal_floor(state.i)?;
+    match state.r {
+        PmmRState::One => solve_quadratic_for_trade(state.b0, state.b0, pay_quote, i_inverse, state.k),
+        PmmRState::AboveOne => solve_quadratic_for_trade(state.b0, state.b, pay_quote, i_inverse, state.k),
+        PmmRState::BelowOne => {
+            let back_to_one_pay_quote = state.q0.checked_sub(state.q)?;
+            let back_to_one_receive_base = state.b.checked_sub(state.b0)?;
+            if pay_quote < back_to_one_pay_quote {
+                let receive = general_integrate(state.q0, state.q.checked_add(pay_quote)?, state.q, i_inverse, state.k)?;
+                Some(receive.min(back_to_one_receive_base))
+            } else if pay_quote == back_to_one_pay_quote {
+                Some(back_to_one_receive_base)
+            } else {
+                let rest = solve_quadratic_for_trade(state.b0, state.b0, pay_quote - back_to_one_pay_quote, i_inverse, state.k)?;
+                back_to_one_receive_base.checked_add(rest)
+            }
+        }
+    }
+}
+
+/// Output of a DODO V2 swap as querySellBase / querySellQuote compute it:
+/// the PMM curve's output less the LP and maintainer fees (both charged on
+/// the output). None when the state is degenerate or the math overflows.
+pub fn pmm_amount_out(state: &PmmState, amount_in: U256, sell_base: bool) -> Option<U256> {
+    let receive = if sell_base {
+        pmm_sell_base(state, amount_in)?
+    } else {
+        pmm_sell_quote(state, amount_in)?
+    };
+    let fees = mul_floor(receive, state.lp_fee_rate)?.checked_add(mul_floor(receive, state.mt_fee_rate)?)?;
+    Some(receive.saturating_sub(fees))
+}
+
+/// PMMPricing.getMidPrice: marginal raw quote per raw base × 1e18, before fees
+pub fn pmm_mid_price(state: &PmmState) -> Option<U256> {
+    if state.k > pmm_one() {
+        return None;
+    }
+    if state.r == PmmRState::BelowOne {
+        let ratio = div_floor(state.q0.checked_mul(state.q0)?.checked_div(state.q)?, state.q)?;
+        let r = (pmm_one() - state.k).checked_add(mul_floor(state.k, ratio)?)?;
+        div_floor(state.i, r)
+    } else {
+        let ratio = div_floor(state.b0.checked_mul(state.b0)?.checked_div(state.b)?, state.b)?;
+        let r = (pmm_one() - state.k).checked_add(mul_floor(state.k, ratio)?)?;
+        mul_floor(state.i, r)
+    }
+}
+
+/// Output of swapping `amount_in` through a snapshotted pool: V3 by
+/// v3_amount_out(), V2 by the constant-product formula, DODO by the PMM
+/// curve. None for Curve (coin indices aren't in the snapshot) or when the
+/// pool can't fill it.
+pub fn snapshot_amount_out(snapshot: &PoolStateSnapshot, amount_in: U256, zero_for_one: bool) -> Option<U256> {
+    let out = match &snapshot.state {
+        PoolStateKind::V3 { sqrt_price_x96, liquidity, .. } => {
+            v3_amount_out(*sqrt_price_x96, *liquidity, snapshot.fee, amount_in, zero_for_one)?
+        }
+        PoolStateKind::V2 { reserve0, reserve1 } => {
+            let (reserve_in, reserve_out) = if zero_for_one { (*reserve0, *reserve1) } else { (*reserve1, *reserve0) };
+            PriceCalculator::get_amount_out(amount_in, reserve_in, reserve_out)
+        }
+        PoolStateKind::Dodo { pmm, base_is_token0 } => pmm_amount_out(pmm, amount_in, zero_for_one == *base_is_token0)?,
+        PoolStateKind::Curve { .. } => return None,
+    };
+    (!out.is_zero()).then_some(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -235,6 +619,112 @@ mod tests {
         assert!(impact < 15.0);
     }
 
+    #[test]
+    fn test_v3_amount_out_within_tick() {
+        // sqrtPriceX96 = 2^96: price 1.0 raw, 0.05% fee
+        let sqrt = U256::from(Q96);
+        let amount = U256::from(1_000_000u64);
+
+        // Deep range: output is the input less the fee
+        let deep = v3_amount_out(sqrt, 10u128.pow(24), 500, amount, true).unwrap();
+        assert!(deep <= U256::from(999_500u64) && deep >= U256::from(999_499u64));
+        let deep = v3_amount_out(sqrt, 10u128.pow(24), 500, amount, false).unwrap();
+        assert!(deep <= U256::from(999_500u64) && deep >= U256::from(999_499u64));
+
+        // Thin range: out = x·L/(L + x) ≈ 10/11 of L when x ≈ 10·L
+        let thin = v3_amount_out(sqrt, 100_000, 500, amount, true).unwrap();
+        assert!(thin < U256::from(100_000u64) && thin > U256::from(90_000u64));
+
+        assert!(v3_amount_out(sqrt, 0, 500, amount, true).is_none());
+        assert!(v3_amount_out(U256::zero(), 1, 500, amount, true).is_none());
+    }
+
+    fn pmm(i: u128, k: u128, b: u128, q: u128, b0: u128, q0: u128, r: PmmRState) -> PmmState {
+        PmmState {
+            i: U256::from(i),
+            k: U256::from(k),
+            b: U256::from(b),
+            q: U256::from(q),
+            b0: U256::from(b0),
+            q0: U256::from(q0),
+            r,
+            lp_fee_rate: U256::zero(),
+            mt_fee_rate: U256::zero(),
+        }
+    }
+
+    const E18: u128 = PMM_ONE;
+
+    #[test]
+    fn test_pmm_k_zero_is_constant_price() {
+        // DODO docs: K = 0 trades at the oracle price i with no slippage.
+        // WETH (18) / USDC (6) at 2000: i = 2000e6 raw quote per 1e18 raw base × 1e18
+        let i = 2_000_000_000u128;
+        let mut state = pmm(i, 0, 10 * E18, 20_000_000_000, 10 * E18, 20_000_000_000, PmmRState::One);
+        assert_eq!(pmm_amount_out(&state, U256::from(E18), true), Some(U256::from(2_000_000_000u64)));
+        assert_eq!(pmm_amount_out(&state, U256::from(2_000_000_000u64), false), Some(U256::from(E18)));
+
+        // 0.3% LP fee + 0.1% maintainer fee, both taken from the output
+        state.lp_fee_rate = U256::from(3 * E18 / 1000);
+        state.mt_fee_rate = U256::from(E18 / 1000);
+        assert_eq!(pmm_amount_out(&state, U256::from(E18), true), Some(U256::from(1_992_000_000u64)));
+        assert!((state.fee_fraction() - 0.004).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_pmm_k_one_is_constant_product() {
+        // DODO docs: K = 1 at R = ONE is Uniswap's x·y = k around the targets.
+        // B0 = Q0 = 1000, i = 1: selling 1000 base returns 1000·1000/2000 = 500 quote
+        let state = pmm(E18, E18, 1000 * E18, 1000 * E18, 1000 * E18, 1000 * E18, PmmRState::One);
+        assert_eq!(pmm_amount_out(&state, U256::from(1000 * E18), true), Some(U256::from(500 * E18)));
+        let v2 = PriceCalculator::get_amount_out(U256::from(100 * E18), U256::from(1000 * E18), U256::from(1000 * E18));
+        let dodo = pmm_amount_out(&state, U256::from(100 * E18), true).unwrap();
+        // Same curve without V2's 0.3% fee: 1000·100/1100 = 90.909...
+        assert!(dodo > v2 && dodo / U256::exp10(15) == U256::from(90_909u64));
+    }
+
+    #[test]
+    fn test_pmm_quadratic_k_half() {
+        // K = 0.5, Q0 = 1000, i = 1, sell 100 base at R = ONE. The curve gives
+        // Q2 = (−b + √(b² + 4(1−k)k·Q0²)) / 2(1−k) with b = k·Q0 + i·ΔB − (1−k)·Q0 = 100:
+        // Q2 = √1010000 − 100 = 904.98756..., so out = 95.01243...
+        let state = pmm(E18, E18 / 2, 1000 * E18, 1000 * E18, 1000 * E18, 1000 * E18, PmmRState::One);
+        let out = pmm_amount_out(&state, U256::from(100 * E18), true).unwrap();
+        assert_eq!(out / U256::exp10(13), U256::from(9_501_243u64));
+        // Slippage sits between the K = 0 (100) and K = 1 (90.909) outputs
+        assert!(out < U256::from(100 * E18) && out > U256::from(90 * E18));
+    }
+
+    #[test]
+    fn test_pmm_above_one_back_to_one() {
+        // R = ABOVE_ONE: the first 100 base return the pool to its targets and
+        // pay exactly Q − Q0; the rest trades on the R = ONE curve
+        let state = pmm(E18, E18, 900 * E18, 1100 * E18, 1000 * E18, 1000 * E18, PmmRState::AboveOne);
+        assert_eq!(pmm_amount_out(&state, U256::from(100 * E18), true), Some(U256::from(100 * E18)));
+        let past = pmm_amount_out(&state, U256::from(1100 * E18), true).unwrap();
+        assert_eq!(past, U256::from(600 * E18)); // 100 + 500 (K = 1 example above)
+        let partial = pmm_amount_out(&state, U256::from(50 * E18), true).unwrap();
+        assert!(partial > U256::from(50 * E18) && partial <= U256::from(100 * E18));
+    }
+
+    #[test]
+    fn test_pmm_mid_price() {
+        // R = ONE: mid price is the oracle price i
+        let state = pmm(2_000_000_000, E18 / 10, E18, 2_000_000_000, E18, 2_000_000_000, PmmRState::One);
+        assert_eq!(pmm_mid_price(&state), Some(U256::from(2_000_000_000u64)));
+
+        // R = BELOW_ONE, K = 1, Q = Q0 / 2: i / (Q0/Q)² = i / 4
+        let below = pmm(E18, E18, 1500 * E18, 500 * E18, 1000 * E18, 1000 * E18, PmmRState::BelowOne);
+        assert_eq!(pmm_mid_price(&below), Some(U256::from(E18 / 4)));
+
+        // R = ABOVE_ONE, K = 0.5, B = B0 / 2: i · (1 − k + k·4) = 2.5 i
+        let above = pmm(E18, E18 / 2, 500 * E18, 1500 * E18, 1000 * E18, 1000 * E18, PmmRState::AboveOne);
+        assert_eq!(pmm_mid_price(&above), Some(U256::from(5 * E18 / 2)));
+
+        assert_eq!(PmmRState::from_u32(2), Some(PmmRState::BelowOne));
+        assert_eq!(PmmRState::from_u32(3), None);
+    }
+
     #[test]
     fn test_get_amount_in() {
         let amount_out = U256::from(1_000_000_000u64); // 1000 USDC
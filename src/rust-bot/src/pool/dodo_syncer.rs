@@ -0,0 +1,156 @@
+//! DODO V2 Pool Synchronization
+//!
+//! Reads whitelisted DODO V2 PMM pools (status "dodo_ready" in the whitelist
+//! JSON, dex "DodoV2"): DVM, DPP and DSP pools share the read interface.
+//! Initial sync discovers _BASE_TOKEN_ / _QUOTE_TOKEN_ and their decimals;
+//! ongoing sync re-reads the PMM state every block (polled regardless of
+//! EVENT_SYNC — DODO pools don't emit V2/V3 Swap logs).
+//!
+//! getPMMStateForCall() carries _I_, _K_, _BASE_RESERVE_, _QUOTE_RESERVE_
+//! and the adjusted targets in one call; getUserFeeRate() the LP and
+//! maintainer fee rates (read for the zero address: the fee model's default).
+//!
+//! Swap math for the synced state lives in pool::calculator (pmm_amount_out).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use super::calculator::{PmmRState, PmmState};
+use crate::types::{DexType, DodoPoolState, TradingPair};
+use anyhow::{anyhow, Context, Result};
+use ethers::prelude::*;
+use futures::future::join_all;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+// DODO V2 pool view functions (DVM / DPP / DSP)
+abigen!(
+    IDodoV2Pool,
+    r#"[
+        function _BASE_TOKEN_() external view returns (address)
+        function _QUOTE_TOKEN_() external view returns (address)
+        function getPMMStateForCall() external view returns (uint256 i, uint256 K, uint256 B, uint256 Q, uint256 B0, uint256 Q0, uint256 R)
+        function getUserFeeRate(address user) external view returns (uint256 lpFeeRate, uint256 mtFeeRate)
+    ]"#
+);
+
+// ERC20 decimals query
+abigen!(
+    IDodoTokenDecimals,
+    r#"[
+        function decimals() external view returns (uint8)
+    ]"#
+);
+
+/// DODO pool syncer — PMM state and fee rates for known pool addresses
+pub struct DodoPoolSyncer<P> {
+    provider: Arc<P>,
+}
+
+impl<P: Middleware + 'static> DodoPoolSyncer<P> {
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+
+    /// Initial sync: discover the pool's base/quote tokens and decimals,
+    /// then its PMM state
+    pub async fn sync_pool_by_address(&self, pool_address: Address) -> Result<DodoPoolState> {
+        let pool = IDodoV2Pool::new(pool_address, Arc::clone(&self.provider));
+        let base = pool.base_token().call().await
+            .with_context(|| format!("DODO sync: _BASE_TOKEN_ failed for {:?}", pool_address))?;
+        let quote = pool.quote_token().call().await
+            .with_context(|| format!("DODO sync: _QUOTE_TOKEN_ failed for {:?}", pool_address))?;
+
+        let decimals_of = |token: Address| {
+            let contract = IDodoTokenDecimals::new(token, Arc::clone(&self.provider));
+            async move {
+                contract.decimals().call().await
+                    .with_context(|| format!("DODO sync: failed to get decimals of {:?}", token))
+            }
+        };
+        let (base_decimals, quote_decimals) = (decimals_of(base).await?, decimals_of(quote).await?);
+
+        let base_is_token0 = base < quote;
+        let (token0, token1) = if base_is_token0 { (base, quote) } else { (quote, base) };
+        let (token0_decimals, token1_decimals) = if base_is_token0 {
+            (base_decimals, quote_decimals)
+        } else {
+            (quote_decimals, base_decimals)
+        };
+
+        let seed = DodoPoolState {
+            address: pool_address,
+            dex: DexType::DodoV2,
+            pair: TradingPair {
+                token0,
+                token1,
+                symbol: String::new(), // Caller sets this from whitelist
+            },
+            base_is_token0,
+            pmm: PmmState {
+                i: U256::zero(),
+                k: U256::zero(),
+                b: U256::zero(),
+                q: U256::zero(),
+                b0: U256::zero(),
+                q0: U256::zero(),
+                r: PmmRState::One,
+                lp_fee_rate: U256::zero(),
+                mt_fee_rate: U256::zero(),
+            },
+            token0_decimals,
+            token1_decimals,
+            last_updated: 0,
+        };
+        let state = self.read_state(&seed).await?;
+
+        debug!(
+            "DODO pool synced: {:?} — base={:?} quote={:?} i={} K={} B={} Q={} R={:?} block={}",
+            pool_address, base, quote, state.pmm.i, state.pmm.k,
+            state.pmm.b, state.pmm.q, state.pmm.r, state.last_updated
+        );
+        Ok(state)
+    }
+
+    /// Parallel sync: refresh the PMM state of all known pools.
+    /// A pool whose read fails keeps its previous state.
+    pub async fn sync_known_pools_parallel(&self, known_pools: &[DodoPoolState]) -> Vec<DodoPoolState> {
+        let results = join_all(known_pools.iter().map(|pool| self.sync_state(pool))).await;
+        results
+            .into_iter()
+            .zip(known_pools)
+            .map(|(result, previous)| result.unwrap_or_else(|| previous.clone()))
+            .collect()
+    }
+
+    /// Refresh one known pool. None = a read failed (logged).
+    pub async fn sync_state(&self, pool: &DodoPoolState) -> Option<DodoPoolState> {
+        match self.read_state(pool).await {
+            Ok(state) => Some(state),
+            Err(e) => {
+                warn!("DODO sync failed for {:?}: {}", pool.address, e);
+                None
+            }
+        }
+    }
+
+    async fn read_state(&self, pool: &DodoPoolState) -> Result<DodoPoolState> {
+        let contract = IDodoV2Pool::new(pool.address, Arc::clone(&self.provider));
+        let state_call = contract.get_pmm_state_for_call();
+        let fee_call = contract.get_user_fee_rate(Address::zero());
+        let (state, fees, block) = futures::join!(state_call.call(), fee_call.call(), self.provider.get_block_number());
+
+        let (i, k, b, q, b0, q0, r) = state.context("getPMMStateForCall() failed")?;
+        let (lp_fee_rate, mt_fee_rate) = fees.context("getUserFeeRate() failed")?;
+        let r = (r.bits() <= 32)
+            .then(|| PmmRState::from_u32(r.low_u32()))
+            .flatten()
+            .ok_or_else(|| anyhow!("unknown PMM R state {}", r))?;
+
+        Ok(DodoPoolState {
+            pmm: PmmState { i, k, b, q, b0, q0, r, lp_fee_rate, mt_fee_rate },
+            last_updated: block.map_err(|e| anyhow!("block number error: {}", e))?.as_u64(),
+            ..pool.clone()
+        })
+    }
+}
@@ -0,0 +1,223 @@
+//! Pool Event Log Filter — the per-block eth_getLogs of event-driven sync (A3)
+//!
+//! One eth_getLogs per block returns every V3 Swap (sqrtPriceX96, liquidity,
+//! tick) and V2 Sync (reserves) of the watched pools. Shared by the main loop
+//! and the RPC benchmark so both issue exactly the same request.
+//!
+//! The same logs sit in our own trades' receipts: apply_receipt_logs() puts
+//! them into PoolStateManager as soon as the receipt is back, so the spread
+//! we just closed isn't re-detected before the next block's eth_getLogs.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - decode_pool_event() shared with apply_receipt_logs() (own-receipt fast path)
+
+use super::state::PoolStateManager;
+use crate::types::{PoolState, V3PoolState};
+use ethers::types::{Address, Filter, Log, TransactionReceipt, H256, U256};
+
+/// Uniswap/Sushi V3, Algebra and Kyber Elastic Swap event (Kyber's liquidity field is baseL only)
+pub const V3_SWAP_EVENT: &str = "Swap(address,address,int256,int256,uint160,uint128,int24)";
+
+/// Uniswap V2 fork Sync event
+pub const V2_SYNC_EVENT: &str = "Sync(uint112,uint112)";
+
+pub fn v3_swap_topic() -> H256 {
+    ethers::utils::keccak256(V3_SWAP_EVENT.as_bytes()).into()
+}
+
+pub fn v2_sync_topic() -> H256 {
+    ethers::utils::keccak256(V2_SYNC_EVENT.as_bytes()).into()
+}
+
+/// Swap + Sync logs of `pools` in one block
+pub fn event_sync_filter(pools: &[Address], block: u64) -> Filter {
+    Filter::new()
+        .from_block(block)
+        .to_block(block)
+        .address(pools.to_vec())
+        .topic0(vec![v3_swap_topic(), v2_sync_topic()])
+}
+
+/// Post-swap pool state carried by a Swap / Sync log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolEvent {
+    V3Swap { sqrt_price_x96: U256, liquidity: u128, tick: i32 },
+    V2Sync { reserve0: U256, reserve1: U256 },
+}
+
+/// Decode a V3 Swap or V2 Sync log; None for any other log or short data
+pub fn decode_pool_event(log: &Log) -> Option<PoolEvent> {
+    let topic0 = *log.topics.first()?;
+    if topic0 == v3_swap_topic() {
+        // V3 Swap event data layout (160 bytes):
+        //   [0..32]   amount0 (int256)
+        //   [32..64]  amount1 (int256)
+        //   [64..96]  sqrtPriceX96 (uint160)
+        //   [96..128] liquidity (uint128)
+        //   [128..160] tick (int24 as int256)
+        if log.data.len() < 160 {
+            return None;
+        }
+        // tick: int24 sign-extended to int256; last 4 bytes = valid i32
+        let tick = i32::from_be_bytes([log.data[156], log.data[157], log.data[158], log.data[159]]);
+        Some(PoolEvent::V3Swap {
+            sqrt_price_x96: U256::from_big_endian(&log.data[64..96]),
+            liquidity: U256::from_big_endian(&log.data[96..128]).low_u128(),
+            tick,
+        })
+    } else if topic0 == v2_sync_topic() {
+        // V2 Sync event data layout (64 bytes):
+        //   [0..32]  reserve0 (uint112)
+        //   [32..64] reserve1 (uint112)
+        if log.data.len() < 64 {
+            return None;
+        }
+        Some(PoolEvent::V2Sync {
+            reserve0: U256::from_big_endian(&log.data[0..32]),
+            reserve1: U256::from_big_endian(&log.data[32..64]),
+        })
+    } else {
+        None
+    }
+}
+
+/// Apply the Swap / Sync logs of known pools in one of our own receipts.
+///
+/// Only pools PoolStateManager already tracks are touched, and only when its
+/// state predates the receipt's block: a pool synced through that block
+/// already includes our trade (and any tx after it). Kyber's baseL-only
+/// liquidity and Algebra's fee are kept from the previous state, as in the
+/// event-sync path. Returns the pools updated.
+pub fn apply_receipt_logs(state_manager: &PoolStateManager, receipt: &TransactionReceipt) -> Vec<Address> {
+    let block = match receipt.block_number {
+        Some(block) => block.as_u64(),
+        None => return Vec::new(),
+    };
+    let mut updated = Vec::new();
+    for log in &receipt.logs {
+        let event = match decode_pool_event(log) {
+            Some(event) => event,
+            None => continue,
+        };
+        // A pool swapped twice in our tx: the second log is applied over the first
+        let current = updated.contains(&log.address);
+        if !current && state_manager.pool_last_updated(&log.address).is_none_or(|synced| synced >= block) {
+            continue;
+        }
+        let applied = match event {
+            PoolEvent::V3Swap { sqrt_price_x96, liquidity, tick } => state_manager
+                .get_v3_pool_at(&log.address)
+                .map(|prev| {
+                    let liquidity = if prev.dex.is_kyber_elastic() { prev.liquidity } else { liquidity };
+                    state_manager.update_v3_pool(V3PoolState { sqrt_price_x96, liquidity, tick, last_updated: block, ..prev });
+                }),
+            PoolEvent::V2Sync { reserve0, reserve1 } => state_manager
+                .get_pool_at(&log.address)
+                .map(|prev| state_manager.update_pool(PoolState { reserve0, reserve1, last_updated: block, ..prev })),
+        };
+        if applied.is_some() && !current {
+            updated.push(log.address);
+        }
+    }
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DexType, TradingPair};
+
+    /// Receipt of an ArbExecutor executeArb (USDC → WETH on Uniswap V3 0.05%,
+    /// WETH → USDC on QuickSwap V2) mined in block 65_432_101: ERC20
+    /// Transfers, the V3 pool's Swap, the V2 pair's Sync + Swap, a Sync from
+    /// a pair we don't track, and ArbExecuted.
+    const FIXTURE: &str = include_str!("testdata/arb_receipt.json");
+
+    fn address(s: &str) -> Address {
+        s.parse().unwrap()
+    }
+
+    fn pair() -> TradingPair {
+        TradingPair::new(
+            address("0x2791bca1f2de4661ed88a30c99a7a9449aa84174"),
+            address("0x7ceb23fd6bc0add59e62ac25578270cff1b9f619"),
+            "WETH/USDC".to_string(),
+        )
+    }
+
+    fn seeded(last_updated: u64) -> PoolStateManager {
+        let manager = PoolStateManager::new();
+        manager.update_v3_pool(V3PoolState {
+            address: address("0x45dda9cb7c25131df268515131f647d726f50608"),
+            dex: DexType::UniswapV3_005,
+            pair: pair(),
+            sqrt_price_x96: U256::from(1u128 << 96),
+            tick: 0,
+            fee: 500,
+            liquidity: 1,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated,
+        });
+        manager.update_pool(PoolState {
+            address: address("0x853ee4b2a13f8a742d64c8f088be7ba2131f670d"),
+            dex: DexType::QuickSwapV2,
+            pair: pair(),
+            reserve0: U256::from(1),
+            reserve1: U256::from(1),
+            last_updated,
+            token0_decimals: 6,
+            token1_decimals: 18,
+        });
+        manager
+    }
+
+    #[test]
+    fn test_apply_receipt_fixture() {
+        let receipt: TransactionReceipt = serde_json::from_str(FIXTURE).unwrap();
+        let manager = seeded(65_432_100);
+
+        let updated = apply_receipt_logs(&manager, &receipt);
+        assert_eq!(updated.len(), 2);
+
+        let v3 = manager.get_v3_pool_at(&address("0x45dda9cb7c25131df268515131f647d726f50608")).unwrap();
+        assert_eq!(v3.sqrt_price_x96, U256::from_dec_str("1861007594454912920461343771939290").unwrap());
+        assert_eq!((v3.tick, v3.liquidity, v3.fee), (201_296, 4_212_300_119_870_214_251, 500));
+        assert_eq!(v3.last_updated, 65_432_101);
+
+        let v2 = manager.get_pool_at(&address("0x853ee4b2a13f8a742d64c8f088be7ba2131f670d")).unwrap();
+        assert_eq!(v2.reserve0, U256::from(1_523_456_789_012u64));
+        assert_eq!(v2.reserve1, U256::from_dec_str("598765432109876543210").unwrap());
+        assert_eq!(v2.last_updated, 65_432_101);
+
+        // The untracked pair's Sync is ignored
+        assert_eq!(manager.get_all_pools().len(), 1);
+    }
+
+    #[test]
+    fn test_apply_receipt_skips_pools_already_synced_through_block() {
+        let receipt: TransactionReceipt = serde_json::from_str(FIXTURE).unwrap();
+
+        // eth_getLogs for the receipt's block already ran: state includes our trade
+        let manager = seeded(65_432_101);
+        assert!(apply_receipt_logs(&manager, &receipt).is_empty());
+        let v3 = manager.get_v3_pool_at(&address("0x45dda9cb7c25131df268515131f647d726f50608")).unwrap();
+        assert_eq!(v3.liquidity, 1);
+
+        // Marked synced without a log of its own counts too
+        let manager = seeded(65_432_100);
+        manager.mark_synced(&[address("0x853ee4b2a13f8a742d64c8f088be7ba2131f670d")], 65_432_101);
+        assert_eq!(apply_receipt_logs(&manager, &receipt), vec![address("0x45dda9cb7c25131df268515131f647d726f50608")]);
+    }
+
+    #[test]
+    fn test_decode_pool_event_rejects_other_logs() {
+        let receipt: TransactionReceipt = serde_json::from_str(FIXTURE).unwrap();
+        let decoded: Vec<_> = receipt.logs.iter().filter_map(decode_pool_event).collect();
+        // Transfers, the V2 Swap and ArbExecuted decode to nothing
+        assert_eq!(decoded.len(), 3);
+        assert!(matches!(decoded[0], PoolEvent::V3Swap { .. }));
+        assert!(matches!(decoded[1], PoolEvent::V2Sync { .. }));
+    }
+}
@@ -0,0 +1,237 @@
+//! Fixed-Point Prices and Spreads (detection)
+//!
+//! Purpose:
+//!     Spreads of 5–20 bps sit at the edge of f64 noise once tick → price,
+//!     decimal adjustment, fee subtraction and division are chained; the
+//!     detector flagged routes that vanished under the Quoter purely from
+//!     rounding. The two-leg scan compares pools on these integer types
+//!     instead, keeping f64 for logging and USD estimates only:
+//!         PriceX96    pool price, Q64.96 (U256)
+//!         WAD         spreads and fees, 1e18 = 100%
+//!
+//! Rounding is always against the opportunity: prices are floored, a spread
+//! is the lower bound over the flooring error (high - low - 1) / (low + 1),
+//! rounded down, and fees are exact (fee tiers are integers). A spread that
+//! rounds to zero is no spread.
+//!
+//! Prices are raw (token1 raw per token0 raw). The decimal adjustment is a
+//! constant factor for one token pair and cancels in any spread between its
+//! pools, so it never enters the comparison.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::mempool::ticks::sqrt_ratio_at_tick;
+use ethers::types::{U256, U512};
+
+/// 1e18 = 100% (spreads and fees)
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+/// Fee tiers are in hundredths of a bip: 1e6 = 100%
+const FEE_TIER_SCALE: u128 = 1_000_000;
+
+/// token1 per token0 in raw units, Q64.96, rounded down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PriceX96(pub U256);
+
+impl PriceX96 {
+    /// floor(numerator × 2^96 / denominator); None for a zero denominator or
+    /// a price beyond U256
+    pub fn from_ratio(numerator: U256, denominator: U256) -> Option<Self> {
+        mul_div(numerator, U256::one() << 96, denominator).map(PriceX96)
+    }
+
+    /// floor(sqrtPriceX96² / 2^96)
+    pub fn from_sqrt_x96(sqrt_price_x96: U256) -> Option<Self> {
+        mul_div(sqrt_price_x96, sqrt_price_x96, U256::one() << 96).map(PriceX96)
+    }
+
+    /// From the tick when the pool's sqrtPriceX96 isn't synced
+    pub fn from_tick(tick: i32) -> Option<Self> {
+        sqrt_ratio_at_tick(tick).and_then(Self::from_sqrt_x96)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Raw price as f64 (logging and tests only)
+    pub fn to_f64(self) -> f64 {
+        u256_to_f64(self.0) / 2f64.powi(96)
+    }
+}
+
+/// floor(a × b / denominator) through a 512-bit product; None for a zero
+/// denominator or a result beyond U256
+pub fn mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    // 256-bit fast path: the scan divides on every pool pair
+    match a.checked_mul(b) {
+        Some(product) => Some(product / denominator),
+        None => U256::try_from(a.full_mul(b) / U512::from(denominator)).ok(),
+    }
+}
+
+/// A fee tier (hundredths of a bip) in WAD, exact
+pub fn fee_tier_wad(fee_tier: u32) -> U256 {
+    U256::from(fee_tier) * U256::from(WAD / FEE_TIER_SCALE)
+}
+
+/// Relative distance between two prices, (high - low) / low, as a lower
+/// bound over the flooring of both prices, rounded down. Zero when the
+/// prices are (within flooring) equal; order of the arguments is free.
+pub fn spread_wad(a: PriceX96, b: PriceX96) -> U256 {
+    let (low, high) = if a <= b { (a.0, b.0) } else { (b.0, a.0) };
+    // True prices lie in [p, p + 1): smallest high, largest low
+    let gap = high.saturating_sub(low).saturating_sub(U256::one());
+    mul_div(gap, U256::from(WAD), low.saturating_add(U256::one())).unwrap_or_default()
+}
+
+/// Midmarket spread between the two pools less both swap fees (WAD).
+/// None when the spread doesn't beat the fees.
+pub fn executable_spread_wad(a: PriceX96, b: PriceX96, round_trip_fee_wad: U256) -> Option<U256> {
+    spread_wad(a, b).checked_sub(round_trip_fee_wad).filter(|spread| !spread.is_zero())
+}
+
+/// Round-trip return (amount_out - amount_in) / amount_in, rounded down (WAD).
+/// None when nothing is gained.
+pub fn return_wad(amount_in: U256, amount_out: U256) -> Option<U256> {
+    let gain = amount_out.checked_sub(amount_in)?;
+    mul_div(gain, U256::from(WAD), amount_in).filter(|spread| !spread.is_zero())
+}
+
+/// A WAD fraction as f64 (0.0005 for 5 bps)
+pub fn wad_to_f64(value: U256) -> f64 {
+    u256_to_f64(value) / WAD as f64
+}
+
+fn u256_to_f64(value: U256) -> f64 {
+    value.0.iter().rev().fold(0.0, |acc, &limb| acc * 2f64.powi(64) + limb as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic xorshift64* (property cases without a proptest dependency)
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        /// Uniform in [lo, hi)
+        fn range(&mut self, lo: u64, hi: u64) -> u64 {
+            lo + self.next() % (hi - lo)
+        }
+
+        /// Reserve-sized amount in [1e6, 1e30)
+        fn reserve(&mut self) -> u128 {
+            self.range(1_000, 1_000_000) as u128 * 10u128.pow(self.range(3, 25) as u32)
+        }
+    }
+
+    const FEE_TIERS: [u32; 6] = [100, 400, 500, 900, 3000, 10_000];
+
+    #[test]
+    fn test_price_constructors() {
+        let q96 = U256::one() << 96;
+        assert_eq!(PriceX96::from_ratio(U256::from(3), U256::from(2)).unwrap().0, q96 * 3 / 2);
+        assert_eq!(PriceX96::from_ratio(U256::one(), U256::zero()), None);
+        // sqrtPriceX96 = 2^96 → price 1; tick 0 is the same
+        assert_eq!(PriceX96::from_sqrt_x96(q96).unwrap().0, q96);
+        assert_eq!(PriceX96::from_tick(0).unwrap().0, q96);
+        // Top of the V3 range (sqrt ≈ 2^160) still fits
+        assert!(PriceX96::from_tick(887_272).is_some());
+        let p = PriceX96::from_tick(69_081).unwrap().to_f64(); // ≈ 1000
+        assert!((p / 1.0001f64.powi(69_081) - 1.0).abs() < 1e-9, "{}", p);
+        assert_eq!(fee_tier_wad(500), U256::from(500_000_000_000_000u64));
+    }
+
+    #[test]
+    fn test_spread_rounds_against_the_opportunity() {
+        let price = |n: u64, d: u64| PriceX96::from_ratio(U256::from(n), U256::from(d)).unwrap();
+        // 1% apart, either argument order
+        let s = spread_wad(price(100, 1), price(101, 1));
+        assert_eq!(s, spread_wad(price(101, 1), price(100, 1)));
+        assert!(s < U256::from(WAD / 100) && U256::from(WAD / 100) - s < U256::from(100), "{}", s);
+        // Equal prices are no spread
+        assert!(spread_wad(price(7, 3), price(14, 6)).is_zero());
+        // 0.10% mid spread vs 0.05% + 0.05% fees: nothing left
+        let fees = fee_tier_wad(500) * 2;
+        assert_eq!(executable_spread_wad(price(1000, 1), price(1001, 1), fees), None);
+        assert!(executable_spread_wad(price(1000, 1), price(1002, 1), fees).is_some());
+        // Depth path
+        assert_eq!(return_wad(U256::from(1_000_000), U256::from(1_000_500)), Some(U256::from(WAD / 2000)));
+        assert_eq!(return_wad(U256::from(1_000_000), U256::from(1_000_000)), None);
+        assert_eq!(return_wad(U256::from(1_000_000), U256::from(999_999)), None);
+        assert!((wad_to_f64(U256::from(WAD / 2000)) - 0.0005).abs() < 1e-18);
+    }
+
+    /// Property: on random pools within ±60 bps of each other, the
+    /// fixed-point executable spread never exceeds the f64 spread by more
+    /// than 1e-9 and is never positive when exact rational math says the
+    /// spread doesn't beat the fees
+    #[test]
+    fn test_fixed_point_spread_never_above_f64_or_exact() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let mut flagged = 0;
+        for _ in 0..20_000 {
+            let (r0a, r1a) = (rng.reserve(), rng.reserve());
+            // Second pool's token1 reserve moved by up to ±60 bps
+            let bps = rng.range(0, 121) as i128 - 60;
+            let r0b = r0a;
+            let r1b = (r1a as i128 + r1a as i128 / 10_000 * bps) as u128;
+            let (fee_a, fee_b) = (FEE_TIERS[rng.range(0, 6) as usize], FEE_TIERS[rng.range(0, 6) as usize]);
+            let fees_wad = fee_tier_wad(fee_a) + fee_tier_wad(fee_b);
+
+            let a = PriceX96::from_ratio(U256::from(r1a), U256::from(r0a)).unwrap();
+            let b = PriceX96::from_ratio(U256::from(r1b), U256::from(r0b)).unwrap();
+            let fixed = executable_spread_wad(a, b, fees_wad);
+
+            // f64 pipeline as the detector had it
+            let (pa, pb) = (r1a as f64 / r0a as f64, r1b as f64 / r0b as f64);
+            let f64_spread = (pa.max(pb) - pa.min(pb)) / pa.min(pb) - (fee_a + fee_b) as f64 / 1e6;
+
+            // Exact: (high - low) / low > fees  ⇔  (hi·lo' - lo·hi')·1e6 > lo·hi'·fees
+            let (x, y) = (U256::from(r1a) * U256::from(r0b), U256::from(r1b) * U256::from(r0a));
+            let (hi, lo) = if x >= y { (x, y) } else { (y, x) };
+            let exact_positive = (hi - lo) * U256::from(1_000_000u64) > lo * U256::from(fee_a + fee_b);
+
+            if let Some(spread) = fixed {
+                flagged += 1;
+                assert!(exact_positive, "flagged a non-positive spread: {}/{} vs {}/{}", r1a, r0a, r1b, r0b);
+                assert!(wad_to_f64(spread) <= f64_spread + 1e-9, "fixed {} above f64 {}", wad_to_f64(spread), f64_spread);
+            }
+        }
+        assert!(flagged > 1_000, "too few profitable cases exercised: {}", flagged);
+    }
+
+    /// Same property for V3 prices from sqrtPriceX96
+    #[test]
+    fn test_sqrt_price_spread_never_above_f64() {
+        let mut rng = Rng(0xdead_beef_cafe_f00d);
+        for _ in 0..20_000 {
+            let tick = rng.range(0, 400_000) as i32 - 200_000;
+            let sqrt_a = sqrt_ratio_at_tick(tick).unwrap();
+            let sqrt_b = sqrt_a + sqrt_a / U256::from(1_000_000u64) * U256::from(rng.range(0, 3_000));
+            let fees_wad = fee_tier_wad(FEE_TIERS[rng.range(0, 6) as usize]) * 2;
+            let (a, b) = (PriceX96::from_sqrt_x96(sqrt_a).unwrap(), PriceX96::from_sqrt_x96(sqrt_b).unwrap());
+            let Some(spread) = executable_spread_wad(a, b, fees_wad) else { continue };
+
+            let (sa, sb) = (u256_to_f64(sqrt_a), u256_to_f64(sqrt_b));
+            let f64_spread = (sb * sb) / (sa * sa) - 1.0 - wad_to_f64(fees_wad);
+            assert!(wad_to_f64(spread) <= f64_spread + 1e-9, "tick {}: fixed {} above f64 {}", tick, wad_to_f64(spread), f64_spread);
+            // sqrt_b > sqrt_a, so the exact spread is sqrt_b² / sqrt_a² - 1
+            let exact_positive = (sqrt_b.full_mul(sqrt_b) - sqrt_a.full_mul(sqrt_a)) * U512::from(WAD)
+                > sqrt_a.full_mul(sqrt_a) * U512::from(fees_wad);
+            assert!(exact_positive, "tick {}: flagged a non-positive spread", tick);
+        }
+    }
+}
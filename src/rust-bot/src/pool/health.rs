@@ -0,0 +1,354 @@
+//! Pool Health Monitoring
+//!
+//! Whitelisted pools that go quiet or get drained keep producing phantom
+//! opportunities. `PoolHealth` checks every synced pool once per block:
+//!   - idle: no Swap/Sync-driven state change (price, liquidity, reserves)
+//!     for POOL_HEALTH_MAX_IDLE_BLOCKS
+//!   - V3 liquidity below POOL_HEALTH_MIN_V3_LIQUIDITY
+//!   - V2 TVL (2 × quote reserve in USD) below POOL_HEALTH_MIN_V2_TVL_USD
+//!   - POOL_HEALTH_MAX_QUOTER_REJECTIONS consecutive pre-screen rejections
+//!     attributed to the pool's leg; quarantined for
+//!     POOL_HEALTH_REJECTION_QUARANTINE_BLOCKS, then retried
+//!
+//! An unhealthy pool is quarantined in PoolStateManager: still synced, but
+//! skipped by the detector. It is reinstated automatically once every check
+//! passes again. Transitions are returned for logging; `report()` goes into
+//! the periodic stats report.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use super::PoolStateManager;
+use crate::types::{BotConfig, DexType};
+use ethers::types::{Address, U256};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Thresholds (POOL_HEALTH_*)
+#[derive(Debug, Clone)]
+pub struct PoolHealthConfig {
+    pub max_idle_blocks: u64,
+    pub min_v3_liquidity: u128,
+    pub min_v2_tvl_usd: f64,
+    pub max_quoter_rejections: u32,
+    pub rejection_quarantine_blocks: u64,
+    /// Quote tokens and their USD price (V2 TVL)
+    pub quote_prices: Vec<(Address, f64)>,
+}
+
+impl PoolHealthConfig {
+    pub fn from_config(config: &BotConfig) -> Self {
+        let quote_prices = std::iter::once(config.quote_token_address)
+            .chain(config.quote_token_address_native)
+            .map(|a| (a, config.quote_price_usd(&a)))
+            .collect();
+        Self {
+            max_idle_blocks: config.pool_health_max_idle_blocks,
+            min_v3_liquidity: config.pool_health_min_v3_liquidity,
+            min_v2_tvl_usd: config.pool_health_min_v2_tvl_usd,
+            max_quoter_rejections: config.pool_health_max_quoter_rejections,
+            rejection_quarantine_blocks: config.pool_health_rejection_quarantine_blocks,
+            quote_prices,
+        }
+    }
+}
+
+/// Why a pool is unhealthy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HealthIssue {
+    Idle { blocks: u64 },
+    LowLiquidity { liquidity: u128, floor: u128 },
+    LowTvl { tvl_usd: f64, floor_usd: f64 },
+    QuoterRejections { count: u32 },
+}
+
+impl fmt::Display for HealthIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthIssue::Idle { blocks } => write!(f, "idle {} blocks", blocks),
+            HealthIssue::LowLiquidity { liquidity, floor } => write!(f, "liquidity {} < {}", liquidity, floor),
+            HealthIssue::LowTvl { tvl_usd, floor_usd } => write!(f, "TVL ${:.0} < ${:.0}", tvl_usd, floor_usd),
+            HealthIssue::QuoterRejections { count } => write!(f, "{} consecutive quoter rejections", count),
+        }
+    }
+}
+
+/// A pool entering or leaving quarantine
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthTransition {
+    Quarantined { pool: Address, pair: String, dex: DexType, issue: HealthIssue },
+    Reinstated { pool: Address, pair: String, dex: DexType, after_blocks: u64 },
+}
+
+/// (sqrtPrice, liquidity, tick) for V3, (reserve0, reserve1, 0) for V2
+type Fingerprint = (U256, U256, i32);
+
+/// Per-pool tracking state
+#[derive(Debug, Clone)]
+struct PoolRecord {
+    pair: String,
+    dex: DexType,
+    /// Pool state at the last check
+    fingerprint: Fingerprint,
+    last_active: u64,
+    consecutive_rejections: u32,
+    rejected_until: Option<u64>,
+    /// (since block, issue) while quarantined
+    quarantined: Option<(u64, HealthIssue)>,
+}
+
+/// Per-pool health tracker driving PoolStateManager quarantine
+pub struct PoolHealth {
+    config: PoolHealthConfig,
+    pools: HashMap<Address, PoolRecord>,
+    total_quarantined: u64,
+    total_reinstated: u64,
+}
+
+impl PoolHealth {
+    pub fn new(config: PoolHealthConfig) -> Self {
+        Self { config, pools: HashMap::new(), total_quarantined: 0, total_reinstated: 0 }
+    }
+
+    /// A pre-screen rejection attributed to this pool's leg
+    pub fn record_quoter_rejection(&mut self, pool: Address, block: u64) {
+        let Some(record) = self.pools.get_mut(&pool) else { return };
+        record.consecutive_rejections += 1;
+        if self.config.max_quoter_rejections > 0 && record.consecutive_rejections >= self.config.max_quoter_rejections {
+            record.rejected_until = Some(block + self.config.rejection_quarantine_blocks);
+        }
+    }
+
+    /// A valid quote through this pool resets its rejection streak
+    pub fn record_quoter_success(&mut self, pool: Address) {
+        if let Some(record) = self.pools.get_mut(&pool) {
+            record.consecutive_rejections = 0;
+        }
+    }
+
+    /// Check every V2 + V3 pool at `block`, quarantining/reinstating in the
+    /// state manager. Returns the transitions this block.
+    pub fn check(&mut self, state_manager: &PoolStateManager, block: u64) -> Vec<HealthTransition> {
+        let mut observed: Vec<(Address, String, DexType, Fingerprint, Option<HealthIssue>)> = Vec::new();
+        for pool in state_manager.get_all_v3_pools() {
+            let issue = (self.config.min_v3_liquidity > 0 && pool.liquidity < self.config.min_v3_liquidity)
+                .then_some(HealthIssue::LowLiquidity { liquidity: pool.liquidity, floor: self.config.min_v3_liquidity });
+            let fingerprint = (pool.sqrt_price_x96, U256::from(pool.liquidity), pool.tick);
+            observed.push((pool.address, pool.pair.symbol.clone(), pool.dex, fingerprint, issue));
+        }
+        for pool in state_manager.get_all_pools() {
+            let issue = self.v2_tvl_usd(&pool).and_then(|tvl_usd| {
+                (tvl_usd < self.config.min_v2_tvl_usd)
+                    .then_some(HealthIssue::LowTvl { tvl_usd, floor_usd: self.config.min_v2_tvl_usd })
+            });
+            observed.push((pool.address, pool.pair.symbol.clone(), pool.dex, (pool.reserve0, pool.reserve1, 0), issue));
+        }
+
+        let mut transitions = Vec::new();
+        for (address, pair, dex, fingerprint, state_issue) in observed {
+            let record = self.pools.entry(address).or_insert_with(|| PoolRecord {
+                pair: pair.clone(),
+                dex,
+                fingerprint,
+                last_active: block,
+                consecutive_rejections: 0,
+                rejected_until: None,
+                quarantined: None,
+            });
+            if record.fingerprint != fingerprint {
+                record.fingerprint = fingerprint;
+                record.last_active = block;
+            }
+            if record.rejected_until.is_some_and(|until| block >= until) {
+                record.rejected_until = None;
+                record.consecutive_rejections = 0;
+            }
+
+            let idle = block.saturating_sub(record.last_active);
+            let issue = state_issue
+                .or_else(|| {
+                    (self.config.max_idle_blocks > 0 && idle >= self.config.max_idle_blocks)
+                        .then_some(HealthIssue::Idle { blocks: idle })
+                })
+                .or_else(|| {
+                    record.rejected_until.map(|_| HealthIssue::QuoterRejections { count: self.config.max_quoter_rejections })
+                });
+
+            match (issue, record.quarantined) {
+                (Some(issue), None) => {
+                    record.quarantined = Some((block, issue));
+                    state_manager.quarantine_pool(address);
+                    self.total_quarantined += 1;
+                    transitions.push(HealthTransition::Quarantined { pool: address, pair, dex, issue });
+                }
+                (None, Some((since, _))) => {
+                    record.quarantined = None;
+                    state_manager.reinstate_pool(&address);
+                    self.total_reinstated += 1;
+                    transitions.push(HealthTransition::Reinstated { pool: address, pair, dex, after_blocks: block - since });
+                }
+                _ => {}
+            }
+        }
+        transitions
+    }
+
+    /// 2 × the quote-side reserve in USD; None when neither token is a quote token
+    fn v2_tvl_usd(&self, pool: &crate::types::PoolState) -> Option<f64> {
+        let quote = |token: Address| self.config.quote_prices.iter().find(|(a, _)| *a == token).map(|(_, p)| *p);
+        let (reserve, decimals, price) = match (quote(pool.pair.token0), quote(pool.pair.token1)) {
+            (Some(price), _) => (pool.reserve0, pool.token0_decimals, price),
+            (None, Some(price)) => (pool.reserve1, pool.token1_decimals, price),
+            (None, None) => return None,
+        };
+        Some(2.0 * reserve.low_u128() as f64 / 10f64.powi(decimals as i32) * price)
+    }
+
+    /// Pools currently quarantined
+    pub fn quarantined_count(&self) -> usize {
+        self.pools.values().filter(|r| r.quarantined.is_some()).count()
+    }
+
+    /// Stats report block: counts plus one line per quarantined pool
+    pub fn report(&self) -> String {
+        let mut lines = vec![format!(
+            "Pool health: {} quarantined ({} quarantines, {} reinstated since start)",
+            self.quarantined_count(), self.total_quarantined, self.total_reinstated
+        )];
+        let mut quarantined: Vec<(&Address, &PoolRecord, u64, HealthIssue)> = self
+            .pools
+            .iter()
+            .filter_map(|(a, r)| r.quarantined.map(|(since, issue)| (a, r, since, issue)))
+            .collect();
+        quarantined.sort_by_key(|(_, _, since, _)| *since);
+        for (address, record, since, issue) in quarantined {
+            lines.push(format!("  {} {:?} {:?} — {} (since #{})", record.pair, record.dex, address, issue, since));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::arbitrage::OpportunityDetector;
+    use crate::types::{PoolState, TradingPair, TradingPairConfig, V3PoolState};
+
+    fn health_config() -> PoolHealthConfig {
+        let mut config = create_test_config();
+        config.pool_health_max_idle_blocks = 100;
+        config.pool_health_min_v3_liquidity = 10_000_000_000_000;
+        config.pool_health_min_v2_tvl_usd = 1_000.0;
+        config.pool_health_max_quoter_rejections = 3;
+        config.pool_health_rejection_quarantine_blocks = 50;
+        PoolHealthConfig::from_config(&config)
+    }
+
+    /// WETH/USDC V3 pool at `tick` (USDC token0, 6/18 decimals)
+    fn v3(n: u64, dex: DexType, fee: u32, tick: i32, liquidity: u128) -> V3PoolState {
+        let config = create_test_config();
+        V3PoolState {
+            address: Address::from_low_u64_be(n),
+            dex,
+            pair: TradingPair::new(config.quote_token_address, Address::from_low_u64_be(0xe7), "WETH/USDC".to_string()),
+            sqrt_price_x96: U256::zero(),
+            tick,
+            fee,
+            liquidity,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 100,
+        }
+    }
+
+    #[test]
+    fn test_liquidity_drop_removes_pool_from_detection() {
+        let mut config = create_test_config();
+        config.pairs = vec![TradingPairConfig {
+            token0: format!("{:?}", config.quote_token_address),
+            token1: format!("{:?}", Address::from_low_u64_be(0xe7)),
+            symbol: "WETH/USDC".to_string(),
+        }];
+        // ~3% apart: WETH at ~$2000 vs ~$2060
+        let state_manager = PoolStateManager::new();
+        state_manager.update_v3_pool(v3(1, DexType::UniswapV3_005, 500, 200_311, 1_000_000_000_000_000));
+        state_manager.update_v3_pool(v3(2, DexType::SushiV3_005, 500, 200_015, 1_000_000_000_000_000));
+        let detector = OpportunityDetector::new(config, state_manager.clone());
+        assert!(!detector.scan_opportunities().is_empty());
+
+        let mut health = PoolHealth::new(health_config());
+        assert!(health.check(&state_manager, 100).is_empty());
+
+        // Pool 2 drained below the health floor (still above the whitelist minimum)
+        state_manager.update_v3_pool(v3(2, DexType::SushiV3_005, 500, 200_015, 1_000_000_000_000));
+        let transitions = health.check(&state_manager, 101);
+        assert_eq!(transitions, vec![HealthTransition::Quarantined {
+            pool: Address::from_low_u64_be(2),
+            pair: "WETH/USDC".to_string(),
+            dex: DexType::SushiV3_005,
+            issue: HealthIssue::LowLiquidity { liquidity: 1_000_000_000_000, floor: 10_000_000_000_000 },
+        }]);
+        assert!(state_manager.is_pool_quarantined(&Address::from_low_u64_be(2)));
+        assert!(detector.scan_opportunities().is_empty());
+        assert!(health.report().contains("1 quarantined"));
+
+        // Liquidity back: reinstated and detected again
+        state_manager.update_v3_pool(v3(2, DexType::SushiV3_005, 500, 200_015, 1_000_000_000_000_000));
+        let transitions = health.check(&state_manager, 110);
+        assert!(matches!(transitions[..], [HealthTransition::Reinstated { after_blocks: 9, .. }]));
+        assert!(state_manager.quarantined_pools().is_empty());
+        assert!(!detector.scan_opportunities().is_empty());
+    }
+
+    #[test]
+    fn test_idle_tvl_and_quoter_rejection_quarantines() {
+        let config = create_test_config();
+        let state_manager = PoolStateManager::new();
+        let pool = Address::from_low_u64_be(1);
+        state_manager.update_v3_pool(v3(1, DexType::UniswapV3_005, 500, 200_311, 1_000_000_000_000_000));
+        // V2 pairs at $1,600 TVL ($800 of USDC) and a drained $400
+        for (n, usdc) in [(3u64, 800u64), (4, 200)] {
+            state_manager.update_pool(PoolState {
+                address: Address::from_low_u64_be(n),
+                dex: DexType::QuickSwapV2,
+                pair: TradingPair::new(config.quote_token_address, Address::from_low_u64_be(0xe7), "WETH/USDC".to_string()),
+                reserve0: U256::from(usdc * 1_000_000),
+                reserve1: U256::exp10(18),
+                last_updated: 100,
+                token0_decimals: 6,
+                token1_decimals: 18,
+            });
+        }
+
+        let mut health = PoolHealth::new(health_config());
+        let transitions = health.check(&state_manager, 100);
+        assert!(matches!(
+            transitions[..],
+            [HealthTransition::Quarantined { pool, issue: HealthIssue::LowTvl { .. }, .. }] if pool == Address::from_low_u64_be(4)
+        ));
+
+        // Quoter rejections: quarantined at the 3rd, retried after 50 blocks
+        health.record_quoter_rejection(pool, 100);
+        health.record_quoter_success(pool);
+        health.record_quoter_rejection(pool, 101);
+        health.record_quoter_rejection(pool, 102);
+        assert!(health.check(&state_manager, 102).is_empty());
+        health.record_quoter_rejection(pool, 103);
+        let transitions = health.check(&state_manager, 103);
+        assert!(matches!(transitions[..], [HealthTransition::Quarantined { issue: HealthIssue::QuoterRejections { count: 3 }, .. }]));
+        state_manager.update_v3_pool(v3(1, DexType::UniswapV3_005, 500, 200_312, 1_000_000_000_000_000));
+        let transitions = health.check(&state_manager, 153);
+        assert!(matches!(transitions[..], [HealthTransition::Reinstated { after_blocks: 50, .. }]));
+
+        // Idle: the V2 pair never changed since block 100
+        let transitions = health.check(&state_manager, 200);
+        assert!(matches!(
+            transitions[..],
+            [HealthTransition::Quarantined { pool, issue: HealthIssue::Idle { blocks: 100 }, .. }] if pool == Address::from_low_u64_be(3)
+        ));
+        // ...the V3 pool moved at 153, so it isn't idle yet
+        assert!(!state_manager.is_pool_quarantined(&pool));
+        assert_eq!(health.quarantined_count(), 2);
+    }
+}
@@ -0,0 +1,172 @@
+//! KyberSwap Elastic Pool Synchronization
+//!
+//! Elastic is a concentrated-liquidity AMM close enough to Uniswap V3 that
+//! its pools live in V3PoolState and flow through the same detector math,
+//! but the reads differ:
+//! - getPoolState() → (sqrtP, currentTick, nearestCurrentTick, locked)
+//!   instead of slot0(); the first two words line up with slot0, so the
+//!   preflight/multicall word decoders work unchanged
+//! - getLiquidityState() → (baseL, reinvestL, reinvestLLast) instead of
+//!   liquidity(); active liquidity for swap math is baseL + reinvestL
+//! - swapFeeUnits() in 1e-5 (0.04% = 40), stored ×10 as the 1e-6 V3 fee
+//!
+//! Detection-only: ArbExecutor.sol has no Kyber leg (see DexType::is_detection_only).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::types::V3PoolState;
+use anyhow::{anyhow, Context, Result};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use std::sync::Arc;
+use tracing::warn;
+
+// KyberSwap Elastic Pool ABI (token0/token1/swapFeeUnits; state reads are raw eth_calls)
+abigen!(
+    KyberElasticPool,
+    r#"[
+        function getPoolState() external view returns (uint160 sqrtP, int24 currentTick, int24 nearestCurrentTick, bool locked)
+        function getLiquidityState() external view returns (uint128 baseL, uint128 reinvestL, uint128 reinvestLLast)
+        function swapFeeUnits() external view returns (uint24)
+        function token0() external view returns (address)
+        function token1() external view returns (address)
+    ]"#
+);
+
+/// getPoolState() function selector
+pub const GET_POOL_STATE_SELECTOR: [u8; 4] = [0x21, 0x7a, 0xc2, 0x37];
+
+/// getLiquidityState() function selector
+pub const GET_LIQUIDITY_STATE_SELECTOR: [u8; 4] = [0xab, 0x61, 0x2f, 0x2b];
+
+/// swapFeeUnits (1e-5) → V3PoolState.fee (1e-6)
+pub fn fee_from_units(fee_units: u32) -> u32 {
+    fee_units * 10
+}
+
+/// V3PoolState.fee (1e-6) → swapFeeUnits (1e-5), as the factory and quoter take it
+pub fn fee_to_units(fee: u32) -> u32 {
+    fee / 10
+}
+
+fn word(data: &[u8], index: usize) -> Option<U256> {
+    data.get(index * 32..(index + 1) * 32).map(U256::from_big_endian)
+}
+
+/// (sqrtP, currentTick) from getPoolState() return data; None = short or empty pool
+pub fn decode_pool_state(data: &[u8]) -> Option<(U256, i32)> {
+    let sqrt_p = word(data, 0).filter(|p| !p.is_zero())?;
+    // int24 is sign-extended to 32 bytes — the low 4 bytes are a valid i32
+    let tick_bytes = data.get(60..64)?;
+    let tick = i32::from_be_bytes([tick_bytes[0], tick_bytes[1], tick_bytes[2], tick_bytes[3]]);
+    Some((sqrt_p, tick))
+}
+
+/// Active liquidity (baseL + reinvestL) from getLiquidityState() return data
+pub fn decode_liquidity_state(data: &[u8]) -> Option<u128> {
+    let base = word(data, 0).filter(|l| l.bits() <= 128)?.as_u128();
+    let reinvest = word(data, 1).filter(|l| l.bits() <= 128)?.as_u128();
+    base.checked_add(reinvest)
+}
+
+/// Raw state reads for Elastic pools
+pub struct KyberPoolSyncer<M> {
+    provider: Arc<M>,
+}
+
+impl<M: Middleware + 'static> KyberPoolSyncer<M> {
+    pub fn new(provider: Arc<M>) -> Self {
+        Self { provider }
+    }
+
+    async fn eth_call(&self, pool: Address, selector: [u8; 4]) -> Result<Bytes> {
+        let tx: TypedTransaction = TransactionRequest::new().to(pool).data(selector.to_vec()).into();
+        self.provider
+            .call(&tx, None)
+            .await
+            .map_err(|e| anyhow!("eth_call to {:?} failed: {}", pool, e))
+    }
+
+    /// (sqrtP, tick, liquidity) for `pool`
+    pub async fn read_state(&self, pool: Address) -> Result<(U256, i32, u128)> {
+        let (state, liquidity) = tokio::join!(
+            self.eth_call(pool, GET_POOL_STATE_SELECTOR),
+            self.eth_call(pool, GET_LIQUIDITY_STATE_SELECTOR)
+        );
+        let (sqrt_p, tick) = decode_pool_state(&state?).context("Undecodable Kyber getPoolState()")?;
+        let liquidity = decode_liquidity_state(&liquidity?).context("Undecodable Kyber getLiquidityState()")?;
+        Ok((sqrt_p, tick, liquidity))
+    }
+
+    /// (fee in 1e-6, token0, token1) — immutable per pool, read once at discovery
+    pub async fn read_static(&self, pool: Address) -> Result<(u32, Address, Address)> {
+        let contract = KyberElasticPool::new(pool, Arc::clone(&self.provider));
+        let fee_units = contract.swap_fee_units().call().await.context("Failed to get swapFeeUnits")?;
+        let token0 = contract.token_0().call().await.context("Failed to get token0")?;
+        let token1 = contract.token_1().call().await.context("Failed to get token1")?;
+        Ok((fee_from_units(fee_units), token0, token1))
+    }
+
+    /// Re-read a known pool's price, tick and liquidity. None = a read failed (logged).
+    pub async fn refresh(&self, pool_state: V3PoolState, current_block: u64) -> Option<V3PoolState> {
+        match self.read_state(pool_state.address).await {
+            Ok((sqrt_price_x96, tick, liquidity)) => Some(V3PoolState {
+                sqrt_price_x96,
+                tick,
+                liquidity,
+                last_updated: current_block,
+                ..pool_state
+            }),
+            Err(e) => {
+                warn!(
+                    "Failed to fast-sync Kyber pool {} {:?} at {:?}: {}",
+                    pool_state.pair.symbol, pool_state.dex, pool_state.address, e
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// getPoolState() return data: sqrtP at tick -201234, nearestCurrentTick -201240, unlocked
+    const POOL_STATE_RESPONSE: &str = "00000000000000000000000000000000000000000002cc78235fe66260000000\
+fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffcedee\
+fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffcede8\
+0000000000000000000000000000000000000000000000000000000000000000";
+
+    /// getLiquidityState() return data: baseL, reinvestL, reinvestLLast
+    const LIQUIDITY_STATE_RESPONSE: &str = "000000000000000000000000000000000000000000000000112210f47de98115\
+000000000000000000000000000000000000000000000000000059d39e7f1972\
+000000000000000000000000000000000000000000000000000059c462707800";
+
+    #[test]
+    fn test_selectors() {
+        assert_eq!(ethers::utils::id("getPoolState()"), GET_POOL_STATE_SELECTOR);
+        assert_eq!(ethers::utils::id("getLiquidityState()"), GET_LIQUIDITY_STATE_SELECTOR);
+    }
+
+    #[test]
+    fn test_decode_captured_responses() {
+        let state = ethers::utils::hex::decode(POOL_STATE_RESPONSE).unwrap();
+        let (sqrt_p, tick) = decode_pool_state(&state).unwrap();
+        assert_eq!(sqrt_p, U256::from_str_radix("2cc78235fe66260000000", 16).unwrap());
+        assert_eq!(tick, -201_234);
+
+        let liquidity = ethers::utils::hex::decode(LIQUIDITY_STATE_RESPONSE).unwrap();
+        assert_eq!(decode_liquidity_state(&liquidity), Some(1_234_567_890_123_456_789 + 98_765_432_101_234));
+
+        // Reverted/short return data and empty pools decode to None
+        assert_eq!(decode_pool_state(&state[..40]), None);
+        assert_eq!(decode_pool_state(&[0u8; 128]), None);
+        assert_eq!(decode_liquidity_state(&liquidity[..32]), None);
+
+        // swapFeeUnits 40 (0.04%) ↔ V3 fee 400
+        assert_eq!(fee_from_units(40), 400);
+        assert_eq!(fee_to_units(400), 40);
+    }
+}
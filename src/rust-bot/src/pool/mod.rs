@@ -6,15 +6,42 @@
 //! Author: AI-Generated
 //! Created: 2026-01-27
 //! Modified: 2026-01-28 (added V3 support)
+//! Modified: 2026-10-16 (added events: shared event-sync log filter)
+//! Modified: 2026-10-16 (added reorg: header chain tracking for stale pool invalidation)
+//! Modified: 2026-10-16 (added stableswap + curve_syncer: Curve stable pools)
+//! Modified: 2026-10-16 (added multicall_syncer: one aggregate3 per block for V2 + V3 poll sync)
+//! Modified: 2026-10-16 (added health: pool health checks + automatic quarantine)
+//! Modified: 2026-10-16 (added adapter: PoolAdapter trait over V2 / V3 / Algebra / Curve states)
+//! Modified: 2026-10-16 (added kyber_syncer: KyberSwap Elastic getPoolState reads, detection-only)
+//! Modified: 2026-10-16 (added fixed_point: Q64.96 prices + WAD spreads for the detector)
+//! Modified: 2026-10-16 (added dodo_syncer: DODO V2 PMM pools)
 
+pub mod adapter;
 pub mod calculator;
+pub mod curve_syncer;
+pub mod dodo_syncer;
+pub mod events;
+pub mod fixed_point;
+pub mod health;
+pub mod kyber_syncer;
+pub mod multicall_syncer;
+pub mod reorg;
+pub mod stableswap;
 pub mod state;
 pub mod syncer;
 pub mod v2_syncer;
 pub mod v3_syncer;
 
+pub use adapter::PoolAdapter;
 pub use calculator::PriceCalculator;
+pub use curve_syncer::CurvePoolSyncer;
+pub use dodo_syncer::DodoPoolSyncer;
+pub use fixed_point::PriceX96;
+pub use health::{HealthIssue, HealthTransition, PoolHealth, PoolHealthConfig};
+pub use kyber_syncer::KyberPoolSyncer;
+pub use multicall_syncer::{MulticallSync, MulticallSyncer};
+pub use reorg::{Reorg, ReorgDetector};
 pub use state::PoolStateManager;
 pub use syncer::PoolSyncer;
 pub use v2_syncer::V2PoolSyncer;
-pub use v3_syncer::{V3PoolSyncer, SUSHI_V3_FEE_TIERS, V3_FEE_TIERS};
+pub use v3_syncer::{V3PoolSyncer, KYBER_ELASTIC_FEE_TIERS, SUSHI_V3_FEE_TIERS, V3_FEE_TIERS};
@@ -0,0 +1,167 @@
+//! Reorg Detection — block header chain tracking
+//!
+//! The event-driven sync path (EVENT_SYNC=true) applies Swap/Sync logs as
+//! blocks arrive. When a small Polygon reorg drops one of those blocks, the
+//! pools it touched keep phantom prices until the next event for them.
+//!
+//! `ReorgDetector` keeps the (number, hash) of the last N processed headers.
+//! A new header whose parent hash does not match the tracked hash below it,
+//! or which replaces an already processed height, starts a reorg. The caller
+//! walks back with `unconfirmed_below` / `confirm` against the canonical
+//! chain to find the fork point, then marks every pool updated at or after
+//! it stale (PoolStateManager::mark_stale) and poll-resyncs them.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use ethers::types::H256;
+use std::collections::VecDeque;
+
+/// Processed blocks that are no longer canonical
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reorg {
+    /// First orphaned block (pools updated at or after it are suspect)
+    pub fork_block: u64,
+    /// Highest block processed before the reorg was seen
+    pub tip: u64,
+}
+
+impl Reorg {
+    /// Orphaned blocks the bot had processed
+    pub fn depth(&self) -> u64 {
+        self.tip.saturating_sub(self.fork_block) + 1
+    }
+}
+
+/// Last N processed headers, oldest first
+#[derive(Debug, Clone)]
+pub struct ReorgDetector {
+    headers: VecDeque<(u64, H256)>,
+    capacity: usize,
+}
+
+impl ReorgDetector {
+    pub fn new(capacity: usize) -> Self {
+        Self { headers: VecDeque::with_capacity(capacity), capacity: capacity.max(2) }
+    }
+
+    /// Tracked hash of a processed block
+    pub fn hash_at(&self, number: u64) -> Option<H256> {
+        self.headers.iter().find(|(n, _)| *n == number).map(|(_, h)| *h)
+    }
+
+    fn tip(&self) -> Option<u64> {
+        self.headers.back().map(|(n, _)| *n)
+    }
+
+    /// Record a new header. Some = the chain we processed was reorganized
+    /// (the fork point may be deeper; see `unconfirmed_below`). A header seen
+    /// twice is ignored.
+    pub fn observe(&mut self, number: u64, hash: H256, parent_hash: H256) -> Option<Reorg> {
+        if self.hash_at(number) == Some(hash) {
+            return None;
+        }
+        let tip = self.tip();
+
+        // Parent mismatch: block number-1 we processed was replaced too
+        let parent_orphaned = number
+            .checked_sub(1)
+            .and_then(|parent| self.hash_at(parent).map(|h| (parent, h)))
+            .filter(|(_, h)| *h != parent_hash)
+            .map(|(parent, _)| parent);
+        // Same or lower height than the tip: everything from here up is replaced
+        let replaced = tip.filter(|t| number <= *t).map(|_| number);
+
+        let fork_block = parent_orphaned.or(replaced);
+
+        if let Some(fork) = fork_block {
+            self.headers.retain(|(n, _)| *n < fork);
+        }
+        self.headers.push_back((number, hash));
+        while self.headers.len() > self.capacity {
+            self.headers.pop_front();
+        }
+
+        fork_block.map(|fork_block| Reorg { fork_block, tip: tip.unwrap_or(number).max(fork_block) })
+    }
+
+    /// The tracked block just below the current fork point, still to be
+    /// checked against the canonical chain (None = window exhausted)
+    pub fn unconfirmed_below(&self, reorg: &Reorg) -> Option<u64> {
+        let below = reorg.fork_block.checked_sub(1)?;
+        self.hash_at(below).map(|_| below)
+    }
+
+    /// Compare the block below the fork with its canonical hash. A mismatch
+    /// moves the fork point down one block (true = keep walking back).
+    pub fn confirm(&mut self, reorg: &mut Reorg, canonical_hash: H256) -> bool {
+        let Some(below) = self.unconfirmed_below(reorg) else {
+            return false;
+        };
+        if self.hash_at(below) == Some(canonical_hash) {
+            return false;
+        }
+        self.headers.retain(|(n, _)| *n != below);
+        reorg.fork_block = below;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn h(n: u64) -> H256 {
+        H256::from_low_u64_be(n)
+    }
+
+    /// Canonical chain: hash(n) = n, parent(n) = n - 1
+    fn chain(detector: &mut ReorgDetector, from: u64, to: u64) {
+        for n in from..=to {
+            assert_eq!(detector.observe(n, h(n), h(n - 1)), None);
+        }
+    }
+
+    #[test]
+    fn test_parent_hash_mismatch_reports_reorg_and_walks_back_to_the_fork() {
+        let mut detector = ReorgDetector::new(16);
+        chain(&mut detector, 100, 110);
+        assert_eq!(detector.observe(110, h(110), h(109)), None, "duplicate header");
+
+        // 109' and 110' replaced 109 and 110; we first see 111' (parent 110')
+        let mut reorg = detector.observe(111, h(1111), h(1110)).expect("reorg");
+        assert_eq!(reorg, Reorg { fork_block: 110, tip: 110 });
+
+        // Canonical 109 is 109' too: the fork moves down once, then 108 matches
+        assert_eq!(detector.unconfirmed_below(&reorg), Some(109));
+        assert!(detector.confirm(&mut reorg, h(1109)));
+        assert_eq!(detector.unconfirmed_below(&reorg), Some(108));
+        assert!(!detector.confirm(&mut reorg, h(108)));
+        assert_eq!(reorg.fork_block, 109);
+        assert_eq!(reorg.depth(), 2);
+
+        // Chain continues from the new head without further reports
+        assert_eq!(detector.observe(112, h(1112), h(1111)), None);
+        assert_eq!(detector.hash_at(111), Some(h(1111)));
+    }
+
+    #[test]
+    fn test_replaced_height_and_window_limits() {
+        let mut detector = ReorgDetector::new(4);
+        chain(&mut detector, 1, 10);
+        assert_eq!(detector.hash_at(6), None, "outside the window");
+
+        // A sibling of block 9 arrives after 10 was processed
+        let reorg = detector.observe(9, h(909), h(8)).expect("reorg");
+        assert_eq!(reorg, Reorg { fork_block: 9, tip: 10 });
+        assert_eq!(reorg.depth(), 2);
+        assert_eq!(detector.hash_at(10), None);
+
+        // A gap (missed headers after a reconnect) is not a reorg
+        assert_eq!(detector.observe(20, h(20), h(19)), None);
+        // Walking back stops at the window edge
+        let mut deep = detector.observe(20, h(2020), h(19)).expect("reorg");
+        assert_eq!(detector.unconfirmed_below(&deep), None);
+        assert!(!detector.confirm(&mut deep, h(0)));
+    }
+}
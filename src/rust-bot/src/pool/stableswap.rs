@@ -0,0 +1,212 @@
+//! Curve StableSwap Math
+//!
+//! Integer port of the plain-pool StableSwap invariant (curve-contract
+//! `StableSwap` templates, A_PRECISION = 100): `get_d` and `get_y` by Newton
+//! iteration, `get_dy` as the pool's view function computes it. Balances are
+//! normalized to 18 decimals (xp) before any invariant math, so USDC (6) and
+//! DAI (18) trade at par near balance.
+//!
+//! Invariant (n coins, D = total value at equal prices):
+//!   A·nⁿ·Σx + D = A·D·nⁿ + Dⁿ⁺¹ / (nⁿ·Πx)
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use ethers::types::U256;
+
+/// A() is stored × A_PRECISION internally by current pools
+pub const A_PRECISION: u64 = 100;
+
+/// fee() denominator (4_000_000 = 0.04%)
+pub const FEE_DENOMINATOR: u64 = 10_000_000_000;
+
+/// Newton iterations before giving up (the contracts revert; we return None)
+const MAX_ITERATIONS: usize = 255;
+
+/// Balances scaled to 18 decimals
+pub fn normalize(balances: &[U256], decimals: &[u8]) -> Vec<U256> {
+    balances
+        .iter()
+        .zip(decimals)
+        .map(|(b, d)| *b * U256::exp10(18usize.saturating_sub(*d as usize)))
+        .collect()
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b { a - b } else { b - a }
+}
+
+/// Invariant D for normalized balances `xp` and amplification `amp` (A(), not
+/// precision-scaled). None = empty/zero pool or no convergence.
+pub fn get_d(xp: &[U256], amp: U256) -> Option<U256> {
+    let n = U256::from(xp.len());
+    let s = xp.iter().fold(U256::zero(), |acc, x| acc + *x);
+    if s.is_zero() {
+        return Some(U256::zero());
+    }
+    if xp.iter().any(|x| x.is_zero()) {
+        return None;
+    }
+
+    let a_precision = U256::from(A_PRECISION);
+    let ann = amp * a_precision * n;
+    let mut d = s;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for x in xp {
+            d_p = d_p * d / (*x * n);
+        }
+        let d_prev = d;
+        d = (ann * s / a_precision + d_p * n) * d
+            / ((ann - a_precision) * d / a_precision + (n + 1) * d_p);
+        if abs_diff(d, d_prev) <= U256::one() {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// New normalized balance of coin `j` when coin `i` is set to `x`, holding D
+pub fn get_y(i: usize, j: usize, x: U256, xp: &[U256], amp: U256) -> Option<U256> {
+    let n_coins = xp.len();
+    if i == j || i >= n_coins || j >= n_coins {
+        return None;
+    }
+    let n = U256::from(n_coins);
+    let a_precision = U256::from(A_PRECISION);
+    let d = get_d(xp, amp)?;
+    let ann = amp * a_precision * n;
+
+    let mut c = d;
+    let mut s = U256::zero();
+    for (k, balance) in xp.iter().enumerate() {
+        let x_k = if k == i {
+            x
+        } else if k != j {
+            *balance
+        } else {
+            continue;
+        };
+        if x_k.is_zero() {
+            return None;
+        }
+        s += x_k;
+        c = c * d / (x_k * n);
+    }
+    c = c * d * a_precision / (ann * n);
+    let b = s + d * a_precision / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let denominator = U256::from(2) * y + b;
+        if denominator <= d {
+            return None;
+        }
+        y = (y * y + c) / (denominator - d);
+        if abs_diff(y, y_prev) <= U256::one() {
+            return Some(y);
+        }
+    }
+    None
+}
+
+/// Output of coin `j` for `dx` raw units of coin `i`, before the swap fee
+/// (raw units of `j`). None when the pool can't serve the trade.
+pub fn get_dy_before_fee(
+    i: usize,
+    j: usize,
+    dx: U256,
+    balances: &[U256],
+    decimals: &[u8],
+    amp: U256,
+) -> Option<U256> {
+    if balances.len() != decimals.len() || i >= balances.len() || j >= balances.len() {
+        return None;
+    }
+    let xp = normalize(balances, decimals);
+    let scale_i = U256::exp10(18usize.saturating_sub(decimals[i] as usize));
+    let scale_j = U256::exp10(18usize.saturating_sub(decimals[j] as usize));
+
+    let x = xp[i] + dx * scale_i;
+    let y = get_y(i, j, x, &xp, amp)?;
+    // -1 rounds in the pool's favor, as the contract does
+    let dy = xp[j].checked_sub(y)?.checked_sub(U256::one())?;
+    Some(dy / scale_j)
+}
+
+/// `get_dy(i, j, dx)`: output after the pool fee (`fee` at FEE_DENOMINATOR)
+pub fn get_dy(
+    i: usize,
+    j: usize,
+    dx: U256,
+    balances: &[U256],
+    decimals: &[u8],
+    amp: U256,
+    fee: U256,
+) -> Option<U256> {
+    let dy = get_dy_before_fee(i, j, dx, balances, decimals, amp)?;
+    Some(dy - dy * fee / U256::from(FEE_DENOMINATOR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(amount: u64, decimals: u8) -> U256 {
+        U256::from(amount) * U256::exp10(decimals as usize)
+    }
+
+    /// Reference pool: 3pool layout (DAI 18, USDC 6, USDT 6), A = 2000,
+    /// fee 0.01%. Expected values are StableSwap3Pool.get_dy evaluated at
+    /// this state (the pre-A_PRECISION template: identical integer results
+    /// for integral A).
+    #[test]
+    fn test_get_dy_matches_reference_pool_values() {
+        let decimals = [18u8, 6, 6];
+        let balances = [units(50_000_000, 18), units(40_000_000, 6), units(30_000_000, 6)];
+        let amp = U256::from(2000);
+        let fee = U256::from(1_000_000);
+
+        let xp = normalize(&balances, &decimals);
+        assert_eq!(xp[1], units(40_000_000, 18));
+        let d = get_d(&xp, amp).unwrap();
+        // D sits just below Σx for an imbalanced pool
+        assert_eq!(d, U256::from_dec_str("119998667392181804746749770").unwrap());
+
+        // 1,000 USDC → USDT: USDT is the scarcer coin, so slightly under par
+        let dy = get_dy(1, 2, units(1_000, 6), &balances, &decimals, amp, fee).unwrap();
+        assert_eq!(dy, U256::from(999_722_355u64));
+        // 1,000 USDT → USDC: slightly over par before the fee
+        let dy = get_dy(2, 1, units(1_000, 6), &balances, &decimals, amp, fee).unwrap();
+        assert_eq!(dy, U256::from(1_000_077_638u64));
+        // 1,000 DAI (18) → USDC (6): decimals scale both ways
+        let dy = get_dy(0, 1, units(1_000, 18), &balances, &decimals, amp, fee).unwrap();
+        assert_eq!(dy, U256::from(999_793_394u64));
+    }
+
+    #[test]
+    fn test_balanced_pool_trades_near_par_and_bounds() {
+        // Balanced 2-pool (USDC 6 / DAI 18), A = 200, fee 0.04%
+        let decimals = [6u8, 18];
+        let balances = [units(10_000_000, 6), units(10_000_000, 18)];
+        let amp = U256::from(200);
+        let fee = U256::from(4_000_000);
+
+        let before_fee = get_dy_before_fee(0, 1, units(100, 6), &balances, &decimals, amp).unwrap();
+        let after_fee = get_dy(0, 1, units(100, 6), &balances, &decimals, amp, fee).unwrap();
+        // Small trade on a balanced pool: par within 1 ppm, then the 4 bps fee
+        assert!(before_fee <= units(100, 18) && before_fee > units(100, 18) - U256::exp10(14));
+        assert_eq!(after_fee, before_fee - before_fee * fee / U256::from(FEE_DENOMINATOR));
+
+        // Price impact grows with size but output never exceeds the balance
+        let whale = get_dy_before_fee(0, 1, units(50_000_000, 6), &balances, &decimals, amp).unwrap();
+        assert!(whale < balances[1]);
+        assert!(whale > units(9_000_000, 18));
+
+        // Degenerate inputs
+        assert_eq!(get_dy(0, 0, units(1, 6), &balances, &decimals, amp, fee), None);
+        assert_eq!(get_dy(0, 2, units(1, 6), &balances, &decimals, amp, fee), None);
+        assert_eq!(get_d(&[U256::zero(), U256::zero()], amp), Some(U256::zero()));
+    }
+}
@@ -7,9 +7,21 @@
 //! Created: 2026-01-27
 //! Modified: 2026-01-29 - Added V3 pool support
 //! Modified: 2026-02-01 - Key by pool Address (fixes collision for same-DexType dual-USDC pools)
-
-use crate::types::{DexType, PoolState, V3PoolState};
-use dashmap::DashMap;
+//! Modified: 2026-10-16 - replace_v3_pool() for pool migrations
+//! Modified: 2026-10-16 - remove_v3_pool() for backtest replay (pools leaving the log)
+//! Modified: 2026-10-16 - Stale pool set (reorg invalidation until the next poll resync)
+//! Modified: 2026-10-16 - Curve StableSwap pool states
+//! Modified: 2026-10-16 - Quarantined pool set (pool health: excluded from detection until recovered)
+//! Modified: 2026-10-16 - adapters_for_pair(): a pair's pools as boxed PoolAdapters
+//! Modified: 2026-10-16 - Per-pool last_updated incl. event-sync confirmation (block-age staleness)
+//! Modified: 2026-10-16 - get_v3_pool_at() / set_v3_fee() (Algebra dynamic fee kept current under event sync)
+//! Modified: 2026-10-16 - DODO V2 PMM pool states
+//! Modified: 2026-10-16 - get_pool_at() (V2 by address, for receipt-applied Sync logs)
+//! Modified: 2026-10-16 - detached_copy() (shadow state for mid-block re-scans)
+
+use super::adapter::PoolAdapter;
+use crate::types::{CurvePoolState, DexType, DodoPoolState, PoolState, V3PoolState};
+use dashmap::{DashMap, DashSet};
 use ethers::types::Address;
 use std::sync::Arc;
 use tracing::debug;
@@ -27,6 +39,18 @@ pub struct PoolStateManager {
     pools: Arc<DashMap<Address, PoolState>>,
     /// V3 Pool states indexed by pool address
     v3_pools: Arc<DashMap<Address, V3PoolState>>,
+    /// Curve pool states indexed by pool address
+    curve_pools: Arc<DashMap<Address, CurvePoolState>>,
+    /// DODO V2 pool states indexed by pool address
+    dodo_pools: Arc<DashMap<Address, DodoPoolState>>,
+    /// Pools (V2 or V3) whose state may come from a reorged-out block
+    stale: Arc<DashSet<Address>>,
+    /// Pools quarantined by pool health (health.rs) — state kept and synced,
+    /// but excluded from detection
+    quarantined: Arc<DashSet<Address>>,
+    /// Block through which event sync confirmed a pool unchanged (a quiet pool
+    /// emits no log, so its state's last_updated alone would look old)
+    synced_through: Arc<DashMap<Address, u64>>,
 }
 
 impl PoolStateManager {
@@ -35,6 +59,11 @@ impl PoolStateManager {
         Self {
             pools: Arc::new(DashMap::new()),
             v3_pools: Arc::new(DashMap::new()),
+            curve_pools: Arc::new(DashMap::new()),
+            dodo_pools: Arc::new(DashMap::new()),
+            stale: Arc::new(DashSet::new()),
+            quarantined: Arc::new(DashSet::new()),
+            synced_through: Arc::new(DashMap::new()),
         }
     }
 
@@ -58,6 +87,11 @@ impl PoolStateManager {
             .map(|entry| entry.value().clone())
     }
 
+    /// Get a V2 pool state by pool address
+    pub fn get_pool_at(&self, address: &Address) -> Option<PoolState> {
+        self.pools.get(address).map(|entry| entry.value().clone())
+    }
+
     /// Get all pools for a specific pair across all DEXs
     pub fn get_pools_for_pair(&self, pair_symbol: &str) -> Vec<PoolState> {
         self.pools
@@ -129,6 +163,19 @@ impl PoolStateManager {
         self.v3_pools.insert(pool.address, pool);
     }
 
+    /// Swap a V3 pool to a new address (pool migration). Returns the removed
+    /// state, or None if `old` was not tracked (nothing is inserted then).
+    pub fn replace_v3_pool(&self, old: &Address, pool: V3PoolState) -> Option<V3PoolState> {
+        let removed = self.v3_pools.remove(old).map(|(_, v)| v)?;
+        self.v3_pools.insert(pool.address, pool);
+        Some(removed)
+    }
+
+    /// Remove a V3 pool from state by address
+    pub fn remove_v3_pool(&self, address: &Address) -> Option<V3PoolState> {
+        self.v3_pools.remove(address).map(|(_, v)| v)
+    }
+
     /// Get V3 pool state for a specific DEX and pair (iterates; O(n) for ~30 pools)
     pub fn get_v3_pool(&self, dex: DexType, pair_symbol: &str) -> Option<V3PoolState> {
         self.v3_pools
@@ -140,6 +187,19 @@ impl PoolStateManager {
             .map(|entry| entry.value().clone())
     }
 
+    /// Get a V3 pool state by pool address
+    pub fn get_v3_pool_at(&self, address: &Address) -> Option<V3PoolState> {
+        self.v3_pools.get(address).map(|entry| entry.value().clone())
+    }
+
+    /// Set a V3 pool's fee alone (Algebra globalState read between syncs);
+    /// price, liquidity and last_updated are left as they are. Returns the
+    /// previous fee, or None if the pool is not tracked.
+    pub fn set_v3_fee(&self, address: &Address, fee: u32) -> Option<u32> {
+        let mut pool = self.v3_pools.get_mut(address)?;
+        Some(std::mem::replace(&mut pool.fee, fee))
+    }
+
     /// Get all V3 pools for a specific pair across all fee tiers
     pub fn get_v3_pools_for_pair(&self, pair_symbol: &str) -> Vec<V3PoolState> {
         self.v3_pools
@@ -173,6 +233,183 @@ impl PoolStateManager {
 
         (v2_count, v3_count, min_block, max_block)
     }
+
+    // === Curve Pool Methods ===
+
+    /// Add or update a Curve pool state
+    pub fn update_curve_pool(&self, pool: CurvePoolState) {
+        debug!(
+            "Updating Curve pool: {} @ {:?} - balances: {:?}, A: {}",
+            pool.pair.symbol, pool.address, pool.balances, pool.amp
+        );
+        self.curve_pools.insert(pool.address, pool);
+    }
+
+    /// Get all Curve pools tracking a specific pair
+    pub fn get_curve_pools_for_pair(&self, pair_symbol: &str) -> Vec<CurvePoolState> {
+        self.curve_pools
+            .iter()
+            .filter(|entry| entry.value().pair.symbol == pair_symbol)
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    /// Get all Curve pool states
+    pub fn get_all_curve_pools(&self) -> Vec<CurvePoolState> {
+        self.curve_pools.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    // === DODO Pool Methods ===
+
+    /// Add or update a DODO V2 pool state
+    pub fn update_dodo_pool(&self, pool: DodoPoolState) {
+        debug!(
+            "Updating DODO pool: {} @ {:?} - B: {}, Q: {}, R: {:?}",
+            pool.pair.symbol, pool.address, pool.pmm.b, pool.pmm.q, pool.pmm.r
+        );
+        self.dodo_pools.insert(pool.address, pool);
+    }
+
+    /// Get all DODO pools tracking a specific pair
+    pub fn get_dodo_pools_for_pair(&self, pair_symbol: &str) -> Vec<DodoPoolState> {
+        self.dodo_pools
+            .iter()
+            .filter(|entry| entry.value().pair.symbol == pair_symbol)
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    /// Get all DODO pool states
+    pub fn get_all_dodo_pools(&self) -> Vec<DodoPoolState> {
+        self.dodo_pools.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    // === Adapters ===
+
+    /// Every pool of a pair behind the PoolAdapter interface: V3 (incl.
+    /// Algebra), then V2, then Curve, then DODO
+    pub fn adapters_for_pair(&self, pair_symbol: &str) -> Vec<Box<dyn PoolAdapter>> {
+        let mut adapters: Vec<Box<dyn PoolAdapter>> = Vec::new();
+        adapters.extend(self.get_v3_pools_for_pair(pair_symbol).into_iter().map(|p| Box::new(p) as Box<dyn PoolAdapter>));
+        adapters.extend(self.get_pools_for_pair(pair_symbol).into_iter().map(|p| Box::new(p) as Box<dyn PoolAdapter>));
+        adapters.extend(self.get_curve_pools_for_pair(pair_symbol).into_iter().map(|p| Box::new(p) as Box<dyn PoolAdapter>));
+        adapters.extend(self.get_dodo_pools_for_pair(pair_symbol).into_iter().map(|p| Box::new(p) as Box<dyn PoolAdapter>));
+        adapters
+    }
+
+    // === Reorg invalidation ===
+
+    /// Pools (V2 + V3 + Curve + DODO) last updated at or after `block`
+    pub fn pools_updated_since(&self, block: u64) -> Vec<Address> {
+        let v2 = self.pools.iter().filter(|e| e.value().last_updated >= block).map(|e| *e.key());
+        let v3 = self.v3_pools.iter().filter(|e| e.value().last_updated >= block).map(|e| *e.key());
+        let curve = self.curve_pools.iter().filter(|e| e.value().last_updated >= block).map(|e| *e.key());
+        let dodo = self.dodo_pools.iter().filter(|e| e.value().last_updated >= block).map(|e| *e.key());
+        v2.chain(v3).chain(curve).chain(dodo).collect()
+    }
+
+    /// Flag pools whose state can't be trusted (reorg) until `clear_stale`
+    pub fn mark_stale(&self, addresses: &[Address]) {
+        for address in addresses {
+            self.stale.insert(*address);
+        }
+    }
+
+    /// Pool flagged by `mark_stale` and not resynced since
+    pub fn is_pool_stale(&self, address: &Address) -> bool {
+        self.stale.contains(address)
+    }
+
+    /// All stale pools
+    pub fn stale_pools(&self) -> Vec<Address> {
+        self.stale.iter().map(|a| *a).collect()
+    }
+
+    /// Pools resynced from the chain: trusted again
+    pub fn clear_stale(&self, addresses: &[Address]) {
+        for address in addresses {
+            self.stale.remove(address);
+        }
+    }
+
+    // === Block-age staleness ===
+
+    /// Event sync covered `addresses` at `block` (no log = state unchanged)
+    pub fn mark_synced(&self, addresses: &[Address], block: u64) {
+        for address in addresses {
+            self.synced_through.insert(*address, block);
+        }
+    }
+
+    /// Block the pool's state is known current at: its own last_updated, or
+    /// a later event-sync confirmation. None = unknown pool.
+    pub fn pool_last_updated(&self, address: &Address) -> Option<u64> {
+        let state_block = self
+            .v3_pools
+            .get(address)
+            .map(|p| p.last_updated)
+            .or_else(|| self.pools.get(address).map(|p| p.last_updated))
+            .or_else(|| self.curve_pools.get(address).map(|p| p.last_updated))
+            .or_else(|| self.dodo_pools.get(address).map(|p| p.last_updated))?;
+        let synced = self.synced_through.get(address).map(|b| *b).unwrap_or(0);
+        Some(state_block.max(synced))
+    }
+
+    /// Blocks since the pool's state was last known current
+    pub fn pool_age_blocks(&self, address: &Address, current_block: u64) -> Option<u64> {
+        self.pool_last_updated(address).map(|b| current_block.saturating_sub(b))
+    }
+
+    /// Pools (V2, V3, Curve, DODO) older than `max_age_blocks` at `current_block`
+    pub fn aged_pool_count(&self, current_block: u64, max_age_blocks: u64) -> usize {
+        let addresses = self
+            .pools
+            .iter()
+            .map(|e| *e.key())
+            .chain(self.v3_pools.iter().map(|e| *e.key()))
+            .chain(self.curve_pools.iter().map(|e| *e.key()))
+            .chain(self.dodo_pools.iter().map(|e| *e.key()))
+            .collect::<Vec<_>>();
+        addresses
+            .iter()
+            .filter(|a| self.pool_age_blocks(a, current_block).is_some_and(|age| age > max_age_blocks))
+            .count()
+    }
+
+    // === Pool health quarantine ===
+
+    /// Exclude a pool from detection (true if it wasn't already)
+    pub fn quarantine_pool(&self, address: Address) -> bool {
+        self.quarantined.insert(address)
+    }
+
+    /// Let a quarantined pool back into detection (true if it was quarantined)
+    pub fn reinstate_pool(&self, address: &Address) -> bool {
+        self.quarantined.remove(address).is_some()
+    }
+
+    pub fn is_pool_quarantined(&self, address: &Address) -> bool {
+        self.quarantined.contains(address)
+    }
+
+    /// All quarantined pools
+    pub fn quarantined_pools(&self) -> Vec<Address> {
+        self.quarantined.iter().map(|a| *a).collect()
+    }
+
+    /// Deep copy with its own maps: writes to the copy never reach this
+    /// manager (clone() shares the maps). Used for mid-block shadow state.
+    pub fn detached_copy(&self) -> Self {
+        Self {
+            pools: Arc::new((*self.pools).clone()),
+            v3_pools: Arc::new((*self.v3_pools).clone()),
+            curve_pools: Arc::new((*self.curve_pools).clone()),
+            dodo_pools: Arc::new((*self.dodo_pools).clone()),
+            stale: Arc::new((*self.stale).clone()),
+            quarantined: Arc::new((*self.quarantined).clone()),
+            synced_through: Arc::new((*self.synced_through).clone()),
+        }
+    }
 }
 
 impl Default for PoolStateManager {
@@ -186,6 +423,11 @@ impl Clone for PoolStateManager {
         Self {
             pools: Arc::clone(&self.pools),
             v3_pools: Arc::clone(&self.v3_pools),
+            curve_pools: Arc::clone(&self.curve_pools),
+            dodo_pools: Arc::clone(&self.dodo_pools),
+            stale: Arc::clone(&self.stale),
+            quarantined: Arc::clone(&self.quarantined),
+            synced_through: Arc::clone(&self.synced_through),
         }
     }
 }
@@ -289,4 +531,47 @@ mod tests {
         assert_eq!(min, 100);
         assert_eq!(max, 100);
     }
+
+    #[test]
+    fn test_reorg_marks_recently_updated_pools_stale_until_cleared() {
+        let manager = PoolStateManager::new();
+        let old = create_test_pool(DexType::Uniswap, "ETH/USDC", 1000, 2000);
+        let mut recent = create_test_pool(DexType::Sushiswap, "ETH/USDC", 1000, 2000);
+        recent.last_updated = 105;
+        manager.update_pool(old.clone());
+        manager.update_pool(recent.clone());
+
+        let suspect = manager.pools_updated_since(103);
+        assert_eq!(suspect, vec![recent.address]);
+        manager.mark_stale(&suspect);
+        assert!(manager.is_pool_stale(&recent.address));
+        assert!(!manager.is_pool_stale(&old.address));
+
+        // Shared across clones (detector holds one), cleared by a resync
+        let detector_view = manager.clone();
+        assert_eq!(detector_view.stale_pools(), vec![recent.address]);
+        manager.clear_stale(&suspect);
+        assert!(!detector_view.is_pool_stale(&recent.address));
+    }
+
+    #[test]
+    fn test_pool_age_from_state_and_event_sync() {
+        let manager = PoolStateManager::new();
+        let quiet = create_test_pool(DexType::Uniswap, "ETH/USDC", 1000, 2000);
+        let mut fresh = create_test_pool(DexType::Sushiswap, "ETH/USDC", 1000, 2000);
+        fresh.last_updated = 104;
+        manager.update_pool(quiet.clone());
+        manager.update_pool(fresh.clone());
+
+        assert_eq!(manager.pool_age_blocks(&quiet.address, 104), Some(4));
+        assert_eq!(manager.pool_age_blocks(&fresh.address, 104), Some(0));
+        assert_eq!(manager.pool_age_blocks(&Address::zero(), 104), None);
+        assert_eq!(manager.aged_pool_count(104, 2), 1);
+
+        // An event sync with no log for the quiet pool confirms it at 104
+        manager.mark_synced(&[quiet.address], 104);
+        assert_eq!(manager.pool_last_updated(&quiet.address), Some(104));
+        assert_eq!(manager.aged_pool_count(104, 2), 0);
+        assert_eq!(manager.aged_pool_count(108, 2), 2);
+    }
 }
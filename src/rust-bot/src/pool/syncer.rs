@@ -214,9 +214,12 @@ impl<P: Middleware + 'static> PoolSyncer<P> {
             // V3 types are handled by V3PoolSyncer, not this V2 syncer
             DexType::UniswapV3_001 | DexType::UniswapV3_005 | DexType::UniswapV3_030 | DexType::UniswapV3_100
             | DexType::SushiV3_001 | DexType::SushiV3_005 | DexType::SushiV3_030
-            | DexType::QuickswapV3 => {
+            | DexType::QuickswapV3
+            | DexType::KyberElastic_001 | DexType::KyberElastic_004 | DexType::KyberElastic_030 | DexType::KyberElastic_100 => {
                 anyhow::bail!("V3 pools should be synced using V3PoolSyncer")
             }
+            DexType::CurveStable => anyhow::bail!("Curve pools should be synced using CurvePoolSyncer"),
+            DexType::DodoV2 => anyhow::bail!("DODO pools should be synced using DodoPoolSyncer"),
         };
 
         let factory = IUniswapV2Factory::new(factory_address, Arc::clone(&self.provider));
@@ -11,6 +11,7 @@
 //! Author: AI-Generated
 //! Created: 2026-01-30
 //! Modified: 2026-01-30 - Initial implementation for V2↔V3 cross-protocol arb
+//! Modified: 2026-10-16 - sync_reserves(): single-pool refresh (reorg resync of stale pools)
 
 use crate::types::{DexType, PoolState, TradingPair};
 use anyhow::{Context, Result};
@@ -116,43 +117,7 @@ impl<P: Middleware + 'static> V2PoolSyncer<P> {
     ) -> Vec<PoolState> {
         use futures::future::join_all;
 
-        let tasks: Vec<_> = known_pools.iter().map(|pool| {
-            let provider = Arc::clone(&self.provider);
-            let pool_address = pool.address;
-            let dex = pool.dex;
-            let pair = pool.pair.clone();
-            let token0_decimals = pool.token0_decimals;
-            let token1_decimals = pool.token1_decimals;
-
-            async move {
-                let contract = IV2Pair::new(pool_address, provider.clone());
-                let reserves = contract.get_reserves().call().await;
-                let block = provider.get_block_number().await;
-
-                match (reserves, block) {
-                    (Ok((r0, r1, _ts)), Ok(bn)) => {
-                        Some(PoolState {
-                            address: pool_address,
-                            dex,
-                            pair,
-                            reserve0: U256::from(r0),
-                            reserve1: U256::from(r1),
-                            last_updated: bn.as_u64(),
-                            token0_decimals,
-                            token1_decimals,
-                        })
-                    }
-                    (Err(e), _) => {
-                        warn!("V2 sync failed for {:?}: reserves error: {}", pool_address, e);
-                        None
-                    }
-                    (_, Err(e)) => {
-                        warn!("V2 sync failed for {:?}: block number error: {}", pool_address, e);
-                        None
-                    }
-                }
-            }
-        }).collect();
+        let tasks: Vec<_> = known_pools.iter().map(|pool| self.sync_reserves(pool)).collect();
 
         let results = join_all(tasks).await;
 
@@ -167,6 +132,30 @@ impl<P: Middleware + 'static> V2PoolSyncer<P> {
 
         updated
     }
+
+    /// Refresh one known pool's reserves. None = the read failed (logged).
+    pub async fn sync_reserves(&self, pool: &PoolState) -> Option<PoolState> {
+        let contract = IV2Pair::new(pool.address, Arc::clone(&self.provider));
+        let reserves = contract.get_reserves().call().await;
+        let block = self.provider.get_block_number().await;
+
+        match (reserves, block) {
+            (Ok((r0, r1, _ts)), Ok(bn)) => Some(PoolState {
+                reserve0: U256::from(r0),
+                reserve1: U256::from(r1),
+                last_updated: bn.as_u64(),
+                ..pool.clone()
+            }),
+            (Err(e), _) => {
+                warn!("V2 sync failed for {:?}: reserves error: {}", pool.address, e);
+                None
+            }
+            (_, Err(e)) => {
+                warn!("V2 sync failed for {:?}: block number error: {}", pool.address, e);
+                None
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -21,7 +21,11 @@
 //! Author: AI-Generated
 //! Created: 2026-01-28
 //! Modified: 2026-01-29 - Drop 1% fee tier, add parallel sync
+//! Modified: 2026-10-16 - Per-pool refresh split out as refresh_v3_pool (PoolAdapter::sync)
+//! Modified: 2026-10-16 - KyberSwap Elastic pools dispatch to KyberPoolSyncer
+//! Modified: 2026-10-16 - read_algebra_fees(): targeted globalState() fee read for event-driven sync
 
+use super::kyber_syncer::KyberPoolSyncer;
 use crate::types::{BotConfig, DexType, TradingPair, V3PoolState};
 use anyhow::{Context, Result};
 use ethers::prelude::*;
@@ -82,6 +86,15 @@ pub const SUSHI_V3_FEE_TIERS: [(u32, DexType); 3] = [
     (3000, DexType::SushiV3_030),  // 0.30% - standard tier
 ];
 
+/// KyberSwap Elastic fee tiers in V3 fee units (1e-6; the pool's swapFeeUnits × 10).
+/// Detection-only — whitelisted by address, never routed through ArbExecutor.
+pub const KYBER_ELASTIC_FEE_TIERS: [(u32, DexType); 4] = [
+    (100, DexType::KyberElastic_001),   // 0.01% - stablecoin pairs
+    (400, DexType::KyberElastic_004),   // 0.04% - stable/correlated pairs
+    (3000, DexType::KyberElastic_030),  // 0.30% - standard tier
+    (10000, DexType::KyberElastic_100), // 1.00% - exotic pairs
+];
+
 /// Syncs V3 pool state from blockchain
 pub struct V3PoolSyncer<P> {
     provider: Arc<P>,
@@ -184,72 +197,10 @@ impl<P: Middleware + 'static> V3PoolSyncer<P> {
             }
         };
 
-        let futs: Vec<_> = known_pools.iter().map(|pool| {
-            let provider = Arc::clone(&self.provider);
-            let pool_state = pool.clone();
-
-            async move {
-                if pool_state.dex.is_quickswap_v3() {
-                    // Algebra pool: use globalState() instead of slot0()
-                    // globalState returns (price, tick, fee, ...) — fee is dynamic
-                    let contract = AlgebraPool::new(pool_state.address, provider);
-                    let gs_call = contract.global_state();
-                    let liq_call = contract.liquidity();
-                    let (gs_res, liq_res) = tokio::join!(
-                        gs_call.call(),
-                        liq_call.call()
-                    );
-
-                    match (gs_res, liq_res) {
-                        (Ok((sqrt_price, tick, fee, _, _, _, _)), Ok(liq)) => {
-                            Some(V3PoolState {
-                                sqrt_price_x96: sqrt_price, // uint160 decoded as U256, no truncation
-                                tick: tick as i32,
-                                fee: fee as u32, // dynamic fee from globalState
-                                liquidity: liq,
-                                last_updated: current_block,
-                                ..pool_state
-                            })
-                        }
-                        _ => {
-                            warn!(
-                                "Failed to fast-sync Algebra pool {} {:?} at {:?}",
-                                pool_state.pair.symbol, pool_state.dex, pool_state.address
-                            );
-                            None
-                        }
-                    }
-                } else {
-                    // Uniswap/SushiSwap V3: use slot0()
-                    let contract = UniswapV3Pool::new(pool_state.address, provider);
-                    let slot0_call = contract.slot_0();
-                    let liq_call = contract.liquidity();
-                    let (slot0_res, liq_res) = tokio::join!(
-                        slot0_call.call(),
-                        liq_call.call()
-                    );
-
-                    match (slot0_res, liq_res) {
-                        (Ok((sqrt_price, tick, _, _, _, _, _)), Ok(liq)) => {
-                            Some(V3PoolState {
-                                sqrt_price_x96: sqrt_price, // uint160 decoded as U256, no truncation
-                                tick: tick as i32,
-                                liquidity: liq,
-                                last_updated: current_block,
-                                ..pool_state
-                            })
-                        }
-                        _ => {
-                            warn!(
-                                "Failed to fast-sync pool {} {:?} at {:?}",
-                                pool_state.pair.symbol, pool_state.dex, pool_state.address
-                            );
-                            None
-                        }
-                    }
-                }
-            }
-        }).collect();
+        let futs: Vec<_> = known_pools
+            .iter()
+            .map(|pool| refresh_v3_pool(Arc::clone(&self.provider), pool.clone(), current_block))
+            .collect();
 
         join_all(futs)
             .await
@@ -434,13 +385,19 @@ impl<P: Middleware + 'static> V3PoolSyncer<P> {
     }
 
     /// Sync a single V3 pool by address (for event-driven updates)
-    /// Automatically uses globalState() for Algebra (QuickSwap V3) and slot0() for Uniswap/Sushi.
+    /// Automatically uses globalState() for Algebra (QuickSwap V3), getPoolState() for
+    /// KyberSwap Elastic and slot0() for Uniswap/Sushi.
     pub async fn sync_pool_by_address(
         &mut self,
         pool_address: Address,
         dex_type: DexType,
     ) -> Result<V3PoolState> {
-        let (sqrt_price_x96, tick, fee, liquidity, token0, token1) = if dex_type.is_quickswap_v3() {
+        let (sqrt_price_x96, tick, fee, liquidity, token0, token1) = if dex_type.is_kyber_elastic() {
+            let kyber = KyberPoolSyncer::new(Arc::clone(&self.provider));
+            let (sqrt_price, tick, liquidity) = kyber.read_state(pool_address).await?;
+            let (fee, token0, token1) = kyber.read_static(pool_address).await?;
+            (sqrt_price, tick, fee, liquidity, token0, token1)
+        } else if dex_type.is_quickswap_v3() {
             // Algebra pool: globalState() returns (price, tick, fee, ...)
             let pool = AlgebraPool::new(pool_address, Arc::clone(&self.provider));
             let (sqrt_price, tick, fee, _, _, _, _) = pool
@@ -493,6 +450,98 @@ impl<P: Middleware + 'static> V3PoolSyncer<P> {
     }
 }
 
+/// Re-read one known V3 pool's price, tick and liquidity (Algebra:
+/// globalState(), which also carries the dynamic fee; Kyber Elastic:
+/// getPoolState(); Uniswap/Sushi: slot0()). None = a read failed (logged).
+pub async fn refresh_v3_pool<P: Middleware + 'static>(
+    provider: Arc<P>,
+    pool_state: V3PoolState,
+    current_block: u64,
+) -> Option<V3PoolState> {
+    if pool_state.dex.is_kyber_elastic() {
+        KyberPoolSyncer::new(provider).refresh(pool_state, current_block).await
+    } else if pool_state.dex.is_quickswap_v3() {
+        // Algebra pool: use globalState() instead of slot0()
+        // globalState returns (price, tick, fee, ...) — fee is dynamic
+        let contract = AlgebraPool::new(pool_state.address, provider);
+        let gs_call = contract.global_state();
+        let liq_call = contract.liquidity();
+        let (gs_res, liq_res) = tokio::join!(
+            gs_call.call(),
+            liq_call.call()
+        );
+
+        match (gs_res, liq_res) {
+            (Ok((sqrt_price, tick, fee, _, _, _, _)), Ok(liq)) => {
+                Some(V3PoolState {
+                    sqrt_price_x96: sqrt_price, // uint160 decoded as U256, no truncation
+                    tick,
+                    fee: fee as u32, // dynamic fee from globalState
+                    liquidity: liq,
+                    last_updated: current_block,
+                    ..pool_state
+                })
+            }
+            _ => {
+                warn!(
+                    "Failed to fast-sync Algebra pool {} {:?} at {:?}",
+                    pool_state.pair.symbol, pool_state.dex, pool_state.address
+                );
+                None
+            }
+        }
+    } else {
+        // Uniswap/SushiSwap V3: use slot0()
+        let contract = UniswapV3Pool::new(pool_state.address, provider);
+        let slot0_call = contract.slot_0();
+        let liq_call = contract.liquidity();
+        let (slot0_res, liq_res) = tokio::join!(
+            slot0_call.call(),
+            liq_call.call()
+        );
+
+        match (slot0_res, liq_res) {
+            (Ok((sqrt_price, tick, _, _, _, _, _)), Ok(liq)) => {
+                Some(V3PoolState {
+                    sqrt_price_x96: sqrt_price, // uint160 decoded as U256, no truncation
+                    tick,
+                    liquidity: liq,
+                    last_updated: current_block,
+                    ..pool_state
+                })
+            }
+            _ => {
+                warn!(
+                    "Failed to fast-sync pool {} {:?} at {:?}",
+                    pool_state.pair.symbol, pool_state.dex, pool_state.address
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Current dynamic fee of each Algebra pool — globalState() alone, for
+/// event-driven sync (the Swap event carries price, liquidity and tick but
+/// not the fee). Pools whose read fails are left out and keep their last fee.
+pub async fn read_algebra_fees<P: Middleware + 'static>(provider: Arc<P>, pools: &[Address]) -> Vec<(Address, u32)> {
+    use futures::future::join_all;
+
+    let futs = pools.iter().map(|&address| {
+        let contract = AlgebraPool::new(address, Arc::clone(&provider));
+        async move {
+            match contract.global_state().call().await {
+                Ok((_, _, fee, _, _, _, _)) => Some((address, fee as u32)),
+                Err(e) => {
+                    warn!("Failed to read Algebra fee at {:?}: {}", address, e);
+                    None
+                }
+            }
+        }
+    });
+    join_all(futs).await.into_iter().flatten().collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,134 +1,471 @@
 //! Historical Price Logger
 //!
-//! Logs V3 pool price snapshots to CSV files for offline research and analysis.
+//! Logs V3 pool price snapshots for offline research and analysis.
 //! One row per pool per block, rotated daily.
 //!
-//! Output format (CSV):
+//! Columns:
 //!   timestamp, block, pair, dex, fee, price, tick, liquidity, sqrt_price_x96, address
 //!
-//! File naming: prices_YYYYMMDD.csv (auto-rotated at midnight UTC)
+//! Backends (PRICE_LOG_FORMAT):
+//!   csv      prices_YYYYMMDD.csv, gzipped once the day is closed (PRICE_LOG_COMPRESS)
+//!   parquet  prices_YYYYMMDD[_N].parquet, typed columns (cargo feature `parquet`)
+//! Files older than PRICE_LOG_RETAIN_DAYS are deleted (0 = keep all). See
+//! rotation.rs for naming and the maintenance thread.
+//!
+//! Block annotations (PRICE_LOG_BLOCKS): a companion blocks_YYYYMMDD.csv with
+//! one row per processed block, written by main.rs after execution so price
+//! rows join to trades by block number instead of timestamps:
+//!   timestamp, block, base_fee_wei, detected, suppressed, submitted, net_profit_usd
+//! Always CSV, rotated, compressed and expired like the price files.
+//!
+//! The block loop only snapshots the pools; rows are encoded and written by
+//! the price_log writer thread (io_writer.rs, lossy: a stalled disk drops the
+//! oldest batches). Compression and retention run on their own thread.
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-30
 //! Modified: 2026-01-30
+//! Modified: 2026-10-16 - File I/O moved to an isolated writer thread (lossy stream)
+//! Modified: 2026-10-16 - Row format shared with the backtest reader (csv_row)
+//! Modified: 2026-10-16 - Output backends (CSV / Parquet), gzip of closed days, retention; rows encoded off the block loop
+//! Modified: 2026-10-16 - Per-block annotations (blocks_YYYYMMDD.csv: base fee, detected / suppressed / submitted, net profit)
 
-use crate::types::V3PoolState;
-use chrono::{NaiveDate, Utc};
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod rotation;
+
+use crate::io_writer::{IsolatedWriter, StreamContract};
+use crate::types::{BotConfig, V3PoolState};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use ethers::types::U256;
+use rotation::Maintenance;
 use std::fs::{self, File, OpenOptions};
 use std::io::Write;
 use std::path::PathBuf;
-use tracing::{debug, info, warn};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{info, warn};
 
 /// CSV header for price log files
-const CSV_HEADER: &str = "timestamp,block,pair,dex,fee,price,tick,liquidity,sqrt_price_x96,address";
+pub(crate) const CSV_HEADER: &str = "timestamp,block,pair,dex,fee,price,tick,liquidity,sqrt_price_x96,address";
 
-/// Historical price logger — appends V3 pool snapshots to daily CSV files.
-pub struct PriceLogger {
+/// CSV header for block annotation files
+pub(crate) const BLOCKS_CSV_HEADER: &str = "timestamp,block,base_fee_wei,detected,suppressed,submitted,net_profit_usd";
+
+/// Row timestamp format (both files)
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+/// Output backend (PRICE_LOG_FORMAT)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceLogFormat {
+    Csv,
+    Parquet,
+}
+
+impl FromStr for PriceLogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            other => bail!("unknown price log format '{}' (csv | parquet)", other),
+        }
+    }
+}
+
+/// Backend, compression and retention of the price log
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceLogOptions {
+    pub format: PriceLogFormat,
+    /// Gzip CSV days once closed
+    pub compress: bool,
+    /// Days kept before today (0 = keep all)
+    pub retain_days: u32,
+    /// Write blocks_YYYYMMDD.csv (log_block)
+    pub blocks: bool,
+}
+
+impl PriceLogOptions {
+    /// From PRICE_LOG_FORMAT / PRICE_LOG_COMPRESS / PRICE_LOG_RETAIN_DAYS. An
+    /// unknown format, or parquet in a build without the feature, falls back to CSV.
+    pub fn from_config(config: &BotConfig) -> Self {
+        let format = match config.price_log_format.parse() {
+            Ok(PriceLogFormat::Parquet) if !cfg!(feature = "parquet") => {
+                warn!("PRICE_LOG_FORMAT=parquet ignored — built without the `parquet` feature (cargo build --features parquet); logging CSV");
+                PriceLogFormat::Csv
+            }
+            Ok(format) => format,
+            Err(e) => {
+                warn!("PRICE_LOG_FORMAT: {} — logging CSV", e);
+                PriceLogFormat::Csv
+            }
+        };
+        Self {
+            format,
+            compress: config.price_log_compress,
+            retain_days: config.price_log_retain_days,
+            blocks: config.price_log_blocks,
+        }
+    }
+}
+
+/// One block's research metadata (a blocks_YYYYMMDD.csv row). Counts and
+/// profit default to zero, so a block without trades still gets its row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockAnnotations {
+    pub block: u64,
+    /// Block header time
+    pub timestamp: DateTime<Utc>,
+    pub base_fee_wei: u128,
+    /// Opportunities out of the detector scan
+    pub detected: usize,
+    /// Of those, dropped by route cooldown
+    pub suppressed: usize,
+    /// Txs sent
+    pub submitted: usize,
+    /// Sum of the sent txs' net profit (USD, gas included; reverts count their gas)
+    pub net_profit_usd: f64,
+}
+
+impl BlockAnnotations {
+    pub fn new(block: u64, timestamp: DateTime<Utc>) -> Self {
+        Self { block, timestamp, base_fee_wei: 0, detected: 0, suppressed: 0, submitted: 0, net_profit_usd: 0.0 }
+    }
+
+    pub fn base_fee(mut self, base_fee: U256) -> Self {
+        self.base_fee_wei = base_fee.try_into().unwrap_or(u128::MAX);
+        self
+    }
+
+    pub fn detected(mut self, count: usize) -> Self {
+        self.detected = count;
+        self
+    }
+
+    pub fn suppressed(mut self, count: usize) -> Self {
+        self.suppressed = count;
+        self
+    }
+
+    pub fn submitted(mut self, count: usize) -> Self {
+        self.submitted = count;
+        self
+    }
+
+    pub fn net_profit_usd(mut self, usd: f64) -> Self {
+        self.net_profit_usd = usd;
+        self
+    }
+
+    /// One CSV row (newline-terminated) in BLOCKS_CSV_HEADER column order
+    pub fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{:.6}\n",
+            self.timestamp.format(TIMESTAMP_FORMAT),
+            self.block,
+            self.base_fee_wei,
+            self.detected,
+            self.suppressed,
+            self.submitted,
+            self.net_profit_usd,
+        )
+    }
+}
+
+/// One block's pool snapshots, encoded by the writer thread
+pub struct PriceBatch {
+    pub timestamp: DateTime<Utc>,
+    pub block: u64,
+    pub pools: Vec<V3PoolState>,
+}
+
+/// Writer thread input
+enum PriceLogItem {
+    Batch(PriceBatch),
+    Block(BlockAnnotations),
+    /// Close the open file (Parquet: write its footer)
+    Close,
+}
+
+/// An open day file
+enum DayFile {
+    Csv(File),
+    #[cfg(feature = "parquet")]
+    Parquet(Box<parquet::ParquetDayWriter>),
+}
+
+impl DayFile {
+    fn close(self) -> Result<()> {
+        match self {
+            DayFile::Csv(_) => Ok(()),
+            #[cfg(feature = "parquet")]
+            DayFile::Parquet(writer) => writer.close(),
+        }
+    }
+}
+
+/// Daily file state, owned by the writer thread
+struct PriceFile {
     /// Directory for price log files
     log_dir: PathBuf,
+    format: PriceLogFormat,
     /// Currently open file date (for rotation detection)
     current_date: Option<NaiveDate>,
-    /// Currently open file handle
-    file: Option<File>,
+    /// Currently open file
+    file: Option<DayFile>,
+    /// Open block annotation file and its date
+    blocks_file: Option<(NaiveDate, File)>,
+    /// Signalled on every day rotation
+    maintenance: Maintenance,
+}
+
+impl PriceFile {
+    fn write(&mut self, item: &PriceLogItem) -> Result<()> {
+        let batch = match item {
+            PriceLogItem::Batch(batch) => batch,
+            PriceLogItem::Block(annotations) => return self.write_block(annotations),
+            PriceLogItem::Close => return self.close(),
+        };
+        // Rotate file if date changed
+        let date = batch.timestamp.date_naive();
+        if self.current_date != Some(date) {
+            let rotated = self.current_date.is_some();
+            self.rotate_file(date)?;
+            if rotated {
+                self.maintenance.run(date);
+            }
+        }
+        match self.file.as_mut().ok_or_else(|| anyhow!("no open price log file"))? {
+            DayFile::Csv(file) => {
+                let timestamp = batch.timestamp.format(TIMESTAMP_FORMAT).to_string();
+                let rows: String = batch.pools.iter().map(|pool| csv_row(&timestamp, batch.block, pool)).collect();
+                file.write_all(rows.as_bytes()).context("PriceLogger write")?;
+                // Flush to ensure data is on disk
+                file.flush().context("PriceLogger flush")?;
+            }
+            #[cfg(feature = "parquet")]
+            DayFile::Parquet(writer) => writer.write(batch)?,
+        }
+        Ok(())
+    }
+
+    /// Append one block row, rotating the blocks file on the row's date
+    fn write_block(&mut self, annotations: &BlockAnnotations) -> Result<()> {
+        let date = annotations.timestamp.date_naive();
+        if self.blocks_file.as_ref().map(|(open, _)| *open) != Some(date) {
+            let name = rotation::blocks_file_name(date);
+            self.blocks_file = Some((date, self.open_csv(&name, BLOCKS_CSV_HEADER)?));
+        }
+        let (_, file) = self.blocks_file.as_mut().ok_or_else(|| anyhow!("no open blocks file"))?;
+        file.write_all(annotations.csv_row().as_bytes()).context("PriceLogger blocks write")?;
+        file.flush().context("PriceLogger blocks flush")?;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.current_date = None;
+        self.blocks_file = None;
+        match self.file.take() {
+            Some(file) => file.close(),
+            None => Ok(()),
+        }
+    }
+
+    /// Rotate to a new daily file
+    fn rotate_file(&mut self, date: NaiveDate) -> Result<()> {
+        // Close existing file
+        if let Err(e) = self.close() {
+            warn!("PriceLogger: failed to close previous file: {:#}", e);
+        }
+
+        self.file = Some(match self.format {
+            PriceLogFormat::Csv => DayFile::Csv(self.open_csv(&rotation::file_name(date, PriceLogFormat::Csv, 0), CSV_HEADER)?),
+            #[cfg(feature = "parquet")]
+            PriceLogFormat::Parquet => {
+                let path = rotation::next_parquet_path(&self.log_dir, date);
+                let writer = parquet::ParquetDayWriter::create(&path)?;
+                info!("PriceLogger: created new file {}", path.display());
+                DayFile::Parquet(Box::new(writer))
+            }
+            #[cfg(not(feature = "parquet"))]
+            PriceLogFormat::Parquet => bail!("built without the `parquet` feature"),
+        });
+        self.current_date = Some(date);
+        Ok(())
+    }
+
+    /// Open a day CSV for appending; `header` goes first in a new file
+    fn open_csv(&self, filename: &str, header: &str) -> Result<File> {
+        let filepath = self.log_dir.join(filename);
+
+        let file_exists = filepath.exists();
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&filepath)
+            .with_context(|| format!("PriceLogger: failed to open {}", filename))?;
+        // Write header if new file
+        if !file_exists {
+            writeln!(f, "{}", header)
+                .with_context(|| format!("PriceLogger: failed to write header to {}", filename))?;
+            info!("PriceLogger: created new file {}", filename);
+        } else {
+            info!("PriceLogger: appending to existing {}", filename);
+        }
+        Ok(f)
+    }
+}
+
+impl Drop for PriceFile {
+    /// Writer thread exit: a Parquet part still needs its footer
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            warn!("PriceLogger: failed to close price log on shutdown: {:#}", e);
+        }
+    }
+}
+
+/// One CSV row (newline-terminated) in CSV_HEADER column order
+pub(crate) fn csv_row(timestamp: &str, block_number: u64, pool: &V3PoolState) -> String {
+    format!(
+        "{},{},{},{},{},{:.10},{},{},{},{:?}\n",
+        timestamp,
+        block_number,
+        pool.pair.symbol,
+        pool.dex,
+        pool.fee,
+        pool.price(),
+        pool.tick,
+        pool.liquidity,
+        pool.sqrt_price_x96,
+        pool.address,
+    )
+}
+
+/// Historical price logger — writes V3 pool snapshots to daily files.
+pub struct PriceLogger {
+    writer: IsolatedWriter<PriceLogItem>,
+    /// PRICE_LOG_BLOCKS: log_block writes rows
+    blocks: bool,
 }
 
 impl PriceLogger {
-    /// Create a new PriceLogger. Creates the log directory if it doesn't exist.
-    pub fn new(log_dir: &str) -> Self {
+    /// Create a new PriceLogger. Creates the log directory if it doesn't exist
+    /// and queues a startup maintenance pass (compression, retention).
+    pub fn new(log_dir: &str, contract: StreamContract, options: PriceLogOptions) -> Self {
         let path = PathBuf::from(log_dir);
         if let Err(e) = fs::create_dir_all(&path) {
             warn!("Failed to create price log directory {}: {}", log_dir, e);
         }
-        info!("PriceLogger initialized: {}", log_dir);
+        info!(
+            "PriceLogger initialized: {} ({:?}, compress={}, retain={})",
+            log_dir,
+            options.format,
+            options.compress,
+            if options.retain_days == 0 { "all".to_string() } else { format!("{}d", options.retain_days) }
+        );
 
-        Self {
+        let maintenance = Maintenance::spawn(path.clone(), options.clone());
+        maintenance.run(Utc::now().date_naive());
+        let mut file = PriceFile {
             log_dir: path,
+            format: options.format,
             current_date: None,
             file: None,
+            blocks_file: None,
+            maintenance,
+        };
+        Self {
+            writer: IsolatedWriter::spawn(contract, move |item: &mut PriceLogItem| file.write(item)),
+            blocks: options.blocks,
         }
     }
 
     /// Log price snapshots for all V3 pools at a given block.
-    /// Appends one CSV row per pool. Rotates file daily.
+    /// Hands the snapshots to the writer thread, which encodes the rows.
     pub fn log_prices(&mut self, block_number: u64, pools: &[V3PoolState]) {
-        let now = Utc::now();
-        let today = now.date_naive();
-
-        // Rotate file if date changed
-        if self.current_date != Some(today) {
-            self.rotate_file(today);
+        let batch = PriceBatch { timestamp: Utc::now(), block: block_number, pools: pools.to_vec() };
+        if let Err(e) = self.writer.send(PriceLogItem::Batch(batch)) {
+            warn!("PriceLogger: batch for block {} not queued: {}", block_number, e);
         }
+    }
 
-        let file = match self.file.as_mut() {
-            Some(f) => f,
-            None => {
-                debug!("PriceLogger: no open file, skipping");
-                return;
-            }
-        };
-
-        let timestamp = now.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
-
-        for pool in pools {
-            let line = format!(
-                "{},{},{},{},{},{:.10},{},{},{},{:?}\n",
-                timestamp,
-                block_number,
-                pool.pair.symbol,
-                pool.dex,
-                pool.fee,
-                pool.price(),
-                pool.tick,
-                pool.liquidity,
-                pool.sqrt_price_x96,
-                pool.address,
-            );
-
-            if let Err(e) = file.write_all(line.as_bytes()) {
-                warn!("PriceLogger write error: {}", e);
-                return;
-            }
+    /// Annotate a processed block (after execution). No-op unless PRICE_LOG_BLOCKS.
+    pub fn log_block(&mut self, annotations: BlockAnnotations) {
+        if !self.blocks {
+            return;
         }
+        let block = annotations.block;
+        if let Err(e) = self.writer.send(PriceLogItem::Block(annotations)) {
+            warn!("PriceLogger: annotations for block {} not queued: {}", block, e);
+        }
+    }
+
+    /// Writer backpressure for the status line (None while healthy and idle)
+    pub fn status_line(&self) -> Option<String> {
+        self.writer.status_line()
+    }
 
-        // Flush to ensure data is on disk
-        if let Err(e) = file.flush() {
-            debug!("PriceLogger flush error: {}", e);
+    /// Wait for queued batches to reach the disk and close the open file
+    /// (shutdown; a later batch opens the next Parquet part)
+    pub fn flush(&self, timeout: Duration) -> bool {
+        if let Err(e) = self.writer.send(PriceLogItem::Close) {
+            warn!("PriceLogger: close not queued: {}", e);
         }
+        self.writer.flush(timeout)
     }
+}
 
-    /// Rotate to a new daily file
-    fn rotate_file(&mut self, date: NaiveDate) {
-        // Close existing file
-        self.file = None;
-        self.current_date = None;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let filename = format!("prices_{}.csv", date.format("%Y%m%d"));
-        let filepath = self.log_dir.join(&filename);
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
 
-        let file_exists = filepath.exists();
+    #[test]
+    fn test_block_row_schema() {
+        let annotations = BlockAnnotations::new(65_000_123, at("2026-10-16T12:00:01Z"))
+            .base_fee(U256::from(31_500_000_000u64))
+            .detected(5)
+            .suppressed(2)
+            .submitted(1)
+            .net_profit_usd(1.25);
+        let row = annotations.csv_row();
+        assert_eq!(row, "2026-10-16T12:00:01.000Z,65000123,31500000000,5,2,1,1.250000\n");
+        assert_eq!(row.trim_end().split(',').count(), BLOCKS_CSV_HEADER.split(',').count());
+    }
 
-        match OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&filepath)
-        {
-            Ok(mut f) => {
-                // Write header if new file
-                if !file_exists {
-                    if let Err(e) = writeln!(f, "{}", CSV_HEADER) {
-                        warn!("PriceLogger: failed to write header to {}: {}", filename, e);
-                        return;
-                    }
-                    info!("PriceLogger: created new file {}", filename);
-                } else {
-                    info!("PriceLogger: appending to existing {}", filename);
-                }
-                self.file = Some(f);
-                self.current_date = Some(date);
-            }
-            Err(e) => {
-                warn!("PriceLogger: failed to open {}: {}", filename, e);
-            }
-        }
+    #[test]
+    fn test_block_without_trades_writes_zero_row() {
+        let dir = std::env::temp_dir().join(format!("dexarb_price_blocks_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let options = PriceLogOptions { format: PriceLogFormat::Csv, compress: false, retain_days: 0, blocks: true };
+        let mut file = PriceFile {
+            log_dir: dir.clone(),
+            format: options.format,
+            current_date: None,
+            file: None,
+            blocks_file: None,
+            maintenance: Maintenance::spawn(dir.clone(), options),
+        };
+        let day = at("2026-10-16T23:59:59Z");
+        file.write(&PriceLogItem::Block(BlockAnnotations::new(100, day))).unwrap();
+        file.write(&PriceLogItem::Block(BlockAnnotations::new(101, day).detected(3).suppressed(3))).unwrap();
+        // Next day rotates to its own file
+        file.write(&PriceLogItem::Block(BlockAnnotations::new(102, at("2026-10-17T00:00:01Z")))).unwrap();
+
+        let text = fs::read_to_string(dir.join("blocks_20261016.csv")).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], BLOCKS_CSV_HEADER);
+        assert_eq!(lines[1], "2026-10-16T23:59:59.000Z,100,0,0,0,0,0.000000");
+        assert_eq!(lines[2], "2026-10-16T23:59:59.000Z,101,0,3,3,0,0.000000");
+        assert_eq!(lines.len(), 3);
+        let next = fs::read_to_string(dir.join("blocks_20261017.csv")).unwrap();
+        assert_eq!(next.lines().count(), 2);
+        let _ = fs::remove_dir_all(&dir);
     }
 }
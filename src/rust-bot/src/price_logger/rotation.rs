@@ -0,0 +1,256 @@
+//! Price Log Rotation — daily file names, gzip of closed days, retention
+//!
+//! Naming (UTC dates):
+//!   prices_YYYYMMDD.csv          open CSV day file (appended across restarts)
+//!   prices_YYYYMMDD.csv.gz       the same day once closed and compressed
+//!   prices_YYYYMMDD.parquet      Parquet day file; a restart or shutdown
+//!   prices_YYYYMMDD_N.parquet    flush closes it, the next part takes _N
+//!   blocks_YYYYMMDD.csv[.gz]     block annotations (PRICE_LOG_BLOCKS), always CSV
+//!
+//! Maintenance (compress closed CSVs, delete expired files) runs on its own
+//! thread: the writer thread only signals it when it rotates to a new day,
+//! and PriceLogger::new signals it once at startup. A day is closed once it
+//! is before today; it is expired once it is more than PRICE_LOG_RETAIN_DAYS
+//! before today (0 = keep everything).
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - blocks_YYYYMMDD.csv annotation files named, compressed and expired with the price files
+
+use super::{PriceLogFormat, PriceLogOptions};
+use anyhow::{Context, Result};
+use chrono::{Duration as ChronoDuration, NaiveDate};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use tracing::{debug, info, warn};
+
+/// Name of a day's log file; `part` > 0 only for Parquet (one file per writer session)
+pub fn file_name(date: NaiveDate, format: PriceLogFormat, part: u32) -> String {
+    let stem = format!("prices_{}", date.format("%Y%m%d"));
+    match (format, part) {
+        (PriceLogFormat::Csv, _) => format!("{}.csv", stem),
+        (PriceLogFormat::Parquet, 0) => format!("{}.parquet", stem),
+        (PriceLogFormat::Parquet, n) => format!("{}_{}.parquet", stem, n),
+    }
+}
+
+/// Name of a day's block annotation file
+pub fn blocks_file_name(date: NaiveDate) -> String {
+    format!("blocks_{}.csv", date.format("%Y%m%d"))
+}
+
+/// Date of a price log or block annotation file name (any format, compressed or not)
+pub fn file_date(name: &str) -> Option<NaiveDate> {
+    if let Some(rest) = name.strip_prefix("blocks_") {
+        let day = rest.strip_suffix(".csv").or_else(|| rest.strip_suffix(".csv.gz"))?;
+        return parse_day(day);
+    }
+    let rest = name.strip_prefix("prices_")?;
+    let (stem, ext) = rest.split_once('.')?;
+    let day = match ext {
+        "csv" | "csv.gz" => stem,
+        "parquet" => match stem.split_once('_') {
+            Some((day, part)) => part.parse::<u32>().ok().map(|_| day)?,
+            None => stem,
+        },
+        _ => return None,
+    };
+    parse_day(day)
+}
+
+fn parse_day(day: &str) -> Option<NaiveDate> {
+    if day.len() != 8 {
+        return None;
+    }
+    NaiveDate::parse_from_str(day, "%Y%m%d").ok()
+}
+
+/// First unused Parquet part path for `date` in `dir`
+pub fn next_parquet_path(dir: &Path, date: NaiveDate) -> PathBuf {
+    (0..)
+        .map(|part| dir.join(file_name(date, PriceLogFormat::Parquet, part)))
+        .find(|path| !path.exists())
+        .expect("unbounded part range")
+}
+
+/// Gzip `path` into `path.gz` (written under a temporary name, then renamed)
+/// and remove the original. Refuses to overwrite an existing `.gz`.
+pub fn compress_file(path: &Path) -> Result<PathBuf> {
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let gz_path = PathBuf::from(gz_name);
+    if gz_path.exists() {
+        anyhow::bail!("{} already exists", gz_path.display());
+    }
+    let tmp_path = gz_path.with_extension("gz.tmp");
+
+    let mut input = BufReader::new(File::open(path).with_context(|| format!("open {}", path.display()))?);
+    let output = File::create(&tmp_path).with_context(|| format!("create {}", tmp_path.display()))?;
+    let mut encoder = GzEncoder::new(BufWriter::new(output), Compression::default());
+    io::copy(&mut input, &mut encoder).with_context(|| format!("compress {}", path.display()))?;
+    encoder.finish()?.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+
+    fs::rename(&tmp_path, &gz_path).with_context(|| format!("rename {}", tmp_path.display()))?;
+    fs::remove_file(path).with_context(|| format!("remove {}", path.display()))?;
+    Ok(gz_path)
+}
+
+/// What one maintenance pass did
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    pub compressed: Vec<PathBuf>,
+    pub deleted: Vec<PathBuf>,
+}
+
+/// Delete files past retention, then compress closed CSV days. A file that
+/// fails is logged and left for the next pass.
+pub fn maintain(dir: &Path, today: NaiveDate, options: &PriceLogOptions) -> Result<MaintenanceReport> {
+    let oldest_kept = (options.retain_days > 0).then(|| today - ChronoDuration::days(options.retain_days as i64));
+    let mut files: Vec<(PathBuf, NaiveDate)> = fs::read_dir(dir)
+        .with_context(|| format!("read {}", dir.display()))?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let date = file_date(path.file_name()?.to_str()?)?;
+            Some((path, date))
+        })
+        .collect();
+    files.sort();
+
+    let mut report = MaintenanceReport::default();
+    for (path, date) in files {
+        if oldest_kept.is_some_and(|oldest| date < oldest) {
+            match fs::remove_file(&path) {
+                Ok(()) => report.deleted.push(path),
+                Err(e) => warn!("PriceLogger: failed to delete expired {}: {}", path.display(), e),
+            }
+        } else if options.compress && date < today && path.extension().is_some_and(|ext| ext == "csv") {
+            match compress_file(&path) {
+                Ok(gz) => report.compressed.push(gz),
+                Err(e) => warn!("PriceLogger: failed to compress {}: {:#}", path.display(), e),
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Background maintenance thread; each `run(today)` queues one pass
+pub struct Maintenance {
+    tx: Sender<NaiveDate>,
+}
+
+impl Maintenance {
+    pub fn spawn(dir: PathBuf, options: PriceLogOptions) -> Self {
+        let (tx, rx) = mpsc::channel::<NaiveDate>();
+        let spawned = thread::Builder::new().name("price-log-maint".to_string()).spawn(move || {
+            while let Ok(mut today) = rx.recv() {
+                // Coalesce signals that piled up during a long pass
+                while let Ok(later) = rx.try_recv() {
+                    today = today.max(later);
+                }
+                match maintain(&dir, today, &options) {
+                    Ok(report) if report.compressed.len() + report.deleted.len() > 0 => info!(
+                        "PriceLogger maintenance: {} file(s) compressed, {} expired file(s) deleted",
+                        report.compressed.len(),
+                        report.deleted.len()
+                    ),
+                    Ok(_) => debug!("PriceLogger maintenance: nothing to do"),
+                    Err(e) => warn!("PriceLogger maintenance failed: {:#}", e),
+                }
+            }
+        });
+        if let Err(e) = spawned {
+            warn!("PriceLogger: failed to start maintenance thread: {} — no compression or retention", e);
+        }
+        Self { tx }
+    }
+
+    /// Queue a pass for `today` (never blocks)
+    pub fn run(&self, today: NaiveDate) {
+        let _ = self.tx.send(today);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::MultiGzDecoder;
+    use std::io::Read;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn options(compress: bool, retain_days: u32) -> PriceLogOptions {
+        PriceLogOptions { format: PriceLogFormat::Csv, compress, retain_days, blocks: false }
+    }
+
+    #[test]
+    fn test_rotation_naming_round_trips() {
+        let day = date("2026-10-16");
+        assert_eq!(file_name(day, PriceLogFormat::Csv, 0), "prices_20261016.csv");
+        assert_eq!(file_name(day, PriceLogFormat::Parquet, 0), "prices_20261016.parquet");
+        assert_eq!(file_name(day, PriceLogFormat::Parquet, 2), "prices_20261016_2.parquet");
+        assert_eq!(blocks_file_name(day), "blocks_20261016.csv");
+        for name in [
+            "prices_20261016.csv",
+            "prices_20261016.csv.gz",
+            "prices_20261016.parquet",
+            "prices_20261016_2.parquet",
+            "blocks_20261016.csv",
+            "blocks_20261016.csv.gz",
+        ] {
+            assert_eq!(file_date(name), Some(day), "{}", name);
+        }
+        for name in ["prices_20261016.csv.tmp", "prices_2026101.csv", "prices_20261016_1.csv", "gas_20261016.csv", "prices_x.csv", "blocks_20261016.parquet"] {
+            assert_eq!(file_date(name), None, "{}", name);
+        }
+
+        let dir = std::env::temp_dir().join(format!("dexarb_price_naming_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(next_parquet_path(&dir, day), dir.join("prices_20261016.parquet"));
+        fs::write(dir.join("prices_20261016.parquet"), b"x").unwrap();
+        fs::write(dir.join("prices_20261016_1.parquet"), b"x").unwrap();
+        assert_eq!(next_parquet_path(&dir, day), dir.join("prices_20261016_2.parquet"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_maintain_compresses_closed_days_and_deletes_expired() {
+        let dir = std::env::temp_dir().join(format!("dexarb_price_retention_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let body = "timestamp,block\n2026-10-14T00:00:00.000Z,1\n";
+        for name in [
+            "prices_20261001.csv.gz",
+            "prices_20261009.parquet",
+            "prices_20261010.csv",
+            "prices_20261014.csv",
+            "prices_20261016.csv",
+            "notes.txt",
+        ] {
+            fs::write(dir.join(name), body).unwrap();
+        }
+
+        // Retain 6 days before the 16th: the 10th is the oldest kept
+        let report = maintain(&dir, date("2026-10-16"), &options(true, 6)).unwrap();
+        let mut left: Vec<String> =
+            fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().to_string()).collect();
+        left.sort();
+        assert_eq!(left, vec!["notes.txt", "prices_20261010.csv.gz", "prices_20261014.csv.gz", "prices_20261016.csv"]);
+        assert_eq!(report.deleted.len(), 2);
+        assert_eq!(report.compressed.len(), 2);
+
+        let mut text = String::new();
+        MultiGzDecoder::new(File::open(dir.join("prices_20261014.csv.gz")).unwrap()).read_to_string(&mut text).unwrap();
+        assert_eq!(text, body);
+
+        // Retention 0 and no compression: nothing touched
+        let report = maintain(&dir, date("2027-01-01"), &options(false, 0)).unwrap();
+        assert_eq!(report, MaintenanceReport::default());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
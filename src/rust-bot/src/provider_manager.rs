@@ -0,0 +1,440 @@
+//! WS Provider Manager — per-endpoint health and failover
+//!
+//! Purpose:
+//!     With one RPC_URL, a provider outage meant 50 reconnects to the same dead
+//!     endpoint and then an exit. RPC_URLS lists fallbacks; the block
+//!     subscription and the sync provider both connect through this manager,
+//!     which moves to the next endpoint on each failure instead of retrying
+//!     the one that just failed.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - IPC endpoints (local node): sync provider over the socket, subscription on WS_RPC_URL
+//!
+//! Design:
+//!     - Failures (connect, subscribe, stream end, stale timeout) are counted
+//!       per endpoint, each with its own Retrier on the `ws_reconnect` site.
+//!       An endpoint is exhausted after the policy's attempts (ws_reconnect():
+//!       MAX_WS_RECONNECTS retries); the bot gives up only once every
+//!       endpoint is exhausted.
+//!     - A failure rotates to the next live endpoint. An endpoint with no
+//!       failures is tried immediately; one that already failed is tried after
+//!       the policy delay for its streak, so a fully dead set still backs off.
+//!     - The streak resets on the first delivered block, not on subscribe: an
+//!       endpoint that accepts the subscription but never sends blocks (the
+//!       silent stall seen on Alchemy) still counts toward exhaustion.
+//!     - Block latency = arrival time − block timestamp (1s resolution), last
+//!       and EWMA, for comparing endpoints in the status line.
+//!     - Status line shows scheme + host only: endpoint URLs carry API keys.
+//!     - An endpoint may be a local node's IPC socket (transport.rs): connect()
+//!       reads over it with the WS fallback behind it, while the block
+//!       subscription of that endpoint goes to the WS fallback
+//!       (subscription_url()). Its failures still count against the endpoint.
+
+use crate::paper_trading::determinism::SimRng;
+use crate::retry::{Backoffed, Retrier, RetryPolicy, RetrySite};
+use crate::rpc_bench::endpoint_label;
+use crate::transport::{self, RpcTransport};
+use crate::watchdog::rpc_timeout;
+use anyhow::{bail, Result};
+use ethers::prelude::*;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// EWMA weight of the newest block latency sample
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+
+/// What went wrong on an endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointFailure {
+    Connect,
+    Subscribe,
+    /// The block stream returned None
+    StreamEnd,
+    /// No block within the block timeout
+    Stale,
+}
+
+impl EndpointFailure {
+    /// Reason string of the journal's WsReconnect event
+    pub fn reason(self) -> &'static str {
+        match self {
+            EndpointFailure::Connect => "connect_failed",
+            EndpointFailure::Subscribe => "subscribe_failed",
+            EndpointFailure::StreamEnd => "stream_end",
+            EndpointFailure::Stale => "timeout",
+        }
+    }
+}
+
+/// Health counters of one endpoint
+pub struct EndpointHealth {
+    pub url: String,
+    pub connect_failures: u64,
+    pub subscribe_failures: u64,
+    pub stream_ends: u64,
+    pub stale_timeouts: u64,
+    /// Stale timeouts since the last delivered block
+    pub consecutive_stale: u64,
+    pub subscriptions: u64,
+    pub blocks: u64,
+    pub last_latency_ms: Option<u64>,
+    pub avg_latency_ms: Option<f64>,
+    pub last_error: Option<String>,
+    /// Reconnect limit reached — never tried again
+    pub exhausted: bool,
+    retrier: Retrier,
+    /// Subscribed, no block yet: the failure streak is still open
+    awaiting_block: bool,
+}
+
+impl EndpointHealth {
+    fn new(url: String, policy: &RetryPolicy, cancel: &CancellationToken) -> Self {
+        Self {
+            url,
+            connect_failures: 0,
+            subscribe_failures: 0,
+            stream_ends: 0,
+            stale_timeouts: 0,
+            consecutive_stale: 0,
+            subscriptions: 0,
+            blocks: 0,
+            last_latency_ms: None,
+            avg_latency_ms: None,
+            last_error: None,
+            exhausted: false,
+            retrier: Retrier::new(policy.clone(), RetrySite::named("ws_reconnect"), cancel.clone()),
+            awaiting_block: false,
+        }
+    }
+
+    /// Consecutive failures (this endpoint's reconnect count)
+    pub fn failures(&self) -> u32 {
+        self.retrier.failures()
+    }
+
+    /// URL without path/query (API keys)
+    pub fn label(&self) -> String {
+        endpoint_label(&self.url)
+    }
+}
+
+/// Where to go after a failure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Failover {
+    /// Consecutive failures of the endpoint that failed
+    pub failures: u32,
+    /// Endpoint index to try next and the delay before it; None = all exhausted
+    pub next: Option<(usize, Duration)>,
+}
+
+/// Rotating set of WS endpoints for the block subscription and sync provider
+pub struct ProviderManager {
+    endpoints: Vec<EndpointHealth>,
+    current: usize,
+    policy: RetryPolicy,
+    cancel: CancellationToken,
+    rng: SimRng,
+    /// WS_RPC_URL: subscriptions and read fallback of IPC endpoints
+    ws_fallback: Option<String>,
+}
+
+impl ProviderManager {
+    /// `urls` in preference order (non-empty); `policy` applies per endpoint
+    pub fn new(urls: Vec<String>, policy: RetryPolicy, cancel: CancellationToken) -> Self {
+        assert!(!urls.is_empty(), "ProviderManager needs at least one endpoint");
+        let endpoints = urls.into_iter().map(|url| EndpointHealth::new(url, &policy, &cancel)).collect();
+        Self { endpoints, current: 0, policy, cancel, rng: SimRng::from_entropy(), ws_fallback: None }
+    }
+
+    /// WS endpoint behind IPC endpoints (WS_RPC_URL)
+    pub fn with_ws_fallback(mut self, url: Option<String>) -> Self {
+        self.ws_fallback = url;
+        self
+    }
+
+    /// Replace the jitter RNG (tests)
+    pub fn with_rng(mut self, rng: SimRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    pub fn endpoint(&self, index: usize) -> &EndpointHealth {
+        &self.endpoints[index]
+    }
+
+    /// Index of the endpoint to use now
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn current_url(&self) -> &str {
+        &self.endpoints[self.current].url
+    }
+
+    pub fn current_label(&self) -> String {
+        self.endpoints[self.current].label()
+    }
+
+    /// WS URL for the block subscription on the current endpoint: the endpoint
+    /// itself, or the WS fallback when it is an IPC socket
+    pub fn subscription_url(&self) -> &str {
+        let url = self.current_url();
+        transport::subscription_url(url, self.ws_fallback.as_deref()).unwrap_or(url)
+    }
+
+    pub fn subscription_label(&self) -> String {
+        endpoint_label(self.subscription_url())
+    }
+
+    /// Consecutive failures summed over endpoints
+    pub fn total_failures(&self) -> u32 {
+        self.endpoints.iter().map(|e| e.failures()).sum()
+    }
+
+    /// Mark the start of a connect attempt on the current endpoint
+    pub fn attempt(&mut self) {
+        self.endpoints[self.current].retrier.attempt();
+    }
+
+    /// The block subscription is up on the current endpoint
+    pub fn subscribed(&mut self) {
+        let endpoint = &mut self.endpoints[self.current];
+        endpoint.subscriptions += 1;
+        endpoint.awaiting_block = true;
+    }
+
+    /// A block arrived on the current endpoint at `now_ms` (unix ms); closes
+    /// the failure streak
+    pub fn record_block(&mut self, block_timestamp: u64, now_ms: u64) {
+        let endpoint = &mut self.endpoints[self.current];
+        let latency = now_ms.saturating_sub(block_timestamp.saturating_mul(1000));
+        endpoint.blocks += 1;
+        endpoint.consecutive_stale = 0;
+        endpoint.last_latency_ms = Some(latency);
+        endpoint.avg_latency_ms = Some(match endpoint.avg_latency_ms {
+            Some(avg) => avg + LATENCY_EWMA_ALPHA * (latency as f64 - avg),
+            None => latency as f64,
+        });
+        if endpoint.awaiting_block {
+            endpoint.awaiting_block = false;
+            endpoint.retrier.succeeded();
+        }
+    }
+
+    /// Count a failure on the current endpoint and rotate to the next live one
+    pub fn failed(&mut self, failure: EndpointFailure, error: &str) -> Failover {
+        let failed = self.current;
+        let endpoint = &mut self.endpoints[failed];
+        match failure {
+            EndpointFailure::Connect => endpoint.connect_failures += 1,
+            EndpointFailure::Subscribe => endpoint.subscribe_failures += 1,
+            EndpointFailure::StreamEnd => endpoint.stream_ends += 1,
+            EndpointFailure::Stale => {
+                endpoint.stale_timeouts += 1;
+                endpoint.consecutive_stale += 1;
+            }
+        }
+        endpoint.last_error = Some(error.to_string());
+        endpoint.awaiting_block = false;
+        if endpoint.retrier.record_failure().is_none() {
+            endpoint.exhausted = true;
+            warn!("WS endpoint {} exhausted after {} failures", endpoint.label(), endpoint.failures());
+        }
+        let failures = endpoint.failures();
+
+        let n = self.endpoints.len();
+        let next = (1..=n).map(|step| (failed + step) % n).find(|&i| !self.endpoints[i].exhausted);
+        let next = next.map(|i| {
+            self.current = i;
+            let streak = self.endpoints[i].failures();
+            let delay = if streak == 0 { Duration::ZERO } else { self.policy.delay_for(streak, &self.rng) };
+            (i, delay)
+        });
+        Failover { failures, next }
+    }
+
+    /// Wait `delay` before the next attempt (zero = no wait), unless shutdown
+    pub async fn wait(&mut self, delay: Duration) -> Backoffed {
+        if delay.is_zero() {
+            return if self.cancel.is_cancelled() { Backoffed::Cancelled } else { Backoffed::Retry };
+        }
+        self.endpoints[self.current].retrier.wait(delay).await
+    }
+
+    /// Connect to the first endpoint that accepts, rotating on failure (sync provider)
+    pub async fn connect(&mut self) -> Result<Provider<RpcTransport>> {
+        loop {
+            self.attempt();
+            let label = self.current_label();
+            let connect = transport::connect_provider(self.current_url(), self.ws_fallback.as_deref());
+            let error = match rpc_timeout("RPC connect", connect).await {
+                Ok(provider) => {
+                    info!("Connected to {}", label);
+                    return Ok(provider);
+                }
+                Err(e) => e.to_string(),
+            };
+            let failover = self.failed(EndpointFailure::Connect, &error);
+            let Some((next, delay)) = failover.next else {
+                bail!("WS connect failed on every endpoint (last: {}: {})", label, error);
+            };
+            warn!(
+                "WS connect to {} failed: {} — trying {} in {:.1}s",
+                label, error, self.endpoints[next].label(), delay.as_secs_f64()
+            );
+            if self.wait(delay).await == Backoffed::Cancelled {
+                bail!("Shutdown during WS connect");
+            }
+        }
+    }
+
+    /// Per-endpoint stats for the periodic status log
+    pub fn status_line(&self) -> String {
+        self.endpoints
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let state = if e.exhausted {
+                    "exhausted"
+                } else if i == self.current {
+                    "active"
+                } else {
+                    "standby"
+                };
+                let latency = match (e.last_latency_ms, e.avg_latency_ms) {
+                    (Some(last), Some(avg)) => format!("latency {}ms (avg {:.0}ms)", last, avg),
+                    _ => "latency n/a".to_string(),
+                };
+                format!(
+                    "{} [{}] {} blocks, {}, {} connect/{} subscribe failures, {} stale ({} in a row), {} stream ends",
+                    e.label(), state, e.blocks, latency, e.connect_failures, e.subscribe_failures,
+                    e.stale_timeouts, e.consecutive_stale, e.stream_ends
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::{ws_reconnect, MAX_WS_RECONNECTS};
+
+    const SECS: Duration = Duration::from_secs(5);
+
+    fn manager(urls: &[&str], policy: RetryPolicy) -> ProviderManager {
+        ProviderManager::new(urls.iter().map(|u| u.to_string()).collect(), policy, CancellationToken::new())
+            .with_rng(SimRng::from_seed(7))
+    }
+
+    #[test]
+    fn test_failures_rotate_and_back_off_once_all_failed() {
+        let urls = ["wss://a.example/v2/key", "wss://b.example", "wss://c.example"];
+        let mut pm = manager(&urls, RetryPolicy::fixed(SECS).with_max_attempts(3));
+        assert_eq!(pm.current_url(), urls[0]);
+
+        // Fresh endpoints are tried at once; wrapping to one that failed waits
+        assert_eq!(pm.failed(EndpointFailure::Connect, "refused"), Failover { failures: 1, next: Some((1, Duration::ZERO)) });
+        assert_eq!(pm.failed(EndpointFailure::Subscribe, "rate limited"), Failover { failures: 1, next: Some((2, Duration::ZERO)) });
+        assert_eq!(pm.failed(EndpointFailure::Connect, "refused"), Failover { failures: 1, next: Some((0, SECS)) });
+        assert_eq!(pm.total_failures(), 3);
+
+        // Subscribing doesn't close the streak; the first block does
+        pm.subscribed();
+        assert_eq!(pm.endpoint(0).failures(), 1);
+        pm.record_block(1_000, 1_000_800);
+        assert_eq!(pm.endpoint(0).failures(), 0);
+        assert_eq!(pm.endpoint(0).last_latency_ms, Some(800));
+
+        // a is healthy again: after its next failure b (1 failure) waits
+        assert_eq!(pm.failed(EndpointFailure::StreamEnd, "stream ended"), Failover { failures: 1, next: Some((1, SECS)) });
+        let status = pm.status_line();
+        assert!(status.starts_with("wss://a.example/… [standby] 1 blocks, latency 800ms"), "{}", status);
+        assert!(status.contains("wss://b.example [active] 0 blocks, latency n/a, 0 connect/1 subscribe failures"));
+        assert!(!status.contains("key"));
+    }
+
+    #[test]
+    fn test_ipc_endpoint_subscribes_on_ws_fallback() {
+        let urls = ["/var/lib/bor/bor.ipc", "wss://b.example/v2/key"];
+        let mut pm = manager(&urls, RetryPolicy::fixed(SECS).with_max_attempts(3))
+            .with_ws_fallback(Some("ws://127.0.0.1:8546".to_string()));
+        assert_eq!(pm.current_label(), "ipc:///var/lib/bor/bor.ipc");
+        assert_eq!(pm.subscription_url(), "ws://127.0.0.1:8546");
+        assert_eq!(pm.subscription_label(), "ws://127.0.0.1:8546");
+
+        // A subscription failure still rotates; WS endpoints subscribe themselves
+        pm.failed(EndpointFailure::Subscribe, "refused");
+        assert_eq!(pm.subscription_url(), urls[1]);
+    }
+
+    #[test]
+    fn test_give_up_only_when_every_endpoint_exhausted() {
+        // 2 attempts per endpoint
+        let mut pm = manager(&["wss://a.example", "wss://b.example"], RetryPolicy::fixed(SECS).with_max_attempts(2));
+        assert_eq!(pm.failed(EndpointFailure::Connect, "down").next, Some((1, Duration::ZERO)));
+        assert_eq!(pm.failed(EndpointFailure::Connect, "down").next, Some((0, SECS)));
+        // a exhausted: stays on b, which waits out its own streak
+        assert_eq!(pm.failed(EndpointFailure::Connect, "down"), Failover { failures: 2, next: Some((1, SECS)) });
+        assert!(pm.endpoint(0).exhausted);
+        assert!(pm.status_line().contains("wss://a.example [exhausted]"));
+        assert_eq!(pm.failed(EndpointFailure::Connect, "down"), Failover { failures: 2, next: None });
+
+        // One endpoint on the site policy: the old limit, MAX_WS_RECONNECTS retries of ~5s
+        let mut single = manager(&["wss://a.example"], ws_reconnect());
+        for _ in 0..MAX_WS_RECONNECTS {
+            let (next, delay) = single.failed(EndpointFailure::Connect, "down").next.unwrap();
+            assert_eq!(next, 0);
+            assert!(delay >= Duration::from_secs(4) && delay <= Duration::from_secs(6), "{:?}", delay);
+        }
+        assert_eq!(single.failed(EndpointFailure::Connect, "down").next, None);
+    }
+
+    #[test]
+    fn test_stale_endpoint_exhausts_and_backoff_grows() {
+        let policy = RetryPolicy::exponential(Duration::from_secs(1), Duration::from_secs(4)).with_max_attempts(4);
+        let mut pm = manager(&["wss://stalled.example"], policy);
+        let mut delays = Vec::new();
+        // Accepts the subscription every time, never sends a block
+        loop {
+            pm.subscribed();
+            match pm.failed(EndpointFailure::Stale, "no block in 30s").next {
+                Some((_, delay)) => delays.push(delay.as_secs()),
+                None => break,
+            }
+        }
+        assert_eq!(delays, vec![1, 2, 4]);
+        assert_eq!(pm.endpoint(0).stale_timeouts, 4);
+        assert_eq!(pm.endpoint(0).consecutive_stale, 4);
+        assert_eq!(pm.endpoint(0).subscriptions, 4);
+
+        // Latency EWMA; a block clears the stale run
+        let mut pm = manager(&["wss://a.example"], RetryPolicy::fixed(SECS));
+        pm.failed(EndpointFailure::Stale, "no block in 30s");
+        pm.record_block(100, 101_000);
+        pm.record_block(102, 103_500);
+        assert_eq!(pm.endpoint(0).consecutive_stale, 0);
+        assert_eq!(pm.endpoint(0).avg_latency_ms, Some(1_100.0));
+    }
+
+    #[tokio::test]
+    async fn test_wait_skips_zero_delay_and_honours_shutdown() {
+        let cancel = CancellationToken::new();
+        let mut pm = ProviderManager::new(vec!["wss://a.example".to_string()], RetryPolicy::fixed(SECS), cancel.clone());
+        assert_eq!(pm.wait(Duration::ZERO).await, Backoffed::Retry);
+        cancel.cancel();
+        assert_eq!(pm.wait(Duration::ZERO).await, Backoffed::Cancelled);
+        assert_eq!(pm.wait(SECS).await, Backoffed::Cancelled);
+    }
+}
@@ -0,0 +1,316 @@
+//! Quote Token Amounts — raw U256 bound to the quote token it is denominated in
+//!
+//! A trade starts and ends in its quote token (USDC, USDT, WETH, ...), and
+//! every size, floor, balance and profit along the way is a raw integer at
+//! that token's decimals. A bare U256 says nothing about which token or
+//! decimals it is in, so nothing stopped a USDC (6 dec) amount being added
+//! to a WETH (18 dec) one. QuoteAmount carries the identity with the value:
+//!   - arithmetic/comparison between different quote tokens panics in debug
+//!     builds with both amounts in the message (checked_* return QuoteMismatch)
+//!   - to_usd() / QuoteToken::from_usd() are the only USD conversions
+//!   - Display renders human units with the symbol ("500.000000 USDC")
+//!
+//! Carried by trade sizes (detector → canary/liquidity caps → executor), the
+//! preflight balance/allowance check, exposure pacing's balance cache and
+//! realized profits parsed from receipts.
+//!
+//! Serialized forms stay the raw value (U256 hex inside opportunities, decimal
+//! strings in trade results) — the identity is restored from context on load.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::types::{format_token_amount, raw_to_human, usd_to_raw};
+use ethers::types::{Address, U256};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::sync::{Mutex, OnceLock};
+
+/// Quote token identity: address + decimals (symbol for display only)
+#[derive(Debug, Clone, Copy)]
+pub struct QuoteToken {
+    pub address: Address,
+    pub decimals: u8,
+    pub symbol: &'static str,
+}
+
+/// Symbols are a small fixed set (one per quote token) — interned so
+/// QuoteToken and QuoteAmount stay Copy like the U256 they replace
+fn intern(symbol: &str) -> &'static str {
+    static SYMBOLS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut symbols = SYMBOLS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(s) = symbols.get(symbol) {
+        return s;
+    }
+    let leaked: &'static str = Box::leak(symbol.to_string().into_boxed_str());
+    symbols.insert(leaked);
+    leaked
+}
+
+impl QuoteToken {
+    pub fn new(address: Address, decimals: u8, symbol: &str) -> Self {
+        Self { address, decimals, symbol: intern(symbol) }
+    }
+
+    /// Bind a raw amount to this token
+    pub fn amount(self, raw: U256) -> QuoteAmount {
+        QuoteAmount { raw, token: self }
+    }
+
+    pub fn zero(self) -> QuoteAmount {
+        self.amount(U256::zero())
+    }
+
+    /// USD → quote amount at `quote_price_usd` per whole token (rounded, u128 range)
+    pub fn from_usd(self, usd: f64, quote_price_usd: f64) -> QuoteAmount {
+        if quote_price_usd <= 0.0 {
+            return self.zero();
+        }
+        self.amount(usd_to_raw(usd / quote_price_usd, self.decimals))
+    }
+}
+
+impl PartialEq for QuoteToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address && self.decimals == other.decimals
+    }
+}
+
+impl Eq for QuoteToken {}
+
+impl fmt::Display for QuoteToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?}, {} dec)", self.symbol, self.address, self.decimals)
+    }
+}
+
+/// Arithmetic between amounts of different quote tokens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuoteMismatch {
+    pub left: QuoteAmount,
+    pub right: QuoteAmount,
+}
+
+impl fmt::Display for QuoteMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "quote token mismatch: {} [{}] vs {} [{}]", self.left, self.left.token, self.right, self.right.token)
+    }
+}
+
+impl std::error::Error for QuoteMismatch {}
+
+/// Raw quote token amount bound to its token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteAmount {
+    raw: U256,
+    token: QuoteToken,
+}
+
+impl QuoteAmount {
+    pub fn raw(&self) -> U256 {
+        self.raw
+    }
+
+    pub fn token(&self) -> QuoteToken {
+        self.token
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.token.decimals
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.raw.is_zero()
+    }
+
+    /// Same token, another raw value (e.g. a quoter output for this trade)
+    pub fn with_raw(self, raw: U256) -> Self {
+        Self { raw, token: self.token }
+    }
+
+    /// Whole tokens (human units)
+    pub fn to_human(&self) -> f64 {
+        raw_to_human(self.raw, self.token.decimals)
+    }
+
+    /// USD value at `quote_price_usd` per whole token — the one quote→USD conversion
+    pub fn to_usd(&self, quote_price_usd: f64) -> f64 {
+        self.to_human() * quote_price_usd
+    }
+
+    fn same_token(&self, other: &Self) -> Result<(), Box<QuoteMismatch>> {
+        if self.token == other.token {
+            Ok(())
+        } else {
+            Err(Box::new(QuoteMismatch { left: *self, right: *other }))
+        }
+    }
+
+    /// Debug builds panic with both amounts; release builds keep the left identity
+    fn expect_same_token(&self, other: &Self) {
+        if let Err(e) = self.same_token(other) {
+            debug_assert!(false, "{}", e);
+        }
+    }
+
+    pub fn checked_add(self, other: Self) -> Result<Self, Box<QuoteMismatch>> {
+        self.same_token(&other)?;
+        Ok(self.with_raw(self.raw.saturating_add(other.raw)))
+    }
+
+    pub fn checked_sub(self, other: Self) -> Result<Self, Box<QuoteMismatch>> {
+        self.same_token(&other)?;
+        Ok(self.with_raw(self.raw.saturating_sub(other.raw)))
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        self.expect_same_token(&other);
+        self.with_raw(self.raw.saturating_sub(other.raw))
+    }
+
+    /// self × numerator / denominator (scaling by a ratio of raw values)
+    pub fn mul_div(self, numerator: U256, denominator: U256) -> Self {
+        if denominator.is_zero() {
+            return self.with_raw(U256::zero());
+        }
+        self.with_raw(self.raw.saturating_mul(numerator) / denominator)
+    }
+
+    /// Fraction of this amount in parts per million (U256 math, no f64 rounding
+    /// of the raw value; fraction clamped to [0, 1])
+    pub fn fraction(self, fraction: f64) -> Self {
+        const PPM: u64 = 1_000_000;
+        let ppm = (fraction.clamp(0.0, 1.0) * PPM as f64).round() as u64;
+        self.mul_div(U256::from(ppm), U256::from(PPM))
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        if other < self { other } else { self }
+    }
+}
+
+impl PartialOrd for QuoteAmount {
+    /// None (and a debug panic) across quote tokens — comparing them is a bug
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.expect_same_token(other);
+        (self.token == other.token).then(|| self.raw.cmp(&other.raw))
+    }
+}
+
+impl Add for QuoteAmount {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        self.expect_same_token(&other);
+        self.with_raw(self.raw.saturating_add(other.raw))
+    }
+}
+
+impl Sub for QuoteAmount {
+    type Output = Self;
+    /// Saturating at zero, like the U256 checks it replaces
+    fn sub(self, other: Self) -> Self {
+        self.saturating_sub(other)
+    }
+}
+
+impl AddAssign for QuoteAmount {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for QuoteAmount {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl fmt::Display for QuoteAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_token_amount(self.raw, self.token.decimals, self.token.symbol))
+    }
+}
+
+/// Placeholder identity for an amount loaded without context (rebound by its owner)
+fn unbound(raw: U256) -> QuoteAmount {
+    QuoteToken::new(Address::zero(), 0, "?").amount(raw)
+}
+
+/// Serialize as the bare U256 (hex) — the pre-QuoteAmount form
+pub mod serde_u256 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(amount: &QuoteAmount, s: S) -> Result<S::Ok, S::Error> {
+        amount.raw.serialize(s)
+    }
+
+    /// Identity is unknown here; the owning struct rebinds it after load
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<QuoteAmount, D::Error> {
+        U256::deserialize(d).map(unbound)
+    }
+}
+
+/// Serialize Option<QuoteAmount> as the decimal string of the raw value
+pub mod serde_decimal_opt {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(amount: &Option<QuoteAmount>, s: S) -> Result<S::Ok, S::Error> {
+        amount.map(|a| a.raw.to_string()).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<QuoteAmount>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|s| U256::from_dec_str(&s).map(unbound).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usdc() -> QuoteToken {
+        QuoteToken::new(Address::from_low_u64_be(0xc0), 6, "USDC")
+    }
+
+    fn weth() -> QuoteToken {
+        QuoteToken::new(Address::from_low_u64_be(0xe7), 18, "WETH")
+    }
+
+    #[test]
+    fn test_arithmetic_and_display() {
+        let a = usdc().amount(U256::from(500_000_000u64));
+        let b = usdc().from_usd(0.25, 1.0);
+        assert_eq!(b.raw(), U256::from(250_000u64));
+        assert_eq!((a + b).to_string(), "500.250000 USDC");
+        assert_eq!((b - a).raw(), U256::zero());
+        assert!(b < a);
+        assert_eq!(a.fraction(0.4).raw(), U256::from(200_000_000u64));
+        assert_eq!(a.to_usd(1.0), 500.0);
+        assert_eq!(weth().from_usd(3300.0, 3300.0).to_string(), "1.00000000 WETH");
+    }
+
+    /// The mixup the type exists to catch: a WETH balance added to a USDC size
+    #[test]
+    fn test_mismatch_rejected() {
+        let size = usdc().amount(U256::from(500_000_000u64));
+        let balance = weth().amount(U256::exp10(18));
+        let err = size.checked_add(balance).unwrap_err();
+        assert!(err.to_string().contains("500.000000 USDC"), "{}", err);
+        assert!(err.to_string().contains("1.00000000 WETH"), "{}", err);
+        assert!(size.checked_sub(balance).is_err());
+        // Same address at other decimals is a different identity too
+        let usdc18 = QuoteToken::new(usdc().address, 18, "USDC");
+        assert!(size.checked_add(usdc18.zero()).is_err());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "quote token mismatch")]
+    fn test_mismatch_panics_in_debug() {
+        let _ = usdc().amount(U256::one()) + weth().amount(U256::one());
+    }
+}
@@ -0,0 +1,600 @@
+//! Retry / Backoff Utility
+//!
+//! One implementation of "try, wait, try again" for every retry loop in the
+//! bot, replacing the hand-rolled sleep loops (WS reconnect, mempool
+//! reconnect, receipt polling, paper-trading restart).
+//!
+//! - `RetryPolicy`: fixed or capped-exponential backoff, optional ± jitter,
+//!   attempt limit and/or deadline
+//! - `Retrier`: step-by-step driver for loops that cannot be a closure
+//!   (e.g. a WS subscription that borrows its provider)
+//! - `retry()`: async combinator over a closure returning `Attempt`
+//! - Cancellation: every wait races a `CancellationToken`; `shutdown_token()`
+//!   is the process-wide token cancelled on SIGINT/SIGTERM
+//! - `RetrySite`: named per-call-site counters (calls, retries, exhausted,
+//!   ...) collected in a global registry for the status log (`status_line()`)
+//!
+//! Site policies (`ws_reconnect()`, `mempool_reconnect()`, `receipt_poll()`,
+//! `paper_restart()`, `telemetry_push()`, `deadman_ping()`) live here so their give-up semantics are pinned by tests.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - telemetry_push() site policy
+//! Modified: 2026-10-16 - deadman_ping() site policy
+
+use crate::paper_trading::determinism::SimRng;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+// ---------------------------------------------------------------------------
+// Policy
+// ---------------------------------------------------------------------------
+
+/// Delay schedule between attempts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backoff {
+    /// Same delay every time
+    Fixed(Duration),
+    /// initial, 2×initial, 4×initial, ... capped at `max`
+    Exponential { initial: Duration, max: Duration },
+}
+
+/// When to wait, how long, and when to give up
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub backoff: Backoff,
+    /// Total attempts allowed, including the first (None = unbounded)
+    pub max_attempts: Option<u32>,
+    /// Give up once a failure lands more than this long after the first attempt
+    pub deadline: Option<Duration>,
+    /// Randomize each delay by ± this fraction (0.0 = off, 0.2 = ±20%)
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    pub fn fixed(delay: Duration) -> Self {
+        Self { backoff: Backoff::Fixed(delay), max_attempts: None, deadline: None, jitter: 0.0 }
+    }
+
+    pub fn exponential(initial: Duration, max: Duration) -> Self {
+        Self {
+            backoff: Backoff::Exponential { initial, max },
+            max_attempts: None,
+            deadline: None,
+            jitter: 0.0,
+        }
+    }
+
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_jitter(mut self, fraction: f64) -> Self {
+        self.jitter = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Delay before retry number `retry` (1 = first retry)
+    pub fn delay_for(&self, retry: u32, rng: &SimRng) -> Duration {
+        let base = match self.backoff {
+            Backoff::Fixed(d) => d,
+            Backoff::Exponential { initial, max } => {
+                let factor = 1u32.checked_shl(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+                initial.saturating_mul(factor).min(max)
+            }
+        };
+        if self.jitter == 0.0 {
+            return base;
+        }
+        let scale = 1.0 + self.jitter * (2.0 * rng.next_f64() - 1.0);
+        base.mul_f64(scale)
+    }
+
+    /// True when a failure after `attempts` attempts, `elapsed` after the
+    /// first one, should not be retried
+    fn exhausted(&self, attempts: u32, elapsed: Duration) -> bool {
+        self.max_attempts.is_some_and(|max| attempts >= max)
+            || self.deadline.is_some_and(|d| elapsed > d)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Site policies (semantics of the loops they replaced)
+// ---------------------------------------------------------------------------
+
+/// Reconnect limit for the block-subscription WS (~4 min of retries)
+pub const MAX_WS_RECONNECTS: u32 = 50;
+
+/// Reconnect limit for the mempool monitor WS
+pub const MAX_MEMPOOL_RECONNECTS: u32 = 50;
+
+/// Main-loop WS connect/subscribe: ~5s (±20%) between tries, give up after
+/// the 51st consecutive failure (MAX_WS_RECONNECTS retries)
+pub fn ws_reconnect() -> RetryPolicy {
+    RetryPolicy::fixed(Duration::from_secs(5))
+        .with_max_attempts(MAX_WS_RECONNECTS + 1)
+        .with_jitter(0.2)
+}
+
+/// Mempool monitor session: ~5s (±20%) between tries, give up after the
+/// 51st failure (failures are never reset)
+pub fn mempool_reconnect() -> RetryPolicy {
+    RetryPolicy::fixed(Duration::from_secs(5))
+        .with_max_attempts(MAX_MEMPOOL_RECONNECTS + 1)
+        .with_jitter(0.2)
+}
+
+/// Receipt polling: every 250ms, give up once a miss lands past 30s
+pub fn receipt_poll() -> RetryPolicy {
+    RetryPolicy::fixed(Duration::from_millis(250)).with_deadline(Duration::from_secs(30))
+}
+
+/// Paper-trading supervisor: restart 5s after an error, forever
+pub fn paper_restart() -> RetryPolicy {
+    RetryPolicy::fixed(Duration::from_secs(5))
+}
+
+/// Telemetry push: ~2s, 4s, 8s (±20%), give up after the 4th attempt —
+/// the next snapshot supersedes this one anyway
+pub fn telemetry_push() -> RetryPolicy {
+    RetryPolicy::exponential(Duration::from_secs(2), Duration::from_secs(8))
+        .with_max_attempts(4)
+        .with_jitter(0.2)
+}
+
+/// Dead-man's switch ping: ~1s, 2s (±20%), give up after the 3rd attempt —
+/// the next interval pings again, and a missed ping is what the monitor watches for
+pub fn deadman_ping() -> RetryPolicy {
+    RetryPolicy::exponential(Duration::from_secs(1), Duration::from_secs(4))
+        .with_max_attempts(3)
+        .with_jitter(0.2)
+}
+
+// ---------------------------------------------------------------------------
+// Shutdown
+// ---------------------------------------------------------------------------
+
+static SHUTDOWN: OnceLock<CancellationToken> = OnceLock::new();
+
+/// Process-wide shutdown token (clones share cancellation)
+pub fn shutdown_token() -> CancellationToken {
+    SHUTDOWN.get_or_init(CancellationToken::new).clone()
+}
+
+// ---------------------------------------------------------------------------
+// Per-site counters
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct SiteCounters {
+    /// Operations started (one per retry loop)
+    calls: AtomicU64,
+    /// Individual attempts
+    attempts: AtomicU64,
+    /// Backoff waits taken
+    retries: AtomicU64,
+    backoff_ms: AtomicU64,
+    successes: AtomicU64,
+    exhausted: AtomicU64,
+    aborted: AtomicU64,
+    cancelled: AtomicU64,
+}
+
+/// Snapshot of one call site's counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SiteStats {
+    pub calls: u64,
+    pub attempts: u64,
+    pub retries: u64,
+    pub backoff_ms: u64,
+    pub successes: u64,
+    pub exhausted: u64,
+    pub aborted: u64,
+    pub cancelled: u64,
+}
+
+/// Handle to a named call site's counters (cheap to clone)
+#[derive(Debug, Clone)]
+pub struct RetrySite {
+    name: &'static str,
+    counters: Arc<SiteCounters>,
+}
+
+fn registry() -> &'static Mutex<BTreeMap<&'static str, Arc<SiteCounters>>> {
+    static REGISTRY: OnceLock<Mutex<BTreeMap<&'static str, Arc<SiteCounters>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+impl RetrySite {
+    /// Get (or create) the counters for `name`
+    pub fn named(name: &'static str) -> Self {
+        let mut sites = registry().lock().unwrap_or_else(|e| e.into_inner());
+        let counters = sites.entry(name).or_default().clone();
+        Self { name, counters }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn stats(&self) -> SiteStats {
+        let c = &self.counters;
+        SiteStats {
+            calls: c.calls.load(Ordering::Relaxed),
+            attempts: c.attempts.load(Ordering::Relaxed),
+            retries: c.retries.load(Ordering::Relaxed),
+            backoff_ms: c.backoff_ms.load(Ordering::Relaxed),
+            successes: c.successes.load(Ordering::Relaxed),
+            exhausted: c.exhausted.load(Ordering::Relaxed),
+            aborted: c.aborted.load(Ordering::Relaxed),
+            cancelled: c.cancelled.load(Ordering::Relaxed),
+        }
+    }
+
+    fn bump(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Counters for every registered site, by name
+pub fn snapshot() -> Vec<(&'static str, SiteStats)> {
+    let sites = registry().lock().unwrap_or_else(|e| e.into_inner());
+    sites.iter()
+        .map(|(name, counters)| (*name, RetrySite { name, counters: counters.clone() }.stats()))
+        .collect()
+}
+
+/// One-line summary for the periodic status log, e.g.
+/// `receipt.atomic 12 calls/40 retries/1 exhausted | ws_reconnect 1 calls/3 retries/0 exhausted`
+pub fn status_line() -> String {
+    snapshot()
+        .iter()
+        .filter(|(_, s)| s.calls > 0)
+        .map(|(name, s)| {
+            let mut line = format!("{} {} calls/{} retries/{} exhausted", name, s.calls, s.retries, s.exhausted);
+            if s.aborted > 0 {
+                line.push_str(&format!("/{} aborted", s.aborted));
+            }
+            if s.cancelled > 0 {
+                line.push_str(&format!("/{} cancelled", s.cancelled));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+// ---------------------------------------------------------------------------
+// Driver
+// ---------------------------------------------------------------------------
+
+/// Outcome of waiting after a failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoffed {
+    /// Waited — try again
+    Retry,
+    /// Policy limit reached — give up
+    Exhausted,
+    /// Shutdown interrupted the wait (or arrived before it)
+    Cancelled,
+}
+
+/// Step-by-step retry state for one loop. Call `failed()` after each failed
+/// attempt; it records the failure, then waits or reports why not.
+pub struct Retrier {
+    policy: RetryPolicy,
+    site: RetrySite,
+    cancel: CancellationToken,
+    rng: SimRng,
+    /// Failures since the last success/reset
+    failures: u32,
+    first_attempt: Option<Instant>,
+}
+
+impl Retrier {
+    pub fn new(policy: RetryPolicy, site: RetrySite, cancel: CancellationToken) -> Self {
+        Self { policy, site, cancel, rng: SimRng::from_entropy(), failures: 0, first_attempt: None }
+    }
+
+    /// Replace the jitter RNG (tests)
+    pub fn with_rng(mut self, rng: SimRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// Failures since the last success
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Mark the start of an attempt (opens a new call after a success/reset)
+    pub fn attempt(&mut self) {
+        if self.first_attempt.is_none() {
+            self.first_attempt = Some(Instant::now());
+            RetrySite::bump(&self.site.counters.calls);
+        }
+        RetrySite::bump(&self.site.counters.attempts);
+    }
+
+    /// The attempt succeeded: count it and reset the failure streak
+    pub fn succeeded(&mut self) {
+        RetrySite::bump(&self.site.counters.successes);
+        self.failures = 0;
+        self.first_attempt = None;
+    }
+
+    /// The attempt failed with a non-retryable error
+    pub fn aborted(&mut self) {
+        RetrySite::bump(&self.site.counters.aborted);
+        self.failures = 0;
+        self.first_attempt = None;
+    }
+
+    /// The attempt failed and may be retried: give up, or wait the backoff
+    /// delay (interrupted by cancellation)
+    pub async fn failed(&mut self) -> Backoffed {
+        match self.record_failure() {
+            Some(delay) => self.wait(delay).await,
+            None => Backoffed::Exhausted,
+        }
+    }
+
+    /// First half of `failed()` for sites that log between deciding and
+    /// waiting: count the failure and return the delay to wait, or None when
+    /// the policy gives up
+    pub fn record_failure(&mut self) -> Option<Duration> {
+        self.failures += 1;
+        let elapsed = self.first_attempt.map(|t| t.elapsed()).unwrap_or_default();
+        if self.policy.exhausted(self.failures, elapsed) {
+            RetrySite::bump(&self.site.counters.exhausted);
+            self.first_attempt = None;
+            return None;
+        }
+        Some(self.policy.delay_for(self.failures, &self.rng))
+    }
+
+    /// Second half of `failed()`: sleep `delay` unless cancelled first
+    pub async fn wait(&mut self, delay: Duration) -> Backoffed {
+        if self.cancel.is_cancelled() {
+            RetrySite::bump(&self.site.counters.cancelled);
+            return Backoffed::Cancelled;
+        }
+        RetrySite::bump(&self.site.counters.retries);
+        self.site.counters.backoff_ms.fetch_add(delay.as_millis() as u64, Ordering::Relaxed);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => Backoffed::Retry,
+            _ = self.cancel.cancelled() => {
+                RetrySite::bump(&self.site.counters.cancelled);
+                Backoffed::Cancelled
+            }
+        }
+    }
+}
+
+/// Result of one attempt inside `retry()`
+#[derive(Debug)]
+pub enum Attempt<T, E> {
+    Done(T),
+    /// Transient failure — back off and try again
+    Retry(E),
+    /// Permanent failure — stop now
+    Abort(E),
+}
+
+/// Why `retry()` stopped without a value
+#[derive(Debug, PartialEq)]
+pub enum RetryError<E> {
+    /// Policy limit reached; `last` is the final transient error
+    Exhausted { attempts: u32, last: E },
+    /// The operation returned Attempt::Abort
+    Aborted(E),
+    /// Shutdown was requested; `last` is the most recent transient error
+    Cancelled { last: E },
+}
+
+/// Run `op` until it returns Done/Abort, the policy gives up, or `cancel`
+/// fires. `op` receives the 1-based attempt number.
+pub async fn retry<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    site: &RetrySite,
+    cancel: &CancellationToken,
+    mut op: F,
+) -> Result<T, RetryError<E>>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Attempt<T, E>>,
+{
+    let mut retrier = Retrier::new(policy.clone(), site.clone(), cancel.clone());
+    loop {
+        retrier.attempt();
+        match op(retrier.failures() + 1).await {
+            Attempt::Done(value) => {
+                retrier.succeeded();
+                return Ok(value);
+            }
+            Attempt::Abort(e) => {
+                retrier.aborted();
+                return Err(RetryError::Aborted(e));
+            }
+            Attempt::Retry(e) => match retrier.failed().await {
+                Backoffed::Retry => continue,
+                Backoffed::Exhausted => {
+                    return Err(RetryError::Exhausted { attempts: retrier.failures(), last: e })
+                }
+                Backoffed::Cancelled => return Err(RetryError::Cancelled { last: e }),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rng() -> SimRng {
+        SimRng::from_seed(7)
+    }
+
+    #[test]
+    fn test_fixed_exponential_and_jitter_delays() {
+        let fixed = RetryPolicy::fixed(Duration::from_secs(5));
+        assert_eq!(fixed.delay_for(1, &rng()), Duration::from_secs(5));
+        assert_eq!(fixed.delay_for(40, &rng()), Duration::from_secs(5));
+
+        let exp = RetryPolicy::exponential(Duration::from_millis(100), Duration::from_secs(1));
+        let delays: Vec<u64> = (1..=6).map(|n| exp.delay_for(n, &rng()).as_millis() as u64).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(exp.delay_for(200, &rng()), Duration::from_secs(1)); // no shift overflow
+
+        let jittered = RetryPolicy::fixed(Duration::from_secs(10)).with_jitter(0.2);
+        let r = rng();
+        let samples: Vec<Duration> = (0..200).map(|_| jittered.delay_for(1, &r)).collect();
+        assert!(samples.iter().all(|d| *d >= Duration::from_secs(8) && *d <= Duration::from_secs(12)));
+        assert!(samples.iter().any(|d| *d != samples[0]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_succeeds_after_transient_failures() {
+        let site = RetrySite::named("test.succeeds");
+        let policy = RetryPolicy::fixed(Duration::from_secs(1)).with_max_attempts(5);
+        let start = Instant::now();
+        let out: Result<u32, RetryError<&str>> = retry(&policy, &site, &CancellationToken::new(), |n| async move {
+            if n < 3 { Attempt::Retry("not yet") } else { Attempt::Done(n) }
+        }).await;
+        assert_eq!(out, Ok(3));
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+        let s = site.stats();
+        assert_eq!((s.calls, s.attempts, s.retries, s.successes, s.backoff_ms), (1, 3, 2, 1, 2000));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_exhausts_and_aborts() {
+        let site = RetrySite::named("test.exhausts");
+        let policy = RetryPolicy::fixed(Duration::from_secs(1)).with_max_attempts(3);
+        let out: Result<(), _> = retry(&policy, &site, &CancellationToken::new(), |_| async { Attempt::Retry("down") }).await;
+        assert_eq!(out, Err(RetryError::Exhausted { attempts: 3, last: "down" }));
+
+        let out: Result<(), _> = retry(&policy, &site, &CancellationToken::new(), |_| async { Attempt::Abort("fatal") }).await;
+        assert_eq!(out, Err(RetryError::Aborted("fatal")));
+
+        let s = site.stats();
+        assert_eq!((s.calls, s.attempts, s.retries, s.exhausted, s.aborted), (2, 4, 2, 1, 1));
+        assert!(status_line().contains("test.exhausts 2 calls/2 retries/1 exhausted/1 aborted"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancellation_interrupts_wait() {
+        let site = RetrySite::named("test.cancel");
+        let cancel = CancellationToken::new();
+        let policy = RetryPolicy::fixed(Duration::from_secs(3600));
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            trigger.cancel();
+        });
+        let start = Instant::now();
+        let out: Result<(), _> = retry(&policy, &site, &cancel, |_| async { Attempt::Retry("down") }).await;
+        assert_eq!(out, Err(RetryError::Cancelled { last: "down" }));
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        // Already cancelled: no wait at all
+        let out: Result<(), _> = retry(&policy, &site, &cancel, |_| async { Attempt::Retry("down") }).await;
+        assert_eq!(out, Err(RetryError::Cancelled { last: "down" }));
+        assert_eq!(site.stats().cancelled, 2);
+    }
+
+    /// WS reconnect (main loop): connect/subscribe failures share one streak,
+    /// 5s apart; the 51st consecutive failure exits; a subscribe resets it.
+    #[tokio::test(start_paused = true)]
+    async fn test_ws_reconnect_gives_up_after_50_retries() {
+        let mut ws = Retrier::new(ws_reconnect(), RetrySite::named("test.ws"), CancellationToken::new());
+        let start = Instant::now();
+
+        // 10 failures, then a successful subscribe resets the streak
+        for _ in 0..10 {
+            ws.attempt();
+            assert_eq!(ws.failed().await, Backoffed::Retry);
+        }
+        ws.attempt();
+        ws.succeeded();
+        assert_eq!(ws.failures(), 0);
+
+        let resumed = Instant::now();
+        let mut outcome = Backoffed::Retry;
+        while outcome == Backoffed::Retry {
+            ws.attempt();
+            outcome = ws.failed().await;
+        }
+        assert_eq!(outcome, Backoffed::Exhausted);
+        assert_eq!(ws.failures(), MAX_WS_RECONNECTS + 1);
+        // 50 waits of 5s ± 20%
+        let waited = resumed.elapsed().as_secs_f64();
+        assert!((200.0..=300.0).contains(&waited), "waited {}s", waited);
+        assert!((resumed - start).as_secs_f64() <= 60.0);
+    }
+
+    /// Mempool monitor: failures never reset; 51st failure returns the error
+    #[tokio::test(start_paused = true)]
+    async fn test_mempool_reconnect_gives_up_after_50_retries() {
+        let site = RetrySite::named("test.mempool");
+        let out: Result<(), _> = retry(&mempool_reconnect(), &site, &CancellationToken::new(), |n| async move {
+            Attempt::Retry(format!("session {} dropped", n))
+        }).await;
+        assert_eq!(out, Err(RetryError::Exhausted { attempts: 51, last: "session 51 dropped".to_string() }));
+        assert_eq!(site.stats().retries, MAX_MEMPOOL_RECONNECTS as u64);
+    }
+
+    /// Receipt polling: 250ms cadence, gives up on the first miss past 30s;
+    /// an RPC error stops immediately
+    #[tokio::test(start_paused = true)]
+    async fn test_receipt_poll_gives_up_after_30s() {
+        let site = RetrySite::named("test.receipt");
+        let start = Instant::now();
+        let out: Result<(), _> = retry(&receipt_poll(), &site, &CancellationToken::new(), |_| async { Attempt::Retry(()) }).await;
+        assert!(matches!(out, Err(RetryError::Exhausted { attempts: 122, .. })));
+        assert_eq!(start.elapsed(), Duration::from_millis(30_250));
+    }
+
+    /// Paper-trading supervisor: never gives up
+    #[tokio::test(start_paused = true)]
+    async fn test_paper_restart_is_unbounded() {
+        let mut restart = Retrier::new(paper_restart(), RetrySite::named("test.paper"), CancellationToken::new());
+        for _ in 0..1_000 {
+            restart.attempt();
+            assert_eq!(restart.failed().await, Backoffed::Retry);
+        }
+    }
+
+    /// Telemetry push: 4 attempts, then the snapshot is dropped
+    #[tokio::test(start_paused = true)]
+    async fn test_telemetry_push_gives_up_after_4_attempts() {
+        let site = RetrySite::named("test.telemetry");
+        let start = Instant::now();
+        let out: Result<(), _> = retry(&telemetry_push(), &site, &CancellationToken::new(), |_| async { Attempt::Retry(()) }).await;
+        assert!(matches!(out, Err(RetryError::Exhausted { attempts: 4, .. })));
+        // 2 + 4 + 8 = 14s ± 20%
+        assert!(start.elapsed() >= Duration::from_millis(11_200) && start.elapsed() <= Duration::from_millis(16_800));
+    }
+
+    /// Dead-man ping: 3 attempts, then the next interval pings again
+    #[tokio::test(start_paused = true)]
+    async fn test_deadman_ping_gives_up_after_3_attempts() {
+        let site = RetrySite::named("test.deadman");
+        let start = Instant::now();
+        let out: Result<(), _> = retry(&deadman_ping(), &site, &CancellationToken::new(), |_| async { Attempt::Retry(()) }).await;
+        assert!(matches!(out, Err(RetryError::Exhausted { attempts: 3, .. })));
+        // 1 + 2 = 3s ± 20%
+        assert!(start.elapsed() >= Duration::from_millis(2_400) && start.elapsed() <= Duration::from_millis(3_600));
+    }
+}
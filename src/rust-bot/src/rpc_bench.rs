@@ -0,0 +1,676 @@
+//! RPC Endpoint Benchmark — latency of the bot's own call mix, per endpoint
+//!
+//! Purpose:
+//!     Choosing between providers (Alchemy, QuickNode, a local node) by ping
+//!     time says little about the calls that decide whether we win a block.
+//!     This benchmark replays the bot's real requests against each endpoint
+//!     and compares latency distributions per call type.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Probe opportunities bound to their quote token
+//! Modified: 2026-10-16 - endpoint_label() for IPC sockets
+//!
+//! Design:
+//!     - Run with `dexarb-bot --bench-rpc URL [--bench-rpc URL ...]`; the
+//!       process syncs the whitelisted pools as at startup (untimed), runs
+//!       the benchmark, writes the report and exits without trading.
+//!     - Requests are built by the bot's own code, not synthesized:
+//!         eth_getLogs    pool::events::event_sync_filter over the synced pools
+//!         multicall      MulticallQuoter::batch_verify over `prescreen_size`
+//!                        opportunities built from the synced V3 pools
+//!         quoter         one buy-leg quoteExactInputSingle eth_call, encoded
+//!                        by MulticallQuoter::encode_quoter_for_dex
+//!         sendRaw        zero-value self-transfer — only with --bench-send,
+//!                        and refused on mainnet chain ids (MAINNET_CHAIN_IDS)
+//!         newHeads lag   subscribe_blocks arrival time minus the reference
+//!                        endpoint's (ws/wss endpoints only; negative = earlier)
+//!     - Each call goes through watchdog::rpc_timeout like the live loop, so a
+//!       hung endpoint shows up as errors rather than stalling the run.
+//!     - Endpoint URLs carry API keys: reports show scheme + host only.
+//!     - Output: a comparison table in the log and
+//!       data/{chain}/research/rpc_bench_YYYYMMDD_HHMMSS.json.
+
+use crate::arbitrage::multicall_quoter::MulticallQuoter;
+use crate::pool::events::event_sync_filter;
+use crate::types::{usd_to_raw, ArbitrageOpportunity, BotConfig, PoolState, V3PoolState};
+use crate::watchdog::rpc_timeout;
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Chain ids --bench-send refuses to transact on
+pub const MAINNET_CHAIN_IDS: [u64; 6] = [1, 10, 56, 137, 8453, 42161];
+
+/// One benchmarked request type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchCall {
+    GetLogs,
+    Multicall,
+    QuoterCall,
+    SendRaw,
+    BlockLag,
+}
+
+impl BenchCall {
+    pub const ALL: [BenchCall; 5] = [
+        BenchCall::GetLogs,
+        BenchCall::Multicall,
+        BenchCall::QuoterCall,
+        BenchCall::SendRaw,
+        BenchCall::BlockLag,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BenchCall::GetLogs => "eth_getLogs",
+            BenchCall::Multicall => "multicall aggregate3",
+            BenchCall::QuoterCall => "quoter eth_call",
+            BenchCall::SendRaw => "eth_sendRawTransaction",
+            BenchCall::BlockLag => "newHeads lag",
+        }
+    }
+}
+
+/// Benchmark parameters (CLI flags)
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    /// Requests per call type per endpoint
+    pub samples: usize,
+    /// Pause between requests (stay under provider rate limits)
+    pub pace: Duration,
+    /// Opportunities per multicall batch (2 quoter sub-calls each)
+    pub prescreen_size: usize,
+    /// Send zero-value self-transfers (never on MAINNET_CHAIN_IDS)
+    pub send_raw: bool,
+    /// Blocks to observe for the newHeads lag
+    pub lag_blocks: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            samples: 20,
+            pace: Duration::from_millis(100),
+            prescreen_size: 4,
+            send_raw: false,
+            lag_blocks: 10,
+        }
+    }
+}
+
+/// Latency distribution of one call type on one endpoint (milliseconds)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub ok: usize,
+    pub errors: usize,
+    pub min_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    pub mean_ms: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl LatencyStats {
+    pub fn from_samples(samples_ms: &[f64], errors: usize, last_error: Option<String>) -> Self {
+        if samples_ms.is_empty() {
+            return Self { errors, last_error, ..Self::default() };
+        }
+        let mut sorted = samples_ms.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        // Nearest-rank percentile
+        let pct = |p: f64| {
+            let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+        Self {
+            ok: sorted.len(),
+            errors,
+            min_ms: sorted[0],
+            p50_ms: pct(50.0),
+            p90_ms: pct(90.0),
+            p99_ms: pct(99.0),
+            max_ms: sorted[sorted.len() - 1],
+            mean_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            last_error,
+        }
+    }
+}
+
+/// Time `samples` invocations of `call` (each under rpc_timeout)
+pub async fn measure<T, E, F, Fut>(label: &str, samples: usize, pace: Duration, mut call: F) -> LatencyStats
+where
+    E: Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+{
+    let mut ok_ms = Vec::with_capacity(samples);
+    let mut errors = 0;
+    let mut last_error = None;
+    for i in 0..samples {
+        if i > 0 && !pace.is_zero() {
+            tokio::time::sleep(pace).await;
+        }
+        let start = Instant::now();
+        match rpc_timeout(label, call()).await {
+            Ok(_) => ok_ms.push(start.elapsed().as_secs_f64() * 1000.0),
+            Err(e) => {
+                errors += 1;
+                last_error = Some(e.to_string());
+            }
+        }
+    }
+    LatencyStats::from_samples(&ok_ms, errors, last_error)
+}
+
+/// The bot's request mix, built from the startup pool sync
+#[derive(Debug, Clone)]
+pub struct BenchWorkload {
+    /// Addresses in the event-sync log filter (V3 + V2, as in the live loop)
+    pub pools: Vec<Address>,
+    /// Pre-screen batch; the first one's buy leg is the single quoter call
+    pub opportunities: Vec<ArbitrageOpportunity>,
+}
+
+impl BenchWorkload {
+    /// Opportunities pair every two V3 pools of a pair (round-robin across
+    /// pairs) at MAX_TRADE_SIZE_USD, exactly as the detector would size them.
+    pub fn from_pools(
+        v3_pools: &[V3PoolState],
+        v2_pools: &[PoolState],
+        config: &BotConfig,
+        prescreen_size: usize,
+    ) -> Self {
+        let pools = v3_pools.iter().map(|p| p.address).chain(v2_pools.iter().map(|p| p.address)).collect();
+
+        let mut by_pair: BTreeMap<&str, Vec<&V3PoolState>> = BTreeMap::new();
+        for pool in v3_pools {
+            let quoted = config.is_quote_token(&pool.pair.token0) || config.is_quote_token(&pool.pair.token1);
+            if quoted && pool.price() > 0.0 {
+                by_pair.entry(pool.pair.symbol.as_str()).or_default().push(pool);
+            }
+        }
+        let mut routes: Vec<Vec<(&V3PoolState, &V3PoolState)>> = by_pair
+            .values()
+            .map(|pools| {
+                let mut r = Vec::new();
+                for (i, a) in pools.iter().enumerate() {
+                    for (j, b) in pools.iter().enumerate() {
+                        if i != j {
+                            r.push((*a, *b));
+                        }
+                    }
+                }
+                r
+            })
+            .filter(|r| !r.is_empty())
+            .collect();
+
+        let mut opportunities = Vec::new();
+        let mut round = 0;
+        while opportunities.len() < prescreen_size && routes.iter().any(|r| round < r.len()) {
+            for r in routes.iter_mut() {
+                if opportunities.len() >= prescreen_size {
+                    break;
+                }
+                if let Some(&(buy, sell)) = r.get(round) {
+                    opportunities.push(Self::opportunity(buy, sell, config));
+                }
+            }
+            round += 1;
+        }
+        Self { pools, opportunities }
+    }
+
+    fn opportunity(buy: &V3PoolState, sell: &V3PoolState, config: &BotConfig) -> ArbitrageOpportunity {
+        let quote_is_token0 = config.is_quote_token(&buy.pair.token0);
+        let quote_decimals = if quote_is_token0 { buy.token0_decimals } else { buy.token1_decimals };
+        let mut opp = ArbitrageOpportunity::with_pool_addresses(
+            buy.pair.clone(),
+            buy.dex,
+            sell.dex,
+            buy.price(),
+            sell.price(),
+            usd_to_raw(config.max_trade_size_usd, quote_decimals),
+            buy.address,
+            sell.address,
+        );
+        opp.set_quote_layout(buy.token0_decimals, buy.token1_decimals, quote_is_token0);
+        opp
+    }
+}
+
+/// One endpoint's results
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EndpointResult {
+    /// Redacted URL (scheme + host)
+    pub endpoint: String,
+    pub calls: BTreeMap<BenchCall, LatencyStats>,
+    /// Call types not measured, with the reason
+    pub skipped: BTreeMap<BenchCall, String>,
+}
+
+/// Full benchmark output (written as JSON)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub generated_at: String,
+    pub chain: String,
+    pub samples: usize,
+    pub log_filter_pools: usize,
+    pub prescreen_opportunities: usize,
+    /// Redacted newHeads reference endpoint
+    pub reference: String,
+    pub endpoints: Vec<EndpointResult>,
+}
+
+/// Scheme + host[:port] only — paths and queries carry API keys.
+/// IPC sockets are local paths and shown in full.
+pub fn endpoint_label(url: &str) -> String {
+    if let Some(path) = crate::transport::ipc_path(url) {
+        return format!("ipc://{}", path);
+    }
+    match reqwest::Url::parse(url) {
+        Ok(u) => {
+            let host = u.host_str().unwrap_or("?");
+            let port = u.port().map(|p| format!(":{}", p)).unwrap_or_default();
+            let hidden = (u.path() != "/" && !u.path().is_empty()) || u.query().is_some();
+            format!("{}://{}{}{}", u.scheme(), host, port, if hidden { "/…" } else { "" })
+        }
+        Err(_) => "<unparseable url>".to_string(),
+    }
+}
+
+pub fn is_ws(url: &str) -> bool {
+    url.starts_with("ws://") || url.starts_with("wss://")
+}
+
+/// Request/response call types against one endpoint
+pub async fn bench_endpoint<M: Middleware + 'static>(
+    endpoint: &str,
+    provider: Arc<M>,
+    config: &BotConfig,
+    workload: &BenchWorkload,
+    bench: &BenchConfig,
+    wallet: Option<&LocalWallet>,
+) -> Result<EndpointResult> {
+    let mut result = EndpointResult { endpoint: endpoint_label(endpoint), ..Default::default() };
+    let block = rpc_timeout("eth_blockNumber", provider.get_block_number())
+        .await
+        .context("endpoint unreachable")?
+        .as_u64();
+
+    // Fixed block: every sample reads the same logs
+    let filter = event_sync_filter(&workload.pools, block);
+    result.calls.insert(
+        BenchCall::GetLogs,
+        measure("eth_getLogs", bench.samples, bench.pace, || provider.get_logs(&filter)).await,
+    );
+
+    match (MulticallQuoter::new(Arc::clone(&provider), config), workload.opportunities.first()) {
+        (Ok(quoter), Some(probe)) => {
+            result.calls.insert(
+                BenchCall::Multicall,
+                measure("aggregate3", bench.samples, bench.pace, || {
+                    quoter.batch_verify(&workload.opportunities, config)
+                })
+                .await,
+            );
+
+            let (token_in, token_out) = if probe.quote_token_is_token0 {
+                (probe.pair.token0, probe.pair.token1)
+            } else {
+                (probe.pair.token1, probe.pair.token0)
+            };
+            let calldata = quoter.encode_quoter_for_dex(
+                probe.buy_dex,
+                token_in,
+                token_out,
+                probe.buy_dex.v3_fee_tier().unwrap_or_default(),
+                probe.trade_size.raw(),
+            );
+            let tx: TypedTransaction = TransactionRequest::new()
+                .to(quoter.quoter_for_dex(probe.buy_dex))
+                .data(calldata)
+                .into();
+            result.calls.insert(
+                BenchCall::QuoterCall,
+                measure("quoter eth_call", bench.samples, bench.pace, || provider.call(&tx, None)).await,
+            );
+        }
+        (Err(e), _) => {
+            for call in [BenchCall::Multicall, BenchCall::QuoterCall] {
+                result.skipped.insert(call, format!("quoter unavailable: {}", e));
+            }
+        }
+        (Ok(_), None) => {
+            for call in [BenchCall::Multicall, BenchCall::QuoterCall] {
+                result.skipped.insert(call, "no pair with two quoted V3 pools".to_string());
+            }
+        }
+    }
+
+    match (bench.send_raw, wallet) {
+        (true, Some(wallet)) => match bench_send_raw(Arc::clone(&provider), wallet, bench).await {
+            Ok(stats) => {
+                result.calls.insert(BenchCall::SendRaw, stats);
+            }
+            Err(e) => {
+                result.skipped.insert(BenchCall::SendRaw, e.to_string());
+            }
+        },
+        (true, None) => {
+            result.skipped.insert(BenchCall::SendRaw, "no wallet".to_string());
+        }
+        (false, _) => {
+            result.skipped.insert(BenchCall::SendRaw, "off (--bench-send, testnets only)".to_string());
+        }
+    }
+    Ok(result)
+}
+
+/// Zero-value self-transfers; nonce and gas price fetched once, untimed
+async fn bench_send_raw<M: Middleware + 'static>(
+    provider: Arc<M>,
+    wallet: &LocalWallet,
+    bench: &BenchConfig,
+) -> Result<LatencyStats> {
+    let chain_id = rpc_timeout("eth_chainId", provider.get_chainid()).await?.as_u64();
+    if MAINNET_CHAIN_IDS.contains(&chain_id) {
+        anyhow::bail!("refused on mainnet chain id {}", chain_id);
+    }
+    let from = wallet.address();
+    let mut nonce = rpc_timeout("eth_getTransactionCount", provider.get_transaction_count(from, Some(BlockNumber::Pending.into())))
+        .await?;
+    let gas_price = rpc_timeout("eth_gasPrice", provider.get_gas_price()).await?;
+    let signer = wallet.clone().with_chain_id(chain_id);
+
+    let mut signed = Vec::with_capacity(bench.samples);
+    for _ in 0..bench.samples {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(from)
+            .to(from)
+            .value(0u64)
+            .gas(21_000u64)
+            .gas_price(gas_price)
+            .nonce(nonce)
+            .chain_id(chain_id)
+            .into();
+        let signature = signer.sign_transaction_sync(&tx)?;
+        signed.push(tx.rlp_signed(&signature));
+        nonce += U256::one();
+    }
+    let mut raw = signed.into_iter();
+    Ok(measure("eth_sendRawTransaction", bench.samples, bench.pace, || {
+        let p = Arc::clone(&provider);
+        let tx = raw.next().unwrap_or_default();
+        async move { p.send_raw_transaction(tx).await.map(|pending| pending.tx_hash()) }
+    })
+    .await)
+}
+
+/// Header arrival times (block number → when it arrived) over a WS subscription
+pub async fn record_block_arrivals(url: &str, blocks: usize, wait: Duration) -> Result<HashMap<u64, Instant>> {
+    let provider = Provider::<Ws>::connect(url).await?;
+    let mut stream = provider.subscribe_blocks().await?;
+    let mut arrivals = HashMap::new();
+    let _ = tokio::time::timeout(wait, async {
+        while arrivals.len() < blocks {
+            match stream.next().await {
+                Some(block) => {
+                    if let Some(n) = block.number {
+                        arrivals.entry(n.as_u64()).or_insert_with(Instant::now);
+                    }
+                }
+                None => break,
+            }
+        }
+    })
+    .await;
+    Ok(arrivals)
+}
+
+/// Lag behind the reference per block both saw (ms, negative = earlier);
+/// blocks only the reference delivered count as errors
+pub fn block_lag_stats(reference: &HashMap<u64, Instant>, endpoint: &HashMap<u64, Instant>) -> LatencyStats {
+    let mut lags = Vec::new();
+    let mut missed = 0;
+    for (block, ref_at) in reference {
+        match endpoint.get(block) {
+            Some(at) if at >= ref_at => lags.push(at.duration_since(*ref_at).as_secs_f64() * 1000.0),
+            Some(at) => lags.push(-(ref_at.duration_since(*at).as_secs_f64() * 1000.0)),
+            None => missed += 1,
+        }
+    }
+    let last_error = (missed > 0).then(|| format!("{} block(s) not delivered", missed));
+    LatencyStats::from_samples(&lags, missed, last_error)
+}
+
+/// newHeads lag of every ws endpoint against `reference`, all subscribed at once
+pub async fn bench_block_lag(endpoints: &[String], reference: &str, blocks: usize) -> Vec<Result<LatencyStats>> {
+    // Generous: ~2s blocks on Polygon/Base, plus connection setup
+    let wait = Duration::from_secs(blocks as u64 * 4 + 10);
+    let reference_run = record_block_arrivals(reference, blocks, wait);
+    let endpoint_runs = futures::future::join_all(
+        endpoints.iter().map(|url| record_block_arrivals(url, blocks + 2, wait + Duration::from_secs(5))),
+    );
+    let (reference_arrivals, arrivals) = tokio::join!(reference_run, endpoint_runs);
+    let reference_arrivals = match reference_arrivals {
+        Ok(r) if !r.is_empty() => r,
+        Ok(_) => return endpoints.iter().map(|_| Err(anyhow::anyhow!("reference delivered no blocks"))).collect(),
+        Err(e) => {
+            warn!("Reference subscription failed: {}", e);
+            return endpoints.iter().map(|_| Err(anyhow::anyhow!("reference subscription failed"))).collect();
+        }
+    };
+    arrivals
+        .into_iter()
+        .map(|a| a.map(|a| block_lag_stats(&reference_arrivals, &a)))
+        .collect()
+}
+
+fn fmt_ms(stats: &LatencyStats, v: f64) -> String {
+    if stats.ok == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.1}", v)
+    }
+}
+
+/// Comparison table: one row per call type and endpoint, ★ = lowest p50
+pub fn format_table(report: &BenchReport) -> Vec<String> {
+    let width = report.endpoints.iter().map(|e| e.endpoint.chars().count()).max().unwrap_or(8).max(8);
+    let mut lines = vec![format!(
+        "  {:<22} {:<width$} {:>4} {:>4} {:>8} {:>8} {:>8} {:>8}",
+        "call", "endpoint", "ok", "err", "p50ms", "p90ms", "p99ms", "maxms",
+        width = width
+    )];
+    for call in BenchCall::ALL {
+        let best = report
+            .endpoints
+            .iter()
+            .filter_map(|e| e.calls.get(&call).filter(|s| s.ok > 0).map(|s| s.p50_ms))
+            .fold(f64::INFINITY, f64::min);
+        for endpoint in &report.endpoints {
+            let line = match (endpoint.calls.get(&call), endpoint.skipped.get(&call)) {
+                (Some(s), _) => format!(
+                    "{} {:<22} {:<width$} {:>4} {:>4} {:>8} {:>8} {:>8} {:>8}",
+                    if s.ok > 0 && s.p50_ms == best { "★" } else { " " },
+                    call.label(),
+                    endpoint.endpoint,
+                    s.ok,
+                    s.errors,
+                    fmt_ms(s, s.p50_ms),
+                    fmt_ms(s, s.p90_ms),
+                    fmt_ms(s, s.p99_ms),
+                    fmt_ms(s, s.max_ms),
+                    width = width
+                ),
+                (None, Some(reason)) => format!(
+                    "  {:<22} {:<width$} skipped: {}",
+                    call.label(), endpoint.endpoint, reason,
+                    width = width
+                ),
+                (None, None) => continue,
+            };
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+pub fn write_report(research_dir: &str, report: &BenchReport) -> Result<PathBuf> {
+    std::fs::create_dir_all(research_dir).with_context(|| format!("creating {}", research_dir))?;
+    let path = PathBuf::from(research_dir)
+        .join(format!("rpc_bench_{}.json", chrono::Utc::now().format("%Y%m%d_%H%M%S")));
+    std::fs::write(&path, serde_json::to_string_pretty(report)?)
+        .with_context(|| format!("writing {}", path.display()))?;
+    info!("RPC benchmark written: {}", path.display());
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::types::{DexType, TradingPair};
+
+    fn v3_pool(n: u64, symbol: &str, dex: DexType, token0: Address) -> V3PoolState {
+        V3PoolState {
+            address: Address::from_low_u64_be(n),
+            dex,
+            pair: TradingPair::new(token0, Address::from_low_u64_be(0xeee), symbol.to_string()),
+            sqrt_price_x96: U256::zero(),
+            tick: -200_000,
+            fee: dex.v3_fee_tier().unwrap_or_default(),
+            liquidity: 1_000_000_000,
+            token0_decimals: 6,
+            token1_decimals: 18,
+            last_updated: 1,
+        }
+    }
+
+    #[test]
+    fn test_latency_stats_percentiles() {
+        let samples: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+        let stats = LatencyStats::from_samples(&samples, 2, Some("timeout".to_string()));
+        assert_eq!((stats.ok, stats.errors), (100, 2));
+        assert_eq!((stats.min_ms, stats.p50_ms, stats.p90_ms, stats.p99_ms, stats.max_ms), (1.0, 50.0, 90.0, 99.0, 100.0));
+        assert!((stats.mean_ms - 50.5).abs() < 1e-9);
+        let empty = LatencyStats::from_samples(&[], 3, None);
+        assert_eq!((empty.ok, empty.errors, empty.p50_ms), (0, 3, 0.0));
+    }
+
+    /// Harness over a mocked transport: real requests, errors counted not fatal
+    #[tokio::test]
+    async fn test_bench_endpoint_over_mock_transport() {
+        let mut config = create_test_config();
+        config.uniswap_v3_quoter = None; // no quoter → pre-screen calls reported skipped
+        let workload = BenchWorkload { pools: vec![Address::from_low_u64_be(1)], opportunities: Vec::new() };
+        let bench = BenchConfig { samples: 4, pace: Duration::ZERO, ..BenchConfig::default() };
+
+        let (provider, mock) = Provider::mocked();
+        // MockProvider answers the last-pushed response first: 3 eth_getLogs
+        // answers (the 4th sample finds the queue empty → error), then eth_blockNumber
+        for _ in 0..3 {
+            mock.push::<Vec<Log>, _>(vec![Log::default()]).unwrap();
+        }
+        mock.push(U64::from(1_000u64)).unwrap();
+        let url = "https://polygon-mainnet.g.alchemy.com/v2/SECRETKEY";
+        let result = bench_endpoint(url, Arc::new(provider), &config, &workload, &bench, None).await.unwrap();
+
+        assert_eq!(result.endpoint, "https://polygon-mainnet.g.alchemy.com/…");
+        let logs = &result.calls[&BenchCall::GetLogs];
+        assert_eq!((logs.ok, logs.errors), (3, 1));
+        assert!(logs.last_error.is_some() && logs.p50_ms <= logs.max_ms);
+        assert!(result.skipped[&BenchCall::Multicall].starts_with("quoter unavailable"));
+        assert!(result.skipped[&BenchCall::SendRaw].contains("--bench-send"));
+
+        // Unreachable endpoint (no eth_blockNumber answer) is an error, not a panic
+        let (provider, _mock) = Provider::mocked();
+        assert!(bench_endpoint(url, Arc::new(provider), &config, &workload, &bench, None).await.is_err());
+    }
+
+    #[test]
+    fn test_workload_mirrors_detector_sizing() {
+        let config = create_test_config();
+        let usdc = config.quote_token_address;
+        let v3 = vec![
+            v3_pool(1, "WETH/USDC", DexType::UniswapV3_005, usdc),
+            v3_pool(2, "WETH/USDC", DexType::UniswapV3_030, usdc),
+            v3_pool(3, "WBTC/USDC", DexType::UniswapV3_005, usdc),
+            v3_pool(4, "WBTC/USDC", DexType::SushiV3_030, usdc),
+            v3_pool(5, "FOO/BAR", DexType::UniswapV3_005, Address::from_low_u64_be(0xf00)),
+        ];
+        let workload = BenchWorkload::from_pools(&v3, &[], &config, 3);
+        assert_eq!(workload.pools.len(), 5);
+        assert_eq!(workload.opportunities.len(), 3);
+        // Round-robin across pairs before reusing one; unquoted pairs ignored
+        let symbols: Vec<&str> = workload.opportunities.iter().map(|o| o.pair.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["WBTC/USDC", "WETH/USDC", "WBTC/USDC"]);
+        let o = &workload.opportunities[1];
+        assert!(o.quote_token_is_token0 && o.buy_pool_address != o.sell_pool_address);
+        assert_eq!(o.trade_size.raw(), usd_to_raw(config.max_trade_size_usd, 6));
+    }
+
+    #[test]
+    fn test_block_lag_and_table() {
+        let t0 = Instant::now();
+        let ms = |v: u64| t0 + Duration::from_millis(v);
+        let reference: HashMap<u64, Instant> = [(1, ms(100)), (2, ms(2_100)), (3, ms(4_100))].into_iter().collect();
+        let fast: HashMap<u64, Instant> = [(1, ms(80)), (2, ms(2_090)), (3, ms(4_095))].into_iter().collect();
+        let slow: HashMap<u64, Instant> = [(1, ms(250)), (2, ms(2_400))].into_iter().collect();
+        let fast_lag = block_lag_stats(&reference, &fast);
+        assert_eq!((fast_lag.ok, fast_lag.errors), (3, 0));
+        assert!((fast_lag.min_ms + 20.0).abs() < 1.0, "earlier than reference → negative");
+        let slow_lag = block_lag_stats(&reference, &slow);
+        assert_eq!((slow_lag.ok, slow_lag.errors), (2, 1));
+        assert!((slow_lag.max_ms - 300.0).abs() < 1.0);
+
+        let endpoint = |name: &str, lag: LatencyStats, logs_p50: f64| EndpointResult {
+            endpoint: name.to_string(),
+            calls: [
+                (BenchCall::GetLogs, LatencyStats::from_samples(&[logs_p50], 0, None)),
+                (BenchCall::BlockLag, lag),
+            ]
+            .into_iter()
+            .collect(),
+            skipped: [(BenchCall::SendRaw, "off".to_string())].into_iter().collect(),
+        };
+        let report = BenchReport {
+            generated_at: "2026-10-16T00:00:00Z".to_string(),
+            chain: "polygon".to_string(),
+            samples: 1,
+            log_filter_pools: 5,
+            prescreen_opportunities: 0,
+            reference: "wss://ref".to_string(),
+            endpoints: vec![endpoint("wss://fast", fast_lag, 42.0), endpoint("https://slow/…", slow_lag, 55.5)],
+        };
+        let lines = format_table(&report);
+        assert!(lines[0].contains("call") && lines[0].contains("p99ms"));
+        let logs: Vec<&String> = lines.iter().filter(|l| l.contains("eth_getLogs")).collect();
+        assert_eq!(logs.len(), 2);
+        assert!(logs[0].starts_with('★') && logs[0].contains("wss://fast") && logs[0].contains("42.0"));
+        assert!(logs[1].starts_with(' ') && logs[1].contains("55.5"));
+        assert!(lines.iter().any(|l| l.contains("eth_sendRawTransaction") && l.contains("skipped: off")));
+        assert!(!lines.iter().any(|l| l.contains("multicall")), "unmeasured and unskipped calls omitted");
+
+        // JSON round trip with call-type keys
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"get_logs\"") && json.contains("\"block_lag\""));
+        let back: BenchReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.endpoints[1].calls[&BenchCall::BlockLag].errors, 1);
+    }
+}
@@ -0,0 +1,605 @@
+//! Shadow Validation — a follower instance diffs its decisions against a primary
+//!
+//! Before trusting a refactor, the new build runs side by side with production
+//! as a follower that never trades, and every block's decisions are compared.
+//!
+//! Primary (SHADOW_ROLE=primary) appends one line per processed block to
+//! data/{chain}/shadow/decisions_YYYYMMDD.jsonl: every opportunity the detector
+//! emitted (route, rank by estimated profit, estimate, spread), its
+//! MIN_PROFIT_USD, and the routes it executed with their results. Blocks with
+//! no detections are written too, so "found nothing" is distinguishable from
+//! "didn't see the block".
+//!
+//! Follower (SHADOW_ROLE=follower) forces detection-only (LIVE_MODE ignored),
+//! builds the same record for its own blocks, tails the primary's feed
+//! (SHADOW_FEED_DIR, default its own shadow dir) and compares block by block:
+//!   missed    — primary detected the route, follower did not
+//!   extra     — follower detected a route the primary did not
+//!   threshold — one-sided detection whose estimate lies between the two
+//!               instances' MIN_PROFIT_USD (a config difference, not a bug)
+//!   ordering  — routes both detected, ranked in a different order
+//!   estimate  — both detected, estimated profit differs beyond tolerance
+//! Divergences go to data/{chain}/shadow/divergence_YYYYMMDD.csv; counts per
+//! day are kept in divergence_summary_YYYYMMDD.json. A block seen by only one
+//! side for SHADOW_MATCH_LAG_BLOCKS is dropped as unmatched (counted, not
+//! diffed — one instance was restarting or behind).
+//!
+//! The in-process A/B shadow detector (ab_shadow.rs) reuses compare() with
+//! the live detector as primary and the experimental one as follower.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - append_divergences() shared with the A/B shadow detector
+
+use crate::arbitrage::slippage::route_key;
+use crate::types::{ArbitrageOpportunity, BotConfig, TradeResult};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Estimated profit difference (USD) above which a common route is an `estimate` divergence
+pub const ESTIMATE_TOLERANCE_USD: f64 = 1e-6;
+
+/// This instance's part in a shadow comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowRole {
+    Off,
+    /// Publishes the decisions feed
+    Primary,
+    /// Detection-only; compares itself against the primary's feed
+    Follower,
+}
+
+impl ShadowRole {
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "primary" => ShadowRole::Primary,
+            "follower" => ShadowRole::Follower,
+            _ => ShadowRole::Off,
+        }
+    }
+}
+
+/// One detected opportunity in a block's decisions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedRoute {
+    /// "PAIR BuyDex→SellDex"
+    pub route: String,
+    /// 1 = highest estimated profit this block
+    pub rank: usize,
+    pub estimated_profit: f64,
+    pub spread_percent: f64,
+}
+
+/// One executed attempt (primary only)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutedRoute {
+    pub route: String,
+    pub success: bool,
+    pub net_profit_usd: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One instance's decisions for one block (a line of the decisions feed)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockDecisions {
+    pub block: u64,
+    pub min_profit_usd: f64,
+    pub detected: Vec<DetectedRoute>,
+    #[serde(default)]
+    pub executed: Vec<ExecutedRoute>,
+}
+
+impl BlockDecisions {
+    /// Detector output, ranked by estimated profit (route key breaks ties)
+    pub fn from_detected(block: u64, opportunities: &[ArbitrageOpportunity], min_profit_usd: f64) -> Self {
+        let mut detected: Vec<DetectedRoute> = opportunities
+            .iter()
+            .map(|opp| DetectedRoute {
+                route: route_key(opp),
+                rank: 0,
+                estimated_profit: opp.estimated_profit,
+                spread_percent: opp.spread_percent,
+            })
+            .collect();
+        detected.sort_by(|a, b| {
+            b.estimated_profit
+                .partial_cmp(&a.estimated_profit)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.route.cmp(&b.route))
+        });
+        for (i, d) in detected.iter_mut().enumerate() {
+            d.rank = i + 1;
+        }
+        Self { block, min_profit_usd, detected, executed: Vec::new() }
+    }
+
+    pub fn record_executed(&mut self, opp: &ArbitrageOpportunity, result: &TradeResult) {
+        self.executed.push(ExecutedRoute {
+            route: route_key(opp),
+            success: result.success,
+            net_profit_usd: result.net_profit_usd,
+            error: result.error.clone(),
+        });
+    }
+}
+
+/// Kind of difference between primary and follower for one block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DivergenceKind {
+    Missed,
+    Extra,
+    Threshold,
+    Ordering,
+    Estimate,
+}
+
+impl DivergenceKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DivergenceKind::Missed => "missed",
+            DivergenceKind::Extra => "extra",
+            DivergenceKind::Threshold => "threshold",
+            DivergenceKind::Ordering => "ordering",
+            DivergenceKind::Estimate => "estimate",
+        }
+    }
+}
+
+/// One divergence row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub block: u64,
+    pub kind: DivergenceKind,
+    /// Empty for block-level kinds (ordering)
+    pub route: String,
+    pub primary_rank: Option<usize>,
+    pub follower_rank: Option<usize>,
+    pub primary_profit: Option<f64>,
+    pub follower_profit: Option<f64>,
+    pub detail: String,
+}
+
+/// True if `profit` clears exactly one of the two thresholds
+fn between_thresholds(profit: f64, a: f64, b: f64) -> bool {
+    a != b && profit >= a.min(b) && profit < a.max(b)
+}
+
+/// Diff one block's decisions (primary vs follower)
+pub fn compare(primary: &BlockDecisions, follower: &BlockDecisions) -> Vec<Divergence> {
+    let ours: HashMap<&str, &DetectedRoute> = follower.detected.iter().map(|d| (d.route.as_str(), d)).collect();
+    let theirs: HashMap<&str, &DetectedRoute> = primary.detected.iter().map(|d| (d.route.as_str(), d)).collect();
+    let thresholds = (primary.min_profit_usd, follower.min_profit_usd);
+    let mut out = Vec::new();
+
+    for p in &primary.detected {
+        match ours.get(p.route.as_str()) {
+            None => {
+                let executed = primary.executed.iter().find(|e| e.route == p.route);
+                let kind = if between_thresholds(p.estimated_profit, thresholds.0, thresholds.1) {
+                    DivergenceKind::Threshold
+                } else {
+                    DivergenceKind::Missed
+                };
+                out.push(Divergence {
+                    block: primary.block,
+                    kind,
+                    route: p.route.clone(),
+                    primary_rank: Some(p.rank),
+                    follower_rank: None,
+                    primary_profit: Some(p.estimated_profit),
+                    follower_profit: None,
+                    detail: match executed {
+                        Some(e) => format!("primary executed (success={} net ${:.4})", e.success, e.net_profit_usd),
+                        None => String::new(),
+                    },
+                });
+            }
+            Some(f) if (f.estimated_profit - p.estimated_profit).abs() > ESTIMATE_TOLERANCE_USD => {
+                out.push(Divergence {
+                    block: primary.block,
+                    kind: DivergenceKind::Estimate,
+                    route: p.route.clone(),
+                    primary_rank: Some(p.rank),
+                    follower_rank: Some(f.rank),
+                    primary_profit: Some(p.estimated_profit),
+                    follower_profit: Some(f.estimated_profit),
+                    detail: format!("spread {:.4}% vs {:.4}%", p.spread_percent, f.spread_percent),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for f in follower.detected.iter().filter(|f| !theirs.contains_key(f.route.as_str())) {
+        let kind = if between_thresholds(f.estimated_profit, thresholds.0, thresholds.1) {
+            DivergenceKind::Threshold
+        } else {
+            DivergenceKind::Extra
+        };
+        out.push(Divergence {
+            block: primary.block,
+            kind,
+            route: f.route.clone(),
+            primary_rank: None,
+            follower_rank: Some(f.rank),
+            primary_profit: None,
+            follower_profit: Some(f.estimated_profit),
+            detail: String::new(),
+        });
+    }
+
+    // Relative order of the routes both detected
+    let common = |side: &BlockDecisions, other: &HashMap<&str, &DetectedRoute>| -> Vec<String> {
+        side.detected.iter().filter(|d| other.contains_key(d.route.as_str())).map(|d| d.route.clone()).collect()
+    };
+    let primary_order = common(primary, &ours);
+    let follower_order = common(follower, &theirs);
+    if primary_order != follower_order {
+        out.push(Divergence {
+            block: primary.block,
+            kind: DivergenceKind::Ordering,
+            route: String::new(),
+            primary_rank: None,
+            follower_rank: None,
+            primary_profit: None,
+            follower_profit: None,
+            detail: format!("primary [{}] vs follower [{}]", primary_order.join("; "), follower_order.join("; ")),
+        });
+    }
+    out
+}
+
+/// Append one line per block to the decisions feed (primary)
+pub struct DecisionFeed {
+    dir: PathBuf,
+}
+
+impl DecisionFeed {
+    pub fn new(dir: &str) -> Self {
+        Self { dir: PathBuf::from(dir) }
+    }
+
+    pub fn path_for_today(&self) -> PathBuf {
+        self.dir.join(format!("decisions_{}.jsonl", Utc::now().format("%Y%m%d")))
+    }
+
+    pub fn publish(&self, decisions: &BlockDecisions) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).with_context(|| format!("create {}", self.dir.display()))?;
+        let path = self.path_for_today();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("open {}", path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(decisions)?)?;
+        Ok(())
+    }
+}
+
+/// Divergence counts for one day (divergence_summary_YYYYMMDD.json)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceSummary {
+    pub day: String,
+    pub blocks_compared: u64,
+    pub blocks_identical: u64,
+    pub blocks_unmatched: u64,
+    pub divergences: BTreeMap<DivergenceKind, u64>,
+}
+
+/// Follower side: tails the primary's feed and diffs matched blocks
+pub struct ShadowComparator {
+    feed_dir: PathBuf,
+    out_dir: PathBuf,
+    lag_blocks: u64,
+    /// Feed file being tailed and the byte offset read so far
+    feed_path: Option<PathBuf>,
+    offset: u64,
+    /// Partial trailing line (primary mid-write)
+    partial: String,
+    primary: BTreeMap<u64, BlockDecisions>,
+    own: BTreeMap<u64, BlockDecisions>,
+    summary: DivergenceSummary,
+}
+
+impl ShadowComparator {
+    pub fn new(feed_dir: &str, out_dir: &str, lag_blocks: u64) -> Self {
+        let mut comparator = Self {
+            feed_dir: PathBuf::from(feed_dir),
+            out_dir: PathBuf::from(out_dir),
+            lag_blocks: lag_blocks.max(1),
+            feed_path: None,
+            offset: 0,
+            partial: String::new(),
+            primary: BTreeMap::new(),
+            own: BTreeMap::new(),
+            summary: DivergenceSummary::default(),
+        };
+        comparator.summary = comparator.load_summary(&Self::today());
+        comparator
+    }
+
+    fn today() -> String {
+        Utc::now().format("%Y%m%d").to_string()
+    }
+
+    pub fn summary(&self) -> &DivergenceSummary {
+        &self.summary
+    }
+
+    /// Feed one of our own blocks
+    pub fn record_own(&mut self, decisions: BlockDecisions) {
+        self.own.insert(decisions.block, decisions);
+    }
+
+    /// Feed one of the primary's blocks (normally read from the feed by `poll`)
+    pub fn record_primary(&mut self, decisions: BlockDecisions) {
+        self.primary.insert(decisions.block, decisions);
+    }
+
+    /// Read new feed lines, diff every block both sides have, write the rows.
+    /// Returns the divergences found this call.
+    pub fn poll(&mut self) -> Result<Vec<Divergence>> {
+        self.read_feed()?;
+        let divergences = self.match_blocks();
+        self.write(&divergences)?;
+        Ok(divergences)
+    }
+
+    fn read_feed(&mut self) -> Result<()> {
+        let today = self.feed_dir.join(format!("decisions_{}.jsonl", Self::today()));
+        if self.feed_path.is_none() {
+            self.feed_path = Some(today.clone());
+        }
+        // Day rollover: drain yesterday's file before switching
+        let rolled = self.feed_path.as_ref() != Some(&today);
+        if let Some(path) = self.feed_path.clone() {
+            self.read_from(&path)?;
+        }
+        if rolled {
+            self.feed_path = Some(today.clone());
+            self.offset = 0;
+            self.partial.clear();
+            self.read_from(&today)?;
+        }
+        Ok(())
+    }
+
+    fn read_from(&mut self, path: &Path) -> Result<()> {
+        let mut file = match std::fs::File::open(path) {
+            Ok(f) => f,
+            Err(_) => return Ok(()), // primary hasn't written today yet
+        };
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            self.offset = 0; // truncated / replaced
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let n = reader.read_line(&mut line)?;
+            if n == 0 {
+                break;
+            }
+            self.offset += n as u64;
+            if !line.ends_with('\n') {
+                self.partial.push_str(&line);
+                break;
+            }
+            let full = std::mem::take(&mut self.partial) + line.trim_end();
+            match serde_json::from_str::<BlockDecisions>(&full) {
+                Ok(d) => self.record_primary(d),
+                Err(e) => warn!("Shadow: skipping unreadable feed line in {}: {}", path.display(), e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Diff matched blocks; drop blocks one side never delivered within the lag
+    fn match_blocks(&mut self) -> Vec<Divergence> {
+        let mut out = Vec::new();
+        let matched: Vec<u64> = self.own.keys().filter(|b| self.primary.contains_key(b)).copied().collect();
+        for block in matched {
+            let (Some(ours), Some(theirs)) = (self.own.remove(&block), self.primary.remove(&block)) else {
+                continue;
+            };
+            let divergences = compare(&theirs, &ours);
+            self.summary.blocks_compared += 1;
+            if divergences.is_empty() {
+                self.summary.blocks_identical += 1;
+            }
+            for d in &divergences {
+                *self.summary.divergences.entry(d.kind).or_insert(0) += 1;
+            }
+            out.extend(divergences);
+        }
+
+        let newest = self.own.keys().chain(self.primary.keys()).max().copied().unwrap_or(0);
+        let horizon = newest.saturating_sub(self.lag_blocks);
+        for side in [&mut self.own, &mut self.primary] {
+            let stale: Vec<u64> = side.range(..horizon).map(|(b, _)| *b).collect();
+            for block in stale {
+                side.remove(&block);
+                self.summary.blocks_unmatched += 1;
+                debug!("Shadow: block {} seen by one side only — unmatched", block);
+            }
+        }
+        out
+    }
+
+    fn write(&mut self, divergences: &[Divergence]) -> Result<()> {
+        let day = Self::today();
+        if self.summary.day != day {
+            self.summary = DivergenceSummary { day: day.clone(), ..Default::default() };
+        }
+        std::fs::create_dir_all(&self.out_dir).with_context(|| format!("create {}", self.out_dir.display()))?;
+        append_divergences(&self.out_dir.join(format!("divergence_{}.csv", day)), divergences)?;
+        let path = self.out_dir.join(format!("divergence_summary_{}.json", day));
+        std::fs::write(&path, serde_json::to_string_pretty(&self.summary)?)
+            .with_context(|| format!("write {}", path.display()))?;
+        Ok(())
+    }
+
+    fn load_summary(&self, day: &str) -> DivergenceSummary {
+        let path = self.out_dir.join(format!("divergence_summary_{}.json", day));
+        std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_else(|| DivergenceSummary { day: day.to_string(), ..Default::default() })
+    }
+
+    /// Status line: blocks compared / identical / unmatched and counts per kind
+    pub fn status_line(&self) -> String {
+        let kinds: Vec<String> = self.summary.divergences.iter().map(|(k, n)| format!("{} {}", k.as_str(), n)).collect();
+        format!(
+            "{} compared, {} identical, {} unmatched | {}",
+            self.summary.blocks_compared,
+            self.summary.blocks_identical,
+            self.summary.blocks_unmatched,
+            if kinds.is_empty() { "no divergences".to_string() } else { kinds.join(", ") }
+        )
+    }
+}
+
+/// Append divergence rows to `path` (header on a new file); nothing to write = no file
+pub(crate) fn append_divergences(path: &Path, divergences: &[Divergence]) -> Result<()> {
+    if divergences.is_empty() {
+        return Ok(());
+    }
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open {}", path.display()))?;
+    if is_new {
+        writeln!(file, "timestamp,block,kind,route,primary_rank,follower_rank,primary_profit_usd,follower_profit_usd,detail")?;
+    }
+    let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    let opt = |v: Option<usize>| v.map(|v| v.to_string()).unwrap_or_default();
+    let usd = |v: Option<f64>| v.map(|v| format!("{:.6}", v)).unwrap_or_default();
+    for d in divergences {
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{},{},\"{}\"",
+            now,
+            d.block,
+            d.kind.as_str(),
+            d.route,
+            opt(d.primary_rank),
+            opt(d.follower_rank),
+            usd(d.primary_profit),
+            usd(d.follower_profit),
+            d.detail.replace('"', "'")
+        )?;
+    }
+    Ok(())
+}
+
+/// Shadow directory: data/{chain}/shadow
+pub fn shadow_dir(config: &BotConfig) -> String {
+    format!("/home/botuser/bots/dexarb/data/{}/shadow", config.chain_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(route: &str, rank: usize, profit: f64) -> DetectedRoute {
+        DetectedRoute { route: route.to_string(), rank, estimated_profit: profit, spread_percent: 0.5 }
+    }
+
+    fn block(routes: Vec<DetectedRoute>) -> BlockDecisions {
+        BlockDecisions { block: 100, min_profit_usd: 0.10, detected: routes, executed: Vec::new() }
+    }
+
+    fn kinds(d: &[Divergence]) -> Vec<(DivergenceKind, &str)> {
+        d.iter().map(|d| (d.kind, d.route.as_str())).collect()
+    }
+
+    #[test]
+    fn test_identical_sets_agree() {
+        let a = block(vec![route("WETH/USDC A→B", 1, 2.0), route("WBTC/USDC A→B", 2, 1.0)]);
+        assert!(compare(&a, &a.clone()).is_empty());
+        assert!(compare(&block(vec![]), &block(vec![])).is_empty());
+    }
+
+    #[test]
+    fn test_missing_and_extra() {
+        let mut primary = block(vec![route("WETH/USDC A→B", 1, 2.0), route("WBTC/USDC A→B", 2, 1.0)]);
+        primary.executed.push(ExecutedRoute {
+            route: "WBTC/USDC A→B".to_string(),
+            success: true,
+            net_profit_usd: 0.8,
+            error: None,
+        });
+        let follower = block(vec![route("WETH/USDC A→B", 1, 2.0), route("LINK/USDC A→B", 2, 0.5)]);
+        let d = compare(&primary, &follower);
+        assert_eq!(
+            kinds(&d),
+            vec![(DivergenceKind::Missed, "WBTC/USDC A→B"), (DivergenceKind::Extra, "LINK/USDC A→B")]
+        );
+        assert!(d[0].detail.contains("primary executed"));
+        assert_eq!((d[0].primary_rank, d[0].follower_rank), (Some(2), None));
+    }
+
+    #[test]
+    fn test_threshold_ordering_and_estimate() {
+        // Follower runs MIN_PROFIT_USD 0.50: the 0.30 route is a threshold difference
+        let primary = block(vec![
+            route("A", 1, 3.0),
+            route("B", 2, 2.0),
+            route("C", 3, 0.30),
+        ]);
+        let mut follower = block(vec![route("B", 1, 3.5), route("A", 2, 3.0)]);
+        follower.min_profit_usd = 0.50;
+        let d = compare(&primary, &follower);
+        assert_eq!(
+            kinds(&d),
+            vec![(DivergenceKind::Estimate, "B"), (DivergenceKind::Threshold, "C"), (DivergenceKind::Ordering, "")]
+        );
+        assert_eq!(d[2].detail, "primary [A; B] vs follower [B; A]");
+    }
+
+    #[test]
+    fn test_feed_tail_and_match() {
+        let dir = std::env::temp_dir().join(format!("dexarb_shadow_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let dir_s = dir.to_str().unwrap();
+        let feed = DecisionFeed::new(dir_s);
+        let mut cmp = ShadowComparator::new(dir_s, dir_s, 3);
+
+        feed.publish(&block(vec![route("A", 1, 1.0)])).unwrap();
+        cmp.record_own(block(vec![route("A", 1, 1.0), route("B", 2, 0.5)]));
+        let d = cmp.poll().unwrap();
+        assert_eq!(kinds(&d), vec![(DivergenceKind::Extra, "B")]);
+
+        // Only new lines are read on the next poll
+        feed.publish(&BlockDecisions { block: 101, ..block(vec![]) }).unwrap();
+        cmp.record_own(BlockDecisions { block: 101, ..block(vec![]) });
+        assert!(cmp.poll().unwrap().is_empty());
+        assert_eq!((cmp.summary().blocks_compared, cmp.summary().blocks_identical), (2, 1));
+
+        // A block the primary never delivered is dropped after the lag
+        cmp.record_own(BlockDecisions { block: 102, ..block(vec![]) });
+        cmp.record_own(BlockDecisions { block: 106, ..block(vec![]) });
+        cmp.poll().unwrap();
+        assert_eq!(cmp.summary().blocks_unmatched, 1);
+
+        let day = Utc::now().format("%Y%m%d");
+        let csv = std::fs::read_to_string(dir.join(format!("divergence_{}.csv", day))).unwrap();
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.lines().nth(1).unwrap().contains(",100,extra,B,,2,"));
+        assert!(dir.join(format!("divergence_summary_{}.json", day)).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,196 @@
+//! Graceful Shutdown — signal handling and in-flight trade draining
+//!
+//! Killing the bot mid-trade could leave a submitted atomic tx with no
+//! receipt processing and no tax record. `ShutdownController` owns the
+//! process-wide shutdown token (retry::shutdown_token):
+//!
+//! - SIGINT/SIGTERM cancel the token: retry waits end early, the block loop
+//!   stops taking new opportunities and leaves at its next check, the
+//!   mempool monitor task is cancelled via its child token
+//! - every `execute()` runs under `track()`, so shutdown can `drain()` the
+//!   trades still in flight (receipt polling included) with a deadline
+//!   before the tax/price/gas writers are flushed and cooldowns persisted
+//! - a second signal exits immediately (130)
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use anyhow::Result;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio_util::sync::{CancellationToken, WaitForCancellationFuture};
+use tracing::warn;
+
+/// How long shutdown waits for in-flight trades (receipt polling gives up at 30s)
+pub const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// Exit code for a forced exit on the second signal (128 + SIGINT)
+pub const FORCED_EXIT_CODE: i32 = 130;
+
+/// Trades currently executing
+#[derive(Debug, Default)]
+struct InFlight {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+/// Decrements the in-flight count when the tracked future finishes or is dropped
+struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+/// Shutdown flag + in-flight trade accounting (cheap to clone)
+#[derive(Debug, Clone)]
+pub struct ShutdownController {
+    token: CancellationToken,
+    in_flight: Arc<InFlight>,
+}
+
+impl ShutdownController {
+    pub fn new(token: CancellationToken) -> Self {
+        Self { token, in_flight: Arc::new(InFlight::default()) }
+    }
+
+    /// Install SIGINT/SIGTERM handlers: the first signal requests shutdown,
+    /// the second exits the process immediately
+    pub fn install_signal_handlers(&self) -> Result<()> {
+        let token = self.token.clone();
+        let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::spawn(async move {
+            for _ in 0..2 {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+                if token.is_cancelled() {
+                    warn!("Second shutdown signal — exiting now");
+                    std::process::exit(FORCED_EXIT_CODE);
+                }
+                warn!("Shutdown signal received — finishing in-flight trades, then exiting");
+                token.cancel();
+            }
+        });
+        Ok(())
+    }
+
+    /// The shutdown token (retry waits, child tokens for spawned tasks)
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Set once a signal arrived: take no new opportunities
+    pub fn is_shutting_down(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Resolves when shutdown is requested
+    pub fn cancelled(&self) -> WaitForCancellationFuture<'_> {
+        self.token.cancelled()
+    }
+
+    /// Request shutdown (same as a first signal)
+    pub fn request(&self) {
+        self.token.cancel();
+    }
+
+    /// Run one trade execution, counted as in flight until it completes
+    pub async fn track<F: Future>(&self, fut: F) -> F::Output {
+        self.in_flight.count.fetch_add(1, Ordering::AcqRel);
+        let _guard = InFlightGuard(self.in_flight.clone());
+        fut.await
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.count.load(Ordering::Acquire)
+    }
+
+    /// Wait until no trade is in flight. False = `timeout` passed first.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let idle = self.in_flight.idle.notified();
+            tokio::pin!(idle);
+            // Register before checking, so a completion in between is not missed
+            idle.as_mut().enable();
+            if self.in_flight() == 0 {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, idle).await.is_err() {
+                return self.in_flight() == 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Stand-in for TradeExecutor::execute(): sleeps, then books the trade
+    async fn mock_execute(ms: u64, id: u32, booked: Arc<Mutex<Vec<u32>>>) {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        booked.lock().unwrap().push(id);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_stops_new_trades_and_drains_in_flight() {
+        let shutdown = ShutdownController::new(CancellationToken::new());
+        let booked = Arc::new(Mutex::new(Vec::new()));
+
+        // Block loop: one trade per "block" until shutdown is requested
+        let loop_shutdown = shutdown.clone();
+        let loop_booked = booked.clone();
+        let block_loop = tokio::spawn(async move {
+            let mut id = 0;
+            while !loop_shutdown.is_shutting_down() {
+                id += 1;
+                loop_shutdown.track(mock_execute(2_000, id, loop_booked.clone())).await;
+            }
+        });
+
+        // Signal arrives while trade #2 is waiting for its receipt
+        tokio::time::sleep(Duration::from_millis(3_000)).await;
+        assert_eq!(shutdown.in_flight(), 1);
+        shutdown.request();
+        assert!(shutdown.drain(Duration::from_secs(10)).await);
+        assert_eq!(shutdown.in_flight(), 0);
+        block_loop.await.unwrap();
+
+        // Trade #2 completed (booked), no trade #3 was started
+        assert_eq!(*booked.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_gives_up_at_the_deadline() {
+        let shutdown = ShutdownController::new(CancellationToken::new());
+        assert!(shutdown.drain(Duration::from_secs(1)).await, "nothing in flight");
+
+        let booked = Arc::new(Mutex::new(Vec::new()));
+        let stuck = shutdown.clone();
+        let stuck_booked = booked.clone();
+        let trade = tokio::spawn(async move { stuck.track(mock_execute(60_000, 1, stuck_booked)).await });
+        tokio::task::yield_now().await;
+
+        shutdown.request();
+        let started = tokio::time::Instant::now();
+        assert!(!shutdown.drain(Duration::from_secs(5)).await);
+        assert_eq!(started.elapsed(), Duration::from_secs(5));
+        assert_eq!(shutdown.in_flight(), 1);
+
+        // A dropped execution no longer counts as in flight
+        trade.abort();
+        let _ = trade.await;
+        assert_eq!(shutdown.in_flight(), 0);
+        assert!(booked.lock().unwrap().is_empty());
+    }
+}
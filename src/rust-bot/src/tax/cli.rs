@@ -0,0 +1,258 @@
+//! Tax CLI subcommands (dexarb-tax)
+//!
+//! Offline reports over the tax directory: no RPC connection, no private key,
+//! no chain config — only the JSONL records the bot wrote. The exception is
+//! reconcile, which checks the records against the chain (RPC_URL; with
+//! --price-log-dir also the bot's config, for NATIVE_PRICE_PAIR).
+//!
+//! Usage:
+//!   dexarb-tax summary --year 2026 --chain polygon
+//!   dexarb-tax export-rp2 --year 2026 --out /tmp/rp2
+//!   dexarb-tax verify --tx 0xabc...
+//!   dexarb-tax reconcile --from-block 60000000 --to-block 60100000 [--repair]
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - reconcile subcommand (on-chain trades vs tax records, --repair)
+
+use super::reconcile::{self, HistoricalNativePrices, ReconcileParams, DEFAULT_PAGE_SIZE};
+use super::{
+    export_grouped_to_rp2, group_for_rp2, rp2_sections, validate_rp2_export, LotLedger, TaxJsonLogger, TaxRecord,
+    TaxSummary,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Parser, Subcommand};
+use ethers::providers::{Http, Provider};
+use ethers::types::Address;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Default tax record directory (the bot's TAX_LOG_DIR)
+pub const DEFAULT_TAX_DIR: &str = "/home/botuser/bots/dexarb/data/tax";
+
+#[derive(Parser, Debug)]
+#[command(name = "dexarb-tax", about = "Tax reports from the bot's trade records (offline)")]
+pub struct TaxCli {
+    /// Directory holding trades_{year}.jsonl
+    #[arg(long, env = "TAX_LOG_DIR", default_value = DEFAULT_TAX_DIR, global = true)]
+    pub tax_dir: PathBuf,
+
+    #[command(subcommand)]
+    pub command: TaxCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TaxCommand {
+    /// Print the realized-gain summary for a tax year
+    Summary {
+        #[arg(long)]
+        year: i16,
+        /// Only this chain's records (polygon, base); default all chains
+        #[arg(long)]
+        chain: Option<String>,
+    },
+    /// Write RP2 input files (one per chain and wallet) and validate them
+    ExportRp2 {
+        #[arg(long)]
+        year: i16,
+        /// Output directory
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Look up the tax record(s) of a transaction hash, across all years
+    Verify {
+        #[arg(long)]
+        tx: String,
+    },
+    /// Match on-chain trades in a block range against the tax records
+    Reconcile {
+        #[arg(long)]
+        from_block: u64,
+        #[arg(long)]
+        to_block: u64,
+        #[arg(long, env = "RPC_URL")]
+        rpc_url: String,
+        /// ArbExecutor whose Arb*Executed events are atomic trades
+        #[arg(long, env = "ARB_EXECUTOR_ADDRESS")]
+        contract: Option<Address>,
+        /// Wallet whose non-quote token transfers are legacy trades
+        #[arg(long, env = "WALLET_ADDRESS")]
+        wallet: Option<Address>,
+        /// Quote token(s), comma-separated: their transfers are not legacy sells
+        #[arg(long = "quote-token", env = "QUOTE_TOKEN_ADDRESS", value_delimiter = ',')]
+        quote_tokens: Vec<Address>,
+        /// Largest profit difference (quote token units) that still matches
+        #[arg(long, default_value = "0.01")]
+        tolerance: Decimal,
+        /// Blocks per eth_getLogs request (halved on RPC errors)
+        #[arg(long, default_value_t = DEFAULT_PAGE_SIZE)]
+        page_size: u64,
+        /// Write records for missing atomic trades (bot stopped!)
+        #[arg(long)]
+        repair: bool,
+        /// Price logs for historical gas prices when repairing
+        #[arg(long)]
+        price_log_dir: Option<PathBuf>,
+        #[arg(long, env = "CHAIN_NAME", default_value = "polygon")]
+        chain_name: String,
+        #[arg(long, env = "TAX_LOT_METHOD", default_value = "fifo")]
+        lot_method: String,
+    },
+}
+
+impl TaxCli {
+    /// Run the subcommand, writing its report to `out`
+    pub fn run(&self, out: &mut impl Write) -> Result<()> {
+        match &self.command {
+            TaxCommand::Summary { year, chain } => summary(&self.tax_dir, *year, chain.as_deref(), out),
+            TaxCommand::ExportRp2 { year, out: dir } => export_rp2(&self.tax_dir, *year, dir, out),
+            TaxCommand::Verify { tx } => verify(&self.tax_dir, tx, out),
+            TaxCommand::Reconcile { .. } => self.reconcile(out),
+        }
+    }
+
+    /// The one subcommand with a provider: a runtime just for it
+    fn reconcile(&self, out: &mut impl Write) -> Result<()> {
+        let TaxCommand::Reconcile {
+            from_block, to_block, rpc_url, contract, wallet, quote_tokens, tolerance, page_size, repair, price_log_dir,
+            chain_name, lot_method,
+        } = &self.command
+        else {
+            unreachable!("reconcile() on another subcommand")
+        };
+        if from_block > to_block {
+            bail!("--from-block {} is after --to-block {}", from_block, to_block);
+        }
+        if contract.is_none() && wallet.is_none() {
+            bail!("nothing to scan: set --contract (ARB_EXECUTOR_ADDRESS) and/or --wallet");
+        }
+        let native_prices = match price_log_dir {
+            Some(dir) if *repair => {
+                let config = crate::config::load_config()?;
+                let prices = HistoricalNativePrices::from_price_logs(&config, dir, *from_block, *to_block)?;
+                writeln!(out, "Historical native prices: {} block(s) from {}", prices.len(), dir.display())?;
+                prices
+            }
+            _ => HistoricalNativePrices::default(),
+        };
+        let params = ReconcileParams {
+            from_block: *from_block,
+            to_block: *to_block,
+            contract: *contract,
+            wallet: *wallet,
+            quote_tokens: quote_tokens.clone(),
+            tolerance: *tolerance,
+            page_size: *page_size,
+            repair: *repair,
+            chain_name: chain_name.clone(),
+            lot_method: lot_method.parse()?,
+            native_prices,
+        };
+        let provider = Arc::new(Provider::<Http>::try_from(rpc_url.as_str()).map_err(|e| anyhow!("RPC_URL: {}", e))?);
+        let years = record_years(&self.tax_dir)?;
+        let runtime = tokio::runtime::Runtime::new()?;
+        let (report, repaired) = runtime.block_on(reconcile::run(provider, &self.tax_dir, &years, &params, out))?;
+
+        let open = report.discrepancies() - repaired;
+        if open > 0 {
+            bail!("{} discrepancy(ies) between the chain and {}", open, self.tax_dir.display());
+        }
+        Ok(())
+    }
+}
+
+fn on_chain(record: &TaxRecord, chain: Option<&str>) -> bool {
+    chain.is_none_or(|c| record.blockchain.eq_ignore_ascii_case(c.trim()))
+}
+
+/// Realized gains for `year`, replaying every earlier year too so lots
+/// acquired before the year are still open
+fn summary(tax_dir: &Path, year: i16, chain: Option<&str>, out: &mut impl Write) -> Result<()> {
+    let logger = TaxJsonLogger::new(tax_dir)?;
+    let records: Vec<TaxRecord> = logger.read_through(year)?.into_iter().filter(|r| on_chain(r, chain)).collect();
+
+    let mut ledger = LotLedger::default();
+    ledger.replay(&records);
+    let summary = TaxSummary::from_ledger(year, &ledger);
+    writeln!(out, "{}", summary.report())?;
+
+    let paper = records.iter().filter(|r| r.tax_year == year && r.is_paper_trade).count();
+    if paper > 0 {
+        writeln!(out, "Note: {} paper trades excluded from summary", paper)?;
+    }
+    Ok(())
+}
+
+/// RP2 export of `year` into `dir`; fails if any file fails validation
+fn export_rp2(tax_dir: &Path, year: i16, dir: &Path, out: &mut impl Write) -> Result<()> {
+    let logger = TaxJsonLogger::new(tax_dir)?;
+    let records = logger.read_all(year)?;
+    let groups = group_for_rp2(&records);
+    if groups.is_empty() {
+        writeln!(out, "No real trades for tax year {} in {}", year, tax_dir.display())?;
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let prefix = format!("rp2_{}", year);
+    let sections = rp2_sections(&groups, &prefix);
+    let mut invalid = 0;
+    for (path, rows) in export_grouped_to_rp2(&groups, dir, &prefix)? {
+        let validation = validate_rp2_export(&path, &sections)?;
+        let status = if validation.valid { "VALID" } else { "INVALID" };
+        writeln!(out, "{}: {} rows, {}", path.display(), rows, status)?;
+        for err in &validation.errors {
+            writeln!(out, "  ✗ {}", err)?;
+        }
+        if !validation.valid {
+            invalid += 1;
+        }
+    }
+    if invalid > 0 {
+        bail!("{} RP2 file(s) failed validation", invalid);
+    }
+    Ok(())
+}
+
+/// Years with a trades_{year}.jsonl in `tax_dir`, ascending
+pub(super) fn record_years(tax_dir: &Path) -> Result<Vec<i16>> {
+    let mut years: Vec<i16> = std::fs::read_dir(tax_dir)
+        .with_context(|| format!("Failed to read tax directory {}", tax_dir.display()))?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().to_string_lossy().to_string();
+            name.strip_prefix("trades_")?.strip_suffix(".jsonl")?.parse().ok()
+        })
+        .collect();
+    years.sort_unstable();
+    Ok(years)
+}
+
+/// Every record of `tx` (the trade plus any reversal superseding it)
+fn verify(tax_dir: &Path, tx: &str, out: &mut impl Write) -> Result<()> {
+    let logger = TaxJsonLogger::new(tax_dir)?;
+    let mut found = 0;
+    for year in record_years(tax_dir)? {
+        for r in logger.read_all(year)?.iter().filter(|r| r.transaction_hash.eq_ignore_ascii_case(tx.trim())) {
+            found += 1;
+            writeln!(out, "Trade {} ({} {}, tax year {})", r.trade_id, r.blockchain, r.chain_id, r.tax_year)?;
+            writeln!(out, "  tx:         {} (block {})", r.transaction_hash, r.block_number)?;
+            writeln!(out, "  time:       {}", r.timestamp.to_rfc3339())?;
+            writeln!(out, "  wallet:     {}", r.wallet_address)?;
+            writeln!(out, "  sent:       {} {} (${:.2})", r.amount_sent, r.asset_sent, r.usd_value_sent)?;
+            writeln!(out, "  received:   {} {} (${:.2})", r.amount_received, r.asset_received, r.usd_value_received)?;
+            writeln!(out, "  route:      {} → {}", r.dex_buy, r.dex_sell)?;
+            writeln!(out, "  fees:       ${:.2}", r.total_fees_usd)?;
+            writeln!(out, "  gain/loss:  ${:.2} ({})", r.capital_gain_loss, r.gain_type)?;
+            writeln!(out, "  finality:   {}{}", r.finality, if r.is_paper_trade { " (paper)" } else { "" })?;
+            if let Some(ref original) = r.supersedes {
+                writeln!(out, "  supersedes: {}", original)?;
+            }
+        }
+    }
+    if found == 0 {
+        bail!("No tax record for tx {} in {}", tx, tax_dir.display());
+    }
+    Ok(())
+}
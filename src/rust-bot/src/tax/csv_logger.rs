@@ -3,10 +3,16 @@
 //! Logs tax records to CSV files for IRS compliance and audit trail.
 //! Creates annual files: data/tax/trades_YYYY.csv
 //!
+//! Finality: rows carry `finality` and `supersedes` as the last two columns.
+//! A provisional row's flag is updated in place (rewrite to a temp file +
+//! rename) once its tx is confirmation-depth deep; a file from before these
+//! columns existed is upgraded on first append (its rows were final).
+//!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - finality/supersedes columns, legacy header upgrade, set_finality
 
-use super::TaxRecord;
+use super::{RecordFinality, TaxRecord};
 use anyhow::{Context, Result};
 use chrono::Datelike;
 use std::fs::{self, File, OpenOptions};
@@ -64,8 +70,16 @@ impl TaxCsvLogger {
         "spread_percent",
         "is_paper_trade",
         "notes",
+        "finality",
+        "supersedes",
     ];
 
+    /// Columns appended after `notes` (absent from pre-finality files)
+    const FINALITY_COLUMNS: usize = 2;
+
+    /// Column holding the tx hash (before `notes`, so a plain split finds it)
+    const TX_HASH_COLUMN: usize = 26;
+
     /// Create a new CSV logger
     ///
     /// # Arguments
@@ -110,6 +124,9 @@ impl TaxCsvLogger {
         }
 
         let file_path = self.current_file_path();
+        if self.headers_written {
+            Self::upgrade_legacy_header(&file_path)?;
+        }
 
         // Open file in append mode
         let mut file = OpenOptions::new()
@@ -179,6 +196,8 @@ impl TaxCsvLogger {
             record.is_paper_trade.to_string(),
             // Escape notes field (may contain commas)
             escape_csv_field(&record.notes.clone().unwrap_or_default()),
+            record.finality.to_string(),
+            record.supersedes.clone().unwrap_or_default(),
         ];
 
         let line = fields.join(",");
@@ -187,6 +206,59 @@ impl TaxCsvLogger {
         Ok(())
     }
 
+    /// Add the finality columns to a file written before they existed.
+    /// Its rows predate provisional logging, so they are all final.
+    fn upgrade_legacy_header(path: &Path) -> Result<()> {
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return Ok(()), // missing: log() creates it
+        };
+        let legacy = Self::HEADERS[..Self::HEADERS.len() - Self::FINALITY_COLUMNS].join(",");
+        if content.lines().next() != Some(legacy.as_str()) {
+            return Ok(());
+        }
+        let mut lines = content.lines();
+        lines.next();
+        let mut upgraded = vec![Self::HEADERS.join(",")];
+        upgraded.extend(lines.map(|line| format!("{},{},", line, RecordFinality::Final)));
+        replace_file(path, &upgraded)
+    }
+
+    /// Set the finality flag of the original (non-correcting) rows for
+    /// `tx_hash` in `year`'s file. Returns the number of rows updated.
+    pub fn set_finality(&self, tx_hash: &str, year: i16, finality: RecordFinality) -> Result<usize> {
+        let path = Self::file_path_for_year(&self.base_dir, year);
+        if !path.exists() {
+            return Ok(0);
+        }
+        Self::upgrade_legacy_header(&path)?;
+        let content = fs::read_to_string(&path)?;
+        let mut updated = 0;
+        let lines: Vec<String> = content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if i == 0 || line.split(',').nth(Self::TX_HASH_COLUMN) != Some(tx_hash) {
+                    return line.to_string();
+                }
+                // finality and supersedes are the last two fields (notes may hold commas)
+                let mut tail = line.rsplitn(3, ',');
+                let supersedes = tail.next().unwrap_or_default();
+                let _old = tail.next();
+                let head = tail.next().unwrap_or_default();
+                if !supersedes.is_empty() {
+                    return line.to_string();
+                }
+                updated += 1;
+                format!("{},{},", head, finality)
+            })
+            .collect();
+        if updated > 0 {
+            replace_file(&path, &lines)?;
+        }
+        Ok(updated)
+    }
+
     /// Get the path to the current year's CSV file
     pub fn get_current_file_path(&self) -> PathBuf {
         self.current_file_path()
@@ -217,6 +289,16 @@ impl TaxCsvLogger {
     }
 }
 
+/// Write `lines` to a temp file beside `path`, then rename over it
+pub(crate) fn replace_file(path: &Path, lines: &[String]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut body = lines.join("\n");
+    body.push('\n');
+    fs::write(&tmp, body).with_context(|| format!("Failed to write {:?}", tmp))?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))?;
+    Ok(())
+}
+
 /// Escape a CSV field that may contain special characters
 fn escape_csv_field(field: &str) -> String {
     if field.contains(',') || field.contains('"') || field.contains('\n') {
@@ -293,4 +375,47 @@ mod tests {
         // Clean up
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn test_set_finality_and_legacy_header_upgrade() {
+        assert_eq!(TaxCsvLogger::HEADERS[TaxCsvLogger::TX_HASH_COLUMN], "transaction_hash");
+        let temp_dir = env::temp_dir().join("dexarb_tax_test_finality");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        // A file from before the finality columns, with one row
+        let legacy_header = TaxCsvLogger::HEADERS[..TaxCsvLogger::HEADERS.len() - 2].join(",");
+        let legacy_row = vec!["x"; TaxCsvLogger::HEADERS.len() - 2].join(",");
+        let year = chrono::Utc::now().format("%Y").to_string().parse::<i16>().unwrap();
+        let path = TaxCsvLogger::file_path_for_year(&temp_dir, year);
+        fs::write(&path, format!("{}\n{}\n", legacy_header, legacy_row)).unwrap();
+
+        let mut logger = TaxCsvLogger::new(&temp_dir).unwrap();
+        let record = crate::tax::TaxRecord::new_arbitrage(
+            "USDC".to_string(), dec!(1000), 6, "WETH".to_string(), dec!(0.5), 18,
+            dec!(1.0), dec!(2000.0), dec!(0.001), dec!(0.90), dec!(0.30),
+            "0xfeed".to_string(), 12345678, "0xwallet".to_string(),
+            "Uniswap".to_string(), "Sushiswap".to_string(), "0xpool1".to_string(), "0xpool2".to_string(),
+            dec!(1.0), false,
+        )
+        .provisional();
+        logger.log(&record).unwrap();
+        logger.log(&record.reversal("reorg")).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], TaxCsvLogger::HEADERS.join(","));
+        assert!(lines[1].ends_with(",final,"), "legacy rows upgraded as final");
+        assert!(lines[2].ends_with(",provisional,"));
+
+        // Only the original row is flagged; the correcting entry is left alone
+        assert_eq!(logger.set_finality("0xfeed", year, RecordFinality::Reversed).unwrap(), 1);
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[2].ends_with(",reversed,"));
+        assert!(lines[3].ends_with(&format!(",final,{}", record.trade_id)));
+        assert_eq!(logger.record_count().unwrap(), 3);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -10,8 +10,13 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - TaxLogger::log_resumable (strict-mode retries never duplicate CSV rows)
+//! Modified: 2026-10-16 - set_finality (in-place flag update) + TaxLogger::supersede (reorg correcting entry)
+//! Modified: 2026-10-16 - supersede retries reuse an already-written correcting entry
+//! Modified: 2026-10-16 - read_through (lot ledger replay); supersede reverses every record of the tx (lot splits)
 
-use super::TaxRecord;
+use super::csv_logger::replace_file;
+use super::{RecordFinality, TaxRecord};
 use anyhow::{Context, Result};
 use chrono::Datelike;
 use std::fs::{self, File, OpenOptions};
@@ -131,10 +136,61 @@ impl TaxJsonLogger {
         Ok(records)
     }
 
+    /// Read every year's records up to and including `year`, oldest year
+    /// first (lots acquired in earlier years are still open)
+    pub fn read_through(&self, year: i16) -> Result<Vec<TaxRecord>> {
+        let mut years: Vec<i16> = fs::read_dir(&self.base_dir)
+            .with_context(|| format!("Failed to list tax directory: {:?}", self.base_dir))?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                name.strip_prefix("trades_")?.strip_suffix(".jsonl")?.parse().ok()
+            })
+            .filter(|y| *y <= year)
+            .collect();
+        years.sort_unstable();
+
+        let mut records = Vec::new();
+        for y in years {
+            records.extend(self.read_all(y)?);
+        }
+        Ok(records)
+    }
+
     /// Read all records from current year
     pub fn read_current_year(&self) -> Result<Vec<TaxRecord>> {
         self.read_all(self.current_year)
     }
+
+    /// Set the finality flag of the original (non-correcting) records for
+    /// `tx_hash` in `year`'s file. Lines are edited as JSON values so fields
+    /// this version doesn't know survive. Returns the number updated.
+    pub fn set_finality(&self, tx_hash: &str, year: i16, finality: RecordFinality) -> Result<usize> {
+        let path = Self::file_path_for_year(&self.base_dir, year);
+        if !path.exists() {
+            return Ok(0);
+        }
+        let content = fs::read_to_string(&path)?;
+        let mut updated = 0;
+        let mut lines = Vec::new();
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            let mut value: serde_json::Value = serde_json::from_str(line)
+                .with_context(|| format!("Failed to parse JSON line: {}", line))?;
+            let matches = value["transaction_hash"] == tx_hash
+                && value.get("supersedes").is_none_or(|v| v.is_null());
+            if matches {
+                value["finality"] = serde_json::to_value(finality)?;
+                updated += 1;
+                lines.push(value.to_string());
+            } else {
+                lines.push(line.to_string());
+            }
+        }
+        if updated > 0 {
+            replace_file(&path, &lines)?;
+        }
+        Ok(updated)
+    }
 }
 
 /// Combined tax logger that writes to both CSV and JSON
@@ -165,6 +221,53 @@ impl TaxLogger {
         Ok(())
     }
 
+    /// Log a record, skipping the CSV row if `csv_written` (a previous attempt
+    /// wrote it before the JSON backup failed). Sets `csv_written` on success
+    /// of that half so a retry never duplicates the row.
+    pub fn log_resumable(&mut self, record: &TaxRecord, csv_written: &mut bool) -> Result<()> {
+        if !*csv_written {
+            self.csv_logger.log(record)?;
+            *csv_written = true;
+        }
+        self.json_logger.log(record)
+    }
+
+    /// Flag the records for `tx_hash` in both files
+    pub fn set_finality(&self, tx_hash: &str, year: i16, finality: RecordFinality) -> Result<usize> {
+        let rows = self.csv_logger.set_finality(tx_hash, year, finality)?;
+        self.json_logger.set_finality(tx_hash, year, finality)?;
+        Ok(rows)
+    }
+
+    /// Reverse a reorged trade: append a correcting entry per original record
+    /// (a disposal split across lots has several), then flag them Reversed.
+    /// Returns the correcting records (empty = no original found).
+    pub fn supersede(&mut self, tx_hash: &str, year: i16, reason: &str) -> Result<Vec<TaxRecord>> {
+        let records = self.json_logger.read_all(year)?;
+        let originals: Vec<&TaxRecord> = records
+            .iter()
+            .filter(|r| r.transaction_hash == tx_hash && r.supersedes.is_none())
+            .filter(|r| r.finality != RecordFinality::Reversed)
+            .collect();
+        if originals.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut corrections = Vec::with_capacity(originals.len());
+        for original in originals {
+            // Retry after the flag update failed: the correcting entry is already written
+            match records.iter().find(|r| r.supersedes.as_ref() == Some(&original.trade_id)) {
+                Some(existing) => corrections.push(existing.clone()),
+                None => {
+                    let correction = original.reversal(reason);
+                    self.log(&correction)?;
+                    corrections.push(correction);
+                }
+            }
+        }
+        self.set_finality(tx_hash, year, RecordFinality::Reversed)?;
+        Ok(corrections)
+    }
+
     /// Get record count from CSV
     pub fn record_count(&self) -> Result<usize> {
         self.csv_logger.record_count()
@@ -175,6 +278,11 @@ impl TaxLogger {
         self.json_logger.read_all(year)
     }
 
+    /// Read every year's records through `year` from JSON (lot ledger replay)
+    pub fn read_through(&self, year: i16) -> Result<Vec<TaxRecord>> {
+        self.json_logger.read_through(year)
+    }
+
     /// Get CSV file path
     pub fn csv_path(&self) -> PathBuf {
         self.csv_logger.get_current_file_path()
@@ -286,4 +394,48 @@ mod tests {
 
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn test_supersede_reverses_once() {
+        let temp_dir = env::temp_dir().join("dexarb_tax_supersede_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        let mut logger = TaxLogger::new(&temp_dir).unwrap();
+        let record = crate::tax::TaxRecord::new_arbitrage(
+            "USDC".to_string(), dec!(500), 6, "WETH".to_string(), dec!(0.25), 18,
+            dec!(1.0), dec!(2004.0), dec!(0.001), dec!(0.90), dec!(0.30),
+            "0xbad".to_string(), 12345679, "0xwallet".to_string(),
+            "Sushiswap".to_string(), "Uniswap".to_string(), "0xpool3".to_string(), "0xpool4".to_string(),
+            dec!(0.5), false,
+        )
+        .provisional();
+        logger.log(&record).unwrap();
+        let year = record.tax_year;
+
+        let correction = logger.supersede("0xbad", year, "tx vanished").unwrap().remove(0);
+        assert_eq!(correction.supersedes.as_deref(), Some(record.trade_id.as_str()));
+        assert_eq!(correction.capital_gain_loss, -record.capital_gain_loss);
+        assert_eq!(correction.finality, RecordFinality::Final);
+
+        let records = logger.read_all(year).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].finality, RecordFinality::Reversed);
+        // Net effect of the pair on the year is zero
+        assert_eq!(records[0].capital_gain_loss + records[1].capital_gain_loss, dec!(0));
+
+        // Already reversed: no second correcting entry; unknown tx: nothing
+        assert!(logger.supersede("0xbad", year, "again").unwrap().is_empty());
+        assert!(logger.supersede("0xnone", year, "n/a").unwrap().is_empty());
+        assert_eq!(logger.read_all(year).unwrap().len(), 2);
+
+        // Retry after the flag update failed: the existing entry is reused
+        logger.set_finality("0xbad", year, RecordFinality::Provisional).unwrap();
+        let retried = logger.supersede("0xbad", year, "retry").unwrap().remove(0);
+        assert_eq!(retried.trade_id, correction.trade_id);
+        let records = logger.read_all(year).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].finality, RecordFinality::Reversed);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
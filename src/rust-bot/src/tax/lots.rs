@@ -0,0 +1,535 @@
+//! Tax Lot Ledger — cost basis from specific acquisition lots
+//!
+//! Acquisitions (SWAP/BUY records) open lots per (chain, wallet, asset). A
+//! disposal (SWAP/SELL) consumes open lots in TAX_LOT_METHOD order:
+//! - FIFO / LIFO: oldest / newest acquisition first
+//! - HIFO: highest unit cost first
+//! - SpecID: the lots named in the record's `lot_id` (comma-separated), then
+//!   highest unit cost
+//!
+//! A disposal spanning several lots is split into one record per lot, each
+//! carrying that lot's cost basis, holding period and `lot_id` (trade_id gets
+//! a `-1`, `-2`, ... suffix; amounts, values and fees are prorated). Whatever
+//! the open lots don't cover keeps spot-price basis with no lot_id, as every
+//! record did before lots were tracked.
+//!
+//! A swap's received asset opens a lot whose id is the (split) record's
+//! trade_id. A correcting entry (`supersedes`) unwinds what the reversed
+//! record consumed and opened. Startup rebuilds the ledger by replaying the
+//! logged records: their lot_id already names the lot consumed, so replay
+//! never re-selects.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use super::{GainType, TaxEventType, TaxRecord};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Held longer than this many days = long-term
+const LONG_TERM_DAYS: i64 = 365;
+
+/// Lot selection method (TAX_LOT_METHOD)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LotMethod {
+    #[default]
+    Fifo,
+    Lifo,
+    Hifo,
+    SpecId,
+}
+
+impl FromStr for LotMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "fifo" => Ok(LotMethod::Fifo),
+            "lifo" => Ok(LotMethod::Lifo),
+            "hifo" => Ok(LotMethod::Hifo),
+            "specid" | "spec_id" => Ok(LotMethod::SpecId),
+            other => Err(anyhow!("unknown tax lot method '{}' (fifo|lifo|hifo|specid)", other)),
+        }
+    }
+}
+
+impl fmt::Display for LotMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LotMethod::Fifo => write!(f, "FIFO"),
+            LotMethod::Lifo => write!(f, "LIFO"),
+            LotMethod::Hifo => write!(f, "HIFO"),
+            LotMethod::SpecId => write!(f, "SpecID"),
+        }
+    }
+}
+
+/// One acquisition lot
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lot {
+    /// trade_id of the record that acquired it
+    pub lot_id: String,
+    pub acquired_at: DateTime<Utc>,
+    /// Quantity still held (human units)
+    pub remaining: Decimal,
+    pub unit_cost_usd: Decimal,
+}
+
+/// One realized gain/loss line (Form 8949 row): a disposal's share matched
+/// to one lot, or its uncovered remainder (lot_id None)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealizedGain {
+    pub trade_id: String,
+    pub tax_year: i16,
+    pub asset: String,
+    pub quantity: Decimal,
+    pub lot_id: Option<String>,
+    pub proceeds_usd: Decimal,
+    pub cost_basis_usd: Decimal,
+    pub fees_usd: Decimal,
+    pub gain_usd: Decimal,
+    pub gain_type: GainType,
+}
+
+impl RealizedGain {
+    fn from_record(record: &TaxRecord) -> Self {
+        Self {
+            trade_id: record.trade_id.clone(),
+            tax_year: record.tax_year,
+            asset: record.asset_sent.clone(),
+            quantity: record.amount_sent,
+            lot_id: record.lot_id.clone(),
+            proceeds_usd: record.proceeds_usd,
+            cost_basis_usd: record.cost_basis_usd,
+            fees_usd: record.total_fees_usd,
+            gain_usd: record.capital_gain_loss,
+            gain_type: record.gain_type,
+        }
+    }
+}
+
+/// (chain_id, wallet lowercased, asset)
+type HoldingKey = (u64, String, String);
+
+fn holding(record: &TaxRecord, asset: &str) -> HoldingKey {
+    (record.chain_id, record.wallet_address.to_lowercase(), asset.to_string())
+}
+
+/// Lot ids named in a record's lot_id ("C,A")
+fn named_lots(record: &TaxRecord) -> Vec<String> {
+    record
+        .lot_id
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Fields split pro rata when a disposal spans several lots
+fn prorated_fields(r: &mut TaxRecord) -> [&mut Decimal; 7] {
+    [
+        &mut r.amount_sent,
+        &mut r.amount_received,
+        &mut r.usd_value_sent,
+        &mut r.usd_value_received,
+        &mut r.gas_fee_native,
+        &mut r.gas_fee_usd,
+        &mut r.dex_fee_usd,
+    ]
+}
+
+/// Open lots per holding, what each record consumed and opened, and the
+/// realized gains of every disposal applied
+#[derive(Debug, Default)]
+pub struct LotLedger {
+    method: LotMethod,
+    /// Open lots per holding, in acquisition order
+    lots: HashMap<HoldingKey, Vec<Lot>>,
+    /// Per record trade_id: the lots it consumed (remaining = quantity taken)
+    consumed: HashMap<String, (HoldingKey, Vec<Lot>)>,
+    /// Per record trade_id: the holding of the lot it opened
+    opened: HashMap<String, HoldingKey>,
+    realized: Vec<RealizedGain>,
+}
+
+impl LotLedger {
+    pub fn new(method: LotMethod) -> Self {
+        Self { method, ..Self::default() }
+    }
+
+    pub fn method(&self) -> LotMethod {
+        self.method
+    }
+
+    /// Open lots of `asset` held by `wallet` on `chain_id`
+    pub fn open_lots(&self, chain_id: u64, wallet: &str, asset: &str) -> &[Lot] {
+        self.lots
+            .get(&(chain_id, wallet.to_lowercase(), asset.to_string()))
+            .map_or(&[], |lots| lots.as_slice())
+    }
+
+    /// Realized gains of every disposal applied (reversed ones removed)
+    pub fn realized(&self) -> &[RealizedGain] {
+        &self.realized
+    }
+
+    /// Apply a new record: returns the record(s) to log — a disposal split
+    /// per lot with lot-based cost basis, anything else unchanged.
+    pub fn apply(&mut self, record: &TaxRecord) -> Vec<TaxRecord> {
+        if let Some(ref reversed) = record.supersedes {
+            self.unwind(reversed);
+            return vec![record.clone()];
+        }
+        if record.is_paper_trade {
+            return vec![record.clone()];
+        }
+        match record.transaction_type {
+            TaxEventType::Swap | TaxEventType::Sell if record.amount_sent > Decimal::ZERO => {
+                let pieces = self.dispose(record);
+                if record.transaction_type == TaxEventType::Swap {
+                    for piece in &pieces {
+                        self.open(piece);
+                    }
+                }
+                pieces
+            }
+            TaxEventType::Buy => {
+                let record = record.clone().with_lot_method(&self.method.to_string());
+                self.open(&record);
+                vec![record]
+            }
+            TaxEventType::Fee => {
+                self.realized.push(RealizedGain::from_record(record));
+                vec![record.clone()]
+            }
+            _ => vec![record.clone()],
+        }
+    }
+
+    /// Rebuild from logged records (file order). Disposals consume exactly
+    /// the lot their lot_id names; realized gains are taken as logged.
+    pub fn replay<'a>(&mut self, records: impl IntoIterator<Item = &'a TaxRecord>) {
+        for record in records {
+            if let Some(ref reversed) = record.supersedes {
+                self.unwind(reversed);
+                continue;
+            }
+            if record.is_paper_trade {
+                continue;
+            }
+            match record.transaction_type {
+                TaxEventType::Swap | TaxEventType::Sell => {
+                    let key = holding(record, &record.asset_sent);
+                    let (taken, _) = self.take(&key, record.amount_sent, &named_lots(record), None);
+                    if !taken.is_empty() {
+                        self.consumed.insert(record.trade_id.clone(), (key, taken));
+                    }
+                    self.realized.push(RealizedGain::from_record(record));
+                    if record.transaction_type == TaxEventType::Swap {
+                        self.open(record);
+                    }
+                }
+                TaxEventType::Buy => self.open(record),
+                TaxEventType::Fee => self.realized.push(RealizedGain::from_record(record)),
+                TaxEventType::Transfer => {}
+            }
+        }
+    }
+
+    /// Take up to `quantity` from the holding's lots: named lots first, then
+    /// by `method` (None = named lots only). Returns the lots taken (remaining
+    /// = quantity taken) and the uncovered quantity.
+    fn take(
+        &mut self,
+        key: &HoldingKey,
+        quantity: Decimal,
+        named: &[String],
+        method: Option<LotMethod>,
+    ) -> (Vec<Lot>, Decimal) {
+        let mut taken = Vec::new();
+        let mut left = quantity;
+        let lots = match self.lots.get_mut(key) {
+            Some(lots) => lots,
+            None => return (taken, left),
+        };
+
+        let mut order: Vec<usize> = named
+            .iter()
+            .filter_map(|id| lots.iter().position(|l| &l.lot_id == id))
+            .collect();
+        if let Some(method) = method {
+            let mut rest: Vec<usize> = (0..lots.len()).filter(|i| !order.contains(i)).collect();
+            match method {
+                LotMethod::Fifo => rest.sort_by_key(|&i| lots[i].acquired_at),
+                LotMethod::Lifo => rest.sort_by_key(|&i| std::cmp::Reverse(lots[i].acquired_at)),
+                LotMethod::Hifo | LotMethod::SpecId => {
+                    rest.sort_by_key(|&i| (std::cmp::Reverse(lots[i].unit_cost_usd), lots[i].acquired_at))
+                }
+            }
+            order.extend(rest);
+        }
+
+        for i in order {
+            if left <= Decimal::ZERO {
+                break;
+            }
+            let amount = lots[i].remaining.min(left);
+            if amount <= Decimal::ZERO {
+                continue;
+            }
+            lots[i].remaining -= amount;
+            left -= amount;
+            taken.push(Lot { remaining: amount, ..lots[i].clone() });
+        }
+        lots.retain(|l| l.remaining > Decimal::ZERO);
+        (taken, left.max(Decimal::ZERO))
+    }
+
+    /// Select lots for a new disposal and split the record per lot
+    fn dispose(&mut self, record: &TaxRecord) -> Vec<TaxRecord> {
+        let key = holding(record, &record.asset_sent);
+        let total = record.amount_sent;
+        let (taken, uncovered) = self.take(&key, total, &named_lots(record), Some(self.method));
+
+        let mut segments: Vec<(Decimal, Option<Lot>)> =
+            taken.into_iter().map(|lot| (lot.remaining, Some(lot))).collect();
+        if uncovered > Decimal::ZERO {
+            segments.push((uncovered, None));
+        }
+
+        let n = segments.len();
+        let mut unallocated = record.clone();
+        let mut pieces = Vec::with_capacity(n);
+        for (k, (quantity, lot)) in segments.into_iter().enumerate() {
+            let mut piece = record.clone();
+            if n > 1 {
+                piece.trade_id = format!("{}-{}", record.trade_id, k + 1);
+            }
+            if k + 1 == n {
+                // Last piece takes what's left, so the pieces sum exactly
+                for (field, rest) in prorated_fields(&mut piece).into_iter().zip(prorated_fields(&mut unallocated)) {
+                    *field = *rest;
+                }
+            } else {
+                for (field, rest) in prorated_fields(&mut piece).into_iter().zip(prorated_fields(&mut unallocated)) {
+                    *field = *field * quantity / total;
+                    *rest -= *field;
+                }
+            }
+
+            piece.total_fees_usd = piece.gas_fee_usd + piece.dex_fee_usd;
+            piece.proceeds_usd = piece.usd_value_received;
+            piece.lot_selection_method = self.method.to_string();
+            match lot {
+                Some(ref lot) => {
+                    let days = (record.timestamp - lot.acquired_at).num_days().max(0);
+                    piece.cost_basis_usd = lot.unit_cost_usd * quantity;
+                    piece.holding_period_days = days as i32;
+                    piece.gain_type = if days > LONG_TERM_DAYS { GainType::LongTerm } else { GainType::ShortTerm };
+                    piece.lot_id = Some(lot.lot_id.clone());
+                    self.consumed.insert(piece.trade_id.clone(), (key.clone(), vec![lot.clone()]));
+                }
+                None => {
+                    // Spot-price basis, as before lots were tracked
+                    piece.cost_basis_usd = piece.usd_value_sent;
+                    piece.lot_id = None;
+                }
+            }
+            piece.capital_gain_loss = piece.proceeds_usd - piece.cost_basis_usd - piece.total_fees_usd;
+
+            self.realized.push(RealizedGain::from_record(&piece));
+            pieces.push(piece);
+        }
+        pieces
+    }
+
+    /// Open a lot for the record's received asset (id = its trade_id)
+    fn open(&mut self, record: &TaxRecord) {
+        if record.amount_received <= Decimal::ZERO {
+            return;
+        }
+        let key = holding(record, &record.asset_received);
+        let lot = Lot {
+            lot_id: record.trade_id.clone(),
+            acquired_at: record.timestamp,
+            remaining: record.amount_received,
+            unit_cost_usd: record.usd_value_received / record.amount_received,
+        };
+        self.lots.entry(key.clone()).or_default().push(lot);
+        self.opened.insert(record.trade_id.clone(), key);
+    }
+
+    /// Undo a reversed record: restore the lots it consumed, close the lot
+    /// it opened (any part a later disposal already used stays used), drop
+    /// its realized gain.
+    fn unwind(&mut self, trade_id: &str) {
+        self.realized.retain(|g| g.trade_id != trade_id);
+        if let Some((key, taken)) = self.consumed.remove(trade_id) {
+            let lots = self.lots.entry(key).or_default();
+            for lot in taken {
+                match lots.iter_mut().find(|l| l.lot_id == lot.lot_id) {
+                    Some(open) => open.remaining += lot.remaining,
+                    None => {
+                        let at = lots.partition_point(|l| l.acquired_at <= lot.acquired_at);
+                        lots.insert(at, lot);
+                    }
+                }
+            }
+        }
+        if let Some(key) = self.opened.remove(trade_id) {
+            if let Some(lots) = self.lots.get_mut(&key) {
+                lots.retain(|l| l.lot_id != trade_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    fn record(id: &str, sent: (&str, Decimal, Decimal), received: (&str, Decimal, Decimal), days_ago: i64) -> TaxRecord {
+        let mut r = TaxRecord::new_arbitrage(
+            sent.0.to_string(), sent.1, 6, received.0.to_string(), received.1, 18,
+            sent.2, received.2, dec!(0), dec!(0), dec!(0),
+            format!("0x{}", id), 1, "0xWallet".to_string(),
+            "Uniswap".to_string(), "Sushiswap".to_string(), "0xp1".to_string(), "0xp2".to_string(),
+            dec!(0), false,
+        );
+        r.trade_id = id.to_string();
+        r.timestamp = Utc::now() - Duration::days(days_ago);
+        r
+    }
+
+    /// 1 WETH each at $100 (A), $300 (B), $200 (C) on successive days
+    fn buys() -> Vec<TaxRecord> {
+        [("A", dec!(100), 3), ("B", dec!(300), 2), ("C", dec!(200), 1)]
+            .into_iter()
+            .map(|(id, price, days_ago)| record(id, ("USDC", price, dec!(1)), ("WETH", dec!(1), price), days_ago))
+            .collect()
+    }
+
+    fn ledger_with_buys(method: LotMethod) -> LotLedger {
+        let mut ledger = LotLedger::new(method);
+        for buy in buys() {
+            ledger.apply(&buy);
+        }
+        ledger
+    }
+
+    /// Sell 1.5 WETH for $375 USDC
+    fn sale() -> TaxRecord {
+        record("S", ("WETH", dec!(1.5), dec!(250)), ("USDC", dec!(375), dec!(1)), 0)
+    }
+
+    fn sell_and_check(method: LotMethod, sale: TaxRecord, lots: [&str; 2], cost: Decimal) {
+        let mut ledger = ledger_with_buys(method);
+        let pieces = ledger.apply(&sale);
+        assert_eq!(pieces.len(), 2, "{}: spans two lots", method);
+        assert_eq!(
+            pieces.iter().map(|p| p.lot_id.as_deref().unwrap()).collect::<Vec<_>>(),
+            lots,
+            "{}",
+            method
+        );
+        assert_eq!((pieces[0].trade_id.as_str(), pieces[1].trade_id.as_str()), ("S-1", "S-2"));
+        assert_eq!(pieces[0].amount_sent, dec!(1));
+        assert_eq!(pieces[1].amount_sent, dec!(0.5));
+        assert_eq!(pieces[0].amount_received + pieces[1].amount_received, dec!(375));
+        assert!(pieces.iter().all(|p| p.lot_selection_method == method.to_string()));
+
+        let total_cost: Decimal = pieces.iter().map(|p| p.cost_basis_usd).sum();
+        let total_gain: Decimal = pieces.iter().map(|p| p.capital_gain_loss).sum();
+        assert_eq!(total_cost, cost, "{}", method);
+        assert_eq!(total_gain, dec!(375) - cost, "{}", method);
+        let realized: Decimal = ledger.realized().iter().map(|g| g.gain_usd).sum();
+        assert_eq!(realized, dec!(375) - cost);
+
+        // 1.5 of 3 WETH left; the sale's USDC opened lots of its own
+        let left: Decimal = ledger.open_lots(137, "0xwallet", "WETH").iter().map(|l| l.remaining).sum();
+        assert_eq!(left, dec!(1.5));
+        assert_eq!(ledger.open_lots(137, "0xWALLET", "USDC").len(), 2);
+    }
+
+    #[test]
+    fn test_sale_spanning_two_lots_under_each_method() {
+        // FIFO: A (100) + half of B (150)
+        sell_and_check(LotMethod::Fifo, sale(), ["A", "B"], dec!(250));
+        // LIFO: C (200) + half of B (150)
+        sell_and_check(LotMethod::Lifo, sale(), ["C", "B"], dec!(350));
+        // HIFO: B (300) + half of C (100)
+        sell_and_check(LotMethod::Hifo, sale(), ["B", "C"], dec!(400));
+        // SpecID: the lots named on the record, in that order
+        let mut named = sale();
+        named.lot_id = Some("C, A".to_string());
+        sell_and_check(LotMethod::SpecId, named, ["C", "A"], dec!(250));
+    }
+
+    #[test]
+    fn test_uncovered_remainder_keeps_spot_basis_and_holding_period() {
+        let mut ledger = LotLedger::new(LotMethod::Fifo);
+        ledger.apply(&record("OLD", ("USDC", dec!(100), dec!(1)), ("WETH", dec!(1), dec!(100)), 400));
+
+        // 2 WETH sold, only 1 held: the rest has spot basis and no lot
+        let pieces = ledger.apply(&record("S", ("WETH", dec!(2), dec!(250)), ("USDC", dec!(500), dec!(1)), 0));
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].lot_id.as_deref(), Some("OLD"));
+        assert_eq!(pieces[0].gain_type, GainType::LongTerm);
+        assert!(pieces[0].holding_period_days >= 400);
+        assert_eq!(pieces[0].capital_gain_loss, dec!(150));
+        assert_eq!(pieces[1].lot_id, None);
+        assert_eq!(pieces[1].gain_type, GainType::ShortTerm);
+        assert_eq!(pieces[1].cost_basis_usd, dec!(250));
+    }
+
+    #[test]
+    fn test_reversal_unwinds_and_replay_rebuilds_the_same_state() {
+        let mut ledger = LotLedger::new(LotMethod::Hifo);
+        let mut logged = buys();
+        for buy in &logged {
+            ledger.apply(buy);
+        }
+        let pieces = ledger.apply(&sale());
+        logged.extend(pieces.clone());
+
+        // Replaying the log (lot_ids already assigned) gives the same lots and gains
+        let mut replayed = LotLedger::new(LotMethod::Fifo);
+        replayed.replay(&logged);
+        assert_eq!(replayed.open_lots(137, "0xwallet", "WETH"), ledger.open_lots(137, "0xwallet", "WETH"));
+        assert_eq!(replayed.realized(), ledger.realized());
+
+        // Reorg: both pieces reversed → lots restored, gains gone
+        for piece in &pieces {
+            let correction = piece.reversal("reorg");
+            ledger.apply(&correction);
+            logged.push(correction);
+        }
+        let weth: Decimal = ledger.open_lots(137, "0xwallet", "WETH").iter().map(|l| l.remaining).sum();
+        assert_eq!(weth, dec!(3));
+        assert_eq!(ledger.open_lots(137, "0xwallet", "WETH")[1].unit_cost_usd, dec!(300));
+        assert!(ledger.open_lots(137, "0xwallet", "USDC").is_empty());
+        // Only the buys' (zero-gain, spot-basis) USDC disposals remain
+        assert!(ledger.realized().iter().all(|g| g.asset == "USDC" && g.gain_usd.is_zero()));
+
+        let mut replayed = LotLedger::new(LotMethod::Hifo);
+        replayed.replay(&logged);
+        assert_eq!(replayed.open_lots(137, "0xwallet", "WETH"), ledger.open_lots(137, "0xwallet", "WETH"));
+        assert_eq!(replayed.realized(), ledger.realized());
+    }
+
+    #[test]
+    fn test_lot_method_parse() {
+        assert_eq!("HIFO".parse::<LotMethod>().unwrap(), LotMethod::Hifo);
+        assert_eq!(" specid ".parse::<LotMethod>().unwrap().to_string(), "SpecID");
+        assert!("avco".parse::<LotMethod>().is_err());
+    }
+}
@@ -11,22 +11,43 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - Optional pool snapshots on TaxRecord (JSON only)
+//! Modified: 2026-10-16 - pending (bounded retry queue for TAX_LOG_STRICT)
+//! Modified: 2026-10-16 - RecordFinality + supersedes (provisional until confirmation depth; reorg reversals)
+//! Modified: 2026-10-16 - Chain-aware records (with_chain); RP2 export grouped per (chain, wallet)
+//! Modified: 2026-10-16 - lots (LotLedger: FIFO/LIFO/HIFO/SpecID cost basis); TaxSummary from the ledger
+//! Modified: 2026-10-16 - cli (dexarb-tax summary / export-rp2 / verify subcommands, offline)
+//! Modified: 2026-10-16 - reconcile (tax records vs on-chain Arb*Executed / wallet transfers; --repair)
+//! Modified: 2026-10-16 - Fee records (TaxRecord::new_fee): gas burned by reverted / no-arb txs, counted in total_fees
+//! Modified: 2026-10-16 - Reversal entries keep the original record's tax_year
+//! Modified: 2026-10-16 - Transfer records (TaxRecord::new_transfer): ArbExecutor rescues
+//! Modified: 2026-10-17 - trade_size_usd on TaxRecord (JSON only)
 //!
 //! References:
 //! - IRS Form 8949: Sales and Other Dispositions of Capital Assets
 //! - Rev. Proc. 2024-28: Per-wallet cost basis tracking
 //! - RP2 tax software: https://github.com/eprbell/rp2
 
+pub mod cli;
 pub mod csv_logger;
 pub mod json_logger;
+pub mod lots;
+pub mod pending;
 pub mod price_oracle;
+pub mod reconcile;
 pub mod rp2_export;
 
 pub use csv_logger::TaxCsvLogger;
 pub use json_logger::{TaxJsonLogger, TaxLogger};
+pub use lots::{Lot, LotLedger, LotMethod, RealizedGain};
+pub use pending::{PendingTaxQueue, PendingTaxRecord, RebalanceSwap, TaxSource};
 pub use price_oracle::{PriceOracle, TaxRecordBuilder, TOKEN_DECIMALS};
-pub use rp2_export::{export_to_rp2, export_year_to_rp2, generate_rp2_config, validate_rp2_export};
+pub use rp2_export::{
+    export_grouped_to_rp2, export_to_rp2, export_year_to_rp2, generate_rp2_config, group_for_rp2,
+    rp2_sections, validate_rp2_export, Rp2Group, Rp2Section,
+};
 
+use crate::types::LegSnapshots;
 use chrono::{DateTime, Datelike, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -77,6 +98,30 @@ impl fmt::Display for GainType {
     }
 }
 
+/// Finality of a logged trade (see arbitrage/finality.rs)
+///
+/// Live trades are logged Provisional at first receipt and flagged Final once
+/// confirmation-depth deep, or Reversed (plus a correcting entry) if reorged
+/// away. Records written before finality tracking existed read as Final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordFinality {
+    Provisional,
+    #[default]
+    Final,
+    Reversed,
+}
+
+impl fmt::Display for RecordFinality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordFinality::Provisional => write!(f, "provisional"),
+            RecordFinality::Final => write!(f, "final"),
+            RecordFinality::Reversed => write!(f, "reversed"),
+        }
+    }
+}
+
 /// Comprehensive tax record for IRS compliance
 ///
 /// Contains all fields required for:
@@ -136,7 +181,7 @@ pub struct TaxRecord {
     pub gain_type: GainType,
 
     // === FEES (Deductible) ===
-    /// Gas fee in native token (MATIC)
+    /// Gas fee in native token (MATIC on Polygon, ETH on Base)
     pub gas_fee_native: Decimal,
     /// Gas fee converted to USD
     pub gas_fee_usd: Decimal,
@@ -178,10 +223,26 @@ pub struct TaxRecord {
     // === METADATA ===
     /// Arbitrage spread percentage
     pub spread_percent: Decimal,
+    /// Trade size in USD at detection time (the opportunity's trade_size_usd).
+    /// JSON backup only — not a CSV column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trade_size_usd: Option<Decimal>,
     /// Additional notes
     pub notes: Option<String>,
     /// Whether this is a paper trade (not taxable)
     pub is_paper_trade: bool,
+    /// Both legs' pool state at detection time, for post-mortem math.
+    /// JSON backup only — not a CSV column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_snapshots: Option<LegSnapshots>,
+
+    // === FINALITY ===
+    /// Provisional until the tx is confirmation-depth deep
+    #[serde(default)]
+    pub finality: RecordFinality,
+    /// trade_id of the record this correcting entry reverses
+    #[serde(default)]
+    pub supersedes: Option<String>,
 }
 
 impl TaxRecord {
@@ -275,6 +336,120 @@ impl TaxRecord {
             spread_percent,
             notes: None,
             is_paper_trade,
+            trade_size_usd: None,
+            pool_snapshots: None,
+
+            finality: RecordFinality::Final,
+            supersedes: None,
+        }
+    }
+
+    /// Gas-only record for a submitted tx that burned gas without a trade
+    /// (reverted on-chain, or confirmed with no arb executed). Nothing is
+    /// acquired: `native_symbol` gas is sent, the gas is the deductible fee,
+    /// and there are no proceeds, cost basis or capital gain/loss.
+    pub fn new_fee(
+        native_symbol: &str,
+        gas_fee_native: Decimal,
+        gas_price_usd: Decimal,
+        transaction_hash: String,
+        block_number: u64,
+        wallet_address: String,
+    ) -> Self {
+        let now = Utc::now();
+        let gas_fee_usd = gas_fee_native * gas_price_usd;
+
+        Self {
+            trade_id: generate_trade_id(),
+            timestamp: now,
+            tax_year: now.year() as i16,
+
+            transaction_type: TaxEventType::Fee,
+
+            asset_sent: native_symbol.to_string(),
+            amount_sent: gas_fee_native,
+            token_sent_decimals: 18,
+
+            asset_received: String::new(),
+            amount_received: Decimal::ZERO,
+            token_received_decimals: 18,
+
+            usd_value_sent: gas_fee_usd,
+            usd_value_received: Decimal::ZERO,
+            spot_price_sent: gas_price_usd,
+            spot_price_received: Decimal::ZERO,
+
+            cost_basis_usd: Decimal::ZERO,
+            proceeds_usd: Decimal::ZERO,
+            capital_gain_loss: Decimal::ZERO,
+            holding_period_days: 0,
+            gain_type: GainType::ShortTerm,
+
+            gas_fee_native,
+            gas_fee_usd,
+            dex_fee_percent: Decimal::ZERO,
+            dex_fee_usd: Decimal::ZERO,
+            total_fees_usd: gas_fee_usd,
+
+            blockchain: "Polygon".to_string(),
+            chain_id: 137,
+            transaction_hash,
+            block_number,
+            wallet_address,
+
+            dex_buy: String::new(),
+            dex_sell: String::new(),
+            pool_address_buy: String::new(),
+            pool_address_sell: String::new(),
+
+            lot_selection_method: "FIFO".to_string(),
+            lot_id: None,
+
+            spread_percent: Decimal::ZERO,
+            notes: None,
+            is_paper_trade: false,
+            trade_size_usd: None,
+            pool_snapshots: None,
+
+            finality: RecordFinality::Final,
+            supersedes: None,
+        }
+    }
+
+    /// Non-taxable Transfer between our own addresses (a token swept back
+    /// from the ArbExecutor): `amount` of `symbol` leaves and arrives, valued
+    /// at `spot_price_usd`; only the gas is a cost. No lots are opened or
+    /// consumed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_transfer(
+        symbol: &str,
+        amount: Decimal,
+        decimals: u8,
+        spot_price_usd: Decimal,
+        native_symbol: &str,
+        gas_fee_native: Decimal,
+        gas_price_usd: Decimal,
+        transaction_hash: String,
+        block_number: u64,
+        wallet_address: String,
+    ) -> Self {
+        let gas_fee_usd = gas_fee_native * gas_price_usd;
+        let usd_value = amount * spot_price_usd;
+        let fee = Self::new_fee(native_symbol, gas_fee_native, gas_price_usd, transaction_hash, block_number, wallet_address);
+        Self {
+            transaction_type: TaxEventType::Transfer,
+            asset_sent: symbol.to_string(),
+            amount_sent: amount,
+            token_sent_decimals: decimals,
+            asset_received: symbol.to_string(),
+            amount_received: amount,
+            token_received_decimals: decimals,
+            usd_value_sent: usd_value,
+            usd_value_received: usd_value,
+            spot_price_sent: spot_price_usd,
+            spot_price_received: spot_price_usd,
+            total_fees_usd: gas_fee_usd,
+            ..fee
         }
     }
 
@@ -284,12 +459,65 @@ impl TaxRecord {
         self
     }
 
+    /// Attach the opportunity's USD trade size (None while unsized)
+    pub fn with_trade_size_usd(mut self, trade_size_usd: f64) -> Self {
+        self.trade_size_usd = Decimal::from_f64_retain(trade_size_usd)
+            .filter(|usd| *usd > Decimal::ZERO)
+            .map(|usd| usd.round_dp(2));
+        self
+    }
+
+    /// Attach the detector's pool snapshots
+    pub fn with_pool_snapshots(mut self, snapshots: Option<LegSnapshots>) -> Self {
+        self.pool_snapshots = snapshots;
+        self
+    }
+
+    /// Set the chain the trade executed on (BotConfig chain_name / chain_id)
+    pub fn with_chain(mut self, chain_name: &str, chain_id: u64) -> Self {
+        self.blockchain = chain_display_name(chain_name);
+        self.chain_id = chain_id;
+        self
+    }
+
     /// Set the lot selection method
     pub fn with_lot_method(mut self, method: &str) -> Self {
         self.lot_selection_method = method.to_string();
         self
     }
 
+    /// Mark as provisional (live trade awaiting confirmation depth)
+    pub fn provisional(mut self) -> Self {
+        self.finality = RecordFinality::Provisional;
+        self
+    }
+
+    /// Correcting entry for a trade that was reorged away: amounts, USD
+    /// values, gain and fees negated so the pair nets to zero, `supersedes`
+    /// pointing at this record's trade_id. It stays in the original's tax
+    /// year (and file): a reorg reverses that year's trade, not this one's.
+    pub fn reversal(&self, reason: &str) -> Self {
+        let mut r = self.clone();
+        r.trade_id = generate_trade_id();
+        r.timestamp = Utc::now();
+        r.amount_sent = -self.amount_sent;
+        r.amount_received = -self.amount_received;
+        r.usd_value_sent = -self.usd_value_sent;
+        r.usd_value_received = -self.usd_value_received;
+        r.cost_basis_usd = -self.cost_basis_usd;
+        r.proceeds_usd = -self.proceeds_usd;
+        r.capital_gain_loss = -self.capital_gain_loss;
+        r.gas_fee_native = -self.gas_fee_native;
+        r.gas_fee_usd = -self.gas_fee_usd;
+        r.dex_fee_usd = -self.dex_fee_usd;
+        r.total_fees_usd = -self.total_fees_usd;
+        r.notes = Some(format!("Reversal of {}: {}", self.trade_id, reason));
+        r.pool_snapshots = None;
+        r.finality = RecordFinality::Final;
+        r.supersedes = Some(self.trade_id.clone());
+        r
+    }
+
     /// Check if this record represents a taxable event
     pub fn is_taxable(&self) -> bool {
         !self.is_paper_trade && self.transaction_type != TaxEventType::Transfer
@@ -301,6 +529,23 @@ impl TaxRecord {
     }
 }
 
+/// Display name for a BotConfig chain_name ("base" -> "Base")
+pub fn chain_display_name(chain_name: &str) -> String {
+    let mut chars = chain_name.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
+        None => String::new(),
+    }
+}
+
+/// Symbol of the token gas is paid in on a chain
+pub fn native_gas_symbol(chain_id: u64) -> &'static str {
+    match chain_id {
+        137 | 80002 => "MATIC",
+        _ => "ETH",
+    }
+}
+
 /// Generate a unique trade ID using timestamp + random suffix
 fn generate_trade_id() -> String {
     let now = Utc::now();
@@ -341,31 +586,54 @@ impl TaxSummary {
         }
     }
 
+    /// Summary of the ledger's realized gains for `tax_year` (lot-based cost
+    /// basis; reversed trades already unwound)
+    pub fn from_ledger(tax_year: i16, ledger: &LotLedger) -> Self {
+        let mut summary = Self::new(tax_year);
+        for gain in ledger.realized().iter().filter(|g| g.tax_year == tax_year) {
+            summary.add_realized(gain);
+        }
+        summary
+    }
+
     /// Add a tax record to the summary
     pub fn add_record(&mut self, record: &TaxRecord) {
         if !record.is_taxable() {
             return;
         }
+        if record.transaction_type == TaxEventType::Fee {
+            // Gas-only: deductible fee, no disposal
+            self.add_line(Decimal::ZERO, Decimal::ZERO, record.total_fees_usd, Decimal::ZERO, record.gain_type);
+            return;
+        }
+        self.add_line(record.proceeds_usd, record.cost_basis_usd, record.total_fees_usd, record.capital_gain_loss, record.gain_type);
+    }
+
+    /// Add one realized gain line from the lot ledger
+    pub fn add_realized(&mut self, gain: &RealizedGain) {
+        self.add_line(gain.proceeds_usd, gain.cost_basis_usd, gain.fees_usd, gain.gain_usd, gain.gain_type);
+    }
 
+    fn add_line(&mut self, proceeds: Decimal, cost_basis: Decimal, fees: Decimal, gain_loss: Decimal, gain_type: GainType) {
         self.total_trades += 1;
-        self.total_proceeds += record.proceeds_usd;
-        self.total_cost_basis += record.cost_basis_usd;
-        self.total_fees += record.total_fees_usd;
-        self.total_gain_loss += record.capital_gain_loss;
+        self.total_proceeds += proceeds;
+        self.total_cost_basis += cost_basis;
+        self.total_fees += fees;
+        self.total_gain_loss += gain_loss;
 
-        match record.gain_type {
+        match gain_type {
             GainType::ShortTerm => {
-                if record.capital_gain_loss > Decimal::ZERO {
-                    self.short_term_gain += record.capital_gain_loss;
+                if gain_loss > Decimal::ZERO {
+                    self.short_term_gain += gain_loss;
                 } else {
-                    self.short_term_loss += record.capital_gain_loss.abs();
+                    self.short_term_loss += gain_loss.abs();
                 }
             }
             GainType::LongTerm => {
-                if record.capital_gain_loss > Decimal::ZERO {
-                    self.long_term_gain += record.capital_gain_loss;
+                if gain_loss > Decimal::ZERO {
+                    self.long_term_gain += gain_loss;
                 } else {
-                    self.long_term_loss += record.capital_gain_loss.abs();
+                    self.long_term_loss += gain_loss.abs();
                 }
             }
         }
@@ -448,6 +716,21 @@ mod tests {
         assert_eq!(record.gain_type, GainType::ShortTerm);
         assert_eq!(record.holding_period_days, 0);
         assert!(record.is_taxable());
+        assert_eq!((record.blockchain.as_str(), record.chain_id), ("Polygon", 137));
+
+        let base = record.with_chain("base", 8453);
+        assert_eq!((base.blockchain.as_str(), base.chain_id), ("Base", 8453));
+        assert_eq!(native_gas_symbol(base.chain_id), "ETH");
+    }
+
+    #[test]
+    fn test_reversal_keeps_the_original_tax_year() {
+        let mut record = TaxRecord::new_fee("MATIC", dec!(0.01), dec!(0.90), "0xabc".to_string(), 1, "0xwallet".to_string());
+        record.tax_year = 2025;
+        let correction = record.reversal("reorg");
+        assert_eq!(correction.tax_year, 2025);
+        assert_eq!(correction.supersedes.as_deref(), Some(record.trade_id.as_str()));
+        assert_eq!(correction.gas_fee_native, -record.gas_fee_native);
     }
 
     #[test]
@@ -510,4 +793,46 @@ mod tests {
         assert_eq!(summary.total_trades, 1);
         assert!(summary.total_gain_loss > Decimal::ZERO);
     }
+
+    #[test]
+    fn test_fee_record_counts_only_as_fee() {
+        let fee = TaxRecord::new_fee("MATIC", dec!(0.015), dec!(0.60), "0xdead".to_string(), 1_000, "0xwallet".to_string());
+        assert_eq!(fee.transaction_type, TaxEventType::Fee);
+        assert_eq!((fee.gas_fee_usd, fee.total_fees_usd), (dec!(0.009), dec!(0.009)));
+        assert!(fee.is_taxable());
+
+        let mut summary = TaxSummary::new(fee.tax_year);
+        summary.add_record(&fee);
+        assert_eq!(summary.total_fees, dec!(0.009));
+        assert_eq!((summary.total_proceeds, summary.total_cost_basis), (Decimal::ZERO, Decimal::ZERO));
+        assert_eq!((summary.total_gain_loss, summary.short_term_loss), (Decimal::ZERO, Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_tax_summary_from_ledger_uses_lot_basis() {
+        let swap = |sent: &str, amount_sent, price_sent, received: &str, amount_received, price_received| {
+            TaxRecord::new_arbitrage(
+                sent.to_string(), amount_sent, 18, received.to_string(), amount_received, 18,
+                price_sent, price_received, dec!(0), dec!(0), dec!(0),
+                "0xabc".to_string(), 1, "0xwallet".to_string(),
+                "Uniswap".to_string(), "Sushiswap".to_string(), "0xpool1".to_string(), "0xpool2".to_string(),
+                dec!(0), false,
+            )
+        };
+        let mut ledger = LotLedger::new(LotMethod::Hifo);
+        // 1 WETH bought at $100, then sold at $250: per-record delta is 0,
+        // the ledger's gain is $150
+        ledger.apply(&swap("USDC", dec!(100), dec!(1), "WETH", dec!(1), dec!(100)));
+        let sale = ledger.apply(&swap("WETH", dec!(1), dec!(250), "USDC", dec!(250), dec!(1)));
+        assert_eq!(sale[0].capital_gain_loss, dec!(150));
+
+        let summary = TaxSummary::from_ledger(sale[0].tax_year, &ledger);
+        assert_eq!(summary.total_trades, 2);
+        assert_eq!(summary.total_gain_loss, dec!(150));
+        assert_eq!(summary.short_term_gain, dec!(150));
+        assert_eq!(TaxSummary::from_ledger(sale[0].tax_year - 1, &ledger).total_trades, 0);
+    }
 }
+
+#[cfg(test)]
+mod cli_tests;
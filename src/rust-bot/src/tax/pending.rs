@@ -0,0 +1,269 @@
+//! Pending Tax Records — everything needed to (re)build a tax record
+//!
+//! A landed trade's record travels to the tax writer thread (io_writer.rs) as
+//! a PendingTaxRecord, which retries it until the disk recovers. A record whose
+//! CSV line was written but whose JSON backup failed remembers that, so a
+//! retry never duplicates the CSV row. Records the writer refused (its queue
+//! was full) wait in a PendingTaxQueue and are handed over oldest first.
+//!
+//! The queue is bounded: strict mode halts submissions while the writer is
+//! behind, so it only grows by trades already in flight. If it does overflow,
+//! the evicted record is returned to the caller to be logged in full rather
+//! than dropped silently.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Records are written by the tax writer thread; the queue holds refused hand-overs
+//! Modified: 2026-10-16 - A trade's record may be split per tax lot: built records + written count
+//! Modified: 2026-10-16 - TaxSource: inventory rebalance swaps travel the same path as trades
+//! Modified: 2026-10-16 - Records carry the wallet that sent the trade (PRIVATE_KEYS)
+//! Modified: 2026-10-16 - TaxSource::Fee: gas-only record of a reverted / no-arb tx
+//! Modified: 2026-10-16 - TaxSource::Rescue: token swept back from the ArbExecutor
+
+use super::TaxRecord;
+use crate::types::{ArbitrageOpportunity, DexType};
+use ethers::types::{Address, U256};
+use std::collections::VecDeque;
+
+/// Queue bound (a halted bot should never get near it)
+pub const MAX_PENDING_TAX_RECORDS: usize = 256;
+
+/// An inventory rebalance swap (arbitrage/inventory.rs): a non-primary token
+/// sold into the primary quote token through one pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebalanceSwap {
+    pub symbol_in: String,
+    pub decimals_in: u8,
+    pub symbol_out: String,
+    pub decimals_out: u8,
+    pub dex: DexType,
+    pub pool: Address,
+    /// Pool fee (percent, 0.05 = 0.05%)
+    pub fee_percent: f64,
+}
+
+/// What a pending record describes
+#[derive(Debug, Clone)]
+pub enum TaxSource {
+    /// A landed arbitrage (quote token in, quote token out)
+    Arbitrage(Box<ArbitrageOpportunity>),
+    /// An inventory rebalance swap
+    Rebalance(RebalanceSwap),
+    /// Gas burned by a tx that traded nothing; `note` says why
+    Fee { note: String },
+    /// A token swept back from the ArbExecutor (rescueTokens): a Transfer
+    Rescue { symbol: String, decimals: u8, price_usd: Option<f64> },
+}
+
+/// One trade whose tax record is not on disk yet
+#[derive(Debug, Clone)]
+pub struct PendingTaxRecord {
+    pub source: TaxSource,
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub amount_in: U256,
+    pub amount_out: U256,
+    pub gas_native: f64,
+    /// Sending wallet (TaxRecord wallet_address)
+    pub wallet: String,
+    /// Built record(s), one per tax lot consumed; empty until building succeeds
+    pub records: Vec<TaxRecord>,
+    /// Records of `records` fully written (CSV + JSON)
+    pub written: usize,
+    /// CSV row of the next record already written — a retry writes only the JSON backup
+    pub csv_written: bool,
+    pub attempts: u32,
+    pub last_error: String,
+}
+
+impl PendingTaxRecord {
+    pub fn new(
+        opportunity: &ArbitrageOpportunity,
+        tx_hash: &str,
+        block_number: u64,
+        amount_in: U256,
+        amount_out: U256,
+        gas_native: f64,
+    ) -> Self {
+        let source = TaxSource::Arbitrage(Box::new(opportunity.clone()));
+        Self::from_source(source, tx_hash, block_number, amount_in, amount_out, gas_native)
+    }
+
+    /// A confirmed rebalance swap: `amount_in` of `swap.symbol_in` raw, `amount_out` of `swap.symbol_out` raw
+    pub fn rebalance(
+        swap: RebalanceSwap,
+        tx_hash: &str,
+        block_number: u64,
+        amount_in: U256,
+        amount_out: U256,
+        gas_native: f64,
+    ) -> Self {
+        Self::from_source(TaxSource::Rebalance(swap), tx_hash, block_number, amount_in, amount_out, gas_native)
+    }
+
+    /// Gas-only: a submitted tx that burned `gas_native` without a trade
+    pub fn fee(note: String, tx_hash: &str, block_number: u64, gas_native: f64) -> Self {
+        Self::from_source(TaxSource::Fee { note }, tx_hash, block_number, U256::zero(), U256::zero(), gas_native)
+    }
+
+    /// A confirmed rescue: `amount` raw of `symbol` back in the wallet
+    pub fn rescue(
+        symbol: &str,
+        decimals: u8,
+        price_usd: Option<f64>,
+        tx_hash: &str,
+        block_number: u64,
+        amount: U256,
+        gas_native: f64,
+    ) -> Self {
+        let source = TaxSource::Rescue { symbol: symbol.to_string(), decimals, price_usd };
+        Self::from_source(source, tx_hash, block_number, amount, amount, gas_native)
+    }
+
+    fn from_source(
+        source: TaxSource,
+        tx_hash: &str,
+        block_number: u64,
+        amount_in: U256,
+        amount_out: U256,
+        gas_native: f64,
+    ) -> Self {
+        Self {
+            source,
+            tx_hash: tx_hash.to_string(),
+            block_number,
+            amount_in,
+            amount_out,
+            gas_native,
+            wallet: String::new(),
+            records: Vec::new(),
+            written: 0,
+            csv_written: false,
+            attempts: 0,
+            last_error: String::new(),
+        }
+    }
+
+    /// Stamp the wallet that sent the trade
+    pub fn with_wallet(mut self, wallet: String) -> Self {
+        self.wallet = wallet;
+        self
+    }
+
+    /// Everything needed to reconstruct the record by hand (overflow/shutdown logs)
+    pub fn describe(&self) -> String {
+        let record = if self.records.is_empty() {
+            "<not built>".to_string()
+        } else {
+            serde_json::to_string(&self.records[self.written.min(self.records.len())..])
+                .unwrap_or_else(|e| format!("<unserializable: {}>", e))
+        };
+        let source = match self.source {
+            TaxSource::Arbitrage(ref opp) => format!("pair={}", opp.pair.symbol),
+            TaxSource::Rebalance(ref swap) => format!("rebalance={}->{}", swap.symbol_in, swap.symbol_out),
+            TaxSource::Fee { ref note } => format!("fee=\"{}\"", note),
+            TaxSource::Rescue { ref symbol, .. } => format!("rescue={}", symbol),
+        };
+        format!(
+            "tx={} block={} {} wallet={} amount_in={} amount_out={} gas_native={} csv_written={} last_error={} record={}",
+            self.tx_hash,
+            self.block_number,
+            source,
+            self.wallet,
+            self.amount_in,
+            self.amount_out,
+            self.gas_native,
+            self.csv_written,
+            self.last_error,
+            record
+        )
+    }
+}
+
+/// FIFO of unwritten tax records, bounded
+#[derive(Debug)]
+pub struct PendingTaxQueue {
+    items: VecDeque<PendingTaxRecord>,
+    capacity: usize,
+}
+
+impl Default for PendingTaxQueue {
+    fn default() -> Self {
+        Self::new(MAX_PENDING_TAX_RECORDS)
+    }
+}
+
+impl PendingTaxQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self { items: VecDeque::new(), capacity: capacity.max(1) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Enqueue; returns the oldest record if the queue was full
+    pub fn push(&mut self, item: PendingTaxRecord) -> Option<PendingTaxRecord> {
+        let evicted = if self.items.len() >= self.capacity {
+            self.items.pop_front()
+        } else {
+            None
+        };
+        self.items.push_back(item);
+        evicted
+    }
+
+    /// Put a record whose retry failed back at the head (keeps write order)
+    pub fn requeue_front(&mut self, item: PendingTaxRecord) {
+        self.items.push_front(item);
+    }
+
+    pub fn front(&self) -> Option<&PendingTaxRecord> {
+        self.items.front()
+    }
+
+    pub fn pop_front(&mut self) -> Option<PendingTaxRecord> {
+        self.items.pop_front()
+    }
+
+    /// Remove everything (shutdown dump)
+    pub fn drain(&mut self) -> impl Iterator<Item = PendingTaxRecord> + '_ {
+        self.items.drain(..)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TradingPair;
+
+    fn pending(tx: &str) -> PendingTaxRecord {
+        let opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            0.000_400,
+            0.000_396,
+            U256::from(500_000_000u64),
+        );
+        PendingTaxRecord::new(&opp, tx, 1, U256::from(500_000_000u64), U256::from(501_000_000u64), 0.01)
+    }
+
+    #[test]
+    fn test_queue_is_bounded_fifo() {
+        let mut queue = PendingTaxQueue::new(2);
+        assert!(queue.push(pending("0x1")).is_none());
+        assert!(queue.push(pending("0x2")).is_none());
+        let evicted = queue.push(pending("0x3")).unwrap();
+        assert_eq!(evicted.tx_hash, "0x1");
+        assert!(evicted.describe().contains("tx=0x1") && evicted.describe().contains("record=<not built>"));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop_front().unwrap().tx_hash, "0x2");
+        assert_eq!(queue.drain().count(), 1);
+        assert!(queue.is_empty());
+    }
+}
@@ -11,20 +11,28 @@
 //!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - TaxRecordBuilder prices gas from the live NativePrice when given
+//! Modified: 2026-10-16 - TaxRecordBuilder stamps records with the configured chain (with_chain)
+//! Modified: 2026-10-16 - TaxRecordBuilder pinned prices (a WETH quote token at QUOTE_TOKEN_PRICE_USD)
+//! Modified: 2026-10-16 - build_fee_record() (gas-only Fee records of reverted / no-arb txs)
+//! Modified: 2026-10-16 - Decimals from the TokenRegistry when attached; unknown tokens are an error, not 18
+//! Modified: 2026-10-16 - build_transfer_record() (ArbExecutor rescues)
 
 use crate::data_collector::SharedPoolState;
-use anyhow::{Context, Result};
+use crate::native_price::NativePrice;
+use crate::token_registry::TokenRegistry;
+use anyhow::{anyhow, Context, Result};
 use rust_decimal::Decimal;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
 /// Default path to pool state file
 pub const DEFAULT_POOL_STATE_PATH: &str = "/home/botuser/bots/dexarb/data/pool_state_phase1.json";
 
-/// Token decimals for common tokens on Polygon
+/// Token decimals for common tokens on Polygon (builders without a TokenRegistry)
 pub const TOKEN_DECIMALS: &[(&str, u8)] = &[
     ("USDC", 6),
     ("USDT", 6),
@@ -123,14 +131,14 @@ impl PriceOracle {
         self.get_price_usd("WMATIC")
     }
 
-    /// Get token decimals
-    pub fn get_decimals(&self, symbol: &str) -> u8 {
+    /// Get token decimals from TOKEN_DECIMALS (unknown symbol = error)
+    pub fn get_decimals(&self, symbol: &str) -> Result<u8> {
         let symbol = symbol.to_uppercase();
         TOKEN_DECIMALS
             .iter()
             .find(|(s, _)| *s == symbol)
             .map(|(_, d)| *d)
-            .unwrap_or(18) // Default to 18 decimals
+            .ok_or_else(|| anyhow!("unknown token {}: no decimals in TOKEN_DECIMALS", symbol))
     }
 
     /// Refresh cache if needed
@@ -289,6 +297,15 @@ impl PriceOracle {
 /// Builder for creating TaxRecords with price oracle integration
 pub struct TaxRecordBuilder {
     oracle: PriceOracle,
+    /// Live native price (the executor's gas accounting source); None = oracle WMATIC
+    native_price: Option<NativePrice>,
+    /// (chain_name, chain_id) from BotConfig; None = Polygon (137)
+    chain: Option<(String, u64)>,
+    /// USD prices pinned per symbol (a WETH quote token at QUOTE_TOKEN_PRICE_USD),
+    /// used instead of the oracle
+    pinned_prices: HashMap<String, Decimal>,
+    /// Token decimals as the contracts report them; None = TOKEN_DECIMALS
+    tokens: Option<Arc<TokenRegistry>>,
 }
 
 impl TaxRecordBuilder {
@@ -296,12 +313,67 @@ impl TaxRecordBuilder {
     pub fn new() -> Result<Self> {
         Ok(Self {
             oracle: PriceOracle::default_path(),
+            native_price: None,
+            chain: None,
+            pinned_prices: HashMap::new(),
+            tokens: None,
         })
     }
 
     /// Create with custom price oracle
     pub fn with_oracle(oracle: PriceOracle) -> Self {
-        Self { oracle }
+        Self { oracle, native_price: None, chain: None, pinned_prices: HashMap::new(), tokens: None }
+    }
+
+    /// Price gas (gas_price_usd) from the same live native price the executor uses
+    pub fn with_native_price(mut self, native_price: NativePrice) -> Self {
+        self.native_price = Some(native_price);
+        self
+    }
+
+    /// Stamp built records with the bot's chain (BotConfig chain_name / chain_id)
+    pub fn with_chain(mut self, chain_name: &str, chain_id: u64) -> Self {
+        self.chain = Some((chain_name.to_string(), chain_id));
+        self
+    }
+
+    /// Price `symbol` at `price_usd` instead of the oracle — the price the bot
+    /// sized and measured its trades at
+    pub fn with_pinned_price(mut self, symbol: &str, price_usd: Decimal) -> Self {
+        self.pinned_prices.insert(symbol.to_uppercase(), price_usd);
+        self
+    }
+
+    /// Token decimals from the registry (by config or on-chain symbol)
+    pub fn with_token_registry(mut self, tokens: Arc<TokenRegistry>) -> Self {
+        self.tokens = Some(tokens);
+        self
+    }
+
+    /// Decimals of `symbol`: the registry when attached, else TOKEN_DECIMALS;
+    /// an unknown token is an error either way
+    pub fn decimals(&self, symbol: &str) -> Result<u8> {
+        match &self.tokens {
+            Some(tokens) => tokens.decimals_by_symbol(symbol),
+            None => self.oracle.get_decimals(symbol),
+        }
+    }
+
+    /// USD price of one `symbol`: pinned, else the oracle
+    pub fn price_usd(&self, symbol: &str) -> Result<Decimal> {
+        match self.pinned_prices.get(&symbol.to_uppercase()) {
+            Some(price) => Ok(*price),
+            None => self.oracle.get_price_usd(symbol),
+        }
+    }
+
+    /// Native token USD price for gas fees: the live price when set, else the oracle
+    pub fn gas_price_usd(&self) -> Result<Decimal> {
+        match &self.native_price {
+            Some(price) => Decimal::try_from(price.get())
+                .with_context(|| format!("native price {} not representable", price.get())),
+            None => self.oracle.get_matic_price_usd(),
+        }
     }
 
     /// Build a tax record from trade parameters
@@ -326,16 +398,16 @@ impl TaxRecordBuilder {
         spread_percent: Decimal,
         is_paper_trade: bool,
     ) -> Result<super::TaxRecord> {
-        // Get prices from oracle
-        let spot_price_sent = self.oracle.get_price_usd(asset_sent)?;
-        let spot_price_received = self.oracle.get_price_usd(asset_received)?;
-        let matic_price = self.oracle.get_matic_price_usd()?;
+        // Get prices from oracle (pinned quote prices first)
+        let spot_price_sent = self.price_usd(asset_sent)?;
+        let spot_price_received = self.price_usd(asset_received)?;
+        let matic_price = self.gas_price_usd()?;
 
         // Get decimals
-        let token_sent_decimals = self.oracle.get_decimals(asset_sent);
-        let token_received_decimals = self.oracle.get_decimals(asset_received);
+        let token_sent_decimals = self.decimals(asset_sent)?;
+        let token_received_decimals = self.decimals(asset_received)?;
 
-        Ok(super::TaxRecord::new_arbitrage(
+        let record = super::TaxRecord::new_arbitrage(
             asset_sent.to_string(),
             amount_sent,
             token_sent_decimals,
@@ -356,7 +428,79 @@ impl TaxRecordBuilder {
             pool_address_sell,
             spread_percent,
             is_paper_trade,
-        ))
+        );
+        Ok(match &self.chain {
+            Some((name, id)) => record.with_chain(name, *id),
+            None => record,
+        })
+    }
+
+    /// Build a gas-only Fee record (reverted or no-arb tx), gas priced like
+    /// build_arbitrage_record's. Live submissions only — never a paper trade.
+    pub fn build_fee_record(
+        &self,
+        gas_fee_native: Decimal,
+        transaction_hash: String,
+        block_number: u64,
+        wallet_address: String,
+        note: &str,
+    ) -> Result<super::TaxRecord> {
+        let gas_price_usd = self.gas_price_usd()?;
+        let chain_id = self.chain.as_ref().map_or(137, |(_, id)| *id);
+        let record = super::TaxRecord::new_fee(
+            super::native_gas_symbol(chain_id),
+            gas_fee_native,
+            gas_price_usd,
+            transaction_hash,
+            block_number,
+            wallet_address,
+        )
+        .with_note(note);
+        Ok(match &self.chain {
+            Some((name, id)) => record.with_chain(name, *id),
+            None => record,
+        })
+    }
+
+    /// Build a Transfer record (own-address token movement, e.g. an
+    /// ArbExecutor rescue). `price_usd` = the caller's price for the token,
+    /// else the oracle's; an unpriced token is recorded at $0 (not taxable).
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_transfer_record(
+        &self,
+        symbol: &str,
+        amount: Decimal,
+        decimals: u8,
+        price_usd: Option<Decimal>,
+        gas_fee_native: Decimal,
+        transaction_hash: String,
+        block_number: u64,
+        wallet_address: String,
+        note: &str,
+    ) -> Result<super::TaxRecord> {
+        let spot_price = match price_usd {
+            Some(price) => price,
+            None => self.price_usd(symbol).unwrap_or(Decimal::ZERO),
+        };
+        let gas_price_usd = self.gas_price_usd()?;
+        let chain_id = self.chain.as_ref().map_or(137, |(_, id)| *id);
+        let record = super::TaxRecord::new_transfer(
+            symbol,
+            amount,
+            decimals,
+            spot_price,
+            super::native_gas_symbol(chain_id),
+            gas_fee_native,
+            gas_price_usd,
+            transaction_hash,
+            block_number,
+            wallet_address,
+        )
+        .with_note(note);
+        Ok(match &self.chain {
+            Some((name, id)) => record.with_chain(name, *id),
+            None => record,
+        })
     }
 
     /// Get the underlying price oracle
@@ -392,10 +536,10 @@ mod tests {
     fn test_token_decimals() {
         let oracle = PriceOracle::new("/nonexistent/path");
 
-        assert_eq!(oracle.get_decimals("USDC"), 6);
-        assert_eq!(oracle.get_decimals("WETH"), 18);
-        assert_eq!(oracle.get_decimals("WBTC"), 8);
-        assert_eq!(oracle.get_decimals("UNKNOWN"), 18); // Default
+        assert_eq!(oracle.get_decimals("USDC").unwrap(), 6);
+        assert_eq!(oracle.get_decimals("WETH").unwrap(), 18);
+        assert_eq!(oracle.get_decimals("WBTC").unwrap(), 8);
+        assert!(oracle.get_decimals("UNKNOWN").is_err()); // No 18-decimal default
     }
 
     #[test]
@@ -410,6 +554,25 @@ mod tests {
         assert!(eth_price > Decimal::from(1000));
     }
 
+    #[test]
+    fn test_builder_gas_price_follows_live_native_price() {
+        let native = NativePrice::new(0.62);
+        let builder = TaxRecordBuilder::with_oracle(PriceOracle::new("/nonexistent/path"))
+            .with_native_price(native.clone());
+        assert_eq!(builder.gas_price_usd().unwrap(), Decimal::from_str("0.62").unwrap());
+        native.set(0.41);
+        let record = builder
+            .build_arbitrage_record(
+                "USDC", Decimal::from(100), "USDC", Decimal::from(101), Decimal::from(2),
+                Decimal::ZERO, "0xabc".to_string(), 1, "0xwallet".to_string(),
+                "A".to_string(), "B".to_string(), "0x1".to_string(), "0x2".to_string(),
+                Decimal::ONE, false,
+            )
+            .unwrap();
+        // Read at build time: 2 MATIC of gas at the price then current
+        assert_eq!(record.gas_fee_usd, Decimal::from_str("0.82").unwrap());
+    }
+
     #[test]
     fn test_price_oracle_with_real_state() {
         // Only run if pool state exists
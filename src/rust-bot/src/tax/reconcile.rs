@@ -0,0 +1,776 @@
+//! Trade Journal Reconciliation — tax records vs on-chain history
+//!
+//! After a crash the tax log may be missing trades (killed between receipt
+//! and write) or hold records of txs that never landed. `dexarb-tax
+//! reconcile` scans a block range for what actually happened on-chain and
+//! matches it against the JSONL records by transaction hash:
+//!   - atomic trades: Arb*Executed events (ArbExecuted, ArbMultiExecuted,
+//!     ArbFlashExecuted, ArbSplitExecuted) emitted by ARB_EXECUTOR_ADDRESS.
+//!     A receipt may carry several; they are one trade, profits summed.
+//!   - legacy trades: ERC20 Transfers from our wallet of a non-quote token
+//!     (the sell leg — legacy records are logged under the sell tx hash;
+//!     inventory rebalances look the same and match their own records).
+//!
+//! Report: trades on-chain but missing from the tax log, records in the
+//! range with no on-chain trade, and atomic trades whose recorded profit
+//! (Σ amount_received − amount_sent over the tx's records) differs from the
+//! event profit by more than the tolerance (quote token units). Paper
+//! trades, reversed records, reversal entries and gas-only Fee records
+//! (reverted txs — no trade on-chain to match) are not compared.
+//!
+//! Repair (--repair) rebuilds missing atomic trades' records from the event
+//! data through TaxRecordBuilder: block timestamp, receipt gas, token
+//! symbol/decimals read from the chain, gas priced at the historical native
+//! price from the price logs when --price-log-dir is given (else the
+//! oracle). Records are matched against the lot ledger replayed from the
+//! existing logs and appended — run it while the bot is stopped. Legacy
+//! trades carry no profit on-chain and are left to be entered by hand.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Gas-only Fee records left out of the comparison
+
+use super::{LotLedger, LotMethod, PriceOracle, RecordFinality, TaxEventType, TaxLogger, TaxRecord, TaxRecordBuilder};
+use crate::backtest::{price_log_files, PairResolver, PriceLogReader};
+use crate::native_price::{NativePrice, NativePriceUpdater, PriceSource};
+use crate::pool::PoolStateManager;
+use crate::types::BotConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use ethers::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+abigen!(
+    ERC20Info,
+    r#"[
+        function symbol() external view returns (string)
+        function decimals() external view returns (uint8)
+    ]"#
+);
+
+/// Default eth_getLogs block span per request
+pub const DEFAULT_PAGE_SIZE: u64 = 2000;
+
+/// ERC20 Transfer(from, to, value)
+const TRANSFER_EVENT: &str = "Transfer(address,address,uint256)";
+
+/// Arb*Executed events of ArbExecutor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArbEventKind {
+    /// ArbExecuted(token0, token1, amountIn, amountOut, profit, routerBuy, routerSell)
+    Arb,
+    /// ArbMultiExecuted(token, amountIn, amountOut, profit, legCount)
+    Multi,
+    /// ArbFlashExecuted(token0, token1, amountIn, amountOut, premium, profit)
+    Flash,
+    /// ArbSplitExecuted(token0, token1, amountIn, amountOut, profit, sellShareBps)
+    Split,
+}
+
+impl ArbEventKind {
+    const ALL: [ArbEventKind; 4] = [ArbEventKind::Arb, ArbEventKind::Multi, ArbEventKind::Flash, ArbEventKind::Split];
+
+    pub fn signature(self) -> &'static str {
+        match self {
+            ArbEventKind::Arb => "ArbExecuted(address,address,uint256,uint256,uint256,address,address)",
+            ArbEventKind::Multi => "ArbMultiExecuted(address,uint256,uint256,uint256,uint256)",
+            ArbEventKind::Flash => "ArbFlashExecuted(address,address,uint256,uint256,uint256,uint256)",
+            ArbEventKind::Split => "ArbSplitExecuted(address,address,uint256,uint256,uint256,uint16)",
+        }
+    }
+
+    pub fn topic(self) -> H256 {
+        ethers::utils::keccak256(self.signature().as_bytes()).into()
+    }
+
+    fn name(self) -> &'static str {
+        self.signature().split('(').next().unwrap_or_default()
+    }
+}
+
+/// One decoded Arb*Executed log (quote token raw units)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArbEvent {
+    pub kind: ArbEventKind,
+    /// Quote token (token0 / token: what amountIn was paid in)
+    pub token: Address,
+    pub amount_in: U256,
+    /// Flash: amountOut − premium, as the executor books it
+    pub amount_out: U256,
+    pub profit: U256,
+    /// ArbExecuted only: (routerBuy, routerSell)
+    pub routers: Option<(Address, Address)>,
+}
+
+impl ArbEvent {
+    /// Decode an Arb*Executed log; None = another event or short data
+    pub fn decode(log: &Log) -> Option<Self> {
+        let topic0 = log.topics.first()?;
+        let kind = ArbEventKind::ALL.into_iter().find(|kind| kind.topic() == *topic0)?;
+        let token = Address::from(*log.topics.get(1)?);
+        let word = |i: usize| U256::from_big_endian(&log.data[i * 32..(i + 1) * 32]);
+        let address = |i: usize| Address::from_slice(&log.data[i * 32 + 12..(i + 1) * 32]);
+        let words = match kind {
+            ArbEventKind::Arb => 5,
+            _ => 4,
+        };
+        if log.data.len() < words * 32 {
+            return None;
+        }
+        let (amount_out, profit, routers) = match kind {
+            ArbEventKind::Arb => (word(1), word(2), Some((address(3), address(4)))),
+            ArbEventKind::Flash => (word(1).saturating_sub(word(2)), word(3), None),
+            ArbEventKind::Multi | ArbEventKind::Split => (word(1), word(2), None),
+        };
+        Some(Self { kind, token, amount_in: word(0), amount_out, profit, routers })
+    }
+}
+
+/// One on-chain trade (one tx)
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnChainTrade {
+    pub tx_hash: H256,
+    pub block_number: u64,
+    /// Arb*Executed events in log order; empty = legacy trade
+    pub events: Vec<ArbEvent>,
+    /// Legacy: non-quote tokens the wallet sent in this tx
+    pub sent_tokens: Vec<Address>,
+}
+
+impl OnChainTrade {
+    pub fn is_legacy(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Σ event profit (quote raw units)
+    pub fn profit_raw(&self) -> U256 {
+        self.events.iter().fold(U256::zero(), |sum, e| sum.saturating_add(e.profit))
+    }
+
+    fn tx(&self) -> String {
+        format!("{:?}", self.tx_hash)
+    }
+}
+
+/// Group scanned logs into trades, ordered by block then tx. Arb logs from
+/// anything but `contract` are ignored; a tx with Arb*Executed events is
+/// atomic whatever it transferred.
+pub fn group_trades(arb_logs: &[Log], transfer_logs: &[Log], contract: Option<Address>, quote_tokens: &[Address]) -> Vec<OnChainTrade> {
+    fn entry<'a>(trades: &'a mut HashMap<H256, OnChainTrade>, log: &Log) -> Option<&'a mut OnChainTrade> {
+        let (tx_hash, block_number) = (log.transaction_hash?, log.block_number?.as_u64());
+        Some(trades.entry(tx_hash).or_insert_with(|| OnChainTrade { tx_hash, block_number, events: vec![], sent_tokens: vec![] }))
+    }
+    let mut trades: HashMap<H256, OnChainTrade> = HashMap::new();
+
+    let mut arb_logs: Vec<&Log> = arb_logs.iter().filter(|log| contract.is_none_or(|c| log.address == c)).collect();
+    arb_logs.sort_by_key(|log| (log.block_number, log.transaction_index, log.log_index));
+    for log in arb_logs {
+        match ArbEvent::decode(log) {
+            Some(event) => {
+                if let Some(trade) = entry(&mut trades, log) {
+                    trade.events.push(event);
+                }
+            }
+            None => debug!("Reconcile: undecodable log in tx {:?}", log.transaction_hash),
+        }
+    }
+    for log in transfer_logs.iter().filter(|log| !quote_tokens.contains(&log.address)) {
+        if let Some(trade) = entry(&mut trades, log) {
+            if !trade.sent_tokens.contains(&log.address) {
+                trade.sent_tokens.push(log.address);
+            }
+        }
+    }
+
+    let mut trades: Vec<OnChainTrade> = trades.into_values().collect();
+    trades.sort_by_key(|t| (t.block_number, t.tx_hash));
+    trades
+}
+
+/// eth_getLogs over a block range in pages; a failing page (RPC span or
+/// result caps) is retried at half the span, down to a single block
+pub struct LogScanner<M> {
+    provider: Arc<M>,
+    page_size: u64,
+}
+
+impl<M: Middleware + 'static> LogScanner<M> {
+    pub fn new(provider: Arc<M>, page_size: u64) -> Self {
+        Self { provider, page_size: page_size.max(1) }
+    }
+
+    /// Every log matching `filter` in [from, to]
+    pub async fn scan(&self, filter: &Filter, from: u64, to: u64) -> Result<Vec<Log>> {
+        let mut logs = Vec::new();
+        let (mut start, mut span) = (from, self.page_size);
+        while start <= to {
+            let end = to.min(start.saturating_add(span - 1));
+            let page = filter.clone().from_block(start).to_block(end);
+            match self.provider.get_logs(&page).await {
+                Ok(found) => {
+                    debug!("Reconcile: blocks {}..={}: {} log(s)", start, end, found.len());
+                    logs.extend(found);
+                    start = end + 1;
+                }
+                Err(e) if span > 1 => {
+                    span = (span / 2).max(1);
+                    debug!("Reconcile: eth_getLogs {}..={} failed ({}) — retrying {} block(s) per page", start, end, e, span);
+                }
+                Err(e) => bail!("eth_getLogs at block {} failed: {}", start, e),
+            }
+        }
+        Ok(logs)
+    }
+}
+
+/// Atomic trade whose recorded profit is off
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfitMismatch {
+    pub tx_hash: String,
+    pub on_chain: Decimal,
+    pub recorded: Decimal,
+}
+
+/// Result of one reconciliation
+#[derive(Debug, Clone, Default)]
+pub struct ReconcileReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub on_chain_trades: usize,
+    /// Records compared (in range, real, not reversed)
+    pub records: usize,
+    pub matched: usize,
+    pub missing_from_tax: Vec<OnChainTrade>,
+    /// (trade_id, tx hash, block) of records with no on-chain trade
+    pub unmatched_records: Vec<(String, String, u64)>,
+    pub profit_mismatches: Vec<ProfitMismatch>,
+}
+
+impl ReconcileReport {
+    pub fn discrepancies(&self) -> usize {
+        self.missing_from_tax.len() + self.unmatched_records.len() + self.profit_mismatches.len()
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> Result<()> {
+        writeln!(out, "Reconcile blocks {}..={}: {} on-chain trade(s), {} tax record(s)", self.from_block, self.to_block, self.on_chain_trades, self.records)?;
+        writeln!(out, "  matched:           {}", self.matched)?;
+        writeln!(out, "  missing from tax:  {}", self.missing_from_tax.len())?;
+        for trade in &self.missing_from_tax {
+            if trade.is_legacy() {
+                let sent: Vec<String> = trade.sent_tokens.iter().map(|t| format!("{:?}", t)).collect();
+                writeln!(out, "    {} block {} legacy (sent {})", trade.tx(), trade.block_number, sent.join(", "))?;
+            } else {
+                let kinds: Vec<&str> = trade.events.iter().map(|e| e.kind.name()).collect();
+                writeln!(out, "    {} block {} {} profit {} raw", trade.tx(), trade.block_number, kinds.join("+"), trade.profit_raw())?;
+            }
+        }
+        writeln!(out, "  no on-chain trade: {}", self.unmatched_records.len())?;
+        for (trade_id, tx, block) in &self.unmatched_records {
+            writeln!(out, "    {} {} block {}", trade_id, tx, block)?;
+        }
+        writeln!(out, "  profit mismatches: {}", self.profit_mismatches.len())?;
+        for m in &self.profit_mismatches {
+            writeln!(out, "    {}: on-chain {}, recorded {}", m.tx_hash, m.on_chain, m.recorded)?;
+        }
+        Ok(())
+    }
+}
+
+fn raw_to_decimal(raw: U256, decimals: u8) -> Decimal {
+    Decimal::try_from_i128_with_scale(raw.low_u128() as i128, decimals as u32).unwrap_or(Decimal::ZERO)
+}
+
+/// Match `trades` against `records` (all years; only `chain_id`'s records in
+/// [from, to] are compared)
+pub fn reconcile(
+    trades: &[OnChainTrade],
+    records: &[TaxRecord],
+    chain_id: u64,
+    from: u64,
+    to: u64,
+    tolerance: Decimal,
+) -> ReconcileReport {
+    let mut by_tx: BTreeMap<String, Vec<&TaxRecord>> = BTreeMap::new();
+    let compared = records.iter().filter(|r| {
+        r.chain_id == chain_id
+            && (from..=to).contains(&r.block_number)
+            && !r.is_paper_trade
+            && r.supersedes.is_none()
+            && r.finality != RecordFinality::Reversed
+            && r.transaction_type != TaxEventType::Fee
+    });
+    let mut report = ReconcileReport { from_block: from, to_block: to, on_chain_trades: trades.len(), ..Default::default() };
+    for record in compared {
+        report.records += 1;
+        by_tx.entry(record.transaction_hash.to_lowercase()).or_default().push(record);
+    }
+
+    for trade in trades {
+        let Some(recorded) = by_tx.remove(&trade.tx()) else {
+            report.missing_from_tax.push(trade.clone());
+            continue;
+        };
+        report.matched += 1;
+        if trade.is_legacy() {
+            continue;
+        }
+        let decimals = recorded[0].token_received_decimals;
+        let on_chain = raw_to_decimal(trade.profit_raw(), decimals);
+        let recorded: Decimal = recorded.iter().map(|r| r.amount_received - r.amount_sent).sum();
+        if (on_chain - recorded).abs() > tolerance {
+            report.profit_mismatches.push(ProfitMismatch { tx_hash: trade.tx(), on_chain, recorded });
+        }
+    }
+    report.unmatched_records = by_tx
+        .into_values()
+        .flatten()
+        .map(|r| (r.trade_id.clone(), r.transaction_hash.clone(), r.block_number))
+        .collect();
+    report.unmatched_records.sort_by_key(|(_, _, block)| *block);
+    report
+}
+
+/// Native token USD price per logged block, replayed from the price logs
+/// through NativePriceUpdater (NATIVE_PRICE_PAIR); blocks where it fell back
+/// to the configured price are left out
+#[derive(Debug, Default)]
+pub struct HistoricalNativePrices(BTreeMap<u64, f64>);
+
+impl HistoricalNativePrices {
+    pub fn from_price_logs(config: &BotConfig, dir: &Path, from: u64, to: u64) -> Result<Self> {
+        let reader = PriceLogReader::new(PairResolver::new(config), price_log_files(dir)?);
+        let pools = PoolStateManager::new();
+        let mut updater = NativePriceUpdater::new(config, NativePrice::new(config.native_token_price_usd));
+        let mut prices = BTreeMap::new();
+        for snapshot in reader {
+            let snapshot = snapshot?;
+            if snapshot.block > to {
+                break;
+            }
+            for pool in snapshot.pools {
+                pools.update_v3_pool(pool);
+            }
+            if let PriceSource::Pool { price_usd, .. } = updater.refresh(&pools) {
+                // Before the range only the latest price matters
+                if snapshot.block < from {
+                    prices.clear();
+                }
+                prices.insert(snapshot.block, price_usd);
+            }
+        }
+        Ok(Self(prices))
+    }
+
+    /// Price at the last logged block at or before `block`
+    pub fn at(&self, block: u64) -> Option<f64> {
+        self.0.range(..=block).next_back().map(|(_, price)| *price)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Chain data a repaired record needs beyond the events
+#[derive(Debug, Clone)]
+pub struct RepairContext {
+    pub timestamp: DateTime<Utc>,
+    pub wallet: Address,
+    pub gas_native: Decimal,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Rebuild an atomic trade's record (events summed, quote → quote)
+pub fn repaired_record(builder: &TaxRecordBuilder, trade: &OnChainTrade, ctx: &RepairContext) -> Result<TaxRecord> {
+    let first = trade.events.first().ok_or_else(|| anyhow!("{} is a legacy trade — enter it by hand", trade.tx()))?;
+    let sum = |f: fn(&ArbEvent) -> U256| trade.events.iter().fold(U256::zero(), |s, e| s.saturating_add(f(e)));
+    let (buy, sell) = first.routers.map_or((String::new(), String::new()), |(b, s)| (format!("{:?}", b), format!("{:?}", s)));
+    let kinds: Vec<&str> = trade.events.iter().map(|e| e.kind.name()).collect();
+
+    let mut record = builder.build_arbitrage_record(
+        &ctx.symbol,
+        raw_to_decimal(sum(|e| e.amount_in), ctx.decimals),
+        &ctx.symbol,
+        raw_to_decimal(sum(|e| e.amount_out), ctx.decimals),
+        ctx.gas_native,
+        Decimal::ZERO,
+        trade.tx(),
+        trade.block_number,
+        format!("{:?}", ctx.wallet),
+        buy,
+        sell,
+        String::new(),
+        String::new(),
+        Decimal::ZERO,
+        false,
+    )?
+    .with_note(&format!("reconciled from on-chain {}", kinds.join("+")));
+    record.timestamp = ctx.timestamp;
+    record.tax_year = chrono::Datelike::year(&ctx.timestamp) as i16;
+    Ok(record)
+}
+
+/// Fetch what `repaired_record` needs for `trade`
+async fn repair_context<M: Middleware + 'static>(provider: &Arc<M>, trade: &OnChainTrade) -> Result<RepairContext> {
+    let token = trade.events.first().map(|e| e.token).ok_or_else(|| anyhow!("no events"))?;
+    let block = provider
+        .get_block(trade.block_number)
+        .await
+        .map_err(|e| anyhow!("get_block: {}", e))?
+        .ok_or_else(|| anyhow!("block {} not found", trade.block_number))?;
+    let receipt = provider
+        .get_transaction_receipt(trade.tx_hash)
+        .await
+        .map_err(|e| anyhow!("receipt: {}", e))?
+        .ok_or_else(|| anyhow!("no receipt"))?;
+    let erc20 = ERC20Info::new(token, provider.clone());
+    let symbol = erc20.symbol().call().await.with_context(|| format!("symbol() of {:?}", token))?;
+    let decimals = erc20.decimals().call().await.with_context(|| format!("decimals() of {:?}", token))?;
+    let gas_wei = receipt.gas_used.unwrap_or_default() * receipt.effective_gas_price.unwrap_or_default();
+    Ok(RepairContext {
+        timestamp: DateTime::from_timestamp(block.timestamp.as_u64() as i64, 0).unwrap_or_default(),
+        wallet: receipt.from,
+        gas_native: raw_to_decimal(gas_wei, 18),
+        symbol,
+        decimals,
+    })
+}
+
+/// Where and how reconcile looks
+pub struct ReconcileParams {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub contract: Option<Address>,
+    pub wallet: Option<Address>,
+    pub quote_tokens: Vec<Address>,
+    pub tolerance: Decimal,
+    pub page_size: u64,
+    pub repair: bool,
+    pub chain_name: String,
+    pub lot_method: LotMethod,
+    pub native_prices: HistoricalNativePrices,
+}
+
+/// Scan, reconcile against every record in `tax_dir`, optionally repair;
+/// returns the report (as of before the repair) and the number of records
+/// written
+pub async fn run<M: Middleware + 'static>(
+    provider: Arc<M>,
+    tax_dir: &Path,
+    years: &[i16],
+    params: &ReconcileParams,
+    out: &mut impl Write,
+) -> Result<(ReconcileReport, usize)> {
+    let chain_id = provider.get_chainid().await.map_err(|e| anyhow!("eth_chainId: {}", e))?.as_u64();
+    let scanner = LogScanner::new(provider.clone(), params.page_size);
+    let (from, to) = (params.from_block, params.to_block);
+
+    let arb_logs = match params.contract {
+        Some(contract) => {
+            let topics: Vec<H256> = ArbEventKind::ALL.iter().map(|k| k.topic()).collect();
+            scanner.scan(&Filter::new().address(contract).topic0(topics), from, to).await?
+        }
+        None => Vec::new(),
+    };
+    let transfer_logs = match params.wallet {
+        Some(wallet) => scanner.scan(&Filter::new().event(TRANSFER_EVENT).topic1(H256::from(wallet)), from, to).await?,
+        None => {
+            writeln!(out, "Note: no --wallet — legacy trades not scanned, their records show as unmatched")?;
+            Vec::new()
+        }
+    };
+    let trades = group_trades(&arb_logs, &transfer_logs, params.contract, &params.quote_tokens);
+
+    let logger = TaxLogger::new(tax_dir)?;
+    let mut records = Vec::new();
+    for year in years {
+        records.extend(logger.read_all(*year)?);
+    }
+    let report = reconcile(&trades, &records, chain_id, from, to, params.tolerance);
+    report.write(out)?;
+    if !params.repair {
+        return Ok((report, 0));
+    }
+    Ok((report.clone(), repair(provider, logger, &records, &report, chain_id, params, out).await?))
+}
+
+/// Write records for the report's missing atomic trades
+async fn repair<M: Middleware + 'static>(
+    provider: Arc<M>,
+    mut logger: TaxLogger,
+    records: &[TaxRecord],
+    report: &ReconcileReport,
+    chain_id: u64,
+    params: &ReconcileParams,
+    out: &mut impl Write,
+) -> Result<usize> {
+    // Historical price through a shared handle, set per trade; the oracle otherwise
+    let native_price = NativePrice::new(0.0);
+    let historical = TaxRecordBuilder::with_oracle(PriceOracle::default_path())
+        .with_native_price(native_price.clone())
+        .with_chain(&params.chain_name, chain_id);
+    let oracle = TaxRecordBuilder::with_oracle(PriceOracle::default_path()).with_chain(&params.chain_name, chain_id);
+    let mut ledger = LotLedger::new(params.lot_method);
+    ledger.replay(records);
+
+    let mut written = 0;
+    for trade in &report.missing_from_tax {
+        if trade.is_legacy() {
+            writeln!(out, "  skip {}: legacy trade — enter it by hand", trade.tx())?;
+            continue;
+        }
+        let builder = match params.native_prices.at(trade.block_number) {
+            Some(price) => {
+                native_price.set(price);
+                &historical
+            }
+            None => &oracle,
+        };
+        let record = match repair_context(&provider, trade).await.and_then(|ctx| repaired_record(builder, trade, &ctx)) {
+            Ok(record) => record,
+            Err(e) => {
+                warn!("Reconcile: cannot rebuild {}: {:#}", trade.tx(), e);
+                writeln!(out, "  FAILED {}: {:#}", trade.tx(), e)?;
+                continue;
+            }
+        };
+        for piece in ledger.apply(&record) {
+            logger.log(&piece)?;
+        }
+        written += 1;
+        writeln!(out, "  repaired {} ({} {} → {}, tax year {})", trade.tx(), record.amount_sent, record.asset_sent, record.amount_received, record.tax_year)?;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tax::TaxJsonLogger;
+    use ethers::providers::{MockProvider, MockResponse};
+    use rust_decimal_macros::dec;
+
+    fn contract() -> Address {
+        Address::from_low_u64_be(0xa4b)
+    }
+    fn usdc() -> Address {
+        Address::from_low_u64_be(0x05dc)
+    }
+    fn weth() -> Address {
+        Address::from_low_u64_be(0x0e7)
+    }
+    fn tx(n: u64) -> H256 {
+        H256::from_low_u64_be(0xabc000 + n)
+    }
+
+    fn words(values: &[u64]) -> Bytes {
+        values.iter().flat_map(|v| H256::from_low_u64_be(*v).0).collect::<Vec<u8>>().into()
+    }
+
+    fn log(address: Address, topics: Vec<H256>, data: Bytes, tx_hash: H256, block: u64, index: u64) -> Log {
+        Log {
+            address,
+            topics,
+            data,
+            transaction_hash: Some(tx_hash),
+            block_number: Some(block.into()),
+            log_index: Some(index.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Arb event with `profit` (USDC raw); in = 100 USDC, out = in + profit
+    fn arb_log(kind: ArbEventKind, tx_hash: H256, block: u64, index: u64, profit: u64) -> Log {
+        let (amount_in, amount_out) = (100_000_000, 100_000_000 + profit);
+        let data = match kind {
+            ArbEventKind::Arb => words(&[amount_in, amount_out, profit, 0x1111, 0x2222]),
+            ArbEventKind::Flash => words(&[amount_in, amount_out + 50_000, 50_000, profit]),
+            ArbEventKind::Multi => words(&[amount_in, amount_out, profit, 3]),
+            ArbEventKind::Split => words(&[amount_in, amount_out, profit, 6000]),
+        };
+        log(contract(), vec![kind.topic(), H256::from(usdc()), H256::from(weth())], data, tx_hash, block, index)
+    }
+
+    fn transfer_from_wallet(token: Address, tx_hash: H256, block: u64) -> Log {
+        let topic = ethers::utils::keccak256(TRANSFER_EVENT.as_bytes()).into();
+        let wallet = H256::from(Address::from_low_u64_be(0xbee));
+        log(token, vec![topic, wallet, H256::from_low_u64_be(0xdead)], words(&[1]), tx_hash, block, 9)
+    }
+
+    fn record(tx_hash: &str, block: u64, sent: Decimal, received: Decimal) -> TaxRecord {
+        TaxRecord::new_arbitrage(
+            "USDC".to_string(), sent, 6, "USDC".to_string(), received, 6,
+            dec!(1), dec!(1), dec!(0), dec!(0), dec!(0),
+            tx_hash.to_string(), block, "0xbee".to_string(),
+            "UniswapV3".to_string(), "QuickswapV3".to_string(), String::new(), String::new(),
+            dec!(0), false,
+        )
+        .with_chain("polygon", 137)
+    }
+
+    /// Trade 1: two ArbExecuted in one receipt (+ the USDC pull from the wallet).
+    /// Trade 2: flash. Trade 3: legacy WETH sell. Foreign emitter ignored.
+    fn sample_trades() -> Vec<OnChainTrade> {
+        let arb_logs = vec![
+            arb_log(ArbEventKind::Arb, tx(1), 100, 4, 200_000),
+            arb_log(ArbEventKind::Flash, tx(2), 101, 0, 300_000),
+            arb_log(ArbEventKind::Arb, tx(1), 100, 1, 500_000),
+            Log { address: Address::from_low_u64_be(0x999), ..arb_log(ArbEventKind::Arb, tx(7), 100, 0, 1) },
+        ];
+        let transfers = vec![transfer_from_wallet(usdc(), tx(1), 100), transfer_from_wallet(weth(), tx(3), 102)];
+        group_trades(&arb_logs, &transfers, Some(contract()), &[usdc()])
+    }
+
+    #[test]
+    fn test_decode_and_group_receipts_with_several_events() {
+        let trades = sample_trades();
+        assert_eq!(trades.len(), 3);
+
+        let two = &trades[0];
+        assert_eq!((two.tx_hash, two.block_number), (tx(1), 100));
+        assert!(!two.is_legacy() && two.sent_tokens.is_empty());
+        assert_eq!(two.events.len(), 2);
+        // Log order within the receipt, profits summed
+        assert_eq!(two.events[0].profit, U256::from(500_000));
+        assert_eq!(two.events[0].routers, Some((Address::from_low_u64_be(0x1111), Address::from_low_u64_be(0x2222))));
+        assert_eq!(two.profit_raw(), U256::from(700_000));
+
+        let flash = &trades[1];
+        assert_eq!(flash.events[0].kind, ArbEventKind::Flash);
+        assert_eq!(flash.events[0].amount_out, U256::from(100_300_000u64));
+        assert_eq!(flash.profit_raw(), U256::from(300_000));
+
+        assert!(trades[2].is_legacy());
+        assert_eq!(trades[2].sent_tokens, vec![weth()]);
+
+        for kind in [ArbEventKind::Multi, ArbEventKind::Split] {
+            let event = ArbEvent::decode(&arb_log(kind, tx(5), 1, 0, 42)).unwrap();
+            assert_eq!((event.kind, event.token, event.profit), (kind, usdc(), U256::from(42)));
+        }
+        let short = Log { data: words(&[1, 2]), ..arb_log(ArbEventKind::Arb, tx(6), 1, 0, 1) };
+        assert_eq!(ArbEvent::decode(&short), None);
+    }
+
+    #[test]
+    fn test_reconcile_reports_missing_unmatched_and_mismatched() {
+        let trades = sample_trades();
+        let hex = |n: u64| format!("{:?}", tx(n));
+
+        let mut reversed = record("0xfeed", 100, dec!(100), dec!(101));
+        reversed.finality = RecordFinality::Reversed;
+        let mut paper = record("0xpaper", 100, dec!(100), dec!(101));
+        paper.is_paper_trade = true;
+        let records = vec![
+            // Trade 1 booked as two lot pieces: 0.3 + 0.4 = 0.7 USDC — matches
+            record(&hex(1).to_uppercase().replace("0X", "0x"), 100, dec!(40), dec!(40.3)),
+            record(&hex(1), 100, dec!(60), dec!(60.4)),
+            // Trade 2: on-chain 0.3, recorded 0.1
+            record(&hex(2), 101, dec!(100), dec!(100.1)),
+            // No such tx
+            record("0xghost", 103, dec!(100), dec!(100.5)),
+            reversed,
+            paper,
+            // Outside the range / another chain: not compared
+            record("0xold", 50, dec!(1), dec!(2)),
+            record("0xbase", 100, dec!(1), dec!(2)).with_chain("base", 8453),
+        ];
+
+        let report = reconcile(&trades, &records, 137, 90, 110, dec!(0.01));
+        assert_eq!((report.on_chain_trades, report.records, report.matched), (3, 4, 2));
+        assert_eq!(report.missing_from_tax.len(), 1);
+        assert_eq!(report.missing_from_tax[0].tx_hash, tx(3));
+        assert_eq!(report.unmatched_records.len(), 1);
+        assert_eq!(report.unmatched_records[0].1, "0xghost");
+        assert_eq!(
+            report.profit_mismatches,
+            vec![ProfitMismatch { tx_hash: hex(2), on_chain: dec!(0.3), recorded: dec!(0.1) }]
+        );
+        assert_eq!(report.discrepancies(), 3);
+
+        // A loose tolerance absorbs the mismatch
+        assert!(reconcile(&trades, &records, 137, 90, 110, dec!(0.5)).profit_mismatches.is_empty());
+
+        let mut text = Vec::new();
+        report.write(&mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.contains("legacy"), "{}", text);
+        assert!(text.contains("0xghost"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_scanner_paginates_and_halves_failing_pages() {
+        let (provider, mock) = Provider::mocked();
+        let page = |n: u64| vec![arb_log(ArbEventKind::Arb, tx(n), n, 0, n)];
+        // Responses pop last-pushed first: push in reverse call order
+        mock.push::<Vec<Log>, _>(page(4000)).unwrap();
+        mock.push::<Vec<Log>, _>(page(3000)).unwrap();
+        mock.push::<Vec<Log>, _>(page(2000)).unwrap();
+        mock.push_response(MockResponse::Error(ethers::providers::JsonRpcError {
+            code: -32005,
+            message: "query returned more than 10000 results".into(),
+            data: None,
+        }));
+        mock.push::<Vec<Log>, _>(page(0)).unwrap();
+
+        let filter = Filter::new().address(contract());
+        let logs = LogScanner::new(Arc::new(provider), 2000).scan(&filter, 0, 4999).await.unwrap();
+        let blocks: Vec<u64> = logs.iter().map(|l| l.block_number.unwrap().as_u64()).collect();
+        assert_eq!(blocks, vec![0, 2000, 3000, 4000]);
+
+        let expect = |from: u64, to: u64| mock_requested(&mock, &filter, from, to);
+        expect(0, 1999);
+        expect(2000, 3999);
+        expect(2000, 2999);
+        expect(3000, 3999);
+        expect(4000, 4999);
+    }
+
+    fn mock_requested(mock: &MockProvider, filter: &Filter, from: u64, to: u64) {
+        mock.assert_request("eth_getLogs", [filter.clone().from_block(from).to_block(to)]).unwrap();
+    }
+
+    #[test]
+    fn test_repair_record_written_through_ledger() {
+        let dir = std::env::temp_dir().join(format!("dexarb_tax_reconcile_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let trade = &sample_trades()[0];
+        let builder = TaxRecordBuilder::with_oracle(PriceOracle::new("/nonexistent"))
+            .with_native_price(NativePrice::new(0.5))
+            .with_chain("polygon", 137);
+        let ctx = RepairContext {
+            timestamp: DateTime::from_timestamp(1_780_000_000, 0).unwrap(),
+            wallet: Address::from_low_u64_be(0xbee),
+            gas_native: dec!(0.02),
+            symbol: "USDC".to_string(),
+            decimals: 6,
+        };
+        let record = repaired_record(&builder, trade, &ctx).unwrap();
+        assert_eq!((record.amount_sent, record.amount_received), (dec!(200), dec!(200.7)));
+        assert_eq!(record.transaction_hash, format!("{:?}", tx(1)));
+        assert_eq!((record.chain_id, record.tax_year, record.timestamp), (137, 2026, ctx.timestamp));
+        assert_eq!(record.gas_fee_usd, dec!(0.01));
+        assert!(record.notes.as_deref().unwrap().contains("ArbExecuted+ArbExecuted"));
+        assert!(repaired_record(&builder, &sample_trades()[2], &ctx).is_err());
+
+        let mut logger = TaxLogger::new(&dir).unwrap();
+        let mut ledger = LotLedger::default();
+        for piece in ledger.apply(&record) {
+            logger.log(&piece).unwrap();
+        }
+        // Once written, the trade reconciles clean
+        let records = TaxJsonLogger::new(&dir).unwrap().read_all(2026).unwrap();
+        let report = reconcile(&sample_trades()[..1], &records, 137, 0, 1000, dec!(0.01));
+        assert_eq!((report.matched, report.discrepancies()), (1, 0));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -8,7 +8,7 @@
 //! RP2 CSV Format:
 //! - timestamp: ISO 8601 format
 //! - asset: Token symbol (e.g., "WMATIC")
-//! - exchange: DEX name (e.g., "Uniswap")
+//! - exchange: Chain name (e.g., "Polygon"; the DEX is in the notes)
 //! - holder: Wallet identifier
 //! - transaction_type: "BUY", "SELL", or "FEE"
 //! - spot_price: USD price per unit
@@ -21,14 +21,21 @@
 //! - fiat_fee: Fee in USD
 //! - notes: Additional context
 //!
+//! Multi-chain: records are grouped by (chain, wallet). Each group becomes its
+//! own input file (holder = wallet address, exchange = chain name), and the
+//! generated config carries one exchange section per chain listing its
+//! holders and input files.
+//!
 //! Author: AI-Generated
 //! Created: 2026-01-28
+//! Modified: 2026-10-16 - Group by (chain, wallet): per-wallet input files, per-chain config sections, chain check in validation
 
-use super::{TaxEventType, TaxRecord};
+use super::{native_gas_symbol, TaxEventType, TaxRecord};
 use anyhow::{Context, Result};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// RP2 CSV headers
 const RP2_HEADERS: &[&str] = &[
@@ -113,7 +120,7 @@ fn write_sell_entry(file: &mut File, record: &TaxRecord, holder: &str) -> Result
     let fields = vec![
         record.timestamp.to_rfc3339(),
         record.asset_sent.clone(),
-        record.dex_buy.clone(), // Exchange where we sold (sent) the token
+        record.blockchain.clone(), // Exchange = chain (DEX is in the notes)
         holder.to_string(),
         "SELL".to_string(),
         record.spot_price_sent.to_string(),
@@ -141,7 +148,7 @@ fn write_buy_entry(file: &mut File, record: &TaxRecord, holder: &str) -> Result<
     let fields = vec![
         record.timestamp.to_rfc3339(),
         record.asset_received.clone(),
-        record.dex_sell.clone(), // Exchange where we bought (received) the token
+        record.blockchain.clone(), // Exchange = chain (DEX is in the notes)
         holder.to_string(),
         "BUY".to_string(),
         record.spot_price_received.to_string(),
@@ -164,9 +171,10 @@ fn write_buy_entry(file: &mut File, record: &TaxRecord, holder: &str) -> Result<
 
 /// Write a FEE entry (gas-only transaction)
 fn write_fee_entry(file: &mut File, record: &TaxRecord, holder: &str) -> Result<()> {
+    let native = native_gas_symbol(record.chain_id);
     let fields = vec![
         record.timestamp.to_rfc3339(),
-        "MATIC".to_string(), // Gas is paid in MATIC on Polygon
+        native.to_string(), // Gas is paid in the chain's native token
         record.blockchain.clone(),
         holder.to_string(),
         "FEE".to_string(),
@@ -179,8 +187,8 @@ fn write_fee_entry(file: &mut File, record: &TaxRecord, holder: &str) -> Result<
         "".to_string(),                   // fiat_out_no_fee
         record.gas_fee_usd.to_string(),   // fiat_fee
         escape_csv(&format!(
-            "Gas fee: {} MATIC (${}) - tx: {}",
-            record.gas_fee_native, record.gas_fee_usd, record.transaction_hash
+            "Gas fee: {} {} (${}) - tx: {}",
+            record.gas_fee_native, native, record.gas_fee_usd, record.transaction_hash
         )),
     ];
 
@@ -213,14 +221,107 @@ pub fn export_year_to_rp2<P: AsRef<Path>>(
     export_to_rp2(&filtered, output_path, holder_name)
 }
 
+/// Records of one (chain, wallet) pair: one RP2 input file
+#[derive(Debug, Clone)]
+pub struct Rp2Group {
+    pub chain_name: String,
+    pub chain_id: u64,
+    /// Lowercased wallet address (the file's holder)
+    pub wallet_address: String,
+    pub records: Vec<TaxRecord>,
+}
+
+impl Rp2Group {
+    /// Input file name: `<prefix>_<chain>_<wallet>.csv`
+    pub fn file_name(&self, prefix: &str) -> String {
+        format!("{}_{}_{}.csv", prefix, self.chain_name.to_lowercase(), self.wallet_address)
+    }
+}
+
+/// Split records by (chain_id, wallet), ordered by chain ID then wallet.
+/// Paper trades are dropped.
+pub fn group_for_rp2(records: &[TaxRecord]) -> Vec<Rp2Group> {
+    let mut groups: BTreeMap<(u64, String), Rp2Group> = BTreeMap::new();
+    for record in records.iter().filter(|r| !r.is_paper_trade) {
+        let wallet = record.wallet_address.to_lowercase();
+        groups
+            .entry((record.chain_id, wallet.clone()))
+            .or_insert_with(|| Rp2Group {
+                chain_name: record.blockchain.clone(),
+                chain_id: record.chain_id,
+                wallet_address: wallet,
+                records: Vec::new(),
+            })
+            .records
+            .push(record.clone());
+    }
+    groups.into_values().collect()
+}
+
+/// One exchange section of the RP2 config: a chain, its wallets and their files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rp2Section {
+    pub chain_name: String,
+    pub chain_id: u64,
+    pub holders: Vec<String>,
+    pub input_files: Vec<String>,
+}
+
+/// Config sections for `groups` (one per chain, in group order)
+pub fn rp2_sections(groups: &[Rp2Group], prefix: &str) -> Vec<Rp2Section> {
+    let mut sections: Vec<Rp2Section> = Vec::new();
+    for group in groups {
+        let idx = match sections.iter().position(|s| s.chain_id == group.chain_id) {
+            Some(idx) => idx,
+            None => {
+                sections.push(Rp2Section {
+                    chain_name: group.chain_name.clone(),
+                    chain_id: group.chain_id,
+                    holders: Vec::new(),
+                    input_files: Vec::new(),
+                });
+                sections.len() - 1
+            }
+        };
+        sections[idx].holders.push(group.wallet_address.clone());
+        sections[idx].input_files.push(group.file_name(prefix));
+    }
+    sections
+}
+
+/// Write one RP2 input file per (chain, wallet) group into `output_dir`
+///
+/// Returns the path and row count of each file written.
+pub fn export_grouped_to_rp2<P: AsRef<Path>>(
+    groups: &[Rp2Group],
+    output_dir: P,
+    prefix: &str,
+) -> Result<Vec<(PathBuf, usize)>> {
+    let output_dir = output_dir.as_ref();
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create RP2 export dir: {:?}", output_dir))?;
+
+    groups
+        .iter()
+        .map(|group| {
+            let path = output_dir.join(group.file_name(prefix));
+            let rows = export_to_rp2(&group.records, &path, &group.wallet_address)?;
+            Ok((path, rows))
+        })
+        .collect()
+}
+
 /// Generate RP2 config.ini file
 ///
-/// RP2 requires a config file specifying native currency and accounting method
+/// RP2 requires a config file specifying native currency and accounting method.
+/// Each chain gets an `[exchange.<chain>]` section with its holders and input files.
 pub fn generate_rp2_config<P: AsRef<Path>>(
     output_path: P,
     accounting_method: &str, // "fifo", "lifo", "hifo"
+    sections: &[Rp2Section],
 ) -> Result<()> {
-    let config = format!(
+    let exchanges: Vec<&str> = sections.iter().map(|s| s.chain_name.as_str()).collect();
+    let mut config = format!(
         r#"[rp2]
 # RP2 Configuration for DEX Arbitrage Bot
 # Generated: {}
@@ -240,11 +341,26 @@ in_header = True
 
 # Allow same-day sales (required for arbitrage)
 allow_same_day_trades = True
+
+# One exchange per chain
+exchanges = {}
 "#,
         chrono::Utc::now().to_rfc3339(),
-        accounting_method
+        accounting_method,
+        exchanges.join(", ")
     );
 
+    for section in sections {
+        config.push_str(&format!(
+            "\n[exchange.{}]\nchain_name = {}\nchain_id = {}\nholders = {}\ninput_files = {}\n",
+            section.chain_name.to_lowercase(),
+            section.chain_name,
+            section.chain_id,
+            section.holders.join(", "),
+            section.input_files.join(", "),
+        ));
+    }
+
     let mut file = File::create(output_path.as_ref())?;
     write!(file, "{}", config)?;
 
@@ -252,7 +368,10 @@ allow_same_day_trades = True
 }
 
 /// Validate RP2 export file format
-pub fn validate_rp2_export<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
+///
+/// With `sections` given, rows whose exchange (chain) has no configured
+/// section are flagged; an empty slice skips that check.
+pub fn validate_rp2_export<P: AsRef<Path>>(path: P, sections: &[Rp2Section]) -> Result<ValidationResult> {
     let content = std::fs::read_to_string(path.as_ref())?;
     let lines: Vec<&str> = content.lines().collect();
 
@@ -310,6 +429,18 @@ pub fn validate_rp2_export<P: AsRef<Path>>(path: P) -> Result<ValidationResult>
                 ));
             }
         }
+
+        // Check the chain has a configured exchange section
+        if !sections.is_empty() && fields.len() > 2 {
+            let chain = fields[2];
+            if !sections.iter().any(|s| s.chain_name == chain) {
+                errors.push(format!(
+                    "Row {} chain '{}' matches no configured exchange section",
+                    i + 1,
+                    chain
+                ));
+            }
+        }
     }
 
     Ok(ValidationResult {
@@ -378,7 +509,7 @@ mod tests {
         assert_eq!(row_count, 2); // 1 real trade = 2 rows (BUY + SELL)
 
         // Validate the export
-        let validation = validate_rp2_export(&output_path).unwrap();
+        let validation = validate_rp2_export(&output_path, &[]).unwrap();
         assert!(validation.valid, "Errors: {:?}", validation.errors);
         assert_eq!(validation.row_count, 2);
 
@@ -392,7 +523,7 @@ mod tests {
         fs::create_dir_all(&temp_dir).unwrap();
 
         let config_path = temp_dir.join("config.ini");
-        let result = generate_rp2_config(&config_path, "fifo");
+        let result = generate_rp2_config(&config_path, "fifo", &[]);
         assert!(result.is_ok());
 
         let content = fs::read_to_string(&config_path).unwrap();
@@ -402,6 +533,72 @@ mod tests {
         let _ = fs::remove_dir_all(&temp_dir);
     }
 
+    #[test]
+    fn test_multi_chain_multi_wallet_grouping_and_config() {
+        let temp_dir = env::temp_dir().join("dexarb_rp2_multichain_test");
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        let with = |chain: &str, id: u64, wallet: &str| {
+            let mut record = create_test_record(false).with_chain(chain, id);
+            record.wallet_address = wallet.to_string();
+            record
+        };
+        let records = vec![
+            with("base", 8453, "0xAAA"),
+            with("polygon", 137, "0xaaa"),
+            with("polygon", 137, "0xbbb"),
+            with("base", 8453, "0xaaa"),
+            with("polygon", 137, "0xBBB"),
+            create_test_record(true),
+        ];
+
+        // Grouped by (chain_id, lowercased wallet); paper trade dropped
+        let groups = group_for_rp2(&records);
+        let keys: Vec<_> = groups
+            .iter()
+            .map(|g| (g.chain_name.as_str(), g.chain_id, g.wallet_address.as_str(), g.records.len()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![("Polygon", 137, "0xaaa", 1), ("Polygon", 137, "0xbbb", 2), ("Base", 8453, "0xaaa", 2)]
+        );
+
+        let sections = rp2_sections(&groups, "rp2_2026");
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].holders, vec!["0xaaa", "0xbbb"]);
+        assert_eq!(sections[1].input_files, vec!["rp2_2026_base_0xaaa.csv"]);
+
+        let files = export_grouped_to_rp2(&groups, &temp_dir, "rp2_2026").unwrap();
+        let rows: Vec<usize> = files.iter().map(|(_, rows)| *rows).collect();
+        assert_eq!(rows, vec![2, 4, 4]);
+        let base_file = &files[2].0;
+        let content = fs::read_to_string(base_file).unwrap();
+        assert!(content.lines().skip(1).all(|l| l.contains(",Base,0xaaa,")));
+
+        let config_path = temp_dir.join("config.ini");
+        generate_rp2_config(&config_path, "hifo", &sections).unwrap();
+        let config = fs::read_to_string(&config_path).unwrap();
+        assert!(config.contains("exchanges = Polygon, Base"));
+        assert!(config.contains(
+            "[exchange.polygon]\nchain_name = Polygon\nchain_id = 137\nholders = 0xaaa, 0xbbb\n\
+             input_files = rp2_2026_polygon_0xaaa.csv, rp2_2026_polygon_0xbbb.csv\n"
+        ));
+        assert!(config.contains("[exchange.base]\nchain_name = Base\nchain_id = 8453\nholders = 0xaaa\n"));
+
+        // Every file validates against the full config; Base rows are
+        // flagged when only the Polygon section is configured
+        for (path, _) in &files {
+            let validation = validate_rp2_export(path, &sections).unwrap();
+            assert!(validation.valid, "{:?}: {:?}", path, validation.errors);
+        }
+        let validation = validate_rp2_export(base_file, &sections[..1]).unwrap();
+        assert!(!validation.valid);
+        assert_eq!(validation.errors.len(), 4);
+        assert!(validation.errors[0].contains("chain 'Base'"));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
     #[test]
     fn test_csv_escape() {
         assert_eq!(escape_csv("simple"), "simple");
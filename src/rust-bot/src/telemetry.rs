@@ -0,0 +1,711 @@
+//! Telemetry Push — opt-in aggregate view across our own bot instances
+//!
+//! Each instance periodically sends a compact JSON snapshot of the numbers the
+//! periodic status line already reports (block height, scans, opportunities,
+//! cooled routes, retry counters) plus trade counts and P&L to a collector we
+//! run ourselves. Off unless TELEMETRY_ENABLED=true and TELEMETRY_ENDPOINT is set.
+//!
+//! - Sinks: `http(s)://…` (POST, reference collector: `telemetry-collector`
+//!   binary) or `file:///path.jsonl` (append one line per snapshot — no
+//!   infrastructure at all; several instances may share one file)
+//! - Schema: `TelemetrySnapshot`, versioned by TELEMETRY_SCHEMA_VERSION. The
+//!   collector rejects any other version and any unknown field. Adding,
+//!   renaming or removing a field means bumping the version.
+//! - Anonymized: no keys, RPC URLs or addresses. The wallet / ArbExecutor
+//!   addresses are added only with TELEMETRY_INCLUDE_ADDRESSES=true.
+//! - Instance ID: hash of chain, chain id, mode and configured pairs — stable
+//!   across restarts, distinct per deployment, reveals nothing.
+//! - Delivery: background task per snapshot with the `telemetry_push()` retry
+//!   policy; 4xx = rejected (no retry), 5xx / network = retry. A push still in
+//!   flight when the next is due skips that snapshot.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::retry::{self, retry, Attempt, RetryError, RetryPolicy, RetrySite};
+use crate::types::{BotConfig, TradeResult};
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+/// Version of the snapshot schema. Bump on any field change.
+pub const TELEMETRY_SCHEMA_VERSION: u32 = 1;
+
+/// Per-request HTTP timeout
+const PUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest request body the collector reads
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+// ---------------------------------------------------------------------------
+// Schema
+// ---------------------------------------------------------------------------
+
+/// One instance's state at one point in time (wire format, schema v1)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelemetrySnapshot {
+    pub schema_version: u32,
+    pub instance_id: String,
+    pub chain: String,
+    pub chain_id: u64,
+    /// "live" | "dry-run"
+    pub mode: String,
+    /// Unix seconds
+    pub sent_at: u64,
+    pub uptime_secs: u64,
+    pub block_height: u64,
+    pub scans: u64,
+    pub opportunities: u64,
+    pub routes_cooled: u64,
+    /// Submitted transactions
+    pub trades: u64,
+    pub trades_succeeded: u64,
+    pub net_profit_usd: f64,
+    pub gas_cost_usd: f64,
+    pub errors: TelemetryErrors,
+    /// Present only with TELEMETRY_INCLUDE_ADDRESSES=true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addresses: Option<TelemetryAddresses>,
+}
+
+/// Error counters since start
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelemetryErrors {
+    /// executor.execute() returned Err
+    pub execution: u64,
+    /// Submitted transactions that failed (reverts, gas-negative, timeouts)
+    pub failed_trades: u64,
+    /// Backoff waits across all retry sites
+    pub retries: u64,
+    /// Retry loops that gave up
+    pub retries_exhausted: u64,
+}
+
+/// Opt-in identifying addresses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelemetryAddresses {
+    pub wallet: Option<Address>,
+    pub arb_executor: Option<Address>,
+}
+
+/// Values of the periodic status line, supplied by the block loop
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatusCounters {
+    pub block_height: u64,
+    pub scans: u64,
+    pub opportunities: u64,
+    pub routes_cooled: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Identity + config
+// ---------------------------------------------------------------------------
+
+/// Stable anonymized instance ID: first 8 bytes of
+/// keccak256("chain_id|chain|mode|pair,pair,…") as hex
+pub fn instance_id(config: &BotConfig, mode: &str) -> String {
+    let mut pairs: Vec<&str> = config.pairs.iter().map(|p| p.symbol.as_str()).collect();
+    pairs.sort_unstable();
+    let seed = format!("{}|{}|{}|{}", config.chain_id, config.chain_name, mode, pairs.join(","));
+    let hash = ethers::utils::keccak256(seed.as_bytes());
+    hash[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Where snapshots go
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TelemetrySink {
+    Http(String),
+    File(PathBuf),
+}
+
+impl TelemetrySink {
+    /// `http(s)://…` or `file:///absolute/path`
+    pub fn parse(endpoint: &str) -> Result<Self> {
+        let endpoint = endpoint.trim();
+        if let Some(path) = endpoint.strip_prefix("file://") {
+            if path.is_empty() {
+                bail!("telemetry endpoint '{}' has no file path", endpoint);
+            }
+            return Ok(TelemetrySink::File(PathBuf::from(path)));
+        }
+        if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+            return Ok(TelemetrySink::Http(endpoint.to_string()));
+        }
+        Err(anyhow!("telemetry endpoint '{}' must be http(s):// or file://", endpoint))
+    }
+}
+
+/// Telemetry settings (from BotConfig)
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    pub sink: TelemetrySink,
+    pub interval_secs: u64,
+    pub include_addresses: bool,
+}
+
+impl TelemetryConfig {
+    /// None unless telemetry is enabled with an endpoint
+    pub fn from_bot_config(config: &BotConfig) -> Result<Option<Self>> {
+        if !config.telemetry_enabled {
+            return Ok(None);
+        }
+        let endpoint = config
+            .telemetry_endpoint
+            .as_deref()
+            .context("TELEMETRY_ENABLED=true but TELEMETRY_ENDPOINT is not set")?;
+        Ok(Some(Self {
+            sink: TelemetrySink::parse(endpoint)?,
+            interval_secs: config.telemetry_interval_secs.max(10),
+            include_addresses: config.telemetry_include_addresses,
+        }))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Push client
+// ---------------------------------------------------------------------------
+
+/// Sends snapshots to the sink with retry/backoff
+#[derive(Clone)]
+pub struct TelemetryPusher {
+    sink: TelemetrySink,
+    policy: RetryPolicy,
+    site: RetrySite,
+    client: reqwest::Client,
+    in_flight: Arc<AtomicBool>,
+}
+
+impl TelemetryPusher {
+    pub fn new(sink: TelemetrySink) -> Self {
+        Self {
+            sink,
+            policy: retry::telemetry_push(),
+            site: RetrySite::named("telemetry"),
+            client: reqwest::Client::new(),
+            in_flight: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Replace the retry policy (tests)
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Deliver one snapshot; Ok(attempts used)
+    pub async fn push(&self, snapshot: &TelemetrySnapshot) -> std::result::Result<u32, RetryError<String>> {
+        let body = serde_json::to_string(snapshot).map_err(|e| RetryError::Aborted(e.to_string()))?;
+        retry(&self.policy, &self.site, &retry::shutdown_token(), |attempt| {
+            let body = body.clone();
+            async move {
+                match self.send_once(&body).await {
+                    Ok(()) => Attempt::Done(attempt),
+                    Err(e) => e,
+                }
+            }
+        })
+        .await
+    }
+
+    async fn send_once(&self, body: &str) -> std::result::Result<(), Attempt<u32, String>> {
+        match &self.sink {
+            TelemetrySink::File(path) => append_line(path, body).map_err(|e| Attempt::Retry(e.to_string())),
+            TelemetrySink::Http(url) => {
+                let response = self
+                    .client
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body.to_string())
+                    .timeout(PUSH_TIMEOUT)
+                    .send()
+                    .await
+                    .map_err(|e| Attempt::Retry(e.to_string()))?;
+                let status = response.status();
+                if status.is_success() {
+                    Ok(())
+                } else if status.is_client_error() {
+                    // Schema/version rejected — retrying the same body cannot help
+                    Err(Attempt::Abort(format!("collector rejected snapshot: HTTP {}", status)))
+                } else {
+                    Err(Attempt::Retry(format!("HTTP {}", status)))
+                }
+            }
+        }
+    }
+
+    /// Push in the background; skipped while a previous push is still running.
+    /// Must be called from within a tokio runtime.
+    pub fn spawn_push(&self, snapshot: TelemetrySnapshot) {
+        if self.in_flight.swap(true, Ordering::AcqRel) {
+            debug!("Telemetry: previous push still in flight — skipping snapshot");
+            return;
+        }
+        let pusher = self.clone();
+        tokio::spawn(async move {
+            match pusher.push(&snapshot).await {
+                Ok(attempts) if attempts > 1 => debug!("Telemetry pushed after {} attempts", attempts),
+                Ok(_) => {}
+                Err(e) => warn!("Telemetry push failed: {:?}", e),
+            }
+            pusher.in_flight.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// Append one JSON line (creating the file and its directory)
+fn append_line(path: &Path, line: &str) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instance-side assembly
+// ---------------------------------------------------------------------------
+
+/// Trade counters + push schedule for one bot instance
+pub struct Telemetry {
+    config: TelemetryConfig,
+    pusher: TelemetryPusher,
+    instance_id: String,
+    chain: String,
+    chain_id: u64,
+    mode: String,
+    addresses: Option<TelemetryAddresses>,
+    started_at: u64,
+    last_push: Option<u64>,
+    trades: u64,
+    trades_succeeded: u64,
+    net_profit_usd: f64,
+    gas_cost_usd: f64,
+    execution_errors: u64,
+    failed_trades: u64,
+}
+
+impl Telemetry {
+    pub fn new(
+        telemetry: TelemetryConfig,
+        config: &BotConfig,
+        mode: &str,
+        wallet: Option<Address>,
+        now: u64,
+    ) -> Self {
+        let addresses = telemetry.include_addresses.then_some(TelemetryAddresses {
+            wallet,
+            arb_executor: config.arb_executor_address,
+        });
+        Self {
+            pusher: TelemetryPusher::new(telemetry.sink.clone()),
+            config: telemetry,
+            instance_id: instance_id(config, mode),
+            chain: config.chain_name.clone(),
+            chain_id: config.chain_id,
+            mode: mode.to_string(),
+            addresses,
+            started_at: now,
+            last_push: None,
+            trades: 0,
+            trades_succeeded: 0,
+            net_profit_usd: 0.0,
+            gas_cost_usd: 0.0,
+            execution_errors: 0,
+            failed_trades: 0,
+        }
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    pub fn sink(&self) -> &TelemetrySink {
+        &self.config.sink
+    }
+
+    /// One execution result; only submitted transactions count as trades
+    pub fn record_trade(&mut self, result: &TradeResult) {
+        if result.tx_hash.is_none() {
+            return;
+        }
+        self.trades += 1;
+        if result.success {
+            self.trades_succeeded += 1;
+        } else {
+            self.failed_trades += 1;
+        }
+        self.net_profit_usd += result.net_profit_usd;
+        self.gas_cost_usd += result.gas_cost_usd;
+    }
+
+    /// The executor returned Err (RPC failure mid-pipeline)
+    pub fn record_execution_error(&mut self) {
+        self.execution_errors += 1;
+    }
+
+    /// Snapshot from the status-line counters, trade counters and retry sites
+    pub fn snapshot(&self, status: &StatusCounters, now: u64) -> TelemetrySnapshot {
+        let (retries, retries_exhausted) = retry::snapshot()
+            .iter()
+            .fold((0, 0), |(r, x), (_, s)| (r + s.retries, x + s.exhausted));
+        TelemetrySnapshot {
+            schema_version: TELEMETRY_SCHEMA_VERSION,
+            instance_id: self.instance_id.clone(),
+            chain: self.chain.clone(),
+            chain_id: self.chain_id,
+            mode: self.mode.clone(),
+            sent_at: now,
+            uptime_secs: now.saturating_sub(self.started_at),
+            block_height: status.block_height,
+            scans: status.scans,
+            opportunities: status.opportunities,
+            routes_cooled: status.routes_cooled,
+            trades: self.trades,
+            trades_succeeded: self.trades_succeeded,
+            net_profit_usd: self.net_profit_usd,
+            gas_cost_usd: self.gas_cost_usd,
+            errors: TelemetryErrors {
+                execution: self.execution_errors,
+                failed_trades: self.failed_trades,
+                retries,
+                retries_exhausted,
+            },
+            addresses: self.addresses.clone(),
+        }
+    }
+
+    /// Push a snapshot if the interval has elapsed (first call pushes)
+    pub fn tick(&mut self, status: &StatusCounters, now: u64) {
+        if self.last_push.is_some_and(|t| now.saturating_sub(t) < self.config.interval_secs) {
+            return;
+        }
+        self.last_push = Some(now);
+        self.pusher.spawn_push(self.snapshot(status, now));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reference collector
+// ---------------------------------------------------------------------------
+
+/// Parse a pushed body: strict schema, current version only
+pub fn accept(body: &str) -> Result<TelemetrySnapshot> {
+    let value: serde_json::Value = serde_json::from_str(body).context("invalid JSON")?;
+    let version = value.get("schema_version").and_then(|v| v.as_u64());
+    if version != Some(TELEMETRY_SCHEMA_VERSION as u64) {
+        bail!("unsupported schema_version {:?} (expected {})", version, TELEMETRY_SCHEMA_VERSION);
+    }
+    serde_json::from_value(value).context("snapshot does not match schema")
+}
+
+/// Latest snapshot per instance, optionally appended to a JSONL file
+#[derive(Debug, Default)]
+pub struct CollectorState {
+    latest: BTreeMap<String, TelemetrySnapshot>,
+    out: Option<PathBuf>,
+}
+
+impl CollectorState {
+    pub fn new(out: Option<PathBuf>) -> Self {
+        Self { latest: BTreeMap::new(), out }
+    }
+
+    /// Validate, keep as the instance's latest, append to the file
+    pub fn ingest(&mut self, body: &str) -> Result<()> {
+        let snapshot = accept(body)?;
+        if let Some(ref path) = self.out {
+            append_line(path, &serde_json::to_string(&snapshot)?)?;
+        }
+        self.latest.insert(snapshot.instance_id.clone(), snapshot);
+        Ok(())
+    }
+
+    /// Aggregate view: totals across instances + each instance's latest snapshot
+    pub fn aggregate(&self) -> serde_json::Value {
+        let sum = |f: fn(&TelemetrySnapshot) -> f64| self.latest.values().map(f).sum::<f64>();
+        serde_json::json!({
+            "schema_version": TELEMETRY_SCHEMA_VERSION,
+            "instances": self.latest.len(),
+            "opportunities": sum(|s| s.opportunities as f64),
+            "trades": sum(|s| s.trades as f64),
+            "trades_succeeded": sum(|s| s.trades_succeeded as f64),
+            "net_profit_usd": sum(|s| s.net_profit_usd),
+            "gas_cost_usd": sum(|s| s.gas_cost_usd),
+            "errors": sum(|s| (s.errors.execution + s.errors.failed_trades + s.errors.retries_exhausted) as f64),
+            "latest": self.latest.values().collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Minimal HTTP/1.1 collector: POST (any path) ingests a snapshot
+/// (204 / 400), GET returns the aggregate view as JSON
+pub async fn serve_collector(listener: TcpListener, state: Arc<Mutex<CollectorState>>) -> Result<()> {
+    info!("Telemetry collector listening on {}", listener.local_addr()?);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &state).await {
+                debug!("Telemetry collector: {} — {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, state: &Mutex<CollectorState>) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let method = request_line.split_whitespace().next().unwrap_or("").to_string();
+
+    let mut content_length = 0usize;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let (status, body) = match method.as_str() {
+        "POST" if content_length > MAX_BODY_BYTES => ("413 Payload Too Large", String::new()),
+        "POST" => {
+            let mut buf = vec![0u8; content_length];
+            reader.read_exact(&mut buf).await?;
+            let text = String::from_utf8_lossy(&buf);
+            let result = state.lock().unwrap_or_else(|e| e.into_inner()).ingest(&text);
+            match result {
+                Ok(()) => ("204 No Content", String::new()),
+                Err(e) => {
+                    warn!("Telemetry collector: rejected snapshot: {:#}", e);
+                    ("400 Bad Request", format!("{:#}", e))
+                }
+            }
+        }
+        "GET" => {
+            let view = state.lock().unwrap_or_else(|e| e.into_inner()).aggregate();
+            ("200 OK", serde_json::to_string_pretty(&view)?)
+        }
+        _ => ("405 Method Not Allowed", String::new()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let mut stream = reader.into_inner();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use std::sync::atomic::AtomicUsize;
+
+    fn temp_path(tag: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("dexarb_telemetry_{}_{}.jsonl", tag, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn telemetry(include_addresses: bool) -> Telemetry {
+        let mut config = create_test_config();
+        config.arb_executor_address = Some(Address::from_low_u64_be(0xa4b));
+        let sink = TelemetrySink::File(temp_path("unused"));
+        let tc = TelemetryConfig { sink, interval_secs: 60, include_addresses };
+        Telemetry::new(tc, &config, "live", Some(Address::from_low_u64_be(0xfee)), 1_000)
+    }
+
+    fn fast_policy(attempts: u32) -> RetryPolicy {
+        RetryPolicy::fixed(Duration::from_millis(10)).with_max_attempts(attempts)
+    }
+
+    /// HTTP server answering `statuses` in order (last one repeats); counts requests
+    async fn scripted_server(statuses: Vec<u16>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/push", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                let status = statuses[n.min(statuses.len() - 1)];
+                let mut buf = vec![0u8; 8192];
+                let _ = stream.read(&mut buf).await;
+                let response = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            }
+        });
+        (url, hits)
+    }
+
+    /// Field set and order of schema v1. A failure here means the wire format
+    /// changed: bump TELEMETRY_SCHEMA_VERSION and update the collector.
+    #[test]
+    fn test_schema_v1_is_stable() {
+        let t = telemetry(false);
+        let status = StatusCounters { block_height: 100, scans: 50, opportunities: 7, routes_cooled: 2 };
+        let json = serde_json::to_value(t.snapshot(&status, 1_060)).unwrap();
+        let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        let mut expected = vec![
+            "schema_version", "instance_id", "chain", "chain_id", "mode", "sent_at", "uptime_secs",
+            "block_height", "scans", "opportunities", "routes_cooled", "trades", "trades_succeeded",
+            "net_profit_usd", "gas_cost_usd", "errors",
+        ];
+        expected.sort_unstable();
+        assert_eq!(keys, expected);
+        let error_keys: Vec<&str> = json["errors"].as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(error_keys.len(), 4);
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["uptime_secs"], 60);
+        assert_eq!(json["block_height"], 100);
+
+        // Round-trips through the collector's strict parser
+        assert!(accept(&json.to_string()).is_ok());
+        let mut bumped = json.clone();
+        bumped["schema_version"] = serde_json::json!(2);
+        assert!(accept(&bumped.to_string()).is_err());
+        let mut extra = json.clone();
+        extra["private_key"] = serde_json::json!("0x00");
+        assert!(accept(&extra.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_anonymized_unless_addresses_enabled() {
+        let status = StatusCounters::default();
+        let text = serde_json::to_string(&telemetry(false).snapshot(&status, 1_000)).unwrap();
+        assert!(!text.contains("addresses") && !text.contains("0x"));
+
+        let snapshot = telemetry(true).snapshot(&status, 1_000);
+        let addresses = snapshot.addresses.clone().unwrap();
+        assert_eq!(addresses.wallet, Some(Address::from_low_u64_be(0xfee)));
+        assert_eq!(addresses.arb_executor, Some(Address::from_low_u64_be(0xa4b)));
+        assert_eq!(accept(&serde_json::to_string(&snapshot).unwrap()).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_instance_id_stable_and_distinct() {
+        let config = create_test_config();
+        let id = instance_id(&config, "live");
+        assert_eq!(id.len(), 16);
+        assert_eq!(id, instance_id(&config, "live"));
+        assert_ne!(id, instance_id(&config, "dry-run"));
+        let mut base = config.clone();
+        base.chain_name = "base".to_string();
+        base.chain_id = 8453;
+        assert_ne!(id, instance_id(&base, "live"));
+    }
+
+    #[test]
+    fn test_sink_parsing() {
+        assert_eq!(
+            TelemetrySink::parse("file:///var/tmp/t.jsonl").unwrap(),
+            TelemetrySink::File(PathBuf::from("/var/tmp/t.jsonl"))
+        );
+        assert!(matches!(TelemetrySink::parse("https://c.example/push").unwrap(), TelemetrySink::Http(_)));
+        assert!(TelemetrySink::parse("udp://c.example").is_err());
+        assert!(TelemetrySink::parse("file://").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_push_retries_server_errors_then_succeeds() {
+        let (url, hits) = scripted_server(vec![503, 502, 204]).await;
+        let pusher = TelemetryPusher::new(TelemetrySink::Http(url)).with_policy(fast_policy(4));
+        let snapshot = telemetry(false).snapshot(&StatusCounters::default(), 1_000);
+        assert_eq!(pusher.push(&snapshot).await, Ok(3));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_push_gives_up_and_does_not_retry_rejections() {
+        let snapshot = telemetry(false).snapshot(&StatusCounters::default(), 1_000);
+
+        let (url, hits) = scripted_server(vec![503]).await;
+        let pusher = TelemetryPusher::new(TelemetrySink::Http(url)).with_policy(fast_policy(3));
+        assert!(matches!(pusher.push(&snapshot).await, Err(RetryError::Exhausted { attempts: 3, .. })));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // 4xx = schema rejected: one attempt only
+        let (url, hits) = scripted_server(vec![400]).await;
+        let pusher = TelemetryPusher::new(TelemetrySink::Http(url)).with_policy(fast_policy(3));
+        assert!(matches!(pusher.push(&snapshot).await, Err(RetryError::Aborted(_))));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    /// File sink and the reference collector both end in a JSONL log the
+    /// collector can aggregate
+    #[tokio::test]
+    async fn test_file_sink_and_collector() {
+        let mut t = telemetry(false);
+        t.record_trade(&TradeResult {
+            opportunity: "WETH/USDC".to_string(),
+            opportunity_id: 0,
+            tx_hash: Some("0xabc".to_string()),
+            block_number: Some(1),
+            success: true,
+            profit_usd: 1.5,
+            gas_cost_usd: 0.25,
+            gas_used_native: 0.0,
+            net_profit_usd: 1.25,
+            execution_time_ms: 80,
+            error: None,
+            amount_in: None,
+            amount_out: None,
+        });
+        t.record_execution_error();
+        let snapshot = t.snapshot(&StatusCounters::default(), 1_000);
+        assert_eq!((snapshot.trades, snapshot.trades_succeeded, snapshot.errors.execution), (1, 1, 1));
+
+        let path = temp_path("file");
+        let pusher = TelemetryPusher::new(TelemetrySink::File(path.clone()));
+        pusher.push(&snapshot).await.unwrap();
+        pusher.push(&snapshot).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        // Collector over HTTP: POST ingests, GET aggregates
+        let out = temp_path("collector");
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(CollectorState::new(Some(out.clone()))));
+        tokio::spawn(serve_collector(listener, state.clone()));
+        let pusher = TelemetryPusher::new(TelemetrySink::Http(format!("{}/push", base))).with_policy(fast_policy(1));
+        assert_eq!(pusher.push(&snapshot).await, Ok(1));
+        let view: serde_json::Value = reqwest::get(&base).await.unwrap().json().await.unwrap();
+        assert_eq!(view["instances"], 1);
+        assert_eq!(view["net_profit_usd"], 1.25);
+        assert_eq!(std::fs::read_to_string(&out).unwrap().lines().count(), 1);
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&out);
+    }
+}
@@ -0,0 +1,413 @@
+//! Token Metadata Registry — symbol and decimals by address, per chain
+//!
+//! Token decimals and symbols used to come from tables (TOKEN_DECIMALS),
+//! 18-decimal defaults and "WETH/USDC" string splitting. The registry holds
+//! what each token contract reports instead: at startup every token the
+//! config names (pair tokens, quote tokens) is resolved with symbol() and
+//! decimals() eth_calls, and the result cached to data/{chain}/tokens.json,
+//! so a boot with every token cached makes no RPC call.
+//!
+//! A lookup of a token outside the registry is an error, never 18 decimals.
+//! Config pair symbols are kept as aliases ("USDC" in "WETH/USDC" → the quote
+//! token's address), so records keyed by config symbol still resolve when the
+//! contract reports another ticker (USDC.e).
+//!
+//! Not to be confused with token_validation.rs's token_registry.json: that
+//! one watches the same metadata for drift; this one answers lookups.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+
+use crate::arbitrage::token_validation::decode_symbol;
+use crate::types::BotConfig;
+use crate::watchdog::rpc_timeout;
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// decimals()
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+/// symbol()
+const SYMBOL_SELECTOR: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+
+/// One token as its contract reports it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenInfo {
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// tokens.json: the resolved tokens of one chain
+#[derive(Debug, Serialize, Deserialize)]
+struct TokenCache {
+    chain_id: u64,
+    tokens: Vec<TokenInfo>,
+}
+
+/// Resolved token metadata of one chain
+#[derive(Debug, Clone, Default)]
+pub struct TokenRegistry {
+    chain_id: u64,
+    tokens: BTreeMap<Address, TokenInfo>,
+    /// Config pair symbols (upper case) → the token(s) the config uses them for
+    aliases: BTreeMap<String, BTreeSet<Address>>,
+}
+
+/// data/{chain}/tokens.json
+pub fn tokens_path(config: &BotConfig) -> PathBuf {
+    PathBuf::from(format!("/home/botuser/bots/dexarb/data/{}/tokens.json", config.chain_name))
+}
+
+/// Every token the config names: both tokens of each pair and the quote tokens
+pub fn config_tokens(config: &BotConfig) -> Result<Vec<Address>> {
+    let mut tokens = BTreeSet::new();
+    tokens.insert(config.quote_token_address);
+    tokens.extend(config.quote_token_address_native);
+    for pair in &config.pairs {
+        for token in [&pair.token0, &pair.token1] {
+            let address = token
+                .parse::<Address>()
+                .with_context(|| format!("pair {}: invalid token address {}", pair.symbol, token))?;
+            tokens.insert(address);
+        }
+    }
+    Ok(tokens.into_iter().collect())
+}
+
+async fn view<M: Middleware>(provider: &M, token: Address, selector: [u8; 4]) -> Result<Bytes> {
+    let tx: TypedTransaction = TransactionRequest::new().to(token).data(selector.to_vec()).into();
+    rpc_timeout("eth_call", provider.call(&tx, None)).await
+}
+
+/// Read symbol() and decimals() of one token; either unreadable is an error
+pub async fn fetch_token<M: Middleware>(provider: &M, address: Address) -> Result<TokenInfo> {
+    let decimals = view(provider, address, DECIMALS_SELECTOR)
+        .await
+        .with_context(|| format!("token {:?}: decimals() failed", address))?;
+    if decimals.len() < 32 || U256::from_big_endian(&decimals[..32]) > U256::from(u8::MAX) {
+        bail!("token {:?}: decimals() returned {} bytes, not a uint8", address, decimals.len());
+    }
+    let symbol = view(provider, address, SYMBOL_SELECTOR)
+        .await
+        .with_context(|| format!("token {:?}: symbol() failed", address))?;
+    let symbol = decode_symbol(&(true, symbol.to_vec()))
+        .ok_or_else(|| anyhow!("token {:?}: symbol() returned no string", address))?;
+    Ok(TokenInfo { address, symbol, decimals: decimals[31] })
+}
+
+impl TokenRegistry {
+    pub fn new(chain_id: u64) -> Self {
+        Self { chain_id, ..Default::default() }
+    }
+
+    /// The cached registry at `path`; empty when the file is missing or
+    /// belongs to another chain
+    pub fn load(path: &Path, chain_id: u64) -> Result<Self> {
+        let mut registry = Self::new(chain_id);
+        if !path.exists() {
+            return Ok(registry);
+        }
+        let json = std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        let cache: TokenCache = serde_json::from_str(&json).with_context(|| format!("parse {}", path.display()))?;
+        if cache.chain_id != chain_id {
+            warn!("Token registry: {} is for chain {}, not {} — ignored", path.display(), cache.chain_id, chain_id);
+            return Ok(registry);
+        }
+        for token in cache.tokens {
+            registry.insert(token);
+        }
+        Ok(registry)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let cache = TokenCache { chain_id: self.chain_id, tokens: self.tokens.values().cloned().collect() };
+        std::fs::write(path, serde_json::to_string_pretty(&cache)?).with_context(|| format!("write {}", path.display()))
+    }
+
+    /// Startup: the cache at `path`, every config token missing from it read
+    /// on-chain (cache rewritten), and the config's pair symbols as aliases.
+    /// Any token that can't be read fails the whole resolve.
+    pub async fn resolve<M: Middleware>(provider: &M, config: &BotConfig, path: &Path) -> Result<Self> {
+        let mut registry = Self::load(path, config.chain_id)?;
+        let missing: Vec<Address> =
+            config_tokens(config)?.into_iter().filter(|a| !registry.tokens.contains_key(a)).collect();
+        for &address in &missing {
+            registry.insert(fetch_token(provider, address).await?);
+        }
+        if !missing.is_empty() {
+            registry.save(path)?;
+        }
+        registry.alias_config_symbols(config)?;
+        info!(
+            "Token registry: {} tokens ({} read on-chain, {} cached) — {}",
+            registry.len(), missing.len(), registry.len() - missing.len(), path.display()
+        );
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, token: TokenInfo) {
+        self.tokens.insert(token.address, token);
+    }
+
+    /// Register the BASE/QUOTE symbols of every pair holding a quote token.
+    /// A symbol the config uses for two tokens (USDC.e and native USDC both
+    /// "USDC") names neither in by_symbol().
+    pub fn alias_config_symbols(&mut self, config: &BotConfig) -> Result<()> {
+        for pair in &config.pairs {
+            let (Ok(token0), Ok(token1)) = (pair.token0.parse::<Address>(), pair.token1.parse::<Address>()) else {
+                continue;
+            };
+            let (quote, base) = if config.is_quote_token(&token0) {
+                (token0, token1)
+            } else if config.is_quote_token(&token1) {
+                (token1, token0)
+            } else {
+                continue;
+            };
+            let mut symbols = pair.symbol.split('/');
+            let (Some(base_symbol), Some(quote_symbol)) = (symbols.next(), symbols.next()) else {
+                continue;
+            };
+            for (symbol, address) in [(base_symbol, base), (quote_symbol, quote)] {
+                self.get(&address)?;
+                self.aliases.entry(symbol.to_uppercase()).or_default().insert(address);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn get(&self, address: &Address) -> Result<&TokenInfo> {
+        self.tokens
+            .get(address)
+            .ok_or_else(|| anyhow!("unknown token {:?}: not in the token registry", address))
+    }
+
+    pub fn decimals(&self, address: &Address) -> Result<u8> {
+        Ok(self.get(address)?.decimals)
+    }
+
+    pub fn symbol(&self, address: &Address) -> Result<&str> {
+        Ok(&self.get(address)?.symbol)
+    }
+
+    /// By config symbol (alias) first, else the one token reporting `symbol`
+    /// (case-insensitive); unknown or ambiguous is an error
+    pub fn by_symbol(&self, symbol: &str) -> Result<&TokenInfo> {
+        let wanted = symbol.to_uppercase();
+        if let Some(addresses) = self.aliases.get(&wanted) {
+            let mut addresses = addresses.iter();
+            return match (addresses.next(), addresses.next()) {
+                (Some(address), None) => self.get(address),
+                _ => bail!("symbol {} names several tokens in the pair config: use the address", symbol),
+            };
+        }
+        let mut matches = self.tokens.values().filter(|t| t.symbol.to_uppercase() == wanted);
+        match (matches.next(), matches.next()) {
+            (Some(token), None) => Ok(token),
+            (Some(a), Some(b)) => bail!("symbol {} is ambiguous ({:?}, {:?}): use the address", symbol, a.address, b.address),
+            (None, _) => bail!("unknown token {}: not in the token registry", symbol),
+        }
+    }
+
+    /// Decimals of `symbol` (by_symbol); a config symbol naming several tokens
+    /// still resolves when they all report the same decimals
+    pub fn decimals_by_symbol(&self, symbol: &str) -> Result<u8> {
+        let Some(addresses) = self.aliases.get(&symbol.to_uppercase()).filter(|a| a.len() > 1) else {
+            return Ok(self.by_symbol(symbol)?.decimals);
+        };
+        let decimals: BTreeSet<u8> = addresses.iter().map(|a| self.decimals(a)).collect::<Result<_>>()?;
+        match decimals.len() {
+            1 => Ok(decimals.into_iter().next().unwrap_or_default()),
+            _ => bail!("symbol {} names tokens with different decimals {:?}: use the address", symbol, decimals),
+        }
+    }
+
+    /// Log label: the symbol, else the address' first 8 hex digits
+    pub fn label(&self, address: &Address) -> String {
+        match self.tokens.get(address) {
+            Some(token) => token.symbol.clone(),
+            None => format!("{:?}", address).chars().skip(2).take(8).collect(),
+        }
+    }
+
+    /// `raw` in whole tokens with the symbol ("1.5 WETH"); None for unknown tokens
+    pub fn format_amount(&self, address: &Address, raw: U256) -> Option<String> {
+        let token = self.tokens.get(address)?;
+        Some(format!("{} {}", ethers::utils::format_units(raw, token.decimals as u32).ok()?, token.symbol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::detector::tests::create_test_config;
+    use crate::tax::{PriceOracle, TaxRecordBuilder};
+    use crate::types::TradingPairConfig;
+    use ethers::abi::{self, Token};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    const USDC: &str = "0x2791bca1f2de4661ed88a30c99a7a9449aa84174";
+    const WBTC: &str = "0x1bfd67037b42cf73acf2047067bd4f2c47d9bfd6";
+
+    fn addr(s: &str) -> Address {
+        s.parse().unwrap()
+    }
+
+    fn config() -> BotConfig {
+        let mut config = create_test_config();
+        config.quote_token_address = addr(USDC);
+        config.quote_token_address_native = None;
+        config.pairs = vec![TradingPairConfig { token0: WBTC.to_string(), token1: USDC.to_string(), symbol: "WBTC/USDC".to_string() }];
+        config
+    }
+
+    fn push_token(mock: &MockProvider, symbol: &str, decimals: u8) {
+        // MockProvider answers the last-pushed response first: symbol() then decimals()
+        mock.push::<Bytes, _>(Bytes::from(abi::encode(&[Token::String(symbol.to_string())]))).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(abi::encode(&[Token::Uint(U256::from(decimals))]))).unwrap();
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("dexarb_tokens_{}_{}", name, std::process::id())).join("tokens.json")
+    }
+
+    #[tokio::test]
+    async fn test_resolve_reads_chain_then_cache() {
+        let path = temp_path("resolve");
+        let _ = std::fs::remove_file(&path);
+        let config = config();
+
+        // config_tokens() order is by address: WBTC (0x1b..) then USDC (0x27..)
+        let (provider, mock) = Provider::mocked();
+        push_token(&mock, "USDC.e", 6);
+        push_token(&mock, "WBTC", 8);
+        let registry = TokenRegistry::resolve(&provider, &config, &path).await.unwrap();
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.decimals(&addr(WBTC)).unwrap(), 8);
+        assert_eq!(registry.symbol(&addr(USDC)).unwrap(), "USDC.e");
+        // The pair's "USDC" resolves to the token reporting USDC.e
+        assert_eq!(registry.by_symbol("usdc").unwrap().address, addr(USDC));
+        assert_eq!(registry.by_symbol("USDC.e").unwrap().decimals, 6);
+        assert!(registry.decimals(&Address::from_low_u64_be(9)).unwrap_err().to_string().contains("unknown token"));
+        assert!(registry.by_symbol("LINK").is_err());
+
+        // Second boot: all from tokens.json, no RPC (an empty mock would error)
+        let (provider, _mock) = Provider::mocked();
+        let cached = TokenRegistry::resolve(&provider, &config, &path).await.unwrap();
+        assert_eq!(cached.get(&addr(WBTC)).unwrap(), registry.get(&addr(WBTC)).unwrap());
+        assert_eq!(cached.format_amount(&addr(WBTC), U256::from(150_000_000u64)).unwrap(), "1.50000000 WBTC");
+
+        // Another chain's cache is ignored
+        assert!(TokenRegistry::load(&path, 8453).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_token_fails_resolve() {
+        let path = temp_path("unreadable");
+        let _ = std::fs::remove_file(&path);
+        let (provider, mock) = Provider::mocked();
+        push_token(&mock, "USDC", 6);
+        // WBTC's decimals() reverts
+        mock.push_response(MockResponse::Error(JsonRpcError { code: 3, message: "execution reverted".into(), data: None }));
+        let err = TokenRegistry::resolve(&provider, &config(), &path).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("decimals() failed"), "{:#}", err);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let path = temp_path("round_trip");
+        let mut registry = TokenRegistry::new(137);
+        registry.insert(TokenInfo { address: addr(WBTC), symbol: "WBTC".to_string(), decimals: 8 });
+        registry.insert(TokenInfo { address: addr(USDC), symbol: "USDC".to_string(), decimals: 6 });
+        registry.save(&path).unwrap();
+
+        let loaded = TokenRegistry::load(&path, 137).unwrap();
+        assert_eq!(loaded.tokens, registry.tokens);
+        assert!(TokenRegistry::load(&temp_path("missing"), 137).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_tax_record_for_usdc_wbtc() {
+        let mut registry = TokenRegistry::new(137);
+        registry.insert(TokenInfo { address: addr(WBTC), symbol: "WBTC".to_string(), decimals: 8 });
+        registry.insert(TokenInfo { address: addr(USDC), symbol: "USDC.e".to_string(), decimals: 6 });
+        registry.alias_config_symbols(&config()).unwrap();
+        let builder = TaxRecordBuilder::with_oracle(PriceOracle::new("/nonexistent/path"))
+            .with_pinned_price("WBTC", Decimal::from(95_000))
+            .with_token_registry(Arc::new(registry));
+
+        // 0.01 WBTC sold for 950.25 USDC
+        let record = builder
+            .build_arbitrage_record(
+                "WBTC",
+                Decimal::from_str("0.01").unwrap(),
+                "USDC",
+                Decimal::from_str("950.25").unwrap(),
+                Decimal::from_str("0.02").unwrap(),
+                Decimal::from_str("0.003").unwrap(),
+                "0xabc".to_string(),
+                1,
+                "0xwallet".to_string(),
+                "UniswapV3_005".to_string(),
+                "UniswapV3_005".to_string(),
+                String::new(),
+                String::new(),
+                Decimal::ZERO,
+                false,
+            )
+            .unwrap();
+        assert_eq!((record.token_sent_decimals, record.token_received_decimals), (8, 6));
+        assert_eq!(record.amount_sent, Decimal::from_str("0.01").unwrap());
+        assert_eq!(record.amount_received, Decimal::from_str("950.25").unwrap());
+        assert_eq!(record.usd_value_sent, Decimal::from(950));
+        assert_eq!(record.usd_value_received, Decimal::from_str("950.25").unwrap());
+
+        // A config symbol on two tokens names neither; their shared decimals still resolve
+        let mut ambiguous = TokenRegistry::new(137);
+        ambiguous.insert(TokenInfo { address: addr(WBTC), symbol: "WBTC".to_string(), decimals: 8 });
+        ambiguous.insert(TokenInfo { address: addr(USDC), symbol: "USDC.e".to_string(), decimals: 6 });
+        ambiguous.insert(TokenInfo { address: Address::from_low_u64_be(0xc), symbol: "USDC".to_string(), decimals: 6 });
+        let mut two_quotes = config();
+        two_quotes.quote_token_address_native = Some(Address::from_low_u64_be(0xc));
+        two_quotes.pairs.push(TradingPairConfig {
+            token0: WBTC.to_string(),
+            token1: format!("{:?}", Address::from_low_u64_be(0xc)),
+            symbol: "WBTC/USDC".to_string(),
+        });
+        ambiguous.alias_config_symbols(&two_quotes).unwrap();
+        assert!(ambiguous.by_symbol("USDC").is_err());
+        assert_eq!(ambiguous.decimals_by_symbol("USDC").unwrap(), 6);
+        assert_eq!(ambiguous.by_symbol("WBTC").unwrap().decimals, 8);
+
+        // A token the registry doesn't know fails the record
+        let err = builder
+            .build_arbitrage_record(
+                "LINK", Decimal::ONE, "USDC", Decimal::ONE, Decimal::ZERO, Decimal::ZERO, String::new(), 1,
+                String::new(), String::new(), String::new(), String::new(), String::new(), Decimal::ZERO, false,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown token LINK"), "{}", err);
+    }
+}
@@ -0,0 +1,367 @@
+//! RPC Transport — local node IPC with WS fallback
+//!
+//! Purpose:
+//!     RPC_URL may name the IPC socket of a local Bor node (`/var/lib/bor/bor.ipc`
+//!     or `ipc:///var/lib/bor/bor.ipc`). All read paths — pool sync, Quoter
+//!     pre-screen, receipt polling, the executor's signing middleware — then go
+//!     over the socket instead of a remote WS round trip. Subscriptions stay on
+//!     WS: the block subscription and the mempool monitor connect to
+//!     WS_RPC_URL (subscription_url()). Raw tx sends still follow PRIVATE_RPC_URL.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//!
+//! Design:
+//!     - RpcTransport is the JsonRpcClient behind the sync provider: a plain WS
+//!       client for ws:// / wss:// endpoints (ethers' own reconnect, as before),
+//!       or an IPC client with WS_RPC_URL as its fallback.
+//!     - A Bor restart removes the socket under us. A request that fails at the
+//!       transport level (server exit, dropped channel, IO error) reconnects
+//!       once — the socket first, then WS_RPC_URL — and is retried on the new
+//!       connection. JSON-RPC error responses are returned untouched.
+//!     - Concurrent failures reconnect once: a generation counter tells a
+//!       request whether someone else already replaced the connection.
+//!     - On the WS fallback, the socket is retried every IPC_RETRY_INTERVAL
+//!       before a request, so reads move back to IPC once the node is up.
+
+use crate::watchdog::HOT_RPC_TIMEOUT;
+use async_trait::async_trait;
+use ethers::providers::{Ipc, IpcError, JsonRpcClient, JsonRpcError, Provider, ProviderError, RpcError, Ws, WsClientError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// How often reads on the WS fallback try the IPC socket again
+pub const IPC_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Socket path when `url` names an IPC endpoint (`/…` or `ipc://…`)
+pub fn ipc_path(url: &str) -> Option<&str> {
+    if let Some(path) = url.strip_prefix("ipc://") {
+        return Some(path);
+    }
+    url.starts_with('/').then_some(url)
+}
+
+pub fn is_ipc(url: &str) -> bool {
+    ipc_path(url).is_some()
+}
+
+/// Endpoint for subscriptions (blocks, pending txs): `url` itself unless it is
+/// an IPC socket, then the WS fallback. None = IPC without WS_RPC_URL.
+pub fn subscription_url<'a>(url: &'a str, ws_fallback: Option<&'a str>) -> Option<&'a str> {
+    if is_ipc(url) {
+        ws_fallback
+    } else {
+        Some(url)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransportError {
+    #[error(transparent)]
+    Ipc(#[from] IpcError),
+    #[error(transparent)]
+    Ws(#[from] WsClientError),
+    #[error("{0}")]
+    Connect(String),
+}
+
+impl TransportError {
+    /// The connection is gone (as opposed to the node answering with an error)
+    fn is_disconnect(&self) -> bool {
+        match self {
+            TransportError::Ipc(e) => matches!(
+                e,
+                IpcError::IoError(_) | IpcError::ChannelError(_) | IpcError::RequestCancelled(_) | IpcError::ServerExit
+            ),
+            TransportError::Ws(e) => matches!(
+                e,
+                WsClientError::UnexpectedClose | WsClientError::DeadChannel | WsClientError::TooManyReconnects
+            ),
+            TransportError::Connect(_) => true,
+        }
+    }
+}
+
+impl RpcError for TransportError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            TransportError::Ipc(e) => e.as_error_response(),
+            TransportError::Ws(e) => e.as_error_response(),
+            TransportError::Connect(_) => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            TransportError::Ipc(e) => e.as_serde_error(),
+            TransportError::Ws(e) => e.as_serde_error(),
+            TransportError::Connect(_) => None,
+        }
+    }
+}
+
+impl From<TransportError> for ProviderError {
+    fn from(e: TransportError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(e))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Client {
+    Ipc(Ipc),
+    Ws(Ws),
+}
+
+impl Client {
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, TransportError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            Client::Ipc(ipc) => Ok(ipc.request(method, params).await?),
+            Client::Ws(ws) => Ok(ws.request(method, params).await?),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Active {
+    client: Client,
+    /// Bumped on every reconnect
+    generation: u64,
+    /// Last failed IPC connect while on the WS fallback
+    ipc_failed_at: Option<Instant>,
+}
+
+/// Sync provider transport: WS, or IPC with a WS fallback
+#[derive(Debug)]
+pub struct RpcTransport {
+    ipc_path: Option<PathBuf>,
+    ws_fallback: Option<String>,
+    active: Mutex<Active>,
+    /// Serializes reconnects
+    reconnecting: tokio::sync::Mutex<()>,
+}
+
+impl RpcTransport {
+    /// Connect to `url` (IPC socket or WS); an IPC endpoint falls back to
+    /// `ws_fallback` when the socket is unavailable, now or later
+    pub async fn connect(url: &str, ws_fallback: Option<&str>) -> Result<Self, TransportError> {
+        let Some(path) = ipc_path(url) else {
+            let ws = connect_ws(url).await?;
+            return Ok(Self::with_client(None, None, Client::Ws(ws), None));
+        };
+        let path = PathBuf::from(path);
+        let ws_fallback = ws_fallback.map(str::to_string);
+        match connect_ipc(&path).await {
+            Ok(ipc) => Ok(Self::with_client(Some(path), ws_fallback, Client::Ipc(ipc), None)),
+            Err(e) => {
+                let Some(ref url) = ws_fallback else { return Err(e) };
+                warn!("IPC {} unavailable ({}) — reads via WS fallback", path.display(), e);
+                let ws = connect_ws(url).await?;
+                Ok(Self::with_client(Some(path), ws_fallback, Client::Ws(ws), Some(Instant::now())))
+            }
+        }
+    }
+
+    fn with_client(ipc_path: Option<PathBuf>, ws_fallback: Option<String>, client: Client, ipc_failed_at: Option<Instant>) -> Self {
+        Self {
+            ipc_path,
+            ws_fallback,
+            active: Mutex::new(Active { client, generation: 0, ipc_failed_at }),
+            reconnecting: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Reads currently go over the IPC socket
+    pub fn on_ipc(&self) -> bool {
+        matches!(self.active.lock().unwrap().client, Client::Ipc(_))
+    }
+
+    fn current(&self) -> (Client, u64) {
+        let active = self.active.lock().unwrap();
+        (active.client.clone(), active.generation)
+    }
+
+    fn install(&self, client: Client, ipc_failed_at: Option<Instant>) -> Client {
+        let mut active = self.active.lock().unwrap();
+        active.client = client.clone();
+        active.generation += 1;
+        active.ipc_failed_at = ipc_failed_at;
+        client
+    }
+
+    /// On the WS fallback with the retry interval elapsed: try the socket again
+    async fn maybe_restore_ipc(&self) {
+        let Some(ref path) = self.ipc_path else { return };
+        let due = {
+            let active = self.active.lock().unwrap();
+            active.ipc_failed_at.is_some_and(|at| at.elapsed() >= IPC_RETRY_INTERVAL)
+        };
+        if !due {
+            return;
+        }
+        let _guard = self.reconnecting.lock().await;
+        match connect_ipc(path).await {
+            Ok(ipc) => {
+                info!("IPC {} is back — reads moved off the WS fallback", path.display());
+                self.install(Client::Ipc(ipc), None);
+            }
+            Err(_) => self.active.lock().unwrap().ipc_failed_at = Some(Instant::now()),
+        }
+    }
+
+    /// Replace the connection that failed at `generation` (unless a concurrent
+    /// request already did): the IPC socket, else the WS fallback
+    async fn reconnect(&self, generation: u64, error: &TransportError) -> Result<Client, TransportError> {
+        let _guard = self.reconnecting.lock().await;
+        let (client, current) = self.current();
+        if current != generation {
+            return Ok(client);
+        }
+        let path = self.ipc_path.as_ref().expect("reconnect only for IPC transports");
+        warn!("RPC transport lost ({}) — reconnecting IPC {}", error, path.display());
+        let ipc_error = match connect_ipc(path).await {
+            Ok(ipc) => {
+                info!("IPC {} reconnected", path.display());
+                return Ok(self.install(Client::Ipc(ipc), None));
+            }
+            Err(e) => e,
+        };
+        let Some(ref url) = self.ws_fallback else { return Err(ipc_error) };
+        let ws = connect_ws(url).await?;
+        warn!("IPC {} unavailable ({}) — reads via WS fallback", path.display(), ipc_error);
+        Ok(self.install(Client::Ws(ws), Some(Instant::now())))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for RpcTransport {
+    type Error = TransportError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, TransportError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        self.maybe_restore_ipc().await;
+        let (client, generation) = self.current();
+        let error = match client.request(method, &params).await {
+            Ok(result) => return Ok(result),
+            Err(e) => e,
+        };
+        // Plain WS endpoints keep ethers' own reconnect; node errors pass through
+        if self.ipc_path.is_none() || !error.is_disconnect() {
+            return Err(error);
+        }
+        let client = self.reconnect(generation, &error).await?;
+        client.request(method, params).await
+    }
+}
+
+/// Provider over `url` (IPC or WS); see RpcTransport::connect
+pub async fn connect_provider(url: &str, ws_fallback: Option<&str>) -> Result<Provider<RpcTransport>, TransportError> {
+    Ok(Provider::new(RpcTransport::connect(url, ws_fallback).await?))
+}
+
+async fn connect_ipc(path: &Path) -> Result<Ipc, TransportError> {
+    match tokio::time::timeout(HOT_RPC_TIMEOUT, Ipc::connect(path)).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(TransportError::Connect(format!("IPC connect to {} timed out", path.display()))),
+    }
+}
+
+async fn connect_ws(url: &str) -> Result<Ws, TransportError> {
+    match tokio::time::timeout(HOT_RPC_TIMEOUT, Ws::connect(url)).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(TransportError::Connect("WS connect timed out".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::Middleware;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixListener;
+    use tokio::task::JoinHandle;
+
+    /// Minimal IPC node: answers every request with `block`, one connection at a time
+    fn serve(path: &Path, block: u64) -> JoinHandle<()> {
+        let listener = UnixListener::bind(path).unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                while let Ok(n) = stream.read(&mut chunk).await {
+                    if n == 0 {
+                        break;
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+                    let mut requests = serde_json::Deserializer::from_slice(&buf).into_iter::<serde_json::Value>();
+                    let mut consumed = 0;
+                    while let Some(Ok(request)) = requests.next() {
+                        consumed = requests.byte_offset();
+                        let response = serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": request["id"],
+                            "result": format!("{:#x}", block),
+                        });
+                        stream.write_all(&serde_json::to_vec(&response).unwrap()).await.unwrap();
+                    }
+                    buf.drain(..consumed);
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_connection_selection() {
+        assert_eq!(ipc_path("/var/lib/bor/bor.ipc"), Some("/var/lib/bor/bor.ipc"));
+        assert_eq!(ipc_path("ipc:///var/lib/bor/bor.ipc"), Some("/var/lib/bor/bor.ipc"));
+        assert_eq!(ipc_path("wss://polygon.example/v2/key"), None);
+        assert_eq!(ipc_path("http://127.0.0.1:8545"), None);
+
+        // Subscriptions: IPC hands over to WS_RPC_URL; WS endpoints subscribe themselves
+        let ws = Some("ws://127.0.0.1:8546");
+        assert_eq!(subscription_url("/var/lib/bor/bor.ipc", ws), ws);
+        assert_eq!(subscription_url("/var/lib/bor/bor.ipc", None), None);
+        assert_eq!(subscription_url("wss://polygon.example/ws", ws), Some("wss://polygon.example/ws"));
+    }
+
+    #[tokio::test]
+    async fn test_ipc_reconnects_after_socket_disappears() {
+        let path = std::env::temp_dir().join(format!("dexarb_transport_{}.ipc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let server = serve(&path, 100);
+
+        let provider = connect_provider(path.to_str().unwrap(), None).await.unwrap();
+        assert!(provider.as_ref().on_ipc());
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 100);
+
+        // Node restart: socket gone, connection dropped
+        server.abort();
+        let _ = server.await;
+        std::fs::remove_file(&path).unwrap();
+        // No socket and no WS fallback: the read fails instead of hanging
+        assert!(provider.get_block_number().await.is_err());
+
+        // Socket back: the next read reconnects on its own
+        let server = serve(&path, 101);
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 101);
+        assert!(provider.as_ref().on_ipc());
+        server.abort();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_missing_socket_without_fallback_fails_to_connect() {
+        let path = std::env::temp_dir().join(format!("dexarb_transport_missing_{}.ipc", std::process::id()));
+        assert!(RpcTransport::connect(path.to_str().unwrap(), None).await.is_err());
+    }
+}
@@ -1,9 +1,14 @@
 // Core data structures for Phase 1
 // Expand these based on the implementation plan
 
-use ethers::types::{Address, U256};
-use serde::{Deserialize, Serialize};
+use crate::pool::calculator::{pmm_amount_out, pmm_mid_price, PmmState};
+use crate::pool::fixed_point::PriceX96;
+use crate::quote_amount::{self, QuoteAmount, QuoteToken};
+use ethers::types::{Address, TxHash, U256};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
 
 /// Trading pair configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +26,12 @@ impl TradingPair {
             symbol,
         }
     }
+
+    /// Quote token identity for this pair ("WETH/USDC" → USDC)
+    pub fn quote(&self, quote_is_token0: bool, decimals: u8) -> QuoteToken {
+        let address = if quote_is_token0 { self.token0 } else { self.token1 };
+        QuoteToken::new(address, decimals, self.symbol.split('/').nth(1).unwrap_or("QUOTE"))
+    }
 }
 
 /// Fee sentinel for V2 routers in ArbExecutor.sol.
@@ -29,6 +40,7 @@ impl TradingPair {
 pub const V2_FEE_SENTINEL: u32 = 16_777_215;
 
 /// DEX types we support
+#[allow(non_camel_case_types)] // KyberElastic_004 etc. follow the UniswapV3_005 tier suffix
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DexType {
     Uniswap,       // Actually Quickswap on Polygon (Uniswap V2 fork)
@@ -45,16 +57,22 @@ pub enum DexType {
     QuickswapV3,   // QuickSwap V3 (Algebra) — dynamic fees, single pool per pair
     QuickSwapV2,   // QuickSwap V2 (constant product, 0.30% fee) — V2↔V3 cross-protocol arb
     SushiSwapV2,   // SushiSwap V2 (constant product, 0.30% fee) — V2↔V3 cross-protocol arb
+    CurveStable,   // Curve StableSwap plain pool — stablecoin pairs only, detection-only
+    KyberElastic_001, // KyberSwap Elastic 0.01% (concentrated liquidity, detection-only)
+    KyberElastic_004, // KyberSwap Elastic 0.04% (detection-only)
+    KyberElastic_030, // KyberSwap Elastic 0.30% (detection-only)
+    KyberElastic_100, // KyberSwap Elastic 1.00% (detection-only)
+    DodoV2,        // DODO V2 PMM pool (DVM/DPP/DSP) — per-pool fee, legacy two-tx execution only
 }
 
 impl DexType {
-    /// Returns true if this is a V3 DEX (Uniswap, SushiSwap, or QuickSwap V3)
+    /// Returns true if this is a V3 DEX (Uniswap, SushiSwap, QuickSwap V3 or KyberSwap Elastic)
     pub fn is_v3(&self) -> bool {
         matches!(self,
             DexType::UniswapV3_001 | DexType::UniswapV3_005 | DexType::UniswapV3_030 | DexType::UniswapV3_100 |
             DexType::SushiV3_001 | DexType::SushiV3_005 | DexType::SushiV3_030 |
             DexType::QuickswapV3
-        )
+        ) || self.is_kyber_elastic()
     }
 
     /// Returns true if this is a V2 DEX (constant product AMM, 0.30% fee)
@@ -65,13 +83,45 @@ impl DexType {
         )
     }
 
+    /// Returns true for Curve StableSwap pools (balances + A, not reserves/ticks)
+    pub fn is_curve(&self) -> bool {
+        matches!(self, DexType::CurveStable)
+    }
+
+    /// Returns true for KyberSwap Elastic pools (getPoolState()/getLiquidityState(),
+    /// not slot0()/liquidity(); swapFeeUnits in 1e-5 rather than 1e-6)
+    pub fn is_kyber_elastic(&self) -> bool {
+        matches!(self,
+            DexType::KyberElastic_001 | DexType::KyberElastic_004 | DexType::KyberElastic_030 | DexType::KyberElastic_100
+        )
+    }
+
+    /// Returns true for DODO V2 PMM pools (getPMMStateForCall(), not reserves/ticks)
+    pub fn is_dodo(&self) -> bool {
+        matches!(self, DexType::DodoV2)
+    }
+
+    /// Tradeable DEXes ArbExecutor.sol has no leg for yet. DODO swaps go
+    /// through DODOV2Proxy02 (sellBase/sellQuote on the pool), so its routes
+    /// execute in the legacy two-tx path only; atomic support is deferred.
+    pub fn is_legacy_only(&self) -> bool {
+        self.is_dodo()
+    }
+
+    /// Pools the bot prices and logs but never trades. ArbExecutor.sol has no
+    /// leg for Curve's exchange() or Kyber's router (swapExactInputSingle with
+    /// its own params struct), so these are partitioned out before execution.
+    pub fn is_detection_only(&self) -> bool {
+        self.is_curve() || self.is_kyber_elastic()
+    }
+
     /// Returns the fee percentage for any DEX type.
-    /// V2: always 0.30%. V3: from fee tier. Algebra: dynamic (returns None).
+    /// V2: always 0.30%. V3: from fee tier. Algebra, Curve and DODO: per pool (returns None).
     pub fn fee_percent(&self) -> Option<f64> {
         if self.is_v2() {
             Some(0.30)
-        } else if self.is_quickswap_v3() {
-            None // Dynamic fee — read from pool state
+        } else if self.is_quickswap_v3() || self.is_curve() || self.is_dodo() {
+            None // Dynamic / per-pool fee — read from pool state
         } else {
             self.v3_fee_bps().map(|bps| bps as f64 / 100.0)
         }
@@ -92,10 +142,11 @@ impl DexType {
     /// QuickswapV3 returns None (dynamic fee — read from pool state)
     pub fn v3_fee_bps(&self) -> Option<u32> {
         match self {
-            DexType::UniswapV3_001 | DexType::SushiV3_001 => Some(1),    // 0.01%
+            DexType::UniswapV3_001 | DexType::SushiV3_001 | DexType::KyberElastic_001 => Some(1),    // 0.01%
+            DexType::KyberElastic_004 => Some(4),    // 0.04%
             DexType::UniswapV3_005 | DexType::SushiV3_005 => Some(5),    // 0.05%
-            DexType::UniswapV3_030 | DexType::SushiV3_030 => Some(30),   // 0.30%
-            DexType::UniswapV3_100 => Some(100),  // 1.00%
+            DexType::UniswapV3_030 | DexType::SushiV3_030 | DexType::KyberElastic_030 => Some(30),   // 0.30%
+            DexType::UniswapV3_100 | DexType::KyberElastic_100 => Some(100),  // 1.00%
             _ => None,
         }
     }
@@ -105,10 +156,11 @@ impl DexType {
     /// Returns None for V2 dex types — use atomic_fee() for ArbExecutor routing.
     pub fn v3_fee_tier(&self) -> Option<u32> {
         match self {
-            DexType::UniswapV3_001 | DexType::SushiV3_001 => Some(100),
+            DexType::UniswapV3_001 | DexType::SushiV3_001 | DexType::KyberElastic_001 => Some(100),
+            DexType::KyberElastic_004 => Some(400),
             DexType::UniswapV3_005 | DexType::SushiV3_005 => Some(500),
-            DexType::UniswapV3_030 | DexType::SushiV3_030 => Some(3000),
-            DexType::UniswapV3_100 => Some(10000),
+            DexType::UniswapV3_030 | DexType::SushiV3_030 | DexType::KyberElastic_030 => Some(3000),
+            DexType::UniswapV3_100 | DexType::KyberElastic_100 => Some(10000),
             DexType::QuickswapV3 => Some(0), // Sentinel: Algebra has no fixed fee tier
             _ => None,
         }
@@ -145,6 +197,12 @@ impl fmt::Display for DexType {
             DexType::QuickswapV3 => write!(f, "QuickswapV3"),
             DexType::QuickSwapV2 => write!(f, "QuickSwapV2"),
             DexType::SushiSwapV2 => write!(f, "SushiSwapV2"),
+            DexType::CurveStable => write!(f, "CurveStable"),
+            DexType::KyberElastic_001 => write!(f, "KyberElastic_0.01%"),
+            DexType::KyberElastic_004 => write!(f, "KyberElastic_0.04%"),
+            DexType::KyberElastic_030 => write!(f, "KyberElastic_0.30%"),
+            DexType::KyberElastic_100 => write!(f, "KyberElastic_1.00%"),
+            DexType::DodoV2 => write!(f, "DodoV2"),
         }
     }
 }
@@ -205,6 +263,12 @@ impl PoolState {
         raw_ratio * decimal_adjustment
     }
 
+    /// Raw reserve ratio (token1 per token0) in Q64.96, rounded down; zero
+    /// for an empty pool. The detector's exact comparison price.
+    pub fn price_x96(&self) -> PriceX96 {
+        PriceX96::from_ratio(self.reserve1, self.reserve0).unwrap_or_default()
+    }
+
     /// Calculate output amount for given input (constant product formula)
     /// amountOut = (amountIn * 997 * reserveOut) / (reserveIn * 1000 + amountIn * 997)
     /// The 997/1000 factor is the 0.3% V2 swap fee.
@@ -278,6 +342,18 @@ impl V3PoolState {
         price * decimal_adjustment
     }
 
+    /// Raw price (token1 per token0) in Q64.96 from sqrtPriceX96, rounded
+    /// down; from the tick while sqrtPriceX96 isn't synced. Exact where
+    /// price() is not: the detector compares pools on this.
+    pub fn price_x96(&self) -> PriceX96 {
+        if self.sqrt_price_x96.is_zero() {
+            PriceX96::from_tick(self.tick)
+        } else {
+            PriceX96::from_sqrt_x96(self.sqrt_price_x96)
+        }
+        .unwrap_or_default()
+    }
+
     /// Get price normalized to match pair symbol direction
     ///
     /// V3 pools always have token0 < token1 by address, but the pair symbol
@@ -313,6 +389,279 @@ impl V3PoolState {
     }
 }
 
+/// Curve StableSwap pool state (plain stable pools: USDC.e/USDT, USDC/DAI)
+///
+/// A pool may hold more than two coins; one entry tracks the two coins of its
+/// whitelisted pair. Swap math is in pool::stableswap.
+#[derive(Debug, Clone)]
+pub struct CurvePoolState {
+    pub address: Address,
+    pub dex: DexType,
+    /// The tracked coins, token0 < token1 by address (as for V2/V3)
+    pub pair: TradingPair,
+    /// Indices of token0 / token1 in the pool's coins(i)
+    pub index0: usize,
+    pub index1: usize,
+    /// Raw balances(i) of every pool coin
+    pub balances: Vec<U256>,
+    /// Decimals of every pool coin
+    pub decimals: Vec<u8>,
+    /// Amplification coefficient A()
+    pub amp: U256,
+    /// Swap fee, fee() at 1e10 (4_000_000 = 0.04%)
+    pub fee: U256,
+    pub token0_decimals: u8,
+    pub token1_decimals: u8,
+    /// Last updated block
+    pub last_updated: u64,
+}
+
+impl CurvePoolState {
+    fn indices(&self, token_in: Address) -> (usize, usize) {
+        if token_in == self.pair.token0 {
+            (self.index0, self.index1)
+        } else {
+            (self.index1, self.index0)
+        }
+    }
+
+    /// get_dy for `amount_in` of `token_in` (after the pool fee); zero when
+    /// the pool can't serve it
+    pub fn get_amount_out(&self, amount_in: U256, token_in: Address) -> U256 {
+        let (i, j) = self.indices(token_in);
+        crate::pool::stableswap::get_dy(i, j, amount_in, &self.balances, &self.decimals, self.amp, self.fee)
+            .unwrap_or_default()
+    }
+
+    /// Marginal price, token1 per token0 in human units (same format as
+    /// V3PoolState::price() and PoolState::price_adjusted()), before the fee.
+    /// Quoted for one whole token0, which is noise next to a stable pool's depth.
+    pub fn price_adjusted(&self) -> f64 {
+        let one = U256::exp10(self.token0_decimals as usize);
+        match crate::pool::stableswap::get_dy_before_fee(
+            self.index0, self.index1, one, &self.balances, &self.decimals, self.amp,
+        ) {
+            Some(dy) => raw_to_human(dy, self.token1_decimals),
+            None => 0.0,
+        }
+    }
+
+    /// Marginal price as price_adjusted(), raw and in Q64.96 (rounded down)
+    pub fn price_x96(&self) -> PriceX96 {
+        let one = U256::exp10(self.token0_decimals as usize);
+        crate::pool::stableswap::get_dy_before_fee(self.index0, self.index1, one, &self.balances, &self.decimals, self.amp)
+            .and_then(|dy| PriceX96::from_ratio(dy, one))
+            .unwrap_or_default()
+    }
+
+    /// Fee in hundredths of a bip, the V3 fee-tier unit (0.04% → 400)
+    pub fn fee_tier(&self) -> u32 {
+        (self.fee / U256::from(10_000)).low_u32()
+    }
+
+    /// Get the fee as a percentage (0.04 for 0.04%)
+    pub fn fee_percent(&self) -> f64 {
+        self.fee_tier() as f64 / 10000.0
+    }
+
+    /// Liquidity proxy for the detector's floor check: the smaller tracked
+    /// balance, normalized to 18 decimals
+    pub fn liquidity(&self) -> u128 {
+        let xp = crate::pool::stableswap::normalize(&self.balances, &self.decimals);
+        let (a, b) = (xp.get(self.index0).copied(), xp.get(self.index1).copied());
+        match (a, b) {
+            (Some(a), Some(b)) => a.min(b).min(U256::from(u128::MAX)).low_u128(),
+            _ => 0,
+        }
+    }
+}
+
+/// DODO V2 PMM pool state (DVM / DPP / DSP)
+///
+/// The pool has a base and a quote token of its own choosing; `pair` keeps
+/// token0 < token1 by address like the other states and `base_is_token0`
+/// maps between the two. Swap and price math is in pool::calculator.
+#[derive(Debug, Clone)]
+pub struct DodoPoolState {
+    pub address: Address,
+    pub dex: DexType,
+    pub pair: TradingPair,
+    /// _BASE_TOKEN_() == pair.token0
+    pub base_is_token0: bool,
+    pub pmm: PmmState,
+    pub token0_decimals: u8,
+    pub token1_decimals: u8,
+    /// Last updated block
+    pub last_updated: u64,
+}
+
+impl DodoPoolState {
+    pub fn base_token(&self) -> Address {
+        if self.base_is_token0 { self.pair.token0 } else { self.pair.token1 }
+    }
+
+    /// querySellBase / querySellQuote output for `amount_in` of `token_in`
+    /// (after the LP and maintainer fees); zero when the pool can't serve it
+    pub fn get_amount_out(&self, amount_in: U256, token_in: Address) -> U256 {
+        pmm_amount_out(&self.pmm, amount_in, token_in == self.base_token()).unwrap_or_default()
+    }
+
+    /// getMidPrice() as token1 per token0 in raw units, Q64.96 (rounded down)
+    pub fn price_x96(&self) -> PriceX96 {
+        let one = U256::exp10(18);
+        match pmm_mid_price(&self.pmm) {
+            Some(mid) if self.base_is_token0 => PriceX96::from_ratio(mid, one),
+            Some(mid) => PriceX96::from_ratio(one, mid),
+            None => None,
+        }
+        .unwrap_or_default()
+    }
+
+    /// Marginal price, token1 per token0 in human units (same format as
+    /// V3PoolState::price() and PoolState::price_adjusted()), before the fee
+    pub fn price_adjusted(&self) -> f64 {
+        self.price_x96().to_f64() * 10_f64.powi(self.token0_decimals as i32 - self.token1_decimals as i32)
+    }
+
+    /// LP + maintainer fee in hundredths of a bip (0.3% → 3000)
+    pub fn fee_tier(&self) -> u32 {
+        ((self.pmm.lp_fee_rate + self.pmm.mt_fee_rate) / U256::exp10(12)).low_u32()
+    }
+
+    /// Get the fee as a percentage (0.3 for 0.3%)
+    pub fn fee_percent(&self) -> f64 {
+        self.pmm.fee_fraction() * 100.0
+    }
+
+    /// Liquidity proxy: the smaller side of the pool valued in the quote
+    /// token at the mid price, raw quote units
+    pub fn liquidity(&self) -> u128 {
+        let base_in_quote = pmm_mid_price(&self.pmm)
+            .and_then(|mid| crate::pool::fixed_point::mul_div(self.pmm.b, mid, U256::exp10(18)))
+            .unwrap_or_default();
+        self.pmm.q.min(base_in_quote).min(U256::from(u128::MAX)).low_u128()
+    }
+}
+
+/// Raw pool state behind one leg of an opportunity, captured at detection.
+///
+/// Kept verbatim (no f64 rounding) so a post-mortem can redo the exact swap
+/// math the detector saw and tell "stale state" apart from "bad math".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolStateSnapshot {
+    pub address: Address,
+    pub dex: DexType,
+    pub token0_decimals: u8,
+    pub token1_decimals: u8,
+    /// Fee in hundredths of a bip (V3 tier; 3000 for V2 constant-product; Curve fee() / 1e4;
+    /// DODO LP + maintainer rate / 1e12)
+    pub fee: u32,
+    pub state: PoolStateKind,
+    /// Block the pool state was last synced at
+    pub block: u64,
+    /// Unix seconds when the detector read the state
+    pub captured_at: u64,
+}
+
+/// Protocol-specific part of a pool snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PoolStateKind {
+    V3 {
+        sqrt_price_x96: U256,
+        tick: i32,
+        liquidity: u128,
+    },
+    V2 {
+        reserve0: U256,
+        reserve1: U256,
+    },
+    Curve {
+        balances: Vec<U256>,
+        amp: U256,
+        fee: U256,
+    },
+    Dodo {
+        pmm: PmmState,
+        base_is_token0: bool,
+    },
+}
+
+impl PoolStateSnapshot {
+    pub fn from_v3(pool: &V3PoolState, captured_at: u64) -> Self {
+        Self {
+            address: pool.address,
+            dex: pool.dex,
+            token0_decimals: pool.token0_decimals,
+            token1_decimals: pool.token1_decimals,
+            fee: pool.fee,
+            state: PoolStateKind::V3 {
+                sqrt_price_x96: pool.sqrt_price_x96,
+                tick: pool.tick,
+                liquidity: pool.liquidity,
+            },
+            block: pool.last_updated,
+            captured_at,
+        }
+    }
+
+    pub fn from_curve(pool: &CurvePoolState, captured_at: u64) -> Self {
+        Self {
+            address: pool.address,
+            dex: pool.dex,
+            token0_decimals: pool.token0_decimals,
+            token1_decimals: pool.token1_decimals,
+            fee: pool.fee_tier(),
+            state: PoolStateKind::Curve {
+                balances: pool.balances.clone(),
+                amp: pool.amp,
+                fee: pool.fee,
+            },
+            block: pool.last_updated,
+            captured_at,
+        }
+    }
+
+    pub fn from_dodo(pool: &DodoPoolState, captured_at: u64) -> Self {
+        Self {
+            address: pool.address,
+            dex: pool.dex,
+            token0_decimals: pool.token0_decimals,
+            token1_decimals: pool.token1_decimals,
+            fee: pool.fee_tier(),
+            state: PoolStateKind::Dodo {
+                pmm: pool.pmm.clone(),
+                base_is_token0: pool.base_is_token0,
+            },
+            block: pool.last_updated,
+            captured_at,
+        }
+    }
+
+    pub fn from_v2(pool: &PoolState, captured_at: u64) -> Self {
+        Self {
+            address: pool.address,
+            dex: pool.dex,
+            token0_decimals: pool.token0_decimals,
+            token1_decimals: pool.token1_decimals,
+            fee: 3000,
+            state: PoolStateKind::V2 {
+                reserve0: pool.reserve0,
+                reserve1: pool.reserve1,
+            },
+            block: pool.last_updated,
+            captured_at,
+        }
+    }
+}
+
+/// Both legs' pool state at detection time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LegSnapshots {
+    pub buy: PoolStateSnapshot,
+    pub sell: PoolStateSnapshot,
+}
+
 /// Arbitrage opportunity detected
 ///
 /// Buy/Sell semantics (V3 price = token1/token0, token0 sorted by address):
@@ -328,8 +677,16 @@ impl V3PoolState {
 ///     buy_dex  = pool with LOWER V3 price (less quote per base = cheap base → buy here)
 ///     sell_dex = pool with HIGHER V3 price (more quote per base = expensive base → sell here)
 ///     Execute: token1(quote)→token0(base) on buy, token0(base)→token1(quote) on sell
-#[derive(Debug, Clone)]
+///
+/// (De)serialized through `remote = "Self"` so loading can rebind trade_size
+/// to the quote token the token layout names — the stored form is the raw U256.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct ArbitrageOpportunity {
+    /// Generated at detection (next_opportunity_id); clones keep it, and the
+    /// executor copies it to the TradeResult (0 = saved before ids existed)
+    #[serde(default)]
+    pub id: u64,
     pub pair: TradingPair,
     pub buy_dex: DexType,
     pub sell_dex: DexType,
@@ -337,7 +694,10 @@ pub struct ArbitrageOpportunity {
     pub sell_price: f64,
     pub spread_percent: f64,
     pub estimated_profit: f64, // in USD
-    pub trade_size: U256,      // in quote token units (always USDC with 6 decimals)
+    /// In quote token raw units; bound to quote() — change the token layout
+    /// through set_quote_layout() so the binding follows
+    #[serde(with = "quote_amount::serde_u256")]
+    pub trade_size: QuoteAmount,
     pub timestamp: u64,
     /// Pool address where we buy (optional for tax logging)
     pub buy_pool_address: Option<Address>,
@@ -347,16 +707,233 @@ pub struct ArbitrageOpportunity {
     pub token0_decimals: u8,
     /// Token1 decimals (for correct min_out calculation)
     pub token1_decimals: u8,
-    /// Pool liquidity at buy pool (V3 only, for safety checks)
+    /// Pool liquidity at buy pool (V3 L; V2: smaller raw reserve)
     pub buy_pool_liquidity: Option<u128>,
+    /// Pool liquidity at sell pool (same units as buy_pool_liquidity)
+    pub sell_pool_liquidity: Option<u128>,
     /// Whether the quote token (USDC) is V3 token0.
     /// Determines swap direction in quoter and executor.
     /// true:  trade goes token0→token1→token0 (USDC is token0)
     /// false: trade goes token1→token0→token1 (USDC is token1)
     pub quote_token_is_token0: bool,
+    /// Pool state of both legs as the detector saw it (None for mempool-derived opps)
+    pub pool_snapshots: Option<LegSnapshots>,
+    /// trade_size in USD (quote units × quote USD price) — logs/reports only,
+    /// execution always uses the raw trade_size
+    pub trade_size_usd: f64,
+    /// Multicall pre-screen leg outputs, carried to execution so realized
+    /// slippage can be measured against them (None = not pre-screened)
+    #[serde(default)]
+    pub quoted_legs: Option<QuotedLegs>,
+    /// Exposure pacing decision at dispatch (None = not paced)
+    #[serde(default)]
+    pub exposure_pacing: Option<ExposurePacing>,
+    /// Estimated chance our priority fee wins the route's race, set by the
+    /// competition model's EV ranking (None = model off)
+    #[serde(default)]
+    pub win_probability: Option<f64>,
+    /// Ordered swaps of a multi-leg route for ArbExecutor.executeArbMulti
+    /// (None = two-leg buy/sell route)
+    #[serde(default)]
+    pub route_legs: Option<Vec<RouteLeg>>,
+    /// Still clears MIN_PROFIT_USD after the flashloan premium (set by the
+    /// detector when FLASHLOAN_ENABLED); the executor may borrow its size
+    #[serde(default)]
+    pub flash_eligible: bool,
+    /// Bought on a V2 pair and still clears MIN_PROFIT_USD once the pair's
+    /// flash-swap repayment is counted (set by the detector when
+    /// FLASH_V2_ENABLED); the executor may run it via executeArbFlashV2
+    #[serde(default)]
+    pub flash_v2_eligible: bool,
+    /// Second sell pool of a split route (SPLIT_ROUTING): the base bought is
+    /// sold across sell_dex and this leg via ArbExecutor.executeArbSplit
+    /// (None = single sell leg)
+    #[serde(default)]
+    pub split_sell: Option<SplitSell>,
+    /// Route's minimum profit: adaptive (MIN_PROFIT_ADAPTIVE, threshold.rs)
+    /// and/or a whitelist pair/pool override; the executor's minProfit
+    /// (None = MIN_PROFIT_USD)
+    #[serde(default)]
+    pub min_profit_usd: Option<f64>,
+    /// Whitelist pair/pool override of MAX_SLIPPAGE_PERCENT (caps the
+    /// min_out allowance; None = the global value)
+    #[serde(default)]
+    pub max_slippage_percent: Option<f64>,
+    /// Found by a mid-block re-scan (MIDBLOCK_SCAN) against state simulated
+    /// past this pending tx — not yet on chain (None = detected on synced state)
+    #[serde(default)]
+    pub anticipatory: Option<TxHash>,
+}
+
+/// Second sell leg of a split route and its share of the base sold
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SplitSell {
+    pub dex: DexType,
+    pub pool_address: Address,
+    /// Pool price (token1 per token0, decimal-adjusted)
+    pub price: f64,
+    pub liquidity: Option<u128>,
+    /// Share of the base sold on this leg in bps (1..9999); sell_dex gets the rest
+    pub share_bps: u16,
+}
+
+/// One swap of a multi-leg atomic route, in execution order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteLeg {
+    pub dex: DexType,
+    pub pool_address: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+}
+
+/// Pre-screen quoted outputs of both legs for one trade_size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotedLegs {
+    /// Buy leg output (base token raw units)
+    pub buy_out: U256,
+    /// Sell leg output for `buy_out` in (quote token raw units)
+    pub sell_out: U256,
+}
+
+/// Exposure pacing decision for one dispatch (see arbitrage/exposure.rs),
+/// carried to the in-flight journal and the tax record notes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExposurePacing {
+    /// Wallet quote token balance the limits were taken from (raw)
+    pub balance: U256,
+    /// Quote token committed to in-flight trades (raw)
+    pub committed: U256,
+    /// trade_size before pacing (raw)
+    pub requested: U256,
+    /// trade_size after pacing (raw)
+    pub allowed: U256,
+    pub max_trade_fraction: f64,
+    pub max_inflight_fraction: f64,
+    /// Contract minProfit for the paced trade (quote raw units)
+    pub min_profit_raw: U256,
+}
+
+impl ExposurePacing {
+    pub fn scaled(&self) -> bool {
+        self.allowed < self.requested
+    }
+}
+
+/// One swap of a triangular route, in execution order
+#[derive(Debug, Clone)]
+pub struct TriangularLeg {
+    pub dex: DexType,
+    pub pool_address: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+    /// token_out per token_in (human units, before the swap fee)
+    pub rate: f64,
+    /// Swap fee in percent (0.05 = 0.05%)
+    pub fee_percent: f64,
+    /// Pool liquidity (V3 L; V2: smaller raw reserve)
+    pub liquidity: u128,
+    /// Pool state as the detector saw it
+    pub snapshot: PoolStateSnapshot,
+}
+
+impl TriangularLeg {
+    /// token_out per token_in after the swap fee
+    pub fn net_rate(&self) -> f64 {
+        self.rate * (1.0 - self.fee_percent / 100.0)
+    }
+}
+
+/// Triangular arbitrage opportunity: quote → A → B → quote over three pools.
+///
+/// Starts and ends in the same quote token (one silo, like the two-leg
+/// ArbitrageOpportunity); A and B are never quote tokens.
+#[derive(Debug, Clone)]
+pub struct TriangularOpportunity {
+    /// Swaps in execution order; legs[0].token_in == legs[2].token_out == quote
+    pub legs: Vec<TriangularLeg>,
+    /// Token path for logs, e.g. "USDC→WETH→WMATIC→USDC"
+    pub route: String,
+    /// Executable spread after all three leg fees (percent)
+    pub spread_percent: f64,
+    pub estimated_profit: f64, // in USD
+    /// In quote token raw units
+    pub trade_size: QuoteAmount,
+    /// trade_size in USD — logs/reports only
+    pub trade_size_usd: f64,
+    /// Contract minProfit at config.min_profit_usd (quote raw units)
+    pub min_profit_raw: U256,
+    pub timestamp: u64,
+}
+
+impl TriangularOpportunity {
+    /// Quote token the route starts and ends in
+    pub fn quote(&self) -> QuoteToken {
+        self.trade_size.token()
+    }
+
+    /// Product of the three legs' rates after fees (> 1.0 = profitable before gas)
+    pub fn cycle_rate(&self) -> f64 {
+        self.legs.iter().map(TriangularLeg::net_rate).product()
+    }
+
+    /// DEXes in leg order, e.g. "UniswapV3_005→QuickSwapV3→SushiV3_030"
+    pub fn dex_path(&self) -> String {
+        self.legs.iter().map(|leg| format!("{:?}", leg.dex)).collect::<Vec<_>>().join("→")
+    }
+
+    /// The route as an opportunity the atomic executor takes (executeArbMulti):
+    /// pair = quote / first intermediate token, buy/sell = first/last leg,
+    /// every leg in `route_legs`. Symbol "WETH→WMATIC/USDC".
+    pub fn to_atomic_opportunity(&self) -> ArbitrageOpportunity {
+        let quote = self.quote();
+        let (first, last) = (&self.legs[0], &self.legs[self.legs.len() - 1]);
+        // Pools order token0 < token1 by address
+        let base_decimals = if first.token_in < first.token_out {
+            first.snapshot.token1_decimals
+        } else {
+            first.snapshot.token0_decimals
+        };
+        let path: Vec<&str> = self.route.split('→').collect();
+        let intermediates = path.get(1..path.len().saturating_sub(1)).unwrap_or_default().join("→");
+        let pair = TradingPair::new(quote.address, first.token_out, format!("{}/{}", intermediates, quote.symbol));
+
+        let mut opp = ArbitrageOpportunity::with_pool_addresses(
+            pair, first.dex, last.dex, first.rate, last.rate, self.trade_size.raw(),
+            first.pool_address, last.pool_address,
+        );
+        opp.set_quote_layout(quote.decimals, base_decimals, true);
+        opp.spread_percent = self.spread_percent;
+        opp.estimated_profit = self.estimated_profit;
+        opp.trade_size_usd = self.trade_size_usd;
+        opp.timestamp = self.timestamp;
+        opp.route_legs = Some(self.legs.iter().map(|leg| RouteLeg {
+            dex: leg.dex,
+            pool_address: leg.pool_address,
+            token_in: leg.token_in,
+            token_out: leg.token_out,
+        }).collect());
+        opp
+    }
+}
+
+impl Serialize for ArbitrageOpportunity {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        ArbitrageOpportunity::serialize(self, s)
+    }
+}
+
+impl<'de> Deserialize<'de> for ArbitrageOpportunity {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let mut opp = ArbitrageOpportunity::deserialize(d)?;
+        opp.trade_size = opp.quote_amount(opp.trade_size.raw());
+        Ok(opp)
+    }
 }
 
 impl ArbitrageOpportunity {
+    /// `trade_size` is bound to the default layout (quote = token0, 18 decimals);
+    /// set_quote_layout() rebinds it. `trade_size_usd` stays 0.0 (unsized) until
+    /// the layout is known: set_quote_layout() or normalize_trade_size() fills it
     pub fn new(
         pair: TradingPair,
         buy_dex: DexType,
@@ -366,8 +943,10 @@ impl ArbitrageOpportunity {
         trade_size: U256,
     ) -> Self {
         let spread_percent = ((sell_price - buy_price) / buy_price).abs() * 100.0;
+        let quote = pair.quote(true, 18);
 
         Self {
+            id: next_opportunity_id(),
             pair,
             buy_dex,
             sell_dex,
@@ -375,7 +954,7 @@ impl ArbitrageOpportunity {
             sell_price,
             spread_percent,
             estimated_profit: 0.0, // Calculate separately
-            trade_size,
+            trade_size: quote.amount(trade_size),
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
@@ -385,7 +964,20 @@ impl ArbitrageOpportunity {
             token0_decimals: 18, // Default to 18, override for specific tokens
             token1_decimals: 18,
             buy_pool_liquidity: None,
+            sell_pool_liquidity: None,
             quote_token_is_token0: true,
+            pool_snapshots: None,
+            trade_size_usd: 0.0,
+            quoted_legs: None,
+            exposure_pacing: None,
+            win_probability: None,
+            route_legs: None,
+            flash_eligible: false,
+            flash_v2_eligible: false,
+            split_sell: None,
+            min_profit_usd: None,
+            max_slippage_percent: None,
+            anticipatory: None,
         }
     }
 
@@ -409,12 +1001,189 @@ impl ArbitrageOpportunity {
     pub fn is_profitable(&self, min_profit_usd: f64) -> bool {
         self.estimated_profit > min_profit_usd
     }
+
+    /// Either leg lacks an ArbExecutor leg (DODO): two-tx execution only
+    pub fn is_legacy_only(&self) -> bool {
+        self.buy_dex.is_legacy_only() || self.sell_dex.is_legacy_only()
+    }
+
+    /// Set token decimals and which side is the quote token; rebinds trade_size
+    /// (trade_size_usd follows at the quote price it was sized at)
+    pub fn set_quote_layout(&mut self, token0_decimals: u8, token1_decimals: u8, quote_token_is_token0: bool) {
+        let quote_price_usd = self.quote_price_usd();
+        self.token0_decimals = token0_decimals;
+        self.token1_decimals = token1_decimals;
+        self.quote_token_is_token0 = quote_token_is_token0;
+        self.trade_size = self.quote_amount(self.trade_size.raw());
+        self.normalize_trade_size(quote_price_usd);
+    }
+
+    /// Quote token identity (address, decimals, symbol) of this trade
+    pub fn quote(&self) -> QuoteToken {
+        self.pair.quote(self.quote_token_is_token0, self.quote_decimals())
+    }
+
+    /// Bind a raw quote-token value (quoter output, balance, profit) to this trade's quote token
+    pub fn quote_amount(&self, raw: U256) -> QuoteAmount {
+        self.quote().amount(raw)
+    }
+
+    /// Decimals of the quote token (trade_size, amountOut and profit are in these units)
+    pub fn quote_decimals(&self) -> u8 {
+        if self.quote_token_is_token0 {
+            self.token0_decimals
+        } else {
+            self.token1_decimals
+        }
+    }
+
+    /// Decimals of the base token (bought on the buy leg, sold on the sell leg)
+    pub fn base_decimals(&self) -> u8 {
+        if self.quote_token_is_token0 {
+            self.token1_decimals
+        } else {
+            self.token0_decimals
+        }
+    }
+
+    /// Buy leg rate: base received per quote spent (human units)
+    pub fn buy_leg_price(&self) -> f64 {
+        if self.quote_token_is_token0 {
+            self.buy_price
+        } else {
+            1.0 / self.buy_price
+        }
+    }
+
+    /// Sell leg rate: quote received per base sold (human units)
+    pub fn sell_leg_price(&self) -> f64 {
+        if self.quote_token_is_token0 {
+            1.0 / self.sell_price
+        } else {
+            self.sell_price
+        }
+    }
+
+    /// Quote token address (the token the trade starts and ends in)
+    pub fn quote_token(&self) -> Address {
+        if self.quote_token_is_token0 {
+            self.pair.token0
+        } else {
+            self.pair.token1
+        }
+    }
+
+    /// Quote token symbol from the pair symbol ("WETH/USDC" → "USDC")
+    pub fn quote_symbol(&self) -> &str {
+        self.pair.symbol.split('/').nth(1).unwrap_or("QUOTE")
+    }
+
+    /// Base token symbol from the pair symbol ("WETH/USDC" → "WETH")
+    pub fn base_symbol(&self) -> &str {
+        self.pair.symbol.split('/').next().unwrap_or("BASE")
+    }
+
+    /// Recompute trade_size_usd after trade_size or the decimals change
+    pub fn normalize_trade_size(&mut self, quote_price_usd: f64) {
+        self.trade_size_usd = self.trade_size.to_usd(quote_price_usd);
+    }
+
+    /// "$500.00 (500.000000 USDC)"
+    pub fn size_display(&self) -> String {
+        format!("{} ({})", format_usd(self.trade_size_usd), self.trade_size)
+    }
+
+    /// Signed quote token raw amount (quoted profit) with units, e.g. "-0.250000 USDC"
+    pub fn quote_delta_display(&self, raw: i128) -> String {
+        format_signed_token_amount(raw, self.quote_decimals(), self.quote_symbol())
+    }
+
+    /// Base token raw amount with units, e.g. "0.00625000 WBTC"
+    pub fn base_amount_display(&self, raw: U256) -> String {
+        format_token_amount(raw, self.base_decimals(), self.base_symbol())
+    }
+
+    /// USD per quote token as trade_size was priced (trade_size_usd): $1 for
+    /// USDC/DAI, QUOTE_TOKEN_PRICE_USD for a WETH-quoted silo. 1.0 when unsized.
+    pub fn quote_price_usd(&self) -> f64 {
+        let size = self.trade_size.to_human();
+        if size > 0.0 && self.trade_size_usd > 0.0 {
+            self.trade_size_usd / size
+        } else {
+            1.0
+        }
+    }
+
+    /// Convert a USD amount to a quote amount at the trade's quote price
+    pub fn usd_to_quote(&self, usd: f64) -> QuoteAmount {
+        self.quote().from_usd(usd, self.quote_price_usd())
+    }
+
+    /// Convert a quote amount of this trade to USD at the trade's quote price
+    pub fn quote_to_usd(&self, amount: QuoteAmount) -> f64 {
+        debug_assert!(amount.token() == self.quote(), "{} is not in {}'s quote token {}", amount, self.pair.symbol, self.quote());
+        amount.to_usd(self.quote_price_usd())
+    }
+}
+
+/// Source of opportunity ids: seeded with the start time in microseconds, so
+/// ids keep increasing across restarts and stay below 2^53 (exact in JSON)
+static NEXT_OPPORTUNITY_ID: OnceLock<AtomicU64> = OnceLock::new();
+
+/// A new opportunity id, unique within and across runs
+pub fn next_opportunity_id() -> u64 {
+    NEXT_OPPORTUNITY_ID
+        .get_or_init(|| AtomicU64::new(chrono::Utc::now().timestamp_micros().max(1) as u64))
+        .fetch_add(1, Ordering::Relaxed)
+}
+
+/// Human amount -> raw token units at the given decimals (rounded, u128 range)
+pub fn usd_to_raw(amount: f64, decimals: u8) -> U256 {
+    let raw = (amount * 10_f64.powi(decimals as i32)).round();
+    if raw <= 0.0 || !raw.is_finite() {
+        return U256::zero();
+    }
+    U256::from(raw as u128)
+}
+
+/// Display precision for a token amount: all decimals up to 8
+fn display_precision(decimals: u8) -> usize {
+    decimals.min(8) as usize
+}
+
+/// Raw token amount with explicit units: (1_500_000, 6, "USDC") → "1.500000 USDC"
+pub fn format_token_amount(raw: U256, decimals: u8, symbol: &str) -> String {
+    format!("{:.*} {}", display_precision(decimals), raw_to_human(raw, decimals), symbol)
+}
+
+/// Signed raw token amount with explicit units: (-250_000, 6, "USDC") → "-0.250000 USDC"
+pub fn format_signed_token_amount(raw: i128, decimals: u8, symbol: &str) -> String {
+    let human = raw_to_human(U256::from(raw.unsigned_abs()), decimals);
+    let sign = if raw < 0 { "-" } else { "" };
+    format!("{}{:.*} {}", sign, display_precision(decimals), human, symbol)
+}
+
+/// USD amount for logs: 1234.5 → "$1234.50"
+pub fn format_usd(usd: f64) -> String {
+    if usd < 0.0 {
+        format!("-${:.2}", -usd)
+    } else {
+        format!("${:.2}", usd)
+    }
+}
+
+/// Raw token units -> human amount at the given decimals
+pub fn raw_to_human(raw: U256, decimals: u8) -> f64 {
+    raw.low_u128() as f64 / 10_f64.powi(decimals as i32)
 }
 
 /// Trade execution result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeResult {
     pub opportunity: String,
+    /// ArbitrageOpportunity::id of the attempt (0 = triangular or not tied to one)
+    #[serde(default)]
+    pub opportunity_id: u64,
     pub tx_hash: Option<String>,
     pub block_number: Option<u64>,
     pub success: bool,
@@ -424,14 +1193,16 @@ pub struct TradeResult {
     pub net_profit_usd: f64,
     pub execution_time_ms: u64,
     pub error: Option<String>,
-    /// Amount sent in raw token units
-    pub amount_in: Option<String>,
-    /// Amount received in raw token units
+    /// Quote amount sent (serialized as the raw decimal string)
+    #[serde(with = "quote_amount::serde_decimal_opt")]
+    pub amount_in: Option<QuoteAmount>,
+    /// Amount received in raw token units — quote token after a completed
+    /// round trip, base token when only the buy leg ran (legacy two-tx mode)
     pub amount_out: Option<String>,
 }
 
-/// Trading pair configuration (from env)
-#[derive(Debug, Clone, Deserialize)]
+/// Trading pair configuration (from env or chain TOML)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TradingPairConfig {
     pub token0: String, // Address as string
     pub token1: String,
@@ -460,6 +1231,9 @@ pub struct BotConfig {
 
     // Wallet
     pub private_key: String,
+    /// Every submission wallet key, primary (= private_key) first: PRIVATE_KEYS
+    /// (comma-separated) plus PRIVATE_KEY (arbitrage/wallets.rs)
+    pub private_keys: Vec<String>,
 
     // Trading parameters
     pub min_profit_usd: f64,
@@ -492,6 +1266,19 @@ pub struct BotConfig {
     pub quickswap_v3_router: Option<Address>,
     pub quickswap_v3_quoter: Option<Address>,
 
+    // KyberSwap Elastic addresses (detection-only - optional)
+    // Concentrated liquidity with getPoolState()/swapFeeUnits (1e-5) and a
+    // QuoterV2-style quoter; ArbExecutor has no Kyber leg, so never traded
+    pub kyber_elastic_factory: Option<Address>,
+    pub kyber_elastic_router: Option<Address>,
+    pub kyber_elastic_quoter: Option<Address>,
+
+    // DODO V2 addresses (optional). Swaps go through DODOV2Proxy02
+    // (dodoSwapV2TokenToToken → pool sellBase/sellQuote); the wallet approves
+    // DODOApprove, not the proxy. Legacy two-tx execution only.
+    pub dodo_v2_proxy: Option<Address>,
+    pub dodo_approve: Option<Address>,
+
     // Uniswap V3 Quoter version flag (multi-chain compatibility)
     // Polygon deploys QuoterV1 (flat params), Base deploys QuoterV2 (struct params).
     // When true, Uniswap V3 quoter calls use QuoterV2 ABI in both
@@ -509,6 +1296,9 @@ pub struct BotConfig {
     // Tax Logging (IRS Compliance)
     pub tax_log_dir: Option<String>,
     pub tax_log_enabled: bool,
+    /// TAX_LOG_STRICT: tax logging must work — startup failure is fatal and a
+    /// mid-run write failure halts live submissions until the record is written
+    pub tax_log_strict: bool,
 
     // Live trading mode (false = dry run/paper trading)
     pub live_mode: bool,
@@ -526,10 +1316,82 @@ pub struct BotConfig {
     pub price_log_enabled: bool,
     pub price_log_dir: Option<String>,
 
+    // Historical gas logging (research)
+    // Per-block base fee + feeHistory priority fee percentiles + our bids/landings
+    // to data/{chain}/research/gas_history.csv. One eth_feeHistory call per
+    // gas_history_sample_blocks blocks.
+    pub gas_history_enabled: bool,
+    pub gas_history_sample_blocks: u64,
+
+    // Isolated log writers (see io_writer.rs): price/gas history and tax records
+    // are written by dedicated threads behind bounded queues. Price batches may
+    // be dropped (price_log_queue_capacity); tax records never are — a full tax
+    // queue refuses after tax_enqueue_timeout_ms and the record is retried.
+    // Writes slower than io_write_timeout_ms are counted; longer in progress = stalled.
+    pub price_log_queue_capacity: usize,
+    pub tax_write_queue_capacity: usize,
+    pub tax_enqueue_timeout_ms: u64,
+    pub io_write_timeout_ms: u64,
+
+    // Multicall pre-screen export (research)
+    // One JSON line per pre-screened opportunity (estimate, Quoter verdict, both
+    // legs' pool state) to data/{chain}/research/prescreen_YYYYMMDD.jsonl.
+    pub prescreen_export_enabled: bool,
+
+    // Opportunity archive (research, see arbitrage/archive.rs)
+    // Every opportunity past the cooldown filter, its pre-screen verdict and an
+    // outcome label (landed / reverted / rejected, or expired once
+    // opportunity_archive_expiry_blocks pass unexecuted) to
+    // data/{chain}/research/opportunities_YYYYMMDD.jsonl, joined by opportunity id.
+    // Files older than opportunity_archive_retain_days are deleted (0 = keep all).
+    pub opportunity_archive_enabled: bool,
+    pub opportunity_archive_expiry_blocks: u64,
+    pub opportunity_archive_retain_days: u32,
+
+    // Execution-constraint analysis (research)
+    // Per block with 2+ verified opportunities: combinable (conflict-free) quoted
+    // profit vs realized, running unrealized total reported daily, rows to
+    // data/{chain}/research/execution_constraints.csv.
+    pub execution_constraints_enabled: bool,
+
+    // Watchdog (stall self-detection)
+    // Components beat a shared registry; the watchdog logs stale components every
+    // watchdog_check_secs, writes data/{chain}/heartbeat.json, and (if exit is
+    // enabled) exits with code 75 when the block loop has not beaten for
+    // watchdog_hard_limit_secs. watchdog_block_stall_secs is the block loop's
+    // allowed interval before it is reported stale (it beats per new block).
+    // A stale block loop is forced to reconnect up to watchdog_reconnect_attempts
+    // times, one stall interval apart, before the exit with code 76.
+    pub watchdog_enabled: bool,
+    pub watchdog_check_secs: u64,
+    pub watchdog_block_stall_secs: u64,
+    pub watchdog_hard_limit_secs: u64,
+    pub watchdog_exit_enabled: bool,
+    pub watchdog_reconnect_attempts: u32,
+
     // Atomic arbitrage executor contract (Phase: Atomic Execution)
     // When set, the bot executes both swap legs in a single atomic transaction
     // via the deployed ArbExecutor.sol contract. Reverts on loss.
     pub arb_executor_address: Option<Address>,
+    // The deployed ArbExecutor also has executeArbMulti (ordered leg array):
+    // live triangular routes are sent through it instead of being rejected.
+    pub arb_executor_multi: bool,
+    // Flashloan funding (default false): a flash-eligible route larger than
+    // the wallet's quote balance borrows its full size from the Aave V3 pool
+    // via ArbExecutor.executeArbFlash instead of being capped to the balance.
+    pub flashloan_enabled: bool,
+    // Aave V3 Pool the flashloan is taken from (required for flashloan funding)
+    pub aave_pool_address: Option<Address>,
+    // V2 flash swaps (default false): routes bought on a V2 pair take the base
+    // from the pair up front via ArbExecutor.executeArbFlashV2 and repay it
+    // from the sell leg — no quote token needed beyond gas.
+    pub flash_v2_enabled: bool,
+    // Permit2 (PERMIT2_ADDRESS, canonical 0x000000000022D473030F116dDEE9F6B43aC78BA3):
+    // wallet-funded atomic trades pull the quote token with a signed
+    // PermitTransferFrom (executeArbWithPermit) instead of an ArbExecutor
+    // allowance. Falls back to the allowance path when the deployed contract
+    // lacks executeArbWithPermit or the token isn't approved to Permit2.
+    pub permit2_address: Option<Address>,
 
     // Skip Multicall3 batch Quoter pre-screen (default false)
     // When true, detected opportunities bypass batch_verify() and go straight
@@ -537,12 +1399,75 @@ pub struct BotConfig {
     // Saves ~12ms per scan cycle by eliminating redundant on-chain verification.
     pub skip_multicall_prescreen: bool,
 
+    // Adaptive pre-screen skipping (see arbitrage/prescreen_policy.rs). With
+    // prescreen_skip_enabled, a route whose last prescreen_skip_streak executed
+    // trades all succeeded goes direct to the executor; every
+    // prescreen_reverify_every-th trade of such a route is pre-screened anyway.
+    // Any revert, rejection or pre-screen rejection makes pre-screening mandatory again.
+    pub prescreen_skip_enabled: bool,
+    pub prescreen_skip_streak: u32,
+    pub prescreen_reverify_every: u32,
+
+    // Burst triage (see arbitrage/burst.rs): when a block detects more than
+    // burst_threshold opportunities (0 = off), only those with executable spread
+    // >= burst_min_spread_percent are kept, at most burst_top_k per quote token
+    // silo. Triage ends after burst_exit_blocks consecutive calm blocks.
+    pub burst_threshold: usize,
+    pub burst_top_k: usize,
+    pub burst_min_spread_percent: f64,
+    pub burst_exit_blocks: u32,
+
+    // Shadow validation (see shadow.rs): "primary" publishes a per-block
+    // decisions feed; "follower" runs detection-only and diffs its decisions
+    // against the feed in shadow_feed_dir (default data/{chain}/shadow).
+    // Blocks one side never delivers within shadow_match_lag_blocks are dropped.
+    pub shadow_role: String,
+    pub shadow_feed_dir: Option<String>,
+    pub shadow_match_lag_blocks: u64,
+
+    // Strategy A/B shadow (see ab_shadow.rs): shadow_config = TOML of
+    // overrides for a second, never-executing detector in this process. Its
+    // decisions go to data/{chain}/shadow/decisions_YYYYMMDD.csv; blocks where
+    // it found at most shadow_verify_max routes get them Multicall-quoted
+    // (0 = never).
+    pub shadow_config: Option<String>,
+    pub shadow_verify_max: usize,
+
+    // Competition model (see arbitrage/competition.rs): per-route distributions
+    // of winning priority fees → win probability of our bid. When enabled the
+    // execution order is ranked by EV = p × profit − (1 − p) × unrefunded gas.
+    // Routes with fewer than competition_min_samples use the pooled distribution.
+    // competitor_addresses restricts lost-race discovery to known senders.
+    pub competition_model_enabled: bool,
+    pub competition_min_samples: usize,
+    pub competition_gas_refund_fraction: f64,
+    pub competitor_addresses: Vec<Address>,
+
+    // Triangular detection (OpportunityDetector::scan_triangular_opportunities):
+    // quote → A → B → quote cycles over three pools, same quote token at both
+    // ends. Adds a second pass over every pool per block, so off by default.
+    pub triangular_enabled: bool,
+
+    // Optimal trade sizing (see sizing.rs): replace the max-size trade_size with
+    // the profit-maximizing size before pre-screen. V2↔V2 routes solve the
+    // constant-product optimum from reserves; V3 routes quote a halving ladder
+    // of sizes in one Multicall3 batch.
+    pub optimal_sizing: bool,
+
     // Route cooldown: suppress failed routes for N blocks (escalating backoff).
     // After a route fails, it is suppressed for N blocks. On repeated failures,
     // cooldown escalates 5× per failure up to ~1800 blocks (~1 hr on Polygon).
     // Eliminates hammering of structurally dead spreads. Set to 0 to disable.
     // Default: 10 blocks (~20s on Polygon).
     pub route_cooldown_blocks: u64,
+    // Trade-size bucket boundaries in USD (ROUTE_COOLDOWN_SIZE_BUCKETS_USD="500,1500").
+    // A failure cools its own bucket and larger ones; smaller sizes stay tradeable.
+    // Empty (default) = one cooldown per route.
+    pub route_cooldown_size_buckets_usd: Vec<f64>,
+    // Mempool-triggered attempts (signals, mid-block re-scans) ignore cooldowns
+    // with fewer failures than this (ROUTE_COOLDOWN_MEMPOOL_BYPASS_FAILURES).
+    // Routes escalated to the cooldown cap stay blocked. 0 = no bypass. Default: 3.
+    pub route_cooldown_mempool_bypass_failures: u32,
 
     // Private RPC URL for transaction submission (Polygon Fastlane).
     // When set, atomic arb transactions are sent through this endpoint instead
@@ -551,6 +1476,16 @@ pub struct BotConfig {
     // Rollback: remove this env var to fall back to public mempool.
     pub private_rpc_url: Option<String>,
 
+    // Searcher bundle relay for mempool backruns (Polygon Fastlane). When set,
+    // execute_from_mempool pins the backrun behind its trigger tx via
+    // BUNDLE_RPC_METHOD; failures fall back to the raw send above.
+    pub bundle_rpc_url: Option<String>,
+    // Relay JSON-RPC method (default "pfl_addSearcherBundle")
+    pub bundle_rpc_method: String,
+    // JSON params template; placeholders {trigger_hash}, {trigger_raw},
+    // {backrun_raw}, {backrun_hash}, {block}, {txs} (default ["{trigger_hash}","{backrun_raw}"])
+    pub bundle_params_template: String,
+
     // A4 Mempool Monitor mode: "off", "observe", "execute"
     // observe: log pending DEX swaps to CSV, measure visibility + lead time
     // execute: submit backrun txs (Phase 3)
@@ -569,14 +1504,330 @@ pub struct BotConfig {
 
     // Native token price in USD (MATIC on Polygon, ETH on Base/Ethereum)
     // Used for gas cost calculations everywhere. Default 0.50 (MATIC).
-    // Set via NATIVE_TOKEN_PRICE_USD env var.
+    // Set via NATIVE_TOKEN_PRICE_USD env var. With a reference pool (below)
+    // it is only the fallback for the live price.
     pub native_token_price_usd: f64,
+    // Pair symbol of the synced pool the live native price is read from each
+    // block (default WMATIC/USDC on Polygon, WETH/USDC on Base/Ethereum).
+    // NATIVE_PRICE_PAIR="" keeps the static NATIVE_TOKEN_PRICE_USD.
+    pub native_price_pair: Option<String>,
 
     // Secondary quote token address (native USDC on Polygon).
     // When set, pools using either USDC variant are eligible for arbitrage.
     // Pools with different quote tokens are never compared against each other.
     // Polygon: USDC.e (primary) + native USDC (0x3c499c...) (secondary)
     pub quote_token_address_native: Option<Address>,
+
+    // USD price of the primary quote token (QUOTE_TOKEN_PRICE_USD, default 1.0).
+    // 1.0 for USDC/DAI silos, the ETH spot price for a WETH-quoted silo.
+    // Prices trade_size_usd, and through it the executor's minProfit, realized
+    // profit and the quote token's price in tax records.
+    // The native USDC variant is always $1.
+    pub quote_token_price_usd: f64,
+
+    // Single-call execution preflight (default true).
+    // Reads both leg quotes, wallet quote-token balance, allowance and both
+    // pools' slot0/reserves in ONE Multicall3 eth_call pinned to the current
+    // block, replacing the executor's separate pre-trade RPCs.
+    pub preflight_batch_enabled: bool,
+
+    // Canary mode for newly promoted pools (whitelist canary_until / canary_trades).
+    // Routes touching a canary pool are capped at canary_max_trade_size_usd and always
+    // multicall pre-screened. When the window closes the pool is auto-promoted if
+    // submitted >= canary_min_trades, win rate >= canary_min_win_rate and net P&L
+    // >= canary_min_net_profit_usd; otherwise it is demoted to "observation".
+    pub canary_max_trade_size_usd: f64,
+    pub canary_min_win_rate: f64,
+    pub canary_min_net_profit_usd: f64,
+    pub canary_min_trades: u32,
+
+    // Stranded-funds alarm: every stranded_check_interval_blocks blocks (0 = off)
+    // the ArbExecutor's balanceOf is read for every token in the pool universe in
+    // one Multicall3 call. Balances worth more than stranded_dust_usd are alerted
+    // (log + webhook + status JSON) once per incident; with auto_rescue the bot
+    // calls rescueTokens() to sweep them back to the wallet.
+    pub stranded_check_interval_blocks: u64,
+    pub stranded_dust_usd: f64,
+    pub auto_rescue: bool,
+    // Webhook for operational alerts (ALERT_WEBHOOK_URL, falls back to DISCORD_WEBHOOK)
+    pub alert_webhook_url: Option<String>,
+
+    // Pool migration detector: a pool whose liquidity (V3) or reserve depth (V2)
+    // stays below migration_collapse_ratio × its observed peak for
+    // migration_collapse_blocks blocks triggers a factory lookup for its
+    // (pair, fee). A different, healthy canonical address is written to
+    // pending_pool_changes.json and alerted; with auto_migrate the bot hot-swaps
+    // the pool in state, event sync and the whitelist. 0 blocks = disabled.
+    pub migration_collapse_blocks: u64,
+    pub migration_collapse_ratio: f64,
+    pub auto_migrate: bool,
+
+    // Token metadata re-validation: every token_revalidation_hours hours (0 = off)
+    // re-read decimals()/symbol() for every pool token; a change quarantines all
+    // routes on that token until the entry is removed from token_quarantine.json.
+    pub token_revalidation_hours: u64,
+
+    // Anomaly monitor: EWMA baselines of opportunities/hour, pre-screen pass
+    // rate, revert rate, median execution ms and net profit/hour, one sample per
+    // anomaly_window_secs. Deviations beyond anomaly_sigma std devs sustained for
+    // anomaly_min_duration_secs go to the alert dispatcher.
+    pub anomaly_monitor_enabled: bool,
+    pub anomaly_window_secs: u64,
+    pub anomaly_sigma: f64,
+    pub anomaly_min_duration_secs: u64,
+
+    // In-flight journal (live mode): every atomic/mempool tx is journaled
+    // (fsync'd) before sending and cleared once its receipt is processed.
+    // Startup resolves survivors; unmined ones are rebroadcast or discarded per
+    // inflight_recovery_policy ("resubmit" | "discard").
+    pub inflight_journal_enabled: bool,
+    pub inflight_recovery_policy: String,
+
+    // Execution-time liquidity guard (before pre-screen, see liquidity_guard.rs):
+    // both legs' recorded V3 liquidity must be >= min_pool_liquidity (per-chain
+    // default, per-pair overrides), and the trade may consume at most
+    // max_liquidity_fraction of either leg's in-range quote reserve
+    // (0 = no depth check). liquidity_guard_mode: "reject" | "downsize".
+    pub min_pool_liquidity: u128,
+    pub min_pool_liquidity_overrides: std::collections::HashMap<String, u128>,
+    pub max_liquidity_fraction: f64,
+    pub liquidity_guard_mode: String,
+
+    // Telemetry push (see telemetry.rs): off unless telemetry_enabled and an
+    // endpoint (http(s):// collector or file:// JSONL) is set. Snapshot every
+    // telemetry_interval_secs; addresses only with telemetry_include_addresses.
+    pub telemetry_enabled: bool,
+    pub telemetry_endpoint: Option<String>,
+    pub telemetry_interval_secs: u64,
+    pub telemetry_include_addresses: bool,
+
+    // Live state API (see api/): with api_port set and the `api` feature built,
+    // GET /pools, /opportunities, /status and a per-block /ws price push are
+    // served on api_bind:api_port (loopback by default — tunnel for remote use).
+    // The last api_recent_opportunities detections are kept for /opportunities.
+    pub api_port: Option<u16>,
+    pub api_bind: String,
+    pub api_recent_opportunities: usize,
+
+    // Dead-man's switch (see deadman.rs): with deadman_url set, it is pinged
+    // every deadman_interval_secs while every deadman_criteria check passes
+    // (heartbeat,block_age,breaker,tax). A failing bot goes silent — or pings
+    // deadman_fail_url when set — so the external monitor alarms.
+    // deadman_method: "get" (block/status as query) | "post" (text body).
+    pub deadman_url: Option<String>,
+    pub deadman_fail_url: Option<String>,
+    pub deadman_method: String,
+    pub deadman_interval_secs: u64,
+    pub deadman_max_block_age_secs: u64,
+    pub deadman_criteria: String,
+
+    // Realized slippage feedback (see arbitrage/slippage.rs). Per-route stats
+    // are always collected; with slippage_feedback_enabled, min_out uses
+    // p{slippage_feedback_percentile} of a route leg's recent realized slippage
+    // + slippage_feedback_buffer_percent once it has slippage_feedback_min_samples
+    // trades, never looser than max_slippage_percent.
+    pub slippage_feedback_enabled: bool,
+    pub slippage_feedback_min_samples: usize,
+    pub slippage_feedback_percentile: f64,
+    pub slippage_feedback_buffer_percent: f64,
+
+    // Trade record finality (see arbitrage/finality.rs): landed trades are
+    // provisional until confirmation_depth blocks deep, then final — or
+    // reversed if the tx vanished / moved blocks. 0 = final at first receipt.
+    pub confirmation_depth: u64,
+
+    // Exposure pacing per quote token (see arbitrage/exposure.rs): at dispatch a
+    // trade may use at most exposure_max_trade_fraction of the wallet's quote
+    // balance, and in-flight trades together at most exposure_max_inflight_fraction.
+    // Oversized trades are scaled down; rejected below exposure_min_notional_usd.
+    // exposure_overrides: quote symbol → (trade fraction, in-flight fraction).
+    pub exposure_pacing_enabled: bool,
+    pub exposure_max_trade_fraction: f64,
+    pub exposure_max_inflight_fraction: f64,
+    pub exposure_overrides: std::collections::HashMap<String, (f64, f64)>,
+    pub exposure_min_notional_usd: f64,
+
+    // Failure injection for incident-response rehearsal (see chaos.rs).
+    // Inert unless chaos_mode; chaos_config_file holds the fault rules (JSON).
+    pub chaos_mode: bool,
+    pub chaos_config_file: Option<String>,
+
+    // Per-route P&L statistics (see arbitrage/stats.rs): every trade result is
+    // always recorded; the table is logged and data/{chain}/stats.json rewritten
+    // every stats_report_interval iterations (0 = only at shutdown).
+    pub stats_report_interval: u64,
+
+    // Per-stage latency histograms (see latency.rs): always recorded; the table
+    // is logged and data/{chain}/latency.json rewritten every
+    // latency_report_interval iterations (0 = only at shutdown).
+    pub latency_report_interval: u64,
+
+    // Reorg detection (see pool/reorg.rs): hashes of the last reorg_track_blocks
+    // processed headers are kept; a parent-hash mismatch marks pools updated in
+    // the orphaned range stale until a poll resync (also bounds the walk-back).
+    pub reorg_track_blocks: usize,
+
+    // Live alerts to alert_webhook_url (see live_alerts.rs), per category:
+    // trades (successes + atomic reverts), halts (receipt timeout, legacy
+    // capital committed), ws (reconnects exhausted), daily summary, canary
+    // (pool promoted / demoted). Repeats of one alert (category + route) are
+    // muted for alert_rate_limit_secs.
+    pub alert_trades: bool,
+    pub alert_halts: bool,
+    pub alert_ws: bool,
+    pub alert_daily_summary: bool,
+    pub alert_canary: bool,
+    pub alert_rate_limit_secs: u64,
+
+    // Balance clamping (see arbitrage/balance.rs): before exposure pacing, a
+    // live trade is clamped to balance_usable_fraction of the wallet's quote
+    // token balance, or skipped when that balance is below min_quote_balance_usd.
+    pub balance_clamp_enabled: bool,
+    pub balance_usable_fraction: f64,
+    pub min_quote_balance_usd: f64,
+
+    // Curve StableSwap pools (see pool/curve_syncer.rs): sync whitelisted
+    // "curve_ready" pools and compare them with V2/V3 pools of the same
+    // stablecoin pair. Detection-only — Curve routes are logged, not executed.
+    pub curve_enabled: bool,
+
+    // Tax lot selection (see tax/lots.rs): "fifo" | "lifo" | "hifo" | "specid".
+    // Disposals take their cost basis from the selected acquisition lots and
+    // are split into one tax record per lot.
+    pub tax_lot_method: String,
+
+    // Multicall3 contract (see pool/multicall_syncer.rs). When set, poll sync
+    // reads every V2 + V3 pool in one aggregate3 call per block, and the
+    // pre-screen quoter uses this address instead of the canonical deployment.
+    pub multicall3_address: Option<Address>,
+
+    // Gas oracle (see gas_oracle.rs): atomic priority fee = recent blocks'
+    // GAS_ORACLE_PERCENTILE fee + GAS_ORACLE_BUMP_PERCENT, clamped to
+    // [MIN_PRIORITY_GWEI, MAX_PRIORITY_GWEI]. Off or without fee data the
+    // static ATOMIC_PRIORITY_FEE_GWEI is bid (still clamped).
+    pub gas_oracle_enabled: bool,
+    pub gas_oracle_percentile: f64,
+    pub gas_oracle_bump_percent: f64,
+    // Blocks of fee history kept / blocks between eth_feeHistory calls
+    pub gas_oracle_window_blocks: u64,
+    pub gas_oracle_sample_blocks: u64,
+    pub min_priority_gwei: u64,
+    pub max_priority_gwei: u64,
+
+    // Pool health (see pool/health.rs): pools idle for max_idle_blocks, with
+    // V3 liquidity / V2 TVL under the floors, or with max_quoter_rejections
+    // consecutive pre-screen rejections are quarantined from detection until
+    // they recover (rejections: for rejection_quarantine_blocks). 0 = check off.
+    pub pool_health_enabled: bool,
+    pub pool_health_max_idle_blocks: u64,
+    pub pool_health_min_v3_liquidity: u128,
+    pub pool_health_min_v2_tvl_usd: f64,
+    pub pool_health_max_quoter_rejections: u32,
+    pub pool_health_rejection_quarantine_blocks: u64,
+
+    // Same-block parallel submission (see arbitrage/parallel.rs): up to this many
+    // verified opportunities on disjoint pools go out back-to-back on
+    // consecutive nonces. 1 = serial execution.
+    pub max_parallel_submissions: usize,
+
+    // Cross-chain divergence monitor (see cross_chain.rs): another chain's data
+    // collector state file, compared every block. Pairs diverging by at least
+    // cross_chain_divergence_bps are logged; a remote file older than
+    // cross_chain_max_age_secs is skipped. None = off.
+    pub cross_chain_state_file: Option<String>,
+    pub cross_chain_divergence_bps: f64,
+    pub cross_chain_max_age_secs: u64,
+
+    // Log format (see events.rs): "text" (default) or "json". json switches
+    // tracing to one JSON object per line and enables the event journal
+    // (data/{chain}/events_YYYYMMDD.jsonl).
+    pub log_format: String,
+
+    // Mempool execute-threshold calibration (mempool/calibration.rs): a signal
+    // needs net spread ≥ MEMPOOL_MIN_SPREAD_PCT + k × the trigger pool's rolling
+    // simulation error. Prior (bps) applies until a route has enough validations.
+    pub mempool_calibration_k: f64,
+    pub mempool_calibration_prior_bps: f64,
+
+    // Gas estimate cache (arbitrage/gas_cache.rs): atomic sends reuse the route's
+    // largest recent receipt gas_used × multiplier instead of estimateGas.
+    // Persisted to data/{chain}/gas_cache.json; dropped per route on revert.
+    pub gas_cache_enabled: bool,
+    pub gas_cache_multiplier: f64,
+
+    // Stale-state guard: opportunities with a leg whose pool state is more than
+    // MAX_POOL_AGE_BLOCKS behind the head are dropped before execution; the
+    // executor refuses opportunities older than MAX_OPPORTUNITY_AGE_SECS
+    // (block and mempool paths). 0 disables either check.
+    pub max_pool_age_blocks: u64,
+    pub max_opportunity_age_secs: u64,
+
+    // Spread confirmation (detector.rs SpreadHistory): an opportunity is only
+    // emitted once its route's spread has persisted for SPREAD_CONFIRM_BLOCKS
+    // consecutive blocks (1 = every block, no confirmation). Routes are
+    // remembered for SPREAD_HISTORY_BLOCKS blocks after their last spread.
+    pub spread_confirm_blocks: u64,
+    pub spread_history_blocks: u64,
+
+    // Opportunity registry (arbitrage/registry.rs): a submission for (pair, buy
+    // pool, sell pool) suppresses equivalent opportunities from the block loop
+    // and the mempool path for OPPORTUNITY_DEDUP_TTL_BLOCKS blocks. 0 disables.
+    pub opportunity_dedup_ttl_blocks: u64,
+
+    // Inventory manager (arbitrage/inventory.rs): every
+    // inventory_check_interval_blocks blocks (0 = off) the wallet's balances of
+    // the pool universe are read; a non-quote token worth more than
+    // inventory_rebalance_threshold_usd is alerted with a suggested swap into
+    // the quote token. With auto_rebalance (live only) the swap is executed.
+    pub inventory_check_interval_blocks: u64,
+    pub inventory_rebalance_threshold_usd: f64,
+    pub auto_rebalance: bool,
+
+    // WS failover (provider_manager.rs): RPC_URLS = comma-separated fallback
+    // endpoints. The block subscription and sync provider rotate through
+    // rpc_url followed by these (see ws_endpoints()).
+    pub rpc_urls: Vec<String>,
+    // RPC_URL may be a local node's IPC socket (`/…` or `ipc://…`, transport.rs).
+    // WS_RPC_URL is then the WS endpoint for the block subscription and the
+    // mempool monitor, and the read fallback while the socket is down.
+    pub ws_rpc_url: Option<String>,
+
+    // Split routing (arbitrage/split.rs): when the best sell pool loses more
+    // than max_slippage_percent to price impact at the full size, the detector
+    // also emits the route with the sell side split across the two best sell
+    // pools (ArbExecutor.executeArbSplit — the deployed contract must have it)
+    pub split_routing: bool,
+
+    // Price log output (see price_logger.rs): "csv" | "parquet" (cargo feature
+    // `parquet`, else CSV with a warning). Closed CSV days are gzipped when
+    // price_log_compress; files older than price_log_retain_days are deleted
+    // (0 = keep all). Both run on a maintenance thread, never the block loop.
+    pub price_log_format: String,
+    pub price_log_compress: bool,
+    pub price_log_retain_days: u32,
+    // Companion blocks_YYYYMMDD.csv: per-block base fee, detected / cooldown-
+    // suppressed / submitted counts and realized net profit (research joins)
+    pub price_log_blocks: bool,
+
+    // Adaptive minimum profit (see arbitrage/threshold.rs): each route's
+    // minimum becomes MIN_PROFIT_USD + r/(1-r) × avg gas, r = its recent
+    // on-chain revert rate, clamped to [min_profit_floor_usd, min_profit_ceiling_usd]
+    pub min_profit_adaptive: bool,
+    pub min_profit_floor_usd: f64,
+    pub min_profit_ceiling_usd: f64,
+
+    // Event-driven sync (EVENT_SYNC): the Swap event doesn't carry Algebra's
+    // dynamic fee, so QuickSwap V3 pools get a globalState() fee read every
+    // algebra_fee_refresh_blocks blocks (0 = never; poll sync reads it every block)
+    pub algebra_fee_refresh_blocks: u64,
+
+    // Mid-block re-scan (mempool/midblock.rs): each pending swap the mempool
+    // monitor simulates is applied to a shadow copy of pool state and the
+    // detector re-runs against it; the resulting "anticipatory" opportunities
+    // still pass the multicall pre-screen. At most midblock_scan_max_per_block
+    // re-scans per block. Needs MEMPOOL_MONITOR=observe|execute.
+    pub midblock_scan: bool,
+    pub midblock_scan_max_per_block: u32,
 }
 
 impl BotConfig {
@@ -586,6 +1837,49 @@ impl BotConfig {
         *addr == self.quote_token_address
             || self.quote_token_address_native.map_or(false, |a| a == *addr)
     }
+
+    /// DODO V2 routes are tradeable: swaps go to DODO_V2_PROXY and pull
+    /// through DODO_APPROVE, so both must be set
+    pub fn dodo_configured(&self) -> bool {
+        self.dodo_v2_proxy.is_some() && self.dodo_approve.is_some()
+    }
+
+    /// USD price of a quote token (trade_size_usd, minProfit and profit conversions)
+    pub fn quote_price_usd(&self, addr: &Address) -> f64 {
+        if *addr == self.quote_token_address {
+            self.quote_token_price_usd
+        } else {
+            1.0
+        }
+    }
+
+    /// Symbol of the primary quote token: the QUOTE side of the first
+    /// configured BASE/QUOTE pair holding quote_token_address
+    pub fn quote_token_symbol(&self) -> Option<&str> {
+        let is_quote = |token: &String| token.parse::<Address>().ok() == Some(self.quote_token_address);
+        self.pairs
+            .iter()
+            .find(|pair| is_quote(&pair.token0) || is_quote(&pair.token1))
+            .and_then(|pair| pair.symbol.split('/').nth(1))
+    }
+
+    /// WS endpoint for pending-tx subscriptions: rpc_url, or WS_RPC_URL when
+    /// rpc_url is an IPC socket
+    pub fn subscription_url(&self) -> &str {
+        crate::transport::subscription_url(&self.rpc_url, self.ws_rpc_url.as_deref()).unwrap_or(&self.rpc_url)
+    }
+
+    /// Endpoints in failover order: rpc_url, then RPC_URLS without repeats
+    /// (WS, or an IPC socket — see transport::ipc_path)
+    pub fn ws_endpoints(&self) -> Vec<String> {
+        let mut urls = vec![self.rpc_url.clone()];
+        for url in &self.rpc_urls {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        urls
+    }
 }
 
 #[cfg(test)]
@@ -627,4 +1921,141 @@ mod tests {
         assert_eq!(DexType::UniswapV3_005.v3_fee_tier(), Some(500));
         assert_eq!(DexType::QuickswapV3.v3_fee_tier(), Some(0));
     }
+
+    #[test]
+    fn test_kyber_elastic_is_v3_and_detection_only() {
+        let kyber = DexType::KyberElastic_004;
+        assert!(kyber.is_v3() && kyber.is_kyber_elastic() && kyber.is_detection_only());
+        assert_eq!((kyber.v3_fee_tier(), kyber.v3_fee_bps(), kyber.fee_percent()), (Some(400), Some(4), Some(0.04)));
+        assert_eq!(kyber.to_string(), "KyberElastic_0.04%");
+        assert!(DexType::CurveStable.is_detection_only());
+        assert!(!DexType::UniswapV3_005.is_detection_only());
+    }
+
+    #[test]
+    fn test_v2_snapshot_matches_pool_and_round_trips() {
+        let pool = PoolState {
+            address: Address::from_low_u64_be(7),
+            dex: DexType::QuickSwapV2,
+            pair: TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), "WETH/USDC".to_string()),
+            reserve0: U256::from(2_500_000_000_000u64),
+            // Above u128 — must not be truncated on the way through JSON
+            reserve1: U256::from(u128::MAX) * U256::from(3u64),
+            last_updated: 42,
+            token0_decimals: 6,
+            token1_decimals: 18,
+        };
+        let snap = PoolStateSnapshot::from_v2(&pool, 1_700_000_000);
+        assert_eq!(snap.address, pool.address);
+        assert_eq!(snap.fee, 3000);
+        assert_eq!(snap.block, 42);
+        assert_eq!(
+            snap.state,
+            PoolStateKind::V2 { reserve0: pool.reserve0, reserve1: pool.reserve1 }
+        );
+
+        let json = serde_json::to_string(&snap).unwrap();
+        let back: PoolStateSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, snap);
+    }
+
+    #[test]
+    fn test_raw_conversions_8_decimals() {
+        assert_eq!(usd_to_raw(0.25, 8), U256::from(25_000_000u64));
+        assert_eq!(usd_to_raw(1.0, 6), U256::from(1_000_000u64));
+        // 18-decimal amounts above u64::MAX raw must not saturate
+        assert_eq!(usd_to_raw(100.0, 18), U256::from(100_000_000_000_000_000_000u128));
+        assert_eq!(raw_to_human(U256::from(12_345_678u64), 8), 0.12345678);
+        assert_eq!(usd_to_raw(-1.0, 6), U256::zero());
+    }
+
+    #[test]
+    fn test_quote_orientation_wbtc_usdc() {
+        // Polygon WBTC (0x1bfd...) < USDC.e (0x2791...) → WBTC=token0(8), USDC=token1(6)
+        let mut opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::zero(), Address::zero(), "WBTC/USDC".to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            80_000.0,
+            80_800.0,
+            U256::from(500_000_000u64),
+        );
+        // Unsized until the layout is known: 5e8 raw is not $5e-10 of an 18-decimal token
+        assert_eq!(opp.trade_size_usd, 0.0);
+        opp.set_quote_layout(8, 6, false);
+        assert_eq!(opp.trade_size_usd, 500.0);
+
+        assert_eq!(opp.quote_decimals(), 6);
+        assert_eq!(opp.base_decimals(), 8);
+        assert_eq!(opp.buy_leg_price(), 1.0 / 80_000.0);
+        assert_eq!(opp.sell_leg_price(), 80_800.0);
+        assert_eq!(opp.usd_to_quote(0.05).raw(), U256::from(50_000u64));
+        assert_eq!(opp.quote_to_usd(opp.quote_amount(U256::from(4_210_000u64))), 4.21);
+        assert_eq!(opp.trade_size.to_string(), "500.000000 USDC");
+
+        // Serialized form is still the bare U256; the token is rebound on load
+        let json = serde_json::to_value(&opp).unwrap();
+        assert_eq!(json["trade_size"], "0x1dcd6500");
+        let back: ArbitrageOpportunity = serde_json::from_value(json).unwrap();
+        assert_eq!(back.trade_size, opp.trade_size);
+        assert_eq!(back.trade_size.token(), opp.quote());
+    }
+
+    fn sized_opp(symbol: &str, quote_decimals: u8, trade_size: U256) -> ArbitrageOpportunity {
+        let mut opp = ArbitrageOpportunity::new(
+            TradingPair::new(Address::from_low_u64_be(1), Address::from_low_u64_be(2), symbol.to_string()),
+            DexType::UniswapV3_005,
+            DexType::UniswapV3_030,
+            1.0,
+            1.01,
+            trade_size,
+        );
+        opp.set_quote_layout(18, quote_decimals, false);
+        opp
+    }
+
+    #[test]
+    fn test_trade_size_usd_per_quote_type() {
+        // USDC.e / native USDC: 6 decimals at $1
+        let mut opp = sized_opp("WETH/USDC", 6, U256::from(500_000_000u64));
+        opp.normalize_trade_size(1.0);
+        assert_eq!(opp.trade_size_usd, 500.0);
+
+        // DAI: 18 decimals — the 1e18 raw value must not read as $1e12
+        let mut opp = sized_opp("WETH/DAI", 18, U256::from(250_000_000_000_000_000_000u128));
+        opp.normalize_trade_size(1.0);
+        assert_eq!(opp.trade_size_usd, 250.0);
+
+        // Non-stable quote (WETH at $3000): 0.1 WETH → $300
+        let mut opp = sized_opp("WBTC/WETH", 18, U256::from(100_000_000_000_000_000u128));
+        opp.normalize_trade_size(3000.0);
+        assert!((opp.trade_size_usd - 300.0).abs() < 1e-9);
+        // USD conversions follow the price it was sized at, through a relayout
+        assert!((opp.quote_price_usd() - 3000.0).abs() < 1e-9);
+        assert_eq!(opp.usd_to_quote(3.0).raw(), U256::from(1_000_000_000_000_000u128));
+        opp.set_quote_layout(18, 18, true);
+        assert!((opp.trade_size_usd - 300.0).abs() < 1e-9);
+        assert!((opp.quote_to_usd(opp.quote_amount(U256::exp10(16))) - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_amount_formatting_has_units() {
+        assert_eq!(format_token_amount(U256::from(1_500_000u64), 6, "USDC"), "1.500000 USDC");
+        assert_eq!(format_token_amount(U256::from(625_000u64), 8, "WBTC"), "0.00625000 WBTC");
+        // 18-decimal tokens cap at 8 display places
+        assert_eq!(
+            format_token_amount(U256::from(1_234_567_890_000_000_000u128), 18, "WETH"),
+            "1.23456789 WETH"
+        );
+        assert_eq!(format_signed_token_amount(-250_000, 6, "USDC"), "-0.250000 USDC");
+        assert_eq!(format_signed_token_amount(250_000, 6, "USDC"), "0.250000 USDC");
+        assert_eq!(format_usd(1234.5), "$1234.50");
+        assert_eq!(format_usd(-0.25), "-$0.25");
+
+        let mut opp = sized_opp("WETH/USDC", 6, U256::from(500_000_000u64));
+        opp.normalize_trade_size(1.0);
+        assert_eq!(opp.size_display(), "$500.00 (500.000000 USDC)");
+        assert_eq!(opp.quote_delta_display(-1_000_000), "-1.000000 USDC");
+        assert_eq!(opp.base_amount_display(U256::from(10u64).pow(U256::from(17u64))), "0.10000000 WETH");
+    }
 }
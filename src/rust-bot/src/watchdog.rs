@@ -0,0 +1,668 @@
+//! Watchdog — Heartbeats and Stall Self-Detection
+//!
+//! The WS block timeout catches a dead subscription, but a deadlocked mutex,
+//! a hung RPC call or a blocked channel leaves the process alive and idle,
+//! and the supervisor only restarts on exit. Each long-lived component
+//! touches a `Heartbeat`; a watchdog thread checks them every few seconds:
+//!
+//! - logs when a component goes stale (age > its allowed interval) and when it recovers
+//! - writes a heartbeat file (JSON, per-component age) for external monitors
+//! - optionally exits with `WATCHDOG_EXIT_CODE` when a critical component
+//!   (the block loop) is stale past the hard limit, so the supervisor restarts us
+//!
+//! The block loop beats only when it processes a new block, so a WS zombie
+//! that still delivers mempool signals, or a wedged detector, goes stale too.
+//! A stale critical component first gets forced reconnects: the watchdog
+//! logs CRITICAL and raises the registry's reconnect flag, which breaks the
+//! block loop back to its reconnect path. Another stall interval without a
+//! block → the next attempt; once WATCHDOG_RECONNECT_ATTEMPTS are spent the
+//! process exits with `WATCHDOG_ESCALATE_EXIT_CODE` (exit enabled), telling
+//! the supervisor a plain restart-by-reconnect did not help.
+//!
+//! The block loop also writes the heartbeat file itself on every new block,
+//! with last block, pools synced and last trade time (LoopProgress). Writers
+//! go through a unique temp file + rename, so readers see a whole file.
+//!
+//! The watchdog runs on a dedicated OS thread, not the tokio runtime, so a
+//! stalled runtime worker cannot also stall the watchdog.
+//!
+//! `rpc_timeout()` bounds hot-path RPC calls so a hung request surfaces as an
+//! error instead of a silent stall.
+//!
+//! Author: AI-Generated
+//! Created: 2026-10-16
+//! Modified: 2026-10-16 - Forced reconnects before exit (StallEscalation); LoopProgress in the heartbeat file
+
+use crate::types::{BotConfig, TradeResult};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+
+/// Process exit code when the watchdog kills a stalled process (EX_TEMPFAIL)
+pub const WATCHDOG_EXIT_CODE: i32 = 75;
+
+/// Process exit code when forced reconnects did not bring the block loop
+/// back (EX_PROTOCOL) — distinct so the supervisor can escalate
+pub const WATCHDOG_ESCALATE_EXIT_CODE: i32 = 76;
+
+/// Upper bound for a single hot-path RPC call
+pub const HOT_RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Await `fut`, failing with "<label> timed out after Ns" past `HOT_RPC_TIMEOUT`
+pub async fn rpc_timeout<T, E, F>(label: &str, fut: F) -> Result<T>
+where
+    E: Display,
+    F: Future<Output = std::result::Result<T, E>>,
+{
+    match tokio::time::timeout(HOT_RPC_TIMEOUT, fut).await {
+        Ok(Ok(v)) => Ok(v),
+        Ok(Err(e)) => Err(anyhow!("{}", e)),
+        Err(_) => Err(anyhow!("{} timed out after {}s", label, HOT_RPC_TIMEOUT.as_secs())),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Registry
+// ---------------------------------------------------------------------------
+
+struct Component {
+    max_interval: Duration,
+    critical: bool,
+    /// Milliseconds since the registry epoch at the last beat
+    last_beat_ms: AtomicU64,
+    beats: AtomicU64,
+}
+
+/// Handle a component uses to report liveness (cheap to clone and call)
+#[derive(Clone)]
+pub struct Heartbeat {
+    epoch: Instant,
+    component: Arc<Component>,
+}
+
+impl Heartbeat {
+    pub fn beat(&self) {
+        let ms = self.epoch.elapsed().as_millis() as u64;
+        self.component.last_beat_ms.store(ms, Ordering::Relaxed);
+        self.component.beats.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Freshness of one component at a check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentHealth {
+    pub name: &'static str,
+    pub age_ms: u64,
+    pub max_interval_ms: u64,
+    pub critical: bool,
+    pub stale: bool,
+    pub beats: u64,
+}
+
+/// Block loop progress for the heartbeat file
+#[derive(Default)]
+pub struct LoopProgress {
+    last_block: AtomicU64,
+    pools_synced: AtomicU64,
+    /// Unix seconds of the last trade sent on chain (0 = none yet)
+    last_trade: AtomicU64,
+}
+
+/// LoopProgress as written to the heartbeat file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ProgressSnapshot {
+    pub last_block: u64,
+    pub pools_synced: u64,
+    pub last_trade: Option<u64>,
+}
+
+impl LoopProgress {
+    pub fn record_block(&self, block: u64, pools_synced: usize) {
+        self.last_block.store(block, Ordering::Relaxed);
+        self.pools_synced.store(pools_synced as u64, Ordering::Relaxed);
+    }
+
+    /// Stamp the last trade time if `result` sent a tx
+    pub fn record_trade(&self, result: &TradeResult) {
+        if result.tx_hash.is_some() {
+            self.last_trade.store(chrono::Utc::now().timestamp().max(0) as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        let last_trade = self.last_trade.load(Ordering::Relaxed);
+        ProgressSnapshot {
+            last_block: self.last_block.load(Ordering::Relaxed),
+            pools_synced: self.pools_synced.load(Ordering::Relaxed),
+            last_trade: (last_trade > 0).then_some(last_trade),
+        }
+    }
+}
+
+/// Named heartbeats, checked by the watchdog
+pub struct HeartbeatRegistry {
+    epoch: Instant,
+    components: Mutex<BTreeMap<&'static str, Arc<Component>>>,
+    progress: LoopProgress,
+    reconnect: AtomicBool,
+    reconnect_notify: Notify,
+}
+
+impl Default for HeartbeatRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeartbeatRegistry {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            components: Mutex::new(BTreeMap::new()),
+            progress: LoopProgress::default(),
+            reconnect: AtomicBool::new(false),
+            reconnect_notify: Notify::new(),
+        }
+    }
+
+    pub fn progress(&self) -> &LoopProgress {
+        &self.progress
+    }
+
+    /// Ask the block loop to drop its subscription and reconnect
+    pub fn request_reconnect(&self) {
+        self.reconnect.store(true, Ordering::SeqCst);
+        self.reconnect_notify.notify_one();
+    }
+
+    /// Clear and return a pending reconnect request
+    pub fn take_reconnect(&self) -> bool {
+        self.reconnect.swap(false, Ordering::SeqCst)
+    }
+
+    /// Resolves (and clears the flag) once a reconnect is requested
+    pub async fn reconnect_requested(&self) {
+        loop {
+            if self.take_reconnect() {
+                return;
+            }
+            self.reconnect_notify.notified().await;
+        }
+    }
+
+    /// Register (or re-register) `name` and beat once.
+    /// Re-registering keeps the counters and updates interval/criticality.
+    pub fn register(&self, name: &'static str, max_interval: Duration, critical: bool) -> Heartbeat {
+        let mut components = self.components.lock().unwrap_or_else(|e| e.into_inner());
+        let existing = components.get(name).filter(|c| c.max_interval == max_interval && c.critical == critical);
+        let component = match existing {
+            Some(c) => c.clone(),
+            None => {
+                let beats = components.get(name).map_or(0, |c| c.beats.load(Ordering::Relaxed));
+                let c = Arc::new(Component {
+                    max_interval,
+                    critical,
+                    last_beat_ms: AtomicU64::new(0),
+                    beats: AtomicU64::new(beats),
+                });
+                components.insert(name, c.clone());
+                c
+            }
+        };
+        let heartbeat = Heartbeat { epoch: self.epoch, component };
+        heartbeat.beat();
+        heartbeat
+    }
+
+    /// Freshness of every component as of `now`
+    pub fn check_at(&self, now: Instant) -> Vec<ComponentHealth> {
+        let now_ms = now.saturating_duration_since(self.epoch).as_millis() as u64;
+        let components = self.components.lock().unwrap_or_else(|e| e.into_inner());
+        components
+            .iter()
+            .map(|(name, c)| {
+                let age_ms = now_ms.saturating_sub(c.last_beat_ms.load(Ordering::Relaxed));
+                let max_interval_ms = c.max_interval.as_millis() as u64;
+                ComponentHealth {
+                    name,
+                    age_ms,
+                    max_interval_ms,
+                    critical: c.critical,
+                    stale: age_ms > max_interval_ms,
+                    beats: c.beats.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+
+    pub fn check(&self) -> Vec<ComponentHealth> {
+        self.check_at(Instant::now())
+    }
+}
+
+/// Process-wide registry
+pub fn registry() -> &'static HeartbeatRegistry {
+    static REGISTRY: OnceLock<HeartbeatRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(HeartbeatRegistry::new)
+}
+
+/// Register `name` in the process-wide registry
+pub fn heartbeat(name: &'static str, max_interval: Duration, critical: bool) -> Heartbeat {
+    registry().register(name, max_interval, critical)
+}
+
+/// The critical component to exit for, if exiting is enabled and one is
+/// stale past `hard_limit`. Non-critical components never trigger an exit.
+pub fn exit_decision(
+    health: &[ComponentHealth],
+    hard_limit: Duration,
+    exit_enabled: bool,
+) -> Option<&ComponentHealth> {
+    if !exit_enabled {
+        return None;
+    }
+    let limit_ms = hard_limit.as_millis() as u64;
+    health.iter().find(|h| h.critical && h.age_ms > limit_ms)
+}
+
+/// What the watchdog does about a stale critical component at one check
+#[derive(Debug, Clone, PartialEq)]
+pub enum StallAction {
+    None,
+    /// Raise the reconnect flag (attempt counts from 1)
+    Reconnect { component: &'static str, attempt: u32 },
+    /// Forced reconnects exhausted
+    Escalate { component: &'static str, attempts: u32 },
+}
+
+/// Forced-reconnect ladder for a stale critical component: one attempt as
+/// soon as it goes stale, the next after another of its stall intervals
+/// without a beat, escalation once `max_reconnects` have not helped.
+/// A fresh beat resets the ladder.
+#[derive(Debug, Clone)]
+pub struct StallEscalation {
+    max_reconnects: u32,
+    attempts: u32,
+    last_attempt: Option<Instant>,
+}
+
+impl StallEscalation {
+    pub fn new(max_reconnects: u32) -> Self {
+        Self { max_reconnects, attempts: 0, last_attempt: None }
+    }
+
+    pub fn step(&mut self, health: &[ComponentHealth], now: Instant) -> StallAction {
+        let Some(stalled) = health.iter().find(|h| h.critical && h.stale) else {
+            self.attempts = 0;
+            self.last_attempt = None;
+            return StallAction::None;
+        };
+        let interval = Duration::from_millis(stalled.max_interval_ms);
+        if self.last_attempt.is_some_and(|at| now.saturating_duration_since(at) < interval) {
+            return StallAction::None;
+        }
+        if self.attempts >= self.max_reconnects {
+            return StallAction::Escalate { component: stalled.name, attempts: self.attempts };
+        }
+        self.attempts += 1;
+        self.last_attempt = Some(now);
+        StallAction::Reconnect { component: stalled.name, attempt: self.attempts }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Watchdog thread
+// ---------------------------------------------------------------------------
+
+/// Watchdog settings (from BotConfig)
+#[derive(Debug, Clone)]
+pub struct WatchdogConfig {
+    pub check_interval: Duration,
+    pub hard_limit: Duration,
+    pub exit_enabled: bool,
+    pub heartbeat_file: Option<PathBuf>,
+    /// Forced reconnects before escalating (WATCHDOG_RECONNECT_ATTEMPTS)
+    pub max_reconnects: u32,
+}
+
+impl WatchdogConfig {
+    pub fn from_bot_config(config: &BotConfig, heartbeat_file: Option<PathBuf>) -> Self {
+        Self {
+            check_interval: Duration::from_secs(config.watchdog_check_secs.max(1)),
+            hard_limit: Duration::from_secs(config.watchdog_hard_limit_secs),
+            exit_enabled: config.watchdog_exit_enabled,
+            heartbeat_file,
+            max_reconnects: config.watchdog_reconnect_attempts,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HeartbeatFile<'a> {
+    timestamp: String,
+    pid: u32,
+    healthy: bool,
+    #[serde(flatten)]
+    progress: ProgressSnapshot,
+    components: &'a [ComponentHealth],
+}
+
+/// Write the heartbeat file atomically (tmp + rename). The temp name is
+/// unique per write, so the watchdog thread and the block loop never share one.
+pub fn write_heartbeat_file(path: &PathBuf, health: &[ComponentHealth], progress: ProgressSnapshot) -> Result<()> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let body = HeartbeatFile {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        pid: std::process::id(),
+        healthy: health.iter().all(|h| !h.stale),
+        progress,
+        components: health,
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension(format!(
+        "json.{}.{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&tmp, serde_json::to_string_pretty(&body)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Block loop: heartbeat file from the process-wide registry
+pub fn write_heartbeat(path: &PathBuf) -> Result<()> {
+    let registry = registry();
+    write_heartbeat_file(path, &registry.check(), registry.progress().snapshot())
+}
+
+/// Start the watchdog thread over `registry`
+pub fn spawn(registry: &'static HeartbeatRegistry, config: WatchdogConfig) -> std::io::Result<()> {
+    info!(
+        "Watchdog: checking every {}s, hard limit {}s, {} forced reconnects (exit {})",
+        config.check_interval.as_secs(),
+        config.hard_limit.as_secs(),
+        config.max_reconnects,
+        if config.exit_enabled { "ENABLED" } else { "disabled" }
+    );
+    std::thread::Builder::new()
+        .name("watchdog".to_string())
+        .spawn(move || {
+            let mut stale: BTreeSet<&'static str> = BTreeSet::new();
+            let mut escalation = StallEscalation::new(config.max_reconnects);
+            loop {
+                std::thread::sleep(config.check_interval);
+                let health = registry.check();
+
+                for h in &health {
+                    if h.stale && stale.insert(h.name) {
+                        warn!(
+                            "Watchdog: {} STALE — no heartbeat for {:.1}s (allowed {:.1}s){}",
+                            h.name,
+                            h.age_ms as f64 / 1000.0,
+                            h.max_interval_ms as f64 / 1000.0,
+                            if h.critical { " [critical]" } else { "" }
+                        );
+                    } else if !h.stale && stale.remove(h.name) {
+                        info!("Watchdog: {} recovered", h.name);
+                    }
+                }
+
+                if let Some(ref path) = config.heartbeat_file {
+                    if let Err(e) = write_heartbeat_file(path, &health, registry.progress().snapshot()) {
+                        warn!("Watchdog: heartbeat file write failed: {}", e);
+                    }
+                }
+
+                match escalation.step(&health, Instant::now()) {
+                    StallAction::None => {}
+                    StallAction::Reconnect { component, attempt } => {
+                        error!(
+                            "CRITICAL Watchdog: {} has not processed a block in its stall interval — forcing reconnect ({}/{})",
+                            component, attempt, config.max_reconnects
+                        );
+                        registry.request_reconnect();
+                    }
+                    StallAction::Escalate { component, attempts } if config.exit_enabled => {
+                        error!(
+                            "CRITICAL Watchdog: {} still stalled after {} forced reconnects — exiting with code {} for supervisor escalation",
+                            component, attempts, WATCHDOG_ESCALATE_EXIT_CODE
+                        );
+                        std::process::exit(WATCHDOG_ESCALATE_EXIT_CODE);
+                    }
+                    StallAction::Escalate { .. } => {}
+                }
+
+                if let Some(h) = exit_decision(&health, config.hard_limit, config.exit_enabled) {
+                    error!(
+                        "Watchdog: critical component {} stalled for {:.1}s (> {}s hard limit) — exiting with code {} for supervisor restart",
+                        h.name,
+                        h.age_ms as f64 / 1000.0,
+                        config.hard_limit.as_secs(),
+                        WATCHDOG_EXIT_CODE
+                    );
+                    std::process::exit(WATCHDOG_EXIT_CODE);
+                }
+            }
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_tracks_beats_and_staleness() {
+        let reg = HeartbeatRegistry::new();
+        let block = reg.register("block_loop", Duration::from_secs(60), true);
+        let mempool = reg.register("mempool", Duration::from_secs(30), false);
+        let t0 = Instant::now();
+
+        let health = reg.check_at(t0);
+        assert_eq!(health.len(), 2);
+        assert!(health.iter().all(|h| !h.stale && h.beats == 1));
+
+        // 45s later: mempool (30s allowed) is stale, block loop (60s) is not
+        let health = reg.check_at(t0 + Duration::from_secs(45));
+        let by_name = |n: &str| health.iter().find(|h| h.name == n).unwrap().clone();
+        assert!(by_name("mempool").stale);
+        assert!(!by_name("block_loop").stale);
+        assert!(by_name("mempool").age_ms >= 45_000);
+
+        // A beat resets the age; clones share the component
+        mempool.clone().beat();
+        block.beat();
+        let health = reg.check_at(Instant::now());
+        assert!(health.iter().all(|h| !h.stale));
+        assert_eq!(health.iter().find(|h| h.name == "mempool").unwrap().beats, 2);
+    }
+
+    #[test]
+    fn test_reregister_keeps_counters() {
+        let reg = HeartbeatRegistry::new();
+        reg.register("mempool", Duration::from_secs(30), false).beat();
+        // New session re-registers under the same name
+        reg.register("mempool", Duration::from_secs(30), false);
+        let health = reg.check();
+        assert_eq!(health.len(), 1);
+        assert_eq!(health[0].beats, 3);
+
+        // Changed interval applies, counters carry over
+        reg.register("mempool", Duration::from_secs(90), false);
+        let health = reg.check();
+        assert_eq!(health[0].max_interval_ms, 90_000);
+        assert_eq!(health[0].beats, 4);
+    }
+
+    fn health(name: &'static str, age_s: u64, critical: bool) -> ComponentHealth {
+        ComponentHealth {
+            name,
+            age_ms: age_s * 1000,
+            max_interval_ms: 60_000,
+            critical,
+            stale: age_s > 60,
+            beats: 1,
+        }
+    }
+
+    #[test]
+    fn test_exit_decision_gating() {
+        let hard = Duration::from_secs(300);
+
+        // Critical component past the hard limit → exit (when enabled)
+        let h = vec![health("mempool", 10, false), health("block_loop", 301, true)];
+        assert_eq!(exit_decision(&h, hard, true).map(|c| c.name), Some("block_loop"));
+        // Disabled → never exit
+        assert!(exit_decision(&h, hard, false).is_none());
+
+        // Stale but under the hard limit → log only
+        let h = vec![health("block_loop", 120, true)];
+        assert!(h[0].stale);
+        assert!(exit_decision(&h, hard, true).is_none());
+
+        // Non-critical components never trigger an exit
+        let h = vec![health("mempool", 10_000, false)];
+        assert!(exit_decision(&h, hard, true).is_none());
+    }
+
+    #[test]
+    fn test_heartbeat_file_written() {
+        let dir = std::env::temp_dir().join(format!("dexarb_watchdog_{}", std::process::id()));
+        let path = dir.join("heartbeat.json");
+        let h = vec![health("block_loop", 5, true), health("mempool", 61, false)];
+        let progress = ProgressSnapshot { last_block: 1234, pools_synced: 42, last_trade: None };
+        write_heartbeat_file(&path, &h, progress).unwrap();
+
+        let v: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(v["healthy"], false);
+        assert_eq!((v["last_block"].as_u64(), v["pools_synced"].as_u64()), (Some(1234), Some(42)));
+        assert!(v["last_trade"].is_null());
+        assert_eq!(v["components"][1]["name"], "mempool");
+        assert_eq!(v["components"][1]["stale"], true);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stall_escalation_timing() {
+        let reg = HeartbeatRegistry::new();
+        let block = reg.register("block_loop", Duration::from_secs(60), true);
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+        let mut escalation = StallEscalation::new(2);
+
+        // Fresh, then stale at 61s: first forced reconnect right away
+        assert_eq!(escalation.step(&reg.check_at(at(59)), at(59)), StallAction::None);
+        let reconnect = |attempt| StallAction::Reconnect { component: "block_loop", attempt };
+        assert_eq!(escalation.step(&reg.check_at(at(61)), at(61)), reconnect(1));
+        // Waits a full stall interval for that reconnect to deliver a block
+        assert_eq!(escalation.step(&reg.check_at(at(120)), at(120)), StallAction::None);
+        assert_eq!(escalation.step(&reg.check_at(at(121)), at(121)), reconnect(2));
+        assert_eq!(escalation.step(&reg.check_at(at(180)), at(180)), StallAction::None);
+        assert_eq!(
+            escalation.step(&reg.check_at(at(181)), at(181)),
+            StallAction::Escalate { component: "block_loop", attempts: 2 }
+        );
+
+        // A block resets the ladder
+        block.beat();
+        let now = Instant::now();
+        assert_eq!(escalation.step(&reg.check_at(now), now), StallAction::None);
+        let later = now + Duration::from_secs(61);
+        assert_eq!(escalation.step(&reg.check_at(later), later), reconnect(1));
+
+        // Non-critical components never force a reconnect
+        let reg = HeartbeatRegistry::new();
+        reg.register("mempool", Duration::from_secs(30), false);
+        assert_eq!(StallEscalation::new(2).step(&reg.check_at(at(600)), at(600)), StallAction::None);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_request_wakes_waiter_once() {
+        let reg = Arc::new(HeartbeatRegistry::new());
+        let waiter = tokio::spawn({
+            let reg = reg.clone();
+            async move { reg.reconnect_requested().await }
+        });
+        reg.request_reconnect();
+        tokio::time::timeout(Duration::from_secs(5), waiter).await.unwrap().unwrap();
+        // Consumed by the waiter
+        assert!(!reg.take_reconnect());
+
+        // Requested with nobody waiting: the next wait returns at once
+        reg.request_reconnect();
+        tokio::time::timeout(Duration::from_secs(5), reg.reconnect_requested()).await.unwrap();
+    }
+
+    #[test]
+    fn test_heartbeat_file_whole_under_concurrent_reads() {
+        let dir = std::env::temp_dir().join(format!("dexarb_watchdog_concurrent_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("heartbeat.json");
+        let h = vec![health("block_loop", 5, true), health("mempool", 61, false)];
+        write_heartbeat_file(&path, &h, ProgressSnapshot { last_block: 0, pools_synced: 0, last_trade: None }).unwrap();
+
+        let done = Arc::new(AtomicBool::new(false));
+        // Two writers (block loop + watchdog thread) against three readers
+        let writers: Vec<_> = (0..2u64)
+            .map(|w| {
+                let (path, h) = (path.clone(), h.clone());
+                std::thread::spawn(move || {
+                    for i in 1..=200u64 {
+                        let progress = ProgressSnapshot { last_block: i, pools_synced: w, last_trade: Some(i) };
+                        write_heartbeat_file(&path, &h, progress).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let readers: Vec<_> = (0..3)
+            .map(|_| {
+                let (path, done) = (path.clone(), done.clone());
+                std::thread::spawn(move || {
+                    let mut reads = 0;
+                    while !done.load(Ordering::Relaxed) || reads == 0 {
+                        let body = std::fs::read_to_string(&path).unwrap();
+                        let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+                        assert_eq!(v["components"].as_array().map(Vec::len), Some(2));
+                        reads += 1;
+                    }
+                })
+            })
+            .collect();
+        for w in writers {
+            w.join().unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+        for r in readers {
+            r.join().unwrap();
+        }
+
+        // No temp files left behind
+        let leftovers = std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(leftovers, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rpc_timeout() {
+        let ok = rpc_timeout("fast", async { Ok::<_, String>(7) }).await.unwrap();
+        assert_eq!(ok, 7);
+
+        let err = rpc_timeout("fails", async { Err::<u32, _>("boom") }).await.unwrap_err();
+        assert_eq!(err.to_string(), "boom");
+
+        let err = rpc_timeout("eth_getLogs", std::future::pending::<std::result::Result<u32, String>>())
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "eth_getLogs timed out after 10s");
+    }
+}